@@ -0,0 +1,515 @@
+//! Descripteurs de rapport HID (HID 1.11 §6.2.2) des manettes génériques.
+//!
+//! Seuls les items utiles à une manette sont suivis : pages Generic Desktop
+//! (axes X..Rz, chapeau) et Button, dans le premier rapport d'entrée qui en
+//! contient. Les boutons HID 1..=11 suivent l'ordre des boutons normalisés ;
+//! le chapeau alimente la croix. Z/Rz servent de stick droit quand Rx/Ry
+//! manquent (manettes DirectInput), de gâchettes sinon.
+
+use crate::gamepad::{GamepadState, AXIS_COUNT, AXIS_LT, AXIS_RT};
+use exo_syscall_abi as syscall;
+
+pub(crate) const PAGE_GENERIC_DESKTOP: u16 = 0x01;
+pub(crate) const PAGE_DIGITIZER: u16 = 0x0d;
+const PAGE_BUTTON: u16 = 0x09;
+const USAGE_JOYSTICK: u16 = 0x04;
+const USAGE_GAMEPAD: u16 = 0x05;
+pub(crate) const USAGE_X: u16 = 0x30;
+pub(crate) const USAGE_Y: u16 = 0x31;
+const USAGE_Z: u16 = 0x32;
+const USAGE_RX: u16 = 0x33;
+const USAGE_RY: u16 = 0x34;
+const USAGE_RZ: u16 = 0x35;
+const USAGE_HAT: u16 = 0x39;
+pub(crate) const USAGE_TIP_SWITCH: u16 = 0x42;
+
+/// Boutons HID repris tels quels ; la croix vient du chapeau.
+pub const HID_BUTTONS: usize = 11;
+/// Usages locaux retenus avant un item Main.
+const MAX_USAGES: usize = 16;
+/// Plus grand rapport décodé, identifiant compris.
+pub const REPORT_MAX: usize = 64;
+
+const ITEM_LONG: u8 = 0xfe;
+const TYPE_MAIN: u8 = 0;
+const TYPE_GLOBAL: u8 = 1;
+const TYPE_LOCAL: u8 = 2;
+pub(crate) const MAIN_INPUT: u8 = 0x8;
+pub(crate) const MAIN_COLLECTION: u8 = 0xa;
+pub(crate) const MAIN_END_COLLECTION: u8 = 0xc;
+const GLOBAL_USAGE_PAGE: u8 = 0x0;
+const GLOBAL_LOGICAL_MIN: u8 = 0x1;
+const GLOBAL_LOGICAL_MAX: u8 = 0x2;
+const GLOBAL_PHYSICAL_MIN: u8 = 0x3;
+const GLOBAL_PHYSICAL_MAX: u8 = 0x4;
+const GLOBAL_UNIT_EXPONENT: u8 = 0x5;
+const GLOBAL_REPORT_SIZE: u8 = 0x7;
+const GLOBAL_REPORT_ID: u8 = 0x8;
+const GLOBAL_REPORT_COUNT: u8 = 0x9;
+const LOCAL_USAGE: u8 = 0x0;
+const LOCAL_USAGE_MIN: u8 = 0x1;
+const LOCAL_USAGE_MAX: u8 = 0x2;
+pub(crate) const INPUT_CONSTANT: u32 = 1 << 0;
+pub(crate) const COLLECTION_APPLICATION: u32 = 0x01;
+pub(crate) const COLLECTION_LOGICAL: u32 = 0x02;
+
+/// Directions du chapeau, dans le sens horaire depuis le haut.
+const HAT_DPAD: [u32; 8] = [
+    DPAD_UP,
+    DPAD_UP | DPAD_RIGHT,
+    DPAD_RIGHT,
+    DPAD_DOWN | DPAD_RIGHT,
+    DPAD_DOWN,
+    DPAD_DOWN | DPAD_LEFT,
+    DPAD_LEFT,
+    DPAD_UP | DPAD_LEFT,
+];
+const DPAD_UP: u32 = 1 << (syscall::INPUT_BTN_DPAD_UP - syscall::INPUT_BTN_SOUTH);
+const DPAD_DOWN: u32 = 1 << (syscall::INPUT_BTN_DPAD_DOWN - syscall::INPUT_BTN_SOUTH);
+const DPAD_LEFT: u32 = 1 << (syscall::INPUT_BTN_DPAD_LEFT - syscall::INPUT_BTN_SOUTH);
+const DPAD_RIGHT: u32 = 1 << (syscall::INPUT_BTN_DPAD_RIGHT - syscall::INPUT_BTN_SOUTH);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DescriptorError {
+    Truncated,
+    /// Pas de collection application Joystick/Gamepad.
+    NotGamepad,
+    /// Pas de collection application Touch Screen.
+    NotTouchscreen,
+    /// Pas de collection application Pen ni Digitizer.
+    NotTablet,
+    /// Aucun bouton ni axe reconnu.
+    NoControls,
+}
+
+/// Un champ du rapport d'entrée.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Field {
+    /// Position en bits après l'identifiant de rapport.
+    pub bit_offset: u16,
+    pub bit_size: u8,
+    pub logical_min: i32,
+    pub logical_max: i32,
+}
+
+impl Field {
+    pub(crate) fn read(&self, data: &[u8]) -> Option<i32> {
+        let mut raw = 0u32;
+        for i in 0..self.bit_size as usize {
+            let bit = self.bit_offset as usize + i;
+            let byte = *data.get(bit / 8)?;
+            raw |= ((byte >> (bit % 8)) & 1) as u32 * (1 << i);
+        }
+        if self.logical_min < 0 && self.bit_size < 32 && raw >> (self.bit_size - 1) & 1 != 0 {
+            raw |= u32::MAX << self.bit_size;
+        }
+        Some(raw as i32)
+    }
+
+    /// Valeur ramenée à `lo..=hi`.
+    pub(crate) fn scaled(&self, data: &[u8], lo: i32, hi: i32) -> Option<i16> {
+        let v = self.read(data)?;
+        let span = self.logical_max as i64 - self.logical_min as i64;
+        if span <= 0 {
+            return Some(0);
+        }
+        let v = (v as i64).clamp(self.logical_min as i64, self.logical_max as i64);
+        let scaled = (v - self.logical_min as i64) * (hi - lo) as i64 / span + lo as i64;
+        Some(scaled as i16)
+    }
+}
+
+/// Disposition d'une manette générique, construite par `Layout::parse`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Layout {
+    /// `0` : le périphérique n'utilise pas d'identifiant de rapport.
+    pub report_id: u8,
+    buttons: [Option<Field>; HID_BUTTONS],
+    axes: [Option<Field>; AXIS_COUNT],
+    hat: Option<Field>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct Globals {
+    pub(crate) usage_page: u16,
+    pub(crate) logical_min: i32,
+    pub(crate) logical_max: i32,
+    /// Bornes physiques (0 et 0 : celles logiques) et exposant d'unité.
+    pub(crate) physical_min: i32,
+    pub(crate) physical_max: i32,
+    pub(crate) unit_exponent: i8,
+    pub(crate) report_size: u32,
+    pub(crate) report_count: u32,
+    pub(crate) report_id: u8,
+}
+
+pub(crate) struct Locals {
+    usages: [u32; MAX_USAGES],
+    count: usize,
+    min: Option<u32>,
+    max: Option<u32>,
+}
+
+impl Locals {
+    const fn new() -> Self {
+        Self {
+            usages: [0; MAX_USAGES],
+            count: 0,
+            min: None,
+            max: None,
+        }
+    }
+
+    /// Usage (page << 16 | id) du `i`-ème champ d'un item Main.
+    pub(crate) fn usage(&self, i: u32, page: u16) -> Option<(u16, u16)> {
+        let full = if let (Some(min), Some(max)) = (self.min, self.max) {
+            let u = min.checked_add(i)?;
+            if u > max {
+                return None;
+            }
+            u
+        } else if self.count > 0 {
+            // Le dernier usage vaut pour les champs restants.
+            self.usages[(i as usize).min(self.count - 1)]
+        } else {
+            return None;
+        };
+        let page = if full > 0xffff {
+            (full >> 16) as u16
+        } else {
+            page
+        };
+        Some((page, full as u16))
+    }
+}
+
+/// Valeur d'un item : non signée, ou signée pour les bornes logiques.
+fn item_value(data: &[u8], signed: bool) -> u32 {
+    let mut v = 0u32;
+    for (i, b) in data.iter().enumerate() {
+        v |= (*b as u32) << (8 * i);
+    }
+    if signed && !data.is_empty() && data.len() < 4 && data[data.len() - 1] & 0x80 != 0 {
+        v |= u32::MAX << (8 * data.len());
+    }
+    v
+}
+
+/// Données d'un rapport d'entrée, sans son identifiant ; `None` pour un
+/// autre rapport.
+pub(crate) fn payload(report_id: u8, report: &[u8]) -> Option<&[u8]> {
+    if report_id == 0 {
+        return Some(report);
+    }
+    if report.first() != Some(&report_id) {
+        return None;
+    }
+    Some(&report[1..])
+}
+
+/// Items Main d'un descripteur, avec les items globaux et locaux en vigueur.
+pub(crate) struct Items<'a> {
+    desc: &'a [u8],
+    pos: usize,
+    pub(crate) globals: Globals,
+    pub(crate) locals: Locals,
+    /// Position courante dans chaque rapport d'entrée rencontré.
+    offsets: [u32; 256],
+}
+
+/// Item Main : `tag` (`MAIN_*`) et valeur.
+pub(crate) struct Main {
+    pub(crate) tag: u8,
+    pub(crate) value: u32,
+}
+
+impl<'a> Items<'a> {
+    pub(crate) fn new(desc: &'a [u8]) -> Self {
+        Self {
+            desc,
+            pos: 0,
+            globals: Globals {
+                usage_page: 0,
+                logical_min: 0,
+                logical_max: 0,
+                physical_min: 0,
+                physical_max: 0,
+                unit_exponent: 0,
+                report_size: 0,
+                report_count: 0,
+                report_id: 0,
+            },
+            locals: Locals::new(),
+            offsets: [0; 256],
+        }
+    }
+
+    /// Item Main suivant ; les locaux du précédent sont oubliés.
+    pub(crate) fn next_main(&mut self) -> Result<Option<Main>, DescriptorError> {
+        self.locals = Locals::new();
+        let desc = self.desc;
+        while self.pos < desc.len() {
+            let prefix = desc[self.pos];
+            if prefix == ITEM_LONG {
+                let len = *desc.get(self.pos + 1).ok_or(DescriptorError::Truncated)? as usize;
+                self.pos += 3 + len;
+                continue;
+            }
+            let size = match prefix & 3 {
+                3 => 4,
+                n => n as usize,
+            };
+            let data = desc
+                .get(self.pos + 1..self.pos + 1 + size)
+                .ok_or(DescriptorError::Truncated)?;
+            self.pos += 1 + size;
+            let kind = (prefix >> 2) & 3;
+            let tag = prefix >> 4;
+            let value = item_value(data, false);
+
+            let (globals, locals) = (&mut self.globals, &mut self.locals);
+            match (kind, tag) {
+                (TYPE_MAIN, _) => return Ok(Some(Main { tag, value })),
+                (TYPE_GLOBAL, GLOBAL_USAGE_PAGE) => globals.usage_page = value as u16,
+                (TYPE_GLOBAL, GLOBAL_LOGICAL_MIN) => {
+                    globals.logical_min = item_value(data, true) as i32
+                }
+                (TYPE_GLOBAL, GLOBAL_LOGICAL_MAX) => {
+                    // Bornes non signées si le minimum l'est.
+                    globals.logical_max = if globals.logical_min < 0 {
+                        item_value(data, true) as i32
+                    } else {
+                        value as i32
+                    }
+                }
+                (TYPE_GLOBAL, GLOBAL_PHYSICAL_MIN) => {
+                    globals.physical_min = item_value(data, true) as i32
+                }
+                (TYPE_GLOBAL, GLOBAL_PHYSICAL_MAX) => {
+                    globals.physical_max = item_value(data, true) as i32
+                }
+                (TYPE_GLOBAL, GLOBAL_UNIT_EXPONENT) => {
+                    // Quartet signé (HID 1.11 §6.2.2.7).
+                    globals.unit_exponent = ((value as u8) << 4) as i8 >> 4
+                }
+                (TYPE_GLOBAL, GLOBAL_REPORT_SIZE) => globals.report_size = value,
+                (TYPE_GLOBAL, GLOBAL_REPORT_COUNT) => globals.report_count = value,
+                (TYPE_GLOBAL, GLOBAL_REPORT_ID) => globals.report_id = value as u8,
+                (TYPE_LOCAL, LOCAL_USAGE) if locals.count < MAX_USAGES => {
+                    locals.usages[locals.count] = value;
+                    locals.count += 1;
+                }
+                (TYPE_LOCAL, LOCAL_USAGE_MIN) => locals.min = Some(value),
+                (TYPE_LOCAL, LOCAL_USAGE_MAX) => locals.max = Some(value),
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
+    /// Usage du `i`-ème champ de l'item Main courant.
+    pub(crate) fn usage(&self, i: u32) -> Option<(u16, u16)> {
+        self.locals.usage(i, self.globals.usage_page)
+    }
+
+    /// Réserve les champs de l'item Input courant dans son rapport ;
+    /// retourne la position en bits du premier.
+    pub(crate) fn input_base(&mut self) -> u32 {
+        let id = self.globals.report_id as usize;
+        let base = self.offsets[id];
+        self.offsets[id] = base + self.globals.report_size * self.globals.report_count;
+        base
+    }
+
+    /// Champ `i` de l'item Input courant, à partir de `base`.
+    pub(crate) fn field(&self, base: u32, i: u32) -> Field {
+        let size = self.globals.report_size;
+        Field {
+            bit_offset: (base + i * size) as u16,
+            bit_size: size as u8,
+            logical_min: self.globals.logical_min,
+            logical_max: self.globals.logical_max,
+        }
+    }
+}
+
+impl Layout {
+    pub fn parse(desc: &[u8]) -> Result<Self, DescriptorError> {
+        let mut layout = Layout {
+            report_id: 0,
+            buttons: [None; HID_BUTTONS],
+            axes: [None; AXIS_COUNT],
+            hat: None,
+        };
+        // Z/Rz, placés une fois Rx/Ry connus.
+        let mut z = None;
+        let mut rz = None;
+        let mut items = Items::new(desc);
+        let mut gamepad = false;
+        // Rapport retenu : le premier qui porte un contrôle.
+        let mut chosen: Option<u8> = None;
+
+        while let Some(main) = items.next_main()? {
+            match main.tag {
+                MAIN_COLLECTION
+                    if main.value == COLLECTION_APPLICATION
+                        && matches!(
+                            items.usage(0),
+                            Some((PAGE_GENERIC_DESKTOP, USAGE_JOYSTICK | USAGE_GAMEPAD))
+                        ) =>
+                {
+                    gamepad = true;
+                }
+                MAIN_INPUT => {
+                    let id = items.globals.report_id;
+                    let base = items.input_base();
+                    let size = items.globals.report_size;
+                    let wanted = gamepad
+                        && main.value & INPUT_CONSTANT == 0
+                        && (1..=32).contains(&size)
+                        && chosen.is_none_or(|c| c == id);
+                    for i in 0..items.globals.report_count {
+                        if !wanted {
+                            break;
+                        }
+                        let Some(usage) = items.usage(i) else {
+                            continue;
+                        };
+                        let slot = match usage {
+                            (PAGE_BUTTON, n @ 1..) if (n as usize) <= HID_BUTTONS => {
+                                &mut layout.buttons[n as usize - 1]
+                            }
+                            (PAGE_GENERIC_DESKTOP, USAGE_X) => &mut layout.axes[0],
+                            (PAGE_GENERIC_DESKTOP, USAGE_Y) => &mut layout.axes[1],
+                            (PAGE_GENERIC_DESKTOP, USAGE_RX) => &mut layout.axes[2],
+                            (PAGE_GENERIC_DESKTOP, USAGE_RY) => &mut layout.axes[3],
+                            (PAGE_GENERIC_DESKTOP, USAGE_Z) => &mut z,
+                            (PAGE_GENERIC_DESKTOP, USAGE_RZ) => &mut rz,
+                            (PAGE_GENERIC_DESKTOP, USAGE_HAT) => &mut layout.hat,
+                            _ => continue,
+                        };
+                        if slot.is_none() {
+                            *slot = Some(items.field(base, i));
+                            chosen = Some(id);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !gamepad {
+            return Err(DescriptorError::NotGamepad);
+        }
+        let (rx, ry) = (layout.axes[2].is_none(), layout.axes[3].is_none());
+        layout.axes[if rx { 2 } else { AXIS_LT }] = z;
+        layout.axes[if ry { 3 } else { AXIS_RT }] = rz;
+        layout.report_id = chosen.ok_or(DescriptorError::NoControls)?;
+        Ok(layout)
+    }
+
+    /// Décode un rapport d'entrée (identifiant compris s'il y en a un) ;
+    /// `None` pour un autre rapport ou un rapport trop court.
+    pub fn decode(&self, report: &[u8]) -> Option<GamepadState> {
+        let data = payload(self.report_id, report)?;
+        let mut state = GamepadState::default();
+        for (i, field) in self.buttons.iter().enumerate() {
+            if let Some(field) = field {
+                if field.read(data)? != 0 {
+                    state.buttons |= 1 << i;
+                }
+            }
+        }
+        for (i, field) in self.axes.iter().enumerate() {
+            if let Some(field) = field {
+                state.axes[i] = if i == AXIS_LT || i == AXIS_RT {
+                    field.scaled(data, 0, i16::MAX as i32)?
+                } else {
+                    field.scaled(data, i16::MIN as i32, i16::MAX as i32)?
+                };
+            }
+        }
+        if let Some(hat) = self.hat {
+            // Hors bornes : chapeau au repos.
+            let v = hat.read(data)? as i64 - hat.logical_min as i64;
+            if let Some(dpad) = usize::try_from(v).ok().and_then(|v| HAT_DPAD.get(v)) {
+                state.buttons |= dpad;
+            }
+        }
+        Some(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Manette DirectInput typique : 12 boutons, chapeau, X/Y/Z/Rz sur 8 bits,
+    /// rapport n° 1.
+    const GENERIC_PAD: &[u8] = &[
+        0x05, 0x01, // Usage Page (Generic Desktop)
+        0x09, 0x05, // Usage (Game Pad)
+        0xa1, 0x01, // Collection (Application)
+        0x85, 0x01, //   Report ID (1)
+        0x05, 0x09, //   Usage Page (Button)
+        0x19, 0x01, //   Usage Minimum (1)
+        0x29, 0x0c, //   Usage Maximum (12)
+        0x15, 0x00, //   Logical Minimum (0)
+        0x25, 0x01, //   Logical Maximum (1)
+        0x75, 0x01, //   Report Size (1)
+        0x95, 0x0c, //   Report Count (12)
+        0x81, 0x02, //   Input (Data, Var, Abs)
+        0x05, 0x01, //   Usage Page (Generic Desktop)
+        0x09, 0x39, //   Usage (Hat switch)
+        0x25, 0x07, //   Logical Maximum (7)
+        0x75, 0x04, //   Report Size (4)
+        0x95, 0x01, //   Report Count (1)
+        0x81, 0x42, //   Input (Data, Var, Abs, Null)
+        0x09, 0x30, //   Usage (X)
+        0x09, 0x31, //   Usage (Y)
+        0x09, 0x32, //   Usage (Z)
+        0x09, 0x35, //   Usage (Rz)
+        0x15, 0x81, //   Logical Minimum (-127)
+        0x25, 0x7f, //   Logical Maximum (127)
+        0x75, 0x08, //   Report Size (8)
+        0x95, 0x04, //   Report Count (4)
+        0x81, 0x02, //   Input (Data, Var, Abs)
+        0xc0, // End Collection
+    ];
+
+    #[test]
+    fn parses_directinput_pad() {
+        let layout = Layout::parse(GENERIC_PAD).unwrap();
+        assert_eq!(layout.report_id, 1);
+        // Bouton 1 et 3, chapeau à droite (2), X à fond à gauche, Rz à fond.
+        let report = [0x01, 0b0000_0101, 0x20, 0x81, 0x00, 0x00, 0x7f];
+        let state = layout.decode(&report).unwrap();
+        assert!(state.pressed(syscall::INPUT_BTN_SOUTH));
+        assert!(state.pressed(syscall::INPUT_BTN_WEST));
+        assert!(state.pressed(syscall::INPUT_BTN_DPAD_RIGHT));
+        assert!(!state.pressed(syscall::INPUT_BTN_DPAD_UP));
+        assert_eq!(state.axis(syscall::INPUT_ABS_X), i16::MIN);
+        // Z/Rz tiennent lieu de stick droit.
+        assert_eq!(state.axis(syscall::INPUT_ABS_RY), i16::MAX);
+        assert_eq!(state.axis(syscall::INPUT_ABS_LT), 0);
+
+        // Chapeau au repos (valeur nulle hors bornes), autre rapport ignoré.
+        let idle = layout.decode(&[0x01, 0, 0xf0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(idle.buttons, 0);
+        assert!(layout.decode(&[0x02, 0, 0, 0, 0, 0, 0]).is_none());
+        assert!(layout.decode(&[0x01, 0]).is_none());
+    }
+
+    #[test]
+    fn rejects_non_gamepads() {
+        // Souris : collection Mouse.
+        let mouse = [0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0xc0];
+        assert_eq!(Layout::parse(&mouse), Err(DescriptorError::NotGamepad));
+        let empty_pad = [0x05, 0x01, 0x09, 0x05, 0xa1, 0x01, 0xc0];
+        assert_eq!(Layout::parse(&empty_pad), Err(DescriptorError::NoControls));
+        assert_eq!(
+            Layout::parse(&GENERIC_PAD[..7]),
+            Err(DescriptorError::Truncated)
+        );
+    }
+}
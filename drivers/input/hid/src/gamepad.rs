@@ -0,0 +1,219 @@
+//! État normalisé d'une manette et rapports XInput (manette Xbox 360 filaire).
+
+use exo_syscall_abi as syscall;
+
+/// Boutons `INPUT_BTN_SOUTH..=INPUT_BTN_DPAD_RIGHT` : bit `i` = code
+/// `INPUT_BTN_SOUTH + i`.
+pub const BUTTON_COUNT: usize = 15;
+/// Axes `INPUT_ABS_X..=INPUT_ABS_RT`, même correspondance.
+pub const AXIS_COUNT: usize = 6;
+pub const AXIS_LT: usize = (syscall::INPUT_ABS_LT - syscall::INPUT_ABS_X) as usize;
+pub const AXIS_RT: usize = (syscall::INPUT_ABS_RT - syscall::INPUT_ABS_X) as usize;
+/// Au plus un événement par bouton et par axe.
+pub const MAX_EVENTS: usize = BUTTON_COUNT + AXIS_COUNT;
+
+/// Interface XInput : classe vendeur, sous-classe/protocole Microsoft.
+pub const XINPUT_CLASS: (u8, u8, u8) = (0xff, 0x5d, 0x01);
+pub const XINPUT_REPORT_LEN: usize = 20;
+pub const XINPUT_RUMBLE_LEN: usize = 8;
+
+const XINPUT_REPORT_INPUT: u8 = 0x00;
+
+/// Bits XInput dans l'ordre des boutons normalisés.
+const XINPUT_BUTTONS: [u16; BUTTON_COUNT] = [
+    0x1000, // A
+    0x2000, // B
+    0x4000, // X
+    0x8000, // Y
+    0x0100, // LB
+    0x0200, // RB
+    0x0020, // Back
+    0x0010, // Start
+    0x0400, // Guide
+    0x0040, // stick gauche
+    0x0080, // stick droit
+    0x0001, // croix haut
+    0x0002, // croix bas
+    0x0004, // croix gauche
+    0x0008, // croix droite
+];
+
+pub const fn button_bit(code: u16) -> Option<u32> {
+    match code.checked_sub(syscall::INPUT_BTN_SOUTH) {
+        Some(i) if (i as usize) < BUTTON_COUNT => Some(1 << i),
+        _ => None,
+    }
+}
+
+pub const fn axis_index(code: u16) -> Option<usize> {
+    match code.checked_sub(syscall::INPUT_ABS_X) {
+        Some(i) if (i as usize) < AXIS_COUNT => Some(i as usize),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct GamepadEvent {
+    pub code: u16,
+    pub value: i16,
+}
+
+impl GamepadEvent {
+    /// Événement prêt pour `INPUT_MSG_PUSH`.
+    pub fn to_wire(self, slot: u8) -> syscall::InputEventWire {
+        let state = if button_bit(self.code).is_some() && self.value != 0 {
+            syscall::INPUT_KEY_PRESSED
+        } else {
+            syscall::INPUT_KEY_RELEASED
+        };
+        syscall::InputEventWire {
+            device: syscall::INPUT_DEVICE_GAMEPAD,
+            state,
+            code: self.code,
+            value: self.value,
+            ascii: slot,
+            modifiers: 0,
+            _pad: [0; 4],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct GamepadState {
+    pub buttons: u32,
+    pub axes: [i16; AXIS_COUNT],
+}
+
+impl GamepadState {
+    pub fn pressed(&self, code: u16) -> bool {
+        button_bit(code).is_some_and(|bit| self.buttons & bit != 0)
+    }
+
+    pub fn axis(&self, code: u16) -> i16 {
+        axis_index(code).map_or(0, |i| self.axes[i])
+    }
+
+    /// Écrit dans `out` les événements qui mènent de `self` à `next` (boutons
+    /// d'abord) ; retourne leur nombre.
+    pub fn diff(&self, next: &GamepadState, out: &mut [GamepadEvent; MAX_EVENTS]) -> usize {
+        let mut n = 0;
+        let changed = self.buttons ^ next.buttons;
+        for i in 0..BUTTON_COUNT {
+            if changed & (1 << i) != 0 {
+                out[n] = GamepadEvent {
+                    code: syscall::INPUT_BTN_SOUTH + i as u16,
+                    value: (next.buttons >> i & 1) as i16,
+                };
+                n += 1;
+            }
+        }
+        for i in 0..AXIS_COUNT {
+            if self.axes[i] != next.axes[i] {
+                out[n] = GamepadEvent {
+                    code: syscall::INPUT_ABS_X + i as u16,
+                    value: next.axes[i],
+                };
+                n += 1;
+            }
+        }
+        n
+    }
+}
+
+pub fn is_xinput(class: u8, subclass: u8, protocol: u8) -> bool {
+    (class, subclass, protocol) == XINPUT_CLASS
+}
+
+/// XInput compte Y vers le haut ; l'état normalisé, comme HID, vers le bas.
+fn flip(v: i16) -> i16 {
+    (-(v as i32)).min(i16::MAX as i32) as i16
+}
+
+fn trigger(v: u8) -> i16 {
+    (v as i32 * i16::MAX as i32 / u8::MAX as i32) as i16
+}
+
+/// Décode un rapport d'entrée XInput ; `None` pour les autres messages
+/// (LED, état de connexion).
+pub fn decode_xinput(report: &[u8]) -> Option<GamepadState> {
+    if report.len() < XINPUT_REPORT_LEN
+        || report[0] != XINPUT_REPORT_INPUT
+        || report[1] as usize != XINPUT_REPORT_LEN
+    {
+        return None;
+    }
+    let le16 = |at: usize| i16::from_le_bytes([report[at], report[at + 1]]);
+    let raw = u16::from_le_bytes([report[2], report[3]]);
+    let mut state = GamepadState::default();
+    for (i, mask) in XINPUT_BUTTONS.iter().enumerate() {
+        if raw & mask != 0 {
+            state.buttons |= 1 << i;
+        }
+    }
+    state.axes = [
+        le16(6),
+        flip(le16(8)),
+        le16(10),
+        flip(le16(12)),
+        trigger(report[4]),
+        trigger(report[5]),
+    ];
+    Some(state)
+}
+
+/// Rapport de sortie XInput : moteurs gauche (lourd) et droit (léger).
+pub fn xinput_rumble(strong: u8, weak: u8) -> [u8; XINPUT_RUMBLE_LEN] {
+    [0x00, XINPUT_RUMBLE_LEN as u8, 0x00, strong, weak, 0, 0, 0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xinput_report(buttons: u16, lt: u8, ly: i16) -> [u8; XINPUT_REPORT_LEN] {
+        let mut r = [0u8; XINPUT_REPORT_LEN];
+        r[1] = XINPUT_REPORT_LEN as u8;
+        r[2..4].copy_from_slice(&buttons.to_le_bytes());
+        r[4] = lt;
+        r[8..10].copy_from_slice(&ly.to_le_bytes());
+        r
+    }
+
+    #[test]
+    fn decodes_xinput_report() {
+        let state = decode_xinput(&xinput_report(0x1001, 255, i16::MIN)).unwrap();
+        assert!(state.pressed(syscall::INPUT_BTN_SOUTH));
+        assert!(state.pressed(syscall::INPUT_BTN_DPAD_UP));
+        assert!(!state.pressed(syscall::INPUT_BTN_EAST));
+        assert_eq!(state.axis(syscall::INPUT_ABS_LT), i16::MAX);
+        assert_eq!(state.axis(syscall::INPUT_ABS_Y), i16::MAX);
+        assert!(decode_xinput(&[0x01, 0x03, 0x0e]).is_none());
+    }
+
+    #[test]
+    fn diff_emits_changed_controls_only() {
+        let before = GamepadState::default();
+        let after = decode_xinput(&xinput_report(0x2000, 0, -100)).unwrap();
+        let mut events = [GamepadEvent::default(); MAX_EVENTS];
+        let n = before.diff(&after, &mut events);
+        assert_eq!(
+            &events[..n],
+            &[
+                GamepadEvent {
+                    code: syscall::INPUT_BTN_EAST,
+                    value: 1
+                },
+                GamepadEvent {
+                    code: syscall::INPUT_ABS_Y,
+                    value: 100
+                },
+            ]
+        );
+        let wire = events[0].to_wire(2);
+        assert_eq!(
+            (wire.device, wire.state, wire.ascii),
+            (syscall::INPUT_DEVICE_GAMEPAD, syscall::INPUT_KEY_PRESSED, 2)
+        );
+        assert_eq!(after.diff(&after, &mut events), 0);
+    }
+}
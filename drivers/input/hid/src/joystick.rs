@@ -0,0 +1,140 @@
+//! API joystick côté application : état des manettes reconstruit à partir
+//! des événements `input_server` (`INPUT_DEVICE_GAMEPAD`), et requêtes de
+//! vibration.
+
+use crate::gamepad::{axis_index, button_bit, GamepadState};
+use exo_syscall_abi as syscall;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Pad {
+    pub connected: bool,
+    pub state: GamepadState,
+}
+
+pub struct Joysticks {
+    pads: [Pad; syscall::INPUT_GAMEPAD_MAX],
+}
+
+impl Joysticks {
+    pub const fn new() -> Self {
+        const EMPTY: Pad = Pad {
+            connected: false,
+            state: GamepadState {
+                buttons: 0,
+                axes: [0; crate::gamepad::AXIS_COUNT],
+            },
+        };
+        Self {
+            pads: [EMPTY; syscall::INPUT_GAMEPAD_MAX],
+        }
+    }
+
+    /// Applique un événement reçu ; retourne le slot concerné, `None` pour
+    /// un événement d'un autre périphérique.
+    pub fn apply(&mut self, event: &syscall::InputEventWire) -> Option<usize> {
+        if event.device != syscall::INPUT_DEVICE_GAMEPAD {
+            return None;
+        }
+        let slot = event.ascii as usize;
+        let pad = self.pads.get_mut(slot)?;
+        if event.code == syscall::INPUT_GAMEPAD_CONNECTED {
+            // Une manette rebranchée repart d'un état neutre.
+            *pad = Pad {
+                connected: event.value != 0,
+                state: GamepadState::default(),
+            };
+        } else if let Some(bit) = button_bit(event.code) {
+            if event.value != 0 {
+                pad.state.buttons |= bit;
+            } else {
+                pad.state.buttons &= !bit;
+            }
+        } else {
+            pad.state.axes[axis_index(event.code)?] = event.value;
+        }
+        Some(slot)
+    }
+
+    pub fn pad(&self, slot: usize) -> Option<&Pad> {
+        self.pads.get(slot).filter(|pad| pad.connected)
+    }
+
+    pub fn pressed(&self, slot: usize, code: u16) -> bool {
+        self.pad(slot).is_some_and(|pad| pad.state.pressed(code))
+    }
+
+    pub fn axis(&self, slot: usize, code: u16) -> i16 {
+        self.pad(slot).map_or(0, |pad| pad.state.axis(code))
+    }
+}
+
+impl Default for Joysticks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Vibration de `slot` pendant `duration_ms` (0 : arrêt), à envoyer à
+/// `INPUT_SERVER_ENDPOINT`.
+pub fn rumble_request(
+    slot: u8,
+    strong: u8,
+    weak: u8,
+    duration_ms: u16,
+    reply_endpoint: u64,
+) -> syscall::InputRequest {
+    syscall::InputRequest {
+        sender_pid: 0,
+        msg_type: syscall::INPUT_MSG_GAMEPAD_RUMBLE,
+        reply_endpoint,
+        event: syscall::InputEventWire {
+            device: syscall::INPUT_DEVICE_GAMEPAD,
+            state: 0,
+            code: (strong as u16) << 8 | weak as u16,
+            value: duration_ms.min(i16::MAX as u16) as i16,
+            ascii: slot,
+            modifiers: 0,
+            _pad: [0; 4],
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamepad::GamepadEvent;
+
+    fn connected(slot: u8, value: i16) -> syscall::InputEventWire {
+        GamepadEvent {
+            code: syscall::INPUT_GAMEPAD_CONNECTED,
+            value,
+        }
+        .to_wire(slot)
+    }
+
+    #[test]
+    fn tracks_pads_from_events() {
+        let mut pads = Joysticks::new();
+        assert_eq!(pads.apply(&connected(1, 1)), Some(1));
+        let press = GamepadEvent {
+            code: syscall::INPUT_BTN_START,
+            value: 1,
+        };
+        let stick = GamepadEvent {
+            code: syscall::INPUT_ABS_RX,
+            value: -4000,
+        };
+        pads.apply(&press.to_wire(1));
+        pads.apply(&stick.to_wire(1));
+        assert!(pads.pressed(1, syscall::INPUT_BTN_START));
+        assert_eq!(pads.axis(1, syscall::INPUT_ABS_RX), -4000);
+        assert!(pads.pad(0).is_none());
+
+        pads.apply(&connected(1, 0));
+        assert!(!pads.pressed(1, syscall::INPUT_BTN_START));
+        assert!(pads.apply(&connected(9, 1)).is_none());
+
+        let req = rumble_request(1, 0xff, 0x10, 200, 7);
+        assert_eq!((req.event.code, req.event.value), (0xff10, 200));
+    }
+}
@@ -0,0 +1,28 @@
+#![no_std]
+//! exo-hid-input — manettes de jeu, écrans tactiles et tablettes pour Exo-OS.
+//!
+//! - `descriptor` : parsing des descripteurs de rapport HID (HID 1.11 §6.2.2)
+//!   en disposition de manette (boutons, axes, chapeau).
+//! - `gamepad` : état normalisé, décodage des rapports HID génériques et
+//!   XInput, différences en événements `input_server`, vibration XInput.
+//! - `joystick` : API côté application, qui suit l'état des manettes à
+//!   partir des événements reçus et construit les requêtes de vibration.
+//!
+//! Le driver de bus annonce chaque manette branchée à `input_server`
+//! (`INPUT_MSG_GAMEPAD_CONNECT`) et la retire au débranchement ; un driver
+//! mort perd ses manettes à la première vibration qui ne passe pas.
+
+#[cfg(test)]
+extern crate std;
+
+pub mod descriptor;
+pub mod gamepad;
+pub mod joystick;
+pub mod tablet;
+pub mod touch;
+
+pub use descriptor::{DescriptorError, Layout};
+pub use gamepad::{GamepadEvent, GamepadState};
+pub use joystick::Joysticks;
+pub use tablet::{PenState, TabletLayout};
+pub use touch::{TouchLayout, Touchscreen};
@@ -0,0 +1,528 @@
+//! Tablettes graphiques et stylets (HID Digitizers, collection Pen).
+//!
+//! Un outil à la fois : position, pression, inclinaison, pointe et bouton
+//! latéral. L'usage Invert (bout gomme en portée) ou Eraser (gomme appuyée)
+//! change l'outil en gomme, annoncé comme un nouvel outil qui entre en
+//! portée, comme le veut zwp_tablet_tool_v2.
+
+use crate::descriptor::{
+    payload, DescriptorError, Field, Items, COLLECTION_APPLICATION, INPUT_CONSTANT,
+    MAIN_COLLECTION, MAIN_END_COLLECTION, MAIN_INPUT, PAGE_DIGITIZER, PAGE_GENERIC_DESKTOP,
+    USAGE_TIP_SWITCH, USAGE_X, USAGE_Y,
+};
+use exo_syscall_abi as syscall;
+
+const USAGE_DIGITIZER: u16 = 0x01;
+const USAGE_PEN: u16 = 0x02;
+const USAGE_TIP_PRESSURE: u16 = 0x30;
+const USAGE_IN_RANGE: u16 = 0x32;
+const USAGE_INVERT: u16 = 0x3c;
+const USAGE_X_TILT: u16 = 0x3d;
+const USAGE_Y_TILT: u16 = 0x3e;
+const USAGE_BARREL_SWITCH: u16 = 0x44;
+const USAGE_ERASER: u16 = 0x45;
+
+/// Inclinaison maximale, en centièmes de degré.
+const TILT_MAX: i64 = 9000;
+/// Pire différence : l'ancien outil relâche pointe et bouton puis sort, le
+/// nouveau entre avec ses cinq axes, sa pointe et son bouton, plus la fin
+/// d'image.
+pub const MAX_EVENTS: usize = 12;
+
+/// Angle : champ et bornes physiques, pour le ramener en centièmes de degré.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Angle {
+    field: Field,
+    physical_min: i32,
+    physical_max: i32,
+    unit_exponent: i8,
+}
+
+impl Angle {
+    fn read(&self, data: &[u8]) -> Option<i16> {
+        let (lo, hi) = if self.physical_min == 0 && self.physical_max == 0 {
+            (self.field.logical_min, self.field.logical_max)
+        } else {
+            (self.physical_min, self.physical_max)
+        };
+        let v = self.field.scaled(data, lo, hi)? as i64;
+        // Degrés × 10^exposant, ramenés aux centièmes.
+        let e = self.unit_exponent as i32 + 2;
+        let v = if e >= 0 {
+            v * 10i64.pow(e.min(6) as u32)
+        } else {
+            v / 10i64.pow((-e).min(6) as u32)
+        };
+        Some(v.clamp(-TILT_MAX, TILT_MAX) as i16)
+    }
+}
+
+/// Disposition d'une tablette, construite par `TabletLayout::parse`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TabletLayout {
+    /// `0` : le périphérique n'utilise pas d'identifiant de rapport.
+    pub report_id: u8,
+    tip: Field,
+    x: Field,
+    y: Field,
+    in_range: Option<Field>,
+    barrel: Option<Field>,
+    invert: Option<Field>,
+    eraser: Option<Field>,
+    pressure: Option<Field>,
+    tilt_x: Option<Angle>,
+    tilt_y: Option<Angle>,
+}
+
+#[derive(Default)]
+struct Fields {
+    tip: Option<Field>,
+    x: Option<Field>,
+    y: Option<Field>,
+    in_range: Option<Field>,
+    barrel: Option<Field>,
+    invert: Option<Field>,
+    eraser: Option<Field>,
+    pressure: Option<Field>,
+    tilt_x: Option<Angle>,
+    tilt_y: Option<Angle>,
+}
+
+impl TabletLayout {
+    pub fn parse(desc: &[u8]) -> Result<Self, DescriptorError> {
+        let mut fields = Fields::default();
+        let mut items = Items::new(desc);
+        let mut seen = false;
+        let mut depth = 0u32;
+        // Profondeur de la collection application Pen ouverte.
+        let mut pen: Option<u32> = None;
+        let mut chosen: Option<u8> = None;
+
+        while let Some(main) = items.next_main()? {
+            match main.tag {
+                MAIN_COLLECTION => {
+                    depth += 1;
+                    if main.value == COLLECTION_APPLICATION
+                        && matches!(
+                            items.usage(0),
+                            Some((PAGE_DIGITIZER, USAGE_PEN | USAGE_DIGITIZER))
+                        )
+                    {
+                        pen = Some(depth);
+                        seen = true;
+                    }
+                }
+                MAIN_END_COLLECTION => {
+                    if pen == Some(depth) {
+                        pen = None;
+                    }
+                    depth = depth.saturating_sub(1);
+                }
+                MAIN_INPUT => {
+                    let id = items.globals.report_id;
+                    let base = items.input_base();
+                    let size = items.globals.report_size;
+                    let wanted = pen.is_some()
+                        && main.value & INPUT_CONSTANT == 0
+                        && (1..=32).contains(&size)
+                        && chosen.is_none_or(|c| c == id);
+                    for i in 0..items.globals.report_count {
+                        if !wanted {
+                            break;
+                        }
+                        let Some(usage) = items.usage(i) else {
+                            continue;
+                        };
+                        let field = items.field(base, i);
+                        let angle = Angle {
+                            field,
+                            physical_min: items.globals.physical_min,
+                            physical_max: items.globals.physical_max,
+                            unit_exponent: items.globals.unit_exponent,
+                        };
+                        let taken = match usage {
+                            (PAGE_GENERIC_DESKTOP, USAGE_X) => fill(&mut fields.x, field),
+                            (PAGE_GENERIC_DESKTOP, USAGE_Y) => fill(&mut fields.y, field),
+                            (PAGE_DIGITIZER, USAGE_TIP_SWITCH) => fill(&mut fields.tip, field),
+                            (PAGE_DIGITIZER, USAGE_IN_RANGE) => fill(&mut fields.in_range, field),
+                            (PAGE_DIGITIZER, USAGE_BARREL_SWITCH) => {
+                                fill(&mut fields.barrel, field)
+                            }
+                            (PAGE_DIGITIZER, USAGE_INVERT) => fill(&mut fields.invert, field),
+                            (PAGE_DIGITIZER, USAGE_ERASER) => fill(&mut fields.eraser, field),
+                            (PAGE_DIGITIZER, USAGE_TIP_PRESSURE) => {
+                                fill(&mut fields.pressure, field)
+                            }
+                            (PAGE_DIGITIZER, USAGE_X_TILT) => fill(&mut fields.tilt_x, angle),
+                            (PAGE_DIGITIZER, USAGE_Y_TILT) => fill(&mut fields.tilt_y, angle),
+                            _ => false,
+                        };
+                        if taken {
+                            chosen = Some(id);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !seen {
+            return Err(DescriptorError::NotTablet);
+        }
+        let (Some(tip), Some(x), Some(y), Some(report_id)) =
+            (fields.tip, fields.x, fields.y, chosen)
+        else {
+            return Err(DescriptorError::NoControls);
+        };
+        Ok(TabletLayout {
+            report_id,
+            tip,
+            x,
+            y,
+            in_range: fields.in_range,
+            barrel: fields.barrel,
+            invert: fields.invert,
+            eraser: fields.eraser,
+            pressure: fields.pressure,
+            tilt_x: fields.tilt_x,
+            tilt_y: fields.tilt_y,
+        })
+    }
+
+    /// Décode un rapport d'entrée ; `None` pour un autre rapport ou un
+    /// rapport trop court.
+    pub fn decode(&self, report: &[u8]) -> Option<PenState> {
+        let data = payload(self.report_id, report)?;
+        let flag = |field: Option<Field>| -> Option<bool> {
+            match field {
+                Some(f) => Some(f.read(data)? != 0),
+                None => Some(false),
+            }
+        };
+        let tip = self.tip.read(data)? != 0;
+        let barrel = flag(self.barrel)?;
+        let eraser = flag(self.eraser)?;
+        let invert = flag(self.invert)?;
+        // Sans In Range, l'outil n'est vu qu'au contact.
+        let in_range = match self.in_range {
+            Some(f) => f.read(data)? != 0,
+            None => tip || eraser || barrel,
+        };
+        if !in_range {
+            return Some(PenState::default());
+        }
+        let range = syscall::INPUT_TOUCH_RANGE as i32;
+        Some(PenState {
+            tool: if invert || eraser {
+                syscall::INPUT_TABLET_TOOL_ERASER
+            } else {
+                syscall::INPUT_TABLET_TOOL_PEN
+            },
+            tip: tip || eraser,
+            button: barrel,
+            x: self.x.scaled(data, 0, range)?,
+            y: self.y.scaled(data, 0, range)?,
+            pressure: match self.pressure {
+                Some(f) => f.scaled(data, 0, syscall::INPUT_TABLET_PRESSURE_MAX as i32)?,
+                None if tip || eraser => syscall::INPUT_TABLET_PRESSURE_MAX,
+                None => 0,
+            },
+            tilt_x: match self.tilt_x {
+                Some(a) => a.read(data)?,
+                None => 0,
+            },
+            tilt_y: match self.tilt_y {
+                Some(a) => a.read(data)?,
+                None => 0,
+            },
+        })
+    }
+}
+
+/// Retient le premier champ d'un usage.
+fn fill<T>(slot: &mut Option<T>, value: T) -> bool {
+    if slot.is_some() {
+        return false;
+    }
+    *slot = Some(value);
+    true
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TabletEvent {
+    pub tool: u8,
+    pub code: u16,
+    pub value: i16,
+}
+
+impl TabletEvent {
+    /// Événement prêt pour `INPUT_MSG_PUSH`.
+    pub fn to_wire(self) -> syscall::InputEventWire {
+        let toggle = matches!(
+            self.code,
+            syscall::INPUT_TABLET_PROXIMITY
+                | syscall::INPUT_TABLET_TIP
+                | syscall::INPUT_TABLET_BUTTON
+        );
+        syscall::InputEventWire {
+            device: syscall::INPUT_DEVICE_TABLET,
+            state: if toggle && self.value != 0 {
+                syscall::INPUT_KEY_PRESSED
+            } else {
+                syscall::INPUT_KEY_RELEASED
+            },
+            code: self.code,
+            value: self.value,
+            ascii: self.tool,
+            modifiers: 0,
+            _pad: [0; 4],
+        }
+    }
+}
+
+/// État de l'outil ; `tool` = 0 hors de portée.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PenState {
+    pub tool: u8,
+    pub tip: bool,
+    pub button: bool,
+    pub x: i16,
+    pub y: i16,
+    pub pressure: i16,
+    /// Centièmes de degré.
+    pub tilt_x: i16,
+    pub tilt_y: i16,
+}
+
+impl PenState {
+    /// Écrit dans `out` les événements qui mènent de `self` à `next` ;
+    /// retourne leur nombre.
+    pub fn diff(&self, next: &PenState, out: &mut [TabletEvent; MAX_EVENTS]) -> usize {
+        let mut n = 0;
+        let mut push = |tool: u8, code: u16, value: i16| {
+            out[n] = TabletEvent { tool, code, value };
+            n += 1;
+        };
+        let same_tool = self.tool == next.tool;
+        if self.tool != 0 && !same_tool {
+            if self.tip {
+                push(self.tool, syscall::INPUT_TABLET_TIP, 0);
+            }
+            if self.button {
+                push(self.tool, syscall::INPUT_TABLET_BUTTON, 0);
+            }
+            push(self.tool, syscall::INPUT_TABLET_PROXIMITY, 0);
+        }
+        if next.tool != 0 {
+            // Un outil qui entre annonce tous ses axes.
+            let was = if same_tool {
+                *self
+            } else {
+                push(next.tool, syscall::INPUT_TABLET_PROXIMITY, 1);
+                PenState::default()
+            };
+            let axes = [
+                (syscall::INPUT_TABLET_X, was.x, next.x),
+                (syscall::INPUT_TABLET_Y, was.y, next.y),
+                (syscall::INPUT_TABLET_PRESSURE, was.pressure, next.pressure),
+                (syscall::INPUT_TABLET_TILT_X, was.tilt_x, next.tilt_x),
+                (syscall::INPUT_TABLET_TILT_Y, was.tilt_y, next.tilt_y),
+            ];
+            for (code, old, new) in axes {
+                if old != new || !same_tool {
+                    push(next.tool, code, new);
+                }
+            }
+            if was.tip != next.tip {
+                push(next.tool, syscall::INPUT_TABLET_TIP, next.tip as i16);
+            }
+            if was.button != next.button {
+                push(next.tool, syscall::INPUT_TABLET_BUTTON, next.button as i16);
+            }
+        }
+        if n > 0 {
+            let tool = if next.tool != 0 { next.tool } else { self.tool };
+            out[n] = TabletEvent {
+                tool,
+                code: syscall::INPUT_TABLET_FRAME,
+                value: 0,
+            };
+            n += 1;
+        }
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stylet : pointe, bouton, Invert, Eraser, In Range ; X/Y sur 15 bits,
+    /// pression sur 12 bits, inclinaisons -127..127 pour -60..60 degrés.
+    const PEN: &[u8] = &[
+        0x05, 0x0d, // Usage Page (Digitizer)
+        0x09, 0x02, // Usage (Pen)
+        0xa1, 0x01, // Collection (Application)
+        0x85, 0x02, //   Report ID (2)
+        0x09, 0x20, //   Usage (Stylus)
+        0xa1, 0x00, //   Collection (Physical)
+        0x09, 0x42, //     Usage (Tip Switch)
+        0x09, 0x44, //     Usage (Barrel Switch)
+        0x09, 0x3c, //     Usage (Invert)
+        0x09, 0x45, //     Usage (Eraser)
+        0x09, 0x32, //     Usage (In Range)
+        0x15, 0x00, //     Logical Minimum (0)
+        0x25, 0x01, //     Logical Maximum (1)
+        0x75, 0x01, //     Report Size (1)
+        0x95, 0x05, //     Report Count (5)
+        0x81, 0x02, //     Input (Data, Var, Abs)
+        0x95, 0x03, //     Report Count (3)
+        0x81, 0x03, //     Input (Const)
+        0x05, 0x01, //     Usage Page (Generic Desktop)
+        0x26, 0xff, 0x7f, // Logical Maximum (32767)
+        0x75, 0x10, //     Report Size (16)
+        0x95, 0x02, //     Report Count (2)
+        0x09, 0x30, //     Usage (X)
+        0x09, 0x31, //     Usage (Y)
+        0x81, 0x02, //     Input (Data, Var, Abs)
+        0x05, 0x0d, //     Usage Page (Digitizer)
+        0x09, 0x30, //     Usage (Tip Pressure)
+        0x26, 0xff, 0x0f, // Logical Maximum (4095)
+        0x95, 0x01, //     Report Count (1)
+        0x81, 0x02, //     Input (Data, Var, Abs)
+        0x09, 0x3d, //     Usage (X Tilt)
+        0x09, 0x3e, //     Usage (Y Tilt)
+        0x15, 0x81, //     Logical Minimum (-127)
+        0x25, 0x7f, //     Logical Maximum (127)
+        0x35, 0xc4, //     Physical Minimum (-60)
+        0x45, 0x3c, //     Physical Maximum (60)
+        0x65, 0x14, //     Unit (Degrees)
+        0x55, 0x00, //     Unit Exponent (0)
+        0x75, 0x08, //     Report Size (8)
+        0x95, 0x02, //     Report Count (2)
+        0x81, 0x02, //     Input (Data, Var, Abs)
+        0xc0, //   End Collection
+        0xc0, // End Collection
+    ];
+
+    const TIP: u8 = 1 << 0;
+    const BARREL: u8 = 1 << 1;
+    const INVERT: u8 = 1 << 2;
+    const IN_RANGE: u8 = 1 << 4;
+
+    fn report(bits: u8, x: u16, y: u16, pressure: u16, tilt: (i8, i8)) -> [u8; 10] {
+        let mut r = [0u8; 10];
+        r[0] = 2;
+        r[1] = bits;
+        r[2..4].copy_from_slice(&x.to_le_bytes());
+        r[4..6].copy_from_slice(&y.to_le_bytes());
+        r[6..8].copy_from_slice(&pressure.to_le_bytes());
+        r[8] = tilt.0 as u8;
+        r[9] = tilt.1 as u8;
+        r
+    }
+
+    fn codes(events: &[TabletEvent]) -> std::vec::Vec<(u8, u16, i16)> {
+        events.iter().map(|e| (e.tool, e.code, e.value)).collect()
+    }
+
+    #[test]
+    fn decodes_pressure_and_tilt() {
+        let layout = TabletLayout::parse(PEN).unwrap();
+        assert_eq!(layout.report_id, 2);
+        let state = layout
+            .decode(&report(
+                IN_RANGE | TIP | BARREL,
+                100,
+                200,
+                4095,
+                (127, -127),
+            ))
+            .unwrap();
+        assert_eq!(
+            state,
+            PenState {
+                tool: syscall::INPUT_TABLET_TOOL_PEN,
+                tip: true,
+                button: true,
+                x: 100,
+                y: 200,
+                pressure: syscall::INPUT_TABLET_PRESSURE_MAX,
+                tilt_x: 6000,
+                tilt_y: -6000,
+            }
+        );
+        let hover = layout.decode(&report(IN_RANGE, 0, 0, 0, (0, 0))).unwrap();
+        assert!(!hover.tip);
+        assert_eq!(hover.tool, syscall::INPUT_TABLET_TOOL_PEN);
+        let away = layout.decode(&report(TIP, 0, 0, 0, (0, 0))).unwrap();
+        assert_eq!(away, PenState::default());
+        assert!(layout.decode(&[1, 0, 0]).is_none());
+    }
+
+    #[test]
+    fn tool_changes_go_through_proximity() {
+        let layout = TabletLayout::parse(PEN).unwrap();
+        let mut out = [TabletEvent::default(); MAX_EVENTS];
+        let pen = syscall::INPUT_TABLET_TOOL_PEN;
+        let eraser = syscall::INPUT_TABLET_TOOL_ERASER;
+
+        let hover = layout.decode(&report(IN_RANGE, 10, 20, 0, (0, 0))).unwrap();
+        let n = PenState::default().diff(&hover, &mut out);
+        assert_eq!(
+            codes(&out[..n]),
+            [
+                (pen, syscall::INPUT_TABLET_PROXIMITY, 1),
+                (pen, syscall::INPUT_TABLET_X, 10),
+                (pen, syscall::INPUT_TABLET_Y, 20),
+                (pen, syscall::INPUT_TABLET_PRESSURE, 0),
+                (pen, syscall::INPUT_TABLET_TILT_X, 0),
+                (pen, syscall::INPUT_TABLET_TILT_Y, 0),
+                (pen, syscall::INPUT_TABLET_FRAME, 0),
+            ]
+        );
+        assert_eq!(out[0].to_wire().state, syscall::INPUT_KEY_PRESSED);
+
+        let down = layout
+            .decode(&report(IN_RANGE | TIP, 10, 20, 2048, (0, 0)))
+            .unwrap();
+        let n = hover.diff(&down, &mut out);
+        assert_eq!(
+            codes(&out[..n]),
+            [
+                (pen, syscall::INPUT_TABLET_PRESSURE, 16387),
+                (pen, syscall::INPUT_TABLET_TIP, 1),
+                (pen, syscall::INPUT_TABLET_FRAME, 0),
+            ]
+        );
+
+        // Retourné sur la gomme sans lever : le stylet sort, la gomme entre.
+        let flipped = layout
+            .decode(&report(IN_RANGE | INVERT, 10, 20, 0, (0, 0)))
+            .unwrap();
+        assert_eq!(flipped.tool, eraser);
+        let n = down.diff(&flipped, &mut out);
+        let seen = codes(&out[..n]);
+        assert_eq!(
+            seen[..3],
+            [
+                (pen, syscall::INPUT_TABLET_TIP, 0),
+                (pen, syscall::INPUT_TABLET_PROXIMITY, 0),
+                (eraser, syscall::INPUT_TABLET_PROXIMITY, 1),
+            ]
+        );
+        assert_eq!(seen.last(), Some(&(eraser, syscall::INPUT_TABLET_FRAME, 0)));
+        assert_eq!(flipped.diff(&flipped, &mut out), 0);
+    }
+
+    #[test]
+    fn rejects_other_devices() {
+        let mouse = [0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0xc0];
+        assert_eq!(TabletLayout::parse(&mouse), Err(DescriptorError::NotTablet));
+        let empty = [0x05, 0x0d, 0x09, 0x02, 0xa1, 0x01, 0xc0];
+        assert_eq!(
+            TabletLayout::parse(&empty),
+            Err(DescriptorError::NoControls)
+        );
+    }
+}
@@ -0,0 +1,509 @@
+//! Écrans tactiles multipoint (HID Digitizers, page 0x0D).
+//!
+//! Descripteurs « Touch Screen » tels que les exige Windows : une collection
+//! logique Finger par contact du rapport (Tip Switch, Contact Identifier,
+//! X, Y) et un Contact Count. En mode hybride, un rapport ne porte qu'une
+//! partie des contacts : le premier donne le total, les suivants 0, et
+//! l'image n'est complète qu'une fois tous les contacts reçus.
+//!
+//! Chaque contact posé reçoit un slot `input_server` qu'il garde jusqu'à ce
+//! qu'il se lève ; les différences entre deux images deviennent des
+//! événements `INPUT_DEVICE_TOUCH` terminés par `INPUT_TOUCH_FRAME`.
+
+use crate::descriptor::{
+    payload, DescriptorError, Field, Items, COLLECTION_APPLICATION, COLLECTION_LOGICAL,
+    INPUT_CONSTANT, MAIN_COLLECTION, MAIN_END_COLLECTION, MAIN_INPUT, PAGE_DIGITIZER,
+    PAGE_GENERIC_DESKTOP, USAGE_TIP_SWITCH, USAGE_X, USAGE_Y,
+};
+use exo_syscall_abi as syscall;
+
+const USAGE_TOUCH_SCREEN: u16 = 0x04;
+const USAGE_FINGER: u16 = 0x22;
+const USAGE_CONTACT_ID: u16 = 0x51;
+const USAGE_CONTACT_COUNT: u16 = 0x54;
+
+/// Contacts suivis, par rapport comme à l'écran.
+pub const MAX_CONTACTS: usize = syscall::INPUT_TOUCH_MAX;
+/// Pire image : chaque slot lève un contact et en pose un autre (X, Y,
+/// contact), plus la fin d'image.
+pub const MAX_EVENTS: usize = MAX_CONTACTS * 4 + 1;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct Finger {
+    tip: Option<Field>,
+    id: Option<Field>,
+    x: Option<Field>,
+    y: Option<Field>,
+}
+
+/// Un contact d'un rapport, position ramenée à `0..=INPUT_TOUCH_RANGE`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Contact {
+    /// Contact Identifier du périphérique, à défaut le rang dans le rapport.
+    pub id: u32,
+    pub x: i16,
+    pub y: i16,
+    /// Faux pour un contact qui se lève (ou une entrée inutilisée).
+    pub tip: bool,
+}
+
+/// Disposition d'un écran tactile, construite par `TouchLayout::parse`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TouchLayout {
+    /// `0` : le périphérique n'utilise pas d'identifiant de rapport.
+    pub report_id: u8,
+    fingers: [Finger; MAX_CONTACTS],
+    finger_count: usize,
+    contact_count: Option<Field>,
+}
+
+impl TouchLayout {
+    pub fn parse(desc: &[u8]) -> Result<Self, DescriptorError> {
+        let mut layout = TouchLayout {
+            report_id: 0,
+            fingers: [Finger::default(); MAX_CONTACTS],
+            finger_count: 0,
+            contact_count: None,
+        };
+        let mut items = Items::new(desc);
+        let mut seen = false;
+        // Profondeur des collections application Touch Screen et Finger
+        // ouvertes.
+        let mut depth = 0u32;
+        let mut screen: Option<u32> = None;
+        let mut finger: Option<(usize, u32)> = None;
+        let mut chosen: Option<u8> = None;
+
+        while let Some(main) = items.next_main()? {
+            match main.tag {
+                MAIN_COLLECTION => {
+                    depth += 1;
+                    match (main.value, items.usage(0)) {
+                        (COLLECTION_APPLICATION, Some((PAGE_DIGITIZER, USAGE_TOUCH_SCREEN))) => {
+                            screen = Some(depth);
+                            seen = true;
+                        }
+                        (COLLECTION_LOGICAL, Some((PAGE_DIGITIZER, USAGE_FINGER)))
+                            if screen.is_some() && layout.finger_count < MAX_CONTACTS =>
+                        {
+                            finger = Some((layout.finger_count, depth));
+                            layout.finger_count += 1;
+                        }
+                        _ => {}
+                    }
+                }
+                MAIN_END_COLLECTION => {
+                    if finger.is_some_and(|(_, d)| d == depth) {
+                        finger = None;
+                    }
+                    if screen == Some(depth) {
+                        screen = None;
+                    }
+                    depth = depth.saturating_sub(1);
+                }
+                MAIN_INPUT => {
+                    let id = items.globals.report_id;
+                    let base = items.input_base();
+                    let size = items.globals.report_size;
+                    let wanted = screen.is_some()
+                        && main.value & INPUT_CONSTANT == 0
+                        && (1..=32).contains(&size)
+                        && chosen.is_none_or(|c| c == id);
+                    for i in 0..items.globals.report_count {
+                        if !wanted {
+                            break;
+                        }
+                        let Some(usage) = items.usage(i) else {
+                            continue;
+                        };
+                        let f = finger.map(|(f, _)| f);
+                        let slot = match (usage, f) {
+                            ((PAGE_DIGITIZER, USAGE_CONTACT_COUNT), _) => &mut layout.contact_count,
+                            ((PAGE_DIGITIZER, USAGE_TIP_SWITCH), Some(f)) => {
+                                &mut layout.fingers[f].tip
+                            }
+                            ((PAGE_DIGITIZER, USAGE_CONTACT_ID), Some(f)) => {
+                                &mut layout.fingers[f].id
+                            }
+                            ((PAGE_GENERIC_DESKTOP, USAGE_X), Some(f)) => &mut layout.fingers[f].x,
+                            ((PAGE_GENERIC_DESKTOP, USAGE_Y), Some(f)) => &mut layout.fingers[f].y,
+                            _ => continue,
+                        };
+                        if slot.is_none() {
+                            *slot = Some(items.field(base, i));
+                            chosen = Some(id);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !seen {
+            return Err(DescriptorError::NotTouchscreen);
+        }
+        let usable = |f: &Finger| f.tip.is_some() && f.x.is_some() && f.y.is_some();
+        if !layout.fingers[..layout.finger_count].iter().any(usable) {
+            return Err(DescriptorError::NoControls);
+        }
+        layout.report_id = chosen.ok_or(DescriptorError::NoControls)?;
+        Ok(layout)
+    }
+
+    /// Décode un rapport d'entrée : toutes ses entrées de contact dans
+    /// `out`, et le Contact Count (0 sans ce champ). `None` pour un autre
+    /// rapport ou un rapport trop court.
+    pub fn decode(
+        &self,
+        report: &[u8],
+        out: &mut [Contact; MAX_CONTACTS],
+    ) -> Option<(usize, usize)> {
+        let data = payload(self.report_id, report)?;
+        let mut n = 0;
+        for (i, f) in self.fingers[..self.finger_count].iter().enumerate() {
+            let (Some(tip), Some(x), Some(y)) = (f.tip, f.x, f.y) else {
+                continue;
+            };
+            let range = syscall::INPUT_TOUCH_RANGE as i32;
+            out[n] = Contact {
+                id: match f.id {
+                    Some(id) => id.read(data)? as u32,
+                    None => i as u32,
+                },
+                x: x.scaled(data, 0, range)?,
+                y: y.scaled(data, 0, range)?,
+                tip: tip.read(data)? != 0,
+            };
+            n += 1;
+        }
+        let count = match self.contact_count {
+            Some(count) => count.read(data)?.max(0) as usize,
+            None => 0,
+        };
+        Some((n, count))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TouchEvent {
+    pub slot: u8,
+    pub code: u16,
+    pub value: i16,
+}
+
+impl TouchEvent {
+    /// Événement prêt pour `INPUT_MSG_PUSH`.
+    pub fn to_wire(self) -> syscall::InputEventWire {
+        let state = if self.code == syscall::INPUT_TOUCH_CONTACT && self.value != 0 {
+            syscall::INPUT_KEY_PRESSED
+        } else {
+            syscall::INPUT_KEY_RELEASED
+        };
+        syscall::InputEventWire {
+            device: syscall::INPUT_DEVICE_TOUCH,
+            state,
+            code: self.code,
+            value: self.value,
+            ascii: self.slot,
+            modifiers: 0,
+            _pad: [0; 4],
+        }
+    }
+}
+
+/// Contacts posés, par slot.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TouchState {
+    slots: [Option<Contact>; MAX_CONTACTS],
+}
+
+impl TouchState {
+    pub fn contact(&self, slot: usize) -> Option<&Contact> {
+        self.slots.get(slot)?.as_ref()
+    }
+
+    /// Passe à l'image `down` (contacts posés) et écrit dans `out` les
+    /// événements correspondants : levers, déplacements puis poses ;
+    /// retourne leur nombre. Un contact sans slot libre est ignoré.
+    pub fn update(&mut self, down: &[Contact], out: &mut [TouchEvent; MAX_EVENTS]) -> usize {
+        let mut n = 0;
+        let mut push = |slot: usize, code: u16, value: i16| {
+            out[n] = TouchEvent {
+                slot: slot as u8,
+                code,
+                value,
+            };
+            n += 1;
+        };
+        for (slot, s) in self.slots.iter_mut().enumerate() {
+            let Some(old) = *s else {
+                continue;
+            };
+            match down.iter().find(|c| c.id == old.id) {
+                None => {
+                    *s = None;
+                    push(slot, syscall::INPUT_TOUCH_CONTACT, 0);
+                }
+                Some(c) => {
+                    if c.x != old.x {
+                        push(slot, syscall::INPUT_TOUCH_X, c.x);
+                    }
+                    if c.y != old.y {
+                        push(slot, syscall::INPUT_TOUCH_Y, c.y);
+                    }
+                    *s = Some(*c);
+                }
+            }
+        }
+        for c in down {
+            if self.slots.iter().flatten().any(|s| s.id == c.id) {
+                continue;
+            }
+            let Some(slot) = self.slots.iter().position(Option::is_none) else {
+                continue;
+            };
+            self.slots[slot] = Some(*c);
+            push(slot, syscall::INPUT_TOUCH_X, c.x);
+            push(slot, syscall::INPUT_TOUCH_Y, c.y);
+            push(slot, syscall::INPUT_TOUCH_CONTACT, 1);
+        }
+        if n > 0 {
+            out[n] = TouchEvent {
+                slot: 0,
+                code: syscall::INPUT_TOUCH_FRAME,
+                value: 0,
+            };
+            n += 1;
+        }
+        n
+    }
+}
+
+/// Écran tactile : rassemble les rapports d'une image (mode hybride) et
+/// suit les contacts.
+pub struct Touchscreen {
+    layout: TouchLayout,
+    state: TouchState,
+    pending: [Contact; MAX_CONTACTS],
+    pending_len: usize,
+    /// Contacts annoncés pour l'image en cours, et déjà reçus.
+    expected: usize,
+    received: usize,
+}
+
+impl Touchscreen {
+    pub fn new(layout: TouchLayout) -> Self {
+        Self {
+            layout,
+            state: TouchState::default(),
+            pending: [Contact::default(); MAX_CONTACTS],
+            pending_len: 0,
+            expected: 0,
+            received: 0,
+        }
+    }
+
+    pub fn state(&self) -> &TouchState {
+        &self.state
+    }
+
+    /// Traite un rapport d'entrée ; retourne le nombre d'événements écrits
+    /// dans `out` (0 tant que l'image est incomplète).
+    pub fn feed(&mut self, report: &[u8], out: &mut [TouchEvent; MAX_EVENTS]) -> usize {
+        let mut entries = [Contact::default(); MAX_CONTACTS];
+        let Some((n, count)) = self.layout.decode(report, &mut entries) else {
+            return 0;
+        };
+        if count > 0 || self.received >= self.expected {
+            // Nouvelle image ; sans Contact Count, un rapport par image.
+            self.expected = if self.layout.contact_count.is_some() {
+                count.min(MAX_CONTACTS)
+            } else {
+                n
+            };
+            self.received = 0;
+            self.pending_len = 0;
+        }
+        let take = (self.expected - self.received).min(n);
+        for c in entries[..take].iter().filter(|c| c.tip) {
+            if self.pending_len < MAX_CONTACTS {
+                self.pending[self.pending_len] = *c;
+                self.pending_len += 1;
+            }
+        }
+        self.received += take;
+        if self.received < self.expected {
+            return 0;
+        }
+        let down = self.pending;
+        self.state.update(&down[..self.pending_len], out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Écran à deux contacts par rapport (X/Y sur 12 bits), rapport n° 1 :
+    /// par contact, Tip Switch + 7 bits de bourrage, identifiant, X, Y.
+    const TOUCH_SCREEN: &[u8] = &[
+        0x05, 0x0d, // Usage Page (Digitizer)
+        0x09, 0x04, // Usage (Touch Screen)
+        0xa1, 0x01, // Collection (Application)
+        0x85, 0x01, //   Report ID (1)
+        0x09, 0x22, //   Usage (Finger)
+        0xa1, 0x02, //   Collection (Logical)
+        0x09, 0x42, //     Usage (Tip Switch)
+        0x15, 0x00, //     Logical Minimum (0)
+        0x25, 0x01, //     Logical Maximum (1)
+        0x75, 0x01, //     Report Size (1)
+        0x95, 0x01, //     Report Count (1)
+        0x81, 0x02, //     Input (Data, Var, Abs)
+        0x95, 0x07, //     Report Count (7)
+        0x81, 0x03, //     Input (Const)
+        0x09, 0x51, //     Usage (Contact Identifier)
+        0x25, 0x3f, //     Logical Maximum (63)
+        0x75, 0x08, //     Report Size (8)
+        0x95, 0x01, //     Report Count (1)
+        0x81, 0x02, //     Input (Data, Var, Abs)
+        0x05, 0x01, //     Usage Page (Generic Desktop)
+        0x26, 0xff, 0x0f, // Logical Maximum (4095)
+        0x75, 0x10, //     Report Size (16)
+        0x09, 0x30, //     Usage (X)
+        0x09, 0x31, //     Usage (Y)
+        0x95, 0x02, //     Report Count (2)
+        0x81, 0x02, //     Input (Data, Var, Abs)
+        0x05, 0x0d, //     Usage Page (Digitizer)
+        0xc0, //   End Collection
+        0x09, 0x22, //   Usage (Finger)
+        0xa1, 0x02, //   Collection (Logical)
+        0x09, 0x42, 0x25, 0x01, 0x75, 0x01, 0x95, 0x01, 0x81, 0x02, //
+        0x95, 0x07, 0x81, 0x03, //
+        0x09, 0x51, 0x25, 0x3f, 0x75, 0x08, 0x95, 0x01, 0x81, 0x02, //
+        0x05, 0x01, 0x26, 0xff, 0x0f, 0x75, 0x10, 0x09, 0x30, 0x09, 0x31, 0x95, 0x02, 0x81,
+        0x02, //
+        0x05, 0x0d, //
+        0xc0, //   End Collection
+        0x09, 0x54, //   Usage (Contact Count)
+        0x25, 0x7f, //   Logical Maximum (127)
+        0x75, 0x08, //   Report Size (8)
+        0x95, 0x01, //   Report Count (1)
+        0x81, 0x02, //   Input (Data, Var, Abs)
+        0xc0, // End Collection
+    ];
+
+    /// (tip, id, x, y) par contact, puis Contact Count.
+    fn report(fingers: [(bool, u8, u16, u16); 2], count: u8) -> [u8; 14] {
+        let mut r = [0u8; 14];
+        r[0] = 1;
+        for (i, (tip, id, x, y)) in fingers.iter().enumerate() {
+            let at = 1 + i * 6;
+            r[at] = *tip as u8;
+            r[at + 1] = *id;
+            r[at + 2..at + 4].copy_from_slice(&x.to_le_bytes());
+            r[at + 4..at + 6].copy_from_slice(&y.to_le_bytes());
+        }
+        r[13] = count;
+        r
+    }
+
+    const NONE: (bool, u8, u16, u16) = (false, 0, 0, 0);
+
+    fn event(slot: u8, code: u16, value: i16) -> TouchEvent {
+        TouchEvent { slot, code, value }
+    }
+
+    #[test]
+    fn parses_and_tracks_contacts() {
+        let layout = TouchLayout::parse(TOUCH_SCREEN).unwrap();
+        assert_eq!(layout.report_id, 1);
+        let mut screen = Touchscreen::new(layout);
+        let mut out = [TouchEvent::default(); MAX_EVENTS];
+
+        let n = screen.feed(&report([(true, 7, 0, 4095), NONE], 1), &mut out);
+        assert_eq!(
+            &out[..n],
+            &[
+                event(0, syscall::INPUT_TOUCH_X, 0),
+                event(0, syscall::INPUT_TOUCH_Y, syscall::INPUT_TOUCH_RANGE),
+                event(0, syscall::INPUT_TOUCH_CONTACT, 1),
+                event(0, syscall::INPUT_TOUCH_FRAME, 0),
+            ]
+        );
+        assert_eq!(out[2].to_wire().state, syscall::INPUT_KEY_PRESSED);
+
+        // Le premier bouge, un second se pose.
+        let n = screen.feed(
+            &report([(true, 7, 4095, 4095), (true, 9, 0, 0)], 2),
+            &mut out,
+        );
+        assert_eq!(
+            &out[..n],
+            &[
+                event(0, syscall::INPUT_TOUCH_X, syscall::INPUT_TOUCH_RANGE),
+                event(1, syscall::INPUT_TOUCH_X, 0),
+                event(1, syscall::INPUT_TOUCH_Y, 0),
+                event(1, syscall::INPUT_TOUCH_CONTACT, 1),
+                event(0, syscall::INPUT_TOUCH_FRAME, 0),
+            ]
+        );
+
+        // Le premier se lève : le second garde son slot.
+        let n = screen.feed(
+            &report([(false, 7, 4095, 4095), (true, 9, 0, 0)], 2),
+            &mut out,
+        );
+        assert_eq!(
+            &out[..n],
+            &[
+                event(0, syscall::INPUT_TOUCH_CONTACT, 0),
+                event(0, syscall::INPUT_TOUCH_FRAME, 0),
+            ]
+        );
+        assert!(screen.state().contact(0).is_none());
+        assert_eq!(screen.state().contact(1).map(|c| c.id), Some(9));
+
+        // Rien ne change : pas d'événement ; autre rapport ignoré.
+        assert_eq!(
+            screen.feed(&report([(true, 9, 0, 0), NONE], 1), &mut out),
+            0
+        );
+        let mut other = report([NONE, NONE], 0);
+        other[0] = 2;
+        assert_eq!(screen.feed(&other, &mut out), 0);
+    }
+
+    #[test]
+    fn hybrid_mode_waits_for_the_whole_frame() {
+        let mut screen = Touchscreen::new(TouchLayout::parse(TOUCH_SCREEN).unwrap());
+        let mut out = [TouchEvent::default(); MAX_EVENTS];
+        // Trois contacts en deux rapports : total dans le premier seulement.
+        let first = report([(true, 1, 0, 0), (true, 2, 0, 0)], 3);
+        assert_eq!(screen.feed(&first, &mut out), 0);
+        let n = screen.feed(&report([(true, 3, 0, 0), NONE], 0), &mut out);
+        let downs = out[..n]
+            .iter()
+            .filter(|e| e.code == syscall::INPUT_TOUCH_CONTACT && e.value == 1)
+            .count();
+        assert_eq!(downs, 3);
+
+        // Plus aucun contact : tout se lève.
+        let n = screen.feed(&report([NONE, NONE], 0), &mut out);
+        let ups = out[..n]
+            .iter()
+            .filter(|e| e.code == syscall::INPUT_TOUCH_CONTACT && e.value == 0)
+            .count();
+        assert_eq!(ups, 3);
+    }
+
+    #[test]
+    fn rejects_other_devices() {
+        let pad = [0x05, 0x01, 0x09, 0x05, 0xa1, 0x01, 0xc0];
+        assert_eq!(
+            TouchLayout::parse(&pad),
+            Err(DescriptorError::NotTouchscreen)
+        );
+        let empty = [0x05, 0x0d, 0x09, 0x04, 0xa1, 0x01, 0xc0];
+        assert_eq!(TouchLayout::parse(&empty), Err(DescriptorError::NoControls));
+    }
+}
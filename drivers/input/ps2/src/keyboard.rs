@@ -1,4 +1,5 @@
 use crate::{InputEvent, InputModifiers, KeyState};
+use exo_syscall_abi as syscall;
 
 pub const KEY_ENTER: u16 = 0x0028;
 pub const KEY_BACKSPACE: u16 = 0x002a;
@@ -9,6 +10,40 @@ pub const KEY_LEFT_SHIFT: u16 = 0x00e1;
 pub const KEY_RIGHT_SHIFT: u16 = 0x00e5;
 pub const KEY_LEFT_CTRL: u16 = 0x00e0;
 pub const KEY_LEFT_ALT: u16 = 0x00e2;
+pub const KEY_LEFT_META: u16 = 0x00e3;
+pub const KEY_RIGHT_META: u16 = 0x00e7;
+pub const KEY_PRINT_SCREEN: u16 = 0x0046;
+pub const KEY_CAPS_LOCK: u16 = 0x0039;
+pub const KEY_SCROLL_LOCK: u16 = 0x0047;
+pub const KEY_NUM_LOCK: u16 = 0x0053;
+pub const KEY_KP_SLASH: u16 = 0x0054;
+pub const KEY_KP_STAR: u16 = 0x0055;
+pub const KEY_KP_MINUS: u16 = 0x0056;
+pub const KEY_KP_PLUS: u16 = 0x0057;
+pub const KEY_KP_1: u16 = 0x0059;
+pub const KEY_KP_9: u16 = 0x0061;
+pub const KEY_KP_0: u16 = 0x0062;
+pub const KEY_KP_DOT: u16 = 0x0063;
+pub const KEY_MUTE: u16 = syscall::INPUT_KEY_MUTE;
+pub const KEY_VOLUME_UP: u16 = syscall::INPUT_KEY_VOLUME_UP;
+pub const KEY_VOLUME_DOWN: u16 = syscall::INPUT_KEY_VOLUME_DOWN;
+pub const KEY_PLAY_PAUSE: u16 = syscall::INPUT_KEY_PLAY_PAUSE;
+pub const KEY_STOP: u16 = syscall::INPUT_KEY_STOP;
+pub const KEY_NEXT_TRACK: u16 = syscall::INPUT_KEY_NEXT_TRACK;
+pub const KEY_PREV_TRACK: u16 = syscall::INPUT_KEY_PREV_TRACK;
+
+/// Keyboard commands and responses (written to / read from the data port).
+pub const CMD_SET_LEDS: u8 = 0xED;
+pub const RESP_ACK: u8 = 0xFA;
+pub const RESP_RESEND: u8 = 0xFE;
+/// Pause/Break prefix; the rest of its sequence would decode as NumLock.
+const PREFIX_PAUSE: u8 = 0xE1;
+const PAUSE_TAIL_SET1: u8 = 5;
+const PAUSE_TAIL_SET2: u8 = 7;
+
+pub const LED_SCROLL_LOCK: u8 = 1 << 0;
+pub const LED_NUM_LOCK: u8 = 1 << 1;
+pub const LED_CAPS_LOCK: u8 = 1 << 2;
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 enum ScancodeSet {
@@ -17,45 +52,123 @@ enum ScancodeSet {
     Set2,
 }
 
+/// Progress of the two-byte `CMD_SET_LEDS` exchange; each byte waits for
+/// the keyboard's ACK, which arrives through the normal scancode stream.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum LedStep {
+    #[default]
+    Idle,
+    CommandSent,
+    CommandAcked,
+    ValueSent,
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct Ps2Keyboard {
     scancode_set: ScancodeSet,
     release_next: bool,
     extended_next: bool,
+    pause_skip: u8,
     modifiers: InputModifiers,
+    /// Lock keys currently down, so typematic repeats do not toggle again.
+    locks_held: u8,
+    leds_dirty: bool,
+    led_step: LedStep,
 }
 
 impl Ps2Keyboard {
     pub const fn new() -> Self {
-        Self {
-            scancode_set: ScancodeSet::Set2,
-            release_next: false,
-            extended_next: false,
-            modifiers: InputModifiers {
-                shift: false,
-                ctrl: false,
-                alt: false,
-                meta: false,
-            },
-        }
+        Self::with_set(ScancodeSet::Set2)
     }
 
     pub const fn new_set1() -> Self {
+        Self::with_set(ScancodeSet::Set1)
+    }
+
+    /// LEDs start dirty: the firmware may have left NumLock lit.
+    const fn with_set(scancode_set: ScancodeSet) -> Self {
         Self {
-            scancode_set: ScancodeSet::Set1,
+            scancode_set,
             release_next: false,
             extended_next: false,
-            modifiers: InputModifiers {
-                shift: false,
-                ctrl: false,
-                alt: false,
-                meta: false,
-            },
+            pause_skip: 0,
+            modifiers: InputModifiers::NONE,
+            locks_held: 0,
+            leds_dirty: true,
+            led_step: LedStep::Idle,
+        }
+    }
+
+    /// Current lock state as a `CMD_SET_LEDS` value.
+    pub fn leds(&self) -> u8 {
+        let mut leds = 0;
+        if self.modifiers.scroll_lock {
+            leds |= LED_SCROLL_LOCK;
         }
+        if self.modifiers.num_lock {
+            leds |= LED_NUM_LOCK;
+        }
+        if self.modifiers.caps_lock {
+            leds |= LED_CAPS_LOCK;
+        }
+        leds
+    }
+
+    /// Next byte the driver must write to the keyboard, if any. Called after
+    /// every `feed`, since ACKs are what move the LED exchange forward.
+    pub fn poll_command(&mut self) -> Option<u8> {
+        match self.led_step {
+            LedStep::Idle if self.leds_dirty => {
+                self.leds_dirty = false;
+                self.led_step = LedStep::CommandSent;
+                Some(CMD_SET_LEDS)
+            }
+            LedStep::CommandAcked => {
+                self.led_step = LedStep::ValueSent;
+                Some(self.leds())
+            }
+            _ => None,
+        }
+    }
+
+    /// The last byte from `poll_command` could not be written; the LEDs stay
+    /// as they are until the next lock toggle.
+    pub fn cancel_command(&mut self) {
+        self.led_step = LedStep::Idle;
     }
 
     pub fn feed(&mut self, byte: u8) -> Option<InputEvent> {
+        if self.pause_skip > 0 {
+            self.pause_skip -= 1;
+            return None;
+        }
         match byte {
+            RESP_ACK => {
+                self.led_step = match self.led_step {
+                    LedStep::CommandSent => LedStep::CommandAcked,
+                    LedStep::ValueSent => LedStep::Idle,
+                    step => step,
+                };
+                None
+            }
+            RESP_RESEND => {
+                match self.led_step {
+                    LedStep::CommandSent => {
+                        self.led_step = LedStep::Idle;
+                        self.leds_dirty = true;
+                    }
+                    LedStep::ValueSent => self.led_step = LedStep::CommandAcked,
+                    _ => {}
+                }
+                None
+            }
+            PREFIX_PAUSE => {
+                self.pause_skip = match self.scancode_set {
+                    ScancodeSet::Set1 => PAUSE_TAIL_SET1,
+                    ScancodeSet::Set2 => PAUSE_TAIL_SET2,
+                };
+                None
+            }
             0xE0 => {
                 self.extended_next = true;
                 None
@@ -85,7 +198,7 @@ impl Ps2Keyboard {
                 };
                 self.update_modifiers(code, state);
                 let ascii = if state == KeyState::Pressed {
-                    hid_to_ascii(code, self.modifiers.shift, self.modifiers.ctrl)
+                    self.ascii(code)
                 } else {
                     0
                 };
@@ -100,9 +213,56 @@ impl Ps2Keyboard {
             KEY_LEFT_SHIFT | KEY_RIGHT_SHIFT => self.modifiers.shift = pressed,
             KEY_LEFT_CTRL => self.modifiers.ctrl = pressed,
             KEY_LEFT_ALT => self.modifiers.alt = pressed,
+            KEY_LEFT_META | KEY_RIGHT_META => self.modifiers.meta = pressed,
+            KEY_CAPS_LOCK => self.update_lock(LED_CAPS_LOCK, pressed),
+            KEY_NUM_LOCK => self.update_lock(LED_NUM_LOCK, pressed),
+            KEY_SCROLL_LOCK => self.update_lock(LED_SCROLL_LOCK, pressed),
             _ => {}
         }
     }
+
+    fn update_lock(&mut self, led: u8, pressed: bool) {
+        if !pressed {
+            self.locks_held &= !led;
+            return;
+        }
+        if self.locks_held & led != 0 {
+            return;
+        }
+        self.locks_held |= led;
+        let lock = match led {
+            LED_CAPS_LOCK => &mut self.modifiers.caps_lock,
+            LED_NUM_LOCK => &mut self.modifiers.num_lock,
+            _ => &mut self.modifiers.scroll_lock,
+        };
+        *lock = !*lock;
+        self.leds_dirty = true;
+    }
+
+    fn ascii(&self, code: u16) -> u8 {
+        let keypad = keypad_ascii(code, self.modifiers.num_lock);
+        if keypad != 0 {
+            return keypad;
+        }
+        // CapsLock only shifts letters.
+        let letter = (0x0004..=0x001d).contains(&code);
+        let shift = self.modifiers.shift ^ (letter && self.modifiers.caps_lock);
+        hid_to_ascii(code, shift, self.modifiers.ctrl)
+    }
+}
+
+/// Keypad operators always type; digits and the dot only with NumLock.
+fn keypad_ascii(code: u16, num_lock: bool) -> u8 {
+    match code {
+        KEY_KP_SLASH => b'/',
+        KEY_KP_STAR => b'*',
+        KEY_KP_MINUS => b'-',
+        KEY_KP_PLUS => b'+',
+        KEY_KP_1..=KEY_KP_9 if num_lock => b'1' + (code - KEY_KP_1) as u8,
+        KEY_KP_0 if num_lock => b'0',
+        KEY_KP_DOT if num_lock => b'.',
+        _ => 0,
+    }
 }
 
 pub fn map_set1_to_hid(scancode: u8, extended: bool) -> Option<u16> {
@@ -111,6 +271,17 @@ pub fn map_set1_to_hid(scancode: u8, extended: bool) -> Option<u16> {
             0x1c => Some(KEY_ENTER),
             0x1d => Some(KEY_LEFT_CTRL),
             0x38 => Some(KEY_LEFT_ALT),
+            0x5b => Some(KEY_LEFT_META),
+            0x5c => Some(KEY_RIGHT_META),
+            0x37 => Some(KEY_PRINT_SCREEN),
+            0x20 => Some(KEY_MUTE),
+            0x30 => Some(KEY_VOLUME_UP),
+            0x2e => Some(KEY_VOLUME_DOWN),
+            0x22 => Some(KEY_PLAY_PAUSE),
+            0x24 => Some(KEY_STOP),
+            0x19 => Some(KEY_NEXT_TRACK),
+            0x10 => Some(KEY_PREV_TRACK),
+            0x35 => Some(KEY_KP_SLASH),
             0x48 => Some(0x0052), // up
             0x50 => Some(0x0051), // down
             0x4b => Some(0x0050), // left
@@ -182,6 +353,23 @@ pub fn map_set1_to_hid(scancode: u8, extended: bool) -> Option<u16> {
         0x36 => Some(KEY_RIGHT_SHIFT),
         0x1d => Some(KEY_LEFT_CTRL),
         0x38 => Some(KEY_LEFT_ALT),
+        0x3a => Some(KEY_CAPS_LOCK),
+        0x45 => Some(KEY_NUM_LOCK),
+        0x46 => Some(KEY_SCROLL_LOCK),
+        0x37 => Some(KEY_KP_STAR),
+        0x4a => Some(KEY_KP_MINUS),
+        0x4e => Some(KEY_KP_PLUS),
+        0x4f => Some(KEY_KP_1),
+        0x50 => Some(0x005a), // keypad 2
+        0x51 => Some(0x005b), // keypad 3
+        0x4b => Some(0x005c), // keypad 4
+        0x4c => Some(0x005d), // keypad 5
+        0x4d => Some(0x005e), // keypad 6
+        0x47 => Some(0x005f), // keypad 7
+        0x48 => Some(0x0060), // keypad 8
+        0x49 => Some(KEY_KP_9),
+        0x52 => Some(KEY_KP_0),
+        0x53 => Some(KEY_KP_DOT),
         _ => None,
     }
 }
@@ -192,6 +380,17 @@ pub fn map_set2_to_hid(scancode: u8, extended: bool) -> Option<u16> {
             0x5a => Some(KEY_ENTER),
             0x14 => Some(KEY_LEFT_CTRL),
             0x11 => Some(KEY_LEFT_ALT),
+            0x1f => Some(KEY_LEFT_META),
+            0x27 => Some(KEY_RIGHT_META),
+            0x7c => Some(KEY_PRINT_SCREEN),
+            0x23 => Some(KEY_MUTE),
+            0x32 => Some(KEY_VOLUME_UP),
+            0x21 => Some(KEY_VOLUME_DOWN),
+            0x34 => Some(KEY_PLAY_PAUSE),
+            0x3b => Some(KEY_STOP),
+            0x4d => Some(KEY_NEXT_TRACK),
+            0x15 => Some(KEY_PREV_TRACK),
+            0x4a => Some(KEY_KP_SLASH),
             0x75 => Some(0x0052), // up
             0x72 => Some(0x0051), // down
             0x6b => Some(0x0050), // left
@@ -263,6 +462,23 @@ pub fn map_set2_to_hid(scancode: u8, extended: bool) -> Option<u16> {
         0x59 => Some(KEY_RIGHT_SHIFT),
         0x14 => Some(KEY_LEFT_CTRL),
         0x11 => Some(KEY_LEFT_ALT),
+        0x58 => Some(KEY_CAPS_LOCK),
+        0x77 => Some(KEY_NUM_LOCK),
+        0x7e => Some(KEY_SCROLL_LOCK),
+        0x7c => Some(KEY_KP_STAR),
+        0x7b => Some(KEY_KP_MINUS),
+        0x79 => Some(KEY_KP_PLUS),
+        0x69 => Some(KEY_KP_1),
+        0x72 => Some(0x005a), // keypad 2
+        0x7a => Some(0x005b), // keypad 3
+        0x6b => Some(0x005c), // keypad 4
+        0x73 => Some(0x005d), // keypad 5
+        0x74 => Some(0x005e), // keypad 6
+        0x6c => Some(0x005f), // keypad 7
+        0x75 => Some(0x0060), // keypad 8
+        0x7d => Some(KEY_KP_9),
+        0x70 => Some(KEY_KP_0),
+        0x71 => Some(KEY_KP_DOT),
         _ => None,
     }
 }
@@ -467,6 +683,83 @@ mod tests {
         assert_eq!(kb.feed(0x21).unwrap().ascii, 3);
     }
 
+    #[test]
+    fn decodes_meta_and_media_keys() {
+        let mut kb = Ps2Keyboard::new();
+        assert!(kb.feed(0xe0).is_none());
+        let meta = kb.feed(0x1f).unwrap();
+        assert_eq!(meta.code, KEY_LEFT_META);
+        assert!(meta.modifiers.meta);
+        assert!(kb.feed(0xe0).is_none());
+        assert_eq!(kb.feed(0x7c).unwrap().code, KEY_PRINT_SCREEN);
+        assert!(kb.feed(0xe0).is_none());
+        assert!(kb.feed(0xf0).is_none());
+        assert!(!kb.feed(0x1f).unwrap().modifiers.meta);
+
+        let mut kb = Ps2Keyboard::new_set1();
+        assert!(kb.feed(0xe0).is_none());
+        assert_eq!(kb.feed(0x30).unwrap().code, KEY_VOLUME_UP);
+    }
+
+    #[test]
+    fn caps_lock_toggles_case_and_leds() {
+        let mut kb = Ps2Keyboard::new();
+        assert_eq!(kb.poll_command(), Some(CMD_SET_LEDS));
+        assert!(kb.feed(RESP_ACK).is_none());
+        assert_eq!(kb.poll_command(), Some(0));
+        assert!(kb.feed(RESP_ACK).is_none());
+        assert_eq!(kb.poll_command(), None);
+
+        assert!(kb.feed(0x58).unwrap().modifiers.caps_lock);
+        // Typematic repeat does not toggle again.
+        assert!(kb.feed(0x58).unwrap().modifiers.caps_lock);
+        assert!(kb.feed(0xf0).is_none());
+        assert!(kb.feed(0x58).is_some());
+        assert_eq!(kb.feed(0x1c).unwrap().ascii, b'A');
+        assert_eq!(kb.feed(0x16).unwrap().ascii, b'1');
+
+        assert_eq!(kb.poll_command(), Some(CMD_SET_LEDS));
+        assert!(kb.feed(RESP_RESEND).is_none());
+        assert_eq!(kb.poll_command(), Some(CMD_SET_LEDS));
+        assert!(kb.feed(RESP_ACK).is_none());
+        assert_eq!(kb.poll_command(), Some(LED_CAPS_LOCK));
+    }
+
+    #[test]
+    fn num_lock_gates_keypad_digits() {
+        let mut kb = Ps2Keyboard::new_set1();
+        assert_eq!(kb.feed(0x4f).unwrap().ascii, 0);
+        assert_eq!(kb.feed(0x4e).unwrap().ascii, b'+');
+        assert_eq!(kb.feed(0x45).unwrap().code, KEY_NUM_LOCK);
+        let one = kb.feed(0x4f).unwrap();
+        assert_eq!((one.code, one.ascii), (KEY_KP_1, b'1'));
+        assert_eq!(kb.leds(), LED_NUM_LOCK);
+    }
+
+    #[test]
+    fn pause_sequence_is_not_num_lock() {
+        let mut kb = Ps2Keyboard::new();
+        for byte in [0xe1, 0x14, 0x77, 0xe1, 0xf0, 0x14, 0xf0, 0x77] {
+            assert!(kb.feed(byte).is_none());
+        }
+        assert_eq!(kb.leds(), 0);
+        assert_eq!(kb.feed(0x1c).unwrap().code, 0x0004);
+    }
+
+    #[test]
+    fn decodes_transport_media_keys() {
+        let mut kb = Ps2Keyboard::new();
+        assert!(kb.feed(0xe0).is_none());
+        assert_eq!(kb.feed(0x34).unwrap().code, KEY_PLAY_PAUSE);
+        assert!(kb.feed(0xe0).is_none());
+        assert!(kb.feed(0xf0).is_none());
+        assert_eq!(kb.feed(0x4d).unwrap().value, 0);
+
+        let mut kb = Ps2Keyboard::new_set1();
+        assert!(kb.feed(0xe0).is_none());
+        assert_eq!(kb.feed(0x10).unwrap().code, KEY_PREV_TRACK);
+    }
+
     #[test]
     fn decodes_translated_set1_key() {
         let mut kb = Ps2Keyboard::new_set1();
@@ -28,6 +28,21 @@ pub struct InputModifiers {
     pub ctrl: bool,
     pub alt: bool,
     pub meta: bool,
+    pub caps_lock: bool,
+    pub num_lock: bool,
+    pub scroll_lock: bool,
+}
+
+impl InputModifiers {
+    pub const NONE: Self = Self {
+        shift: false,
+        ctrl: false,
+        alt: false,
+        meta: false,
+        caps_lock: false,
+        num_lock: false,
+        scroll_lock: false,
+    };
 }
 
 #[repr(C)]
@@ -60,12 +75,7 @@ impl InputEvent {
             code,
             value,
             ascii: 0,
-            modifiers: InputModifiers {
-                shift: false,
-                ctrl: false,
-                alt: false,
-                meta: false,
-            },
+            modifiers: InputModifiers::NONE,
         }
     }
 }
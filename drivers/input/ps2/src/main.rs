@@ -177,6 +177,15 @@ fn modifier_bits(event: &InputEvent) -> u8 {
     if event.modifiers.meta {
         bits |= syscall::INPUT_MOD_META;
     }
+    if event.modifiers.caps_lock {
+        bits |= syscall::INPUT_MOD_CAPS_LOCK;
+    }
+    if event.modifiers.num_lock {
+        bits |= syscall::INPUT_MOD_NUM_LOCK;
+    }
+    if event.modifiers.scroll_lock {
+        bits |= syscall::INPUT_MOD_SCROLL_LOCK;
+    }
     bits
 }
 
@@ -221,6 +230,17 @@ fn push_input_event(event: InputEvent) {
     };
 }
 
+/// Writes the next keyboard command byte (LEDs); its ACK comes back with the
+/// scancodes.
+#[cfg(target_os = "none")]
+fn send_keyboard_command(controller: &mut I8042<SyscallPorts>, keyboard: &mut Ps2Keyboard) {
+    if let Some(byte) = keyboard.poll_command() {
+        if controller.write_data(byte).is_err() {
+            keyboard.cancel_command();
+        }
+    }
+}
+
 #[cfg(target_os = "none")]
 fn drain_controller(controller: &mut I8042<SyscallPorts>, keyboard: &mut Ps2Keyboard) -> bool {
     let mut drained_any = false;
@@ -233,6 +253,7 @@ fn drain_controller(controller: &mut I8042<SyscallPorts>, keyboard: &mut Ps2Keyb
         if let Some(event) = keyboard.feed(byte) {
             push_input_event(event);
         }
+        send_keyboard_command(controller, keyboard);
         drained += 1;
     }
     drained_any
@@ -266,6 +287,8 @@ pub extern "C" fn _start() -> ! {
         Ps2Keyboard::new_set1()
     };
 
+    send_keyboard_command(&mut controller, &mut keyboard);
+
     let mut irq_buf = [0u8; 9];
     loop {
         let wave = recv_irq_notification(endpoint, &mut irq_buf);
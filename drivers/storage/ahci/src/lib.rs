@@ -16,8 +16,10 @@
 extern crate alloc;
 
 pub mod regs;
+pub mod smart;
 pub mod structures;
 
+use smart::{SmartData, SMART_PAGE_BYTES};
 use structures::{CmdHeader, FisRegH2D, PrdtEntry, FIS_H2D_DWORDS};
 
 pub const EXOFS_BLOCK_SIZE: usize = 4096;
@@ -55,6 +57,8 @@ pub enum AhciError {
     UnsupportedSectorSize(u32),
     PrdtBuild,
     IdentifyFailed,
+    /// SMART désactivé/non supporté, ou page à somme de contrôle fausse.
+    SmartFailed,
 }
 
 pub struct AhciDevice<H: AhciHal> {
@@ -211,6 +215,25 @@ impl<H: AhciHal> AhciDevice<H> {
         Ok(())
     }
 
+    /// Lit les attributs SMART et leurs seuils (SMART READ DATA puis READ
+    /// THRESHOLDS).
+    pub fn smart_read(&mut self) -> Result<SmartData, AhciError> {
+        let data = self.smart_page(structures::smart::READ_DATA)?;
+        let thresholds = self.smart_page(structures::smart::READ_THRESHOLDS)?;
+        SmartData::parse(&data, &thresholds).ok_or(AhciError::SmartFailed)
+    }
+
+    fn smart_page(&mut self, feature: u8) -> Result<[u8; SMART_PAGE_BYTES], AhciError> {
+        self.issue_command(FisRegH2D::smart(feature), false, SMART_PAGE_BYTES)
+            .map_err(|_| AhciError::SmartFailed)?;
+        let mut page = [0u8; SMART_PAGE_BYTES];
+        // SAFETY: data est une page DMA valide remplie par la commande (512 octets).
+        for (i, b) in page.iter_mut().enumerate() {
+            *b = unsafe { core::ptr::read_volatile(self.data.virt.add(i)) };
+        }
+        Ok(page)
+    }
+
     /// Trouve un slot de commande libre (CI et SACT à 0).
     fn find_free_slot(&self) -> Option<u32> {
         let used = self.read_port(regs::PORT_CI) | self.read_port(regs::PORT_SACT);
@@ -0,0 +1,401 @@
+//! ATA SMART : décodage des pages READ DATA / READ THRESHOLDS (512 octets
+//! chacune) et prédiction de défaillance.
+//!
+//! Tout est **pur** : le driver lit les deux pages, [`SmartData::parse`] les
+//! fusionne en une table d'attributs. [`HealthMonitor::observe`] compare les
+//! relevés successifs et ne signale que les changements (secteurs réalloués
+//! ou en attente qui augmentent, seuil constructeur franchi, usure SSD).
+
+/// Taille d'une page SMART (données ou seuils).
+pub const SMART_PAGE_BYTES: usize = 512;
+/// Nombre d'entrées d'attribut dans une page (12 octets chacune, dès l'octet 2).
+pub const MAX_ATTRIBUTES: usize = 30;
+const ENTRY_BYTES: usize = 12;
+const TABLE_OFFSET: usize = 2;
+
+/// Identifiants d'attributs (usage de fait, pas de norme ATA).
+pub mod attr {
+    pub const REALLOCATED_SECTORS: u8 = 5;
+    pub const POWER_ON_HOURS: u8 = 9;
+    pub const WEAR_LEVELING: u8 = 177;
+    pub const REPORTED_UNCORRECTABLE: u8 = 187;
+    pub const TEMPERATURE: u8 = 194;
+    pub const PENDING_SECTORS: u8 = 197;
+    pub const OFFLINE_UNCORRECTABLE: u8 = 198;
+    pub const SSD_LIFE_LEFT: u8 = 231;
+    pub const MEDIA_WEAROUT: u8 = 233;
+}
+
+/// Attributs dont l'usure se lit sur la valeur normalisée (100 = neuf).
+const WEAR_ATTRIBUTES: [u8; 3] = [
+    attr::WEAR_LEVELING,
+    attr::SSD_LIFE_LEFT,
+    attr::MEDIA_WEAROUT,
+];
+
+/// Granularité des événements d'usure (un événement par palier franchi).
+pub const WEAR_STEP_PERCENT: u8 = 10;
+/// Usure à partir de laquelle le disque est signalé.
+pub const WEAR_WARNING_PERCENT: u8 = 90;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Attribute {
+    pub id: u8,
+    pub flags: u16,
+    /// Valeur normalisée courante (1..=253, plus haut = meilleur).
+    pub value: u8,
+    pub worst: u8,
+    /// Seuil constructeur (0 : jamais en échec).
+    pub threshold: u8,
+    /// Valeur brute sur 48 bits, interprétation propre au constructeur.
+    pub raw: u64,
+}
+
+impl Attribute {
+    /// Bit 0 des flags : attribut « pre-failure », son échec prédit une panne.
+    pub fn is_prefailure(&self) -> bool {
+        self.flags & 1 != 0
+    }
+
+    /// Valeur normalisée à ou sous le seuil constructeur.
+    pub fn is_failing(&self) -> bool {
+        self.threshold != 0 && self.value <= self.threshold
+    }
+}
+
+/// Table d'attributs fusionnée (données + seuils).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SmartData {
+    attrs: [Attribute; MAX_ATTRIBUTES],
+    len: usize,
+}
+
+impl Default for SmartData {
+    fn default() -> Self {
+        Self {
+            attrs: [Attribute::default(); MAX_ATTRIBUTES],
+            len: 0,
+        }
+    }
+}
+
+/// Les pages SMART se terminent par un octet de somme : la somme des 512
+/// octets doit être nulle modulo 256.
+pub fn checksum_ok(page: &[u8]) -> bool {
+    page.len() >= SMART_PAGE_BYTES
+        && page[..SMART_PAGE_BYTES]
+            .iter()
+            .fold(0u8, |acc, &b| acc.wrapping_add(b))
+            == 0
+}
+
+impl SmartData {
+    /// Fusionne les pages READ DATA et READ THRESHOLDS ; `None` si l'une est
+    /// tronquée ou a une somme de contrôle fausse.
+    pub fn parse(data: &[u8], thresholds: &[u8]) -> Option<Self> {
+        if !checksum_ok(data) || !checksum_ok(thresholds) {
+            return None;
+        }
+        let mut out = Self::default();
+        for i in 0..MAX_ATTRIBUTES {
+            let e = &data[TABLE_OFFSET + i * ENTRY_BYTES..][..ENTRY_BYTES];
+            if e[0] == 0 {
+                continue;
+            }
+            let mut raw = [0u8; 8];
+            raw[..6].copy_from_slice(&e[5..11]);
+            // Les seuils sont rangés par id, pas forcément dans le même ordre.
+            let threshold = (0..MAX_ATTRIBUTES)
+                .map(|j| &thresholds[TABLE_OFFSET + j * ENTRY_BYTES..][..2])
+                .find(|t| t[0] == e[0])
+                .map_or(0, |t| t[1]);
+            out.attrs[out.len] = Attribute {
+                id: e[0],
+                flags: u16::from_le_bytes([e[1], e[2]]),
+                value: e[3],
+                worst: e[4],
+                threshold,
+                raw: u64::from_le_bytes(raw),
+            };
+            out.len += 1;
+        }
+        Some(out)
+    }
+
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attrs[..self.len]
+    }
+
+    pub fn get(&self, id: u8) -> Option<&Attribute> {
+        self.attributes().iter().find(|a| a.id == id)
+    }
+
+    /// Compteur brut d'un attribut, 0 s'il est absent.
+    fn raw(&self, id: u8) -> u64 {
+        self.get(id).map_or(0, |a| a.raw)
+    }
+
+    pub fn reallocated_sectors(&self) -> u64 {
+        self.raw(attr::REALLOCATED_SECTORS)
+    }
+
+    pub fn pending_sectors(&self) -> u64 {
+        self.raw(attr::PENDING_SECTORS)
+    }
+
+    /// Erreurs non corrigibles (signalées à l'hôte + trouvées hors ligne).
+    pub fn uncorrectable(&self) -> u64 {
+        self.raw(attr::REPORTED_UNCORRECTABLE)
+            .saturating_add(self.raw(attr::OFFLINE_UNCORRECTABLE))
+    }
+
+    pub fn power_on_hours(&self) -> Option<u64> {
+        self.get(attr::POWER_ON_HOURS).map(|a| a.raw & 0xFFFF_FFFF)
+    }
+
+    /// Température courante : octet bas de la valeur brute.
+    pub fn temperature_celsius(&self) -> Option<u8> {
+        self.get(attr::TEMPERATURE).map(|a| a.raw as u8)
+    }
+
+    /// Usure SSD en pourcentage, `None` pour un disque sans attribut d'usure
+    /// (disque rotatif le plus souvent).
+    pub fn wear_percent(&self) -> Option<u8> {
+        WEAR_ATTRIBUTES
+            .iter()
+            .find_map(|&id| self.get(id))
+            .map(|a| 100u8.saturating_sub(a.value.min(100)))
+    }
+
+    /// Attribut pre-failure sous son seuil, s'il y en a un.
+    pub fn failing_attribute(&self) -> Option<&Attribute> {
+        self.attributes()
+            .iter()
+            .find(|a| a.is_prefailure() && a.is_failing())
+    }
+
+    /// Verdict global à partir d'un seul relevé.
+    pub fn assess(&self) -> Health {
+        if self.failing_attribute().is_some() {
+            Health::Failing
+        } else if self.reallocated_sectors() > 0
+            || self.pending_sectors() > 0
+            || self.uncorrectable() > 0
+            || self
+                .wear_percent()
+                .is_some_and(|w| w >= WEAR_WARNING_PERCENT)
+        {
+            Health::Warning
+        } else {
+            Health::Good
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Health {
+    #[default]
+    Good,
+    /// Le disque fonctionne mais se dégrade : à surveiller, sauvegarder.
+    Warning,
+    /// Défaillance prédite : remplacer le disque.
+    Failing,
+}
+
+/// Changement notable entre deux relevés.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HealthEvent {
+    /// Le verdict global a changé (dans un sens ou dans l'autre).
+    Verdict { from: Health, to: Health },
+    /// Un attribut pre-failure vient de passer sous son seuil.
+    ThresholdExceeded { id: u8, value: u8, threshold: u8 },
+    /// Nouveaux secteurs réalloués.
+    Reallocated { new: u64, total: u64 },
+    /// Nouveaux secteurs en attente de réallocation.
+    Pending { new: u64, total: u64 },
+    /// Nouvelles erreurs non corrigibles.
+    Uncorrectable { new: u64, total: u64 },
+    /// L'usure SSD a franchi un palier de [`WEAR_STEP_PERCENT`].
+    Wear(u8),
+}
+
+/// Suivi des relevés successifs d'un même disque.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HealthMonitor {
+    last: Option<SmartData>,
+}
+
+impl HealthMonitor {
+    pub const fn new() -> Self {
+        Self { last: None }
+    }
+
+    /// Dernier relevé observé.
+    pub fn last(&self) -> Option<&SmartData> {
+        self.last.as_ref()
+    }
+
+    /// Intègre un relevé et signale ce qui a changé. Le premier relevé est
+    /// comparé à un disque neuf.
+    pub fn observe(&mut self, data: &SmartData, mut emit: impl FnMut(HealthEvent)) -> Health {
+        let prev = self.last.unwrap_or_default();
+        let verdict = data.assess();
+        let prev_verdict = self.last.map_or(Health::Good, |d| d.assess());
+        if verdict != prev_verdict {
+            emit(HealthEvent::Verdict {
+                from: prev_verdict,
+                to: verdict,
+            });
+        }
+        for a in data.attributes() {
+            let was_failing = prev.get(a.id).is_some_and(|p| p.is_failing());
+            if a.is_prefailure() && a.is_failing() && !was_failing {
+                emit(HealthEvent::ThresholdExceeded {
+                    id: a.id,
+                    value: a.value,
+                    threshold: a.threshold,
+                });
+            }
+        }
+        let total = data.reallocated_sectors();
+        if total > prev.reallocated_sectors() {
+            let new = total - prev.reallocated_sectors();
+            emit(HealthEvent::Reallocated { new, total });
+        }
+        let total = data.pending_sectors();
+        if total > prev.pending_sectors() {
+            let new = total - prev.pending_sectors();
+            emit(HealthEvent::Pending { new, total });
+        }
+        let total = data.uncorrectable();
+        if total > prev.uncorrectable() {
+            let new = total - prev.uncorrectable();
+            emit(HealthEvent::Uncorrectable { new, total });
+        }
+        let wear = data.wear_percent().unwrap_or(0);
+        let prev_wear = prev.wear_percent().unwrap_or(0);
+        if wear / WEAR_STEP_PERCENT > prev_wear / WEAR_STEP_PERCENT {
+            emit(HealthEvent::Wear(wear));
+        }
+        self.last = Some(*data);
+        verdict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// (id, flags, value, raw, threshold)
+    type Row = (u8, u16, u8, u64, u8);
+
+    fn pages(rows: &[Row]) -> ([u8; SMART_PAGE_BYTES], [u8; SMART_PAGE_BYTES]) {
+        let mut data = [0u8; SMART_PAGE_BYTES];
+        let mut thr = [0u8; SMART_PAGE_BYTES];
+        data[0] = 0x10;
+        thr[0] = 0x10;
+        for (i, &(id, flags, value, raw, threshold)) in rows.iter().enumerate() {
+            let e = &mut data[TABLE_OFFSET + i * ENTRY_BYTES..][..ENTRY_BYTES];
+            e[0] = id;
+            e[1..3].copy_from_slice(&flags.to_le_bytes());
+            e[3] = value;
+            e[4] = value;
+            e[5..11].copy_from_slice(&raw.to_le_bytes()[..6]);
+            // Seuils rangés à l'envers : la fusion se fait par id.
+            let t = &mut thr[TABLE_OFFSET + (MAX_ATTRIBUTES - 1 - i) * ENTRY_BYTES..];
+            t[0] = id;
+            t[1] = threshold;
+        }
+        for page in [&mut data, &mut thr] {
+            let sum = page[..511].iter().fold(0u8, |a, &b| a.wrapping_add(b));
+            page[511] = sum.wrapping_neg();
+        }
+        (data, thr)
+    }
+
+    fn smart(rows: &[Row]) -> SmartData {
+        let (d, t) = pages(rows);
+        SmartData::parse(&d, &t).unwrap()
+    }
+
+    const HEALTHY: [Row; 4] = [
+        (attr::REALLOCATED_SECTORS, 0x33, 100, 0, 10),
+        (attr::POWER_ON_HOURS, 0x32, 98, 0x0001_0000_2710, 0),
+        (attr::TEMPERATURE, 0x22, 65, 35 | (50 << 16), 0),
+        (attr::MEDIA_WEAROUT, 0x32, 97, 0, 0),
+    ];
+
+    #[test]
+    fn parses_table_and_merges_thresholds() {
+        let s = smart(&HEALTHY);
+        assert_eq!(s.attributes().len(), 4);
+        let r = s.get(attr::REALLOCATED_SECTORS).unwrap();
+        assert_eq!((r.value, r.threshold), (100, 10));
+        assert!(r.is_prefailure() && !r.is_failing());
+        assert_eq!(s.power_on_hours(), Some(10_000));
+        assert_eq!(s.temperature_celsius(), Some(35));
+        assert_eq!(s.wear_percent(), Some(3));
+        assert_eq!(s.assess(), Health::Good);
+    }
+
+    #[test]
+    fn rejects_bad_checksum() {
+        let (mut d, t) = pages(&HEALTHY);
+        d[10] ^= 1;
+        assert!(SmartData::parse(&d, &t).is_none());
+        assert!(SmartData::parse(&d[..100], &t).is_none());
+    }
+
+    #[test]
+    fn prefailure_attribute_below_threshold_predicts_failure() {
+        let mut rows = HEALTHY;
+        rows[0] = (attr::REALLOCATED_SECTORS, 0x33, 9, 1800, 10);
+        let s = smart(&rows);
+        assert_eq!(s.failing_attribute().map(|a| a.id), Some(5));
+        assert_eq!(s.assess(), Health::Failing);
+        // Un attribut « old-age » sous son seuil n'est pas une prédiction.
+        rows[0] = (attr::REALLOCATED_SECTORS, 0x32, 9, 0, 10);
+        assert_eq!(smart(&rows).assess(), Health::Good);
+    }
+
+    #[test]
+    fn monitor_reports_growth_once() {
+        let mut mon = HealthMonitor::new();
+        let mut events = Vec::new();
+        mon.observe(&smart(&HEALTHY), |e| events.push(e));
+        assert!(events.is_empty());
+
+        let mut rows = HEALTHY;
+        rows[0] = (attr::REALLOCATED_SECTORS, 0x33, 100, 8, 10);
+        rows[3] = (attr::MEDIA_WEAROUT, 0x32, 88, 0, 0);
+        let verdict = mon.observe(&smart(&rows), |e| events.push(e));
+        assert_eq!(verdict, Health::Warning);
+        assert_eq!(
+            events,
+            [
+                HealthEvent::Verdict {
+                    from: Health::Good,
+                    to: Health::Warning
+                },
+                HealthEvent::Reallocated { new: 8, total: 8 },
+                HealthEvent::Wear(12),
+            ]
+        );
+        events.clear();
+        mon.observe(&smart(&rows), |e| events.push(e));
+        assert!(events.is_empty(), "relevé identique : pas de doublon");
+
+        rows[0] = (attr::REALLOCATED_SECTORS, 0x33, 5, 2000, 10);
+        mon.observe(&smart(&rows), |e| events.push(e));
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[1],
+            HealthEvent::ThresholdExceeded {
+                id: 5,
+                value: 5,
+                threshold: 10
+            }
+        );
+    }
+}
@@ -10,6 +10,15 @@ pub mod ata {
     pub const WRITE_DMA_EXT: u8 = 0x35;
     pub const IDENTIFY_DEVICE: u8 = 0xEC;
     pub const FLUSH_CACHE_EXT: u8 = 0xEA;
+    pub const SMART: u8 = 0xB0;
+}
+
+/// Sous-commandes SMART (registre Feature) et signature LBA obligatoire.
+pub mod smart {
+    pub const READ_DATA: u8 = 0xD0;
+    pub const READ_THRESHOLDS: u8 = 0xD1;
+    pub const LBA_MID: u8 = 0x4F;
+    pub const LBA_HIGH: u8 = 0xC2;
 }
 
 /// Type de FIS Register Host-to-Device.
@@ -176,6 +185,21 @@ impl FisRegH2D {
         }
     }
 
+    /// Construit un FIS SMART (0xB0) pour la sous-commande `feature`, qui lit
+    /// une page de 512 octets.
+    pub fn smart(feature: u8) -> Self {
+        Self {
+            fis_type: FIS_TYPE_REG_H2D,
+            pmport_c: 1 << 7,
+            command: ata::SMART,
+            featurel: feature,
+            lba1: smart::LBA_MID,
+            lba2: smart::LBA_HIGH,
+            countl: 1,
+            ..Default::default()
+        }
+    }
+
     /// Construit un FIS FLUSH CACHE EXT (0xEA).
     pub fn flush() -> Self {
         Self {
@@ -250,4 +274,12 @@ mod tests {
         assert_eq!(FisRegH2D::identify().command, ata::IDENTIFY_DEVICE);
         assert_eq!(FisRegH2D::flush().command, ata::FLUSH_CACHE_EXT);
     }
+
+    #[test]
+    fn fis_smart_carries_feature_and_signature() {
+        let f = FisRegH2D::smart(smart::READ_THRESHOLDS);
+        assert_eq!(f.command, ata::SMART);
+        assert_eq!(f.featurel, 0xD1);
+        assert_eq!((f.lba1, f.lba2), (0x4F, 0xC2));
+    }
 }
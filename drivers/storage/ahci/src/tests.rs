@@ -6,7 +6,7 @@
 extern crate std;
 
 use super::*;
-use crate::structures::{ata, CmdHeader, FisRegH2D, PrdtEntry};
+use crate::structures::{ata, smart, CmdHeader, FisRegH2D, PrdtEntry};
 use alloc::alloc::{alloc_zeroed, dealloc, Layout};
 use alloc::vec::Vec;
 use core::cell::RefCell;
@@ -94,6 +94,23 @@ impl MockInner {
                     // word 106 = 0 → secteurs de 512 octets.
                 }
             }
+            ata::SMART => {
+                // Un seul attribut : 3 secteurs réalloués (id 5, pre-failure,
+                // valeur 100, seuil 36). Octet 511 = somme de contrôle.
+                let mut page = [0u8; 512];
+                let entry: &[u8] = if fis.featurel == smart::READ_DATA {
+                    &[5, 0x33, 0x00, 100, 100, 3]
+                } else {
+                    &[5, 36]
+                };
+                page[2..2 + entry.len()].copy_from_slice(entry);
+                let sum = page.iter().fold(0u8, |a, &b| a.wrapping_add(b));
+                page[511] = sum.wrapping_neg();
+                // SAFETY: dba = buffer DMA ≥ 512 octets.
+                unsafe {
+                    core::ptr::copy_nonoverlapping(page.as_ptr(), dba as *mut u8, 512);
+                }
+            }
             ata::READ_DMA_EXT => {
                 if byte_off + byte_len <= self.disk.len() {
                     // SAFETY: dba buffer ≥ byte_len.
@@ -259,3 +276,15 @@ fn flush_succeeds() {
     let mut dev = AhciDevice::new(MockAhci::new()).expect("init");
     dev.flush().expect("flush");
 }
+
+#[test]
+fn smart_read_merges_data_and_thresholds() {
+    let mut dev = AhciDevice::new(MockAhci::new()).expect("init");
+    let data = dev.smart_read().expect("smart");
+    let realloc = data.get(crate::smart::attr::REALLOCATED_SECTORS).unwrap();
+    assert_eq!(
+        (realloc.value, realloc.threshold, realloc.raw),
+        (100, 36, 3)
+    );
+    assert_eq!(data.assess(), crate::smart::Health::Warning);
+}
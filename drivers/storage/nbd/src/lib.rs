@@ -0,0 +1,455 @@
+#![no_std]
+//! exo-nbd — initiateur Network Block Device pour Exo-OS.
+//!
+//! Client du protocole NBD (handshake « fixed newstyle », réponses simples)
+//! pour un volume servi par `nbd-server`, `qemu-nbd` ou `nbdkit`. Comme pour
+//! `exo-nvme`, le matériel — ici la connexion TCP — est injecté par
+//! l'appelant via [`NbdTransport`] ; les tests le simulent en mémoire.
+//! Aucun transport n'existe encore côté kernel : le crate n'est branché sur
+//! aucun `BlockDevice` et rien ne monte un export.
+//!
+//! ## Handshake
+//! `NBD_OPT_GO` (taille, drapeaux et taille de bloc préférée en une
+//! option), repli sur `NBD_OPT_EXPORT_NAME` pour les serveurs qui ne le
+//! connaissent pas. L'oldstyle n'est pas supporté.
+//!
+//! ## Reconnexion
+//! Une erreur de transport ou une réponse incohérente (magic, cookie)
+//! abandonne la connexion : la requête est rejouée après reconnexion et
+//! nouveau handshake, au plus `retries` fois, avec une attente croissante
+//! fournie par le transport ([`NbdTransport::backoff`]). Les requêtes sont
+//! idempotentes (lecture, écriture à offset fixe, flush), le rejeu est donc
+//! sûr. Un export dont la taille a changé entre deux connexions est refusé
+//! ([`NbdError::SizeChanged`]) plutôt que monté de travers. Une erreur
+//! renvoyée par le serveur (`EIO`, `ENOSPC`…) n'est PAS rejouée.
+//!
+//! I/O synchrone, une requête en vol à la fois, aucune allocation.
+
+pub mod proto;
+
+use proto::{
+    Info, OptionReply, Request, SimpleReply, CMD_DISC, CMD_FLAG_FUA, CMD_FLUSH, CMD_READ,
+    CMD_WRITE, EXPORT_NAME_REPLY_LEN, EXPORT_NAME_ZEROES, FLAG_C_FIXED_NEWSTYLE, FLAG_C_NO_ZEROES,
+    FLAG_FIXED_NEWSTYLE, FLAG_NO_ZEROES, GREETING_LEN, INFO_BLOCK_SIZE, OPTION_REPLY_LEN,
+    OPT_EXPORT_NAME, OPT_GO, REP_ACK, REP_ERR_UNKNOWN, REP_ERR_UNSUP, REP_FLAG_ERROR, REP_INFO,
+    SIMPLE_REPLY_LEN, TFLAG_READ_ONLY, TFLAG_SEND_FLUSH, TFLAG_SEND_FUA,
+};
+
+/// Port IANA de NBD.
+pub const DEFAULT_PORT: u16 = 10809;
+/// Nom d'export le plus long accepté.
+pub const EXPORT_NAME_MAX: usize = 64;
+/// Plus grand transfert par requête ; les accès plus longs sont découpés.
+pub const MAX_TRANSFER: usize = 128 * 1024;
+/// Rejeux par requête après une coupure.
+pub const DEFAULT_RETRIES: u32 = 5;
+/// Taille de bloc supposée si le serveur n'en annonce pas.
+const DEFAULT_BLOCK_SIZE: u32 = 512;
+/// Plus longue réponse d'option lue ; le reste est ignoré.
+const OPTION_PAYLOAD_MAX: usize = 64;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Transport — connexion fournie par l'appelant (ou un mock en test)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Coupure ou refus de connexion ; le détail reste au transport.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransportError;
+
+/// Flux d'octets fiable vers le serveur (TCP).
+pub trait NbdTransport {
+    /// Ouvre une connexion neuve ; une connexion précédente est abandonnée.
+    fn connect(&mut self) -> Result<(), TransportError>;
+    /// Envoie tout `data`.
+    fn send(&mut self, data: &[u8]) -> Result<(), TransportError>;
+    /// Remplit tout `buf`.
+    fn recv(&mut self, buf: &mut [u8]) -> Result<(), TransportError>;
+    fn close(&mut self);
+    /// Attente avant la reconnexion numéro `attempt` (1, 2…).
+    fn backoff(&mut self, attempt: u32);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NbdError {
+    Transport,
+    /// Réponse hors protocole (magic, cookie, handshake incomplet).
+    Protocol,
+    NameTooLong,
+    /// Le serveur ne connaît pas l'export.
+    UnknownExport,
+    /// Option refusée par le serveur (type de réponse d'erreur).
+    Refused(u32),
+    /// La taille de l'export a changé depuis la première connexion.
+    SizeChanged,
+    ReadOnly,
+    OutOfBounds,
+    /// errno renvoyé par le serveur.
+    Io(u32),
+}
+
+impl NbdError {
+    /// La connexion est à refaire et la requête à rejouer.
+    fn retryable(self) -> bool {
+        matches!(self, NbdError::Transport | NbdError::Protocol)
+    }
+}
+
+impl From<TransportError> for NbdError {
+    fn from(_: TransportError) -> Self {
+        NbdError::Transport
+    }
+}
+
+/// Export négocié.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Export {
+    pub size: u64,
+    /// Drapeaux de transmission (`proto::TFLAG_*`).
+    pub flags: u16,
+    /// Taille de bloc préférée annoncée (512 à défaut).
+    pub block_size: u32,
+}
+
+impl Export {
+    pub fn read_only(&self) -> bool {
+        self.flags & TFLAG_READ_ONLY != 0
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Client
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub struct NbdClient<T: NbdTransport> {
+    transport: T,
+    name: [u8; EXPORT_NAME_MAX],
+    name_len: usize,
+    /// Export de la première connexion réussie.
+    export: Option<Export>,
+    connected: bool,
+    next_cookie: u64,
+    retries: u32,
+    reconnects: u64,
+}
+
+impl<T: NbdTransport> NbdClient<T> {
+    pub fn new(transport: T, export_name: &[u8]) -> Result<Self, NbdError> {
+        if export_name.len() > EXPORT_NAME_MAX {
+            return Err(NbdError::NameTooLong);
+        }
+        let mut name = [0u8; EXPORT_NAME_MAX];
+        name[..export_name.len()].copy_from_slice(export_name);
+        Ok(Self {
+            transport,
+            name,
+            name_len: export_name.len(),
+            export: None,
+            connected: false,
+            next_cookie: 1,
+            retries: DEFAULT_RETRIES,
+            reconnects: 0,
+        })
+    }
+
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Première connexion (avec rejeux) ; retourne l'export négocié.
+    pub fn connect(&mut self) -> Result<Export, NbdError> {
+        self.retry(|client| client.ensure_connected())?;
+        self.export.ok_or(NbdError::Protocol)
+    }
+
+    pub fn export(&self) -> Option<Export> {
+        self.export
+    }
+
+    /// Reconnexions après coupure depuis la première connexion.
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects
+    }
+
+    pub fn read(&mut self, offset: u64, buf: &mut [u8]) -> Result<(), NbdError> {
+        self.check_bounds(offset, buf.len())?;
+        let mut done = 0;
+        while done < buf.len() {
+            let len = (buf.len() - done).min(MAX_TRANSFER);
+            let at = offset + done as u64;
+            let chunk = &mut buf[done..done + len];
+            self.retry(|client| client.transact(CMD_READ, 0, at, &[], chunk))?;
+            done += len;
+        }
+        Ok(())
+    }
+
+    /// `fua` : écriture durable au retour (repli sur un flush si le serveur
+    /// n'accepte pas FUA).
+    pub fn write(&mut self, offset: u64, data: &[u8], fua: bool) -> Result<(), NbdError> {
+        self.check_bounds(offset, data.len())?;
+        let export = self.export.ok_or(NbdError::Protocol)?;
+        if export.read_only() {
+            return Err(NbdError::ReadOnly);
+        }
+        let flags = if fua && export.flags & TFLAG_SEND_FUA != 0 {
+            CMD_FLAG_FUA
+        } else {
+            0
+        };
+        let mut done = 0;
+        while done < data.len() {
+            let len = (data.len() - done).min(MAX_TRANSFER);
+            let at = offset + done as u64;
+            let chunk = &data[done..done + len];
+            self.retry(|client| client.transact(CMD_WRITE, flags, at, chunk, &mut []))?;
+            done += len;
+        }
+        if fua && flags == 0 {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Sans effet si le serveur n'annonce pas `SEND_FLUSH` (écritures déjà
+    /// durables).
+    pub fn flush(&mut self) -> Result<(), NbdError> {
+        let export = self.export.ok_or(NbdError::Protocol)?;
+        if export.flags & TFLAG_SEND_FLUSH == 0 {
+            return Ok(());
+        }
+        self.retry(|client| client.transact(CMD_FLUSH, 0, 0, &[], &mut []))
+    }
+
+    /// `NBD_CMD_DISC` puis fermeture ; une nouvelle requête reconnecte.
+    pub fn disconnect(&mut self) {
+        if self.connected {
+            let request = Request {
+                flags: 0,
+                kind: CMD_DISC,
+                cookie: self.cookie(),
+                offset: 0,
+                len: 0,
+            };
+            let _ = self.transport.send(&request.encode());
+        }
+        self.drop_connection();
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    fn check_bounds(&mut self, offset: u64, len: usize) -> Result<(), NbdError> {
+        if self.export.is_none() {
+            self.connect()?;
+        }
+        let size = self.export.ok_or(NbdError::Protocol)?.size;
+        match offset.checked_add(len as u64) {
+            Some(end) if end <= size => Ok(()),
+            _ => Err(NbdError::OutOfBounds),
+        }
+    }
+
+    fn cookie(&mut self) -> u64 {
+        let cookie = self.next_cookie;
+        self.next_cookie = self.next_cookie.wrapping_add(1);
+        cookie
+    }
+
+    fn drop_connection(&mut self) {
+        if self.connected {
+            self.transport.close();
+        }
+        self.connected = false;
+    }
+
+    /// Exécute `op`, en reconnectant et rejouant sur erreur de transport ou
+    /// de protocole.
+    fn retry(
+        &mut self,
+        mut op: impl FnMut(&mut Self) -> Result<(), NbdError>,
+    ) -> Result<(), NbdError> {
+        let mut attempt = 0;
+        loop {
+            match op(self) {
+                Ok(()) => return Ok(()),
+                Err(e) if e.retryable() && attempt < self.retries => {
+                    self.drop_connection();
+                    attempt += 1;
+                    self.transport.backoff(attempt);
+                }
+                Err(e) => {
+                    if e.retryable() {
+                        self.drop_connection();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    fn ensure_connected(&mut self) -> Result<(), NbdError> {
+        if self.connected {
+            return Ok(());
+        }
+        self.transport.connect()?;
+        self.connected = true;
+        let result = self.handshake().and_then(|export| match self.export {
+            None => {
+                self.export = Some(export);
+                Ok(())
+            }
+            Some(first) if first.size != export.size => Err(NbdError::SizeChanged),
+            Some(_) => {
+                self.reconnects += 1;
+                Ok(())
+            }
+        });
+        // Un handshake raté ne laisse jamais une connexion à moitié négociée.
+        if result.is_err() {
+            self.drop_connection();
+        }
+        result
+    }
+
+    fn handshake(&mut self) -> Result<Export, NbdError> {
+        let mut greeting = [0u8; GREETING_LEN];
+        self.transport.recv(&mut greeting)?;
+        let flags = proto::decode_greeting(&greeting).ok_or(NbdError::Protocol)?;
+        if flags & FLAG_FIXED_NEWSTYLE == 0 {
+            return Err(NbdError::Protocol);
+        }
+        let no_zeroes = flags & FLAG_NO_ZEROES != 0;
+        let client_flags = FLAG_C_FIXED_NEWSTYLE | if no_zeroes { FLAG_C_NO_ZEROES } else { 0 };
+        self.transport.send(&client_flags.to_be_bytes())?;
+        match self.opt_go()? {
+            Some(export) => Ok(export),
+            None => self.opt_export_name(no_zeroes),
+        }
+    }
+
+    /// `NBD_OPT_GO` ; `None` si le serveur ne le connaît pas.
+    fn opt_go(&mut self) -> Result<Option<Export>, NbdError> {
+        let name_len = self.name_len;
+        let len = 4 + name_len + 2 + 2;
+        self.transport
+            .send(&proto::encode_option(OPT_GO, len as u32))?;
+        self.transport.send(&(name_len as u32).to_be_bytes())?;
+        self.transport.send(&self.name[..name_len])?;
+        self.transport.send(&1u16.to_be_bytes())?;
+        self.transport.send(&INFO_BLOCK_SIZE.to_be_bytes())?;
+
+        let mut size_flags = None;
+        let mut block_size = DEFAULT_BLOCK_SIZE;
+        loop {
+            let mut raw = [0u8; OPTION_REPLY_LEN];
+            self.transport.recv(&mut raw)?;
+            let reply = OptionReply::decode(&raw).ok_or(NbdError::Protocol)?;
+            if reply.option != OPT_GO {
+                return Err(NbdError::Protocol);
+            }
+            let mut payload = [0u8; OPTION_PAYLOAD_MAX];
+            let kept = self.recv_payload(reply.len, &mut payload)?;
+            match reply.kind {
+                REP_INFO => match Info::decode(&payload[..kept]).ok_or(NbdError::Protocol)? {
+                    Info::Export { size, flags } => size_flags = Some((size, flags)),
+                    Info::BlockSize { preferred, .. } if preferred.is_power_of_two() => {
+                        block_size = preferred
+                    }
+                    Info::BlockSize { .. } | Info::Other => {}
+                },
+                REP_ACK => {
+                    let (size, flags) = size_flags.ok_or(NbdError::Protocol)?;
+                    return Ok(Some(Export {
+                        size,
+                        flags,
+                        block_size,
+                    }));
+                }
+                REP_ERR_UNSUP => return Ok(None),
+                REP_ERR_UNKNOWN => return Err(NbdError::UnknownExport),
+                kind if kind & REP_FLAG_ERROR != 0 => return Err(NbdError::Refused(kind)),
+                _ => {}
+            }
+        }
+    }
+
+    /// `NBD_OPT_EXPORT_NAME` : sans réponse d'option, le serveur passe
+    /// directement en transmission (ou coupe si l'export est inconnu).
+    fn opt_export_name(&mut self, no_zeroes: bool) -> Result<Export, NbdError> {
+        let name_len = self.name_len;
+        self.transport
+            .send(&proto::encode_option(OPT_EXPORT_NAME, name_len as u32))?;
+        self.transport.send(&self.name[..name_len])?;
+        let mut raw = [0u8; EXPORT_NAME_REPLY_LEN];
+        self.transport.recv(&mut raw)?;
+        if !no_zeroes {
+            let mut zeroes = [0u8; EXPORT_NAME_ZEROES];
+            self.transport.recv(&mut zeroes)?;
+        }
+        let mut size = [0u8; 8];
+        size.copy_from_slice(&raw[..8]);
+        Ok(Export {
+            size: u64::from_be_bytes(size),
+            flags: u16::from_be_bytes([raw[8], raw[9]]),
+            block_size: DEFAULT_BLOCK_SIZE,
+        })
+    }
+
+    /// Lit `len` octets de réponse d'option ; garde le début dans `keep`.
+    fn recv_payload(&mut self, len: u32, keep: &mut [u8]) -> Result<usize, NbdError> {
+        let len = len as usize;
+        let kept = len.min(keep.len());
+        self.transport.recv(&mut keep[..kept])?;
+        let mut rest = len - kept;
+        let mut sink = [0u8; OPTION_PAYLOAD_MAX];
+        while rest > 0 {
+            let n = rest.min(sink.len());
+            self.transport.recv(&mut sink[..n])?;
+            rest -= n;
+        }
+        Ok(kept)
+    }
+
+    /// Une requête et sa réponse simple ; `data` part avec une écriture,
+    /// `into` reçoit une lecture.
+    fn transact(
+        &mut self,
+        kind: u16,
+        flags: u16,
+        offset: u64,
+        data: &[u8],
+        into: &mut [u8],
+    ) -> Result<(), NbdError> {
+        self.ensure_connected()?;
+        let cookie = self.cookie();
+        let request = Request {
+            flags,
+            kind,
+            cookie,
+            offset,
+            len: (data.len() + into.len()) as u32,
+        };
+        self.transport.send(&request.encode())?;
+        if !data.is_empty() {
+            self.transport.send(data)?;
+        }
+        let mut raw = [0u8; SIMPLE_REPLY_LEN];
+        self.transport.recv(&mut raw)?;
+        let reply = SimpleReply::decode(&raw).ok_or(NbdError::Protocol)?;
+        if reply.cookie != cookie {
+            return Err(NbdError::Protocol);
+        }
+        if reply.error != 0 {
+            return Err(NbdError::Io(reply.error));
+        }
+        if !into.is_empty() {
+            self.transport.recv(into)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
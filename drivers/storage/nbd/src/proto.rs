@@ -0,0 +1,213 @@
+//! Format filaire NBD : handshake « fixed newstyle », requêtes et réponses
+//! simples de la phase de transmission. Tous les entiers sont big-endian.
+
+/// Salutation serveur, suivie de [`IHAVEOPT`].
+pub const NBDMAGIC: u64 = 0x4e42_444d_4147_4943;
+/// Magic des options client (et de la salutation newstyle).
+pub const IHAVEOPT: u64 = 0x4948_4156_454f_5054;
+/// Magic des réponses aux options.
+pub const REPLY_MAGIC: u64 = 0x0003_e889_0455_65a9;
+pub const REQUEST_MAGIC: u32 = 0x2560_9513;
+pub const SIMPLE_REPLY_MAGIC: u32 = 0x6744_6698;
+
+/// Drapeaux de handshake (serveur).
+pub const FLAG_FIXED_NEWSTYLE: u16 = 1 << 0;
+pub const FLAG_NO_ZEROES: u16 = 1 << 1;
+/// Drapeaux de handshake (client).
+pub const FLAG_C_FIXED_NEWSTYLE: u32 = 1 << 0;
+pub const FLAG_C_NO_ZEROES: u32 = 1 << 1;
+
+pub const OPT_EXPORT_NAME: u32 = 1;
+pub const OPT_GO: u32 = 7;
+
+pub const REP_ACK: u32 = 1;
+pub const REP_INFO: u32 = 3;
+pub const REP_FLAG_ERROR: u32 = 1 << 31;
+pub const REP_ERR_UNSUP: u32 = REP_FLAG_ERROR | 1;
+pub const REP_ERR_UNKNOWN: u32 = REP_FLAG_ERROR | 6;
+
+pub const INFO_EXPORT: u16 = 0;
+pub const INFO_BLOCK_SIZE: u16 = 3;
+
+/// Drapeaux de transmission (par export).
+pub const TFLAG_HAS_FLAGS: u16 = 1 << 0;
+pub const TFLAG_READ_ONLY: u16 = 1 << 1;
+pub const TFLAG_SEND_FLUSH: u16 = 1 << 2;
+pub const TFLAG_SEND_FUA: u16 = 1 << 3;
+
+pub const CMD_READ: u16 = 0;
+pub const CMD_WRITE: u16 = 1;
+pub const CMD_DISC: u16 = 2;
+pub const CMD_FLUSH: u16 = 3;
+pub const CMD_FLAG_FUA: u16 = 1 << 0;
+
+/// Salutation : NBDMAGIC, IHAVEOPT, drapeaux.
+pub const GREETING_LEN: usize = 18;
+/// En-tête d'option : IHAVEOPT, option, longueur.
+pub const OPTION_LEN: usize = 16;
+pub const OPTION_REPLY_LEN: usize = 20;
+/// Fin de `NBD_OPT_EXPORT_NAME` : taille, drapeaux (+ 124 zéros sans
+/// `FLAG_NO_ZEROES`).
+pub const EXPORT_NAME_REPLY_LEN: usize = 10;
+pub const EXPORT_NAME_ZEROES: usize = 124;
+pub const REQUEST_LEN: usize = 28;
+pub const SIMPLE_REPLY_LEN: usize = 16;
+
+fn be16(b: &[u8]) -> u16 {
+    u16::from_be_bytes([b[0], b[1]])
+}
+
+fn be32(b: &[u8]) -> u32 {
+    u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn be64(b: &[u8]) -> u64 {
+    let mut raw = [0u8; 8];
+    raw.copy_from_slice(&b[..8]);
+    u64::from_be_bytes(raw)
+}
+
+/// Salutation serveur ; `None` si les magics ne sont pas ceux du newstyle.
+pub fn decode_greeting(raw: &[u8; GREETING_LEN]) -> Option<u16> {
+    (be64(&raw[0..]) == NBDMAGIC && be64(&raw[8..]) == IHAVEOPT).then(|| be16(&raw[16..]))
+}
+
+pub fn encode_greeting(flags: u16) -> [u8; GREETING_LEN] {
+    let mut out = [0u8; GREETING_LEN];
+    out[0..8].copy_from_slice(&NBDMAGIC.to_be_bytes());
+    out[8..16].copy_from_slice(&IHAVEOPT.to_be_bytes());
+    out[16..18].copy_from_slice(&flags.to_be_bytes());
+    out
+}
+
+pub fn encode_option(option: u32, len: u32) -> [u8; OPTION_LEN] {
+    let mut out = [0u8; OPTION_LEN];
+    out[0..8].copy_from_slice(&IHAVEOPT.to_be_bytes());
+    out[8..12].copy_from_slice(&option.to_be_bytes());
+    out[12..16].copy_from_slice(&len.to_be_bytes());
+    out
+}
+
+/// En-tête d'option : `(option, longueur)`.
+pub fn decode_option(raw: &[u8; OPTION_LEN]) -> Option<(u32, u32)> {
+    (be64(&raw[0..]) == IHAVEOPT).then(|| (be32(&raw[8..]), be32(&raw[12..])))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OptionReply {
+    pub option: u32,
+    pub kind: u32,
+    pub len: u32,
+}
+
+impl OptionReply {
+    pub fn encode(&self) -> [u8; OPTION_REPLY_LEN] {
+        let mut out = [0u8; OPTION_REPLY_LEN];
+        out[0..8].copy_from_slice(&REPLY_MAGIC.to_be_bytes());
+        out[8..12].copy_from_slice(&self.option.to_be_bytes());
+        out[12..16].copy_from_slice(&self.kind.to_be_bytes());
+        out[16..20].copy_from_slice(&self.len.to_be_bytes());
+        out
+    }
+
+    pub fn decode(raw: &[u8; OPTION_REPLY_LEN]) -> Option<Self> {
+        (be64(&raw[0..]) == REPLY_MAGIC).then(|| Self {
+            option: be32(&raw[8..]),
+            kind: be32(&raw[12..]),
+            len: be32(&raw[16..]),
+        })
+    }
+}
+
+/// Contenu d'un `NBD_REP_INFO` utile au client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Info {
+    /// Taille (octets) et drapeaux de transmission.
+    Export {
+        size: u64,
+        flags: u16,
+    },
+    /// Tailles de bloc minimale, préférée et maximale.
+    BlockSize {
+        min: u32,
+        preferred: u32,
+        max: u32,
+    },
+    Other,
+}
+
+impl Info {
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        let kind = be16(payload.get(..2)?);
+        let body = &payload[2..];
+        Some(match kind {
+            INFO_EXPORT if body.len() >= 10 => Info::Export {
+                size: be64(body),
+                flags: be16(&body[8..]),
+            },
+            INFO_BLOCK_SIZE if body.len() >= 12 => Info::BlockSize {
+                min: be32(body),
+                preferred: be32(&body[4..]),
+                max: be32(&body[8..]),
+            },
+            INFO_EXPORT | INFO_BLOCK_SIZE => return None,
+            _ => Info::Other,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Request {
+    pub flags: u16,
+    pub kind: u16,
+    pub cookie: u64,
+    pub offset: u64,
+    pub len: u32,
+}
+
+impl Request {
+    pub fn encode(&self) -> [u8; REQUEST_LEN] {
+        let mut out = [0u8; REQUEST_LEN];
+        out[0..4].copy_from_slice(&REQUEST_MAGIC.to_be_bytes());
+        out[4..6].copy_from_slice(&self.flags.to_be_bytes());
+        out[6..8].copy_from_slice(&self.kind.to_be_bytes());
+        out[8..16].copy_from_slice(&self.cookie.to_be_bytes());
+        out[16..24].copy_from_slice(&self.offset.to_be_bytes());
+        out[24..28].copy_from_slice(&self.len.to_be_bytes());
+        out
+    }
+
+    pub fn decode(raw: &[u8; REQUEST_LEN]) -> Option<Self> {
+        (be32(raw) == REQUEST_MAGIC).then(|| Self {
+            flags: be16(&raw[4..]),
+            kind: be16(&raw[6..]),
+            cookie: be64(&raw[8..]),
+            offset: be64(&raw[16..]),
+            len: be32(&raw[24..]),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimpleReply {
+    /// errno côté serveur, 0 si succès.
+    pub error: u32,
+    pub cookie: u64,
+}
+
+impl SimpleReply {
+    pub fn encode(&self) -> [u8; SIMPLE_REPLY_LEN] {
+        let mut out = [0u8; SIMPLE_REPLY_LEN];
+        out[0..4].copy_from_slice(&SIMPLE_REPLY_MAGIC.to_be_bytes());
+        out[4..8].copy_from_slice(&self.error.to_be_bytes());
+        out[8..16].copy_from_slice(&self.cookie.to_be_bytes());
+        out
+    }
+
+    pub fn decode(raw: &[u8; SIMPLE_REPLY_LEN]) -> Option<Self> {
+        (be32(raw) == SIMPLE_REPLY_MAGIC).then(|| Self {
+            error: be32(&raw[4..]),
+            cookie: be64(&raw[8..]),
+        })
+    }
+}
@@ -0,0 +1,408 @@
+//! Un **serveur NBD simulé** en mémoire (handshake fixed newstyle, phase de
+//! transmission, coupures injectées) valide le client de bout en bout :
+//! négociation, repli `EXPORT_NAME`, I/O découpées, rejeu après coupure.
+
+extern crate std;
+
+use super::*;
+use proto::{decode_option, encode_greeting, TFLAG_HAS_FLAGS};
+use std::collections::VecDeque;
+use std::vec::Vec;
+
+const EXPORT: &[u8] = b"root";
+const DISK_BYTES: usize = 512 * 1024;
+const EIO: u32 = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    ClientFlags,
+    Options,
+    Transmission,
+    Closed,
+}
+
+struct MockServer {
+    disk: Vec<u8>,
+    flags: u16,
+    opt_go: bool,
+    no_zeroes: bool,
+    /// Taille annoncée (diffère de `disk.len()` pour simuler un export changé).
+    size: u64,
+    /// Connexions refusées avant la suivante acceptée.
+    refuse: u32,
+    /// Coupe la connexion à la réception de la N-ième requête suivante.
+    drop_at: Option<u32>,
+    /// Offset dont les accès renvoient EIO.
+    bad_offset: Option<u64>,
+    phase: Phase,
+    client_no_zeroes: bool,
+    inbound: Vec<u8>,
+    outbound: VecDeque<u8>,
+    connects: u32,
+    backoffs: Vec<u32>,
+    commands: Vec<(u16, u16)>,
+}
+
+impl MockServer {
+    fn new() -> Self {
+        Self {
+            disk: std::vec![0u8; DISK_BYTES],
+            flags: TFLAG_HAS_FLAGS | TFLAG_SEND_FLUSH | TFLAG_SEND_FUA,
+            opt_go: true,
+            no_zeroes: true,
+            size: DISK_BYTES as u64,
+            refuse: 0,
+            drop_at: None,
+            bad_offset: None,
+            phase: Phase::Closed,
+            client_no_zeroes: false,
+            inbound: Vec::new(),
+            outbound: VecDeque::new(),
+            connects: 0,
+            backoffs: Vec::new(),
+            commands: Vec::new(),
+        }
+    }
+
+    fn reply(&mut self, bytes: &[u8]) {
+        self.outbound.extend(bytes.iter().copied());
+    }
+
+    fn option_reply(&mut self, option: u32, kind: u32, payload: &[u8]) {
+        let header = OptionReply {
+            option,
+            kind,
+            len: payload.len() as u32,
+        };
+        self.reply(&header.encode());
+        self.reply(payload);
+    }
+
+    fn process(&mut self) {
+        loop {
+            match self.phase {
+                Phase::ClientFlags if self.inbound.len() >= 4 => {
+                    let flags: Vec<u8> = self.inbound.drain(..4).collect();
+                    self.client_no_zeroes = flags[3] as u32 & FLAG_C_NO_ZEROES != 0;
+                    self.phase = Phase::Options;
+                }
+                Phase::Options if self.inbound.len() >= proto::OPTION_LEN => {
+                    let mut header = [0u8; proto::OPTION_LEN];
+                    header.copy_from_slice(&self.inbound[..proto::OPTION_LEN]);
+                    let (option, len) = decode_option(&header).expect("magic d'option");
+                    let total = proto::OPTION_LEN + len as usize;
+                    if self.inbound.len() < total {
+                        return;
+                    }
+                    let data: Vec<u8> = self.inbound.drain(..total).skip(16).collect();
+                    self.option(option, &data);
+                }
+                Phase::Transmission if self.inbound.len() >= proto::REQUEST_LEN => {
+                    let mut raw = [0u8; proto::REQUEST_LEN];
+                    raw.copy_from_slice(&self.inbound[..proto::REQUEST_LEN]);
+                    let request = Request::decode(&raw).expect("magic de requête");
+                    let payload = if request.kind == CMD_WRITE {
+                        request.len as usize
+                    } else {
+                        0
+                    };
+                    if self.inbound.len() < proto::REQUEST_LEN + payload {
+                        return;
+                    }
+                    let data: Vec<u8> = self
+                        .inbound
+                        .drain(..proto::REQUEST_LEN + payload)
+                        .skip(proto::REQUEST_LEN)
+                        .collect();
+                    self.command(request, &data);
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn option(&mut self, option: u32, data: &[u8]) {
+        match option {
+            OPT_GO if !self.opt_go => self.option_reply(option, REP_ERR_UNSUP, &[]),
+            OPT_GO => {
+                let name_len = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+                if &data[4..4 + name_len] != EXPORT {
+                    return self.option_reply(option, REP_ERR_UNKNOWN, &[]);
+                }
+                let mut export = Vec::from(proto::INFO_EXPORT.to_be_bytes());
+                export.extend_from_slice(&self.size.to_be_bytes());
+                export.extend_from_slice(&self.flags.to_be_bytes());
+                self.option_reply(option, REP_INFO, &export);
+                let mut sizes = Vec::from(INFO_BLOCK_SIZE.to_be_bytes());
+                for size in [512u32, 4096, 1 << 20] {
+                    sizes.extend_from_slice(&size.to_be_bytes());
+                }
+                self.option_reply(option, REP_INFO, &sizes);
+                // Info inconnue du client, à ignorer.
+                self.option_reply(option, REP_INFO, &[0, 42, 1, 2, 3]);
+                self.option_reply(option, REP_ACK, &[]);
+                self.phase = Phase::Transmission;
+            }
+            OPT_EXPORT_NAME => {
+                assert_eq!(data, EXPORT);
+                let size = self.size.to_be_bytes();
+                let flags = self.flags.to_be_bytes();
+                self.reply(&size);
+                self.reply(&flags);
+                if !self.client_no_zeroes {
+                    self.reply(&[0u8; EXPORT_NAME_ZEROES]);
+                }
+                self.phase = Phase::Transmission;
+            }
+            _ => self.option_reply(option, REP_ERR_UNSUP, &[]),
+        }
+    }
+
+    fn command(&mut self, request: Request, data: &[u8]) {
+        if let Some(n) = self.drop_at {
+            if n == 0 {
+                self.drop_at = None;
+                self.phase = Phase::Closed;
+                self.outbound.clear();
+                return;
+            }
+            self.drop_at = Some(n - 1);
+        }
+        self.commands.push((request.kind, request.flags));
+        let start = request.offset as usize;
+        let end = start + request.len as usize;
+        let error = if self.bad_offset == Some(request.offset) {
+            EIO
+        } else {
+            0
+        };
+        let reply = SimpleReply {
+            error,
+            cookie: request.cookie,
+        };
+        match request.kind {
+            CMD_READ => {
+                self.reply(&reply.encode());
+                if error == 0 {
+                    let bytes = self.disk[start..end].to_vec();
+                    self.reply(&bytes);
+                }
+            }
+            CMD_WRITE => {
+                if error == 0 {
+                    self.disk[start..end].copy_from_slice(data);
+                }
+                self.reply(&reply.encode());
+            }
+            CMD_FLUSH => self.reply(&reply.encode()),
+            CMD_DISC => self.phase = Phase::Closed,
+            _ => panic!("commande inattendue {}", request.kind),
+        }
+    }
+}
+
+impl NbdTransport for MockServer {
+    fn connect(&mut self) -> Result<(), TransportError> {
+        if self.refuse > 0 {
+            self.refuse -= 1;
+            return Err(TransportError);
+        }
+        self.connects += 1;
+        self.inbound.clear();
+        self.outbound.clear();
+        let greeting_flags = if self.no_zeroes {
+            FLAG_FIXED_NEWSTYLE | FLAG_NO_ZEROES
+        } else {
+            FLAG_FIXED_NEWSTYLE
+        };
+        self.reply(&encode_greeting(greeting_flags));
+        self.phase = Phase::ClientFlags;
+        Ok(())
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        if self.phase == Phase::Closed {
+            return Err(TransportError);
+        }
+        self.inbound.extend_from_slice(data);
+        self.process();
+        Ok(())
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> Result<(), TransportError> {
+        if self.outbound.len() < buf.len() {
+            return Err(TransportError);
+        }
+        for b in buf.iter_mut() {
+            *b = self.outbound.pop_front().unwrap();
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        self.phase = Phase::Closed;
+    }
+
+    fn backoff(&mut self, attempt: u32) {
+        self.backoffs.push(attempt);
+    }
+}
+
+fn client(server: MockServer) -> NbdClient<MockServer> {
+    NbdClient::new(server, EXPORT).unwrap()
+}
+
+fn pattern(len: usize, seed: u8) -> Vec<u8> {
+    (0..len)
+        .map(|i| (i as u8).wrapping_mul(31) ^ seed)
+        .collect()
+}
+
+#[test]
+fn opt_go_negotiates_export() {
+    let mut c = client(MockServer::new());
+    let export = c.connect().unwrap();
+    assert_eq!(export.size, DISK_BYTES as u64);
+    assert_eq!(export.block_size, 4096);
+    assert!(!export.read_only());
+    assert_eq!(c.reconnects(), 0);
+}
+
+#[test]
+fn falls_back_to_export_name() {
+    for no_zeroes in [true, false] {
+        let mut server = MockServer::new();
+        server.opt_go = false;
+        server.no_zeroes = no_zeroes;
+        let mut c = client(server);
+        let export = c.connect().unwrap();
+        assert_eq!(export.size, DISK_BYTES as u64);
+        assert_eq!(export.block_size, 512);
+        c.write(8192, b"exo", false).unwrap();
+        let mut back = [0u8; 3];
+        c.read(8192, &mut back).unwrap();
+        assert_eq!(&back, b"exo");
+    }
+}
+
+#[test]
+fn unknown_export_is_not_retried() {
+    let mut c = NbdClient::new(MockServer::new(), b"home").unwrap();
+    assert_eq!(c.connect(), Err(NbdError::UnknownExport));
+    assert_eq!(c.transport().connects, 1);
+    assert!(NbdClient::new(MockServer::new(), &[b'x'; EXPORT_NAME_MAX + 1]).is_err());
+}
+
+#[test]
+fn read_write_roundtrip_splits_large_transfers() {
+    let mut c = client(MockServer::new());
+    let data = pattern(MAX_TRANSFER * 2 + 4096, 0x5a);
+    c.write(4096, &data, false).unwrap();
+    let mut back = std::vec![0u8; data.len()];
+    c.read(4096, &mut back).unwrap();
+    assert_eq!(back, data);
+    let writes = c
+        .transport()
+        .commands
+        .iter()
+        .filter(|(kind, _)| *kind == CMD_WRITE)
+        .count();
+    assert_eq!(writes, 3);
+}
+
+#[test]
+fn dropped_connection_reconnects_and_replays() {
+    let mut c = client(MockServer::new());
+    c.write(0, &pattern(4096, 1), false).unwrap();
+    c.transport.drop_at = Some(0);
+    c.transport.refuse = 2;
+    let mut back = std::vec![0u8; 4096];
+    c.read(0, &mut back).unwrap();
+    assert_eq!(back, pattern(4096, 1));
+    assert_eq!(c.reconnects(), 1);
+    assert_eq!(c.transport().backoffs, [1, 2, 3]);
+}
+
+#[test]
+fn gives_up_after_retries() {
+    let mut c = client(MockServer::new()).with_retries(2);
+    c.connect().unwrap();
+    c.transport.drop_at = Some(0);
+    c.transport.refuse = 10;
+    let mut back = [0u8; 512];
+    assert_eq!(c.read(0, &mut back), Err(NbdError::Transport));
+    assert_eq!(c.transport().backoffs, [1, 2]);
+}
+
+#[test]
+fn size_change_on_reconnect_is_refused() {
+    let mut c = client(MockServer::new()).with_retries(1);
+    c.connect().unwrap();
+    c.transport.drop_at = Some(0);
+    c.transport.size = DISK_BYTES as u64 / 2;
+    let mut back = [0u8; 512];
+    assert_eq!(c.read(0, &mut back), Err(NbdError::SizeChanged));
+    assert_eq!(c.reconnects(), 0);
+}
+
+#[test]
+fn server_error_is_reported_without_replay() {
+    let mut c = client(MockServer::new());
+    c.transport.bad_offset = Some(8192);
+    let mut back = [0u8; 512];
+    assert_eq!(c.read(8192, &mut back), Err(NbdError::Io(EIO)));
+    assert!(c.transport().backoffs.is_empty());
+    // La connexion reste utilisable.
+    c.read(0, &mut back).unwrap();
+    assert_eq!(c.transport().connects, 1);
+}
+
+#[test]
+fn bounds_and_read_only() {
+    let mut server = MockServer::new();
+    server.flags |= TFLAG_READ_ONLY;
+    let mut c = client(server);
+    let mut back = [0u8; 512];
+    assert_eq!(
+        c.read(DISK_BYTES as u64 - 256, &mut back),
+        Err(NbdError::OutOfBounds)
+    );
+    assert_eq!(c.read(u64::MAX, &mut back), Err(NbdError::OutOfBounds));
+    assert_eq!(c.write(0, &back, false), Err(NbdError::ReadOnly));
+    c.read(DISK_BYTES as u64 - 512, &mut back).unwrap();
+}
+
+#[test]
+fn fua_and_flush_follow_export_flags() {
+    let mut c = client(MockServer::new());
+    c.write(0, b"a", true).unwrap();
+    c.flush().unwrap();
+    assert_eq!(
+        c.transport().commands,
+        [(CMD_WRITE, CMD_FLAG_FUA), (CMD_FLUSH, 0)]
+    );
+
+    let mut server = MockServer::new();
+    server.flags = TFLAG_HAS_FLAGS | TFLAG_SEND_FLUSH;
+    let mut c = client(server);
+    c.write(0, b"a", true).unwrap();
+    assert_eq!(c.transport().commands, [(CMD_WRITE, 0), (CMD_FLUSH, 0)]);
+
+    let mut server = MockServer::new();
+    server.flags = TFLAG_HAS_FLAGS;
+    let mut c = client(server);
+    c.connect().unwrap();
+    c.flush().unwrap();
+    assert!(c.transport().commands.is_empty());
+}
+
+#[test]
+fn disconnect_then_request_reconnects() {
+    let mut c = client(MockServer::new());
+    c.connect().unwrap();
+    c.disconnect();
+    assert_eq!(c.transport().commands, [(CMD_DISC, 0)]);
+    let mut back = [0u8; 512];
+    c.read(0, &mut back).unwrap();
+    assert_eq!(c.transport().connects, 2);
+}
@@ -10,6 +10,7 @@
 /// Opcodes admin.
 pub mod admin {
     pub const CREATE_IO_SQ: u8 = 0x01;
+    pub const GET_LOG_PAGE: u8 = 0x02;
     pub const CREATE_IO_CQ: u8 = 0x05;
     pub const IDENTIFY: u8 = 0x06;
 }
@@ -27,6 +28,14 @@ pub mod cns {
     pub const CONTROLLER: u32 = 0x01;
 }
 
+/// Identifiants de page pour Get Log Page.
+pub mod log_page {
+    pub const SMART_HEALTH: u8 = 0x02;
+}
+
+/// NSID « tous les namespaces » : la page SMART est alors globale au contrôleur.
+pub const NSID_ALL: u32 = 0xFFFF_FFFF;
+
 // ── Submission Queue Entry (64 octets = 16 dwords) ───────────────────────────
 
 /// Entrée de file de soumission NVMe. `repr(C)` 64 octets, écrite telle quelle
@@ -87,6 +96,19 @@ impl Sqe {
         s
     }
 
+    /// Get Log Page (admin 0x02) → `bytes` octets (multiple de 4) en PRP1.
+    pub fn get_log_page(cid: u16, nsid: u32, lid: u8, bytes: usize, buf_phys: u64) -> Self {
+        let mut s = Self::zeroed();
+        s.set_cdw0(admin::GET_LOG_PAGE, cid);
+        s.set_nsid(nsid);
+        s.set_prp1(buf_phys);
+        // NUMD (0-based, en dwords) : NUMDL dans CDW10[31:16], NUMDU dans CDW11[15:0].
+        let numd = (bytes / 4).saturating_sub(1) as u32;
+        s.dword[10] = lid as u32 | ((numd & 0xFFFF) << 16);
+        s.dword[11] = numd >> 16;
+        s
+    }
+
     /// Create I/O Completion Queue (admin 0x05).
     /// `cq_phys` doit être contigu physiquement (PC=1). `ien`=interruptions.
     pub fn create_io_cq(cid: u16, qid: u16, qsize: u16, cq_phys: u64, ien: bool) -> Self {
@@ -209,6 +231,16 @@ mod tests {
         assert_eq!(s.dword[7], 0); // PRP1 high
     }
 
+    #[test]
+    fn get_log_page_numd_is_zero_based_dwords() {
+        let s = Sqe::get_log_page(9, NSID_ALL, log_page::SMART_HEALTH, 512, 0xBEEF_0000);
+        assert_eq!(s.opcode(), admin::GET_LOG_PAGE);
+        assert_eq!(s.dword[1], NSID_ALL);
+        assert_eq!(s.dword[10], 0x02 | (127 << 16), "512 octets → NUMDL=127");
+        assert_eq!(s.dword[11], 0);
+        assert_eq!(s.dword[6], 0xBEEF_0000);
+    }
+
     #[test]
     fn read_command_nlb_is_zero_based() {
         // Lire 8 blocs logiques à partir de SLBA=0x1_0000_0002.
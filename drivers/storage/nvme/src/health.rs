@@ -0,0 +1,258 @@
+//! Page SMART / Health Information (Get Log Page, LID 0x02) et suivi de l'état
+//! du disque.
+//!
+//! Le décodage est **pur** (octets → [`SmartLog`]) et l'évaluation aussi : le
+//! driver se contente de lire la page, un moniteur l'interroge périodiquement
+//! et passe chaque relevé à [`HealthMonitor::observe`], qui ne signale que
+//! les *changements* (nouvel avertissement, usure qui franchit un palier,
+//! erreurs média en hausse) pour ne pas répéter la même alerte à chaque tour.
+
+/// Taille de la page SMART / Health.
+pub const SMART_LOG_BYTES: usize = 512;
+
+/// Bits du champ Critical Warning (octet 0).
+pub mod critical {
+    pub const SPARE_BELOW_THRESHOLD: u8 = 1 << 0;
+    pub const TEMPERATURE: u8 = 1 << 1;
+    pub const DEGRADED_RELIABILITY: u8 = 1 << 2;
+    pub const READ_ONLY: u8 = 1 << 3;
+    pub const VOLATILE_BACKUP_FAILED: u8 = 1 << 4;
+    pub const PMR_READ_ONLY: u8 = 1 << 5;
+}
+
+/// Avertissements qui annoncent une perte de données imminente.
+const FAILING_WARNINGS: u8 = critical::SPARE_BELOW_THRESHOLD
+    | critical::DEGRADED_RELIABILITY
+    | critical::READ_ONLY
+    | critical::VOLATILE_BACKUP_FAILED
+    | critical::PMR_READ_ONLY;
+
+/// Usure (Percentage Used) à partir de laquelle le disque est signalé.
+pub const WEAR_WARNING_PERCENT: u8 = 90;
+/// Granularité des événements d'usure (un événement par palier franchi).
+pub const WEAR_STEP_PERCENT: u8 = 10;
+
+/// Vue décodée de la page SMART. Les compteurs 128 bits de la spec sont
+/// saturés à `u64` (2^64 unités n'arrivent pas sur un disque réel).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SmartLog {
+    pub critical_warning: u8,
+    /// Température composite en kelvins (0 : non rapportée).
+    pub temperature_k: u16,
+    /// Réserve de blocs disponible, en pourcentage.
+    pub available_spare: u8,
+    pub spare_threshold: u8,
+    /// Usure estimée ; peut dépasser 100.
+    pub percentage_used: u8,
+    /// En unités de 512 000 octets.
+    pub data_units_read: u64,
+    pub data_units_written: u64,
+    pub power_cycles: u64,
+    pub power_on_hours: u64,
+    pub unsafe_shutdowns: u64,
+    pub media_errors: u64,
+    pub error_log_entries: u64,
+}
+
+impl SmartLog {
+    /// Décode la page ; `None` si le buffer est plus court que la page.
+    pub fn parse(page: &[u8]) -> Option<Self> {
+        if page.len() < SMART_LOG_BYTES {
+            return None;
+        }
+        Some(Self {
+            critical_warning: page[0],
+            temperature_k: u16::from_le_bytes([page[1], page[2]]),
+            available_spare: page[3],
+            spare_threshold: page[4],
+            percentage_used: page[5],
+            data_units_read: counter(page, 32),
+            data_units_written: counter(page, 48),
+            power_cycles: counter(page, 112),
+            power_on_hours: counter(page, 128),
+            unsafe_shutdowns: counter(page, 144),
+            media_errors: counter(page, 160),
+            error_log_entries: counter(page, 176),
+        })
+    }
+
+    /// Température en degrés Celsius, `None` si non rapportée.
+    pub fn temperature_celsius(&self) -> Option<i16> {
+        (self.temperature_k != 0).then(|| self.temperature_k as i16 - 273)
+    }
+
+    /// Verdict global à partir d'un seul relevé.
+    pub fn assess(&self) -> Health {
+        if self.critical_warning & FAILING_WARNINGS != 0 {
+            Health::Failing
+        } else if self.critical_warning != 0
+            || self.percentage_used >= WEAR_WARNING_PERCENT
+            || self.media_errors > 0
+        {
+            Health::Warning
+        } else {
+            Health::Good
+        }
+    }
+}
+
+/// Compteur little-endian 128 bits à `off`, saturé à `u64`.
+fn counter(page: &[u8], off: usize) -> u64 {
+    let mut lo = [0u8; 8];
+    lo.copy_from_slice(&page[off..off + 8]);
+    if page[off + 8..off + 16].iter().any(|&b| b != 0) {
+        return u64::MAX;
+    }
+    u64::from_le_bytes(lo)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Health {
+    #[default]
+    Good,
+    /// Le disque fonctionne mais se dégrade : à surveiller, sauvegarder.
+    Warning,
+    /// Défaillance prédite : remplacer le disque.
+    Failing,
+}
+
+/// Changement notable entre deux relevés.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HealthEvent {
+    /// Le verdict global a changé (dans un sens ou dans l'autre).
+    Verdict { from: Health, to: Health },
+    /// Bits de Critical Warning apparus depuis le relevé précédent.
+    CriticalWarning(u8),
+    /// L'usure a franchi un palier de [`WEAR_STEP_PERCENT`].
+    Wear(u8),
+    /// Nouvelles erreurs média / intégrité (`total` depuis la fabrication).
+    MediaErrors { new: u64, total: u64 },
+}
+
+/// Suivi des relevés successifs d'un même contrôleur.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HealthMonitor {
+    last: Option<SmartLog>,
+}
+
+impl HealthMonitor {
+    pub const fn new() -> Self {
+        Self { last: None }
+    }
+
+    /// Dernier relevé observé.
+    pub fn last(&self) -> Option<&SmartLog> {
+        self.last.as_ref()
+    }
+
+    /// Intègre un relevé et signale ce qui a changé. Le premier relevé est
+    /// comparé à un disque neuf.
+    pub fn observe(&mut self, log: &SmartLog, mut emit: impl FnMut(HealthEvent)) -> Health {
+        let prev = self.last.unwrap_or_default();
+        let verdict = log.assess();
+        let prev_verdict = self.last.map_or(Health::Good, |l| l.assess());
+        if verdict != prev_verdict {
+            emit(HealthEvent::Verdict {
+                from: prev_verdict,
+                to: verdict,
+            });
+        }
+        let raised = log.critical_warning & !prev.critical_warning;
+        if raised != 0 {
+            emit(HealthEvent::CriticalWarning(raised));
+        }
+        if log.percentage_used / WEAR_STEP_PERCENT > prev.percentage_used / WEAR_STEP_PERCENT {
+            emit(HealthEvent::Wear(log.percentage_used));
+        }
+        if log.media_errors > prev.media_errors {
+            emit(HealthEvent::MediaErrors {
+                new: log.media_errors - prev.media_errors,
+                total: log.media_errors,
+            });
+        }
+        self.last = Some(*log);
+        verdict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn page(f: impl FnOnce(&mut [u8; SMART_LOG_BYTES])) -> SmartLog {
+        let mut p = [0u8; SMART_LOG_BYTES];
+        p[1..3].copy_from_slice(&310u16.to_le_bytes());
+        p[3] = 100;
+        p[4] = 10;
+        f(&mut p);
+        SmartLog::parse(&p).unwrap()
+    }
+
+    #[test]
+    fn parses_fields_at_spec_offsets() {
+        let log = page(|p| {
+            p[5] = 42;
+            p[32] = 0x10;
+            p[128..130].copy_from_slice(&1234u16.to_le_bytes());
+            p[160] = 3;
+            p[144 + 8] = 1; // compteur > 2^64 → saturé
+        });
+        assert_eq!(log.temperature_celsius(), Some(37));
+        assert_eq!(log.percentage_used, 42);
+        assert_eq!(log.data_units_read, 0x10);
+        assert_eq!(log.power_on_hours, 1234);
+        assert_eq!(log.media_errors, 3);
+        assert_eq!(log.unsafe_shutdowns, u64::MAX);
+        assert!(SmartLog::parse(&[0u8; 100]).is_none());
+    }
+
+    #[test]
+    fn assessment_ranks_warnings() {
+        assert_eq!(page(|_| {}).assess(), Health::Good);
+        assert_eq!(page(|p| p[5] = 95).assess(), Health::Warning);
+        assert_eq!(
+            page(|p| p[0] = critical::TEMPERATURE).assess(),
+            Health::Warning
+        );
+        assert_eq!(
+            page(|p| p[0] = critical::SPARE_BELOW_THRESHOLD).assess(),
+            Health::Failing
+        );
+    }
+
+    #[test]
+    fn monitor_reports_only_changes() {
+        let mut mon = HealthMonitor::new();
+        let mut events = Vec::new();
+        mon.observe(&page(|p| p[5] = 8), |e| events.push(e));
+        assert!(events.is_empty(), "disque sain : rien à signaler");
+
+        mon.observe(&page(|p| p[5] = 12), |e| events.push(e));
+        assert_eq!(events, [HealthEvent::Wear(12)]);
+        events.clear();
+        mon.observe(&page(|p| p[5] = 15), |e| events.push(e));
+        assert!(events.is_empty(), "même palier : pas de doublon");
+
+        let verdict = mon.observe(
+            &page(|p| {
+                p[5] = 15;
+                p[0] = critical::DEGRADED_RELIABILITY;
+                p[160] = 2;
+            }),
+            |e| events.push(e),
+        );
+        assert_eq!(verdict, Health::Failing);
+        assert_eq!(
+            events,
+            [
+                HealthEvent::Verdict {
+                    from: Health::Good,
+                    to: Health::Failing
+                },
+                HealthEvent::CriticalWarning(critical::DEGRADED_RELIABILITY),
+                HealthEvent::MediaErrors { new: 2, total: 2 },
+            ]
+        );
+    }
+}
@@ -19,10 +19,12 @@
 extern crate alloc;
 
 pub mod cmd;
+pub mod health;
 pub mod queue;
 pub mod regs;
 
 use cmd::{cns, Completion, Sqe};
+use health::{SmartLog, SMART_LOG_BYTES};
 use queue::{CqRing, SqRing};
 
 /// Taille de bloc ExoFS présentée en surface.
@@ -254,6 +256,25 @@ impl<H: NvmeHal> NvmeDevice<H> {
         .map_err(|status| NvmeError::IoCommandFailed(status))
     }
 
+    /// Lit la page SMART / Health globale au contrôleur (Get Log Page 0x02).
+    pub fn smart_log(&mut self) -> Result<SmartLog, NvmeError> {
+        let cid = self.alloc_cid();
+        let sqe = Sqe::get_log_page(
+            cid,
+            cmd::NSID_ALL,
+            cmd::log_page::SMART_HEALTH,
+            SMART_LOG_BYTES,
+            self.data.phys,
+        );
+        self.submit_admin(sqe, cid)?;
+        let mut page = [0u8; SMART_LOG_BYTES];
+        // SAFETY: `data` est une page DMA valide remplie par le contrôleur.
+        for (i, b) in page.iter_mut().enumerate() {
+            *b = unsafe { core::ptr::read_volatile(self.data.virt.add(i)) };
+        }
+        SmartLog::parse(&page).ok_or(NvmeError::InvalidBuffer)
+    }
+
     // ── Surface bloc ─────────────────────────────────────────────────────────
 
     pub fn block_size(&self) -> u32 {
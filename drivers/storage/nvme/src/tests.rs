@@ -143,6 +143,24 @@ impl MockInner {
                         }
                     }
                 }
+                admin::GET_LOG_PAGE => {
+                    let prp1 = (sqe.dword[6] as u64) | ((sqe.dword[7] as u64) << 32);
+                    let lid = (sqe.dword[10] & 0xFF) as u8;
+                    let bytes = (((sqe.dword[10] >> 16) as usize) + 1) * 4;
+                    // SAFETY: prp1 = buffer DMA ≥ `bytes` (≤ 4096).
+                    unsafe {
+                        let buf = prp1 as *mut u8;
+                        core::ptr::write_bytes(buf, 0, bytes);
+                        if lid == crate::cmd::log_page::SMART_HEALTH {
+                            // 40 °C, réserve 100 %/seuil 10 %, usure 3 %, 7 erreurs média.
+                            for (off, val) in
+                                [(1, 0x39), (2, 0x01), (3, 100), (4, 10), (5, 3), (160, 7)]
+                            {
+                                core::ptr::write_volatile(buf.add(off), val);
+                            }
+                        }
+                    }
+                }
                 admin::CREATE_IO_CQ => {
                     let prp1 = (sqe.dword[6] as u64) | ((sqe.dword[7] as u64) << 32);
                     let qsize = ((sqe.dword[10] >> 16) & 0xFFFF) as u16 + 1;
@@ -418,3 +436,17 @@ fn phase_tag_holds_across_many_commands() {
     dev.read_block(0, &mut rbuf).expect("read");
     assert!(rbuf.iter().all(|&b| b == 0x33));
 }
+
+#[test]
+fn smart_log_reads_controller_health_page() {
+    let mut dev = NvmeDevice::new(MockNvme::new()).expect("init");
+    let log = dev.smart_log().expect("smart log");
+    assert_eq!(log.temperature_celsius(), Some(40));
+    assert_eq!(log.available_spare, 100);
+    assert_eq!(log.percentage_used, 3);
+    assert_eq!(log.media_errors, 7);
+    assert_eq!(log.assess(), health::Health::Warning);
+    // Le buffer rebond reste utilisable pour les I/O bloc ensuite.
+    let buf = [0x11u8; EXOFS_BLOCK_SIZE];
+    dev.write_block(1, &buf).expect("write");
+}
@@ -4,8 +4,16 @@
 extern crate std;
 
 pub mod console;
+pub mod ligature;
 pub mod line_disc;
+pub mod links;
+pub mod profile;
 pub mod pty;
+pub mod selection;
 pub mod vt100;
 
+pub use ligature::{Cluster, Ligature, LigatureTable};
 pub use line_disc::{LineDiscipline, LineEvent, Signal};
+pub use links::{find_links, link_at, LinkKind, LinkSpan};
+pub use profile::{Profile, ProfileError, Rgb};
+pub use selection::{CellPos, Selection, SelectionBuffers, SelectionTarget};
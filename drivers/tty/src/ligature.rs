@@ -0,0 +1,114 @@
+/// Multi-cell glyph provided by a font (`->` drawn as one arrow over 2 cells).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Ligature {
+    pub sequence: &'static [u8],
+    pub glyph: u16,
+}
+
+/// Run of cells drawn with one glyph. `glyph == None` means "draw each cell
+/// with its own glyph", so a grid stays aligned whatever the font supports.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cluster {
+    pub start: usize,
+    pub cells: usize,
+    pub glyph: Option<u16>,
+}
+
+/// Ligatures exported by the font loader for the profile's font family.
+#[derive(Clone, Copy, Debug)]
+pub struct LigatureTable<'a> {
+    entries: &'a [Ligature],
+}
+
+impl<'a> LigatureTable<'a> {
+    pub const EMPTY: LigatureTable<'static> = LigatureTable { entries: &[] };
+
+    pub const fn new(entries: &'a [Ligature]) -> Self {
+        Self { entries }
+    }
+
+    /// Longest ligature starting at `text[0]`, if any.
+    pub fn longest_match(&self, text: &[u8]) -> Option<Ligature> {
+        self.entries
+            .iter()
+            .filter(|l| l.sequence.len() > 1 && text.starts_with(l.sequence))
+            .max_by_key(|l| l.sequence.len())
+            .copied()
+    }
+
+    /// Splits `text` into clusters. With `enabled == false` (profile setting)
+    /// every cell is its own cluster. Returns the number of clusters written.
+    pub fn shape(&self, text: &[u8], enabled: bool, out: &mut [Cluster]) -> usize {
+        let mut pos = 0usize;
+        let mut n = 0usize;
+        while pos < text.len() && n < out.len() {
+            let lig = if enabled {
+                self.longest_match(&text[pos..])
+            } else {
+                None
+            };
+            let cells = lig.map_or(1, |l| l.sequence.len());
+            out[n] = Cluster {
+                start: pos,
+                cells,
+                glyph: lig.map(|l| l.glyph),
+            };
+            pos += cells;
+            n += 1;
+        }
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &[Ligature] = &[
+        Ligature {
+            sequence: b"->",
+            glyph: 900,
+        },
+        Ligature {
+            sequence: b"==",
+            glyph: 901,
+        },
+        Ligature {
+            sequence: b"===",
+            glyph: 902,
+        },
+    ];
+
+    const EMPTY_CLUSTER: Cluster = Cluster {
+        start: 0,
+        cells: 0,
+        glyph: None,
+    };
+
+    #[test]
+    fn longest_sequence_wins() {
+        let table = LigatureTable::new(TABLE);
+        let mut out = [EMPTY_CLUSTER; 8];
+        let n = table.shape(b"a===b->", true, &mut out);
+        assert_eq!(n, 4);
+        assert_eq!(
+            out[1],
+            Cluster {
+                start: 1,
+                cells: 3,
+                glyph: Some(902)
+            }
+        );
+        assert_eq!(out[3].glyph, Some(900));
+        assert_eq!(out[3].cells, 2);
+    }
+
+    #[test]
+    fn disabled_ligatures_keep_one_cell_per_cluster() {
+        let table = LigatureTable::new(TABLE);
+        let mut out = [EMPTY_CLUSTER; 8];
+        assert_eq!(table.shape(b"->", false, &mut out), 2);
+        assert_eq!(out[0].glyph, None);
+        assert_eq!(LigatureTable::EMPTY.shape(b"==", true, &mut out), 2);
+    }
+}
@@ -0,0 +1,164 @@
+const URL_SCHEMES: &[&[u8]] = &[b"https://", b"http://", b"ftp://", b"file://", b"mailto:"];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LinkKind {
+    Url,
+    Path,
+}
+
+/// Clickable span `[start, end)` in a rendered line. The terminal hands the
+/// target to the association database; this module only finds it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LinkSpan {
+    pub start: usize,
+    pub end: usize,
+    pub kind: LinkKind,
+}
+
+impl LinkSpan {
+    pub fn text<'a>(&self, line: &'a [u8]) -> &'a [u8] {
+        &line[self.start..self.end]
+    }
+}
+
+fn is_delimiter(b: u8) -> bool {
+    b.is_ascii_whitespace() || b < 0x20 || matches!(b, b'"' | b'\'' | b'<' | b'>' | b'`')
+}
+
+fn starts_token(line: &[u8], pos: usize) -> bool {
+    pos == 0 || is_delimiter(line[pos - 1]) || matches!(line[pos - 1], b'(' | b'[' | b'=')
+}
+
+fn token_end(line: &[u8], start: usize) -> usize {
+    let mut end = start;
+    while end < line.len() && !is_delimiter(line[end]) {
+        end += 1;
+    }
+    end
+}
+
+/// Drops sentence punctuation and unbalanced closing brackets.
+fn trim_trailing(line: &[u8], start: usize, mut end: usize) -> usize {
+    while end > start {
+        let last = line[end - 1];
+        let strip = match last {
+            b'.' | b',' | b';' | b':' | b'!' | b'?' => true,
+            b')' => count(&line[start..end], b'(') < count(&line[start..end], b')'),
+            b']' => count(&line[start..end], b'[') < count(&line[start..end], b']'),
+            _ => false,
+        };
+        if !strip {
+            break;
+        }
+        end -= 1;
+    }
+    end
+}
+
+fn count(bytes: &[u8], needle: u8) -> usize {
+    bytes.iter().filter(|&&b| b == needle).count()
+}
+
+fn match_at(line: &[u8], pos: usize) -> Option<LinkSpan> {
+    if !starts_token(line, pos) {
+        return None;
+    }
+    let rest = &line[pos..];
+    let (kind, min_len) = if let Some(scheme) = URL_SCHEMES.iter().find(|s| rest.starts_with(s)) {
+        (LinkKind::Url, scheme.len() + 1)
+    } else if rest.starts_with(b"~/") || rest.starts_with(b"./") || rest.starts_with(b"../") {
+        (LinkKind::Path, 3)
+    } else if rest.first() == Some(&b'/') {
+        (LinkKind::Path, 2)
+    } else {
+        return None;
+    };
+    let end = trim_trailing(line, pos, token_end(line, pos));
+    (end - pos >= min_len).then_some(LinkSpan {
+        start: pos,
+        end,
+        kind,
+    })
+}
+
+/// Fills `out` with the links of `line`, left to right. Returns the count.
+pub fn find_links(line: &[u8], out: &mut [LinkSpan]) -> usize {
+    let mut found = 0usize;
+    let mut pos = 0usize;
+    while pos < line.len() && found < out.len() {
+        match match_at(line, pos) {
+            Some(span) => {
+                out[found] = span;
+                found += 1;
+                pos = span.end.max(pos + 1);
+            }
+            None => pos += 1,
+        }
+    }
+    found
+}
+
+/// Link under column `col` (mouse hover / click).
+pub fn link_at(line: &[u8], col: usize) -> Option<LinkSpan> {
+    let mut pos = 0usize;
+    while pos <= col && pos < line.len() {
+        match match_at(line, pos) {
+            Some(span) if col < span.end => return Some(span),
+            Some(span) => pos = span.end.max(pos + 1),
+            None => pos += 1,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts<'a>(line: &'a [u8], spans: &[LinkSpan]) -> std::vec::Vec<&'a [u8]> {
+        spans.iter().map(|s| s.text(line)).collect()
+    }
+
+    #[test]
+    fn finds_urls_and_paths() {
+        let line = b"see https://exo-os.org/docs, or /etc/exo/profile.conf and ~/notes.";
+        let mut spans = [LinkSpan {
+            start: 0,
+            end: 0,
+            kind: LinkKind::Url,
+        }; 4];
+        let n = find_links(line, &mut spans);
+        assert_eq!(
+            texts(line, &spans[..n]),
+            [
+                &b"https://exo-os.org/docs"[..],
+                b"/etc/exo/profile.conf",
+                b"~/notes"
+            ]
+        );
+        assert_eq!(spans[0].kind, LinkKind::Url);
+        assert_eq!(spans[1].kind, LinkKind::Path);
+    }
+
+    #[test]
+    fn keeps_balanced_parentheses() {
+        let line = b"(https://en.wikipedia.org/wiki/Rust_(language))";
+        let span = link_at(line, 5).unwrap();
+        assert_eq!(
+            span.text(line),
+            b"https://en.wikipedia.org/wiki/Rust_(language)"
+        );
+    }
+
+    #[test]
+    fn ignores_mid_word_slashes_and_bare_schemes() {
+        let mut spans = [LinkSpan {
+            start: 0,
+            end: 0,
+            kind: LinkKind::Url,
+        }; 4];
+        assert_eq!(find_links(b"and/or 3/4 http:// /", &mut spans), 0);
+        assert_eq!(link_at(b"x https://a.b y", 0), None);
+        assert_eq!(link_at(b"x https://a.b y", 14), None);
+    }
+}
@@ -0,0 +1,347 @@
+pub const PROFILE_NAME_MAX: usize = 32;
+pub const FONT_FAMILY_MAX: usize = 48;
+pub const PALETTE_LEN: usize = 16;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    pub fn parse_hex(text: &[u8]) -> Option<Self> {
+        let digits = text.strip_prefix(b"#")?;
+        if digits.len() != 6 {
+            return None;
+        }
+        let mut v = [0u8; 3];
+        for (i, pair) in digits.chunks(2).enumerate() {
+            v[i] = (hex_nibble(pair[0])? << 4) | hex_nibble(pair[1])?;
+        }
+        Some(Self::new(v[0], v[1], v[2]))
+    }
+
+    fn write_hex(self, out: &mut [u8; 7]) {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+        out[0] = b'#';
+        for (i, c) in [self.r, self.g, self.b].into_iter().enumerate() {
+            out[1 + i * 2] = HEX[(c >> 4) as usize];
+            out[2 + i * 2] = HEX[(c & 0xf) as usize];
+        }
+    }
+}
+
+fn hex_nibble(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+pub const DEFAULT_PALETTE: [Rgb; PALETTE_LEN] = [
+    Rgb::new(0x00, 0x00, 0x00),
+    Rgb::new(0xcd, 0x00, 0x00),
+    Rgb::new(0x00, 0xcd, 0x00),
+    Rgb::new(0xcd, 0xcd, 0x00),
+    Rgb::new(0x00, 0x00, 0xee),
+    Rgb::new(0xcd, 0x00, 0xcd),
+    Rgb::new(0x00, 0xcd, 0xcd),
+    Rgb::new(0xe5, 0xe5, 0xe5),
+    Rgb::new(0x7f, 0x7f, 0x7f),
+    Rgb::new(0xff, 0x00, 0x00),
+    Rgb::new(0x00, 0xff, 0x00),
+    Rgb::new(0xff, 0xff, 0x00),
+    Rgb::new(0x5c, 0x5c, 0xff),
+    Rgb::new(0xff, 0x00, 0xff),
+    Rgb::new(0x00, 0xff, 0xff),
+    Rgb::new(0xff, 0xff, 0xff),
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProfileError {
+    UnknownKey,
+    InvalidValue,
+    TooLong,
+    MissingSeparator,
+}
+
+/// Terminal profile persisted as `key=value` lines in the config service.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Profile {
+    name: [u8; PROFILE_NAME_MAX],
+    name_len: usize,
+    font_family: [u8; FONT_FAMILY_MAX],
+    font_family_len: usize,
+    pub font_size_px: u16,
+    pub ligatures: bool,
+    pub padding_px: u16,
+    pub foreground: Rgb,
+    pub background: Rgb,
+    pub cursor: Rgb,
+    pub palette: [Rgb; PALETTE_LEN],
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profile {
+    pub const fn new() -> Self {
+        let mut name = [0u8; PROFILE_NAME_MAX];
+        name[0] = b'd';
+        name[1] = b'e';
+        name[2] = b'f';
+        name[3] = b'a';
+        name[4] = b'u';
+        name[5] = b'l';
+        name[6] = b't';
+        Self {
+            name,
+            name_len: 7,
+            font_family: [0; FONT_FAMILY_MAX],
+            font_family_len: 0,
+            font_size_px: 16,
+            ligatures: false,
+            padding_px: 4,
+            foreground: DEFAULT_PALETTE[7],
+            background: DEFAULT_PALETTE[0],
+            cursor: DEFAULT_PALETTE[15],
+            palette: DEFAULT_PALETTE,
+        }
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name[..self.name_len]
+    }
+
+    /// Empty means "use the built-in console font".
+    pub fn font_family(&self) -> &[u8] {
+        &self.font_family[..self.font_family_len]
+    }
+
+    pub fn set_name(&mut self, name: &[u8]) -> Result<(), ProfileError> {
+        if name.is_empty() || name.contains(&b'\n') {
+            return Err(ProfileError::InvalidValue);
+        }
+        self.name_len = copy_bounded(&mut self.name, name)?;
+        Ok(())
+    }
+
+    pub fn set_font_family(&mut self, family: &[u8]) -> Result<(), ProfileError> {
+        if family.contains(&b'\n') {
+            return Err(ProfileError::InvalidValue);
+        }
+        self.font_family_len = copy_bounded(&mut self.font_family, family)?;
+        Ok(())
+    }
+
+    pub fn apply(&mut self, key: &[u8], value: &[u8]) -> Result<(), ProfileError> {
+        match key {
+            b"name" => self.set_name(value),
+            b"font.family" => self.set_font_family(value),
+            b"font.size" => {
+                self.font_size_px = parse_u16(value)
+                    .filter(|&v| (6..=96).contains(&v))
+                    .ok_or(ProfileError::InvalidValue)?;
+                Ok(())
+            }
+            b"font.ligatures" => {
+                self.ligatures = parse_bool(value).ok_or(ProfileError::InvalidValue)?;
+                Ok(())
+            }
+            b"padding" => {
+                self.padding_px = parse_u16(value)
+                    .filter(|&v| v <= 64)
+                    .ok_or(ProfileError::InvalidValue)?;
+                Ok(())
+            }
+            b"color.foreground" => set_color(&mut self.foreground, value),
+            b"color.background" => set_color(&mut self.background, value),
+            b"color.cursor" => set_color(&mut self.cursor, value),
+            _ => match key.strip_prefix(b"color.").and_then(parse_u16) {
+                Some(idx) if (idx as usize) < PALETTE_LEN => {
+                    set_color(&mut self.palette[idx as usize], value)
+                }
+                _ => Err(ProfileError::UnknownKey),
+            },
+        }
+    }
+
+    /// Unknown keys are skipped so newer profiles still load on older builds.
+    pub fn parse(text: &[u8]) -> Result<Self, ProfileError> {
+        let mut profile = Self::new();
+        for line in text.split(|&b| b == b'\n') {
+            let line = line.trim_ascii();
+            if line.is_empty() || line[0] == b'#' {
+                continue;
+            }
+            let eq = line
+                .iter()
+                .position(|&b| b == b'=')
+                .ok_or(ProfileError::MissingSeparator)?;
+            match profile.apply(line[..eq].trim_ascii(), line[eq + 1..].trim_ascii()) {
+                Ok(()) | Err(ProfileError::UnknownKey) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(profile)
+    }
+
+    pub fn serialize(&self, out: &mut [u8]) -> Option<usize> {
+        let mut w = Writer { out, len: 0 };
+        w.line(b"name", self.name())?;
+        w.line(b"font.family", self.font_family())?;
+        let mut num = [0u8; 5];
+        w.line(b"font.size", fmt_u16(self.font_size_px, &mut num))?;
+        w.line(
+            b"font.ligatures",
+            if self.ligatures { b"true" } else { b"false" },
+        )?;
+        w.line(b"padding", fmt_u16(self.padding_px, &mut num))?;
+        let mut hex = [0u8; 7];
+        for (key, color) in [
+            (&b"color.foreground"[..], self.foreground),
+            (&b"color.background"[..], self.background),
+            (&b"color.cursor"[..], self.cursor),
+        ] {
+            color.write_hex(&mut hex);
+            w.line(key, &hex)?;
+        }
+        for (i, color) in self.palette.iter().enumerate() {
+            let mut key = [0u8; 8];
+            key[..6].copy_from_slice(b"color.");
+            let digits = fmt_u16(i as u16, &mut num);
+            let key_len = 6 + digits.len();
+            key[6..key_len].copy_from_slice(digits);
+            color.write_hex(&mut hex);
+            w.line(&key[..key_len], &hex)?;
+        }
+        Some(w.len)
+    }
+}
+
+struct Writer<'a> {
+    out: &'a mut [u8],
+    len: usize,
+}
+
+impl Writer<'_> {
+    fn put(&mut self, bytes: &[u8]) -> Option<()> {
+        let end = self.len.checked_add(bytes.len())?;
+        self.out.get_mut(self.len..end)?.copy_from_slice(bytes);
+        self.len = end;
+        Some(())
+    }
+
+    fn line(&mut self, key: &[u8], value: &[u8]) -> Option<()> {
+        self.put(key)?;
+        self.put(b"=")?;
+        self.put(value)?;
+        self.put(b"\n")
+    }
+}
+
+fn copy_bounded(dst: &mut [u8], src: &[u8]) -> Result<usize, ProfileError> {
+    if src.len() > dst.len() {
+        return Err(ProfileError::TooLong);
+    }
+    dst[..src.len()].copy_from_slice(src);
+    Ok(src.len())
+}
+
+fn set_color(slot: &mut Rgb, value: &[u8]) -> Result<(), ProfileError> {
+    *slot = Rgb::parse_hex(value).ok_or(ProfileError::InvalidValue)?;
+    Ok(())
+}
+
+fn parse_bool(value: &[u8]) -> Option<bool> {
+    match value {
+        b"true" | b"1" | b"yes" => Some(true),
+        b"false" | b"0" | b"no" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_u16(value: &[u8]) -> Option<u16> {
+    if value.is_empty() {
+        return None;
+    }
+    let mut v: u16 = 0;
+    for &b in value {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        v = v.checked_mul(10)?.checked_add((b - b'0') as u16)?;
+    }
+    Some(v)
+}
+
+fn fmt_u16(mut value: u16, out: &mut [u8; 5]) -> &[u8] {
+    let mut pos = out.len();
+    loop {
+        pos -= 1;
+        out[pos] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    &out[pos..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_keys_and_skips_unknown_ones() {
+        let text = b"# solarized\nname = Solarized\nfont.family=Fira Code\nfont.size=14\n\
+font.ligatures=true\npadding=8\ncolor.background=#002b36\ncolor.4=#268bd2\nwindow.blur=1\n";
+        let p = Profile::parse(text).unwrap();
+        assert_eq!(p.name(), b"Solarized");
+        assert_eq!(p.font_family(), b"Fira Code");
+        assert_eq!(p.font_size_px, 14);
+        assert!(p.ligatures);
+        assert_eq!(p.padding_px, 8);
+        assert_eq!(p.background, Rgb::new(0x00, 0x2b, 0x36));
+        assert_eq!(p.palette[4], Rgb::new(0x26, 0x8b, 0xd2));
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert_eq!(
+            Profile::parse(b"font.size=200"),
+            Err(ProfileError::InvalidValue)
+        );
+        assert_eq!(
+            Profile::parse(b"color.cursor=red"),
+            Err(ProfileError::InvalidValue)
+        );
+        assert_eq!(
+            Profile::parse(b"padding"),
+            Err(ProfileError::MissingSeparator)
+        );
+    }
+
+    #[test]
+    fn serialize_round_trips() {
+        let mut p = Profile::new();
+        p.set_name(b"work").unwrap();
+        p.set_font_family(b"JetBrains Mono").unwrap();
+        p.ligatures = true;
+        p.palette[12] = Rgb::new(1, 2, 3);
+        let mut out = [0u8; 1024];
+        let n = p.serialize(&mut out).unwrap();
+        assert_eq!(Profile::parse(&out[..n]), Ok(p));
+        assert_eq!(p.serialize(&mut [0u8; 16]), None);
+    }
+}
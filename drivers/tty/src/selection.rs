@@ -0,0 +1,184 @@
+pub const SELECTION_BUF_SIZE: usize = 4096;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SelectionTarget {
+    /// Explicit copy (Ctrl+Shift+C), pasted with Ctrl+Shift+V.
+    Clipboard,
+    /// Updated on every finished selection, pasted with middle click.
+    Primary,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct CellPos {
+    pub row: u16,
+    pub col: u16,
+}
+
+impl CellPos {
+    pub const fn new(row: u16, col: u16) -> Self {
+        Self { row, col }
+    }
+}
+
+/// Linear (stream) selection between an anchor and the dragged head, inclusive.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Selection {
+    anchor: CellPos,
+    head: CellPos,
+}
+
+impl Selection {
+    pub const fn new(anchor: CellPos) -> Self {
+        Self {
+            anchor,
+            head: anchor,
+        }
+    }
+
+    pub fn extend(&mut self, head: CellPos) {
+        self.head = head;
+    }
+
+    pub fn bounds(&self) -> (CellPos, CellPos) {
+        if self.anchor <= self.head {
+            (self.anchor, self.head)
+        } else {
+            (self.head, self.anchor)
+        }
+    }
+
+    pub fn contains(&self, pos: CellPos) -> bool {
+        let (start, end) = self.bounds();
+        start <= pos && pos <= end
+    }
+
+    /// Copies the selected text of `rows` into `out`: trailing blanks of each
+    /// row are dropped and rows are joined with `\n`. Returns bytes written.
+    pub fn extract(&self, rows: &[&[u8]], out: &mut [u8]) -> usize {
+        let (start, end) = self.bounds();
+        let mut len = 0usize;
+        let last_row = (end.row as usize).min(rows.len().saturating_sub(1));
+        for r in start.row as usize..=last_row {
+            let Some(row) = rows.get(r) else { break };
+            let from = if r == start.row as usize {
+                (start.col as usize).min(row.len())
+            } else {
+                0
+            };
+            let to = if r == end.row as usize {
+                (end.col as usize + 1).min(row.len())
+            } else {
+                row.len()
+            };
+            let mut piece = &row[from..to.max(from)];
+            if r != end.row as usize || to == row.len() {
+                piece = piece.trim_ascii_end();
+            }
+            len += copy_into(&mut out[len..], piece);
+            if r != last_row {
+                len += copy_into(&mut out[len..], b"\n");
+            }
+        }
+        len
+    }
+}
+
+fn copy_into(out: &mut [u8], src: &[u8]) -> usize {
+    let n = src.len().min(out.len());
+    out[..n].copy_from_slice(&src[..n]);
+    n
+}
+
+/// Local copies of both selections; the terminal mirrors them to the
+/// clipboard service when it is reachable.
+pub struct SelectionBuffers {
+    clipboard: [u8; SELECTION_BUF_SIZE],
+    clipboard_len: usize,
+    primary: [u8; SELECTION_BUF_SIZE],
+    primary_len: usize,
+}
+
+impl Default for SelectionBuffers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SelectionBuffers {
+    pub const fn new() -> Self {
+        Self {
+            clipboard: [0; SELECTION_BUF_SIZE],
+            clipboard_len: 0,
+            primary: [0; SELECTION_BUF_SIZE],
+            primary_len: 0,
+        }
+    }
+
+    pub fn store(&mut self, target: SelectionTarget, data: &[u8]) -> usize {
+        let (buf, len) = match target {
+            SelectionTarget::Clipboard => (&mut self.clipboard, &mut self.clipboard_len),
+            SelectionTarget::Primary => (&mut self.primary, &mut self.primary_len),
+        };
+        *len = copy_into(buf, data);
+        *len
+    }
+
+    pub fn copy_selection(
+        &mut self,
+        target: SelectionTarget,
+        selection: &Selection,
+        rows: &[&[u8]],
+    ) -> usize {
+        let (buf, len) = match target {
+            SelectionTarget::Clipboard => (&mut self.clipboard, &mut self.clipboard_len),
+            SelectionTarget::Primary => (&mut self.primary, &mut self.primary_len),
+        };
+        *len = selection.extract(rows, buf);
+        *len
+    }
+
+    pub fn get(&self, target: SelectionTarget) -> &[u8] {
+        match target {
+            SelectionTarget::Clipboard => &self.clipboard[..self.clipboard_len],
+            SelectionTarget::Primary => &self.primary[..self.primary_len],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROWS: [&[u8]; 3] = [b"$ ls -l   ", b"total 0   ", b"drwx  bin "];
+
+    #[test]
+    fn extract_spans_rows_and_trims_padding() {
+        let mut sel = Selection::new(CellPos::new(0, 2));
+        sel.extend(CellPos::new(2, 3));
+        let mut out = [0u8; 64];
+        let n = sel.extract(&ROWS, &mut out);
+        assert_eq!(&out[..n], b"ls -l\ntotal 0\ndrwx");
+    }
+
+    #[test]
+    fn reversed_drag_is_normalized() {
+        let mut sel = Selection::new(CellPos::new(1, 4));
+        sel.extend(CellPos::new(1, 0));
+        assert!(sel.contains(CellPos::new(1, 2)));
+        assert!(!sel.contains(CellPos::new(0, 9)));
+        let mut out = [0u8; 16];
+        let n = sel.extract(&ROWS, &mut out);
+        assert_eq!(&out[..n], b"total");
+    }
+
+    #[test]
+    fn clipboard_and_primary_are_independent() {
+        let mut buffers = SelectionBuffers::new();
+        let mut sel = Selection::new(CellPos::new(0, 2));
+        sel.extend(CellPos::new(0, 3));
+        buffers.copy_selection(SelectionTarget::Primary, &sel, &ROWS);
+        buffers.store(SelectionTarget::Clipboard, b"echo hi");
+        assert_eq!(buffers.get(SelectionTarget::Primary), b"ls");
+        assert_eq!(buffers.get(SelectionTarget::Clipboard), b"echo hi");
+    }
+}
@@ -0,0 +1,330 @@
+//! File de capture partagée et contrôle d'accès.
+//!
+//! Le client fournit des buffers en mémoire partagée (`queue`), le pilote les
+//! remplit dans l'ordre et les rend via `dequeue`, comme `VIDIOC_QBUF/DQBUF`.
+//! L'ouverture est refusée tant que le portail n'a pas accordé la caméra au
+//! PID demandeur ; une révocation coupe le flux au prochain `enforce`.
+
+pub const MAX_CAPTURE_BUFFERS: usize = 4;
+pub const MAX_PORTAL_GRANTS: usize = 8;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CaptureError {
+    /// Pas d'accord du portail pour ce PID.
+    Denied,
+    /// Caméra déjà ouverte par un autre client.
+    Busy,
+    NotOwner,
+    BadBuffer,
+    WrongState,
+    Full,
+}
+
+/// Accords donnés par l'utilisateur via le portail (dialogue de permission).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CapturePortal {
+    grants: [u32; MAX_PORTAL_GRANTS],
+}
+
+impl CapturePortal {
+    pub const fn new() -> Self {
+        Self {
+            grants: [0; MAX_PORTAL_GRANTS],
+        }
+    }
+
+    pub fn grant(&mut self, pid: u32) -> Result<(), CaptureError> {
+        if pid == 0 || self.allowed(pid) {
+            return Ok(());
+        }
+        let slot = self
+            .grants
+            .iter_mut()
+            .find(|g| **g == 0)
+            .ok_or(CaptureError::Full)?;
+        *slot = pid;
+        Ok(())
+    }
+
+    pub fn revoke(&mut self, pid: u32) {
+        for g in self.grants.iter_mut().filter(|g| **g == pid) {
+            *g = 0;
+        }
+    }
+
+    pub fn allowed(&self, pid: u32) -> bool {
+        pid != 0 && self.grants.contains(&pid)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BufferState {
+    Unused,
+    /// Rendu au pilote, en attente de remplissage.
+    Queued,
+    Filling,
+    Done {
+        sequence: u32,
+        len: usize,
+        pts: Option<u32>,
+    },
+    /// Entre les mains du client.
+    Dequeued,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct CaptureBuffer {
+    shm_handle: u64,
+    size: usize,
+    state: BufferState,
+    /// Ordre de mise en file, pour remplir en FIFO.
+    ticket: u32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CapturedFrame {
+    pub index: usize,
+    pub shm_handle: u64,
+    pub sequence: u32,
+    pub len: usize,
+    pub pts: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CaptureDevice {
+    owner: u32,
+    streaming: bool,
+    buffers: [CaptureBuffer; MAX_CAPTURE_BUFFERS],
+    next_ticket: u32,
+    sequence: u32,
+    dropped: u32,
+}
+
+impl Default for CaptureDevice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CaptureDevice {
+    pub const fn new() -> Self {
+        Self {
+            owner: 0,
+            streaming: false,
+            buffers: [CaptureBuffer {
+                shm_handle: 0,
+                size: 0,
+                state: BufferState::Unused,
+                ticket: 0,
+            }; MAX_CAPTURE_BUFFERS],
+            next_ticket: 0,
+            sequence: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn open(&mut self, pid: u32, portal: &CapturePortal) -> Result<(), CaptureError> {
+        if !portal.allowed(pid) {
+            return Err(CaptureError::Denied);
+        }
+        if self.owner != 0 && self.owner != pid {
+            return Err(CaptureError::Busy);
+        }
+        self.owner = pid;
+        Ok(())
+    }
+
+    pub fn close(&mut self, pid: u32) -> Result<(), CaptureError> {
+        self.check_owner(pid)?;
+        *self = Self::new();
+        Ok(())
+    }
+
+    /// À appeler après chaque changement d'accord : ferme la caméra si son
+    /// propriétaire n'y a plus droit.
+    pub fn enforce(&mut self, portal: &CapturePortal) {
+        if self.owner != 0 && !portal.allowed(self.owner) {
+            *self = Self::new();
+        }
+    }
+
+    /// PID qui utilise la caméra, pour l'indicateur de confidentialité.
+    pub fn active_owner(&self) -> Option<u32> {
+        (self.owner != 0 && self.streaming).then_some(self.owner)
+    }
+
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    pub fn register_buffer(
+        &mut self,
+        pid: u32,
+        index: usize,
+        shm_handle: u64,
+        size: usize,
+    ) -> Result<(), CaptureError> {
+        self.check_owner(pid)?;
+        let buf = self.buffers.get_mut(index).ok_or(CaptureError::BadBuffer)?;
+        if !matches!(buf.state, BufferState::Unused | BufferState::Dequeued) || size == 0 {
+            return Err(CaptureError::WrongState);
+        }
+        buf.shm_handle = shm_handle;
+        buf.size = size;
+        buf.state = BufferState::Dequeued;
+        Ok(())
+    }
+
+    pub fn queue(&mut self, pid: u32, index: usize) -> Result<(), CaptureError> {
+        self.check_owner(pid)?;
+        let ticket = self.next_ticket;
+        let buf = self.buffers.get_mut(index).ok_or(CaptureError::BadBuffer)?;
+        if buf.state != BufferState::Dequeued {
+            return Err(CaptureError::WrongState);
+        }
+        buf.state = BufferState::Queued;
+        buf.ticket = ticket;
+        self.next_ticket = ticket.wrapping_add(1);
+        Ok(())
+    }
+
+    pub fn stream_on(&mut self, pid: u32) -> Result<(), CaptureError> {
+        self.check_owner(pid)?;
+        self.streaming = true;
+        Ok(())
+    }
+
+    /// Arrête le flux ; tous les buffers reviennent au client.
+    pub fn stream_off(&mut self, pid: u32) -> Result<(), CaptureError> {
+        self.check_owner(pid)?;
+        self.streaming = false;
+        for buf in self.buffers.iter_mut() {
+            if buf.state != BufferState::Unused {
+                buf.state = BufferState::Dequeued;
+            }
+        }
+        Ok(())
+    }
+
+    /// Côté pilote : buffer à remplir pour la prochaine trame, s'il y en a un.
+    pub fn begin_frame(&mut self) -> Option<(usize, u64, usize)> {
+        if !self.streaming {
+            return None;
+        }
+        if let Some(i) = self
+            .buffers
+            .iter()
+            .position(|b| b.state == BufferState::Filling)
+        {
+            let b = &self.buffers[i];
+            return Some((i, b.shm_handle, b.size));
+        }
+        let next_ticket = self.next_ticket;
+        let i = self
+            .buffers
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.state == BufferState::Queued)
+            .min_by_key(|(_, b)| b.ticket.wrapping_sub(next_ticket))
+            .map(|(i, _)| i);
+        let Some(i) = i else {
+            // Le client ne rend pas ses buffers assez vite.
+            self.dropped = self.dropped.wrapping_add(1);
+            return None;
+        };
+        let b = &mut self.buffers[i];
+        b.state = BufferState::Filling;
+        Some((i, b.shm_handle, b.size))
+    }
+
+    /// Côté pilote : la trame en cours est complète (`len` octets écrits).
+    pub fn complete_frame(&mut self, len: usize, pts: Option<u32>) -> Result<(), CaptureError> {
+        let buf = self
+            .buffers
+            .iter_mut()
+            .find(|b| b.state == BufferState::Filling)
+            .ok_or(CaptureError::WrongState)?;
+        if len > buf.size {
+            return Err(CaptureError::BadBuffer);
+        }
+        buf.state = BufferState::Done {
+            sequence: self.sequence,
+            len,
+            pts,
+        };
+        self.sequence = self.sequence.wrapping_add(1);
+        Ok(())
+    }
+
+    pub fn dequeue(&mut self, pid: u32) -> Result<Option<CapturedFrame>, CaptureError> {
+        self.check_owner(pid)?;
+        let oldest = self
+            .buffers
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| match b.state {
+                BufferState::Done { sequence, len, pts } => Some((i, sequence, len, pts)),
+                _ => None,
+            })
+            .min_by_key(|&(_, seq, _, _)| seq.wrapping_sub(self.sequence));
+        let Some((index, sequence, len, pts)) = oldest else {
+            return Ok(None);
+        };
+        self.buffers[index].state = BufferState::Dequeued;
+        Ok(Some(CapturedFrame {
+            index,
+            shm_handle: self.buffers[index].shm_handle,
+            sequence,
+            len,
+            pts,
+        }))
+    }
+
+    fn check_owner(&self, pid: u32) -> Result<(), CaptureError> {
+        if self.owner == 0 || self.owner != pid {
+            return Err(CaptureError::NotOwner);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn portal_gates_access_and_revocation_stops_capture() {
+        let mut portal = CapturePortal::new();
+        let mut cam = CaptureDevice::new();
+        assert_eq!(cam.open(42, &portal), Err(CaptureError::Denied));
+        portal.grant(42).unwrap();
+        portal.grant(7).unwrap();
+        cam.open(42, &portal).unwrap();
+        assert_eq!(cam.open(7, &portal), Err(CaptureError::Busy));
+
+        cam.register_buffer(42, 0, 0xa0, 64).unwrap();
+        cam.register_buffer(42, 1, 0xa1, 64).unwrap();
+        cam.queue(42, 1).unwrap();
+        cam.queue(42, 0).unwrap();
+        cam.stream_on(42).unwrap();
+        assert_eq!(cam.active_owner(), Some(42));
+
+        // Remplissage dans l'ordre de mise en file.
+        assert_eq!(cam.begin_frame(), Some((1, 0xa1, 64)));
+        cam.complete_frame(10, Some(5)).unwrap();
+        assert_eq!(cam.begin_frame(), Some((0, 0xa0, 64)));
+        cam.complete_frame(20, None).unwrap();
+        assert_eq!(cam.begin_frame(), None);
+        assert_eq!(cam.dropped(), 1);
+
+        let first = cam.dequeue(42).unwrap().unwrap();
+        assert_eq!((first.index, first.sequence, first.len), (1, 0, 10));
+        assert_eq!(cam.dequeue(7), Err(CaptureError::NotOwner));
+
+        portal.revoke(42);
+        cam.enforce(&portal);
+        assert_eq!(cam.active_owner(), None);
+        assert_eq!(cam.dequeue(42), Err(CaptureError::NotOwner));
+        cam.open(7, &portal).unwrap();
+    }
+}
@@ -0,0 +1,254 @@
+//! Descripteurs VideoStreaming (UVC 1.1 §3.9) et contrôle PROBE/COMMIT (§4.3.1.1).
+
+pub const CS_INTERFACE: u8 = 0x24;
+pub const VS_FORMAT_UNCOMPRESSED: u8 = 0x04;
+pub const VS_FRAME_UNCOMPRESSED: u8 = 0x05;
+pub const VS_FORMAT_MJPEG: u8 = 0x06;
+pub const VS_FRAME_MJPEG: u8 = 0x07;
+
+/// Requêtes class-specific sur l'interface VideoStreaming.
+pub const SET_CUR: u8 = 0x01;
+pub const GET_CUR: u8 = 0x81;
+pub const VS_PROBE_CONTROL: u8 = 0x01;
+pub const VS_COMMIT_CONTROL: u8 = 0x02;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PixelFormat {
+    Yuyv,
+    Nv12,
+    Mjpeg,
+}
+
+impl PixelFormat {
+    /// Les GUID de format UVC commencent par le FourCC.
+    fn from_guid(guid: &[u8]) -> Option<Self> {
+        match guid.get(..4)? {
+            b"YUY2" => Some(PixelFormat::Yuyv),
+            b"NV12" => Some(PixelFormat::Nv12),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FrameDesc {
+    pub format_index: u8,
+    pub frame_index: u8,
+    pub format: PixelFormat,
+    pub width: u16,
+    pub height: u16,
+    /// Intervalles en unités de 100 ns.
+    pub default_interval: u32,
+    pub min_interval: u32,
+    pub max_frame_size: u32,
+}
+
+fn le16(b: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([b[at], b[at + 1]])
+}
+
+fn le32(b: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]])
+}
+
+fn parse_frame(d: &[u8], format_index: u8, format: PixelFormat) -> Option<FrameDesc> {
+    if d.len() < 26 {
+        return None;
+    }
+    let default_interval = le32(d, 21);
+    let min_interval = match d[25] {
+        // Intervalle continu : min/max/step.
+        0 if d.len() >= 38 => le32(d, 26),
+        0 => return None,
+        n => {
+            let list = d.get(26..26 + n as usize * 4)?;
+            list.chunks_exact(4)
+                .map(|c| le32(c, 0))
+                .min()
+                .unwrap_or(default_interval)
+        }
+    };
+    Some(FrameDesc {
+        format_index,
+        frame_index: d[3],
+        format,
+        width: le16(d, 5),
+        height: le16(d, 7),
+        default_interval,
+        min_interval,
+        max_frame_size: le32(d, 17),
+    })
+}
+
+/// Parcourt les descripteurs class-specific d'une interface VideoStreaming
+/// et remplit `out`. Retourne le nombre de tailles trouvées. Les formats
+/// inconnus (H.264 frame-based, etc.) sont ignorés.
+pub fn parse_streaming(descs: &[u8], out: &mut [FrameDesc]) -> usize {
+    let mut pos = 0usize;
+    let mut n = 0usize;
+    let mut current: Option<(u8, PixelFormat)> = None;
+    while pos + 3 <= descs.len() {
+        let len = descs[pos] as usize;
+        if len < 3 || pos + len > descs.len() {
+            break;
+        }
+        let d = &descs[pos..pos + len];
+        pos += len;
+        if d[1] != CS_INTERFACE {
+            continue;
+        }
+        match d[2] {
+            VS_FORMAT_UNCOMPRESSED if len >= 21 => {
+                current = PixelFormat::from_guid(&d[5..21]).map(|f| (d[3], f));
+            }
+            VS_FORMAT_MJPEG if len >= 4 => current = Some((d[3], PixelFormat::Mjpeg)),
+            VS_FORMAT_UNCOMPRESSED | VS_FORMAT_MJPEG => current = None,
+            VS_FRAME_UNCOMPRESSED | VS_FRAME_MJPEG => {
+                let Some((format_index, format)) = current else {
+                    continue;
+                };
+                if n == out.len() {
+                    break;
+                }
+                if let Some(frame) = parse_frame(d, format_index, format) {
+                    out[n] = frame;
+                    n += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    n
+}
+
+/// Choisit le mode le plus proche d'une demande : d'abord la taille exacte
+/// si elle existe, sinon la plus grande qui tient dedans ; un format non
+/// compressé est préféré à taille égale.
+pub fn best_match(frames: &[FrameDesc], width: u16, height: u16) -> Option<FrameDesc> {
+    frames
+        .iter()
+        .filter(|f| f.width <= width && f.height <= height)
+        .max_by_key(|f| {
+            (
+                f.width as u32 * f.height as u32,
+                f.format != PixelFormat::Mjpeg,
+            )
+        })
+        .copied()
+}
+
+pub const STREAMING_CONTROL_LEN: usize = 26;
+
+/// Bloc PROBE/COMMIT (champs UVC 1.0, compris par tous les périphériques).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StreamingControl {
+    pub hint: u16,
+    pub format_index: u8,
+    pub frame_index: u8,
+    pub frame_interval: u32,
+    pub max_frame_size: u32,
+    pub max_payload_size: u32,
+}
+
+impl StreamingControl {
+    pub fn for_frame(frame: &FrameDesc, interval: u32) -> Self {
+        Self {
+            // bmHint : dwFrameInterval fixé.
+            hint: 1,
+            format_index: frame.format_index,
+            frame_index: frame.frame_index,
+            frame_interval: interval.max(frame.min_interval),
+            max_frame_size: 0,
+            max_payload_size: 0,
+        }
+    }
+
+    pub fn encode(&self) -> [u8; STREAMING_CONTROL_LEN] {
+        let mut b = [0u8; STREAMING_CONTROL_LEN];
+        b[0..2].copy_from_slice(&self.hint.to_le_bytes());
+        b[2] = self.format_index;
+        b[3] = self.frame_index;
+        b[4..8].copy_from_slice(&self.frame_interval.to_le_bytes());
+        b[18..22].copy_from_slice(&self.max_frame_size.to_le_bytes());
+        b[22..26].copy_from_slice(&self.max_payload_size.to_le_bytes());
+        b
+    }
+
+    /// Réponse GET_CUR(PROBE) : le périphérique renseigne les tailles max.
+    pub fn decode(b: &[u8]) -> Option<Self> {
+        if b.len() < STREAMING_CONTROL_LEN {
+            return None;
+        }
+        Some(Self {
+            hint: le16(b, 0),
+            format_index: b[2],
+            frame_index: b[3],
+            frame_interval: le32(b, 4),
+            max_frame_size: le32(b, 18),
+            max_payload_size: le32(b, 22),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn frame(subtype: u8, index: u8, w: u16, h: u16, intervals: &[u32]) -> Vec<u8> {
+        let mut d = std::vec![0u8; 26];
+        d[1] = CS_INTERFACE;
+        d[2] = subtype;
+        d[3] = index;
+        d[5..7].copy_from_slice(&w.to_le_bytes());
+        d[7..9].copy_from_slice(&h.to_le_bytes());
+        d[17..21].copy_from_slice(&(w as u32 * h as u32 * 2).to_le_bytes());
+        d[21..25].copy_from_slice(&intervals[0].to_le_bytes());
+        d[25] = intervals.len() as u8;
+        for i in intervals {
+            d.extend_from_slice(&i.to_le_bytes());
+        }
+        d[0] = d.len() as u8;
+        d
+    }
+
+    #[test]
+    fn parses_formats_and_picks_a_mode() {
+        let mut descs = std::vec![27u8, CS_INTERFACE, VS_FORMAT_UNCOMPRESSED, 1, 2];
+        descs.extend_from_slice(b"YUY2\x00\x00\x10\x00\x80\x00\x00\xaa\x00\x38\x9b\x71");
+        descs.extend_from_slice(&[16, 0, 0, 0, 0, 0]);
+        descs.extend(frame(
+            VS_FRAME_UNCOMPRESSED,
+            1,
+            640,
+            480,
+            &[333_333, 666_666],
+        ));
+        descs.extend(frame(VS_FRAME_UNCOMPRESSED, 2, 1280, 720, &[1_000_000]));
+        descs.extend_from_slice(&[11, CS_INTERFACE, VS_FORMAT_MJPEG, 2, 1, 0, 0, 0, 0, 0, 0]);
+        descs.extend(frame(VS_FRAME_MJPEG, 1, 1280, 720, &[333_333]));
+
+        let mut out = [FrameDesc {
+            format_index: 0,
+            frame_index: 0,
+            format: PixelFormat::Yuyv,
+            width: 0,
+            height: 0,
+            default_interval: 0,
+            min_interval: 0,
+            max_frame_size: 0,
+        }; 8];
+        let n = parse_streaming(&descs, &mut out);
+        assert_eq!(n, 3);
+        assert_eq!(out[2].format, PixelFormat::Mjpeg);
+        assert_eq!(out[2].format_index, 2);
+
+        let pick = best_match(&out[..n], 1280, 720).unwrap();
+        assert_eq!((pick.format, pick.frame_index), (PixelFormat::Yuyv, 2));
+        assert_eq!(best_match(&out[..n], 800, 600).unwrap().width, 640);
+
+        let ctl = StreamingControl::for_frame(&out[0], 0);
+        assert_eq!(ctl.frame_interval, 333_333);
+        assert_eq!(StreamingControl::decode(&ctl.encode()), Some(ctl));
+    }
+}
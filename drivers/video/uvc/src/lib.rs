@@ -0,0 +1,20 @@
+#![no_std]
+//! exo-uvc — capture vidéo pour Exo-OS.
+//!
+//! - `descriptors` : parsing des descripteurs VideoStreaming (formats/tailles/
+//!   intervalles) et encodage de la négociation PROBE/COMMIT.
+//! - `payload` : en-têtes de payload UVC et réassemblage des trames.
+//! - `capture` : file de buffers partagés (queue/dequeue, façon V4L2) et
+//!   contrôle d'accès : un client ne peut ouvrir une caméra qu'après un
+//!   accord donné via le portail.
+
+#[cfg(test)]
+extern crate std;
+
+pub mod capture;
+pub mod descriptors;
+pub mod payload;
+
+pub use capture::{CaptureDevice, CaptureError, CapturePortal};
+pub use descriptors::{FrameDesc, PixelFormat, StreamingControl};
+pub use payload::{FrameAssembler, PayloadHeader};
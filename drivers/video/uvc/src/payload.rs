@@ -0,0 +1,153 @@
+//! En-têtes de payload UVC (§2.4.3.3) et réassemblage des trames.
+
+pub const HEADER_FID: u8 = 0x01;
+pub const HEADER_EOF: u8 = 0x02;
+pub const HEADER_PTS: u8 = 0x04;
+pub const HEADER_SCR: u8 = 0x08;
+pub const HEADER_ERR: u8 = 0x40;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PayloadHeader {
+    pub len: usize,
+    pub info: u8,
+    pub pts: Option<u32>,
+}
+
+impl PayloadHeader {
+    pub fn parse(packet: &[u8]) -> Option<Self> {
+        let len = *packet.first()? as usize;
+        if len < 2 || len > packet.len() {
+            return None;
+        }
+        let info = packet[1];
+        let pts = if info & HEADER_PTS != 0 && len >= 6 {
+            Some(u32::from_le_bytes([
+                packet[2], packet[3], packet[4], packet[5],
+            ]))
+        } else {
+            None
+        };
+        Some(Self { len, info, pts })
+    }
+
+    pub fn fid(&self) -> bool {
+        self.info & HEADER_FID != 0
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PushResult {
+    /// Paquet absorbé, trame en cours.
+    Pending,
+    /// Trame complète de `len` octets dans le buffer.
+    Frame { len: usize, pts: Option<u32> },
+    /// Trame abandonnée (erreur signalée, débordement ou EOF manquant).
+    Dropped,
+}
+
+/// Recolle les paquets d'un endpoint VideoStreaming dans un buffer de trame.
+/// Un changement de FID sans EOF préalable signifie que la fin de la trame
+/// précédente a été perdue : elle est abandonnée.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameAssembler {
+    fid: Option<bool>,
+    len: usize,
+    pts: Option<u32>,
+    broken: bool,
+}
+
+impl FrameAssembler {
+    pub const fn new() -> Self {
+        Self {
+            fid: None,
+            len: 0,
+            pts: None,
+            broken: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn push(&mut self, packet: &[u8], buf: &mut [u8]) -> PushResult {
+        let Some(header) = PayloadHeader::parse(packet) else {
+            return PushResult::Pending;
+        };
+        let mut result = PushResult::Pending;
+        if self.fid.is_some_and(|fid| fid != header.fid()) && (self.len > 0 || self.broken) {
+            result = PushResult::Dropped;
+            self.len = 0;
+            self.broken = false;
+        }
+        if self.len == 0 {
+            self.pts = header.pts;
+        }
+        self.fid = Some(header.fid());
+        if header.info & HEADER_ERR != 0 {
+            self.broken = true;
+        }
+
+        let data = &packet[header.len..];
+        match buf.get_mut(self.len..self.len + data.len()) {
+            Some(dst) if !self.broken => {
+                dst.copy_from_slice(data);
+                self.len += data.len();
+            }
+            _ => self.broken = true,
+        }
+
+        if header.info & HEADER_EOF != 0 {
+            let done = if self.broken || self.len == 0 {
+                PushResult::Dropped
+            } else {
+                PushResult::Frame {
+                    len: self.len,
+                    pts: self.pts,
+                }
+            };
+            self.len = 0;
+            self.broken = false;
+            // Le prochain paquet ouvre une nouvelle trame quel que soit son FID.
+            self.fid = None;
+            return done;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reassembles_frames_across_packets() {
+        let mut asm = FrameAssembler::new();
+        let mut buf = [0u8; 8];
+        let first = [6, HEADER_PTS, 0x10, 0, 0, 0, 1, 2, 3];
+        assert_eq!(asm.push(&first, &mut buf), PushResult::Pending);
+        assert_eq!(
+            asm.push(&[2, HEADER_EOF, 4, 5], &mut buf),
+            PushResult::Frame {
+                len: 5,
+                pts: Some(0x10)
+            }
+        );
+        assert_eq!(&buf[..5], &[1, 2, 3, 4, 5]);
+
+        // EOF perdu : la bascule de FID abandonne la trame tronquée.
+        assert_eq!(asm.push(&[2, HEADER_FID, 9], &mut buf), PushResult::Pending);
+        assert_eq!(asm.push(&[2, 0, 7], &mut buf), PushResult::Dropped);
+        assert_eq!(
+            asm.push(&[2, HEADER_EOF, 8], &mut buf),
+            PushResult::Frame { len: 2, pts: None }
+        );
+
+        // Débordement du buffer.
+        assert_eq!(
+            asm.push(&[2, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9], &mut buf),
+            PushResult::Pending
+        );
+        assert_eq!(asm.push(&[2, HEADER_EOF], &mut buf), PushResult::Dropped);
+    }
+}
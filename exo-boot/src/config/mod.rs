@@ -4,6 +4,7 @@
 //! Dans les deux cas, retourne un `BootConfig` complet (defaults si pas de fichier).
 //!
 //! Chemin UEFI : `/EFI/exo-os/exo-boot.cfg` sur l'ESP actif.
+//! Chemin PXE  : `exo-boot.cfg` par TFTP, à côté du fichier de boot DHCP.
 //! Chemin BIOS : Config intégrée en dur (pas de lecture fichier en mode réel).
 
 pub mod defaults;
@@ -53,6 +54,32 @@ pub fn load_config_uefi(
     config
 }
 
+/// Charge la configuration par TFTP (boot PXE : pas de SimpleFileSystem).
+/// Le fichier `exo-boot.cfg` est cherché à côté du fichier de boot DHCP ;
+/// absent ou invalide → defaults, comme sur l'ESP.
+#[cfg(feature = "uefi-boot")]
+pub fn load_config_pxe(
+    bt:           &uefi::table::boot::BootServices,
+    image_handle: uefi::Handle,
+    device:       uefi::Handle,
+) -> BootConfig {
+    let mut config = BootConfig::default_config();
+
+    if let Ok(file_buf) =
+        crate::uefi::protocols::pxe::load_file_tftp(bt, image_handle, device, UEFI_CONFIG_PATH)
+    {
+        if parser::parse_config(file_buf.as_bytes(), &mut config).is_err() {
+            config = BootConfig::default_config();
+        }
+    }
+
+    if config.validate().is_err() {
+        config = BootConfig::default_config();
+    }
+
+    config
+}
+
 // ─── Chargement BIOS ─────────────────────────────────────────────────────────
 
 /// Retourne la configuration par défaut pour le chemin BIOS.
@@ -147,9 +147,18 @@ pub struct BootInfo {
     pub boot_flags:           u64,
     /// Timestamp TSC au moment de la collecte (utile pour profiling).
     pub boot_tsc:             u64,
+    // ── Écran ────────────────────────────────────────────────────────────
+    /// Adresse physique de l'EDID de l'écran (protocole EFI EDID), 0 si absent.
+    pub edid_phys:            u64,
+    /// Taille de l'EDID en bytes (multiple de 128, au plus 512).
+    pub edid_size:            u64,
+    // ── Boot réseau ──────────────────────────────────────────────────────
+    /// IPv4 du serveur de boot (octets réseau dans les 4 octets bas), 0 si
+    /// inconnu ou boot disque. Significatif avec `NETWORK_BOOT`.
+    pub netboot_server:       u64,
     // ── Réservé ──────────────────────────────────────────────────────────
     /// Champs réservés — DOIVENT être à zéro (RÈGLE BOOT-03).
-    pub _reserved:            [u64; 16],
+    pub _reserved:            [u64; 13],
 }
 
 /// Flags de BootInfo.boot_flags
@@ -164,6 +173,9 @@ pub mod boot_flags {
     pub const ACPI2_PRESENT:       u64 = 1 << 3;
     /// Framebuffer GOP disponible.
     pub const FRAMEBUFFER_PRESENT: u64 = 1 << 4;
+    /// Chargé depuis le réseau (PXE/TFTP ou UEFI HTTP boot). Seul le kernel
+    /// vient du réseau : la racine reste le volume ExoFS local.
+    pub const NETWORK_BOOT:        u64 = 1 << 5;
 }
 
 impl BootInfo {
@@ -62,12 +62,18 @@ fn efi_main(
 
     let boot_services = system_table.boot_services();
 
-    // Étape 2 : Configuration
-    let cfg = config::load_config_uefi(boot_services, image_handle);
+    // Étape 2 : Configuration — ESP, ou TFTP si exo-boot vient de PXE.
+    // HTTP boot monte l'image ESP en RAM disk : chemin fichier habituel.
+    let netboot = uefi::protocols::pxe::detect(boot_services, image_handle);
+    let cfg = match netboot {
+        Some(uefi::protocols::pxe::NetBoot::Pxe { device, .. }) =>
+            config::load_config_pxe(boot_services, image_handle, device),
+        _ => config::load_config_uefi(boot_services, image_handle),
+    };
 
     // Étape 3 : Framebuffer GOP
-    let framebuffer = uefi::protocols::graphics::init_gop(boot_services)
-        .unwrap_or_else(|_| display::framebuffer::Framebuffer::absent());
+    let (framebuffer, edid) = uefi::protocols::graphics::init_gop(boot_services, image_handle)
+        .unwrap_or_else(|_| (display::framebuffer::Framebuffer::absent(), &[]));
     display::init_display_from_gop(
         framebuffer.phys_addr, framebuffer.width, framebuffer.height,
         framebuffer.stride, framebuffer.format, framebuffer.size_bytes,
@@ -96,9 +102,20 @@ fn efi_main(
     boot_println!("Carte mémoire: {} entrées", uefi_memmap.entries.len());
 
     // Étape 5 : Chargement kernel + vérification signature (RÈGLE BOOT-02)
-    let kernel_data = uefi::protocols::file::load_file(
-        boot_services, image_handle, cfg.kernel_path.as_str(),
-    ).expect("Impossible de charger le kernel depuis l'ESP");
+    let kernel_data = match netboot {
+        Some(uefi::protocols::pxe::NetBoot::Pxe { device, server }) => {
+            boot_println!(
+                "Boot reseau PXE: serveur {}.{}.{}.{}",
+                server[0], server[1], server[2], server[3]
+            );
+            uefi::protocols::pxe::load_file_tftp(
+                boot_services, image_handle, device, cfg.kernel_path.as_str(),
+            ).expect("Impossible de charger le kernel par TFTP")
+        }
+        _ => uefi::protocols::file::load_file(
+            boot_services, image_handle, cfg.kernel_path.as_str(),
+        ).expect("Impossible de charger le kernel depuis l'ESP"),
+    };
     boot_println!("Kernel: {} bytes", kernel_data.len());
 
     // RÈGLE BOOT-02 — vérification de signature kernel FAIL-CLOSED (exo-verity).
@@ -106,12 +123,14 @@ fn efi_main(
     //   Verified   → OK ; Tampered → refus TOUJOURS ; Unsigned/NoVerifierKey →
     //   refus si strict, sinon avertissement (dev). La vérif crypto Ed25519 +
     //   verify_strict tourne TOUJOURS (plus de stub fail-open).
+    // Boot réseau → politique stricte d'office : le kernel ET la config
+    // viennent d'un serveur non authentifié, un kernel non signé est refusé.
     {
         let uefi_sb_enforcing =
             uefi::secure_boot::query_secure_boot_status(&system_table).is_enforcing();
         match kernel_loader::verify::enforce_or_panic(
             kernel_data.as_bytes(),
-            cfg.secure_boot_required,
+            cfg.secure_boot_required || netboot.is_some(),
             uefi_sb_enforcing,
         ) {
             None => boot_println!("Signature kernel: verifiee (Ed25519)"),
@@ -178,6 +197,11 @@ fn efi_main(
     boot_info_ref.kernel_entry_offset  = load_result.entry_offset;
     boot_info_ref.kernel_elf_phys      = params.elf_phys_addr;
     boot_info_ref.kernel_elf_size      = kernel_data.len() as u64;
+    // Identité-mappé : l'adresse de la copie statique est son adresse physique.
+    boot_info_ref.edid_phys            = if edid.is_empty() { 0 } else { edid.as_ptr() as u64 };
+    boot_info_ref.edid_size            = edid.len() as u64;
+    boot_info_ref.netboot_server       =
+        netboot.map_or(0, |n| u32::from_ne_bytes(n.server_ipv4()) as u64);
     boot_info_ref.boot_flags = {
         use kernel_loader::handoff::boot_flags::*;
         let mut flags = UEFI_BOOT;
@@ -185,6 +209,7 @@ fn efi_main(
         if cfg.secure_boot_required                { flags |= SECURE_BOOT_ACTIVE; }
         if boot_info_ref.framebuffer.is_present()  { flags |= FRAMEBUFFER_PRESENT; }
         if boot_info_ref.acpi_rsdp != 0            { flags |= ACPI2_PRESENT; }
+        if netboot.is_some()                       { flags |= NETWORK_BOOT; }
         flags
     };
     boot_info_ref.record_tsc();
@@ -0,0 +1,80 @@
+//! edid.rs — EFI_EDID_ACTIVE / EFI_EDID_DISCOVERED — EDID de l'écran.
+//!
+//! Le firmware installe ces protocoles sur le handle du GOP :
+//!   - EDID Active     (GUID : bd8c1056-9f36-44ec-92a8-a6337f817986) : EDID
+//!     réellement utilisé, éventuellement corrigé par la plateforme ;
+//!   - EDID Discovered (GUID : 1c0c34f6-d380-41fa-a049-8ad06c1a66aa) : EDID
+//!     lu tel quel sur l'écran.
+//!
+//! Exo-boot s'en sert pour choisir le mode GOP natif de l'écran, puis en
+//! passe une copie au kernel (BootInfo.edid_phys / edid_size) : la couche
+//! d'affichage la relit via SYS_DISPLAY_EDID et la décode avec
+//! `exo_services::edid` (parseur partagé, même code qu'ici).
+
+use exo_services::edid::EDID_MAX;
+use uefi::prelude::*;
+use uefi::proto::unsafe_protocol;
+
+use crate::uefi::services::open_protocol_safe;
+
+#[repr(C)]
+#[unsafe_protocol("bd8c1056-9f36-44ec-92a8-a6337f817986")]
+pub struct EdidActive {
+    size_of_edid: u32,
+    edid:         *const u8,
+}
+
+#[repr(C)]
+#[unsafe_protocol("1c0c34f6-d380-41fa-a049-8ad06c1a66aa")]
+pub struct EdidDiscovered {
+    size_of_edid: u32,
+    edid:         *const u8,
+}
+
+/// Copie de l'EDID. Statique : elle doit survivre à ExitBootServices jusqu'à
+/// la copie faite par le kernel dans early_init.
+static mut EDID_COPY: [u8; EDID_MAX] = [0; EDID_MAX];
+
+/// Lit l'EDID de l'écran piloté par `gop_handle` (Active, sinon Discovered).
+/// Retourne la copie statique, vide si le firmware n'en fournit pas.
+pub fn read_edid(bt: &BootServices, gop_handle: Handle, image_handle: Handle) -> &'static [u8] {
+    crate::uefi::exit::assert_boot_services_active("read_edid");
+
+    // SAFETY : gop_handle porte le GOP ; ouverture non exclusive (GetProtocol),
+    // protocole relâché à la fin du scope.
+    let active = unsafe { open_protocol_safe::<EdidActive>(bt, gop_handle, image_handle) };
+    if let Ok(edid) = active {
+        // SAFETY : tampon firmware de `size_of_edid` octets, valide tant que
+        // le protocole est ouvert.
+        if let Some(copy) = unsafe { copy_edid(edid.edid, edid.size_of_edid) } {
+            return copy;
+        }
+    }
+    // SAFETY : idem pour EDID Discovered.
+    let discovered = unsafe { open_protocol_safe::<EdidDiscovered>(bt, gop_handle, image_handle) };
+    if let Ok(edid) = discovered {
+        // SAFETY : idem.
+        if let Some(copy) = unsafe { copy_edid(edid.edid, edid.size_of_edid) } {
+            return copy;
+        }
+    }
+    &[]
+}
+
+/// Copie au plus `EDID_MAX` octets (blocs de 128 entiers) dans `EDID_COPY`.
+///
+/// # Safety
+/// `ptr` doit être nul ou pointer `size` octets lisibles.
+unsafe fn copy_edid(ptr: *const u8, size: u32) -> Option<&'static [u8]> {
+    let len = (size as usize).min(EDID_MAX) / 128 * 128;
+    if ptr.is_null() || len == 0 {
+        return None;
+    }
+    // SAFETY : bootloader single-threaded ; `ptr` couvre `len` octets
+    // (contrat de l'appelant), EDID_COPY n'est écrit qu'ici.
+    unsafe {
+        let copy = &mut *core::ptr::addr_of_mut!(EDID_COPY);
+        core::ptr::copy_nonoverlapping(ptr, copy.as_mut_ptr(), len);
+        Some(&copy[..len])
+    }
+}
@@ -208,6 +208,14 @@ pub struct FileBuffer {
 }
 
 impl FileBuffer {
+    /// Enveloppe un buffer du pool UEFI déjà rempli (chargement TFTP, `pxe.rs`).
+    ///
+    /// # Safety
+    /// `ptr` doit provenir de `allocate_pool` et être valide pour `size` octets.
+    pub(super) unsafe fn from_pool(ptr: *mut u8, size: usize) -> Self {
+        Self { ptr, size }
+    }
+
     /// Accès en lecture aux données du fichier.
     #[inline]
     pub fn as_bytes(&self) -> &[u8] {
@@ -8,28 +8,37 @@
 //!   - Afficher le logo et la barre de progression au démarrage
 //!   - Passer FramebufferInfo au kernel via BootInfo (kernel/tty l'utilise)
 //!
+//! Le mode est choisi d'après l'EDID de l'écran (`protocols::edid`) : sa
+//! résolution native si le GOP la propose, sinon l'heuristique 1080p/720p.
+//!
 //! RÈGLE BOOT-06 : L'adresse du framebuffer physique est dans BootInfo.
 //! Le kernel mappe ce framebuffer dans son espace virtuel au démarrage.
 //! Contrairement aux Boot Services, le framebuffer physique RESTE VALIDE
 //! après ExitBootServices.
 
+use exo_services::edid::Edid;
 use uefi::proto::console::gop::{GraphicsOutput, Mode, PixelFormat};
 use uefi::prelude::*;
 use crate::display::framebuffer::{Framebuffer, FramebufferFormat};
 
 // ─── Point d'entrée principal ─────────────────────────────────────────────────
 
-/// Initialise le GOP et retourne une structure `Framebuffer` utilisable.
+/// Initialise le GOP et retourne une structure `Framebuffer` utilisable, avec
+/// l'EDID de l'écran (vide si le firmware n'en fournit pas).
 ///
 /// Sélectionne automatiquement la résolution optimale parmi celles disponibles :
-///   - Préférence 1920×1080 (1080p) si disponible
+///   - Résolution native de l'écran (mode préféré de l'EDID) si disponible
+///   - Sinon 1920×1080 (1080p) si disponible
 ///   - Sinon : résolution maximale ≤ 4K (évite les FB > 64 MB)
 ///   - Sinon : mode courant sans modification
 ///
 /// # Errors
 /// - `GopError::ProtocolNotFound` : Aucun GOP disponible sur ce système.
 /// - `GopError::NoSuitableMode`   : GOP présent mais aucun mode utilisable.
-pub fn init_gop(bt: &BootServices) -> Result<Framebuffer, GopError> {
+pub fn init_gop(
+    bt:           &BootServices,
+    image_handle: Handle,
+) -> Result<(Framebuffer, &'static [u8]), GopError> {
     crate::uefi::exit::assert_boot_services_active("GOP init");
 
     // Trouve le handle du GOP, puis ouvre le protocole en mode exclusif.
@@ -43,8 +52,15 @@ pub fn init_gop(bt: &BootServices) -> Result<Framebuffer, GopError> {
 
     let gop: &mut GraphicsOutput = &mut *gop_scoped;
 
+    // ── Résolution native d'après l'EDID ──────────────────────────────────────
+    let edid   = super::edid::read_edid(bt, gop_handle, image_handle);
+    let native = Edid::parse(edid)
+        .ok()
+        .and_then(|edid| edid.preferred())
+        .map(|mode| (mode.width, mode.height));
+
     // ── Sélection du mode optimal ─────────────────────────────────────────────
-    let selected_mode = select_optimal_mode(gop, bt)?;
+    let selected_mode = select_optimal_mode(gop, bt, native)?;
 
     // ── Application du mode ───────────────────────────────────────────────────
     gop.set_mode(&selected_mode)
@@ -69,13 +85,17 @@ pub fn init_gop(bt: &BootServices) -> Result<Framebuffer, GopError> {
         size_bytes: fb_size,
     };
 
-    Ok(framebuffer)
+    Ok((framebuffer, edid))
 }
 
 // ─── Sélection du mode optimal ────────────────────────────────────────────────
 
 /// Sélectionne le mode GOP optimal selon les critères de préférence.
-fn select_optimal_mode(gop: &mut GraphicsOutput, bt: &BootServices) -> Result<Mode, GopError> {
+fn select_optimal_mode(
+    gop:    &mut GraphicsOutput,
+    bt:     &BootServices,
+    native: Option<(u32, u32)>,
+) -> Result<Mode, GopError> {
     let mut best_mode:     Option<Mode>  = None;
     let mut best_score:    i64           = -1;
     let mut mode_count                   = 0u32;
@@ -95,7 +115,7 @@ fn select_optimal_mode(gop: &mut GraphicsOutput, bt: &BootServices) -> Result<Mo
             continue;
         }
 
-        let score = score_mode(w as u32, h as u32);
+        let score = score_mode(w as u32, h as u32, native);
         if score > best_score {
             best_score = score;
             best_mode  = Some(mode);
@@ -112,8 +132,13 @@ fn select_optimal_mode(gop: &mut GraphicsOutput, bt: &BootServices) -> Result<Mo
 }
 
 /// Calcule un score pour un mode GOP.
-/// Objectif : préférer 1920×1080, puis maximiser la résolution dans la limite 4K.
-fn score_mode(width: u32, height: u32) -> i64 {
+/// Objectif : préférer la résolution native de l'écran, puis 1920×1080, puis
+/// maximiser la résolution dans la limite 4K.
+fn score_mode(width: u32, height: u32, native: Option<(u32, u32)>) -> i64 {
+    // Mode préféré de l'EDID : pas de mise à l'échelle par l'écran
+    if native == Some((width, height)) {
+        return i64::MAX;
+    }
     // Bonus maximal pour exactement 1080p
     if width == 1920 && height == 1080 {
         return i64::MAX / 2;
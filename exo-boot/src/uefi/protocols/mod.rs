@@ -5,11 +5,15 @@
 //!
 //! Protocoles implémentés :
 //!   - `graphics`      : GOP — Graphics Output Protocol (framebuffer)
+//!   - `edid`          : EFI_EDID_ACTIVE/DISCOVERED — EDID de l'écran
 //!   - `file`          : EFI_FILE_PROTOCOL — lecture FAT32/ESP
 //!   - `loaded_image`  : EFI_LOADED_IMAGE — infos sur le bootloader lui-même
+//!   - `pxe`           : EFI_PXE_BASE_CODE — boot réseau (TFTP) + détection HTTP boot
 //!   - `rng`           : EFI_RNG_PROTOCOL — entropy initiale (KASLR + CSPRNG)
 
+pub mod edid;
 pub mod file;
 pub mod graphics;
 pub mod loaded_image;
+pub mod pxe;
 pub mod rng;
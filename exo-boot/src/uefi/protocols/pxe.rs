@@ -0,0 +1,221 @@
+//! pxe.rs — Boot réseau : EFI_PXE_BASE_CODE (TFTP) et détection HTTP boot.
+//!
+//! Deux façons d'arriver ici depuis le réseau :
+//!   - **PXE** : le firmware a fait DHCP et chargé exo-boot par TFTP. Le device
+//!     d'exo-boot porte EFI_PXE_BASE_CODE_PROTOCOL (DHCP déjà fait, pas de
+//!     SimpleFileSystem) : kernel et config sont lus par TFTP sur le même
+//!     serveur, dans le répertoire du fichier de boot DHCP.
+//!       → `<dir>/exo-boot.cfg`, `<dir>/kernel.elf` (nom de `kernel_path`)
+//!   - **HTTP boot** : le firmware a téléchargé l'image ESP (`.img`) et l'a
+//!     montée en RAM disk. Le chemin du device contient un nœud URI ; les
+//!     fichiers se lisent via `file.rs` comme sur disque.
+//!
+//! RÈGLE BOOT-02 inchangée : le kernel reçu du réseau passe par la même
+//! vérification Ed25519 que celui de l'ESP — et un kernel non signé est
+//! refusé en boot réseau (voir `main.rs`).
+
+use uefi::prelude::*;
+use uefi::proto::device_path::{DevicePath, DeviceSubType, DeviceType};
+use uefi::proto::loaded_image::LoadedImage;
+use uefi::proto::network::pxe::BaseCode;
+use uefi::proto::network::IpAddress;
+use uefi::table::boot::{OpenProtocolAttributes, OpenProtocolParams};
+use uefi::CStr8;
+
+use super::file::FileBuffer;
+
+// ─── Constantes ───────────────────────────────────────────────────────────────
+
+/// Même limite que `file.rs` : 64 MB.
+const MAX_FILE_SIZE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Offsets BOOTP (RFC 951) dans le paquet DHCP ACK brut.
+const BOOTP_SIADDR: usize = 20;
+const BOOTP_FILE: usize = 108;
+const BOOTP_FILE_LEN: usize = 128;
+
+/// Longueur max d'un chemin TFTP (null-terminator compris).
+pub const TFTP_PATH_MAX: usize = 256;
+
+// ─── Détection ────────────────────────────────────────────────────────────────
+
+/// Source réseau d'exo-boot.
+#[derive(Debug, Clone, Copy)]
+pub enum NetBoot {
+    /// PXE/TFTP : serveur DHCP `siaddr` et device portant BaseCode.
+    Pxe { device: Handle, server: [u8; 4] },
+    /// UEFI HTTP boot d'une image ESP montée en RAM disk.
+    Http,
+}
+
+impl NetBoot {
+    /// Adresse IPv4 du serveur de boot transmise au kernel (0 si inconnue).
+    pub fn server_ipv4(&self) -> [u8; 4] {
+        match self {
+            Self::Pxe { server, .. } => *server,
+            Self::Http => [0; 4],
+        }
+    }
+}
+
+/// Détermine si exo-boot a été chargé depuis le réseau. `None` = boot disque.
+pub fn detect(bt: &BootServices, image_handle: Handle) -> Option<NetBoot> {
+    crate::uefi::exit::assert_boot_services_active("pxe::detect");
+
+    let device = {
+        let loaded_image = bt.open_protocol_exclusive::<LoadedImage>(image_handle).ok()?;
+        loaded_image.device()?
+    };
+
+    // SAFETY : GetProtocol non exclusif sur notre propre device de boot ; rien
+    // n'est installé et les protocoles sont relâchés à la fin du scope.
+    let params = OpenProtocolParams { handle: device, agent: image_handle, controller: None };
+    if let Ok(pxe) = unsafe { bt.open_protocol::<BaseCode>(params, OpenProtocolAttributes::GetProtocol) } {
+        let mode = pxe.mode();
+        if mode.dhcp_ack_received {
+            let packet: &[u8; 1472] = mode.dhcp_ack.as_ref();
+            if let Some((server, _)) = parse_bootp(packet) {
+                return Some(NetBoot::Pxe { device, server });
+            }
+        }
+    }
+
+    // SAFETY : idem, lecture seule du chemin du device.
+    let params = OpenProtocolParams { handle: device, agent: image_handle, controller: None };
+    let path = unsafe { bt.open_protocol::<DevicePath>(params, OpenProtocolAttributes::GetProtocol) }.ok()?;
+    let is_uri = path.node_iter().any(|node| {
+        node.device_type() == DeviceType::MESSAGING && node.sub_type() == DeviceSubType::MESSAGING_URI
+    });
+    is_uri.then_some(NetBoot::Http)
+}
+
+// ─── TFTP ─────────────────────────────────────────────────────────────────────
+
+/// Charge `name` par TFTP, à côté du fichier de boot DHCP.
+///
+/// La mémoire est allouée dans le pool UEFI (LOADER_DATA), comme `file::load_file`.
+pub fn load_file_tftp(
+    bt:           &BootServices,
+    image_handle: Handle,
+    device:       Handle,
+    name:         &str,
+) -> Result<FileBuffer, PxeError> {
+    crate::uefi::exit::assert_boot_services_active("load_file_tftp");
+
+    // SAFETY : GetProtocol sur le device PXE déjà démarré par le firmware ; le
+    // protocole reste ouvert le temps du transfert uniquement.
+    let params = OpenProtocolParams { handle: device, agent: image_handle, controller: None };
+    let mut pxe = unsafe { bt.open_protocol::<BaseCode>(params, OpenProtocolAttributes::GetProtocol) }
+        .map_err(|e| PxeError::ProtocolNotFound { status: e.status() })?;
+
+    let (server, boot_file) = {
+        let packet: &[u8; 1472] = pxe.mode().dhcp_ack.as_ref();
+        let (server, boot_file) = parse_bootp(packet).ok_or(PxeError::NoDhcpAck)?;
+        let mut copy = [0u8; BOOTP_FILE_LEN];
+        copy[..boot_file.len()].copy_from_slice(boot_file);
+        (server, (copy, boot_file.len()))
+    };
+
+    let mut path = [0u8; TFTP_PATH_MAX];
+    let len = sibling_path(&boot_file.0[..boot_file.1], name, &mut path).ok_or(PxeError::PathTooLong)?;
+    let filename = CStr8::from_bytes_with_nul(&path[..=len]).map_err(|_| PxeError::PathTooLong)?;
+    let server_ip = IpAddress::new_v4(server);
+
+    let size = pxe
+        .tftp_get_file_size(&server_ip, filename)
+        .map_err(|e| PxeError::NotFound { status: e.status() })?;
+    if size == 0 {
+        return Err(PxeError::Empty);
+    }
+    if size > MAX_FILE_SIZE_BYTES {
+        return Err(PxeError::TooLarge { size, limit: MAX_FILE_SIZE_BYTES });
+    }
+
+    let buf_ptr = bt
+        .allocate_pool(uefi::table::boot::MemoryType::LOADER_DATA, size as usize)
+        .map_err(|e| PxeError::AllocationFailed { size: size as usize, status: e.status() })?;
+    // SAFETY : buf_ptr est valide pour `size` octets, aligné UEFI.
+    let buf = unsafe { core::slice::from_raw_parts_mut(buf_ptr, size as usize) };
+
+    let read = match pxe.tftp_read_file(&server_ip, filename, Some(buf)) {
+        Ok(read) => read,
+        Err(e) => {
+            // SAFETY : buf_ptr alloué ci-dessus par allocate_pool.
+            unsafe { let _ = bt.free_pool(buf_ptr); }
+            return Err(PxeError::ReadFailed { status: e.status() });
+        }
+    };
+    if read != size {
+        // SAFETY : idem.
+        unsafe { let _ = bt.free_pool(buf_ptr); }
+        return Err(PxeError::PartialRead { expected: size, got: read });
+    }
+
+    // SAFETY : buffer du pool UEFI entièrement rempli par TFTP.
+    Ok(unsafe { FileBuffer::from_pool(buf_ptr, size as usize) })
+}
+
+// ─── Helpers ──────────────────────────────────────────────────────────────────
+
+/// `(siaddr, fichier de boot)` d'un paquet DHCP/BOOTP. `None` si pas de serveur.
+pub fn parse_bootp(packet: &[u8]) -> Option<([u8; 4], &[u8])> {
+    let si = packet.get(BOOTP_SIADDR..BOOTP_SIADDR + 4)?;
+    let server = [si[0], si[1], si[2], si[3]];
+    if server == [0; 4] {
+        return None;
+    }
+    let file = packet.get(BOOTP_FILE..BOOTP_FILE + BOOTP_FILE_LEN)?;
+    let end = file.iter().position(|&b| b == 0).unwrap_or(file.len());
+    Some((server, &file[..end]))
+}
+
+/// Chemin de `name` (seul son dernier composant est gardé) dans le répertoire
+/// de `boot_file`, séparateurs `/`, null-terminé dans `out`. Retourne la
+/// longueur sans le null.
+///
+/// `exoos/bootx64.efi` + `/EFI/exo-os/kernel.elf` → `exoos/kernel.elf`.
+pub fn sibling_path(boot_file: &[u8], name: &str, out: &mut [u8]) -> Option<usize> {
+    let is_sep = |b: &u8| *b == b'/' || *b == b'\\';
+    let dir_len = boot_file.iter().rposition(is_sep).map_or(0, |i| i + 1);
+    let name = name.as_bytes();
+    let base = &name[name.iter().rposition(is_sep).map_or(0, |i| i + 1)..];
+    let len = dir_len + base.len();
+    if base.is_empty() || len >= out.len() {
+        return None;
+    }
+    for (dst, &src) in out.iter_mut().zip(boot_file[..dir_len].iter().chain(base)) {
+        *dst = if src == b'\\' { b'/' } else { src };
+    }
+    out[len] = 0;
+    Some(len)
+}
+
+// ─── Erreurs ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug)]
+pub enum PxeError {
+    ProtocolNotFound { status: uefi::Status },
+    NoDhcpAck,
+    PathTooLong,
+    NotFound         { status: uefi::Status },
+    Empty,
+    TooLarge         { size: u64, limit: u64 },
+    AllocationFailed { size: usize, status: uefi::Status },
+    ReadFailed       { status: uefi::Status },
+    PartialRead      { expected: u64, got: u64 },
+}
+
+impl core::fmt::Display for PxeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotFound { status } =>
+                write!(f, "Fichier introuvable sur le serveur TFTP : {:?}", status),
+            Self::TooLarge { size, limit } =>
+                write!(f, "Fichier TFTP trop grand : {} > {} bytes", size, limit),
+            Self::PartialRead { expected, got } =>
+                write!(f, "Lecture TFTP partielle : {} / {} bytes", got, expected),
+            other =>
+                write!(f, "PxeError : {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! # arch/aarch64/boot.rs — Entrée AArch64 par device tree
+//!
+//! Protocole de boot arm64 (Linux) : MMU éteinte, `x0` = adresse physique du
+//! DTB. `_start` parque les CPU secondaires (`wfe`), descend d'EL2 à EL1 si
+//! besoin, installe la pile de boot, efface la BSS puis appelle
+//! `aarch64_boot_main`.
+//!
+//! `aarch64_boot_main` lit le device tree (console PL011, RAM, GICv3), pose
+//! les vecteurs, l'identité MMU, le GIC et le timer virtuel, puis reste en
+//! attente d'IRQ : mémoire, ordonnanceur et pilotes ne sont pas encore portés.
+//!
+//! Image liée par `arch/aarch64/linker.ld`.
+
+use super::{console, exceptions, gic, mmu, timer, Aarch64};
+use crate::arch::fdt::Fdt;
+use crate::arch::interface::ArchTimer;
+
+/// Début de la RAM de `qemu -M virt`, où QEMU dépose le DTB lorsqu'il charge
+/// une image ELF sans renseigner `x0`.
+const QEMU_VIRT_DTB_FALLBACK: u64 = 0x4000_0000;
+
+/// Période du tick de démonstration (100 Hz).
+const TICK_NS: u64 = 10_000_000;
+
+core::arch::global_asm!(
+    ".section .text.boot, \"ax\"",
+    ".global _start",
+    "_start:",
+    // Seul le CPU d'affinité 0 démarre ; les autres attendent le futur SMP.
+    "    mrs x1, mpidr_el1",
+    "    and x1, x1, #0xFF",
+    "    cbz x1, 2f",
+    "1:  wfe",
+    "    b 1b",
+    "2:  mov x19, x0",
+    "    mrs x1, CurrentEL",
+    "    lsr x1, x1, #2",
+    "    cmp x1, #2",
+    "    b.ne 3f",
+    // EL2 → EL1 : EL1 en AArch64, compteurs/timers accessibles, registres
+    // système GICv3 autorisés à EL1, DAIF masqués au retour.
+    "    mov x1, #(1 << 31)",
+    "    msr hcr_el2, x1",
+    "    mov x1, #0x3",
+    "    msr cnthctl_el2, x1",
+    "    msr cntvoff_el2, xzr",
+    "    mov x1, #0x9",
+    "    msr icc_sre_el2, x1",
+    "    isb",
+    "    mov x1, #0x3c5",
+    "    msr spsr_el2, x1",
+    "    adr x1, 3f",
+    "    msr elr_el2, x1",
+    "    eret",
+    "3:  mov x1, #(3 << 20)",
+    "    msr cpacr_el1, x1",
+    "    isb",
+    "    adrp x1, __boot_stack_top",
+    "    add x1, x1, :lo12:__boot_stack_top",
+    "    mov sp, x1",
+    "    adrp x1, __bss_start",
+    "    add x1, x1, :lo12:__bss_start",
+    "    adrp x2, __bss_end",
+    "    add x2, x2, :lo12:__bss_end",
+    "4:  cmp x1, x2",
+    "    b.hs 5f",
+    "    str xzr, [x1], #8",
+    "    b 4b",
+    "5:  mov x0, x19",
+    "    bl aarch64_boot_main",
+    "6:  wfi",
+    "    b 6b",
+    ".section .bss.boot_stack, \"aw\", %nobits",
+    ".balign 16",
+    "__boot_stack_bottom:",
+    "    .space 0x10000",
+    "__boot_stack_top:",
+);
+
+fn log(msg: &[u8]) {
+    console::write_bytes(msg);
+}
+
+/// Localise et valide le DTB (registre `x0`, sinon emplacement QEMU).
+fn locate_fdt(dtb: u64) -> Option<Fdt<'static>> {
+    [dtb, QEMU_VIRT_DTB_FALLBACK]
+        .into_iter()
+        .filter(|&addr| addr != 0)
+        // SAFETY: MMU éteinte, `addr` est une adresse physique de RAM ;
+        // `from_ptr` vérifie le magic avant de lire `totalsize` octets.
+        .find_map(|addr| unsafe { Fdt::from_ptr(addr as *const u8) }.ok())
+}
+
+/// Point d'entrée Rust du CPU de boot.
+#[no_mangle]
+extern "C" fn aarch64_boot_main(dtb: u64) -> ! {
+    let Some(fdt) = locate_fdt(dtb) else {
+        // Sans DTB, ni console ni GIC : rien à annoncer.
+        super::halt_cpu()
+    };
+    console::init_from_fdt(&fdt);
+    log(b"[AARCH64] Exo-OS boot EL1, dtb=");
+    console::write_hex(dtb);
+    log(b"\n");
+    if let Some(bootargs) = fdt.bootargs() {
+        log(b"[AARCH64] bootargs: ");
+        log(bootargs);
+        log(b"\n");
+    }
+    if let Some(node) = fdt.memory() {
+        for (base, size) in node.reg() {
+            log(b"[AARCH64] RAM ");
+            console::write_hex(base);
+            log(b" + ");
+            console::write_hex(size);
+            log(b"\n");
+        }
+    }
+
+    exceptions::install();
+    // SAFETY: appel unique sur le CPU de boot, MMU encore éteinte.
+    unsafe { mmu::enable_identity(&fdt) };
+    log(b"[AARCH64] MMU: identite 1 GiB active\n");
+
+    if let Err(err) = gic::init_from_fdt(&fdt) {
+        let msg: &[u8] = match err {
+            gic::GicError::NotFound => b"[AARCH64] GICv3 absent du device tree\n",
+            gic::GicError::NoRedistributor => b"[AARCH64] GICv3: redistributeur introuvable\n",
+        };
+        log(msg);
+        super::halt_cpu()
+    }
+    timer::init();
+    log(b"[AARCH64] GICv3 + timer virtuel: ");
+    console::write_hex(Aarch64::frequency_hz());
+    log(b" Hz\n");
+
+    Aarch64::arm_oneshot_ns(TICK_NS);
+    super::irq_enable();
+    log(b"[AARCH64] scaffolding: memoire/ordonnanceur non portes, attente IRQ\n");
+    loop {
+        let before = timer::ticks();
+        // SAFETY: attente d'interruption, IRQ démasquées juste au-dessus.
+        unsafe { core::arch::asm!("wfi", options(nostack, nomem)) };
+        if timer::ticks() != before {
+            Aarch64::arm_oneshot_ns(TICK_NS);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! # arch/aarch64/console.rs — Console debug PL011
+//!
+//! UART ARM PrimeCell PL011, trouvée dans le device tree (`arm,pl011`).
+//! Émission seule, en attente active sur FIFO pleine ; le débit reste celui
+//! programmé par le firmware (l'horloge de référence n'est pas décrite de
+//! façon fiable par tous les DTB).
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::arch::fdt::Fdt;
+
+const UARTDR: u64 = 0x00;
+const UARTFR: u64 = 0x18;
+const UARTLCR_H: u64 = 0x2C;
+const UARTCR: u64 = 0x30;
+const UARTIMSC: u64 = 0x38;
+
+const FR_TXFF: u32 = 1 << 5;
+/// FIFO actif, 8 bits.
+const LCR_H_FEN_8BIT: u32 = (1 << 4) | (0b11 << 5);
+/// UARTEN + TXE + RXE.
+const CR_ENABLE: u32 = (1 << 0) | (1 << 8) | (1 << 9);
+
+/// Boucles d'attente max de FIFO non pleine avant d'abandonner l'octet.
+const TX_SPIN_LIMIT: u32 = 100_000;
+
+/// Base MMIO de la PL011 active (0 = console absente).
+static BASE: AtomicU64 = AtomicU64::new(0);
+
+#[inline(always)]
+fn write_reg(base: u64, reg: u64, value: u32) {
+    // SAFETY: `base` provient du device tree et reste mappé (identité de boot).
+    unsafe { core::ptr::write_volatile((base + reg) as *mut u32, value) }
+}
+
+#[inline(always)]
+fn read_reg(base: u64, reg: u64) -> u32 {
+    // SAFETY: idem `write_reg`.
+    unsafe { core::ptr::read_volatile((base + reg) as *const u32) }
+}
+
+/// Programme la PL011 à `base` (8N1, FIFO, interruptions masquées).
+pub fn init(base: u64) {
+    write_reg(base, UARTCR, 0);
+    write_reg(base, UARTIMSC, 0);
+    write_reg(base, UARTLCR_H, LCR_H_FEN_8BIT);
+    write_reg(base, UARTCR, CR_ENABLE);
+    BASE.store(base, Ordering::Release);
+}
+
+/// Cherche la PL011 du device tree (`stdout-path` d'abord) et l'active.
+pub fn init_from_fdt(fdt: &Fdt<'_>) -> bool {
+    let node = fdt
+        .stdout_path()
+        .and_then(|path| core::str::from_utf8(path).ok())
+        .and_then(|path| fdt.find_path(path))
+        .filter(|node| node.is_compatible(b"arm,pl011"))
+        .or_else(|| fdt.find_compatible(b"arm,pl011"));
+    match node.and_then(|node| node.reg_first()) {
+        Some((base, _)) => {
+            init(base);
+            true
+        }
+        None => false,
+    }
+}
+
+#[inline]
+pub fn is_active() -> bool {
+    BASE.load(Ordering::Acquire) != 0
+}
+
+fn write_raw(base: u64, byte: u8) {
+    let mut spins = 0u32;
+    while read_reg(base, UARTFR) & FR_TXFF != 0 {
+        spins += 1;
+        if spins >= TX_SPIN_LIMIT {
+            return;
+        }
+        core::hint::spin_loop();
+    }
+    write_reg(base, UARTDR, byte as u32);
+}
+
+/// Écrit des octets bruts (`\n` → `\r\n`) si la console est active.
+pub fn write_bytes(bytes: &[u8]) {
+    let base = BASE.load(Ordering::Acquire);
+    if base == 0 {
+        return;
+    }
+    for &byte in bytes {
+        if byte == b'\n' {
+            write_raw(base, b'\r');
+        }
+        write_raw(base, byte);
+    }
+}
+
+/// Écrit `value` en hexadécimal (`0x` + 16 chiffres).
+pub fn write_hex(value: u64) {
+    let mut buf = *b"0x0000000000000000";
+    for i in 0..16 {
+        let nibble = ((value >> ((15 - i) * 4)) & 0xF) as u8;
+        buf[2 + i] = if nibble < 10 {
+            b'0' + nibble
+        } else {
+            b'a' + nibble - 10
+        };
+    }
+    write_bytes(&buf);
+}
@@ -0,0 +1,80 @@
+//! # arch/aarch64/context.rs — Changement de contexte noyau AArch64
+//!
+//! Sauvegarde les registres callee-saved de l'AAPCS64 (x19..x28, fp, lr) et
+//! `sp`. Les registres FP/SIMD ne sont pas sauvegardés : le port cible
+//! `aarch64-unknown-none-softfloat`, le noyau n'en utilise pas.
+
+use super::Aarch64;
+use crate::arch::interface::ArchContext;
+
+/// Contexte sauvegardé (disposition figée par `exo_aarch64_switch`).
+#[repr(C)]
+#[derive(Default, Debug)]
+pub struct Context {
+    /// x19..x28.
+    pub callee: [u64; 10],
+    /// x29.
+    pub fp: u64,
+    /// x30 : adresse de reprise.
+    pub lr: u64,
+    pub sp: u64,
+}
+
+const _: () = assert!(core::mem::size_of::<Context>() == 13 * 8);
+
+core::arch::global_asm!(
+    ".section .text",
+    ".global exo_aarch64_switch",
+    ".type exo_aarch64_switch, %function",
+    // x0 = prev, x1 = next
+    "exo_aarch64_switch:",
+    "    mov x9, sp",
+    "    stp x19, x20, [x0, #0]",
+    "    stp x21, x22, [x0, #16]",
+    "    stp x23, x24, [x0, #32]",
+    "    stp x25, x26, [x0, #48]",
+    "    stp x27, x28, [x0, #64]",
+    "    stp x29, x30, [x0, #80]",
+    "    str x9, [x0, #96]",
+    "    ldp x19, x20, [x1, #0]",
+    "    ldp x21, x22, [x1, #16]",
+    "    ldp x23, x24, [x1, #32]",
+    "    ldp x25, x26, [x1, #48]",
+    "    ldp x27, x28, [x1, #64]",
+    "    ldp x29, x30, [x1, #80]",
+    "    ldr x9, [x1, #96]",
+    "    mov sp, x9",
+    "    ret",
+    // Premier lancement : x19 = entrée, x20 = argument (voir `init`).
+    ".global exo_aarch64_thread_start",
+    ".type exo_aarch64_thread_start, %function",
+    "exo_aarch64_thread_start:",
+    "    mov x0, x20",
+    "    blr x19",
+    "1:  wfi",
+    "    b 1b",
+);
+
+extern "C" {
+    fn exo_aarch64_switch(prev: *mut Context, next: *const Context);
+    fn exo_aarch64_thread_start() -> !;
+}
+
+impl ArchContext for Aarch64 {
+    type Context = Context;
+
+    fn init(ctx: &mut Context, entry: extern "C" fn(usize) -> !, arg: usize, stack_top: u64) {
+        *ctx = Context::default();
+        ctx.callee[0] = entry as usize as u64;
+        ctx.callee[1] = arg as u64;
+        ctx.lr = exo_aarch64_thread_start as usize as u64;
+        // AAPCS64 : pile alignée sur 16 octets.
+        ctx.sp = stack_top & !0xF;
+    }
+
+    unsafe fn switch(prev: *mut Context, next: *const Context) {
+        // SAFETY: contrat de `ArchContext::switch` transmis à l'appelant ; la
+        // routine respecte l'AAPCS64 (seuls x9 et les callee-saved sont touchés).
+        unsafe { exo_aarch64_switch(prev, next) }
+    }
+}
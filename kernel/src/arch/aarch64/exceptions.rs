@@ -0,0 +1,162 @@
+//! # arch/aarch64/exceptions.rs — Table de vecteurs EL1 (VBAR_EL1)
+//!
+//! 16 entrées de 0x80 octets (EL courant SP0/SPx, EL inférieur AArch64/
+//! AArch32 × synchrone/IRQ/FIQ/SError). Chaque entrée sauvegarde x0/x1 puis
+//! saute au tronc commun, qui complète un `TrapFrame` sur la pile et appelle
+//! `aarch64_trap_dispatch`.
+//!
+//! Les IRQ passent par le GICv3 ; les exceptions synchrones et SError sont
+//! fatales tant que l'ABI syscall AArch64 n'existe pas.
+
+use super::{console, gic, timer, Aarch64};
+use crate::arch::interface::InterruptController;
+
+/// Registres sauvegardés à l'entrée d'une exception (272 octets).
+#[repr(C)]
+pub struct TrapFrame {
+    pub x: [u64; 31],
+    pub elr: u64,
+    pub spsr: u64,
+    pub esr: u64,
+}
+
+const _: () = assert!(core::mem::size_of::<TrapFrame>() == 272);
+
+/// Type d'exception (`kind & 3`) ; 0 = synchrone, 3 = SError.
+const KIND_IRQ: u64 = 1;
+const KIND_FIQ: u64 = 2;
+
+core::arch::global_asm!(
+    ".macro EXO_VECTOR kind",
+    ".balign 0x80",
+    "    sub sp, sp, #272",
+    "    stp x0, x1, [sp, #0]",
+    "    mov x1, #\\kind",
+    "    b exo_aarch64_trap_common",
+    ".endm",
+    ".section .text",
+    ".balign 0x800",
+    ".global exo_aarch64_vectors",
+    "exo_aarch64_vectors:",
+    "    EXO_VECTOR 0",
+    "    EXO_VECTOR 1",
+    "    EXO_VECTOR 2",
+    "    EXO_VECTOR 3",
+    "    EXO_VECTOR 4",
+    "    EXO_VECTOR 5",
+    "    EXO_VECTOR 6",
+    "    EXO_VECTOR 7",
+    "    EXO_VECTOR 8",
+    "    EXO_VECTOR 9",
+    "    EXO_VECTOR 10",
+    "    EXO_VECTOR 11",
+    "    EXO_VECTOR 12",
+    "    EXO_VECTOR 13",
+    "    EXO_VECTOR 14",
+    "    EXO_VECTOR 15",
+    "exo_aarch64_trap_common:",
+    "    stp x2, x3, [sp, #16]",
+    "    stp x4, x5, [sp, #32]",
+    "    stp x6, x7, [sp, #48]",
+    "    stp x8, x9, [sp, #64]",
+    "    stp x10, x11, [sp, #80]",
+    "    stp x12, x13, [sp, #96]",
+    "    stp x14, x15, [sp, #112]",
+    "    stp x16, x17, [sp, #128]",
+    "    stp x18, x19, [sp, #144]",
+    "    stp x20, x21, [sp, #160]",
+    "    stp x22, x23, [sp, #176]",
+    "    stp x24, x25, [sp, #192]",
+    "    stp x26, x27, [sp, #208]",
+    "    stp x28, x29, [sp, #224]",
+    "    mrs x2, elr_el1",
+    "    stp x30, x2, [sp, #240]",
+    "    mrs x2, spsr_el1",
+    "    mrs x3, esr_el1",
+    "    stp x2, x3, [sp, #256]",
+    "    mov x0, sp",
+    "    bl aarch64_trap_dispatch",
+    "    ldp x30, x2, [sp, #240]",
+    "    ldr x3, [sp, #256]",
+    "    msr elr_el1, x2",
+    "    msr spsr_el1, x3",
+    "    ldp x2, x3, [sp, #16]",
+    "    ldp x4, x5, [sp, #32]",
+    "    ldp x6, x7, [sp, #48]",
+    "    ldp x8, x9, [sp, #64]",
+    "    ldp x10, x11, [sp, #80]",
+    "    ldp x12, x13, [sp, #96]",
+    "    ldp x14, x15, [sp, #112]",
+    "    ldp x16, x17, [sp, #128]",
+    "    ldp x18, x19, [sp, #144]",
+    "    ldp x20, x21, [sp, #160]",
+    "    ldp x22, x23, [sp, #176]",
+    "    ldp x24, x25, [sp, #192]",
+    "    ldp x26, x27, [sp, #208]",
+    "    ldp x28, x29, [sp, #224]",
+    "    ldp x0, x1, [sp, #0]",
+    "    add sp, sp, #272",
+    "    eret",
+);
+
+extern "C" {
+    static exo_aarch64_vectors: u8;
+}
+
+/// Installe la table de vecteurs dans VBAR_EL1.
+pub fn install() {
+    // SAFETY: la table est alignée sur 2 KiB et reste en mémoire pour toute
+    // la vie du noyau.
+    unsafe {
+        core::arch::asm!(
+            "msr vbar_el1, {}",
+            "isb",
+            in(reg) core::ptr::addr_of!(exo_aarch64_vectors) as u64,
+            options(nostack),
+        );
+    }
+}
+
+fn handle_irq() {
+    let intid = gic::acknowledge();
+    if intid >= gic::INTID_SPURIOUS_FIRST {
+        return;
+    }
+    if intid == timer::VIRTUAL_TIMER_PPI {
+        timer::handle_irq();
+    } else {
+        console::write_bytes(b"[AARCH64] IRQ non geree ");
+        console::write_hex(intid as u64);
+        console::write_bytes(b"\n");
+    }
+    Aarch64::end_of_interrupt(intid);
+}
+
+fn fatal(frame: &TrapFrame, kind: u64) -> ! {
+    let far: u64;
+    // SAFETY: lecture FAR_EL1, sans effet de bord.
+    unsafe {
+        core::arch::asm!("mrs {}, far_el1", out(reg) far, options(nostack, nomem));
+    }
+    console::write_bytes(b"[AARCH64] exception fatale kind=");
+    console::write_hex(kind);
+    console::write_bytes(b" esr=");
+    console::write_hex(frame.esr);
+    console::write_bytes(b" elr=");
+    console::write_hex(frame.elr);
+    console::write_bytes(b" far=");
+    console::write_hex(far);
+    console::write_bytes(b"\n");
+    super::halt_cpu()
+}
+
+/// Point d'entrée Rust des vecteurs (`kind` = index 0..15 de l'entrée).
+#[no_mangle]
+extern "C" fn aarch64_trap_dispatch(frame: &mut TrapFrame, kind: u64) {
+    match kind & 3 {
+        KIND_IRQ => handle_irq(),
+        // Aucune FIQ n'est routée en Groupe 0 : ignorée.
+        KIND_FIQ => {}
+        _ => fatal(frame, kind),
+    }
+}
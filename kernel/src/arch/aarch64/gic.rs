@@ -0,0 +1,287 @@
+//! # arch/aarch64/gic.rs — Contrôleur d'interruptions GICv3
+//!
+//! Distributeur (GICD, SPI 32..1019), redistributeur du CPU courant (GICR,
+//! SGI/PPI 0..31) et interface CPU par registres système `ICC_*_EL1`.
+//! Toutes les lignes sont en Groupe 1 non sécurisé, routage par affinité
+//! (ARE_NS). Seul le CPU de boot est initialisé : les secondaires restent
+//! parqués par `boot.rs`.
+//!
+//! Les bases MMIO viennent du nœud `arm,gic-v3` du device tree
+//! (`reg` = GICD puis GICR).
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use super::Aarch64;
+use crate::arch::fdt::Fdt;
+use crate::arch::interface::InterruptController;
+
+// ── Distributeur ──────────────────────────────────────────────────────────────
+
+const GICD_CTLR: u64 = 0x0000;
+const GICD_TYPER: u64 = 0x0004;
+const GICD_IGROUPR: u64 = 0x0080;
+const GICD_ISENABLER: u64 = 0x0100;
+const GICD_ICENABLER: u64 = 0x0180;
+const GICD_IPRIORITYR: u64 = 0x0400;
+const GICD_IROUTER: u64 = 0x6000;
+
+const GICD_CTLR_ENABLE_G1NS: u32 = 1 << 1;
+const GICD_CTLR_ARE_NS: u32 = 1 << 4;
+const GICD_CTLR_RWP: u32 = 1 << 31;
+
+// ── Redistributeur ────────────────────────────────────────────────────────────
+
+/// Taille d'un redistributeur GICv3 (RD_base + SGI_base).
+const GICR_FRAME_STRIDE: u64 = 0x2_0000;
+const GICR_SGI_OFFSET: u64 = 0x1_0000;
+const GICR_TYPER: u64 = 0x0008;
+const GICR_WAKER: u64 = 0x0014;
+const GICR_TYPER_LAST: u64 = 1 << 4;
+const GICR_WAKER_PROCESSOR_SLEEP: u32 = 1 << 1;
+const GICR_WAKER_CHILDREN_ASLEEP: u32 = 1 << 2;
+
+/// Redistributeurs parcourus au plus pour trouver le CPU courant.
+const GICR_MAX_FRAMES: u64 = 512;
+
+/// Priorité par défaut (plus petit = plus prioritaire).
+const DEFAULT_PRIORITY: u8 = 0xA0;
+
+/// Premier INTID spécial (1020..1023) : aucune interruption en attente.
+pub const INTID_SPURIOUS_FIRST: u32 = 1020;
+/// Premier INTID de SPI.
+const SPI_FIRST: u32 = 32;
+
+/// Échec d'initialisation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GicError {
+    /// Pas de nœud `arm,gic-v3` (ou `reg` incomplet).
+    NotFound,
+    /// Aucun redistributeur ne correspond au MPIDR courant.
+    NoRedistributor,
+}
+
+static GICD_BASE: AtomicU64 = AtomicU64::new(0);
+/// Frame SGI_base du redistributeur du CPU de boot.
+static GICR_SGI_BASE: AtomicU64 = AtomicU64::new(0);
+
+#[inline(always)]
+fn read32(addr: u64) -> u32 {
+    // SAFETY: adresse GIC issue du device tree, mappée Device par l'identité.
+    unsafe { core::ptr::read_volatile(addr as *const u32) }
+}
+
+#[inline(always)]
+fn write32(addr: u64, value: u32) {
+    // SAFETY: idem `read32`.
+    unsafe { core::ptr::write_volatile(addr as *mut u32, value) }
+}
+
+#[inline(always)]
+fn read64(addr: u64) -> u64 {
+    // SAFETY: idem `read32`.
+    unsafe { core::ptr::read_volatile(addr as *const u64) }
+}
+
+#[inline(always)]
+fn write64(addr: u64, value: u64) {
+    // SAFETY: idem `read32`.
+    unsafe { core::ptr::write_volatile(addr as *mut u64, value) }
+}
+
+/// Affinité MPIDR empaquetée au format GICR_TYPER / GICD_IROUTER
+/// (`Aff3:Aff2:Aff1:Aff0`, 8 bits chacun).
+fn mpidr_affinity() -> u32 {
+    let mpidr = super::read_mpidr();
+    ((mpidr >> 8) & 0xFF00_0000) as u32 | (mpidr & 0x00FF_FFFF) as u32
+}
+
+fn wait_rwp(gicd: u64) {
+    while read32(gicd + GICD_CTLR) & GICD_CTLR_RWP != 0 {
+        core::hint::spin_loop();
+    }
+}
+
+fn init_distributor(gicd: u64) {
+    write32(gicd + GICD_CTLR, 0);
+    wait_rwp(gicd);
+    let lines = ((read32(gicd + GICD_TYPER) & 0x1F) + 1) * 32;
+    let lines = lines.min(INTID_SPURIOUS_FIRST);
+    for reg in (SPI_FIRST / 32)..(lines / 32) {
+        let off = reg as u64 * 4;
+        write32(gicd + GICD_ICENABLER + off, u32::MAX);
+        write32(gicd + GICD_IGROUPR + off, u32::MAX);
+    }
+    for intid in SPI_FIRST..lines {
+        // SAFETY: IPRIORITYR est accessible à l'octet (spécification GICv3).
+        unsafe {
+            core::ptr::write_volatile(
+                (gicd + GICD_IPRIORITYR + intid as u64) as *mut u8,
+                DEFAULT_PRIORITY,
+            );
+        }
+    }
+    write32(gicd + GICD_CTLR, GICD_CTLR_ARE_NS | GICD_CTLR_ENABLE_G1NS);
+    wait_rwp(gicd);
+}
+
+/// Trouve le redistributeur du CPU courant ; retourne sa base RD.
+fn find_redistributor(gicr: u64) -> Option<u64> {
+    let affinity = mpidr_affinity() as u64;
+    for frame in 0..GICR_MAX_FRAMES {
+        let rd = gicr + frame * GICR_FRAME_STRIDE;
+        let typer = read64(rd + GICR_TYPER);
+        if typer >> 32 == affinity {
+            return Some(rd);
+        }
+        if typer & GICR_TYPER_LAST != 0 {
+            break;
+        }
+    }
+    None
+}
+
+fn init_redistributor(rd: u64) -> u64 {
+    let waker = read32(rd + GICR_WAKER);
+    write32(rd + GICR_WAKER, waker & !GICR_WAKER_PROCESSOR_SLEEP);
+    while read32(rd + GICR_WAKER) & GICR_WAKER_CHILDREN_ASLEEP != 0 {
+        core::hint::spin_loop();
+    }
+    let sgi = rd + GICR_SGI_OFFSET;
+    write32(sgi + GICD_ICENABLER, u32::MAX);
+    write32(sgi + GICD_IGROUPR, u32::MAX);
+    for intid in 0..SPI_FIRST {
+        // SAFETY: GICR_IPRIORITYR<n> a la même disposition que GICD.
+        unsafe {
+            core::ptr::write_volatile(
+                (sgi + GICD_IPRIORITYR + intid as u64) as *mut u8,
+                DEFAULT_PRIORITY,
+            );
+        }
+    }
+    sgi
+}
+
+fn init_cpu_interface() {
+    // SAFETY: registres ICC accessibles à EL1 (ICC_SRE_EL2.Enable posé par
+    // boot.rs) ; on active l'accès système, démasque toutes les priorités et
+    // le Groupe 1.
+    unsafe {
+        core::arch::asm!(
+            "mrs {tmp}, icc_sre_el1",
+            "orr {tmp}, {tmp}, #1",
+            "msr icc_sre_el1, {tmp}",
+            "isb",
+            "msr icc_pmr_el1, {pmr}",
+            "msr icc_bpr1_el1, xzr",
+            "msr icc_igrpen1_el1, {one}",
+            "isb",
+            tmp = out(reg) _,
+            pmr = in(reg) 0xFFu64,
+            one = in(reg) 1u64,
+            options(nostack),
+        );
+    }
+}
+
+/// Initialise GICD, le GICR du CPU de boot et l'interface CPU.
+pub fn init_from_fdt(fdt: &Fdt<'_>) -> Result<(), GicError> {
+    let node = fdt
+        .find_compatible(b"arm,gic-v3")
+        .ok_or(GicError::NotFound)?;
+    let mut regs = node.reg();
+    let (gicd, _) = regs.next().ok_or(GicError::NotFound)?;
+    let (gicr, _) = regs.next().ok_or(GicError::NotFound)?;
+    init_distributor(gicd);
+    let rd = find_redistributor(gicr).ok_or(GicError::NoRedistributor)?;
+    GICD_BASE.store(gicd, Ordering::Release);
+    GICR_SGI_BASE.store(init_redistributor(rd), Ordering::Release);
+    init_cpu_interface();
+    Ok(())
+}
+
+/// Acquitte l'interruption la plus prioritaire (ICC_IAR1_EL1).
+///
+/// Un résultat ≥ `INTID_SPURIOUS_FIRST` signifie « rien à traiter » et ne
+/// doit pas recevoir d'EOI.
+#[inline]
+pub fn acknowledge() -> u32 {
+    let iar: u64;
+    // SAFETY: lecture ICC_IAR1_EL1 depuis le gestionnaire d'IRQ EL1.
+    unsafe {
+        core::arch::asm!("mrs {}, icc_iar1_el1", out(reg) iar, options(nostack, nomem));
+    }
+    (iar & 0x00FF_FFFF) as u32
+}
+
+/// Encode ICC_SGI1R_EL1 pour l'INTID `intid` (0..15) vers `affinity`
+/// (`Aff2:Aff1:Aff0`).
+fn sgi1r_value(affinity: u32, intid: u32) -> u64 {
+    let aff0 = (affinity & 0xFF) as u64;
+    let aff1 = ((affinity >> 8) & 0xFF) as u64;
+    let aff2 = ((affinity >> 16) & 0xFF) as u64;
+    (1u64 << (aff0 & 0xF))
+        | (aff1 << 16)
+        | (((intid & 0xF) as u64) << 24)
+        | (aff2 << 32)
+        | ((aff0 >> 4) << 44)
+}
+
+/// Registre et bit d'enable pour `irq` : frame SGI du GICR (< 32) ou GICD.
+fn enable_reg(irq: u32, set: bool) -> Option<(u64, u32)> {
+    let (base, off) = if irq < SPI_FIRST {
+        (GICR_SGI_BASE.load(Ordering::Acquire), 0)
+    } else {
+        (GICD_BASE.load(Ordering::Acquire), (irq / 32) as u64 * 4)
+    };
+    if base == 0 || irq >= INTID_SPURIOUS_FIRST {
+        return None;
+    }
+    let reg = if set { GICD_ISENABLER } else { GICD_ICENABLER };
+    Some((base + reg + off, 1 << (irq % 32)))
+}
+
+impl InterruptController for Aarch64 {
+    #[inline]
+    fn end_of_interrupt(irq: u32) {
+        // SAFETY: EOI de l'INTID retourné par `acknowledge`.
+        unsafe {
+            core::arch::asm!("msr icc_eoir1_el1, {}", in(reg) irq as u64, options(nostack, nomem));
+        }
+    }
+
+    fn mask(irq: u32) {
+        if let Some((reg, bit)) = enable_reg(irq, false) {
+            write32(reg, bit);
+        }
+    }
+
+    /// Les SPI sont routées vers le CPU courant avant d'être démasquées.
+    fn unmask(irq: u32) {
+        if irq >= SPI_FIRST && irq < INTID_SPURIOUS_FIRST {
+            let gicd = GICD_BASE.load(Ordering::Acquire);
+            if gicd != 0 {
+                let affinity = mpidr_affinity() as u64;
+                let route = ((affinity >> 24) << 32) | (affinity & 0x00FF_FFFF);
+                write64(gicd + GICD_IROUTER + irq as u64 * 8, route);
+            }
+        }
+        if let Some((reg, bit)) = enable_reg(irq, true) {
+            write32(reg, bit);
+        }
+    }
+
+    /// `cpu` = affinité `Aff2:Aff1:Aff0`, `irq` = SGI 0..15.
+    fn send_ipi(cpu: u32, irq: u32) {
+        // SAFETY: écriture ICC_SGI1R_EL1 ; barrière pour ordonner les écritures
+        // mémoire précédentes avant la génération de la SGI.
+        unsafe {
+            core::arch::asm!(
+                "dsb ishst",
+                "msr icc_sgi1r_el1, {}",
+                "isb",
+                in(reg) sgi1r_value(cpu, irq),
+                options(nostack),
+            );
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! # arch/aarch64/mmu.rs — MMU EL1 (VMSAv8-64, granule 4 KiB, VA 48 bits)
+//!
+//! Programme MAIR_EL1/TCR_EL1 et installe une identité de boot en blocs de
+//! 1 GiB sur les 512 premiers GiB : la RAM décrite par `/memory` est en
+//! Normal Write-Back, le reste en Device-nGnRE (GIC, UART…). TTBR1 (moitié
+//! haute) reste désactivé tant que le noyau n'est pas relié en high-half.
+//!
+//! L'encodage des descripteurs est partagé avec le mapper générique :
+//! `memory::virt::page_table::Aarch64Format`.
+
+use super::Aarch64;
+use crate::arch::fdt::Fdt;
+use crate::arch::interface::ArchMmu;
+use crate::memory::core::PageFlags;
+use crate::memory::virt::page_table::aarch64::MAIR_EL1_VALUE;
+use crate::memory::virt::page_table::format::RawTable;
+use crate::memory::virt::page_table::{Aarch64Format, PageTableFormat};
+
+const BLOCK_1G: u64 = 1 << 30;
+
+// TCR_EL1 : T0SZ = 16 (48 bits), walks Inner Shareable WB-WA, TG0 = 4 KiB,
+// EPD1 = 1 (TTBR1 inutilisé), IPS renseigné depuis ID_AA64MMFR0_EL1.
+const TCR_T0SZ_48: u64 = 16;
+const TCR_IRGN0_WBWA: u64 = 0b01 << 8;
+const TCR_ORGN0_WBWA: u64 = 0b01 << 10;
+const TCR_SH0_INNER: u64 = 0b11 << 12;
+const TCR_T1SZ_48: u64 = 16 << 16;
+const TCR_EPD1: u64 = 1 << 23;
+const TCR_TG1_4K: u64 = 0b10 << 30;
+const TCR_IPS_SHIFT: u64 = 32;
+
+const SCTLR_M: u64 = 1 << 0;
+const SCTLR_C: u64 = 1 << 2;
+const SCTLR_I: u64 = 1 << 12;
+
+/// Masque de la base de table dans TTBRn_EL1 (ASID exclu).
+const TTBR_BADDR_MASK: u64 = 0x0000_FFFF_FFFF_F000;
+
+#[repr(C, align(4096))]
+struct BootTable(RawTable);
+
+// Tables de l'identité de boot : L0 → L1 (blocs 1 GiB), écrites une seule
+// fois par le CPU de boot, MMU éteinte.
+static mut BOOT_L0: BootTable = BootTable([0; 512]);
+static mut BOOT_L1: BootTable = BootTable([0; 512]);
+
+fn tcr_value() -> u64 {
+    let mmfr0: u64;
+    // SAFETY: lecture d'un registre d'identification, sans effet de bord.
+    unsafe {
+        core::arch::asm!("mrs {}, id_aa64mmfr0_el1", out(reg) mmfr0, options(nostack, nomem));
+    }
+    let ips = (mmfr0 & 0x7).min(0b101);
+    TCR_T0SZ_48
+        | TCR_IRGN0_WBWA
+        | TCR_ORGN0_WBWA
+        | TCR_SH0_INNER
+        | TCR_T1SZ_48
+        | TCR_EPD1
+        | TCR_TG1_4K
+        | (ips << TCR_IPS_SHIFT)
+}
+
+/// Construit l'identité de boot et active MMU + caches.
+///
+/// # Safety
+/// Appel unique, sur le CPU de boot, MMU éteinte et image chargée dans la
+/// RAM décrite par le device tree.
+pub unsafe fn enable_identity(fdt: &Fdt<'_>) {
+    // SAFETY: appel unique garanti par l'appelant ; aucune autre référence
+    // aux tables n'existe.
+    let (l0, l1) = unsafe {
+        (
+            &mut (*core::ptr::addr_of_mut!(BOOT_L0)).0,
+            &mut (*core::ptr::addr_of_mut!(BOOT_L1)).0,
+        )
+    };
+    for (i, entry) in l1.iter_mut().enumerate() {
+        let base = i as u64 * BLOCK_1G;
+        let flags = if fdt.overlaps_ram(base, BLOCK_1G) {
+            PageFlags::KERNEL_CODE.set(PageFlags::WRITABLE)
+        } else {
+            PageFlags::KERNEL_DMA
+        };
+        *entry = Aarch64Format::block_entry(base, flags);
+    }
+    l0[0] = Aarch64Format::table_entry(l1.as_ptr() as u64);
+    let root = l0.as_ptr() as u64;
+
+    // SAFETY: MMU éteinte : l'identité couvre le code courant, la pile et les
+    // MMIO ; TLB invalidé avant l'activation.
+    unsafe {
+        core::arch::asm!(
+            "msr mair_el1, {mair}",
+            "msr tcr_el1, {tcr}",
+            "msr ttbr0_el1, {root}",
+            "isb",
+            "tlbi vmalle1",
+            "dsb ish",
+            "isb",
+            "mrs {tmp}, sctlr_el1",
+            "orr {tmp}, {tmp}, {bits}",
+            "msr sctlr_el1, {tmp}",
+            "isb",
+            mair = in(reg) MAIR_EL1_VALUE,
+            tcr = in(reg) tcr_value(),
+            root = in(reg) root,
+            bits = in(reg) SCTLR_M | SCTLR_C | SCTLR_I,
+            tmp = out(reg) _,
+            options(nostack),
+        );
+    }
+}
+
+impl ArchMmu for Aarch64 {
+    type Format = Aarch64Format;
+
+    #[inline]
+    fn current_root() -> u64 {
+        let ttbr0: u64;
+        // SAFETY: lecture de TTBR0_EL1 à EL1.
+        unsafe {
+            core::arch::asm!("mrs {}, ttbr0_el1", out(reg) ttbr0, options(nostack, nomem));
+        }
+        ttbr0 & TTBR_BADDR_MASK
+    }
+
+    unsafe fn activate(root_phys: u64) {
+        // SAFETY: contrat de `ArchMmu::activate` transmis à l'appelant ; sans
+        // ASID, le changement de racine impose l'invalidation complète.
+        unsafe {
+            core::arch::asm!(
+                "dsb ishst",
+                "msr ttbr0_el1, {}",
+                "isb",
+                in(reg) root_phys & TTBR_BADDR_MASK,
+                options(nostack),
+            );
+        }
+        super::flush_tlb();
+    }
+
+    #[inline]
+    fn flush_page(virt: u64) {
+        super::flush_tlb_page(virt);
+    }
+
+    #[inline]
+    fn flush_all() {
+        super::flush_tlb();
+    }
+}
+
+// Le format doit rester celui attendu par TCR_EL1 ci-dessus.
+const _: () = assert!(Aarch64Format::LEVELS == 4 && Aarch64Format::VA_BITS == 48);
@@ -1,12 +1,32 @@
-//! # arch/aarch64 — Placeholder AArch64
+//! # arch/aarch64 — Port AArch64 (scaffolding)
 //!
-//! Module d'architecture minimal pour les cibles AArch64 (ARMv8-A 64 bits).
+//! Module d'architecture pour les cibles AArch64 (ARMv8-A 64 bits), cible
+//! `aarch64-unknown-none-softfloat`, image liée par `linker.ld`.
 //!
 //! ## État
-//! ExoOS v0.2.0 ne declare pas AArch64 comme cible de boot supportee : pas de
-//! VBAR/EL1 complet, pas de GIC/APIC equivalent, pas de SMP ni ABI syscall
-//! AArch64. Les primitives ci-dessous restent localisees pour un futur port ;
-//! le refus de cible se fait au niveau crate, pas dans ce module.
+//! Présent : boot par device tree (`boot.rs`, EL2 → EL1), vecteurs EL1
+//! (`exceptions.rs`), GICv3 (`gic.rs`), timer générique virtuel (`timer.rs`),
+//! identité MMU 1 GiB (`mmu.rs`), changement de contexte (`context.rs`) et
+//! console PL011 (`console.rs`), le tout derrière `arch::interface`
+//! (`Aarch64`).
+//!
+//! Absent : SMP (secondaires parqués), ABI syscall AArch64, pilotes, et le
+//! portage des couches memory/scheduler encore écrites contre x86_64. ExoOS
+//! v0.2.0 ne déclare donc pas AArch64 comme cible de boot supportée ; le
+//! refus de cible se fait au niveau crate, pas dans ce module.
+
+pub mod boot;
+pub mod console;
+pub mod context;
+pub mod exceptions;
+pub mod gic;
+pub mod mmu;
+pub mod timer;
+
+use crate::arch::interface::{Arch, ArchCpu};
+
+/// Architecture AArch64 (type vide, méthodes associées).
+pub struct Aarch64;
 
 // ── Primitives de base ────────────────────────────────────────────────────────
 
@@ -146,7 +166,7 @@ pub fn arch_info() -> ArchInfo {
 }
 
 /// Lit la fréquence du compteur générique (CNTFRQ_EL0)
-fn read_cntfrq() -> u64 {
+pub fn read_cntfrq() -> u64 {
     let val: u32;
     // SAFETY: CNTFRQ_EL0 lisible depuis EL0/EL1
     unsafe {
@@ -154,3 +174,42 @@ fn read_cntfrq() -> u64 {
     }
     val as u64
 }
+
+/// Lit MPIDR_EL1 (affinité du CPU courant).
+#[inline(always)]
+pub fn read_mpidr() -> u64 {
+    let val: u64;
+    // SAFETY: MPIDR_EL1 lisible à EL1, sans effet de bord
+    unsafe {
+        core::arch::asm!("mrs {}, mpidr_el1", out(reg) val, options(nostack, nomem));
+    }
+    val
+}
+
+// ── arch::interface ──────────────────────────────────────────────────────────
+
+impl ArchCpu for Aarch64 {
+    /// Aff0 de MPIDR_EL1 (topologie à un seul cluster pour l'instant).
+    #[inline]
+    fn cpu_id() -> u32 {
+        (read_mpidr() & 0xFF) as u32
+    }
+
+    #[inline]
+    fn irq_save() -> u64 {
+        irq_save()
+    }
+
+    #[inline]
+    fn irq_restore(state: u64) {
+        irq_restore(state)
+    }
+
+    fn halt() -> ! {
+        halt_cpu()
+    }
+}
+
+impl Arch for Aarch64 {
+    const NAME: &'static str = "aarch64";
+}
@@ -0,0 +1,70 @@
+//! # arch/aarch64/timer.rs — Timer générique ARM (virtuel)
+//!
+//! Compteur `CNTVCT_EL0` à `CNTFRQ_EL0` Hz et échéance one-shot par
+//! `CNTV_TVAL_EL0`. L'interruption est la PPI 27 (timer virtuel EL1),
+//! délivrée par le GICv3.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use super::Aarch64;
+use crate::arch::interface::{ns_to_ticks, ArchTimer, InterruptController};
+
+/// INTID de la PPI du timer virtuel.
+pub const VIRTUAL_TIMER_PPI: u32 = 27;
+
+const CNTV_CTL_ENABLE: u64 = 1 << 0;
+const CNTV_CTL_IMASK: u64 = 1 << 1;
+
+/// TVAL est signé sur 32 bits.
+const TVAL_MAX: u64 = i32::MAX as u64;
+
+static FREQUENCY_HZ: AtomicU64 = AtomicU64::new(0);
+static TICKS: AtomicU64 = AtomicU64::new(0);
+
+#[inline(always)]
+fn write_ctl(value: u64) {
+    // SAFETY: CNTV_CTL_EL0 accessible à EL1 ; seul le timer virtuel est touché.
+    unsafe {
+        core::arch::asm!("msr cntv_ctl_el0, {}", "isb", in(reg) value, options(nostack, nomem));
+    }
+}
+
+/// Lit CNTFRQ_EL0, masque le timer et démasque sa PPI au GIC.
+pub fn init() {
+    FREQUENCY_HZ.store(super::read_cntfrq(), Ordering::Release);
+    write_ctl(CNTV_CTL_IMASK);
+    Aarch64::unmask(VIRTUAL_TIMER_PPI);
+}
+
+/// Gestionnaire de la PPI : désarme l'échéance (one-shot).
+pub fn handle_irq() {
+    write_ctl(CNTV_CTL_IMASK);
+    TICKS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Échéances expirées depuis le boot.
+#[inline]
+pub fn ticks() -> u64 {
+    TICKS.load(Ordering::Relaxed)
+}
+
+impl ArchTimer for Aarch64 {
+    #[inline]
+    fn counter() -> u64 {
+        super::read_tsc()
+    }
+
+    #[inline]
+    fn frequency_hz() -> u64 {
+        FREQUENCY_HZ.load(Ordering::Acquire)
+    }
+
+    fn arm_oneshot_ns(ns: u64) {
+        let ticks = ns_to_ticks(ns, Self::frequency_hz()).clamp(1, TVAL_MAX);
+        // SAFETY: CNTV_TVAL_EL0 accessible à EL1.
+        unsafe {
+            core::arch::asm!("msr cntv_tval_el0, {}", in(reg) ticks, options(nostack, nomem));
+        }
+        write_ctl(CNTV_CTL_ENABLE);
+    }
+}
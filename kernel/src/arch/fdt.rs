@@ -0,0 +1,610 @@
+//! # arch/fdt.rs — Lecteur de Flattened Device Tree (DTB)
+//!
+//! Parseur en lecture seule, sans allocation, du format DTB v17 (spécification
+//! Devicetree 0.4) : validation de l'en-tête, parcours des nœuds en profondeur,
+//! propriétés, `reg` selon `#address-cells`/`#size-cells` du parent, recherche
+//! par chemin ou par `compatible`, `/memory` et `/chosen/bootargs`.
+//!
+//! Indépendant de l'architecture : utilisé par les boots AArch64 (x0 = DTB) et
+//! RISC-V (a1 = DTB), et par tout futur port démarré par un firmware à device
+//! tree.
+
+/// Magic d'en-tête (big-endian).
+pub const FDT_MAGIC: u32 = 0xD00D_FEED;
+
+/// Version la plus récente comprise ; les blobs compatibles v16 sont acceptés.
+const FDT_VERSION: u32 = 17;
+const FDT_MIN_COMPAT_VERSION: u32 = 16;
+
+const HEADER_SIZE: usize = 40;
+
+const FDT_BEGIN_NODE: u32 = 0x1;
+const FDT_END_NODE: u32 = 0x2;
+const FDT_PROP: u32 = 0x3;
+const FDT_NOP: u32 = 0x4;
+const FDT_END: u32 = 0x9;
+
+/// Profondeur max suivie pour les `#address-cells`/`#size-cells`.
+const MAX_DEPTH: usize = 16;
+
+/// Valeurs par défaut imposées par la spécification.
+const DEFAULT_ADDRESS_CELLS: u32 = 2;
+const DEFAULT_SIZE_CELLS: u32 = 1;
+
+/// Erreur de validation d'un blob.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FdtError {
+    /// Magic absent : ce n'est pas un DTB.
+    BadMagic,
+    /// Version incompatible avec le format v17.
+    BadVersion,
+    /// Blob plus court que l'en-tête ou que `totalsize`.
+    Truncated,
+    /// Bloc structure ou chaînes hors du blob.
+    BadOffset,
+}
+
+#[inline]
+fn be32(data: &[u8], off: usize) -> Option<u32> {
+    let bytes = data.get(off..off.checked_add(4)?)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[inline]
+fn align4(off: usize) -> usize {
+    (off + 3) & !3
+}
+
+/// Chaîne terminée par NUL à `off` (sans le NUL).
+fn cstr(data: &[u8], off: usize) -> Option<&[u8]> {
+    let rest = data.get(off..)?;
+    let len = rest.iter().position(|&b| b == 0)?;
+    Some(&rest[..len])
+}
+
+/// Lit un nombre de `cells` cellules 32 bits big-endian (1 ou 2 utiles).
+fn read_cells(data: &[u8], cells: u32) -> Option<u64> {
+    if data.len() < cells as usize * 4 {
+        return None;
+    }
+    let mut value = 0u64;
+    for i in 0..cells as usize {
+        value = (value << 32) | be32(data, i * 4)? as u64;
+    }
+    Some(value)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// BLOB
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Device tree validé.
+#[derive(Copy, Clone)]
+pub struct Fdt<'a> {
+    structs: &'a [u8],
+    strings: &'a [u8],
+    boot_cpuid: u32,
+}
+
+impl<'a> Fdt<'a> {
+    /// Valide l'en-tête de `blob` (qui peut dépasser `totalsize`).
+    pub fn new(blob: &'a [u8]) -> Result<Self, FdtError> {
+        let header = |idx: usize| be32(blob, idx * 4).ok_or(FdtError::Truncated);
+        if header(0)? != FDT_MAGIC {
+            return Err(FdtError::BadMagic);
+        }
+        let total = header(1)? as usize;
+        if total < HEADER_SIZE || blob.len() < total {
+            return Err(FdtError::Truncated);
+        }
+        if header(5)? < FDT_MIN_COMPAT_VERSION || header(6)? > FDT_VERSION {
+            return Err(FdtError::BadVersion);
+        }
+        let blob = &blob[..total];
+        let region = |off: u32, size: u32| {
+            let start = off as usize;
+            let end = start
+                .checked_add(size as usize)
+                .ok_or(FdtError::BadOffset)?;
+            blob.get(start..end).ok_or(FdtError::BadOffset)
+        };
+        Ok(Self {
+            structs: region(header(2)?, header(9)?)?,
+            strings: region(header(3)?, header(8)?)?,
+            boot_cpuid: header(7)?,
+        })
+    }
+
+    /// Valide le DTB situé à `ptr` (taille lue dans l'en-tête).
+    ///
+    /// # Safety
+    /// `ptr` doit pointer vers une mémoire lisible d'au moins `totalsize`
+    /// octets, valide pour `'a`.
+    pub unsafe fn from_ptr(ptr: *const u8) -> Result<Self, FdtError> {
+        if ptr.is_null() {
+            return Err(FdtError::BadMagic);
+        }
+        // SAFETY: l'appelant garantit au moins un en-tête lisible.
+        let head = unsafe { core::slice::from_raw_parts(ptr, 8) };
+        if be32(head, 0) != Some(FDT_MAGIC) {
+            return Err(FdtError::BadMagic);
+        }
+        let total = be32(head, 4).unwrap_or(0) as usize;
+        // SAFETY: `totalsize` octets lisibles garantis par l'appelant.
+        Self::new(unsafe { core::slice::from_raw_parts(ptr, total) })
+    }
+
+    /// CPU physique de démarrage indiqué par le firmware.
+    #[inline]
+    pub fn boot_cpuid(&self) -> u32 {
+        self.boot_cpuid
+    }
+
+    /// Parcours en profondeur de tous les nœuds (la racine en premier).
+    pub fn nodes(&self) -> NodeIter<'a> {
+        NodeIter {
+            fdt: *self,
+            offset: 0,
+            depth: 0,
+            cells: [(DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS); MAX_DEPTH],
+        }
+    }
+
+    /// Nœud au chemin absolu `path` (`/`, `/chosen`, `/soc/uart`…).
+    ///
+    /// Un composant sans `@` correspond à tout nœud de ce nom, quelle que soit
+    /// son adresse d'unité.
+    pub fn find_path(&self, path: &str) -> Option<FdtNode<'a>> {
+        let mut components = [&[][..]; MAX_DEPTH];
+        let mut count = 0;
+        for part in path
+            .as_bytes()
+            .split(|&b| b == b'/')
+            .filter(|p| !p.is_empty())
+        {
+            *components.get_mut(count)? = part;
+            count += 1;
+        }
+        let mut matched = 0usize;
+        for node in self.nodes() {
+            let depth = node.depth();
+            if depth == 0 {
+                if count == 0 {
+                    return Some(node);
+                }
+                continue;
+            }
+            matched = matched.min(depth - 1);
+            if depth == matched + 1 && node.name_matches(components[matched]) {
+                matched += 1;
+                if matched == count {
+                    return Some(node);
+                }
+            }
+        }
+        None
+    }
+
+    /// Premier nœud dont la liste `compatible` contient `compatible`.
+    pub fn find_compatible(&self, compatible: &[u8]) -> Option<FdtNode<'a>> {
+        self.nodes().find(|node| node.is_compatible(compatible))
+    }
+
+    /// Premier nœud `device_type = "memory"` (ou nommé `memory`).
+    pub fn memory(&self) -> Option<FdtNode<'a>> {
+        self.nodes().find(|node| {
+            node.property(b"device_type") == Some(b"memory\0") || node.name_matches(b"memory")
+        })
+    }
+
+    /// Vrai si `[base, base + size)` chevauche une région `reg` d'un nœud
+    /// `device_type = "memory"` (identités de boot des ports).
+    pub fn overlaps_ram(&self, base: u64, size: u64) -> bool {
+        let end = base.saturating_add(size);
+        self.nodes()
+            .filter(|node| node.property(b"device_type") == Some(b"memory\0"))
+            .flat_map(|node| node.reg())
+            .any(|(start, len)| start < end && base < start.saturating_add(len))
+    }
+
+    /// Ligne de commande `/chosen/bootargs` (sans le NUL final).
+    pub fn bootargs(&self) -> Option<&'a [u8]> {
+        self.find_path("/chosen")?.property_str(b"bootargs")
+    }
+
+    /// Chemin `/chosen/stdout-path` (console firmware), options `:` retirées.
+    pub fn stdout_path(&self) -> Option<&'a [u8]> {
+        let path = self.find_path("/chosen")?.property_str(b"stdout-path")?;
+        Some(path.split(|&b| b == b':').next().unwrap_or(path))
+    }
+
+    fn token(&self, off: usize) -> Option<u32> {
+        be32(self.structs, off)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// NŒUDS
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Nœud du device tree.
+#[derive(Copy, Clone)]
+pub struct FdtNode<'a> {
+    fdt: Fdt<'a>,
+    name: &'a [u8],
+    /// Offset (bloc structure) du premier jeton après le nom.
+    props: usize,
+    depth: usize,
+    /// `#address-cells`/`#size-cells` du parent (pour `reg`).
+    address_cells: u32,
+    size_cells: u32,
+}
+
+impl<'a> FdtNode<'a> {
+    /// Nom complet (`uart@9000000`) ; vide pour la racine.
+    #[inline]
+    pub fn name(&self) -> &'a [u8] {
+        self.name
+    }
+
+    /// Profondeur (0 = racine).
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Vrai si `pattern` désigne ce nœud (avec ou sans adresse d'unité).
+    pub fn name_matches(&self, pattern: &[u8]) -> bool {
+        if pattern.contains(&b'@') {
+            return self.name == pattern;
+        }
+        self.name.split(|&b| b == b'@').next() == Some(pattern)
+    }
+
+    /// Propriétés de ce nœud (celles des enfants exclues).
+    pub fn properties(&self) -> PropIter<'a> {
+        PropIter {
+            fdt: self.fdt,
+            offset: self.props,
+        }
+    }
+
+    /// Valeur brute de la propriété `name`.
+    pub fn property(&self, name: &[u8]) -> Option<&'a [u8]> {
+        self.properties().find(|p| p.name == name).map(|p| p.value)
+    }
+
+    /// Propriété chaîne, NUL final retiré.
+    pub fn property_str(&self, name: &[u8]) -> Option<&'a [u8]> {
+        let value = self.property(name)?;
+        Some(value.strip_suffix(&[0]).unwrap_or(value))
+    }
+
+    /// Propriété `<u32>`.
+    pub fn property_u32(&self, name: &[u8]) -> Option<u32> {
+        be32(self.property(name)?, 0)
+    }
+
+    /// Vrai si la liste de chaînes `compatible` contient `compatible`.
+    pub fn is_compatible(&self, compatible: &[u8]) -> bool {
+        self.property(b"compatible")
+            .is_some_and(|list| list.split(|&b| b == 0).any(|entry| entry == compatible))
+    }
+
+    /// Paires `(adresse, taille)` de la propriété `reg`.
+    pub fn reg(&self) -> RegIter<'a> {
+        RegIter {
+            data: self.property(b"reg").unwrap_or(&[]),
+            address_cells: self.address_cells,
+            size_cells: self.size_cells,
+        }
+    }
+
+    /// Première paire `reg`.
+    #[inline]
+    pub fn reg_first(&self) -> Option<(u64, u64)> {
+        self.reg().next()
+    }
+
+    /// `#address-cells`/`#size-cells` que ce nœud impose à ses enfants.
+    fn child_cells(&self) -> (u32, u32) {
+        (
+            self.property_u32(b"#address-cells")
+                .unwrap_or(DEFAULT_ADDRESS_CELLS),
+            self.property_u32(b"#size-cells")
+                .unwrap_or(DEFAULT_SIZE_CELLS),
+        )
+    }
+}
+
+/// Itérateur de nœuds en profondeur.
+pub struct NodeIter<'a> {
+    fdt: Fdt<'a>,
+    offset: usize,
+    depth: usize,
+    /// `cells[d]` = cellules imposées aux nœuds de profondeur `d`.
+    cells: [(u32, u32); MAX_DEPTH],
+}
+
+impl<'a> Iterator for NodeIter<'a> {
+    type Item = FdtNode<'a>;
+
+    fn next(&mut self) -> Option<FdtNode<'a>> {
+        loop {
+            let token = self.fdt.token(self.offset)?;
+            self.offset += 4;
+            match token {
+                FDT_BEGIN_NODE => {
+                    let name = cstr(self.fdt.structs, self.offset)?;
+                    self.offset = align4(self.offset + name.len() + 1);
+                    let (address_cells, size_cells) = *self.cells.get(self.depth)?;
+                    let node = FdtNode {
+                        fdt: self.fdt,
+                        name,
+                        props: self.offset,
+                        depth: self.depth,
+                        address_cells,
+                        size_cells,
+                    };
+                    self.depth += 1;
+                    if let Some(slot) = self.cells.get_mut(self.depth) {
+                        *slot = node.child_cells();
+                    }
+                    return Some(node);
+                }
+                FDT_END_NODE => self.depth = self.depth.checked_sub(1)?,
+                FDT_PROP => {
+                    let len = self.fdt.token(self.offset)? as usize;
+                    self.offset = align4(self.offset + 8 + len);
+                }
+                FDT_NOP => {}
+                FDT_END => return None,
+                _ => return None, // jeton invalide : blob corrompu
+            }
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// PROPRIÉTÉS
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Propriété d'un nœud.
+#[derive(Copy, Clone, Debug)]
+pub struct FdtProp<'a> {
+    pub name: &'a [u8],
+    pub value: &'a [u8],
+}
+
+/// Itérateur des propriétés d'un nœud.
+pub struct PropIter<'a> {
+    fdt: Fdt<'a>,
+    offset: usize,
+}
+
+impl<'a> Iterator for PropIter<'a> {
+    type Item = FdtProp<'a>;
+
+    fn next(&mut self) -> Option<FdtProp<'a>> {
+        loop {
+            match self.fdt.token(self.offset)? {
+                FDT_NOP => self.offset += 4,
+                FDT_PROP => {
+                    let len = self.fdt.token(self.offset + 4)? as usize;
+                    let name_off = self.fdt.token(self.offset + 8)? as usize;
+                    let start = self.offset + 12;
+                    let value = self.fdt.structs.get(start..start.checked_add(len)?)?;
+                    self.offset = align4(start + len);
+                    return Some(FdtProp {
+                        name: cstr(self.fdt.strings, name_off)?,
+                        value,
+                    });
+                }
+                // Premier enfant, fin du nœud ou FDT_END : plus de propriétés.
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// Itérateur des paires `(adresse, taille)` d'une propriété `reg`.
+pub struct RegIter<'a> {
+    data: &'a [u8],
+    address_cells: u32,
+    size_cells: u32,
+}
+
+impl Iterator for RegIter<'_> {
+    type Item = (u64, u64);
+
+    fn next(&mut self) -> Option<(u64, u64)> {
+        let addr_len = self.address_cells as usize * 4;
+        let entry_len = addr_len + self.size_cells as usize * 4;
+        if entry_len == 0 || self.address_cells > 2 || self.size_cells > 2 {
+            return None;
+        }
+        let entry = self.data.get(..entry_len)?;
+        self.data = &self.data[entry_len..];
+        Some((
+            read_cells(entry, self.address_cells)?,
+            read_cells(&entry[addr_len..], self.size_cells)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    /// Construit un DTB minimal pour les tests (pas de réservations mémoire).
+    #[derive(Default)]
+    pub(crate) struct DtbBuilder {
+        structs: Vec<u8>,
+        strings: Vec<u8>,
+    }
+
+    impl DtbBuilder {
+        fn token(&mut self, token: u32) {
+            self.structs.extend_from_slice(&token.to_be_bytes());
+        }
+
+        fn pad(&mut self) {
+            while !self.structs.len().is_multiple_of(4) {
+                self.structs.push(0);
+            }
+        }
+
+        pub(crate) fn begin(&mut self, name: &str) -> &mut Self {
+            self.token(FDT_BEGIN_NODE);
+            self.structs.extend_from_slice(name.as_bytes());
+            self.structs.push(0);
+            self.pad();
+            self
+        }
+
+        pub(crate) fn end(&mut self) -> &mut Self {
+            self.token(FDT_END_NODE);
+            self
+        }
+
+        pub(crate) fn prop(&mut self, name: &str, value: &[u8]) -> &mut Self {
+            let name_off = self.strings.len() as u32;
+            self.strings.extend_from_slice(name.as_bytes());
+            self.strings.push(0);
+            self.token(FDT_PROP);
+            self.token(value.len() as u32);
+            self.token(name_off);
+            self.structs.extend_from_slice(value);
+            self.pad();
+            self
+        }
+
+        pub(crate) fn prop_cells(&mut self, name: &str, cells: &[u32]) -> &mut Self {
+            let value: Vec<u8> = cells.iter().flat_map(|c| c.to_be_bytes()).collect();
+            self.prop(name, &value)
+        }
+
+        pub(crate) fn finish(&mut self) -> Vec<u8> {
+            self.token(FDT_END);
+            let rsvmap = HEADER_SIZE;
+            let off_struct = rsvmap + 16;
+            let off_strings = off_struct + self.structs.len();
+            let total = off_strings + self.strings.len();
+            let header = [
+                FDT_MAGIC,
+                total as u32,
+                off_struct as u32,
+                off_strings as u32,
+                rsvmap as u32,
+                FDT_VERSION,
+                FDT_MIN_COMPAT_VERSION,
+                0,
+                self.strings.len() as u32,
+                self.structs.len() as u32,
+            ];
+            let mut blob: Vec<u8> = header.iter().flat_map(|w| w.to_be_bytes()).collect();
+            blob.extend_from_slice(&[0; 16]);
+            blob.extend_from_slice(&self.structs);
+            blob.extend_from_slice(&self.strings);
+            blob
+        }
+    }
+
+    /// Arbre type `qemu -M virt` réduit.
+    pub(crate) fn virt_dtb() -> Vec<u8> {
+        DtbBuilder::default()
+            .begin("")
+            .prop_cells("#address-cells", &[2])
+            .prop_cells("#size-cells", &[2])
+            .prop("compatible", b"linux,dummy-virt\0")
+            .begin("chosen")
+            .prop("bootargs", b"console=ttyAMA0 quiet\0")
+            .prop("stdout-path", b"/pl011@9000000:115200\0")
+            .end()
+            .begin("memory@40000000")
+            .prop("device_type", b"memory\0")
+            .prop_cells("reg", &[0, 0x4000_0000, 0, 0x800_0000])
+            .end()
+            .begin("intc@8000000")
+            .prop("compatible", b"arm,gic-v3\0")
+            .prop_cells(
+                "reg",
+                &[0, 0x0800_0000, 0, 0x1_0000, 0, 0x080A_0000, 0, 0xF6_0000],
+            )
+            .end()
+            .begin("pl011@9000000")
+            .prop("compatible", b"arm,pl011\0arm,primecell\0")
+            .prop_cells("reg", &[0, 0x0900_0000, 0, 0x1000])
+            .end()
+            .begin("soc")
+            .prop_cells("#address-cells", &[1])
+            .prop_cells("#size-cells", &[1])
+            .begin("uart@10000000")
+            .prop("compatible", b"ns16550a\0")
+            .prop_cells("reg", &[0x1000_0000, 0x100])
+            .end()
+            .end()
+            .end()
+            .finish()
+    }
+
+    #[test]
+    fn test_fdt_header_validation() {
+        let blob = virt_dtb();
+        assert!(Fdt::new(&blob).is_ok());
+        assert_eq!(
+            Fdt::new(&blob[..blob.len() - 1]).err(),
+            Some(FdtError::Truncated)
+        );
+        let mut bad = blob.clone();
+        bad[0] = 0;
+        assert_eq!(Fdt::new(&bad).err(), Some(FdtError::BadMagic));
+        let mut old = blob;
+        old[23] = 15; // version 15
+        assert_eq!(Fdt::new(&old).err(), Some(FdtError::BadVersion));
+    }
+
+    #[test]
+    fn test_fdt_paths_and_properties() {
+        let blob = virt_dtb();
+        let fdt = Fdt::new(&blob).unwrap();
+        assert_eq!(fdt.nodes().count(), 7);
+        assert_eq!(fdt.bootargs(), Some(&b"console=ttyAMA0 quiet"[..]));
+        assert_eq!(fdt.stdout_path(), Some(&b"/pl011@9000000"[..]));
+        assert!(fdt
+            .find_path("/")
+            .unwrap()
+            .is_compatible(b"linux,dummy-virt"));
+        assert_eq!(fdt.find_path("/soc/uart").unwrap().name(), b"uart@10000000");
+        assert!(fdt.find_path("/uart").is_none());
+        assert!(fdt.find_path("/pl011@9000001").is_none());
+    }
+
+    #[test]
+    fn test_fdt_reg_cells() {
+        let blob = virt_dtb();
+        let fdt = Fdt::new(&blob).unwrap();
+        assert_eq!(
+            fdt.memory().unwrap().reg_first(),
+            Some((0x4000_0000, 0x800_0000))
+        );
+        assert!(fdt.overlaps_ram(0x4000_0000, 1 << 30));
+        assert!(fdt.overlaps_ram(0, 0x4000_0001));
+        assert!(!fdt.overlaps_ram(0, 0x4000_0000));
+        assert!(!fdt.overlaps_ram(0x4800_0000, 1 << 30));
+        let gic = fdt.find_compatible(b"arm,gic-v3").unwrap();
+        let mut regs = gic.reg();
+        assert_eq!(regs.next(), Some((0x0800_0000, 0x1_0000)));
+        assert_eq!(regs.next(), Some((0x080A_0000, 0xF6_0000)));
+        assert_eq!(regs.next(), None);
+        assert_eq!(
+            fdt.find_compatible(b"arm,primecell").unwrap().reg_first(),
+            Some((0x0900_0000, 0x1000))
+        );
+        assert_eq!(
+            fdt.find_compatible(b"ns16550a").unwrap().reg_first(),
+            Some((0x1000_0000, 0x100))
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! # arch/interface.rs — Interface d'architecture par traits
+//!
+//! Contrat minimal que chaque port fournit au reste du noyau : CPU,
+//! contrôleur d'interruptions, timer, MMU et changement de contexte. Chaque
+//! architecture l'implémente sur un type vide (`x86_64::X86_64`,
+//! `aarch64::Aarch64`) exposé sous l'alias `arch::Current`.
+//!
+//! Les méthodes sont associées (pas de `self`) : il n'existe qu'une
+//! architecture par image, l'appel est résolu à la compilation.
+//!
+//! Le chemin x86_64 historique (APIC, walker PML4, `switch_asm.s`) reste
+//! appelé directement par ses clients ; cette interface sert d'abord aux
+//! ports (AArch64) et au code neuf qui doit rester portable.
+
+use crate::memory::virt::page_table::PageTableFormat;
+
+/// Primitives CPU locales.
+pub trait ArchCpu {
+    /// Identifiant logique du CPU courant.
+    fn cpu_id() -> u32;
+
+    /// Masque les IRQ et retourne l'état précédent (opaque).
+    fn irq_save() -> u64;
+
+    /// Restaure un état retourné par `irq_save`.
+    fn irq_restore(state: u64);
+
+    /// Arrêt définitif du CPU courant.
+    fn halt() -> !;
+}
+
+/// Contrôleur d'interruptions (IO-APIC + LAPIC, GICv3…).
+///
+/// `irq` est le numéro natif du contrôleur : GSI sur x86_64, INTID sur GIC.
+pub trait InterruptController {
+    /// Acquitte l'interruption en cours de traitement.
+    fn end_of_interrupt(irq: u32);
+
+    /// Masque une ligne d'interruption.
+    fn mask(irq: u32);
+
+    /// Démasque une ligne d'interruption.
+    fn unmask(irq: u32);
+
+    /// Envoie l'IPI `irq` au CPU `cpu` (APIC ID, affinité MPIDR…).
+    fn send_ipi(cpu: u32, irq: u32);
+}
+
+/// Compteur monotone et timer one-shot du CPU courant.
+pub trait ArchTimer {
+    /// Valeur brute du compteur (TSC, CNTVCT_EL0…).
+    fn counter() -> u64;
+
+    /// Fréquence du compteur en Hz (0 si non calibré).
+    fn frequency_hz() -> u64;
+
+    /// Arme une échéance one-shot dans `ns` nanosecondes.
+    fn arm_oneshot_ns(ns: u64);
+}
+
+/// Unité de gestion mémoire.
+pub trait ArchMmu {
+    /// Encodage des tables de pages de l'architecture.
+    type Format: PageTableFormat;
+
+    /// Racine de traduction active (adresse physique).
+    fn current_root() -> u64;
+
+    /// Installe la racine `root_phys`.
+    ///
+    /// # Safety
+    /// La table doit mapper le code et la pile en cours d'exécution.
+    unsafe fn activate(root_phys: u64);
+
+    /// Invalide la traduction de `virt` sur le CPU courant.
+    fn flush_page(virt: u64);
+
+    /// Invalide toutes les traductions non globales du CPU courant.
+    fn flush_all();
+}
+
+/// Contexte d'exécution noyau sauvegardé au changement de thread.
+pub trait ArchContext {
+    /// Registres callee-saved + pile ; disposition propre à l'architecture.
+    type Context: Default;
+
+    /// Prépare `ctx` pour démarrer `entry(arg)` sur la pile `stack_top`.
+    fn init(ctx: &mut Self::Context, entry: extern "C" fn(usize) -> !, arg: usize, stack_top: u64);
+
+    /// Sauvegarde le contexte courant dans `prev` et reprend `next`.
+    ///
+    /// # Safety
+    /// `next` doit avoir été initialisé par `init` ou sauvegardé par `switch`,
+    /// et sa pile doit rester valide.
+    unsafe fn switch(prev: *mut Self::Context, next: *const Self::Context);
+}
+
+/// Architecture complète telle que vue par le code portable.
+pub trait Arch: ArchCpu + InterruptController + ArchTimer + ArchMmu {
+    /// Nom court de l'architecture (journal, `uname -m`).
+    const NAME: &'static str;
+}
+
+/// Convertit des nanosecondes en ticks d'un compteur à `hz` (sans overflow).
+#[inline]
+pub fn ns_to_ticks(ns: u64, hz: u64) -> u64 {
+    ((ns as u128 * hz as u128) / 1_000_000_000) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ns_to_ticks() {
+        assert_eq!(ns_to_ticks(1_000_000_000, 62_500_000), 62_500_000);
+        assert_eq!(ns_to_ticks(1_000, 24_000_000), 24);
+        assert_eq!(ns_to_ticks(u64::MAX, 1_000_000_000), u64::MAX);
+        assert_eq!(ns_to_ticks(0, 3_000_000_000), 0);
+    }
+}
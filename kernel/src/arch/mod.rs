@@ -6,8 +6,11 @@
 //! ## Hiérarchie
 //! ```
 //! arch/ (transverse)
-//!   └── x86_64/   ← implémentation principale
-//!   └── aarch64/  ← placeholder ARM64 (futur)
+//!   ├── interface.rs ← traits CPU / IRQ / timer / MMU / contexte (`Current`)
+//!   ├── fdt.rs       ← lecteur de device tree (ports DT)
+//!   └── x86_64/      ← implémentation principale
+//!   └── aarch64/     ← port ARM64 (scaffolding : DT, GICv3, timer générique)
+//!   └── riscv64/     ← port RISC-V (scaffolding : SBI, PLIC, Sv39)
 //! ```
 //!
 //! ## Règles absolues
@@ -22,7 +25,12 @@ pub mod x86_64;
 #[cfg(target_arch = "aarch64")]
 pub mod aarch64;
 
+#[cfg(target_arch = "riscv64")]
+pub mod riscv64;
+
 pub mod constants;
+pub mod fdt;
+pub mod interface;
 
 /// Lecture du TSC (timestamp counter) — disponible sur toutes les architectures.
 pub mod time;
@@ -39,6 +47,21 @@ pub use self::x86_64::{
 #[cfg(target_arch = "aarch64")]
 pub use self::aarch64::halt_cpu;
 
+#[cfg(target_arch = "riscv64")]
+pub use self::riscv64::halt_cpu;
+
+/// Implémentation de `interface::Arch` de la cible compilée.
+#[cfg(target_arch = "x86_64")]
+pub type Current = self::x86_64::arch_impl::X86_64;
+
+/// Implémentation de `interface::Arch` de la cible compilée.
+#[cfg(target_arch = "aarch64")]
+pub type Current = self::aarch64::Aarch64;
+
+/// Implémentation de `interface::Arch` de la cible compilée.
+#[cfg(target_arch = "riscv64")]
+pub type Current = self::riscv64::Riscv64;
+
 /// Informations architecture exportées vers le reste du noyau
 #[derive(Debug, Clone, Copy)]
 pub struct ArchInfo {
@@ -0,0 +1,127 @@
+//! # arch/riscv64/boot.rs — Entrée RISC-V depuis OpenSBI
+//!
+//! OpenSBI (`fw_jump`/`fw_dynamic`) entre en S-mode, MMU éteinte, avec
+//! `a0` = hartid et `a1` = adresse physique du DTB. Plusieurs harts peuvent
+//! arriver ensemble : une loterie atomique élit le hart de boot, les autres
+//! restent en `wfi` jusqu'au futur SMP (SBI HSM).
+//!
+//! `_start` installe `gp`, `tp` (= hartid), la pile de boot, efface la BSS
+//! puis appelle `riscv64_boot_main`, qui pose console SBI, pièges, Sv39,
+//! PLIC et timer avant d'attendre les IRQ : mémoire, ordonnanceur et pilotes
+//! ne sont pas encore portés.
+//!
+//! Image liée par `arch/riscv64/linker.ld`.
+
+use super::{console, mmu, plic, timer, trap, Riscv64};
+use crate::arch::fdt::Fdt;
+use crate::arch::interface::ArchTimer;
+
+/// Période du tick de démonstration (100 Hz).
+const TICK_NS: u64 = 10_000_000;
+
+/// `sie.SSIE | sie.SEIE` (STIE est posé par `timer::init`).
+const SIE_SSIE_SEIE: u64 = (1 << 1) | (1 << 9);
+
+core::arch::global_asm!(
+    ".section .text.boot, \"ax\"",
+    ".global _start",
+    "_start:",
+    "    .option push",
+    "    .option norelax",
+    "    lla gp, __global_pointer$",
+    "    .option pop",
+    "    csrw sie, zero",
+    "    lla t0, exo_riscv_boot_lottery",
+    "    li t1, 1",
+    "    amoadd.w t1, t1, (t0)",
+    "    bnez t1, 3f",
+    "    mv tp, a0",
+    "    lla sp, __boot_stack_top",
+    "    mv s0, a0",
+    "    mv s1, a1",
+    "    lla t0, __bss_start",
+    "    lla t1, __bss_end",
+    "1:  bgeu t0, t1, 2f",
+    "    sd zero, 0(t0)",
+    "    addi t0, t0, 8",
+    "    j 1b",
+    "2:  mv a0, s0",
+    "    mv a1, s1",
+    "    call riscv64_boot_main",
+    "3:  wfi",
+    "    j 3b",
+    ".section .data",
+    ".balign 4",
+    "exo_riscv_boot_lottery:",
+    "    .word 0",
+    ".section .bss.boot_stack, \"aw\", @nobits",
+    ".balign 16",
+    "__boot_stack_bottom:",
+    "    .space 0x10000",
+    "__boot_stack_top:",
+);
+
+fn log(msg: &[u8]) {
+    console::write_bytes(msg);
+}
+
+/// Point d'entrée Rust du hart de boot.
+#[no_mangle]
+extern "C" fn riscv64_boot_main(hartid: u64, dtb: u64) -> ! {
+    console::init();
+    log(b"[RISCV64] Exo-OS boot S-mode, hart=");
+    console::write_hex(hartid);
+    log(b" dtb=");
+    console::write_hex(dtb);
+    log(b"\n");
+
+    // SAFETY: MMU éteinte, `dtb` est l'adresse physique fournie par OpenSBI ;
+    // `from_ptr` vérifie le magic avant de lire `totalsize` octets.
+    let Ok(fdt) = (unsafe { Fdt::from_ptr(dtb as *const u8) }) else {
+        log(b"[RISCV64] DTB invalide\n");
+        super::halt_cpu()
+    };
+    if let Some(bootargs) = fdt.bootargs() {
+        log(b"[RISCV64] bootargs: ");
+        log(bootargs);
+        log(b"\n");
+    }
+    if let Some(node) = fdt.memory() {
+        for (base, size) in node.reg() {
+            log(b"[RISCV64] RAM ");
+            console::write_hex(base);
+            log(b" + ");
+            console::write_hex(size);
+            log(b"\n");
+        }
+    }
+
+    trap::install();
+    // SAFETY: appel unique sur le hart de boot, MMU encore éteinte.
+    unsafe { mmu::enable_identity(&fdt) };
+    log(b"[RISCV64] MMU: Sv39 identite 1 GiB active\n");
+
+    if plic::init_from_fdt(&fdt, hartid).is_err() {
+        log(b"[RISCV64] PLIC absent du device tree\n");
+    }
+    timer::init(&fdt);
+    log(b"[RISCV64] PLIC + timer SBI: ");
+    console::write_hex(Riscv64::frequency_hz());
+    log(b" Hz\n");
+
+    // SAFETY: vecteur de pièges installé ; démasque IPI et IRQ externes.
+    unsafe {
+        core::arch::asm!("csrs sie, {}", in(reg) SIE_SSIE_SEIE, options(nostack, nomem));
+    }
+    Riscv64::arm_oneshot_ns(TICK_NS);
+    super::irq_enable();
+    log(b"[RISCV64] scaffolding: memoire/ordonnanceur non portes, attente IRQ\n");
+    loop {
+        let before = timer::ticks();
+        // SAFETY: attente d'interruption, IRQ démasquées juste au-dessus.
+        unsafe { core::arch::asm!("wfi", options(nostack, nomem)) };
+        if timer::ticks() != before {
+            Riscv64::arm_oneshot_ns(TICK_NS);
+        }
+    }
+}
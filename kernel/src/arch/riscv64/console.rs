@@ -0,0 +1,65 @@
+//! # arch/riscv64/console.rs — Console précoce via SBI
+//!
+//! Aucun pilote UART : la sortie passe par le firmware, extension DBCN
+//! (SBI 2.0) si présente, sinon `sbi_console_putchar` legacy. Disponible dès
+//! l'entrée du noyau, avant le device tree et la MMU.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use super::sbi;
+
+const BACKEND_NONE: u8 = 0;
+const BACKEND_DBCN: u8 = 1;
+const BACKEND_LEGACY: u8 = 2;
+
+static BACKEND: AtomicU8 = AtomicU8::new(BACKEND_NONE);
+
+/// Choisit le canal SBI (DBCN de préférence).
+pub fn init() {
+    let backend = if sbi::probe_extension(sbi::EID_DBCN) {
+        BACKEND_DBCN
+    } else {
+        BACKEND_LEGACY
+    };
+    BACKEND.store(backend, Ordering::Release);
+}
+
+#[inline]
+pub fn is_active() -> bool {
+    BACKEND.load(Ordering::Acquire) != BACKEND_NONE
+}
+
+fn write_raw(backend: u8, byte: u8) {
+    match backend {
+        BACKEND_DBCN => {
+            let _ = sbi::dbcn_write_byte(byte);
+        }
+        BACKEND_LEGACY => sbi::legacy_putchar(byte),
+        _ => {}
+    }
+}
+
+/// Écrit des octets bruts (`\n` → `\r\n`) si la console est active.
+pub fn write_bytes(bytes: &[u8]) {
+    let backend = BACKEND.load(Ordering::Acquire);
+    for &byte in bytes {
+        if byte == b'\n' {
+            write_raw(backend, b'\r');
+        }
+        write_raw(backend, byte);
+    }
+}
+
+/// Écrit `value` en hexadécimal (`0x` + 16 chiffres).
+pub fn write_hex(value: u64) {
+    let mut buf = *b"0x0000000000000000";
+    for i in 0..16 {
+        let nibble = ((value >> ((15 - i) * 4)) & 0xF) as u8;
+        buf[2 + i] = if nibble < 10 {
+            b'0' + nibble
+        } else {
+            b'a' + nibble - 10
+        };
+    }
+    write_bytes(&buf);
+}
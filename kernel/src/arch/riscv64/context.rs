@@ -0,0 +1,87 @@
+//! # arch/riscv64/context.rs — Changement de contexte noyau RISC-V
+//!
+//! Sauvegarde les registres callee-saved du psABI (ra, sp, s0..s11). L'état
+//! F/D n'est pas sauvegardé : `sstatus.FS` reste Off en noyau.
+
+use super::Riscv64;
+use crate::arch::interface::ArchContext;
+
+/// Contexte sauvegardé (disposition figée par `exo_riscv_switch`).
+#[repr(C)]
+#[derive(Default, Debug)]
+pub struct Context {
+    /// Adresse de reprise.
+    pub ra: u64,
+    pub sp: u64,
+    /// s0..s11.
+    pub s: [u64; 12],
+}
+
+const _: () = assert!(core::mem::size_of::<Context>() == 14 * 8);
+
+core::arch::global_asm!(
+    ".section .text",
+    ".global exo_riscv_switch",
+    // a0 = prev, a1 = next
+    "exo_riscv_switch:",
+    "    sd ra, 0(a0)",
+    "    sd sp, 8(a0)",
+    "    sd s0, 16(a0)",
+    "    sd s1, 24(a0)",
+    "    sd s2, 32(a0)",
+    "    sd s3, 40(a0)",
+    "    sd s4, 48(a0)",
+    "    sd s5, 56(a0)",
+    "    sd s6, 64(a0)",
+    "    sd s7, 72(a0)",
+    "    sd s8, 80(a0)",
+    "    sd s9, 88(a0)",
+    "    sd s10, 96(a0)",
+    "    sd s11, 104(a0)",
+    "    ld ra, 0(a1)",
+    "    ld sp, 8(a1)",
+    "    ld s0, 16(a1)",
+    "    ld s1, 24(a1)",
+    "    ld s2, 32(a1)",
+    "    ld s3, 40(a1)",
+    "    ld s4, 48(a1)",
+    "    ld s5, 56(a1)",
+    "    ld s6, 64(a1)",
+    "    ld s7, 72(a1)",
+    "    ld s8, 80(a1)",
+    "    ld s9, 88(a1)",
+    "    ld s10, 96(a1)",
+    "    ld s11, 104(a1)",
+    "    ret",
+    // Premier lancement : s0 = entrée, s1 = argument (voir `init`).
+    ".global exo_riscv_thread_start",
+    "exo_riscv_thread_start:",
+    "    mv a0, s1",
+    "    jalr s0",
+    "1:  wfi",
+    "    j 1b",
+);
+
+extern "C" {
+    fn exo_riscv_switch(prev: *mut Context, next: *const Context);
+    fn exo_riscv_thread_start() -> !;
+}
+
+impl ArchContext for Riscv64 {
+    type Context = Context;
+
+    fn init(ctx: &mut Context, entry: extern "C" fn(usize) -> !, arg: usize, stack_top: u64) {
+        *ctx = Context::default();
+        ctx.ra = exo_riscv_thread_start as usize as u64;
+        // psABI : pile alignée sur 16 octets.
+        ctx.sp = stack_top & !0xF;
+        ctx.s[0] = entry as usize as u64;
+        ctx.s[1] = arg as u64;
+    }
+
+    unsafe fn switch(prev: *mut Context, next: *const Context) {
+        // SAFETY: contrat de `ArchContext::switch` transmis à l'appelant ; la
+        // routine ne touche que ra, sp et s0..s11.
+        unsafe { exo_riscv_switch(prev, next) }
+    }
+}
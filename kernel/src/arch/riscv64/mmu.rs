@@ -0,0 +1,100 @@
+//! # arch/riscv64/mmu.rs — MMU Sv39
+//!
+//! Identité de boot en gigapages sur la moitié basse de l'espace Sv39
+//! (256 GiB) : RAM `/memory` en RWX, le reste (PLIC, UART, virtio…) en RW non
+//! exécutable. La cacheabilité est fixée par les PMA de la plateforme.
+//!
+//! L'encodage des entrées est partagé avec le mapper générique :
+//! `memory::virt::page_table::Sv39Format`.
+
+use super::Riscv64;
+use crate::arch::fdt::Fdt;
+use crate::arch::interface::ArchMmu;
+use crate::memory::core::PageFlags;
+use crate::memory::virt::page_table::format::RawTable;
+use crate::memory::virt::page_table::riscv64::SATP_MODE_SV39;
+use crate::memory::virt::page_table::{PageTableFormat, Sv39Format};
+
+const GIGAPAGE: u64 = 1 << 30;
+
+/// Entrées racine de la moitié basse (VA 0..2^38).
+const LOWER_HALF_ENTRIES: usize = 256;
+
+/// Champ PPN de satp.
+const SATP_PPN_MASK: u64 = (1 << 44) - 1;
+
+#[repr(C, align(4096))]
+struct BootTable(RawTable);
+
+// Racine de l'identité de boot, écrite une seule fois par le hart de boot,
+// MMU éteinte.
+static mut BOOT_ROOT: BootTable = BootTable([0; 512]);
+
+#[inline]
+fn satp_for(root_phys: u64) -> u64 {
+    SATP_MODE_SV39 | ((root_phys >> 12) & SATP_PPN_MASK)
+}
+
+/// Construit l'identité de boot et active Sv39.
+///
+/// # Safety
+/// Appel unique, sur le hart de boot, MMU éteinte et image chargée dans la
+/// RAM décrite par le device tree.
+pub unsafe fn enable_identity(fdt: &Fdt<'_>) {
+    // SAFETY: appel unique garanti par l'appelant ; aucune autre référence à
+    // la table n'existe.
+    let root = unsafe { &mut (*core::ptr::addr_of_mut!(BOOT_ROOT)).0 };
+    for (i, entry) in root.iter_mut().take(LOWER_HALF_ENTRIES).enumerate() {
+        let base = i as u64 * GIGAPAGE;
+        let flags = if fdt.overlaps_ram(base, GIGAPAGE) {
+            PageFlags::KERNEL_CODE.set(PageFlags::WRITABLE)
+        } else {
+            PageFlags::KERNEL_DATA
+        };
+        // Une feuille Sv39 a le même encodage à tous les niveaux.
+        *entry = Sv39Format::leaf_entry(base, flags);
+    }
+    // SAFETY: l'identité couvre le code courant, la pile et les MMIO.
+    unsafe { Riscv64::activate(root.as_ptr() as u64) };
+}
+
+impl ArchMmu for Riscv64 {
+    type Format = Sv39Format;
+
+    #[inline]
+    fn current_root() -> u64 {
+        let satp: u64;
+        // SAFETY: lecture du CSR satp en S-mode.
+        unsafe {
+            core::arch::asm!("csrr {}, satp", out(reg) satp, options(nostack, nomem));
+        }
+        (satp & SATP_PPN_MASK) << 12
+    }
+
+    unsafe fn activate(root_phys: u64) {
+        // SAFETY: contrat de `ArchMmu::activate` transmis à l'appelant ; sans
+        // ASID, le changement de racine impose l'invalidation complète.
+        unsafe {
+            core::arch::asm!(
+                "sfence.vma",
+                "csrw satp, {}",
+                "sfence.vma",
+                in(reg) satp_for(root_phys),
+                options(nostack),
+            );
+        }
+    }
+
+    #[inline]
+    fn flush_page(virt: u64) {
+        super::flush_tlb_page(virt);
+    }
+
+    #[inline]
+    fn flush_all() {
+        super::flush_tlb();
+    }
+}
+
+// Le format doit rester celui annoncé par SATP_MODE_SV39.
+const _: () = assert!(Sv39Format::LEVELS == 3 && Sv39Format::VA_BITS == 39);
@@ -0,0 +1,211 @@
+//! # arch/riscv64 — Port RISC-V 64 bits (scaffolding)
+//!
+//! Module d'architecture pour les cibles rv64gc en S-mode sous OpenSBI, cible
+//! `riscv64gc-unknown-none-elf`, image liée par `linker.ld`.
+//!
+//! ## État
+//! Présent : boot par device tree (`boot.rs`, loterie de hart), appels SBI
+//! (`sbi.rs`), console SBI DBCN/legacy (`console.rs`), pièges S-mode
+//! (`trap.rs`), PLIC (`plic.rs`), timer SBI (`timer.rs`), identité Sv39 en
+//! gigapages (`mmu.rs`) et changement de contexte (`context.rs`), le tout
+//! derrière `arch::interface` (`Riscv64`). L'encodage Sv39 est partagé avec
+//! le mapper générique (`memory::virt::page_table::Sv39Format`).
+//!
+//! Absent : SMP (harts secondaires parqués), ABI syscall RISC-V, pilotes, et
+//! le portage des couches memory/scheduler encore écrites contre x86_64. Comme
+//! AArch64, RISC-V n'est pas une cible de boot supportée en v0.2.0 ; le refus
+//! de cible se fait au niveau crate.
+
+pub mod boot;
+pub mod console;
+pub mod context;
+pub mod mmu;
+pub mod plic;
+pub mod sbi;
+pub mod timer;
+pub mod trap;
+
+use crate::arch::interface::{Arch, ArchCpu};
+
+/// Architecture RISC-V 64 bits (type vide, méthodes associées).
+pub struct Riscv64;
+
+/// `sstatus.SIE`.
+const SSTATUS_SIE: u64 = 1 << 1;
+
+// ── Primitives de base ────────────────────────────────────────────────────────
+
+/// Lit le compteur de temps (CSR `time`, pseudo-instruction `rdtime`)
+#[inline(always)]
+pub fn read_time() -> u64 {
+    let val: u64;
+    // SAFETY: `time` est lisible en S-mode (scounteren géré par OpenSBI)
+    unsafe {
+        core::arch::asm!("rdtime {}", out(reg) val, options(nostack, nomem));
+    }
+    val
+}
+
+/// Arrête le hart (WFI — Wait For Interrupt)
+#[inline(always)]
+pub fn halt_cpu() -> ! {
+    loop {
+        // SAFETY: WFI est une instruction d'attente — sortie par interruption
+        unsafe {
+            core::arch::asm!("wfi", options(nostack, nomem));
+        }
+    }
+}
+
+/// Désactive les interruptions (sstatus.SIE = 0)
+#[inline(always)]
+pub fn irq_disable() {
+    // SAFETY: écriture de sstatus depuis le S-mode, seul SIE est touché
+    unsafe {
+        core::arch::asm!("csrc sstatus, {}", in(reg) SSTATUS_SIE, options(nostack, nomem));
+    }
+}
+
+/// Active les interruptions (sstatus.SIE = 1)
+#[inline(always)]
+pub fn irq_enable() {
+    // SAFETY: écriture de sstatus depuis le S-mode, seul SIE est touché
+    unsafe {
+        core::arch::asm!("csrs sstatus, {}", in(reg) SSTATUS_SIE, options(nostack, nomem));
+    }
+}
+
+/// Sauvegarde et désactive les interruptions (retourne l'ancien SIE)
+#[inline(always)]
+pub fn irq_save() -> u64 {
+    let old: u64;
+    // SAFETY: lecture/effacement atomique de sstatus.SIE
+    unsafe {
+        core::arch::asm!(
+            "csrrc {}, sstatus, {}",
+            out(reg) old,
+            in(reg) SSTATUS_SIE,
+            options(nostack, nomem),
+        );
+    }
+    old & SSTATUS_SIE
+}
+
+/// Restaure l'état des interruptions
+#[inline(always)]
+pub fn irq_restore(state: u64) {
+    // SAFETY: ne repositionne que SIE tel que retourné par `irq_save`
+    unsafe {
+        core::arch::asm!(
+            "csrs sstatus, {}",
+            in(reg) state & SSTATUS_SIE,
+            options(nostack, nomem),
+        );
+    }
+}
+
+/// Barrière mémoire complète (FENCE RW,RW)
+#[inline(always)]
+pub fn memory_barrier() {
+    // SAFETY: instruction barrière — aucun effet de bord sur l'état CPU
+    unsafe {
+        core::arch::asm!("fence rw, rw", options(nostack));
+    }
+}
+
+/// Barrière de charge (FENCE R,R)
+#[inline(always)]
+pub fn load_fence() {
+    // SAFETY: barrière load-load
+    unsafe {
+        core::arch::asm!("fence r, r", options(nostack));
+    }
+}
+
+/// Barrière d'écriture (FENCE W,W)
+#[inline(always)]
+pub fn store_fence() {
+    // SAFETY: barrière store-store
+    unsafe {
+        core::arch::asm!("fence w, w", options(nostack));
+    }
+}
+
+/// Invalide une page TLB à l'adresse virtuelle donnée (hart local)
+#[inline(always)]
+pub fn flush_tlb_page(virt_addr: u64) {
+    // SAFETY: SFENCE.VMA sur une adresse, toutes ASID
+    unsafe {
+        core::arch::asm!("sfence.vma {}, zero", in(reg) virt_addr, options(nostack));
+    }
+}
+
+/// Invalide tout le TLB du hart local
+#[inline(always)]
+pub fn flush_tlb() {
+    // SAFETY: SFENCE.VMA global
+    unsafe {
+        core::arch::asm!("sfence.vma", options(nostack));
+    }
+}
+
+/// Délai actif en ticks du compteur `time`
+pub fn spin_delay_ticks(ticks: u64) {
+    let start = read_time();
+    while read_time().wrapping_sub(start) < ticks {
+        core::hint::spin_loop();
+    }
+}
+
+/// Hartid du hart courant (`tp`, posé par `_start`).
+#[inline(always)]
+pub fn hart_id() -> u64 {
+    let val: u64;
+    // SAFETY: lecture de `tp`, réservé au hartid en noyau
+    unsafe {
+        core::arch::asm!("mv {}, tp", out(reg) val, options(nostack, nomem));
+    }
+    val
+}
+
+// ── ArchInfo ─────────────────────────────────────────────────────────────────
+
+use super::ArchInfo;
+
+/// Retourne les informations d'architecture RISC-V
+pub fn arch_info() -> ArchInfo {
+    ArchInfo {
+        cpu_count: 1,
+        has_apic: false,
+        has_x2apic: false,
+        has_acpi: false,
+        page_size: 4096,
+    }
+}
+
+// ── arch::interface ──────────────────────────────────────────────────────────
+
+impl ArchCpu for Riscv64 {
+    #[inline]
+    fn cpu_id() -> u32 {
+        hart_id() as u32
+    }
+
+    #[inline]
+    fn irq_save() -> u64 {
+        irq_save()
+    }
+
+    #[inline]
+    fn irq_restore(state: u64) {
+        irq_restore(state)
+    }
+
+    fn halt() -> ! {
+        halt_cpu()
+    }
+}
+
+impl Arch for Riscv64 {
+    const NAME: &'static str = "riscv64";
+}
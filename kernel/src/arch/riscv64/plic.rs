@@ -0,0 +1,138 @@
+//! # arch/riscv64/plic.rs — Platform-Level Interrupt Controller
+//!
+//! Interruptions externes (sources 1..`riscv,ndev`) vers le contexte S-mode
+//! du hart de boot. Le contexte suit la numérotation de `qemu -M virt` et des
+//! SoC SiFive (`2 × hart + 1`) ; le décodage complet de
+//! `interrupts-extended` viendra avec le SMP.
+
+use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use super::Riscv64;
+use crate::arch::fdt::Fdt;
+use crate::arch::interface::InterruptController;
+
+const PRIORITY_BASE: u64 = 0x0000;
+const ENABLE_BASE: u64 = 0x2000;
+const ENABLE_STRIDE: u64 = 0x80;
+const CONTEXT_BASE: u64 = 0x20_0000;
+const CONTEXT_STRIDE: u64 = 0x1000;
+const CONTEXT_THRESHOLD: u64 = 0x0;
+const CONTEXT_CLAIM: u64 = 0x4;
+
+/// Sources max de la spécification (0 = « pas d'interruption »).
+const MAX_SOURCES: u32 = 1024;
+/// Priorité donnée à une source démasquée (0 = jamais délivrée).
+const DEFAULT_PRIORITY: u32 = 1;
+
+/// Échec d'initialisation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlicError {
+    /// Pas de nœud `riscv,plic0` / `sifive,plic-1.0.0` (ou `reg` absent).
+    NotFound,
+}
+
+static BASE: AtomicU64 = AtomicU64::new(0);
+static CONTEXT: AtomicU32 = AtomicU32::new(0);
+static SOURCES: AtomicU32 = AtomicU32::new(0);
+
+#[inline(always)]
+fn read32(addr: u64) -> u32 {
+    // SAFETY: registre PLIC issu du device tree, mappé par l'identité.
+    unsafe { core::ptr::read_volatile(addr as *const u32) }
+}
+
+#[inline(always)]
+fn write32(addr: u64, value: u32) {
+    // SAFETY: idem `read32`.
+    unsafe { core::ptr::write_volatile(addr as *mut u32, value) }
+}
+
+/// Contexte S-mode du hart `hartid`.
+#[inline]
+fn supervisor_context(hartid: u64) -> u32 {
+    (hartid * 2 + 1) as u32
+}
+
+fn context_reg(reg: u64) -> u64 {
+    BASE.load(Ordering::Acquire)
+        + CONTEXT_BASE
+        + CONTEXT.load(Ordering::Relaxed) as u64 * CONTEXT_STRIDE
+        + reg
+}
+
+fn enable_word(irq: u32) -> u64 {
+    BASE.load(Ordering::Acquire)
+        + ENABLE_BASE
+        + CONTEXT.load(Ordering::Relaxed) as u64 * ENABLE_STRIDE
+        + (irq / 32) as u64 * 4
+}
+
+/// Initialise le PLIC pour le contexte S-mode de `hartid` : toutes les
+/// sources masquées, seuil 0.
+pub fn init_from_fdt(fdt: &Fdt<'_>, hartid: u64) -> Result<(), PlicError> {
+    let node = fdt
+        .find_compatible(b"riscv,plic0")
+        .or_else(|| fdt.find_compatible(b"sifive,plic-1.0.0"))
+        .ok_or(PlicError::NotFound)?;
+    let (base, _) = node.reg_first().ok_or(PlicError::NotFound)?;
+    let sources = node
+        .property_u32(b"riscv,ndev")
+        .map_or(MAX_SOURCES, |ndev| (ndev + 1).min(MAX_SOURCES));
+    BASE.store(base, Ordering::Release);
+    CONTEXT.store(supervisor_context(hartid), Ordering::Relaxed);
+    SOURCES.store(sources, Ordering::Release);
+
+    for irq in 1..sources {
+        write32(base + PRIORITY_BASE + irq as u64 * 4, 0);
+    }
+    for word in 0..sources.div_ceil(32) {
+        write32(enable_word(word * 32), 0);
+    }
+    write32(context_reg(CONTEXT_THRESHOLD), 0);
+    Ok(())
+}
+
+/// Réclame la source la plus prioritaire en attente (0 = aucune).
+#[inline]
+pub fn claim() -> u32 {
+    if BASE.load(Ordering::Acquire) == 0 {
+        return 0;
+    }
+    read32(context_reg(CONTEXT_CLAIM))
+}
+
+fn valid(irq: u32) -> bool {
+    irq != 0 && irq < SOURCES.load(Ordering::Acquire)
+}
+
+impl InterruptController for Riscv64 {
+    /// Complétion PLIC de la source retournée par `claim`.
+    #[inline]
+    fn end_of_interrupt(irq: u32) {
+        if valid(irq) {
+            write32(context_reg(CONTEXT_CLAIM), irq);
+        }
+    }
+
+    fn mask(irq: u32) {
+        if valid(irq) {
+            let word = enable_word(irq);
+            write32(word, read32(word) & !(1 << (irq % 32)));
+        }
+    }
+
+    fn unmask(irq: u32) {
+        if valid(irq) {
+            let base = BASE.load(Ordering::Acquire);
+            write32(base + PRIORITY_BASE + irq as u64 * 4, DEFAULT_PRIORITY);
+            let word = enable_word(irq);
+            write32(word, read32(word) | (1 << (irq % 32)));
+        }
+    }
+
+    /// IPI logicielle S-mode vers le hart `cpu` ; `irq` est ignoré (une
+    /// seule interruption logicielle par hart).
+    fn send_ipi(cpu: u32, _irq: u32) {
+        let _ = super::sbi::send_ipi(1, cpu as usize);
+    }
+}
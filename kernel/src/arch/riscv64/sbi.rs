@@ -0,0 +1,76 @@
+//! # arch/riscv64/sbi.rs — Appels SBI (Supervisor Binary Interface)
+//!
+//! Interface vers le firmware M-mode (OpenSBI) : `ecall` avec `a7` = EID,
+//! `a6` = FID, arguments dans `a0..a5`, retour `(erreur, valeur)` dans
+//! `a0`/`a1` (SBI v0.2+). Seules les extensions utilisées par le port sont
+//! exposées : Base (sonde), TIME, sPI, DBCN et la console legacy en repli.
+
+/// Extension Base.
+const EID_BASE: usize = 0x10;
+/// Extension Timer (`TIME`).
+const EID_TIME: usize = 0x5449_4D45;
+/// Extension IPI (`sPI`).
+const EID_IPI: usize = 0x0073_5049;
+/// Extension Debug Console (`DBCN`, SBI 2.0).
+pub const EID_DBCN: usize = 0x4442_434E;
+/// Console legacy v0.1 (`sbi_console_putchar`).
+const EID_LEGACY_PUTCHAR: usize = 0x01;
+
+const FID_BASE_PROBE: usize = 3;
+const FID_TIME_SET_TIMER: usize = 0;
+const FID_IPI_SEND: usize = 0;
+const FID_DBCN_WRITE_BYTE: usize = 2;
+
+/// Code d'erreur SBI (négatif, spécification SBI §3).
+pub type SbiError = isize;
+
+#[inline(always)]
+fn ecall(eid: usize, fid: usize, arg0: usize, arg1: usize) -> Result<usize, SbiError> {
+    let error: isize;
+    let value: usize;
+    // SAFETY: `ecall` depuis S-mode vers le firmware ; seuls a0/a1 sont
+    // modifiés selon la convention SBI.
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            inlateout("a0") arg0 => error,
+            inlateout("a1") arg1 => value,
+            in("a6") fid,
+            in("a7") eid,
+            options(nostack),
+        );
+    }
+    if error == 0 {
+        Ok(value)
+    } else {
+        Err(error)
+    }
+}
+
+/// Vrai si le firmware implémente l'extension `eid`.
+pub fn probe_extension(eid: usize) -> bool {
+    matches!(ecall(EID_BASE, FID_BASE_PROBE, eid, 0), Ok(v) if v != 0)
+}
+
+/// Programme la prochaine interruption timer S-mode à `stime` (en ticks
+/// `time`) ; `u64::MAX` désarme et acquitte STIP.
+pub fn set_timer(stime: u64) -> Result<(), SbiError> {
+    ecall(EID_TIME, FID_TIME_SET_TIMER, stime as usize, 0).map(|_| ())
+}
+
+/// Envoie une IPI logicielle S-mode aux harts `hart_mask << hart_mask_base`.
+pub fn send_ipi(hart_mask: usize, hart_mask_base: usize) -> Result<(), SbiError> {
+    ecall(EID_IPI, FID_IPI_SEND, hart_mask, hart_mask_base).map(|_| ())
+}
+
+/// Écrit un octet sur la console de debug SBI 2.0.
+#[inline]
+pub fn dbcn_write_byte(byte: u8) -> Result<(), SbiError> {
+    ecall(EID_DBCN, FID_DBCN_WRITE_BYTE, byte as usize, 0).map(|_| ())
+}
+
+/// Écrit un octet via la console legacy (pas de code d'erreur fiable).
+#[inline]
+pub fn legacy_putchar(byte: u8) {
+    let _ = ecall(EID_LEGACY_PUTCHAR, 0, byte as usize, 0);
+}
@@ -0,0 +1,60 @@
+//! # arch/riscv64/timer.rs — Timer S-mode (CSR `time` + SBI TIME)
+//!
+//! Compteur `time` à `timebase-frequency` Hz (nœud `/cpus` du device tree)
+//! et échéance one-shot par `sbi_set_timer`, délivrée en interruption timer
+//! superviseur (scause 5).
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use super::{sbi, Riscv64};
+use crate::arch::fdt::Fdt;
+use crate::arch::interface::{ns_to_ticks, ArchTimer};
+
+/// `sie.STIE`.
+const SIE_STIE: u64 = 1 << 5;
+
+static FREQUENCY_HZ: AtomicU64 = AtomicU64::new(0);
+static TICKS: AtomicU64 = AtomicU64::new(0);
+
+/// Lit la fréquence du device tree, désarme le timer et active STIE.
+pub fn init(fdt: &Fdt<'_>) {
+    let hz = fdt
+        .find_path("/cpus")
+        .and_then(|cpus| cpus.property_u32(b"timebase-frequency"))
+        .unwrap_or(0);
+    FREQUENCY_HZ.store(hz as u64, Ordering::Release);
+    let _ = sbi::set_timer(u64::MAX);
+    // SAFETY: `sie` est un CSR superviseur ; seul STIE est positionné.
+    unsafe {
+        core::arch::asm!("csrs sie, {}", in(reg) SIE_STIE, options(nostack, nomem));
+    }
+}
+
+/// Gestionnaire de l'interruption timer : désarme l'échéance (one-shot).
+pub fn handle_irq() {
+    let _ = sbi::set_timer(u64::MAX);
+    TICKS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Échéances expirées depuis le boot.
+#[inline]
+pub fn ticks() -> u64 {
+    TICKS.load(Ordering::Relaxed)
+}
+
+impl ArchTimer for Riscv64 {
+    #[inline]
+    fn counter() -> u64 {
+        super::read_time()
+    }
+
+    #[inline]
+    fn frequency_hz() -> u64 {
+        FREQUENCY_HZ.load(Ordering::Acquire)
+    }
+
+    fn arm_oneshot_ns(ns: u64) {
+        let ticks = ns_to_ticks(ns, Self::frequency_hz()).max(1);
+        let _ = sbi::set_timer(super::read_time().saturating_add(ticks));
+    }
+}
@@ -0,0 +1,177 @@
+//! # arch/riscv64/trap.rs — Pièges S-mode (stvec, mode direct)
+//!
+//! Point d'entrée unique `exo_riscv_trap_entry` : sauvegarde x1..x31, `sepc`,
+//! `sstatus`, `scause` et `stval` dans un `TrapFrame` sur la pile courante,
+//! puis appelle `riscv64_trap_dispatch`. Pas encore de bascule de pile via
+//! `sscratch` : seuls les pièges S-mode → S-mode sont pris en charge tant que
+//! l'ABI utilisateur RISC-V n'existe pas.
+
+use super::{console, plic, timer, Riscv64};
+use crate::arch::interface::InterruptController;
+
+/// Registres sauvegardés (288 octets, pile alignée sur 16).
+#[repr(C)]
+pub struct TrapFrame {
+    /// x1..x31 (`x[0]` = ra, `x[1]` = sp d'origine).
+    pub x: [u64; 31],
+    pub sepc: u64,
+    pub sstatus: u64,
+    pub scause: u64,
+    pub stval: u64,
+    _pad: u64,
+}
+
+const _: () = assert!(core::mem::size_of::<TrapFrame>() == 288);
+
+/// Bit « interruption » de `scause`.
+const SCAUSE_INTERRUPT: u64 = 1 << 63;
+const IRQ_S_SOFTWARE: u64 = 1;
+const IRQ_S_TIMER: u64 = 5;
+const IRQ_S_EXTERNAL: u64 = 9;
+
+/// `sip.SSIP`.
+const SIP_SSIP: u64 = 1 << 1;
+
+core::arch::global_asm!(
+    ".section .text",
+    ".balign 4",
+    ".global exo_riscv_trap_entry",
+    "exo_riscv_trap_entry:",
+    "    addi sp, sp, -288",
+    "    sd x1, 0(sp)",
+    "    sd x3, 16(sp)",
+    "    sd x4, 24(sp)",
+    "    sd x5, 32(sp)",
+    "    sd x6, 40(sp)",
+    "    sd x7, 48(sp)",
+    "    sd x8, 56(sp)",
+    "    sd x9, 64(sp)",
+    "    sd x10, 72(sp)",
+    "    sd x11, 80(sp)",
+    "    sd x12, 88(sp)",
+    "    sd x13, 96(sp)",
+    "    sd x14, 104(sp)",
+    "    sd x15, 112(sp)",
+    "    sd x16, 120(sp)",
+    "    sd x17, 128(sp)",
+    "    sd x18, 136(sp)",
+    "    sd x19, 144(sp)",
+    "    sd x20, 152(sp)",
+    "    sd x21, 160(sp)",
+    "    sd x22, 168(sp)",
+    "    sd x23, 176(sp)",
+    "    sd x24, 184(sp)",
+    "    sd x25, 192(sp)",
+    "    sd x26, 200(sp)",
+    "    sd x27, 208(sp)",
+    "    sd x28, 216(sp)",
+    "    sd x29, 224(sp)",
+    "    sd x30, 232(sp)",
+    "    sd x31, 240(sp)",
+    "    addi t0, sp, 288",
+    "    sd t0, 8(sp)",
+    "    csrr t0, sepc",
+    "    sd t0, 248(sp)",
+    "    csrr t0, sstatus",
+    "    sd t0, 256(sp)",
+    "    csrr t0, scause",
+    "    sd t0, 264(sp)",
+    "    csrr t0, stval",
+    "    sd t0, 272(sp)",
+    "    mv a0, sp",
+    "    call riscv64_trap_dispatch",
+    "    ld t0, 248(sp)",
+    "    csrw sepc, t0",
+    "    ld t0, 256(sp)",
+    "    csrw sstatus, t0",
+    "    ld x1, 0(sp)",
+    "    ld x3, 16(sp)",
+    "    ld x4, 24(sp)",
+    "    ld x5, 32(sp)",
+    "    ld x6, 40(sp)",
+    "    ld x7, 48(sp)",
+    "    ld x8, 56(sp)",
+    "    ld x9, 64(sp)",
+    "    ld x10, 72(sp)",
+    "    ld x11, 80(sp)",
+    "    ld x12, 88(sp)",
+    "    ld x13, 96(sp)",
+    "    ld x14, 104(sp)",
+    "    ld x15, 112(sp)",
+    "    ld x16, 120(sp)",
+    "    ld x17, 128(sp)",
+    "    ld x18, 136(sp)",
+    "    ld x19, 144(sp)",
+    "    ld x20, 152(sp)",
+    "    ld x21, 160(sp)",
+    "    ld x22, 168(sp)",
+    "    ld x23, 176(sp)",
+    "    ld x24, 184(sp)",
+    "    ld x25, 192(sp)",
+    "    ld x26, 200(sp)",
+    "    ld x27, 208(sp)",
+    "    ld x28, 216(sp)",
+    "    ld x29, 224(sp)",
+    "    ld x30, 232(sp)",
+    "    ld x31, 240(sp)",
+    "    addi sp, sp, 288",
+    "    sret",
+);
+
+extern "C" {
+    fn exo_riscv_trap_entry();
+}
+
+/// Installe `exo_riscv_trap_entry` dans `stvec` (mode direct).
+pub fn install() {
+    // SAFETY: l'entrée est alignée sur 4 octets (mode direct = bits 1:0 à 0)
+    // et reste en mémoire pour toute la vie du noyau.
+    unsafe {
+        core::arch::asm!(
+            "csrw stvec, {}",
+            in(reg) exo_riscv_trap_entry as usize,
+            options(nostack, nomem),
+        );
+    }
+}
+
+fn handle_external() {
+    let irq = plic::claim();
+    if irq == 0 {
+        return;
+    }
+    console::write_bytes(b"[RISCV64] IRQ externe non geree ");
+    console::write_hex(irq as u64);
+    console::write_bytes(b"\n");
+    Riscv64::end_of_interrupt(irq);
+}
+
+fn fatal(frame: &TrapFrame) -> ! {
+    console::write_bytes(b"[RISCV64] exception fatale scause=");
+    console::write_hex(frame.scause);
+    console::write_bytes(b" sepc=");
+    console::write_hex(frame.sepc);
+    console::write_bytes(b" stval=");
+    console::write_hex(frame.stval);
+    console::write_bytes(b"\n");
+    super::halt_cpu()
+}
+
+/// Point d'entrée Rust des pièges.
+#[no_mangle]
+extern "C" fn riscv64_trap_dispatch(frame: &mut TrapFrame) {
+    if frame.scause & SCAUSE_INTERRUPT == 0 {
+        fatal(frame);
+    }
+    match frame.scause & !SCAUSE_INTERRUPT {
+        IRQ_S_TIMER => timer::handle_irq(),
+        IRQ_S_EXTERNAL => handle_external(),
+        IRQ_S_SOFTWARE => {
+            // SAFETY: acquittement de l'IPI logicielle (sip.SSIP).
+            unsafe {
+                core::arch::asm!("csrc sip, {}", in(reg) SIP_SSIP, options(nostack, nomem));
+            }
+        }
+        _ => fatal(frame),
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Fournit `read_ticks()` indépendant de l'architecture.
 
-/// Lit le compteur de cycles (TSC sur x86_64, systick sur ARM).
+/// Lit le compteur de cycles (TSC sur x86_64, systick sur ARM, `time` sur RISC-V).
 pub fn read_ticks() -> u64 {
     #[cfg(target_arch = "x86_64")]
     {
@@ -19,7 +19,15 @@ pub fn read_ticks() -> u64 {
         unsafe { core::arch::asm!("mrs {}, cntvct_el0", out(reg) count, options(nostack, nomem)); }
         count
     }
-    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    #[cfg(target_arch = "riscv64")]
+    {
+        crate::arch::riscv64::read_time()
+    }
+    #[cfg(not(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "riscv64"
+    )))]
     {
         0u64
     }
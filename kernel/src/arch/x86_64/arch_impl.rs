@@ -0,0 +1,107 @@
+//! # arch/x86_64/arch_impl.rs — `arch::interface` pour x86_64
+//!
+//! Adaptateur mince au-dessus des primitives existantes (APIC, TSC, CR3).
+//! Le changement de contexte reste `scheduler::core::switch` (`switch_asm.s`),
+//! lié au TCB : `ArchContext` n'est pas implémenté ici.
+
+use super::apic::{self, io_apic, local_apic, x2apic};
+use super::{cpu::tsc, smp::percpu};
+use crate::arch::interface::{Arch, ArchCpu, ArchMmu, ArchTimer, InterruptController};
+use crate::memory::virt::page_table::X86_64Format;
+
+/// Masque de l'adresse PML4 dans CR3 (PCID et bits de contrôle exclus).
+const CR3_ADDR_MASK: u64 = 0x000F_FFFF_FFFF_F000;
+
+/// Architecture x86_64 (type vide, méthodes associées).
+pub struct X86_64;
+
+impl ArchCpu for X86_64 {
+    #[inline]
+    fn cpu_id() -> u32 {
+        percpu::current_cpu_id()
+    }
+
+    #[inline]
+    fn irq_save() -> u64 {
+        super::irq_save()
+    }
+
+    #[inline]
+    fn irq_restore(state: u64) {
+        super::irq_restore(state)
+    }
+
+    fn halt() -> ! {
+        super::halt_cpu()
+    }
+}
+
+impl InterruptController for X86_64 {
+    /// L'EOI LAPIC ne dépend pas de la ligne : `irq` est ignoré.
+    #[inline]
+    fn end_of_interrupt(_irq: u32) {
+        apic::eoi();
+    }
+
+    fn mask(irq: u32) {
+        io_apic::mask_irq(irq);
+    }
+
+    fn unmask(irq: u32) {
+        io_apic::unmask_irq(irq);
+    }
+
+    /// `cpu` = APIC ID, `irq` = vecteur IDT.
+    fn send_ipi(cpu: u32, irq: u32) {
+        if apic::is_x2apic() {
+            x2apic::send_ipi_x2apic(cpu, irq as u8, 0);
+        } else {
+            local_apic::send_ipi(cpu as u8, irq as u8, local_apic::ICR_DM_FIXED);
+        }
+    }
+}
+
+impl ArchTimer for X86_64 {
+    #[inline]
+    fn counter() -> u64 {
+        tsc::read_tsc()
+    }
+
+    #[inline]
+    fn frequency_hz() -> u64 {
+        tsc::tsc_hz()
+    }
+
+    /// LAPIC one-shot à la microseconde (arrondi supérieur).
+    fn arm_oneshot_ns(ns: u64) {
+        local_apic::timer_oneshot_us(ns.div_ceil(1_000).max(1));
+    }
+}
+
+impl ArchMmu for X86_64 {
+    type Format = X86_64Format;
+
+    #[inline]
+    fn current_root() -> u64 {
+        super::read_cr3() & CR3_ADDR_MASK
+    }
+
+    unsafe fn activate(root_phys: u64) {
+        // SAFETY: contrat de `ArchMmu::activate` transmis à l'appelant.
+        unsafe { super::write_cr3(root_phys & CR3_ADDR_MASK) }
+    }
+
+    #[inline]
+    fn flush_page(virt: u64) {
+        super::invlpg(virt);
+    }
+
+    fn flush_all() {
+        // SAFETY: recharger la CR3 courante ne change pas l'espace actif.
+        unsafe { super::write_cr3(super::read_cr3()) }
+    }
+}
+
+impl Arch for X86_64 {
+    const NAME: &'static str = "x86_64";
+}
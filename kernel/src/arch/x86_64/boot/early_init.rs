@@ -240,6 +240,8 @@ pub unsafe fn arch_boot_init(mb2_magic: u32, mb2_info: u64, rsdp_phys: u64) -> B
         boot_info.multiboot2_magic = mb2_magic;
         boot_info.multiboot2_addr = mb2_info;
         let mb2 = super::multiboot2::parse_multiboot2(mb2_info);
+        // Console série : avant la mémoire pour que la suite du boot y soit visible.
+        super::super::serial::init_from_cmdline(super::multiboot2::cmdline(&mb2));
         boot_info.total_memory_kb = mb2.total_memory_kb;
         boot_info.rsdp_phys = if mb2.rsdp_phys != 0 {
             mb2.rsdp_phys
@@ -281,7 +283,9 @@ pub unsafe fn arch_boot_init(mb2_magic: u32, mb2_info: u64, rsdp_phys: u64) -> B
         // ── Chemin exo-boot UEFI ─────────────────────────────────────────────
         // `mb2_info` = adresse physique du BootInfo exo-boot (identité-mappée).
         // `mb2_magic` = EXOBOOT_MAGIC_U32 (0x4F4F_5845 "EXOO").
-        //
+        // Pas de ligne de commande : seule la feature active la console série.
+        super::super::serial::init_from_cmdline(&[]);
+
         // RÈGLE MEM-02 : EmergencyPool EN PREMIER.
         crate::memory::physical::frame::emergency_pool::init();
 
@@ -316,6 +320,24 @@ pub unsafe fn arch_boot_init(mb2_magic: u32, mb2_info: u64, rsdp_phys: u64) -> B
         boot_info.framebuffer_size_bytes =
             core::ptr::read_volatile((mb2_info + 6192) as *const u64);
 
+        // 6320..6335 = EDID de l'écran (adresse physique, taille), 0 sans
+        // protocole EDID côté firmware. Copié tant que la mémoire d'exo-boot
+        // est encore intacte.
+        let edid_phys = core::ptr::read_volatile((mb2_info + 6320) as *const u64);
+        let edid_size = core::ptr::read_volatile((mb2_info + 6328) as *const u64);
+        if edid_phys != 0 && edid_size != 0 {
+            use crate::arch::x86_64::framebuffer_early::{set_boot_edid, BOOT_EDID_MAX};
+            let len = (edid_size as usize).min(BOOT_EDID_MAX);
+            set_boot_edid(core::slice::from_raw_parts(edid_phys as *const u8, len));
+        }
+
+        // 6304 = boot_flags, 6336 = netboot_server : boot PXE / HTTP, relu
+        // par init via SYS_NETBOOT_INFO.
+        super::netboot::record(
+            core::ptr::read_volatile((mb2_info + 6304) as *const u64),
+            core::ptr::read_volatile((mb2_info + 6336) as *const u64),
+        );
+
         // Enregistre la PML4 courante (configurée par exo-boot)
         crate::memory::virt::address_space::KERNEL_AS.init(
             crate::memory::core::types::PhysAddr::new(super::super::read_cr3()),
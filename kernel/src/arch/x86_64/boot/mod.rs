@@ -19,6 +19,7 @@ pub mod memory_map;
 #[cfg_attr(not(feature = "multiboot2_compat"),
     allow(dead_code, unused_imports))]
 pub mod multiboot2;
+pub mod netboot;
 pub mod trampoline_asm;
 pub mod uefi;
 
@@ -291,3 +291,23 @@ pub fn mmap_entries(info: &Multiboot2Info) -> &'static [MmapEntry] {
         core::slice::from_raw_parts(info.mmap_ptr as *const MmapEntry, info.mmap_count as usize)
     }
 }
+
+/// Longueur max lue pour la ligne de commande (garde-fou si non terminée).
+const CMDLINE_MAX: usize = 1024;
+
+/// Retourne la ligne de commande Multiboot2 (sans le NUL final), ou `&[]`.
+pub fn cmdline(info: &Multiboot2Info) -> &'static [u8] {
+    if info.cmdline_ptr == 0 {
+        return &[];
+    }
+    let base = info.cmdline_ptr as *const u8;
+    let mut len = 0usize;
+    // SAFETY: chaîne NUL-terminée du tag CMDLINE, identité-mappée au boot ;
+    // la lecture est bornée par CMDLINE_MAX.
+    unsafe {
+        while len < CMDLINE_MAX && *base.add(len) != 0 {
+            len += 1;
+        }
+        core::slice::from_raw_parts(base, len)
+    }
+}
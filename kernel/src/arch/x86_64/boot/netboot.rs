@@ -0,0 +1,48 @@
+//! # arch/x86_64/boot/netboot  Boot reseau transmis par exo-boot
+//!
+//! exo-boot pose `NETWORK_BOOT` dans `BootInfo.boot_flags` quand il a ete
+//! charge par PXE/TFTP ou UEFI HTTP boot, avec l'IPv4 du serveur de boot
+//! (PXE seulement). init le lit via `SYS_NETBOOT_INFO` ; la racine reste le
+//! volume ExoFS local (pas de racine reseau).
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Bit `NETWORK_BOOT` de `BootInfo.boot_flags` (exo-boot `handoff.rs`).
+pub const NETWORK_BOOT: u64 = 1 << 5;
+
+/// Bit 32 : boot reseau ; bits 0..31 : IPv4 du serveur (octets a.b.c.d).
+static NETBOOT: AtomicU64 = AtomicU64::new(0);
+const NETBOOT_SET: u64 = 1 << 32;
+
+/// Enregistre l'etat de boot reseau lu dans le BootInfo exo-boot.
+pub fn record(boot_flags: u64, netboot_server: u64) {
+    if boot_flags & NETWORK_BOOT == 0 {
+        return;
+    }
+    let [a, b, c, d, ..] = netboot_server.to_le_bytes();
+    let server = u32::from_be_bytes([a, b, c, d]) as u64;
+    NETBOOT.store(NETBOOT_SET | server, Ordering::Release);
+}
+
+/// IPv4 du serveur de boot (`[0; 4]` si inconnue), `None` en boot disque.
+pub fn server() -> Option<[u8; 4]> {
+    let state = NETBOOT.load(Ordering::Acquire);
+    (state & NETBOOT_SET != 0).then(|| (state as u32).to_be_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_netboot_server_byte_order() {
+        assert_eq!(server(), None);
+        record(0x04, u32::from_le_bytes([10, 0, 2, 2]) as u64);
+        assert_eq!(server(), None);
+        record(
+            NETWORK_BOOT | 0x04,
+            u32::from_le_bytes([10, 0, 2, 2]) as u64,
+        );
+        assert_eq!(server(), Some([10, 0, 2, 2]));
+    }
+}
@@ -41,6 +41,15 @@ pub const MSR_CSTAR: u32 = 0xC000_0083;
 /// MSR SFMASK : masque RFLAGS appliqué lors d'un SYSCALL
 pub const MSR_SFMASK: u32 = 0xC000_0084;
 
+/// MSR SYSENTER_CS : sélecteur CS Ring 0 chargé par SYSENTER (SS = CS+8)
+pub const MSR_IA32_SYSENTER_CS: u32 = 0x174;
+
+/// MSR SYSENTER_ESP : pile Ring 0 chargée par SYSENTER
+pub const MSR_IA32_SYSENTER_ESP: u32 = 0x175;
+
+/// MSR SYSENTER_EIP : point d'entrée Ring 0 de SYSENTER
+pub const MSR_IA32_SYSENTER_EIP: u32 = 0x176;
+
 /// MSR GS_BASE : base du segment GS (kernel)
 pub const MSR_GS_BASE: u32 = 0xC000_0101;
 
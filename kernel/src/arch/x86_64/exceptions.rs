@@ -305,6 +305,8 @@ define_exception_handler_no_errcode!(ipi_panic_handler, do_ipi_panic);
 define_exception_handler_no_errcode!(exophoenix_freeze_handler, do_exophoenix_freeze);
 define_exception_handler_no_errcode!(exophoenix_pmc_handler, do_exophoenix_pmc);
 define_exception_handler_no_errcode!(exophoenix_tlb_handler, do_exophoenix_tlb);
+#[cfg(feature = "ia32_compat")]
+define_exception_handler_no_errcode!(ia32_int80_handler, do_ia32_int80);
 
 // ── Handlers Rust d'exceptions ────────────────────────────────────────────────
 
@@ -626,6 +628,10 @@ extern "C" fn do_breakpoint(frame: *mut ExceptionFrame) {
         // SIGTRAP
         exception_return_to_user(frame);
     }
+    // Kernel : int3 temporaire d'un correctif à chaud en cours d'écriture
+    if crate::livepatch::poke::handle_int3(&mut frame.rip) {
+        return;
+    }
     // Kernel : kprobe ou debug noyau
 }
 
@@ -1079,6 +1085,9 @@ extern "C" fn do_page_fault(frame: *mut ExceptionFrame) {
         FaultResult::Oom { addr } => {
             // Out of memory — OOM killer notifié.
             let _ = addr;
+            crate::memory::utils::pressure::note_oom(
+                crate::arch::x86_64::time::ktime::ktime_get_ns(),
+            );
             if frame.from_userspace() {
                 queue_signal_for_current(crate::process::signal::Signal::SIGKILL);
                 exception_return_to_user(frame);
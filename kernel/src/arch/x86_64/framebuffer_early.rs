@@ -468,6 +468,36 @@ pub fn user_framebuffer_contains(phys_addr: u64, size: u64) -> bool {
     phys_addr >= fb.phys_addr && request_end <= fb_end
 }
 
+/// EDID de l'écran transmis par exo-boot : bloc de base et jusqu'à trois
+/// extensions, lu par la couche d'affichage via `SYS_DISPLAY_EDID`.
+pub const BOOT_EDID_MAX: usize = 512;
+
+struct BootEdid {
+    bytes: [u8; BOOT_EDID_MAX],
+    len: usize,
+}
+
+static BOOT_EDID: Mutex<BootEdid> = Mutex::new(BootEdid {
+    bytes: [0; BOOT_EDID_MAX],
+    len: 0,
+});
+
+/// Conserve l'EDID de boot (tronqué à `BOOT_EDID_MAX`, par blocs entiers).
+pub fn set_boot_edid(bytes: &[u8]) {
+    let len = bytes.len().min(BOOT_EDID_MAX) / 128 * 128;
+    let mut edid = BOOT_EDID.lock();
+    edid.bytes[..len].copy_from_slice(&bytes[..len]);
+    edid.len = len;
+}
+
+/// Copie l'EDID de boot dans `out` ; retourne sa taille (0 si absent).
+pub fn boot_edid(out: &mut [u8]) -> usize {
+    let edid = BOOT_EDID.lock();
+    let n = edid.len.min(out.len());
+    out[..n].copy_from_slice(&edid.bytes[..n]);
+    edid.len
+}
+
 /// Affiche un message de progression dans la liste des modules.
 pub fn stage_ok(label: &str) {
     let mut state = CONSOLE.lock();
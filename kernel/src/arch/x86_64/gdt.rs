@@ -26,6 +26,7 @@
 //! 0x20 = USER_DS    (Ring 3, 64-bit data)      ← SS pour SYSRET
 //! 0x28 = USER_CS64  (Ring 3, 64-bit code)      ← CS pour SYSRET
 //! 0x30/0x38 = TSS   (2 descripteurs × 8 bytes)
+//! 0x40 = USER_TLS32 (Ring 3, TLS i386 — set_thread_area, feature ia32_compat)
 //! ```
 
 use core::sync::atomic::{AtomicBool, Ordering};
@@ -42,6 +43,10 @@ pub const GDT_USER_CS32: u16 = 0x18 | 3; // Ring 3
 pub const GDT_USER_DS: u16 = 0x20 | 3; // Ring 3
 pub const GDT_USER_CS64: u16 = 0x28 | 3; // Ring 3
 pub const GDT_TSS_SEL: u16 = 0x30; // TSS (Ring 0)
+pub const GDT_USER_TLS32: u16 = 0x40 | 3; // Ring 3, TLS i386 (set_thread_area)
+
+/// Index GDT du descripteur TLS 32-bit (ancien slot de padding).
+pub const GDT_TLS32_INDEX: usize = 8;
 
 const _: () = assert!(
     GDT_KERNEL_DS == GDT_KERNEL_CS + 8,
@@ -170,7 +175,7 @@ impl GdtDescriptor {
 
 // ── Table GDT per-CPU ─────────────────────────────────────────────────────────
 
-const GDT_ENTRIES: usize = 9; // NULL, KCODE, KDATA, UCODE32, UDATA, UCODE64, TSS×2
+const GDT_ENTRIES: usize = 9; // NULL, KCODE, KDATA, UCODE32, UDATA, UCODE64, TSS×2, TLS32
 
 /// GDT d'un CPU (alignée 8 bytes)
 #[derive(Debug, Clone, Copy)]
@@ -208,10 +213,17 @@ impl Gdt {
         let tss_limit = (core::mem::size_of::<tss::TaskStateSegment>() - 1) as u32;
         self.entries[6] = GdtDescriptor::tss_lower(tss_addr, tss_limit);
         self.entries[7] = GdtDescriptor::tss_upper(tss_addr);
-        self.entries[8] = GdtDescriptor::null(); // padding pour alignement
+        self.entries[8] = GdtDescriptor::null(); // TLS32 : vide tant qu'aucun thread i386
     }
 }
 
+/// Encode un descripteur segment user (DPL=3) pour `set_thread_area`.
+///
+/// `access` et `flags` sont déjà validés par l'appelant (compat i386).
+pub const fn encode_user_descriptor(base: u32, limit: u32, access: u8, flags: u8) -> u64 {
+    GdtDescriptor::new(base, limit, access, flags).0
+}
+
 // ── GDTs statiques per-CPU ────────────────────────────────────────────────────
 
 static mut CPU_GDTS: [Gdt; MAX_CPUS] = [Gdt::new(); MAX_CPUS];
@@ -297,6 +309,29 @@ pub unsafe fn init_gdt_for_cpu(cpu_id: usize, kernel_stack_top: u64) {
     GDT_INITIALIZED.store(true, Ordering::Release);
 }
 
+/// Installe le descripteur TLS 32-bit `raw` dans la GDT du CPU `cpu_id`.
+///
+/// Appelé au context switch et par `set_thread_area`. La nouvelle base n'est
+/// prise en compte qu'au prochain chargement du sélecteur `GDT_USER_TLS32`.
+///
+/// # SAFETY
+/// `cpu_id` doit être le CPU courant (la GDT d'un autre CPU peut être en
+/// cours d'utilisation) et `raw` un descripteur DPL=3 non système.
+pub unsafe fn load_tls32_descriptor(cpu_id: usize, raw: u64) {
+    if cpu_id >= MAX_CPUS {
+        return;
+    }
+    // SAFETY: slot per-CPU du CPU courant, seule l'entrée TLS32 est modifiée ;
+    // aucun sélecteur kernel/TSS ne la référence.
+    unsafe {
+        let gdt = &mut *core::ptr::addr_of_mut!(CPU_GDTS[cpu_id]);
+        core::ptr::write_volatile(
+            &mut gdt.entries[GDT_TLS32_INDEX] as *mut GdtDescriptor,
+            GdtDescriptor(raw),
+        );
+    }
+}
+
 /// Retourne `true` si la GDT a été initialisée
 #[inline(always)]
 pub fn gdt_ready() -> bool {
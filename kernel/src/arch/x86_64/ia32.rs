@@ -0,0 +1,529 @@
+//! # arch/x86_64/ia32.rs — Entrées syscall i386 (feature `ia32_compat`)
+//!
+//! Un processus chargé depuis un ELF32/EM_386 tourne en mode compatibilité
+//! (CS = `GDT_USER_CS32`). Il entre dans le noyau par deux chemins :
+//!
+//! | Entrée        | Mécanisme                         | Stub ASM              |
+//! |---------------|-----------------------------------|-----------------------|
+//! | `int 0x80`    | interrupt gate DPL3 (idt.rs)      | `ia32_int80_handler`  |
+//! | `sysenter`    | MSR SYSENTER_* (Intel uniquement) | `ia32_sysenter_entry` |
+//!
+//! Les deux construisent une `ExceptionFrame` complète et retournent par
+//! IRETQ : tous les registres i386 sont restaurés depuis la frame, et
+//! `syscall::compat::ia32::dispatch_ia32` n'écrit que `eax` (et, après un
+//! execve réussi, EIP/ESP/CS/SS).
+//!
+//! ## Convention sysenter
+//! Pas de vDSO : l'appelant suit le contrat `__kernel_vsyscall` de Linux
+//! (`push ecx; push edx; push ebp; mov ebp, esp; sysenter`), précédé du
+//! `call` qui empile l'adresse de retour. `do_ia32_sysenter` relit ces
+//! quatre mots depuis `[ebp]`, ce qui fournit aussi le 6e argument.
+//!
+//! ## Exec 64-bit → i386
+//! SYSRETQ ne sait pas revenir proprement en mode compat vers une nouvelle
+//! image ; `syscall_rust_handler` diverge alors vers `enter_user32`.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use super::cpu::features::{cpu_features_or_none, CpuVendor};
+use super::cpu::msr;
+use super::exceptions::ExceptionFrame;
+use super::gdt::{
+    encode_user_descriptor, load_tls32_descriptor, GDT_KERNEL_CS, GDT_TLS32_INDEX, GDT_USER_CS32,
+    GDT_USER_DS,
+};
+use super::smp::percpu::{self, MAX_CPUS};
+
+// ── Piles SYSENTER ────────────────────────────────────────────────────────────
+
+/// Pile chargée par SYSENTER avant le switch vers `gs:[0x00]`.
+///
+/// `ia32_sysenter_entry` n'y pousse rien : elle ne sert que de RSP valide
+/// pendant les quelques instructions qui précèdent le chargement de la pile
+/// kernel du thread (NMI/#MC utilisent leurs IST).
+#[repr(C, align(64))]
+struct SysenterStack([u8; 64]);
+
+static mut SYSENTER_STACKS: [SysenterStack; MAX_CPUS] =
+    [const { SysenterStack([0; 64]) }; MAX_CPUS];
+
+/// Sommet de la pile SYSENTER du CPU `cpu_id` (mappée dans la PML4 user KPTI).
+pub fn sysenter_stack_top(cpu_id: usize) -> u64 {
+    let cpu = cpu_id.min(MAX_CPUS - 1);
+    // SAFETY: calcul d'adresse uniquement, aucun accès au contenu.
+    let base = unsafe { core::ptr::addr_of!(SYSENTER_STACKS[cpu]) as u64 };
+    base + core::mem::size_of::<SysenterStack>() as u64
+}
+
+// ── Instrumentation ───────────────────────────────────────────────────────────
+
+static INT80_COUNT: AtomicU64 = AtomicU64::new(0);
+static SYSENTER_COUNT: AtomicU64 = AtomicU64::new(0);
+static SYSENTER_FAULTS: AtomicU64 = AtomicU64::new(0);
+
+/// Snapshot des compteurs d'entrée i386.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ia32EntryStats {
+    /// Entrées par `int 0x80`
+    pub int80: u64,
+    /// Entrées par `sysenter`
+    pub sysenter: u64,
+    /// Frames `__kernel_vsyscall` illisibles (retour vers EIP=0)
+    pub sysenter_faults: u64,
+}
+
+pub fn ia32_entry_stats() -> Ia32EntryStats {
+    Ia32EntryStats {
+        int80: INT80_COUNT.load(Ordering::Relaxed),
+        sysenter: SYSENTER_COUNT.load(Ordering::Relaxed),
+        sysenter_faults: SYSENTER_FAULTS.load(Ordering::Relaxed),
+    }
+}
+
+// ── Initialisation per-CPU ────────────────────────────────────────────────────
+
+/// Configure SYSENTER et les segments data user sur le CPU courant.
+///
+/// Appelé par `init_syscall()` sur chaque CPU. Sur AMD, SYSENTER lève #UD en
+/// mode long : seul `int 0x80` est disponible et les MSR restent à zéro.
+pub fn init_sysenter() {
+    load_user_data_segments();
+
+    let is_intel = cpu_features_or_none().is_some_and(|f| f.vendor == CpuVendor::Intel);
+    if !is_intel {
+        return;
+    }
+    let cpu_id = percpu::current_cpu_id() as usize;
+    // SAFETY: MSR SYSENTER_* architecturaux sur Intel 64, Ring 0. CS pointe
+    // sur le code kernel 64-bit (SS = CS+8 = KERNEL_DS), EIP sur un stub valide.
+    unsafe {
+        msr::write_msr(msr::MSR_IA32_SYSENTER_CS, GDT_KERNEL_CS as u64);
+        msr::write_msr(msr::MSR_IA32_SYSENTER_ESP, sysenter_stack_top(cpu_id));
+        msr::write_msr(
+            msr::MSR_IA32_SYSENTER_EIP,
+            ia32_sysenter_entry as *const () as u64,
+        );
+    }
+}
+
+/// Charge `GDT_USER_DS` dans DS/ES.
+///
+/// Le mode compat utilise DS/ES : un sélecteur nul y lève #GP. Le noyau
+/// 64-bit les ignore, et un sélecteur DPL3 survit aux IRETQ vers Ring 3.
+#[inline]
+pub fn load_user_data_segments() {
+    // SAFETY: GDT_USER_DS est un segment data DPL3 valide ; chargeable en
+    // Ring 0 (max(CPL, RPL) <= DPL) et sans effet sur l'adressage 64-bit.
+    unsafe {
+        core::arch::asm!(
+            "mov ds, {sel:x}",
+            "mov es, {sel:x}",
+            sel = in(reg) GDT_USER_DS,
+            options(nostack, preserves_flags),
+        );
+    }
+}
+
+// ── Stubs ASM ─────────────────────────────────────────────────────────────────
+
+const IA32_USER_CS: u64 = GDT_USER_CS32 as u64;
+const IA32_USER_DS: u64 = GDT_USER_DS as u64;
+
+// Entrée SYSENTER (mode compat → Ring 0 64-bit, IF=0).
+//
+// Construit une ExceptionFrame identique à celle de
+// `define_exception_handler_no_errcode!` : RIP/RSP/RFLAGS sont des
+// placeholders complétés par `do_ia32_sysenter` depuis la frame user.
+core::arch::global_asm!(
+    ".section .text",
+    ".global ia32_sysenter_entry",
+    ".type   ia32_sysenter_entry, @function",
+    "ia32_sysenter_entry:",
+    "swapgs",
+    "mov  gs:[0x50], rax",
+    "mov  rax, gs:[0x40]", // kpti_kernel_cr3
+    "test rax, rax",
+    "jz   3f",
+    "mov  cr3, rax",
+    "3:",
+    "mov  rax, gs:[0x50]",
+    "mov  rsp, gs:[0x00]", // pile kernel du thread courant
+    "push {user_ds}",      // SS
+    "push rbp",            // RSP (recalculé depuis ebp)
+    "push 0x202",          // RFLAGS
+    "push {user_cs}",      // CS
+    "push 0",              // RIP (lu depuis la frame __kernel_vsyscall)
+    "push 0",              // error_code synthétique
+    "push rax",
+    "push rbx",
+    "push rcx",
+    "push rdx",
+    "push rsi",
+    "push rdi",
+    "push rbp",
+    "push r8",
+    "push r9",
+    "push r10",
+    "push r11",
+    "push r12",
+    "push r13",
+    "push r14",
+    "push r15",
+    "mov  rdi, rsp",
+    "mov  r12, rsp",
+    "and  rsp, -16",
+    "call do_ia32_sysenter",
+    "mov  rsp, r12",
+    "pop  r15",
+    "pop  r14",
+    "pop  r13",
+    "pop  r12",
+    "pop  r11",
+    "pop  r10",
+    "pop  r9",
+    "pop  r8",
+    "pop  rbp",
+    "pop  rdi",
+    "pop  rsi",
+    "pop  rdx",
+    "pop  rcx",
+    "pop  rbx",
+    "pop  rax",
+    "add  rsp, 8",
+    "mov  gs:[0x50], rax",
+    "mov  rax, gs:[0x48]", // kpti_user_cr3
+    "test rax, rax",
+    "jz   4f",
+    "mov  cr3, rax",
+    "4:",
+    "mov  rax, gs:[0x50]",
+    "swapgs",
+    "iretq",
+    ".size ia32_sysenter_entry, . - ia32_sysenter_entry",
+    user_ds = const IA32_USER_DS,
+    user_cs = const IA32_USER_CS,
+);
+
+// Premier retour vers une image i386 depuis un contexte SYSCALL 64-bit.
+//
+// rdi = EIP d'entrée, rsi = ESP initial. GS kernel actif, pile kernel
+// quelconque (abandonnée : la prochaine entrée repart de gs:[0x00]).
+core::arch::global_asm!(
+    ".section .text",
+    ".global ia32_enter_user32_asm",
+    ".type   ia32_enter_user32_asm, @function",
+    "ia32_enter_user32_asm:",
+    "cli",
+    "mov  eax, {user_ds}",
+    "mov  ds, eax",
+    "mov  es, eax",
+    "push {user_ds}", // SS
+    "push rsi",       // ESP
+    "push 0x202",     // RFLAGS : IF=1
+    "push {user_cs}", // CS
+    "push rdi",       // EIP
+    "xor  ebx, ebx",
+    "xor  ecx, ecx",
+    "xor  edx, edx",
+    "xor  esi, esi",
+    "xor  edi, edi",
+    "xor  ebp, ebp",
+    "xor  r8d, r8d",
+    "xor  r9d, r9d",
+    "xor  r10d, r10d",
+    "xor  r11d, r11d",
+    "xor  r12d, r12d",
+    "xor  r13d, r13d",
+    "xor  r14d, r14d",
+    "xor  r15d, r15d",
+    "mov  rax, gs:[0x48]", // kpti_user_cr3
+    "test rax, rax",
+    "jz   5f",
+    "mov  cr3, rax",
+    "5:",
+    "xor  eax, eax",
+    "swapgs",
+    "iretq",
+    ".size ia32_enter_user32_asm, . - ia32_enter_user32_asm",
+    user_ds = const IA32_USER_DS,
+    user_cs = const IA32_USER_CS,
+);
+
+extern "C" {
+    fn ia32_sysenter_entry();
+    fn ia32_enter_user32_asm(rip: u64, rsp: u64) -> !;
+}
+
+/// Adresses des stubs exécutés sous CR3 user (mappés par kpti_split.rs).
+pub fn kpti_transition_stubs() -> [u64; 2] {
+    [
+        ia32_sysenter_entry as *const () as u64,
+        ia32_enter_user32_asm as *const () as u64,
+    ]
+}
+
+/// Saute vers une image i386 fraîchement chargée par execve.
+///
+/// # Safety
+/// Contexte SYSCALL (GS kernel actif), `rip`/`rsp` < 4 GiB dans l'espace
+/// du processus courant, CR3 user KPTI déjà publié dans `gs:[0x48]`.
+pub unsafe fn enter_user32(rip: u64, rsp: u64) -> ! {
+    // SAFETY: contrat de l'appelant ; le stub ne revient jamais.
+    unsafe { ia32_enter_user32_asm(rip & 0xFFFF_FFFF, rsp & 0xFFFF_FFFF) }
+}
+
+// ── Handlers Rust ─────────────────────────────────────────────────────────────
+
+/// Handler `int 0x80` (stub `ia32_int80_handler` dans exceptions.rs).
+#[no_mangle]
+extern "C" fn do_ia32_int80(frame: *mut ExceptionFrame) {
+    // SAFETY: frame construite par le stub d'entrée sur la pile kernel.
+    let frame = unsafe { &mut *frame };
+    INT80_COUNT.fetch_add(1, Ordering::Relaxed);
+    crate::arch::x86_64::spectre::apply_ibrs();
+    crate::syscall::compat::ia32::dispatch_ia32(frame);
+    load_user_data_segments();
+}
+
+/// Handler SYSENTER : reconstruit EIP/ESP depuis la frame `__kernel_vsyscall`.
+#[no_mangle]
+extern "C" fn do_ia32_sysenter(frame: *mut ExceptionFrame) {
+    // SAFETY: frame construite par `ia32_sysenter_entry` sur la pile kernel.
+    let frame = unsafe { &mut *frame };
+    SYSENTER_COUNT.fetch_add(1, Ordering::Relaxed);
+    crate::arch::x86_64::spectre::apply_ibrs();
+
+    // [ebp] = ebp appelant, [ebp+4] = edx, [ebp+8] = ecx, [ebp+12] = EIP retour.
+    let user_sp = frame.rbp & 0xFFFF_FFFF;
+    let mut words = [0u32; 4];
+    let read = crate::syscall::validation::copy_from_user(
+        words.as_mut_ptr() as *mut u8,
+        user_sp as *const u8,
+        core::mem::size_of_val(&words),
+    );
+    if read.is_err() {
+        // Frame illisible : retour vers EIP=0 → #PF Ring 3 → SIGSEGV.
+        SYSENTER_FAULTS.fetch_add(1, Ordering::Relaxed);
+        frame.rip = 0;
+        frame.rsp = user_sp;
+        frame.rax = crate::syscall::errno::EFAULT as u64 & 0xFFFF_FFFF;
+        return;
+    }
+    frame.rbp = words[0] as u64;
+    frame.rdx = words[1] as u64;
+    frame.rcx = words[2] as u64;
+    frame.rip = words[3] as u64;
+    frame.rsp = user_sp + 16;
+
+    crate::syscall::compat::ia32::dispatch_ia32(frame);
+    load_user_data_segments();
+}
+
+// ── État i386 du processus courant ────────────────────────────────────────────
+
+/// `true` si le processus courant exécute une image i386.
+pub fn current_is_ia32() -> bool {
+    use crate::process::core::pcb::process_flags;
+    use crate::process::core::pid::Pid;
+    use crate::process::core::registry::PROCESS_REGISTRY;
+    use crate::scheduler::core::task::ThreadControlBlock;
+
+    // SAFETY: try_read_current_tcb vérifie que GS pointe la zone per-CPU.
+    let tcb_ptr = unsafe { percpu::try_read_current_tcb().unwrap_or(0) };
+    if tcb_ptr == 0 {
+        return false;
+    }
+    // SAFETY: TCB courant maintenu par le scheduler pendant le syscall.
+    let tcb = unsafe { &*(tcb_ptr as *const ThreadControlBlock) };
+    PROCESS_REGISTRY
+        .find_by_pid(Pid(tcb.pid.0))
+        .is_some_and(|pcb| pcb.flags.load(Ordering::Acquire) & process_flags::IA32 != 0)
+}
+
+// ── TLS i386 (set_thread_area / get_thread_area) ──────────────────────────────
+
+/// `struct user_desc` de Linux i386 (asm/ldt.h).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UserDesc {
+    pub entry_number: u32,
+    pub base_addr: u32,
+    pub limit: u32,
+    /// seg_32bit:1, contents:2, read_exec_only:1, limit_in_pages:1,
+    /// seg_not_present:1, useable:1
+    pub flags: u32,
+}
+
+const UD_SEG_32BIT: u32 = 1 << 0;
+const UD_CONTENTS_SHIFT: u32 = 1;
+const UD_READ_EXEC_ONLY: u32 = 1 << 3;
+const UD_LIMIT_IN_PAGES: u32 = 1 << 4;
+const UD_SEG_NOT_PRESENT: u32 = 1 << 5;
+const UD_USEABLE: u32 = 1 << 6;
+
+/// Seul slot TLS exposé : `entry_number` vaut 8 (sélecteur `GDT_USER_TLS32`).
+pub const TLS32_ENTRY: u32 = GDT_TLS32_INDEX as u32;
+
+impl UserDesc {
+    /// Descripteur « vide » de Linux : efface le slot.
+    fn is_empty(&self) -> bool {
+        self.base_addr == 0
+            && self.limit == 0
+            && self.flags & !UD_USEABLE == (UD_READ_EXEC_ONLY | UD_SEG_NOT_PRESENT)
+    }
+}
+
+/// Encode un `user_desc` en descripteur GDT brut (0 = slot vide).
+///
+/// Seuls les segments data (`contents` 0 ou 1) sont acceptés : le slot TLS
+/// ne doit jamais devenir un segment de code.
+pub fn encode_tls_desc(desc: &UserDesc) -> Option<u64> {
+    if desc.is_empty() {
+        return Some(0);
+    }
+    let contents = (desc.flags >> UD_CONTENTS_SHIFT) & 0x3;
+    if contents > 1 {
+        return None;
+    }
+    // P | DPL=3 | S=1 | type data (bit 1 = writable, bit 2 = expand-down, bit 0 = accessed)
+    let mut access: u8 = 0x60 | 0x10 | 0x01 | ((contents as u8) << 2);
+    if desc.flags & UD_READ_EXEC_ONLY == 0 {
+        access |= 0x02;
+    }
+    if desc.flags & UD_SEG_NOT_PRESENT == 0 {
+        access |= 0x80;
+    }
+    let mut gran: u8 = 0;
+    if desc.flags & UD_LIMIT_IN_PAGES != 0 {
+        gran |= 0x8;
+    }
+    if desc.flags & UD_SEG_32BIT != 0 {
+        gran |= 0x4;
+    }
+    if desc.flags & UD_USEABLE != 0 {
+        gran |= 0x1;
+    }
+    Some(encode_user_descriptor(
+        desc.base_addr,
+        desc.limit & 0xF_FFFF,
+        access,
+        gran,
+    ))
+}
+
+/// Décode un descripteur brut vers `user_desc` (pour get_thread_area).
+pub fn decode_tls_desc(raw: u64) -> UserDesc {
+    if raw == 0 {
+        return UserDesc {
+            entry_number: TLS32_ENTRY,
+            flags: UD_READ_EXEC_ONLY | UD_SEG_NOT_PRESENT,
+            ..UserDesc::default()
+        };
+    }
+    let base = ((raw >> 16) & 0xFF_FFFF) | (((raw >> 56) & 0xFF) << 24);
+    let limit = (raw & 0xFFFF) | (((raw >> 48) & 0xF) << 16);
+    let access = (raw >> 40) as u8;
+    let gran = ((raw >> 52) & 0xF) as u8;
+    let mut flags = ((access as u32 >> 2) & 0x3) << UD_CONTENTS_SHIFT;
+    if access & 0x02 == 0 {
+        flags |= UD_READ_EXEC_ONLY;
+    }
+    if access & 0x80 == 0 {
+        flags |= UD_SEG_NOT_PRESENT;
+    }
+    if gran & 0x8 != 0 {
+        flags |= UD_LIMIT_IN_PAGES;
+    }
+    if gran & 0x4 != 0 {
+        flags |= UD_SEG_32BIT;
+    }
+    if gran & 0x1 != 0 {
+        flags |= UD_USEABLE;
+    }
+    UserDesc {
+        entry_number: TLS32_ENTRY,
+        base_addr: base as u32,
+        limit: limit as u32,
+        flags,
+    }
+}
+
+/// Installe le TLS i386 du thread courant : TCB, GDT du CPU et base GS.
+///
+/// La base est aussi publiée dans `user_gs_base` pour que le GS caché reste
+/// cohérent même si l'appelant ne recharge pas son sélecteur.
+pub fn install_current_tls32(raw: u64, base: u32) -> bool {
+    use crate::scheduler::core::task::ThreadControlBlock;
+
+    // SAFETY: try_read_current_tcb vérifie que GS pointe la zone per-CPU.
+    let tcb_ptr = unsafe { percpu::try_read_current_tcb().unwrap_or(0) };
+    if tcb_ptr == 0 {
+        return false;
+    }
+    // SAFETY: TCB du thread courant, seul writer pendant son propre syscall.
+    let tcb = unsafe { &mut *(tcb_ptr as *mut ThreadControlBlock) };
+    tcb.set_tls32_desc(raw);
+    tcb.user_gs_base = base as u64;
+    let cpu_id = percpu::current_cpu_id() as usize;
+    // SAFETY: CPU courant, descripteur DPL3 data construit par encode_tls_desc ;
+    // MSR_KERNEL_GS_BASE contient la base GS user tant que GS kernel est actif.
+    unsafe {
+        load_tls32_descriptor(cpu_id, raw);
+        msr::write_msr(msr::MSR_KERNEL_GS_BASE, base as u64);
+    }
+    true
+}
+
+/// Descripteur TLS i386 du thread courant (0 si absent).
+pub fn current_tls32_desc() -> u64 {
+    use crate::scheduler::core::task::ThreadControlBlock;
+
+    // SAFETY: try_read_current_tcb vérifie que GS pointe la zone per-CPU.
+    let tcb_ptr = unsafe { percpu::try_read_current_tcb().unwrap_or(0) };
+    if tcb_ptr == 0 {
+        return 0;
+    }
+    // SAFETY: TCB courant maintenu par le scheduler pendant le syscall.
+    unsafe { (*(tcb_ptr as *const ThreadControlBlock)).tls32_desc() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tls_desc_roundtrip() {
+        let desc = UserDesc {
+            entry_number: TLS32_ENTRY,
+            base_addr: 0x0804_c000,
+            limit: 0xF_FFFF,
+            flags: UD_SEG_32BIT | UD_LIMIT_IN_PAGES | UD_USEABLE,
+        };
+        let raw = encode_tls_desc(&desc).unwrap();
+        // P=1, DPL=3, S=1, data r/w accessed
+        assert_eq!((raw >> 40) as u8, 0xF3);
+        assert_eq!(decode_tls_desc(raw), desc);
+    }
+
+    #[test]
+    fn test_tls_desc_empty_clears_slot() {
+        let desc = UserDesc {
+            entry_number: TLS32_ENTRY,
+            flags: UD_READ_EXEC_ONLY | UD_SEG_NOT_PRESENT,
+            ..UserDesc::default()
+        };
+        assert_eq!(encode_tls_desc(&desc), Some(0));
+        assert_eq!(decode_tls_desc(0), desc);
+    }
+
+    #[test]
+    fn test_tls_desc_rejects_code_segment() {
+        let desc = UserDesc {
+            entry_number: TLS32_ENTRY,
+            base_addr: 0x1000,
+            limit: 0xFFFF,
+            flags: UD_SEG_32BIT | (2 << UD_CONTENTS_SHIFT),
+        };
+        assert_eq!(encode_tls_desc(&desc), None);
+    }
+}
@@ -64,6 +64,9 @@ const _: () = assert!(
 /// Vecteur IRQ timer (APIC Local Timer)
 pub const VEC_IRQ_TIMER: u8 = IRQ_BASE;
 
+/// Vecteur `int 0x80` — entrée syscall i386 (feature `ia32_compat`)
+pub const VEC_IA32_SYSCALL: u8 = 0x80;
+
 /// Vecteur IPI reschedule (scheduler)
 pub const VEC_IPI_RESCHEDULE: u8 = 0xE0;
 
@@ -110,6 +113,8 @@ impl IdtEntryFlags {
     pub const TRAP_GATE: Self = Self(0x8F); // P=1, DPL=0, Type=0xF
     /// Trap gate accessible depuis Ring 3 (pour INT3, syscall soft)
     pub const TRAP_GATE_USER: Self = Self(0xEF); // P=1, DPL=3, Type=0xF
+    /// Interrupt gate accessible depuis Ring 3 (int 0x80 : IF=0 jusqu'au swapgs)
+    pub const INTERRUPT_GATE_USER: Self = Self(0xEE); // P=1, DPL=3, Type=0xE
 }
 
 /// Entrée IDT 64-bit (16 bytes)
@@ -288,6 +293,10 @@ extern "C" {
     fn exophoenix_freeze_handler();
     fn exophoenix_pmc_handler();
     fn exophoenix_tlb_handler();
+
+    // Entrée syscall i386
+    #[cfg(feature = "ia32_compat")]
+    fn ia32_int80_handler();
 }
 
 // ── Initialisation IDT ────────────────────────────────────────────────────────
@@ -589,6 +598,14 @@ pub fn init_idt() {
     idt.set_stack_index(VEC_EXOPHOENIX_FREEZE, IST_EXOPHOENIX_IPI as u8 + 1);
     idt.set_stack_index(VEC_EXOPHOENIX_PMC, IST_EXOPHOENIX_IPI as u8 + 1);
     idt.set_stack_index(VEC_EXOPHOENIX_TLB, IST_EXOPHOENIX_IPI as u8 + 1);
+    // ── Syscall i386 (int 0x80) ───────────────────────────────────────────────
+    #[cfg(feature = "ia32_compat")]
+    idt.set_handler(
+        VEC_IA32_SYSCALL,
+        ia32_int80_handler as *const () as u64,
+        0,
+        IdtEntryFlags::INTERRUPT_GATE_USER,
+    );
     idt.set_handler(
         VEC_IPI_PANIC,
         ipi_panic_handler as *const () as u64,
@@ -25,5 +25,6 @@ pub use routing::{
     reset_all_masked_since,
     revoke_all_irq,
     sys_irq_register_canonical as sys_irq_register,
+    sys_irq_register_eventfd,
     sys_irq_register_syscall,
 };
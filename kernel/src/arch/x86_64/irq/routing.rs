@@ -67,6 +67,7 @@ pub fn sys_irq_register_syscall(
         Some(reg_params.gsi),
         None,
         Some(reg_params),
+        None,
     )
 }
 
@@ -95,6 +96,43 @@ pub fn sys_irq_register_canonical(
         gsi,
         pci_bdf,
         None,
+        None,
+    )
+}
+
+/// Enregistre un handler livré par eventfd (liaison `eventfd_slot` de
+/// `drivers::irq_eventfd`). Réservé par l'appelant aux sources sans
+/// masquage jusqu'à l'acquittement : une IRQ IOAPIC level doit rester
+/// masquée tant que le driver n'a pas servi le périphérique, ce qu'un
+/// compteur ne peut pas dire.
+pub fn sys_irq_register_eventfd(
+    irq_vector: IrqVector,
+    owner_pid: IrqOwnerPid,
+    source_kind: IrqSourceKind,
+    pci_bdf: Option<u64>,
+    eventfd_slot: u16,
+) -> Result<u64, IrqError> {
+    let endpoint = IpcEndpoint {
+        pid: owner_pid.0,
+        chan_idx: 0,
+        generation: 0,
+        _pad: 0,
+    };
+    let gsi = source_kind.needs_ioapic_mask().then(|| {
+        irq_vector
+            .as_u8()
+            .saturating_sub(IrqVector::VECTOR_IRQ_BASE) as u32
+    });
+
+    sys_irq_register_common(
+        owner_pid,
+        irq_vector,
+        source_kind,
+        endpoint,
+        gsi,
+        pci_bdf,
+        None,
+        Some(eventfd_slot),
     )
 }
 
@@ -106,6 +144,7 @@ fn sys_irq_register_common(
     gsi: Option<u32>,
     pci_bdf: Option<u64>,
     ioapic_route: Option<IrqRouteRegistration>,
+    eventfd_slot: Option<u16>,
 ) -> Result<u64, IrqError> {
     if !irq_vector.is_valid() {
         return Err(IrqError::InvalidVector);
@@ -192,6 +231,7 @@ fn sys_irq_register_common(
         generation,
         owner_pid,
         endpoint,
+        eventfd_slot,
     };
 
     {
@@ -399,6 +439,7 @@ pub fn reset_all_masked_since() {
 #[inline(never)]
 pub fn dispatch_irq(vector: u8, _error_code: Option<u64>) {
     let irq_vector = IrqVector(vector);
+    crate::security::crypto::entropy::add_interrupt_timing(vector);
 
     let table = IRQ_TABLE.read();
     let route = match table.get(irq_vector) {
@@ -440,13 +481,20 @@ pub fn dispatch_irq(vector: u8, _error_code: Option<u64>) {
         }
     }
 
-    // ÉTAPE 3: Collecte handlers sans alloc
+    // ÉTAPE 3: Collecte handlers sans alloc ; les handlers eventfd sont
+    // servis tout de suite et n'attendent pas d'acquittement.
     let mut eps: [Option<IpcEndpoint>; MAX_HANDLERS_PER_IRQ] = [None; MAX_HANDLERS_PER_IRQ];
     let mut n_eps = 0usize;
+    let mut n_eventfds = 0usize;
 
     {
         let handlers = route.handlers.read();
         for h in handlers.iter() {
+            if let Some(slot) = h.eventfd_slot {
+                crate::drivers::irq_eventfd::signal(slot);
+                n_eventfds += 1;
+                continue;
+            }
             if n_eps >= MAX_HANDLERS_PER_IRQ {
                 break;
             }
@@ -456,6 +504,12 @@ pub fn dispatch_irq(vector: u8, _error_code: Option<u64>) {
     }
     let n = n_eps as u32;
 
+    if n == 0 && n_eventfds != 0 {
+        route.masked_since.store(0, Ordering::Release);
+        route.soft_alarmed.store(false, Ordering::Release);
+        return;
+    }
+
     if n == 0 {
         route.pending_acks.store(0, Ordering::Release);
         route.handled_count.store(0, Ordering::Release);
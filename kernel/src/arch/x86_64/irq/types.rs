@@ -111,6 +111,9 @@ pub struct IrqHandler {
     pub generation: u64,
     pub owner_pid: IrqOwnerPid,
     pub endpoint: IpcEndpoint,
+    /// Liaison eventfd (`drivers::irq_eventfd`) : l'IRQ incrémente l'eventfd
+    /// au lieu d'un message IPC, sans acquittement attendu.
+    pub eventfd_slot: Option<u16>,
 }
 
 #[derive(Debug)]
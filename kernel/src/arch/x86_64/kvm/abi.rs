@@ -0,0 +1,339 @@
+//! # kvm/abi.rs — ABI utilisateur de `/dev/exo-kvm`
+//!
+//! Numéros d'ioctl et structures échangées avec le VMM. Les dispositions
+//! reprennent celles de Linux KVM (`kvm_regs`, `kvm_sregs`, `kvm_segment`,
+//! `kvm_userspace_memory_region`, `kvm_cpuid_entry2`) pour qu'un VMM existant
+//! se porte en changeant les numéros ; `kvm_run` est simplifiée et passée en
+//! argument de `EXO_KVM_RUN` au lieu d'être mappée.
+
+use super::Backend;
+
+// ── ioctl du descripteur système (`open("/dev/exo-kvm")`) ────────────────────
+
+/// → `EXO_KVM_API_VERSION`
+pub const EXO_KVM_GET_API_VERSION: u64 = 0xAE00;
+/// → descripteur de VM
+pub const EXO_KVM_CREATE_VM: u64 = 0xAE01;
+/// `(cap)` → 0 si absente, sinon valeur de la capacité
+pub const EXO_KVM_CHECK_EXTENSION: u64 = 0xAE03;
+
+// ── ioctl du descripteur de VM ───────────────────────────────────────────────
+
+/// `(vcpu_id)` → descripteur de vCPU
+pub const EXO_KVM_CREATE_VCPU: u64 = 0xAE41;
+/// `(*const KvmMemoryRegion)` ; `memory_size == 0` retire le slot
+pub const EXO_KVM_SET_USER_MEMORY_REGION: u64 = 0xAE46;
+
+// ── ioctl du descripteur de vCPU ─────────────────────────────────────────────
+
+/// `(*mut KvmRun)` → 0, raison de sortie dans `exit_reason`
+pub const EXO_KVM_RUN: u64 = 0xAE80;
+pub const EXO_KVM_GET_REGS: u64 = 0xAE81;
+pub const EXO_KVM_SET_REGS: u64 = 0xAE82;
+pub const EXO_KVM_GET_SREGS: u64 = 0xAE83;
+pub const EXO_KVM_SET_SREGS: u64 = 0xAE84;
+/// `(vector)` — IRQ externe injectée dès que l'invité l'accepte
+pub const EXO_KVM_INTERRUPT: u64 = 0xAE86;
+/// `(*const KvmCpuid)` — remplace les feuilles CPUID vues par l'invité
+pub const EXO_KVM_SET_CPUID: u64 = 0xAE90;
+
+pub const EXO_KVM_API_VERSION: i64 = 12;
+
+// ── Capacités (`EXO_KVM_CHECK_EXTENSION`) ────────────────────────────────────
+
+/// Technologie matérielle : `EXO_KVM_BACKEND_VMX` ou `EXO_KVM_BACKEND_SVM`.
+pub const EXO_KVM_CAP_BACKEND: u64 = 0;
+/// Nombre maximal de vCPU par VM.
+pub const EXO_KVM_CAP_MAX_VCPUS: u64 = 1;
+/// Nombre maximal de slots mémoire par VM.
+pub const EXO_KVM_CAP_NR_MEMSLOTS: u64 = 2;
+pub const EXO_KVM_BACKEND_VMX: i64 = 1;
+pub const EXO_KVM_BACKEND_SVM: i64 = 2;
+
+pub const MAX_VCPUS: usize = 16;
+pub const MAX_MEMSLOTS: usize = 32;
+pub const MAX_CPUID_ENTRIES: usize = 64;
+
+// ── Raisons de sortie (`KvmRun::exit_reason`, valeurs Linux) ────────────────
+
+pub const EXO_KVM_EXIT_UNKNOWN: u32 = 0;
+pub const EXO_KVM_EXIT_IO: u32 = 2;
+pub const EXO_KVM_EXIT_HLT: u32 = 5;
+pub const EXO_KVM_EXIT_MMIO: u32 = 6;
+pub const EXO_KVM_EXIT_IRQ_WINDOW_OPEN: u32 = 7;
+pub const EXO_KVM_EXIT_SHUTDOWN: u32 = 8;
+pub const EXO_KVM_EXIT_FAIL_ENTRY: u32 = 9;
+pub const EXO_KVM_EXIT_INTR: u32 = 10;
+pub const EXO_KVM_EXIT_INTERNAL_ERROR: u32 = 17;
+
+pub const EXO_KVM_EXIT_IO_IN: u8 = 0;
+pub const EXO_KVM_EXIT_IO_OUT: u8 = 1;
+
+/// Sous-codes de `EXO_KVM_EXIT_INTERNAL_ERROR` (dans `KvmRun::hardware_reason`).
+pub const EXO_KVM_INTERNAL_EMULATION: u64 = 1;
+pub const EXO_KVM_INTERNAL_UNHANDLED_EXIT: u64 = 2;
+
+// ── Validation des arguments scalaires ───────────────────────────────────────
+
+/// `EXO_KVM_CREATE_VCPU` : identifiant inférieur à `MAX_VCPUS`.
+pub(super) fn vcpu_id_arg(arg: u64) -> Option<u32> {
+    (arg < MAX_VCPUS as u64).then_some(arg as u32)
+}
+
+/// `EXO_KVM_INTERRUPT` : vecteur sur 8 bits.
+pub(super) fn irq_vector_arg(arg: u64) -> Option<u8> {
+    u8::try_from(arg).ok()
+}
+
+/// `EXO_KVM_CHECK_EXTENSION` : 0 pour une capacité inconnue, et pour toutes
+/// sans technologie matérielle utilisable.
+pub(super) fn extension_value(cap: u64, backend: Option<Backend>) -> i64 {
+    let Some(backend) = backend else {
+        return 0;
+    };
+    match cap {
+        EXO_KVM_CAP_BACKEND => match backend {
+            Backend::Vmx => EXO_KVM_BACKEND_VMX,
+            Backend::Svm => EXO_KVM_BACKEND_SVM,
+        },
+        EXO_KVM_CAP_MAX_VCPUS => MAX_VCPUS as i64,
+        EXO_KVM_CAP_NR_MEMSLOTS => MAX_MEMSLOTS as i64,
+        _ => 0,
+    }
+}
+
+// ── Structures ───────────────────────────────────────────────────────────────
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KvmMemoryRegion {
+    pub slot: u32,
+    pub flags: u32,
+    pub guest_phys_addr: u64,
+    pub memory_size: u64,
+    pub userspace_addr: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KvmRegs {
+    pub rax: u64,
+    pub rbx: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    pub rsp: u64,
+    pub rbp: u64,
+    pub r8: u64,
+    pub r9: u64,
+    pub r10: u64,
+    pub r11: u64,
+    pub r12: u64,
+    pub r13: u64,
+    pub r14: u64,
+    pub r15: u64,
+    pub rip: u64,
+    pub rflags: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KvmSegment {
+    pub base: u64,
+    /// Limite en octets (granularité déjà appliquée).
+    pub limit: u32,
+    pub selector: u16,
+    pub type_: u8,
+    pub present: u8,
+    pub dpl: u8,
+    pub db: u8,
+    pub s: u8,
+    pub l: u8,
+    pub g: u8,
+    pub avl: u8,
+    pub unusable: u8,
+    pub padding: u8,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KvmDtable {
+    pub base: u64,
+    pub limit: u16,
+    pub padding: [u16; 3],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KvmSregs {
+    pub cs: KvmSegment,
+    pub ds: KvmSegment,
+    pub es: KvmSegment,
+    pub fs: KvmSegment,
+    pub gs: KvmSegment,
+    pub ss: KvmSegment,
+    pub tr: KvmSegment,
+    pub ldt: KvmSegment,
+    pub gdt: KvmDtable,
+    pub idt: KvmDtable,
+    pub cr0: u64,
+    pub cr2: u64,
+    pub cr3: u64,
+    pub cr4: u64,
+    pub cr8: u64,
+    pub efer: u64,
+    pub apic_base: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KvmCpuidEntry {
+    pub function: u32,
+    pub index: u32,
+    /// `EXO_KVM_CPUID_FLAG_SIGNIFICANT_INDEX` : `index` (ECX) doit correspondre.
+    pub flags: u32,
+    pub eax: u32,
+    pub ebx: u32,
+    pub ecx: u32,
+    pub edx: u32,
+    pub padding: [u32; 3],
+}
+
+pub const EXO_KVM_CPUID_FLAG_SIGNIFICANT_INDEX: u32 = 1;
+
+/// En-tête de `EXO_KVM_SET_CPUID`, suivi de `nent` `KvmCpuidEntry`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KvmCpuid {
+    pub nent: u32,
+    pub padding: u32,
+}
+
+impl KvmCpuid {
+    /// `nent`, s'il ne dépasse pas `MAX_CPUID_ENTRIES`.
+    pub(super) fn entry_count(&self) -> Option<usize> {
+        let count = self.nent as usize;
+        (count <= MAX_CPUID_ENTRIES).then_some(count)
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KvmRunIo {
+    /// `EXO_KVM_EXIT_IO_IN` ou `EXO_KVM_EXIT_IO_OUT`.
+    pub direction: u8,
+    /// 1, 2 ou 4 octets.
+    pub size: u8,
+    pub port: u16,
+    pub padding: u32,
+    /// Valeur écrite (OUT) ; pour IN, le VMM y dépose la valeur lue avant
+    /// le `EXO_KVM_RUN` suivant.
+    pub data: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KvmRunMmio {
+    pub phys_addr: u64,
+    /// Même convention que `KvmRunIo::data`, little-endian.
+    pub data: [u8; 8],
+    pub len: u32,
+    pub is_write: u8,
+    pub padding: [u8; 3],
+}
+
+/// Argument de `EXO_KVM_RUN`, lu à l'entrée (résultat du IN/MMIO précédent,
+/// fenêtre d'interruption) et réécrit à la sortie.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KvmRun {
+    /// Entrée : sortir dès que l'invité peut recevoir une IRQ.
+    pub request_interrupt_window: u8,
+    /// Sortie : `EXO_KVM_INTERRUPT` serait injectée immédiatement.
+    pub ready_for_interrupt_injection: u8,
+    /// Sortie : RFLAGS.IF de l'invité.
+    pub if_flag: u8,
+    pub padding: [u8; 5],
+    pub exit_reason: u32,
+    pub padding2: u32,
+    pub io: KvmRunIo,
+    pub mmio: KvmRunMmio,
+    /// `EXO_KVM_EXIT_FAIL_ENTRY` / `EXIT_INTERNAL_ERROR` : raison matérielle
+    /// ou sous-code.
+    pub hardware_reason: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::mem::size_of;
+
+    #[test]
+    fn layouts_match_linux_kvm() {
+        assert_eq!(size_of::<KvmMemoryRegion>(), 32);
+        assert_eq!(size_of::<KvmRegs>(), 144);
+        assert_eq!(size_of::<KvmSegment>(), 24);
+        assert_eq!(size_of::<KvmDtable>(), 16);
+        assert_eq!(size_of::<KvmCpuidEntry>(), 40);
+        assert_eq!(size_of::<KvmCpuid>(), 8);
+    }
+
+    #[test]
+    fn vcpu_ids_are_bounded() {
+        assert_eq!(vcpu_id_arg(0), Some(0));
+        assert_eq!(
+            vcpu_id_arg(MAX_VCPUS as u64 - 1),
+            Some(MAX_VCPUS as u32 - 1)
+        );
+        assert_eq!(vcpu_id_arg(MAX_VCPUS as u64), None);
+        // Pas de troncature silencieuse en u32.
+        assert_eq!(vcpu_id_arg(1 << 32), None);
+    }
+
+    #[test]
+    fn irq_vectors_fit_in_a_byte() {
+        assert_eq!(irq_vector_arg(0x20), Some(0x20));
+        assert_eq!(irq_vector_arg(0xFF), Some(0xFF));
+        assert_eq!(irq_vector_arg(0x100), None);
+        assert_eq!(irq_vector_arg(u64::MAX), None);
+    }
+
+    #[test]
+    fn cpuid_tables_are_bounded() {
+        let header = |nent| KvmCpuid { nent, padding: 0 };
+        assert_eq!(header(0).entry_count(), Some(0));
+        assert_eq!(
+            header(MAX_CPUID_ENTRIES as u32).entry_count(),
+            Some(MAX_CPUID_ENTRIES)
+        );
+        assert_eq!(header(MAX_CPUID_ENTRIES as u32 + 1).entry_count(), None);
+        assert_eq!(header(u32::MAX).entry_count(), None);
+    }
+
+    #[test]
+    fn extensions_report_the_backend_and_limits() {
+        for (backend, id) in [
+            (Backend::Vmx, EXO_KVM_BACKEND_VMX),
+            (Backend::Svm, EXO_KVM_BACKEND_SVM),
+        ] {
+            assert_eq!(extension_value(EXO_KVM_CAP_BACKEND, Some(backend)), id);
+            assert_eq!(
+                extension_value(EXO_KVM_CAP_MAX_VCPUS, Some(backend)),
+                MAX_VCPUS as i64
+            );
+            assert_eq!(
+                extension_value(EXO_KVM_CAP_NR_MEMSLOTS, Some(backend)),
+                MAX_MEMSLOTS as i64
+            );
+            assert_eq!(extension_value(0x100, Some(backend)), 0);
+        }
+        for cap in [
+            EXO_KVM_CAP_BACKEND,
+            EXO_KVM_CAP_MAX_VCPUS,
+            EXO_KVM_CAP_NR_MEMSLOTS,
+        ] {
+            assert_eq!(extension_value(cap, None), 0);
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! # kvm/mmio.rs — Décodage des accès MMIO de l'invité
+//!
+//! Une faute EPT/NPT hors des slots mémoire est un accès à un périphérique
+//! émulé par le VMM. Le matériel ne donne ni la taille ni la donnée : on
+//! décode l'instruction. Seuls les `mov` qu'émettent `readl`/`writel` et
+//! consorts sont reconnus (88/89/8A/8B, C6/C7, A0–A3, movzx/movsx) ; le reste
+//! remonte au VMM en `EXO_KVM_EXIT_INTERNAL_ERROR`.
+
+use super::GuestGprs;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Extend {
+    /// Écriture de registre x86 ordinaire (32 bits → mise à zéro du haut).
+    None,
+    Zero,
+    Sign,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Access {
+    Write(u64),
+    Read {
+        reg: u8,
+        /// Taille du registre destination (octets).
+        reg_size: u8,
+        /// Registre 8 bits haut (AH, CH, DH, BH).
+        high_byte: bool,
+        extend: Extend,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct MmioInsn {
+    pub len: u8,
+    /// Taille de l'accès mémoire (octets).
+    pub size: u8,
+    pub access: Access,
+}
+
+/// Mode d'exécution de l'invité au moment de la faute.
+#[derive(Clone, Copy)]
+pub(super) struct CpuMode {
+    /// CS.L avec EFER.LMA.
+    pub long: bool,
+    /// CS.D : opérandes et adresses 32 bits par défaut.
+    pub default_32: bool,
+}
+
+/// Décode `bytes` (au plus 15 octets lus à RIP).
+pub(super) fn decode(bytes: &[u8], mode: CpuMode, gprs: &GuestGprs) -> Option<MmioInsn> {
+    let mut pos = 0usize;
+    let mut opsize_override = false;
+    let mut addr_override = false;
+    let mut rex = 0u8;
+
+    while let Some(&byte) = bytes.get(pos) {
+        match byte {
+            0x66 => opsize_override = true,
+            0x67 => addr_override = true,
+            0x26 | 0x2E | 0x36 | 0x3E | 0x64 | 0x65 | 0xF0 | 0xF2 | 0xF3 => {}
+            _ => break,
+        }
+        pos += 1;
+    }
+    if mode.long {
+        if let Some(&byte) = bytes.get(pos) {
+            if byte & 0xF0 == 0x40 {
+                rex = byte;
+                pos += 1;
+            }
+        }
+    }
+    let rex_w = rex & 0x8 != 0;
+    let op_size: u8 = if rex_w {
+        8
+    } else if opsize_override == mode.default_32 {
+        2
+    } else {
+        4
+    };
+    let addr_size: u8 = match (mode.long, mode.default_32, addr_override) {
+        (true, _, false) => 8,
+        (true, _, true) | (false, true, false) | (false, false, true) => 4,
+        (false, true, true) | (false, false, false) => 2,
+    };
+
+    let opcode = *bytes.get(pos)?;
+    pos += 1;
+    let (size, access) = match opcode {
+        0x88 | 0x89 | 0x8A | 0x8B | 0xC6 | 0xC7 => {
+            let size = if opcode & 1 == 0 { 1 } else { op_size };
+            let modrm = *bytes.get(pos)?;
+            let reg = ((modrm >> 3) & 7) | if rex & 0x4 != 0 { 8 } else { 0 };
+            pos += modrm_len(&bytes[pos..], addr_size)?;
+            let access = match opcode {
+                0x88 | 0x89 => Access::Write(read_reg(gprs, reg, size, rex != 0)),
+                0x8A | 0x8B => Access::Read {
+                    reg,
+                    reg_size: size,
+                    high_byte: size == 1 && rex == 0 && reg >= 4,
+                    extend: Extend::None,
+                },
+                _ => {
+                    if (modrm >> 3) & 7 != 0 {
+                        return None;
+                    }
+                    let imm_size = size.min(4) as usize;
+                    let imm = read_le(bytes.get(pos..pos + imm_size)?);
+                    pos += imm_size;
+                    // imm32 étendu en signe pour un opérande 64 bits.
+                    let imm = if size == 8 {
+                        imm as u32 as i32 as i64 as u64
+                    } else {
+                        imm
+                    };
+                    Access::Write(imm)
+                }
+            };
+            (size, access)
+        }
+        0xA0..=0xA3 => {
+            let size = if opcode & 1 == 0 { 1 } else { op_size };
+            pos += addr_size as usize;
+            if bytes.len() < pos {
+                return None;
+            }
+            let access = if opcode & 2 != 0 {
+                Access::Write(read_reg(gprs, 0, size, rex != 0))
+            } else {
+                Access::Read {
+                    reg: 0,
+                    reg_size: size,
+                    high_byte: false,
+                    extend: Extend::None,
+                }
+            };
+            (size, access)
+        }
+        0x0F => {
+            let opcode2 = *bytes.get(pos)?;
+            pos += 1;
+            let (size, extend) = match opcode2 {
+                0xB6 => (1, Extend::Zero),
+                0xB7 => (2, Extend::Zero),
+                0xBE => (1, Extend::Sign),
+                0xBF => (2, Extend::Sign),
+                _ => return None,
+            };
+            let modrm = *bytes.get(pos)?;
+            let reg = ((modrm >> 3) & 7) | if rex & 0x4 != 0 { 8 } else { 0 };
+            pos += modrm_len(&bytes[pos..], addr_size)?;
+            (
+                size,
+                Access::Read {
+                    reg,
+                    reg_size: op_size,
+                    high_byte: false,
+                    extend,
+                },
+            )
+        }
+        _ => return None,
+    };
+
+    Some(MmioInsn {
+        len: pos as u8,
+        size,
+        access,
+    })
+}
+
+/// Écrit la donnée lue (`data`, `insn.size` octets) dans le registre cible.
+pub(super) fn complete_read(insn: &MmioInsn, data: u64, gprs: &mut GuestGprs) {
+    let Access::Read {
+        reg,
+        reg_size,
+        high_byte,
+        extend,
+    } = insn.access
+    else {
+        return;
+    };
+    let bits = insn.size as u32 * 8;
+    let data = if bits >= 64 {
+        data
+    } else {
+        data & ((1u64 << bits) - 1)
+    };
+    let value = match extend {
+        Extend::Sign => ((data << (64 - bits)) as i64 >> (64 - bits)) as u64,
+        Extend::None | Extend::Zero => data,
+    };
+    if high_byte {
+        let slot = &mut gprs.regs[(reg - 4) as usize];
+        *slot = (*slot & !0xFF00) | ((value & 0xFF) << 8);
+        return;
+    }
+    let slot = &mut gprs.regs[reg as usize];
+    *slot = match reg_size {
+        1 => (*slot & !0xFF) | (value & 0xFF),
+        2 => (*slot & !0xFFFF) | (value & 0xFFFF),
+        4 => value & 0xFFFF_FFFF,
+        _ => value,
+    };
+}
+
+/// Longueur ModRM + SIB + déplacement ; `None` pour un opérande registre.
+fn modrm_len(bytes: &[u8], addr_size: u8) -> Option<usize> {
+    let modrm = *bytes.first()?;
+    let md = modrm >> 6;
+    let rm = modrm & 7;
+    if md == 3 {
+        return None;
+    }
+    let len = if addr_size == 2 {
+        1 + match (md, rm) {
+            (0, 6) => 2,
+            (0, _) => 0,
+            (1, _) => 1,
+            _ => 2,
+        }
+    } else {
+        let mut len = 1;
+        let mut base = rm;
+        if rm == 4 {
+            base = *bytes.get(1)? & 7;
+            len += 1;
+        }
+        len + match md {
+            0 if rm == 5 || (rm == 4 && base == 5) => 4,
+            0 => 0,
+            1 => 1,
+            _ => 4,
+        }
+    };
+    (bytes.len() >= len).then_some(len)
+}
+
+fn read_reg(gprs: &GuestGprs, reg: u8, size: u8, rex: bool) -> u64 {
+    if size == 1 && !rex && (4..8).contains(&reg) {
+        return (gprs.regs[(reg - 4) as usize] >> 8) & 0xFF;
+    }
+    let value = gprs.regs[reg as usize];
+    match size {
+        1 => value & 0xFF,
+        2 => value & 0xFFFF,
+        4 => value & 0xFFFF_FFFF,
+        _ => value,
+    }
+}
+
+fn read_le(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0u64, |acc, &byte| (acc << 8) | byte as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONG: CpuMode = CpuMode {
+        long: true,
+        default_32: true,
+    };
+
+    #[test]
+    fn decodes_the_moves_emitted_by_mmio_accessors() {
+        let mut gprs = GuestGprs::default();
+        gprs.regs[1] = 0x1122_3344_5566_7788; // rcx
+
+        // mov dword [rax+0x10], ecx
+        let insn = decode(&[0x89, 0x48, 0x10], LONG, &gprs).unwrap();
+        assert_eq!(insn.len, 3);
+        assert_eq!(insn.size, 4);
+        assert_eq!(insn.access, Access::Write(0x5566_7788));
+
+        // movzx eax, word [rdi]
+        let insn = decode(&[0x0F, 0xB7, 0x07], LONG, &gprs).unwrap();
+        assert_eq!((insn.len, insn.size), (3, 2));
+        gprs.regs[0] = u64::MAX;
+        complete_read(&insn, 0xBEEF, &mut gprs);
+        assert_eq!(gprs.regs[0], 0xBEEF);
+
+        // mov r9, qword [rip+disp32]
+        let insn = decode(&[0x4C, 0x8B, 0x0D, 1, 2, 3, 4], LONG, &gprs).unwrap();
+        assert_eq!((insn.len, insn.size), (7, 8));
+        complete_read(&insn, 0xAB, &mut gprs);
+        assert_eq!(gprs.regs[9], 0xAB);
+
+        // mov qword [rbx], -1 (imm32 étendu en signe)
+        let insn = decode(&[0x48, 0xC7, 0x03, 0xFF, 0xFF, 0xFF, 0xFF], LONG, &gprs).unwrap();
+        assert_eq!(insn.access, Access::Write(u64::MAX));
+
+        // Opérande registre : pas un accès mémoire.
+        assert!(decode(&[0x89, 0xC8], LONG, &gprs).is_none());
+    }
+}
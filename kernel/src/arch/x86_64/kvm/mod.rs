@@ -0,0 +1,518 @@
+//! # arch/x86_64/kvm — Hyperviseur minimal (`/dev/exo-kvm`)
+//!
+//! Exo-OS hôte de machines virtuelles pour un VMM en espace utilisateur,
+//! sur le modèle de Linux KVM :
+//!
+//! - `open("/dev/exo-kvm")` donne le descripteur système ; `EXO_KVM_CREATE_VM`
+//!   puis `EXO_KVM_CREATE_VCPU` rendent des descripteurs de VM et de vCPU.
+//! - La RAM invitée est de la mémoire du VMM (`EXO_KVM_SET_USER_MEMORY_REGION`) :
+//!   ses pages sont épinglées et projetées par EPT (Intel) ou NPT (AMD).
+//! - `EXO_KVM_RUN` exécute l'invité jusqu'à une sortie que le noyau ne traite
+//!   pas lui-même : port I/O, accès MMIO hors RAM, HLT, arrêt (triple faute).
+//!   CPUID, les MSR usuels et les accès aux CR sont émulés ici.
+//! - Le VMM émule le chipset (PIC, PIT, série, virtio…) et injecte ses IRQ
+//!   par `EXO_KVM_INTERRUPT`.
+//!
+//! Réservé à root : le matériel de virtualisation n'est pas partagé entre
+//! utilisateurs. Les VM meurent avec le processus qui les a créées.
+//!
+//! ## Contraintes
+//! - VMX : EPT et « unrestricted guest » requis (invité en mode réel sans
+//!   émulation). SVM : NPT requis.
+//! - Un vCPU ne migre pas pendant `EXO_KVM_RUN` (préemption coupée) ; la
+//!   boucle rend la main à chaque signal ou demande de reschedule
+//!   (`EXO_KVM_EXIT_INTR`) et le VMCS/VMCB est relâché entre deux appels.
+//! - FPU invitée limitée à x87/SSE (XSAVE masqué dans CPUID).
+
+pub mod abi;
+mod mmio;
+mod npt;
+mod svm;
+mod vm;
+mod vmx;
+
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::arch::x86_64::cpu::fpu::{fxrstor, fxsave};
+use crate::arch::x86_64::smp::percpu::{current_cpu_id, MAX_CPUS};
+use crate::scheduler::fpu::lazy::{cr0_clear_ts, cr0_set_ts, cr0_ts_is_set};
+use crate::syscall::errno::{
+    EACCES, EBUSY, EEXIST, EFAULT, EINTR, EINVAL, ENOENT, ENOMEM, ENOTSUP,
+};
+
+pub use vm::{ioctl, release_for_pid, IoctlOutcome};
+
+/// Erreurs des ioctl `/dev/exo-kvm`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KvmError {
+    /// Ni VMX ni SVM utilisable (ou EPT/NPT absent).
+    NotSupported,
+    NoMemory,
+    Invalid,
+    Busy,
+    Fault,
+    Exists,
+    /// VM ou vCPU détruit, ou slot inconnu.
+    NotFound,
+    PermissionDenied,
+    Interrupted,
+}
+
+impl KvmError {
+    pub fn to_errno(self) -> i64 {
+        match self {
+            KvmError::NotSupported => ENOTSUP,
+            KvmError::NoMemory => ENOMEM,
+            KvmError::Invalid => EINVAL,
+            KvmError::Busy => EBUSY,
+            KvmError::Fault => EFAULT,
+            KvmError::Exists => EEXIST,
+            KvmError::NotFound => ENOENT,
+            KvmError::PermissionDenied => EACCES,
+            KvmError::Interrupted => EINTR,
+        }
+    }
+}
+
+/// Cible d'un descripteur `/dev/exo-kvm`, stockée dans son blob synthétique.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KvmHandle {
+    System,
+    Vm(u32),
+    Vcpu { vm: u32, vcpu: u32 },
+}
+
+impl KvmHandle {
+    pub const ENCODED_LEN: usize = 9;
+
+    pub fn encode(self) -> [u8; Self::ENCODED_LEN] {
+        let (kind, vm, vcpu) = match self {
+            KvmHandle::System => (0u8, 0u32, 0u32),
+            KvmHandle::Vm(vm) => (1, vm, 0),
+            KvmHandle::Vcpu { vm, vcpu } => (2, vm, vcpu),
+        };
+        let mut out = [0u8; Self::ENCODED_LEN];
+        out[0] = kind;
+        out[1..5].copy_from_slice(&vm.to_le_bytes());
+        out[5..9].copy_from_slice(&vcpu.to_le_bytes());
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < Self::ENCODED_LEN {
+            return None;
+        }
+        let vm = u32::from_le_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]);
+        let vcpu = u32::from_le_bytes([bytes[5], bytes[6], bytes[7], bytes[8]]);
+        match bytes[0] {
+            0 => Some(KvmHandle::System),
+            1 => Some(KvmHandle::Vm(vm)),
+            2 => Some(KvmHandle::Vcpu { vm, vcpu }),
+            _ => None,
+        }
+    }
+}
+
+/// Registres généraux de l'invité, indexés par numéro x86 (RAX = 0 … R15 = 15).
+///
+/// Disposition lue et écrite par les stubs d'entrée VMX/SVM : ne pas
+/// réordonner. RSP (index 4) vit dans le VMCS/VMCB et n'est recopié ici
+/// qu'autour des sorties.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GuestGprs {
+    pub regs: [u64; 16],
+}
+
+const RAX: usize = 0;
+const RCX: usize = 1;
+const RDX: usize = 2;
+const RBX: usize = 3;
+const RSP: usize = 4;
+
+// ── Technologie matérielle ───────────────────────────────────────────────────
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    Vmx,
+    Svm,
+}
+
+const BACKEND_UNKNOWN: u8 = 0;
+const BACKEND_NONE: u8 = 1;
+const BACKEND_VMX: u8 = 2;
+const BACKEND_SVM: u8 = 3;
+
+static BACKEND: AtomicU8 = AtomicU8::new(BACKEND_UNKNOWN);
+static CPU_ENABLED: [AtomicBool; MAX_CPUS] = [const { AtomicBool::new(false) }; MAX_CPUS];
+
+/// VMX ou SVM si le CPU et le firmware les autorisent ; détecté une fois.
+pub fn backend() -> Option<Backend> {
+    let cached = match BACKEND.load(Ordering::Acquire) {
+        BACKEND_UNKNOWN => {
+            let detected = detect_backend(vmx::supported, svm::supported);
+            BACKEND.store(detected, Ordering::Release);
+            detected
+        }
+        cached => cached,
+    };
+    cached_backend(cached)
+}
+
+/// VMX d'abord ; SVM n'est sondé que si VMX est inutilisable.
+fn detect_backend(vmx: impl FnOnce() -> bool, svm: impl FnOnce() -> bool) -> u8 {
+    if vmx() {
+        BACKEND_VMX
+    } else if svm() {
+        BACKEND_SVM
+    } else {
+        BACKEND_NONE
+    }
+}
+
+fn cached_backend(cached: u8) -> Option<Backend> {
+    match cached {
+        BACKEND_VMX => Some(Backend::Vmx),
+        BACKEND_SVM => Some(Backend::Svm),
+        _ => None,
+    }
+}
+
+/// Active VMX (VMXON) ou SVM (EFER.SVME) sur le CPU courant, une fois.
+/// Appelé préemption coupée.
+fn enable_on_current_cpu(backend: Backend) -> Result<(), KvmError> {
+    let cpu = current_cpu_id() as usize;
+    let flag = CPU_ENABLED.get(cpu).ok_or(KvmError::NotSupported)?;
+    if flag.load(Ordering::Acquire) {
+        return Ok(());
+    }
+    match backend {
+        Backend::Vmx => vmx::enable_cpu()?,
+        Backend::Svm => svm::enable_cpu()?,
+    }
+    flag.store(true, Ordering::Release);
+    Ok(())
+}
+
+/// Invalide, sur le CPU courant, les traductions invité → hôte en cache pour
+/// la table `root`, avant que des pages retirées d'un slot soient libérées.
+/// Appelé préemption coupée, aucun vCPU de la VM en cours d'exécution.
+///
+/// Les autres CPU gardent leurs entrées jusqu'au prochain chargement d'un
+/// vCPU, qui invalide de son côté (INVEPT de `load`) : l'invité ne les revoit
+/// jamais.
+fn flush_guest_tlb(backend: Backend, root: u64) {
+    match backend {
+        Backend::Vmx => {
+            let cpu = current_cpu_id() as usize;
+            // VMX jamais activé ici : aucun invité exécuté, rien en cache.
+            if CPU_ENABLED
+                .get(cpu)
+                .is_some_and(|flag| flag.load(Ordering::Acquire))
+            {
+                vmx::flush_ept(root);
+            }
+        }
+        // ASID partagé vidé (TLB_CONTROL) à chaque VMRUN : une entrée NPT
+        // périmée ne sert jamais. INVLPGA ne couvre pas ces traductions.
+        Backend::Svm => {}
+    }
+}
+
+// ── Sorties matérielles, vues par la boucle commune ──────────────────────────
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Exit {
+    Io {
+        port: u16,
+        size: u8,
+        write: bool,
+        /// INS/OUTS ou préfixe REP : non pris en charge.
+        string: bool,
+        /// Longueur de l'instruction.
+        len: u8,
+    },
+    /// Faute EPT/NPT à `gpa`.
+    GuestPageFault {
+        gpa: u64,
+    },
+    Cpuid {
+        len: u8,
+    },
+    Rdmsr {
+        len: u8,
+    },
+    Wrmsr {
+        len: u8,
+    },
+    Hlt {
+        len: u8,
+    },
+    /// IRQ hôte, NMI ou sortie sans effet sur l'invité : relancer.
+    Host,
+    InterruptWindow,
+    Shutdown,
+    /// Instruction à refuser par #UD (VMCALL, instructions VMX/SVM…).
+    InvalidOpcode,
+    /// Instruction sans effet à sauter (INVD, WBINVD, MONITOR/MWAIT…).
+    Skip {
+        len: u8,
+    },
+    EntryFailed(u64),
+    Unhandled(u64),
+}
+
+/// État de pagination de l'invité, pour lire l'instruction fautive.
+#[derive(Clone, Copy, Debug, Default)]
+struct Paging {
+    pub cr0: u64,
+    pub cr3: u64,
+    pub cr4: u64,
+    pub efer: u64,
+    pub cs_base: u64,
+    pub cs_l: bool,
+    pub cs_db: bool,
+}
+
+/// Opérations propres à VMX ou SVM sur un vCPU.
+///
+/// `load` rend le vCPU courant sur ce CPU (VMPTRLD, ASID…) ; toutes les
+/// autres méthodes sauf `put` supposent ce chargement fait.
+trait VcpuHw: Send {
+    fn load(&mut self, state: &ArchState, state_dirty: bool, eptp: u64) -> Result<(), KvmError>;
+    fn put(&mut self, state: &mut ArchState);
+    fn enter(&mut self, gprs: &mut GuestGprs) -> Exit;
+    fn rip(&self) -> u64;
+    fn set_rip(&mut self, rip: u64);
+    fn rflags(&self) -> u64;
+    fn paging(&self) -> Paging;
+    /// IF = 1 et aucun masquage (STI, MOV SS, événement en cours).
+    fn interruptible(&self) -> bool;
+    fn inject_irq(&mut self, vector: u8);
+    fn inject_exception(&mut self, vector: u8, error_code: Option<u32>);
+    fn set_irq_window(&mut self, enabled: bool);
+    /// MSR émulés ; `None` / `false` : #GP dans l'invité.
+    fn read_msr(&mut self, msr: u32) -> Option<u64>;
+    fn write_msr(&mut self, msr: u32, value: u64) -> bool;
+}
+
+/// Registres système de l'invité hors exécution (`EXO_KVM_{GET,SET}_SREGS`).
+#[derive(Clone, Copy, Debug, Default)]
+struct ArchState {
+    pub rip: u64,
+    pub rflags: u64,
+    pub sregs: abi::KvmSregs,
+}
+
+impl ArchState {
+    /// État de reset x86 : mode réel, CS:IP = F000:FFF0.
+    fn reset() -> Self {
+        let data = abi::KvmSegment {
+            limit: 0xFFFF,
+            type_: 3,
+            present: 1,
+            s: 1,
+            ..Default::default()
+        };
+        let code = abi::KvmSegment {
+            base: 0xFFFF_0000,
+            selector: 0xF000,
+            type_: 11,
+            ..data
+        };
+        let mut sregs = abi::KvmSregs {
+            cs: code,
+            ds: data,
+            es: data,
+            fs: data,
+            gs: data,
+            ss: data,
+            tr: abi::KvmSegment {
+                limit: 0xFFFF,
+                type_: 11,
+                present: 1,
+                ..Default::default()
+            },
+            ldt: abi::KvmSegment {
+                limit: 0xFFFF,
+                type_: 2,
+                present: 1,
+                ..Default::default()
+            },
+            cr0: 0x6000_0010,
+            apic_base: 0xFEE0_0900,
+            ..Default::default()
+        };
+        sregs.gdt.limit = 0xFFFF;
+        sregs.idt.limit = 0xFFFF;
+        Self {
+            rip: 0xFFF0,
+            rflags: 0x2,
+            sregs,
+        }
+    }
+}
+
+// ── FPU invitée ──────────────────────────────────────────────────────────────
+
+/// Zone FXSAVE (x87/SSE) ; l'état FPU invité y vit entre deux entrées.
+#[repr(C, align(16))]
+struct FxArea([u8; 512]);
+
+impl FxArea {
+    /// FCW = 0x37F, MXCSR = 0x1F80 : valeurs après reset.
+    fn new() -> Self {
+        let mut area = [0u8; 512];
+        area[0..2].copy_from_slice(&0x037Fu16.to_le_bytes());
+        area[24..28].copy_from_slice(&0x1F80u32.to_le_bytes());
+        Self(area)
+    }
+}
+
+/// FPU basculée sur l'état invité autour d'une entrée VMX/SVM.
+///
+/// FPU paresseuse : CR0.TS = 1 signifie que l'état hôte est déjà sauvegardé
+/// dans son TCB, les registres peuvent être écrasés sans sauvegarde.
+struct FpuSwap {
+    host: FxArea,
+    host_ts: bool,
+}
+
+impl FpuSwap {
+    /// # Safety
+    /// Interruptions coupées jusqu'à `leave`.
+    unsafe fn enter(guest: &FxArea) -> Self {
+        let mut swap = Self {
+            host: FxArea([0; 512]),
+            // SAFETY: lecture de CR0 en ring 0.
+            host_ts: unsafe { cr0_ts_is_set() },
+        };
+        // SAFETY: zones FXSAVE alignées sur 16 octets ; CLTS en ring 0.
+        unsafe {
+            if swap.host_ts {
+                cr0_clear_ts();
+            } else {
+                fxsave(swap.host.0.as_mut_ptr());
+            }
+            fxrstor(guest.0.as_ptr());
+        }
+        swap
+    }
+
+    /// # Safety
+    /// Appelé après la sortie, interruptions toujours coupées.
+    unsafe fn leave(self, guest: &mut FxArea) {
+        // SAFETY: voir `enter`.
+        unsafe {
+            fxsave(guest.0.as_mut_ptr());
+            if self.host_ts {
+                cr0_set_ts();
+            } else {
+                fxrstor(self.host.0.as_ptr());
+            }
+        }
+    }
+}
+
+// ── CPUID hôte ───────────────────────────────────────────────────────────────
+
+#[inline]
+fn cpuid(leaf: u32, subleaf: u32) -> (u32, u32, u32, u32) {
+    let (eax, ecx, edx): (u32, u32, u32);
+    let ebx_r: u64;
+    // SAFETY: CPUID non-privilégiée ; xchg préserve rbx réservé par LLVM.
+    unsafe {
+        core::arch::asm!(
+            "xchg {tmp:r}, rbx",
+            "cpuid",
+            "xchg {tmp:r}, rbx",
+            inout("eax") leaf => eax,
+            inout("ecx") subleaf => ecx,
+            out("edx") edx,
+            tmp = inout(reg) 0u64 => ebx_r,
+            options(nostack, nomem),
+        );
+    }
+    (eax, ebx_r as u32, ecx, edx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_round_trip_through_their_encoding() {
+        let handles = [
+            KvmHandle::System,
+            KvmHandle::Vm(7),
+            KvmHandle::Vm(u32::MAX),
+            KvmHandle::Vcpu { vm: 3, vcpu: 15 },
+            KvmHandle::Vcpu {
+                vm: 0x1234_5678,
+                vcpu: 0x9ABC_DEF0,
+            },
+        ];
+        for handle in handles {
+            assert_eq!(KvmHandle::decode(&handle.encode()), Some(handle));
+        }
+        assert_eq!(
+            KvmHandle::Vcpu { vm: 1, vcpu: 2 }.encode(),
+            [2, 1, 0, 0, 0, 2, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn malformed_handles_are_rejected() {
+        let mut bytes = KvmHandle::Vm(1).encode();
+        assert_eq!(
+            KvmHandle::decode(&bytes[..KvmHandle::ENCODED_LEN - 1]),
+            None
+        );
+        assert_eq!(KvmHandle::decode(&[]), None);
+        bytes[0] = 3;
+        assert_eq!(KvmHandle::decode(&bytes), None);
+        bytes[0] = 0xFF;
+        assert_eq!(KvmHandle::decode(&bytes), None);
+        // Octets au-delà de l'encodage ignorés (blob arrondi).
+        let mut long = [0u8; 16];
+        long[..KvmHandle::ENCODED_LEN].copy_from_slice(&KvmHandle::Vm(9).encode());
+        assert_eq!(KvmHandle::decode(&long), Some(KvmHandle::Vm(9)));
+    }
+
+    #[test]
+    fn vmx_is_preferred_and_svm_probed_only_without_it() {
+        let unreachable = || -> bool { panic!("SVM sondé alors que VMX est utilisable") };
+        assert_eq!(detect_backend(|| true, unreachable), BACKEND_VMX);
+        assert_eq!(detect_backend(|| false, || true), BACKEND_SVM);
+        assert_eq!(detect_backend(|| false, || false), BACKEND_NONE);
+    }
+
+    #[test]
+    fn cached_backend_decodes_every_state() {
+        assert_eq!(cached_backend(BACKEND_VMX), Some(Backend::Vmx));
+        assert_eq!(cached_backend(BACKEND_SVM), Some(Backend::Svm));
+        assert_eq!(cached_backend(BACKEND_NONE), None);
+        assert_eq!(cached_backend(BACKEND_UNKNOWN), None);
+    }
+
+    #[test]
+    fn errors_map_to_distinct_errnos() {
+        let errors = [
+            KvmError::NotSupported,
+            KvmError::NoMemory,
+            KvmError::Invalid,
+            KvmError::Busy,
+            KvmError::Fault,
+            KvmError::Exists,
+            KvmError::NotFound,
+            KvmError::PermissionDenied,
+            KvmError::Interrupted,
+        ];
+        for (i, a) in errors.iter().enumerate() {
+            assert!(a.to_errno() < 0, "{a:?}");
+            for b in &errors[i + 1..] {
+                assert_ne!(a.to_errno(), b.to_errno(), "{a:?} / {b:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! # kvm/npt.rs — Traduction adresse physique invité → hôte (EPT / NPT)
+//!
+//! Table à 4 niveaux, pages de 4 KiB uniquement. Intel EPT et AMD NPT ne
+//! diffèrent que par les bits d'entrée : EPT encode R/W/X et le type mémoire
+//! (WB) dans les feuilles, NPT reprend le format x86 ordinaire (P/RW/US,
+//! tout accès invité y est un accès « utilisateur »).
+
+use alloc::vec::Vec;
+
+use crate::memory::core::phys_to_virt;
+use crate::memory::{alloc_page, free_page, AllocFlags, Frame, PhysAddr};
+
+use super::KvmError;
+
+const ENTRIES: usize = 512;
+const ADDR_MASK: u64 = 0x000F_FFFF_FFFF_F000;
+
+/// EPT : R | W | X ; NPT : P | RW | US.
+const TABLE_BITS: u64 = 0x7;
+/// Type mémoire write-back (EPT, bits 5:3 des feuilles).
+const EPT_MEMTYPE_WB: u64 = 6 << 3;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum TableFormat {
+    Ept,
+    Npt,
+}
+
+impl TableFormat {
+    const fn leaf_bits(self) -> u64 {
+        match self {
+            TableFormat::Ept => TABLE_BITS | EPT_MEMTYPE_WB,
+            TableFormat::Npt => TABLE_BITS,
+        }
+    }
+}
+
+pub(super) struct GuestPageTable {
+    format: TableFormat,
+    root: Frame,
+    /// Tables intermédiaires, libérées avec la VM.
+    tables: Vec<Frame>,
+}
+
+impl GuestPageTable {
+    pub(super) fn new(format: TableFormat) -> Result<Self, KvmError> {
+        let root = alloc_page(AllocFlags::ZEROED).map_err(|_| KvmError::NoMemory)?;
+        Ok(Self {
+            format,
+            root,
+            tables: Vec::new(),
+        })
+    }
+
+    pub(super) fn root(&self) -> PhysAddr {
+        self.root.start_address()
+    }
+
+    /// Associe la page invité `gpa` à la page hôte `hpa` (alignées 4 KiB).
+    pub(super) fn map(&mut self, gpa: u64, hpa: PhysAddr) -> Result<(), KvmError> {
+        let mut table = self.root.start_address();
+        for level in (1..4).rev() {
+            let entry = entry_ptr(table, index(gpa, level));
+            // SAFETY: `entry` désigne une entrée d'une table de cette VM,
+            // accessible par la physmap ; la VM est verrouillée par l'appelant.
+            let value = unsafe { entry.read_volatile() };
+            table = if value & TABLE_BITS != 0 {
+                PhysAddr::new(value & ADDR_MASK)
+            } else {
+                self.tables.try_reserve(1).map_err(|_| KvmError::NoMemory)?;
+                let frame = alloc_page(AllocFlags::ZEROED).map_err(|_| KvmError::NoMemory)?;
+                self.tables.push(frame);
+                let phys = frame.start_address();
+                // SAFETY: voir ci-dessus ; la nouvelle table est remise à zéro.
+                unsafe { entry.write_volatile(phys.as_u64() | TABLE_BITS) };
+                phys
+            };
+        }
+        let leaf = entry_ptr(table, index(gpa, 0));
+        // SAFETY: voir ci-dessus.
+        unsafe { leaf.write_volatile(leaf_entry(self.format, hpa)) };
+        Ok(())
+    }
+
+    /// Retire la page invité `gpa` ; l'appelant invalide le TLB invité.
+    pub(super) fn unmap(&mut self, gpa: u64) {
+        let mut table = self.root.start_address();
+        for level in (1..4).rev() {
+            // SAFETY: voir `map`.
+            let value = unsafe { entry_ptr(table, index(gpa, level)).read_volatile() };
+            if value & TABLE_BITS == 0 {
+                return;
+            }
+            table = PhysAddr::new(value & ADDR_MASK);
+        }
+        // SAFETY: voir `map`.
+        unsafe { entry_ptr(table, index(gpa, 0)).write_volatile(0) };
+    }
+}
+
+impl Drop for GuestPageTable {
+    fn drop(&mut self) {
+        for frame in self.tables.drain(..) {
+            let _ = free_page(frame);
+        }
+        let _ = free_page(self.root);
+    }
+}
+
+#[inline]
+fn leaf_entry(format: TableFormat, hpa: PhysAddr) -> u64 {
+    (hpa.as_u64() & ADDR_MASK) | format.leaf_bits()
+}
+
+#[inline]
+fn index(gpa: u64, level: u32) -> usize {
+    ((gpa >> (12 + 9 * level)) as usize) & (ENTRIES - 1)
+}
+
+#[inline]
+fn entry_ptr(table: PhysAddr, index: usize) -> *mut u64 {
+    (phys_to_virt(table).as_u64() as *mut u64).wrapping_add(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gpa_splits_into_four_table_indices() {
+        let gpa = (5u64 << 39) | (4 << 30) | (3 << 21) | (2 << 12) | 0x123;
+        assert_eq!(
+            [index(gpa, 3), index(gpa, 2), index(gpa, 1), index(gpa, 0)],
+            [5, 4, 3, 2]
+        );
+        assert_eq!(index(u64::MAX, 0), ENTRIES - 1);
+    }
+
+    #[test]
+    fn leaves_map_write_back_ram_rwx() {
+        let hpa = PhysAddr::new(0x1234_5000);
+        // EPT : R | W | X, type mémoire WB.
+        assert_eq!(leaf_entry(TableFormat::Ept, hpa), 0x1234_5000 | 0x7 | (6 << 3));
+        // NPT : P | RW | US.
+        assert_eq!(leaf_entry(TableFormat::Npt, hpa), 0x1234_5000 | 0x7);
+        // Bits hors adresse physique jamais recopiés.
+        let high = PhysAddr::new(0x000F_FFFF_FFFF_F000);
+        assert_eq!(leaf_entry(TableFormat::Npt, high) & !ADDR_MASK, TABLE_BITS);
+    }
+}
@@ -0,0 +1,902 @@
+//! # kvm/svm.rs — Backend AMD-V (SVM)
+//!
+//! Un VMCB par vCPU, sans état caché sur le CPU : aucun bit « clean » n'est
+//! positionné, le vCPU peut reprendre n'importe où. Tous les invités
+//! partagent l'ASID 1 et le TLB invité est vidé à chaque `EXO_KVM_RUN`.
+//!
+//! VMRUN ne sauve pas FS/GS/TR/LDTR cachés ni les MSR SYSCALL : VMSAVE de
+//! l'hôte puis VMLOAD de l'invité avant l'entrée, l'inverse après.
+//! Requiert NPT, « next RIP » et l'assistance au décodage (CR4).
+
+use crate::arch::x86_64::cpu::msr::{
+    read_msr, write_msr, MSR_CSTAR, MSR_FS_BASE, MSR_GS_BASE, MSR_IA32_EFER, MSR_IA32_PAT,
+    MSR_KERNEL_GS_BASE, MSR_LSTAR, MSR_SFMASK, MSR_STAR,
+};
+use crate::arch::x86_64::{irq_restore, irq_save};
+use crate::memory::core::phys_to_virt;
+use crate::memory::{alloc_page, alloc_pages, free_page, free_pages, AllocFlags, Frame};
+
+use super::abi::KvmSegment;
+use super::{cpuid, ArchState, Exit, FpuSwap, FxArea, GuestGprs, KvmError, Paging, VcpuHw};
+use super::{RAX, RSP};
+
+const MSR_VM_CR: u32 = 0xC001_0114;
+const VM_CR_SVMDIS: u64 = 1 << 4;
+const MSR_VM_HSAVE_PA: u32 = 0xC001_0117;
+const MSR_SYSENTER_CS: u32 = 0x174;
+const MSR_SYSENTER_ESP: u32 = 0x175;
+const MSR_SYSENTER_EIP: u32 = 0x176;
+
+/// CPUID 0x8000_000A:EDX.
+const SVM_FEATURE_NPT: u32 = 1 << 0;
+const SVM_FEATURE_NRIPS: u32 = 1 << 3;
+const SVM_FEATURE_DECODE_ASSISTS: u32 = 1 << 7;
+
+// ── VMCB : zone de contrôle ──────────────────────────────────────────────────
+
+const VMCB_INTERCEPT_CR_WRITE: usize = 0x002;
+const VMCB_INTERCEPT_MISC1: usize = 0x00C;
+const VMCB_INTERCEPT_MISC2: usize = 0x010;
+const VMCB_IOPM_BASE: usize = 0x040;
+const VMCB_MSRPM_BASE: usize = 0x048;
+const VMCB_TSC_OFFSET: usize = 0x050;
+const VMCB_ASID: usize = 0x058;
+const VMCB_TLB_CONTROL: usize = 0x05C;
+const VMCB_INT_CTL: usize = 0x060;
+const VMCB_INT_STATE: usize = 0x068;
+const VMCB_EXIT_CODE: usize = 0x070;
+const VMCB_EXIT_INFO1: usize = 0x078;
+const VMCB_EXIT_INFO2: usize = 0x080;
+const VMCB_EXIT_INT_INFO: usize = 0x088;
+const VMCB_NP_ENABLE: usize = 0x090;
+const VMCB_EVENT_INJ: usize = 0x0A8;
+const VMCB_N_CR3: usize = 0x0B0;
+const VMCB_CLEAN: usize = 0x0C0;
+const VMCB_NEXT_RIP: usize = 0x0C8;
+
+// ── VMCB : zone d'état invité ────────────────────────────────────────────────
+
+const VMCB_ES: usize = 0x400;
+const VMCB_CS: usize = 0x410;
+const VMCB_SS: usize = 0x420;
+const VMCB_DS: usize = 0x430;
+const VMCB_FS: usize = 0x440;
+const VMCB_GS: usize = 0x450;
+const VMCB_GDTR: usize = 0x460;
+const VMCB_LDTR: usize = 0x470;
+const VMCB_IDTR: usize = 0x480;
+const VMCB_TR: usize = 0x490;
+const VMCB_CPL: usize = 0x4CB;
+const VMCB_EFER: usize = 0x4D0;
+const VMCB_CR4: usize = 0x548;
+const VMCB_CR3: usize = 0x550;
+const VMCB_CR0: usize = 0x558;
+const VMCB_DR7: usize = 0x560;
+const VMCB_DR6: usize = 0x568;
+const VMCB_RFLAGS: usize = 0x570;
+const VMCB_RIP: usize = 0x578;
+const VMCB_RSP: usize = 0x5D8;
+const VMCB_RAX: usize = 0x5F8;
+const VMCB_STAR: usize = 0x600;
+const VMCB_LSTAR: usize = 0x608;
+const VMCB_CSTAR: usize = 0x610;
+const VMCB_SFMASK: usize = 0x618;
+const VMCB_KERNEL_GS_BASE: usize = 0x620;
+const VMCB_SYSENTER_CS: usize = 0x628;
+const VMCB_SYSENTER_ESP: usize = 0x630;
+const VMCB_SYSENTER_EIP: usize = 0x638;
+const VMCB_CR2: usize = 0x640;
+const VMCB_G_PAT: usize = 0x668;
+
+/// Dans un descripteur de segment du VMCB.
+const SEG_SELECTOR: usize = 0;
+const SEG_ATTRIB: usize = 2;
+const SEG_LIMIT: usize = 4;
+const SEG_BASE: usize = 8;
+
+// ── Interceptions ────────────────────────────────────────────────────────────
+
+const INTERCEPT_CR4: u16 = 1 << 4;
+
+const INTERCEPT_INTR: u32 = 1 << 0;
+const INTERCEPT_NMI: u32 = 1 << 1;
+const INTERCEPT_VINTR: u32 = 1 << 4;
+const INTERCEPT_RDPMC: u32 = 1 << 15;
+const INTERCEPT_CPUID: u32 = 1 << 18;
+const INTERCEPT_INVD: u32 = 1 << 22;
+const INTERCEPT_HLT: u32 = 1 << 24;
+const INTERCEPT_IOIO: u32 = 1 << 27;
+const INTERCEPT_MSR: u32 = 1 << 28;
+const INTERCEPT_SHUTDOWN: u32 = 1 << 31;
+
+/// VMRUN (obligatoire), VMMCALL, VMLOAD, VMSAVE, STGI, CLGI, SKINIT, RDTSCP.
+const INTERCEPT_SVM_INSNS: u32 = 0xFF;
+const INTERCEPT_MONITOR: u32 = 1 << 10;
+const INTERCEPT_MWAIT: u32 = 1 << 11;
+const INTERCEPT_XSETBV: u32 = 1 << 13;
+
+const INT_CTL_V_IRQ: u64 = 1 << 8;
+const INT_CTL_V_INTR_PRIO: u64 = 0xF << 16;
+const INT_CTL_V_IGN_TPR: u64 = 1 << 20;
+/// IF hôte seul masque les IRQ physiques pendant l'exécution invitée.
+const INT_CTL_V_INTR_MASKING: u64 = 1 << 24;
+
+const TLB_CONTROL_FLUSH_ALL: u8 = 1;
+
+const EVENT_VALID: u64 = 1 << 31;
+const EVENT_HAS_ERROR_CODE: u64 = 1 << 11;
+const EVENT_TYPE_EXCEPTION: u64 = 3 << 8;
+
+// ── Codes de sortie ──────────────────────────────────────────────────────────
+
+const EXIT_WRITE_CR4: u64 = 0x014;
+const EXIT_INTR: u64 = 0x060;
+const EXIT_NMI: u64 = 0x061;
+const EXIT_VINTR: u64 = 0x064;
+const EXIT_RDPMC: u64 = 0x06F;
+const EXIT_CPUID: u64 = 0x072;
+const EXIT_INVD: u64 = 0x076;
+const EXIT_HLT: u64 = 0x078;
+const EXIT_IOIO: u64 = 0x07B;
+const EXIT_MSR: u64 = 0x07C;
+const EXIT_SHUTDOWN: u64 = 0x07F;
+const EXIT_VMRUN: u64 = 0x080;
+const EXIT_RDTSCP: u64 = 0x087;
+const EXIT_MONITOR: u64 = 0x08A;
+const EXIT_MWAIT: u64 = 0x08B;
+const EXIT_XSETBV: u64 = 0x08D;
+const EXIT_NPF: u64 = 0x400;
+const EXIT_INVALID: u64 = u64::MAX;
+
+const IOIO_IN: u64 = 1 << 0;
+const IOIO_STRING: u64 = 1 << 2;
+const IOIO_REP: u64 = 1 << 3;
+const IOIO_SIZE8: u64 = 1 << 4;
+const IOIO_SIZE16: u64 = 1 << 5;
+
+// ── Registres de contrôle ────────────────────────────────────────────────────
+
+const CR0_PE: u64 = 1 << 0;
+const CR4_PAE: u64 = 1 << 5;
+const CR4_SMXE: u64 = 1 << 14;
+const CR4_OSXSAVE: u64 = 1 << 18;
+const EFER_SCE: u64 = 1 << 0;
+const EFER_LME: u64 = 1 << 8;
+const EFER_LMA: u64 = 1 << 10;
+const EFER_NXE: u64 = 1 << 11;
+const EFER_SVME: u64 = 1 << 12;
+const CR0_PG: u64 = 1 << 31;
+const RFLAGS_IF: u64 = 1 << 9;
+
+const PAT_RESET: u64 = 0x0007_0406_0007_0406;
+const GUEST_ASID: u32 = 1;
+/// IOPM : 12 KiB (ordre 2), MSRPM : 8 KiB (ordre 1), tout à 1 = tout intercepter.
+const IOPM_ORDER: usize = 2;
+const MSRPM_ORDER: usize = 1;
+
+const VECTOR_GP: u8 = 13;
+
+extern "C" {
+    /// VMSAVE hôte, VMLOAD + VMRUN + VMSAVE invité, VMLOAD hôte. RAX et RSP
+    /// invités passent par le VMCB, les autres registres par `gprs`.
+    fn exo_svm_run(gprs: *mut GuestGprs, vmcb_pa: u64, host_save_pa: u64);
+}
+
+core::arch::global_asm!(
+    ".section .text",
+    ".global exo_svm_run",
+    ".type   exo_svm_run, @function",
+    "exo_svm_run:",
+    "push  rbp",
+    "push  rbx",
+    "push  r12",
+    "push  r13",
+    "push  r14",
+    "push  r15",
+    "push  rdi",
+    "push  rdx",
+    "mov   rax, rdx",
+    "vmsave rax",
+    "mov   rax, rsi",
+    "mov   rcx, qword ptr [rdi + 8]",
+    "mov   rdx, qword ptr [rdi + 16]",
+    "mov   rbx, qword ptr [rdi + 24]",
+    "mov   rbp, qword ptr [rdi + 40]",
+    "mov   rsi, qword ptr [rdi + 48]",
+    "mov   r8,  qword ptr [rdi + 64]",
+    "mov   r9,  qword ptr [rdi + 72]",
+    "mov   r10, qword ptr [rdi + 80]",
+    "mov   r11, qword ptr [rdi + 88]",
+    "mov   r12, qword ptr [rdi + 96]",
+    "mov   r13, qword ptr [rdi + 104]",
+    "mov   r14, qword ptr [rdi + 112]",
+    "mov   r15, qword ptr [rdi + 120]",
+    "mov   rdi, qword ptr [rdi + 56]",
+    "vmload rax",
+    "vmrun rax",
+    // #VMEXIT : RAX = adresse du VMCB invité (restauré depuis la zone hôte).
+    "vmsave rax",
+    "push  rdi",
+    "mov   rdi, qword ptr [rsp + 16]",
+    "mov   qword ptr [rdi + 8], rcx",
+    "mov   qword ptr [rdi + 16], rdx",
+    "mov   qword ptr [rdi + 24], rbx",
+    "mov   qword ptr [rdi + 40], rbp",
+    "mov   qword ptr [rdi + 48], rsi",
+    "mov   qword ptr [rdi + 64], r8",
+    "mov   qword ptr [rdi + 72], r9",
+    "mov   qword ptr [rdi + 80], r10",
+    "mov   qword ptr [rdi + 88], r11",
+    "mov   qword ptr [rdi + 96], r12",
+    "mov   qword ptr [rdi + 104], r13",
+    "mov   qword ptr [rdi + 112], r14",
+    "mov   qword ptr [rdi + 120], r15",
+    "pop   rax",
+    "mov   qword ptr [rdi + 56], rax",
+    "pop   rax",
+    "vmload rax",
+    "pop   rdi",
+    "pop   r15",
+    "pop   r14",
+    "pop   r13",
+    "pop   r12",
+    "pop   rbx",
+    "pop   rbp",
+    "ret",
+    ".size exo_svm_run, . - exo_svm_run",
+);
+
+// ── Disponibilité et activation ──────────────────────────────────────────────
+
+pub(super) fn supported() -> bool {
+    // SAFETY: VM_CR existe dès que CPUID annonce SVM.
+    usable(cpuid, |msr| unsafe { read_msr(msr) })
+}
+
+/// SVM annoncé, avec NPT, « next RIP » et l'assistance au décodage, et non
+/// désactivé par le firmware. Feuilles et MSR lus dans l'ordre où leur
+/// existence est établie.
+fn usable(cpuid: impl Fn(u32, u32) -> (u32, u32, u32, u32), read: impl Fn(u32) -> u64) -> bool {
+    if cpuid(0x8000_0000, 0).0 < 0x8000_000A || cpuid(0x8000_0001, 0).2 & (1 << 2) == 0 {
+        return false;
+    }
+    let required = SVM_FEATURE_NPT | SVM_FEATURE_NRIPS | SVM_FEATURE_DECODE_ASSISTS;
+    if cpuid(0x8000_000A, 0).3 & required != required {
+        return false;
+    }
+    read(MSR_VM_CR) & VM_CR_SVMDIS == 0
+}
+
+/// EFER.SVME et zone de sauvegarde hôte de VMRUN (VM_HSAVE_PA) sur le CPU
+/// courant. La zone n'est jamais libérée : SVM reste actif jusqu'à l'arrêt.
+pub(super) fn enable_cpu() -> Result<(), KvmError> {
+    let hsave = alloc_page(AllocFlags::ZEROED).map_err(|_| KvmError::NoMemory)?;
+    // SAFETY: SVM supporté ; préemption coupée par l'appelant.
+    unsafe {
+        write_msr(MSR_IA32_EFER, read_msr(MSR_IA32_EFER) | EFER_SVME);
+        write_msr(MSR_VM_HSAVE_PA, hsave.start_address().as_u64());
+    }
+    Ok(())
+}
+
+// ── vCPU ─────────────────────────────────────────────────────────────────────
+
+pub(super) struct SvmVcpu {
+    vmcb: Frame,
+    /// Zone VMSAVE de l'hôte (FS/GS/TR/LDTR cachés, MSR SYSCALL).
+    host_save: Frame,
+    iopm: Frame,
+    msrpm: Frame,
+    configured: bool,
+    guest_fx: FxArea,
+}
+
+impl SvmVcpu {
+    pub(super) fn new() -> Result<Self, KvmError> {
+        let vmcb = alloc_page(AllocFlags::ZEROED).map_err(|_| KvmError::NoMemory)?;
+        let host_save = alloc_page(AllocFlags::ZEROED);
+        let iopm = alloc_pages(IOPM_ORDER, AllocFlags::NONE);
+        let msrpm = alloc_pages(MSRPM_ORDER, AllocFlags::NONE);
+        let (Ok(host_save), Ok(iopm), Ok(msrpm)) = (host_save, iopm, msrpm) else {
+            let _ = free_page(vmcb);
+            if let Ok(frame) = host_save {
+                let _ = free_page(frame);
+            }
+            if let Ok(frame) = iopm {
+                let _ = free_pages(frame, IOPM_ORDER);
+            }
+            if let Ok(frame) = msrpm {
+                let _ = free_pages(frame, MSRPM_ORDER);
+            }
+            return Err(KvmError::NoMemory);
+        };
+        for (frame, order) in [(iopm, IOPM_ORDER), (msrpm, MSRPM_ORDER)] {
+            let bytes = 4096usize << order;
+            // SAFETY: bloc fraîchement alloué de `bytes` octets, accessible
+            // par la physmap.
+            unsafe {
+                core::ptr::write_bytes(
+                    phys_to_virt(frame.start_address()).as_u64() as *mut u8,
+                    0xFF,
+                    bytes,
+                );
+            }
+        }
+        Ok(Self {
+            vmcb,
+            host_save,
+            iopm,
+            msrpm,
+            configured: false,
+            guest_fx: FxArea::new(),
+        })
+    }
+
+    fn vmcb_ptr(&self, offset: usize) -> *mut u8 {
+        (phys_to_virt(self.vmcb.start_address()).as_u64() as *mut u8).wrapping_add(offset)
+    }
+
+    fn read<T: Copy>(&self, offset: usize) -> T {
+        // SAFETY: `offset` est un champ du VMCB de ce vCPU, page possédée.
+        unsafe { (self.vmcb_ptr(offset) as *const T).read_volatile() }
+    }
+
+    fn write<T: Copy>(&mut self, offset: usize, value: T) {
+        // SAFETY: voir `read`.
+        unsafe { (self.vmcb_ptr(offset) as *mut T).write_volatile(value) }
+    }
+
+    fn write_controls(&mut self) {
+        self.write::<u16>(VMCB_INTERCEPT_CR_WRITE, INTERCEPT_CR4);
+        self.write::<u32>(
+            VMCB_INTERCEPT_MISC1,
+            INTERCEPT_INTR
+                | INTERCEPT_NMI
+                | INTERCEPT_RDPMC
+                | INTERCEPT_CPUID
+                | INTERCEPT_INVD
+                | INTERCEPT_HLT
+                | INTERCEPT_IOIO
+                | INTERCEPT_MSR
+                | INTERCEPT_SHUTDOWN,
+        );
+        self.write::<u32>(
+            VMCB_INTERCEPT_MISC2,
+            INTERCEPT_SVM_INSNS | INTERCEPT_MONITOR | INTERCEPT_MWAIT | INTERCEPT_XSETBV,
+        );
+        self.write::<u64>(VMCB_IOPM_BASE, self.iopm.start_address().as_u64());
+        self.write::<u64>(VMCB_MSRPM_BASE, self.msrpm.start_address().as_u64());
+        self.write::<u64>(VMCB_TSC_OFFSET, 0);
+        self.write::<u32>(VMCB_ASID, GUEST_ASID);
+        self.write::<u64>(VMCB_INT_CTL, INT_CTL_V_INTR_MASKING);
+        self.write::<u64>(VMCB_NP_ENABLE, 1);
+        self.write::<u64>(VMCB_EVENT_INJ, 0);
+        self.write::<u64>(VMCB_G_PAT, PAT_RESET);
+        self.write::<u64>(VMCB_DR6, 0xFFFF_0FF0);
+        self.write::<u64>(VMCB_DR7, 0x400);
+    }
+
+    fn write_guest_state(&mut self, state: &ArchState) {
+        let sregs = &state.sregs;
+        for (offset, segment) in [
+            (VMCB_ES, &sregs.es),
+            (VMCB_CS, &sregs.cs),
+            (VMCB_SS, &sregs.ss),
+            (VMCB_DS, &sregs.ds),
+            (VMCB_FS, &sregs.fs),
+            (VMCB_GS, &sregs.gs),
+            (VMCB_LDTR, &sregs.ldt),
+            (VMCB_TR, &sregs.tr),
+        ] {
+            self.write::<u16>(offset + SEG_SELECTOR, segment.selector);
+            self.write::<u16>(offset + SEG_ATTRIB, segment_attrib(segment));
+            self.write::<u32>(offset + SEG_LIMIT, segment.limit);
+            self.write::<u64>(offset + SEG_BASE, segment.base);
+        }
+        self.write::<u32>(VMCB_GDTR + SEG_LIMIT, sregs.gdt.limit as u32);
+        self.write::<u64>(VMCB_GDTR + SEG_BASE, sregs.gdt.base);
+        self.write::<u32>(VMCB_IDTR + SEG_LIMIT, sregs.idt.limit as u32);
+        self.write::<u64>(VMCB_IDTR + SEG_BASE, sregs.idt.base);
+        let cpl = if sregs.cr0 & CR0_PE != 0 {
+            sregs.ss.dpl
+        } else {
+            0
+        };
+        self.write::<u8>(VMCB_CPL, cpl);
+        self.write::<u64>(VMCB_EFER, sregs.efer | EFER_SVME);
+        self.write::<u64>(VMCB_CR0, sregs.cr0);
+        self.write::<u64>(VMCB_CR2, sregs.cr2);
+        self.write::<u64>(VMCB_CR3, sregs.cr3);
+        self.write::<u64>(VMCB_CR4, sregs.cr4);
+        self.write::<u64>(VMCB_RIP, state.rip);
+        self.write::<u64>(VMCB_RFLAGS, state.rflags | 0x2);
+        self.write::<u64>(VMCB_INT_STATE, 0);
+    }
+
+    fn set_cr4(&mut self, value: u64) -> bool {
+        if value >> 32 != 0 || value & (CR4_SMXE | CR4_OSXSAVE) != 0 {
+            return false;
+        }
+        if self.read::<u64>(VMCB_EFER) & EFER_LMA != 0 && value & CR4_PAE == 0 {
+            return false;
+        }
+        self.write::<u64>(VMCB_CR4, value);
+        true
+    }
+
+    fn decode_exit(&mut self, gprs: &GuestGprs) -> Exit {
+        let code = self.read::<u64>(VMCB_EXIT_CODE);
+        let info1 = self.read::<u64>(VMCB_EXIT_INFO1);
+        let len = self
+            .read::<u64>(VMCB_NEXT_RIP)
+            .wrapping_sub(self.read::<u64>(VMCB_RIP)) as u8;
+
+        // Événement interrompu par la sortie (IRQ injectée puis faute NPT…).
+        let interrupted = self.read::<u64>(VMCB_EXIT_INT_INFO);
+        if interrupted & EVENT_VALID != 0 {
+            self.write::<u64>(VMCB_EVENT_INJ, interrupted);
+        }
+
+        match code {
+            EXIT_WRITE_CR4 => {
+                // Assistance au décodage : bit 63 valide, bits 3:0 = GPR source.
+                if info1 & (1 << 63) == 0 {
+                    return Exit::Unhandled(code);
+                }
+                let value = gprs.regs[(info1 & 0xF) as usize];
+                if self.set_cr4(value) {
+                    let next = self.read::<u64>(VMCB_NEXT_RIP);
+                    self.set_rip(next);
+                } else {
+                    self.inject_exception(VECTOR_GP, Some(0));
+                }
+                Exit::Host
+            }
+            EXIT_NPF => Exit::GuestPageFault {
+                gpa: self.read::<u64>(VMCB_EXIT_INFO2),
+            },
+            code => code_exit(code, info1, len),
+        }
+    }
+}
+
+/// Sorties décodées sur le code et EXITINFO1 seuls.
+fn code_exit(code: u64, info1: u64, len: u8) -> Exit {
+    match code {
+        // NMI hôte : retenue par GIF = 0, délivrée au STGI qui suit.
+        EXIT_INTR | EXIT_NMI => Exit::Host,
+        EXIT_VINTR => Exit::InterruptWindow,
+        EXIT_CPUID => Exit::Cpuid { len },
+        EXIT_HLT => Exit::Hlt { len },
+        EXIT_INVD | EXIT_MONITOR | EXIT_MWAIT => Exit::Skip { len },
+        EXIT_RDPMC | EXIT_VMRUN..=EXIT_RDTSCP | EXIT_XSETBV => Exit::InvalidOpcode,
+        EXIT_IOIO => Exit::Io {
+            port: (info1 >> 16) as u16,
+            size: if info1 & IOIO_SIZE8 != 0 {
+                1
+            } else if info1 & IOIO_SIZE16 != 0 {
+                2
+            } else {
+                4
+            },
+            write: info1 & IOIO_IN == 0,
+            string: info1 & (IOIO_STRING | IOIO_REP) != 0,
+            len,
+        },
+        // EXITINFO1 : 0 pour RDMSR, 1 pour WRMSR.
+        EXIT_MSR if info1 == 0 => Exit::Rdmsr { len },
+        EXIT_MSR => Exit::Wrmsr { len },
+        EXIT_SHUTDOWN => Exit::Shutdown,
+        EXIT_INVALID => Exit::EntryFailed(code),
+        other => Exit::Unhandled(other),
+    }
+}
+
+/// Champ de la zone d'état du VMCB qui porte `msr` (hors EFER).
+fn vmcb_msr_offset(msr: u32) -> Option<usize> {
+    Some(match msr {
+        MSR_STAR => VMCB_STAR,
+        MSR_LSTAR => VMCB_LSTAR,
+        MSR_CSTAR => VMCB_CSTAR,
+        MSR_SFMASK => VMCB_SFMASK,
+        MSR_KERNEL_GS_BASE => VMCB_KERNEL_GS_BASE,
+        MSR_FS_BASE => VMCB_FS + SEG_BASE,
+        MSR_GS_BASE => VMCB_GS + SEG_BASE,
+        MSR_SYSENTER_CS => VMCB_SYSENTER_CS,
+        MSR_SYSENTER_ESP => VMCB_SYSENTER_ESP,
+        MSR_SYSENTER_EIP => VMCB_SYSENTER_EIP,
+        MSR_IA32_PAT => VMCB_G_PAT,
+        _ => return None,
+    })
+}
+
+impl VcpuHw for SvmVcpu {
+    fn load(
+        &mut self,
+        state: &ArchState,
+        state_dirty: bool,
+        npt_root: u64,
+    ) -> Result<(), KvmError> {
+        let first = !self.configured;
+        if first {
+            self.write_controls();
+            self.configured = true;
+        }
+        self.write::<u64>(VMCB_N_CR3, npt_root);
+        // ASID partagé entre VM et slots modifiables entre deux RUN.
+        self.write::<u8>(VMCB_TLB_CONTROL, TLB_CONTROL_FLUSH_ALL);
+        self.write::<u32>(VMCB_CLEAN, 0);
+        if state_dirty || first {
+            self.write_guest_state(state);
+        }
+        Ok(())
+    }
+
+    fn put(&mut self, state: &mut ArchState) {
+        let sregs = &mut state.sregs;
+        for (offset, segment) in [
+            (VMCB_ES, &mut sregs.es),
+            (VMCB_CS, &mut sregs.cs),
+            (VMCB_SS, &mut sregs.ss),
+            (VMCB_DS, &mut sregs.ds),
+            (VMCB_FS, &mut sregs.fs),
+            (VMCB_GS, &mut sregs.gs),
+            (VMCB_LDTR, &mut sregs.ldt),
+            (VMCB_TR, &mut sregs.tr),
+        ] {
+            *segment = segment_from_vmcb(
+                self.read::<u16>(offset + SEG_SELECTOR),
+                self.read::<u64>(offset + SEG_BASE),
+                self.read::<u32>(offset + SEG_LIMIT),
+                self.read::<u16>(offset + SEG_ATTRIB),
+            );
+        }
+        sregs.gdt.base = self.read::<u64>(VMCB_GDTR + SEG_BASE);
+        sregs.gdt.limit = self.read::<u32>(VMCB_GDTR + SEG_LIMIT) as u16;
+        sregs.idt.base = self.read::<u64>(VMCB_IDTR + SEG_BASE);
+        sregs.idt.limit = self.read::<u32>(VMCB_IDTR + SEG_LIMIT) as u16;
+        sregs.efer = self.read::<u64>(VMCB_EFER) & !EFER_SVME;
+        sregs.cr0 = self.read::<u64>(VMCB_CR0);
+        sregs.cr2 = self.read::<u64>(VMCB_CR2);
+        sregs.cr3 = self.read::<u64>(VMCB_CR3);
+        sregs.cr4 = self.read::<u64>(VMCB_CR4);
+        state.rip = self.read::<u64>(VMCB_RIP);
+        state.rflags = self.read::<u64>(VMCB_RFLAGS);
+    }
+
+    fn enter(&mut self, gprs: &mut GuestGprs) -> Exit {
+        self.write::<u64>(VMCB_RSP, gprs.regs[RSP]);
+        self.write::<u64>(VMCB_RAX, gprs.regs[RAX]);
+        let vmcb_pa = self.vmcb.start_address().as_u64();
+        let host_save_pa = self.host_save.start_address().as_u64();
+        // SAFETY: SVM actif sur ce CPU, préemption coupée. GIF = 0 entre
+        // CLGI et STGI : ni IRQ ni NMI hôte pendant la bascule d'état.
+        unsafe {
+            let flags = irq_save();
+            core::arch::asm!("clgi", options(nostack, nomem));
+            let fpu = FpuSwap::enter(&self.guest_fx);
+            exo_svm_run(gprs, vmcb_pa, host_save_pa);
+            fpu.leave(&mut self.guest_fx);
+            core::arch::asm!("stgi", options(nostack, nomem));
+            irq_restore(flags);
+        }
+        self.write::<u8>(VMCB_TLB_CONTROL, 0);
+        gprs.regs[RSP] = self.read::<u64>(VMCB_RSP);
+        gprs.regs[RAX] = self.read::<u64>(VMCB_RAX);
+        self.decode_exit(gprs)
+    }
+
+    fn rip(&self) -> u64 {
+        self.read::<u64>(VMCB_RIP)
+    }
+
+    fn set_rip(&mut self, rip: u64) {
+        self.write::<u64>(VMCB_RIP, rip);
+        // Une instruction sautée lève le masquage STI/MOV SS.
+        self.write::<u64>(VMCB_INT_STATE, 0);
+    }
+
+    fn rflags(&self) -> u64 {
+        self.read::<u64>(VMCB_RFLAGS)
+    }
+
+    fn paging(&self) -> Paging {
+        let cs_attrib = self.read::<u16>(VMCB_CS + SEG_ATTRIB);
+        Paging {
+            cr0: self.read::<u64>(VMCB_CR0),
+            cr3: self.read::<u64>(VMCB_CR3),
+            cr4: self.read::<u64>(VMCB_CR4),
+            efer: self.read::<u64>(VMCB_EFER),
+            cs_base: self.read::<u64>(VMCB_CS + SEG_BASE),
+            cs_l: cs_attrib & (1 << 9) != 0,
+            cs_db: cs_attrib & (1 << 10) != 0,
+        }
+    }
+
+    fn interruptible(&self) -> bool {
+        self.read::<u64>(VMCB_RFLAGS) & RFLAGS_IF != 0
+            && self.read::<u64>(VMCB_INT_STATE) & 1 == 0
+            && self.read::<u64>(VMCB_EVENT_INJ) & EVENT_VALID == 0
+    }
+
+    fn inject_irq(&mut self, vector: u8) {
+        // Type 0 : interruption externe.
+        self.write::<u64>(VMCB_EVENT_INJ, vector as u64 | EVENT_VALID);
+    }
+
+    fn inject_exception(&mut self, vector: u8, error_code: Option<u32>) {
+        let mut event = vector as u64 | EVENT_TYPE_EXCEPTION | EVENT_VALID;
+        // Pas de code d'erreur en mode réel.
+        if let Some(code) = error_code.filter(|_| self.read::<u64>(VMCB_CR0) & CR0_PE != 0) {
+            event |= EVENT_HAS_ERROR_CODE | (code as u64) << 32;
+        }
+        self.write::<u64>(VMCB_EVENT_INJ, event);
+    }
+
+    fn set_irq_window(&mut self, enabled: bool) {
+        let window = INT_CTL_V_IRQ | INT_CTL_V_INTR_PRIO | INT_CTL_V_IGN_TPR;
+        let int_ctl = self.read::<u64>(VMCB_INT_CTL);
+        let intercepts = self.read::<u32>(VMCB_INTERCEPT_MISC1);
+        if enabled {
+            self.write::<u64>(VMCB_INT_CTL, int_ctl | window);
+            self.write::<u32>(VMCB_INTERCEPT_MISC1, intercepts | INTERCEPT_VINTR);
+        } else {
+            self.write::<u64>(VMCB_INT_CTL, int_ctl & !window);
+            self.write::<u32>(VMCB_INTERCEPT_MISC1, intercepts & !INTERCEPT_VINTR);
+        }
+    }
+
+    fn read_msr(&mut self, msr: u32) -> Option<u64> {
+        if msr == MSR_IA32_EFER {
+            return Some(self.read::<u64>(VMCB_EFER) & !EFER_SVME);
+        }
+        Some(self.read::<u64>(vmcb_msr_offset(msr)?))
+    }
+
+    fn write_msr(&mut self, msr: u32, value: u64) -> bool {
+        let offset = match msr {
+            MSR_IA32_EFER => {
+                if value & !(EFER_SCE | EFER_LME | EFER_LMA | EFER_NXE) != 0 {
+                    return false;
+                }
+                let efer = self.read::<u64>(VMCB_EFER);
+                // LME figé tant que la pagination est active.
+                if self.read::<u64>(VMCB_CR0) & CR0_PG != 0 && (value ^ efer) & EFER_LME != 0 {
+                    return false;
+                }
+                let value = (value & !EFER_LMA) | (efer & EFER_LMA) | EFER_SVME;
+                self.write::<u64>(VMCB_EFER, value);
+                return true;
+            }
+            msr => match vmcb_msr_offset(msr) {
+                Some(offset) => offset,
+                None => return false,
+            },
+        };
+        self.write::<u64>(offset, value);
+        true
+    }
+}
+
+impl Drop for SvmVcpu {
+    fn drop(&mut self) {
+        let _ = free_page(self.vmcb);
+        let _ = free_page(self.host_save);
+        let _ = free_pages(self.iopm, IOPM_ORDER);
+        let _ = free_pages(self.msrpm, MSRPM_ORDER);
+    }
+}
+
+// ── Conversions de segments ──────────────────────────────────────────────────
+
+/// Attributs VMCB : octet d'accès en bits 7:0, AVL/L/DB/G en bits 11:8.
+fn segment_attrib(segment: &KvmSegment) -> u16 {
+    if segment.unusable != 0 {
+        return 0;
+    }
+    (segment.type_ as u16 & 0xF)
+        | (segment.s as u16 & 1) << 4
+        | (segment.dpl as u16 & 3) << 5
+        | (segment.present as u16 & 1) << 7
+        | (segment.avl as u16 & 1) << 8
+        | (segment.l as u16 & 1) << 9
+        | (segment.db as u16 & 1) << 10
+        | (segment.g as u16 & 1) << 11
+}
+
+fn segment_from_vmcb(selector: u16, base: u64, limit: u32, attrib: u16) -> KvmSegment {
+    let present = ((attrib >> 7) & 1) as u8;
+    KvmSegment {
+        base,
+        limit,
+        selector,
+        type_: (attrib & 0xF) as u8,
+        s: ((attrib >> 4) & 1) as u8,
+        dpl: ((attrib >> 5) & 3) as u8,
+        present,
+        avl: ((attrib >> 8) & 1) as u8,
+        l: ((attrib >> 9) & 1) as u8,
+        db: ((attrib >> 10) & 1) as u8,
+        g: ((attrib >> 11) & 1) as u8,
+        unusable: (present == 0) as u8,
+        padding: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEATURES: u32 = SVM_FEATURE_NPT | SVM_FEATURE_NRIPS | SVM_FEATURE_DECODE_ASSISTS;
+
+    fn leaves(
+        max_ext: u32,
+        ext1_ecx: u32,
+        features: u32,
+    ) -> impl Fn(u32, u32) -> (u32, u32, u32, u32) {
+        move |leaf, _| match leaf {
+            0x8000_0000 => (max_ext, 0, 0, 0),
+            0x8000_0001 => (0, 0, ext1_ecx, 0),
+            0x8000_000A => {
+                assert!(
+                    max_ext >= 0x8000_000A,
+                    "feuille 0x8000_000A lue hors limite"
+                );
+                (1, 0, 0, features)
+            }
+            other => panic!("feuille {other:#x} inattendue"),
+        }
+    }
+
+    fn vm_cr(value: u64) -> impl Fn(u32) -> u64 {
+        move |msr| {
+            assert_eq!(msr, MSR_VM_CR);
+            value
+        }
+    }
+
+    const SVM: u32 = 1 << 2;
+
+    #[test]
+    fn usable_requires_npt_nrips_and_decode_assists() {
+        assert!(usable(leaves(0x8000_001F, SVM, FEATURES), vm_cr(0)));
+        assert!(!usable(leaves(0x8000_001F, 0, FEATURES), vm_cr(0)));
+        assert!(!usable(leaves(0x8000_0008, SVM, FEATURES), vm_cr(0)));
+        for missing in [
+            SVM_FEATURE_NPT,
+            SVM_FEATURE_NRIPS,
+            SVM_FEATURE_DECODE_ASSISTS,
+        ] {
+            assert!(!usable(
+                leaves(0x8000_001F, SVM, FEATURES & !missing),
+                vm_cr(0)
+            ));
+        }
+        // Désactivé par le firmware.
+        assert!(!usable(
+            leaves(0x8000_001F, SVM, FEATURES),
+            vm_cr(VM_CR_SVMDIS)
+        ));
+    }
+
+    #[test]
+    fn exit_codes_are_decoded() {
+        assert_eq!(code_exit(EXIT_INTR, 0, 0), Exit::Host);
+        assert_eq!(code_exit(EXIT_NMI, 0, 0), Exit::Host);
+        assert_eq!(code_exit(EXIT_VINTR, 0, 0), Exit::InterruptWindow);
+        assert_eq!(code_exit(EXIT_CPUID, 0, 2), Exit::Cpuid { len: 2 });
+        assert_eq!(code_exit(EXIT_HLT, 0, 1), Exit::Hlt { len: 1 });
+        assert_eq!(code_exit(EXIT_SHUTDOWN, 0, 0), Exit::Shutdown);
+        for skipped in [EXIT_INVD, EXIT_MONITOR, EXIT_MWAIT] {
+            assert_eq!(code_exit(skipped, 0, 3), Exit::Skip { len: 3 });
+        }
+        // VMRUN … RDTSCP (VMMCALL, VMLOAD, STGI, SKINIT…), RDPMC, XSETBV : #UD.
+        for refused in (EXIT_VMRUN..=EXIT_RDTSCP).chain([EXIT_RDPMC, EXIT_XSETBV]) {
+            assert_eq!(
+                code_exit(refused, 0, 3),
+                Exit::InvalidOpcode,
+                "{refused:#x}"
+            );
+        }
+        assert_eq!(
+            code_exit(EXIT_INVALID, 0, 0),
+            Exit::EntryFailed(EXIT_INVALID)
+        );
+        assert_eq!(
+            code_exit(EXIT_WRITE_CR4, 0, 0),
+            Exit::Unhandled(EXIT_WRITE_CR4)
+        );
+        assert_eq!(code_exit(0x07E, 0, 0), Exit::Unhandled(0x07E));
+    }
+
+    #[test]
+    fn msr_direction_comes_from_exitinfo1() {
+        assert_eq!(code_exit(EXIT_MSR, 0, 2), Exit::Rdmsr { len: 2 });
+        assert_eq!(code_exit(EXIT_MSR, 1, 2), Exit::Wrmsr { len: 2 });
+    }
+
+    #[test]
+    fn ioio_exitinfo1_is_decoded() {
+        // OUT DX, AL vers 0x3F8.
+        assert_eq!(
+            code_exit(EXIT_IOIO, (0x3F8 << 16) | IOIO_SIZE8, 1),
+            Exit::Io {
+                port: 0x3F8,
+                size: 1,
+                write: true,
+                string: false,
+                len: 1
+            }
+        );
+        // IN AX, DX.
+        assert_eq!(
+            code_exit(EXIT_IOIO, (0x1F0 << 16) | IOIO_SIZE16 | IOIO_IN, 1),
+            Exit::Io {
+                port: 0x1F0,
+                size: 2,
+                write: false,
+                string: false,
+                len: 1
+            }
+        );
+        // OUT DX, EAX : ni SZ8 ni SZ16.
+        let Exit::Io { size: 4, .. } = code_exit(EXIT_IOIO, 0xCF8 << 16, 1) else {
+            panic!("taille 32 bits non décodée");
+        };
+        for string in [IOIO_STRING, IOIO_REP] {
+            let Exit::Io { string: true, .. } = code_exit(EXIT_IOIO, string | IOIO_SIZE8, 2) else {
+                panic!("chaîne non détectée");
+            };
+        }
+    }
+
+    #[test]
+    fn guest_msrs_map_to_vmcb_state() {
+        assert_eq!(vmcb_msr_offset(MSR_STAR), Some(VMCB_STAR));
+        assert_eq!(vmcb_msr_offset(MSR_LSTAR), Some(VMCB_LSTAR));
+        assert_eq!(vmcb_msr_offset(MSR_CSTAR), Some(VMCB_CSTAR));
+        assert_eq!(vmcb_msr_offset(MSR_SFMASK), Some(VMCB_SFMASK));
+        assert_eq!(
+            vmcb_msr_offset(MSR_KERNEL_GS_BASE),
+            Some(VMCB_KERNEL_GS_BASE)
+        );
+        assert_eq!(vmcb_msr_offset(MSR_FS_BASE), Some(VMCB_FS + SEG_BASE));
+        assert_eq!(vmcb_msr_offset(MSR_GS_BASE), Some(VMCB_GS + SEG_BASE));
+        assert_eq!(vmcb_msr_offset(MSR_SYSENTER_CS), Some(VMCB_SYSENTER_CS));
+        assert_eq!(vmcb_msr_offset(MSR_SYSENTER_ESP), Some(VMCB_SYSENTER_ESP));
+        assert_eq!(vmcb_msr_offset(MSR_SYSENTER_EIP), Some(VMCB_SYSENTER_EIP));
+        assert_eq!(vmcb_msr_offset(MSR_IA32_PAT), Some(VMCB_G_PAT));
+        // EFER (SVME masqué) traité à part ; VM_CR et HSAVE jamais exposés.
+        for msr in [MSR_IA32_EFER, MSR_VM_CR, MSR_VM_HSAVE_PA, 0x10] {
+            assert_eq!(vmcb_msr_offset(msr), None, "{msr:#x}");
+        }
+    }
+
+    #[test]
+    fn segments_round_trip_through_attributes() {
+        let code64 = KvmSegment {
+            limit: 0xFFFF_FFFF,
+            selector: 0x08,
+            type_: 11,
+            present: 1,
+            s: 1,
+            dpl: 3,
+            l: 1,
+            g: 1,
+            ..Default::default()
+        };
+        let back = segment_from_vmcb(0x08, 0, 0xFFFF_FFFF, segment_attrib(&code64));
+        assert_eq!(
+            (
+                back.type_,
+                back.s,
+                back.dpl,
+                back.present,
+                back.l,
+                back.db,
+                back.g
+            ),
+            (11, 1, 3, 1, 1, 0, 1)
+        );
+    }
+}
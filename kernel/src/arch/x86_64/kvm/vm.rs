@@ -0,0 +1,1151 @@
+//! # kvm/vm.rs — VM, slots mémoire, vCPU et boucle `EXO_KVM_RUN`
+//!
+//! Partie commune à VMX et SVM : validation des ioctl, RAM invitée épinglée,
+//! émulation CPUID/MSR, décodage MMIO et conversion des sorties matérielles
+//! en raisons `EXO_KVM_EXIT_*` pour le VMM.
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use spin::Mutex;
+
+use crate::drivers::dma::{pin_user_range_for_pid, PinnedPage};
+use crate::memory::core::phys_to_virt;
+use crate::memory::dma::core::types::DmaError;
+use crate::memory::PhysAddr;
+use crate::scheduler::core::preempt::PreemptGuard;
+use crate::scheduler::core::task::ThreadControlBlock;
+use crate::syscall::validation::{copy_from_user, read_user_typed, write_user_typed};
+
+use super::abi::*;
+use super::mmio::{self, Access, CpuMode, MmioInsn};
+use super::npt::{GuestPageTable, TableFormat};
+use super::svm::SvmVcpu;
+use super::vmx::VmxVcpu;
+use super::{
+    backend, cpuid, enable_on_current_cpu, flush_guest_tlb, ArchState, Backend, Exit, GuestGprs, KvmError,
+    KvmHandle, Paging, VcpuHw, RAX, RBX, RCX, RDX, RSP,
+};
+
+const PAGE_SIZE: u64 = 4096;
+const PAGE_MASK: u64 = PAGE_SIZE - 1;
+
+const CR0_PE: u64 = 1 << 0;
+const CR0_PG: u64 = 1 << 31;
+const CR4_PSE: u64 = 1 << 4;
+const CR4_PAE: u64 = 1 << 5;
+const EFER_LMA: u64 = 1 << 10;
+const RFLAGS_IF: u64 = 1 << 9;
+
+const VECTOR_UD: u8 = 6;
+const VECTOR_GP: u8 = 13;
+
+/// Résultat d'un ioctl : valeur de retour ou nouveau descripteur à installer.
+#[derive(Clone, Copy, Debug)]
+pub enum IoctlOutcome {
+    Value(i64),
+    NewFd(KvmHandle),
+}
+
+// ── Registre global ──────────────────────────────────────────────────────────
+
+static VMS: Mutex<Vec<Arc<Vm>>> = Mutex::new(Vec::new());
+static NEXT_VM_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Plage de RAM invitée d'un slot, validée par `check_region`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SlotRange {
+    slot: u32,
+    gpa: u64,
+    size: u64,
+}
+
+impl SlotRange {
+    fn contains(&self, gpa: u64) -> bool {
+        gpa >= self.gpa && gpa - self.gpa < self.size
+    }
+
+    fn overlaps(&self, other: &SlotRange) -> bool {
+        other.gpa < self.gpa + self.size && self.gpa < other.gpa + other.size
+    }
+
+    fn page_gpas(&self) -> impl Iterator<Item = u64> {
+        let gpa = self.gpa;
+        (0..self.size / PAGE_SIZE).map(move |page| gpa + page * PAGE_SIZE)
+    }
+}
+
+struct MemSlot {
+    range: SlotRange,
+    pages: Vec<PinnedPage>,
+}
+
+impl MemSlot {
+    fn contains(&self, gpa: u64) -> bool {
+        self.range.contains(gpa)
+    }
+
+    fn hpa_of(&self, gpa: u64) -> PhysAddr {
+        self.pages[((gpa - self.range.gpa) / PAGE_SIZE) as usize].phys
+    }
+}
+
+impl Drop for MemSlot {
+    fn drop(&mut self) {
+        for page in &self.pages {
+            page.unpin();
+        }
+    }
+}
+
+struct Memory {
+    slots: Vec<MemSlot>,
+    table: GuestPageTable,
+}
+
+impl Memory {
+    fn hpa(&self, gpa: u64) -> Option<PhysAddr> {
+        let slot = self.slots.iter().find(|slot| slot.contains(gpa))?;
+        let page = slot.hpa_of(gpa & !PAGE_MASK);
+        Some(PhysAddr::new(page.as_u64() + (gpa & PAGE_MASK)))
+    }
+
+    /// Copie la RAM invitée `[gpa, gpa + out.len())` ; `false` hors slots.
+    fn read(&self, mut gpa: u64, out: &mut [u8]) -> bool {
+        let mut done = 0;
+        while done < out.len() {
+            let Some(hpa) = self.hpa(gpa) else {
+                return false;
+            };
+            let chunk = ((PAGE_SIZE - (gpa & PAGE_MASK)) as usize).min(out.len() - done);
+            let src = phys_to_virt(hpa).as_u64() as *const u8;
+            // SAFETY: `hpa` est une page épinglée de la VM, accessible par la
+            // physmap ; `chunk` ne dépasse pas la fin de la page.
+            unsafe { core::ptr::copy_nonoverlapping(src, out[done..].as_mut_ptr(), chunk) };
+            done += chunk;
+            gpa += chunk as u64;
+        }
+        true
+    }
+
+    fn read_u64(&self, gpa: u64) -> Option<u64> {
+        let mut bytes = [0u8; 8];
+        self.read(gpa, &mut bytes)
+            .then(|| u64::from_le_bytes(bytes))
+    }
+
+    fn read_u32(&self, gpa: u64) -> Option<u64> {
+        let mut bytes = [0u8; 4];
+        self.read(gpa, &mut bytes)
+            .then(|| u32::from_le_bytes(bytes) as u64)
+    }
+
+    /// Adresse linéaire → physique invité, en parcourant les tables de l'invité.
+    fn translate(&self, paging: &Paging, la: u64) -> Option<u64> {
+        const ADDR_MASK: u64 = 0x000F_FFFF_FFFF_F000;
+        const PRESENT: u64 = 1;
+        const HUGE: u64 = 1 << 7;
+
+        if paging.cr0 & CR0_PG == 0 {
+            return Some(la & 0xFFFF_FFFF);
+        }
+        if paging.efer & EFER_LMA != 0 || paging.cr4 & CR4_PAE != 0 {
+            let (mut table, top) = if paging.efer & EFER_LMA != 0 {
+                (paging.cr3 & ADDR_MASK, 3)
+            } else {
+                let pdpte = self.read_u64((paging.cr3 & 0xFFFF_FFE0) + ((la >> 30) & 3) * 8)?;
+                if pdpte & PRESENT == 0 {
+                    return None;
+                }
+                (pdpte & ADDR_MASK, 1)
+            };
+            for level in (0..=top).rev() {
+                let shift = 12 + 9 * level;
+                let entry = self.read_u64(table + ((la >> shift) & 0x1FF) * 8)?;
+                if entry & PRESENT == 0 {
+                    return None;
+                }
+                if level > 0 && entry & HUGE != 0 {
+                    let offset_mask = (1u64 << shift) - 1;
+                    return Some((entry & ADDR_MASK & !offset_mask) | (la & offset_mask));
+                }
+                table = entry & ADDR_MASK;
+            }
+            return Some(table | (la & PAGE_MASK));
+        }
+        let pde = self.read_u32((paging.cr3 & 0xFFFF_F000) + ((la >> 22) & 0x3FF) * 4)?;
+        if pde & PRESENT == 0 {
+            return None;
+        }
+        if pde & HUGE != 0 && paging.cr4 & CR4_PSE != 0 {
+            return Some((pde & 0xFFC0_0000) | (la & 0x3F_FFFF));
+        }
+        let pte = self.read_u32((pde & 0xFFFF_F000) + ((la >> 12) & 0x3FF) * 4)?;
+        (pte & PRESENT != 0).then_some((pte & 0xFFFF_F000) | (la & PAGE_MASK))
+    }
+}
+
+struct Vm {
+    id: u32,
+    owner_pid: u32,
+    backend: Backend,
+    memory: Mutex<Memory>,
+    /// `(id, vCPU)` : l'id se lit sans prendre le verrou d'un vCPU en cours.
+    vcpus: Mutex<Vec<(u32, Arc<Mutex<Vcpu>>)>>,
+    /// vCPU dans `EXO_KVM_RUN` : les slots ne bougent pas pendant ce temps.
+    running: AtomicU32,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Pending {
+    /// IN : la valeur arrive dans `KvmRun::io.data` au RUN suivant.
+    IoIn {
+        size: u8,
+    },
+    MmioRead(MmioInsn),
+}
+
+struct Vcpu {
+    id: u32,
+    hw: Box<dyn VcpuHw>,
+    gprs: GuestGprs,
+    state: ArchState,
+    /// `state` modifié par le VMM depuis le dernier RUN.
+    state_dirty: bool,
+    pending_irq: Option<u8>,
+    pending: Option<Pending>,
+    /// Table du VMM (`EXO_KVM_SET_CPUID`) ; vide : CPUID hôte filtré.
+    cpuid: Vec<KvmCpuidEntry>,
+}
+
+/// Détruit les VM de `pid` (sortie du processus). Un vCPU en cours
+/// d'exécution garde sa VM vivante jusqu'à son retour.
+pub fn release_for_pid(pid: u32) {
+    let released: Vec<Arc<Vm>> = {
+        let mut vms = VMS.lock();
+        let mut released = Vec::new();
+        let mut i = 0;
+        while i < vms.len() {
+            if vms[i].owner_pid == pid {
+                released.push(vms.swap_remove(i));
+            } else {
+                i += 1;
+            }
+        }
+        released
+    };
+    // Libération des pages hors du verrou global.
+    drop(released);
+}
+
+fn find_vm(id: u32, pid: u32) -> Result<Arc<Vm>, KvmError> {
+    let vms = VMS.lock();
+    let vm = vms
+        .iter()
+        .find(|vm| vm.id == id)
+        .ok_or(KvmError::NotFound)?;
+    if vm.owner_pid != pid {
+        return Err(KvmError::PermissionDenied);
+    }
+    Ok(vm.clone())
+}
+
+pub fn ioctl(
+    handle: KvmHandle,
+    request: u64,
+    arg: u64,
+    pid: u32,
+) -> Result<IoctlOutcome, KvmError> {
+    match handle {
+        KvmHandle::System => system_ioctl(request, arg, pid),
+        KvmHandle::Vm(id) => vm_ioctl(&*find_vm(id, pid)?, request, arg),
+        KvmHandle::Vcpu { vm, vcpu } => vcpu_ioctl(&*find_vm(vm, pid)?, vcpu, request, arg),
+    }
+}
+
+fn system_ioctl(request: u64, arg: u64, pid: u32) -> Result<IoctlOutcome, KvmError> {
+    match request {
+        EXO_KVM_GET_API_VERSION => Ok(IoctlOutcome::Value(EXO_KVM_API_VERSION)),
+        EXO_KVM_CHECK_EXTENSION => Ok(IoctlOutcome::Value(extension_value(arg, backend()))),
+        EXO_KVM_CREATE_VM => {
+            let backend = backend().ok_or(KvmError::NotSupported)?;
+            let format = match backend {
+                Backend::Vmx => TableFormat::Ept,
+                Backend::Svm => TableFormat::Npt,
+            };
+            let vm = Arc::new(Vm {
+                id: NEXT_VM_ID.fetch_add(1, Ordering::Relaxed),
+                owner_pid: pid,
+                backend,
+                memory: Mutex::new(Memory {
+                    slots: Vec::new(),
+                    table: GuestPageTable::new(format)?,
+                }),
+                vcpus: Mutex::new(Vec::new()),
+                running: AtomicU32::new(0),
+            });
+            let id = vm.id;
+            let mut vms = VMS.lock();
+            vms.try_reserve(1).map_err(|_| KvmError::NoMemory)?;
+            vms.push(vm);
+            Ok(IoctlOutcome::NewFd(KvmHandle::Vm(id)))
+        }
+        _ => Err(KvmError::Invalid),
+    }
+}
+
+fn vm_ioctl(vm: &Vm, request: u64, arg: u64) -> Result<IoctlOutcome, KvmError> {
+    match request {
+        EXO_KVM_SET_USER_MEMORY_REGION => {
+            let region: KvmMemoryRegion = read_user_typed(arg).map_err(|_| KvmError::Fault)?;
+            set_memory_region(vm, &region)?;
+            Ok(IoctlOutcome::Value(0))
+        }
+        EXO_KVM_CREATE_VCPU => {
+            let id = vcpu_id_arg(arg).ok_or(KvmError::Invalid)?;
+            let mut vcpus = vm.vcpus.lock();
+            if vcpus.iter().any(|(vcpu_id, _)| *vcpu_id == id) {
+                return Err(KvmError::Exists);
+            }
+            let hw: Box<dyn VcpuHw> = match vm.backend {
+                Backend::Vmx => Box::new(VmxVcpu::new()?),
+                Backend::Svm => Box::new(SvmVcpu::new()?),
+            };
+            vcpus.try_reserve(1).map_err(|_| KvmError::NoMemory)?;
+            let vcpu = Arc::new(Mutex::new(Vcpu {
+                id,
+                hw,
+                gprs: GuestGprs::default(),
+                state: ArchState::reset(),
+                state_dirty: true,
+                pending_irq: None,
+                pending: None,
+                cpuid: Vec::new(),
+            }));
+            vcpus.push((id, vcpu));
+            Ok(IoctlOutcome::NewFd(KvmHandle::Vcpu {
+                vm: vm.id,
+                vcpu: id,
+            }))
+        }
+        _ => Err(KvmError::Invalid),
+    }
+}
+
+/// Arguments de `EXO_KVM_SET_USER_MEMORY_REGION` : slot connu, pas de
+/// drapeau, tout aligné sur 4 KiB et sans débordement.
+fn check_region(region: &KvmMemoryRegion) -> Result<SlotRange, KvmError> {
+    let KvmMemoryRegion {
+        slot,
+        flags,
+        guest_phys_addr: gpa,
+        memory_size: size,
+        userspace_addr: uaddr,
+    } = *region;
+    if slot as usize >= MAX_MEMSLOTS
+        || flags != 0
+        || (gpa | size | uaddr) & PAGE_MASK != 0
+        || gpa.checked_add(size).is_none()
+        || uaddr.checked_add(size).is_none()
+    {
+        return Err(KvmError::Invalid);
+    }
+    Ok(SlotRange { slot, gpa, size })
+}
+
+/// Indice du slot que `new` remplace (même numéro), après avoir vérifié qu'il
+/// ne chevauche aucun autre slot. Un slot vide ne chevauche rien.
+fn place_slot(
+    slots: impl Iterator<Item = SlotRange>,
+    new: &SlotRange,
+) -> Result<Option<usize>, KvmError> {
+    let mut replaced = None;
+    for (index, range) in slots.enumerate() {
+        if range.slot == new.slot {
+            replaced = Some(index);
+        } else if new.size != 0 && range.overlaps(new) {
+            return Err(KvmError::Exists);
+        }
+    }
+    Ok(replaced)
+}
+
+/// Remplace, ajoute ou (taille nulle) retire un slot. Tout ce qui peut
+/// échouer — chevauchement, épinglage, tables — passe avant de toucher à
+/// l'ancien slot : une erreur laisse la VM telle quelle. Les anciennes pages
+/// ne sont désépinglées qu'après invalidation du TLB invité.
+fn set_memory_region(vm: &Vm, region: &KvmMemoryRegion) -> Result<(), KvmError> {
+    let new = check_region(region)?;
+    let uaddr = region.userspace_addr;
+
+    // `run` compte ses vCPU sous ce verrou : aucun n'entre pendant le
+    // changement.
+    let mut memory = vm.memory.lock();
+    if vm.running.load(Ordering::Acquire) != 0 {
+        return Err(KvmError::Busy);
+    }
+    let replaced = place_slot(memory.slots.iter().map(|s| s.range), &new)?;
+    if new.size == 0 && replaced.is_none() {
+        return Ok(());
+    }
+
+    let new = if new.size == 0 {
+        None
+    } else {
+        let pages = pin_user_range_for_pid(vm.owner_pid, uaddr as usize, new.size as usize)
+            .map_err(|e| match e {
+                DmaError::OutOfMemory => KvmError::NoMemory,
+                _ => KvmError::Fault,
+            })?;
+        // Épinglées : désépinglées par `Drop` si la suite échoue.
+        let slot = MemSlot { range: new, pages };
+        memory
+            .slots
+            .try_reserve(1)
+            .map_err(|_| KvmError::NoMemory)?;
+        map_slot(&mut memory, &slot, replaced)?;
+        Some(slot)
+    };
+
+    // Pages de l'ancien slot que le nouveau ne recouvre pas.
+    if let Some(index) = replaced {
+        let old = memory.slots[index].range;
+        for gpa in old.page_gpas() {
+            if !new.as_ref().is_some_and(|slot| slot.contains(gpa)) {
+                memory.table.unmap(gpa);
+            }
+        }
+        let root = memory.table.root().as_u64();
+        let _preempt = PreemptGuard::new();
+        flush_guest_tlb(vm.backend, root);
+    }
+
+    let old = match (replaced, new) {
+        (Some(index), Some(new)) => Some(core::mem::replace(&mut memory.slots[index], new)),
+        (Some(index), None) => Some(memory.slots.swap_remove(index)),
+        (None, Some(new)) => {
+            memory.slots.push(new);
+            None
+        }
+        (None, None) => None,
+    };
+    drop(memory);
+    // Désépinglage hors du verrou, TLB déjà invalidé.
+    drop(old);
+    Ok(())
+}
+
+/// Projette `slot` dans les tables EPT/NPT. En cas d'échec, les pages déjà
+/// projetées reviennent à l'état antérieur : celles de l'ancien slot
+/// `replaced` sont rétablies, les autres retirées.
+fn map_slot(memory: &mut Memory, slot: &MemSlot, replaced: Option<usize>) -> Result<(), KvmError> {
+    for (i, gpa) in slot.range.page_gpas().enumerate() {
+        if let Err(e) = memory.table.map(gpa, slot.pages[i].phys) {
+            for done in slot.range.page_gpas().take(i) {
+                let previous = replaced
+                    .map(|index| &memory.slots[index])
+                    .filter(|old| old.contains(done))
+                    .map(|old| old.hpa_of(done));
+                match previous {
+                    // Tables intermédiaires déjà présentes : ne peut échouer.
+                    Some(hpa) => {
+                        let _ = memory.table.map(done, hpa);
+                    }
+                    None => memory.table.unmap(done),
+                }
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+fn vcpu_ioctl(vm: &Vm, id: u32, request: u64, arg: u64) -> Result<IoctlOutcome, KvmError> {
+    let vcpu = vm
+        .vcpus
+        .lock()
+        .iter()
+        .find(|(vcpu_id, _)| *vcpu_id == id)
+        .map(|(_, vcpu)| vcpu.clone())
+        .ok_or(KvmError::NotFound)?;
+    // Un seul appelant par vCPU : un second RUN concurrent est refusé.
+    let mut vcpu = vcpu.try_lock().ok_or(KvmError::Busy)?;
+
+    match request {
+        EXO_KVM_RUN => run(vm, &mut vcpu, arg)?,
+        EXO_KVM_GET_REGS => {
+            write_user_typed(arg, vcpu.regs()).map_err(|_| KvmError::Fault)?;
+        }
+        EXO_KVM_SET_REGS => {
+            let regs: KvmRegs = read_user_typed(arg).map_err(|_| KvmError::Fault)?;
+            vcpu.set_regs(&regs);
+        }
+        EXO_KVM_GET_SREGS => {
+            write_user_typed(arg, vcpu.state.sregs).map_err(|_| KvmError::Fault)?;
+        }
+        EXO_KVM_SET_SREGS => {
+            vcpu.state.sregs = read_user_typed(arg).map_err(|_| KvmError::Fault)?;
+            vcpu.state_dirty = true;
+        }
+        EXO_KVM_INTERRUPT => {
+            let vector = irq_vector_arg(arg).ok_or(KvmError::Invalid)?;
+            if vcpu.pending_irq.is_some() {
+                return Err(KvmError::Busy);
+            }
+            vcpu.pending_irq = Some(vector);
+        }
+        EXO_KVM_SET_CPUID => {
+            let header: KvmCpuid = read_user_typed(arg).map_err(|_| KvmError::Fault)?;
+            let count = header.entry_count().ok_or(KvmError::Invalid)?;
+            let mut entries = Vec::new();
+            entries
+                .try_reserve_exact(count)
+                .map_err(|_| KvmError::NoMemory)?;
+            entries.resize(count, KvmCpuidEntry::default());
+            copy_from_user(
+                entries.as_mut_ptr() as *mut u8,
+                (arg + core::mem::size_of::<KvmCpuid>() as u64) as *const u8,
+                count * core::mem::size_of::<KvmCpuidEntry>(),
+            )
+            .map_err(|_| KvmError::Fault)?;
+            vcpu.cpuid = entries;
+        }
+        _ => return Err(KvmError::Invalid),
+    }
+    Ok(IoctlOutcome::Value(0))
+}
+
+impl Vcpu {
+    fn regs(&self) -> KvmRegs {
+        let r = &self.gprs.regs;
+        KvmRegs {
+            rax: r[RAX],
+            rbx: r[RBX],
+            rcx: r[RCX],
+            rdx: r[RDX],
+            rsi: r[6],
+            rdi: r[7],
+            rsp: r[RSP],
+            rbp: r[5],
+            r8: r[8],
+            r9: r[9],
+            r10: r[10],
+            r11: r[11],
+            r12: r[12],
+            r13: r[13],
+            r14: r[14],
+            r15: r[15],
+            rip: self.state.rip,
+            rflags: self.state.rflags,
+        }
+    }
+
+    fn set_regs(&mut self, regs: &KvmRegs) {
+        self.gprs.regs = [
+            regs.rax, regs.rcx, regs.rdx, regs.rbx, regs.rsp, regs.rbp, regs.rsi, regs.rdi,
+            regs.r8, regs.r9, regs.r10, regs.r11, regs.r12, regs.r13, regs.r14, regs.r15,
+        ];
+        self.state.rip = regs.rip;
+        // Bit 1 de RFLAGS toujours à 1.
+        self.state.rflags = regs.rflags | 0x2;
+        self.state_dirty = true;
+    }
+}
+
+// ── EXO_KVM_RUN ──────────────────────────────────────────────────────────────
+
+fn run(vm: &Vm, vcpu: &mut Vcpu, arg: u64) -> Result<(), KvmError> {
+    let mut run: KvmRun = read_user_typed(arg).map_err(|_| KvmError::Fault)?;
+
+    // Résultat de la lecture (IN ou MMIO) qui a provoqué la sortie précédente ;
+    // RIP est déjà avancé.
+    match vcpu.pending.take() {
+        Some(Pending::IoIn { size }) => {
+            let rax = &mut vcpu.gprs.regs[RAX];
+            *rax = match size {
+                1 => (*rax & !0xFF) | (run.io.data & 0xFF),
+                2 => (*rax & !0xFFFF) | (run.io.data & 0xFFFF),
+                _ => run.io.data & 0xFFFF_FFFF,
+            };
+        }
+        Some(Pending::MmioRead(insn)) => {
+            mmio::complete_read(&insn, u64::from_le_bytes(run.mmio.data), &mut vcpu.gprs)
+        }
+        None => {}
+    }
+
+    {
+        // Sous le verrou mémoire : exclut un changement de slot concurrent.
+        let _memory = vm.memory.lock();
+        vm.running.fetch_add(1, Ordering::AcqRel);
+    }
+    let result = {
+        let _preempt = PreemptGuard::new();
+        run_loop(vm, vcpu, &mut run)
+    };
+    vm.running.fetch_sub(1, Ordering::AcqRel);
+    result?;
+    write_user_typed(arg, run).map_err(|_| KvmError::Fault)
+}
+
+/// Signal en attente ou reschedule demandé : rendre la main au VMM.
+fn should_yield() -> bool {
+    // SAFETY: lecture du slot TCB per-CPU, validée par `try_read_current_tcb`.
+    let Some(tcb) = (unsafe { crate::arch::x86_64::smp::percpu::try_read_current_tcb() }) else {
+        return false;
+    };
+    if tcb == 0 {
+        return false;
+    }
+    // SAFETY: le TCB courant reste vivant tant que ce thread s'exécute.
+    let tcb = unsafe { &*(tcb as *const ThreadControlBlock) };
+    tcb.has_signal_pending() || tcb.need_resched()
+}
+
+fn run_loop(vm: &Vm, vcpu: &mut Vcpu, run: &mut KvmRun) -> Result<(), KvmError> {
+    enable_on_current_cpu(vm.backend)?;
+    let eptp = vm.memory.lock().table.root().as_u64();
+
+    let Vcpu {
+        id,
+        hw,
+        gprs,
+        state,
+        state_dirty,
+        pending_irq,
+        pending,
+        cpuid: cpuid_table,
+    } = vcpu;
+    hw.load(state, *state_dirty, eptp)?;
+    *state_dirty = false;
+    run.hardware_reason = 0;
+
+    let reason = loop {
+        let window_requested = run.request_interrupt_window != 0;
+        match *pending_irq {
+            Some(vector) if hw.interruptible() => {
+                hw.inject_irq(vector);
+                *pending_irq = None;
+                hw.set_irq_window(false);
+            }
+            Some(_) => hw.set_irq_window(true),
+            None if window_requested && hw.interruptible() => {
+                break EXO_KVM_EXIT_IRQ_WINDOW_OPEN;
+            }
+            None => hw.set_irq_window(window_requested),
+        }
+
+        match hw.enter(gprs) {
+            Exit::Host | Exit::InterruptWindow => {
+                if should_yield() {
+                    break EXO_KVM_EXIT_INTR;
+                }
+            }
+            Exit::Cpuid { len } => {
+                emulate_cpuid(*id, cpuid_table, gprs);
+                skip(hw.as_mut(), len);
+            }
+            Exit::Rdmsr { len } => {
+                let msr = gprs.regs[RCX] as u32;
+                let value = if msr == MSR_IA32_APIC_BASE {
+                    Some(state.sregs.apic_base)
+                } else {
+                    hw.read_msr(msr).or_else(|| common_msr_read(msr))
+                };
+                match value {
+                    Some(value) => {
+                        gprs.regs[RAX] = value & 0xFFFF_FFFF;
+                        gprs.regs[RDX] = value >> 32;
+                        skip(hw.as_mut(), len);
+                    }
+                    None => hw.inject_exception(VECTOR_GP, Some(0)),
+                }
+            }
+            Exit::Wrmsr { len } => {
+                let msr = gprs.regs[RCX] as u32;
+                let value = (gprs.regs[RDX] << 32) | (gprs.regs[RAX] & 0xFFFF_FFFF);
+                let accepted = if msr == MSR_IA32_APIC_BASE {
+                    state.sregs.apic_base = value;
+                    true
+                } else {
+                    hw.write_msr(msr, value) || common_msr_write(msr)
+                };
+                if accepted {
+                    skip(hw.as_mut(), len);
+                } else {
+                    hw.inject_exception(VECTOR_GP, Some(0));
+                }
+            }
+            Exit::Hlt { len } => {
+                skip(hw.as_mut(), len);
+                // Une IRQ déjà en attente réveille l'invité sans aller-retour.
+                if pending_irq.is_none() || hw.rflags() & RFLAGS_IF == 0 {
+                    break EXO_KVM_EXIT_HLT;
+                }
+            }
+            Exit::Io {
+                port,
+                size,
+                write,
+                string,
+                len,
+            } => {
+                if string {
+                    run.hardware_reason = EXO_KVM_INTERNAL_EMULATION;
+                    break EXO_KVM_EXIT_INTERNAL_ERROR;
+                }
+                let mask = (1u64 << (size as u32 * 8)) - 1;
+                run.io = KvmRunIo {
+                    direction: if write {
+                        EXO_KVM_EXIT_IO_OUT
+                    } else {
+                        EXO_KVM_EXIT_IO_IN
+                    },
+                    size,
+                    port,
+                    padding: 0,
+                    data: if write { gprs.regs[RAX] & mask } else { 0 },
+                };
+                skip(hw.as_mut(), len);
+                if !write {
+                    *pending = Some(Pending::IoIn { size });
+                }
+                break EXO_KVM_EXIT_IO;
+            }
+            Exit::GuestPageFault { gpa } => {
+                let memory = vm.memory.lock();
+                if memory.hpa(gpa).is_some() {
+                    // Page présente dans un slot : faute EPT/NPT inattendue.
+                    run.hardware_reason = EXO_KVM_INTERNAL_EMULATION;
+                    break EXO_KVM_EXIT_INTERNAL_ERROR;
+                }
+                let Some(insn) = fetch_mmio_insn(&memory, hw.as_ref(), gprs) else {
+                    run.hardware_reason = EXO_KVM_INTERNAL_EMULATION;
+                    break EXO_KVM_EXIT_INTERNAL_ERROR;
+                };
+                drop(memory);
+                run.mmio = KvmRunMmio {
+                    phys_addr: gpa,
+                    len: insn.size as u32,
+                    ..Default::default()
+                };
+                match insn.access {
+                    Access::Write(value) => {
+                        run.mmio.is_write = 1;
+                        run.mmio.data = value.to_le_bytes();
+                    }
+                    Access::Read { .. } => *pending = Some(Pending::MmioRead(insn)),
+                }
+                skip(hw.as_mut(), insn.len);
+                break EXO_KVM_EXIT_MMIO;
+            }
+            Exit::Shutdown => break EXO_KVM_EXIT_SHUTDOWN,
+            Exit::InvalidOpcode => hw.inject_exception(VECTOR_UD, None),
+            Exit::Skip { len } => skip(hw.as_mut(), len),
+            Exit::EntryFailed(reason) => {
+                run.hardware_reason = reason;
+                break EXO_KVM_EXIT_FAIL_ENTRY;
+            }
+            Exit::Unhandled(reason) => {
+                run.hardware_reason = EXO_KVM_INTERNAL_UNHANDLED_EXIT | (reason << 32);
+                break EXO_KVM_EXIT_INTERNAL_ERROR;
+            }
+        }
+    };
+
+    run.exit_reason = reason;
+    run.if_flag = (hw.rflags() & RFLAGS_IF != 0) as u8;
+    run.ready_for_interrupt_injection = (pending_irq.is_none() && hw.interruptible()) as u8;
+    hw.put(state);
+    Ok(())
+}
+
+fn skip(hw: &mut dyn VcpuHw, len: u8) {
+    let rip = hw.rip();
+    hw.set_rip(rip.wrapping_add(len as u64));
+}
+
+/// Lit et décode l'instruction à RIP après une faute EPT/NPT hors RAM.
+fn fetch_mmio_insn(memory: &Memory, hw: &dyn VcpuHw, gprs: &GuestGprs) -> Option<MmioInsn> {
+    let paging = hw.paging();
+    let long = paging.efer & EFER_LMA != 0 && paging.cs_l;
+    let mut la = hw.rip();
+    if !long {
+        la = paging.cs_base.wrapping_add(la) & 0xFFFF_FFFF;
+    }
+    let mut bytes = [0u8; 15];
+    let mut fetched = 0;
+    while fetched < bytes.len() {
+        let Some(gpa) = memory.translate(&paging, la) else {
+            break;
+        };
+        let chunk = ((PAGE_SIZE - (la & PAGE_MASK)) as usize).min(bytes.len() - fetched);
+        if !memory.read(gpa, &mut bytes[fetched..fetched + chunk]) {
+            break;
+        }
+        fetched += chunk;
+        la = la.wrapping_add(chunk as u64);
+    }
+    let mode = CpuMode {
+        long,
+        default_32: paging.cr0 & CR0_PE != 0 && paging.cs_db,
+    };
+    mmio::decode(&bytes[..fetched], mode, gprs)
+}
+
+// ── CPUID ────────────────────────────────────────────────────────────────────
+
+fn emulate_cpuid(vcpu_id: u32, table: &[KvmCpuidEntry], gprs: &mut GuestGprs) {
+    let leaf = gprs.regs[RAX] as u32;
+    let subleaf = gprs.regs[RCX] as u32;
+    let (eax, ebx, ecx, edx) = if table.is_empty() {
+        default_cpuid(vcpu_id, leaf, subleaf)
+    } else {
+        table
+            .iter()
+            .find(|e| {
+                e.function == leaf
+                    && (e.flags & EXO_KVM_CPUID_FLAG_SIGNIFICANT_INDEX == 0 || e.index == subleaf)
+            })
+            .map_or((0, 0, 0, 0), |e| (e.eax, e.ebx, e.ecx, e.edx))
+    };
+    gprs.regs[RAX] = eax as u64;
+    gprs.regs[RBX] = ebx as u64;
+    gprs.regs[RCX] = ecx as u64;
+    gprs.regs[RDX] = edx as u64;
+}
+
+/// CPUID hôte sans ce que l'invité ne peut pas utiliser ici : VMX/SVM
+/// imbriqués, XSAVE/AVX (seul FXSAVE est basculé), x2APIC et TSC-deadline
+/// (APIC émulé par le VMM), topologie SMT hôte.
+fn default_cpuid(vcpu_id: u32, leaf: u32, subleaf: u32) -> (u32, u32, u32, u32) {
+    const LEAF1_ECX_HIDDEN: u32 = (1 << 3) // MONITOR
+        | (1 << 4) // DS-CPL
+        | (1 << 5) // VMX
+        | (1 << 6) // SMX
+        | (1 << 7) // EIST
+        | (1 << 8) // TM2
+        | (1 << 15) // PDCM
+        | (1 << 17) // PCID
+        | (1 << 21) // x2APIC
+        | (1 << 24) // TSC-deadline
+        | (1 << 26) // XSAVE
+        | (1 << 27) // OSXSAVE
+        | (1 << 28); // AVX
+    const LEAF1_EDX_HIDDEN: u32 = (1 << 21) // DS
+        | (1 << 22) // ACPI
+        | (1 << 28) // HTT
+        | (1 << 29) // TM
+        | (1 << 31); // PBE
+    const HYPERVISOR: u32 = 1 << 31;
+    /// FSGSBASE, BMI1, SMEP, BMI2, ERMS, ADX, SMAP, CLFLUSHOPT, CLWB, SHA.
+    const LEAF7_EBX_KEPT: u32 = (1 << 0)
+        | (1 << 3)
+        | (1 << 7)
+        | (1 << 8)
+        | (1 << 9)
+        | (1 << 19)
+        | (1 << 20)
+        | (1 << 23)
+        | (1 << 24)
+        | (1 << 29);
+    /// SVM, ExtApicSpace, SKINIT, WDT, TopologyExtensions, PerfCtrExtCore.
+    const EXT1_ECX_HIDDEN: u32 =
+        (1 << 2) | (1 << 3) | (1 << 12) | (1 << 13) | (1 << 22) | (1 << 23);
+    const EXT1_EDX_RDTSCP: u32 = 1 << 27;
+
+    let max_basic = cpuid(0, 0).0.min(7);
+    let max_ext = cpuid(0x8000_0000, 0).0.min(0x8000_0008);
+    match leaf {
+        0 => {
+            let (_, ebx, ecx, edx) = cpuid(0, 0);
+            (max_basic, ebx, ecx, edx)
+        }
+        1 => {
+            let (eax, ebx, ecx, edx) = cpuid(1, 0);
+            let ebx = (ebx & 0xFFFF) | (1 << 16) | (vcpu_id << 24);
+            (
+                eax,
+                ebx,
+                (ecx & !LEAF1_ECX_HIDDEN) | HYPERVISOR,
+                edx & !LEAF1_EDX_HIDDEN,
+            )
+        }
+        2 => cpuid(2, 0),
+        4 if leaf <= max_basic => {
+            let (eax, ebx, ecx, edx) = cpuid(4, subleaf);
+            // Un cœur, un thread par cache.
+            (eax & 0x3FFF, ebx, ecx, edx)
+        }
+        7 if leaf <= max_basic && subleaf == 0 => {
+            let (_, ebx, _, _) = cpuid(7, 0);
+            (0, ebx & LEAF7_EBX_KEPT, 0, 0)
+        }
+        0x4000_0000 => (
+            0x4000_0001,
+            u32::from_le_bytes(*b"ExoK"),
+            u32::from_le_bytes(*b"VMEx"),
+            u32::from_le_bytes(*b"oKVM"),
+        ),
+        0x8000_0000 => {
+            let (_, ebx, ecx, edx) = cpuid(0x8000_0000, 0);
+            (max_ext, ebx, ecx, edx)
+        }
+        0x8000_0001 => {
+            let (eax, ebx, ecx, edx) = cpuid(0x8000_0001, 0);
+            (eax, ebx, ecx & !EXT1_ECX_HIDDEN, edx & !EXT1_EDX_RDTSCP)
+        }
+        0x8000_0002..=0x8000_0006 if leaf <= max_ext => cpuid(leaf, 0),
+        0x8000_0007 if leaf <= max_ext => {
+            // TSC invariant seulement.
+            let (_, _, _, edx) = cpuid(leaf, 0);
+            (0, 0, 0, edx & (1 << 8))
+        }
+        0x8000_0008 if leaf <= max_ext => {
+            // Largeurs d'adresses physiques et linéaires seulement.
+            let (eax, _, _, _) = cpuid(leaf, 0);
+            (eax & 0xFFFF, 0, 0, 0)
+        }
+        _ => (0, 0, 0, 0),
+    }
+}
+
+// ── MSR sans état hors backend ───────────────────────────────────────────────
+
+const MSR_IA32_TSC: u32 = 0x10;
+/// Rangé dans `KvmSregs::apic_base` ; l'APIC lui-même est émulé par le VMM.
+const MSR_IA32_APIC_BASE: u32 = 0x1B;
+const MSR_IA32_FEATURE_CONTROL: u32 = 0x3A;
+const MSR_IA32_BIOS_SIGN_ID: u32 = 0x8B;
+const MSR_IA32_MTRRCAP: u32 = 0xFE;
+const MSR_IA32_MCG_CAP: u32 = 0x179;
+const MSR_IA32_MCG_STATUS: u32 = 0x17A;
+const MSR_IA32_MISC_ENABLE: u32 = 0x1A0;
+const MSR_IA32_MTRR_DEF_TYPE: u32 = 0x2FF;
+const MSR_IA32_MC0_CTL: u32 = 0x400;
+const MSR_IA32_MC_LAST: u32 = 0x47F;
+const MSR_AMD_HWCR: u32 = 0xC001_0015;
+
+/// MSR lus avec une valeur fixe : pas de MTRR ni de machine-check, VMX
+/// verrouillé hors service, « fast strings » seul dans MISC_ENABLE.
+fn common_msr_read(msr: u32) -> Option<u64> {
+    match msr {
+        MSR_IA32_TSC => Some(crate::arch::x86_64::cpu::tsc::read_tsc()),
+        MSR_IA32_FEATURE_CONTROL => Some(1),
+        MSR_IA32_MISC_ENABLE => Some(1),
+        MSR_IA32_BIOS_SIGN_ID
+        | MSR_IA32_MTRRCAP
+        | MSR_IA32_MTRR_DEF_TYPE
+        | MSR_IA32_MCG_CAP
+        | MSR_IA32_MCG_STATUS
+        | MSR_AMD_HWCR
+        | MSR_IA32_MC0_CTL..=MSR_IA32_MC_LAST => Some(0),
+        _ => None,
+    }
+}
+
+/// Écritures ignorées sur les MSR de `common_msr_read`.
+fn common_msr_write(msr: u32) -> bool {
+    msr != MSR_IA32_FEATURE_CONTROL && common_msr_read(msr).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: u64 = 1 << 20;
+
+    fn region(slot: u32, gpa: u64, size: u64) -> KvmMemoryRegion {
+        KvmMemoryRegion {
+            slot,
+            flags: 0,
+            guest_phys_addr: gpa,
+            memory_size: size,
+            userspace_addr: 0x7f00_0000_0000,
+        }
+    }
+
+    fn range(slot: u32, gpa: u64, size: u64) -> SlotRange {
+        SlotRange { slot, gpa, size }
+    }
+
+    #[test]
+    fn region_arguments_are_validated() {
+        assert_eq!(check_region(&region(0, 0, 2 * MIB)), Ok(range(0, 0, 2 * MIB)));
+        assert_eq!(check_region(&region(3, MIB, 0)), Ok(range(3, MIB, 0)));
+
+        let invalid = [
+            region(MAX_MEMSLOTS as u32, 0, MIB),
+            KvmMemoryRegion {
+                flags: 1,
+                ..region(0, 0, MIB)
+            },
+            region(0, 0x800, MIB),
+            region(0, 0, MIB + 1),
+            KvmMemoryRegion {
+                userspace_addr: 0x7f00_0000_0010,
+                ..region(0, 0, MIB)
+            },
+            region(0, !PAGE_MASK, 2 * PAGE_SIZE),
+            KvmMemoryRegion {
+                userspace_addr: !PAGE_MASK,
+                ..region(0, 0, 2 * PAGE_SIZE)
+            },
+        ];
+        for region in invalid {
+            assert_eq!(check_region(&region), Err(KvmError::Invalid), "{region:?}");
+        }
+    }
+
+    #[test]
+    fn slots_may_not_overlap_another_slot() {
+        let slots = [range(0, 0, 2 * MIB), range(1, 4 * MIB, MIB)];
+        let place = |new: SlotRange| place_slot(slots.iter().copied(), &new);
+
+        assert_eq!(place(range(2, 2 * MIB, 2 * MIB)), Ok(None));
+        assert_eq!(place(range(2, MIB, 2 * MIB)), Err(KvmError::Exists));
+        assert_eq!(place(range(2, 4 * MIB + MIB - PAGE_SIZE, MIB)), Err(KvmError::Exists));
+        assert_eq!(place(range(2, 0, 8 * MIB)), Err(KvmError::Exists));
+        // Slot vide : suppression, jamais de chevauchement.
+        assert_eq!(place(range(2, 0, 0)), Ok(None));
+    }
+
+    #[test]
+    fn a_slot_replaces_itself_in_place() {
+        let slots = [range(0, 0, 2 * MIB), range(1, 4 * MIB, MIB)];
+        let place = |new: SlotRange| place_slot(slots.iter().copied(), &new);
+
+        // Agrandi sur sa propre plage, déplacé, ou supprimé.
+        assert_eq!(place(range(1, 3 * MIB, 2 * MIB)), Ok(Some(1)));
+        assert_eq!(place(range(1, 8 * MIB, MIB)), Ok(Some(1)));
+        assert_eq!(place(range(0, 0, 0)), Ok(Some(0)));
+        // Mais pas sur un autre slot.
+        assert_eq!(place(range(1, MIB, 4 * MIB)), Err(KvmError::Exists));
+    }
+
+    #[test]
+    fn slot_pages_cover_the_range() {
+        let slot = range(0, 2 * MIB, 3 * PAGE_SIZE);
+        let mut pages = slot.page_gpas();
+        assert_eq!(pages.next(), Some(2 * MIB));
+        assert_eq!(pages.next(), Some(2 * MIB + PAGE_SIZE));
+        assert_eq!(pages.next(), Some(2 * MIB + 2 * PAGE_SIZE));
+        assert_eq!(pages.next(), None);
+        assert!(slot.contains(2 * MIB + 3 * PAGE_SIZE - 1));
+        assert!(!slot.contains(2 * MIB + 3 * PAGE_SIZE));
+        assert!(!slot.contains(2 * MIB - 1));
+    }
+
+    fn entry(function: u32, index: u32, flags: u32, eax: u32) -> KvmCpuidEntry {
+        KvmCpuidEntry {
+            function,
+            index,
+            flags,
+            eax,
+            ebx: eax + 1,
+            ecx: eax + 2,
+            edx: eax + 3,
+            ..Default::default()
+        }
+    }
+
+    fn guest_cpuid(table: &[KvmCpuidEntry], leaf: u32, subleaf: u32) -> [u64; 4] {
+        let mut gprs = GuestGprs::default();
+        gprs.regs[RAX] = leaf as u64;
+        gprs.regs[RCX] = subleaf as u64;
+        gprs.regs[RBX] = 0xDEAD;
+        gprs.regs[RDX] = 0xBEEF;
+        emulate_cpuid(0, table, &mut gprs);
+        let r = &gprs.regs;
+        [r[RAX], r[RBX], r[RCX], r[RDX]]
+    }
+
+    #[test]
+    fn cpuid_table_matches_leaf_and_significant_index() {
+        let table = [
+            entry(0, 0, 0, 0x10),
+            entry(4, 0, EXO_KVM_CPUID_FLAG_SIGNIFICANT_INDEX, 0x40),
+            entry(4, 1, EXO_KVM_CPUID_FLAG_SIGNIFICANT_INDEX, 0x41),
+            entry(7, 5, 0, 0x70),
+        ];
+        assert_eq!(guest_cpuid(&table, 0, 0), [0x10, 0x11, 0x12, 0x13]);
+        assert_eq!(guest_cpuid(&table, 4, 1), [0x41, 0x42, 0x43, 0x44]);
+        assert_eq!(guest_cpuid(&table, 4, 0), [0x40, 0x41, 0x42, 0x43]);
+        // Sous-feuille absente d'une feuille indexée : zéros.
+        assert_eq!(guest_cpuid(&table, 4, 2), [0; 4]);
+        // Index ignoré sans le drapeau.
+        assert_eq!(guest_cpuid(&table, 7, 0), [0x70, 0x71, 0x72, 0x73]);
+        // Feuille absente : zéros, registres hauts de 64 bits effacés.
+        assert_eq!(guest_cpuid(&table, 0x4000_0000, 0), [0; 4]);
+    }
+
+    #[test]
+    fn default_cpuid_hides_what_the_guest_cannot_use() {
+        let [_, ebx, ecx, _] = guest_cpuid(&[], 1, 0);
+        assert_eq!(ecx & (1 << 5), 0, "VMX");
+        assert_eq!(ecx & (1 << 26), 0, "XSAVE");
+        assert_eq!(ecx & (1 << 28), 0, "AVX");
+        assert_ne!(ecx & (1 << 31), 0, "hyperviseur");
+        // Un seul processeur logique, APIC ID = numéro de vCPU.
+        assert_eq!((ebx >> 16) & 0xFF, 1);
+
+        let mut gprs = GuestGprs::default();
+        gprs.regs[RAX] = 1;
+        emulate_cpuid(5, &[], &mut gprs);
+        assert_eq!(gprs.regs[RBX] >> 24, 5);
+
+        let [max, ..] = guest_cpuid(&[], 0, 0);
+        assert!(max <= 7);
+        let [max_ext, ..] = guest_cpuid(&[], 0x8000_0000, 0);
+        assert!(max_ext <= 0x8000_0008);
+        let [_, _, ext_ecx, ext_edx] = guest_cpuid(&[], 0x8000_0001, 0);
+        assert_eq!(ext_ecx & (1 << 2), 0, "SVM");
+        assert_eq!(ext_edx & (1 << 27), 0, "RDTSCP");
+
+        let [eax, ebx, ecx, edx] = guest_cpuid(&[], 0x4000_0000, 0);
+        assert_eq!(eax, 0x4000_0001);
+        let mut signature = [0u8; 12];
+        signature[..4].copy_from_slice(&(ebx as u32).to_le_bytes());
+        signature[4..8].copy_from_slice(&(ecx as u32).to_le_bytes());
+        signature[8..].copy_from_slice(&(edx as u32).to_le_bytes());
+        assert_eq!(&signature, b"ExoKVMExoKVM");
+        assert_eq!(guest_cpuid(&[], 0x4000_0001, 0), [0; 4]);
+    }
+
+    #[test]
+    fn fixed_msrs_read_their_table_values() {
+        assert_eq!(common_msr_read(MSR_IA32_FEATURE_CONTROL), Some(1));
+        assert_eq!(common_msr_read(MSR_IA32_MISC_ENABLE), Some(1));
+        for msr in [
+            MSR_IA32_BIOS_SIGN_ID,
+            MSR_IA32_MTRRCAP,
+            MSR_IA32_MTRR_DEF_TYPE,
+            MSR_IA32_MCG_CAP,
+            MSR_IA32_MCG_STATUS,
+            MSR_AMD_HWCR,
+            MSR_IA32_MC0_CTL,
+            MSR_IA32_MC_LAST,
+        ] {
+            assert_eq!(common_msr_read(msr), Some(0), "{msr:#x}");
+        }
+        assert!(common_msr_read(MSR_IA32_TSC).is_some());
+        // APIC_BASE vit dans les sregs ; le reste relève du backend ou #GP.
+        for msr in [MSR_IA32_APIC_BASE, MSR_IA32_MC_LAST + 1, 0xC000_0080, 0x48] {
+            assert_eq!(common_msr_read(msr), None, "{msr:#x}");
+        }
+    }
+
+    #[test]
+    fn fixed_msr_writes_are_ignored_except_feature_control() {
+        assert!(common_msr_write(MSR_IA32_MISC_ENABLE));
+        assert!(common_msr_write(MSR_IA32_BIOS_SIGN_ID));
+        assert!(common_msr_write(MSR_IA32_MC0_CTL + 4));
+        assert!(common_msr_write(MSR_IA32_TSC));
+        // Verrouillé : l'écrire est une #GP, comme sur le matériel.
+        assert!(!common_msr_write(MSR_IA32_FEATURE_CONTROL));
+        assert!(!common_msr_write(MSR_IA32_APIC_BASE));
+        assert!(!common_msr_write(0x48));
+    }
+}
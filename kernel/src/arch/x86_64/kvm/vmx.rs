@@ -0,0 +1,1441 @@
+//! # kvm/vmx.rs — Backend Intel VT-x
+//!
+//! Un VMCS par vCPU, chargé (VMPTRLD) au début de `EXO_KVM_RUN` et relâché
+//! (VMCLEAR) à la fin : le vCPU peut reprendre sur un autre CPU. L'état hôte
+//! est réécrit à chaque chargement pour la même raison.
+//!
+//! L'invité tourne en « unrestricted guest » sur EPT : mode réel, protégé et
+//! long sans émulation. Seuls les bits de CR0/CR4 imposés par VMX (NE, VMXE)
+//! et OSXSAVE sont interceptés. Les MSR SYSCALL et KERNEL_GS_BASE, absents
+//! du VMCS, sont échangés autour de l'entrée.
+
+use crate::arch::x86_64::cpu::msr::{
+    read_msr, write_msr, MSR_CSTAR, MSR_FS_BASE, MSR_GS_BASE, MSR_IA32_EFER, MSR_IA32_PAT,
+    MSR_KERNEL_GS_BASE, MSR_LSTAR, MSR_SFMASK, MSR_STAR,
+};
+use crate::arch::x86_64::{irq_restore, irq_save, read_cr2, read_cr3, read_cr4, write_cr4};
+use crate::memory::core::phys_to_virt;
+use crate::memory::{alloc_page, free_page, AllocFlags, Frame};
+
+use super::abi::KvmSegment;
+use super::{cpuid, ArchState, Exit, FpuSwap, FxArea, GuestGprs, KvmError, Paging, VcpuHw, RSP};
+
+// ── MSR de capacité ──────────────────────────────────────────────────────────
+
+const IA32_FEATURE_CONTROL: u32 = 0x3A;
+const FEATURE_CONTROL_LOCKED: u64 = 1 << 0;
+const FEATURE_CONTROL_VMXON_OUTSIDE_SMX: u64 = 1 << 2;
+const IA32_VMX_BASIC: u32 = 0x480;
+const IA32_VMX_PINBASED_CTLS: u32 = 0x481;
+const IA32_VMX_PROCBASED_CTLS: u32 = 0x482;
+const IA32_VMX_EXIT_CTLS: u32 = 0x483;
+const IA32_VMX_ENTRY_CTLS: u32 = 0x484;
+const IA32_VMX_CR0_FIXED0: u32 = 0x486;
+const IA32_VMX_CR0_FIXED1: u32 = 0x487;
+const IA32_VMX_CR4_FIXED0: u32 = 0x488;
+const IA32_VMX_CR4_FIXED1: u32 = 0x489;
+const IA32_VMX_PROCBASED_CTLS2: u32 = 0x48B;
+const IA32_VMX_EPT_VPID_CAP: u32 = 0x48C;
+const IA32_VMX_TRUE_PINBASED_CTLS: u32 = 0x48D;
+const IA32_VMX_TRUE_PROCBASED_CTLS: u32 = 0x48E;
+const IA32_VMX_TRUE_EXIT_CTLS: u32 = 0x48F;
+const IA32_VMX_TRUE_ENTRY_CTLS: u32 = 0x490;
+const VMX_BASIC_TRUE_CTLS: u64 = 1 << 55;
+
+const EPT_CAP_WALK_4: u64 = 1 << 6;
+const EPT_CAP_WB: u64 = 1 << 14;
+const EPT_CAP_INVEPT: u64 = 1 << 20;
+const EPT_CAP_INVEPT_SINGLE: u64 = 1 << 25;
+const EPT_CAP_INVEPT_ALL: u64 = 1 << 26;
+
+const MSR_SYSENTER_CS: u32 = 0x174;
+const MSR_SYSENTER_ESP: u32 = 0x175;
+const MSR_SYSENTER_EIP: u32 = 0x176;
+
+// ── Contrôles d'exécution ────────────────────────────────────────────────────
+
+const PIN_EXTERNAL_INTERRUPT: u32 = 1 << 0;
+const PIN_NMI: u32 = 1 << 3;
+
+const PROC_INTERRUPT_WINDOW: u32 = 1 << 2;
+const PROC_HLT: u32 = 1 << 7;
+const PROC_MWAIT: u32 = 1 << 10;
+const PROC_RDPMC: u32 = 1 << 11;
+const PROC_UNCONDITIONAL_IO: u32 = 1 << 24;
+const PROC_MONITOR: u32 = 1 << 29;
+const PROC_SECONDARY: u32 = 1 << 31;
+
+const SECONDARY_EPT: u32 = 1 << 1;
+const SECONDARY_UNRESTRICTED_GUEST: u32 = 1 << 7;
+
+const EXIT_HOST_ADDR_SPACE_SIZE: u32 = 1 << 9;
+const EXIT_SAVE_PAT: u32 = 1 << 18;
+const EXIT_LOAD_PAT: u32 = 1 << 19;
+const EXIT_SAVE_EFER: u32 = 1 << 20;
+const EXIT_LOAD_EFER: u32 = 1 << 21;
+
+const ENTRY_LOAD_DEBUG: u32 = 1 << 2;
+const ENTRY_IA32E_MODE: u32 = 1 << 9;
+const ENTRY_LOAD_PAT: u32 = 1 << 14;
+const ENTRY_LOAD_EFER: u32 = 1 << 15;
+
+// ── Champs du VMCS ───────────────────────────────────────────────────────────
+
+const GUEST_ES_SELECTOR: u64 = 0x0800;
+const HOST_ES_SELECTOR: u64 = 0x0C00;
+const HOST_CS_SELECTOR: u64 = 0x0C02;
+const HOST_SS_SELECTOR: u64 = 0x0C04;
+const HOST_DS_SELECTOR: u64 = 0x0C06;
+const HOST_FS_SELECTOR: u64 = 0x0C08;
+const HOST_GS_SELECTOR: u64 = 0x0C0A;
+const HOST_TR_SELECTOR: u64 = 0x0C0C;
+const EPT_POINTER: u64 = 0x201A;
+const GUEST_PHYSICAL_ADDRESS: u64 = 0x2400;
+const VMCS_LINK_POINTER: u64 = 0x2800;
+const GUEST_IA32_DEBUGCTL: u64 = 0x2802;
+const GUEST_IA32_PAT: u64 = 0x2804;
+const GUEST_IA32_EFER: u64 = 0x2806;
+const HOST_IA32_PAT: u64 = 0x2C00;
+const HOST_IA32_EFER: u64 = 0x2C02;
+const PIN_BASED_CONTROLS: u64 = 0x4000;
+const PROC_BASED_CONTROLS: u64 = 0x4002;
+const EXCEPTION_BITMAP: u64 = 0x4004;
+const CR3_TARGET_COUNT: u64 = 0x400A;
+const EXIT_CONTROLS: u64 = 0x400C;
+const EXIT_MSR_STORE_COUNT: u64 = 0x400E;
+const EXIT_MSR_LOAD_COUNT: u64 = 0x4010;
+const ENTRY_CONTROLS: u64 = 0x4012;
+const ENTRY_MSR_LOAD_COUNT: u64 = 0x4014;
+const ENTRY_INTR_INFO: u64 = 0x4016;
+const ENTRY_EXCEPTION_ERROR_CODE: u64 = 0x4018;
+const ENTRY_INSTRUCTION_LEN: u64 = 0x401A;
+const SECONDARY_CONTROLS: u64 = 0x401E;
+const VM_INSTRUCTION_ERROR: u64 = 0x4400;
+const EXIT_REASON: u64 = 0x4402;
+const EXIT_INTR_INFO: u64 = 0x4404;
+const IDT_VECTORING_INFO: u64 = 0x4408;
+const IDT_VECTORING_ERROR_CODE: u64 = 0x440A;
+const EXIT_INSTRUCTION_LEN: u64 = 0x440C;
+const GUEST_ES_LIMIT: u64 = 0x4800;
+const GUEST_GDTR_LIMIT: u64 = 0x4810;
+const GUEST_IDTR_LIMIT: u64 = 0x4812;
+const GUEST_ES_AR: u64 = 0x4814;
+const GUEST_INTERRUPTIBILITY: u64 = 0x4824;
+const GUEST_ACTIVITY_STATE: u64 = 0x4826;
+const GUEST_SYSENTER_CS: u64 = 0x482A;
+const HOST_SYSENTER_CS: u64 = 0x4C00;
+const CR0_GUEST_HOST_MASK: u64 = 0x6000;
+const CR4_GUEST_HOST_MASK: u64 = 0x6002;
+const CR0_READ_SHADOW: u64 = 0x6004;
+const CR4_READ_SHADOW: u64 = 0x6006;
+const EXIT_QUALIFICATION: u64 = 0x6400;
+const GUEST_CR0: u64 = 0x6800;
+const GUEST_CR3: u64 = 0x6802;
+const GUEST_CR4: u64 = 0x6804;
+const GUEST_ES_BASE: u64 = 0x6806;
+const GUEST_CS_BASE: u64 = 0x6808;
+const GUEST_FS_BASE: u64 = 0x680E;
+const GUEST_GS_BASE: u64 = 0x6810;
+const GUEST_GDTR_BASE: u64 = 0x6816;
+const GUEST_IDTR_BASE: u64 = 0x6818;
+const GUEST_DR7: u64 = 0x681A;
+const GUEST_RSP: u64 = 0x681C;
+const GUEST_RIP: u64 = 0x681E;
+const GUEST_RFLAGS: u64 = 0x6820;
+const GUEST_PENDING_DBG_EXCEPTIONS: u64 = 0x6822;
+const GUEST_SYSENTER_ESP: u64 = 0x6824;
+const GUEST_SYSENTER_EIP: u64 = 0x6826;
+const HOST_CR0: u64 = 0x6C00;
+const HOST_CR3: u64 = 0x6C02;
+const HOST_CR4: u64 = 0x6C04;
+const HOST_FS_BASE: u64 = 0x6C06;
+const HOST_GS_BASE: u64 = 0x6C08;
+const HOST_TR_BASE: u64 = 0x6C0A;
+const HOST_GDTR_BASE: u64 = 0x6C0C;
+const HOST_IDTR_BASE: u64 = 0x6C0E;
+const HOST_SYSENTER_ESP: u64 = 0x6C10;
+const HOST_SYSENTER_EIP: u64 = 0x6C12;
+const HOST_RIP: u64 = 0x6C16;
+
+/// Ordre des segments dans les groupes de champs du VMCS (pas de 2).
+const SEGMENT_ES: u64 = 0;
+const SEGMENT_CS: u64 = 1;
+const SEGMENT_SS: u64 = 2;
+const SEGMENT_DS: u64 = 3;
+const SEGMENT_FS: u64 = 4;
+const SEGMENT_GS: u64 = 5;
+const SEGMENT_LDTR: u64 = 6;
+const SEGMENT_TR: u64 = 7;
+
+const AR_UNUSABLE: u64 = 1 << 16;
+
+const INTR_INFO_VALID: u64 = 1 << 31;
+const INTR_INFO_HAS_ERROR_CODE: u64 = 1 << 11;
+const INTR_INFO_NMI_UNBLOCKING: u64 = 1 << 12;
+const INTR_TYPE_MASK: u64 = 7 << 8;
+const INTR_TYPE_NMI: u64 = 2 << 8;
+const INTR_TYPE_HARD_EXCEPTION: u64 = 3 << 8;
+
+// ── Raisons de sortie ────────────────────────────────────────────────────────
+
+const EXIT_REASON_ENTRY_FAILURE: u64 = 1 << 31;
+const EXIT_EXCEPTION_NMI: u64 = 0;
+const EXIT_EXTERNAL_INTERRUPT: u64 = 1;
+const EXIT_TRIPLE_FAULT: u64 = 2;
+const EXIT_INTERRUPT_WINDOW: u64 = 7;
+const EXIT_CPUID: u64 = 10;
+const EXIT_HLT: u64 = 12;
+const EXIT_INVD: u64 = 13;
+const EXIT_RDPMC: u64 = 15;
+const EXIT_VMCALL: u64 = 18;
+const EXIT_VMXON: u64 = 27;
+const EXIT_CR_ACCESS: u64 = 28;
+const EXIT_IO: u64 = 30;
+const EXIT_RDMSR: u64 = 31;
+const EXIT_WRMSR: u64 = 32;
+const EXIT_MWAIT: u64 = 36;
+const EXIT_MONITOR: u64 = 39;
+const EXIT_EPT_VIOLATION: u64 = 48;
+const EXIT_INVEPT: u64 = 50;
+const EXIT_INVVPID: u64 = 53;
+const EXIT_XSETBV: u64 = 55;
+
+// ── Registres de contrôle ────────────────────────────────────────────────────
+
+const CR0_PE: u64 = 1 << 0;
+const CR0_PG: u64 = 1 << 31;
+const CR4_PAE: u64 = 1 << 5;
+const CR4_VMXE: u64 = 1 << 13;
+const CR4_SMXE: u64 = 1 << 14;
+const CR4_OSXSAVE: u64 = 1 << 18;
+const EFER_SCE: u64 = 1 << 0;
+const EFER_LME: u64 = 1 << 8;
+const EFER_LMA: u64 = 1 << 10;
+const EFER_NXE: u64 = 1 << 11;
+const RFLAGS_IF: u64 = 1 << 9;
+
+/// PAT au reset : WB, WT, UC-, UC répétés.
+const PAT_RESET: u64 = 0x0007_0406_0007_0406;
+/// EPTP : type mémoire WB, parcours à 4 niveaux.
+const EPTP_FLAGS: u64 = 6 | (3 << 3);
+
+const VECTOR_GP: u8 = 13;
+
+extern "C" {
+    /// Entre dans l'invité et rend 0 après une sortie, 1 si VMLAUNCH/VMRESUME
+    /// échoue. Les registres de `gprs` (sauf RSP) sont échangés.
+    fn exo_vmx_run(gprs: *mut GuestGprs, launched: u64) -> u64;
+    /// Point de retour des sorties VM (HOST_RIP), interne à `exo_vmx_run`.
+    fn exo_vmx_exit();
+}
+
+// HOST_RSP est écrit depuis l'asm : la pile de sortie est celle de l'appel,
+// le pointeur `gprs` au sommet.
+core::arch::global_asm!(
+    ".section .text",
+    ".global exo_vmx_run",
+    ".type   exo_vmx_run, @function",
+    "exo_vmx_run:",
+    "push  rbp",
+    "push  rbx",
+    "push  r12",
+    "push  r13",
+    "push  r14",
+    "push  r15",
+    "push  rdi",
+    "mov   rax, 0x6C14", // HOST_RSP
+    "vmwrite rax, rsp",
+    "jna   2f",
+    "test  rsi, rsi",
+    "mov   rax, qword ptr [rdi + 0]",
+    "mov   rcx, qword ptr [rdi + 8]",
+    "mov   rdx, qword ptr [rdi + 16]",
+    "mov   rbx, qword ptr [rdi + 24]",
+    "mov   rbp, qword ptr [rdi + 40]",
+    "mov   rsi, qword ptr [rdi + 48]",
+    "mov   r8,  qword ptr [rdi + 64]",
+    "mov   r9,  qword ptr [rdi + 72]",
+    "mov   r10, qword ptr [rdi + 80]",
+    "mov   r11, qword ptr [rdi + 88]",
+    "mov   r12, qword ptr [rdi + 96]",
+    "mov   r13, qword ptr [rdi + 104]",
+    "mov   r14, qword ptr [rdi + 112]",
+    "mov   r15, qword ptr [rdi + 120]",
+    "mov   rdi, qword ptr [rdi + 56]",
+    "jnz   1f",
+    "vmlaunch",
+    "jmp   2f",
+    "1:",
+    "vmresume",
+    // Échec d'entrée : l'invité n'a pas tourné.
+    "2:",
+    "pop   rdi",
+    "mov   eax, 1",
+    "jmp   3f",
+    ".global exo_vmx_exit",
+    "exo_vmx_exit:",
+    "push  rdi",
+    "mov   rdi, qword ptr [rsp + 8]",
+    "mov   qword ptr [rdi + 0], rax",
+    "mov   qword ptr [rdi + 8], rcx",
+    "mov   qword ptr [rdi + 16], rdx",
+    "mov   qword ptr [rdi + 24], rbx",
+    "mov   qword ptr [rdi + 40], rbp",
+    "mov   qword ptr [rdi + 48], rsi",
+    "mov   qword ptr [rdi + 64], r8",
+    "mov   qword ptr [rdi + 72], r9",
+    "mov   qword ptr [rdi + 80], r10",
+    "mov   qword ptr [rdi + 88], r11",
+    "mov   qword ptr [rdi + 96], r12",
+    "mov   qword ptr [rdi + 104], r13",
+    "mov   qword ptr [rdi + 112], r14",
+    "mov   qword ptr [rdi + 120], r15",
+    "pop   rax",
+    "mov   qword ptr [rdi + 56], rax",
+    "pop   rdi",
+    "xor   eax, eax",
+    "3:",
+    "pop   r15",
+    "pop   r14",
+    "pop   r13",
+    "pop   r12",
+    "pop   rbx",
+    "pop   rbp",
+    "ret",
+    ".size exo_vmx_run, . - exo_vmx_run",
+);
+
+// ── Instructions VMX ─────────────────────────────────────────────────────────
+
+/// # Safety
+/// VMX actif et un VMCS courant sur ce CPU.
+#[inline]
+unsafe fn vmread(field: u64) -> u64 {
+    let value: u64;
+    // SAFETY: délégué à l'appelant.
+    unsafe {
+        core::arch::asm!("vmread {0}, {1}", out(reg) value, in(reg) field, options(nostack));
+    }
+    value
+}
+
+/// # Safety
+/// Voir `vmread`.
+#[inline]
+unsafe fn vmwrite(field: u64, value: u64) {
+    // SAFETY: délégué à l'appelant.
+    unsafe {
+        core::arch::asm!("vmwrite {0}, {1}", in(reg) field, in(reg) value, options(nostack));
+    }
+}
+
+/// VMXON, VMPTRLD ou VMCLEAR sur la région physique `phys` ; `true` si réussi.
+macro_rules! vmx_region_op {
+    ($name:ident, $insn:literal) => {
+        /// # Safety
+        /// CR4.VMXE actif ; `phys` désigne une région VMX de ce module.
+        unsafe fn $name(phys: u64) -> bool {
+            let ok: u8;
+            // SAFETY: délégué à l'appelant ; l'opérande est lu en mémoire.
+            unsafe {
+                core::arch::asm!(
+                    concat!($insn, " qword ptr [{0}]"),
+                    "seta {1}",
+                    in(reg) &phys,
+                    out(reg_byte) ok,
+                    options(nostack),
+                );
+            }
+            ok != 0
+        }
+    };
+}
+
+vmx_region_op!(vmxon, "vmxon");
+vmx_region_op!(vmptrld, "vmptrld");
+vmx_region_op!(vmclear, "vmclear");
+
+/// # Safety
+/// VMX actif sur ce CPU.
+unsafe fn invept(eptp: u64) {
+    // SAFETY: MSR de capacité présent (VMX vérifié par `supported`).
+    let caps = unsafe { read_msr(IA32_VMX_EPT_VPID_CAP) };
+    let (kind, descriptor) = if caps & EPT_CAP_INVEPT_SINGLE != 0 {
+        (1u64, [eptp, 0u64])
+    } else {
+        (2, [0, 0])
+    };
+    // SAFETY: type annoncé par IA32_VMX_EPT_VPID_CAP ; descripteur de 128 bits.
+    unsafe {
+        core::arch::asm!("invept {0}, [{1}]", in(reg) kind, in(reg) &descriptor, options(nostack));
+    }
+}
+
+/// INVEPT de la table `root` (mono-contexte si disponible) sur le CPU
+/// courant, où VMX est actif.
+pub(super) fn flush_ept(root: u64) {
+    // SAFETY: VMX actif sur ce CPU, vérifié par l'appelant.
+    unsafe { invept(root | EPTP_FLAGS) };
+}
+
+#[inline]
+fn read_cr0() -> u64 {
+    let value: u64;
+    // SAFETY: lecture de CR0 en ring 0.
+    unsafe { core::arch::asm!("mov {0}, cr0", out(reg) value, options(nostack, nomem)) };
+    value
+}
+
+/// # Safety
+/// Aucune faute de page ne doit survenir avant l'entrée dans l'invité.
+#[inline]
+unsafe fn write_cr2(value: u64) {
+    // SAFETY: délégué à l'appelant.
+    unsafe { core::arch::asm!("mov cr2, {0}", in(reg) value, options(nostack, nomem)) };
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Default)]
+struct DescriptorPointer {
+    limit: u16,
+    base: u64,
+}
+
+// ── Disponibilité et activation ──────────────────────────────────────────────
+
+/// VMX, EPT 4 niveaux WB avec INVEPT, et « unrestricted guest ».
+pub(super) fn supported() -> bool {
+    let (_, _, ecx, _) = cpuid(1, 0);
+    // SAFETY: MSR VMX présents dès que CPUID.1:ECX.VMX = 1.
+    ecx & (1 << 5) != 0 && usable(|msr| unsafe { read_msr(msr) })
+}
+
+/// Capacités VMX lues par `read`, dans l'ordre : un MSR n'est lu que si
+/// les précédents annoncent son existence (PROCBASED_CTLS2 n'existe pas sans
+/// contrôles secondaires).
+fn usable(read: impl Fn(u32) -> u64) -> bool {
+    let control = read(IA32_FEATURE_CONTROL);
+    if control & FEATURE_CONTROL_LOCKED != 0 && control & FEATURE_CONTROL_VMXON_OUTSIDE_SMX == 0 {
+        return false;
+    }
+    if (read(IA32_VMX_PROCBASED_CTLS) >> 32) as u32 & PROC_SECONDARY == 0 {
+        return false;
+    }
+    let secondary = (read(IA32_VMX_PROCBASED_CTLS2) >> 32) as u32;
+    let required = SECONDARY_EPT | SECONDARY_UNRESTRICTED_GUEST;
+    if secondary & required != required {
+        return false;
+    }
+    let ept = read(IA32_VMX_EPT_VPID_CAP);
+    ept & EPT_CAP_WALK_4 != 0
+        && ept & EPT_CAP_WB != 0
+        && ept & EPT_CAP_INVEPT != 0
+        && ept & (EPT_CAP_INVEPT_SINGLE | EPT_CAP_INVEPT_ALL) != 0
+}
+
+fn vmcs_revision() -> u32 {
+    // SAFETY: IA32_VMX_BASIC présent si VMX supporté.
+    (unsafe { read_msr(IA32_VMX_BASIC) } & 0x7FFF_FFFF) as u32
+}
+
+/// VMXON sur le CPU courant. La région VMXON n'est jamais libérée : VMX
+/// reste actif sur ce CPU jusqu'à l'arrêt.
+pub(super) fn enable_cpu() -> Result<(), KvmError> {
+    // SAFETY: préemption coupée par l'appelant ; MSR VMX présents.
+    unsafe {
+        let control = read_msr(IA32_FEATURE_CONTROL);
+        if control & FEATURE_CONTROL_LOCKED == 0 {
+            write_msr(
+                IA32_FEATURE_CONTROL,
+                control | FEATURE_CONTROL_LOCKED | FEATURE_CONTROL_VMXON_OUTSIDE_SMX,
+            );
+        }
+        let cr0 = read_cr0();
+        if cr0 & read_msr(IA32_VMX_CR0_FIXED0) != read_msr(IA32_VMX_CR0_FIXED0)
+            || cr0 & !read_msr(IA32_VMX_CR0_FIXED1) != 0
+        {
+            return Err(KvmError::NotSupported);
+        }
+
+        let region = alloc_page(AllocFlags::ZEROED).map_err(|_| KvmError::NoMemory)?;
+        let phys = region.start_address();
+        (phys_to_virt(phys).as_u64() as *mut u32).write_volatile(vmcs_revision());
+
+        let cr4 = read_cr4();
+        write_cr4((cr4 | CR4_VMXE | read_msr(IA32_VMX_CR4_FIXED0)) & read_msr(IA32_VMX_CR4_FIXED1));
+        if !vmxon(phys.as_u64()) {
+            write_cr4(cr4);
+            let _ = free_page(region);
+            return Err(KvmError::NotSupported);
+        }
+    }
+    Ok(())
+}
+
+/// `(wanted | bits imposés à 1) & bits autorisés à 1`.
+fn adjust_controls(msr: u32, wanted: u32) -> u32 {
+    // SAFETY: MSR de capacité VMX présents (VMX supporté).
+    let caps = unsafe { read_msr(msr) };
+    (wanted | caps as u32) & (caps >> 32) as u32
+}
+
+// ── vCPU ─────────────────────────────────────────────────────────────────────
+
+/// MSR SYSCALL et KERNEL_GS_BASE, hors VMCS.
+#[derive(Clone, Copy, Default)]
+struct SyscallMsrs {
+    star: u64,
+    lstar: u64,
+    cstar: u64,
+    sfmask: u64,
+    kernel_gs_base: u64,
+}
+
+impl SyscallMsrs {
+    /// # Safety
+    /// Ring 0.
+    unsafe fn read() -> Self {
+        // SAFETY: MSR architecturaux x86_64.
+        unsafe {
+            Self {
+                star: read_msr(MSR_STAR),
+                lstar: read_msr(MSR_LSTAR),
+                cstar: read_msr(MSR_CSTAR),
+                sfmask: read_msr(MSR_SFMASK),
+                kernel_gs_base: read_msr(MSR_KERNEL_GS_BASE),
+            }
+        }
+    }
+
+    /// # Safety
+    /// Interruptions coupées : les valeurs invitées ne doivent servir à
+    /// aucun SYSCALL ni SWAPGS de l'hôte.
+    unsafe fn write(&self) {
+        // SAFETY: délégué à l'appelant.
+        unsafe {
+            write_msr(MSR_STAR, self.star);
+            write_msr(MSR_LSTAR, self.lstar);
+            write_msr(MSR_CSTAR, self.cstar);
+            write_msr(MSR_SFMASK, self.sfmask);
+            write_msr(MSR_KERNEL_GS_BASE, self.kernel_gs_base);
+        }
+    }
+}
+
+pub(super) struct VmxVcpu {
+    vmcs: Frame,
+    /// Contrôles écrits dans le VMCS (premier chargement).
+    configured: bool,
+    /// VMLAUNCH fait depuis le dernier VMCLEAR.
+    launched: bool,
+    guest_fx: FxArea,
+    guest_msrs: SyscallMsrs,
+    /// EFER vu par l'invité (LMA calculé ici).
+    efer: u64,
+    /// CR2 n'a pas de champ VMCS.
+    cr2: u64,
+    host_gdtr: DescriptorPointer,
+    cr0_fixed0: u64,
+    cr0_fixed1: u64,
+    cr4_fixed0: u64,
+    cr4_fixed1: u64,
+}
+
+impl VmxVcpu {
+    pub(super) fn new() -> Result<Self, KvmError> {
+        let vmcs = alloc_page(AllocFlags::ZEROED).map_err(|_| KvmError::NoMemory)?;
+        // SAFETY: page fraîchement allouée, accessible par la physmap.
+        unsafe {
+            (phys_to_virt(vmcs.start_address()).as_u64() as *mut u32)
+                .write_volatile(vmcs_revision());
+        }
+        // SAFETY: MSR VMX présents (backend VMX choisi).
+        let (cr0_fixed0, cr0_fixed1, cr4_fixed0, cr4_fixed1) = unsafe {
+            (
+                read_msr(IA32_VMX_CR0_FIXED0),
+                read_msr(IA32_VMX_CR0_FIXED1),
+                read_msr(IA32_VMX_CR4_FIXED0),
+                read_msr(IA32_VMX_CR4_FIXED1),
+            )
+        };
+        Ok(Self {
+            vmcs,
+            configured: false,
+            launched: false,
+            guest_fx: FxArea::new(),
+            guest_msrs: SyscallMsrs::default(),
+            efer: 0,
+            cr2: 0,
+            host_gdtr: DescriptorPointer::default(),
+            // « Unrestricted guest » : PE et PG restent libres.
+            cr0_fixed0: cr0_fixed0 & !(CR0_PE | CR0_PG),
+            cr0_fixed1,
+            cr4_fixed0,
+            cr4_fixed1,
+        })
+    }
+
+    fn vmcs_phys(&self) -> u64 {
+        self.vmcs.start_address().as_u64()
+    }
+
+    /// Bits de CR0 imposés par VMX : interceptés, l'invité voit son ombre.
+    fn cr0_mask(&self) -> u64 {
+        (self.cr0_fixed0 | !self.cr0_fixed1) & 0xFFFF_FFFF
+    }
+
+    fn cr4_mask(&self) -> u64 {
+        (self.cr4_fixed0 | !self.cr4_fixed1 | CR4_OSXSAVE | CR4_SMXE) & 0xFFFF_FFFF
+    }
+
+    /// # Safety
+    /// VMCS courant.
+    unsafe fn write_controls(&mut self) {
+        // SAFETY: MSR VMX présents ; VMCS courant (délégué à l'appelant).
+        unsafe {
+            let true_ctls = read_msr(IA32_VMX_BASIC) & VMX_BASIC_TRUE_CTLS != 0;
+            let (pin_msr, proc_msr, exit_msr, entry_msr) = if true_ctls {
+                (
+                    IA32_VMX_TRUE_PINBASED_CTLS,
+                    IA32_VMX_TRUE_PROCBASED_CTLS,
+                    IA32_VMX_TRUE_EXIT_CTLS,
+                    IA32_VMX_TRUE_ENTRY_CTLS,
+                )
+            } else {
+                (
+                    IA32_VMX_PINBASED_CTLS,
+                    IA32_VMX_PROCBASED_CTLS,
+                    IA32_VMX_EXIT_CTLS,
+                    IA32_VMX_ENTRY_CTLS,
+                )
+            };
+            let pin = adjust_controls(pin_msr, PIN_EXTERNAL_INTERRUPT | PIN_NMI);
+            let proc = adjust_controls(
+                proc_msr,
+                PROC_HLT
+                    | PROC_MWAIT
+                    | PROC_RDPMC
+                    | PROC_UNCONDITIONAL_IO
+                    | PROC_MONITOR
+                    | PROC_SECONDARY,
+            );
+            let secondary = adjust_controls(
+                IA32_VMX_PROCBASED_CTLS2,
+                SECONDARY_EPT | SECONDARY_UNRESTRICTED_GUEST,
+            );
+            let exit = adjust_controls(
+                exit_msr,
+                EXIT_HOST_ADDR_SPACE_SIZE
+                    | EXIT_SAVE_PAT
+                    | EXIT_LOAD_PAT
+                    | EXIT_SAVE_EFER
+                    | EXIT_LOAD_EFER,
+            );
+            let entry = adjust_controls(
+                entry_msr,
+                ENTRY_LOAD_DEBUG | ENTRY_LOAD_PAT | ENTRY_LOAD_EFER,
+            );
+
+            vmwrite(PIN_BASED_CONTROLS, pin as u64);
+            vmwrite(PROC_BASED_CONTROLS, proc as u64);
+            vmwrite(SECONDARY_CONTROLS, secondary as u64);
+            vmwrite(EXIT_CONTROLS, exit as u64);
+            vmwrite(ENTRY_CONTROLS, entry as u64);
+            vmwrite(EXCEPTION_BITMAP, 0);
+            vmwrite(CR3_TARGET_COUNT, 0);
+            vmwrite(EXIT_MSR_STORE_COUNT, 0);
+            vmwrite(EXIT_MSR_LOAD_COUNT, 0);
+            vmwrite(ENTRY_MSR_LOAD_COUNT, 0);
+            vmwrite(ENTRY_INTR_INFO, 0);
+            vmwrite(VMCS_LINK_POINTER, u64::MAX);
+            vmwrite(CR0_GUEST_HOST_MASK, self.cr0_mask());
+            vmwrite(CR4_GUEST_HOST_MASK, self.cr4_mask());
+            vmwrite(GUEST_IA32_PAT, PAT_RESET);
+            vmwrite(GUEST_IA32_DEBUGCTL, 0);
+            vmwrite(GUEST_DR7, 0x400);
+            vmwrite(GUEST_SYSENTER_CS, 0);
+            vmwrite(GUEST_SYSENTER_ESP, 0);
+            vmwrite(GUEST_SYSENTER_EIP, 0);
+        }
+    }
+
+    /// État hôte du CPU courant (sélecteurs, bases per-CPU, TSS, GDT/IDT).
+    ///
+    /// # Safety
+    /// VMCS courant, préemption coupée.
+    unsafe fn write_host_state(&mut self) {
+        // SAFETY: lectures de registres système en ring 0 ; VMCS courant.
+        unsafe {
+            let (cs, ss, tr): (u16, u16, u16);
+            core::arch::asm!(
+                "mov {0:x}, cs",
+                "mov {1:x}, ss",
+                "str {2:x}",
+                out(reg) cs,
+                out(reg) ss,
+                out(reg) tr,
+                options(nostack, nomem),
+            );
+            let mut gdtr = DescriptorPointer::default();
+            let mut idtr = DescriptorPointer::default();
+            core::arch::asm!(
+                "sgdt [{0}]",
+                "sidt [{1}]",
+                in(reg) &mut gdtr,
+                in(reg) &mut idtr,
+                options(nostack),
+            );
+            self.host_gdtr = gdtr;
+
+            vmwrite(HOST_CS_SELECTOR, (cs & !7) as u64);
+            vmwrite(HOST_SS_SELECTOR, (ss & !7) as u64);
+            vmwrite(HOST_DS_SELECTOR, 0);
+            vmwrite(HOST_ES_SELECTOR, 0);
+            vmwrite(HOST_FS_SELECTOR, 0);
+            vmwrite(HOST_GS_SELECTOR, 0);
+            vmwrite(HOST_TR_SELECTOR, (tr & !7) as u64);
+            vmwrite(HOST_CR3, read_cr3());
+            vmwrite(HOST_CR4, read_cr4());
+            vmwrite(HOST_FS_BASE, read_msr(MSR_FS_BASE));
+            vmwrite(HOST_GS_BASE, read_msr(MSR_GS_BASE));
+            vmwrite(HOST_TR_BASE, tss_base(gdtr.base, tr));
+            vmwrite(HOST_GDTR_BASE, gdtr.base);
+            vmwrite(HOST_IDTR_BASE, idtr.base);
+            vmwrite(HOST_SYSENTER_CS, read_msr(MSR_SYSENTER_CS));
+            vmwrite(HOST_SYSENTER_ESP, read_msr(MSR_SYSENTER_ESP));
+            vmwrite(HOST_SYSENTER_EIP, read_msr(MSR_SYSENTER_EIP));
+            vmwrite(HOST_IA32_PAT, read_msr(MSR_IA32_PAT));
+            vmwrite(HOST_IA32_EFER, read_msr(MSR_IA32_EFER));
+            vmwrite(HOST_RIP, exo_vmx_exit as *const () as usize as u64);
+        }
+    }
+
+    /// # Safety
+    /// VMCS courant.
+    unsafe fn write_guest_state(&mut self, state: &ArchState) {
+        let sregs = &state.sregs;
+        let segments = [
+            (SEGMENT_ES, &sregs.es),
+            (SEGMENT_CS, &sregs.cs),
+            (SEGMENT_SS, &sregs.ss),
+            (SEGMENT_DS, &sregs.ds),
+            (SEGMENT_FS, &sregs.fs),
+            (SEGMENT_GS, &sregs.gs),
+            (SEGMENT_LDTR, &sregs.ldt),
+            (SEGMENT_TR, &sregs.tr),
+        ];
+        // SAFETY: VMCS courant (délégué à l'appelant).
+        unsafe {
+            for (index, segment) in segments {
+                vmwrite(GUEST_ES_SELECTOR + 2 * index, segment.selector as u64);
+                vmwrite(GUEST_ES_BASE + 2 * index, segment.base);
+                vmwrite(GUEST_ES_LIMIT + 2 * index, segment.limit as u64);
+                vmwrite(GUEST_ES_AR + 2 * index, segment_ar(segment));
+            }
+            vmwrite(GUEST_GDTR_BASE, sregs.gdt.base);
+            vmwrite(GUEST_GDTR_LIMIT, sregs.gdt.limit as u64);
+            vmwrite(GUEST_IDTR_BASE, sregs.idt.base);
+            vmwrite(GUEST_IDTR_LIMIT, sregs.idt.limit as u64);
+
+            self.cr2 = sregs.cr2;
+            self.efer = sregs.efer & !EFER_LMA;
+            vmwrite(GUEST_CR0, self.cr0_actual(sregs.cr0));
+            vmwrite(CR0_READ_SHADOW, sregs.cr0);
+            vmwrite(GUEST_CR4, self.cr4_actual(sregs.cr4));
+            vmwrite(CR4_READ_SHADOW, sregs.cr4);
+            vmwrite(GUEST_CR3, sregs.cr3);
+            self.set_long_mode(sregs.cr0 & CR0_PG != 0 && self.efer & EFER_LME != 0);
+
+            vmwrite(GUEST_RIP, state.rip);
+            vmwrite(GUEST_RFLAGS, state.rflags | 0x2);
+            vmwrite(GUEST_INTERRUPTIBILITY, 0);
+            vmwrite(GUEST_ACTIVITY_STATE, 0);
+            vmwrite(GUEST_PENDING_DBG_EXCEPTIONS, 0);
+        }
+    }
+
+    fn cr0_actual(&self, cr0: u64) -> u64 {
+        (cr0 | self.cr0_fixed0) & self.cr0_fixed1
+    }
+
+    fn cr4_actual(&self, cr4: u64) -> u64 {
+        (cr4 | self.cr4_fixed0) & self.cr4_fixed1
+    }
+
+    /// # Safety
+    /// VMCS courant.
+    unsafe fn cr0(&self) -> u64 {
+        let mask = self.cr0_mask();
+        // SAFETY: délégué à l'appelant.
+        unsafe { (vmread(GUEST_CR0) & !mask) | (vmread(CR0_READ_SHADOW) & mask) }
+    }
+
+    /// # Safety
+    /// VMCS courant.
+    unsafe fn cr4(&self) -> u64 {
+        let mask = self.cr4_mask();
+        // SAFETY: délégué à l'appelant.
+        unsafe { (vmread(GUEST_CR4) & !mask) | (vmread(CR4_READ_SHADOW) & mask) }
+    }
+
+    /// EFER.LMA et le contrôle d'entrée « IA-32e mode guest » suivent
+    /// CR0.PG && EFER.LME : VMX ne les met pas à jour seul.
+    ///
+    /// # Safety
+    /// VMCS courant.
+    unsafe fn set_long_mode(&mut self, enabled: bool) {
+        if enabled {
+            self.efer |= EFER_LMA;
+        } else {
+            self.efer &= !EFER_LMA;
+        }
+        // SAFETY: délégué à l'appelant.
+        unsafe {
+            vmwrite(GUEST_IA32_EFER, self.efer);
+            let entry = vmread(ENTRY_CONTROLS);
+            let entry = if enabled {
+                entry | ENTRY_IA32E_MODE as u64
+            } else {
+                entry & !(ENTRY_IA32E_MODE as u64)
+            };
+            vmwrite(ENTRY_CONTROLS, entry);
+        }
+    }
+
+    /// # Safety
+    /// VMCS courant.
+    unsafe fn set_cr0(&mut self, value: u64) -> bool {
+        if value >> 32 != 0 || (value & CR0_PG != 0 && value & CR0_PE == 0) {
+            return false;
+        }
+        // SAFETY: délégué à l'appelant.
+        unsafe {
+            let long = value & CR0_PG != 0 && self.efer & EFER_LME != 0;
+            if long && self.cr4() & CR4_PAE == 0 {
+                return false;
+            }
+            self.set_long_mode(long);
+            vmwrite(GUEST_CR0, self.cr0_actual(value));
+            vmwrite(CR0_READ_SHADOW, value);
+        }
+        true
+    }
+
+    /// # Safety
+    /// VMCS courant.
+    unsafe fn set_cr4(&mut self, value: u64) -> bool {
+        if value >> 32 != 0 || value & (CR4_VMXE | CR4_SMXE | CR4_OSXSAVE) != 0 {
+            return false;
+        }
+        if self.efer & EFER_LMA != 0 && value & CR4_PAE == 0 {
+            return false;
+        }
+        // SAFETY: délégué à l'appelant.
+        unsafe {
+            vmwrite(GUEST_CR4, self.cr4_actual(value));
+            vmwrite(CR4_READ_SHADOW, value);
+        }
+        true
+    }
+
+    /// MOV vers CR0/CR4 ou LMSW sur un bit intercepté.
+    ///
+    /// # Safety
+    /// VMCS courant.
+    unsafe fn cr_access(&mut self, qualification: u64, gprs: &GuestGprs, len: u8) -> Exit {
+        // SAFETY: délégué à l'appelant.
+        unsafe {
+            let accepted = match CrAccess::decode(qualification) {
+                CrAccess::MovToCr0(gpr) => self.set_cr0(gprs.regs[gpr] & 0xFFFF_FFFF),
+                CrAccess::MovToCr4(gpr) => self.set_cr4(gprs.regs[gpr]),
+                CrAccess::Lmsw(source) => {
+                    // PE ne peut pas retomber.
+                    let cr0 = self.cr0();
+                    self.set_cr0((cr0 & !0xE) | source | (cr0 & CR0_PE))
+                }
+                CrAccess::Other => return Exit::Unhandled(EXIT_CR_ACCESS),
+            };
+            if accepted {
+                self.set_rip(self.rip() + len as u64);
+            } else {
+                self.inject_exception(VECTOR_GP, Some(0));
+            }
+        }
+        Exit::Host
+    }
+
+    /// # Safety
+    /// VMCS courant, interruptions coupées (NMI relayée avant STI).
+    unsafe fn decode_exit(&mut self, gprs: &GuestGprs) -> Exit {
+        // SAFETY: délégué à l'appelant.
+        unsafe {
+            let reason = vmread(EXIT_REASON);
+            if reason & EXIT_REASON_ENTRY_FAILURE != 0 {
+                return Exit::EntryFailed(reason & 0xFFFF);
+            }
+            // Événement interrompu par la sortie (IRQ injectée puis faute EPT…).
+            let vectoring = vmread(IDT_VECTORING_INFO);
+            if vectoring & INTR_INFO_VALID != 0 {
+                vmwrite(ENTRY_INTR_INFO, vectoring & !INTR_INFO_NMI_UNBLOCKING);
+                if vectoring & INTR_INFO_HAS_ERROR_CODE != 0 {
+                    vmwrite(ENTRY_EXCEPTION_ERROR_CODE, vmread(IDT_VECTORING_ERROR_CODE));
+                }
+                vmwrite(ENTRY_INSTRUCTION_LEN, vmread(EXIT_INSTRUCTION_LEN));
+            }
+
+            let len = vmread(EXIT_INSTRUCTION_LEN) as u8;
+            match reason & 0xFFFF {
+                EXIT_EXCEPTION_NMI => {
+                    // NMI hôte survenue dans l'invité : la relayer au handler.
+                    if vmread(EXIT_INTR_INFO) & INTR_TYPE_MASK == INTR_TYPE_NMI {
+                        core::arch::asm!("int 2", options(nostack));
+                    }
+                    Exit::Host
+                }
+                EXIT_CR_ACCESS => self.cr_access(vmread(EXIT_QUALIFICATION), gprs, len),
+                EXIT_IO => io_exit(vmread(EXIT_QUALIFICATION), len),
+                EXIT_EPT_VIOLATION => Exit::GuestPageFault {
+                    gpa: vmread(GUEST_PHYSICAL_ADDRESS),
+                },
+                basic => basic_exit(basic, len),
+            }
+        }
+    }
+}
+
+/// Sorties décodées sur la seule raison de base (sans autre champ du VMCS).
+fn basic_exit(basic: u64, len: u8) -> Exit {
+    match basic {
+        EXIT_EXTERNAL_INTERRUPT => Exit::Host,
+        EXIT_TRIPLE_FAULT => Exit::Shutdown,
+        EXIT_INTERRUPT_WINDOW => Exit::InterruptWindow,
+        EXIT_CPUID => Exit::Cpuid { len },
+        EXIT_HLT => Exit::Hlt { len },
+        EXIT_INVD | EXIT_MWAIT | EXIT_MONITOR => Exit::Skip { len },
+        EXIT_RDPMC | EXIT_VMCALL..=EXIT_VMXON | EXIT_INVEPT | EXIT_INVVPID | EXIT_XSETBV => {
+            Exit::InvalidOpcode
+        }
+        EXIT_RDMSR => Exit::Rdmsr { len },
+        EXIT_WRMSR => Exit::Wrmsr { len },
+        other => Exit::Unhandled(other),
+    }
+}
+
+/// Qualification d'une sortie IN/OUT : taille - 1 (bits 2:0), IN (bit 3),
+/// chaîne ou REP (bits 5:4), port (bits 31:16).
+fn io_exit(qualification: u64, len: u8) -> Exit {
+    Exit::Io {
+        port: (qualification >> 16) as u16,
+        size: (qualification & 7) as u8 + 1,
+        write: qualification & (1 << 3) == 0,
+        string: qualification & (3 << 4) != 0,
+        len,
+    }
+}
+
+/// Qualification d'une sortie « accès CR » réduite aux cas émulés.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CrAccess {
+    /// MOV vers CR0 depuis le GPR indiqué.
+    MovToCr0(usize),
+    /// MOV vers CR4 depuis le GPR indiqué.
+    MovToCr4(usize),
+    /// LMSW : bits 3:0 de la source.
+    Lmsw(u64),
+    Other,
+}
+
+impl CrAccess {
+    fn decode(qualification: u64) -> Self {
+        let cr = qualification & 0xF;
+        let access = (qualification >> 4) & 3;
+        let gpr = ((qualification >> 8) & 0xF) as usize;
+        match (access, cr) {
+            (0, 0) => CrAccess::MovToCr0(gpr),
+            (0, 4) => CrAccess::MovToCr4(gpr),
+            (3, _) => CrAccess::Lmsw((qualification >> 16) & 0xF),
+            _ => CrAccess::Other,
+        }
+    }
+}
+
+/// Champ du VMCS invité qui porte `msr`, pour les MSR sauvés par le matériel.
+fn vmcs_msr_field(msr: u32) -> Option<u64> {
+    Some(match msr {
+        MSR_FS_BASE => GUEST_FS_BASE,
+        MSR_GS_BASE => GUEST_GS_BASE,
+        MSR_SYSENTER_CS => GUEST_SYSENTER_CS,
+        MSR_SYSENTER_ESP => GUEST_SYSENTER_ESP,
+        MSR_SYSENTER_EIP => GUEST_SYSENTER_EIP,
+        MSR_IA32_PAT => GUEST_IA32_PAT,
+        _ => return None,
+    })
+}
+
+impl VcpuHw for VmxVcpu {
+    fn load(&mut self, state: &ArchState, state_dirty: bool, eptp: u64) -> Result<(), KvmError> {
+        // SAFETY: VMX actif sur ce CPU (enable_on_current_cpu), préemption
+        // coupée jusqu'à `put`.
+        unsafe {
+            if !self.configured {
+                // Un VMCS neuf doit passer par VMCLEAR avant son premier VMPTRLD.
+                if !vmclear(self.vmcs_phys()) {
+                    return Err(KvmError::NotSupported);
+                }
+            }
+            if !vmptrld(self.vmcs_phys()) {
+                return Err(KvmError::NotSupported);
+            }
+            let first = !self.configured;
+            if first {
+                self.write_controls();
+                self.configured = true;
+            }
+            let eptp = eptp | EPTP_FLAGS;
+            vmwrite(EPT_POINTER, eptp);
+            // Les slots ont pu changer depuis le dernier RUN.
+            invept(eptp);
+            self.write_host_state();
+            if state_dirty || first {
+                self.write_guest_state(state);
+            }
+        }
+        Ok(())
+    }
+
+    fn put(&mut self, state: &mut ArchState) {
+        // SAFETY: VMCS chargé par `load`.
+        unsafe {
+            let sregs = &mut state.sregs;
+            let segments = [
+                (SEGMENT_ES, &mut sregs.es),
+                (SEGMENT_CS, &mut sregs.cs),
+                (SEGMENT_SS, &mut sregs.ss),
+                (SEGMENT_DS, &mut sregs.ds),
+                (SEGMENT_FS, &mut sregs.fs),
+                (SEGMENT_GS, &mut sregs.gs),
+                (SEGMENT_LDTR, &mut sregs.ldt),
+                (SEGMENT_TR, &mut sregs.tr),
+            ];
+            for (index, segment) in segments {
+                *segment = segment_from_vmcs(
+                    vmread(GUEST_ES_SELECTOR + 2 * index) as u16,
+                    vmread(GUEST_ES_BASE + 2 * index),
+                    vmread(GUEST_ES_LIMIT + 2 * index) as u32,
+                    vmread(GUEST_ES_AR + 2 * index),
+                );
+            }
+            sregs.gdt.base = vmread(GUEST_GDTR_BASE);
+            sregs.gdt.limit = vmread(GUEST_GDTR_LIMIT) as u16;
+            sregs.idt.base = vmread(GUEST_IDTR_BASE);
+            sregs.idt.limit = vmread(GUEST_IDTR_LIMIT) as u16;
+            sregs.cr0 = self.cr0();
+            sregs.cr2 = self.cr2;
+            sregs.cr3 = vmread(GUEST_CR3);
+            sregs.cr4 = self.cr4();
+            sregs.efer = self.efer;
+            state.rip = vmread(GUEST_RIP);
+            state.rflags = vmread(GUEST_RFLAGS);
+
+            // Le VMCS quitte ce CPU : prochain RUN par VMLAUNCH, ailleurs au besoin.
+            vmclear(self.vmcs_phys());
+        }
+        self.launched = false;
+    }
+
+    fn enter(&mut self, gprs: &mut GuestGprs) -> Exit {
+        // SAFETY: VMCS chargé par `load` ; interruptions coupées de la
+        // bascule FPU/MSR jusqu'au retour à l'état hôte.
+        unsafe {
+            vmwrite(GUEST_RSP, gprs.regs[RSP]);
+            let flags = irq_save();
+            let fpu = FpuSwap::enter(&self.guest_fx);
+            // CR0.TS vient d'être effacé si besoin : la sortie le laisse ainsi.
+            vmwrite(HOST_CR0, read_cr0());
+            let host_msrs = SyscallMsrs::read();
+            self.guest_msrs.write();
+            write_cr2(self.cr2);
+
+            let failed = exo_vmx_run(gprs, self.launched as u64) != 0;
+
+            self.cr2 = read_cr2();
+            // La sortie VM ramène la limite de la GDT à 0xFFFF.
+            core::arch::asm!("lgdt [{0}]", in(reg) &self.host_gdtr, options(nostack));
+            self.guest_msrs = SyscallMsrs::read();
+            host_msrs.write();
+            fpu.leave(&mut self.guest_fx);
+
+            let exit = if failed {
+                Exit::EntryFailed(vmread(VM_INSTRUCTION_ERROR))
+            } else {
+                self.launched = true;
+                gprs.regs[RSP] = vmread(GUEST_RSP);
+                self.decode_exit(gprs)
+            };
+            irq_restore(flags);
+            exit
+        }
+    }
+
+    fn rip(&self) -> u64 {
+        // SAFETY: VMCS chargé.
+        unsafe { vmread(GUEST_RIP) }
+    }
+
+    fn set_rip(&mut self, rip: u64) {
+        // SAFETY: VMCS chargé. Une instruction sautée lève le masquage STI/MOV SS.
+        unsafe {
+            vmwrite(GUEST_RIP, rip);
+            vmwrite(GUEST_INTERRUPTIBILITY, vmread(GUEST_INTERRUPTIBILITY) & !3);
+        }
+    }
+
+    fn rflags(&self) -> u64 {
+        // SAFETY: VMCS chargé.
+        unsafe { vmread(GUEST_RFLAGS) }
+    }
+
+    fn paging(&self) -> Paging {
+        // SAFETY: VMCS chargé.
+        unsafe {
+            let cs_ar = vmread(GUEST_ES_AR + 2 * SEGMENT_CS);
+            Paging {
+                cr0: self.cr0(),
+                cr3: vmread(GUEST_CR3),
+                cr4: self.cr4(),
+                efer: self.efer,
+                cs_base: vmread(GUEST_CS_BASE),
+                cs_l: cs_ar & (1 << 13) != 0,
+                cs_db: cs_ar & (1 << 14) != 0,
+            }
+        }
+    }
+
+    fn interruptible(&self) -> bool {
+        // SAFETY: VMCS chargé.
+        unsafe {
+            vmread(GUEST_RFLAGS) & RFLAGS_IF != 0
+                && vmread(GUEST_INTERRUPTIBILITY) & 3 == 0
+                && vmread(ENTRY_INTR_INFO) & INTR_INFO_VALID == 0
+        }
+    }
+
+    fn inject_irq(&mut self, vector: u8) {
+        // SAFETY: VMCS chargé. Type 0 : interruption externe.
+        unsafe { vmwrite(ENTRY_INTR_INFO, vector as u64 | INTR_INFO_VALID) };
+    }
+
+    fn inject_exception(&mut self, vector: u8, error_code: Option<u32>) {
+        // SAFETY: VMCS chargé.
+        unsafe {
+            let mut info = vector as u64 | INTR_TYPE_HARD_EXCEPTION | INTR_INFO_VALID;
+            // Pas de code d'erreur en mode réel.
+            if let Some(code) = error_code.filter(|_| self.cr0() & CR0_PE != 0) {
+                info |= INTR_INFO_HAS_ERROR_CODE;
+                vmwrite(ENTRY_EXCEPTION_ERROR_CODE, code as u64);
+            }
+            vmwrite(ENTRY_INTR_INFO, info);
+        }
+    }
+
+    fn set_irq_window(&mut self, enabled: bool) {
+        // SAFETY: VMCS chargé.
+        unsafe {
+            let controls = vmread(PROC_BASED_CONTROLS);
+            let controls = if enabled {
+                controls | PROC_INTERRUPT_WINDOW as u64
+            } else {
+                controls & !(PROC_INTERRUPT_WINDOW as u64)
+            };
+            vmwrite(PROC_BASED_CONTROLS, controls);
+        }
+    }
+
+    fn read_msr(&mut self, msr: u32) -> Option<u64> {
+        // SAFETY: VMCS chargé.
+        unsafe {
+            Some(match msr {
+                MSR_IA32_EFER => self.efer,
+                MSR_STAR => self.guest_msrs.star,
+                MSR_LSTAR => self.guest_msrs.lstar,
+                MSR_CSTAR => self.guest_msrs.cstar,
+                MSR_SFMASK => self.guest_msrs.sfmask,
+                MSR_KERNEL_GS_BASE => self.guest_msrs.kernel_gs_base,
+                msr => vmread(vmcs_msr_field(msr)?),
+            })
+        }
+    }
+
+    fn write_msr(&mut self, msr: u32, value: u64) -> bool {
+        // SAFETY: VMCS chargé.
+        unsafe {
+            match msr {
+                MSR_IA32_EFER => {
+                    if value & !(EFER_SCE | EFER_LME | EFER_LMA | EFER_NXE) != 0 {
+                        return false;
+                    }
+                    // LME figé tant que la pagination est active.
+                    if self.cr0() & CR0_PG != 0 && (value ^ self.efer) & EFER_LME != 0 {
+                        return false;
+                    }
+                    self.efer = (value & !EFER_LMA) | (self.efer & EFER_LMA);
+                    vmwrite(GUEST_IA32_EFER, self.efer);
+                }
+                MSR_STAR => self.guest_msrs.star = value,
+                MSR_LSTAR => self.guest_msrs.lstar = value,
+                MSR_CSTAR => self.guest_msrs.cstar = value,
+                MSR_SFMASK => self.guest_msrs.sfmask = value,
+                MSR_KERNEL_GS_BASE => self.guest_msrs.kernel_gs_base = value,
+                msr => match vmcs_msr_field(msr) {
+                    Some(field) => vmwrite(field, value),
+                    None => return false,
+                },
+            }
+        }
+        true
+    }
+}
+
+impl Drop for VmxVcpu {
+    fn drop(&mut self) {
+        // VMCLEAR fait à chaque `put` : le VMCS n'est actif sur aucun CPU.
+        let _ = free_page(self.vmcs);
+    }
+}
+
+// ── Conversions de segments ──────────────────────────────────────────────────
+
+fn segment_ar(segment: &KvmSegment) -> u64 {
+    if segment.unusable != 0 || segment.present == 0 {
+        return AR_UNUSABLE;
+    }
+    (segment.type_ as u64 & 0xF)
+        | (segment.s as u64 & 1) << 4
+        | (segment.dpl as u64 & 3) << 5
+        | (segment.present as u64 & 1) << 7
+        | (segment.avl as u64 & 1) << 12
+        | (segment.l as u64 & 1) << 13
+        | (segment.db as u64 & 1) << 14
+        | (segment.g as u64 & 1) << 15
+}
+
+fn segment_from_vmcs(selector: u16, base: u64, limit: u32, ar: u64) -> KvmSegment {
+    KvmSegment {
+        base,
+        limit,
+        selector,
+        type_: (ar & 0xF) as u8,
+        s: ((ar >> 4) & 1) as u8,
+        dpl: ((ar >> 5) & 3) as u8,
+        present: ((ar >> 7) & 1) as u8,
+        avl: ((ar >> 12) & 1) as u8,
+        l: ((ar >> 13) & 1) as u8,
+        db: ((ar >> 14) & 1) as u8,
+        g: ((ar >> 15) & 1) as u8,
+        unusable: ((ar >> 16) & 1) as u8,
+        padding: 0,
+    }
+}
+
+/// Base du TSS hôte, lue dans son descripteur système de 16 octets.
+///
+/// # Safety
+/// `gdt_base` est la GDT courante et `tr` un sélecteur valide.
+unsafe fn tss_base(gdt_base: u64, tr: u16) -> u64 {
+    let descriptor = (gdt_base + (tr & !7) as u64) as *const u64;
+    // SAFETY: délégué à l'appelant.
+    let (low, high) = unsafe {
+        (
+            descriptor.read_unaligned(),
+            descriptor.add(1).read_unaligned(),
+        )
+    };
+    ((low >> 16) & 0xFF_FFFF) | ((low >> 32) & 0xFF00_0000) | (high << 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPT_CAPS: u64 = EPT_CAP_WALK_4 | EPT_CAP_WB | EPT_CAP_INVEPT | EPT_CAP_INVEPT_SINGLE;
+
+    fn msrs(control: u64, procbased: u32, secondary: u32, ept: u64) -> impl Fn(u32) -> u64 {
+        move |msr| match msr {
+            IA32_FEATURE_CONTROL => control,
+            IA32_VMX_PROCBASED_CTLS => (procbased as u64) << 32,
+            IA32_VMX_PROCBASED_CTLS2 => {
+                assert!(
+                    procbased & PROC_SECONDARY != 0,
+                    "PROCBASED_CTLS2 lu sans contrôles secondaires"
+                );
+                (secondary as u64) << 32
+            }
+            IA32_VMX_EPT_VPID_CAP => ept,
+            other => panic!("MSR {other:#x} inattendu"),
+        }
+    }
+
+    const SECONDARY: u32 = SECONDARY_EPT | SECONDARY_UNRESTRICTED_GUEST;
+
+    #[test]
+    fn usable_requires_ept_and_unrestricted_guest() {
+        assert!(usable(msrs(0, PROC_SECONDARY, SECONDARY, EPT_CAPS)));
+        let locked = FEATURE_CONTROL_LOCKED | FEATURE_CONTROL_VMXON_OUTSIDE_SMX;
+        assert!(usable(msrs(locked, PROC_SECONDARY, SECONDARY, EPT_CAPS)));
+        // Verrouillé par le firmware sans VMXON hors SMX.
+        assert!(!usable(msrs(
+            FEATURE_CONTROL_LOCKED,
+            PROC_SECONDARY,
+            SECONDARY,
+            EPT_CAPS
+        )));
+        assert!(!usable(msrs(0, 0, SECONDARY, EPT_CAPS)));
+        assert!(!usable(msrs(0, PROC_SECONDARY, SECONDARY_EPT, EPT_CAPS)));
+        assert!(!usable(msrs(
+            0,
+            PROC_SECONDARY,
+            SECONDARY_UNRESTRICTED_GUEST,
+            EPT_CAPS
+        )));
+        for missing in [
+            EPT_CAP_WALK_4,
+            EPT_CAP_WB,
+            EPT_CAP_INVEPT,
+            EPT_CAP_INVEPT_SINGLE,
+        ] {
+            assert!(!usable(msrs(
+                0,
+                PROC_SECONDARY,
+                SECONDARY,
+                EPT_CAPS & !missing
+            )));
+        }
+        let all_context = EPT_CAPS & !EPT_CAP_INVEPT_SINGLE | EPT_CAP_INVEPT_ALL;
+        assert!(usable(msrs(0, PROC_SECONDARY, SECONDARY, all_context)));
+    }
+
+    #[test]
+    fn basic_exit_reasons_are_decoded() {
+        assert_eq!(basic_exit(EXIT_EXTERNAL_INTERRUPT, 0), Exit::Host);
+        assert_eq!(basic_exit(EXIT_TRIPLE_FAULT, 0), Exit::Shutdown);
+        assert_eq!(basic_exit(EXIT_INTERRUPT_WINDOW, 0), Exit::InterruptWindow);
+        assert_eq!(basic_exit(EXIT_CPUID, 2), Exit::Cpuid { len: 2 });
+        assert_eq!(basic_exit(EXIT_HLT, 1), Exit::Hlt { len: 1 });
+        assert_eq!(basic_exit(EXIT_RDMSR, 2), Exit::Rdmsr { len: 2 });
+        assert_eq!(basic_exit(EXIT_WRMSR, 2), Exit::Wrmsr { len: 2 });
+        for skipped in [EXIT_INVD, EXIT_MWAIT, EXIT_MONITOR] {
+            assert_eq!(basic_exit(skipped, 3), Exit::Skip { len: 3 });
+        }
+        // VMCALL, VMCLEAR … VMXON, INVEPT, INVVPID, XSETBV, RDPMC : #UD.
+        for refused in
+            (EXIT_VMCALL..=EXIT_VMXON).chain([EXIT_RDPMC, EXIT_INVEPT, EXIT_INVVPID, EXIT_XSETBV])
+        {
+            assert_eq!(basic_exit(refused, 3), Exit::InvalidOpcode, "{refused}");
+        }
+        assert_eq!(
+            basic_exit(EXIT_VMXON + 1, 0),
+            Exit::Unhandled(EXIT_VMXON + 1)
+        );
+        assert_eq!(
+            basic_exit(EXIT_CR_ACCESS, 0),
+            Exit::Unhandled(EXIT_CR_ACCESS)
+        );
+    }
+
+    #[test]
+    fn io_qualification_is_decoded() {
+        // OUT DX, AL vers 0x3F8.
+        assert_eq!(
+            io_exit(0x03F8 << 16, 1),
+            Exit::Io {
+                port: 0x3F8,
+                size: 1,
+                write: true,
+                string: false,
+                len: 1
+            }
+        );
+        // IN EAX, 0x71.
+        assert_eq!(
+            io_exit((0x71 << 16) | (1 << 6) | (1 << 3) | 3, 2),
+            Exit::Io {
+                port: 0x71,
+                size: 4,
+                write: false,
+                string: false,
+                len: 2
+            }
+        );
+        // INSW et REP OUTSB : chaînes.
+        for string in [1 << 4, 1 << 5] {
+            let Exit::Io {
+                string: true, size, ..
+            } = io_exit((0x60 << 16) | string | 1, 2)
+            else {
+                panic!("chaîne non détectée");
+            };
+            assert_eq!(size, 2);
+        }
+    }
+
+    #[test]
+    fn cr_access_qualification_is_decoded() {
+        // MOV CR0, RBX ; MOV CR4, R12.
+        assert_eq!(CrAccess::decode(3 << 8), CrAccess::MovToCr0(3));
+        assert_eq!(CrAccess::decode((12 << 8) | 4), CrAccess::MovToCr4(12));
+        // LMSW AX = 0b1011 : la source est en bits 31:16.
+        assert_eq!(
+            CrAccess::decode((0xB << 16) | (3 << 4)),
+            CrAccess::Lmsw(0xB)
+        );
+        assert_eq!(
+            CrAccess::decode((0xFFFB << 16) | (3 << 4)),
+            CrAccess::Lmsw(0xB)
+        );
+        // MOV depuis CR0, CLTS, MOV CR3 : non interceptés par ce backend.
+        assert_eq!(CrAccess::decode(1 << 4), CrAccess::Other);
+        assert_eq!(CrAccess::decode(2 << 4), CrAccess::Other);
+        assert_eq!(CrAccess::decode(3), CrAccess::Other);
+    }
+
+    #[test]
+    fn hardware_saved_msrs_map_to_guest_fields() {
+        assert_eq!(vmcs_msr_field(MSR_FS_BASE), Some(GUEST_FS_BASE));
+        assert_eq!(vmcs_msr_field(MSR_GS_BASE), Some(GUEST_GS_BASE));
+        assert_eq!(vmcs_msr_field(MSR_SYSENTER_CS), Some(GUEST_SYSENTER_CS));
+        assert_eq!(vmcs_msr_field(MSR_SYSENTER_ESP), Some(GUEST_SYSENTER_ESP));
+        assert_eq!(vmcs_msr_field(MSR_SYSENTER_EIP), Some(GUEST_SYSENTER_EIP));
+        assert_eq!(vmcs_msr_field(MSR_IA32_PAT), Some(GUEST_IA32_PAT));
+        // EFER et les MSR SYSCALL vivent dans `VmxVcpu`, pas dans le VMCS.
+        for msr in [MSR_IA32_EFER, MSR_STAR, MSR_LSTAR, MSR_KERNEL_GS_BASE, 0x10] {
+            assert_eq!(vmcs_msr_field(msr), None, "{msr:#x}");
+        }
+    }
+
+    #[test]
+    fn segments_round_trip_through_access_rights() {
+        let code64 = KvmSegment {
+            base: 0,
+            limit: 0xFFFF_FFFF,
+            selector: 0x08,
+            type_: 11,
+            present: 1,
+            s: 1,
+            l: 1,
+            g: 1,
+            ..Default::default()
+        };
+        let ar = segment_ar(&code64);
+        assert_eq!(ar & AR_UNUSABLE, 0);
+        let back = segment_from_vmcs(code64.selector, code64.base, code64.limit, ar);
+        assert_eq!(
+            (back.type_, back.s, back.present, back.l, back.db, back.g),
+            (11, 1, 1, 1, 0, 1)
+        );
+        let unusable = KvmSegment {
+            unusable: 1,
+            ..Default::default()
+        };
+        assert_ne!(segment_ar(&unusable) & AR_UNUSABLE, 0);
+    }
+}
@@ -243,6 +243,27 @@ impl<'a> UserFaultAllocator<'a> {
     }
 }
 
+/// Pages demandées aux shrinkers quand un fault userspace ne trouve pas de
+/// frame libre.
+const USER_FAULT_RECLAIM_PAGES: u64 = 32;
+
+/// Alloue une frame pour un fault userspace ; sur échec, reclaim direct via
+/// les shrinkers puis un second essai. L'attente est comptée comme blocage
+/// mémoire (`memory::utils::pressure`).
+fn alloc_user_fault_page(flags: AllocFlags) -> Result<Frame, AllocError> {
+    use crate::arch::x86_64::time::ktime::ktime_get_ns;
+    use crate::memory::utils::pressure;
+
+    if let Ok(frame) = alloc_page(flags) {
+        return Ok(frame);
+    }
+    pressure::stall_begin(ktime_get_ns());
+    crate::memory::run_shrinkers(USER_FAULT_RECLAIM_PAGES);
+    let result = alloc_page(flags);
+    pressure::stall_end(ktime_get_ns());
+    result
+}
+
 impl FrameAllocatorForWalk for UserFaultAllocator<'_> {
     fn alloc_frame(&self, flags: AllocFlags) -> Result<Frame, AllocError> {
         alloc_page(flags)
@@ -256,12 +277,12 @@ impl FrameAllocatorForWalk for UserFaultAllocator<'_> {
 impl FaultAllocator for UserFaultAllocator<'_> {
     #[inline]
     fn alloc_zeroed(&self) -> Result<Frame, AllocError> {
-        alloc_page(AllocFlags::ZEROED)
+        alloc_user_fault_page(AllocFlags::ZEROED)
     }
 
     #[inline]
     fn alloc_nonzeroed(&self) -> Result<Frame, AllocError> {
-        alloc_page(AllocFlags::NONE)
+        alloc_user_fault_page(AllocFlags::NONE)
     }
 
     #[inline]
@@ -5,6 +5,7 @@
 
 pub mod acpi;
 pub mod apic;
+pub mod arch_impl; // arch::interface (X86_64)
 pub mod boot;
 pub mod boot_display;
 pub mod cpu;
@@ -12,10 +13,15 @@ pub mod exceptions;
 pub mod framebuffer_early;
 pub mod gdt;
 pub mod idt;
+#[cfg(feature = "ia32_compat")]
+pub mod ia32; // Entrées syscall i386 (int 0x80, SYSENTER)
 pub mod irq;
+#[cfg(feature = "kvm")]
+pub mod kvm; // Hyperviseur VMX/SVM (/dev/exo-kvm)
 pub mod memory_iface;
 pub mod paging;
 pub mod sched_iface; // Pont FFI arch → scheduler (C ABI exports)
+pub mod serial; // Console série COM1 (profil serveur, console=ttyS0)
 pub mod smp;
 pub mod spectre;
 pub mod syscall;
@@ -0,0 +1,154 @@
+//! # arch/x86_64/serial.rs — Console série COM1 (UART 16550)
+//!
+//! Sortie de la console debug du noyau sur le premier port série, pour les
+//! images sans framebuffer (profil serveur, invités VM `-nographic`). Le port
+//! est programmé en 115200 8N1, FIFO actif et interruptions coupées : la
+//! réception reste à la charge de `tty_server` (Ring1), qui sonde LSR/RBR via
+//! `SYS_IOPORT_READ`.
+//!
+//! Activation :
+//! - feature cargo `serial_console` (toujours actif), ou
+//! - jeton `console=ttyS0` sur la ligne de commande Multiboot2.
+//!
+//! `terminal::debug_write` recopie alors chaque octet ici (`\n` → `\r\n`).
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Base d'E/S du port COM1.
+pub const COM1_BASE: u16 = 0x3F8;
+/// Dernier port du bloc COM1 (registres 0..7).
+pub const COM1_LAST: u16 = COM1_BASE + 7;
+
+const REG_DATA: u16 = 0; // RBR/THR (DLL si DLAB=1)
+const REG_IER: u16 = 1; // IER (DLM si DLAB=1)
+const REG_FCR: u16 = 2;
+const REG_LCR: u16 = 3;
+const REG_MCR: u16 = 4;
+const REG_LSR: u16 = 5;
+const REG_SCRATCH: u16 = 7;
+
+const LCR_DLAB: u8 = 0x80;
+const LCR_8N1: u8 = 0x03;
+/// FIFO on, vidage RX/TX, seuil 14 octets.
+const FCR_ENABLE: u8 = 0xC7;
+/// DTR + RTS + OUT2.
+const MCR_READY: u8 = 0x0B;
+const LSR_THR_EMPTY: u8 = 0x20;
+
+/// Diviseur de 115 200 baud (horloge UART 1,8432 MHz / 16).
+const BAUD_DIVISOR: u16 = 1;
+
+/// Boucles d'attente max de THR vide avant d'abandonner l'octet.
+const TX_SPIN_LIMIT: u32 = 100_000;
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+#[inline(always)]
+fn outb(port: u16, value: u8) {
+    #[cfg(test)]
+    {
+        let _ = (port, value);
+    }
+    #[cfg(not(test))]
+    // SAFETY: accès aux registres COM1 en Ring 0, sans effet mémoire.
+    unsafe {
+        core::arch::asm!("out dx, al", in("dx") port, in("al") value, options(nomem, nostack));
+    }
+}
+
+#[inline(always)]
+fn inb(port: u16) -> u8 {
+    #[cfg(test)]
+    {
+        let _ = port;
+        0xFF
+    }
+    #[cfg(not(test))]
+    {
+        let value: u8;
+        // SAFETY: lecture des registres COM1 en Ring 0, sans effet mémoire.
+        unsafe {
+            core::arch::asm!("in al, dx", out("al") value, in("dx") port, options(nomem, nostack));
+        }
+        value
+    }
+}
+
+/// Vrai si la ligne de commande demande la console série (`console=ttyS0`).
+pub fn cmdline_requests_serial(cmdline: &[u8]) -> bool {
+    cmdline
+        .split(|&b| b == b' ' || b == b'\t')
+        .any(|token| token == b"console=ttyS0" || token.starts_with(b"console=ttyS0,"))
+}
+
+/// Programme COM1 et active la recopie de la console debug.
+///
+/// Le registre scratch sert de sonde : absent (lecture 0xFF flottante), le
+/// port reste inactif et `init` retourne `false`.
+pub fn init() -> bool {
+    outb(COM1_BASE + REG_SCRATCH, 0x5A);
+    if inb(COM1_BASE + REG_SCRATCH) != 0x5A {
+        return false;
+    }
+    outb(COM1_BASE + REG_IER, 0x00);
+    outb(COM1_BASE + REG_LCR, LCR_DLAB);
+    outb(COM1_BASE + REG_DATA, (BAUD_DIVISOR & 0xFF) as u8);
+    outb(COM1_BASE + REG_IER, (BAUD_DIVISOR >> 8) as u8);
+    outb(COM1_BASE + REG_LCR, LCR_8N1);
+    outb(COM1_BASE + REG_FCR, FCR_ENABLE);
+    outb(COM1_BASE + REG_MCR, MCR_READY);
+    ACTIVE.store(true, Ordering::Release);
+    true
+}
+
+/// Active la console série si la feature ou la ligne de commande le demande.
+pub fn init_from_cmdline(cmdline: &[u8]) -> bool {
+    if cfg!(feature = "serial_console") || cmdline_requests_serial(cmdline) {
+        init()
+    } else {
+        false
+    }
+}
+
+#[inline]
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Acquire)
+}
+
+fn write_raw(byte: u8) {
+    let mut spins = 0u32;
+    while inb(COM1_BASE + REG_LSR) & LSR_THR_EMPTY == 0 {
+        spins += 1;
+        if spins >= TX_SPIN_LIMIT {
+            return;
+        }
+        core::hint::spin_loop();
+    }
+    outb(COM1_BASE + REG_DATA, byte);
+}
+
+/// Écrit un octet sur COM1 si la console série est active.
+#[inline]
+pub fn write_byte(byte: u8) {
+    if !is_active() {
+        return;
+    }
+    if byte == b'\n' {
+        write_raw(b'\r');
+    }
+    write_raw(byte);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cmdline_requests_serial() {
+        assert!(cmdline_requests_serial(b"console=ttyS0"));
+        assert!(cmdline_requests_serial(b"quiet console=ttyS0,115200n8"));
+        assert!(!cmdline_requests_serial(b"console=ttyS1"));
+        assert!(!cmdline_requests_serial(b"noconsole=ttyS0"));
+        assert!(!cmdline_requests_serial(b""));
+    }
+}
@@ -146,6 +146,7 @@ pub fn init_syscall() {
     }
 
     // 4. MSR CSTAR : handler mode compat (non utilisé — pointe vers une fonction vide)
+    //    Les binaires i386 (feature ia32_compat) entrent par int 0x80/SYSENTER.
     // SAFETY: syscall_cstar_asm est une fonction noop valide
     unsafe {
         write_msr(MSR_CSTAR, syscall_cstar_noop as *const () as u64);
@@ -163,6 +164,10 @@ pub fn init_syscall() {
     unsafe {
         write_msr(MSR_SFMASK, sfmask);
     }
+
+    // 6. SYSENTER + DS/ES user pour les binaires i386 (arch/x86_64/ia32.rs)
+    #[cfg(feature = "ia32_compat")]
+    super::ia32::init_sysenter();
 }
 
 // ── Entrée SYSCALL en ASM ─────────────────────────────────────────────────────
@@ -418,8 +423,21 @@ pub extern "C" fn syscall_rust_handler(frame: *mut SyscallFrame) {
 
     // SAFETY: frame provient de syscall_rust_handler qui reçoit un pointeur
     // valide depuis l'ASM. La durée de vie est bornée à cette stackframe.
+    #[cfg(feature = "ia32_compat")]
+    let nr = frame.rax;
     crate::syscall::dispatch::dispatch(frame);
 
+    // Exec réussi vers une image i386 : SYSRETQ retournerait en mode 64-bit,
+    // on repart directement en mode compat par IRETQ.
+    #[cfg(feature = "ia32_compat")]
+    let exec_ok = nr == crate::syscall::numbers::SYS_EXECVE && frame.rax == 0;
+    #[cfg(feature = "ia32_compat")]
+    if exec_ok && super::ia32::current_is_ia32() {
+        // SAFETY: contexte SYSCALL, image i386 chargée (rip/rsp < 4 GiB) et
+        // CR3 user publié par exec.
+        unsafe { super::ia32::enter_user32(frame.rcx, frame.rsp) }
+    }
+
     // ── ARCH-SYSRET (V-35) : vérification de l'adresse de retour ───────────
     // Les adresses non-canoniques sont laissées à l'ASM, qui bascule alors sur
     // IRETQ pour éviter le #GP Ring0 de CVE-2012-0217. Les adresses canoniques
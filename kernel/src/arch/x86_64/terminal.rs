@@ -2,7 +2,8 @@
 //!
 //! Normal terminal input/output is owned by the Ring1 stack:
 //! `ps2_driver -> input_server -> tty_server -> fb_server`. Ring0 keeps only
-//! the QEMU debugcon writer and a handoff marker used by the IRQ registration
+//! the QEMU debugcon writer (mirrored to COM1 when the serial console is
+//! enabled, see `serial`) and a handoff marker used by the IRQ registration
 //! path.
 
 use core::sync::atomic::{AtomicBool, Ordering};
@@ -27,6 +28,7 @@ fn debug_byte(byte: u8) {
 pub fn debug_write(bytes: &[u8]) {
     for &byte in bytes {
         debug_byte(byte);
+        super::serial::write_byte(byte);
     }
 }
 
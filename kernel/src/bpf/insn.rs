@@ -0,0 +1,230 @@
+// kernel/src/bpf/insn.rs
+//
+// Encodage des instructions — sous-ensemble d'eBPF, même format binaire :
+// 8 octets `opcode | dst:4 src:4 | off:i16 | imm:i32` (little-endian).
+// `LD_IMM64` occupe deux slots (imm bas puis imm haut dans le second).
+
+/// Registres : r0 retour, r1–r5 arguments (écrasés par un appel), r6–r9
+/// préservés, r10 pointeur de pile en lecture seule.
+pub const REG_COUNT: usize = 11;
+pub const REG_FP: u8 = 10;
+
+/// Pile d'un programme (octets, sous r10).
+pub const STACK_SIZE: usize = 512;
+/// Nombre maximal d'instructions d'un programme.
+pub const MAX_INSNS: usize = 4096;
+
+// Classes
+pub const CLASS_LD: u8 = 0x00;
+pub const CLASS_LDX: u8 = 0x01;
+pub const CLASS_ST: u8 = 0x02;
+pub const CLASS_STX: u8 = 0x03;
+pub const CLASS_ALU: u8 = 0x04;
+pub const CLASS_JMP: u8 = 0x05;
+pub const CLASS_ALU64: u8 = 0x07;
+
+// Source d'opérande (ALU / JMP)
+pub const SRC_K: u8 = 0x00;
+pub const SRC_X: u8 = 0x08;
+
+// Opérations ALU
+pub const ALU_ADD: u8 = 0x00;
+pub const ALU_SUB: u8 = 0x10;
+pub const ALU_MUL: u8 = 0x20;
+pub const ALU_DIV: u8 = 0x30;
+pub const ALU_OR: u8 = 0x40;
+pub const ALU_AND: u8 = 0x50;
+pub const ALU_LSH: u8 = 0x60;
+pub const ALU_RSH: u8 = 0x70;
+pub const ALU_NEG: u8 = 0x80;
+pub const ALU_MOD: u8 = 0x90;
+pub const ALU_XOR: u8 = 0xa0;
+pub const ALU_MOV: u8 = 0xb0;
+pub const ALU_ARSH: u8 = 0xc0;
+
+// Opérations de saut
+pub const JMP_JA: u8 = 0x00;
+pub const JMP_JEQ: u8 = 0x10;
+pub const JMP_JGT: u8 = 0x20;
+pub const JMP_JGE: u8 = 0x30;
+pub const JMP_JSET: u8 = 0x40;
+pub const JMP_JNE: u8 = 0x50;
+pub const JMP_JSGT: u8 = 0x60;
+pub const JMP_JSGE: u8 = 0x70;
+pub const JMP_CALL: u8 = 0x80;
+pub const JMP_EXIT: u8 = 0x90;
+pub const JMP_JLT: u8 = 0xa0;
+pub const JMP_JLE: u8 = 0xb0;
+pub const JMP_JSLT: u8 = 0xc0;
+pub const JMP_JSLE: u8 = 0xd0;
+
+// Accès mémoire
+pub const MODE_IMM: u8 = 0x00;
+pub const MODE_MEM: u8 = 0x60;
+pub const SIZE_W: u8 = 0x00;
+pub const SIZE_H: u8 = 0x08;
+pub const SIZE_B: u8 = 0x10;
+pub const SIZE_DW: u8 = 0x18;
+
+/// `LD_IMM64` : constante 64 bits, ou référence de map si `src == PSEUDO_MAP`.
+pub const LD_IMM64: u8 = CLASS_LD | MODE_IMM | SIZE_DW;
+pub const PSEUDO_MAP: u8 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Insn {
+    pub op: u8,
+    pub dst: u8,
+    pub src: u8,
+    pub off: i16,
+    pub imm: i32,
+}
+
+impl Insn {
+    pub const LEN: usize = 8;
+
+    pub const fn new(op: u8, dst: u8, src: u8, off: i16, imm: i32) -> Self {
+        Self {
+            op,
+            dst,
+            src,
+            off,
+            imm,
+        }
+    }
+
+    pub fn decode(raw: &[u8]) -> Self {
+        Self {
+            op: raw[0],
+            dst: raw[1] & 0x0f,
+            src: raw[1] >> 4,
+            off: i16::from_le_bytes([raw[2], raw[3]]),
+            imm: i32::from_le_bytes([raw[4], raw[5], raw[6], raw[7]]),
+        }
+    }
+
+    pub fn encode(&self) -> [u8; Self::LEN] {
+        let mut out = [0u8; Self::LEN];
+        out[0] = self.op;
+        out[1] = (self.src << 4) | (self.dst & 0x0f);
+        out[2..4].copy_from_slice(&self.off.to_le_bytes());
+        out[4..8].copy_from_slice(&self.imm.to_le_bytes());
+        out
+    }
+
+    #[inline]
+    pub fn class(&self) -> u8 {
+        self.op & 0x07
+    }
+
+    /// Opération ALU ou de saut (bits 4–7).
+    #[inline]
+    pub fn code(&self) -> u8 {
+        self.op & 0xf0
+    }
+
+    #[inline]
+    pub fn source(&self) -> u8 {
+        self.op & 0x08
+    }
+
+    #[inline]
+    pub fn mode(&self) -> u8 {
+        self.op & 0xe0
+    }
+
+    /// Largeur d'un accès mémoire (octets).
+    #[inline]
+    pub fn access_size(&self) -> usize {
+        match self.op & 0x18 {
+            SIZE_W => 4,
+            SIZE_H => 2,
+            SIZE_B => 1,
+            _ => 8,
+        }
+    }
+}
+
+/// Décode un programme (`len` multiple de 8).
+pub fn decode_all(raw: &[u8]) -> Option<alloc::vec::Vec<Insn>> {
+    if raw.is_empty() || !raw.len().is_multiple_of(Insn::LEN) || raw.len() / Insn::LEN > MAX_INSNS {
+        return None;
+    }
+    let mut out = alloc::vec::Vec::new();
+    out.try_reserve_exact(raw.len() / Insn::LEN).ok()?;
+    out.extend(raw.chunks_exact(Insn::LEN).map(Insn::decode));
+    Some(out)
+}
+
+/// Constructeurs utilisés par les tests et les programmes noyau embarqués.
+pub mod asm {
+    use super::*;
+
+    pub const fn mov64_imm(dst: u8, imm: i32) -> Insn {
+        Insn::new(CLASS_ALU64 | ALU_MOV | SRC_K, dst, 0, 0, imm)
+    }
+    pub const fn mov64_reg(dst: u8, src: u8) -> Insn {
+        Insn::new(CLASS_ALU64 | ALU_MOV | SRC_X, dst, src, 0, 0)
+    }
+    pub const fn alu64_imm(op: u8, dst: u8, imm: i32) -> Insn {
+        Insn::new(CLASS_ALU64 | op | SRC_K, dst, 0, 0, imm)
+    }
+    pub const fn alu64_reg(op: u8, dst: u8, src: u8) -> Insn {
+        Insn::new(CLASS_ALU64 | op | SRC_X, dst, src, 0, 0)
+    }
+    pub const fn alu32_imm(op: u8, dst: u8, imm: i32) -> Insn {
+        Insn::new(CLASS_ALU | op | SRC_K, dst, 0, 0, imm)
+    }
+    pub const fn jmp_imm(op: u8, dst: u8, imm: i32, off: i16) -> Insn {
+        Insn::new(CLASS_JMP | op | SRC_K, dst, 0, off, imm)
+    }
+    pub const fn jmp_reg(op: u8, dst: u8, src: u8, off: i16) -> Insn {
+        Insn::new(CLASS_JMP | op | SRC_X, dst, src, off, 0)
+    }
+    pub const fn ja(off: i16) -> Insn {
+        Insn::new(CLASS_JMP | JMP_JA, 0, 0, off, 0)
+    }
+    pub const fn ldx(size: u8, dst: u8, src: u8, off: i16) -> Insn {
+        Insn::new(CLASS_LDX | MODE_MEM | size, dst, src, off, 0)
+    }
+    pub const fn stx(size: u8, dst: u8, src: u8, off: i16) -> Insn {
+        Insn::new(CLASS_STX | MODE_MEM | size, dst, src, off, 0)
+    }
+    pub const fn st_imm(size: u8, dst: u8, off: i16, imm: i32) -> Insn {
+        Insn::new(CLASS_ST | MODE_MEM | size, dst, 0, off, imm)
+    }
+    /// Référence de map : deux slots.
+    pub const fn ld_map(dst: u8, map_id: u32) -> [Insn; 2] {
+        [
+            Insn::new(LD_IMM64, dst, PSEUDO_MAP, 0, map_id as i32),
+            Insn::new(0, 0, 0, 0, 0),
+        ]
+    }
+    pub const fn ld_imm64(dst: u8, imm: u64) -> [Insn; 2] {
+        [
+            Insn::new(LD_IMM64, dst, 0, 0, imm as u32 as i32),
+            Insn::new(0, 0, 0, 0, (imm >> 32) as u32 as i32),
+        ]
+    }
+    pub const fn call(helper: i32) -> Insn {
+        Insn::new(CLASS_JMP | JMP_CALL, 0, 0, 0, helper)
+    }
+    pub const fn exit() -> Insn {
+        Insn::new(CLASS_JMP | JMP_EXIT, 0, 0, 0, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let insn = Insn::new(CLASS_STX | MODE_MEM | SIZE_DW, 10, 3, -8, 0x1234_5678);
+        let raw = insn.encode();
+        assert_eq!(raw[1], 0x3a);
+        assert_eq!(Insn::decode(&raw), insn);
+        assert_eq!(insn.class(), CLASS_STX);
+        assert_eq!(insn.access_size(), 8);
+        assert!(decode_all(&raw[..7]).is_none());
+    }
+}
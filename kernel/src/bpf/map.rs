@@ -0,0 +1,382 @@
+// kernel/src/bpf/map.rs
+//
+// Maps partagées programmes ↔ espace utilisateur.
+//
+// Tout le stockage est alloué à la création et n'est jamais réalloué : un
+// pointeur de valeur rendu à un programme (`lookup_ptr`) reste valide tant que
+// la map existe (le programme en garde une référence `Arc`). Une entrée de
+// hash supprimée pendant qu'un programme la lit devient une tombe, pas de la
+// mémoire libérée.
+//
+// Les programmes s'exécutent dans des points de trace (context switch, IRQ) :
+// la recherche y est sans verrou et les modifications n'attendent jamais
+// (`try_*`, `Busy` si l'espace utilisateur tient le verrou).
+
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+
+use spin::Mutex;
+
+use super::BpfError;
+
+pub const MAX_KEY_SIZE: u32 = 64;
+pub const MAX_VALUE_SIZE: u32 = 4096;
+pub const MAX_ENTRIES: u32 = 65_536;
+/// Plafond du stockage d'une map (octets).
+pub const MAX_MAP_BYTES: usize = 4 * 1024 * 1024;
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapKind {
+    /// Clé `u32` = index dans `[0, max_entries)`, valeurs pré-remplies de zéros.
+    Array = 0,
+    /// Adressage ouvert, sonde linéaire, `max_entries` entrées au plus.
+    Hash = 1,
+}
+
+impl MapKind {
+    pub fn from_u64(v: u64) -> Option<Self> {
+        match v {
+            0 => Some(Self::Array),
+            1 => Some(Self::Hash),
+            _ => None,
+        }
+    }
+}
+
+const SLOT_EMPTY: u8 = 0;
+const SLOT_USED: u8 = 1;
+const SLOT_TOMB: u8 = 2;
+/// En-tête d'un slot de hash (état, aligné sur 8).
+const SLOT_HEADER: usize = 8;
+
+pub struct BpfMap {
+    pub kind: MapKind,
+    pub key_size: u32,
+    pub value_size: u32,
+    pub max_entries: u32,
+    /// `max_entries` pour un tableau, puissance de deux ≥ 2 × `max_entries`
+    /// pour un hash.
+    slots: usize,
+    slot_size: usize,
+    /// Sérialise les modifications structurelles (insertion, suppression).
+    lock: Mutex<u32>,
+    data: UnsafeCell<Vec<u8>>,
+}
+
+// SAFETY: `data` n'est jamais réallouée ; l'état des slots change sous `lock`.
+// Les octets de valeur peuvent être lus et écrits concurremment (même contrat
+// que les maps eBPF : pas de déchirure garantie au-delà d'un mot).
+unsafe impl Sync for BpfMap {}
+unsafe impl Send for BpfMap {}
+
+fn fnv1a(key: &[u8]) -> u64 {
+    key.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+impl BpfMap {
+    pub fn new(
+        kind: MapKind,
+        key_size: u32,
+        value_size: u32,
+        max_entries: u32,
+    ) -> Result<Self, BpfError> {
+        let valid = match kind {
+            MapKind::Array => key_size == 4,
+            MapKind::Hash => key_size > 0 && key_size <= MAX_KEY_SIZE,
+        };
+        if !valid
+            || value_size == 0
+            || value_size > MAX_VALUE_SIZE
+            || max_entries == 0
+            || max_entries > MAX_ENTRIES
+        {
+            return Err(BpfError::InvalidArgument);
+        }
+        let (slots, slot_size) = match kind {
+            MapKind::Array => (max_entries as usize, value_size as usize),
+            MapKind::Hash => (
+                (max_entries as usize * 2).next_power_of_two(),
+                SLOT_HEADER + ((key_size as usize + 7) & !7) + value_size as usize,
+            ),
+        };
+        let bytes = slots
+            .checked_mul(slot_size)
+            .filter(|&b| b <= MAX_MAP_BYTES)
+            .ok_or(BpfError::InvalidArgument)?;
+        let mut data = Vec::new();
+        data.try_reserve_exact(bytes)
+            .map_err(|_| BpfError::NoMemory)?;
+        data.resize(bytes, 0);
+        Ok(Self {
+            kind,
+            key_size,
+            value_size,
+            max_entries,
+            slots,
+            slot_size,
+            lock: Mutex::new(0),
+            data: UnsafeCell::new(data),
+        })
+    }
+
+    #[inline]
+    fn base(&self) -> *mut u8 {
+        // SAFETY: le Vec n'est jamais réalloué ; on ne prend que son pointeur.
+        unsafe { (*self.data.get()).as_mut_ptr() }
+    }
+
+    #[inline]
+    fn slot(&self, idx: usize) -> *mut u8 {
+        // SAFETY: idx < self.slots, vérifié par tous les appelants.
+        unsafe { self.base().add(idx * self.slot_size) }
+    }
+
+    fn key_offset(&self) -> usize {
+        SLOT_HEADER
+    }
+
+    fn value_offset(&self) -> usize {
+        SLOT_HEADER + ((self.key_size as usize + 7) & !7)
+    }
+
+    fn slot_state(&self, idx: usize) -> u8 {
+        // SAFETY: octet d'état du slot idx, dans le stockage.
+        unsafe { core::ptr::read_volatile(self.slot(idx)) }
+    }
+
+    fn slot_key(&self, idx: usize) -> &[u8] {
+        // SAFETY: clé du slot, écrite sous `lock` avant le passage à SLOT_USED.
+        unsafe {
+            core::slice::from_raw_parts(
+                self.slot(idx).add(self.key_offset()),
+                self.key_size as usize,
+            )
+        }
+    }
+
+    /// Slot de hash portant `key`, s'il existe.
+    fn find(&self, key: &[u8]) -> Option<usize> {
+        let mask = self.slots - 1;
+        let mut idx = fnv1a(key) as usize & mask;
+        for _ in 0..self.slots {
+            match self.slot_state(idx) {
+                SLOT_EMPTY => return None,
+                SLOT_USED if self.slot_key(idx) == key => return Some(idx),
+                _ => {}
+            }
+            idx = (idx + 1) & mask;
+        }
+        None
+    }
+
+    /// Adresse de la valeur associée à `key` (utilisée par les programmes).
+    pub fn lookup_ptr(&self, key: &[u8]) -> Option<*mut u8> {
+        match self.kind {
+            MapKind::Array => {
+                let idx = u32::from_le_bytes(key.try_into().ok()?) as usize;
+                (idx < self.slots).then(|| self.slot(idx))
+            }
+            MapKind::Hash => {
+                // Sans verrou : clé écrite avant l'état SLOT_USED ; au pire une
+                // entrée recyclée pendant la sonde est vue, jamais de la
+                // mémoire hors du stockage.
+                let idx = self.find(key)?;
+                // SAFETY: valeur du slot idx, dans le stockage.
+                Some(unsafe { self.slot(idx).add(self.value_offset()) })
+            }
+        }
+    }
+
+    /// Copie la valeur de `key` dans `out` (`value_size` octets).
+    pub fn lookup(&self, key: &[u8], out: &mut [u8]) -> Result<(), BpfError> {
+        let ptr = self.lookup_ptr(key).ok_or(BpfError::NotFound)?;
+        // SAFETY: `value_size` octets de valeur, `out` a la même longueur.
+        unsafe { core::ptr::copy_nonoverlapping(ptr, out.as_mut_ptr(), self.value_size as usize) };
+        Ok(())
+    }
+
+    /// Crée ou remplace la valeur de `key`.
+    pub fn update(&self, key: &[u8], value: &[u8]) -> Result<(), BpfError> {
+        self.update_with(key, value, true)
+    }
+
+    /// `update` depuis un programme : ne prend jamais le verrou en attente.
+    pub fn try_update(&self, key: &[u8], value: &[u8]) -> Result<(), BpfError> {
+        self.update_with(key, value, false)
+    }
+
+    fn guard(&self, wait: bool) -> Result<spin::MutexGuard<'_, u32>, BpfError> {
+        if wait {
+            Ok(self.lock.lock())
+        } else {
+            self.lock.try_lock().ok_or(BpfError::Busy)
+        }
+    }
+
+    fn update_with(&self, key: &[u8], value: &[u8], wait: bool) -> Result<(), BpfError> {
+        if key.len() != self.key_size as usize || value.len() != self.value_size as usize {
+            return Err(BpfError::InvalidArgument);
+        }
+        let dst = match self.kind {
+            MapKind::Array => self.lookup_ptr(key).ok_or(BpfError::InvalidArgument)?,
+            MapKind::Hash => {
+                let mut count = self.guard(wait)?;
+                let idx = match self.find(key) {
+                    Some(idx) => idx,
+                    None => {
+                        if *count >= self.max_entries {
+                            return Err(BpfError::NoSpace);
+                        }
+                        let mask = self.slots - 1;
+                        let mut idx = fnv1a(key) as usize & mask;
+                        while self.slot_state(idx) == SLOT_USED {
+                            idx = (idx + 1) & mask;
+                        }
+                        // SAFETY: slot libre idx ; clé écrite avant l'état
+                        // (les lecteurs sans verrou voient SLOT_USED en dernier).
+                        unsafe {
+                            core::ptr::copy_nonoverlapping(
+                                key.as_ptr(),
+                                self.slot(idx).add(self.key_offset()),
+                                key.len(),
+                            );
+                            core::ptr::write_bytes(
+                                self.slot(idx).add(self.value_offset()),
+                                0,
+                                self.value_size as usize,
+                            );
+                            core::ptr::write_volatile(self.slot(idx), SLOT_USED);
+                        }
+                        *count += 1;
+                        idx
+                    }
+                };
+                // SAFETY: valeur du slot idx.
+                unsafe { self.slot(idx).add(self.value_offset()) }
+            }
+        };
+        // SAFETY: `dst` désigne `value_size` octets de valeur de cette map.
+        unsafe { core::ptr::copy_nonoverlapping(value.as_ptr(), dst, value.len()) };
+        Ok(())
+    }
+
+    pub fn delete(&self, key: &[u8]) -> Result<(), BpfError> {
+        self.delete_with(key, true)
+    }
+
+    /// `delete` depuis un programme : ne prend jamais le verrou en attente.
+    pub fn try_delete(&self, key: &[u8]) -> Result<(), BpfError> {
+        self.delete_with(key, false)
+    }
+
+    fn delete_with(&self, key: &[u8], wait: bool) -> Result<(), BpfError> {
+        if self.kind == MapKind::Array {
+            return Err(BpfError::InvalidArgument);
+        }
+        let mut count = self.guard(wait)?;
+        let idx = self.find(key).ok_or(BpfError::NotFound)?;
+        // SAFETY: octet d'état du slot idx ; la valeur reste en place.
+        unsafe { core::ptr::write_volatile(self.slot(idx), SLOT_TOMB) };
+        *count -= 1;
+        Ok(())
+    }
+
+    /// Clé suivant `key` dans l'ordre de stockage (première si `None`).
+    pub fn next_key(&self, key: Option<&[u8]>, out: &mut [u8]) -> Result<(), BpfError> {
+        match self.kind {
+            MapKind::Array => {
+                let next = match key {
+                    None => 0,
+                    Some(k) => {
+                        let k: [u8; 4] = k.try_into().map_err(|_| BpfError::InvalidArgument)?;
+                        u32::from_le_bytes(k).saturating_add(1)
+                    }
+                };
+                if next >= self.max_entries {
+                    return Err(BpfError::NotFound);
+                }
+                out[..4].copy_from_slice(&next.to_le_bytes());
+                Ok(())
+            }
+            MapKind::Hash => {
+                let _guard = self.lock.lock();
+                let start = match key.and_then(|k| self.find(k)) {
+                    Some(idx) => idx + 1,
+                    None => 0,
+                };
+                let idx = (start..self.slots)
+                    .find(|&i| self.slot_state(i) == SLOT_USED)
+                    .ok_or(BpfError::NotFound)?;
+                out[..self.key_size as usize].copy_from_slice(self.slot_key(idx));
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn array_map_is_zero_filled_and_bounded() {
+        let map = BpfMap::new(MapKind::Array, 4, 8, 4).unwrap();
+        let mut out = [0xffu8; 8];
+        map.lookup(&2u32.to_le_bytes(), &mut out).unwrap();
+        assert_eq!(out, [0; 8]);
+        map.update(&2u32.to_le_bytes(), &7u64.to_le_bytes())
+            .unwrap();
+        map.lookup(&2u32.to_le_bytes(), &mut out).unwrap();
+        assert_eq!(u64::from_le_bytes(out), 7);
+        assert_eq!(
+            map.lookup(&4u32.to_le_bytes(), &mut out),
+            Err(BpfError::NotFound)
+        );
+        assert!(map.delete(&2u32.to_le_bytes()).is_err());
+        assert!(BpfMap::new(MapKind::Array, 8, 8, 4).is_err());
+    }
+
+    #[test]
+    fn hash_map_insert_delete_and_capacity() {
+        let map = BpfMap::new(MapKind::Hash, 4, 4, 2).unwrap();
+        map.update(b"aaaa", &1u32.to_le_bytes()).unwrap();
+        map.update(b"bbbb", &2u32.to_le_bytes()).unwrap();
+        assert_eq!(
+            map.update(b"cccc", &3u32.to_le_bytes()),
+            Err(BpfError::NoSpace)
+        );
+        // Remplacement d'une clé existante : pas de nouvelle entrée.
+        map.update(b"aaaa", &9u32.to_le_bytes()).unwrap();
+        let mut out = [0u8; 4];
+        map.lookup(b"aaaa", &mut out).unwrap();
+        assert_eq!(u32::from_le_bytes(out), 9);
+
+        map.delete(b"aaaa").unwrap();
+        assert_eq!(map.lookup(b"aaaa", &mut out), Err(BpfError::NotFound));
+        map.update(b"cccc", &3u32.to_le_bytes()).unwrap();
+        map.lookup(b"bbbb", &mut out).unwrap();
+        assert_eq!(u32::from_le_bytes(out), 2);
+    }
+
+    #[test]
+    fn hash_map_iteration_visits_every_key() {
+        let map = BpfMap::new(MapKind::Hash, 4, 4, 8).unwrap();
+        for i in 0u32..5 {
+            map.update(&i.to_le_bytes(), &i.to_le_bytes()).unwrap();
+        }
+        let mut seen = 0u32;
+        let mut key = [0u8; 4];
+        let mut prev: Option<[u8; 4]> = None;
+        while map
+            .next_key(prev.as_ref().map(|k| &k[..]), &mut key)
+            .is_ok()
+        {
+            seen |= 1 << u32::from_le_bytes(key);
+            prev = Some(key);
+        }
+        assert_eq!(seen, 0b11111);
+    }
+}
@@ -0,0 +1,450 @@
+// kernel/src/bpf/mod.rs
+//
+// ═══════════════════════════════════════════════════════════════════════════════
+// bpf — programmes vérifiés chargés à chaud (Exo-OS · Transverse)
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Petite machine à registres au format eBPF : l'observabilité et le filtrage
+// réseau se programment depuis l'espace utilisateur, sans recompiler le noyau.
+//
+//   insn      encodage (sous-ensemble d'eBPF : ALU 32/64, sauts, LDX/ST/STX,
+//             LD_IMM64, appels de helpers)
+//   verifier  preuve de sûreté mémoire avant chargement
+//   vm        interpréteur, budget d'instructions par invocation
+//   map       tableaux et tables de hachage partagés avec l'espace utilisateur
+//
+// Points d'attache :
+//   - points de trace (`TraceKind` 1..=6) : contexte `TraceCtx`, en lecture seule,
+//     même sans session de trace active ;
+//   - filtres réseau `NET_INGRESS` / `NET_EGRESS` : contexte `NetCtx`, octets
+//     du paquet via `pkt_load`, verdict dans r0. Aucun appelant pour l'instant :
+//     la pile réseau branchera `net_filter` sur ses chemins RX / TX.
+//
+// RÈGLE BPF-01 : aucun programme n'est chargé sans passer le vérificateur.
+// RÈGLE BPF-02 : un programme ne bloque jamais — points d'attache lus par
+//   `try_read`, maps modifiées par `try_lock`, budget `vm::MAX_STEPS`.
+// RÈGLE BPF-03 : chargement, attache et accès aux maps réservés à root.
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub mod insn;
+pub mod map;
+pub mod verifier;
+pub mod vm;
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use spin::{Mutex, RwLock};
+
+use crate::trace::{TraceKind, TraceRecord, TRACE_NAME_LEN};
+use insn::{Insn, LD_IMM64, PSEUDO_MAP};
+use map::{BpfMap, MapKind};
+use verifier::{VerifierError, VerifyEnv};
+
+/// Maps vivantes au plus.
+pub const BPF_MAX_MAPS: usize = 64;
+/// Programmes chargés au plus.
+pub const BPF_MAX_PROGS: usize = 32;
+/// Maps distinctes référencées par un programme.
+pub const BPF_MAX_PROG_MAPS: usize = 8;
+
+/// Points d'attache réseau (les points de trace reprennent `TraceKind`).
+pub const NET_INGRESS: u32 = 0x100;
+pub const NET_EGRESS: u32 = 0x101;
+
+/// Verdicts d'un filtre réseau (r0) ; toute autre valeur vaut `VERDICT_PASS`.
+pub const VERDICT_PASS: u64 = 0;
+pub const VERDICT_DROP: u64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BpfError {
+    InvalidArgument,
+    /// Map, programme, clé ou point d'attache inexistant.
+    NotFound,
+    /// Registre ou map plein.
+    NoSpace,
+    NoMemory,
+    /// Point d'attache déjà occupé.
+    Exists,
+    /// Verrou tenu ailleurs (chemin programme, qui n'attend jamais).
+    Busy,
+    /// Programme refusé par le vérificateur.
+    Rejected(VerifierError),
+}
+
+/// Type de programme : fixe le contexte, les helpers et les points d'attache.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgType {
+    Trace = 0,
+    NetFilter = 1,
+}
+
+impl ProgType {
+    pub fn from_u64(v: u64) -> Option<Self> {
+        match v {
+            0 => Some(Self::Trace),
+            1 => Some(Self::NetFilter),
+            _ => None,
+        }
+    }
+
+    fn ctx_size(self) -> usize {
+        match self {
+            Self::Trace => core::mem::size_of::<TraceCtx>(),
+            Self::NetFilter => core::mem::size_of::<NetCtx>(),
+        }
+    }
+}
+
+/// Contexte d'un programme de trace (r1), copie d'un `TraceRecord`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TraceCtx {
+    pub ts_ns: u64,
+    pub kind: u32,
+    pub cpu: u32,
+    pub pid: u32,
+    pub tid: u32,
+    pub arg0: u64,
+    pub arg1: u64,
+    pub name: [u8; TRACE_NAME_LEN],
+}
+
+/// Contexte d'un filtre réseau (r1) ; le contenu passe par `pkt_load`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct NetCtx {
+    pub len: u32,
+    pub ifindex: u32,
+}
+
+/// Programme vérifié.
+pub struct Program {
+    pub kind: ProgType,
+    insns: Vec<Insn>,
+    /// Maps référencées, par index local (`LD_IMM64` réécrits au chargement).
+    maps: Vec<Arc<BpfMap>>,
+    /// Invocations, et invocations interrompues par le budget.
+    pub runs: AtomicU64,
+    pub aborted: AtomicU64,
+}
+
+impl Program {
+    fn run(&self, ctx: &[u8], packet: &[u8], now_ns: u64) -> Option<u64> {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        let env = vm::RunEnv {
+            maps: &self.maps,
+            ctx,
+            packet,
+            now_ns,
+        };
+        let ret = vm::run(&self.insns, &env);
+        if ret.is_none() {
+            self.aborted.fetch_add(1, Ordering::Relaxed);
+        }
+        ret
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Registres
+// ─────────────────────────────────────────────────────────────────────────────
+
+static MAPS: Mutex<[Option<Arc<BpfMap>>; BPF_MAX_MAPS]> =
+    Mutex::new([const { None }; BPF_MAX_MAPS]);
+static PROGS: Mutex<[Option<Arc<Program>>; BPF_MAX_PROGS]> =
+    Mutex::new([const { None }; BPF_MAX_PROGS]);
+
+/// Slots d'attache : points de trace 1..=6 puis entrée / sortie réseau.
+const ATTACH_SLOTS: usize = 8;
+static ATTACHED: [RwLock<Option<Arc<Program>>>; ATTACH_SLOTS] =
+    [const { RwLock::new(None) }; ATTACH_SLOTS];
+/// Bit `n` : slot `n` occupé. Seul coût d'un point sans programme.
+static ATTACHED_MASK: AtomicU32 = AtomicU32::new(0);
+
+fn slot_of(point: u32) -> Option<(usize, ProgType)> {
+    match point {
+        1..=6 => Some((point as usize - 1, ProgType::Trace)),
+        NET_INGRESS => Some((6, ProgType::NetFilter)),
+        NET_EGRESS => Some((7, ProgType::NetFilter)),
+        _ => None,
+    }
+}
+
+fn insert<T>(table: &mut [Option<Arc<T>>], value: T) -> Result<u32, BpfError> {
+    let slot = table
+        .iter()
+        .position(Option::is_none)
+        .ok_or(BpfError::NoSpace)?;
+    table[slot] = Some(Arc::new(value));
+    Ok(slot as u32)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Maps
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Crée une map ; retourne son identifiant.
+pub fn map_create(
+    kind: MapKind,
+    key_size: u32,
+    value_size: u32,
+    max_entries: u32,
+) -> Result<u32, BpfError> {
+    let map = BpfMap::new(kind, key_size, value_size, max_entries)?;
+    insert(&mut *MAPS.lock(), map)
+}
+
+pub fn map_get(id: u32) -> Result<Arc<BpfMap>, BpfError> {
+    MAPS.lock()
+        .get(id as usize)
+        .and_then(Clone::clone)
+        .ok_or(BpfError::NotFound)
+}
+
+/// Retire la map du registre ; les programmes qui l'utilisent la gardent.
+pub fn map_free(id: u32) -> Result<(), BpfError> {
+    MAPS.lock()
+        .get_mut(id as usize)
+        .and_then(Option::take)
+        .map(drop)
+        .ok_or(BpfError::NotFound)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Programmes
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Vérifie et enregistre un programme ; retourne son identifiant.
+///
+/// Les `LD_IMM64` de map portent un identifiant global de map, remplacé ici
+/// par un index local avant vérification.
+pub fn prog_load(kind: ProgType, mut insns: Vec<Insn>) -> Result<u32, BpfError> {
+    let mut maps: Vec<Arc<BpfMap>> = Vec::new();
+    for insn in insns.iter_mut() {
+        if insn.op != LD_IMM64 || insn.src != PSEUDO_MAP {
+            continue;
+        }
+        let map = map_get(insn.imm as u32)?;
+        let local = match maps.iter().position(|m| Arc::ptr_eq(m, &map)) {
+            Some(idx) => idx,
+            None if maps.len() < BPF_MAX_PROG_MAPS => {
+                maps.try_reserve(1).map_err(|_| BpfError::NoMemory)?;
+                maps.push(map);
+                maps.len() - 1
+            }
+            None => return Err(BpfError::NoSpace),
+        };
+        insn.imm = local as i32;
+    }
+
+    let sizes: Vec<(u32, u32)> = maps.iter().map(|m| (m.key_size, m.value_size)).collect();
+    let env = VerifyEnv {
+        ctx_size: kind.ctx_size(),
+        packet: kind == ProgType::NetFilter,
+        maps: &sizes,
+    };
+    verifier::verify(&insns, &env).map_err(BpfError::Rejected)?;
+
+    let prog = Program {
+        kind,
+        insns,
+        maps,
+        runs: AtomicU64::new(0),
+        aborted: AtomicU64::new(0),
+    };
+    insert(&mut *PROGS.lock(), prog)
+}
+
+/// Retire le programme du registre ; un programme attaché le reste.
+pub fn prog_unload(id: u32) -> Result<(), BpfError> {
+    PROGS
+        .lock()
+        .get_mut(id as usize)
+        .and_then(Option::take)
+        .map(drop)
+        .ok_or(BpfError::NotFound)
+}
+
+/// Attache le programme `id` au point `point` (libre, type compatible).
+pub fn attach(id: u32, point: u32) -> Result<(), BpfError> {
+    let (slot, kind) = slot_of(point).ok_or(BpfError::InvalidArgument)?;
+    let prog = PROGS
+        .lock()
+        .get(id as usize)
+        .and_then(Clone::clone)
+        .ok_or(BpfError::NotFound)?;
+    if prog.kind != kind {
+        return Err(BpfError::InvalidArgument);
+    }
+    let mut attached = ATTACHED[slot].write();
+    if attached.is_some() {
+        return Err(BpfError::Exists);
+    }
+    *attached = Some(prog);
+    ATTACHED_MASK.fetch_or(1 << slot, Ordering::Release);
+    Ok(())
+}
+
+pub fn detach(point: u32) -> Result<(), BpfError> {
+    let (slot, _) = slot_of(point).ok_or(BpfError::InvalidArgument)?;
+    let mut attached = ATTACHED[slot].write();
+    ATTACHED_MASK.fetch_and(!(1 << slot), Ordering::Release);
+    attached.take().map(drop).ok_or(BpfError::NotFound)
+}
+
+/// Exécute `f` sur le programme attaché au slot, sans jamais attendre
+/// (BPF-02). Le verrou de lecture est tenu pendant l'exécution : `detach`
+/// attend la fin des exécutions en cours et la dernière référence n'est
+/// jamais rendue depuis un point d'attache.
+#[inline]
+fn with_attached<R>(slot: usize, f: impl FnOnce(&Program) -> R) -> Option<R> {
+    if ATTACHED_MASK.load(Ordering::Relaxed) & (1 << slot) == 0 {
+        return None;
+    }
+    let attached = ATTACHED[slot].try_read()?;
+    attached.as_deref().map(f)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Points d'attache
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Un programme est-il attaché au point de trace `kind` ?
+#[inline(always)]
+pub fn trace_probed(kind: TraceKind) -> bool {
+    ATTACHED_MASK.load(Ordering::Relaxed) & (1 << (kind as u32 - 1)) != 0
+}
+
+/// Exécute le programme attaché au point de trace de `record`.
+pub fn run_trace(record: &TraceRecord) {
+    let ctx = TraceCtx {
+        ts_ns: record.ts_ns,
+        kind: record.kind as u32,
+        cpu: record.cpu as u32,
+        pid: record.pid,
+        tid: record.tid,
+        arg0: record.arg0,
+        arg1: record.arg1,
+        name: record.name,
+    };
+    // SAFETY: `TraceCtx` est repr(C) sans octet de remplissage.
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            &ctx as *const TraceCtx as *const u8,
+            core::mem::size_of::<TraceCtx>(),
+        )
+    };
+    with_attached(record.kind as usize - 1, |prog| {
+        prog.run(bytes, &[], record.ts_ns)
+    });
+}
+
+/// Filtre réseau : verdict du programme attaché à `point` pour `packet`.
+/// `VERDICT_PASS` sans programme ou si le budget est épuisé.
+pub fn net_filter(point: u32, ifindex: u32, packet: &[u8]) -> u64 {
+    let Some((slot, ProgType::NetFilter)) = slot_of(point) else {
+        return VERDICT_PASS;
+    };
+    let ctx = NetCtx {
+        len: packet.len() as u32,
+        ifindex,
+    };
+    // SAFETY: `NetCtx` est repr(C) sans octet de remplissage.
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            &ctx as *const NetCtx as *const u8,
+            core::mem::size_of::<NetCtx>(),
+        )
+    };
+    match with_attached(slot, |prog| prog.run(bytes, packet, now_ns())) {
+        Some(Some(VERDICT_DROP)) => VERDICT_DROP,
+        _ => VERDICT_PASS,
+    }
+}
+
+#[cfg(not(test))]
+#[inline]
+fn now_ns() -> u64 {
+    crate::scheduler::timer::clock::monotonic_ns()
+}
+
+#[cfg(test)]
+#[inline]
+fn now_ns() -> u64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::insn::asm::*;
+    use super::insn::*;
+    use super::*;
+    use std::vec;
+
+    const _: () = assert!(core::mem::size_of::<TraceCtx>() == 56);
+
+    #[test]
+    fn net_filter_drops_matching_packets_and_counts_in_map() {
+        // Compte les paquets dont l'octet 0 vaut 0x45, et les rejette.
+        let map = map_create(MapKind::Array, 4, 8, 1).unwrap();
+        let [l0, l1] = ld_map(1, map);
+        let prog = vec![
+            mov64_imm(1, 0),
+            mov64_reg(2, 10),
+            alu64_imm(ALU_ADD, 2, -8),
+            mov64_imm(3, 1),
+            call(vm::HELPER_PKT_LOAD),
+            ldx(SIZE_B, 6, 10, -8),
+            mov64_imm(0, VERDICT_PASS as i32),
+            jmp_imm(JMP_JNE, 6, 0x45, 10),
+            st_imm(SIZE_W, 10, -4, 0),
+            l0,
+            l1,
+            mov64_reg(2, 10),
+            alu64_imm(ALU_ADD, 2, -4),
+            call(vm::HELPER_MAP_LOOKUP),
+            jmp_imm(JMP_JEQ, 0, 0, 2),
+            mov64_imm(1, 1),
+            stx(SIZE_DW, 0, 1, 0),
+            mov64_imm(0, VERDICT_DROP as i32),
+            exit(),
+        ];
+        let id = prog_load(ProgType::NetFilter, prog).unwrap();
+        assert_eq!(attach(id, 7), Err(BpfError::InvalidArgument));
+        attach(id, NET_INGRESS).unwrap();
+        assert_eq!(attach(id, NET_INGRESS), Err(BpfError::Exists));
+
+        assert_eq!(net_filter(NET_INGRESS, 1, &[0x45, 0]), VERDICT_DROP);
+        assert_eq!(net_filter(NET_INGRESS, 1, &[0x60, 0]), VERDICT_PASS);
+        assert_eq!(net_filter(NET_EGRESS, 1, &[0x45, 0]), VERDICT_PASS);
+        let mut out = [0u8; 8];
+        map_get(map)
+            .unwrap()
+            .lookup(&0u32.to_le_bytes(), &mut out)
+            .unwrap();
+        assert_eq!(u64::from_le_bytes(out), 1);
+
+        detach(NET_INGRESS).unwrap();
+        assert_eq!(net_filter(NET_INGRESS, 1, &[0x45, 0]), VERDICT_PASS);
+        prog_unload(id).unwrap();
+        map_free(map).unwrap();
+    }
+
+    #[test]
+    fn load_rejects_unverified_and_unknown_maps() {
+        let bad = vec![mov64_reg(0, 1), exit()];
+        assert!(matches!(
+            prog_load(ProgType::Trace, bad),
+            Err(BpfError::Rejected(VerifierError { pc: 1, .. }))
+        ));
+        let [l0, l1] = ld_map(1, BPF_MAX_MAPS as u32);
+        let unknown = vec![l0, l1, mov64_imm(0, 0), exit()];
+        assert_eq!(
+            prog_load(ProgType::Trace, unknown).err(),
+            Some(BpfError::NotFound)
+        );
+    }
+}
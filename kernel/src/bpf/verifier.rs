@@ -0,0 +1,511 @@
+// kernel/src/bpf/verifier.rs
+//
+// Vérificateur : interprétation abstraite de tous les chemins avant le
+// chargement d'un programme.
+//
+// Chaque registre porte un type (scalaire, pointeur de contexte, de pile, de
+// valeur de map…) avec un décalage constant. Aux jonctions de chemins les états
+// sont fusionnés ; le treillis est de hauteur finie, donc l'analyse termine même
+// en présence de boucles. La terminaison du programme lui-même n'est pas
+// prouvée ici : elle est garantie à l'exécution par `vm::MAX_STEPS`.
+//
+// Garanties pour un programme accepté :
+//   - tout accès mémoire tombe dans le contexte (lecture seule), la pile ou une
+//     valeur de map dont la nullité a été testée ;
+//   - aucun registre n'est lu avant d'avoir été écrit ;
+//   - aucun pointeur noyau ne sort du programme (retour, map, comparaison) ;
+//   - seuls les helpers autorisés pour le type de programme sont appelés.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::insn::*;
+use super::vm::{
+    self, HELPER_KTIME_NS, HELPER_MAP_DELETE, HELPER_MAP_LOOKUP, HELPER_MAP_UPDATE, HELPER_PKT_LOAD,
+};
+
+/// États analysés au plus (garde-fou, le treillis borne déjà l'analyse).
+const MAX_VISITS: usize = 1 << 20;
+/// Décalage maximal d'un pointeur, pour que les calculs de bornes ne
+/// débordent jamais.
+const MAX_PTR_OFF: i64 = 1 << 29;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifierError {
+    /// Instruction fautive.
+    pub pc: usize,
+    pub reason: &'static str,
+}
+
+/// Ce que le programme a le droit de voir.
+pub struct VerifyEnv<'a> {
+    /// Taille du contexte pointé par r1 à l'entrée.
+    pub ctx_size: usize,
+    /// Helper `pkt_load` autorisé (filtres réseau).
+    pub packet: bool,
+    /// `(key_size, value_size)` des maps, par index local.
+    pub maps: &'a [(u32, u32)],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Ty {
+    /// Non écrit, ou fusion de types incompatibles : illisible.
+    Uninit,
+    Const(u64),
+    Scalar,
+    Ctx(i64),
+    /// Décalage relatif à r10 (sommet de pile).
+    Stack(i64),
+    MapRef(u32),
+    MapValue(u32, i64),
+    /// Retour de `map_lookup`, à tester contre 0 avant usage.
+    MapValueOrNull(u32),
+}
+
+impl Ty {
+    fn is_scalar(self) -> bool {
+        matches!(self, Ty::Const(_) | Ty::Scalar)
+    }
+
+    fn join(self, other: Ty) -> Ty {
+        if self == other {
+            self
+        } else if self.is_scalar() && other.is_scalar() {
+            Ty::Scalar
+        } else {
+            Ty::Uninit
+        }
+    }
+
+    fn offset(self, delta: i64) -> Result<Ty, &'static str> {
+        let add = |off: i64| {
+            off.checked_add(delta)
+                .filter(|o| o.abs() <= MAX_PTR_OFF)
+                .ok_or("décalage de pointeur hors limites")
+        };
+        match self {
+            Ty::Ctx(o) => Ok(Ty::Ctx(add(o)?)),
+            Ty::Stack(o) => Ok(Ty::Stack(add(o)?)),
+            Ty::MapValue(m, o) => Ok(Ty::MapValue(m, add(o)?)),
+            _ => Err("arithmétique de pointeur interdite"),
+        }
+    }
+}
+
+type State = [Ty; REG_COUNT];
+
+/// Vérifie `insns` ; les références de map (`LD_IMM64`, `src == PSEUDO_MAP`)
+/// portent déjà un index local dans `env.maps`.
+pub fn verify(insns: &[Insn], env: &VerifyEnv<'_>) -> Result<(), VerifierError> {
+    let n = insns.len();
+    if n == 0 || n > MAX_INSNS {
+        return Err(VerifierError {
+            pc: 0,
+            reason: "taille de programme invalide",
+        });
+    }
+
+    // Second slot de chaque LD_IMM64 : jamais une cible de saut.
+    let mut second = vec![false; n];
+    let mut pc = 0;
+    while pc < n {
+        if insns[pc].op == LD_IMM64 {
+            let next = insns.get(pc + 1).copied();
+            if !next.is_some_and(|i| i.op == 0 && i.dst == 0 && i.src == 0 && i.off == 0) {
+                return Err(VerifierError {
+                    pc,
+                    reason: "LD_IMM64 incomplet",
+                });
+            }
+            second[pc + 1] = true;
+            pc += 2;
+        } else {
+            pc += 1;
+        }
+    }
+
+    let mut init = [Ty::Uninit; REG_COUNT];
+    init[1] = Ty::Ctx(0);
+    init[REG_FP as usize] = Ty::Stack(0);
+
+    let mut states: Vec<Option<State>> = vec![None; n];
+    states[0] = Some(init);
+    let mut work = vec![0usize];
+    let mut visits = 0usize;
+
+    while let Some(pc) = work.pop() {
+        visits += 1;
+        if visits > MAX_VISITS {
+            return Err(VerifierError {
+                pc,
+                reason: "programme trop complexe",
+            });
+        }
+        let Some(state) = states[pc] else { continue };
+        let succ =
+            step(insns, &second, pc, state, env).map_err(|reason| VerifierError { pc, reason })?;
+        for (target, next) in succ.into_iter().flatten() {
+            let merged = match states[target] {
+                None => next,
+                Some(old) => {
+                    let mut merged = old;
+                    for (m, n) in merged.iter_mut().zip(next.iter()) {
+                        *m = m.join(*n);
+                    }
+                    if merged == old {
+                        continue;
+                    }
+                    merged
+                }
+            };
+            states[target] = Some(merged);
+            work.push(target);
+        }
+    }
+    Ok(())
+}
+
+type Succ = [Option<(usize, State)>; 2];
+
+fn step(
+    insns: &[Insn],
+    second: &[bool],
+    pc: usize,
+    mut st: State,
+    env: &VerifyEnv<'_>,
+) -> Result<Succ, &'static str> {
+    let insn = insns[pc];
+    let target = |delta: i64| -> Result<usize, &'static str> {
+        let t = pc as i64 + 1 + delta;
+        if t < 0 || t as usize >= insns.len() || second[t as usize] {
+            return Err("saut hors du programme");
+        }
+        Ok(t as usize)
+    };
+    let imm = insn.imm as i64 as u64;
+
+    match insn.class() {
+        CLASS_ALU64 | CLASS_ALU => {
+            let code = insn.code();
+            if code > ALU_ARSH {
+                return Err("opération ALU invalide");
+            }
+            let dst = writable(insn.dst)?;
+            let is64 = insn.class() == CLASS_ALU64;
+            let src = if code == ALU_NEG {
+                Ty::Const(0)
+            } else if insn.source() == SRC_X {
+                read(&st, insn.src)?
+            } else {
+                Ty::Const(imm)
+            };
+            st[dst] = if code == ALU_MOV {
+                match src {
+                    _ if is64 => src,
+                    Ty::Const(c) => Ty::Const(vm::alu32(ALU_MOV, 0, c)),
+                    Ty::Scalar => Ty::Scalar,
+                    _ => return Err("troncature de pointeur"),
+                }
+            } else {
+                match (read(&st, insn.dst)?, src) {
+                    (Ty::Const(a), Ty::Const(b)) if is64 => Ty::Const(vm::alu64(code, a, b)),
+                    (Ty::Const(a), Ty::Const(b)) => Ty::Const(vm::alu32(code, a, b)),
+                    (a, b) if a.is_scalar() && b.is_scalar() => Ty::Scalar,
+                    (ptr, Ty::Const(c)) if is64 && code == ALU_ADD => ptr.offset(c as i64)?,
+                    (ptr, Ty::Const(c)) if is64 && code == ALU_SUB => {
+                        ptr.offset((c as i64).checked_neg().ok_or("décalage invalide")?)?
+                    }
+                    _ => return Err("arithmétique de pointeur interdite"),
+                }
+            };
+            Ok([Some((target(0)?, st)), None])
+        }
+        CLASS_LDX => {
+            if insn.mode() != MODE_MEM {
+                return Err("mode de chargement invalide");
+            }
+            let dst = writable(insn.dst)?;
+            let base = read(&st, insn.src)?;
+            access(base, insn.off as i64, insn.access_size(), false, env)?;
+            st[dst] = Ty::Scalar;
+            Ok([Some((target(0)?, st)), None])
+        }
+        CLASS_ST | CLASS_STX => {
+            if insn.mode() != MODE_MEM {
+                return Err("mode d'écriture invalide");
+            }
+            let base = read(&st, insn.dst)?;
+            if insn.class() == CLASS_STX && !read(&st, insn.src)?.is_scalar() {
+                return Err("écriture d'un pointeur en mémoire");
+            }
+            access(base, insn.off as i64, insn.access_size(), true, env)?;
+            Ok([Some((target(0)?, st)), None])
+        }
+        CLASS_LD => {
+            if insn.op != LD_IMM64 {
+                return Err("instruction de chargement invalide");
+            }
+            let dst = writable(insn.dst)?;
+            st[dst] = match insn.src {
+                0 => {
+                    let high = insns[pc + 1].imm as u32 as u64;
+                    Ty::Const((high << 32) | insn.imm as u32 as u64)
+                }
+                PSEUDO_MAP if (insn.imm as u32 as usize) < env.maps.len() => {
+                    Ty::MapRef(insn.imm as u32)
+                }
+                PSEUDO_MAP => return Err("map inconnue"),
+                _ => return Err("LD_IMM64 invalide"),
+            };
+            Ok([Some((target(1)?, st)), None])
+        }
+        CLASS_JMP => match insn.code() {
+            JMP_JA => Ok([Some((target(insn.off as i64)?, st)), None]),
+            JMP_EXIT => {
+                if !read(&st, 0)?.is_scalar() {
+                    return Err("r0 doit être un scalaire à la sortie");
+                }
+                Ok([None, None])
+            }
+            JMP_CALL => {
+                st[0] = helper(insn.imm, &st, env)?;
+                st[1..6].fill(Ty::Uninit);
+                Ok([Some((target(0)?, st)), None])
+            }
+            code if code > JMP_JSLE => Err("opération de saut invalide"),
+            code => {
+                let dst = read(&st, insn.dst)?;
+                let src = if insn.source() == SRC_X {
+                    read(&st, insn.src)?
+                } else {
+                    Ty::Const(imm)
+                };
+                let taken = target(insn.off as i64)?;
+                let fall = target(0)?;
+                match (dst, src) {
+                    (Ty::Const(a), Ty::Const(b)) => {
+                        let next = if vm::jump_taken(code, a, b) {
+                            taken
+                        } else {
+                            fall
+                        };
+                        Ok([Some((next, st)), None])
+                    }
+                    (a, b) if a.is_scalar() && b.is_scalar() => {
+                        Ok([Some((taken, st)), Some((fall, st))])
+                    }
+                    (Ty::MapValueOrNull(m), Ty::Const(0))
+                        if insn.source() == SRC_K && (code == JMP_JEQ || code == JMP_JNE) =>
+                    {
+                        let reg = insn.dst as usize;
+                        let (mut null, mut valid) = (st, st);
+                        null[reg] = Ty::Const(0);
+                        valid[reg] = Ty::MapValue(m, 0);
+                        if code == JMP_JEQ {
+                            Ok([Some((taken, null)), Some((fall, valid))])
+                        } else {
+                            Ok([Some((taken, valid)), Some((fall, null))])
+                        }
+                    }
+                    _ => Err("comparaison de pointeur interdite"),
+                }
+            }
+        },
+        _ => Err("classe d'instruction invalide"),
+    }
+}
+
+fn read(st: &State, reg: u8) -> Result<Ty, &'static str> {
+    match st.get(reg as usize) {
+        None => Err("registre invalide"),
+        Some(Ty::Uninit) => Err("lecture d'un registre non initialisé"),
+        Some(ty) => Ok(*ty),
+    }
+}
+
+fn writable(reg: u8) -> Result<usize, &'static str> {
+    match reg {
+        REG_FP => Err("r10 est en lecture seule"),
+        r if (r as usize) < REG_COUNT => Ok(r as usize),
+        _ => Err("registre invalide"),
+    }
+}
+
+/// Accès de `size` octets à `base + off`.
+fn access(
+    base: Ty,
+    off: i64,
+    size: usize,
+    write: bool,
+    env: &VerifyEnv<'_>,
+) -> Result<(), &'static str> {
+    let (start, lo, hi) = match base {
+        Ty::Ctx(_) if write => return Err("contexte en lecture seule"),
+        Ty::Ctx(o) => (o + off, 0, env.ctx_size as i64),
+        Ty::Stack(o) => (o + off, -(STACK_SIZE as i64), 0),
+        Ty::MapValue(m, o) => (o + off, 0, env.maps[m as usize].1 as i64),
+        Ty::MapValueOrNull(_) => return Err("valeur de map non testée contre 0"),
+        _ => return Err("accès mémoire hors pointeur"),
+    };
+    if start < lo || start + size as i64 > hi {
+        return Err("accès mémoire hors limites");
+    }
+    Ok(())
+}
+
+/// `(index, key_size, value_size)` de la map référencée par `ty`.
+fn map_arg(ty: Ty, env: &VerifyEnv<'_>) -> Result<(u32, u32, u32), &'static str> {
+    match ty {
+        Ty::MapRef(m) => {
+            let (key, value) = env.maps[m as usize];
+            Ok((m, key, value))
+        }
+        _ => Err("r1 doit être une référence de map"),
+    }
+}
+
+/// Type de r0 après l'appel du helper `id`.
+fn helper(id: i32, st: &State, env: &VerifyEnv<'_>) -> Result<Ty, &'static str> {
+    let arg = |reg: u8| read(st, reg);
+    match id {
+        HELPER_MAP_LOOKUP => {
+            let (m, key, _) = map_arg(arg(1)?, env)?;
+            access(arg(2)?, 0, key as usize, false, env)?;
+            Ok(Ty::MapValueOrNull(m))
+        }
+        HELPER_MAP_UPDATE => {
+            let (_, key, value) = map_arg(arg(1)?, env)?;
+            access(arg(2)?, 0, key as usize, false, env)?;
+            access(arg(3)?, 0, value as usize, false, env)?;
+            Ok(Ty::Scalar)
+        }
+        HELPER_MAP_DELETE => {
+            let (_, key, _) = map_arg(arg(1)?, env)?;
+            access(arg(2)?, 0, key as usize, false, env)?;
+            Ok(Ty::Scalar)
+        }
+        HELPER_KTIME_NS => Ok(Ty::Scalar),
+        HELPER_PKT_LOAD if env.packet => {
+            if !arg(1)?.is_scalar() {
+                return Err("décalage de paquet non scalaire");
+            }
+            let len = match arg(3)? {
+                Ty::Const(len) if (1..=STACK_SIZE as u64).contains(&len) => len as usize,
+                _ => return Err("longueur de pkt_load non constante"),
+            };
+            access(arg(2)?, 0, len, true, env)?;
+            Ok(Ty::Scalar)
+        }
+        _ => Err("helper inconnu ou interdit"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bpf::insn::asm::*;
+
+    const TRACE: VerifyEnv<'static> = VerifyEnv {
+        ctx_size: 16,
+        packet: false,
+        maps: &[(4, 8)],
+    };
+
+    fn reason(prog: &[Insn], env: &VerifyEnv<'_>) -> &'static str {
+        verify(prog, env).unwrap_err().reason
+    }
+
+    #[test]
+    fn accepts_loop_and_checked_map_access() {
+        let [l0, l1] = ld_map(1, 0);
+        let prog = [
+            ldx(SIZE_W, 6, 1, 0),
+            mov64_imm(2, 0),
+            alu64_reg(ALU_ADD, 6, 2),
+            alu64_imm(ALU_ADD, 2, 1),
+            jmp_imm(JMP_JLT, 2, 10, -3),
+            stx(SIZE_W, 10, 6, -4),
+            l0,
+            l1,
+            mov64_reg(2, 10),
+            alu64_imm(ALU_ADD, 2, -4),
+            call(HELPER_MAP_LOOKUP),
+            jmp_imm(JMP_JEQ, 0, 0, 2),
+            ldx(SIZE_DW, 0, 0, 0),
+            exit(),
+            mov64_imm(0, 0),
+            exit(),
+        ];
+        assert_eq!(verify(&prog, &TRACE), Ok(()));
+    }
+
+    #[test]
+    fn rejects_unsafe_memory_access() {
+        // Pile hors limites, contexte inscrit, contexte dépassé.
+        assert_eq!(
+            reason(&[st_imm(SIZE_DW, 10, -516, 0), exit()], &TRACE),
+            "accès mémoire hors limites"
+        );
+        assert_eq!(
+            reason(&[st_imm(SIZE_W, 1, 0, 0), exit()], &TRACE),
+            "contexte en lecture seule"
+        );
+        assert_eq!(
+            reason(&[ldx(SIZE_DW, 0, 1, 12), exit()], &TRACE),
+            "accès mémoire hors limites"
+        );
+        // Valeur de map utilisée sans test de nullité.
+        let [l0, l1] = ld_map(1, 0);
+        let prog = [
+            st_imm(SIZE_W, 10, -4, 0),
+            l0,
+            l1,
+            mov64_reg(2, 10),
+            alu64_imm(ALU_ADD, 2, -4),
+            call(HELPER_MAP_LOOKUP),
+            ldx(SIZE_DW, 0, 0, 0),
+            exit(),
+        ];
+        assert_eq!(reason(&prog, &TRACE), "valeur de map non testée contre 0");
+    }
+
+    #[test]
+    fn rejects_leaks_and_malformed_programs() {
+        assert_eq!(
+            reason(&[mov64_reg(0, 1), exit()], &TRACE),
+            "r0 doit être un scalaire à la sortie"
+        );
+        assert_eq!(
+            reason(&[stx(SIZE_DW, 10, 1, -8), mov64_imm(0, 0), exit()], &TRACE),
+            "écriture d'un pointeur en mémoire"
+        );
+        assert_eq!(
+            reason(&[exit()], &TRACE),
+            "lecture d'un registre non initialisé"
+        );
+        assert_eq!(
+            reason(&[mov64_imm(10, 0), exit()], &TRACE),
+            "r10 est en lecture seule"
+        );
+        assert_eq!(reason(&[ja(5), exit()], &TRACE), "saut hors du programme");
+        assert_eq!(reason(&[mov64_imm(0, 0)], &TRACE), "saut hors du programme");
+        assert_eq!(
+            reason(&[call(HELPER_PKT_LOAD), exit()], &TRACE),
+            "helper inconnu ou interdit"
+        );
+        let [l0, _] = ld_imm64(0, 1);
+        assert_eq!(reason(&[l0, exit()], &TRACE), "LD_IMM64 incomplet");
+    }
+
+    #[test]
+    fn constant_branches_prune_dead_paths() {
+        // Chemin mort : l'accès hors limites n'est jamais atteint.
+        let prog = [
+            mov64_imm(0, 1),
+            jmp_imm(JMP_JEQ, 0, 1, 1),
+            ldx(SIZE_DW, 0, 1, 100),
+            exit(),
+        ];
+        assert_eq!(verify(&prog, &TRACE), Ok(()));
+    }
+}
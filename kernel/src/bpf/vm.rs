@@ -0,0 +1,346 @@
+// kernel/src/bpf/vm.rs
+//
+// Interpréteur des programmes vérifiés.
+//
+// Les registres portent des adresses réelles (contexte, pile, valeurs de map) :
+// le vérificateur a prouvé chaque accès, l'interpréteur ne refait aucun
+// contrôle de bornes. Seule la terminaison est garantie ici, par un budget
+// d'instructions exécutées (`MAX_STEPS`) — les boucles sont autorisées.
+
+use alloc::sync::Arc;
+
+use super::insn::*;
+use super::map::BpfMap;
+
+/// Instructions exécutées au plus par invocation.
+pub const MAX_STEPS: u32 = 1 << 16;
+
+pub const HELPER_MAP_LOOKUP: i32 = 1;
+pub const HELPER_MAP_UPDATE: i32 = 2;
+pub const HELPER_MAP_DELETE: i32 = 3;
+pub const HELPER_KTIME_NS: i32 = 4;
+/// `pkt_load(offset, dst, len)` : copie d'octets du paquet (filtres réseau).
+pub const HELPER_PKT_LOAD: i32 = 5;
+
+/// Opération ALU 64 bits (division par zéro → 0, modulo par zéro → `dst`).
+pub fn alu64(code: u8, dst: u64, src: u64) -> u64 {
+    match code {
+        ALU_ADD => dst.wrapping_add(src),
+        ALU_SUB => dst.wrapping_sub(src),
+        ALU_MUL => dst.wrapping_mul(src),
+        ALU_DIV => dst.checked_div(src).unwrap_or(0),
+        ALU_OR => dst | src,
+        ALU_AND => dst & src,
+        ALU_LSH => dst << (src & 63),
+        ALU_RSH => dst >> (src & 63),
+        ALU_NEG => dst.wrapping_neg(),
+        ALU_MOD => dst.checked_rem(src).unwrap_or(dst),
+        ALU_XOR => dst ^ src,
+        ALU_MOV => src,
+        _ => ((dst as i64) >> (src & 63)) as u64,
+    }
+}
+
+/// Opération ALU 32 bits, résultat étendu par zéros.
+pub fn alu32(code: u8, dst: u64, src: u64) -> u64 {
+    let (d, s) = (dst as u32, src as u32);
+    let r = match code {
+        ALU_ADD => d.wrapping_add(s),
+        ALU_SUB => d.wrapping_sub(s),
+        ALU_MUL => d.wrapping_mul(s),
+        ALU_DIV => d.checked_div(s).unwrap_or(0),
+        ALU_OR => d | s,
+        ALU_AND => d & s,
+        ALU_LSH => d << (s & 31),
+        ALU_RSH => d >> (s & 31),
+        ALU_NEG => d.wrapping_neg(),
+        ALU_MOD => d.checked_rem(s).unwrap_or(d),
+        ALU_XOR => d ^ s,
+        ALU_MOV => s,
+        _ => ((d as i32) >> (s & 31)) as u32,
+    };
+    r as u64
+}
+
+/// Condition d'un saut conditionnel.
+pub fn jump_taken(code: u8, dst: u64, src: u64) -> bool {
+    match code {
+        JMP_JEQ => dst == src,
+        JMP_JNE => dst != src,
+        JMP_JGT => dst > src,
+        JMP_JGE => dst >= src,
+        JMP_JLT => dst < src,
+        JMP_JLE => dst <= src,
+        JMP_JSET => dst & src != 0,
+        JMP_JSGT => (dst as i64) > (src as i64),
+        JMP_JSGE => (dst as i64) >= (src as i64),
+        JMP_JSLT => (dst as i64) < (src as i64),
+        _ => (dst as i64) <= (src as i64),
+    }
+}
+
+/// Environnement d'exécution d'une invocation.
+pub struct RunEnv<'a> {
+    pub maps: &'a [Arc<BpfMap>],
+    /// Contexte en lecture seule (r1).
+    pub ctx: &'a [u8],
+    /// Paquet accessible par `pkt_load` (filtres réseau).
+    pub packet: &'a [u8],
+    pub now_ns: u64,
+}
+
+/// Exécute `insns` (déjà vérifié). `None` si le budget est épuisé.
+pub fn run(insns: &[Insn], env: &RunEnv<'_>) -> Option<u64> {
+    let mut stack = [0u64; STACK_SIZE / 8];
+    let mut regs = [0u64; REG_COUNT];
+    regs[1] = env.ctx.as_ptr() as u64;
+    regs[REG_FP as usize] = stack.as_mut_ptr() as u64 + STACK_SIZE as u64;
+
+    let mut pc = 0usize;
+    for _ in 0..MAX_STEPS {
+        let insn = insns[pc];
+        let dst = insn.dst as usize;
+        let imm = insn.imm as i64 as u64;
+        pc += 1;
+        match insn.class() {
+            CLASS_ALU64 | CLASS_ALU => {
+                let src = if insn.source() == SRC_X {
+                    regs[insn.src as usize]
+                } else {
+                    imm
+                };
+                regs[dst] = if insn.class() == CLASS_ALU64 {
+                    alu64(insn.code(), regs[dst], src)
+                } else {
+                    alu32(insn.code(), regs[dst], src)
+                };
+            }
+            CLASS_JMP => match insn.code() {
+                JMP_JA => pc = (pc as i64 + insn.off as i64) as usize,
+                JMP_EXIT => return Some(regs[0]),
+                JMP_CALL => {
+                    regs[0] = call_helper(insn.imm, &regs, env);
+                    regs[1..6].fill(0);
+                }
+                code => {
+                    let src = if insn.source() == SRC_X {
+                        regs[insn.src as usize]
+                    } else {
+                        imm
+                    };
+                    if jump_taken(code, regs[dst], src) {
+                        pc = (pc as i64 + insn.off as i64) as usize;
+                    }
+                }
+            },
+            CLASS_LDX => {
+                let addr = regs[insn.src as usize].wrapping_add(insn.off as i64 as u64);
+                // SAFETY: accès prouvé dans les bornes par le vérificateur.
+                regs[dst] = unsafe { load(addr, insn.access_size()) };
+            }
+            CLASS_STX | CLASS_ST => {
+                let addr = regs[dst].wrapping_add(insn.off as i64 as u64);
+                let value = if insn.class() == CLASS_STX {
+                    regs[insn.src as usize]
+                } else {
+                    imm
+                };
+                // SAFETY: accès prouvé dans les bornes et inscriptible.
+                unsafe { store(addr, insn.access_size(), value) };
+            }
+            _ => {
+                // LD_IMM64 : constante ou index local de map.
+                let high = insns[pc].imm as u32 as u64;
+                regs[dst] = if insn.src == PSEUDO_MAP {
+                    insn.imm as u32 as u64
+                } else {
+                    (high << 32) | insn.imm as u32 as u64
+                };
+                pc += 1;
+            }
+        }
+    }
+    None
+}
+
+unsafe fn load(addr: u64, size: usize) -> u64 {
+    // SAFETY: contrat de l'appelant (adresse vérifiée, `size` octets lisibles).
+    unsafe {
+        match size {
+            1 => core::ptr::read_unaligned(addr as *const u8) as u64,
+            2 => core::ptr::read_unaligned(addr as *const u16) as u64,
+            4 => core::ptr::read_unaligned(addr as *const u32) as u64,
+            _ => core::ptr::read_unaligned(addr as *const u64),
+        }
+    }
+}
+
+unsafe fn store(addr: u64, size: usize, value: u64) {
+    // SAFETY: contrat de l'appelant (adresse vérifiée, `size` octets inscriptibles).
+    unsafe {
+        match size {
+            1 => core::ptr::write_unaligned(addr as *mut u8, value as u8),
+            2 => core::ptr::write_unaligned(addr as *mut u16, value as u16),
+            4 => core::ptr::write_unaligned(addr as *mut u32, value as u32),
+            _ => core::ptr::write_unaligned(addr as *mut u64, value),
+        }
+    }
+}
+
+/// Région mémoire d'argument de helper, bornes prouvées par le vérificateur.
+///
+/// # Safety
+/// `addr..addr + len` est une région vérifiée (pile, contexte ou valeur de map).
+unsafe fn region<'a>(addr: u64, len: usize) -> &'a [u8] {
+    // SAFETY: contrat de la fonction.
+    unsafe { core::slice::from_raw_parts(addr as *const u8, len) }
+}
+
+fn call_helper(helper: i32, regs: &[u64; REG_COUNT], env: &RunEnv<'_>) -> u64 {
+    let errno = |ok: bool| if ok { 0 } else { u64::MAX };
+    match helper {
+        HELPER_MAP_LOOKUP => {
+            let map = &env.maps[regs[1] as usize];
+            // SAFETY: r2 désigne `key_size` octets (vérifié).
+            let key = unsafe { region(regs[2], map.key_size as usize) };
+            map.lookup_ptr(key).map_or(0, |p| p as u64)
+        }
+        HELPER_MAP_UPDATE => {
+            let map = &env.maps[regs[1] as usize];
+            // SAFETY: r2 / r3 désignent clé et valeur de la map (vérifié).
+            let (key, value) = unsafe {
+                (
+                    region(regs[2], map.key_size as usize),
+                    region(regs[3], map.value_size as usize),
+                )
+            };
+            errno(map.try_update(key, value).is_ok())
+        }
+        HELPER_MAP_DELETE => {
+            let map = &env.maps[regs[1] as usize];
+            // SAFETY: r2 désigne `key_size` octets (vérifié).
+            let key = unsafe { region(regs[2], map.key_size as usize) };
+            errno(map.try_delete(key).is_ok())
+        }
+        HELPER_KTIME_NS => env.now_ns,
+        _ => {
+            // HELPER_PKT_LOAD
+            let len = regs[3] as usize;
+            // SAFETY: r2 désigne `len` octets inscriptibles (pile ou valeur de
+            // map, r3 constant vérifié) ; aucune autre référence pendant l'appel.
+            let dst = unsafe { core::slice::from_raw_parts_mut(regs[2] as *mut u8, len) };
+            let src = usize::try_from(regs[1])
+                .ok()
+                .and_then(|off| env.packet.get(off..off.checked_add(len)?));
+            match src {
+                Some(src) => {
+                    dst.copy_from_slice(src);
+                    0
+                }
+                None => u64::MAX,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::asm::*;
+    use super::*;
+    use std::vec;
+
+    fn env<'a>(maps: &'a [Arc<BpfMap>], ctx: &'a [u8]) -> RunEnv<'a> {
+        RunEnv {
+            maps,
+            ctx,
+            packet: &[],
+            now_ns: 42,
+        }
+    }
+
+    #[test]
+    fn alu_edge_cases_follow_ebpf() {
+        assert_eq!(alu64(ALU_DIV, 7, 0), 0);
+        assert_eq!(alu64(ALU_MOD, 7, 0), 7);
+        assert_eq!(alu64(ALU_LSH, 1, 65), 2);
+        assert_eq!(alu64(ALU_ARSH, (-8i64) as u64, 1), (-4i64) as u64);
+        assert_eq!(alu32(ALU_ADD, u32::MAX as u64, 1), 0);
+        assert_eq!(alu32(ALU_MOV, 0, u64::MAX), u32::MAX as u64);
+        assert!(jump_taken(JMP_JSLT, (-1i64) as u64, 0));
+        assert!(!jump_taken(JMP_JLT, (-1i64) as u64, 0));
+    }
+
+    #[test]
+    fn loop_sums_and_reads_context() {
+        // r0 = ctx[0] + (0 + 1 + … + 9)
+        let prog = [
+            ldx(SIZE_W, 0, 1, 0),
+            mov64_imm(2, 0),
+            alu64_reg(ALU_ADD, 0, 2),
+            alu64_imm(ALU_ADD, 2, 1),
+            jmp_imm(JMP_JLT, 2, 10, -3),
+            exit(),
+        ];
+        let ctx = 100u32.to_le_bytes();
+        assert_eq!(run(&prog, &env(&[], &ctx)), Some(145));
+    }
+
+    #[test]
+    fn infinite_loop_hits_step_budget() {
+        let prog = [ja(-1), exit()];
+        assert_eq!(run(&prog, &env(&[], &[])), None);
+    }
+
+    #[test]
+    fn map_helpers_update_and_lookup() {
+        let map = Arc::new(BpfMap::new(super::super::map::MapKind::Hash, 4, 8, 4).unwrap());
+        let maps = [map.clone()];
+        let [ld0, ld1] = ld_map(1, 0);
+        let [ld2, ld3] = ld_map(1, 0);
+        let prog = vec![
+            st_imm(SIZE_W, 10, -4, 7),   // clé = 7
+            st_imm(SIZE_DW, 10, -16, 9), // valeur = 9
+            ld0,
+            ld1,
+            mov64_reg(2, 10),
+            alu64_imm(ALU_ADD, 2, -4),
+            mov64_reg(3, 10),
+            alu64_imm(ALU_ADD, 3, -16),
+            call(HELPER_MAP_UPDATE),
+            ld2,
+            ld3,
+            mov64_reg(2, 10),
+            alu64_imm(ALU_ADD, 2, -4),
+            call(HELPER_MAP_LOOKUP),
+            jmp_imm(JMP_JEQ, 0, 0, 1),
+            ldx(SIZE_DW, 0, 0, 0),
+            exit(),
+        ];
+        assert_eq!(run(&prog, &env(&maps, &[])), Some(9));
+        let mut out = [0u8; 8];
+        map.lookup(&7u32.to_le_bytes(), &mut out).unwrap();
+        assert_eq!(u64::from_le_bytes(out), 9);
+    }
+
+    #[test]
+    fn pkt_load_is_bounds_checked() {
+        let prog = [
+            mov64_imm(1, 2),
+            mov64_reg(2, 10),
+            alu64_imm(ALU_ADD, 2, -8),
+            mov64_imm(3, 2),
+            call(HELPER_PKT_LOAD),
+            jmp_imm(JMP_JNE, 0, 0, 2),
+            ldx(SIZE_H, 0, 10, -8),
+            exit(),
+            mov64_imm(0, -1),
+            exit(),
+        ];
+        let packet = [0u8, 0, 0x34, 0x12];
+        let mut e = env(&[], &[]);
+        e.packet = &packet;
+        assert_eq!(run(&prog, &e), Some(0x1234));
+        e.packet = &packet[..3];
+        assert_eq!(run(&prog, &e), Some(u64::MAX));
+    }
+}
@@ -0,0 +1,107 @@
+//! # config.rs — Configuration de build du noyau
+//!
+//! Les sous-systèmes optionnels sont choisis à la compilation par des features
+//! cargo, elles-mêmes générées par `tools/exo_config` depuis un profil
+//! `configs/*.exoconfig` (desktop, serveur, embarqué…). `BUILD_CONFIG` est la
+//! table constante qui en résulte : elle est résumée au boot et publiée en
+//! lecture seule sous `kernel.config.<nom>` (voir `sysctl::builtin`).
+//!
+//! Ajouter une option : déclarer la feature dans `kernel/Cargo.toml`, l'entrée
+//! ici, le tunable dans `sysctl/builtin.rs` et l'option dans le catalogue de
+//! `exo-config`.
+
+/// Option de build et son état dans l'image courante.
+#[derive(Copy, Clone, Debug)]
+pub struct BuildOption {
+    /// Nom de la feature cargo (= suffixe du sysctl `kernel.config.<nom>`).
+    pub name: &'static str,
+    pub description: &'static str,
+    pub enabled: bool,
+}
+
+pub const MULTIBOOT2_COMPAT: BuildOption = BuildOption {
+    name: "multiboot2_compat",
+    description: "Multiboot2 boot path (GRUB 2)",
+    enabled: cfg!(feature = "multiboot2_compat"),
+};
+
+pub const KVM: BuildOption = BuildOption {
+    name: "kvm",
+    description: "exo-kvm hypervisor (/dev/exo-kvm)",
+    enabled: cfg!(feature = "kvm"),
+};
+
+pub const IA32_COMPAT: BuildOption = BuildOption {
+    name: "ia32_compat",
+    description: "i386 syscall compatibility (int 0x80, sysenter)",
+    enabled: cfg!(feature = "ia32_compat"),
+};
+
+pub const STRICT_EXEC_SIGNATURES: BuildOption = BuildOption {
+    name: "strict_exec_signatures",
+    description: "Reject executables with an invalid signature",
+    enabled: cfg!(feature = "strict_exec_signatures"),
+};
+
+pub const SERIAL_CONSOLE: BuildOption = BuildOption {
+    name: "serial_console",
+    description: "COM1 serial console always on (else console=ttyS0)",
+    enabled: cfg!(feature = "serial_console"),
+};
+
+pub const DEV_NO_VMM: BuildOption = BuildOption {
+    name: "dev_no_vmm",
+    description: "IPC shared memory simulated as virt=phys (development)",
+    enabled: cfg!(feature = "dev_no_vmm"),
+};
+
+/// Table constante des options de build, dans l'ordre d'affichage.
+pub const BUILD_CONFIG: [BuildOption; 6] = [
+    MULTIBOOT2_COMPAT,
+    KVM,
+    IA32_COMPAT,
+    STRICT_EXEC_SIGNATURES,
+    SERIAL_CONSOLE,
+    DEV_NO_VMM,
+];
+
+/// Vrai si l'option `name` est compilée dans l'image (inconnue → faux).
+pub fn is_enabled(name: &str) -> bool {
+    BUILD_CONFIG
+        .iter()
+        .any(|option| option.enabled && option.name == name)
+}
+
+/// Résume la configuration de build sur la console debug (une ligne).
+pub fn log_build_config() {
+    #[cfg(target_os = "none")]
+    {
+        use crate::arch::x86_64::terminal::debug_write;
+        debug_write(b"[CONFIG]");
+        for option in BUILD_CONFIG.iter() {
+            debug_write(b" ");
+            debug_write(option.name.as_bytes());
+            debug_write(if option.enabled { b"=y" } else { b"=n" });
+        }
+        debug_write(b"\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_config_matches_features() {
+        assert_eq!(is_enabled("kvm"), cfg!(feature = "kvm"));
+        assert_eq!(is_enabled("ia32_compat"), cfg!(feature = "ia32_compat"));
+        assert!(!is_enabled("no_such_option"));
+    }
+
+    #[test]
+    fn test_build_config_names_unique() {
+        for (i, a) in BUILD_CONFIG.iter().enumerate() {
+            assert!(BUILD_CONFIG[i + 1..].iter().all(|b| b.name != a.name));
+        }
+    }
+}
@@ -14,6 +14,13 @@ use crate::arch::x86_64::irq_save;
 use crate::memory::core::types::PhysAddr;
 use crate::process::core::pid::Pid;
 use crate::process::PROCESS_REGISTRY;
+use crate::security::capability::Rights;
+
+/// Droits périphérique d'un claim par plage (`sys_pci_claim`) : MMIO, DMA et
+/// IRQ, comme avant l'introduction des droits par claim.
+pub const DEVICE_RIGHTS_ALL: Rights = Rights::DEV_MMIO
+    .union(Rights::DEV_DMA)
+    .union(Rights::DEV_IRQ);
 
 /// Erreur de revendication de périphérique
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -44,6 +51,8 @@ pub struct DeviceClaim {
     pub owner_pid: Pid,
     pub generation: u64,
     pub bdf: Option<PciBdf>,
+    /// Sous-ensemble de `DEVICE_RIGHTS_ALL` accordé au propriétaire.
+    pub rights: Rights,
 }
 
 impl DeviceClaim {
@@ -170,11 +179,81 @@ pub fn sys_pci_claim(
         owner_pid: d_pid,
         generation: gen,
         bdf,
+        rights: DEVICE_RIGHTS_ALL,
     });
 
     Ok(())
 }
 
+/// Claim d'une fonction PCI entière pour `driver_pid` : chaque BAR MMIO de
+/// `bars` (base, taille) devient un claim portant `bdf` et `rights`. Un
+/// nouveau claim du même propriétaire remplace les droits des précédents.
+/// Retourne le nombre de BARs claimés.
+pub fn claim_pci_device(
+    bdf: PciBdf,
+    driver_pid: u32,
+    rights: Rights,
+    calling_pid: u32,
+    bars: &[(PhysAddr, usize)],
+) -> Result<usize, ClaimError> {
+    if driver_pid == 0 || !rights.is_subset_of(DEVICE_RIGHTS_ALL) {
+        return Err(ClaimError::PermissionDenied);
+    }
+    if !check_sys_admin_capability(Pid(calling_pid)) {
+        return Err(ClaimError::PermissionDenied);
+    }
+
+    let d_pid = Pid(driver_pid);
+    let _irq = irq_save();
+    let mut claims = DEVICE_CLAIMS.write();
+
+    if claims
+        .iter()
+        .any(|c| c.bdf == Some(bdf) && c.owner_pid != d_pid)
+    {
+        return Err(ClaimError::AlreadyClaimed);
+    }
+    for &(base, size) in bars {
+        if md_is_ram_region(base, size) {
+            return Err(ClaimError::PhysIsRam);
+        }
+        if claims
+            .iter()
+            .any(|c| c.owner_pid != d_pid && c.overlaps(base, size))
+        {
+            return Err(ClaimError::AlreadyClaimed);
+        }
+    }
+
+    claims.retain(|c| !(c.owner_pid == d_pid && c.bdf == Some(bdf)));
+    if claims.try_reserve(bars.len().max(1)).is_err() {
+        return Err(ClaimError::TableFull);
+    }
+    let gen = get_process_generation(d_pid);
+    for &(phys_base, size) in bars {
+        claims.push(DeviceClaim {
+            phys_base,
+            size,
+            owner_pid: d_pid,
+            generation: gen,
+            bdf: Some(bdf),
+            rights,
+        });
+    }
+    if bars.is_empty() {
+        // Fonction sans BAR MMIO : le claim porte quand même BDF et droits.
+        claims.push(DeviceClaim {
+            phys_base: PhysAddr::new(0),
+            size: 0,
+            owner_pid: d_pid,
+            generation: gen,
+            bdf: Some(bdf),
+            rights,
+        });
+    }
+    Ok(bars.len())
+}
+
 pub fn claim_trusted_mmio_for_pid(
     phys_base: PhysAddr,
     size: usize,
@@ -207,6 +286,7 @@ pub fn claim_trusted_mmio_for_pid(
         owner_pid: d_pid,
         generation: gen,
         bdf: None,
+        rights: Rights::DEV_MMIO,
     });
 
     Ok(())
@@ -218,6 +298,26 @@ pub fn revoke_claims_for_pid(pid: u32) {
     claims.retain(|c| c.owner_pid.0 != pid);
 }
 
+/// Retire le claim par plage (`phys_base`, `size`) de `pid`, et lui seul :
+/// les autres claims du processus restent en place.
+pub fn revoke_claim_range(pid: u32, phys_base: PhysAddr, size: usize) {
+    let _irq = irq_save();
+    let mut claims = DEVICE_CLAIMS.write();
+    if let Some(pos) = claims.iter().position(|c| {
+        c.owner_pid.0 == pid && c.phys_base.as_u64() == phys_base.as_u64() && c.size == size
+    }) {
+        claims.remove(pos);
+    }
+}
+
+/// Retire les claims de `pid` sur la fonction `bdf` (tous ses BARs), et
+/// eux seuls.
+pub fn revoke_device_claim(pid: u32, bdf: PciBdf) {
+    let _irq = irq_save();
+    let mut claims = DEVICE_CLAIMS.write();
+    claims.retain(|c| !(c.owner_pid.0 == pid && c.bdf == Some(bdf)));
+}
+
 pub fn bdf_of_pid(pid: u32) -> Option<PciBdf> {
     let claims = DEVICE_CLAIMS.read();
     claims
@@ -226,6 +326,15 @@ pub fn bdf_of_pid(pid: u32) -> Option<PciBdf> {
         .and_then(|claim| claim.bdf)
 }
 
+/// Droits périphérique cumulés des claims de `pid`.
+pub fn rights_of_pid(pid: u32) -> Rights {
+    let claims = DEVICE_CLAIMS.read();
+    claims
+        .iter()
+        .filter(|claim| claim.owner_pid.0 == pid)
+        .fold(Rights::NONE, |rights, claim| rights.union(claim.rights))
+}
+
 pub fn claim_contains(pid: u32, phys_base: PhysAddr, size: usize) -> bool {
     let Some((start, end)) = checked_range(phys_base, size) else {
         return false;
@@ -233,7 +342,7 @@ pub fn claim_contains(pid: u32, phys_base: PhysAddr, size: usize) -> bool {
 
     let claims = DEVICE_CLAIMS.read();
     claims.iter().any(|claim| {
-        if claim.owner_pid.0 != pid {
+        if claim.owner_pid.0 != pid || !claim.rights.contains(Rights::DEV_MMIO) {
             return false;
         }
 
@@ -540,6 +540,46 @@ fn unmap_mmio_record(record: MmioRecord) {
     }
 }
 
+/// Épingle en écriture les pages de `[vaddr, vaddr + size)` de `pid`, sans
+/// mapping IOMMU (RAM invitée de `/dev/exo-kvm`). Même séquence que
+/// `sys_dma_map` : COW résolu, permissions vérifiées, puis épinglage.
+pub fn pin_user_range_for_pid(
+    pid: u32,
+    vaddr: usize,
+    size: usize,
+) -> Result<Vec<PinnedPage>, DmaError> {
+    if size == 0 {
+        return Err(DmaError::InvalidParams);
+    }
+    let page_count = (size + PAGE_SIZE - 1) / PAGE_SIZE;
+    let mut pinned: Vec<PinnedPage> = Vec::new();
+    pinned
+        .try_reserve_exact(page_count)
+        .map_err(|_| DmaError::OutOfMemory)?;
+
+    for i in 0..page_count {
+        let vpage = vaddr + i * PAGE_SIZE;
+        let pinned_page = page_tables::resolve_cow_or_fault(pid, vpage, PageProtection::WRITE)
+            .map_err(|e| match e {
+                CowError::OutOfMemory => DmaError::OutOfMemory,
+                _ => DmaError::InvalidParams,
+            })
+            .and_then(|()| match page_tables::query_perms_single(pid, vpage) {
+                Some(perms) if perms.is_writable() => Ok(()),
+                _ => Err(DmaError::InvalidParams),
+            })
+            .and_then(|()| page_tables::pin_user_page(pid, vpage).ok_or(DmaError::InvalidParams));
+        match pinned_page {
+            Ok(page) => pinned.push(page),
+            Err(e) => {
+                rollback_pinned_pages(&pinned);
+                return Err(e);
+            }
+        }
+    }
+    Ok(pinned)
+}
+
 /// Mappe une plage virtuelle utilisateur en espace DMA/IOMMU.
 /// FIX-68 Obligatoire : Résolution du Copy-On-Write (COW) avant l'interrogation des permissions.
 pub fn sys_dma_map(
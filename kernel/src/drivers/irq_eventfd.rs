@@ -0,0 +1,223 @@
+//! # drivers/irq_eventfd.rs
+//!
+//! Livraison d'IRQ par eventfd aux drivers Ring 3 (`SYS_IRQ_EVENTFD`).
+//!
+//! Alternative au message IPC de `dispatch_irq` pour les sources qui n'ont
+//! pas besoin d'acquittement (IOAPIC edge, MSI, MSI-X) : l'ISR incrémente un
+//! compteur atomique par liaison, sans verrou ni allocation ; la lecture ou
+//! le poll de l'eventfd (`fs_bridge::eventfd_state`) ajoute les IRQ en
+//! attente à sa valeur. Le driver attend donc ses interruptions avec
+//! `read`/`epoll` comme n'importe quel descripteur.
+//!
+//! Les liaisons ne sont écrites que sous `irq_save` ; l'ISR ne lit que les
+//! compteurs.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use spin::RwLock;
+
+use crate::arch::x86_64::irq_save;
+use crate::fs::exofs::core::BlobId;
+
+/// Liaisons IRQ → eventfd simultanées, tous drivers confondus.
+pub const MAX_IRQ_EVENTFDS: usize = 32;
+
+#[derive(Clone, Copy)]
+struct Binding {
+    owner_pid: u32,
+    blob_id: BlobId,
+}
+
+static BINDINGS: RwLock<[Option<Binding>; MAX_IRQ_EVENTFDS]> =
+    RwLock::new([None; MAX_IRQ_EVENTFDS]);
+static PENDING: [AtomicU64; MAX_IRQ_EVENTFDS] = [const { AtomicU64::new(0) }; MAX_IRQ_EVENTFDS];
+
+/// Lie l'eventfd `blob_id` de `owner_pid` ; retourne l'index de la liaison
+/// et `true` si elle vient d'être créée (`false` : réutilisée), `None` si la
+/// table est pleine.
+pub fn bind(owner_pid: u32, blob_id: BlobId) -> Option<(u16, bool)> {
+    let _irq = irq_save();
+    let mut bindings = BINDINGS.write();
+    if let Some(slot) = bindings
+        .iter()
+        .position(|b| matches!(b, Some(b) if b.owner_pid == owner_pid && b.blob_id == blob_id))
+    {
+        return Some((slot as u16, false));
+    }
+    let slot = bindings.iter().position(Option::is_none)?;
+    PENDING[slot].store(0, Ordering::Relaxed);
+    bindings[slot] = Some(Binding { owner_pid, blob_id });
+    Some((slot as u16, true))
+}
+
+/// Défait une liaison que `bind` vient de créer, quand l'enregistrement de
+/// l'IRQ échoue ensuite. Une liaison réutilisée sert déjà une autre IRQ et
+/// ne doit pas passer par ici.
+pub fn unbind(slot: u16) {
+    let _irq = irq_save();
+    let mut bindings = BINDINGS.write();
+    if let Some(binding) = bindings.get_mut(slot as usize) {
+        *binding = None;
+        PENDING[slot as usize].store(0, Ordering::Relaxed);
+    }
+}
+
+/// Appelé depuis l'ISR : une IRQ de plus pour la liaison `slot`.
+#[inline]
+pub fn signal(slot: u16) {
+    if let Some(pending) = PENDING.get(slot as usize) {
+        pending.fetch_add(1, Ordering::Release);
+    }
+}
+
+/// IRQ arrivées depuis le dernier appel pour l'eventfd `blob_id`.
+pub fn drain(blob_id: &BlobId) -> u64 {
+    let bindings = BINDINGS.read();
+    bindings
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| matches!(b, Some(b) if b.blob_id == *blob_id))
+        .map(|(slot, _)| PENDING[slot].swap(0, Ordering::AcqRel))
+        .sum()
+}
+
+/// Libère les liaisons d'un driver qui se termine.
+pub fn release_for_pid(pid: u32) -> usize {
+    let _irq = irq_save();
+    let mut bindings = BINDINGS.write();
+    let mut released = 0;
+    for binding in bindings.iter_mut() {
+        if matches!(binding, Some(b) if b.owner_pid == pid) {
+            *binding = None;
+            released += 1;
+        }
+    }
+    released
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// La table est globale : un test à la fois.
+    static TABLE: spin::Mutex<()> = spin::Mutex::new(());
+
+    #[test]
+    fn irqs_accumulate_until_drained_and_die_with_the_driver() {
+        let _table = TABLE.lock();
+        let blob = BlobId([0x5a; 32]);
+        let (slot, created) = bind(4242, blob).unwrap();
+        assert!(created);
+        assert_eq!(bind(4242, blob), Some((slot, false)));
+
+        signal(slot);
+        signal(slot);
+        assert_eq!(drain(&blob), 2);
+        assert_eq!(drain(&blob), 0);
+
+        signal(slot);
+        assert_eq!(release_for_pid(4242), 1);
+        assert_eq!(drain(&blob), 0);
+    }
+
+    #[test]
+    fn bindings_are_per_owner_and_eventfd() {
+        let _table = TABLE.lock();
+        let shared = BlobId([0x11; 32]);
+        let other = BlobId([0x12; 32]);
+        let (a, _) = bind(5001, shared).unwrap();
+        let (b, _) = bind(5002, shared).unwrap();
+        let (c, _) = bind(5001, other).unwrap();
+        assert!(a != b && a != c && b != c);
+        assert_eq!(bind(5002, shared), Some((b, false)));
+
+        // Un eventfd partagé voit les IRQ de toutes ses liaisons.
+        signal(a);
+        signal(b);
+        signal(c);
+        assert_eq!(drain(&shared), 2);
+        assert_eq!(drain(&other), 1);
+
+        assert_eq!(release_for_pid(5001), 2);
+        assert_eq!(release_for_pid(5002), 1);
+    }
+
+    #[test]
+    fn full_table_refuses_new_bindings() {
+        let _table = TABLE.lock();
+        let kept = BlobId([0x21; 32]);
+        let (kept_slot, _) = bind(6001, kept).unwrap();
+        let mut filled = 0;
+        while bind(6002, BlobId([filled as u8; 32])).is_some() {
+            filled += 1;
+            assert!(filled <= MAX_IRQ_EVENTFDS);
+        }
+        assert_eq!(bind(6003, kept), None);
+        // Une liaison existante reste joignable.
+        assert_eq!(bind(6001, kept), Some((kept_slot, false)));
+
+        assert_eq!(release_for_pid(6002), filled);
+        assert!(bind(6003, kept).is_some());
+        assert_eq!(release_for_pid(6001), 1);
+        assert_eq!(release_for_pid(6003), 1);
+    }
+
+    #[test]
+    fn repeated_signals_read_as_one_count() {
+        let _table = TABLE.lock();
+        let blob = BlobId([0x31; 32]);
+        let (slot, _) = bind(7001, blob).unwrap();
+        for _ in 0..1000 {
+            signal(slot);
+        }
+        signal(MAX_IRQ_EVENTFDS as u16);
+        assert_eq!(drain(&blob), 1000);
+        assert_eq!(drain(&blob), 0);
+        signal(slot);
+        assert_eq!(drain(&blob), 1);
+        assert_eq!(release_for_pid(7001), 1);
+    }
+
+    #[test]
+    fn exit_releases_only_the_owner_bindings() {
+        let _table = TABLE.lock();
+        let dead = BlobId([0x41; 32]);
+        let alive = BlobId([0x42; 32]);
+        let (dead_slot, _) = bind(8001, dead).unwrap();
+        let (alive_slot, _) = bind(8002, alive).unwrap();
+
+        assert_eq!(release_for_pid(8001), 1);
+        assert_eq!(release_for_pid(8001), 0);
+        // IRQ en vol après la sortie : perdue avec la liaison.
+        signal(dead_slot);
+        signal(alive_slot);
+        assert_eq!(drain(&dead), 0);
+        assert_eq!(drain(&alive), 1);
+
+        // Le slot libéré repart de zéro pour le driver suivant.
+        let (reused, _) = bind(8003, alive).unwrap();
+        assert_eq!(reused, dead_slot);
+        assert_eq!(drain(&alive), 0);
+
+        assert_eq!(release_for_pid(8002), 1);
+        assert_eq!(release_for_pid(8003), 1);
+    }
+
+    #[test]
+    fn failed_registration_gives_its_slot_back() {
+        let _table = TABLE.lock();
+        let blob = BlobId([0x51; 32]);
+        let (slot, created) = bind(9001, blob).unwrap();
+        assert!(created);
+        signal(slot);
+        unbind(slot);
+        assert_eq!(drain(&blob), 0);
+        assert_eq!(release_for_pid(9001), 0);
+
+        // Le slot rendu est le premier libre pour la liaison suivante.
+        let (again, created) = bind(9002, blob).unwrap();
+        assert_eq!((again, created), (slot, true));
+        unbind(MAX_IRQ_EVENTFDS as u16);
+        assert_eq!(release_for_pid(9002), 1);
+    }
+}
@@ -13,6 +13,7 @@ pub mod device_claims;
 pub mod device_server_ipc;
 pub mod dma;
 pub mod iommu;
+pub mod irq_eventfd;
 mod pci_cfg;
 mod pci_link;
 pub mod pci_topology;
@@ -22,7 +23,7 @@ pub mod tests;
 
 // Re-export key types and functions
 pub use crate::memory::dma::core::types::IommuDomainId;
-pub use device_claims::ClaimError;
+pub use device_claims::{rights_of_pid, ClaimError, DEVICE_RIGHTS_ALL};
 pub use dma::{
     sys_dma_alloc_for_pid, sys_dma_free_for_pid, sys_mmio_map_for_pid, sys_mmio_unmap_for_pid,
 };
@@ -191,11 +192,71 @@ pub fn sys_pci_claim(
     device_claims::sys_pci_claim(phys_base, size, pid, custom_bdf, calling_pid)
 }
 
+/// Claim d'une fonction PCI entière : tous ses BARs MMIO, avec les droits
+/// périphérique `rights` (`DEV_MMIO`, `DEV_DMA`, `DEV_IRQ`).
+pub fn sys_pci_claim_device(
+    bdf: pci_types::PciAddress,
+    pid: u32,
+    rights: crate::security::capability::Rights,
+    calling_pid: u32,
+) -> Result<usize, ClaimError> {
+    let bdf = device_claims::PciBdf {
+        bus: bdf.bus(),
+        dev: bdf.device(),
+        func: bdf.function(),
+    };
+    let mut bars = [(crate::memory::core::types::PhysAddr::new(0), 0usize); 6];
+    let mut count = 0;
+    for bar in pci_cfg::pci_mmio_bars(bdf).into_iter().flatten() {
+        bars[count] = bar;
+        count += 1;
+    }
+
+    device_claims::claim_pci_device(bdf, pid, rights, calling_pid, &bars[..count])
+}
+
+/// Mappe le BAR `index` de la fonction PCI claimée par `pid` ; retourne
+/// (adresse virtuelle, taille). `claim_contains` exige `DEV_MMIO`.
+pub fn sys_pci_map_bar_for_pid(pid: u32, index: u8) -> Result<(u64, usize), MmioError> {
+    let bdf = device_claims::bdf_of_pid(pid).ok_or(MmioError::PermissionDenied)?;
+    let (phys, size) = pci_cfg::pci_mmio_bars(bdf)
+        .get(index as usize)
+        .copied()
+        .flatten()
+        .ok_or(MmioError::InvalidParams)?;
+    let virt = dma::sys_mmio_map_for_pid(pid, phys, size)?;
+    Ok((virt, size))
+}
+
+/// BDF brut (`bus << 8 | dev << 3 | func`) de la fonction claimée par `pid`.
+pub fn claimed_bdf_raw(pid: u32) -> Option<u64> {
+    device_claims::bdf_of_pid(pid)
+        .map(|bdf| ((bdf.bus as u64) << 8) | ((bdf.dev as u64) << 3) | bdf.func as u64)
+}
+
 pub fn release_claims_for_pid(pid: u32) -> usize {
     device_claims::revoke_claims_for_pid(pid);
     0
 }
 
+/// Annule un claim par plage qui vient d'être accordé à `pid` sans toucher
+/// à ses claims antérieurs.
+pub fn release_claim_range(pid: u32, phys_base: crate::memory::core::types::PhysAddr, size: usize) {
+    device_claims::revoke_claim_range(pid, phys_base, size);
+}
+
+/// Annule le claim de la fonction PCI `bdf` qui vient d'être accordé à `pid`.
+pub fn release_device_claim(pid: u32, bdf: pci_types::PciAddress) {
+    device_claims::revoke_device_claim(
+        pid,
+        device_claims::PciBdf {
+            bus: bdf.bus(),
+            dev: bdf.device(),
+            func: bdf.function(),
+        },
+    );
+}
+
 pub fn release_claim_for_owner(pid: u32) -> usize {
     device_claims::revoke_claims_for_pid(pid);
     iommu::release_domain_for_pid(pid);
@@ -248,6 +309,7 @@ fn revoke_mmio(pid: u32) {
 #[inline]
 fn revoke_irq(pid: u32) {
     crate::arch::x86_64::irq::routing::revoke_all_irq_for_pid(pid);
+    irq_eventfd::release_for_pid(pid);
     let _ = release_all_msi_for_pid(pid);
 }
 
@@ -306,6 +306,16 @@ pub(super) fn pci_mmio_bar_contains(bdf: PciBdf, phys_base: PhysAddr, size: usiz
     })
 }
 
+/// BARs MMIO de `bdf` par index (base, taille) ; `None` pour les BARs I/O,
+/// vides ou de taille nulle, et pour la moitié haute d'un BAR 64 bits.
+pub(super) fn pci_mmio_bars(bdf: PciBdf) -> [Option<(PhysAddr, usize)>; 6] {
+    let header_type = pci_cfg_read8(bdf, PCI_HEADER_TYPE_OFFSET);
+    pci_bars(bdf, header_type).map(|bar| {
+        (bar.kind == 0 && bar.phys != 0 && bar.size != 0)
+            .then(|| (PhysAddr::new(bar.phys), bar.size as usize))
+    })
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn find_pci_device(
     vendor_filter: u16,
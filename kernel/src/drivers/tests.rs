@@ -4,7 +4,10 @@ pub mod smp_tests {
     use crate::arch::x86_64::boot::memory_map::{
         MemoryRegion, MemoryRegionType, MEMORY_MAP, MEMORY_REGION_COUNT,
     };
-    use crate::drivers::device_claims::{sys_pci_claim, ClaimError, PciBdf, DEVICE_CLAIMS};
+    use crate::drivers::device_claims::{
+        claim_contains, claim_trusted_mmio_for_pid, revoke_claim_range, revoke_device_claim,
+        sys_pci_claim, ClaimError, DeviceClaim, PciBdf, DEVICE_CLAIMS,
+    };
     use crate::drivers::iommu::fault_queue::{IommuFaultEvent, IommuFaultQueue};
     use crate::memory::core::types::PhysAddr;
     use core::sync::atomic::Ordering;
@@ -158,11 +161,59 @@ pub mod smp_tests {
         assert_eq!(successes, 1);
         assert_eq!(failures_already_claimed, 49);
 
-        // Cleanup test environment
-        DEVICE_CLAIMS.write().clear();
+        // Cleanup test environment (seulement nos claims : les autres tests
+        // partagent la table)
+        DEVICE_CLAIMS
+            .write()
+            .retain(|c| c.phys_base.as_u64() != phys_base.as_u64());
         unsafe {
             MEMORY_MAP[0] = old_region0;
             MEMORY_REGION_COUNT = old_count;
         }
     }
+
+    #[test]
+    fn test_04_rollback_releases_only_the_new_claim() {
+        // Un échec IOMMU après un claim ne doit annuler que ce claim.
+        let pid = 0x0C1A;
+        let kept = PhysAddr::new(0xB100_0000);
+        let fresh = PhysAddr::new(0xB200_0000);
+        claim_trusted_mmio_for_pid(kept, 4096, pid).unwrap();
+        claim_trusted_mmio_for_pid(fresh, 4096, pid).unwrap();
+
+        revoke_claim_range(pid, fresh, 4096);
+        assert!(claim_contains(pid, kept, 4096));
+        assert!(!claim_contains(pid, fresh, 4096));
+
+        let old = PciBdf {
+            bus: 7,
+            dev: 1,
+            func: 0,
+        };
+        let new = PciBdf {
+            bus: 7,
+            dev: 2,
+            func: 0,
+        };
+        {
+            let mut claims = DEVICE_CLAIMS.write();
+            for (bdf, base) in [(old, 0xB300_0000), (new, 0xB400_0000), (new, 0xB500_0000)] {
+                claims.push(DeviceClaim {
+                    phys_base: PhysAddr::new(base),
+                    size: 4096,
+                    owner_pid: crate::process::core::pid::Pid(pid),
+                    generation: 0,
+                    bdf: Some(bdf),
+                    rights: crate::drivers::DEVICE_RIGHTS_ALL,
+                });
+            }
+        }
+        revoke_device_claim(pid, new);
+        assert!(claim_contains(pid, PhysAddr::new(0xB300_0000), 4096));
+        assert!(!claim_contains(pid, PhysAddr::new(0xB400_0000), 4096));
+        assert!(!claim_contains(pid, PhysAddr::new(0xB500_0000), 4096));
+        assert!(claim_contains(pid, kept, 4096));
+
+        crate::drivers::device_claims::revoke_claims_for_pid(pid);
+    }
 }
@@ -12,6 +12,7 @@
 
 use crate::arch::constants::USER_ELF_BASE_MIN;
 use crate::fs::exofs::cache::blob_cache::BLOB_CACHE;
+use crate::fs::exofs::cache::boot_preload;
 use crate::fs::exofs::core::types::BlobId;
 use crate::fs::exofs::syscall::object_store;
 use crate::fs::exofs::syscall::path_resolve::resolve_path_to_blob;
@@ -21,6 +22,7 @@ use crate::memory::core::layout::{
 use crate::memory::core::AllocError;
 use crate::memory::core::PageFlags;
 use crate::memory::physical::allocator::buddy;
+use crate::memory::virt::address_space::IA32_USER_END;
 use crate::memory::virt::fault::demand_paging::FileFaultProvider;
 use crate::memory::virt::page_table::builder::PageTableBuilder;
 use crate::memory::virt::page_table::walker::FrameAllocatorForWalk;
@@ -46,6 +48,12 @@ const PF_X: u32 = 0x1;
 const PF_W: u32 = 0x2;
 const PF_R: u32 = 0x4;
 const PHDR_SIZE: usize = 56;
+const PHDR32_SIZE: usize = 32;
+const EHDR32_SIZE: usize = 52;
+const EM_386: u16 = 3;
+const EM_X86_64: u16 = 0x3E;
+/// Sommet de pile d'une image i386 : sous `IA32_USER_END`, aligné 64 KiB.
+const IA32_STACK_TOP: u64 = IA32_USER_END & !0xFFFF;
 const DYNAMIC_LOADER_HANDOFF_MAGIC: u64 = 0x5845_4f4c_4459_4e01; // "XEOLDYN\1"
 const DYNAMIC_LOADER_HANDOFF_VERSION: u32 = 1;
 const DYNAMIC_LOADER_PATH_MAX: usize = 128;
@@ -130,6 +138,66 @@ fn trace_blob(prefix: &[u8], blob_id: &BlobId) {
     }
 }
 
+/// Classe ELF validée par `validate_elf_header`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ElfClass {
+    /// ELFCLASS64 / EM_X86_64.
+    Elf64,
+    /// ELFCLASS32 / EM_386 (feature `ia32_compat`).
+    Elf32,
+}
+
+impl ElfClass {
+    const fn phdr_size(self) -> usize {
+        match self {
+            ElfClass::Elf64 => PHDR_SIZE,
+            ElfClass::Elf32 => PHDR32_SIZE,
+        }
+    }
+
+    /// Première adresse interdite pour un segment PT_LOAD de cette classe.
+    const fn user_end(self) -> u64 {
+        match self {
+            ElfClass::Elf64 => USER_ELF_BASE_MAX,
+            ElfClass::Elf32 => IA32_USER_END,
+        }
+    }
+}
+
+/// Program header normalisé (les champs ELF32 sont étendus en 64-bit).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ElfPhdr {
+    p_type: u32,
+    p_flags: u32,
+    p_offset: u64,
+    p_vaddr: u64,
+    p_filesz: u64,
+    p_memsz: u64,
+}
+
+impl ElfPhdr {
+    fn parse(ph: &[u8], class: ElfClass) -> Self {
+        match class {
+            ElfClass::Elf64 => Self {
+                p_type: elf_u32(ph, 0),
+                p_flags: elf_u32(ph, 4),
+                p_offset: elf_u64(ph, 8),
+                p_vaddr: elf_u64(ph, 16),
+                p_filesz: elf_u64(ph, 32),
+                p_memsz: elf_u64(ph, 40),
+            },
+            ElfClass::Elf32 => Self {
+                p_type: elf_u32(ph, 0),
+                p_offset: elf_u32(ph, 4) as u64,
+                p_vaddr: elf_u32(ph, 8) as u64,
+                p_filesz: elf_u32(ph, 16) as u64,
+                p_memsz: elf_u32(ph, 20) as u64,
+                p_flags: elf_u32(ph, 24),
+            },
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 struct ElfSegmentMeta {
     file_data_start: u64,
@@ -359,7 +427,7 @@ impl ElfLoader for ExoFsElfLoader {
         clear_elf_segments(file_id);
 
         // ── 2. Lire le blob depuis le cache ──────────────────────────────────
-        let elf_data = read_blob_from_cache(&blob_id)?;
+        let elf_data = read_blob_from_cache(path.as_bytes(), &blob_id)?;
 
         // ── 3. Valider l'en-tête ELF et détecter PT_INTERP ───────────────────
         // FIX-LOADER-STATIC : ne charger /lib/ld-exo.so QUE si le binaire déclare
@@ -369,10 +437,15 @@ impl ElfLoader for ExoFsElfLoader {
         // demand-paging et compliquait le chemin de chargement sans aucun bénéfice
         // (runtime_entry est un no-op pour un EXEC sans relocations). Un binaire
         // dynamique (vrai PT_INTERP) continue d'utiliser son interpréteur.
-        validate_elf_header(&elf_data)?;
-        let interp_path = read_interpreter_path(&elf_data)?;
+        let class = validate_elf_header(&elf_data)?;
+        let interp_path = read_interpreter_path(&elf_data, class)?;
         let _ = default_interpreter_path; // conservé pour usage dynamique futur
 
+        // Compat i386 : binaires statiques uniquement (le handoff ld-exo est 64-bit).
+        if class == ElfClass::Elf32 && interp_path.is_some() {
+            return Err(ElfLoadError::UnsupportedArch);
+        }
+
         // ── 5. Créer le nouvel espace d'adressage ────────────────────────────
         let alloc = ElfWalkAllocator;
         let mut builder = PageTableBuilder::new(&alloc).map_err(|_| ElfLoadError::OutOfMemory)?;
@@ -385,9 +458,12 @@ impl ElfLoader for ExoFsElfLoader {
         }
         let pml4_phys = builder.pml4_phys();
         let child_as = Box::new(UserAddressSpace::new(pml4_phys, 0));
+        if class == ElfClass::Elf32 {
+            child_as.apply_compat32_layout();
+        }
 
         // ── 6. Charger l'exécutable et éventuellement son interpréteur ───────
-        let main_image = install_elf_image(&elf_data, file_id, &child_as, 0)?;
+        let main_image = install_elf_image(&elf_data, class, file_id, &child_as, 0)?;
         let mut entry_point = main_image.entry_point;
         let mut entry_arg0 = 0u64;
         let mut interp_image = None;
@@ -402,9 +478,12 @@ impl ElfLoader for ExoFsElfLoader {
             })?;
             let interp_file_id = register_elf_blob(interp_blob).ok_or(ElfLoadError::OutOfMemory)?;
             clear_elf_segments(interp_file_id);
-            let interp_data = read_blob_from_cache(&interp_blob)?;
-            validate_elf_header(&interp_data)?;
-            let image = install_elf_image(&interp_data, interp_file_id, &child_as, 0)?;
+            let interp_data = read_blob_from_cache(interp.as_bytes(), &interp_blob)?;
+            if validate_elf_header(&interp_data)? != ElfClass::Elf64 {
+                return Err(ElfLoadError::UnsupportedArch);
+            }
+            let image =
+                install_elf_image(&interp_data, ElfClass::Elf64, interp_file_id, &child_as, 0)?;
             entry_point = image.entry_point;
             interp_image = Some((interp, image));
         }
@@ -415,15 +494,24 @@ impl ElfLoader for ExoFsElfLoader {
         child_as.init_heap_bounds(brk_start);
 
         // ── 8. Pile utilisateur : bootstrap eager minimal, plafond layout partagé ─
-        const STACK_TOP: u64 = USER_STACK_TOP.as_u64();
         const STACK_SIZE: usize = USER_STACK_BOOTSTRAP_SIZE;
+        let stack_top = match class {
+            ElfClass::Elf64 => USER_STACK_TOP.as_u64(),
+            ElfClass::Elf32 => IA32_STACK_TOP,
+        };
         let stack_size = STACK_SIZE;
-        let stack_base = STACK_TOP.saturating_sub(stack_size as u64);
+        let stack_base = stack_top.saturating_sub(stack_size as u64);
 
         let stack_frames = map_stack_pages(&mut builder, &alloc, stack_base, stack_size)?;
         install_stack_vma(&child_as, stack_base, stack_size)?;
-        let executable_stack_top =
-            build_initial_process_stack(&stack_frames, stack_base, STACK_TOP, argv, envp)?;
+        let executable_stack_top = match class {
+            ElfClass::Elf64 => {
+                build_initial_process_stack(&stack_frames, stack_base, stack_top, argv, envp)?
+            }
+            ElfClass::Elf32 => {
+                build_initial_process_stack32(&stack_frames, stack_base, stack_top, argv, envp)?
+            }
+        };
         if let Some((interp, image)) = interp_image {
             let handoff =
                 build_dynamic_handoff(path, &interp, &main_image, &image, executable_stack_top);
@@ -466,6 +554,7 @@ impl ElfLoader for ExoFsElfLoader {
             cr3: child_cr3,
             addr_space_ptr,
             signal_tcb_vaddr: 0,
+            compat32: class == ElfClass::Elf32,
         })
     }
 }
@@ -616,11 +705,14 @@ fn resolve_blob_id(path_bytes: &[u8]) -> Result<BlobId, ElfLoadError> {
 }
 
 /// Lit le contenu complet d'un blob depuis le cache, puis depuis le disque ExoFS.
-fn read_blob_from_cache(blob_id: &BlobId) -> Result<Arc<[u8]>, ElfLoadError> {
+/// `path` alimente la trace de préchargement de boot.
+fn read_blob_from_cache(path: &[u8], blob_id: &BlobId) -> Result<Arc<[u8]>, ElfLoadError> {
     if let Some(data) = BLOB_CACHE.get(blob_id) {
         trace_blob(b"elf: cache hit ", blob_id);
+        boot_preload::note_open(path, *blob_id, true);
         return Ok(data);
     }
+    boot_preload::note_open(path, *blob_id, false);
 
     trace_blob(b"elf: cache miss ", blob_id);
     let Some(data) =
@@ -640,27 +732,35 @@ fn read_blob_from_cache(blob_id: &BlobId) -> Result<Arc<[u8]>, ElfLoadError> {
 }
 
 /// Valide magic, classe ELF et architecture.
-fn validate_elf_header(data: &[u8]) -> Result<(), ElfLoadError> {
-    if data.len() < 64 {
+///
+/// ELFCLASS32/EM_386 n'est accepté qu'avec la feature `ia32_compat`.
+fn validate_elf_header(data: &[u8]) -> Result<ElfClass, ElfLoadError> {
+    if data.len() < EHDR32_SIZE {
         return Err(ElfLoadError::InvalidElf);
     }
     if &data[0..4] != b"\x7FELF" {
         return Err(ElfLoadError::InvalidElf);
     }
-    // EI_CLASS = 2 → 64-bit
-    if data[4] != 2 {
-        return Err(ElfLoadError::UnsupportedArch);
-    }
     // EI_DATA = 1 → little-endian
     if data[5] != 1 {
         return Err(ElfLoadError::UnsupportedArch);
     }
-    // e_machine = 0x3E → EM_X86_64
     let e_machine = u16::from_le_bytes([data[18], data[19]]);
-    if e_machine != 0x3E {
-        return Err(ElfLoadError::UnsupportedArch);
+    match data[4] {
+        // EI_CLASS = 2 → 64-bit, e_machine = EM_X86_64
+        2 => {
+            if data.len() < 64 {
+                return Err(ElfLoadError::InvalidElf);
+            }
+            if e_machine != EM_X86_64 {
+                return Err(ElfLoadError::UnsupportedArch);
+            }
+            Ok(ElfClass::Elf64)
+        }
+        // EI_CLASS = 1 → 32-bit, e_machine = EM_386
+        1 if cfg!(feature = "ia32_compat") && e_machine == EM_386 => Ok(ElfClass::Elf32),
+        _ => Err(ElfLoadError::UnsupportedArch),
     }
-    Ok(())
 }
 
 fn elf_u16(data: &[u8], off: usize) -> u16 {
@@ -675,14 +775,30 @@ fn elf_u64(data: &[u8], off: usize) -> u64 {
     u64::from_le_bytes(data[off..off + 8].try_into().unwrap())
 }
 
-fn phdr_span(data: &[u8]) -> Result<(usize, usize, usize), ElfLoadError> {
-    if data.len() < 64 {
-        return Err(ElfLoadError::InvalidElf);
-    }
-    let e_phoff = elf_u64(data, 32) as usize;
-    let e_phentsize = elf_u16(data, 54) as usize;
-    let e_phnum = elf_u16(data, 56) as usize;
-    if e_phentsize < PHDR_SIZE {
+fn phdr_span(data: &[u8], class: ElfClass) -> Result<(usize, usize, usize), ElfLoadError> {
+    let (e_phoff, e_phentsize, e_phnum) = match class {
+        ElfClass::Elf64 => {
+            if data.len() < 64 {
+                return Err(ElfLoadError::InvalidElf);
+            }
+            (
+                elf_u64(data, 32) as usize,
+                elf_u16(data, 54) as usize,
+                elf_u16(data, 56) as usize,
+            )
+        }
+        ElfClass::Elf32 => {
+            if data.len() < EHDR32_SIZE {
+                return Err(ElfLoadError::InvalidElf);
+            }
+            (
+                elf_u32(data, 28) as usize,
+                elf_u16(data, 42) as usize,
+                elf_u16(data, 44) as usize,
+            )
+        }
+    };
+    if e_phentsize < class.phdr_size() {
         return Err(ElfLoadError::InvalidElf);
     }
     let table_bytes = e_phentsize
@@ -699,28 +815,34 @@ fn phdr_span(data: &[u8]) -> Result<(usize, usize, usize), ElfLoadError> {
 
 fn phdr_at(
     data: &[u8],
+    class: ElfClass,
     e_phoff: usize,
     e_phentsize: usize,
     idx: usize,
-) -> Result<&[u8], ElfLoadError> {
+) -> Result<ElfPhdr, ElfLoadError> {
     let off = e_phoff
         .checked_add(idx.saturating_mul(e_phentsize))
         .ok_or(ElfLoadError::InvalidElf)?;
-    let end = off.checked_add(PHDR_SIZE).ok_or(ElfLoadError::InvalidElf)?;
+    let end = off
+        .checked_add(class.phdr_size())
+        .ok_or(ElfLoadError::InvalidElf)?;
     if end > data.len() {
         return Err(ElfLoadError::InvalidElf);
     }
-    Ok(&data[off..end])
+    Ok(ElfPhdr::parse(&data[off..end], class))
 }
 
-fn read_interpreter_path(data: &[u8]) -> Result<Option<InterpreterPath>, ElfLoadError> {
-    let (e_phoff, e_phnum, e_phentsize) = phdr_span(data)?;
+fn read_interpreter_path(
+    data: &[u8],
+    class: ElfClass,
+) -> Result<Option<InterpreterPath>, ElfLoadError> {
+    let (e_phoff, e_phnum, e_phentsize) = phdr_span(data, class)?;
     let mut i = 0usize;
     while i < e_phnum {
-        let ph = phdr_at(data, e_phoff, e_phentsize, i)?;
-        if elf_u32(ph, 0) == PT_INTERP {
-            let offset = elf_u64(ph, 8) as usize;
-            let filesz = elf_u64(ph, 32) as usize;
+        let ph = phdr_at(data, class, e_phoff, e_phentsize, i)?;
+        if ph.p_type == PT_INTERP {
+            let offset = ph.p_offset as usize;
+            let filesz = ph.p_filesz as usize;
             if filesz == 0 || filesz > DYNAMIC_LOADER_PATH_MAX {
                 return Err(ElfLoadError::InvalidElf);
             }
@@ -751,13 +873,23 @@ fn default_interpreter_path(path: &str) -> Result<Option<InterpreterPath>, ElfLo
 
 fn install_elf_image(
     elf_data: &[u8],
+    class: ElfClass,
     file_id: u64,
     child_as: &UserAddressSpace,
     load_bias: u64,
 ) -> Result<LoadedElfImage, ElfLoadError> {
-    let entry_point = elf_u64(elf_data, 24).saturating_add(load_bias);
-    let phent = elf_u16(elf_data, 54) as u64;
-    let (e_phoff, e_phnum, e_phentsize) = phdr_span(elf_data)?;
+    let (e_phoff, e_phnum, e_phentsize) = phdr_span(elf_data, class)?;
+    let e_entry = match class {
+        ElfClass::Elf64 => elf_u64(elf_data, 24),
+        ElfClass::Elf32 => elf_u32(elf_data, 24) as u64,
+    };
+    let entry_point = e_entry.saturating_add(load_bias);
+    let phent = e_phentsize as u64;
+    // Taille d'une entrée Elf_Dyn (d_tag + d_val).
+    let dyn_size = match class {
+        ElfClass::Elf64 => 16,
+        ElfClass::Elf32 => 8,
+    };
     let mut brk_end = 0u64;
     let mut phdr_vaddr = 0u64;
     let mut dynamic_vaddr = 0u64;
@@ -765,19 +897,19 @@ fn install_elf_image(
 
     let mut i = 0usize;
     while i < e_phnum {
-        let ph = phdr_at(elf_data, e_phoff, e_phentsize, i)?;
-        let p_type = elf_u32(ph, 0);
-        let p_flags = elf_u32(ph, 4);
-        let p_offset = elf_u64(ph, 8) as usize;
-        let p_vaddr = elf_u64(ph, 16).saturating_add(load_bias);
-        let p_filesz = elf_u64(ph, 32) as usize;
-        let p_memsz = elf_u64(ph, 40) as usize;
+        let ph = phdr_at(elf_data, class, e_phoff, e_phentsize, i)?;
+        let p_type = ph.p_type;
+        let p_flags = ph.p_flags;
+        let p_offset = ph.p_offset as usize;
+        let p_vaddr = ph.p_vaddr.saturating_add(load_bias);
+        let p_filesz = ph.p_filesz as usize;
+        let p_memsz = ph.p_memsz as usize;
 
         if p_type == PT_PHDR {
             phdr_vaddr = p_vaddr;
         } else if p_type == PT_DYNAMIC {
             dynamic_vaddr = p_vaddr;
-            dynamic_count = (p_filesz / 16) as u64;
+            dynamic_count = (p_filesz / dyn_size) as u64;
         }
 
         if p_type != PT_LOAD {
@@ -792,7 +924,7 @@ fn install_elf_image(
         if p_filesz > p_memsz || p_offset.saturating_add(p_filesz) > elf_data.len() {
             return Err(ElfLoadError::InvalidElf);
         }
-        validate_user_segment_range(p_vaddr, p_memsz)?;
+        validate_user_segment_range(p_vaddr, p_memsz, class.user_end())?;
         register_elf_segment(file_id, p_offset as u64, p_filesz as u64)?;
 
         install_elf_vma(
@@ -863,7 +995,11 @@ fn build_dynamic_handoff(
     handoff
 }
 
-fn validate_user_segment_range(p_vaddr: u64, p_memsz: usize) -> Result<(), ElfLoadError> {
+fn validate_user_segment_range(
+    p_vaddr: u64,
+    p_memsz: usize,
+    user_end: u64,
+) -> Result<(), ElfLoadError> {
     let Some(end_unaligned) = p_vaddr.checked_add(p_memsz as u64) else {
         return Err(ElfLoadError::InvalidElf);
     };
@@ -875,7 +1011,7 @@ fn validate_user_segment_range(p_vaddr: u64, p_memsz: usize) -> Result<(), ElfLo
         return Err(ElfLoadError::InvalidElf);
     }
 
-    if end > USER_ELF_BASE_MAX {
+    if end > user_end {
         return Err(ElfLoadError::InvalidElf);
     }
 
@@ -1039,6 +1175,16 @@ fn write_stack_u64(
     write_stack_bytes(stack_frames, stack_base, user_vaddr, &value.to_ne_bytes())
 }
 
+fn push_stack_u32(
+    stack_frames: &[Frame; USER_STACK_BOOTSTRAP_PAGES],
+    stack_base: u64,
+    sp: &mut u64,
+    value: u32,
+) -> Result<(), ElfLoadError> {
+    *sp = sp.checked_sub(4).ok_or(ElfLoadError::InvalidElf)?;
+    write_stack_bytes(stack_frames, stack_base, *sp, &value.to_le_bytes())
+}
+
 fn push_stack_bytes(
     stack_frames: &[Frame; USER_STACK_BOOTSTRAP_PAGES],
     stack_base: u64,
@@ -1125,6 +1271,58 @@ fn build_initial_process_stack(
     Ok(sp)
 }
 
+/// Pile initiale i386 : même contrat que `build_initial_process_stack`, en
+/// mots de 32 bits. L'ABI i386 veut ESP % 16 == 0 avec argc en [ESP].
+fn build_initial_process_stack32(
+    stack_frames: &[Frame; USER_STACK_BOOTSTRAP_PAGES],
+    stack_base: u64,
+    stack_top: u64,
+    argv: &[&str],
+    envp: &[&str],
+) -> Result<u64, ElfLoadError> {
+    let mut sp = stack_top;
+    let mut argv_ptrs: Vec<u32> = Vec::new();
+    let mut envp_ptrs: Vec<u32> = Vec::new();
+    argv_ptrs
+        .try_reserve(argv.len())
+        .map_err(|_| ElfLoadError::OutOfMemory)?;
+    envp_ptrs
+        .try_reserve(envp.len())
+        .map_err(|_| ElfLoadError::OutOfMemory)?;
+
+    for arg in argv {
+        let ptr = push_stack_bytes(stack_frames, stack_base, &mut sp, arg.as_bytes())?;
+        argv_ptrs.push(u32::try_from(ptr).map_err(|_| ElfLoadError::InvalidElf)?);
+    }
+    for env in envp {
+        let ptr = push_stack_bytes(stack_frames, stack_base, &mut sp, env.as_bytes())?;
+        envp_ptrs.push(u32::try_from(ptr).map_err(|_| ElfLoadError::InvalidElf)?);
+    }
+
+    sp &= !15u64;
+
+    // argc + argv + NULL + envp + NULL + AT_NULL (2 mots) : compléter pour
+    // que le bloc final soit un multiple de 16 octets.
+    let word_slots = argv_ptrs.len() + envp_ptrs.len() + 5;
+    for _ in 0..(4 - word_slots % 4) % 4 {
+        push_stack_u32(stack_frames, stack_base, &mut sp, 0)?;
+    }
+    push_stack_u32(stack_frames, stack_base, &mut sp, 0)?;
+    push_stack_u32(stack_frames, stack_base, &mut sp, 0)?;
+
+    push_stack_u32(stack_frames, stack_base, &mut sp, 0)?;
+    for &ptr in envp_ptrs.iter().rev() {
+        push_stack_u32(stack_frames, stack_base, &mut sp, ptr)?;
+    }
+
+    push_stack_u32(stack_frames, stack_base, &mut sp, 0)?;
+    for &ptr in argv_ptrs.iter().rev() {
+        push_stack_u32(stack_frames, stack_base, &mut sp, ptr)?;
+    }
+    push_stack_u32(stack_frames, stack_base, &mut sp, argv.len() as u32)?;
+    Ok(sp)
+}
+
 /// Instance statique du chargeur ELF.
 pub static EXO_ELF_LOADER: ExoFsElfLoader = ExoFsElfLoader;
 
@@ -1146,4 +1344,92 @@ mod tests {
         assert_eq!(validate_load_segment_flags(PF_R | PF_W), Ok(()));
         assert_eq!(validate_load_segment_flags(PF_R), Ok(()));
     }
+
+    fn elf_ident(class: u8, machine: u16, len: usize) -> Vec<u8> {
+        let mut data = alloc::vec![0u8; len];
+        data[0..4].copy_from_slice(b"\x7FELF");
+        data[4] = class;
+        data[5] = 1;
+        data[18..20].copy_from_slice(&machine.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_validate_elf_header_x86_64() {
+        let data = elf_ident(2, EM_X86_64, 64);
+        assert_eq!(validate_elf_header(&data), Ok(ElfClass::Elf64));
+        let data = elf_ident(2, EM_386, 64);
+        assert_eq!(
+            validate_elf_header(&data),
+            Err(ElfLoadError::UnsupportedArch)
+        );
+    }
+
+    #[test]
+    fn test_validate_elf_header_elf32_gated_by_feature() {
+        let data = elf_ident(1, EM_386, EHDR32_SIZE);
+        if cfg!(feature = "ia32_compat") {
+            assert_eq!(validate_elf_header(&data), Ok(ElfClass::Elf32));
+        } else {
+            assert_eq!(
+                validate_elf_header(&data),
+                Err(ElfLoadError::UnsupportedArch)
+            );
+        }
+        let data = elf_ident(1, EM_X86_64, EHDR32_SIZE);
+        assert_eq!(
+            validate_elf_header(&data),
+            Err(ElfLoadError::UnsupportedArch)
+        );
+    }
+
+    #[test]
+    fn test_elf32_program_header_layout() {
+        let mut data = elf_ident(1, EM_386, EHDR32_SIZE + PHDR32_SIZE);
+        data[28..32].copy_from_slice(&(EHDR32_SIZE as u32).to_le_bytes());
+        data[42..44].copy_from_slice(&(PHDR32_SIZE as u16).to_le_bytes());
+        data[44..46].copy_from_slice(&1u16.to_le_bytes());
+        let ph = EHDR32_SIZE;
+        data[ph..ph + 4].copy_from_slice(&PT_LOAD.to_le_bytes());
+        data[ph + 4..ph + 8].copy_from_slice(&0x1000u32.to_le_bytes());
+        data[ph + 8..ph + 12].copy_from_slice(&0x0804_8000u32.to_le_bytes());
+        data[ph + 16..ph + 20].copy_from_slice(&0x200u32.to_le_bytes());
+        data[ph + 20..ph + 24].copy_from_slice(&0x400u32.to_le_bytes());
+        data[ph + 24..ph + 28].copy_from_slice(&(PF_R | PF_X).to_le_bytes());
+
+        let (e_phoff, e_phnum, e_phentsize) = phdr_span(&data, ElfClass::Elf32).unwrap();
+        assert_eq!(
+            (e_phoff, e_phnum, e_phentsize),
+            (EHDR32_SIZE, 1, PHDR32_SIZE)
+        );
+        let parsed = phdr_at(&data, ElfClass::Elf32, e_phoff, e_phentsize, 0).unwrap();
+        assert_eq!(
+            parsed,
+            ElfPhdr {
+                p_type: PT_LOAD,
+                p_flags: PF_R | PF_X,
+                p_offset: 0x1000,
+                p_vaddr: 0x0804_8000,
+                p_filesz: 0x200,
+                p_memsz: 0x400,
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_user_segment_range_ia32_limit() {
+        let ia32_end = ElfClass::Elf32.user_end();
+        assert_eq!(
+            validate_user_segment_range(0x0804_8000, 0x1000, ia32_end),
+            Ok(())
+        );
+        assert_eq!(
+            validate_user_segment_range(0xFFFF_F000, 0x1000, ia32_end),
+            Err(ElfLoadError::InvalidElf)
+        );
+        assert_eq!(
+            validate_user_segment_range(0x1_0000_0000, 0x1000, ElfClass::Elf64.user_end()),
+            Ok(())
+        );
+    }
 }
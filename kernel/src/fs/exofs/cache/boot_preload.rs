@@ -0,0 +1,337 @@
+//! boot_preload.rs — Trace des fichiers ouverts au boot et mesure du
+//! préchargement (no_std).
+//!
+//! Le service de préchargement d'init ouvre une fenêtre d'enregistrement au
+//! démarrage : chaque fichier existant ouvert pendant la fenêtre est noté une
+//! fois (chemin normalisé, ordre du premier accès). À la fermeture, init lit
+//! la trace et la fusionne dans la liste du prochain boot. Au boot suivant,
+//! il précharge cette liste dans `BLOB_CACHE` pendant que les services
+//! démarrent.
+//!
+//! Mesure pendant la fenêtre :
+//! - `hits`   : ouverture d'un blob préchargé, trouvé en cache ;
+//! - `misses` : ouverture qui a dû lire le disque (froide) ;
+//! - `unused` : blobs préchargés jamais ouverts (lecture disque perdue).
+//!
+//! Règles : RECUR-01, OOM-02 (try_reserve, trace bornée), ARITH-02.
+
+extern crate alloc;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::fs::exofs::core::BlobId;
+use crate::scheduler::sync::spinlock::SpinLock;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Constantes
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Fichiers distincts retenus par fenêtre.
+pub const TRACE_MAX_ENTRIES: usize = 1024;
+/// Taille maximale du texte de trace (`chemin\n` par fichier).
+pub const TRACE_MAX_BYTES: usize = 64 * 1024;
+
+const SEEN_OPENED: u8 = 1 << 0;
+const SEEN_PREFETCHED: u8 = 1 << 1;
+const SEEN_HIT: u8 = 1 << 2;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Statistiques
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Bilan de la fenêtre courante (ou de la dernière), copié tel quel vers
+/// userspace par `exo_preload(STATS)`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PreloadSnapshot {
+    /// 1 tant que la fenêtre d'enregistrement est ouverte.
+    pub recording: u64,
+    /// Fichiers distincts notés dans la trace.
+    pub traced: u64,
+    /// Fichiers ouverts mais non notés (trace pleine).
+    pub dropped: u64,
+    /// Blobs lus sur disque par le préchargement.
+    pub prefetched: u64,
+    /// Demandes de préchargement déjà satisfaites par le cache.
+    pub already_cached: u64,
+    pub hits: u64,
+    pub misses: u64,
+    /// Blobs préchargés sans ouverture pendant la fenêtre.
+    pub unused: u64,
+}
+
+impl PreloadSnapshot {
+    /// Taux de succès en pour mille : part des ouvertures « utiles » servies
+    /// par le préchargement plutôt que par le disque.
+    pub fn hit_rate_permille(&self) -> u64 {
+        let total = self.hits.saturating_add(self.misses);
+        if total == 0 {
+            return 0;
+        }
+        self.hits.saturating_mul(1000) / total
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// PreloadTrace
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// État d'une fenêtre d'enregistrement.
+pub struct PreloadTrace {
+    recording: bool,
+    /// Drapeaux `SEEN_*` par blob rencontré pendant la fenêtre.
+    seen: BTreeMap<BlobId, u8>,
+    /// `chemin\n` dans l'ordre du premier accès.
+    text: Vec<u8>,
+    stats: PreloadSnapshot,
+}
+
+impl Default for PreloadTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PreloadTrace {
+    pub const fn new() -> Self {
+        Self {
+            recording: false,
+            seen: BTreeMap::new(),
+            text: Vec::new(),
+            stats: PreloadSnapshot {
+                recording: 0,
+                traced: 0,
+                dropped: 0,
+                prefetched: 0,
+                already_cached: 0,
+                hits: 0,
+                misses: 0,
+                unused: 0,
+            },
+        }
+    }
+
+    /// Ouvre une nouvelle fenêtre : trace et compteurs repartent de zéro.
+    pub fn start(&mut self) {
+        self.seen.clear();
+        self.text.clear();
+        self.stats = PreloadSnapshot::default();
+        self.recording = true;
+    }
+
+    /// Ferme la fenêtre. La trace reste lisible jusqu'au prochain `start`.
+    pub fn stop(&mut self) {
+        self.recording = false;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Ouverture d'un fichier existant. `cached` : le blob était déjà dans
+    /// `BLOB_CACHE` avant l'ouverture.
+    pub fn note_open(&mut self, path: &[u8], blob_id: BlobId, cached: bool) {
+        if !self.recording {
+            return;
+        }
+        let flags = self.seen.get(&blob_id).copied().unwrap_or(0);
+        let mut next = flags;
+        if !cached {
+            self.stats.misses = self.stats.misses.saturating_add(1);
+        } else if flags & (SEEN_PREFETCHED | SEEN_HIT) == SEEN_PREFETCHED {
+            self.stats.hits = self.stats.hits.saturating_add(1);
+            next |= SEEN_HIT;
+        }
+        if flags & SEEN_OPENED == 0 {
+            next |= SEEN_OPENED;
+            if self.push_path(path) {
+                self.stats.traced = self.stats.traced.saturating_add(1);
+            } else {
+                self.stats.dropped = self.stats.dropped.saturating_add(1);
+            }
+        }
+        if next != flags {
+            self.seen.insert(blob_id, next);
+        }
+    }
+
+    /// Préchargement d'un blob. `loaded` : lu sur disque (absent du cache).
+    pub fn note_prefetch(&mut self, blob_id: BlobId, loaded: bool) {
+        if !loaded {
+            self.stats.already_cached = self.stats.already_cached.saturating_add(1);
+            return;
+        }
+        self.stats.prefetched = self.stats.prefetched.saturating_add(1);
+        if self.recording {
+            let flags = self.seen.get(&blob_id).copied().unwrap_or(0);
+            self.seen.insert(blob_id, flags | SEEN_PREFETCHED);
+        }
+    }
+
+    fn push_path(&mut self, path: &[u8]) -> bool {
+        if path.is_empty()
+            || path.contains(&b'\n')
+            || self.stats.traced as usize >= TRACE_MAX_ENTRIES
+            || self.text.len().saturating_add(path.len()) >= TRACE_MAX_BYTES
+        {
+            return false;
+        }
+        if self.text.try_reserve(path.len().saturating_add(1)).is_err() {
+            return false;
+        }
+        self.text.extend_from_slice(path);
+        self.text.push(b'\n');
+        true
+    }
+
+    /// Copie la trace à partir de `offset` ; retourne le nombre d'octets.
+    pub fn read(&self, offset: usize, out: &mut [u8]) -> usize {
+        let Some(rest) = self.text.get(offset..) else {
+            return 0;
+        };
+        let n = rest.len().min(out.len());
+        out[..n].copy_from_slice(&rest[..n]);
+        n
+    }
+
+    pub fn snapshot(&self) -> PreloadSnapshot {
+        let mut snap = self.stats;
+        snap.recording = self.recording as u64;
+        snap.unused = self
+            .seen
+            .values()
+            .filter(|&&f| f & SEEN_PREFETCHED != 0 && f & SEEN_OPENED == 0)
+            .count() as u64;
+        snap
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Instance globale
+// ─────────────────────────────────────────────────────────────────────────────
+
+static BOOT_PRELOAD: SpinLock<PreloadTrace> = SpinLock::new(PreloadTrace::new());
+
+pub fn start_recording() {
+    BOOT_PRELOAD.lock().start();
+}
+
+pub fn stop_recording() {
+    BOOT_PRELOAD.lock().stop();
+}
+
+/// Appelé par `open()` pour un fichier existant, hors fenêtre : coût d'un
+/// verrou et d'un test.
+pub fn note_open(path: &[u8], blob_id: BlobId, cached: bool) {
+    let mut trace = BOOT_PRELOAD.lock();
+    if trace.is_recording() {
+        trace.note_open(path, blob_id, cached);
+    }
+}
+
+pub fn note_prefetch(blob_id: BlobId, loaded: bool) {
+    BOOT_PRELOAD.lock().note_prefetch(blob_id, loaded);
+}
+
+pub fn read_trace(offset: usize, out: &mut [u8]) -> usize {
+    BOOT_PRELOAD.lock().read(offset, out)
+}
+
+pub fn snapshot() -> PreloadSnapshot {
+    BOOT_PRELOAD.lock().snapshot()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blob(b: u8) -> BlobId {
+        BlobId([b; 32])
+    }
+
+    fn trace_text(t: &PreloadTrace) -> Vec<u8> {
+        let mut out = [0u8; 256];
+        let n = t.read(0, &mut out);
+        out[..n].to_vec()
+    }
+
+    #[test]
+    fn records_first_open_only_while_recording() {
+        let mut t = PreloadTrace::new();
+        t.note_open(b"/etc/passwd", blob(1), false);
+        assert_eq!(t.snapshot(), PreloadSnapshot::default());
+
+        t.start();
+        t.note_open(b"/bin/exosh", blob(2), false);
+        t.note_open(b"/lib/libc.so", blob(3), false);
+        t.note_open(b"/bin/exosh", blob(2), true);
+        t.stop();
+        t.note_open(b"/late", blob(4), false);
+
+        assert_eq!(trace_text(&t), b"/bin/exosh\n/lib/libc.so\n");
+        let s = t.snapshot();
+        assert_eq!((s.recording, s.traced, s.misses, s.hits), (0, 2, 2, 0));
+
+        let mut tail = [0u8; 4];
+        assert_eq!(t.read(20, &mut tail), 4);
+        assert_eq!(&tail, b".so\n");
+        assert_eq!(t.read(500, &mut tail), 0);
+    }
+
+    #[test]
+    fn prefetched_blob_counts_one_hit() {
+        let mut t = PreloadTrace::new();
+        t.start();
+        t.note_prefetch(blob(1), true);
+        t.note_prefetch(blob(2), true);
+        t.note_prefetch(blob(3), false);
+        t.note_open(b"/a", blob(1), true);
+        t.note_open(b"/a", blob(1), true);
+        // Préchargé puis évincé avant usage : compté comme un miss.
+        t.note_open(b"/b", blob(2), false);
+        t.note_open(b"/c", blob(4), false);
+
+        let s = t.snapshot();
+        assert_eq!(s.prefetched, 2);
+        assert_eq!(s.already_cached, 1);
+        assert_eq!((s.hits, s.misses, s.unused), (1, 2, 0));
+        assert_eq!(s.hit_rate_permille(), 333);
+    }
+
+    #[test]
+    fn unused_prefetches_are_reported() {
+        let mut t = PreloadTrace::new();
+        t.start();
+        t.note_prefetch(blob(1), true);
+        t.note_prefetch(blob(2), true);
+        t.note_open(b"/a", blob(1), true);
+        let s = t.snapshot();
+        assert_eq!((s.hits, s.unused), (1, 1));
+        assert_eq!(s.hit_rate_permille(), 1000);
+
+        t.start();
+        assert_eq!(t.snapshot().prefetched, 0);
+        assert!(trace_text(&t).is_empty());
+    }
+
+    #[test]
+    fn trace_is_bounded() {
+        let mut t = PreloadTrace::new();
+        t.start();
+        let mut i = 0u32;
+        while i < TRACE_MAX_ENTRIES as u32 + 3 {
+            let mut id = [0u8; 32];
+            id[..4].copy_from_slice(&i.to_le_bytes());
+            t.note_open(b"/usr/share/x", BlobId(id), false);
+            i += 1;
+        }
+        t.note_open(b"/bad\npath", blob(0xee), false);
+        let s = t.snapshot();
+        assert_eq!(s.traced, TRACE_MAX_ENTRIES as u64);
+        assert_eq!(s.dropped, 4);
+    }
+}
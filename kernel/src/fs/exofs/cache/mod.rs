@@ -6,6 +6,7 @@
 //! Règles : RECUR-01, OOM-02, ARITH-02.
 
 pub mod blob_cache;
+pub mod boot_preload;
 pub mod cache_eviction;
 pub mod cache_policy;
 pub mod cache_pressure;
@@ -18,6 +19,7 @@ pub mod object_cache;
 pub mod path_cache;
 
 pub use blob_cache::{BlobCache, BLOB_CACHE};
+pub use boot_preload::PreloadSnapshot;
 pub use cache_eviction::{EvictionAlgorithm, EvictionPolicy};
 pub use cache_policy::{CacheConfig, CachePolicy};
 pub use cache_pressure::{CachePressure, CACHE_PRESSURE};
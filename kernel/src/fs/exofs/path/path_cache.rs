@@ -18,10 +18,13 @@ extern crate alloc;
 use super::path_component::fnv1a_hash;
 use crate::fs::exofs::core::ObjectId;
 use crate::scheduler::sync::spinlock::SpinLock;
+use core::sync::atomic::{AtomicU64, Ordering};
 
 pub const CACHE_SIZE: usize = 256;
 pub const CACHE_MASK: usize = CACHE_SIZE - 1;
 pub const CACHE_TTL_TICKS: u64 = 1_000_000_000;
+/// TTL effectif (tunable `fs.exofs.path_cache_ttl_ticks`).
+pub static CACHE_TTL_TUNABLE: AtomicU64 = AtomicU64::new(CACHE_TTL_TICKS);
 
 #[derive(Clone)]
 pub struct PathCacheEntry {
@@ -85,7 +88,7 @@ impl PathCacheInner {
         let e = &self.entries[idx];
         if e.valid && e.hash == hash && e.gen == self.gen {
             let now = crate::arch::time::read_ticks();
-            if now.saturating_sub(e.tick) < CACHE_TTL_TICKS {
+            if now.saturating_sub(e.tick) < CACHE_TTL_TUNABLE.load(Ordering::Relaxed) {
                 self.stats.hits = self.stats.hits.saturating_add(1);
                 let oid = e.oid.clone();
                 self.entries[idx].tick = now;
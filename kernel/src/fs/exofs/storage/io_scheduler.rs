@@ -0,0 +1,603 @@
+// kernel/src/fs/exofs/storage/io_scheduler.rs
+//
+// ═══════════════════════════════════════════════════════════════════════════════
+// Ordonnanceur d'I/O bloc à priorités — ExoFS
+// Ring 0 · no_std · Exo-OS
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Toute I/O disque passe par `virtio_adapter::with_global_disk`, qui sérialise
+// les requêtes : une seule est servie à la fois. Sans ordonnanceur, l'ordre de
+// service était celui du spinlock (arbitraire) — un téléchargement en tâche de
+// fond pouvait monopoliser le disque pendant qu'une ouverture de fichier
+// interactive attendait.
+//
+// Ce module décide QUI passe ensuite (deadline simplifié + classes façon
+// `ionice`) :
+//   1. Une requête dont l'échéance est dépassée passe en premier (la plus
+//      ancienne échéance d'abord) — aucune classe ne peut affamer les autres.
+//   2. Sinon la meilleure priorité : classe RealTime < BestEffort < Idle, puis
+//      niveau 0 (meilleur) à 7 ; à priorité égale, les lectures (synchrones,
+//      quelqu'un attend) passent avant les écritures.
+//   3. À égalité, balayage C-SCAN : LBA ≥ position courante de la tête, sinon
+//      retour au plus petit LBA.
+//   4. La classe Idle n'est servie que si le disque n'a servi aucune autre
+//      classe depuis `idle_grace_ns` (ou si son échéance, longue, expire).
+//
+// L'encodage de la priorité est celui de Linux (`ioprio_set`) :
+// `classe << 13 | niveau`, 0 = « non défini » (BestEffort niveau 4).
+//
+// Règles ExoFS appliquées :
+// - OOM-02   : file bornée (MAX_WAITERS), aucune allocation.
+// - WAITQ-01 : une requête qui attend un disque occupé dort sur la WaitQueue
+//   de son emplacement ; `complete` réveille la suivante élue.
+// - ARITH-02 : saturating_* pour toutes les échéances.
+
+#[cfg(not(test))]
+use crate::scheduler::sync::wait_queue::WaitNode;
+use crate::scheduler::sync::wait_queue::WaitQueue;
+use core::sync::atomic::{AtomicU64, Ordering};
+use spin::{Mutex, MutexGuard};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Constantes et tunables
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub const IOPRIO_CLASS_SHIFT: u32 = 13;
+pub const IOPRIO_LEVEL_MASK: u32 = (1 << IOPRIO_CLASS_SHIFT) - 1;
+/// Niveaux 0 (meilleur) à 7 dans les classes RealTime et BestEffort.
+pub const IOPRIO_LEVELS: u8 = 8;
+
+/// Requêtes en attente simultanées (= threads bloqués sur le disque).
+pub const MAX_WAITERS: usize = 64;
+
+/// Échéance d'une lecture (BestEffort).
+pub const READ_EXPIRE_NS: u64 = 100_000_000;
+/// Échéance d'une écriture (BestEffort).
+pub const WRITE_EXPIRE_NS: u64 = 1_000_000_000;
+/// Silence des autres classes exigé avant de servir la classe Idle.
+pub const IDLE_GRACE_NS: u64 = 10_000_000;
+
+pub static READ_EXPIRE_TUNABLE: AtomicU64 = AtomicU64::new(READ_EXPIRE_NS);
+pub static WRITE_EXPIRE_TUNABLE: AtomicU64 = AtomicU64::new(WRITE_EXPIRE_NS);
+pub static IDLE_GRACE_TUNABLE: AtomicU64 = AtomicU64::new(IDLE_GRACE_NS);
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Priorités
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IoClass {
+    /// Accès disque garanti en premier (root uniquement).
+    RealTime = 1,
+    BestEffort = 2,
+    /// Servi uniquement quand le disque est libre.
+    Idle = 3,
+}
+
+impl IoClass {
+    const COUNT: usize = 3;
+
+    #[inline]
+    fn index(self) -> usize {
+        self as usize - 1
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IoPriority {
+    pub class: IoClass,
+    /// 0..IOPRIO_LEVELS ; toujours 0 pour Idle.
+    pub level: u8,
+}
+
+impl IoPriority {
+    /// Priorité d'un processus qui n'a jamais appelé `ioprio_set`.
+    pub const DEFAULT: Self = Self {
+        class: IoClass::BestEffort,
+        level: 4,
+    };
+
+    /// Décode la valeur Linux ; 0 (classe « none ») donne [`Self::DEFAULT`].
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        let level = raw & IOPRIO_LEVEL_MASK;
+        let class = match raw >> IOPRIO_CLASS_SHIFT {
+            0 => return (level == 0).then_some(Self::DEFAULT),
+            1 => IoClass::RealTime,
+            2 => IoClass::BestEffort,
+            3 => IoClass::Idle,
+            _ => return None,
+        };
+        if level >= IOPRIO_LEVELS as u32 {
+            return None;
+        }
+        let level = if class == IoClass::Idle {
+            0
+        } else {
+            level as u8
+        };
+        Some(Self { class, level })
+    }
+
+    pub fn to_raw(self) -> u32 {
+        ((self.class as u32) << IOPRIO_CLASS_SHIFT) | self.level as u32
+    }
+
+    /// Rang global : plus petit = servi d'abord.
+    #[inline]
+    fn rank(self) -> u8 {
+        (self.class as u8) * IOPRIO_LEVELS + self.level
+    }
+
+    /// Échéance relative : RealTime deux fois plus pressé que BestEffort,
+    /// Idle huit fois plus patient (mais jamais affamé).
+    fn expire_ns(self, dir: IoDir) -> u64 {
+        let base = match dir {
+            IoDir::Read => READ_EXPIRE_TUNABLE.load(Ordering::Relaxed),
+            IoDir::Write => WRITE_EXPIRE_TUNABLE.load(Ordering::Relaxed),
+        };
+        match self.class {
+            IoClass::RealTime => base / 2,
+            IoClass::BestEffort => base,
+            IoClass::Idle => base.saturating_mul(8),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IoDir {
+    Read,
+    Write,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// File d'attente
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Clone, Copy, Debug)]
+struct Waiter {
+    ticket: u64,
+    prio: IoPriority,
+    dir: IoDir,
+    lba: u64,
+    enqueued_ns: u64,
+    deadline_ns: u64,
+}
+
+/// État de l'ordonnanceur. Pur (l'horloge est passée en argument) pour
+/// rester testable hors noyau.
+pub struct IoQueue {
+    waiters: [Option<Waiter>; MAX_WAITERS],
+    next_ticket: u64,
+    busy: bool,
+    /// Dernier LBA servi (position de la tête pour le C-SCAN).
+    head_lba: u64,
+    /// Fin de la dernière requête non-Idle servie.
+    last_non_idle_ns: u64,
+    in_flight: Option<IoClass>,
+}
+
+impl IoQueue {
+    pub const fn new() -> Self {
+        Self {
+            waiters: [None; MAX_WAITERS],
+            next_ticket: 1,
+            busy: false,
+            head_lba: 0,
+            last_non_idle_ns: 0,
+            in_flight: None,
+        }
+    }
+
+    pub fn pending(&self) -> usize {
+        self.waiters.iter().flatten().count()
+    }
+
+    /// Emplacement de `ticket` dans la file (index de sa WaitQueue).
+    fn slot_of(&self, ticket: u64) -> Option<usize> {
+        self.waiters
+            .iter()
+            .position(|w| w.is_some_and(|w| w.ticket == ticket))
+    }
+
+    /// Inscrit une requête ; `None` si la file est pleine (réessayer).
+    pub fn enqueue(&mut self, prio: IoPriority, dir: IoDir, lba: u64, now: u64) -> Option<u64> {
+        let slot = self.waiters.iter_mut().find(|w| w.is_none())?;
+        let ticket = self.next_ticket;
+        self.next_ticket = self.next_ticket.wrapping_add(1).max(1);
+        *slot = Some(Waiter {
+            ticket,
+            prio,
+            dir,
+            lba,
+            enqueued_ns: now,
+            deadline_ns: now.saturating_add(prio.expire_ns(dir)),
+        });
+        Some(ticket)
+    }
+
+    /// Requête à servir maintenant, selon les règles de l'en-tête.
+    fn pick(&self, now: u64) -> Option<Waiter> {
+        let waiters = || self.waiters.iter().flatten();
+        if let Some(w) = waiters()
+            .filter(|w| w.deadline_ns <= now)
+            .min_by_key(|w| (w.deadline_ns, w.ticket))
+        {
+            return Some(*w);
+        }
+        let grace = IDLE_GRACE_TUNABLE.load(Ordering::Relaxed);
+        let idle_allowed = self.in_flight.is_none_or(|c| c == IoClass::Idle)
+            && now.saturating_sub(self.last_non_idle_ns) >= grace;
+        let head = self.head_lba;
+        waiters()
+            .filter(|w| w.prio.class != IoClass::Idle || idle_allowed)
+            .min_by_key(|w| (w.prio.rank(), w.dir, w.lba < head, w.lba, w.ticket))
+            .copied()
+    }
+
+    /// Le disque est-il attribué à `ticket` ? Si oui, la requête quitte la
+    /// file et le disque est marqué occupé jusqu'à [`complete`](Self::complete).
+    pub fn try_dispatch(&mut self, ticket: u64, now: u64) -> Option<DispatchInfo> {
+        if self.busy {
+            return None;
+        }
+        let w = self.pick(now).filter(|w| w.ticket == ticket)?;
+        for slot in self.waiters.iter_mut() {
+            if slot.is_some_and(|s| s.ticket == ticket) {
+                *slot = None;
+            }
+        }
+        self.busy = true;
+        self.head_lba = w.lba;
+        self.in_flight = Some(w.prio.class);
+        Some(DispatchInfo {
+            class: w.prio.class,
+            waited_ns: now.saturating_sub(w.enqueued_ns),
+            expired: w.deadline_ns <= now,
+        })
+    }
+
+    /// Libère le disque. Retourne l'emplacement de la prochaine requête élue
+    /// (à réveiller), `None` si aucune n'est éligible maintenant.
+    pub fn complete(&mut self, now: u64) -> Option<usize> {
+        if self.in_flight.is_some_and(|c| c != IoClass::Idle) {
+            self.last_non_idle_ns = now;
+        }
+        self.busy = false;
+        self.in_flight = None;
+        self.slot_of(self.pick(now)?.ticket)
+    }
+}
+
+impl Default for IoQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DispatchInfo {
+    pub class: IoClass,
+    pub waited_ns: u64,
+    /// Servie parce que son échéance était dépassée.
+    pub expired: bool,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Statistiques
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub struct IoSchedStats {
+    dispatched: [AtomicU64; IoClass::COUNT],
+    wait_ns_total: [AtomicU64; IoClass::COUNT],
+    wait_ns_max: [AtomicU64; IoClass::COUNT],
+    expired: AtomicU64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IoClassSnapshot {
+    pub dispatched: u64,
+    pub wait_ns_total: u64,
+    pub wait_ns_max: u64,
+}
+
+impl IoSchedStats {
+    const fn new() -> Self {
+        Self {
+            dispatched: [const { AtomicU64::new(0) }; IoClass::COUNT],
+            wait_ns_total: [const { AtomicU64::new(0) }; IoClass::COUNT],
+            wait_ns_max: [const { AtomicU64::new(0) }; IoClass::COUNT],
+            expired: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, info: &DispatchInfo) {
+        let i = info.class.index();
+        self.dispatched[i].fetch_add(1, Ordering::Relaxed);
+        self.wait_ns_total[i].fetch_add(info.waited_ns, Ordering::Relaxed);
+        self.wait_ns_max[i].fetch_max(info.waited_ns, Ordering::Relaxed);
+        if info.expired {
+            self.expired.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn class(&self, class: IoClass) -> IoClassSnapshot {
+        let i = class.index();
+        IoClassSnapshot {
+            dispatched: self.dispatched[i].load(Ordering::Relaxed),
+            wait_ns_total: self.wait_ns_total[i].load(Ordering::Relaxed),
+            wait_ns_max: self.wait_ns_max[i].load(Ordering::Relaxed),
+        }
+    }
+
+    /// Requêtes servies au titre de leur échéance (signe de contention).
+    pub fn expired(&self) -> u64 {
+        self.expired.load(Ordering::Relaxed)
+    }
+}
+
+pub static IO_SCHED_STATS: IoSchedStats = IoSchedStats::new();
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Point d'entrée noyau
+// ─────────────────────────────────────────────────────────────────────────────
+
+static IO_QUEUE: Mutex<IoQueue> = Mutex::new(IoQueue::new());
+
+/// Une file de threads endormis par emplacement de [`IoQueue`].
+static IO_WAIT: [WaitQueue; MAX_WAITERS] = [const { WaitQueue::new() }; MAX_WAITERS];
+
+/// Disque attribué à l'appelant ; le rend à l'ordonnanceur en sortie de portée.
+pub struct DispatchGuard(());
+
+impl Drop for DispatchGuard {
+    fn drop(&mut self) {
+        let (next, pending) = {
+            let mut q = IO_QUEUE.lock();
+            let next = q.complete(now_ns());
+            (next, q.pending())
+        };
+        match next {
+            Some(slot) => {
+                IO_WAIT[slot].notify_all();
+            }
+            // Personne d'éligible (classe Idle dans sa période de grâce) :
+            // tout le monde repasse par `admit`, qui ne dort plus disque libre.
+            None if pending > 0 => {
+                for queue in IO_WAIT.iter() {
+                    queue.notify_all();
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+#[inline]
+fn now_ns() -> u64 {
+    0
+}
+
+#[cfg(not(test))]
+#[inline]
+fn now_ns() -> u64 {
+    crate::scheduler::timer::clock::monotonic_ns()
+}
+
+/// Priorité d'I/O du processus courant. Contexte noyau (boot, recovery,
+/// threads sans processus) et tests : BestEffort niveau 0.
+#[cfg(test)]
+pub fn current_priority() -> IoPriority {
+    KERNEL_PRIORITY
+}
+
+#[cfg(not(test))]
+pub fn current_priority() -> IoPriority {
+    // SAFETY: lecture du pointeur TCB courant publié par le scheduler dans le percpu.
+    let tcb_raw = unsafe { crate::arch::x86_64::smp::percpu::read_current_tcb() };
+    if tcb_raw == 0 {
+        return KERNEL_PRIORITY;
+    }
+    // SAFETY: tcb_raw != 0 ; pointe vers le TCB vivant du thread courant.
+    let pid =
+        unsafe { (*(tcb_raw as *const crate::scheduler::core::task::ThreadControlBlock)).pid };
+    if pid.0 == 0 {
+        return KERNEL_PRIORITY;
+    }
+    crate::process::core::registry::PROCESS_REGISTRY
+        .find_by_pid(crate::process::core::pid::Pid(pid.0))
+        .and_then(|pcb| IoPriority::from_raw(pcb.io_priority()))
+        .unwrap_or(IoPriority::DEFAULT)
+}
+
+const KERNEL_PRIORITY: IoPriority = IoPriority {
+    class: IoClass::BestEffort,
+    level: 0,
+};
+
+/// Endort le thread courant sur `queue` jusqu'au prochain `complete`.
+/// L'insertion a lieu sous le verrou de la file : un réveil émis après
+/// `drop(guard)` ne peut pas être perdu. Sans thread courant (boot) ou
+/// préemption désactivée, on ne peut pas dormir : simple pause.
+#[cfg(not(test))]
+fn park(queue: &WaitQueue, guard: MutexGuard<'_, IoQueue>) {
+    use crate::scheduler::core::task::{CpuId, TaskState, ThreadControlBlock};
+
+    // SAFETY: lecture du pointeur TCB courant publié par le scheduler dans le percpu.
+    let tcb =
+        unsafe { crate::arch::x86_64::smp::percpu::read_current_tcb() } as *mut ThreadControlBlock;
+    if tcb.is_null() || crate::scheduler::core::preempt::is_preempt_disabled() {
+        drop(guard);
+        core::hint::spin_loop();
+        return;
+    }
+    // SAFETY: tcb est le TCB vivant du thread courant ; nœud EmergencyPool (WAITQ-01).
+    unsafe {
+        let Some(node) = WaitNode::alloc(tcb, 0) else {
+            drop(guard);
+            core::hint::spin_loop();
+            return;
+        };
+        (*tcb).set_state(TaskState::Sleeping);
+        queue.insert(node);
+        drop(guard);
+        let cpu = (*tcb).cpu_id.load(Ordering::Relaxed) as usize;
+        if cpu < crate::scheduler::core::preempt::MAX_CPUS {
+            let rq = crate::scheduler::core::runqueue::run_queue(CpuId(cpu as u32));
+            crate::scheduler::core::switch::schedule_block(rq, &mut *tcb);
+        }
+    }
+}
+
+#[cfg(test)]
+fn park(_queue: &WaitQueue, guard: MutexGuard<'_, IoQueue>) {
+    drop(guard);
+    core::hint::spin_loop();
+}
+
+/// Attend que l'ordonnanceur attribue le disque à cette requête. On ne dort
+/// que disque occupé : la requête en cours réveillera la suivante élue.
+pub fn admit(prio: IoPriority, dir: IoDir, lba: u64) -> DispatchGuard {
+    let ticket = loop {
+        if let Some(t) = IO_QUEUE.lock().enqueue(prio, dir, lba, now_ns()) {
+            break t;
+        }
+        core::hint::spin_loop();
+    };
+    loop {
+        let mut q = IO_QUEUE.lock();
+        if let Some(info) = q.try_dispatch(ticket, now_ns()) {
+            drop(q);
+            IO_SCHED_STATS.record(&info);
+            return DispatchGuard(());
+        }
+        match q.slot_of(ticket) {
+            Some(slot) if q.busy => park(&IO_WAIT[slot], q),
+            _ => {
+                drop(q);
+                core::hint::spin_loop();
+            }
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BE: IoPriority = IoPriority::DEFAULT;
+    const RT: IoPriority = IoPriority {
+        class: IoClass::RealTime,
+        level: 0,
+    };
+    const IDLE: IoPriority = IoPriority {
+        class: IoClass::Idle,
+        level: 0,
+    };
+
+    /// Sert la file jusqu'au bout et retourne l'ordre des tickets.
+    fn drain(q: &mut IoQueue, tickets: &[u64], mut now: u64) -> alloc::vec::Vec<u64> {
+        let mut order = alloc::vec::Vec::new();
+        while order.len() < tickets.len() {
+            let next = tickets
+                .iter()
+                .copied()
+                .find(|t| q.try_dispatch(*t, now).is_some());
+            match next {
+                Some(t) => {
+                    order.push(t);
+                    q.complete(now);
+                }
+                None => now += 1_000_000,
+            }
+        }
+        order
+    }
+
+    #[test]
+    fn raw_encoding_matches_ioprio_set() {
+        assert_eq!(IoPriority::from_raw(0), Some(IoPriority::DEFAULT));
+        assert_eq!(IoPriority::from_raw(RT.to_raw()), Some(RT));
+        assert_eq!(IoPriority::from_raw(2 << 13 | 7).map(|p| p.level), Some(7));
+        assert_eq!(IoPriority::from_raw(3 << 13 | 5), Some(IDLE));
+        assert_eq!(IoPriority::from_raw(2 << 13 | 8), None);
+        assert_eq!(IoPriority::from_raw(4 << 13), None);
+    }
+
+    #[test]
+    fn interactive_read_overtakes_background_writes() {
+        let mut q = IoQueue::new();
+        let bulk = IoPriority {
+            class: IoClass::BestEffort,
+            level: 7,
+        };
+        let w1 = q.enqueue(bulk, IoDir::Write, 10, 0).unwrap();
+        let w2 = q.enqueue(bulk, IoDir::Write, 11, 0).unwrap();
+        let r = q.enqueue(BE, IoDir::Read, 5000, 0).unwrap();
+        let rt = q.enqueue(RT, IoDir::Write, 9000, 0).unwrap();
+        assert_eq!(drain(&mut q, &[w1, w2, r, rt], 1), [rt, r, w1, w2]);
+    }
+
+    #[test]
+    fn same_priority_sweeps_upwards_from_head() {
+        let mut q = IoQueue::new();
+        q.head_lba = 100;
+        let a = q.enqueue(BE, IoDir::Read, 50, 0).unwrap();
+        let b = q.enqueue(BE, IoDir::Read, 300, 0).unwrap();
+        let c = q.enqueue(BE, IoDir::Read, 120, 0).unwrap();
+        assert_eq!(drain(&mut q, &[a, b, c], 1), [c, b, a]);
+    }
+
+    #[test]
+    fn idle_waits_for_a_quiet_disk() {
+        let mut q = IoQueue::new();
+        let idle = q.enqueue(IDLE, IoDir::Write, 0, 0).unwrap();
+        let be = q.enqueue(BE, IoDir::Write, 0, 0).unwrap();
+        assert!(q.try_dispatch(be, 1_000).is_some());
+        assert!(q.try_dispatch(idle, 1_000).is_none(), "disque occupé");
+        q.complete(2_000);
+        assert!(q.try_dispatch(idle, 2_000 + IDLE_GRACE_NS - 1).is_none());
+        assert!(q.try_dispatch(idle, 2_000 + IDLE_GRACE_NS).is_some());
+    }
+
+    #[test]
+    fn complete_names_the_waiter_to_wake() {
+        let mut q = IoQueue::new();
+        let w = q.enqueue(BE, IoDir::Write, 0, 0).unwrap();
+        let r = q.enqueue(BE, IoDir::Read, 0, 0).unwrap();
+        assert!(q.try_dispatch(r, 1).is_some());
+        assert_eq!(q.complete(2), q.slot_of(w));
+        assert!(q.try_dispatch(w, 2).is_some());
+        q.enqueue(IDLE, IoDir::Read, 0, 3).unwrap();
+        assert_eq!(q.complete(3), None, "Idle encore en période de grâce");
+    }
+
+    #[test]
+    fn expired_requests_are_not_starved() {
+        let mut q = IoQueue::new();
+        let idle = q.enqueue(IDLE, IoDir::Read, 0, 0).unwrap();
+        let late = READ_EXPIRE_NS * 8;
+        // Flot continu de lectures BestEffort : Idle passe à son échéance.
+        let be = q.enqueue(BE, IoDir::Read, 0, late).unwrap();
+        let info = q.try_dispatch(idle, late).unwrap();
+        assert!(info.expired);
+        assert_eq!(info.waited_ns, late);
+        q.complete(late);
+        assert!(q.try_dispatch(be, late).is_some());
+    }
+
+    #[test]
+    fn queue_is_bounded() {
+        let mut q = IoQueue::new();
+        for i in 0..MAX_WAITERS {
+            assert!(q.enqueue(BE, IoDir::Read, i as u64, 0).is_some());
+        }
+        assert_eq!(q.enqueue(BE, IoDir::Read, 0, 0), None);
+        assert_eq!(q.pending(), MAX_WAITERS);
+    }
+}
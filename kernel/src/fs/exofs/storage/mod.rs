@@ -80,6 +80,9 @@ pub mod block_cache;
 /// Batching et coalescing d'opérations I/O
 pub mod io_batch;
 
+/// Ordonnanceur d'I/O bloc à priorités (classes ioprio, échéances)
+pub mod io_scheduler;
+
 /// Écriture de checksums Blake3 sur flux/blocs
 pub mod checksum_writer;
 
@@ -160,6 +163,9 @@ pub use block_cache::BlockCache;
 // I/O batch
 pub use io_batch::{BatchStats, IoBatch, IoBatchQueue, IoBatchReport};
 
+// I/O scheduler
+pub use io_scheduler::{IoClass, IoDir, IoPriority, IO_SCHED_STATS};
+
 // Checksums
 pub use checksum_reader::{ChecksumReader, VerifyMode};
 pub use checksum_writer::{BlockChecksumMap, ChecksumTag, ChecksumWriter};
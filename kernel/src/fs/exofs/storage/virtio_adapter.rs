@@ -4,6 +4,7 @@ use crate::fs::exofs::core::DiskOffset;
 use crate::fs::exofs::core::ExofsError;
 use crate::fs::exofs::core::ExofsResult;
 use crate::fs::exofs::recovery::boot_recovery::BlockDevice;
+use crate::fs::exofs::storage::io_scheduler::{self, IoDir};
 use crate::memory::core::{Frame, PageFlags, PhysAddr, VirtAddr, PAGE_SIZE};
 use crate::memory::physical::allocator::{alloc_pages, free_pages};
 use alloc::sync::Arc;
@@ -293,6 +294,29 @@ where
     f(device.as_ref())
 }
 
+/// Variante de [`with_global_disk`] qui passe d'abord par l'ordonnanceur
+/// d'I/O : l'appelant attend son tour selon la priorité d'I/O de son
+/// processus. `lba` (premier bloc visé) ne sert qu'à l'ordre de balayage.
+///
+/// Ne pas imbriquer : la requête en cours détient le disque jusqu'au retour.
+pub fn with_global_disk_io<T, F>(dir: IoDir, lba: u64, f: F) -> ExofsResult<T>
+where
+    F: FnOnce(&dyn BlockDevice) -> ExofsResult<T>,
+{
+    let _turn = io_scheduler::admit(io_scheduler::current_priority(), dir, lba);
+    with_global_disk(f)
+}
+
+/// Bloc qui contient `offset`, pour l'ordre de balayage de l'ordonnanceur
+/// (il compte en LBA, pas en octets).
+fn lba_hint(offset: DiskOffset) -> u64 {
+    let block_size = GLOBAL_DISK
+        .lock()
+        .as_ref()
+        .map_or(0, |disk| disk.block_size() as u64);
+    offset.0.checked_div(block_size).unwrap_or(0)
+}
+
 pub fn flush_global_disk() -> ExofsResult<()> {
     with_global_disk(|device| device.flush())
 }
@@ -301,7 +325,7 @@ pub fn write_at_offset(offset: DiskOffset, data: &[u8]) -> ExofsResult<usize> {
     if data.is_empty() {
         return Ok(0);
     }
-    with_global_disk(|device| {
+    with_global_disk_io(IoDir::Write, lba_hint(offset), |device| {
         let block_size = device.block_size() as usize;
         if block_size == 0 {
             return Err(ExofsError::InvalidSize);
@@ -334,7 +358,7 @@ pub fn read_at_offset(offset: DiskOffset, len: usize) -> ExofsResult<Vec<u8>> {
     if len == 0 {
         return Ok(out);
     }
-    with_global_disk(|device| {
+    with_global_disk_io(IoDir::Read, lba_hint(offset), |device| {
         let block_size = device.block_size() as usize;
         if block_size == 0 {
             return Err(ExofsError::InvalidSize);
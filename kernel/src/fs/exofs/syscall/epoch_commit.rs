@@ -508,6 +508,11 @@ fn do_commit(args: &EpochCommitArgs) -> ExofsResult<EpochCommitResult> {
     } else {
         cur
     };
+    // Compteurs de quota gardés en mémoire : dans leurs blobs avant la collecte.
+    if let Err(e) = super::quota_query::flush_quota_tables() {
+        COMMIT_STATE.store(STATE_IDLE, Ordering::Release);
+        return Err(e);
+    }
     let entries = match collect_epoch_blobs(epoch_to_commit) {
         Ok(v) => v,
         Err(e) => {
@@ -3,7 +3,7 @@
 //! RÈGLE 9/10/RECUR-01/OOM-02/ARITH-02.
 
 use super::object_fd::{OBJECT_LIFECYCLE_LOCK, OBJECT_TABLE};
-use super::quota_query::{check_quota, quota_add_usage};
+use super::quota_query::{check_project_quota, project_add_usage, project_of, set_project};
 use super::validation::{
     exofs_err_to_errno, read_user_path_heap, write_user_struct, EFAULT,
 };
@@ -128,8 +128,9 @@ fn create_object(
         return Err(ExofsError::NoSpace);
     }
     let existed_before = BLOB_CACHE.contains(&blob_id);
+    let project = parent_project(path_bytes, path_len)?;
     if !existed_before {
-        check_quota(args.owner_uid, args.initial_size, 1)?;
+        check_project_quota(args.owner_uid, project, args.initial_size, 1)?;
     }
 
     // Répercuter la création dans le répertoire parent pour que readdir
@@ -154,7 +155,7 @@ fn create_object(
             let bytes = dir_index.serialize()?;
             actual_size = bytes.len() as u64;
             if !existed_before {
-                check_quota(args.owner_uid, actual_size, 1)?;
+                check_project_quota(args.owner_uid, project, actual_size, 1)?;
             }
             BLOB_CACHE.insert(blob_id, bytes)?;
             BLOB_CACHE.mark_dirty(&blob_id).ok();
@@ -171,7 +172,7 @@ fn create_object(
             let dir_index = PathIndex::new(ObjectId([0u8; 32]));
             let bytes = dir_index.serialize()?;
             actual_size = bytes.len() as u64;
-            check_quota(args.owner_uid, actual_size, 1)?;
+            check_project_quota(args.owner_uid, project, actual_size, 1)?;
             BLOB_CACHE.insert(blob_id, bytes)?;
         } else if args.initial_size > 0 {
             let sz = args.initial_size as usize;
@@ -199,7 +200,13 @@ fn create_object(
     )?;
 
     if quota_object_added {
-        if let Err(err) = quota_add_usage(args.owner_uid, actual_size, 1) {
+        if let Some(p) = project {
+            if let Err(err) = set_project(blob_id, p) {
+                OBJECT_TABLE.close(fd);
+                return Err(err);
+            }
+        }
+        if let Err(err) = project_add_usage(args.owner_uid, project, actual_size, 1) {
             OBJECT_TABLE.close(fd);
             return Err(err);
         }
@@ -217,6 +224,21 @@ fn create_object(
     })
 }
 
+/// Projet hérité du répertoire parent (quotas par arbre).
+fn parent_project(path_bytes: &[u8], path_len: usize) -> ExofsResult<Option<u64>> {
+    let path = &path_bytes[..path_len];
+    let slash = match path.iter().rposition(|&b| b == b'/') {
+        Some(slash) => slash,
+        None => return Ok(None),
+    };
+    let parent = if slash == 0 {
+        &path[..1]
+    } else {
+        &path[..slash]
+    };
+    project_of(&BlobId::from_bytes_blake3(parent))
+}
+
 fn object_id_from_blob(blob_id: &BlobId) -> ObjectId {
     object_id_from_blob_id(blob_id)
 }
@@ -4,7 +4,7 @@
 
 use super::object_fd::{OBJECT_LIFECYCLE_LOCK, OBJECT_TABLE};
 use super::object_store;
-use super::quota_query::quota_sub_usage;
+use super::quota_query::{open_project_of, project_release, quota_sub_usage, set_project};
 use super::validation::{
     exofs_err_to_errno, kernel_struct_to_bytes, read_user_path_heap, write_user_struct, EFAULT,
 };
@@ -98,12 +98,19 @@ fn delete_blob(blob_id: BlobId, flags: u32) -> ExofsResult<DeleteResult> {
     let owner_uid = OBJECT_TABLE
         .entry_for_blob(&blob_id)
         .map(|entry| entry.owner_uid);
+    let project = open_project_of(&blob_id)?;
 
     BLOB_CACHE.invalidate(&blob_id);
     let _ = object_store::free_blob_mapping(&blob_id)?;
+    // Quotas rendus avant d'effacer la métadonnée : un échec de cette
+    // dernière ne doit pas laisser l'usage compté pour un objet supprimé.
     if let Some(owner) = owner_uid {
         quota_sub_usage(owner, bytes_freed, 1)?;
     }
+    if let Some(p) = project {
+        project_release(p, bytes_freed, 1)?;
+        set_project(blob_id, 0)?;
+    }
 
     Ok(DeleteResult {
         blob_id: *blob_id.as_bytes(),
@@ -267,6 +274,34 @@ mod tests {
         assert!(object_exists(&id));
     }
 
+    #[test]
+    fn test_delete_releases_project_usage_and_tag() {
+        use super::super::quota_query::{
+            check_project_quota, project_add_usage, project_of, quota_flags, set_quota,
+            QuotaSetArgs,
+        };
+
+        let (project, uid) = (0x7301u64, 0x7302u64);
+        set_quota(&QuotaSetArgs {
+            owner_uid: project,
+            new_soft_bytes: 0,
+            new_hard_bytes: 1000,
+            new_soft_objects: 0,
+            new_hard_objects: 2,
+            flags: quota_flags::HARD_LIMIT | quota_flags::BY_PROJECT,
+            _pad: 0,
+        })
+        .test_unwrap();
+        let id = make_blob(b"/delete/project/file", &[0u8; 900]);
+        set_project(id, project).test_unwrap();
+        project_add_usage(uid, Some(project), 900, 1).test_unwrap();
+        assert!(check_project_quota(uid, Some(project), 200, 0).is_err());
+
+        delete_blob(id, 0).test_unwrap();
+        assert!(check_project_quota(uid, Some(project), 200, 0).is_ok());
+        assert_eq!(project_of(&id).test_unwrap(), None);
+    }
+
     #[test]
     fn test_delete_nonexistent_error() {
         let id = BlobId::from_bytes_blake3(b"/does/not/exist/xyz");
@@ -110,6 +110,9 @@ pub struct ObjectFdEntry {
     pub epoch_id: u64,
     /// Uid de l'appelant (pour vérification de droits ultérieure).
     pub owner_uid: u64,
+    /// Projet de quota de l'objet (0 = aucun) : lu à l'ouverture, tenu à
+    /// jour par `quota_query::set_project`.
+    pub project: u64,
 }
 
 impl ObjectFdEntry {
@@ -124,6 +127,7 @@ impl ObjectFdEntry {
             ref_count: 0,
             epoch_id: 0,
             owner_uid: 0,
+            project: 0,
         }
     }
 
@@ -236,6 +240,7 @@ impl ObjectFdTableInner {
         size: u64,
         epoch_id: u64,
         owner_uid: u64,
+        project: u64,
         _next_fd: u32,
     ) -> ExofsResult<u32> {
         let idx = self.find_free_slot().ok_or(ExofsError::NoSpace)?;
@@ -250,6 +255,7 @@ impl ObjectFdTableInner {
             ref_count: 1,
             epoch_id,
             owner_uid,
+            project,
         };
         self.open_count = self.open_count.saturating_add(1);
         Ok(fd)
@@ -484,6 +490,15 @@ impl ObjectFdTable {
             self.errors.fetch_add(1, Ordering::Relaxed);
             return Err(ExofsError::InvalidArgument);
         }
+        // Lu une fois ici plutôt qu'à chaque écriture (hors verrou : lecture
+        // de métadonnées).
+        let project = match super::quota_query::project_of(&blob_id) {
+            Ok(project) => project.unwrap_or(0),
+            Err(err) => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+                return Err(err);
+            }
+        };
         self.acquire();
         // SAFETY: accès exclusif garanti par lock atomique acquis avant.
         let r = unsafe { &mut *self.inner.get() }.open_slot(
@@ -492,6 +507,7 @@ impl ObjectFdTable {
             size,
             epoch_id,
             owner_uid,
+            project,
             self.next_hint.load(Ordering::Relaxed),
         );
         self.release();
@@ -743,6 +759,21 @@ impl ObjectFdTable {
         count
     }
 
+    /// Met à jour le projet mémorisé dans chaque fd ouvert sur `id`.
+    pub fn set_project_for(&self, id: &BlobId, project: u64) {
+        self.acquire();
+        // SAFETY: accès exclusif garanti par lock atomique acquis avant.
+        let inner = unsafe { &mut *self.inner.get() };
+        let mut i = 0usize;
+        while i < MAX_FDS {
+            if !inner.slots[i].is_free() && inner.slots[i].blob_id == *id {
+                inner.slots[i].project = project;
+            }
+            i = i.wrapping_add(1);
+        }
+        self.release();
+    }
+
     /// Retourne une entrée ouverte portant ce BlobId, si elle existe.
     pub fn entry_for_blob(&self, id: &BlobId) -> Option<ObjectFdEntry> {
         self.acquire();
@@ -4,6 +4,7 @@
 //! RÈGLE 9/10/RECUR-01/OOM-02/ARITH-02.
 
 use super::object_fd::OBJECT_TABLE;
+use super::object_store;
 use super::validation::{
     copy_struct_from_user, exofs_err_to_errno, EFAULT, EINVAL,
     EXOFS_META_MAX,
@@ -243,18 +244,28 @@ fn serialize_entries(entries: &[MetaEntry]) -> ExofsResult<Vec<u8>> {
     Ok(buf)
 }
 
-/// Charge les entrées du cache ou retourne un Vec vide.
+/// Charge les entrées (cache, puis disque) ou retourne un Vec vide.
 fn load_entries(meta_id: BlobId) -> ExofsResult<Vec<MetaEntry>> {
-    match BLOB_CACHE.get(&meta_id) {
-        Some(data) => deserialize_entries(&data),
+    if let Some(data) = BLOB_CACHE.get(&meta_id) {
+        return deserialize_entries(&data);
+    }
+    match object_store::load_blob_data_if_available(&meta_id)? {
+        Some(data) if data.is_empty() => Ok(Vec::new()),
+        Some(data) => {
+            let entries = deserialize_entries(&data)?;
+            BLOB_CACHE.insert(meta_id, data)?;
+            Ok(entries)
+        }
         None => Ok(Vec::new()),
     }
 }
 
-/// Enregistre les entrées dans le cache.
+/// Enregistre les entrées dans le cache ; marquées dirty, elles partent sur
+/// disque au prochain commit d'epoch.
 fn save_entries(meta_id: BlobId, entries: &[MetaEntry]) -> ExofsResult<()> {
     let buf = serialize_entries(entries)?;
-    BLOB_CACHE.insert(meta_id, buf.to_vec())
+    BLOB_CACHE.insert(meta_id, buf.to_vec())?;
+    BLOB_CACHE.mark_dirty(&meta_id)
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -323,9 +334,8 @@ pub fn meta_delete(blob_id: BlobId, key: &[u8]) -> ExofsResult<()> {
 }
 
 pub fn meta_clear(blob_id: BlobId) -> ExofsResult<()> {
-    let meta_id = meta_blob_id(blob_id);
-    BLOB_CACHE.invalidate(&meta_id);
-    Ok(())
+    // Bloc vide plutôt qu'invalidation : la copie disque reviendrait sinon.
+    save_entries(meta_blob_id(blob_id), &[])
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
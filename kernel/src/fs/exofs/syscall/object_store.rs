@@ -10,6 +10,7 @@ use alloc::vec::Vec;
 use core::sync::atomic::{AtomicBool, Ordering};
 
 use crate::fs::exofs::core::{BlobId, ExofsError, ExofsResult};
+use crate::fs::exofs::storage::io_scheduler::IoDir;
 use crate::fs::exofs::storage::virtio_adapter;
 use crate::scheduler::sync::spinlock::SpinLock;
 
@@ -483,7 +484,7 @@ pub fn persist_catalog_to_global_disk() -> ExofsResult<bool> {
         return Ok(false);
     }
     let (mappings, free_extents, next_lba) = OBJECT_STORE.snapshot_catalog()?;
-    virtio_adapter::with_global_disk(|device| {
+    virtio_adapter::with_global_disk_io(IoDir::Write, OBJECT_INDEX_LBA, |device| {
         let block_size = device.block_size() as usize;
         if block_size == 0 {
             return Err(ExofsError::InvalidSize);
@@ -551,7 +552,10 @@ pub fn persist_blob_data_if_disk(blob_id: BlobId, data: &[u8], sync: bool) -> Ex
     // `Some(clé)` → chaque bloc est chiffré (XChaCha20 flux, longueur-préservant).
     let at_rest_key = crate::fs::exofs::crypto::at_rest::blob_at_rest_key(&blob_id);
 
-    let wrote = virtio_adapter::with_global_disk(|device| {
+    // L'extent n'est réservé que sous le verrou disque : la position
+    // actuelle du blob (réécriture) sert d'indication à l'ordonnanceur.
+    let lba_hint = OBJECT_STORE.lookup(&blob_id).map_or(0, |m| m.base_lba);
+    let wrote = virtio_adapter::with_global_disk_io(IoDir::Write, lba_hint, |device| {
         let block_size = device.block_size();
         let block_size_usize = block_size as usize;
         if block_size_usize == 0 {
@@ -614,7 +618,7 @@ pub fn load_blob_data_if_available(blob_id: &BlobId) -> ExofsResult<Option<Vec<u
     // FIX-F1 : déchiffrement-at-rest GATED (symétrique au persist). None → en clair.
     let at_rest_key = crate::fs::exofs::crypto::at_rest::blob_at_rest_key(blob_id);
 
-    virtio_adapter::with_global_disk(|device| {
+    virtio_adapter::with_global_disk_io(IoDir::Read, mapping.base_lba, |device| {
         if device.block_size() != mapping.block_size {
             return Err(ExofsError::InvalidState);
         }
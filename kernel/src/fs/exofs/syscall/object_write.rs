@@ -8,7 +8,9 @@
 
 use super::object_fd::OBJECT_TABLE;
 use super::object_store;
-use super::quota_query::{check_quota, quota_add_usage, quota_sub_usage};
+use super::quota_query::{
+    check_project_quota, open_project_of, project_add_usage, project_sub_usage,
+};
 use super::validation::{
     exofs_err_to_errno, read_user_buf, validate_count, validate_fd, validate_offset,
     CapabilityType, EFAULT,
@@ -163,8 +165,9 @@ fn write_blob(blob_id: BlobId, offset: u64, data: &[u8]) -> ExofsResult<WriteRes
     let new_size = write_end.max(existing_size);
     let growth = new_size.saturating_sub(existing_size);
     let owner_uid = owner_for_blob(blob_id);
+    let project = open_project_of(&blob_id)?;
     if growth > 0 {
-        check_quota(owner_uid, growth, 0)?;
+        check_project_quota(owner_uid, project, growth, 0)?;
     }
 
     let new_size_usize = new_size as usize;
@@ -177,7 +180,7 @@ fn write_blob(blob_id: BlobId, offset: u64, data: &[u8]) -> ExofsResult<WriteRes
     BLOB_CACHE.write_at(blob_id, start, data)?;
     persist_cached_blob_if_disk(blob_id)?;
     if growth > 0 {
-        quota_add_usage(owner_uid, growth, 0)?;
+        project_add_usage(owner_uid, project, growth, 0)?;
     }
 
     Ok(WriteResult {
@@ -519,12 +522,13 @@ pub fn truncate_blob(blob_id: BlobId, new_size: usize) -> ExofsResult<()> {
     ensure_blob_cached(blob_id)?;
     let current_size = BLOB_CACHE.len(&blob_id).unwrap_or(0);
     let owner_uid = owner_for_blob(blob_id);
+    let project = open_project_of(&blob_id)?;
 
     if new_size == current_size {
         return Ok(());
     }
     if new_size > current_size {
-        check_quota(owner_uid, (new_size - current_size) as u64, 0)?;
+        check_project_quota(owner_uid, project, (new_size - current_size) as u64, 0)?;
     }
 
     if !BLOB_CACHE.contains(&blob_id) {
@@ -533,9 +537,9 @@ pub fn truncate_blob(blob_id: BlobId, new_size: usize) -> ExofsResult<()> {
     BLOB_CACHE.resize(blob_id, new_size)?;
     persist_cached_blob_if_disk(blob_id)?;
     if new_size > current_size {
-        quota_add_usage(owner_uid, (new_size - current_size) as u64, 0)?;
+        project_add_usage(owner_uid, project, (new_size - current_size) as u64, 0)?;
     } else {
-        quota_sub_usage(owner_uid, (current_size - new_size) as u64, 0)?;
+        project_sub_usage(owner_uid, project, (current_size - new_size) as u64, 0)?;
     }
 
     Ok(())
@@ -1,14 +1,33 @@
 //! quota_query.rs — SYS_EXOFS_QUOTA_QUERY (515)
 //!
-//! Interroge et fixe les quotas ExoFS par objet/uid.
+//! Interroge et fixe les quotas ExoFS par uid propriétaire ou par projet.
+//!
+//! Un projet est un arbre de répertoires : `ASSIGN_PROJECT` rattache un
+//! répertoire à un identifiant de projet, puis chaque objet créé dessous
+//! hérite du projet de son parent. Octets et objets (inodes) sont comptés
+//! pour l'uid ET pour le projet ; le premier des deux plafonds atteint
+//! renvoie EDQUOT. Le projet d'un objet est une métadonnée de l'objet
+//! (`exofs.project`), persistée avec lui et mémorisée dans l'entrée de la
+//! table d'objets à l'ouverture, pour que les écritures ne la relisent pas ;
+//! seuls les objets créés après l'attribution sont comptés.
+//!
+//! Les compteurs d'usage changent à chaque écriture : les tables modifiées
+//! restent en mémoire et ne sont écrites dans leur blob qu'au commit d'epoch
+//! (`flush_quota_tables`), ou quand trop de tables sont chargées.
+//!
 //! RECUR-01 / OOM-02 / ARITH-02.
 
+use super::object_fd::OBJECT_TABLE;
+use super::object_set_meta::{meta_delete, meta_get, meta_set};
+use super::object_store;
 use super::validation::{
-    copy_kernel_bytes_to_struct, copy_struct_from_user, exofs_err_to_errno, kernel_struct_to_bytes, write_user_struct, CapabilityType, EFAULT,
+    copy_kernel_bytes_to_struct, copy_struct_from_user, exofs_err_to_errno, kernel_struct_to_bytes, read_user_path_heap, write_user_struct, CapabilityType, EFAULT,
 };
 use crate::fs::exofs::cache::blob_cache::BLOB_CACHE;
 use crate::fs::exofs::core::types::BlobId;
 use crate::fs::exofs::core::{ExofsError, ExofsResult};
+use crate::scheduler::sync::spinlock::SpinLock;
+use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -18,6 +37,10 @@ use alloc::vec::Vec;
 const QUOTA_MAGIC: u32 = 0x5155_4F54; // "QUOT"
 const QUOTA_UNLIMITED: u64 = u64::MAX;
 pub const QUOTA_MAX_ENTRIES: usize = 256;
+/// Tables de quota gardées en mémoire entre deux commits.
+const QUOTA_TABLES_MAX: usize = 64;
+/// Métadonnée portant le projet d'un objet (u64 LE).
+const PROJECT_META_KEY: &[u8] = b"exofs.project";
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Flags
@@ -29,7 +52,12 @@ pub mod quota_flags {
     pub const BY_UID: u32 = 0x0004;
     pub const SOFT_LIMIT: u32 = 0x0008;
     pub const HARD_LIMIT: u32 = 0x0010;
-    pub const VALID_MASK: u32 = GET | SET | BY_UID | SOFT_LIMIT | HARD_LIMIT;
+    /// `owner_uid` désigne un identifiant de projet.
+    pub const BY_PROJECT: u32 = 0x0020;
+    /// Rattache le répertoire pointé par a3 au projet `owner_uid` (0 = détacher).
+    pub const ASSIGN_PROJECT: u32 = 0x0040;
+    pub const VALID_MASK: u32 =
+        GET | SET | BY_UID | SOFT_LIMIT | HARD_LIMIT | BY_PROJECT | ASSIGN_PROJECT;
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -95,29 +123,58 @@ const QUOTA_ENTRY_SIZE: usize = core::mem::size_of::<QuotaEntry>();
 const QUOTA_HDR: usize = 8;
 
 // ─────────────────────────────────────────────────────────────────────────────
-// Stockage quota (blob dédié par uid)
+// Stockage quota (blob dédié par uid ou par projet)
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Dérive le BlobId de la table de quotas pour un uid.
-fn quota_blob_id(owner_uid: u64) -> BlobId {
-    let uid_bytes = owner_uid.to_le_bytes();
+/// Domaine d'une entrée de quota ; l'identifiant est rangé dans `owner_uid`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuotaScope {
+    User(u64),
+    Project(u64),
+}
+
+impl QuotaScope {
+    fn id(self) -> u64 {
+        match self {
+            Self::User(id) | Self::Project(id) => id,
+        }
+    }
+}
+
+/// Dérive le BlobId de la table de quotas d'un uid ou d'un projet.
+fn quota_blob_id(scope: QuotaScope) -> BlobId {
+    let id_bytes = scope.id().to_le_bytes();
     let mut buf = [0u8; 10];
     let mut i = 0usize;
     while i < 8 {
-        buf[i] = uid_bytes[i];
+        buf[i] = id_bytes[i];
         i = i.wrapping_add(1);
     }
-    buf[8] = 0x51;
-    buf[9] = 0x55; // "QU"
+    match scope {
+        QuotaScope::User(_) => {
+            buf[8] = 0x51;
+            buf[9] = 0x55; // "QU"
+        }
+        QuotaScope::Project(_) => {
+            buf[8] = 0x51;
+            buf[9] = 0x50; // "QP"
+        }
+    }
     BlobId::from_bytes_blake3(&buf)
 }
 
-/// Charge les entrées de quota depuis le cache.
+/// Charge les entrées de quota depuis le cache, puis le disque.
 /// OOM-02 / RECUR-01.
 fn load_quota_entries(blob_id: BlobId) -> ExofsResult<Vec<QuotaEntry>> {
     let data = match BLOB_CACHE.get(&blob_id) {
         Some(d) => d,
-        None => return Ok(Vec::new()),
+        None => match object_store::load_blob_data_if_available(&blob_id)? {
+            Some(data) => {
+                BLOB_CACHE.insert(blob_id, data)?;
+                BLOB_CACHE.get(&blob_id).ok_or(ExofsError::BlobNotFound)?
+            }
+            None => return Ok(Vec::new()),
+        },
     };
     if data.len() < QUOTA_HDR {
         return Ok(Vec::new());
@@ -142,7 +199,8 @@ fn load_quota_entries(blob_id: BlobId) -> ExofsResult<Vec<QuotaEntry>> {
     Ok(out)
 }
 
-/// Sérialise et sauvegarde les entrées de quota.
+/// Sérialise les entrées de quota dans leur blob, marqué dirty pour le
+/// prochain commit d'epoch.
 /// OOM-02 / RECUR-01.
 fn save_quota_entries(blob_id: BlobId, entries: &[QuotaEntry]) -> ExofsResult<()> {
     let n = entries.len().min(QUOTA_MAX_ENTRIES);
@@ -174,32 +232,110 @@ fn save_quota_entries(blob_id: BlobId, entries: &[QuotaEntry]) -> ExofsResult<()
     BLOB_CACHE
         .insert(blob_id, buf.to_vec())
         .map_err(|_| ExofsError::NoSpace)?;
+    BLOB_CACHE.mark_dirty(&blob_id)
+}
+
+/// Table de quota chargée ; `dirty` tant qu'elle diffère de son blob.
+struct QuotaTable {
+    entries: Vec<QuotaEntry>,
+    dirty: bool,
+}
+
+/// Tables lues ou modifiées depuis le chargement ; elles font foi sur leur
+/// blob. Ordre des verrous : `QUOTA_TABLES` puis le cache.
+static QUOTA_TABLES: SpinLock<BTreeMap<BlobId, QuotaTable>> = SpinLock::new(BTreeMap::new());
+
+fn flush_tables(tables: &mut BTreeMap<BlobId, QuotaTable>) -> ExofsResult<()> {
+    for (bid, table) in tables.iter_mut() {
+        if table.dirty {
+            save_quota_entries(*bid, &table.entries)?;
+            table.dirty = false;
+        }
+    }
     Ok(())
 }
 
+/// Écrit les tables modifiées dans leur blob. Appelé au commit d'epoch,
+/// avant la collecte des blobs dirty.
+pub fn flush_quota_tables() -> ExofsResult<()> {
+    flush_tables(&mut QUOTA_TABLES.lock())
+}
+
+/// Lit une table : la copie en mémoire si elle est chargée, le blob sinon.
+fn read_table<R>(bid: BlobId, f: impl FnOnce(&[QuotaEntry]) -> R) -> ExofsResult<R> {
+    if let Some(table) = QUOTA_TABLES.lock().get(&bid) {
+        return Ok(f(&table.entries));
+    }
+    Ok(f(&load_quota_entries(bid)?))
+}
+
+/// Modifie une table en mémoire ; elle n'est écrite qu'au prochain flush.
+/// Le blob est lu hors verrou : une table chargée entre-temps par un autre
+/// appelant l'emporte sur cette lecture.
+fn update_table<R>(
+    bid: BlobId,
+    f: impl FnOnce(&mut Vec<QuotaEntry>) -> ExofsResult<R>,
+) -> ExofsResult<R> {
+    loop {
+        let loaded = if QUOTA_TABLES.lock().contains_key(&bid) {
+            None
+        } else {
+            Some(load_quota_entries(bid)?)
+        };
+        let mut tables = QUOTA_TABLES.lock();
+        if !tables.contains_key(&bid) {
+            let entries = match loaded {
+                Some(entries) => entries,
+                // Déchargée entre les deux verrous : relire hors verrou.
+                None => continue,
+            };
+            if tables.len() >= QUOTA_TABLES_MAX {
+                flush_tables(&mut tables)?;
+                tables.clear();
+            }
+            tables.insert(
+                bid,
+                QuotaTable {
+                    entries,
+                    dirty: false,
+                },
+            );
+        }
+        let table = tables.get_mut(&bid).ok_or(ExofsError::InternalError)?;
+        let out = f(&mut table.entries)?;
+        table.dirty = true;
+        return Ok(out);
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Opérations GET / SET
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Lit les quotas d'un uid depuis le cache.
-pub fn get_quota(owner_uid: u64) -> ExofsResult<QuotaInfo> {
-    let bid = quota_blob_id(owner_uid);
-    let entries = load_quota_entries(bid)?;
-    let mut i = 0usize;
-    while i < entries.len() {
-        if entries[i].owner_uid == owner_uid {
-            return Ok(QuotaInfo {
-                used_bytes: entries[i].used_bytes,
-                soft_bytes: entries[i].soft_bytes,
-                hard_bytes: entries[i].hard_bytes,
-                used_objects: entries[i].used_objects,
-                soft_objects: entries[i].soft_objects,
-                hard_objects: entries[i].hard_objects,
-                flags: entries[i].flags,
-                _pad: 0,
-            });
+/// Lit les quotas d'un uid ou d'un projet depuis le cache.
+pub fn get_scope_quota(scope: QuotaScope) -> ExofsResult<QuotaInfo> {
+    let id = scope.id();
+    let found = read_table(quota_blob_id(scope), |entries| {
+        let mut i = 0usize;
+        while i < entries.len() {
+            if entries[i].owner_uid == id {
+                return Some(QuotaInfo {
+                    used_bytes: entries[i].used_bytes,
+                    soft_bytes: entries[i].soft_bytes,
+                    hard_bytes: entries[i].hard_bytes,
+                    used_objects: entries[i].used_objects,
+                    soft_objects: entries[i].soft_objects,
+                    hard_objects: entries[i].hard_objects,
+                    flags: entries[i].flags,
+                    _pad: 0,
+                });
+            }
+            i = i.wrapping_add(1);
         }
-        i = i.wrapping_add(1);
+        None
+    })?;
+    if let Some(info) = found {
+        return Ok(info);
     }
     // Quota non trouvé → quotas illimités par défaut.
     Ok(QuotaInfo {
@@ -214,52 +350,64 @@ pub fn get_quota(owner_uid: u64) -> ExofsResult<QuotaInfo> {
     })
 }
 
-/// Fixe les limites de quota pour un uid.
+/// Lit les quotas d'un uid depuis le cache.
+pub fn get_quota(owner_uid: u64) -> ExofsResult<QuotaInfo> {
+    get_scope_quota(QuotaScope::User(owner_uid))
+}
+
+/// Fixe les limites de quota pour un uid, ou un projet avec `BY_PROJECT`.
 /// OOM-02
 pub fn set_quota(set: &QuotaSetArgs) -> ExofsResult<()> {
-    let bid = quota_blob_id(set.owner_uid);
-    let mut entries = load_quota_entries(bid)?;
-    let mut found = false;
-    let mut i = 0usize;
-    while i < entries.len() {
-        if entries[i].owner_uid == set.owner_uid {
-            if set.flags & quota_flags::SOFT_LIMIT != 0 {
-                entries[i].soft_bytes = set.new_soft_bytes;
-                entries[i].soft_objects = set.new_soft_objects;
-            }
-            if set.flags & quota_flags::HARD_LIMIT != 0 {
-                entries[i].hard_bytes = set.new_hard_bytes;
-                entries[i].hard_objects = set.new_hard_objects;
+    let scope = if set.flags & quota_flags::BY_PROJECT != 0 {
+        QuotaScope::Project(set.owner_uid)
+    } else {
+        QuotaScope::User(set.owner_uid)
+    };
+    let bid = quota_blob_id(scope);
+    update_table(bid, |entries| {
+        let mut found = false;
+        let mut i = 0usize;
+        while i < entries.len() {
+            if entries[i].owner_uid == set.owner_uid {
+                if set.flags & quota_flags::SOFT_LIMIT != 0 {
+                    entries[i].soft_bytes = set.new_soft_bytes;
+                    entries[i].soft_objects = set.new_soft_objects;
+                }
+                if set.flags & quota_flags::HARD_LIMIT != 0 {
+                    entries[i].hard_bytes = set.new_hard_bytes;
+                    entries[i].hard_objects = set.new_hard_objects;
+                }
+                found = true;
+                break;
             }
-            found = true;
-            break;
+            i = i.wrapping_add(1);
         }
-        i = i.wrapping_add(1);
-    }
-    if !found {
-        if entries.len() >= QUOTA_MAX_ENTRIES {
-            return Err(ExofsError::QuotaExceeded);
+        if !found {
+            if entries.len() >= QUOTA_MAX_ENTRIES {
+                return Err(ExofsError::QuotaExceeded);
+            }
+            let ne = QuotaEntry {
+                owner_uid: set.owner_uid,
+                used_bytes: 0,
+                soft_bytes: set.new_soft_bytes,
+                hard_bytes: set.new_hard_bytes,
+                used_objects: 0,
+                soft_objects: set.new_soft_objects,
+                hard_objects: set.new_hard_objects,
+                flags: set.flags,
+                _pad: 0,
+            };
+            entries.try_reserve(1).map_err(|_| ExofsError::NoMemory)?;
+            entries.push(ne);
         }
-        let ne = QuotaEntry {
-            owner_uid: set.owner_uid,
-            used_bytes: 0,
-            soft_bytes: set.new_soft_bytes,
-            hard_bytes: set.new_hard_bytes,
-            used_objects: 0,
-            soft_objects: set.new_soft_objects,
-            hard_objects: set.new_hard_objects,
-            flags: set.flags,
-            _pad: 0,
-        };
-        entries.try_reserve(1).map_err(|_| ExofsError::NoMemory)?;
-        entries.push(ne);
-    }
-    save_quota_entries(quota_blob_id(set.owner_uid), &entries)
+        Ok(())
+    })?;
+    // Les limites partent tout de suite dans leur blob.
+    flush_quota_tables()
 }
 
-/// Vérifie si une allocation est dans les limites.
-pub fn check_quota(owner_uid: u64, extra_bytes: u64, extra_objects: u64) -> ExofsResult<()> {
-    let q = get_quota(owner_uid)?;
+fn check_scope(scope: QuotaScope, extra_bytes: u64, extra_objects: u64) -> ExofsResult<()> {
+    let q = get_scope_quota(scope)?;
     if q.hard_bytes != QUOTA_UNLIMITED {
         let new_bytes = q.used_bytes.saturating_add(extra_bytes);
         if new_bytes > q.hard_bytes {
@@ -275,41 +423,73 @@ pub fn check_quota(owner_uid: u64, extra_bytes: u64, extra_objects: u64) -> Exof
     Ok(())
 }
 
+/// Ajoute `bytes`/`objects` à l'usage ; une entrée sans limite est créée
+/// au premier usage pour que le compteur soit juste quand on en pose une.
+fn add_scope_usage(scope: QuotaScope, bytes: u64, objects: u64) -> ExofsResult<()> {
+    let id = scope.id();
+    let bid = quota_blob_id(scope);
+    update_table(bid, |entries| {
+        let mut i = 0usize;
+        while i < entries.len() {
+            if entries[i].owner_uid == id {
+                entries[i].used_bytes = entries[i].used_bytes.saturating_add(bytes);
+                entries[i].used_objects = entries[i].used_objects.saturating_add(objects);
+                break;
+            }
+            i = i.wrapping_add(1);
+        }
+        if i == entries.len() {
+            if entries.len() >= QUOTA_MAX_ENTRIES {
+                return Ok(());
+            }
+            entries.try_reserve(1).map_err(|_| ExofsError::NoMemory)?;
+            entries.push(QuotaEntry {
+                owner_uid: id,
+                used_bytes: bytes,
+                soft_bytes: QUOTA_UNLIMITED,
+                hard_bytes: QUOTA_UNLIMITED,
+                used_objects: objects,
+                soft_objects: QUOTA_UNLIMITED,
+                hard_objects: QUOTA_UNLIMITED,
+                flags: 0,
+                _pad: 0,
+            });
+        }
+        Ok(())
+    })
+}
+
+/// Décrémente l'usage (sans passer sous zero).
+fn sub_scope_usage(scope: QuotaScope, bytes: u64, objects: u64) -> ExofsResult<()> {
+    let id = scope.id();
+    let bid = quota_blob_id(scope);
+    update_table(bid, |entries| {
+        let mut i = 0usize;
+        while i < entries.len() {
+            if entries[i].owner_uid == id {
+                entries[i].used_bytes = entries[i].used_bytes.saturating_sub(bytes);
+                entries[i].used_objects = entries[i].used_objects.saturating_sub(objects);
+                break;
+            }
+            i = i.wrapping_add(1);
+        }
+        Ok(())
+    })
+}
+
+/// Vérifie si une allocation est dans les limites.
+pub fn check_quota(owner_uid: u64, extra_bytes: u64, extra_objects: u64) -> ExofsResult<()> {
+    check_scope(QuotaScope::User(owner_uid), extra_bytes, extra_objects)
+}
+
 /// Incrémente l'usage quota d'un uid.
 pub fn quota_add_usage(owner_uid: u64, bytes: u64, objects: u64) -> ExofsResult<()> {
-    let bid = quota_blob_id(owner_uid);
-    let mut entries = load_quota_entries(bid)?;
-    let mut found = false;
-    let mut i = 0usize;
-    while i < entries.len() {
-        if entries[i].owner_uid == owner_uid {
-            entries[i].used_bytes = entries[i].used_bytes.saturating_add(bytes);
-            entries[i].used_objects = entries[i].used_objects.saturating_add(objects);
-            found = true;
-            break;
-        }
-        i = i.wrapping_add(1);
-    }
-    if !found {
-        return Ok(());
-    }
-    save_quota_entries(quota_blob_id(owner_uid), &entries)
+    add_scope_usage(QuotaScope::User(owner_uid), bytes, objects)
 }
 
 /// Décrémente l'usage quota (sans passer sous zero).
 pub fn quota_sub_usage(owner_uid: u64, bytes: u64, objects: u64) -> ExofsResult<()> {
-    let bid = quota_blob_id(owner_uid);
-    let mut entries = load_quota_entries(bid)?;
-    let mut i = 0usize;
-    while i < entries.len() {
-        if entries[i].owner_uid == owner_uid {
-            entries[i].used_bytes = entries[i].used_bytes.saturating_sub(bytes);
-            entries[i].used_objects = entries[i].used_objects.saturating_sub(objects);
-            break;
-        }
-        i = i.wrapping_add(1);
-    }
-    save_quota_entries(quota_blob_id(owner_uid), &entries)
+    sub_scope_usage(QuotaScope::User(owner_uid), bytes, objects)
 }
 
 /// Vide le compteur d'usage d'un uid.
@@ -317,6 +497,102 @@ pub fn quota_reset_usage(owner_uid: u64) -> ExofsResult<()> {
     quota_sub_usage(owner_uid, u64::MAX, u64::MAX)
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Projets (quotas par arbre de répertoires)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Projet auquel appartient un objet, s'il y en a un.
+pub fn project_of(blob_id: &BlobId) -> ExofsResult<Option<u64>> {
+    let mut value: Vec<u8> = Vec::new();
+    match meta_get(*blob_id, PROJECT_META_KEY, &mut value) {
+        Ok(8) => Ok(Some(u64::from_le_bytes([
+            value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7],
+        ]))),
+        Ok(_) => Err(ExofsError::CorruptedStructure),
+        Err(ExofsError::ObjectNotFound) | Err(ExofsError::BlobNotFound) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Projet d'un objet pour une écriture : celui mémorisé dans la table
+/// d'objets s'il est ouvert, sinon celui de ses métadonnées.
+pub fn open_project_of(blob_id: &BlobId) -> ExofsResult<Option<u64>> {
+    match OBJECT_TABLE.entry_for_blob(blob_id) {
+        Some(entry) => Ok((entry.project != 0).then_some(entry.project)),
+        None => project_of(blob_id),
+    }
+}
+
+/// Rattache un objet à un projet ; `0` le détache.
+pub fn set_project(blob_id: BlobId, project: u64) -> ExofsResult<()> {
+    if project == 0 {
+        meta_delete(blob_id, PROJECT_META_KEY)?;
+    } else {
+        meta_set(blob_id, PROJECT_META_KEY, &project.to_le_bytes())?;
+    }
+    OBJECT_TABLE.set_project_for(&blob_id, project);
+    Ok(())
+}
+
+/// Vérifie une allocation contre le quota de l'uid et celui du projet.
+pub fn check_project_quota(
+    owner_uid: u64,
+    project: Option<u64>,
+    extra_bytes: u64,
+    extra_objects: u64,
+) -> ExofsResult<()> {
+    check_quota(owner_uid, extra_bytes, extra_objects)?;
+    match project {
+        Some(p) => check_scope(QuotaScope::Project(p), extra_bytes, extra_objects),
+        None => Ok(()),
+    }
+}
+
+/// Incrémente l'usage de l'uid et du projet.
+pub fn project_add_usage(
+    owner_uid: u64,
+    project: Option<u64>,
+    bytes: u64,
+    objects: u64,
+) -> ExofsResult<()> {
+    quota_add_usage(owner_uid, bytes, objects)?;
+    match project {
+        Some(p) => add_scope_usage(QuotaScope::Project(p), bytes, objects),
+        None => Ok(()),
+    }
+}
+
+/// Décrémente l'usage de l'uid et du projet.
+pub fn project_sub_usage(
+    owner_uid: u64,
+    project: Option<u64>,
+    bytes: u64,
+    objects: u64,
+) -> ExofsResult<()> {
+    quota_sub_usage(owner_uid, bytes, objects)?;
+    match project {
+        Some(p) => sub_scope_usage(QuotaScope::Project(p), bytes, objects),
+        None => Ok(()),
+    }
+}
+
+/// Décrémente l'usage d'un projet seul (suppression d'un objet).
+pub fn project_release(project: u64, bytes: u64, objects: u64) -> ExofsResult<()> {
+    sub_scope_usage(QuotaScope::Project(project), bytes, objects)
+}
+
+/// `ASSIGN_PROJECT` : le répertoire doit exister.
+fn assign_project(path_ptr: u64, project: u64) -> Result<(), i64> {
+    let mut path: Vec<u8> = Vec::new();
+    let len = read_user_path_heap(path_ptr, &mut path)?;
+    super::object_create::validate_create_path(&path, len).map_err(exofs_err_to_errno)?;
+    let blob_id = BlobId::from_bytes_blake3(&path[..len]);
+    if !BLOB_CACHE.contains(&blob_id) {
+        return Err(exofs_err_to_errno(ExofsError::ObjectNotFound));
+    }
+    set_project(blob_id, project).map_err(exofs_err_to_errno)
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Handler SYS_EXOFS_QUOTA_QUERY (515)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -324,7 +600,7 @@ pub fn quota_reset_usage(owner_uid: u64) -> ExofsResult<()> {
 pub fn sys_exofs_quota_query(
     args_ptr: u64,
     result_ptr: u64,
-    _a3: u64,
+    path_ptr: u64,
     _a4: u64,
     _a5: u64,
     cap_rights: u64,
@@ -343,11 +619,17 @@ pub fn sys_exofs_quota_query(
     // FIX-SEC-T0.4 : QuotaSet = admin → gate RÉEL (cap FS_ROOT ADMIN ; après le moindre
     // privilège T1.0, seul init la détient). QuotaQuery = lecture → permissif (gaté T1).
     let _ = cap_rights;
-    if args.flags & quota_flags::SET != 0 {
+    if args.flags & (quota_flags::SET | quota_flags::ASSIGN_PROJECT) != 0 {
         if let Err(e) = super::captable::check_root(CapabilityType::ExoFsQuotaSet) {
             return e;
         }
     }
+    if args.flags & quota_flags::ASSIGN_PROJECT != 0 {
+        return match assign_project(path_ptr, args.owner_uid) {
+            Ok(()) => 0,
+            Err(e) => e,
+        };
+    }
     if args.flags & quota_flags::SET != 0 {
         // SAFETY: invariant de sécurité vérifié par les préconditions de la fonction appelante.
         let set_args = match unsafe { copy_struct_from_user::<QuotaSetArgs>(args_ptr) } {
@@ -359,7 +641,12 @@ pub fn sys_exofs_quota_query(
         }
         return 0;
     }
-    let info = match get_quota(args.owner_uid) {
+    let scope = if args.flags & quota_flags::BY_PROJECT != 0 {
+        QuotaScope::Project(args.owner_uid)
+    } else {
+        QuotaScope::User(args.owner_uid)
+    };
+    let info = match get_scope_quota(scope) {
         Ok(i) => i,
         Err(e) => return exofs_err_to_errno(e),
     };
@@ -476,6 +763,85 @@ mod tests {
     fn test_quota_reset_usage() {
         quota_reset_usage(0xBEEF).ok();
     }
+
+    #[test]
+    fn test_project_quota_separate_from_uid() {
+        let project = 0x7001u64;
+        let uid = 0x7002u64;
+        let set = QuotaSetArgs {
+            owner_uid: project,
+            new_soft_bytes: 0,
+            new_hard_bytes: 1000,
+            new_soft_objects: 0,
+            new_hard_objects: 2,
+            flags: quota_flags::HARD_LIMIT | quota_flags::BY_PROJECT,
+            _pad: 0,
+        };
+        set_quota(&set).test_unwrap();
+        assert_eq!(get_quota(project).test_unwrap().hard_bytes, QUOTA_UNLIMITED);
+
+        let blob = BlobId::from_bytes_blake3(b"/srv/quota-project/file");
+        set_project(blob, project).test_unwrap();
+        assert_eq!(project_of(&blob).test_unwrap(), Some(project));
+        project_add_usage(uid, Some(project), 900, 1).test_unwrap();
+        assert_eq!(get_quota(uid).test_unwrap().used_bytes, 900);
+        assert!(check_project_quota(uid, Some(project), 200, 0).is_err());
+        assert!(check_project_quota(uid, None, 200, 0).is_ok());
+
+        project_release(project, 900, 1).test_unwrap();
+        assert!(check_project_quota(uid, Some(project), 200, 0).is_ok());
+        set_project(blob, 0).test_unwrap();
+        assert_eq!(project_of(&blob).test_unwrap(), None);
+    }
+
+    #[test]
+    fn test_usage_reaches_blob_on_flush() {
+        let uid = 0x7101u64;
+        let bid = quota_blob_id(QuotaScope::User(uid));
+        quota_add_usage(uid, 300, 2).test_unwrap();
+        quota_add_usage(uid, 200, 1).test_unwrap();
+        assert_eq!(get_quota(uid).test_unwrap().used_bytes, 500);
+
+        flush_quota_tables().test_unwrap();
+        let saved = load_quota_entries(bid).test_unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].used_bytes, 500);
+        assert_eq!(saved[0].used_objects, 3);
+        assert!(BLOB_CACHE.is_dirty(&bid));
+    }
+
+    #[test]
+    fn test_project_tag_is_object_metadata() {
+        let blob = BlobId::from_bytes_blake3(b"/srv/quota-project/tagged");
+        set_project(blob, 0x7201).test_unwrap();
+        let mut value: Vec<u8> = Vec::new();
+        meta_get(blob, PROJECT_META_KEY, &mut value).test_unwrap();
+        assert_eq!(value, 0x7201u64.to_le_bytes());
+        set_project(blob, 0).test_unwrap();
+        assert!(meta_get(blob, PROJECT_META_KEY, &mut value).is_err());
+    }
+
+    #[test]
+    fn test_open_fd_caches_the_project() {
+        use super::super::object_fd::open_flags;
+
+        let blob = BlobId::from_bytes_blake3(b"/srv/quota-project/open");
+        set_project(blob, 0x7401).test_unwrap();
+        let fd = OBJECT_TABLE
+            .open(blob, open_flags::O_RDWR, 0, 0, 0)
+            .test_unwrap();
+        assert_eq!(OBJECT_TABLE.get(fd).test_unwrap().project, 0x7401);
+        // Plus de lecture de métadonnée une fois ouvert.
+        meta_delete(blob, PROJECT_META_KEY).test_unwrap();
+        assert_eq!(open_project_of(&blob).test_unwrap(), Some(0x7401));
+        // set_project garde l'entrée à jour.
+        set_project(blob, 0x7402).test_unwrap();
+        assert_eq!(open_project_of(&blob).test_unwrap(), Some(0x7402));
+        OBJECT_TABLE.close(fd);
+        assert_eq!(open_project_of(&blob).test_unwrap(), Some(0x7402));
+        set_project(blob, 0).test_unwrap();
+        assert_eq!(open_project_of(&blob).test_unwrap(), None);
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -22,17 +22,23 @@ pub mod typed;
 
 // Canal brut (raw mailbox) — bridge syscall ↔ IPC
 pub use raw::{
+    deadline_after_ms,
     mailbox_close,
     mailbox_open,
     mailbox_open_count,
+    mailbox_set_label,
+    raw_stats_at,
     raw_stats_snapshot,
     recv_raw,
     recv_raw_checked,
     send_raw,
     // IPC-04 (v6) — variantes cap-checked pour la couche syscall
     send_raw_checked,
+    send_raw_prio,
+    RawPriority,
     RawSlotStats,
     MAX_RAW_SLOTS,
+    RAW_LABEL_LEN,
 };
 
 // Canal synchrone (rendezvous)
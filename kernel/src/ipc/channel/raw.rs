@@ -18,6 +18,7 @@ use crate::ipc::core::types::{alloc_message_id, EndpointId, IpcError, MessageId}
 use crate::ipc::stats::counters::{StatEvent, IPC_STATS};
 use crate::scheduler::sync::spinlock::SpinLock;
 use crate::security::capability::{CapTable, CapToken, Rights};
+use crate::trace::{self, TraceKind};
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Dimensionnement
@@ -31,17 +32,83 @@ pub const RAW_RING_DEPTH: usize = 16;
 
 const RAW_RING_MASK: usize = RAW_RING_DEPTH - 1;
 
+/// Longueur maximale du nom attaché à une mailbox (introspection).
+pub const RAW_LABEL_LEN: usize = 32;
+
 const _: () = assert!(
     RAW_RING_DEPTH.is_power_of_two(),
     "RAW_RING_DEPTH doit être une puissance de 2"
 );
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Priorités et échéances
+// ─────────────────────────────────────────────────────────────────────────────
+//
+// Chaque message porte une classe de priorité et, optionnellement, une
+// échéance. L'anneau garde un seul stock de RAW_RING_DEPTH messages, réparti
+// en trois voies FIFO d'indices :
+//   - High   : messages de contrôle (audio, entrée) — servis en premier ;
+//   - Normal : défaut (0), tout le trafic existant ;
+//   - Bulk   : transferts volumineux — servis quand rien d'autre n'attend.
+//
+// RÈGLE IPC-RAW-03 : ordre FIFO préservé à l'intérieur d'une voie.
+// RÈGLE IPC-RAW-04 : anti-famine — après RAW_STARVATION_LIMIT livraisons
+//   consécutives d'une voie supérieure alors qu'une voie inférieure attend,
+//   le message le plus ancien toutes voies confondues passe.
+// RÈGLE IPC-RAW-05 : un message dont l'échéance est passée n'est jamais
+//   livré ; il est retiré à la réception ou quand l'anneau est plein, et
+//   compté dans `expire_count`.
+
+/// Classe de priorité d'un message raw (5ᵉ argument de `exo_ipc_send`).
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RawPriority {
+    Normal = 0,
+    High = 1,
+    Bulk = 2,
+}
+
+impl RawPriority {
+    #[inline]
+    pub fn from_u64(raw: u64) -> Option<Self> {
+        match raw {
+            0 => Some(Self::Normal),
+            1 => Some(Self::High),
+            2 => Some(Self::Bulk),
+            _ => None,
+        }
+    }
+
+    /// Index de voie : 0 = la plus prioritaire.
+    #[inline(always)]
+    const fn lane(self) -> usize {
+        match self {
+            Self::High => 0,
+            Self::Normal => 1,
+            Self::Bulk => 2,
+        }
+    }
+}
+
+/// Nombre de voies de priorité par mailbox.
+pub const RAW_LANES: usize = 3;
+
+/// Livraisons consécutives d'une voie supérieure tolérées pendant qu'une
+/// voie inférieure attend (RÈGLE IPC-RAW-04).
+pub const RAW_STARVATION_LIMIT: u32 = 8;
+
 // ─────────────────────────────────────────────────────────────────────────────
 // InnerMsg — un message dans l'anneau (no-alloc)
 // ─────────────────────────────────────────────────────────────────────────────
 
 struct InnerMsg {
     len: usize,
+    /// Ordre d'arrivée dans la mailbox (anti-famine).
+    seq: u64,
+    /// Échéance absolue (ns monotones), 0 = aucune.
+    deadline_ns: u64,
+    /// Instant d'arrivée (ns monotones), pour la latence de livraison.
+    enqueued_ns: u64,
     data: [u8; MAX_MSG_SIZE],
 }
 
@@ -49,69 +116,211 @@ impl InnerMsg {
     const fn empty() -> Self {
         Self {
             len: 0,
+            seq: 0,
+            deadline_ns: 0,
+            enqueued_ns: 0,
             data: [0u8; MAX_MSG_SIZE],
         }
     }
+
+    #[inline(always)]
+    fn expired(&self, now_ns: u64) -> bool {
+        self.deadline_ns != 0 && self.deadline_ns <= now_ns
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
-// InnerRing — anneau circulaire (sous SpinLock)
+// Lane — file FIFO d'indices de messages d'une classe de priorité
 // ─────────────────────────────────────────────────────────────────────────────
 
+#[derive(Copy, Clone)]
+struct Lane {
+    idx: [u8; RAW_RING_DEPTH],
+    head: usize,
+    len: usize,
+}
+
+impl Lane {
+    const fn empty() -> Self {
+        Self {
+            idx: [0u8; RAW_RING_DEPTH],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn front(&self) -> Option<usize> {
+        (self.len != 0).then(|| self.idx[self.head & RAW_RING_MASK] as usize)
+    }
+
+    #[inline]
+    fn push(&mut self, msg: usize) {
+        self.idx[self.head.wrapping_add(self.len) & RAW_RING_MASK] = msg as u8;
+        self.len += 1;
+    }
+
+    #[inline]
+    fn pop_front(&mut self) {
+        self.head = self.head.wrapping_add(1);
+        self.len -= 1;
+    }
+
+    /// Garde les indices pour lesquels `keep` est vrai, dans l'ordre.
+    fn retain(&mut self, mut keep: impl FnMut(usize) -> bool) {
+        let mut kept = 0usize;
+        for i in 0..self.len {
+            let msg = self.idx[self.head.wrapping_add(i) & RAW_RING_MASK];
+            if keep(msg as usize) {
+                self.idx[self.head.wrapping_add(kept) & RAW_RING_MASK] = msg;
+                kept += 1;
+            }
+        }
+        self.len = kept;
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// InnerRing — stock de messages + voies de priorité (sous SpinLock)
+// ─────────────────────────────────────────────────────────────────────────────
+
+const _: () = assert!(
+    RAW_RING_DEPTH <= 32,
+    "le masque de slots libres tient dans un u32"
+);
+
+const RAW_ALL_FREE: u32 = u32::MAX >> (32 - RAW_RING_DEPTH);
+
 struct InnerRing {
-    head: usize,  // lecture (consommateur)
-    tail: usize,  // écriture (producteur)
+    /// Bit i = msgs[i] libre.
+    free: u32,
     count: usize, // messages présents
+    /// Messages présents portant une échéance.
+    deadlines: usize,
+    next_seq: u64,
+    /// Livraisons consécutives d'une voie supérieure alors qu'une voie
+    /// inférieure attendait.
+    bypassed: u32,
+    lanes: [Lane; RAW_LANES],
     msgs: [InnerMsg; RAW_RING_DEPTH],
 }
 
 impl InnerRing {
     const fn empty() -> Self {
         Self {
-            head: 0,
-            tail: 0,
+            free: RAW_ALL_FREE,
             count: 0,
-            // NOTE: const array init avec fonction const
-            msgs: {
-                let arr = [const { InnerMsg::empty() }; RAW_RING_DEPTH];
-                arr
-            },
+            deadlines: 0,
+            next_seq: 0,
+            bypassed: 0,
+            lanes: [Lane::empty(); RAW_LANES],
+            msgs: [const { InnerMsg::empty() }; RAW_RING_DEPTH],
         }
     }
 
     /// Enfile un message. Retourne `false` si l'anneau est plein.
     #[inline]
-    fn enqueue(&mut self, data: &[u8]) -> bool {
+    fn enqueue(
+        &mut self,
+        data: &[u8],
+        priority: RawPriority,
+        deadline_ns: u64,
+        now_ns: u64,
+    ) -> bool {
         if self.count == RAW_RING_DEPTH {
             return false;
         }
+        let idx = self.free.trailing_zeros() as usize;
+        self.free &= !(1 << idx);
         let len = data.len().min(MAX_MSG_SIZE);
-        let slot = &mut self.msgs[self.tail & RAW_RING_MASK];
+        let slot = &mut self.msgs[idx];
         slot.len = len;
+        slot.seq = self.next_seq;
+        slot.deadline_ns = deadline_ns;
+        slot.enqueued_ns = now_ns;
         slot.data[..len].copy_from_slice(&data[..len]);
-        self.tail = self.tail.wrapping_add(1);
+        self.next_seq = self.next_seq.wrapping_add(1);
+        self.lanes[priority.lane()].push(idx);
         self.count += 1;
+        if deadline_ns != 0 {
+            self.deadlines += 1;
+        }
         true
     }
 
-    /// Défile un message. Retourne `None` si vide.
+    /// Voie à servir : la plus prioritaire non vide, sauf famine d'une voie
+    /// inférieure (RÈGLE IPC-RAW-04). Retourne `(voie, famine)`.
+    fn pick_lane(&self) -> Option<(usize, bool)> {
+        let top = self.lanes.iter().position(|lane| lane.len != 0)?;
+        let lower_waiting = self.lanes[top + 1..].iter().any(|lane| lane.len != 0);
+        if !lower_waiting || self.bypassed < RAW_STARVATION_LIMIT {
+            return Some((top, false));
+        }
+        let oldest = (top..RAW_LANES)
+            .filter_map(|lane| {
+                self.lanes[lane]
+                    .front()
+                    .map(|idx| (lane, self.msgs[idx].seq))
+            })
+            .min_by_key(|&(_, seq)| seq)
+            .map(|(lane, _)| lane)?;
+        Some((oldest, true))
+    }
+
+    /// Défile un message. Retourne `None` si vide, sinon sa taille et le
+    /// temps passé dans l'anneau.
     ///
     /// Si le buffer appelant est trop petit, le message reste en tete de file :
     /// aucun dequeue partiel n'est autorise, afin d'eviter la troncature IPC.
     #[inline]
-    fn dequeue(&mut self, buf: &mut [u8]) -> Result<Option<usize>, IpcError> {
-        if self.count == 0 {
+    fn dequeue(&mut self, buf: &mut [u8], now_ns: u64) -> Result<Option<(usize, u64)>, IpcError> {
+        let Some((lane, starving)) = self.pick_lane() else {
             return Ok(None);
-        }
-        let slot = &self.msgs[self.head & RAW_RING_MASK];
+        };
+        let idx = self.lanes[lane].front().ok_or(IpcError::Internal)?;
+        let slot = &self.msgs[idx];
         if slot.len > buf.len() {
             return Err(IpcError::MessageTooLarge);
         }
         let len = slot.len;
+        let latency_ns = now_ns.saturating_sub(slot.enqueued_ns);
         buf[..len].copy_from_slice(&slot.data[..len]);
-        self.head = self.head.wrapping_add(1);
+        if slot.deadline_ns != 0 {
+            self.deadlines -= 1;
+        }
+        self.lanes[lane].pop_front();
+        self.free |= 1 << idx;
         self.count -= 1;
-        Ok(Some(len))
+        let lower_waiting = self.lanes[lane + 1..].iter().any(|l| l.len != 0);
+        self.bypassed = if !starving && lower_waiting {
+            self.bypassed.saturating_add(1)
+        } else {
+            0
+        };
+        Ok(Some((len, latency_ns)))
+    }
+
+    /// Retire les messages dont l'échéance est passée ; retourne leur nombre.
+    fn expire(&mut self, now_ns: u64) -> usize {
+        if self.deadlines == 0 {
+            return 0;
+        }
+        let msgs = &self.msgs;
+        let mut freed = 0u32;
+        for lane in self.lanes.iter_mut() {
+            lane.retain(|idx| {
+                let expired = msgs[idx].expired(now_ns);
+                if expired {
+                    freed |= 1 << idx;
+                }
+                !expired
+            });
+        }
+        let n = freed.count_ones() as usize;
+        self.free |= freed;
+        self.count -= n;
+        self.deadlines -= n;
+        n
     }
 
     #[inline(always)]
@@ -127,9 +336,11 @@ impl InnerRing {
 
     /// Réinitialise l'anneau (utilisé lors de `mailbox_close`).
     fn reset(&mut self) {
-        self.head = 0;
-        self.tail = 0;
+        self.free = RAW_ALL_FREE;
         self.count = 0;
+        self.deadlines = 0;
+        self.bypassed = 0;
+        self.lanes = [Lane::empty(); RAW_LANES];
     }
 }
 
@@ -146,6 +357,12 @@ struct RawSlot {
     send_count: AtomicU64,
     recv_count: AtomicU64,
     drop_count: AtomicU64,
+    expire_count: AtomicU64,
+    peak_depth: AtomicU64,
+    peak_latency_ns: AtomicU64,
+    total_latency_ns: AtomicU64,
+    /// Nom enregistré (`exo_ipc_create`), complété de NUL.
+    label: SpinLock<[u8; RAW_LABEL_LEN]>,
 }
 
 impl RawSlot {
@@ -156,6 +373,89 @@ impl RawSlot {
             send_count: AtomicU64::new(0),
             recv_count: AtomicU64::new(0),
             drop_count: AtomicU64::new(0),
+            expire_count: AtomicU64::new(0),
+            peak_depth: AtomicU64::new(0),
+            peak_latency_ns: AtomicU64::new(0),
+            total_latency_ns: AtomicU64::new(0),
+            label: SpinLock::new([0u8; RAW_LABEL_LEN]),
+        }
+    }
+
+    /// Remet les compteurs à zéro pour un nouvel endpoint.
+    fn reset_stats(&self) {
+        for counter in [
+            &self.send_count,
+            &self.recv_count,
+            &self.drop_count,
+            &self.expire_count,
+            &self.peak_depth,
+            &self.peak_latency_ns,
+            &self.total_latency_ns,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        *self.label.lock() = [0u8; RAW_LABEL_LEN];
+    }
+
+    /// Enfile sous le verrou et tient les compteurs à jour.
+    #[inline]
+    fn push(
+        &self,
+        ring: &mut InnerRing,
+        data: &[u8],
+        priority: RawPriority,
+        deadline_ns: u64,
+    ) -> bool {
+        let now_ns = monotonic_ns();
+        if ring.is_full() {
+            self.expire(ring, now_ns);
+        }
+        if !ring.enqueue(data, priority, deadline_ns, now_ns) {
+            return false;
+        }
+        self.send_count.fetch_add(1, Ordering::Relaxed);
+        self.peak_depth
+            .fetch_max(ring.count as u64, Ordering::Relaxed);
+        IPC_STATS.record(StatEvent::MessageSent);
+        trace::ipc(
+            TraceKind::IpcSend,
+            self.endpoint_id.load(Ordering::Relaxed),
+            data.len() as u64,
+        );
+        true
+    }
+
+    /// Défile sous le verrou et tient les compteurs à jour.
+    #[inline]
+    fn pop(&self, ring: &mut InnerRing, buf: &mut [u8]) -> Result<Option<usize>, IpcError> {
+        let now_ns = monotonic_ns();
+        self.expire(ring, now_ns);
+        let Some((len, latency_ns)) = ring.dequeue(buf, now_ns)? else {
+            return Ok(None);
+        };
+        self.recv_count.fetch_add(1, Ordering::Relaxed);
+        self.peak_latency_ns
+            .fetch_max(latency_ns, Ordering::Relaxed);
+        self.total_latency_ns
+            .fetch_add(latency_ns, Ordering::Relaxed);
+        IPC_STATS.record(StatEvent::MessageReceived);
+        trace::ipc(
+            TraceKind::IpcRecv,
+            self.endpoint_id.load(Ordering::Relaxed),
+            latency_ns,
+        );
+        Ok(Some(len))
+    }
+
+    /// Retire les messages périmés de l'anneau verrouillé (RÈGLE IPC-RAW-05).
+    #[inline]
+    fn expire(&self, ring: &mut InnerRing, now_ns: u64) {
+        let n = ring.expire(now_ns);
+        if n != 0 {
+            self.expire_count.fetch_add(n as u64, Ordering::Relaxed);
+            for _ in 0..n {
+                IPC_STATS.record(StatEvent::MessageDropped);
+            }
         }
     }
 }
@@ -173,6 +473,32 @@ static RAW_TABLE: [RawSlot; MAX_RAW_SLOTS] = [const { RawSlot::new() }; MAX_RAW_
 /// Nombre de mailboxes ouvertes.
 static OPEN_COUNT: AtomicUsize = AtomicUsize::new(0);
 
+#[cfg(not(test))]
+#[inline]
+fn monotonic_ns() -> u64 {
+    crate::scheduler::timer::clock::monotonic_ns()
+}
+
+#[cfg(test)]
+#[inline]
+fn monotonic_ns() -> u64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_nanos() as u64 + 1
+}
+
+/// Échéance absolue pour un délai relatif en millisecondes (0 = aucune).
+#[inline]
+pub fn deadline_after_ms(ms: u64) -> u64 {
+    if ms == 0 {
+        0
+    } else {
+        monotonic_ns().saturating_add(ms.saturating_mul(1_000_000))
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Fonctions internes
 // ─────────────────────────────────────────────────────────────────────────────
@@ -217,6 +543,7 @@ pub fn mailbox_open(ep_id: EndpointId) -> bool {
             .compare_exchange(0, id, Ordering::AcqRel, Ordering::Acquire)
             .is_ok()
         {
+            RAW_TABLE[idx].reset_stats();
             OPEN_COUNT.fetch_add(1, Ordering::Relaxed);
             return true;
         }
@@ -239,6 +566,17 @@ pub fn mailbox_close(ep_id: EndpointId) {
     }
 }
 
+/// Attache un nom lisible à la mailbox de `ep_id` (tronqué à
+/// `RAW_LABEL_LEN` octets). Sans effet si la mailbox n'est pas ouverte.
+pub fn mailbox_set_label(ep_id: EndpointId, name: &[u8]) {
+    if let Some(idx) = find_slot(ep_id.get()) {
+        let len = name.len().min(RAW_LABEL_LEN);
+        let mut label = RAW_TABLE[idx].label.lock();
+        *label = [0u8; RAW_LABEL_LEN];
+        label[..len].copy_from_slice(&name[..len]);
+    }
+}
+
 /// Nombre de mailboxes actuellement ouvertes.
 #[inline]
 pub fn mailbox_open_count() -> usize {
@@ -257,6 +595,20 @@ pub fn mailbox_open_count() -> usize {
 ///
 /// Retourne un `MessageId` unique alloué côté kernel.
 pub fn send_raw(ep_id: EndpointId, data: &[u8], flags: u32) -> Result<MessageId, IpcError> {
+    send_raw_prio(ep_id, data, flags, RawPriority::Normal, 0)
+}
+
+/// `send_raw` avec classe de priorité et échéance absolue (`deadline_ns`,
+/// ns monotones, 0 = aucune ; voir `deadline_after_ms`).
+///
+/// Anneau plein : les messages périmés sont retirés avant d'attendre.
+pub fn send_raw_prio(
+    ep_id: EndpointId,
+    data: &[u8],
+    flags: u32,
+    priority: RawPriority,
+    deadline_ns: u64,
+) -> Result<MessageId, IpcError> {
     if data.len() > MAX_MSG_SIZE {
         return Err(IpcError::MessageTooLarge);
     }
@@ -279,10 +631,7 @@ pub fn send_raw(ep_id: EndpointId, data: &[u8], flags: u32) -> Result<MessageId,
 
     {
         let mut ring = slot.ring.lock();
-        if !ring.is_full() {
-            ring.enqueue(data);
-            slot.send_count.fetch_add(1, Ordering::Relaxed);
-            IPC_STATS.record(StatEvent::MessageSent);
+        if slot.push(&mut ring, data, priority, deadline_ns) {
             return Ok(alloc_message_id());
         }
         // Anneau plein.
@@ -298,9 +647,7 @@ pub fn send_raw(ep_id: EndpointId, data: &[u8], flags: u32) -> Result<MessageId,
         core::hint::spin_loop();
         spins = spins.saturating_add(1);
         let mut ring = slot.ring.lock();
-        if ring.enqueue(data) {
-            slot.send_count.fetch_add(1, Ordering::Relaxed);
-            IPC_STATS.record(StatEvent::MessageSent);
+        if slot.push(&mut ring, data, priority, deadline_ns) {
             return Ok(alloc_message_id());
         }
         if spins > 200_000 {
@@ -337,10 +684,7 @@ pub fn try_send_raw_nowait(ep_id: EndpointId, data: &[u8]) -> Result<MessageId,
         return Err(IpcError::WouldBlock);
     };
 
-    if !ring.is_full() {
-        ring.enqueue(data);
-        slot.send_count.fetch_add(1, Ordering::Relaxed);
-        IPC_STATS.record(StatEvent::MessageSent);
+    if slot.push(&mut ring, data, RawPriority::Normal, 0) {
         return Ok(alloc_message_id());
     }
 
@@ -366,14 +710,8 @@ pub fn recv_raw(ep_id: EndpointId, buf: &mut [u8], flags: u32) -> Result<usize,
 
     {
         let mut ring = slot.ring.lock();
-        match ring.dequeue(buf) {
-            Ok(Some(n)) => {
-                slot.recv_count.fetch_add(1, Ordering::Relaxed);
-                IPC_STATS.record(StatEvent::MessageReceived);
-                return Ok(n);
-            }
-            Ok(None) => {}
-            Err(err) => return Err(err),
+        if let Some(n) = slot.pop(&mut ring, buf)? {
+            return Ok(n);
         }
         if nowait {
             return Err(IpcError::WouldBlock);
@@ -386,14 +724,8 @@ pub fn recv_raw(ep_id: EndpointId, buf: &mut [u8], flags: u32) -> Result<usize,
         core::hint::spin_loop();
         spins = spins.saturating_add(1);
         let mut ring = slot.ring.lock();
-        match ring.dequeue(buf) {
-            Ok(Some(n)) => {
-                slot.recv_count.fetch_add(1, Ordering::Relaxed);
-                IPC_STATS.record(StatEvent::MessageReceived);
-                return Ok(n);
-            }
-            Ok(None) => {}
-            Err(err) => return Err(err),
+        if let Some(n) = slot.pop(&mut ring, buf)? {
+            return Ok(n);
         }
         if spins > 1_000_000 {
             return Err(IpcError::Timeout);
@@ -406,32 +738,71 @@ pub fn recv_raw(ep_id: EndpointId, buf: &mut [u8], flags: u32) -> Result<usize,
 // ─────────────────────────────────────────────────────────────────────────────
 
 /// Statistiques d'une mailbox active.
+///
+/// Les compteurs repartent de zéro à chaque `mailbox_open()` d'un slot libre.
 #[derive(Copy, Clone, Debug, Default)]
 pub struct RawSlotStats {
     pub endpoint_id: u64,
     pub send_count: u64,
     pub recv_count: u64,
     pub drop_count: u64,
+    /// Messages retirés sans livraison, échéance passée.
+    pub expire_count: u64,
+    /// Messages en attente au moment du snapshot.
+    pub depth: u32,
+    pub capacity: u32,
+    /// Plus forte occupation observée.
+    pub peak_depth: u32,
+    /// Plus long séjour d'un message livré dans l'anneau.
+    pub peak_latency_ns: u64,
+    /// Somme des séjours des messages livrés (moyenne = / `recv_count`).
+    pub total_latency_ns: u64,
+    /// Nom attaché par `mailbox_set_label()`, complété de NUL.
+    pub label: [u8; RAW_LABEL_LEN],
+}
+
+impl RawSlotStats {
+    /// Stats du slot `idx`, `None` s'il est libre.
+    fn of(idx: usize) -> Option<Self> {
+        let slot = &RAW_TABLE[idx];
+        let endpoint_id = slot.endpoint_id.load(Ordering::Relaxed);
+        if endpoint_id == 0 {
+            return None;
+        }
+        Some(Self {
+            endpoint_id,
+            send_count: slot.send_count.load(Ordering::Relaxed),
+            recv_count: slot.recv_count.load(Ordering::Relaxed),
+            drop_count: slot.drop_count.load(Ordering::Relaxed),
+            expire_count: slot.expire_count.load(Ordering::Relaxed),
+            depth: slot.ring.lock().count as u32,
+            capacity: RAW_RING_DEPTH as u32,
+            peak_depth: slot.peak_depth.load(Ordering::Relaxed) as u32,
+            peak_latency_ns: slot.peak_latency_ns.load(Ordering::Relaxed),
+            total_latency_ns: slot.total_latency_ns.load(Ordering::Relaxed),
+            label: *slot.label.lock(),
+        })
+    }
 }
 
 /// Snapshot des stats de toutes les mailboxes actives.
 /// Retourne un tableau de `MAX_RAW_SLOTS` éléments (`None` = slot libre).
 pub fn raw_stats_snapshot() -> [Option<RawSlotStats>; MAX_RAW_SLOTS] {
     let mut out = [None; MAX_RAW_SLOTS];
-    for i in 0..MAX_RAW_SLOTS {
-        let ep = RAW_TABLE[i].endpoint_id.load(Ordering::Relaxed);
-        if ep != 0 {
-            out[i] = Some(RawSlotStats {
-                endpoint_id: ep,
-                send_count: RAW_TABLE[i].send_count.load(Ordering::Relaxed),
-                recv_count: RAW_TABLE[i].recv_count.load(Ordering::Relaxed),
-                drop_count: RAW_TABLE[i].drop_count.load(Ordering::Relaxed),
-            });
-        }
+    for (i, stats) in out.iter_mut().enumerate() {
+        *stats = RawSlotStats::of(i);
     }
     out
 }
 
+/// Première mailbox active à partir du slot `index` : son slot et ses stats.
+///
+/// Sert l'itération depuis l'espace utilisateur (`SYS_EXO_IPC_STAT`) sans
+/// copier toute la table.
+pub fn raw_stats_at(index: usize) -> Option<(usize, RawSlotStats)> {
+    (index..MAX_RAW_SLOTS).find_map(|i| RawSlotStats::of(i).map(|stats| (i, stats)))
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // API capability-checked — IPC-04 (v6) : appel direct security/access_control/
 // ─────────────────────────────────────────────────────────────────────────────
@@ -469,9 +840,144 @@ pub fn recv_raw_checked(
 
 #[cfg(test)]
 mod tests {
-    use super::{mailbox_close, mailbox_open, recv_raw, send_raw};
+    use super::{
+        mailbox_close, mailbox_open, mailbox_set_label, raw_stats_at, raw_stats_snapshot, recv_raw,
+        send_raw, send_raw_prio, InnerRing, RawPriority, RAW_RING_DEPTH, RAW_STARVATION_LIMIT,
+    };
     use crate::ipc::core::types::EndpointId;
 
+    fn next(ring: &mut InnerRing) -> Option<u8> {
+        let mut out = [0u8; 8];
+        ring.dequeue(&mut out, 0).unwrap().map(|_| out[0])
+    }
+
+    #[test]
+    fn test_raw_lanes_deliver_high_first_fifo_within_lane() {
+        let mut ring = InnerRing::empty();
+        assert!(ring.enqueue(&[1], RawPriority::Bulk, 0, 0));
+        assert!(ring.enqueue(&[2], RawPriority::Normal, 0, 0));
+        assert!(ring.enqueue(&[3], RawPriority::High, 0, 0));
+        assert!(ring.enqueue(&[4], RawPriority::Normal, 0, 0));
+        assert!(ring.enqueue(&[5], RawPriority::High, 0, 0));
+        let order: [Option<u8>; 6] = core::array::from_fn(|_| next(&mut ring));
+        assert_eq!(order, [Some(3), Some(5), Some(2), Some(4), Some(1), None]);
+    }
+
+    #[test]
+    fn test_raw_lanes_starvation_guard() {
+        let mut ring = InnerRing::empty();
+        assert!(ring.enqueue(&[0xB0], RawPriority::Bulk, 0, 0));
+        let mut high_before_bulk = 0u32;
+        for round in 0..=RAW_STARVATION_LIMIT as u8 {
+            assert!(ring.enqueue(&[round], RawPriority::High, 0, 0));
+            if next(&mut ring) == Some(0xB0) {
+                break;
+            }
+            high_before_bulk += 1;
+        }
+        assert_eq!(high_before_bulk, RAW_STARVATION_LIMIT);
+    }
+
+    #[test]
+    fn test_raw_deadlines_expire_without_delivery() {
+        let mut ring = InnerRing::empty();
+        assert!(ring.enqueue(&[1], RawPriority::High, 100, 0));
+        assert!(ring.enqueue(&[2], RawPriority::Normal, 0, 0));
+        assert!(ring.enqueue(&[3], RawPriority::Normal, 200, 0));
+        assert_eq!(ring.expire(99), 0);
+        assert_eq!(ring.expire(100), 1);
+        assert_eq!(next(&mut ring), Some(2));
+        assert_eq!(next(&mut ring), Some(3));
+        assert_eq!(ring.expire(u64::MAX), 0);
+        assert!(ring.is_empty());
+
+        // Les slots libérés par l'expiration sont réutilisables.
+        for i in 0..RAW_RING_DEPTH as u8 {
+            assert!(ring.enqueue(&[i], RawPriority::Bulk, 10, 0));
+        }
+        assert!(!ring.enqueue(&[0xFF], RawPriority::High, 0, 0));
+        assert_eq!(ring.expire(10), RAW_RING_DEPTH);
+        assert!(ring.enqueue(&[0xFF], RawPriority::High, 0, 0));
+        assert_eq!(next(&mut ring), Some(0xFF));
+    }
+
+    #[test]
+    fn test_raw_send_prio_skips_stale_messages() {
+        let ep = EndpointId::new(11).unwrap();
+        mailbox_close(ep);
+        assert!(mailbox_open(ep));
+
+        // Échéance 1 ns : déjà passée à la réception.
+        send_raw_prio(ep, b"stale", 0, RawPriority::High, 1).expect("send stale");
+        send_raw_prio(ep, b"bulk", 0, RawPriority::Bulk, 0).expect("send bulk");
+        send_raw(ep, b"normal", 0).expect("send normal");
+
+        let mut out = [0u8; 32];
+        let n = recv_raw(ep, &mut out, 0x0001).expect("recv normal");
+        assert_eq!(&out[..n], b"normal");
+        let n = recv_raw(ep, &mut out, 0x0001).expect("recv bulk");
+        assert_eq!(&out[..n], b"bulk");
+        let stats = raw_stats_snapshot()
+            .into_iter()
+            .flatten()
+            .find(|s| s.endpoint_id == 11)
+            .expect("stats");
+        assert_eq!(stats.expire_count, 1);
+
+        mailbox_close(ep);
+    }
+
+    #[test]
+    fn test_raw_dequeue_reports_latency() {
+        let mut ring = InnerRing::empty();
+        assert!(ring.enqueue(&[7], RawPriority::Normal, 0, 1_000));
+        let mut out = [0u8; 8];
+        assert_eq!(ring.dequeue(&mut out, 4_500).unwrap(), Some((1, 3_500)));
+        assert_eq!(ring.dequeue(&mut out, 5_000).unwrap(), None);
+    }
+
+    #[test]
+    fn test_raw_stats_track_depth_latency_and_label() {
+        let ep = EndpointId::new(12).unwrap();
+        mailbox_close(ep);
+        assert!(mailbox_open(ep));
+        mailbox_set_label(ep, b"exo.test.stats");
+
+        send_raw(ep, b"a", 0).expect("send a");
+        send_raw(ep, b"b", 0).expect("send b");
+        let mut out = [0u8; 8];
+        recv_raw(ep, &mut out, 0x0001).expect("recv a");
+
+        let mut index = 0;
+        let stats = loop {
+            let (slot, stats) = raw_stats_at(index).expect("stats");
+            if stats.endpoint_id == 12 {
+                break stats;
+            }
+            index = slot + 1;
+        };
+        assert_eq!((stats.depth, stats.peak_depth), (1, 2));
+        assert_eq!(stats.capacity, RAW_RING_DEPTH as u32);
+        assert_eq!((stats.send_count, stats.recv_count), (2, 1));
+        assert!(stats.peak_latency_ns <= stats.total_latency_ns);
+        assert_eq!(&stats.label[..15], b"exo.test.stats\0");
+
+        // Un nouvel endpoint sur le même slot repart de zéro.
+        mailbox_close(ep);
+        assert!(mailbox_open(ep));
+        let stats = raw_stats_snapshot()
+            .into_iter()
+            .flatten()
+            .find(|s| s.endpoint_id == 12)
+            .expect("stats");
+        assert_eq!(
+            (stats.send_count, stats.peak_depth, stats.label[0]),
+            (0, 0, 0)
+        );
+
+        mailbox_close(ep);
+    }
+
     #[test]
     fn test_raw_mailbox_open_send_recv_roundtrip() {
         let ep = EndpointId::new(5).unwrap();
@@ -105,6 +105,9 @@ pub struct ShmMapping {
     pub process_id: AtomicU32,
     /// Adresse virtuelle de début du mapping dans l'espace du processus
     pub virt_base: AtomicU64,
+    /// Espace d'adressage cible (adresse de son descripteur, 0 = inconnu) :
+    /// permet d'oublier le mapping au munmap ou à la destruction de l'espace.
+    pub addr_space: AtomicU64,
     /// Permissions effectives du mapping (intersection créateur + demandeur)
     pub permissions: AtomicU32,
     /// Nombre de pages mappées
@@ -113,7 +116,7 @@ pub struct ShmMapping {
     pub created_at: AtomicU64,
     /// Mapping actif / libéré
     pub active: AtomicU32,
-    _pad: [u8; 12],
+    _pad: [u8; 4],
 }
 
 // SAFETY: tous les champs sont atomiques
@@ -126,11 +129,12 @@ impl ShmMapping {
             desc_idx: AtomicU32::new(u32::MAX),
             process_id: AtomicU32::new(0),
             virt_base: AtomicU64::new(0),
+            addr_space: AtomicU64::new(0),
             permissions: AtomicU32::new(0),
             mapped_pages: AtomicU32::new(0),
             created_at: AtomicU64::new(0),
             active: AtomicU32::new(0),
-            _pad: [0u8; 12],
+            _pad: [0u8; 4],
         }
     }
 
@@ -198,6 +202,7 @@ impl ShmMappingTable {
             entry.desc_idx.store(u32::MAX, Ordering::Relaxed);
             entry.process_id.store(0, Ordering::Relaxed);
             entry.virt_base.store(0, Ordering::Relaxed);
+            entry.addr_space.store(0, Ordering::Relaxed);
             entry.permissions.store(0, Ordering::Relaxed);
             entry.mapped_pages.store(0, Ordering::Relaxed);
             entry.created_at.store(0, Ordering::Relaxed);
@@ -213,6 +218,27 @@ impl ShmMappingTable {
         self.entries.get(idx)
     }
 
+    pub(crate) fn active_mapping_at(&self, pid: u32, virt_base: u64) -> Option<usize> {
+        self.entries.iter().position(|entry| {
+            entry.is_active()
+                && entry.process_id.load(Ordering::Relaxed) == pid
+                && entry.virt_base.load(Ordering::Relaxed) == virt_base
+        })
+    }
+
+    /// Mapping actif posé dans `addr_space` (à `virt_base` si précisé).
+    pub(crate) fn active_mapping_in(
+        &self,
+        addr_space: u64,
+        virt_base: Option<u64>,
+    ) -> Option<usize> {
+        self.entries.iter().position(|entry| {
+            entry.is_active()
+                && entry.addr_space.load(Ordering::Relaxed) == addr_space
+                && virt_base.is_none_or(|v| entry.virt_base.load(Ordering::Relaxed) == v)
+        })
+    }
+
     pub(crate) fn active_mapping_for_desc(&self, desc_idx: usize) -> Option<usize> {
         self.entries
             .iter()
@@ -8,6 +8,8 @@ use core::sync::atomic::Ordering;
 
 use crate::ipc::shared_memory::descriptor::SHM_DESC_DIR;
 use crate::ipc::shared_memory::mapping::SHM_MAPPING_TABLE;
+use crate::ipc::shared_memory::pool::shm_pool_contains;
+use crate::ipc::shared_memory::user_region;
 use crate::memory::virt::mmap::{
     register_shm_provider, ShmMapError, ShmProviderFns, ShmRegionInfo,
 };
@@ -18,6 +20,8 @@ pub fn register_with_memory() {
         page_phys,
         release_region,
         register_mapping,
+        owns_frame: shm_pool_contains,
+        forget_mappings: user_region::forget_address_space,
     });
 }
 
@@ -62,6 +66,7 @@ fn release_region(desc_idx: usize) {
 fn register_mapping(
     desc_idx: usize,
     pid: u32,
+    addr_space: u64,
     virt_base: u64,
     writable: bool,
     n_pages: usize,
@@ -84,6 +89,7 @@ fn register_mapping(
         mapping.desc_idx.store(desc_idx as u32, Ordering::Relaxed);
         mapping.process_id.store(pid, Ordering::Relaxed);
         mapping.virt_base.store(virt_base, Ordering::Relaxed);
+        mapping.addr_space.store(addr_space, Ordering::Relaxed);
         mapping
             .permissions
             .store(if writable { 0x3 } else { 0x1 }, Ordering::Relaxed);
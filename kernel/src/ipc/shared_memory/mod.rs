@@ -7,6 +7,7 @@
 //   - mapping   : association région ↔ espace virtuel processus
 //   - allocator : allocation par classe de taille (Small/Medium/Large/Huge)
 //   - numa_aware: allocation avec affinité NUMA (jusqu'à 8 nœuds)
+//   - user_region: régions créées et mappées depuis userspace (exo_shm)
 
 pub mod allocator;
 pub mod descriptor;
@@ -15,6 +16,7 @@ pub mod memory_bridge;
 pub mod numa_aware;
 pub mod page;
 pub mod pool;
+pub mod user_region;
 
 // ---------------------------------------------------------------------------
 // Re-exports
@@ -26,7 +28,8 @@ pub use page::{PageFlags, PhysAddr, ShmPage, ShmPageStats, HUGE_PAGE_SIZE, PAGE_
 // Pool de pages
 pub use pool::{
     init_shm_pool, shm_alloc_contiguous, shm_free_contiguous, shm_page_alloc, shm_page_free,
-    shm_page_phys, shm_page_ref, shm_pool_stats, ShmPoolStats, POOL_BITMAP_WORDS,
+    shm_page_phys, shm_page_ref, shm_pool_contains, shm_pool_stats, ShmPoolStats,
+    POOL_BITMAP_WORDS,
 };
 
 // Descripteurs de régions
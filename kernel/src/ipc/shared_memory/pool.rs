@@ -148,6 +148,13 @@ pub fn shm_page_phys(page_idx: usize) -> Option<PhysAddr> {
     }
 }
 
+/// Indique si `phys` est une frame du pool SHM. Ces frames ne sont jamais
+/// rendues au buddy par le démappage d'un espace d'adressage.
+pub fn shm_pool_contains(phys: u64) -> bool {
+    let base = POOL_BASE_PHYS.load(Ordering::Relaxed);
+    base != 0 && phys >= base && phys < base + (SHM_POOL_PAGES * PAGE_SIZE) as u64
+}
+
 // ---------------------------------------------------------------------------
 // Statistiques du pool
 // ---------------------------------------------------------------------------
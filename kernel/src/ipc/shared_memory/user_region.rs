@@ -0,0 +1,375 @@
+// ipc/shared_memory/user_region.rs — Régions SHM créées depuis userspace (exo_shm)
+//
+// Le répertoire SHM sert aussi aux canaux IPC du noyau : seules les régions
+// enregistrées ici sont visibles par exo_shm(2), identifiées par leur ShmId.
+//
+// Règles d'accès :
+//   - seul le créateur mappe en écriture et détruit la région ;
+//   - les autres processus ne mappent qu'en lecture, et seulement une région
+//     créée avec USER_REGION_PUBLIC_READ (atlas partagés, tables en lecture) ;
+//   - une région encore mappée ne peut pas être détruite ;
+//   - au plus MAX_USER_REGIONS_PER_PROCESS régions par processus.
+//
+// Cycle de vie : un mapping est oublié par exo_shm UNMAP, par munmap(2) ou
+// à la destruction de l'espace d'adressage qui le porte. À la sortie du
+// créateur, ses régions deviennent orphelines (plus mappables) et sont
+// libérées dès que leur dernier mapping disparaît.
+
+use crate::ipc::core::types::{IpcError, ProcessId};
+use crate::ipc::shared_memory::allocator::{shm_alloc_pages, shm_free_by_idx};
+use crate::ipc::shared_memory::descriptor::{ShmPermissions, MAX_SHM_PAGES_PER_DESC, SHM_DESC_DIR};
+use crate::ipc::shared_memory::mapping::{ShmMappingTable, SHM_MAPPING_TABLE};
+use crate::ipc::shared_memory::page::PAGE_SIZE;
+use crate::memory::core::{phys_to_virt, PhysAddr as MemPhysAddr};
+use crate::scheduler::sync::spinlock::SpinLock;
+
+/// Nombre maximal de régions userspace simultanées.
+pub const MAX_USER_REGIONS: usize = 64;
+
+/// Régions simultanées par processus : un seul ne peut pas remplir la table.
+pub const MAX_USER_REGIONS_PER_PROCESS: usize = 8;
+
+/// Région mappable en lecture seule par tout processus.
+pub const USER_REGION_PUBLIC_READ: u32 = 1 << 0;
+
+/// Propriétaire d'une région dont le créateur est sorti (pid 0 n'appelle
+/// jamais exo_shm).
+const ORPHAN: ProcessId = ProcessId(0);
+
+#[derive(Clone, Copy)]
+struct UserRegion {
+    id: u32,
+    desc_idx: usize,
+    owner: ProcessId,
+    flags: u32,
+}
+
+impl UserRegion {
+    fn may_map(&self, caller: ProcessId, writable: bool) -> bool {
+        if self.owner == ORPHAN {
+            return false;
+        }
+        if self.owner == caller {
+            return true;
+        }
+        !writable && self.flags & USER_REGION_PUBLIC_READ != 0
+    }
+}
+
+type UserRegions = [Option<UserRegion>; MAX_USER_REGIONS];
+
+static USER_REGIONS: SpinLock<UserRegions> = SpinLock::new([None; MAX_USER_REGIONS]);
+
+fn mapping_count(desc_idx: usize) -> u32 {
+    let dir = SHM_DESC_DIR.lock();
+    // SAFETY: accès sous SHM_DESC_DIR.
+    unsafe { dir.get(desc_idx) }.map_or(0, |desc| desc.mapping_count())
+}
+
+/// Libère les régions orphelines que plus personne ne mappe.
+fn free_unmapped_orphans(regions: &mut UserRegions) {
+    for slot in regions.iter_mut() {
+        let Some(region) = *slot else {
+            continue;
+        };
+        if region.owner == ORPHAN && mapping_count(region.desc_idx) == 0 {
+            let _ = shm_free_by_idx(region.desc_idx);
+            *slot = None;
+        }
+    }
+}
+
+/// Crée une région de `bytes` octets (arrondis à la page) possédée par
+/// `owner`. Les pages sont remises à zéro : le pool est recyclé entre
+/// processus. Retourne l'identifiant de la région.
+pub fn create(owner: ProcessId, bytes: usize, flags: u32) -> Result<u32, IpcError> {
+    if flags & !USER_REGION_PUBLIC_READ != 0 {
+        return Err(IpcError::InvalidArgument);
+    }
+    let n_pages = bytes.div_ceil(PAGE_SIZE);
+    if n_pages == 0 || n_pages > MAX_SHM_PAGES_PER_DESC {
+        return Err(IpcError::InvalidArgument);
+    }
+
+    let mut regions = USER_REGIONS.lock();
+    // Filet de sécurité : un créateur mort sans passer par `release_for_pid`
+    // (ou dont le PID a été recyclé) ne garde pas ses régions.
+    for region in regions.iter_mut().flatten() {
+        if region.owner != ORPHAN && !crate::process::is_alive(region.owner.0) {
+            region.owner = ORPHAN;
+        }
+    }
+    free_unmapped_orphans(&mut regions);
+    let owned = regions
+        .iter()
+        .flatten()
+        .filter(|r| r.owner == owner)
+        .count();
+    if owned >= MAX_USER_REGIONS_PER_PROCESS {
+        return Err(IpcError::ResourceExhausted);
+    }
+    let slot = regions
+        .iter()
+        .position(Option::is_none)
+        .ok_or(IpcError::ResourceExhausted)?;
+    let handle = shm_alloc_pages(owner, ShmPermissions::READ_WRITE, n_pages)?;
+
+    {
+        let dir = SHM_DESC_DIR.lock();
+        // SAFETY: descripteur tout juste alloué, protégé par SHM_DESC_DIR.
+        if let Some(desc) = unsafe { dir.get(handle.desc_idx) } {
+            for i in 0..n_pages {
+                let Some(phys) = desc.page_phys(i) else {
+                    continue;
+                };
+                let virt = phys_to_virt(MemPhysAddr::new(phys.0));
+                // SAFETY: page du pool SHM, accessible via la physmap et non
+                // encore mappée en userspace.
+                unsafe {
+                    core::ptr::write_bytes(virt.as_u64() as *mut u8, 0, PAGE_SIZE);
+                }
+            }
+        }
+    }
+
+    let id = handle.shm_id.get();
+    regions[slot] = Some(UserRegion {
+        id,
+        desc_idx: handle.desc_idx,
+        owner,
+        flags,
+    });
+    Ok(id)
+}
+
+/// Vérifie le droit de `caller` à mapper la région `id` et retourne l'index
+/// du descripteur.
+pub fn resolve(id: u32, caller: ProcessId, writable: bool) -> Result<usize, IpcError> {
+    let regions = USER_REGIONS.lock();
+    let region = regions
+        .iter()
+        .flatten()
+        .find(|r| r.id == id)
+        .ok_or(IpcError::NotFound)?;
+    if !region.may_map(caller, writable) {
+        return Err(IpcError::PermissionDenied);
+    }
+    Ok(region.desc_idx)
+}
+
+/// Retire de la table le mapping trouvé par `find` et décompte sa région.
+/// Retourne `false` si `find` n'a rien trouvé.
+fn drop_mapping(find: impl FnOnce(&ShmMappingTable) -> Option<usize>) -> bool {
+    let desc_idx = {
+        let mut tbl = SHM_MAPPING_TABLE.lock();
+        let Some(idx) = find(&tbl) else {
+            return false;
+        };
+        let desc_idx = tbl.entry(idx).map(|m| m.desc_idx());
+        tbl.free(idx);
+        desc_idx
+    };
+    if let Some(desc_idx) = desc_idx {
+        let dir = SHM_DESC_DIR.lock();
+        // SAFETY: accès sous SHM_DESC_DIR.
+        if let Some(desc) = unsafe { dir.get(desc_idx) } {
+            desc.remove_mapping();
+        }
+    }
+    true
+}
+
+/// Oublie le mapping de `pid` à `virt_base`, une fois ses pages démappées.
+pub fn forget_mapping(pid: u32, virt_base: u64) -> Result<(), IpcError> {
+    if !drop_mapping(|tbl| tbl.active_mapping_at(pid, virt_base)) {
+        return Err(IpcError::InvalidHandle);
+    }
+    free_unmapped_orphans(&mut USER_REGIONS.lock());
+    Ok(())
+}
+
+/// Oublie les mappings posés dans l'espace d'adressage `addr_space` : celui
+/// à `virt_base` (munmap), ou tous (destruction de l'espace).
+pub fn forget_address_space(addr_space: u64, virt_base: Option<u64>) {
+    let mut dropped = false;
+    while drop_mapping(|tbl| tbl.active_mapping_in(addr_space, virt_base)) {
+        dropped = true;
+    }
+    if dropped {
+        free_unmapped_orphans(&mut USER_REGIONS.lock());
+    }
+}
+
+/// Sortie de `pid` : ses régions deviennent orphelines (plus mappables, le
+/// PID peut être recyclé) et celles que plus personne ne mappe sont libérées.
+pub fn release_for_pid(pid: u32) {
+    let mut regions = USER_REGIONS.lock();
+    for region in regions.iter_mut().flatten() {
+        if region.owner.0 == pid {
+            region.owner = ORPHAN;
+        }
+    }
+    free_unmapped_orphans(&mut regions);
+}
+
+/// Détruit la région `id` ; réservé au créateur, refusé tant qu'un mapping
+/// reste actif.
+pub fn destroy(id: u32, caller: ProcessId) -> Result<(), IpcError> {
+    let mut regions = USER_REGIONS.lock();
+    let slot = regions
+        .iter()
+        .position(|r| r.is_some_and(|r| r.id == id))
+        .ok_or(IpcError::NotFound)?;
+    let Some(region) = regions[slot] else {
+        return Err(IpcError::NotFound);
+    };
+    if region.owner != caller {
+        return Err(IpcError::PermissionDenied);
+    }
+    {
+        let dir = SHM_DESC_DIR.lock();
+        // SAFETY: accès sous SHM_DESC_DIR.
+        let desc = unsafe { dir.get(region.desc_idx) }.ok_or(IpcError::InvalidHandle)?;
+        if desc.mapping_count() != 0 {
+            // Encore mappée quelque part : EBUSY côté syscall.
+            return Err(IpcError::AlreadyConnected);
+        }
+    }
+    shm_free_by_idx(region.desc_idx)?;
+    regions[slot] = None;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(flags: u32) -> UserRegion {
+        UserRegion {
+            id: 1,
+            desc_idx: 0,
+            owner: ProcessId(10),
+            flags,
+        }
+    }
+
+    #[test]
+    fn only_the_owner_maps_writable() {
+        let public = region(USER_REGION_PUBLIC_READ);
+        assert!(public.may_map(ProcessId(10), true));
+        assert!(!public.may_map(ProcessId(11), true));
+        assert!(public.may_map(ProcessId(11), false));
+    }
+
+    #[test]
+    fn private_regions_stay_with_their_owner() {
+        let private = region(0);
+        assert!(private.may_map(ProcessId(10), false));
+        assert!(!private.may_map(ProcessId(11), false));
+    }
+
+    #[test]
+    fn orphaned_regions_are_not_mappable() {
+        let mut orphan = region(USER_REGION_PUBLIC_READ);
+        orphan.owner = ORPHAN;
+        assert!(!orphan.may_map(ProcessId(11), false));
+        assert!(!orphan.may_map(ORPHAN, true));
+    }
+
+    /// Région d'une page inscrite sans passer par `create` (pas de physmap
+    /// hors noyau pour la remise à zéro).
+    fn register_region(owner: ProcessId) -> (u32, usize) {
+        let desc_idx =
+            crate::ipc::shared_memory::descriptor::shm_create(owner, ShmPermissions::READ_WRITE, 1)
+                .expect("descripteur SHM");
+        let id = crate::ipc::shared_memory::descriptor::shm_get_id(desc_idx)
+            .expect("id SHM")
+            .get();
+        let mut regions = USER_REGIONS.lock();
+        let slot = regions
+            .iter()
+            .position(Option::is_none)
+            .expect("slot libre");
+        regions[slot] = Some(UserRegion {
+            id,
+            desc_idx,
+            owner,
+            flags: USER_REGION_PUBLIC_READ,
+        });
+        (id, desc_idx)
+    }
+
+    /// Ce que fait `map_shm_into_process` côté IPC, sans table de pages.
+    fn map_region(desc_idx: usize, pid: u32, addr_space: u64, virt_base: u64) {
+        let dir = SHM_DESC_DIR.lock();
+        // SAFETY: accès sous SHM_DESC_DIR.
+        unsafe { dir.get(desc_idx) }
+            .expect("descripteur")
+            .add_mapping();
+        drop(dir);
+        let mut tbl = SHM_MAPPING_TABLE.lock();
+        let idx = tbl.alloc().expect("slot de mapping");
+        let entry = tbl.entry(idx).expect("entrée");
+        entry
+            .desc_idx
+            .store(desc_idx as u32, core::sync::atomic::Ordering::Relaxed);
+        entry
+            .process_id
+            .store(pid, core::sync::atomic::Ordering::Relaxed);
+        entry
+            .addr_space
+            .store(addr_space, core::sync::atomic::Ordering::Relaxed);
+        entry
+            .virt_base
+            .store(virt_base, core::sync::atomic::Ordering::Relaxed);
+        entry.mark_active();
+    }
+
+    fn region_exists(id: u32) -> bool {
+        USER_REGIONS.lock().iter().flatten().any(|r| r.id == id)
+    }
+
+    #[test]
+    fn destroy_succeeds_once_the_mapper_has_exited() {
+        let owner = ProcessId(0x5025_0001);
+        let (id, desc_idx) = register_region(owner);
+        map_region(desc_idx, 0x5025_0002, 0x5025_a000, 0x4000_0000);
+        assert_eq!(destroy(id, owner), Err(IpcError::AlreadyConnected));
+
+        // Le mappeur sort : son espace d'adressage est détruit.
+        forget_address_space(0x5025_a000, None);
+        assert_eq!(destroy(id, owner), Ok(()));
+        assert!(!region_exists(id));
+    }
+
+    #[test]
+    fn munmap_forgets_only_that_mapping() {
+        let owner = ProcessId(0x5025_0011);
+        let (id, desc_idx) = register_region(owner);
+        map_region(desc_idx, 0x5025_0012, 0x5025_b000, 0x4000_0000);
+        map_region(desc_idx, 0x5025_0012, 0x5025_b000, 0x5000_0000);
+
+        forget_address_space(0x5025_b000, Some(0x4000_0000));
+        assert_eq!(mapping_count(desc_idx), 1);
+        assert_eq!(destroy(id, owner), Err(IpcError::AlreadyConnected));
+        assert_eq!(forget_mapping(0x5025_0012, 0x5000_0000), Ok(()));
+        assert_eq!(destroy(id, owner), Ok(()));
+    }
+
+    #[test]
+    fn owner_exit_frees_the_region_after_its_last_mapping() {
+        let owner = ProcessId(0x5025_0021);
+        let (id, desc_idx) = register_region(owner);
+        map_region(desc_idx, 0x5025_0022, 0x5025_c000, 0x4000_0000);
+
+        release_for_pid(owner.0);
+        assert!(region_exists(id), "encore mappée");
+        assert_eq!(
+            resolve(id, ProcessId(0x5025_0023), false),
+            Err(IpcError::PermissionDenied)
+        );
+
+        forget_address_space(0x5025_c000, None);
+        assert!(!region_exists(id));
+    }
+}
@@ -77,6 +77,21 @@ pub mod drivers;
 /// ExoPhoenix (Kernel B) : état partagé SSR + orchestration sentinelle.
 pub mod exophoenix;
 
+/// Transverse : configuration de build (features `exo-config`)
+pub mod config;
+
+/// Transverse : registre de tunables runtime (`/proc/sys`, SYS_EXO_SYSCTL)
+pub mod sysctl;
+
+/// Transverse : points de trace par CPU, export Chrome trace-event
+pub mod trace;
+
+/// Transverse : correctifs à chaud de fonctions noyau (modules signés)
+pub mod livepatch;
+
+/// Transverse : programmes bpf vérifiés (points de trace, filtres réseau)
+pub mod bpf;
+
 /// Interface syscall → dispatch vers les couches supérieures
 pub mod syscall;
 
@@ -268,6 +283,10 @@ pub unsafe fn kernel_init(cpu_count: usize) {
     // toute divergence dans ExoLedger sans paniquer (boot-safe). Best-effort.
     crate::security::start_integrity_monitor();
 
+    // Sites livepatch : sans eux, SYS_EXO_LIVEPATCH refuse tout correctif
+    // (ENOENT). Après la sécurité : un correctif passe par code_signing.
+    let _livepatch_sites = crate::livepatch::init();
+
     // ── Phase 5b : ExoPhoenix Stage0 (domaine IOMMU de blocage) ─────────────
     // FIX-STAGE0 (rapport_analyse_kernel_exo_os.md §4.1) :
     // stage0_init_all_steps() n'était jamais appelé, laissant
@@ -351,6 +370,10 @@ pub unsafe fn kernel_init(cpu_count: usize) {
         crate::syscall::net_bridge::net_bridge_preinit();
     }
     kdb(b'@'); // fs_bridge/net_bridge actifs
+
+    // Tunables runtime : tous les sous-systèmes propriétaires sont initialisés.
+    crate::sysctl::init();
+    crate::config::log_build_config();
     crate::arch::x86_64::boot_display::stage_ok("FS");
 }
 
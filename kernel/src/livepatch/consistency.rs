@@ -0,0 +1,70 @@
+// kernel/src/livepatch/consistency.rs
+//
+// Vérification de cohérence : aucun thread endormi dans une fonction patchée.
+//
+// Sans unwinder ni métadonnées de frames, le parcours est conservateur : tout
+// mot de la pile noyau sauvegardée (de `kstack_ptr` au sommet) qui tombe dans
+// la plage de la fonction compte comme une adresse de retour. Un faux positif
+// ne coûte qu'un EBUSY ; l'appelant réessaie plus tard.
+//
+// Les threads en cours d'exécution (`Running`) n'ont pas de pile sauvegardée
+// exploitable : ils terminent l'appel en cours dans l'ancien code, comme avec
+// une redirection ftrace. Seuls les dormeurs, qui peuvent y rester sans borne,
+// bloquent la transition.
+
+use crate::process::core::registry::PROCESS_REGISTRY;
+use crate::process::core::KSTACK_SIZE;
+use crate::scheduler::core::task::TaskState;
+
+/// Vrai si un mot de `stack` tombe dans `[start, end)`.
+pub fn stack_references(stack: &[u64], start: u64, end: u64) -> bool {
+    stack.iter().any(|&word| word >= start && word < end)
+}
+
+/// Vrai si un thread endormi a une adresse dans `[start, end)` sur sa pile.
+pub fn task_sleeping_in(start: u64, end: u64) -> bool {
+    let mut found = false;
+    PROCESS_REGISTRY.for_each(|pcb| {
+        pcb.for_each_thread_ptr(|thread| {
+            if found {
+                return;
+            }
+            // SAFETY: les slots du PCB ne contiennent que des threads vivants,
+            // retirés par unregister_thread_ptr() avant libération.
+            let tcb = unsafe { (*thread).tcb() };
+            if matches!(
+                tcb.task_state(),
+                TaskState::Running | TaskState::Zombie | TaskState::Dead
+            ) {
+                return;
+            }
+            let (low, top) = (tcb.kstack_ptr, tcb.kstack_top());
+            if low == 0 || low % 8 != 0 || top <= low || top - low > KSTACK_SIZE as u64 {
+                return;
+            }
+            // SAFETY: [kstack_ptr, kstack_top) est la partie occupée de la pile
+            // noyau du thread, mappée tant que le thread existe ; lecture seule
+            // d'un thread qui ne s'exécute pas.
+            let stack = unsafe {
+                core::slice::from_raw_parts(low as *const u64, ((top - low) / 8) as usize)
+            };
+            found = stack_references(stack, start, end);
+        });
+    });
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn return_address_inside_range_is_detected() {
+        let stack = [0x10, 0xFFFF_8000_0000_1234, 0x40_1020, 0];
+        assert!(stack_references(&stack, 0x40_1000, 0x40_1100));
+        assert!(!stack_references(&stack, 0x40_1100, 0x40_1200));
+        // Borne haute exclue.
+        assert!(!stack_references(&stack, 0x40_1000, 0x40_1020));
+        assert!(!stack_references(&[], 0, u64::MAX));
+    }
+}
@@ -0,0 +1,224 @@
+// kernel/src/livepatch/format.rs
+//
+// Format d'un correctif à chaud — corps d'un module signé EXOMOD.
+//
+// Disposition (little-endian) :
+//   [0..8]    magic        b"EXOLIVE1"
+//   [8..72]   target       nom du site patchable (NUL-padded)
+//   [72..76]  code_len     u32
+//   [76..80]  reloc_count  u32
+//   [80..]    code         code_len octets, position-indépendant hors relocations
+//   puis      relocs       reloc_count × 72 octets : offset u32, kind u32,
+//                          symbol [u8; 64] (nom d'un site enregistré)
+//
+// Pas de table de symboles noyau : une relocation ne peut viser qu'un site
+// déclaré via `register_site`.
+
+use super::LivepatchError;
+
+pub const MAGIC: [u8; 8] = *b"EXOLIVE1";
+pub const NAME_LEN: usize = 64;
+pub const HEADER_LEN: usize = 80;
+pub const RELOC_LEN: usize = 72;
+
+/// Déplacement relatif 32 bits (cible − fin du champ), `call`/`jmp rel32`.
+pub const RELOC_REL32: u32 = 1;
+/// Adresse absolue 64 bits (`movabs`, tables de pointeurs).
+pub const RELOC_ABS64: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reloc<'a> {
+    pub offset: u32,
+    pub kind: u32,
+    pub symbol: &'a [u8],
+}
+
+impl Reloc<'_> {
+    fn width(&self) -> usize {
+        if self.kind == RELOC_REL32 {
+            4
+        } else {
+            8
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PatchImage<'a> {
+    pub target: &'a [u8],
+    pub code: &'a [u8],
+    relocs: &'a [u8],
+}
+
+fn le32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn name(raw: &[u8]) -> &[u8] {
+    let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+    &raw[..end]
+}
+
+impl<'a> PatchImage<'a> {
+    /// Valide la structure complète : taille exacte, relocations dans le code,
+    /// types connus, noms non vides.
+    pub fn parse(body: &'a [u8]) -> Result<Self, LivepatchError> {
+        let header = body.get(..HEADER_LEN).ok_or(LivepatchError::Malformed)?;
+        if header[..8] != MAGIC {
+            return Err(LivepatchError::Malformed);
+        }
+        let target = name(&header[8..8 + NAME_LEN]);
+        let code_len = le32(&header[72..]) as usize;
+        let reloc_count = le32(&header[76..]) as usize;
+        let relocs_len = reloc_count
+            .checked_mul(RELOC_LEN)
+            .ok_or(LivepatchError::Malformed)?;
+        let total = HEADER_LEN
+            .checked_add(code_len)
+            .and_then(|n| n.checked_add(relocs_len))
+            .ok_or(LivepatchError::Malformed)?;
+        if target.is_empty() || code_len == 0 || body.len() != total {
+            return Err(LivepatchError::Malformed);
+        }
+
+        let image = Self {
+            target,
+            code: &body[HEADER_LEN..HEADER_LEN + code_len],
+            relocs: &body[HEADER_LEN + code_len..],
+        };
+        for reloc in image.relocs() {
+            let end = reloc.offset as usize + reloc.width();
+            let known = reloc.kind == RELOC_REL32 || reloc.kind == RELOC_ABS64;
+            if !known || end > code_len || reloc.symbol.is_empty() {
+                return Err(LivepatchError::Malformed);
+            }
+        }
+        Ok(image)
+    }
+
+    pub fn relocs(&self) -> impl Iterator<Item = Reloc<'a>> + 'a {
+        self.relocs.chunks_exact(RELOC_LEN).map(|raw| Reloc {
+            offset: le32(raw),
+            kind: le32(&raw[4..]),
+            symbol: name(&raw[8..8 + NAME_LEN]),
+        })
+    }
+}
+
+/// Applique les relocations de `image` à `code` (copie de `image.code`) qui
+/// sera exécuté à `load_addr`. `resolve` donne l'adresse d'un symbole.
+pub fn relocate(
+    image: &PatchImage<'_>,
+    code: &mut [u8],
+    load_addr: u64,
+    mut resolve: impl FnMut(&[u8]) -> Option<u64>,
+) -> Result<(), LivepatchError> {
+    for reloc in image.relocs() {
+        let target = resolve(reloc.symbol).ok_or(LivepatchError::UnresolvedSymbol)?;
+        let at = reloc.offset as usize;
+        if reloc.kind == RELOC_REL32 {
+            let next = load_addr + at as u64 + 4;
+            let disp = i32::try_from(target.wrapping_sub(next) as i64)
+                .map_err(|_| LivepatchError::RelocOverflow)?;
+            code[at..at + 4].copy_from_slice(&disp.to_le_bytes());
+        } else {
+            code[at..at + 8].copy_from_slice(&target.to_le_bytes());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn image(target: &[u8], code: &[u8], relocs: &[(u32, u32, &[u8])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&MAGIC);
+        let mut name = [0u8; NAME_LEN];
+        name[..target.len()].copy_from_slice(target);
+        out.extend_from_slice(&name);
+        out.extend_from_slice(&(code.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(relocs.len() as u32).to_le_bytes());
+        out.extend_from_slice(code);
+        for (offset, kind, symbol) in relocs {
+            out.extend_from_slice(&offset.to_le_bytes());
+            out.extend_from_slice(&kind.to_le_bytes());
+            let mut name = [0u8; NAME_LEN];
+            name[..symbol.len()].copy_from_slice(symbol);
+            out.extend_from_slice(&name);
+        }
+        out
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        let raw = image(
+            b"ipc_check",
+            &[0xE8, 0, 0, 0, 0, 0xC3],
+            &[(1, RELOC_REL32, b"audit")],
+        );
+        let img = PatchImage::parse(&raw).unwrap();
+        assert_eq!(img.target, b"ipc_check");
+        assert_eq!(img.code.len(), 6);
+        let relocs: Vec<_> = img.relocs().collect();
+        assert_eq!(
+            relocs,
+            [Reloc {
+                offset: 1,
+                kind: RELOC_REL32,
+                symbol: b"audit"
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_bad_layout() {
+        let mut raw = image(b"f", &[0xC3], &[]);
+        raw.push(0);
+        assert_eq!(
+            PatchImage::parse(&raw).unwrap_err(),
+            LivepatchError::Malformed
+        );
+        // Relocation qui déborde du code.
+        let raw = image(b"f", &[0xC3, 0, 0], &[(0, RELOC_REL32, b"g")]);
+        assert!(PatchImage::parse(&raw).is_err());
+        // Type inconnu.
+        let raw = image(b"f", &[0; 16], &[(0, 7, b"g")]);
+        assert!(PatchImage::parse(&raw).is_err());
+        let mut raw = image(b"f", &[0xC3], &[]);
+        raw[0] = b'X';
+        assert!(PatchImage::parse(&raw).is_err());
+    }
+
+    #[test]
+    fn relocate_rel32_and_abs64() {
+        let raw = image(
+            b"f",
+            &[0xE8, 0, 0, 0, 0, 0x48, 0xB8, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[(1, RELOC_REL32, b"g"), (7, RELOC_ABS64, b"g")],
+        );
+        let img = PatchImage::parse(&raw).unwrap();
+        let mut code = img.code.to_vec();
+        relocate(&img, &mut code, 0x1000, |s| (s == b"g").then_some(0x900)).unwrap();
+        // 0x900 - (0x1000 + 1 + 4)
+        assert_eq!(i32::from_le_bytes(code[1..5].try_into().unwrap()), -0x705);
+        assert_eq!(u64::from_le_bytes(code[7..15].try_into().unwrap()), 0x900);
+    }
+
+    #[test]
+    fn relocate_reports_unknown_and_far_symbols() {
+        let raw = image(b"f", &[0xE8, 0, 0, 0, 0], &[(1, RELOC_REL32, b"g")]);
+        let img = PatchImage::parse(&raw).unwrap();
+        let mut code = img.code.to_vec();
+        assert_eq!(
+            relocate(&img, &mut code, 0x1000, |_| None),
+            Err(LivepatchError::UnresolvedSymbol)
+        );
+        assert_eq!(
+            relocate(&img, &mut code, 0x1000, |_| Some(0x1_0000_2000)),
+            Err(LivepatchError::RelocOverflow)
+        );
+    }
+}
@@ -0,0 +1,461 @@
+// kernel/src/livepatch/mod.rs
+//
+// ═══════════════════════════════════════════════════════════════════════════════
+// livepatch — correctifs à chaud de fonctions noyau (Exo-OS · Transverse)
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Un correctif remplace une fonction noyau sans redémarrer (déploiement OSTree
+// en place) : le code de remplacement arrive dans un module signé EXOMOD
+// (`integrity_check::code_signing`), est copié dans une zone réservée de .text,
+// puis l'entrée de la fonction d'origine reçoit un `jmp rel32` vers lui —
+// trampoline à l'entrée, façon ftrace. `revert` restaure les octets d'origine.
+//
+// Le noyau n'a ni chargeur de modules ni table de symboles : seules les
+// fonctions déclarées par `livepatch_site!` (table de boot, `sites`) sont
+// patchables, et ce sont les seuls symboles que les relocations d'un
+// correctif peuvent viser.
+//
+// Transition :
+//   1. signature Ed25519 + hash BLAKE3 du module, anti-rejeu
+//   2. aucun thread endormi dans la fonction (pile noyau, `consistency`)
+//   3. écriture du saut (int3 → octets → opcode, `poke`)
+//   4. nouvelle vérification ; si un thread s'est endormi dans l'ancien code
+//      entre-temps, retour arrière et EBUSY
+//   5. hash de référence de .text recalculé, entrée ExoLedger
+//
+// RÈGLE LIVEPATCH-01 : un correctif non signé n'est JAMAIS appliqué (CSIGN-01).
+// RÈGLE LIVEPATCH-02 : un site ne porte qu'un correctif à la fois ; le code
+//   d'un correctif retiré reste en place (un thread peut encore y dormir).
+// RÈGLE LIVEPATCH-03 : un site enregistré a ≥ 5 octets et aucun saut interne
+//   vers ses octets 1..5 (précondition du trampoline d'entrée).
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub mod consistency;
+pub mod format;
+pub mod poke;
+pub mod sites;
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use spin::Mutex;
+
+use crate::security::integrity_check::{
+    register_loaded_module, verify_module_signature, CodeSignError, ModuleHeader,
+};
+use format::PatchImage;
+use poke::JMP_LEN;
+
+/// Sites patchables au plus.
+pub const LIVEPATCH_MAX_SITES: usize = 64;
+/// Taille de la zone d'accueil des fonctions de remplacement.
+pub const LIVEPATCH_AREA_SIZE: usize = 64 * 1024;
+/// Taille maximale d'un module de correctif (en-tête EXOMOD compris).
+pub const LIVEPATCH_MAX_IMAGE: usize = ModuleHeader::SIZE + LIVEPATCH_AREA_SIZE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LivepatchError {
+    /// Module refusé par la vérification de signature.
+    Signature(CodeSignError),
+    /// Module déjà appliqué une fois (anti-rejeu).
+    Replayed,
+    /// Image de correctif invalide.
+    Malformed,
+    /// Aucun site enregistré sous ce nom.
+    UnknownSite,
+    /// Le site porte déjà un correctif.
+    AlreadyPatched,
+    /// Le site ne porte aucun correctif.
+    NotPatched,
+    /// Relocation vers un symbole qui n'est pas un site enregistré.
+    UnresolvedSymbol,
+    /// Cible hors de portée d'un déplacement 32 bits.
+    RelocOverflow,
+    /// Zone de remplacement ou registre plein.
+    OutOfSpace,
+    /// Site trop court pour le trampoline, ou déjà enregistré.
+    BadSite,
+    /// Un thread dort dans la fonction ; réessayer plus tard.
+    Busy,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Zone de remplacement
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Octets réservés dans .text (donc exécutables), remplis d'`int3`.
+#[repr(C, align(16))]
+struct PatchArea(UnsafeCell<[u8; LIVEPATCH_AREA_SIZE]>);
+
+// SAFETY: écrite uniquement via `poke` sous le verrou `SITES`.
+unsafe impl Sync for PatchArea {}
+
+#[cfg_attr(target_os = "none", link_section = ".text.livepatch")]
+static PATCH_AREA: PatchArea = PatchArea(UnsafeCell::new([0xCC; LIVEPATCH_AREA_SIZE]));
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Registre des sites
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Clone, Copy)]
+struct Applied {
+    /// Entrée du code de remplacement.
+    code: u64,
+    code_len: usize,
+    /// Octets d'origine écrasés par le saut.
+    saved: [u8; JMP_LEN],
+}
+
+#[derive(Clone, Copy)]
+struct Site {
+    name: &'static str,
+    entry: u64,
+    len: u64,
+    applied: Option<Applied>,
+}
+
+struct Registry {
+    sites: [Option<Site>; LIVEPATCH_MAX_SITES],
+    /// Octets consommés dans `PATCH_AREA` (jamais rendus, LIVEPATCH-02).
+    area_used: usize,
+}
+
+impl Registry {
+    fn find(&self, name: &[u8]) -> Option<usize> {
+        self.sites
+            .iter()
+            .position(|s| s.is_some_and(|s| s.name.as_bytes() == name))
+    }
+
+    fn site(&self, idx: usize) -> Site {
+        self.sites[idx].expect("index de site valide")
+    }
+}
+
+static SITES: Mutex<Registry> = Mutex::new(Registry {
+    sites: [None; LIVEPATCH_MAX_SITES],
+    area_used: 0,
+});
+
+static APPLIED: AtomicU64 = AtomicU64::new(0);
+static REVERTED: AtomicU64 = AtomicU64::new(0);
+static REFUSED: AtomicU64 = AtomicU64::new(0);
+
+/// Déclare une fonction noyau patchable sous un nom : `livepatch_site!(nom,
+/// chemin::fonction, taille)`, la taille étant un majorant. La fonction doit
+/// être `#[inline(never)]` : une copie inlinée échapperait au trampoline.
+///
+/// ```rust,ignore
+/// livepatch_site!("ipc_check_direct", ipc_policy::check_direct_ipc, 0x400)?;
+/// ```
+#[macro_export]
+macro_rules! livepatch_site {
+    ($name:literal, $func:path, $len:expr) => {
+        $crate::livepatch::register_site($name, $func as *const () as usize as u64, $len)
+    };
+}
+
+/// Enregistre les sites de la table de boot (`sites`). Appelé une
+/// fois, après l'initialisation de la sécurité ; retourne le nombre de sites.
+pub fn init() -> usize {
+    sites::register_builtin()
+}
+
+/// Déclare `entry` (taille `len`, majorant accepté) patchable sous `name`.
+/// Passer par `livepatch_site!` pour une fonction noyau.
+pub fn register_site(name: &'static str, entry: u64, len: u64) -> Result<(), LivepatchError> {
+    if name.is_empty() || name.len() > format::NAME_LEN || len < JMP_LEN as u64 {
+        return Err(LivepatchError::BadSite);
+    }
+    let mut reg = SITES.lock();
+    if reg.find(name.as_bytes()).is_some() {
+        return Err(LivepatchError::BadSite);
+    }
+    let slot = reg
+        .sites
+        .iter_mut()
+        .find(|s| s.is_none())
+        .ok_or(LivepatchError::OutOfSpace)?;
+    *slot = Some(Site {
+        name,
+        entry,
+        len,
+        applied: None,
+    });
+    Ok(())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Application / retrait
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Vérifie et applique un module de correctif (`ModuleHeader` + image).
+/// Retourne l'index du site patché.
+pub fn apply(module: &[u8]) -> Result<usize, LivepatchError> {
+    let result = apply_inner(module);
+    match result {
+        Ok(_) => APPLIED.fetch_add(1, Ordering::Relaxed),
+        Err(_) => REFUSED.fetch_add(1, Ordering::Relaxed),
+    };
+    result
+}
+
+fn apply_inner(module: &[u8]) -> Result<usize, LivepatchError> {
+    if module.len() < ModuleHeader::SIZE || module.len() > LIVEPATCH_MAX_IMAGE {
+        return Err(LivepatchError::Malformed);
+    }
+    // SAFETY: ModuleHeader est repr(C), composé d'entiers et de tableaux
+    // d'octets (tout motif binaire valide) ; lecture non alignée d'une copie.
+    let header = unsafe { core::ptr::read_unaligned(module.as_ptr() as *const ModuleHeader) };
+    let body = &module[ModuleHeader::SIZE..];
+    if header.module_size as usize != body.len() {
+        return Err(LivepatchError::Malformed);
+    }
+    verify_module_signature(&header, body).map_err(LivepatchError::Signature)?;
+    let image = PatchImage::parse(body)?;
+    install(&header, &image)
+}
+
+/// Pose un correctif dont la signature a été vérifiée.
+fn install(header: &ModuleHeader, image: &PatchImage<'_>) -> Result<usize, LivepatchError> {
+    let mut reg = SITES.lock();
+    let idx = reg.find(image.target).ok_or(LivepatchError::UnknownSite)?;
+    let site = reg.site(idx);
+    if site.applied.is_some() {
+        return Err(LivepatchError::AlreadyPatched);
+    }
+
+    let code_len = image.code.len();
+    let offset = (reg.area_used + 15) & !15;
+    if offset + code_len > LIVEPATCH_AREA_SIZE {
+        return Err(LivepatchError::OutOfSpace);
+    }
+    let load_addr = PATCH_AREA.0.get() as u64 + offset as u64;
+    let jump = poke::encode_jmp(site.entry, load_addr).ok_or(LivepatchError::RelocOverflow)?;
+
+    let mut code = alloc::vec::Vec::new();
+    code.try_reserve_exact(code_len)
+        .map_err(|_| LivepatchError::OutOfSpace)?;
+    code.extend_from_slice(image.code);
+    format::relocate(image, &mut code, load_addr, |sym| {
+        reg.find(sym).map(|i| reg.site(i).entry)
+    })?;
+
+    if consistency::task_sleeping_in(site.entry, site.entry + site.len) {
+        return Err(LivepatchError::Busy);
+    }
+    register_loaded_module(header).map_err(|e| match e {
+        CodeSignError::AlreadyLoaded => LivepatchError::Replayed,
+        other => LivepatchError::Signature(other),
+    })?;
+
+    let mut saved = [0u8; JMP_LEN];
+    // SAFETY: `entry` est l'entrée d'un site enregistré (LIVEPATCH-03), lisible.
+    unsafe {
+        core::ptr::copy_nonoverlapping(site.entry as *const u8, saved.as_mut_ptr(), JMP_LEN);
+    }
+    // SAFETY: zone libre de PATCH_AREA, encore inatteignable ; puis entrée d'un
+    // site enregistré déviée vers ce code (LIVEPATCH-03), verrou SITES tenu.
+    unsafe {
+        poke::write_code(load_addr, &code);
+        poke::patch_entry(site.entry, &jump, load_addr);
+    }
+    reg.area_used = offset + code_len;
+
+    if consistency::task_sleeping_in(site.entry, site.entry + site.len) {
+        // SAFETY: restauration des octets d'origine lus ci-dessus.
+        unsafe { poke::patch_entry(site.entry, &saved, load_addr) };
+        rebaseline();
+        return Err(LivepatchError::Busy);
+    }
+
+    reg.sites[idx] = Some(Site {
+        applied: Some(Applied {
+            code: load_addr,
+            code_len,
+            saved,
+        }),
+        ..site
+    });
+    drop(reg);
+    rebaseline();
+    audit(idx, 1);
+    Ok(idx)
+}
+
+/// Retire le correctif du site `name` (octets d'origine restaurés).
+pub fn revert(name: &[u8]) -> Result<(), LivepatchError> {
+    let mut reg = SITES.lock();
+    let idx = reg.find(name).ok_or(LivepatchError::UnknownSite)?;
+    let site = reg.site(idx);
+    let applied = site.applied.ok_or(LivepatchError::NotPatched)?;
+    let range = applied.code..applied.code + applied.code_len as u64;
+    if consistency::task_sleeping_in(range.start, range.end) {
+        return Err(LivepatchError::Busy);
+    }
+    // SAFETY: octets d'origine sauvegardés à l'application, même entrée.
+    unsafe { poke::patch_entry(site.entry, &applied.saved, applied.code) };
+    reg.sites[idx] = Some(Site {
+        applied: None,
+        ..site
+    });
+    drop(reg);
+    rebaseline();
+    audit(idx, 0);
+    REVERTED.fetch_add(1, Ordering::Relaxed);
+    Ok(())
+}
+
+/// La modification de .text est légitime : nouveau hash de référence pour le
+/// moniteur d'intégrité (sinon il la signalerait comme une altération).
+#[cfg(not(test))]
+fn rebaseline() {
+    crate::security::integrity_check::runtime_check::rebaseline_text();
+}
+
+/// Binaire de test host : pas de symboles `_text_start`/`_text_end` (linker
+/// script noyau), donc pas de moniteur d'intégrité à réaligner.
+#[cfg(test)]
+fn rebaseline() {}
+
+/// Entrée ExoLedger P0 : b"LIVEPTCH", site et sens (1 = appliqué, 0 = retiré).
+fn audit(idx: usize, applied: u64) {
+    crate::security::exoledger::exo_ledger_append_p0(
+        crate::security::exoledger::ActionTag::Custom {
+            tag: 0x4C49_5645_5054_4348,
+            data: (idx as u64) << 8 | applied,
+        },
+    );
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LivepatchStats {
+    pub applied: u64,
+    pub reverted: u64,
+    pub refused: u64,
+    pub area_used: usize,
+}
+
+pub fn stats() -> LivepatchStats {
+    LivepatchStats {
+        applied: APPLIED.load(Ordering::Relaxed),
+        reverted: REVERTED.load(Ordering::Relaxed),
+        refused: REFUSED.load(Ordering::Relaxed),
+        area_used: SITES.lock().area_used,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    /// Fausse fonction : octets ordinaires (l'écriture hôte de `poke` est une
+    /// copie), statiques pour rester à portée rel32 de `PATCH_AREA`.
+    struct DummyText(UnsafeCell<[u8; 16]>);
+
+    // SAFETY: seul le test ci-dessous y accède, sous le verrou SITES.
+    unsafe impl Sync for DummyText {}
+
+    // push rbp ; mov rbp, rsp ; xor eax, eax ; pop rbp ; ret
+    const DUMMY_CODE: [u8; 16] = [
+        0x55, 0x48, 0x89, 0xE5, 0x31, 0xC0, 0x5D, 0xC3, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC,
+        0xCC,
+    ];
+    static DUMMY: DummyText = DummyText(UnsafeCell::new(DUMMY_CODE));
+
+    fn dummy_bytes() -> [u8; 16] {
+        // SAFETY: lecture d'une copie ; aucune écriture concurrente hors test.
+        unsafe { *DUMMY.0.get() }
+    }
+
+    fn header(hash_byte: u8) -> ModuleHeader {
+        ModuleHeader {
+            magic: ModuleHeader::MAGIC,
+            version: 1,
+            module_size: 0,
+            name: [0; 64],
+            semver: [0; 3],
+            code_hash: [hash_byte; 32],
+            signature: [0; 64],
+            key_index: 0,
+            _pad: [0; 3],
+        }
+    }
+
+    /// `mov eax, 1 ; jmp rel32 <symbol>` : le correctif rejoint un site.
+    fn image(target: &[u8], symbol: &[u8]) -> Vec<u8> {
+        let code = [0xB8, 1, 0, 0, 0, 0xE9, 0, 0, 0, 0];
+        let mut out = Vec::new();
+        out.extend_from_slice(&format::MAGIC);
+        let mut name = [0u8; format::NAME_LEN];
+        name[..target.len()].copy_from_slice(target);
+        out.extend_from_slice(&name);
+        out.extend_from_slice(&(code.len() as u32).to_le_bytes());
+        out.extend_from_slice(&1u32.to_le_bytes());
+        out.extend_from_slice(&code);
+        out.extend_from_slice(&6u32.to_le_bytes());
+        out.extend_from_slice(&format::RELOC_REL32.to_le_bytes());
+        let mut sym = [0u8; format::NAME_LEN];
+        sym[..symbol.len()].copy_from_slice(symbol);
+        out.extend_from_slice(&sym);
+        out
+    }
+
+    #[test]
+    fn apply_and_revert_on_a_registered_site() {
+        let entry = DUMMY.0.get() as u64;
+        register_site("lp_selftest", entry, DUMMY_CODE.len() as u64).unwrap();
+        assert_eq!(
+            register_site("lp_selftest", entry, 16),
+            Err(LivepatchError::BadSite)
+        );
+        assert_eq!(
+            register_site("lp_short", entry, 4),
+            Err(LivepatchError::BadSite)
+        );
+
+        let raw = image(b"lp_selftest", b"lp_selftest");
+        let img = PatchImage::parse(&raw).unwrap();
+        let unknown = image(b"lp_absent", b"lp_selftest");
+        assert_eq!(
+            install(&header(0xA1), &PatchImage::parse(&unknown).unwrap()),
+            Err(LivepatchError::UnknownSite)
+        );
+        let unresolved = image(b"lp_selftest", b"lp_absent");
+        assert_eq!(
+            install(&header(0xA2), &PatchImage::parse(&unresolved).unwrap()),
+            Err(LivepatchError::UnresolvedSymbol)
+        );
+        assert_eq!(dummy_bytes(), DUMMY_CODE);
+
+        let idx = install(&header(0xA3), &img).unwrap();
+        let applied = SITES.lock().site(idx).applied.unwrap();
+        // Entrée déviée vers le code chargé, reste de la fonction intact.
+        let patched = dummy_bytes();
+        assert_eq!(
+            patched[..JMP_LEN],
+            poke::encode_jmp(entry, applied.code).unwrap()
+        );
+        assert_eq!(patched[JMP_LEN..], DUMMY_CODE[JMP_LEN..]);
+        assert_eq!(applied.saved, DUMMY_CODE[..JMP_LEN]);
+        // Relocation REL32 résolue vers l'entrée du site.
+        // SAFETY: code copié dans PATCH_AREA par `install`, lecture seule.
+        let loaded =
+            unsafe { core::slice::from_raw_parts(applied.code as *const u8, applied.code_len) };
+        let disp = i32::from_le_bytes(loaded[6..10].try_into().unwrap());
+        assert_eq!((applied.code + 10).wrapping_add(disp as i64 as u64), entry);
+        assert_eq!(
+            install(&header(0xA4), &img),
+            Err(LivepatchError::AlreadyPatched)
+        );
+
+        revert(b"lp_selftest").unwrap();
+        assert_eq!(dummy_bytes(), DUMMY_CODE);
+        assert_eq!(revert(b"lp_selftest"), Err(LivepatchError::NotPatched));
+        assert_eq!(revert(b"lp_absent"), Err(LivepatchError::UnknownSite));
+        // Même module une seconde fois : anti-rejeu.
+        assert_eq!(install(&header(0xA3), &img), Err(LivepatchError::Replayed));
+        assert_eq!(dummy_bytes(), DUMMY_CODE);
+    }
+}
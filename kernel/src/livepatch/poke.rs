@@ -0,0 +1,163 @@
+// kernel/src/livepatch/poke.rs
+//
+// Écriture dans .text sur un noyau vivant (x86_64).
+//
+// .text est mappé RX : on écrit par l'alias physmap (RW, NX) de la page
+// physique, ce qui évite de lever CR0.WP (interdit quand CET est actif).
+//
+// Protocole d'écriture d'une instruction de 5 octets à l'entrée d'une fonction
+// (même séquence que le text_poke_bp de Linux) :
+//   1. `int3` sur le premier octet              → synchronisation des cœurs
+//   2. octets 1..5                              → synchronisation
+//   3. premier octet définitif                  → synchronisation
+// Un CPU qui exécute l'entrée pendant l'opération prend le #BP ; le handler
+// (`handle_int3`) le dévie vers la cible du site, jamais vers un mélange
+// d'ancien et de nouveau code.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Taille d'un `jmp rel32`.
+pub const JMP_LEN: usize = 5;
+const OPCODE_JMP_REL32: u8 = 0xE9;
+const OPCODE_INT3: u8 = 0xCC;
+
+/// Entrée en cours de modification (0 = aucune) et sa cible pendant la fenêtre.
+static POKE_SITE: AtomicU64 = AtomicU64::new(0);
+static POKE_TARGET: AtomicU64 = AtomicU64::new(0);
+
+/// `jmp rel32` placé à `from` et sautant à `to` ; `None` hors de ±2 GiB.
+pub fn encode_jmp(from: u64, to: u64) -> Option<[u8; JMP_LEN]> {
+    let disp = i32::try_from(to.wrapping_sub(from + JMP_LEN as u64) as i64).ok()?;
+    let mut out = [OPCODE_JMP_REL32, 0, 0, 0, 0];
+    out[1..].copy_from_slice(&disp.to_le_bytes());
+    Some(out)
+}
+
+/// Hook du handler #BP noyau : dévie un CPU tombé sur l'`int3` temporaire.
+/// Retourne `true` si l'exception a été consommée.
+pub fn handle_int3(rip: &mut u64) -> bool {
+    let site = POKE_SITE.load(Ordering::Acquire);
+    // `rip` pointe après l'int3.
+    if site == 0 || rip.wrapping_sub(1) != site {
+        return false;
+    }
+    *rip = POKE_TARGET.load(Ordering::Acquire);
+    true
+}
+
+/// Remplace les `JMP_LEN` octets à `entry` par `bytes`. Pendant l'opération,
+/// un appel à `entry` est dévié vers `detour`.
+///
+/// # Safety
+/// `entry` est l'entrée d'une fonction de .text d'au moins `JMP_LEN` octets dont
+/// aucun saut interne ne vise les octets 1..5 ; `detour` est une entrée de
+/// fonction valide à signature identique. Appelant sérialisé (verrou livepatch).
+pub unsafe fn patch_entry(entry: u64, bytes: &[u8; JMP_LEN], detour: u64) {
+    POKE_TARGET.store(detour, Ordering::Release);
+    POKE_SITE.store(entry, Ordering::Release);
+
+    // SAFETY: contrat de la fonction — entrée de fonction valide dans .text.
+    unsafe {
+        write_text(entry, &[OPCODE_INT3]);
+        sync_cores();
+        write_text(entry + 1, &bytes[1..]);
+        sync_cores();
+        write_text(entry, &bytes[..1]);
+        sync_cores();
+    }
+
+    POKE_SITE.store(0, Ordering::Release);
+}
+
+/// Copie `bytes` à `addr` (zone de correctifs, non exécutée à ce stade).
+///
+/// # Safety
+/// `addr..addr + bytes.len()` est dans .text et n'est pas encore atteignable.
+pub unsafe fn write_code(addr: u64, bytes: &[u8]) {
+    // SAFETY: contrat de la fonction.
+    unsafe { write_text(addr, bytes) };
+    sync_cores();
+}
+
+#[cfg(target_os = "none")]
+unsafe fn write_text(addr: u64, bytes: &[u8]) {
+    use crate::memory::core::address::{kernel_virt_to_phys, phys_to_virt};
+    use crate::memory::core::layout::KERNEL_START;
+    use crate::memory::core::types::{PhysAddr, VirtAddr};
+
+    // L'image noyau est liée en identité basse ; la fenêtre haute reste
+    // acceptée pour les adresses `KERNEL_START + …`.
+    let phys = if addr >= KERNEL_START.as_u64() {
+        kernel_virt_to_phys(VirtAddr::new(addr))
+    } else {
+        PhysAddr::new(addr)
+    };
+    // Un .text contigu physiquement : l'écriture ne traverse pas de trou.
+    let alias = phys_to_virt(phys).as_u64() as *mut u8;
+    // SAFETY: alias physmap RW de la page .text ; aucune référence Rust n'existe
+    // sur ces octets (code machine), l'écriture est volatile octet par octet.
+    unsafe {
+        for (i, &b) in bytes.iter().enumerate() {
+            core::ptr::write_volatile(alias.add(i), b);
+        }
+    }
+}
+
+#[cfg(not(target_os = "none"))]
+unsafe fn write_text(addr: u64, bytes: &[u8]) {
+    // SAFETY: tests hôte — `addr` désigne un tampon ordinaire.
+    unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), addr as *mut u8, bytes.len()) };
+}
+
+/// Sérialise tous les cœurs : CPUID localement, IPI (retour par `iretq`,
+/// sérialisant) sur les autres, avec attente des acquittements.
+#[cfg(target_os = "none")]
+fn sync_cores() {
+    // CPUID, instruction sérialisante : barrière après la modification de code.
+    let _ = core::arch::x86_64::__cpuid(0);
+    let cpus = crate::arch::x86_64::smp::hotplug::online_cpu_count();
+    crate::arch::x86_64::apic::ipi::broadcast_tlb_shootdown(0, cpus);
+}
+
+#[cfg(not(target_os = "none"))]
+fn sync_cores() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jmp_encoding_forward_and_backward() {
+        assert_eq!(encode_jmp(0x1000, 0x1005), Some([0xE9, 0, 0, 0, 0]));
+        assert_eq!(encode_jmp(0x1000, 0x2000), Some([0xE9, 0xFB, 0x0F, 0, 0]));
+        assert_eq!(
+            encode_jmp(0x2000, 0x1000),
+            Some([0xE9, 0xFB, 0xEF, 0xFF, 0xFF])
+        );
+        assert_eq!(encode_jmp(0x1000, 0x1_0000_1000), None);
+    }
+
+    #[test]
+    fn patch_entry_writes_jump_and_clears_window() {
+        let mut text = [0x55u8, 0x48, 0x89, 0xE5, 0x90, 0xC3];
+        let entry = text.as_mut_ptr() as u64;
+        let jmp = encode_jmp(entry, entry + 0x40).unwrap();
+        // SAFETY: tampon local, pas de code exécuté.
+        unsafe { patch_entry(entry, &jmp, entry + 0x40) };
+        assert_eq!(text[..JMP_LEN], jmp);
+        assert_eq!(text[JMP_LEN], 0xC3);
+
+        let mut rip = entry + 1;
+        assert!(!handle_int3(&mut rip));
+
+        // Fenêtre ouverte : l'int3 de l'entrée est dévié, le reste ignoré.
+        POKE_TARGET.store(0x9000, Ordering::Release);
+        POKE_SITE.store(0x4000, Ordering::Release);
+        let mut rip = 0x4001;
+        assert!(handle_int3(&mut rip));
+        assert_eq!(rip, 0x9000);
+        let mut other = 0x5001;
+        assert!(!handle_int3(&mut other));
+        POKE_SITE.store(0, Ordering::Release);
+    }
+}
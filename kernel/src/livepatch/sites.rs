@@ -0,0 +1,37 @@
+// kernel/src/livepatch/sites.rs
+//
+// Table de boot des fonctions patchables.
+//
+// Une fonction entre ici quand une faille y serait corrigée à chaud : contrôles
+// de politique et de droits appelés sur les chemins IPC/syscall. Chaque entrée
+// est `#[inline(never)]` à sa définition (sinon les appelants garderaient une
+// copie non patchée) et sa taille est un majorant : une plage trop large ne
+// coûte que des EBUSY de plus (`consistency`).
+
+use crate::livepatch_site;
+
+use super::LivepatchError;
+
+/// Enregistre les sites de la table ; un échec n'empêche pas les suivants.
+pub fn register_builtin() -> usize {
+    let results: [Result<(), LivepatchError>; 2] = [
+        livepatch_site!(
+            "ipc_check_direct",
+            crate::security::ipc_policy::check_direct_ipc,
+            0x400
+        ),
+        livepatch_site!(
+            "cap_check_token",
+            crate::security::capability::check_token,
+            0x400
+        ),
+    ];
+    let mut registered = 0;
+    for result in results {
+        match result {
+            Ok(()) => registered += 1,
+            Err(err) => log::warn!("livepatch: site refusé ({:?})", err),
+        }
+    }
+    registered
+}
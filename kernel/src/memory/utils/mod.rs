@@ -1,9 +1,10 @@
 // kernel/src/memory/utils/mod.rs
 //
-// Module utils — futex table (UNIQUE), OOM killer, shrinker.
+// Module utils — futex table (UNIQUE), OOM killer, shrinker, pression mémoire.
 
 pub mod futex_table;
 pub mod oom_killer;
+pub mod pressure;
 pub mod shrinker;
 
 // Re-exports futex_table
@@ -19,6 +20,9 @@ pub use oom_killer::{
     OomCandidateProviderFn, OomKillCandidate, OomKillSendFn, OomScorer, OomStats, OOM_STATS,
 };
 
+// Re-exports pressure
+pub use pressure::{PressureLevel, PressureReport, PRESSURE_WINDOW_NS};
+
 // Re-exports shrinker
 pub use shrinker::{
     register_shrinker, run_shrinkers, shrink_all, unregister_shrinker, ShrinkerEntry, ShrinkerFn,
@@ -0,0 +1,332 @@
+// kernel/src/memory/utils/pressure.rs
+//
+// Pression mémoire — temps de blocage façon PSI.
+//
+// Principe :
+//   • Une tâche est « bloquée sur la mémoire » pendant un reclaim direct
+//     (shrinkers lancés depuis un fault faute de frame libre).
+//     `stall_begin` / `stall_end` encadrent ces périodes ; le temps où au
+//     moins une tâche est bloquée est cumulé (mesure `some` de PSI ; la
+//     mesure `full` n'est pas tenue, faute de compter les tâches runnable).
+//   • Toutes les 2 s, la part de la fenêtre passée bloquée alimente trois
+//     moyennes exponentielles (10 s, 60 s, 300 s), en virgule fixe comme
+//     le loadavg.
+//   • Le niveau (None/Low/Medium/Critical) combine avg10 et la part de
+//     RAM libre. Chaque changement de niveau et chaque OOM incrémente
+//     `seq` : le descripteur pollable de `exo_psi(OPEN)` devient lisible
+//     quand `seq` a bougé depuis sa dernière lecture.
+//
+// COUCHE 0 — pas de dépendance scheduler/process/ipc/fs ; les horodatages
+// (ns monotones) sont fournis par l'appelant.
+
+use spin::Mutex;
+
+use crate::memory::physical::stats::{free_pages, total_pages};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Constantes
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Période d'échantillonnage des moyennes.
+pub const PRESSURE_WINDOW_NS: u64 = 2_000_000_000;
+
+/// 1.0 en virgule fixe.
+const FIXED_1: u64 = 1 << 11;
+/// `FIXED_1 * exp(-2/10)`, `exp(-2/60)`, `exp(-2/300)`.
+const EXP: [u64; 3] = [1677, 1981, 2034];
+/// Au-delà de 300 s sans mise à jour, les moyennes repartent de zéro.
+const MAX_CATCHUP_WINDOWS: u64 = 150;
+
+/// Seuils avg10 (‰ du temps bloqué) : Low, Medium, Critical.
+const STALL_PERMILLE: [u32; 3] = [10, 100, 300];
+/// Seuils de RAM libre (‰ du total) : Low, Medium, Critical.
+const FREE_PERMILLE: [u64; 3] = [150, 80, 30];
+
+/// Durée pendant laquelle le niveau reste Critical après un OOM.
+const OOM_HOLD_NS: u64 = 10_000_000_000;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Types publics
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Niveau de pression exposé à userspace.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PressureLevel {
+    None = 0,
+    Low = 1,
+    Medium = 2,
+    Critical = 3,
+}
+
+/// Instantané copié tel quel vers userspace (`exo_psi(READ)` et lecture du
+/// descripteur pollable).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PressureReport {
+    /// Part du temps bloqué, en ‰, sur 10 s, 60 s et 300 s.
+    pub avg10: u32,
+    pub avg60: u32,
+    pub avg300: u32,
+    /// `PressureLevel` en u32.
+    pub level: u32,
+    /// Temps bloqué cumulé depuis le boot, en µs.
+    pub total_us: u64,
+    pub free_pages: u64,
+    pub total_pages: u64,
+    /// OOM constatés (fault sans frame après reclaim, kill demandé).
+    pub oom_events: u64,
+    /// Incrémenté à chaque changement de niveau et à chaque OOM.
+    pub seq: u64,
+}
+
+const _: () = assert!(core::mem::size_of::<PressureReport>() == 56);
+
+// ─────────────────────────────────────────────────────────────────────────────
+// État
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct PressureState {
+    /// Tâches actuellement bloquées.
+    stalled: u32,
+    /// Début de la période `some` en cours (valide si `stalled > 0`).
+    stall_start_ns: u64,
+    /// Temps bloqué cumulé, périodes closes uniquement.
+    total_ns: u64,
+    window_start_ns: u64,
+    /// Temps bloqué cumulé au début de la fenêtre courante.
+    window_total_ns: u64,
+    avg: [u64; 3],
+    level: PressureLevel,
+    oom_events: u64,
+    oom_hold_until_ns: u64,
+    seq: u64,
+}
+
+impl PressureState {
+    const fn new() -> Self {
+        Self {
+            stalled: 0,
+            stall_start_ns: 0,
+            total_ns: 0,
+            window_start_ns: 0,
+            window_total_ns: 0,
+            avg: [0; 3],
+            level: PressureLevel::None,
+            oom_events: 0,
+            oom_hold_until_ns: 0,
+            seq: 0,
+        }
+    }
+
+    /// Temps bloqué cumulé à l'instant `t`, période en cours comprise.
+    fn total_at(&self, t: u64) -> u64 {
+        if self.stalled == 0 {
+            return self.total_ns;
+        }
+        self.total_ns + t.saturating_sub(self.stall_start_ns)
+    }
+
+    /// Clôt les fenêtres écoulées jusqu'à `now` et met à jour les moyennes.
+    fn roll(&mut self, now: u64) {
+        let elapsed = now.saturating_sub(self.window_start_ns) / PRESSURE_WINDOW_NS;
+        if elapsed > MAX_CATCHUP_WINDOWS {
+            self.avg = [0; 3];
+            self.window_start_ns = now;
+            self.window_total_ns = self.total_at(now);
+            return;
+        }
+        for _ in 0..elapsed {
+            let end = self.window_start_ns + PRESSURE_WINDOW_NS;
+            let total = self.total_at(end);
+            let stalled = total
+                .saturating_sub(self.window_total_ns)
+                .min(PRESSURE_WINDOW_NS);
+            let sample = stalled * FIXED_1 / PRESSURE_WINDOW_NS;
+            for (avg, &exp) in self.avg.iter_mut().zip(EXP.iter()) {
+                *avg = (*avg * exp + sample * (FIXED_1 - exp)) / FIXED_1;
+            }
+            self.window_start_ns = end;
+            self.window_total_ns = total;
+        }
+    }
+
+    fn permille(&self, i: usize) -> u32 {
+        (self.avg[i] * 1000 / FIXED_1) as u32
+    }
+
+    /// Recalcule le niveau ; incrémente `seq` s'il change.
+    fn refresh(&mut self, now: u64, free: u64, total: u64) {
+        self.roll(now);
+        let level = if now < self.oom_hold_until_ns {
+            PressureLevel::Critical
+        } else {
+            level_for(self.permille(0), free, total)
+        };
+        if level != self.level {
+            self.level = level;
+            self.seq += 1;
+        }
+    }
+
+    fn begin(&mut self, now: u64) {
+        self.roll(now);
+        if self.stalled == 0 {
+            self.stall_start_ns = now;
+        }
+        self.stalled += 1;
+    }
+
+    fn end(&mut self, now: u64) {
+        self.roll(now);
+        if self.stalled == 0 {
+            return;
+        }
+        self.stalled -= 1;
+        if self.stalled == 0 {
+            self.total_ns += now.saturating_sub(self.stall_start_ns);
+        }
+    }
+
+    fn note_oom(&mut self, now: u64) {
+        self.oom_events += 1;
+        self.oom_hold_until_ns = now.saturating_add(OOM_HOLD_NS);
+        self.level = PressureLevel::Critical;
+        self.seq += 1;
+    }
+
+    fn report(&self, now: u64, free: u64, total: u64) -> PressureReport {
+        PressureReport {
+            avg10: self.permille(0),
+            avg60: self.permille(1),
+            avg300: self.permille(2),
+            level: self.level as u32,
+            total_us: self.total_at(now) / 1_000,
+            free_pages: free,
+            total_pages: total,
+            oom_events: self.oom_events,
+            seq: self.seq,
+        }
+    }
+}
+
+/// Niveau pour un avg10 donné et `free` pages libres sur `total`.
+fn level_for(avg10_permille: u32, free: u64, total: u64) -> PressureLevel {
+    let free_permille = free.saturating_mul(1000).checked_div(total).unwrap_or(1000);
+    let levels = [
+        PressureLevel::Low,
+        PressureLevel::Medium,
+        PressureLevel::Critical,
+    ];
+    let mut level = PressureLevel::None;
+    for (i, &l) in levels.iter().enumerate() {
+        if avg10_permille >= STALL_PERMILLE[i] || free_permille < FREE_PERMILLE[i] {
+            level = l;
+        }
+    }
+    level
+}
+
+static PRESSURE: Mutex<PressureState> = Mutex::new(PressureState::new());
+
+// ─────────────────────────────────────────────────────────────────────────────
+// API
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Une tâche commence à attendre de la mémoire (reclaim direct).
+pub fn stall_begin(now_ns: u64) {
+    PRESSURE.lock().begin(now_ns);
+}
+
+/// Fin d'une attente ouverte par `stall_begin`.
+pub fn stall_end(now_ns: u64) {
+    let mut state = PRESSURE.lock();
+    state.end(now_ns);
+    state.refresh(now_ns, free_pages() as u64, total_pages() as u64);
+}
+
+/// OOM constaté : le niveau reste Critical pendant `OOM_HOLD_NS`.
+pub fn note_oom(now_ns: u64) {
+    PRESSURE.lock().note_oom(now_ns);
+}
+
+/// Numéro de séquence courant, niveau recalculé.
+pub fn seq(now_ns: u64) -> u64 {
+    let mut state = PRESSURE.lock();
+    state.refresh(now_ns, free_pages() as u64, total_pages() as u64);
+    state.seq
+}
+
+/// Instantané courant, niveau recalculé.
+pub fn snapshot(now_ns: u64) -> PressureReport {
+    let free = free_pages() as u64;
+    let total = total_pages() as u64;
+    let mut state = PRESSURE.lock();
+    state.refresh(now_ns, free, total);
+    state.report(now_ns, free, total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEC: u64 = 1_000_000_000;
+
+    #[test]
+    fn full_stall_raises_averages_and_level() {
+        let mut state = PressureState::new();
+        state.begin(0);
+        state.end(10 * SEC);
+        state.refresh(10 * SEC, 1000, 1000);
+        let report = state.report(10 * SEC, 1000, 1000);
+        assert!(report.avg10 > 600, "avg10 = {}", report.avg10);
+        assert!(report.avg60 < report.avg10);
+        assert!(report.avg300 < report.avg60);
+        assert_eq!(report.total_us, 10_000_000);
+        assert_eq!(report.level, PressureLevel::Critical as u32);
+        assert_eq!(report.seq, 1);
+    }
+
+    #[test]
+    fn pressure_decays_once_stalls_stop() {
+        let mut state = PressureState::new();
+        state.begin(0);
+        state.end(10 * SEC);
+        state.refresh(10 * SEC, 1000, 1000);
+        state.refresh(60 * SEC, 1000, 1000);
+        assert!(state.permille(0) < STALL_PERMILLE[0]);
+        assert_eq!(state.level, PressureLevel::None);
+        assert_eq!(state.seq, 2);
+    }
+
+    #[test]
+    fn nested_stalls_count_wall_time_once() {
+        let mut state = PressureState::new();
+        state.begin(0);
+        state.begin(SEC / 2);
+        state.end(SEC);
+        state.end(2 * SEC);
+        assert_eq!(state.total_at(3 * SEC), 2 * SEC);
+    }
+
+    #[test]
+    fn low_free_memory_alone_sets_the_level() {
+        assert_eq!(level_for(0, 500, 1000), PressureLevel::None);
+        assert_eq!(level_for(0, 100, 1000), PressureLevel::Low);
+        assert_eq!(level_for(0, 50, 1000), PressureLevel::Medium);
+        assert_eq!(level_for(0, 10, 1000), PressureLevel::Critical);
+        assert_eq!(level_for(150, 500, 1000), PressureLevel::Medium);
+    }
+
+    #[test]
+    fn oom_holds_critical_then_releases() {
+        let mut state = PressureState::new();
+        state.note_oom(SEC);
+        state.refresh(2 * SEC, 1000, 1000);
+        assert_eq!(state.level, PressureLevel::Critical);
+        assert_eq!(state.seq, 1);
+        state.refresh(SEC + OOM_HOLD_NS, 1000, 1000);
+        assert_eq!(state.level, PressureLevel::None);
+        assert_eq!(state.seq, 2);
+    }
+}
@@ -208,6 +208,9 @@ impl AddressSpaceCloner for KernelAddressSpaceCloner {
         unsafe {
             free_userspace_tables(addr_space.pml4_phys());
         }
+        // Les frames SHM ont été laissées au pool : reste à décompter les
+        // mappings de cet espace pour que leurs régions restent destructibles.
+        crate::memory::virt::mmap::shm_forget_mappings(&addr_space, None);
     }
 }
 
@@ -432,6 +435,11 @@ fn release_leaf_frame(entry: PageTableEntry) {
         return;
     };
     let remaining = COW_TRACKER.dec(frame);
+    // Les frames SHM restent au pool : la région vit au-delà du processus
+    // (ses mappings sont oubliés par `free_addr_space`).
+    if crate::memory::virt::mmap::shm_owns_frame(frame.start_address().as_u64()) {
+        return;
+    }
     if remaining == 0 || (remaining == u32::MAX && !entry.is_cow()) {
         let _ = buddy::free_pages(frame, 0);
     }
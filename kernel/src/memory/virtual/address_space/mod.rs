@@ -15,4 +15,6 @@ pub use tlb::{
     flush_all, flush_all_including_global, flush_range, flush_single, register_tlb_ipi_sender,
     shootdown, shootdown_sync, TlbFlushType, TlbShootdownQueue, TlbStats, TLB_QUEUE, TLB_STATS,
 };
-pub use user::{UserAddressSpace, UserAsStats, USER_MMAP_BASE, USER_STACK_SIZE};
+pub use user::{
+    UserAddressSpace, UserAsStats, IA32_MMAP_BASE, IA32_USER_END, USER_MMAP_BASE, USER_STACK_SIZE,
+};
@@ -21,6 +21,12 @@ use spin::Mutex;
 pub const USER_MMAP_BASE: u64 = 0x0000_7F00_0000_0000;
 pub const USER_STACK_SIZE: u64 = 8 * 1024 * 1024; // 8 MiB
 
+/// Fin de l'espace user d'un processus i386 (compat 32-bit) : 4 GiB moins
+/// deux pages de garde sous le plafond des registres 32-bit.
+pub const IA32_USER_END: u64 = 0xFFFF_E000;
+/// Base mmap d'un processus i386 (au-dessus d'un brk raisonnable).
+pub const IA32_MMAP_BASE: u64 = 0x4000_0000;
+
 /// Statistiques de l'espace d'adressage utilisateur.
 pub struct UserAsStats {
     pub page_faults: AtomicU64,
@@ -61,6 +67,7 @@ struct UserAsInner {
     vma_tree: VmaTree,
     mmap_hint: VirtAddr, // Hint pour mmap (bump)
     stack_bottom: VirtAddr,
+    user_limit: VirtAddr, // USER_END, ou IA32_USER_END pour une image i386
 }
 
 // SAFETY: UserAddressSpace est thread-safe via son Mutex interne.
@@ -78,6 +85,7 @@ impl UserAddressSpace {
                 vma_tree: VmaTree::new(),
                 mmap_hint: VirtAddr::new(USER_MMAP_BASE),
                 stack_bottom,
+                user_limit: USER_END,
             }),
             stats: UserAsStats::new(),
             pml4_phys,
@@ -92,6 +100,30 @@ impl UserAddressSpace {
         self.pml4_phys
     }
 
+    /// Restreint cet espace au layout i386 : toute VMA reste sous 4 GiB.
+    ///
+    /// Appelé par le chargeur ELF avant d'installer une image ELFCLASS32.
+    pub fn apply_compat32_layout(&self) {
+        let mut inner = self.inner.lock();
+        inner.user_limit = VirtAddr::new(IA32_USER_END);
+        inner.mmap_hint = VirtAddr::new(IA32_MMAP_BASE);
+    }
+
+    /// Première adresse hors de l'espace user de ce processus.
+    #[inline]
+    pub fn user_limit(&self) -> VirtAddr {
+        self.inner.lock().user_limit
+    }
+
+    /// `true` si `[start, start+len)` tient sous `user_limit()`.
+    #[inline]
+    pub fn range_fits(&self, start: u64, len: u64) -> bool {
+        match start.checked_add(len) {
+            Some(end) => end <= self.user_limit().as_u64(),
+            None => false,
+        }
+    }
+
     /// Initialise les bornes brk de cet espace après chargement ELF.
     #[inline]
     pub fn init_heap_bounds(&self, brk_start: u64) {
@@ -226,6 +258,7 @@ impl UserAddressSpace {
         dst.vma_tree = cloned_tree;
         dst.mmap_hint = src.mmap_hint;
         dst.stack_bottom = src.stack_bottom;
+        dst.user_limit = src.user_limit;
         child.stats.vma_count.store(cloned_count, Ordering::Relaxed);
         true
     }
@@ -262,7 +295,7 @@ impl UserAddressSpace {
             size,
             hint_addr,
             VirtAddr::new(PAGE_SIZE as u64), // min = 4 KiB (éviter NULL)
-            USER_END,
+            inner.user_limit,
         )
     }
 
@@ -222,6 +222,10 @@ pub fn do_mmap_in_as(
         if addr == 0 || addr % PAGE_SIZE as u64 != 0 {
             return Err(MmapError::InvalidAddress);
         }
+        // Un processus i386 ne peut pas fixer une VMA au-delà de 4 GiB.
+        if !user_as.range_fits(addr, len_aligned as u64) {
+            return Err(MmapError::InvalidAddress);
+        }
         VirtAddr::new(addr)
     } else {
         let hint = if addr != 0 {
@@ -295,9 +299,9 @@ pub fn do_munmap_in_as(user_as: &UserAddressSpace, addr: u64) -> Result<(), Mmap
         None => Err(MmapError::NotMapped),
         Some(vma_ptr) => {
             // Récupérer les bornes de la VMA avant de libérer le descripteur.
-            let (vma_start, vma_end) = unsafe {
+            let (vma_start, vma_end, backing) = unsafe {
                 let vma = &*vma_ptr;
-                (vma.start, vma.end)
+                (vma.start, vma.end, vma.backing)
             };
 
             // Libérer le VmaDescriptor alloué par do_mmap / do_brk.
@@ -353,10 +357,19 @@ pub fn do_munmap_in_as(user_as: &UserAddressSpace, addr: u64) -> Result<(), Mmap
                 }
 
                 // Phase 3 : libérer les frames (TLBs déjà invalidés partout).
+                // Une frame SHM appartient au pool, pas au buddy.
                 for i in 0..count {
+                    if shm_owns_frame(frames[i].start_address().as_u64()) {
+                        continue;
+                    }
                     let _ = crate::memory::physical::allocator::buddy::free_page(frames[i]);
                 }
             }
+            // Région SHM démappée par munmap(2) plutôt que par exo_shm : une
+            // fois ses PTEs parties, la région peut perdre ce mapping.
+            if backing == VmaBacking::Shared {
+                shm_forget_mappings(user_as, Some(vma_start.as_u64()));
+            }
             Ok(())
         }
     }
@@ -516,6 +529,9 @@ pub fn do_mremap_zero_copy(
     }
 
     let target_start = if flags & MREMAP_FIXED != 0 {
+        if !user_as.range_fits(new_addr, new_len_aligned as u64) {
+            return Err(MmapError::InvalidAddress);
+        }
         VirtAddr::new(new_addr)
     } else {
         user_as
@@ -647,12 +663,14 @@ pub fn do_brk(addr: u64) -> Result<u64, MmapError> {
         return Ok(cur);
     }
 
-    if addr < heap_base || addr > USER_MMAP_BASE {
+    // Plafond du heap : base mmap 64-bit, ou fin de l'espace i386 (4 GiB).
+    let brk_max = USER_MMAP_BASE.min(user_as.user_limit().as_u64());
+    if addr < heap_base || addr > brk_max {
         return Err(MmapError::InvalidAddress);
     }
 
     let new_brk = align_up_page(addr).ok_or(MmapError::InvalidAddress)?;
-    if new_brk < heap_base || new_brk > USER_MMAP_BASE {
+    if new_brk < heap_base || new_brk > brk_max {
         return Err(MmapError::InvalidAddress);
     }
 
@@ -731,10 +749,13 @@ pub type ShmReleaseRegionFn = fn(desc_idx: usize);
 pub type ShmRegisterMappingFn = fn(
     desc_idx: usize,
     pid: u32,
+    addr_space: u64,
     virt_base: u64,
     writable: bool,
     n_pages: usize,
 ) -> Result<usize, ShmMapError>;
+pub type ShmOwnsFrameFn = fn(phys: u64) -> bool;
+pub type ShmForgetMappingsFn = fn(addr_space: u64, virt_base: Option<u64>);
 
 /// Callbacks IPC nécessaires pour mapper une région SHM sans importer IPC dans memory/.
 #[derive(Clone, Copy)]
@@ -743,18 +764,26 @@ pub struct ShmProviderFns {
     pub page_phys: ShmPagePhysFn,
     pub release_region: ShmReleaseRegionFn,
     pub register_mapping: ShmRegisterMappingFn,
+    /// Frame du pool SHM : à démapper sans la rendre au buddy.
+    pub owns_frame: ShmOwnsFrameFn,
+    /// Oublie les mappings d'un espace (celui à `virt_base`, ou tous).
+    pub forget_mappings: ShmForgetMappingsFn,
 }
 
 static SHM_REGION_INFO_FN: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
 static SHM_PAGE_PHYS_FN: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
 static SHM_RELEASE_REGION_FN: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
 static SHM_REGISTER_MAPPING_FN: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+static SHM_OWNS_FRAME_FN: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+static SHM_FORGET_MAPPINGS_FN: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
 
 pub fn register_shm_provider(provider: ShmProviderFns) {
     SHM_REGION_INFO_FN.store(provider.region_info as *mut (), Ordering::Release);
     SHM_PAGE_PHYS_FN.store(provider.page_phys as *mut (), Ordering::Release);
     SHM_RELEASE_REGION_FN.store(provider.release_region as *mut (), Ordering::Release);
     SHM_REGISTER_MAPPING_FN.store(provider.register_mapping as *mut (), Ordering::Release);
+    SHM_OWNS_FRAME_FN.store(provider.owns_frame as *mut (), Ordering::Release);
+    SHM_FORGET_MAPPINGS_FN.store(provider.forget_mappings as *mut (), Ordering::Release);
 }
 
 fn shm_provider() -> Option<ShmProviderFns> {
@@ -762,10 +791,14 @@ fn shm_provider() -> Option<ShmProviderFns> {
     let page_phys = SHM_PAGE_PHYS_FN.load(Ordering::Acquire);
     let release_region = SHM_RELEASE_REGION_FN.load(Ordering::Acquire);
     let register_mapping = SHM_REGISTER_MAPPING_FN.load(Ordering::Acquire);
+    let owns_frame = SHM_OWNS_FRAME_FN.load(Ordering::Acquire);
+    let forget_mappings = SHM_FORGET_MAPPINGS_FN.load(Ordering::Acquire);
     if region_info.is_null()
         || page_phys.is_null()
         || release_region.is_null()
         || register_mapping.is_null()
+        || owns_frame.is_null()
+        || forget_mappings.is_null()
     {
         return None;
     }
@@ -775,9 +808,32 @@ fn shm_provider() -> Option<ShmProviderFns> {
         page_phys: unsafe { core::mem::transmute(page_phys) },
         release_region: unsafe { core::mem::transmute(release_region) },
         register_mapping: unsafe { core::mem::transmute(register_mapping) },
+        owns_frame: unsafe { core::mem::transmute(owns_frame) },
+        forget_mappings: unsafe { core::mem::transmute(forget_mappings) },
     })
 }
 
+/// Frame du pool SHM : munmap et la destruction d'un espace d'adressage la
+/// démappent sans la rendre au buddy.
+pub fn shm_owns_frame(phys: u64) -> bool {
+    shm_provider().is_some_and(|provider| (provider.owns_frame)(phys))
+}
+
+/// Identité d'un espace d'adressage dans `SHM_MAPPING_TABLE`.
+#[inline]
+fn shm_addr_space_id(user_as: &UserAddressSpace) -> u64 {
+    user_as as *const UserAddressSpace as u64
+}
+
+/// Oublie côté IPC les mappings SHM de `user_as` (celui posé à `virt_base`,
+/// ou tous) : le compteur de mappings de chaque région redescend, sans quoi
+/// sa destruction resterait refusée.
+pub fn shm_forget_mappings(user_as: &UserAddressSpace, virt_base: Option<u64>) {
+    if let Some(provider) = shm_provider() {
+        (provider.forget_mappings)(shm_addr_space_id(user_as), virt_base);
+    }
+}
+
 /// Résultat d'un mappage SHM dans un espace d'adressage.
 pub struct ShmMapIntoResult {
     /// Adresse virtuelle de base du mapping dans l'espace cible.
@@ -929,14 +985,19 @@ pub fn map_shm_into_process(
     }
 
     // ── 6. Enregistrer dans SHM_MAPPING_TABLE ────────────────────────────
-    let mapping_idx =
-        (provider.register_mapping)(desc_idx, pid, virt_base.as_u64(), writable, n_pages).map_err(
-            |err| {
-                rollback_shm_into_process(user_as, virt_base, n_pages);
-                (provider.release_region)(desc_idx);
-                err
-            },
-        )?;
+    let mapping_idx = (provider.register_mapping)(
+        desc_idx,
+        pid,
+        shm_addr_space_id(user_as),
+        virt_base.as_u64(),
+        writable,
+        n_pages,
+    )
+    .map_err(|err| {
+        rollback_shm_into_process(user_as, virt_base, n_pages);
+        (provider.release_region)(desc_idx);
+        err
+    })?;
 
     Ok(ShmMapIntoResult {
         virt_base: virt_base.as_u64(),
@@ -944,3 +1005,35 @@ pub fn map_shm_into_process(
         mapping_idx,
     })
 }
+
+/// Retire un mapping posé par [`map_shm_into_process`] : PTEs démappées,
+/// TLB shootdown synchrone, VMA supprimée. Les frames restent au pool SHM.
+///
+/// Retourne le nombre de pages démappées ; l'entrée de `SHM_MAPPING_TABLE`
+/// reste à libérer par l'appelant côté IPC.
+pub fn unmap_shm_from_process(
+    user_as: &UserAddressSpace,
+    virt_base: u64,
+) -> Result<usize, ShmMapError> {
+    let start = VirtAddr::new(virt_base);
+    let vma_ptr = user_as.find_vma(start).ok_or(ShmMapError::InvalidRegion)?;
+    // SAFETY: find_vma() retourne un descripteur vivant de `user_as`.
+    let end = unsafe {
+        let vma = &*vma_ptr;
+        if vma.start != start || vma.backing != VmaBacking::Shared {
+            return Err(ShmMapError::InvalidRegion);
+        }
+        vma.end
+    };
+    let n_pages = ((end.as_u64() - start.as_u64()) as usize) / PAGE_SIZE;
+
+    rollback_shm_into_process(user_as, start, n_pages);
+    // SAFETY: plage canonique user, appelé hors IRQ.
+    unsafe {
+        crate::memory::virt::shootdown_sync(
+            crate::memory::virt::TlbFlushType::Range { start, end },
+            crate::arch::x86_64::acpi::madt::madt_cpu_count(),
+        );
+    }
+    Ok(n_pages)
+}
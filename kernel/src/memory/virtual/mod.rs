@@ -30,6 +30,7 @@ pub use fault::{
 };
 pub use mmap::{
     do_brk, do_mmap, do_mmap_in_as, do_mprotect, do_mprotect_in_as, do_mremap_zero_copy, do_munmap,
-    do_munmap_in_as, map_shm_into_process, register_current_as_getter, CurrentAsGetterFn,
-    MmapError, MremapZeroCopy, ShmMapError, ShmMapIntoResult,
+    do_munmap_in_as, map_shm_into_process, register_current_as_getter, shm_owns_frame,
+    unmap_shm_from_process, CurrentAsGetterFn, MmapError, MremapZeroCopy, ShmMapError,
+    ShmMapIntoResult,
 };
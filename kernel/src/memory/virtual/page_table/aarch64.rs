@@ -0,0 +1,196 @@
+// kernel/src/memory/virtual/page_table/aarch64.rs
+//
+// Descripteurs VMSAv8-64 stage 1, granule 4 KiB, VA 48 bits (4 niveaux).
+// Encodage pur (testable sur l'hôte) ; la programmation de MAIR/TCR/TTBR est
+// dans `arch/aarch64/mmu.rs`.
+// Couche 0 — aucune dépendance externe.
+//
+// Descripteur (niveau 1 = feuille 4 KiB dans la numérotation du mapper) :
+//   [0]     valide
+//   [1]     table (niveaux > 1) / page (niveau 1) ; 0 = bloc
+//   [4:2]   AttrIndx → MAIR_EL1
+//   [7:6]   AP[2:1] : bit 6 = accès EL0, bit 7 = lecture seule
+//   [9:8]   SH (11 = Inner Shareable)
+//   [10]    AF (Access Flag, positionné d'office : pas de gestion matérielle)
+//   [11]    nG (non global : ASID)
+//   [47:12] adresse de sortie
+//   [53]    PXN, [54] UXN
+//   [55..58] logiciel : CoW, pinned
+
+use super::format::PageTableFormat;
+use crate::memory::core::PageFlags;
+
+const DESC_VALID: u64 = 1 << 0;
+const DESC_TABLE_OR_PAGE: u64 = 1 << 1;
+const DESC_ATTR_SHIFT: u64 = 2;
+const DESC_ATTR_MASK: u64 = 0b111 << DESC_ATTR_SHIFT;
+const DESC_AP_EL0: u64 = 1 << 6;
+const DESC_AP_RO: u64 = 1 << 7;
+const DESC_SH_INNER: u64 = 0b11 << 8;
+const DESC_AF: u64 = 1 << 10;
+const DESC_NG: u64 = 1 << 11;
+const DESC_PXN: u64 = 1 << 53;
+const DESC_UXN: u64 = 1 << 54;
+const DESC_SW_COW: u64 = 1 << 55;
+const DESC_SW_PINNED: u64 = 1 << 56;
+const DESC_ADDR_MASK: u64 = 0x0000_FFFF_FFFF_F000;
+
+/// Index MAIR : mémoire normale Write-Back (RAM).
+pub const MAIR_IDX_NORMAL: u64 = 0;
+/// Index MAIR : Device-nGnRE (MMIO).
+pub const MAIR_IDX_DEVICE: u64 = 1;
+/// Index MAIR : mémoire normale non cacheable (write-combining).
+pub const MAIR_IDX_NORMAL_NC: u64 = 2;
+
+/// Valeur de MAIR_EL1 cohérente avec les index ci-dessus.
+pub const MAIR_EL1_VALUE: u64 = 0xFF | (0x04 << 8) | (0x44 << 16);
+
+/// Format AArch64 pour `GenericMapper`.
+pub struct Aarch64Format;
+
+impl Aarch64Format {
+    /// Descripteur de bloc (2 MiB au niveau 2, 1 GiB au niveau 3 du mapper).
+    ///
+    /// Utilisé pour l'identité de boot (`arch/aarch64/mmu.rs`) ; `phys` doit
+    /// être aligné sur la taille du bloc.
+    #[inline]
+    pub fn block_entry(phys: u64, flags: PageFlags) -> u64 {
+        Self::leaf_entry(phys, flags) & !DESC_TABLE_OR_PAGE
+    }
+}
+
+impl PageTableFormat for Aarch64Format {
+    const NAME: &'static str = "aarch64-4k-48";
+    const LEVELS: usize = 4;
+    const VA_BITS: u32 = 48;
+
+    #[inline]
+    fn is_valid(entry: u64) -> bool {
+        entry & DESC_VALID != 0
+    }
+
+    #[inline]
+    fn is_leaf(entry: u64, level: usize) -> bool {
+        Self::is_valid(entry) && (level == 1 || entry & DESC_TABLE_OR_PAGE == 0)
+    }
+
+    #[inline]
+    fn table_entry(table_phys: u64) -> u64 {
+        (table_phys & DESC_ADDR_MASK) | DESC_VALID | DESC_TABLE_OR_PAGE
+    }
+
+    fn leaf_entry(phys: u64, flags: PageFlags) -> u64 {
+        let attr = if flags.intersects(PageFlags::NO_CACHE | PageFlags::DMA) {
+            MAIR_IDX_DEVICE
+        } else if flags.contains(PageFlags::WRITE_COMBINING) {
+            MAIR_IDX_NORMAL_NC
+        } else {
+            MAIR_IDX_NORMAL
+        };
+        let mut desc = (phys & DESC_ADDR_MASK)
+            | DESC_VALID
+            | DESC_TABLE_OR_PAGE
+            | (attr << DESC_ATTR_SHIFT)
+            | DESC_SH_INNER
+            | DESC_AF;
+        if !flags.is_writable() {
+            desc |= DESC_AP_RO;
+        }
+        if flags.is_user() {
+            // Jamais exécutable par EL1 (équivalent SMEP).
+            desc |= DESC_AP_EL0 | DESC_PXN;
+            if !flags.is_executable() {
+                desc |= DESC_UXN;
+            }
+        } else {
+            desc |= DESC_UXN;
+            if !flags.is_executable() {
+                desc |= DESC_PXN;
+            }
+        }
+        if !flags.contains(PageFlags::GLOBAL) {
+            desc |= DESC_NG;
+        }
+        if flags.is_cow() {
+            desc |= DESC_SW_COW;
+        }
+        if flags.is_pinned() {
+            desc |= DESC_SW_PINNED;
+        }
+        desc
+    }
+
+    #[inline]
+    fn entry_phys(entry: u64) -> u64 {
+        entry & DESC_ADDR_MASK
+    }
+
+    fn entry_flags(entry: u64) -> PageFlags {
+        if !Self::is_valid(entry) {
+            return PageFlags::EMPTY;
+        }
+        let mut flags = PageFlags::PRESENT;
+        let user = entry & DESC_AP_EL0 != 0;
+        if entry & DESC_AP_RO == 0 {
+            flags = flags.set(PageFlags::WRITABLE);
+        }
+        if user {
+            flags = flags.set(PageFlags::USER);
+        }
+        let no_exec = if user { DESC_UXN } else { DESC_PXN };
+        if entry & no_exec != 0 {
+            flags = flags.set(PageFlags::NO_EXECUTE);
+        }
+        if entry & DESC_NG == 0 {
+            flags = flags.set(PageFlags::GLOBAL);
+        }
+        if entry & DESC_AF != 0 {
+            flags = flags.set(PageFlags::ACCESSED);
+        }
+        match (entry & DESC_ATTR_MASK) >> DESC_ATTR_SHIFT {
+            MAIR_IDX_DEVICE => flags = flags.set(PageFlags::NO_CACHE),
+            MAIR_IDX_NORMAL_NC => flags = flags.set(PageFlags::WRITE_COMBINING),
+            _ => {}
+        }
+        if entry & DESC_SW_COW != 0 {
+            flags = flags.set(PageFlags::COW);
+        }
+        if entry & DESC_SW_PINNED != 0 {
+            flags = flags.set(PageFlags::PINNED);
+        }
+        flags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::virt::page_table::format::tests::exercise_format;
+
+    #[test]
+    fn test_aarch64_format_roundtrip() {
+        exercise_format::<Aarch64Format>(0xFFFF_8000_4000_0000);
+        exercise_format::<Aarch64Format>(0x0000_0000_0040_0000);
+    }
+
+    #[test]
+    fn test_aarch64_leaf_permissions() {
+        let user = Aarch64Format::leaf_entry(0x1000, PageFlags::USER_DATA);
+        assert_ne!(user & DESC_PXN, 0);
+        assert_ne!(user & DESC_UXN, 0);
+        assert_ne!(user & DESC_NG, 0);
+        let code = Aarch64Format::leaf_entry(0x1000, PageFlags::KERNEL_CODE);
+        assert_eq!(code & DESC_PXN, 0);
+        assert_ne!(code & (DESC_UXN | DESC_AP_RO), 0);
+        let mmio = Aarch64Format::leaf_entry(0x1000, PageFlags::KERNEL_DMA);
+        assert_eq!((mmio & DESC_ATTR_MASK) >> DESC_ATTR_SHIFT, MAIR_IDX_DEVICE);
+    }
+
+    #[test]
+    fn test_aarch64_block_is_leaf() {
+        let block = Aarch64Format::block_entry(0x4000_0000, PageFlags::KERNEL_DATA);
+        assert!(Aarch64Format::is_leaf(block, 3));
+        assert!(!Aarch64Format::is_leaf(Aarch64Format::table_entry(0x5000), 3));
+        assert_eq!(Aarch64Format::entry_phys(block), 0x4000_0000);
+    }
+}
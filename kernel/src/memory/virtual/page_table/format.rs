@@ -0,0 +1,297 @@
+// kernel/src/memory/virtual/page_table/format.rs
+//
+// Format de table de pages indépendant de l'architecture + mapper générique.
+// Couche 0 — aucune dépendance externe.
+//
+// `PageFlags` reste le vocabulaire canonique du noyau ; chaque architecture
+// fournit un `PageTableFormat` qui le traduit en bits matériels (PTE x86_64,
+// descripteurs VMSAv8-64…). `GenericMapper` parcourt n'importe quel format à
+// tables de 512 entrées de 8 octets et pages 4 KiB, ce qui couvre x86_64,
+// AArch64 (granule 4 KiB) et RISC-V Sv39/Sv48.
+//
+// Le walker x86_64 historique (`walker.rs`) reste le chemin de production ;
+// ce mapper sert les ports et les tests croisés des formats.
+
+use core::marker::PhantomData;
+
+use crate::memory::core::PageFlags;
+
+/// Entrées par table (9 bits d'index, granule 4 KiB).
+pub const ENTRIES_PER_TABLE: usize = 512;
+
+/// Décalage d'une page 4 KiB.
+pub const PAGE_SHIFT: u32 = 12;
+
+/// Table brute telle que vue par le mapper générique.
+pub type RawTable = [u64; ENTRIES_PER_TABLE];
+
+// ─────────────────────────────────────────────────────────────────────────────
+// FORMAT
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Encodage des entrées d'une table de pages pour une architecture.
+///
+/// Les niveaux sont numérotés de `LEVELS` (racine) à 1 (feuille 4 KiB).
+pub trait PageTableFormat {
+    /// Nom court (journal, diagnostic).
+    const NAME: &'static str;
+    /// Nombre de niveaux de la hiérarchie.
+    const LEVELS: usize;
+    /// Bits d'adresse virtuelle traduits (48 pour 4 niveaux, 39 pour Sv39).
+    const VA_BITS: u32;
+
+    /// Index dans la table de niveau `level` pour `virt`.
+    #[inline]
+    fn index(virt: u64, level: usize) -> usize {
+        ((virt >> (PAGE_SHIFT + 9 * (level as u32 - 1))) & 0x1FF) as usize
+    }
+
+    /// Adresse canonique : bits au-delà de `VA_BITS` = extension du bit de signe.
+    #[inline]
+    fn is_canonical(virt: u64) -> bool {
+        let shift = 64 - Self::VA_BITS;
+        (((virt << shift) as i64) >> shift) as u64 == virt
+    }
+
+    /// Entrée valide (présente) ?
+    fn is_valid(entry: u64) -> bool;
+
+    /// Entrée valide terminant la traduction au niveau `level` (page ou bloc).
+    fn is_leaf(entry: u64, level: usize) -> bool;
+
+    /// Entrée de niveau intermédiaire pointant vers `table_phys`.
+    fn table_entry(table_phys: u64) -> u64;
+
+    /// Feuille 4 KiB `phys` avec les droits `flags`.
+    fn leaf_entry(phys: u64, flags: PageFlags) -> u64;
+
+    /// Adresse physique (table ou page) portée par une entrée.
+    fn entry_phys(entry: u64) -> u64;
+
+    /// Droits d'une feuille, retraduits en `PageFlags`.
+    fn entry_flags(entry: u64) -> PageFlags;
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// ACCÈS AUX TABLES
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Accès aux tables physiques pour le mapper générique.
+///
+/// En noyau : physmap (`PHYS_MAP_BASE + phys`) et allocateur de frames ;
+/// en test : arène mémoire.
+pub trait TableAccess {
+    /// Pointeur vers la table à l'adresse physique `phys`.
+    ///
+    /// # Safety
+    /// `phys` doit désigner une table allouée par `alloc_table` (ou la racine)
+    /// et encore vivante.
+    unsafe fn table(&mut self, phys: u64) -> *mut RawTable;
+
+    /// Alloue une table mise à zéro ; `None` si la mémoire manque.
+    fn alloc_table(&mut self) -> Option<u64>;
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// MAPPER GÉNÉRIQUE
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Erreur du mapper générique.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MapError {
+    /// Adresse virtuelle hors de l'espace traduit par le format.
+    NonCanonical,
+    /// Adresse virtuelle ou physique non alignée sur 4 KiB.
+    Misaligned,
+    /// Une feuille existe déjà à cette adresse.
+    AlreadyMapped,
+    /// Aucune feuille à cette adresse.
+    NotMapped,
+    /// Un bloc (huge page) couvre déjà l'adresse.
+    HugePage,
+    /// Allocation d'une table intermédiaire impossible.
+    OutOfMemory,
+}
+
+/// Mapper 4 KiB générique sur un format `F`.
+pub struct GenericMapper<F: PageTableFormat, A: TableAccess> {
+    root: u64,
+    access: A,
+    _format: PhantomData<F>,
+}
+
+const PAGE_MASK: u64 = (1 << PAGE_SHIFT) - 1;
+
+impl<F: PageTableFormat, A: TableAccess> GenericMapper<F, A> {
+    /// Mapper sur la table racine `root_phys`.
+    pub fn new(root_phys: u64, access: A) -> Self {
+        Self {
+            root: root_phys,
+            access,
+            _format: PhantomData,
+        }
+    }
+
+    /// Adresse physique de la table racine.
+    #[inline]
+    pub fn root(&self) -> u64 {
+        self.root
+    }
+
+    fn check_virt(virt: u64) -> Result<(), MapError> {
+        if !F::is_canonical(virt) {
+            return Err(MapError::NonCanonical);
+        }
+        if virt & PAGE_MASK != 0 {
+            return Err(MapError::Misaligned);
+        }
+        Ok(())
+    }
+
+    /// Table de niveau 1 couvrant `virt`, créée au besoin si `create`.
+    fn leaf_table(&mut self, virt: u64, create: bool) -> Result<u64, MapError> {
+        let mut table = self.root;
+        let mut level = F::LEVELS;
+        while level > 1 {
+            let idx = F::index(virt, level);
+            // SAFETY: `table` est la racine ou une table obtenue d'une entrée
+            // intermédiaire valide de ce même arbre.
+            let entry = unsafe { (*self.access.table(table))[idx] };
+            table = if !F::is_valid(entry) {
+                if !create {
+                    return Err(MapError::NotMapped);
+                }
+                let fresh = self.access.alloc_table().ok_or(MapError::OutOfMemory)?;
+                // SAFETY: même table qu'au-dessus ; l'allocation n'invalide pas
+                // les tables existantes.
+                unsafe { (*self.access.table(table))[idx] = F::table_entry(fresh) };
+                fresh
+            } else if F::is_leaf(entry, level) {
+                return Err(MapError::HugePage);
+            } else {
+                F::entry_phys(entry)
+            };
+            level -= 1;
+        }
+        Ok(table)
+    }
+
+    /// Mappe la page `virt` → `phys` (4 KiB).
+    pub fn map(&mut self, virt: u64, phys: u64, flags: PageFlags) -> Result<(), MapError> {
+        Self::check_virt(virt)?;
+        if phys & PAGE_MASK != 0 {
+            return Err(MapError::Misaligned);
+        }
+        let table = self.leaf_table(virt, true)?;
+        // SAFETY: `table` est une table de niveau 1 de cet arbre.
+        let slot = unsafe { &mut (*self.access.table(table))[F::index(virt, 1)] };
+        if F::is_valid(*slot) {
+            return Err(MapError::AlreadyMapped);
+        }
+        *slot = F::leaf_entry(phys, flags);
+        Ok(())
+    }
+
+    /// Retire la page `virt` et retourne son adresse physique.
+    ///
+    /// Les tables intermédiaires ne sont pas libérées ; l'invalidation TLB
+    /// reste à la charge de l'appelant (`ArchMmu::flush_page`).
+    pub fn unmap(&mut self, virt: u64) -> Result<u64, MapError> {
+        Self::check_virt(virt)?;
+        let table = self.leaf_table(virt, false)?;
+        // SAFETY: `table` est une table de niveau 1 de cet arbre.
+        let slot = unsafe { &mut (*self.access.table(table))[F::index(virt, 1)] };
+        if !F::is_valid(*slot) {
+            return Err(MapError::NotMapped);
+        }
+        let phys = F::entry_phys(*slot);
+        *slot = 0;
+        Ok(phys)
+    }
+
+    /// Traduit `virt` (offset compris) en `(phys, flags)` de la feuille.
+    pub fn translate(&mut self, virt: u64) -> Option<(u64, PageFlags)> {
+        if !F::is_canonical(virt) {
+            return None;
+        }
+        let table = self.leaf_table(virt & !PAGE_MASK, false).ok()?;
+        // SAFETY: `table` est une table de niveau 1 de cet arbre.
+        let entry = unsafe { (*self.access.table(table))[F::index(virt, 1)] };
+        F::is_valid(entry).then(|| {
+            (
+                F::entry_phys(entry) | (virt & PAGE_MASK),
+                F::entry_flags(entry),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::boxed::Box;
+    use std::vec::Vec;
+
+    /// Arène de tables : la table `i` vit à l'adresse physique `(i + 1) * 4 KiB`.
+    #[derive(Default)]
+    pub(crate) struct Arena {
+        tables: Vec<Box<RawTable>>,
+    }
+
+    impl Arena {
+        pub(crate) fn with_root() -> (Self, u64) {
+            let mut arena = Self::default();
+            let root = arena.alloc_table().unwrap();
+            (arena, root)
+        }
+    }
+
+    impl TableAccess for Arena {
+        unsafe fn table(&mut self, phys: u64) -> *mut RawTable {
+            let idx = (phys >> PAGE_SHIFT) as usize - 1;
+            &mut *self.tables[idx] as *mut RawTable
+        }
+
+        fn alloc_table(&mut self) -> Option<u64> {
+            self.tables.push(Box::new([0; ENTRIES_PER_TABLE]));
+            Some((self.tables.len() as u64) << PAGE_SHIFT)
+        }
+    }
+
+    /// Scénario commun à tous les formats : map, traduction, doublon, unmap.
+    pub(crate) fn exercise_format<F: PageTableFormat>(virt: u64) {
+        let (arena, root) = Arena::with_root();
+        let mut mapper = GenericMapper::<F, _>::new(root, arena);
+        let phys = 0x4_2000;
+
+        assert_eq!(mapper.map(virt, phys, PageFlags::KERNEL_DATA), Ok(()));
+        let (got, flags) = mapper.translate(virt + 0x123).unwrap();
+        assert_eq!(got, phys + 0x123);
+        assert!(flags.is_present() && flags.is_writable());
+        assert!(!flags.is_executable() && !flags.is_user());
+        assert_eq!(
+            mapper.map(virt, phys, PageFlags::KERNEL_DATA),
+            Err(MapError::AlreadyMapped)
+        );
+
+        let user = virt + 0x1000;
+        mapper.map(user, 0x9000, PageFlags::USER_CODE).unwrap();
+        let (_, flags) = mapper.translate(user).unwrap();
+        assert!(flags.is_user() && flags.is_executable() && !flags.is_writable());
+
+        assert_eq!(mapper.unmap(virt), Ok(phys));
+        assert!(mapper.translate(virt).is_none());
+        assert_eq!(mapper.unmap(virt), Err(MapError::NotMapped));
+        assert_eq!(
+            mapper.map(virt + 1, phys, PageFlags::KERNEL_DATA),
+            Err(MapError::Misaligned)
+        );
+        assert!(!F::is_canonical(1u64 << (F::VA_BITS - 1)));
+    }
+
+    #[test]
+    fn test_x86_64_format_roundtrip() {
+        exercise_format::<super::super::x86_64::X86_64Format>(0xFFFF_8000_0020_0000);
+        exercise_format::<super::super::x86_64::X86_64Format>(0x0000_7FFF_F000_0000);
+    }
+}
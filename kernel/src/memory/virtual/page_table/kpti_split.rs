@@ -281,6 +281,23 @@ pub unsafe fn build_user_shadow_pml4(
     map_transition_page(source_pml4_phys, user_pml4, tss_ptr, true, false)?;
     map_current_entry_stacks(source_pml4_phys, user_pml4, cpu_id, entry_stack_top)?;
 
+    // Entrée SYSENTER i386 (+ sa pile de transition) et retour initial vers
+    // une image i386 : exécutés avant/après le switch CR3 kernel.
+    #[cfg(feature = "ia32_compat")]
+    {
+        use crate::arch::x86_64::ia32;
+        for stub in ia32::kpti_transition_stubs() {
+            map_transition_page(source_pml4_phys, user_pml4, stub, false, true)?;
+        }
+        map_transition_page(
+            source_pml4_phys,
+            user_pml4,
+            ia32::sysenter_stack_top(cpu_id) - 1,
+            true,
+            false,
+        )?;
+    }
+
     if trampoline_phys.as_u64() != 0 {
         map_phys_page(
             user_pml4,
@@ -1,18 +1,25 @@
 // kernel/src/memory/virtual/page_table/mod.rs
 //
-// Module page_table — tables de pages x86_64.
+// Module page_table — tables de pages x86_64, formats des autres architectures
+// et mapper générique (`format`).
 // Couche 0 — aucune dépendance externe sauf `spin`.
 
+pub mod aarch64;
 pub mod builder;
+pub mod format;
 pub mod kpti_split;
+pub mod riscv64;
 pub mod walker;
 pub mod x86_64;
 
 pub use x86_64::{
     invlpg, phys_to_table, phys_to_table_mut, phys_to_table_ref, read_cr3, write_cr3, PageTable,
-    PageTableEntry, PageTableLevel,
+    PageTableEntry, PageTableLevel, X86_64Format,
 };
 
+pub use aarch64::Aarch64Format;
 pub use builder::PageTableBuilder;
+pub use format::{GenericMapper, MapError, PageTableFormat, TableAccess};
 pub use kpti_split::{should_enable_kpti, KptiState, KptiTable, KPTI};
+pub use riscv64::Sv39Format;
 pub use walker::{FrameAllocatorForWalk, PageTableWalker, WalkResult};
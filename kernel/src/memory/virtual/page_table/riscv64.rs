@@ -0,0 +1,143 @@
+// kernel/src/memory/virtual/page_table/riscv64.rs
+//
+// Entrées Sv39 (RISC-V privileged spec 1.12) : 3 niveaux, VA 39 bits, pages
+// 4 KiB, mégapages 2 MiB et gigapages 1 GiB. Encodage pur (testable sur
+// l'hôte) ; satp et sfence.vma sont dans `arch/riscv64/mmu.rs`.
+// Couche 0 — aucune dépendance externe.
+//
+// PTE :
+//   [0] V   [1] R   [2] W   [3] X   [4] U   [5] G   [6] A   [7] D
+//   [9:8]   RSW (logiciel : CoW, pinned)
+//   [53:10] PPN
+//
+// Une entrée valide sans R/W/X pointe vers la table suivante ; sinon c'est
+// une feuille, quel que soit le niveau. Sans Svpbmt, la cacheabilité vient
+// des PMA de la plateforme : NO_CACHE/WRITE_COMBINING ne sont pas encodés.
+
+use super::format::PageTableFormat;
+use crate::memory::core::PageFlags;
+
+const PTE_V: u64 = 1 << 0;
+const PTE_R: u64 = 1 << 1;
+const PTE_W: u64 = 1 << 2;
+const PTE_X: u64 = 1 << 3;
+const PTE_U: u64 = 1 << 4;
+const PTE_G: u64 = 1 << 5;
+const PTE_A: u64 = 1 << 6;
+const PTE_D: u64 = 1 << 7;
+const PTE_SW_COW: u64 = 1 << 8;
+const PTE_SW_PINNED: u64 = 1 << 9;
+const PTE_PPN_SHIFT: u32 = 10;
+const PTE_PPN_MASK: u64 = (1 << 44) - 1;
+
+/// Champ MODE de satp pour Sv39.
+pub const SATP_MODE_SV39: u64 = 8 << 60;
+
+/// Format Sv39 pour `GenericMapper`.
+pub struct Sv39Format;
+
+impl PageTableFormat for Sv39Format {
+    const NAME: &'static str = "riscv64-sv39";
+    const LEVELS: usize = 3;
+    const VA_BITS: u32 = 39;
+
+    #[inline]
+    fn is_valid(entry: u64) -> bool {
+        entry & PTE_V != 0
+    }
+
+    #[inline]
+    fn is_leaf(entry: u64, _level: usize) -> bool {
+        Self::is_valid(entry) && entry & (PTE_R | PTE_W | PTE_X) != 0
+    }
+
+    #[inline]
+    fn table_entry(table_phys: u64) -> u64 {
+        ((table_phys >> 12) << PTE_PPN_SHIFT) | PTE_V
+    }
+
+    /// A et D sont posés d'office : sans Svadu, un accès à une page A=0 (ou
+    /// une écriture D=0) fauterait au lieu de mettre le bit à jour.
+    fn leaf_entry(phys: u64, flags: PageFlags) -> u64 {
+        let mut pte = ((phys >> 12) << PTE_PPN_SHIFT) | PTE_V | PTE_R | PTE_A;
+        if flags.is_writable() {
+            pte |= PTE_W | PTE_D;
+        }
+        if flags.is_executable() {
+            pte |= PTE_X;
+        }
+        if flags.is_user() {
+            pte |= PTE_U;
+        }
+        if flags.contains(PageFlags::GLOBAL) {
+            pte |= PTE_G;
+        }
+        if flags.is_cow() {
+            pte |= PTE_SW_COW;
+        }
+        if flags.is_pinned() {
+            pte |= PTE_SW_PINNED;
+        }
+        pte
+    }
+
+    #[inline]
+    fn entry_phys(entry: u64) -> u64 {
+        ((entry >> PTE_PPN_SHIFT) & PTE_PPN_MASK) << 12
+    }
+
+    fn entry_flags(entry: u64) -> PageFlags {
+        if !Self::is_valid(entry) {
+            return PageFlags::EMPTY;
+        }
+        let mut flags = PageFlags::PRESENT;
+        if entry & PTE_W != 0 {
+            flags = flags.set(PageFlags::WRITABLE);
+        }
+        if entry & PTE_X == 0 {
+            flags = flags.set(PageFlags::NO_EXECUTE);
+        }
+        if entry & PTE_U != 0 {
+            flags = flags.set(PageFlags::USER);
+        }
+        if entry & PTE_G != 0 {
+            flags = flags.set(PageFlags::GLOBAL);
+        }
+        if entry & PTE_A != 0 {
+            flags = flags.set(PageFlags::ACCESSED);
+        }
+        if entry & PTE_D != 0 {
+            flags = flags.set(PageFlags::DIRTY);
+        }
+        if entry & PTE_SW_COW != 0 {
+            flags = flags.set(PageFlags::COW);
+        }
+        if entry & PTE_SW_PINNED != 0 {
+            flags = flags.set(PageFlags::PINNED);
+        }
+        flags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::virt::page_table::format::tests::exercise_format;
+
+    #[test]
+    fn test_sv39_format_roundtrip() {
+        exercise_format::<Sv39Format>(0xFFFF_FFC0_0020_0000);
+        exercise_format::<Sv39Format>(0x0000_0000_8020_0000);
+    }
+
+    #[test]
+    fn test_sv39_table_vs_leaf() {
+        let table = Sv39Format::table_entry(0x8040_0000);
+        assert!(Sv39Format::is_valid(table) && !Sv39Format::is_leaf(table, 2));
+        assert_eq!(Sv39Format::entry_phys(table), 0x8040_0000);
+        let giga = Sv39Format::leaf_entry(0x8000_0000, PageFlags::KERNEL_CODE);
+        assert!(Sv39Format::is_leaf(giga, 3));
+        assert_eq!(giga & (PTE_W | PTE_U), 0);
+        assert!(!Sv39Format::is_canonical(0x0000_0040_0000_0000));
+    }
+}
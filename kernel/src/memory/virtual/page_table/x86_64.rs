@@ -428,3 +428,51 @@ pub unsafe fn invlpg(addr: VirtAddr) {
         options(nostack, preserves_flags),
     );
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// FORMAT GÉNÉRIQUE (format.rs)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Format x86_64 4 niveaux pour `GenericMapper` (réutilise l'encodage PTE).
+pub struct X86_64Format;
+
+impl super::format::PageTableFormat for X86_64Format {
+    const NAME: &'static str = "x86_64-4level";
+    const LEVELS: usize = 4;
+    const VA_BITS: u32 = 48;
+
+    #[inline]
+    fn is_valid(entry: u64) -> bool {
+        PageTableEntry(entry).is_present()
+    }
+
+    #[inline]
+    fn is_leaf(entry: u64, level: usize) -> bool {
+        let entry = PageTableEntry(entry);
+        entry.is_present() && (level == 1 || entry.is_huge())
+    }
+
+    #[inline]
+    fn table_entry(table_phys: u64) -> u64 {
+        (table_phys & PageTableEntry::PHYS_MASK)
+            | PageTableEntry::FLAG_PRESENT
+            | PageTableEntry::FLAG_WRITABLE
+            | PageTableEntry::FLAG_USER
+    }
+
+    #[inline]
+    fn leaf_entry(phys: u64, flags: PageFlags) -> u64 {
+        let flags = flags.set(PageFlags::PRESENT);
+        PageTableEntry::from_page_flags(Frame::containing(PhysAddr::new(phys)), flags).raw()
+    }
+
+    #[inline]
+    fn entry_phys(entry: u64) -> u64 {
+        PageTableEntry(entry).phys_addr().as_u64()
+    }
+
+    #[inline]
+    fn entry_flags(entry: u64) -> PageFlags {
+        PageTableEntry(entry).to_page_flags()
+    }
+}
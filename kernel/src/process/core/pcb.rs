@@ -96,6 +96,10 @@ pub mod process_flags {
     pub const VFORK_DONE: u32 = 1 << 10;
     /// Enfant vfork() partageant temporairement l'adresse parent jusqu'à exec/exit.
     pub const VFORK_SHARED_AS: u32 = 1 << 11;
+    /// Gelé par le freezer : ses threads se garent au retour vers userspace.
+    pub const FROZEN: u32 = 1 << 12;
+    /// Image i386 chargée (compat 32-bit, feature `ia32_compat`).
+    pub const IA32: u32 = 1 << 13;
 }
 
 pub use process_flags as ProcessFlags;
@@ -454,6 +458,8 @@ pub struct ProcessControlBlock {
     pub exit_code: AtomicU32,
     /// Umask POSIX par processus.
     pub umask: AtomicU32,
+    /// Priorité d'I/O encodée façon `ioprio_set` (0 = non définie).
+    pub io_priority: AtomicU32,
 
     // ── Threads ───────────────────────────────────────────────────────────────
     /// Nombre de threads actifs dans ce processus.
@@ -574,6 +580,7 @@ impl ProcessControlBlock {
             flags: AtomicU32::new(0),
             exit_code: AtomicU32::new(0),
             umask: AtomicU32::new(DEFAULT_UMASK),
+            io_priority: AtomicU32::new(0),
             thread_count: AtomicU32::new(1),
             main_thread,
             thread_slots: [const { AtomicPtr::new(core::ptr::null_mut()) };
@@ -726,6 +733,18 @@ impl ProcessControlBlock {
         self.umask.swap(mask & 0o777, Ordering::AcqRel)
     }
 
+    /// Priorité d'I/O brute (`classe << 13 | niveau`), lue par l'ordonnanceur
+    /// d'I/O bloc.
+    #[inline(always)]
+    pub fn io_priority(&self) -> u32 {
+        self.io_priority.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    pub fn set_io_priority(&self, raw: u32) {
+        self.io_priority.store(raw, Ordering::Relaxed);
+    }
+
     /// Incrémente le compteur de threads actifs.
     #[inline(always)]
     pub fn inc_threads(&self) -> u32 {
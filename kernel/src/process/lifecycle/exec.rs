@@ -82,6 +82,8 @@ pub struct ElfLoadResult {
     pub addr_space_ptr: usize,
     /// Adresse virtuelle du SignalTcb mappé (0 si absent) — pour PROC-VMA/V-17.
     pub signal_tcb_vaddr: u64,
+    /// Image i386 (ELFCLASS32/EM_386) — retour userspace en CS32.
+    pub compat32: bool,
 }
 
 /// Erreurs renvoyées par ElfLoader.
@@ -357,6 +359,7 @@ pub fn do_execve(
     thread.sched_tcb.set_kpti_user_cr3(exec_kpti_user_cr3);
     thread.sched_tcb.fs_base = elf_result.tls_base;
     thread.sched_tcb.user_gs_base = 0;
+    thread.sched_tcb.set_tls32_desc(0);
 
     // Mettre à jour les adresses du thread.
     // CORRECTION P2-03 : calculer et propager stack_base et stack_size au lieu de 0
@@ -439,6 +442,12 @@ pub fn do_execve(
         !(process_flags::FORKED | process_flags::VFORK_SHARED_AS),
         Ordering::Release,
     );
+    // Image i386 : le retour userspace (syscall, fork, exec) passe en CS32.
+    if elf_result.compat32 {
+        pcb.flags.fetch_or(process_flags::IA32, Ordering::Release);
+    } else {
+        pcb.flags.fetch_and(!process_flags::IA32, Ordering::Release);
+    }
 
     if old_as_ptr != 0 && old_as_ptr != elf_result.addr_space_ptr && !old_as_is_vfork_shared {
         crate::memory::virt::address_space::fork_impl::KERNEL_AS_CLONER.free_addr_space(old_as_ptr);
@@ -58,6 +58,8 @@ fn mark_exit(
     }
     close_all_pid_vfs(pcb.pid.0);
     drivers::driver_do_exit(pcb.pid.0);
+    #[cfg(feature = "kvm")]
+    crate::arch::x86_64::kvm::release_for_pid(pcb.pid.0);
 
     thread.join_result.store(join_result, Ordering::Release);
     thread.join_done.store(true, Ordering::Release);
@@ -69,6 +71,9 @@ fn mark_exit(
 
     let remaining_threads = pcb.dec_threads();
     if remaining_threads == 0 {
+        // Régions exo_shm du processus : orphelines, libérées dès que plus
+        // personne ne les mappe.
+        crate::ipc::shared_memory::user_region::release_for_pid(pcb.pid.0);
         // FIX-APP-08 (Security_Application_Audit §GAP-08) : tracer la terminaison
         // du processus dans ExoLedger. process spawn/exit n'étaient pas audités.
         // ActionTag::Custom { tag = 0x4558_4954 "EXIT", data = pid|status<<32 }.
@@ -17,7 +17,7 @@
 // ═══════════════════════════════════════════════════════════════════════════════
 
 #[cfg(target_arch = "x86_64")]
-use crate::arch::x86_64::gdt::{GDT_USER_CS64, GDT_USER_DS};
+use crate::arch::x86_64::gdt::{GDT_USER_CS32, GDT_USER_CS64, GDT_USER_DS};
 use crate::process::core::pcb::{process_flags, ProcessControlBlock, ProcessState};
 use crate::process::core::pid::{Pid, Tid, PID_ALLOCATOR, TID_ALLOCATOR};
 use crate::process::core::registry::PROCESS_REGISTRY;
@@ -410,6 +410,14 @@ pub fn do_fork(ctx: &ForkContext<'_>) -> Result<ForkResult, ForkError> {
     let child_thread_ptr = Box::into_raw(child_thread);
     fork_trace(b"fork: thread\n");
 
+    // Un parent i386 (ia32_compat) a un fils i386 : CS32 au retour de fork.
+    let parent_ia32 = parent_pcb.flags.load(Ordering::Acquire) & process_flags::IA32;
+    let child_cs = if parent_ia32 != 0 {
+        GDT_USER_CS32 as u64
+    } else {
+        GDT_USER_CS64 as u64
+    };
+
     // Configurer le point de retour du fils (RIP + RSP au retour de fork).
     // SAFETY: child_thread_ptr valide.
     unsafe {
@@ -452,7 +460,7 @@ pub fn do_fork(ctx: &ForkContext<'_>) -> Result<ForkResult, ForkError> {
         *frame_ptr.add(12) = ctx.user_r9;
         // iretq frame (RSP pointe ici à l'entrée fork_child_trampoline).
         *frame_ptr.add(13) = ctx.child_rip; // RIP  userspace
-        *frame_ptr.add(14) = GDT_USER_CS64 as u64; // CS   ring3 (code64)
+        *frame_ptr.add(14) = child_cs; // CS   ring3 (code64, ou code32 si i386)
 
         // CORRECTION P2-02 : propager les RFLAGS du parent avec masquage sécurisé.
         // Masque des flags sûrs à hériter (POSIX + sécurité kernel).
@@ -491,6 +499,9 @@ pub fn do_fork(ctx: &ForkContext<'_>) -> Result<ForkResult, ForkError> {
         let child = &mut *child_thread_ptr;
         child.sched_tcb.fs_base = parent.sched_tcb.fs_base;
         child.sched_tcb.user_gs_base = parent.sched_tcb.user_gs_base;
+        child
+            .sched_tcb
+            .set_tls32_desc(parent.sched_tcb.tls32_desc());
         let tls_base = parent.tls_gs_base.load(Ordering::Relaxed);
         child.addresses.tls_base = tls_base;
         child.tls_gs_base.store(tls_base, Ordering::Release);
@@ -575,6 +586,7 @@ pub fn do_fork(ctx: &ForkContext<'_>) -> Result<ForkResult, ForkError> {
     child_pcb.user_ns.clone_from(&parent_pcb.user_ns);
     child_pcb.set_pgroup_id(parent_pcb.pgroup_id());
     child_pcb.set_session_id(parent_pcb.session_id());
+    child_pcb.set_io_priority(parent_pcb.io_priority());
 
     // FIX-SEC-T0.2 : l'enfant HÉRITE la table de capabilities du parent (les caps =
     // objets ExoFS ouverts + droits délégués). Sans ça, un fork perdrait tous les
@@ -601,10 +613,11 @@ pub fn do_fork(ctx: &ForkContext<'_>) -> Result<ForkResult, ForkError> {
     // par fork (RÈGLE ZT-03 / SAND-03). No-op si le parent n'est pas restreint.
     crate::security::zero_trust::inherit_restrictions(parent_pcb.pid.0, child_pid.0);
 
-    // Marquer FORKED.
-    child_pcb
-        .flags
-        .fetch_or(child_process_flags(ctx.flags), Ordering::Release);
+    // Marquer FORKED (+ IA32 hérité du parent).
+    child_pcb.flags.fetch_or(
+        child_process_flags(ctx.flags) | parent_ia32,
+        Ordering::Release,
+    );
     child_pcb.set_state(ProcessState::Running);
     fork_trace(b"fork: pcb state\n");
 
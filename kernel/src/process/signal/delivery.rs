@@ -93,6 +93,8 @@ pub fn send_signal_number_to_pid(pid: Pid, sig_n: u8) -> Result<(), SendError> {
 
     // Notifier le scheduler : signal_pending = true (PROC-04 via raise_signal_pending).
     thread.raise_signal_pending();
+    // Processus gelé : réveiller ses threads pour la livraison.
+    crate::process::state::freezer::notify_signal(pcb);
     Ok(())
 }
 
@@ -154,6 +156,7 @@ pub struct SyscallFrame {
 /// 2. Lire SigAction dans la table PCB.
 /// 3. Dispatcher selon kind : User | Ignore | Term | Core | Stop | Cont.
 /// 4. SA_RESETHAND : réinitialiser handler après délivrance.
+/// 5. Processus gelé : garer le thread (voir `process::state::freezer`).
 pub fn handle_pending_signals(
     thread: &mut crate::process::core::tcb::ProcessThread,
     frame: &mut SyscallFrame,
@@ -215,6 +218,12 @@ pub fn handle_pending_signals(
     if remaining_std == 0 && remaining_rt == 0 {
         clear_signal_pending(thread);
     }
+
+    // Freezer : le thread se gare ici tant que son processus est gelé ; un
+    // signal arrivé entre-temps est livré avant de se garer à nouveau.
+    if crate::process::state::freezer::park_while_frozen(&thread.sched_tcb, pcb) {
+        handle_pending_signals(thread, frame);
+    }
 }
 
 #[inline]
@@ -0,0 +1,164 @@
+// kernel/src/process/state/freezer.rs
+//
+// ═══════════════════════════════════════════════════════════════════════════════
+// Freezer — gel des applications d'arrière-plan (façon cgroup freezer)
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Principe :
+//   • `freeze(pid)` pose `process_flags::FROZEN` puis lève `signal_pending` sur
+//     chaque thread : au prochain retour vers userspace (RÈGLE SIGNAL-01),
+//     `handle_pending_signals` termine la livraison puis gare le thread dans
+//     FREEZER_WAIT_QUEUE. Contrairement à SIGSTOP, rien n'est visible du
+//     processus ni de son parent : pas d'état Stopped, pas de SIGCHLD.
+//   • `thaw(pid)` retire le drapeau, relève `signal_pending` (ferme la fenêtre
+//     de réveil manqué de `wait_interruptible`) et réveille la file.
+//   • Un signal envoyé à un processus gelé réveille ses threads : il est livré
+//     (SIGKILL termine le processus), puis le thread se gare de nouveau avant
+//     de rendre la main à userspace. Un handler utilisateur ne s'exécute donc
+//     qu'au dégel.
+//   • Un thread bloqué dans un syscall ne se gare qu'au retour de celui-ci ;
+//     il ne consomme de toute façon pas de CPU d'ici là.
+//
+// La politique (quelles applications geler, exemptions) vit en userspace.
+
+use core::sync::atomic::Ordering;
+
+use crate::process::core::pcb::{process_flags, Credentials, ProcessControlBlock, ProcessState};
+use crate::process::core::pid::Pid;
+use crate::process::core::registry::PROCESS_REGISTRY;
+use crate::scheduler::core::task::ThreadControlBlock;
+use crate::scheduler::sync::wait_queue::WaitQueue;
+
+/// Threads garés par le freezer, tous processus confondus.
+static FREEZER_WAIT_QUEUE: WaitQueue = WaitQueue::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FreezeError {
+    /// PID cible inexistant, zombie ou en cours de sortie.
+    NoSuchProcess,
+    /// L'appelant n'est ni root ni propriétaire de la cible.
+    PermissionDenied,
+    /// Cible non gelable (init, l'appelant lui-même).
+    InvalidTarget,
+}
+
+/// Règle de `kill(2)` : root, ou même UID réel/effectif.
+fn may_freeze(caller: &Credentials, target: &Credentials) -> bool {
+    caller.is_root()
+        || caller.uid == target.uid
+        || caller.euid == target.uid
+        || caller.uid == target.suid
+        || caller.euid == target.suid
+}
+
+fn lookup(caller_pid: Pid, target_pid: Pid) -> Result<&'static ProcessControlBlock, FreezeError> {
+    if target_pid.0 <= 1 || target_pid == caller_pid {
+        return Err(FreezeError::InvalidTarget);
+    }
+    let target = PROCESS_REGISTRY
+        .find_by_pid(target_pid)
+        .ok_or(FreezeError::NoSuchProcess)?;
+    let state = target.state();
+    if state == ProcessState::Zombie || state == ProcessState::Dead || target.is_exiting() {
+        return Err(FreezeError::NoSuchProcess);
+    }
+    // PID 0 : appel interne du noyau.
+    if caller_pid.0 != 0 {
+        let caller = PROCESS_REGISTRY
+            .find_by_pid(caller_pid)
+            .ok_or(FreezeError::PermissionDenied)?;
+        if !may_freeze(&caller.get_creds(), &target.get_creds()) {
+            return Err(FreezeError::PermissionDenied);
+        }
+    }
+    Ok(target)
+}
+
+/// Lève `signal_pending` sur chaque thread du processus.
+fn kick_threads(pcb: &ProcessControlBlock) {
+    pcb.for_each_thread_ptr(|ptr| {
+        // SAFETY: les slots du PCB ne contiennent que des threads vivants,
+        // retirés par unregister_thread_ptr() avant libération.
+        unsafe { (*ptr).raise_signal_pending() };
+    });
+}
+
+#[inline]
+pub fn is_frozen(pcb: &ProcessControlBlock) -> bool {
+    pcb.flags.load(Ordering::Acquire) & process_flags::FROZEN != 0
+}
+
+/// Gèle `target_pid`. Retourne `false` s'il l'était déjà.
+pub fn freeze(caller_pid: Pid, target_pid: Pid) -> Result<bool, FreezeError> {
+    let pcb = lookup(caller_pid, target_pid)?;
+    let old = pcb.flags.fetch_or(process_flags::FROZEN, Ordering::AcqRel);
+    if old & process_flags::FROZEN != 0 {
+        return Ok(false);
+    }
+    kick_threads(pcb);
+    Ok(true)
+}
+
+/// Dégèle `target_pid`. Retourne `false` s'il n'était pas gelé.
+pub fn thaw(caller_pid: Pid, target_pid: Pid) -> Result<bool, FreezeError> {
+    let pcb = lookup(caller_pid, target_pid)?;
+    let old = pcb
+        .flags
+        .fetch_and(!process_flags::FROZEN, Ordering::AcqRel);
+    if old & process_flags::FROZEN == 0 {
+        return Ok(false);
+    }
+    kick_threads(pcb);
+    FREEZER_WAIT_QUEUE.notify_all();
+    Ok(true)
+}
+
+/// État de gel de `target_pid`, mêmes droits que `freeze`.
+pub fn frozen_state(caller_pid: Pid, target_pid: Pid) -> Result<bool, FreezeError> {
+    lookup(caller_pid, target_pid).map(is_frozen)
+}
+
+/// Un signal vient d'être mis en file pour `pcb` : s'il est gelé, ses
+/// threads doivent se réveiller pour le livrer.
+pub fn notify_signal(pcb: &ProcessControlBlock) {
+    if is_frozen(pcb) {
+        FREEZER_WAIT_QUEUE.notify_all();
+    }
+}
+
+/// Gare le thread courant tant que son processus est gelé.
+///
+/// Appelé par `handle_pending_signals` après la livraison. Retourne `true`
+/// si un signal est arrivé pendant le gel : l'appelant le livre puis revient
+/// ici avant tout retour vers userspace.
+pub(crate) fn park_while_frozen(tcb: &ThreadControlBlock, pcb: &ProcessControlBlock) -> bool {
+    let tcb_ptr = tcb as *const ThreadControlBlock as *mut ThreadControlBlock;
+    while is_frozen(pcb) && !pcb.is_exiting() {
+        // SAFETY: `tcb` est le TCB du thread courant, au retour vers userspace.
+        let woken = unsafe { FREEZER_WAIT_QUEUE.wait_interruptible(tcb_ptr) };
+        if !woken && tcb.has_signal_pending() {
+            return is_frozen(pcb);
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_may_freeze_anyone() {
+        let root = Credentials::ROOT;
+        assert!(may_freeze(&root, &Credentials::new(1000, 1000)));
+    }
+
+    #[test]
+    fn users_only_freeze_their_own_processes() {
+        let alice = Credentials::new(1000, 1000);
+        let bob = Credentials::new(1001, 1001);
+        assert!(may_freeze(&alice, &Credentials::new(1000, 100)));
+        assert!(!may_freeze(&alice, &bob));
+        assert!(!may_freeze(&alice, &Credentials::ROOT));
+    }
+}
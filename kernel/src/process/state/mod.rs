@@ -1,9 +1,11 @@
 // kernel/src/process/state/mod.rs
 //
-// Machine à états du processus et pont DMA Wakeup.
+// Machine à états du processus, freezer et pont DMA Wakeup.
 
+pub mod freezer;
 pub mod transitions;
 pub mod wakeup;
 
+pub use freezer::FreezeError;
 pub use transitions::{transition, StateTransition, TransitionError};
 pub use wakeup::{register_with_dma, PROCESS_WAKEUP_HANDLER};
@@ -208,6 +208,7 @@ fork_child_trampoline:
     popq    %r10
     popq    %r8
     popq    %r9
+    movq    %r10, %rcx      // rcx indéfini en ABI 64-bit ; porte ecx pour un fils i386
     xor     %eax, %eax      // rax = 0 : le fils retourne 0 de fork()
     clts                    // autoriser FPU/SIMD dès le premier retour Ring3
     movq    %gs:0x48, %rax  // KPTI user CR3 publié par switch.rs (0 si inactif)
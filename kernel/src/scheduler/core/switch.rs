@@ -393,6 +393,7 @@ pub unsafe fn context_switch(prev: &mut ThreadControlBlock, next: &mut ThreadCon
     // Comptabiliser le temps réellement passé en Running par `prev`.
     let now_tsc = tsc::read_tsc();
     let cpu_idx = percpu::current_cpu_id() as usize;
+    crate::trace::sched_switch(cpu_idx, prev.pid.0, prev.tid, next.pid.0, next.tid);
     if cpu_idx < MAX_CPUS {
         // SAFETY: cpu_idx < MAX_CPUS borne l'accès au tableau per-CPU statique ;
         // chaque cœur n'écrit que son propre slot (pas de partage mutable), donc
@@ -486,6 +487,17 @@ pub unsafe fn context_switch(prev: &mut ThreadControlBlock, next: &mut ThreadCon
         msr::write_msr(MSR_KERNEL_GS_BASE, next.user_gs_base);
     }
 
+    // TLS i386 : recharger le descripteur GDT du thread entrant (0 = vide),
+    // et DS/ES user (un VM-exit exo-kvm les remet à zéro).
+    #[cfg(feature = "ia32_compat")]
+    {
+        // SAFETY: cpu_idx est le CPU courant, préemption désactivée.
+        unsafe {
+            crate::arch::x86_64::gdt::load_tls32_descriptor(cpu_idx, next.tls32_desc());
+        }
+        crate::arch::x86_64::ia32::load_user_data_segments();
+    }
+
     // Publier seulement après FS puis user-GS, conformément à ContextSwitch.tla.
     core::sync::atomic::fence(Ordering::SeqCst);
     percpu::set_current_tcb(next as *mut ThreadControlBlock);
@@ -263,7 +263,7 @@ pub mod task_flags {
 //       [200] affinity_hi[0]     : u64   (CPUs 64..127)
 //       [208] affinity_hi[1]     : u64   (CPUs 128..191)
 //       [216] affinity_hi[2]     : u64   (CPUs 192..255)
-//       [224..232] réservé
+//       [224] tls32_desc         : u64   (descripteur GDT TLS i386)
 //   [232] fpu_state_ptr: u64       ← ExoPhoenix OFFSET HARDCODÉ
 //   [240] rq_next:       u64       intrusive RunQueue
 //   [248] rq_prev:       u64       intrusive RunQueue
@@ -402,6 +402,10 @@ const _: () = assert!(
     offset_of!(ThreadControlBlock, _cold_reserve) + 72 == 216,
     "TCB scheduler: affinity_hi[2] doit être à l'offset absolu 216"
 );
+const _: () = assert!(
+    offset_of!(ThreadControlBlock, _cold_reserve) + 80 + 8 <= 232,
+    "TCB ia32: tls32_desc doit tenir dans _cold_reserve avant fpu_state_ptr (232)"
+);
 const _: () = assert!(
     offset_of!(ThreadControlBlock, _cold_reserve) + 88 == 232,
     "TCB ExoShield: _cold_reserve se termine à l'offset 232 (fpu_state_ptr)"
@@ -422,6 +426,7 @@ const _: () = assert!(
 impl ThreadControlBlock {
     const KSTACK_TOP_COLD_OFFSET: usize = 32;
     const KPTI_USER_CR3_COLD_OFFSET: usize = 40;
+    const TLS32_DESC_COLD_OFFSET: usize = 80;
 
     /// Crée un nouveau TCB utilisateur.
     pub fn new(
@@ -758,6 +763,31 @@ impl ThreadControlBlock {
         }
     }
 
+    /// Descripteur GDT brut du TLS i386 (`set_thread_area`), 0 si absent.
+    #[inline(always)]
+    pub fn tls32_desc(&self) -> u64 {
+        unsafe {
+            core::ptr::read_unaligned(
+                self._cold_reserve
+                    .as_ptr()
+                    .add(Self::TLS32_DESC_COLD_OFFSET) as *const u64,
+            )
+        }
+    }
+
+    /// Mémorise le descripteur TLS i386 rechargé à chaque context switch.
+    #[inline(always)]
+    pub fn set_tls32_desc(&mut self, raw: u64) {
+        unsafe {
+            core::ptr::write_unaligned(
+                self._cold_reserve
+                    .as_mut_ptr()
+                    .add(Self::TLS32_DESC_COLD_OFFSET) as *mut u64,
+                raw,
+            )
+        }
+    }
+
     /// TSC de création du thread, stocké dans `_cold_reserve[24..32]`.
     #[inline(always)]
     pub fn creation_tsc(&self) -> u64 {
@@ -90,7 +90,11 @@ pub fn release_rt_constraint(cpu: usize) {
 }
 
 /// Retourne le C-state maximum autorisé sur le CPU `cpu`.
+/// Le profil performance (mode jeu) maintient tous les CPUs en C0.
 pub fn max_allowed_cstate(cpu: usize) -> CState {
+    if super::power_profile::performance() {
+        return CState::C0;
+    }
     if cpu >= MAX_CPUS {
         return CState::C1;
     }
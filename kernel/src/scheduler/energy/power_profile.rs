@@ -1,6 +1,23 @@
 // kernel/src/scheduler/energy/power_profile.rs
 //
-// Profil de puissance — table .rodata de coût énergétique par P-state/C-state.
+// Profil de puissance — table .rodata de coût énergétique par P-state/C-state,
+// et profil choisi par l'espace utilisateur (sysctl `kernel.power.profile`).
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Profil équilibré : C-states profonds et HLT au repos.
+pub const PROFILE_BALANCED: u64 = 0;
+/// Profil performance (mode jeu) : CPUs maintenus en C0 et au P-state
+/// maximal, la boucle idle scrute au lieu de dormir.
+pub const PROFILE_PERFORMANCE: u64 = 1;
+
+/// Valeur du sysctl `kernel.power.profile`.
+pub static POWER_PROFILE_TUNABLE: AtomicU64 = AtomicU64::new(PROFILE_BALANCED);
+
+#[inline]
+pub fn performance() -> bool {
+    POWER_PROFILE_TUNABLE.load(Ordering::Relaxed) == PROFILE_PERFORMANCE
+}
 
 /// Coût énergétique normalisé par P-state (P0 = 1000 = référence).
 /// Valeurs indicatives ; calibrage réel via RAPL/ACPI.
@@ -40,6 +40,9 @@ const _: () = assert!(
     "CFS wakeup preemption threshold must fit inside the target period"
 );
 
+/// Seuil de wakeup preemption effectif (tunable `kernel.sched.cfs_wakeup_preempt_ns`).
+pub static CFS_WAKEUP_PREEMPT_TUNABLE: AtomicU64 = AtomicU64::new(CFS_WAKEUP_PREEMPT_NS);
+
 /// Compteur global de préemptions CFS (instrumentation).
 pub static CFS_PREEMPTIONS: AtomicU64 = AtomicU64::new(0);
 /// Compteur global de wakeup-preemptions (instrumentation).
@@ -92,7 +95,7 @@ pub fn should_preempt_on_wakeup(
     // FIX-VRUNTIME-01 : utiliser wrapping_add pour éviter le panic en debug
     // et le wrap silencieux en release. Sémantique correcte : préempter si le
     // thread réveillé a couru significativement MOINS que le thread courant.
-    let woken_vr_bumped = woken_vr.wrapping_add(CFS_WAKEUP_PREEMPT_NS);
+    let woken_vr_bumped = woken_vr.wrapping_add(CFS_WAKEUP_PREEMPT_TUNABLE.load(Ordering::Relaxed));
     if woken_vr_bumped < running_vr {
         CFS_WAKEUP_PREEMPT_COUNT.fetch_add(1, Ordering::Relaxed);
         return true;
@@ -12,11 +12,20 @@
 //  2. Si oui → cède immédiatement (reschedule).
 //  3. Sinon  → exécute `hlt` pour économiser l'énergie.
 //
+// Sous le profil performance (`kernel.power.profile = 1`, mode jeu), le CPU
+// reste en C0 au P-state maximal et scrute la run queue : pas de latence de
+// sortie de HLT au réveil d'un thread.
+//
 // La boucle idle est aussi le point de délégation vers energy::c_states
-// pour choisir le C-state optimal selon l'inactivité prévue.
+// pour choisir le C-state optimal selon l'inactivité prévue, et celui du
+// reseed continu du CSPRNG (security::crypto::entropy) avant chaque HLT.
 // ═══════════════════════════════════════════════════════════════════════════════
 
+use crate::scheduler::core::preempt::PreemptGuard;
 use crate::scheduler::core::task::{ThreadControlBlock, SCHED_IDLE_BIT};
+use crate::scheduler::energy::{frequency, power_profile};
+use crate::scheduler::timer::clock::monotonic_ns;
+use crate::security::crypto::entropy;
 use core::sync::atomic::{AtomicU64, Ordering};
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -29,6 +38,12 @@ pub static IDLE_ENTRIES: AtomicU64 = AtomicU64::new(0);
 pub static IDLE_HLT_COUNT: AtomicU64 = AtomicU64::new(0);
 /// Nombre de fois que la boucle idle a détecté du travail et cedé.
 pub static IDLE_WAKEUPS: AtomicU64 = AtomicU64::new(0);
+/// Itérations scrutées sans HLT sous le profil performance.
+pub static IDLE_POLL_COUNT: AtomicU64 = AtomicU64::new(0);
+
+extern "C" {
+    fn arch_current_cpu() -> u32;
+}
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Marquage de la tâche idle
@@ -63,7 +78,20 @@ pub unsafe fn idle_iteration(nr_running: usize) -> bool {
         return true;
     }
 
-    // Aucun thread prêt — exécuter HLT (attend la prochaine IRQ).
+    if power_profile::performance() {
+        IDLE_POLL_COUNT.fetch_add(1, Ordering::Relaxed);
+        let _preempt = PreemptGuard::new();
+        let cpu = arch_current_cpu() as usize;
+        if frequency::current_pstate(cpu) != 0 {
+            frequency::set_pstate(cpu, 0);
+        }
+        core::hint::spin_loop();
+        return false;
+    }
+
+    // Aucun thread prêt : récolte d'entropie (IRQ, gigue, reseed) puis HLT
+    // (attend la prochaine IRQ).
+    entropy::idle_work(monotonic_ns());
     IDLE_HLT_COUNT.fetch_add(1, Ordering::Relaxed);
     core::arch::asm!("hlt", options(nomem, nostack, preserves_flags));
 
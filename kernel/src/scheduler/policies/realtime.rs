@@ -27,6 +27,8 @@ use core::sync::atomic::{AtomicU64, Ordering};
 
 /// Quantum SCHED_RR (10ms en nanosecondes).
 pub const RR_TIMESLICE_NS: u64 = 10_000_000;
+/// Quantum SCHED_RR effectif (tunable `kernel.sched.rr_timeslice_ns`).
+pub static RR_TIMESLICE_TUNABLE: AtomicU64 = AtomicU64::new(RR_TIMESLICE_NS);
 /// Priorité POSIX RT la plus basse.
 pub const RT_PRIO_MIN: u8 = 1;
 /// Priorité POSIX RT la plus haute.
@@ -87,7 +89,7 @@ pub fn rr_tick(tcb: &ThreadControlBlock, elapsed_since_schedule_ns: u64) -> bool
     if tcb.policy != SchedPolicy::RoundRobin {
         return false;
     }
-    if elapsed_since_schedule_ns >= RR_TIMESLICE_NS {
+    if elapsed_since_schedule_ns >= RR_TIMESLICE_TUNABLE.load(Ordering::Relaxed) {
         RR_QUANTUM_EXPIRATIONS.fetch_add(1, Ordering::Relaxed);
         return true;
     }
@@ -96,5 +98,7 @@ pub fn rr_tick(tcb: &ThreadControlBlock, elapsed_since_schedule_ns: u64) -> bool
 
 /// Quantum restant pour un thread SCHED_RR (en nanosecondes).
 pub fn rr_remaining_slice(elapsed_since_schedule_ns: u64) -> u64 {
-    RR_TIMESLICE_NS.saturating_sub(elapsed_since_schedule_ns)
+    RR_TIMESLICE_TUNABLE
+        .load(Ordering::Relaxed)
+        .saturating_sub(elapsed_since_schedule_ns)
 }
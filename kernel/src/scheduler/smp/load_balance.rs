@@ -27,6 +27,8 @@ use core::sync::atomic::{AtomicU64, Ordering};
 
 /// Intervalle entre deux passes d'équilibrage par CPU (en ticks, à HZ=1000 → 4ms).
 pub const BALANCE_INTERVAL_TICKS: u64 = 4;
+/// Intervalle effectif (tunable `kernel.sched.balance_interval_ticks`, ≥ 1).
+pub static BALANCE_INTERVAL_TUNABLE: AtomicU64 = AtomicU64::new(BALANCE_INTERVAL_TICKS);
 /// Déséquilibre minimum pour déclencher une migration (différence de tâches).
 pub const IMBALANCE_THRESHOLD: usize = 2;
 /// Maximum de migrations par passe d'équilibrage.
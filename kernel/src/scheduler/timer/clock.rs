@@ -13,6 +13,7 @@
 // ## Ce que ce module fournit
 //   - `monotonic_ns()` / `monotonic_us()` — temps monotone, délèguent à ktime
 //   - `realtime_ns()` — temps UNIX, délègue à ktime
+//   - `boottime_ns()` — CLOCK_BOOTTIME : monotone + veille non comptée
 //   - `scheduler_now_ns()` — alias sémantique pour le tick handler
 //   - `rdtsc()` / `rdtscp()` — lectures brutes pour profiling perf uniquement
 //     (pas pour le temps kernel — RÈGLE ARCH-TIME-02)
@@ -27,6 +28,8 @@
 //   dérive TSC était corrigée dans ktime mais pas dans ce module.
 // ═════════════════════════════════════════════════════════════════════════════
 
+use core::sync::atomic::{AtomicU64, Ordering};
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Initialisation
 // ─────────────────────────────────────────────────────────────────────────────
@@ -160,3 +163,55 @@ pub unsafe fn set_realtime_from_epoch(epoch_ns: u64) {
 pub fn realtime_offset_ns() -> u64 {
     crate::arch::x86_64::time::ktime::ktime_rtoffset_ns()
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Veille — CLOCK_BOOTTIME et état de reprise
+// ─────────────────────────────────────────────────────────────────────────────
+//
+// ktime compte le temps de veille quand le TSC continue de tourner (s2idle,
+// VM suspendue par l'hôte) mais pas quand il repart de zéro (S3) : le
+// chemin de reprise de la plateforme rapporte alors la durée mesurée par
+// ailleurs (RTC) via `note_resume(slept_ns, false)`, qui s'ajoute à
+// CLOCK_BOOTTIME. CLOCK_MONOTONIC reste ktime.
+
+/// Veille non comptée par ktime, ajoutée à CLOCK_BOOTTIME.
+static SLEEP_OFFSET_NS: AtomicU64 = AtomicU64::new(0);
+/// Nombre de reprises depuis le boot.
+static RESUME_GENERATION: AtomicU64 = AtomicU64::new(0);
+/// Durée de la dernière veille.
+static LAST_SLEEP_NS: AtomicU64 = AtomicU64::new(0);
+/// Veille cumulée depuis le boot.
+static TOTAL_SLEEP_NS: AtomicU64 = AtomicU64::new(0);
+
+/// CLOCK_BOOTTIME : temps monotone, veille comprise.
+#[inline]
+pub fn boottime_ns() -> u64 {
+    monotonic_ns().saturating_add(SLEEP_OFFSET_NS.load(Ordering::Acquire))
+}
+
+/// Convertit une échéance CLOCK_BOOTTIME en échéance ktime.
+#[inline]
+pub fn boottime_to_monotonic_ns(boottime_ns: u64) -> u64 {
+    boottime_ns.saturating_sub(SLEEP_OFFSET_NS.load(Ordering::Acquire))
+}
+
+/// Appelé par le chemin de reprise de la plateforme après `slept_ns` de
+/// veille ; `counted_by_ktime` : le TSC a continué de tourner pendant la
+/// veille.
+pub fn note_resume(slept_ns: u64, counted_by_ktime: bool) {
+    if !counted_by_ktime {
+        SLEEP_OFFSET_NS.fetch_add(slept_ns, Ordering::AcqRel);
+    }
+    LAST_SLEEP_NS.store(slept_ns, Ordering::Release);
+    TOTAL_SLEEP_NS.fetch_add(slept_ns, Ordering::AcqRel);
+    RESUME_GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+/// État de reprise : (génération, dernière veille, veille cumulée), en ns.
+pub fn sleep_state() -> (u64, u64, u64) {
+    (
+        RESUME_GENERATION.load(Ordering::Acquire),
+        LAST_SLEEP_NS.load(Ordering::Acquire),
+        TOTAL_SLEEP_NS.load(Ordering::Acquire),
+    )
+}
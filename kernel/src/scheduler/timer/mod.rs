@@ -7,7 +7,8 @@ pub mod sleep;
 pub mod tick;
 
 pub use clock::{
-    elapsed_since_ns, monotonic_ns, monotonic_us, rdtsc, realtime_ns, scheduler_now_ns,
+    boottime_ns, elapsed_since_ns, monotonic_ns, monotonic_us, rdtsc, realtime_ns,
+    scheduler_now_ns,
 };
 pub use deadline_timer::{dl_enqueue, dl_pick_next, dl_tick};
 pub use hrtimer::{arm as hrtimer_arm, cancel as hrtimer_cancel, fire_expired as hrtimer_fire};
@@ -21,7 +21,7 @@ use crate::scheduler::core::task::{
     CpuId, SchedPolicy, ThreadControlBlock, SCHED_NEED_RESCHED_BIT,
 };
 use crate::scheduler::policies::{rr_tick, tick_check_preempt, timeslice_for};
-use crate::scheduler::smp::load_balance::{balance_cpu, BALANCE_INTERVAL_TICKS};
+use crate::scheduler::smp::load_balance::{balance_cpu, BALANCE_INTERVAL_TUNABLE};
 use core::ptr::NonNull;
 use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
@@ -154,7 +154,7 @@ pub unsafe extern "C" fn scheduler_tick(cpu_id: u32, current: *mut ThreadControl
     }
 
     // ── 6. Équilibrage de charge ──────────────────────────────────────────
-    if tick % BALANCE_INTERVAL_TICKS == 0 {
+    if tick % BALANCE_INTERVAL_TUNABLE.load(Ordering::Relaxed).max(1) == 0 {
         balance_cpu(CpuId(cpu_id));
     }
 }
@@ -280,6 +280,8 @@ pub fn create(
 ///
 /// Utilisé par les serveurs Ring 1 qui souhaitent valider qu'une requête IPC
 /// porte bien une capability noyau correspondant au service attendu.
+/// Site livepatch `cap_check_token` : jamais inlinée.
+#[inline(never)]
 pub fn check_token(
     token: token::CapToken,
     required_rights: u32,
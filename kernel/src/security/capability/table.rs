@@ -451,6 +451,27 @@ impl CapTable {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Première entrée occupée à partir du slot `index` : slot, ObjectId et vue.
+    ///
+    /// Sert l'introspection (`exo_cap_list`) : lectures atomiques sans lock et
+    /// hors statistiques de lookup. Une entrée modifiée pendant la lecture peut
+    /// mêler ancien et nouvel état — sans conséquence pour un outil d'inspection.
+    pub fn entry_at(&self, index: usize) -> Option<(usize, ObjectId, CapEntryView)> {
+        (index..CAP_TABLE_CAPACITY).find_map(|slot| {
+            let entry = &self.entries[slot];
+            let oid = entry.object_id.load(Ordering::Acquire);
+            if oid == SLOT_FREE {
+                return None;
+            }
+            let view = CapEntryView {
+                rights: Rights::from_bits_truncate(entry.rights.load(Ordering::Acquire)),
+                generation: entry.generation.load(Ordering::Acquire),
+                type_tag: CapObjectType::from_u16(entry.type_tag.load(Ordering::Acquire) as u16),
+            };
+            Some((slot, ObjectId::from_raw(oid), view))
+        })
+    }
 }
 
 impl Default for CapTable {
@@ -463,7 +484,7 @@ impl Default for CapTable {
 // CapEntryView — vue lecture
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Vue en lecture d'une entrée — retournée par `get()`.
+/// Vue en lecture d'une entrée — retournée par `get()` et `entry_at()`.
 #[derive(Debug, Clone, Copy)]
 pub struct CapEntryView {
     pub rights: Rights,
@@ -533,4 +554,31 @@ mod tier_sec_tests {
         // init conserve l'intégralité de ses droits.
         assert!(parent.check_object(oid(0), r(R_ADMIN), CapObjectType::FileInode));
     }
+
+    /// `entry_at` parcourt les seuls slots occupés, dans l'ordre des slots, et
+    /// reprend au slot suivant celui retourné.
+    #[test]
+    fn entry_at_walks_occupied_slots() {
+        let t = CapTable::new();
+        assert!(t.entry_at(0).is_none());
+        t.grant(oid(3), r(R_READ), CapObjectType::FileInode)
+            .unwrap();
+        t.grant(oid(11), r(R_WRITE | R_ADMIN), CapObjectType::IpcEndpoint)
+            .unwrap();
+        t.remove(oid(3)).unwrap();
+        t.grant(oid(5), r(R_READ), CapObjectType::Device).unwrap();
+
+        let mut seen = [(0u64, 0u32, CapObjectType::Invalid); 2];
+        let mut n = 0;
+        let mut index = 0;
+        while let Some((slot, object_id, view)) = t.entry_at(index) {
+            seen[n] = (object_id.as_u64(), view.rights.bits(), view.type_tag);
+            n += 1;
+            index = slot + 1;
+        }
+        assert_eq!(n, 2);
+        seen.sort_by_key(|e| e.0);
+        assert_eq!(seen[0], (5, R_READ, CapObjectType::Device));
+        assert_eq!(seen[1], (11, R_WRITE | R_ADMIN, CapObjectType::IpcEndpoint));
+    }
 }
@@ -0,0 +1,503 @@
+// kernel/src/security/crypto/entropy.rs
+//
+// ═══════════════════════════════════════════════════════════════════════════════
+// Entropie continue — collecteur de gigue CPU + horodatage des IRQ
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// rng.rs seed le CSPRNG au boot puis le reseed tous les 4096 blocs ; sans
+// RDSEED/RDRAND (VM qui les masque), ces reseeds ne tirent que quelques
+// lectures TSC. Ce module réalimente le pool EN CONTINU :
+//
+//   • horodatage des IRQ : `dispatch_irq` dépose (TSC, vecteur) dans un anneau
+//     sans verrou ; crédit 1/64 bit par IRQ (comme Linux) ;
+//   • collecteur de gigue : durée au TSC d'un parcours mémoire dépendant des
+//     données ; tests de santé SP 800-90B (Repetition Count + Adaptive
+//     Proportion) ; crédit 1/8 bit par échantillon non bloqué ;
+//   • pool d'entrée : lots de 32 échantillons chaînés par BLAKE3 keyed ;
+//   • reseed du ChaCha20 depuis la boucle idle, dès 128 bits crédités et
+//     l'intervalle écoulé : rapide (5 s) pour les 12 premiers reseeds, pour
+//     sortir vite d'un seed de boot faible, lent (60 s) ensuite.
+//
+// Santé du pool exposée pour audit, en lecture seule, dans
+// /proc/sys/kernel/random/ ; les deux intervalles y sont réglables.
+//
+// RÈGLE ENTROPY-01 : `add_interrupt_timing` ne prend AUCUN verrou (contexte IRQ).
+// RÈGLE ENTROPY-02 : le travail idle n'utilise que `try_lock` — jamais d'attente.
+// RÈGLE ENTROPY-03 : un lot de gigue qui échoue aux tests de santé est mélangé
+//   au pool mais JAMAIS crédité.
+// ═══════════════════════════════════════════════════════════════════════════════
+
+use super::blake3::blake3_mac;
+use super::rng::rng_try_reseed;
+use crate::arch::x86_64::cpu::tsc::read_tsc;
+use crate::scheduler::core::preempt::PreemptGuard;
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use spin::Mutex;
+
+/// Capacité du pool en bits (taille du condensé).
+const POOL_BITS: u32 = 256;
+/// Entropie créditée minimale pour reseeder le CSPRNG.
+const RESEED_MIN_BITS: u32 = 128;
+/// Les crédits sont comptés en 1/64 de bit.
+const CREDIT_SHIFT: u32 = 6;
+
+/// Reseeds au rythme rapide avant de passer au rythme lent.
+const FAST_RESEEDS: u64 = 12;
+/// Intervalle entre reseeds, phase rapide (ms).
+pub const FAST_RESEED_MS: u64 = 5_000;
+/// Intervalle entre reseeds, régime établi (ms).
+pub const SLOW_RESEED_MS: u64 = 60_000;
+pub static FAST_RESEED_TUNABLE: AtomicU64 = AtomicU64::new(FAST_RESEED_MS);
+pub static SLOW_RESEED_TUNABLE: AtomicU64 = AtomicU64::new(SLOW_RESEED_MS);
+
+/// Échantillons par lot mélangé dans le pool.
+const BATCH: usize = 32;
+
+/// Emplacements de l'anneau des IRQ : au-delà, les plus anciennes sont écrasées.
+const IRQ_RING: usize = 64;
+/// Crédit d'une IRQ (1/64 bit).
+const IRQ_CREDIT: u32 = 1;
+
+/// Échantillons par lot de gigue.
+const JITTER_SAMPLES: usize = 64;
+/// Un lot de gigue au plus toutes les 100 ms.
+const JITTER_PERIOD_NS: u64 = 100_000_000;
+/// Crédit d'un échantillon de gigue non bloqué (1/8 bit).
+const JITTER_CREDIT: u32 = 8;
+/// Mémoire parcourue par le collecteur (défauts de cache = gigue).
+const JITTER_MEMORY: usize = 2048;
+const WALK_STEPS: usize = 32;
+/// Pas premier avec la taille : le parcours couvre toute la mémoire.
+const WALK_STRIDE: usize = 67;
+
+/// Repetition Count Test : même durée ce nombre de fois de suite = panne
+/// (valeur de jitterentropy, H = 1 bit, α = 2⁻³⁰).
+const RCT_CUTOFF: u32 = 30;
+/// Adaptive Proportion Test : fenêtre et seuil (SP 800-90B §4.4.2, H = 1 bit).
+const APT_WINDOW: u32 = 512;
+const APT_CUTOFF: u32 = 325;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Valeurs exposées dans /proc/sys/kernel/random/ (sysctl/builtin.rs)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Entropie créditée au pool, en bits.
+pub static ENTROPY_AVAIL_VALUE: AtomicU64 = AtomicU64::new(0);
+/// Reseeds du CSPRNG depuis le pool.
+pub static RESEED_COUNT_VALUE: AtomicU64 = AtomicU64::new(0);
+/// Horloge monotone du dernier reseed (ms), 0 = jamais.
+pub static LAST_RESEED_MS_VALUE: AtomicU64 = AtomicU64::new(0);
+/// Horodatages d'IRQ mélangés.
+pub static IRQ_SAMPLES_VALUE: AtomicU64 = AtomicU64::new(0);
+/// Échantillons de gigue mélangés.
+pub static JITTER_SAMPLES_VALUE: AtomicU64 = AtomicU64::new(0);
+/// 1 si le dernier lot de gigue a passé les tests de santé.
+pub static JITTER_HEALTHY_VALUE: AtomicU64 = AtomicU64::new(0);
+/// Lots de gigue rejetés par les tests de santé.
+pub static HEALTH_FAILURES_VALUE: AtomicU64 = AtomicU64::new(0);
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Anneau des horodatages d'IRQ (sans verrou)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// 0 = emplacement vide.
+static IRQ_SAMPLES: [AtomicU64; IRQ_RING] = [const { AtomicU64::new(0) }; IRQ_RING];
+static IRQ_HEAD: AtomicUsize = AtomicUsize::new(0);
+
+/// Dépose l'horodatage d'une IRQ. Appelé depuis `dispatch_irq` (ENTROPY-01).
+#[inline]
+pub fn add_interrupt_timing(vector: u8) {
+    let slot = IRQ_HEAD.fetch_add(1, Ordering::Relaxed) % IRQ_RING;
+    let sample = read_tsc() ^ ((vector as u64) << 56);
+    IRQ_SAMPLES[slot].store(sample | 1, Ordering::Relaxed);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Pool d'entrée
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct Pool {
+    /// Condensé chaîné : chaque lot y est mélangé par BLAKE3 keyed.
+    digest: [u8; 32],
+    batch: [u64; BATCH],
+    len: usize,
+    /// Entropie créditée, en 1/64 de bit, plafonnée à POOL_BITS.
+    credit: u32,
+}
+
+impl Pool {
+    const fn new() -> Self {
+        Self {
+            digest: [0u8; 32],
+            batch: [0u64; BATCH],
+            len: 0,
+            credit: 0,
+        }
+    }
+
+    fn add(&mut self, sample: u64) {
+        self.batch[self.len] = sample;
+        self.len += 1;
+        if self.len == BATCH {
+            self.mix();
+        }
+    }
+
+    /// Mélange le lot en cours dans le condensé.
+    fn mix(&mut self) {
+        if self.len == 0 {
+            return;
+        }
+        let mut bytes = [0u8; BATCH * 8];
+        for (chunk, sample) in bytes.chunks_exact_mut(8).zip(&self.batch[..self.len]) {
+            chunk.copy_from_slice(&sample.to_le_bytes());
+        }
+        self.digest = blake3_mac(&self.digest, &bytes[..self.len * 8]);
+        self.batch = [0u64; BATCH];
+        self.len = 0;
+    }
+
+    fn credit(&mut self, units: u32) {
+        self.credit = self
+            .credit
+            .saturating_add(units)
+            .min(POOL_BITS << CREDIT_SHIFT);
+    }
+
+    fn bits(&self) -> u32 {
+        self.credit >> CREDIT_SHIFT
+    }
+
+    /// Seed de reseed dérivé du pool ; ne consomme rien tant que `consume`
+    /// n'est pas appelé (le reseed peut échouer, ENTROPY-02).
+    fn seed(&mut self) -> [u8; 32] {
+        self.mix();
+        blake3_mac(&self.digest, b"exo-entropy-reseed")
+    }
+
+    /// Le seed a été utilisé : avance le condensé (le seed n'est plus
+    /// dérivable de l'état) et remet le crédit à zéro.
+    fn consume(&mut self) {
+        self.digest = blake3_mac(&self.digest, b"exo-entropy-next");
+        self.credit = 0;
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests de santé de la source de gigue
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct Health {
+    last_delta: u64,
+    last_delta2: u64,
+    rct_value: u64,
+    rct_count: u32,
+    apt_value: u64,
+    apt_count: u32,
+    apt_seen: u32,
+    /// Un test a échoué depuis `start_block`.
+    failed: bool,
+}
+
+impl Health {
+    const fn new() -> Self {
+        Self {
+            last_delta: 0,
+            last_delta2: 0,
+            rct_value: 0,
+            rct_count: 0,
+            apt_value: 0,
+            apt_count: 0,
+            apt_seen: 0,
+            failed: false,
+        }
+    }
+
+    fn start_block(&mut self) {
+        self.failed = false;
+    }
+
+    /// Passe une durée mesurée aux tests ; `true` si elle est créditable
+    /// (dérivées première, seconde et troisième non nulles).
+    fn check(&mut self, delta: u64) -> bool {
+        let delta2 = delta.wrapping_sub(self.last_delta);
+        let delta3 = delta2.wrapping_sub(self.last_delta2);
+        self.last_delta = delta;
+        self.last_delta2 = delta2;
+
+        if delta == self.rct_value {
+            self.rct_count += 1;
+            if self.rct_count >= RCT_CUTOFF {
+                self.failed = true;
+            }
+        } else {
+            self.rct_value = delta;
+            self.rct_count = 1;
+        }
+
+        if self.apt_seen == 0 {
+            self.apt_value = delta;
+            self.apt_count = 1;
+        } else if delta == self.apt_value {
+            self.apt_count += 1;
+            if self.apt_count >= APT_CUTOFF {
+                self.failed = true;
+            }
+        }
+        self.apt_seen = (self.apt_seen + 1) % APT_WINDOW;
+
+        delta != 0 && delta2 != 0 && delta3 != 0
+    }
+}
+
+/// Reseed dû : `reseeds` faits, le dernier à `last_ns`, intervalles en ms.
+fn reseed_due(reseeds: u64, last_ns: u64, now_ns: u64, fast_ms: u64, slow_ms: u64) -> bool {
+    let interval_ms = if reseeds < FAST_RESEEDS {
+        fast_ms
+    } else {
+        slow_ms
+    };
+    now_ns.saturating_sub(last_ns) >= interval_ms.saturating_mul(1_000_000)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Collecteur
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct Collector {
+    pool: Pool,
+    health: Health,
+    memory: [u8; JITTER_MEMORY],
+    walk: usize,
+    last_jitter_ns: u64,
+    last_reseed_ns: u64,
+    reseeds: u64,
+    irq_samples: u64,
+    jitter_samples: u64,
+    health_failures: u64,
+    jitter_healthy: bool,
+}
+
+impl Collector {
+    const fn new() -> Self {
+        Self {
+            pool: Pool::new(),
+            health: Health::new(),
+            memory: [0u8; JITTER_MEMORY],
+            walk: 0,
+            last_jitter_ns: 0,
+            last_reseed_ns: 0,
+            reseeds: 0,
+            irq_samples: 0,
+            jitter_samples: 0,
+            health_failures: 0,
+            jitter_healthy: false,
+        }
+    }
+
+    /// Mélange les horodatages d'IRQ déposés depuis le dernier passage.
+    fn drain_irqs(&mut self) {
+        for slot in IRQ_SAMPLES.iter() {
+            let sample = slot.swap(0, Ordering::Relaxed);
+            if sample != 0 {
+                self.pool.add(sample);
+                self.pool.credit(IRQ_CREDIT);
+                self.irq_samples += 1;
+            }
+        }
+    }
+
+    /// Durée au TSC d'un parcours mémoire dont chaque pas dépend de l'octet lu.
+    fn measure(&mut self) -> u64 {
+        let start = read_tsc();
+        let mut at = self.walk;
+        for _ in 0..WALK_STEPS {
+            let cell = &mut self.memory[at];
+            *cell = cell.wrapping_add(1);
+            at = (at + WALK_STRIDE + *cell as usize) % JITTER_MEMORY;
+        }
+        self.walk = at;
+        read_tsc().wrapping_sub(start)
+    }
+
+    /// Un lot de gigue : mélangé dans tous les cas, crédité s'il est sain.
+    fn collect_jitter(&mut self) {
+        self.health.start_block();
+        let mut credited = 0u32;
+        for _ in 0..JITTER_SAMPLES {
+            let delta = self.measure();
+            if self.health.check(delta) {
+                credited += 1;
+            }
+            self.pool.add(delta);
+        }
+        self.jitter_samples += JITTER_SAMPLES as u64;
+        self.jitter_healthy = !self.health.failed;
+        if self.jitter_healthy {
+            self.pool.credit(credited * JITTER_CREDIT);
+        } else {
+            self.health_failures += 1;
+        }
+    }
+
+    fn try_reseed(&mut self, now_ns: u64) {
+        if self.pool.bits() < RESEED_MIN_BITS
+            || !reseed_due(
+                self.reseeds,
+                self.last_reseed_ns,
+                now_ns,
+                FAST_RESEED_TUNABLE.load(Ordering::Relaxed),
+                SLOW_RESEED_TUNABLE.load(Ordering::Relaxed),
+            )
+        {
+            return;
+        }
+        let mut seed = self.pool.seed();
+        if rng_try_reseed(&seed) {
+            self.pool.consume();
+            self.reseeds += 1;
+            self.last_reseed_ns = now_ns;
+        }
+        for b in seed.iter_mut() {
+            // SAFETY: effacement borné d'un buffer local.
+            unsafe {
+                core::ptr::write_volatile(b, 0);
+            }
+        }
+        core::sync::atomic::fence(Ordering::SeqCst);
+    }
+
+    fn publish(&self) {
+        ENTROPY_AVAIL_VALUE.store(self.pool.bits() as u64, Ordering::Relaxed);
+        RESEED_COUNT_VALUE.store(self.reseeds, Ordering::Relaxed);
+        LAST_RESEED_MS_VALUE.store(self.last_reseed_ns / 1_000_000, Ordering::Relaxed);
+        IRQ_SAMPLES_VALUE.store(self.irq_samples, Ordering::Relaxed);
+        JITTER_SAMPLES_VALUE.store(self.jitter_samples, Ordering::Relaxed);
+        JITTER_HEALTHY_VALUE.store(self.jitter_healthy as u64, Ordering::Relaxed);
+        HEALTH_FAILURES_VALUE.store(self.health_failures, Ordering::Relaxed);
+    }
+}
+
+static COLLECTOR: Mutex<Collector> = Mutex::new(Collector::new());
+
+/// Travail d'entropie d'une itération idle : vide l'anneau des IRQ, prend un
+/// lot de gigue si la période est écoulée, reseed le CSPRNG si dû.
+///
+/// Publie les compteurs à chaque passage : l'enregistrement des tunables
+/// (qui remet les valeurs à leur défaut) ne perd donc rien.
+pub fn idle_work(now_ns: u64) {
+    let _preempt = PreemptGuard::new();
+    // Un seul CPU à la fois ; les autres retournent dormir (ENTROPY-02).
+    let Some(mut collector) = COLLECTOR.try_lock() else {
+        return;
+    };
+    collector.drain_irqs();
+    if now_ns.saturating_sub(collector.last_jitter_ns) >= JITTER_PERIOD_NS {
+        collector.last_jitter_ns = now_ns;
+        collector.collect_jitter();
+    }
+    collector.try_reseed(now_ns);
+    collector.publish();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn health_tests_catch_a_stuck_source() {
+        let mut health = Health::new();
+        health.start_block();
+        // Une durée constante : ni créditée, ni saine au-delà du seuil RCT.
+        for i in 0..RCT_CUTOFF {
+            health.check(100);
+            assert_eq!(health.failed, i + 1 >= RCT_CUTOFF);
+        }
+        // Des durées qui varient passent ; l'échec reste acquis jusqu'au lot suivant.
+        assert!(health.check(137));
+        health.start_block();
+        for i in 0..64u64 {
+            health.check(1000 + (i * 7919) % 251);
+        }
+        assert!(!health.failed);
+    }
+
+    #[test]
+    fn adaptive_proportion_test_catches_a_biased_source() {
+        let mut health = Health::new();
+        health.start_block();
+        // Une valeur sur deux identique ne déclenche pas le RCT...
+        for i in 0..APT_WINDOW as u64 {
+            health.check(if i % 2 == 0 { 100 } else { 200 + i });
+        }
+        assert!(!health.failed);
+        // ... mais 325 fois la première valeur dans une fenêtre, si, même
+        // par séries de 7.
+        for i in 0..APT_WINDOW as u64 {
+            health.check(if i % 8 == 7 { 300 + i } else { 100 });
+        }
+        assert!(health.failed);
+    }
+
+    #[test]
+    fn pool_credit_is_capped_and_consumed_by_reseed() {
+        let mut pool = Pool::new();
+        pool.credit(u32::MAX);
+        assert_eq!(pool.bits(), POOL_BITS);
+        for i in 0..(BATCH as u64 + 3) {
+            pool.add(i);
+        }
+        assert_eq!(pool.len, 3);
+        let before = pool.digest;
+        let seed = pool.seed();
+        assert_eq!(pool.len, 0);
+        assert_ne!(pool.digest, before);
+        // Sans `consume`, le même état redonne le même seed.
+        assert_eq!(pool.seed(), seed);
+        pool.consume();
+        assert_eq!(pool.bits(), 0);
+        assert_ne!(pool.seed(), seed);
+    }
+
+    #[test]
+    fn reseeds_fast_then_slow() {
+        let s = 1_000_000_000;
+        assert!(!reseed_due(0, 0, 4 * s, FAST_RESEED_MS, SLOW_RESEED_MS));
+        assert!(reseed_due(0, 0, 5 * s, FAST_RESEED_MS, SLOW_RESEED_MS));
+        assert!(reseed_due(
+            FAST_RESEEDS - 1,
+            60 * s,
+            65 * s,
+            FAST_RESEED_MS,
+            SLOW_RESEED_MS
+        ));
+        assert!(!reseed_due(
+            FAST_RESEEDS,
+            60 * s,
+            65 * s,
+            FAST_RESEED_MS,
+            SLOW_RESEED_MS
+        ));
+        assert!(reseed_due(
+            FAST_RESEEDS,
+            60 * s,
+            120 * s,
+            FAST_RESEED_MS,
+            SLOW_RESEED_MS
+        ));
+    }
+
+    #[test]
+    fn interrupt_timings_are_drained_and_credited() {
+        let mut collector = Collector::new();
+        for vector in 0..(IRQ_RING as u8 + 8) {
+            add_interrupt_timing(vector);
+        }
+        collector.drain_irqs();
+        // L'anneau a écrasé les plus anciennes.
+        assert_eq!(collector.irq_samples, IRQ_RING as u64);
+        assert_eq!(collector.pool.credit, IRQ_RING as u32 * IRQ_CREDIT);
+        collector.drain_irqs();
+        assert_eq!(collector.irq_samples, IRQ_RING as u64);
+    }
+}
@@ -8,6 +8,7 @@
 //   • blake3             : Hash BLAKE3 — 256 bits, mode keyed + derive_key
 //   • xchacha20_poly1305 : AEAD XChaCha20-Poly1305
 //   • rng                : CSPRNG (RDRAND + ChaCha20 block function)
+//   • entropy            : Reseed continu (gigue CPU + horodatage des IRQ)
 //   • kdf                : HKDF-BLAKE3 — dérivation de clés
 //   • x25519             : ECDH X25519
 //   • ed25519            : Signatures Ed25519
@@ -24,6 +25,7 @@
 pub mod aes_gcm;
 pub mod blake3;
 pub mod ed25519;
+pub mod entropy;
 pub mod kdf;
 pub mod rng;
 pub mod x25519;
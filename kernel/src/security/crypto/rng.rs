@@ -10,6 +10,8 @@
 //     xchacha20_poly1305.rs — arithmétique u32 pure, zéro SIMD/SSE2.
 //   • Reseed : toutes les 4096 blocs ChaCha20 générés, mixage RDRAND.
 //   • Fallback : TSC + adresse de pile quand RDRAND échoue.
+//   • Reseed continu : pool de gigue + horodatage des IRQ (entropy.rs),
+//     depuis la boucle idle.
 //
 // RÈGLE CRYPTO-CRATES : ChaCha20 est la SEULE primitive crypto maison
 // autorisée (la crate chacha20 déclenche LLVM ERROR: split 128-bit sur
@@ -439,6 +441,20 @@ pub fn rng_fill(buf: &mut [u8]) -> Result<(), RngError> {
     KERNEL_RNG.lock().fill(buf)
 }
 
+/// Reseed depuis le pool d'entropie continue (entropy.rs). Non bloquant :
+/// `false` si le RNG n'est pas initialisé ou si son verrou est pris.
+pub(crate) fn rng_try_reseed(extra: &[u8; 32]) -> bool {
+    let Some(mut rng) = KERNEL_RNG.try_lock() else {
+        return false;
+    };
+    if !rng.initialized {
+        return false;
+    }
+    rng.prng.reseed(extra);
+    rng.reseed_count += 1;
+    true
+}
+
 /// Génère un u64 aléatoire.
 pub fn rng_u64() -> Result<u64, RngError> {
     let mut buf = [0u8; 8];
@@ -150,6 +150,17 @@ pub fn init_runtime_integrity() {
     state.last_ok_tsc = read_tsc();
 }
 
+/// Recalcule le hash de référence de `.text` après une modification légitime
+/// (correctif `livepatch` signé et appliqué). No-op avant l'initialisation.
+pub fn rebaseline_text() {
+    let mut state = INTEGRITY_STATE.lock();
+    if !state.initialized {
+        return;
+    }
+    // SAFETY: Voir text_section().
+    state.text_hash = unsafe { blake3_hash(text_section()) };
+}
+
 /// Effectue une vérification d'intégrité des sections kernel.
 ///
 /// Retourne Err si une altération est détectée.
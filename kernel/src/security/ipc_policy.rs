@@ -229,6 +229,8 @@ pub fn can_inject_src_pid(pid: Pid) -> bool {
     is_ring1_trusted_class(class_of(pid))
 }
 
+/// Site livepatch `ipc_check_direct` : jamais inlinée.
+#[inline(never)]
 pub fn check_direct_ipc(src: Pid, dst: Pid) -> IpcPolicyResult {
     let src_class = class_of(src);
     let dst_class = class_of(dst);
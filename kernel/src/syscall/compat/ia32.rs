@@ -0,0 +1,881 @@
+//! # syscall/compat/ia32.rs — ABI syscall Linux i386 (feature `ia32_compat`)
+//!
+//! Point d'entrée commun de `int 0x80` et `sysenter` (arch/x86_64/ia32.rs).
+//! Les appels i386 sont ramenés sur le dispatcher natif :
+//!
+//! 1. **Mapping direct** : même sémantique, seul le numéro change
+//!    (`I386_DIRECT`). Les arguments u32 sont étendus à zéro, sauf ceux
+//!    marqués signés (fd `AT_FDCWD`, pid -1, off_t 32-bit) qui sont étendus
+//!    avec signe.
+//! 2. **Traductions** : structures dont le layout diffère entre i386 et
+//!    x86_64 (`stat64`, `timespec`, `iovec`, `rusage`, `rlimit`, …).
+//!    La version native est produite dans une zone scratch sous ESP puis
+//!    convertie vers le buffer de l'appelant.
+//! 3. **Refus** : tout le reste retourne `-ENOSYS` (socketcall, threads
+//!    CLONE_VM, handlers de signaux — seules les dispositions SIG_DFL/SIG_IGN
+//!    sont acceptées, faute de trampoline `sigreturn` 32-bit).
+//!
+//! ## Registres
+//! `eax` = numéro, `ebx, ecx, edx, esi, edi, ebp` = arguments 1..6. Seul
+//! `eax` est modifié au retour ; un `execve` réussi réécrit en plus
+//! EIP/ESP/CS/SS pour démarrer la nouvelle image.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::arch::x86_64::exceptions::ExceptionFrame;
+use crate::arch::x86_64::gdt::{GDT_USER_CS32, GDT_USER_CS64, GDT_USER_DS};
+use crate::arch::x86_64::syscall::SyscallFrame;
+use crate::syscall::errno::{EFAULT, EINVAL, ENOSYS, EOVERFLOW};
+use crate::syscall::numbers::*;
+use crate::syscall::validation::{copy_from_user, copy_to_user};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Compteurs
+// ─────────────────────────────────────────────────────────────────────────────
+
+static IA32_CALLS: AtomicU64 = AtomicU64::new(0);
+static IA32_TRANSLATED: AtomicU64 = AtomicU64::new(0);
+static IA32_BLOCKED: AtomicU64 = AtomicU64::new(0);
+
+/// Statistiques de la couche compat i386.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Ia32CompatStats {
+    /// Syscalls i386 reçus
+    pub calls: u64,
+    /// Syscalls dont les structures ont été converties
+    pub translated: u64,
+    /// Syscalls refusés (-ENOSYS / -EINVAL délibéré)
+    pub blocked: u64,
+}
+
+/// Snapshot des compteurs i386.
+pub fn ia32_compat_stats() -> Ia32CompatStats {
+    Ia32CompatStats {
+        calls: IA32_CALLS.load(Ordering::Relaxed),
+        translated: IA32_TRANSLATED.load(Ordering::Relaxed),
+        blocked: IA32_BLOCKED.load(Ordering::Relaxed),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Table de mapping direct i386 → x86_64
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Bits d'arguments à étendre avec signe (bit N = argument N+1).
+const S0: u8 = 1 << 0;
+const S1: u8 = 1 << 1;
+const S2: u8 = 1 << 2;
+
+/// `(numéro i386, numéro x86_64, arguments signés)`.
+const I386_DIRECT: &[(u32, u64, u8)] = &[
+    (1, SYS_EXIT, S0),
+    (2, SYS_FORK, 0),
+    (3, SYS_READ, 0),
+    (4, SYS_WRITE, 0),
+    (5, SYS_OPEN, 0),
+    (6, SYS_CLOSE, 0),
+    (8, SYS_CREAT, 0),
+    (9, SYS_LINK, 0),
+    (10, SYS_UNLINK, 0),
+    (11, SYS_EXECVE, 0),
+    (12, SYS_CHDIR, 0),
+    (14, SYS_MKNOD, 0),
+    (15, SYS_CHMOD, 0),
+    (20, SYS_GETPID, 0),
+    (23, SYS_SETUID, 0),
+    (24, SYS_GETUID, 0),
+    (27, SYS_ALARM, 0),
+    (29, SYS_PAUSE, 0),
+    (33, SYS_ACCESS, 0),
+    (36, SYS_SYNC, 0),
+    (37, SYS_KILL, S0),
+    (38, SYS_RENAME, 0),
+    (39, SYS_MKDIR, 0),
+    (40, SYS_RMDIR, 0),
+    (41, SYS_DUP, 0),
+    (42, SYS_PIPE, 0),
+    (45, SYS_BRK, 0),
+    (46, SYS_SETGID, 0),
+    (47, SYS_GETGID, 0),
+    (49, SYS_GETEUID, 0),
+    (50, SYS_GETEGID, 0),
+    (54, SYS_IOCTL, 0),
+    (55, SYS_FCNTL, 0),
+    (57, SYS_SETPGID, S0 | S1),
+    (60, SYS_UMASK, 0),
+    (61, SYS_CHROOT, 0),
+    (63, SYS_DUP2, 0),
+    (64, SYS_GETPPID, 0),
+    (65, SYS_GETPGRP, 0),
+    (66, SYS_SETSID, 0),
+    (83, SYS_SYMLINK, 0),
+    (85, SYS_READLINK, 0),
+    (91, SYS_MUNMAP, 0),
+    (93, SYS_FTRUNCATE, S1),
+    (94, SYS_FCHMOD, 0),
+    (118, SYS_FSYNC, 0),
+    (122, SYS_UNAME, 0),
+    (125, SYS_MPROTECT, 0),
+    (132, SYS_GETPGID, S0),
+    (133, SYS_FCHDIR, 0),
+    (144, SYS_MSYNC, 0),
+    (148, SYS_FDATASYNC, 0),
+    (150, SYS_MLOCK, 0),
+    (151, SYS_MUNLOCK, 0),
+    (158, SYS_SCHED_YIELD, 0),
+    (163, SYS_MREMAP, 0),
+    (168, SYS_POLL, S2),
+    (175, SYS_RT_SIGPROCMASK, 0),
+    (183, SYS_GETCWD, 0),
+    (190, SYS_VFORK, 0),
+    (199, SYS_GETUID, 0),
+    (200, SYS_GETGID, 0),
+    (201, SYS_GETEUID, 0),
+    (202, SYS_GETEGID, 0),
+    (213, SYS_SETUID, 0),
+    (214, SYS_SETGID, 0),
+    (219, SYS_MADVISE, 0),
+    (220, SYS_GETDENTS64, 0),
+    (221, SYS_FCNTL, 0),
+    (224, SYS_GETTID, 0),
+    (252, SYS_EXIT_GROUP, S0),
+    (258, SYS_SET_TID_ADDRESS, 0),
+    (270, SYS_TGKILL, S0 | S1),
+    (295, SYS_OPENAT, S0),
+    (296, SYS_MKDIRAT, S0),
+    (301, SYS_UNLINKAT, S0),
+    (302, SYS_RENAMEAT, S0 | S2),
+    (305, SYS_READLINKAT, S0),
+    (307, SYS_FACCESSAT, S0),
+    (331, SYS_PIPE2, 0),
+    (355, SYS_GETRANDOM, 0),
+];
+
+/// Cherche un numéro i386 dans la table de mapping direct.
+pub fn lookup_direct(nr: u32) -> Option<(u64, u8)> {
+    I386_DIRECT
+        .iter()
+        .find(|&&(i386, _, _)| i386 == nr)
+        .map(|&(_, native, signed)| (native, signed))
+}
+
+/// Étend un argument i386 vers 64 bits (avec signe si demandé).
+#[inline]
+fn widen(arg: u32, signed: bool) -> u64 {
+    if signed {
+        arg as i32 as i64 as u64
+    } else {
+        arg as u64
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Numéros i386 traduits
+// ─────────────────────────────────────────────────────────────────────────────
+
+const NR_WAITPID: u32 = 7;
+const NR_TIME: u32 = 13;
+const NR_LSEEK: u32 = 19;
+const NR_TIMES: u32 = 43;
+const NR_SETRLIMIT: u32 = 75;
+const NR_GETRLIMIT: u32 = 76;
+const NR_GETRUSAGE: u32 = 77;
+const NR_GETTIMEOFDAY: u32 = 78;
+const NR_OLD_MMAP: u32 = 90;
+const NR_SOCKETCALL: u32 = 102;
+const NR_WAIT4: u32 = 114;
+const NR_CLONE: u32 = 120;
+const NR_LLSEEK: u32 = 140;
+const NR_READV: u32 = 145;
+const NR_WRITEV: u32 = 146;
+const NR_NANOSLEEP: u32 = 162;
+const NR_RT_SIGACTION: u32 = 174;
+const NR_UGETRLIMIT: u32 = 191;
+const NR_MMAP2: u32 = 192;
+const NR_STAT64: u32 = 195;
+const NR_LSTAT64: u32 = 196;
+const NR_FSTAT64: u32 = 197;
+const NR_FUTEX: u32 = 240;
+const NR_SET_THREAD_AREA: u32 = 243;
+const NR_GET_THREAD_AREA: u32 = 244;
+const NR_CLOCK_GETTIME: u32 = 265;
+const NR_CLOCK_GETRES: u32 = 266;
+const NR_FSTATAT64: u32 = 300;
+
+const CLONE_VM: u32 = 0x0000_0100;
+const CLONE_THREAD: u32 = 0x0001_0000;
+const RLIM_INFINITY_32: u32 = u32::MAX;
+const MMAP2_PAGE_SHIFT: u32 = 12;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Point d'entrée
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Dispatche un syscall i386 décrit par `frame` et écrit le résultat dans eax.
+pub fn dispatch_ia32(frame: &mut ExceptionFrame) {
+    IA32_CALLS.fetch_add(1, Ordering::Relaxed);
+
+    let nr = frame.rax as u32;
+    let args = [
+        frame.rbx as u32,
+        frame.rcx as u32,
+        frame.rdx as u32,
+        frame.rsi as u32,
+        frame.rdi as u32,
+        frame.rbp as u32,
+    ];
+    let mut ctx = Ia32Call::new(frame);
+
+    let result = if let Some((native, signed)) = lookup_direct(nr) {
+        let mut wide = [0u64; 6];
+        for (i, slot) in wide.iter_mut().enumerate() {
+            *slot = widen(args[i], signed & (1 << i) != 0);
+        }
+        ctx.native(native, wide)
+    } else {
+        IA32_TRANSLATED.fetch_add(1, Ordering::Relaxed);
+        translate(&mut ctx, nr, args)
+    };
+
+    if ctx.blocked {
+        IA32_BLOCKED.fetch_add(1, Ordering::Relaxed);
+    }
+
+    if let Some(exec) = ctx.exec_target {
+        start_new_image(frame, &exec);
+        return;
+    }
+    frame.rax = result as u32 as u64;
+}
+
+/// Réinitialise la frame pour démarrer l'image chargée par execve.
+fn start_new_image(frame: &mut ExceptionFrame, exec: &SyscallFrame) {
+    let ia32 = crate::arch::x86_64::ia32::current_is_ia32();
+    frame.cs = if ia32 { GDT_USER_CS32 } else { GDT_USER_CS64 } as u64;
+    frame.ss = GDT_USER_DS as u64;
+    frame.rip = exec.rcx;
+    frame.rsp = exec.rsp;
+    frame.rflags = 0x202;
+    frame.rax = 0;
+    frame.rbx = 0;
+    frame.rcx = 0;
+    frame.rdx = 0;
+    frame.rsi = 0;
+    frame.rdi = exec.rdi;
+    frame.rbp = 0;
+    frame.r8 = 0;
+    frame.r9 = 0;
+    frame.r10 = 0;
+    frame.r11 = 0;
+    frame.r12 = 0;
+    frame.r13 = 0;
+    frame.r14 = 0;
+    frame.r15 = 0;
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Contexte d'appel natif
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Appel en cours : frame native de base + allocateur scratch sous ESP.
+struct Ia32Call {
+    base: SyscallFrame,
+    scratch: u64,
+    scratch_floor: u64,
+    exec_target: Option<SyscallFrame>,
+    blocked: bool,
+}
+
+/// Marge laissée sous ESP avant la zone scratch.
+const SCRATCH_GAP: u64 = 128;
+/// Taille maximale de la zone scratch (pile user déjà touchée en pratique).
+const SCRATCH_MAX: u64 = 2048;
+
+impl Ia32Call {
+    fn new(frame: &ExceptionFrame) -> Self {
+        // Frame native « vue depuis SYSCALL » : rcx/r11 portent RIP/RFLAGS
+        // (fork s'en sert pour le contexte enfant), les callee-saved restent
+        // ceux de l'appelant i386.
+        let base = SyscallFrame {
+            rax: 0,
+            r9: 0,
+            r8: 0,
+            r10: frame.rcx & 0xFFFF_FFFF,
+            rdx: frame.rdx & 0xFFFF_FFFF,
+            rdi: frame.rdi & 0xFFFF_FFFF,
+            rsi: frame.rsi & 0xFFFF_FFFF,
+            rsp: frame.rsp & 0xFFFF_FFFF,
+            r15: frame.r15,
+            r14: frame.r14,
+            r13: frame.r13,
+            r12: frame.r12,
+            rbx: frame.rbx & 0xFFFF_FFFF,
+            rbp: frame.rbp & 0xFFFF_FFFF,
+            r11: frame.rflags,
+            rcx: frame.rip & 0xFFFF_FFFF,
+        };
+        let top = base.rsp.saturating_sub(SCRATCH_GAP) & !15;
+        Self {
+            base,
+            scratch: top,
+            scratch_floor: top.saturating_sub(SCRATCH_MAX),
+            exec_target: None,
+            blocked: false,
+        }
+    }
+
+    /// Exécute `nr` via le dispatcher natif et retourne la valeur brute.
+    fn native(&mut self, nr: u64, args: [u64; 6]) -> i64 {
+        let mut sf = self.base;
+        sf.rax = nr;
+        sf.rdi = args[0];
+        sf.rsi = args[1];
+        sf.rdx = args[2];
+        sf.r10 = args[3];
+        sf.r8 = args[4];
+        sf.r9 = args[5];
+        crate::syscall::dispatch::dispatch(&mut sf);
+        let ret = sf.rax as i64;
+        if nr == SYS_EXECVE && ret == 0 {
+            self.exec_target = Some(sf);
+        }
+        ret
+    }
+
+    /// Réserve `len` octets (alignés 16) dans la zone scratch user.
+    fn alloc(&mut self, len: usize) -> Result<u64, i64> {
+        let next = self.scratch.checked_sub(len as u64).ok_or(EFAULT)? & !15;
+        if next < self.scratch_floor || next == 0 {
+            return Err(EFAULT);
+        }
+        self.scratch = next;
+        Ok(next)
+    }
+
+    fn refuse(&mut self, errno: i64) -> i64 {
+        self.blocked = true;
+        errno
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Accès mémoire user (pointeurs i386 : alignement 4 au mieux)
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn read_bytes<const N: usize>(addr: u64) -> Result<[u8; N], i64> {
+    let mut buf = [0u8; N];
+    copy_from_user(buf.as_mut_ptr(), addr as *const u8, N).map_err(|e| e.to_errno())?;
+    Ok(buf)
+}
+
+fn write_bytes(addr: u64, bytes: &[u8]) -> Result<(), i64> {
+    copy_to_user(addr as *mut u8, bytes.as_ptr(), bytes.len()).map_err(|e| e.to_errno())
+}
+
+fn read_u32(addr: u64) -> Result<u32, i64> {
+    read_bytes::<4>(addr).map(u32::from_le_bytes)
+}
+
+fn read_u64(addr: u64) -> Result<u64, i64> {
+    read_bytes::<8>(addr).map(u64::from_le_bytes)
+}
+
+/// Lit un tableau de `N` champs 64-bit natifs.
+fn read_native_words<const N: usize>(addr: u64) -> Result<[i64; N], i64> {
+    let mut out = [0i64; N];
+    for (i, word) in out.iter_mut().enumerate() {
+        *word = read_u64(addr + (i * 8) as u64)? as i64;
+    }
+    Ok(out)
+}
+
+/// Écrit des champs tronqués à 32 bits (timespec, timeval, rusage, tms i386).
+fn write_words32(addr: u64, words: &[i64]) -> Result<(), i64> {
+    let mut buf = [0u8; 4 * 18];
+    let len = words.len() * 4;
+    if len > buf.len() {
+        return Err(EINVAL);
+    }
+    for (i, &w) in words.iter().enumerate() {
+        buf[i * 4..i * 4 + 4].copy_from_slice(&(w as i32).to_le_bytes());
+    }
+    write_bytes(addr, &buf[..len])
+}
+
+/// Copie une paire `{i32, i32}` i386 (timespec/timeval) vers la scratch native.
+fn widen_pair(ctx: &mut Ia32Call, ptr: u32) -> Result<u64, i64> {
+    if ptr == 0 {
+        return Ok(0);
+    }
+    let raw = read_bytes::<8>(ptr as u64)?;
+    let sec = i32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as i64;
+    let frac = i32::from_le_bytes([raw[4], raw[5], raw[6], raw[7]]) as i64;
+    let native = ctx.alloc(16)?;
+    let mut buf = [0u8; 16];
+    buf[..8].copy_from_slice(&sec.to_le_bytes());
+    buf[8..].copy_from_slice(&frac.to_le_bytes());
+    write_bytes(native, &buf)?;
+    Ok(native)
+}
+
+/// Convertit une paire native `{i64, i64}` vers le buffer i386 `ptr`.
+fn narrow_pair(native: u64, ptr: u32) -> Result<(), i64> {
+    let words = read_native_words::<2>(native)?;
+    if words[0] > i32::MAX as i64 || words[0] < i32::MIN as i64 {
+        return Err(EOVERFLOW);
+    }
+    write_words32(ptr as u64, &words)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Conversion stat → stat64 i386
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Taille de `struct stat` x86_64.
+pub const NATIVE_STAT_SIZE: usize = 144;
+/// Taille de `struct stat64` i386 (packed(4)).
+pub const STAT64_SIZE: usize = 96;
+
+/// Convertit un `struct stat` x86_64 en `struct stat64` i386.
+pub fn stat_to_stat64(native: &[u8; NATIVE_STAT_SIZE]) -> [u8; STAT64_SIZE] {
+    let n64 = |off: usize| {
+        let mut b = [0u8; 8];
+        b.copy_from_slice(&native[off..off + 8]);
+        u64::from_le_bytes(b)
+    };
+    let n32 = |off: usize| {
+        let mut b = [0u8; 4];
+        b.copy_from_slice(&native[off..off + 4]);
+        u32::from_le_bytes(b)
+    };
+    let mut out = [0u8; STAT64_SIZE];
+    let mut put64 = |off: usize, v: u64| out[off..off + 8].copy_from_slice(&v.to_le_bytes());
+    put64(0, n64(0)); // st_dev
+    put64(32, n64(40)); // st_rdev
+    put64(44, n64(48)); // st_size
+    put64(56, n64(64)); // st_blocks
+    put64(88, n64(8)); // st_ino
+    let mut put32 = |off: usize, v: u32| out[off..off + 4].copy_from_slice(&v.to_le_bytes());
+    put32(12, n64(8) as u32); // __st_ino
+    put32(16, n32(24)); // st_mode
+    put32(20, n64(16) as u32); // st_nlink
+    put32(24, n32(28)); // st_uid
+    put32(28, n32(32)); // st_gid
+    put32(52, n64(56) as u32); // st_blksize
+    put32(64, n64(72) as u32); // st_atime
+    put32(68, n64(80) as u32);
+    put32(72, n64(88) as u32); // st_mtime
+    put32(76, n64(96) as u32);
+    put32(80, n64(104) as u32); // st_ctime
+    put32(84, n64(112) as u32);
+    out
+}
+
+/// Exécute un stat natif dans la scratch puis écrit le stat64 i386.
+fn stat64_call(ctx: &mut Ia32Call, nr: u64, args: [u64; 6], out_idx: usize) -> i64 {
+    let out = args[out_idx];
+    let native = match ctx.alloc(NATIVE_STAT_SIZE) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+    let mut native_args = args;
+    native_args[out_idx] = native;
+    let ret = ctx.native(nr, native_args);
+    if ret < 0 {
+        return ret;
+    }
+    let converted = read_bytes::<NATIVE_STAT_SIZE>(native).map(|st| stat_to_stat64(&st));
+    match converted.and_then(|st64| write_bytes(out, &st64)) {
+        Ok(()) => ret,
+        Err(e) => e,
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Traductions
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn translate(ctx: &mut Ia32Call, nr: u32, a: [u32; 6]) -> i64 {
+    let u = |i: usize| a[i] as u64;
+    let s = |i: usize| a[i] as i32 as i64 as u64;
+    let res = match nr {
+        NR_WAITPID => wait4(ctx, s(0), u(1), u(2), 0),
+        NR_WAIT4 => wait4(ctx, s(0), u(1), u(2), a[3]),
+        NR_TIME => sys_time(ctx, a[0]),
+        NR_LSEEK => {
+            let ret = ctx.native(SYS_LSEEK, [u(0), s(1), u(2), 0, 0, 0]);
+            if ret > i32::MAX as i64 {
+                Err(EOVERFLOW)
+            } else {
+                Ok(ret)
+            }
+        }
+        NR_LLSEEK => {
+            let offset = ((a[1] as u64) << 32) | a[2] as u64;
+            let ret = ctx.native(SYS_LSEEK, [u(0), offset, u(4), 0, 0, 0]);
+            if ret < 0 {
+                Ok(ret)
+            } else {
+                write_bytes(u(3), &ret.to_le_bytes()).map(|()| 0)
+            }
+        }
+        NR_TIMES => times(ctx, a[0]),
+        NR_SETRLIMIT => setrlimit(ctx, u(0), a[1]),
+        NR_GETRLIMIT => getrlimit(ctx, u(0), a[1], i32::MAX as u32),
+        NR_UGETRLIMIT => getrlimit(ctx, u(0), a[1], RLIM_INFINITY_32),
+        NR_GETRUSAGE => getrusage(ctx, s(0), a[1]),
+        NR_GETTIMEOFDAY => {
+            let tv = ctx.alloc(16);
+            tv.and_then(|tv| {
+                let ret = ctx.native(SYS_GETTIMEOFDAY, [tv, u(1), 0, 0, 0, 0]);
+                if ret == 0 && a[0] != 0 {
+                    narrow_pair(tv, a[0])?;
+                }
+                Ok(ret)
+            })
+        }
+        NR_OLD_MMAP => read_bytes::<24>(u(0)).and_then(|raw| {
+            let mut m = [0u64; 6];
+            for (i, slot) in m.iter_mut().enumerate() {
+                let w = [raw[i * 4], raw[i * 4 + 1], raw[i * 4 + 2], raw[i * 4 + 3]];
+                *slot = u32::from_le_bytes(w) as u64;
+            }
+            m[4] = m[4] as u32 as i32 as i64 as u64; // fd (-1 pour anonyme)
+            Ok(ctx.native(SYS_MMAP, m))
+        }),
+        NR_MMAP2 => {
+            let offset = u(5) << MMAP2_PAGE_SHIFT;
+            Ok(ctx.native(SYS_MMAP, [u(0), u(1), u(2), u(3), s(4), offset]))
+        }
+        NR_READV => vectored_io(ctx, SYS_READV, a[0], a[1], a[2]),
+        NR_WRITEV => vectored_io(ctx, SYS_WRITEV, a[0], a[1], a[2]),
+        NR_NANOSLEEP => widen_pair(ctx, a[0]).and_then(|req| {
+            let rem = if a[1] != 0 { ctx.alloc(16)? } else { 0 };
+            let ret = ctx.native(SYS_NANOSLEEP, [req, rem, 0, 0, 0, 0]);
+            if rem != 0 && ret == crate::syscall::errno::EINTR {
+                narrow_pair(rem, a[1])?;
+            }
+            Ok(ret)
+        }),
+        NR_RT_SIGACTION => rt_sigaction(ctx, a),
+        NR_STAT64 => Ok(stat64_call(ctx, SYS_STAT, [u(0), u(1), 0, 0, 0, 0], 1)),
+        NR_LSTAT64 => Ok(stat64_call(ctx, SYS_LSTAT, [u(0), u(1), 0, 0, 0, 0], 1)),
+        NR_FSTAT64 => Ok(stat64_call(ctx, SYS_FSTAT, [u(0), u(1), 0, 0, 0, 0], 1)),
+        NR_FSTATAT64 => Ok(stat64_call(
+            ctx,
+            SYS_NEWFSTATAT,
+            [s(0), u(1), u(2), u(3), 0, 0],
+            2,
+        )),
+        NR_FUTEX => futex(ctx, a),
+        NR_SET_THREAD_AREA => set_thread_area(a[0]),
+        NR_GET_THREAD_AREA => get_thread_area(a[0]),
+        NR_CLOCK_GETTIME | NR_CLOCK_GETRES => {
+            let native_nr = if nr == NR_CLOCK_GETTIME {
+                SYS_CLOCK_GETTIME
+            } else {
+                SYS_CLOCK_GETRES
+            };
+            let ts = if a[1] != 0 { ctx.alloc(16) } else { Ok(0) };
+            ts.and_then(|ts| {
+                let ret = ctx.native(native_nr, [s(0), ts, 0, 0, 0, 0]);
+                if ret == 0 && ts != 0 {
+                    narrow_pair(ts, a[1])?;
+                }
+                Ok(ret)
+            })
+        }
+        NR_CLONE => clone(ctx, a),
+        // socketcall multiplexé : pas de pile réseau i386 pour l'instant
+        NR_SOCKETCALL => Err(ctx.refuse(ENOSYS)),
+        _ => Err(ctx.refuse(ENOSYS)),
+    };
+    res.unwrap_or_else(|e| e)
+}
+
+fn wait4(ctx: &mut Ia32Call, pid: u64, status: u64, options: u64, rusage: u32) -> Result<i64, i64> {
+    let native_ru = if rusage != 0 { ctx.alloc(144)? } else { 0 };
+    let ret = ctx.native(SYS_WAIT4, [pid, status, options, native_ru, 0, 0]);
+    if ret > 0 && native_ru != 0 {
+        let words = read_native_words::<18>(native_ru)?;
+        write_words32(rusage as u64, &words)?;
+    }
+    Ok(ret)
+}
+
+fn sys_time(ctx: &mut Ia32Call, tloc: u32) -> Result<i64, i64> {
+    let ts = ctx.alloc(16)?;
+    // CLOCK_REALTIME
+    let ret = ctx.native(SYS_CLOCK_GETTIME, [0, ts, 0, 0, 0, 0]);
+    if ret < 0 {
+        return Ok(ret);
+    }
+    let secs = read_native_words::<1>(ts)?[0];
+    if tloc != 0 {
+        write_words32(tloc as u64, &[secs])?;
+    }
+    Ok(secs as i32 as i64)
+}
+
+fn times(ctx: &mut Ia32Call, buf: u32) -> Result<i64, i64> {
+    let tms = if buf != 0 { ctx.alloc(32)? } else { 0 };
+    let ret = ctx.native(SYS_TIMES, [tms, 0, 0, 0, 0, 0]);
+    if ret >= 0 && tms != 0 {
+        let words = read_native_words::<4>(tms)?;
+        write_words32(buf as u64, &words)?;
+    }
+    Ok(ret as i32 as i64)
+}
+
+fn getrlimit(ctx: &mut Ia32Call, resource: u64, ptr: u32, infinity: u32) -> Result<i64, i64> {
+    let native = ctx.alloc(16)?;
+    let ret = ctx.native(SYS_GETRLIMIT, [resource, native, 0, 0, 0, 0]);
+    if ret < 0 {
+        return Ok(ret);
+    }
+    let lim = read_native_words::<2>(native)?;
+    let clamp = |v: i64| (v as u64).min(infinity as u64) as u32;
+    let mut out = [0u8; 8];
+    out[..4].copy_from_slice(&clamp(lim[0]).to_le_bytes());
+    out[4..].copy_from_slice(&clamp(lim[1]).to_le_bytes());
+    write_bytes(ptr as u64, &out)?;
+    Ok(ret)
+}
+
+fn setrlimit(ctx: &mut Ia32Call, resource: u64, ptr: u32) -> Result<i64, i64> {
+    let raw = read_bytes::<8>(ptr as u64)?;
+    let widen_lim = |v: u32| {
+        if v == RLIM_INFINITY_32 {
+            u64::MAX
+        } else {
+            v as u64
+        }
+    };
+    let cur = widen_lim(u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]));
+    let max = widen_lim(u32::from_le_bytes([raw[4], raw[5], raw[6], raw[7]]));
+    let native = ctx.alloc(16)?;
+    let mut buf = [0u8; 16];
+    buf[..8].copy_from_slice(&cur.to_le_bytes());
+    buf[8..].copy_from_slice(&max.to_le_bytes());
+    write_bytes(native, &buf)?;
+    Ok(ctx.native(SYS_SETRLIMIT, [resource, native, 0, 0, 0, 0]))
+}
+
+fn getrusage(ctx: &mut Ia32Call, who: u64, ptr: u32) -> Result<i64, i64> {
+    let native = ctx.alloc(144)?;
+    let ret = ctx.native(SYS_GETRUSAGE, [who, native, 0, 0, 0, 0]);
+    if ret == 0 {
+        let words = read_native_words::<18>(native)?;
+        write_words32(ptr as u64, &words)?;
+    }
+    Ok(ret)
+}
+
+/// readv/writev : les `iovec` i386 (2 × u32) sont élargis par lots de 64.
+fn vectored_io(ctx: &mut Ia32Call, nr: u64, fd: u32, iov: u32, iovcnt: u32) -> Result<i64, i64> {
+    const BATCH: usize = 64;
+    if iovcnt as i32 <= 0 || iovcnt > 1024 {
+        return if iovcnt == 0 { Ok(0) } else { Err(EINVAL) };
+    }
+    let native = ctx.alloc(BATCH * 16)?;
+    let mut total: i64 = 0;
+    let mut done = 0u32;
+    while done < iovcnt {
+        let batch = (iovcnt - done).min(BATCH as u32);
+        let mut expected: u64 = 0;
+        for i in 0..batch {
+            let entry = read_bytes::<8>(iov as u64 + ((done + i) as u64) * 8)?;
+            let base = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]) as u64;
+            let len = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]) as u64;
+            let mut buf = [0u8; 16];
+            buf[..8].copy_from_slice(&base.to_le_bytes());
+            buf[8..].copy_from_slice(&len.to_le_bytes());
+            write_bytes(native + i as u64 * 16, &buf)?;
+            expected += len;
+        }
+        let ret = ctx.native(nr, [fd as u64, native, batch as u64, 0, 0, 0]);
+        if ret < 0 {
+            return Ok(if total > 0 { total } else { ret });
+        }
+        total += ret;
+        if (ret as u64) < expected {
+            break;
+        }
+        done += batch;
+    }
+    if total > i32::MAX as i64 {
+        return Err(EOVERFLOW);
+    }
+    Ok(total)
+}
+
+/// rt_sigaction i386 : `{handler, flags, restorer : u32, mask : u64}`.
+///
+/// Aucun trampoline `sigreturn` 32-bit n'existe : seuls SIG_DFL et SIG_IGN
+/// sont installables, un vrai handler est refusé par -EINVAL.
+fn rt_sigaction(ctx: &mut Ia32Call, a: [u32; 6]) -> Result<i64, i64> {
+    const SIG_IGN: u32 = 1;
+    let (sig, act, oldact, sigsetsize) = (a[0] as u64, a[1], a[2], a[3] as u64);
+    let native_act = if act != 0 {
+        let raw = read_bytes::<20>(act as u64)?;
+        let handler = u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]);
+        if handler > SIG_IGN {
+            return Err(ctx.refuse(EINVAL));
+        }
+        let flags = u32::from_le_bytes([raw[4], raw[5], raw[6], raw[7]]) as u64;
+        let mut mask = [0u8; 8];
+        mask.copy_from_slice(&raw[12..20]);
+        let p = ctx.alloc(32)?;
+        let mut buf = [0u8; 32];
+        buf[..8].copy_from_slice(&(handler as u64).to_le_bytes());
+        buf[8..16].copy_from_slice(&flags.to_le_bytes());
+        buf[24..].copy_from_slice(&mask);
+        write_bytes(p, &buf)?;
+        p
+    } else {
+        0
+    };
+    let native_old = if oldact != 0 { ctx.alloc(32)? } else { 0 };
+    let ret = ctx.native(
+        SYS_RT_SIGACTION,
+        [sig, native_act, native_old, sigsetsize, 0, 0],
+    );
+    if ret == 0 && native_old != 0 {
+        let old = read_native_words::<4>(native_old)?;
+        let mut out = [0u8; 20];
+        out[..4].copy_from_slice(&(old[0] as u32).to_le_bytes());
+        out[4..8].copy_from_slice(&(old[1] as u32).to_le_bytes());
+        out[8..12].copy_from_slice(&(old[2] as u32).to_le_bytes());
+        out[12..].copy_from_slice(&old[3].to_le_bytes());
+        write_bytes(oldact as u64, &out)?;
+    }
+    Ok(ret)
+}
+
+/// futex : l'argument 4 est un `timespec*` pour les opérations d'attente,
+/// un entier (val2) sinon.
+fn futex(ctx: &mut Ia32Call, a: [u32; 6]) -> Result<i64, i64> {
+    const FUTEX_CMD_MASK: u32 = !(128 | 256); // PRIVATE | CLOCK_REALTIME
+    let cmd = a[1] & FUTEX_CMD_MASK;
+    let timeout = match cmd {
+        0 | 6 | 9 | 11 => widen_pair(ctx, a[3])?,
+        _ => a[3] as u64,
+    };
+    Ok(ctx.native(
+        SYS_FUTEX,
+        [
+            a[0] as u64,
+            a[1] as u64,
+            a[2] as u64,
+            timeout,
+            a[4] as u64,
+            a[5] as u64,
+        ],
+    ))
+}
+
+fn set_thread_area(ptr: u32) -> Result<i64, i64> {
+    use crate::arch::x86_64::ia32::{
+        encode_tls_desc, install_current_tls32, UserDesc, TLS32_ENTRY,
+    };
+
+    let raw = read_bytes::<16>(ptr as u64)?;
+    let field = |i: usize| u32::from_le_bytes([raw[i], raw[i + 1], raw[i + 2], raw[i + 3]]);
+    let desc = UserDesc {
+        entry_number: field(0),
+        base_addr: field(4),
+        limit: field(8),
+        flags: field(12),
+    };
+    if desc.entry_number != u32::MAX && desc.entry_number != TLS32_ENTRY {
+        return Err(EINVAL);
+    }
+    let encoded = encode_tls_desc(&desc).ok_or(EINVAL)?;
+    if !install_current_tls32(encoded, desc.base_addr) {
+        return Err(EINVAL);
+    }
+    if desc.entry_number == u32::MAX {
+        write_bytes(ptr as u64, &TLS32_ENTRY.to_le_bytes())?;
+    }
+    Ok(0)
+}
+
+fn get_thread_area(ptr: u32) -> Result<i64, i64> {
+    use crate::arch::x86_64::ia32::{current_tls32_desc, decode_tls_desc, TLS32_ENTRY};
+
+    if read_u32(ptr as u64)? != TLS32_ENTRY {
+        return Err(EINVAL);
+    }
+    let desc = decode_tls_desc(current_tls32_desc());
+    let mut out = [0u8; 16];
+    out[..4].copy_from_slice(&desc.entry_number.to_le_bytes());
+    out[4..8].copy_from_slice(&desc.base_addr.to_le_bytes());
+    out[8..12].copy_from_slice(&desc.limit.to_le_bytes());
+    out[12..].copy_from_slice(&desc.flags.to_le_bytes());
+    write_bytes(ptr as u64, &out)?;
+    Ok(0)
+}
+
+/// clone i386 `(flags, newsp, ptid, tls, ctid)`.
+///
+/// Sans CLONE_VM c'est un fork (pile enfant optionnelle) ; les threads
+/// partageant l'espace d'adressage ne sont pas encore supportés.
+fn clone(ctx: &mut Ia32Call, a: [u32; 6]) -> Result<i64, i64> {
+    let (flags, newsp) = (a[0], a[1]);
+    if flags & (CLONE_VM | CLONE_THREAD) != 0 {
+        return Err(ctx.refuse(ENOSYS));
+    }
+    let saved_rsp = ctx.base.rsp;
+    if newsp != 0 {
+        ctx.base.rsp = newsp as u64;
+    }
+    let ret = ctx.native(SYS_FORK, [0; 6]);
+    ctx.base.rsp = saved_rsp;
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_direct_table_unique() {
+        for (i, &(nr, _, _)) in I386_DIRECT.iter().enumerate() {
+            assert!(I386_DIRECT[i + 1..]
+                .iter()
+                .all(|&(other, _, _)| other != nr));
+        }
+        assert_eq!(lookup_direct(4), Some((SYS_WRITE, 0)));
+        assert_eq!(lookup_direct(295), Some((SYS_OPENAT, S0)));
+        assert_eq!(lookup_direct(NR_STAT64), None);
+    }
+
+    #[test]
+    fn test_widen_signed_args() {
+        assert_eq!(widen(0xFFFF_FF9C, true) as i64, -100); // AT_FDCWD
+        assert_eq!(widen(0xFFFF_FF9C, false), 0xFFFF_FF9C);
+    }
+
+    #[test]
+    fn test_stat_to_stat64_layout() {
+        let mut native = [0u8; NATIVE_STAT_SIZE];
+        let mut set = |off: usize, v: u64| native[off..off + 8].copy_from_slice(&v.to_le_bytes());
+        set(0, 0x0801); // st_dev
+        set(8, 0x1_0000_0042); // st_ino
+        set(16, 3); // st_nlink
+        set(24, 0x3E8_0000_81A4); // st_mode | st_uid << 32
+        set(48, 0x1_2345_6789); // st_size
+        set(88, 1_700_000_000); // st_mtime
+        let st = stat_to_stat64(&native);
+        let r32 = |off: usize| u32::from_le_bytes(st[off..off + 4].try_into().unwrap());
+        let r64 = |off: usize| u64::from_le_bytes(st[off..off + 8].try_into().unwrap());
+        assert_eq!(r64(0), 0x0801);
+        assert_eq!(r32(12), 0x42);
+        assert_eq!(r32(16), 0o100644);
+        assert_eq!(r32(20), 3);
+        assert_eq!(r32(24), 1000);
+        assert_eq!(r64(44), 0x1_2345_6789);
+        assert_eq!(r32(72), 1_700_000_000);
+        assert_eq!(r64(88), 0x1_0000_0042);
+    }
+}
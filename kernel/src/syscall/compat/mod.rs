@@ -1,11 +1,12 @@
 //! # syscall/compat/mod.rs — Couche de compatibilité ABI
 //!
-//! Ce sous-module regroupe deux couches complémentaires :
+//! Ce sous-module regroupe les couches de compatibilité :
 //!
 //! | Module     | Rôle                                                          |
 //! |------------|---------------------------------------------------------------|
 //! | `linux`    | Traduit les numéros Linux supprimés / renommés               |
 //! | `posix`    | Handlers POSIX.1-2017 + constantes + validation des args      |
+//! | `ia32`     | ABI i386 (`int 0x80`/`sysenter`), feature `ia32_compat`       |
 //!
 //! ## Pipeline d'appel (dispatch.rs)
 //!
@@ -20,6 +21,8 @@
 //! obsolètes, puis `get_handler` qui délègue vers `get_posix_handler` en
 //! second recours pour les numéros POSIX non inclus dans la table principale.
 
+#[cfg(feature = "ia32_compat")]
+pub mod ia32;
 pub mod linux;
 pub mod posix;
 
@@ -36,6 +36,7 @@ use crate::arch::x86_64::cpu::tsc::read_tsc;
 use crate::arch::x86_64::syscall::SyscallFrame;
 use crate::syscall::compat::linux::translate_linux_nr;
 use crate::syscall::fast_path::try_fast_path;
+use crate::syscall::hot_path;
 use crate::syscall::numbers::{is_valid_syscall, ENOSYS};
 use crate::syscall::table::get_handler;
 // FIX-APP-02: imports pour audit_syscall_entry/exit (APP-02)
@@ -51,8 +52,15 @@ static DISPATCH_FAST_PATH: AtomicU64 = AtomicU64::new(0);
 static DISPATCH_SLOW_PATH: AtomicU64 = AtomicU64::new(0);
 static DISPATCH_ENOSYS: AtomicU64 = AtomicU64::new(0);
 static DISPATCH_COMPAT: AtomicU64 = AtomicU64::new(0);
+static DISPATCH_HOT_PATH: AtomicU64 = AtomicU64::new(0);
 /// Somme des latences dispatch (cycles TSC). Échantillonné 1/256.
 static DISPATCH_LATENCY_CYC: AtomicU64 = AtomicU64::new(0);
+/// Coût d'aiguillage jusqu'à l'appel du handler (cycles TSC, échantillonné
+/// 1/256) : table chaude vs slow-path complet.
+static DISPATCH_HOT_OVERHEAD_CYC: AtomicU64 = AtomicU64::new(0);
+static DISPATCH_HOT_OVERHEAD_SAMPLES: AtomicU64 = AtomicU64::new(0);
+static DISPATCH_SLOW_OVERHEAD_CYC: AtomicU64 = AtomicU64::new(0);
+static DISPATCH_SLOW_OVERHEAD_SAMPLES: AtomicU64 = AtomicU64::new(0);
 
 #[cfg(all(target_arch = "x86_64", debug_assertions))]
 #[inline]
@@ -81,6 +89,12 @@ pub struct DispatchStats {
     pub enosys: u64,
     pub compat: u64,
     pub latency_cyc: u64,
+    /// Appels servis par la table chaude (`hot_path`)
+    pub hot_path: u64,
+    pub hot_overhead_cyc: u64,
+    pub hot_overhead_samples: u64,
+    pub slow_overhead_cyc: u64,
+    pub slow_overhead_samples: u64,
 }
 
 /// Retourne un snapshot instantané des compteurs.
@@ -92,6 +106,20 @@ pub fn dispatch_stats() -> DispatchStats {
         enosys: DISPATCH_ENOSYS.load(Ordering::Relaxed),
         compat: DISPATCH_COMPAT.load(Ordering::Relaxed),
         latency_cyc: DISPATCH_LATENCY_CYC.load(Ordering::Relaxed),
+        hot_path: DISPATCH_HOT_PATH.load(Ordering::Relaxed),
+        hot_overhead_cyc: DISPATCH_HOT_OVERHEAD_CYC.load(Ordering::Relaxed),
+        hot_overhead_samples: DISPATCH_HOT_OVERHEAD_SAMPLES.load(Ordering::Relaxed),
+        slow_overhead_cyc: DISPATCH_SLOW_OVERHEAD_CYC.load(Ordering::Relaxed),
+        slow_overhead_samples: DISPATCH_SLOW_OVERHEAD_SAMPLES.load(Ordering::Relaxed),
+    }
+}
+
+/// Échantillonne (1/256) le coût d'aiguillage depuis l'entrée du dispatch.
+#[inline(always)]
+fn sample_overhead(tsc_start: u64, cycles: &AtomicU64, samples: &AtomicU64) {
+    if (tsc_start & 0xFF) == 0 {
+        cycles.fetch_add(read_tsc().saturating_sub(tsc_start), Ordering::Relaxed);
+        samples.fetch_add(1, Ordering::Relaxed);
     }
 }
 
@@ -115,8 +143,9 @@ pub fn dispatch_stats() -> DispatchStats {
 /// 2. Lecture des 6 arguments depuis la frame
 /// 3. Validation du numéro syscall
 /// 4. Essai fast-path (getpid, gettid, yield, clock_gettime, …)
+/// 4b. Table chaude guidée par le profil (`hot_path::lookup`)
 /// 5. Traduction numéro compat Linux si nécessaire
-/// 6. Lookup handler dans `table::get_handler(nr)`
+/// 6. Lookup handler dans `table::get_handler(nr)` (+ profil `hot_path::record`)
 /// 7. Appel handler
 /// 8. Écriture du résultat dans `frame.rax`
 /// 9. Check signal pending (RÈGLE SIGNAL-01)
@@ -227,6 +256,21 @@ pub fn dispatch(frame: &mut SyscallFrame) {
         return;
     }
 
+    // ── [3b] Table chaude ──────────────────────────────────────────────────
+    // Numéros promus par le profil : même handler que `get_handler(nr)`,
+    // sans traduction compat ni cascade de cas spéciaux.
+    if let Some(handler) = hot_path::lookup(nr) {
+        DISPATCH_HOT_PATH.fetch_add(1, Ordering::Relaxed);
+        sample_overhead(
+            tsc_start,
+            &DISPATCH_HOT_OVERHEAD_CYC,
+            &DISPATCH_HOT_OVERHEAD_SAMPLES,
+        );
+        let result = handler(arg1, arg2, arg3, arg4, arg5, arg6);
+        finish_table_call(frame, tsc_start, nr, result, caller_pid, caller_tid);
+        return;
+    }
+
     // ── [4] Traduction numéro compat Linux ─────────────────────────────────
     // Certains numéros Linux peuvent avoir un mapping alternatif dans Exo-OS.
     // Par ex. un numéro retiré de Linux qui est remappé vers l'équivalent Exo-OS.
@@ -289,10 +333,37 @@ pub fn dispatch(frame: &mut SyscallFrame) {
     // ── [6] Slow-path : lookup dans la table ───────────────────────────────
     DISPATCH_SLOW_PATH.fetch_add(1, Ordering::Relaxed);
     let handler = get_handler(effective_nr);
+    if effective_nr == nr {
+        hot_path::record(nr);
+    }
+    sample_overhead(
+        tsc_start,
+        &DISPATCH_SLOW_OVERHEAD_CYC,
+        &DISPATCH_SLOW_OVERHEAD_SAMPLES,
+    );
 
     // ── [7] Exécution du handler ───────────────────────────────────────────
     let result = handler(arg1, arg2, arg3, arg4, arg5, arg6);
+    finish_table_call(
+        frame,
+        tsc_start,
+        effective_nr,
+        result,
+        caller_pid,
+        caller_tid,
+    );
+}
 
+/// Fin commune des appels servis par la table (slow-path et table chaude).
+#[inline]
+fn finish_table_call(
+    frame: &mut SyscallFrame,
+    tsc_start: u64,
+    effective_nr: u64,
+    result: i64,
+    caller_pid: u32,
+    caller_tid: u32,
+) {
     // ── [7] Comptabilisation d'une erreur ENOSYS ───────────────────────────
     if result == ENOSYS {
         DISPATCH_ENOSYS.fetch_add(1, Ordering::Relaxed);
@@ -733,13 +804,15 @@ fn handle_fork_like_inplace(
 
 /// Copie un tableau de chaînes null-terminé depuis l'espace utilisateur.
 ///
-/// `argv_ptr` pointe vers un tableau de pointeurs `char*` terminé par NULL.
+/// `argv_ptr` pointe vers un tableau de pointeurs `char*` terminé par NULL,
+/// de `ptr_size` octets chacun (8, ou 4 pour un processus i386).
 /// Retourne `Some(Vec<String>)` ou `None` si une adresse est invalide.
 ///
 /// EXEC-01 : seule fonction autorisée à lire argv/envp depuis userspace.
 fn copy_userspace_argv(
     argv_ptr: u64,
     max_args: usize,
+    ptr_size: usize,
 ) -> Option<alloc::vec::Vec<alloc::string::String>> {
     use crate::syscall::validation::{copy_from_user, UserStr, USER_ADDR_MAX};
     use alloc::string::String;
@@ -756,15 +829,15 @@ fn copy_userspace_argv(
     let mut saw_null = false;
 
     for i in 0..max_args {
-        // Lire le i-ème pointeur (u64) du tableau.
-        let ptr_addr = match argv_ptr.checked_add(i as u64 * 8) {
-            Some(a) if a <= USER_ADDR_MAX.saturating_sub(8) => a,
+        // Lire le i-ème pointeur (u64, ou u32 étendu) du tableau.
+        let ptr_addr = match argv_ptr.checked_add(i as u64 * ptr_size as u64) {
+            Some(a) if a <= USER_ADDR_MAX.saturating_sub(ptr_size as u64) => a,
             _ => return None,
         };
 
         let mut raw = [0u8; core::mem::size_of::<u64>()];
-        copy_from_user(raw.as_mut_ptr(), ptr_addr as *const u8, raw.len()).ok()?;
-        let str_ptr = u64::from_ne_bytes(raw);
+        copy_from_user(raw.as_mut_ptr(), ptr_addr as *const u8, ptr_size).ok()?;
+        let str_ptr = u64::from_le_bytes(raw);
 
         if str_ptr == 0 {
             saw_null = true;
@@ -873,7 +946,16 @@ fn handle_execve_inplace(frame: &mut SyscallFrame) {
     // ARGV-01 (EXEC-01) : copier argv/envp depuis userspace avant do_execve().
     // frame.rsi = argv_ptr (tableau de char* null-terminé)
     // frame.rdx = envp_ptr (tableau de char* null-terminé)
-    let argv_strings = match copy_userspace_argv(frame.rsi, 1024) {
+    // L'image appelante (pas la nouvelle) fixe la taille des pointeurs.
+    let ptr_size = if pcb.flags.load(Ordering::Acquire)
+        & crate::process::core::pcb::process_flags::IA32
+        != 0
+    {
+        4
+    } else {
+        8
+    };
+    let argv_strings = match copy_userspace_argv(frame.rsi, 1024, ptr_size) {
         Some(v) => v,
         None => {
             exec_trace(b"execve: argv fault\n");
@@ -883,7 +965,7 @@ fn handle_execve_inplace(frame: &mut SyscallFrame) {
     };
     let argv_refs: alloc::vec::Vec<&str> = argv_strings.iter().map(|s| s.as_str()).collect();
 
-    let envp_strings = match copy_userspace_argv(frame.rdx, 4096) {
+    let envp_strings = match copy_userspace_argv(frame.rdx, 4096, ptr_size) {
         Some(v) => v,
         None => {
             exec_trace(b"execve: env fault\n");
@@ -336,8 +336,9 @@ pub fn sys_getcpu(cpu_ptr: u64, node_ptr: u64, _tcache: u64) -> i64 {
 /// `clock_gettime(clkid, timespec_ptr)` — lit une horloge POSIX.
 ///
 /// Horloges supportées :
-/// - `CLOCK_MONOTONIC` / `CLOCK_MONOTONIC_RAW` / `CLOCK_BOOTTIME` :
+/// - `CLOCK_MONOTONIC` / `CLOCK_MONOTONIC_RAW` :
 ///   TSC → nanosecondes via `monotonic_ns()`.
+/// - `CLOCK_BOOTTIME` : `boottime_ns()`, veille comprise.
 /// - `CLOCK_REALTIME` / `CLOCK_REALTIME_COARSE` :
 ///   `monotonic_ns()` + offset réel (CLOCK_REALTIME_OFFSET).
 /// - `CLOCK_MONOTONIC_COARSE` : identique MONOTONIC (granularité tick).
@@ -356,9 +357,8 @@ pub fn sys_clock_gettime(clkid: u64, ts_ptr: u64) -> i64 {
     };
 
     let ns: u64 = match clock_id {
-        CLOCK_MONOTONIC | CLOCK_MONOTONIC_RAW | CLOCK_BOOTTIME | CLOCK_MONOTONIC_COARSE => {
-            monotonic_ns()
-        }
+        CLOCK_MONOTONIC | CLOCK_MONOTONIC_RAW | CLOCK_MONOTONIC_COARSE => monotonic_ns(),
+        CLOCK_BOOTTIME => crate::scheduler::timer::clock::boottime_ns(),
         CLOCK_REALTIME | CLOCK_REALTIME_COARSE => {
             // Offset réel = monotonic + REALTIME_OFFSET (mis à jour par settimeofday / NTP).
             // L'offset est stocké dans un AtomicU64 global du module timer/clock.
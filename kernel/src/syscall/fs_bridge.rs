@@ -20,7 +20,9 @@ use alloc::vec::Vec;
 use core::mem::size_of;
 use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 
-use crate::fs::exofs::cache::BLOB_CACHE;
+#[cfg(feature = "kvm")]
+use crate::arch::x86_64::kvm::KvmHandle;
+use crate::fs::exofs::cache::{boot_preload, BLOB_CACHE};
 use crate::fs::exofs::core::{BlobId, ExofsError, ObjectId};
 use crate::fs::exofs::path::path_component::{PathComponent, PathComponentBuf};
 use crate::fs::exofs::path::path_index::{
@@ -254,6 +256,8 @@ const STAT_MODE_FILE: u32 = 0o100000 | 0o644;
 const STAT_MODE_SYMLINK: u32 = 0o120000 | 0o777;
 pub const TTY_PTS0_HANDLE: u32 = 0xffff_ff01;
 const TTY_SERVER_ENDPOINT_NAME: &[u8] = b"tty_server";
+#[cfg(feature = "kvm")]
+const KVM_DEVICE_PATH: &[u8] = b"/dev/exo-kvm";
 const TTY_MSG_READ_LINE: u32 = 0x131;
 const TTY_MSG_WRITE: u32 = 0x132;
 const TTY_LINE_MAX: usize = 184;
@@ -273,7 +277,10 @@ const PSEUDO_EVENTFD_TAG: u8 = 0xE7;
 const PSEUDO_EPOLL_TAG: u8 = 0xE9;
 const PSEUDO_INOTIFY_TAG: u8 = 0x1D;
 const PSEUDO_SOCKET_TAG: u8 = 0x5C;
+const PSEUDO_PRESSURE_TAG: u8 = 0x9F;
+const PSEUDO_KVM_TAG: u8 = 0x4B;
 const SOCKET_HEADER_LEN: usize = 32;
+const POSIX_FADV_WILLNEED: u32 = 3;
 const S_IFMT: u32 = 0o170000;
 const S_IFIFO: u32 = 0o010000;
 const S_IFDIR: u32 = 0o040000;
@@ -583,6 +590,17 @@ fn resolve_fd(pid: u32, fd: u32) -> Result<ResolvedFd, FsBridgeError> {
     })
 }
 
+/// Blob de l'eventfd `fd` de `pid` (cible de `SYS_IRQ_EVENTFD`).
+pub fn fs_eventfd_blob(fd: u32, pid: u32) -> Result<BlobId, FsBridgeError> {
+    let entry = OBJECT_TABLE
+        .get(resolve_fd(pid, fd)?.handle)
+        .map_err(exofs_to_bridge_error)?;
+    if !is_pseudo_blob(&entry.blob_id, PSEUDO_EVENTFD_TAG) {
+        return Err(FsBridgeError::Invalid);
+    }
+    Ok(entry.blob_id)
+}
+
 #[inline]
 fn install_process_fd(pid: u32, handle: u64, flags: u32) -> Option<i32> {
     crate::process::core::registry::PROCESS_REGISTRY
@@ -1069,6 +1087,22 @@ fn is_pseudo_blob(blob_id: &BlobId, tag: u8) -> bool {
     bytes[0] == tag && bytes[1] == b'E' && bytes[2] == b'X' && bytes[3] == b'O'
 }
 
+/// Blob synthétique (pipe, eventfd, socket…) : jamais sur disque.
+#[inline]
+fn is_any_pseudo_blob(blob_id: &BlobId) -> bool {
+    [
+        PSEUDO_PIPE_TAG,
+        PSEUDO_EVENTFD_TAG,
+        PSEUDO_EPOLL_TAG,
+        PSEUDO_INOTIFY_TAG,
+        PSEUDO_SOCKET_TAG,
+        PSEUDO_PRESSURE_TAG,
+        PSEUDO_KVM_TAG,
+    ]
+    .iter()
+    .any(|&tag| is_pseudo_blob(blob_id, tag))
+}
+
 #[inline]
 fn eventfd_state(blob_id: BlobId) -> Result<(u64, u32), FsBridgeError> {
     let data = snapshot_blob(&blob_id)?;
@@ -1079,6 +1113,13 @@ fn eventfd_state(blob_id: BlobId) -> Result<(u64, u32), FsBridgeError> {
         data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
     ]);
     let flags = if data.len() > 8 { data[8] as u32 } else { 0 };
+    // IRQ livrées par `SYS_IRQ_EVENTFD` depuis la dernière lecture.
+    let irqs = crate::drivers::irq_eventfd::drain(&blob_id);
+    if irqs != 0 {
+        let value = value.saturating_add(irqs).min(u64::MAX - 1);
+        store_eventfd_state(blob_id, value, flags)?;
+        return Ok((value, flags));
+    }
     Ok((value, flags))
 }
 
@@ -1094,6 +1135,36 @@ fn store_eventfd_state(blob_id: BlobId, value: u64, flags: u32) -> Result<(), Fs
     Ok(())
 }
 
+/// Dernier `seq` de pression mémoire lu sur ce descripteur.
+#[inline]
+fn pressure_seen_seq(blob_id: BlobId) -> Result<u64, FsBridgeError> {
+    let data = snapshot_blob(&blob_id)?;
+    if data.len() < 8 {
+        return Err(FsBridgeError::Invalid);
+    }
+    Ok(u64::from_le_bytes([
+        data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
+    ]))
+}
+
+#[inline]
+fn store_pressure_seen_seq(blob_id: BlobId, seq: u64) -> Result<(), FsBridgeError> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&seq.to_le_bytes());
+    BLOB_CACHE
+        .insert(blob_id, data)
+        .map_err(exofs_to_bridge_error)?;
+    let _ = BLOB_CACHE.mark_dirty(&blob_id);
+    Ok(())
+}
+
+/// Un événement de pression n'a pas encore été lu sur ce descripteur.
+#[inline]
+fn pressure_pending(blob_id: BlobId) -> bool {
+    let seq = crate::memory::utils::pressure::seq(crate::scheduler::timer::clock::monotonic_ns());
+    pressure_seen_seq(blob_id).is_ok_and(|seen| seen != seq)
+}
+
 #[inline]
 fn socket_blob_with_peer(peer: BlobId) -> Vec<u8> {
     let mut data = Vec::new();
@@ -1230,6 +1301,8 @@ fn fd_readiness(fd: u32, pid: u32) -> Result<(bool, bool), FsBridgeError> {
         readable = false;
     } else if readable && is_pseudo_blob(&entry.blob_id, PSEUDO_SOCKET_TAG) {
         readable = socket_payload_len(entry.blob_id) != 0;
+    } else if readable && is_pseudo_blob(&entry.blob_id, PSEUDO_PRESSURE_TAG) {
+        readable = pressure_pending(entry.blob_id);
     }
 
     Ok((readable, writable))
@@ -1762,6 +1835,20 @@ pub fn fs_read(fd: u32, buf_ptr: u64, count: usize, pid: u32) -> Result<i64, FsB
         return Err(FsBridgeError::WouldBlock);
     }
 
+    if is_pseudo_blob(&entry.blob_id, PSEUDO_PRESSURE_TAG) {
+        use crate::memory::utils::pressure::{self, PressureReport};
+        if count < size_of::<PressureReport>() {
+            return Err(FsBridgeError::Invalid);
+        }
+        let report = pressure::snapshot(crate::scheduler::timer::clock::monotonic_ns());
+        if pressure_seen_seq(entry.blob_id)? == report.seq {
+            return Err(FsBridgeError::WouldBlock);
+        }
+        write_user_typed(buf_ptr, report).map_err(|_| FsBridgeError::Fault)?;
+        store_pressure_seen_seq(entry.blob_id, report.seq)?;
+        return Ok(size_of::<PressureReport>() as i64);
+    }
+
     if is_pseudo_blob(&entry.blob_id, PSEUDO_SOCKET_TAG) {
         let data = read_socket_payload(entry.blob_id, count, true)?;
         if data.is_empty() {
@@ -2013,6 +2100,16 @@ pub fn fs_pwritev(
 /// `open(path, flags, mode)` → fd.
 #[inline]
 pub fn fs_open(path: &[u8], flags: u32, mode: u32, pid: u32) -> Result<i64, FsBridgeError> {
+    #[cfg(feature = "kvm")]
+    if path == KVM_DEVICE_PATH {
+        if !crate::process::core::registry::PROCESS_REGISTRY
+            .find_by_pid(crate::process::core::pid::Pid(pid))
+            .is_some_and(|pcb| pcb.is_root())
+        {
+            return Err(FsBridgeError::PermDenied);
+        }
+        return fs_kvm_open(KvmHandle::System, flags & (O_CLOEXEC | O_NONBLOCK), pid);
+    }
     if is_tty_path(path) {
         let fd_flags = fd_table_flags(flags, open_flags::O_RDWR);
         if let Some(logical_fd) = install_process_fd(pid, TTY_PTS0_HANDLE as u64, fd_flags) {
@@ -2060,7 +2157,9 @@ pub fn fs_open(path: &[u8], flags: u32, mode: u32, pid: u32) -> Result<i64, FsBr
         upsert_parent_entry(&parent_path, &leaf, blob_id, PATH_INDEX_KIND_FILE)?;
     }
     if exists {
+        let cached = BLOB_CACHE.contains(&blob_id);
         ensure_blob_exists(blob_id)?;
+        boot_preload::note_open(&normalized_path, blob_id, cached);
     }
     if fd_flags & open_flags::O_TRUNC != 0 {
         if !open_flags::can_write(fd_flags) {
@@ -3225,6 +3324,84 @@ pub fn fs_eventfd2(initval: u32, flags: u32, pid: u32) -> Result<i64, FsBridgeEr
     }
 }
 
+/// `exo_psi(OPEN, flags)` : descripteur lisible à chaque changement de
+/// niveau de pression mémoire ; `read` rend un `PressureReport`.
+#[inline]
+pub fn fs_pressure_open(flags: u32, pid: u32) -> Result<i64, FsBridgeError> {
+    if !is_fs_ready() {
+        return Err(FsBridgeError::NotReady);
+    }
+    if flags & !(O_CLOEXEC | O_NONBLOCK) != 0 {
+        return Err(FsBridgeError::Invalid);
+    }
+
+    let blob_id = next_pseudo_blob(PSEUDO_PRESSURE_TAG);
+    // Jamais lu : la première lecture rend l'état courant.
+    store_pressure_seen_seq(blob_id, u64::MAX)?;
+    let fd = OBJECT_TABLE
+        .open(blob_id, open_flags::O_RDONLY, 0, 0, pid as u64)
+        .map_err(exofs_to_bridge_error)?;
+    if process_has_fd_table(pid) {
+        if let Some(logical_fd) =
+            install_process_fd(pid, fd as u64, fd_table_flags(flags, open_flags::O_RDONLY))
+        {
+            Ok(logical_fd as i64)
+        } else {
+            let _ = OBJECT_TABLE.close(fd);
+            Err(FsBridgeError::NoMemory)
+        }
+    } else {
+        Ok(fd as i64)
+    }
+}
+
+/// Descripteur `/dev/exo-kvm` (système, VM ou vCPU) : le blob porte le
+/// `KvmHandle` encodé, les `ioctl` sont routés vers `arch::x86_64::kvm`.
+#[cfg(feature = "kvm")]
+pub fn fs_kvm_open(handle: KvmHandle, flags: u32, pid: u32) -> Result<i64, FsBridgeError> {
+    if !is_fs_ready() {
+        return Err(FsBridgeError::NotReady);
+    }
+    if flags & !(O_CLOEXEC | O_NONBLOCK) != 0 {
+        return Err(FsBridgeError::Invalid);
+    }
+
+    let blob_id = next_pseudo_blob(PSEUDO_KVM_TAG);
+    BLOB_CACHE
+        .insert(blob_id, handle.encode().to_vec())
+        .map_err(exofs_to_bridge_error)?;
+    let _ = BLOB_CACHE.mark_dirty(&blob_id);
+    let fd = OBJECT_TABLE
+        .open(blob_id, open_flags::O_RDWR, 0, 0, pid as u64)
+        .map_err(exofs_to_bridge_error)?;
+    if process_has_fd_table(pid) {
+        if let Some(logical_fd) =
+            install_process_fd(pid, fd as u64, fd_table_flags(flags, open_flags::O_RDWR))
+        {
+            Ok(logical_fd as i64)
+        } else {
+            let _ = OBJECT_TABLE.close(fd);
+            Err(FsBridgeError::NoMemory)
+        }
+    } else {
+        Ok(fd as i64)
+    }
+}
+
+/// `KvmHandle` porté par `fd`, ou `None` si ce n'est pas un descripteur KVM.
+#[cfg(feature = "kvm")]
+pub fn fs_kvm_handle(fd: u32, pid: u32) -> Result<Option<KvmHandle>, FsBridgeError> {
+    let entry = OBJECT_TABLE
+        .get(resolve_fd(pid, fd)?.handle)
+        .map_err(exofs_to_bridge_error)?;
+    if !is_pseudo_blob(&entry.blob_id, PSEUDO_KVM_TAG) {
+        return Ok(None);
+    }
+    KvmHandle::decode(&snapshot_blob(&entry.blob_id)?)
+        .map(Some)
+        .ok_or(FsBridgeError::Invalid)
+}
+
 /// `epoll_create1(flags)`.
 #[inline]
 pub fn fs_epoll_create1(flags: u32, pid: u32) -> Result<i64, FsBridgeError> {
@@ -3955,12 +4132,52 @@ pub fn fs_fadvise64(
     if advice > 5 {
         return Err(FsBridgeError::Invalid);
     }
-    let _ = OBJECT_TABLE
+    let entry = OBJECT_TABLE
         .get(resolve_fd(pid, fd)?.handle)
         .map_err(exofs_to_bridge_error)?;
+    if advice == POSIX_FADV_WILLNEED && !is_any_pseudo_blob(&entry.blob_id) {
+        ensure_blob_exists(entry.blob_id)?;
+    }
     Ok(0)
 }
 
+/// `readahead(fd, offset, count)` — recharge le blob dans `BLOB_CACHE`.
+///
+/// Un blob est lu en entier à l'ouverture : la fenêtre demandée est donc
+/// toujours couverte, et l'appel ne fait d'I/O que si le blob a été évincé
+/// depuis.
+pub fn fs_readahead(fd: u32, offset: u64, count: u64, pid: u32) -> Result<i64, FsBridgeError> {
+    if !is_fs_ready() {
+        return Err(FsBridgeError::NotReady);
+    }
+    let _ = (offset, count);
+    let entry = OBJECT_TABLE
+        .get(resolve_fd(pid, fd)?.handle)
+        .map_err(exofs_to_bridge_error)?;
+    if is_any_pseudo_blob(&entry.blob_id) {
+        return Err(FsBridgeError::Invalid);
+    }
+    ensure_blob_exists(entry.blob_id)?;
+    Ok(0)
+}
+
+/// Préchargement de boot : charge le blob d'un chemin existant dans
+/// `BLOB_CACHE` sans ouvrir de descripteur ni alimenter la trace.
+/// `Ok(1)` : lu sur disque ; `Ok(0)` : déjà en cache.
+pub fn fs_preload(path: &[u8]) -> Result<i64, FsBridgeError> {
+    if !is_fs_ready() {
+        return Err(FsBridgeError::NotReady);
+    }
+    let normalized_path = resolve_path_with_symlinks(path, true, false)?;
+    let (blob_id, _) = path_entry(&normalized_path)?;
+    let cached = BLOB_CACHE.contains(&blob_id);
+    if !cached {
+        ensure_blob_exists(blob_id)?;
+    }
+    boot_preload::note_prefetch(blob_id, !cached);
+    Ok(!cached as i64)
+}
+
 /// `ioctl(fd, request, arg)`.
 #[inline]
 pub fn fs_ioctl(fd: u32, request: u64, arg: u64, pid: u32) -> Result<i64, FsBridgeError> {
@@ -3979,6 +4196,12 @@ pub fn fs_ioctl(fd: u32, request: u64, arg: u64, pid: u32) -> Result<i64, FsBrid
                 eventfd_state(entry.blob_id)
                     .map(|(value, _)| if value == 0 { 0 } else { 8 })
                     .unwrap_or(0)
+            } else if is_pseudo_blob(&entry.blob_id, PSEUDO_PRESSURE_TAG) {
+                if pressure_pending(entry.blob_id) {
+                    size_of::<crate::memory::utils::PressureReport>()
+                } else {
+                    0
+                }
             } else if is_pseudo_blob(&entry.blob_id, PSEUDO_PIPE_TAG) {
                 blob_len(&entry.blob_id)
             } else if is_pseudo_blob(&entry.blob_id, PSEUDO_SOCKET_TAG) {
@@ -0,0 +1,249 @@
+//! # syscall/hot_path.rs — Table de saut directe guidée par le profil
+//!
+//! Le slow-path de `dispatch()` enchaîne la traduction compat Linux, les cas
+//! spéciaux (fork, execve, rt_sigreturn) puis le `match` de
+//! `table::get_handler`. Pour les quelques syscalls qui dominent la charge
+//! (read/write/futex/epoll_wait sur un serveur, ioctl/poll sur un
+//! compositeur), ce trajet est payé à chaque appel.
+//!
+//! Ce module profile les numéros qui atteignent la table et promeut les plus
+//! chauds dans `HOT` : un tableau de pointeurs de handlers indexé par numéro,
+//! consulté juste après le fast-path. Un syscall promu coûte un load + un
+//! saut indirect au lieu du trajet complet.
+//!
+//! ## Profil
+//! - `record(nr)` est appelé par le slow-path pour tout numéro servi tel quel
+//!   par la table (ni traduit, ni cas spécial) : un compteur par numéro.
+//! - Tous les `REBUILD_INTERVAL` appels profilés, un seul CPU reconstruit la
+//!   table (`REBUILDING`) : les `HOT_SLOTS` numéros les plus fréquents au-dessus
+//!   de `PROMOTE_MIN_HITS` sont promus, les autres rétrogradés, puis les
+//!   compteurs sont divisés par deux (décroissance exponentielle).
+//!
+//! ## Invariants
+//! - Un numéro promu a exactement le handler `get_handler(nr)` : la promotion
+//!   ne change jamais la sémantique, seulement le trajet.
+//! - Les numéros traduits par `compat::linux` ou traités en cas spécial ne
+//!   sont jamais profilés, donc jamais promus.
+//! - Sysctl `kernel.syscall.hot_table` à 0 : table vidée, profil figé.
+//!
+//! ## RÈGLE NO-ALLOC
+//! Tableaux statiques uniquement ; la reconstruction est O(SYSCALL_TABLE_SIZE).
+
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+
+use crate::syscall::numbers::SYSCALL_TABLE_SIZE;
+use crate::syscall::table::{get_handler, sys_enosys, SyscallHandler};
+use crate::sysctl::SysctlError;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Paramètres
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Nombre maximal de syscalls promus simultanément.
+pub const HOT_SLOTS: usize = 16;
+
+/// Appels profilés entre deux reconstructions.
+pub const REBUILD_INTERVAL: u64 = 4096;
+
+/// Appels minimum (fenêtre décroissante) pour mériter une promotion.
+pub const PROMOTE_MIN_HITS: u32 = 64;
+
+/// Valeur du sysctl `kernel.syscall.hot_table` (1 = actif).
+pub static HOT_TABLE_TUNABLE: AtomicU64 = AtomicU64::new(1);
+
+// ─────────────────────────────────────────────────────────────────────────────
+// État
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Handlers promus (`SyscallHandler` en `usize`, 0 = numéro froid).
+static HOT: [AtomicUsize; SYSCALL_TABLE_SIZE] = [const { AtomicUsize::new(0) }; SYSCALL_TABLE_SIZE];
+
+/// Compteurs de profil par numéro (fenêtre décroissante).
+static PROFILE: [AtomicU32; SYSCALL_TABLE_SIZE] = [const { AtomicU32::new(0) }; SYSCALL_TABLE_SIZE];
+
+static PROFILED: AtomicU64 = AtomicU64::new(0);
+static REBUILDING: AtomicBool = AtomicBool::new(false);
+
+static HOT_HITS: AtomicU64 = AtomicU64::new(0);
+static HOT_PROMOTIONS: AtomicU64 = AtomicU64::new(0);
+static HOT_DEMOTIONS: AtomicU64 = AtomicU64::new(0);
+static HOT_REBUILDS: AtomicU64 = AtomicU64::new(0);
+static HOT_PROMOTED: AtomicU64 = AtomicU64::new(0);
+
+/// Snapshot des compteurs de la table chaude.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct HotPathStats {
+    /// Appels servis par la table chaude
+    pub hits: u64,
+    /// Promotions cumulées
+    pub promotions: u64,
+    /// Rétrogradations cumulées
+    pub demotions: u64,
+    /// Reconstructions effectuées
+    pub rebuilds: u64,
+    /// Numéros actuellement promus
+    pub promoted: u64,
+}
+
+pub fn hot_path_stats() -> HotPathStats {
+    HotPathStats {
+        hits: HOT_HITS.load(Ordering::Relaxed),
+        promotions: HOT_PROMOTIONS.load(Ordering::Relaxed),
+        demotions: HOT_DEMOTIONS.load(Ordering::Relaxed),
+        rebuilds: HOT_REBUILDS.load(Ordering::Relaxed),
+        promoted: HOT_PROMOTED.load(Ordering::Relaxed),
+    }
+}
+
+#[inline(always)]
+fn enabled() -> bool {
+    HOT_TABLE_TUNABLE.load(Ordering::Relaxed) != 0
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Lookup (chemin chaud)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Handler promu pour `nr`, ou `None` si le numéro est froid.
+#[inline(always)]
+pub fn lookup(nr: u64) -> Option<SyscallHandler> {
+    let raw = HOT.get(nr as usize)?.load(Ordering::Relaxed);
+    if raw == 0 {
+        return None;
+    }
+    HOT_HITS.fetch_add(1, Ordering::Relaxed);
+    // SAFETY: seules des valeurs `get_handler(nr) as usize` sont publiées dans
+    // HOT (voir `rebuild`) ; ce sont des fonctions statiques du noyau.
+    Some(unsafe { core::mem::transmute::<usize, SyscallHandler>(raw) })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Profil et reconstruction
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Enregistre un appel servi tel quel par `table::get_handler(nr)`.
+#[inline]
+pub fn record(nr: u64) {
+    if !enabled() {
+        return;
+    }
+    let Some(counter) = PROFILE.get(nr as usize) else {
+        return;
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+    let seen = PROFILED.fetch_add(1, Ordering::Relaxed) + 1;
+    if seen.is_multiple_of(REBUILD_INTERVAL) {
+        rebuild();
+    }
+}
+
+/// Insère `(hits, nr)` dans `top`, trié par fréquence décroissante.
+fn insert_top(top: &mut [(u32, u16); HOT_SLOTS], hits: u32, nr: u16) {
+    if hits <= top[HOT_SLOTS - 1].0 {
+        return;
+    }
+    let mut i = HOT_SLOTS - 1;
+    while i > 0 && top[i - 1].0 < hits {
+        top[i] = top[i - 1];
+        i -= 1;
+    }
+    top[i] = (hits, nr);
+}
+
+/// Sélectionne les numéros à promouvoir d'après les compteurs `hits`.
+fn select_hot(hits: impl Iterator<Item = u32>) -> [(u32, u16); HOT_SLOTS] {
+    let mut top = [(0u32, 0u16); HOT_SLOTS];
+    for (nr, count) in hits.enumerate() {
+        if count >= PROMOTE_MIN_HITS {
+            insert_top(&mut top, count, nr as u16);
+        }
+    }
+    top
+}
+
+/// Recalcule la table chaude depuis le profil courant.
+///
+/// Sans verrou bloquant : si un autre CPU reconstruit déjà, on abandonne.
+pub fn rebuild() {
+    if REBUILDING
+        .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        return;
+    }
+
+    let top = select_hot(PROFILE.iter().map(|c| c.load(Ordering::Relaxed)));
+    let is_hot = |nr: usize| top.iter().any(|&(hits, n)| hits != 0 && n as usize == nr);
+
+    let mut promoted = 0u64;
+    for (nr, slot) in HOT.iter().enumerate() {
+        let current = slot.load(Ordering::Relaxed);
+        if is_hot(nr) {
+            let handler = get_handler(nr as u64);
+            if handler as *const () == sys_enosys as *const () {
+                continue;
+            }
+            if current == 0 {
+                slot.store(handler as usize, Ordering::Relaxed);
+                HOT_PROMOTIONS.fetch_add(1, Ordering::Relaxed);
+            }
+            promoted += 1;
+        } else if current != 0 {
+            slot.store(0, Ordering::Relaxed);
+            HOT_DEMOTIONS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    for counter in PROFILE.iter() {
+        let hits = counter.load(Ordering::Relaxed);
+        counter.store(hits / 2, Ordering::Relaxed);
+    }
+
+    HOT_PROMOTED.store(promoted, Ordering::Relaxed);
+    HOT_REBUILDS.fetch_add(1, Ordering::Relaxed);
+    REBUILDING.store(false, Ordering::Release);
+}
+
+/// Vide la table chaude (les appels repassent par le slow-path complet).
+pub fn flush() {
+    for slot in HOT.iter() {
+        if slot.swap(0, Ordering::Relaxed) != 0 {
+            HOT_DEMOTIONS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    HOT_PROMOTED.store(0, Ordering::Relaxed);
+}
+
+/// Hook sysctl `kernel.syscall.hot_table` : désactiver vide la table.
+pub fn on_hot_table_change(_old: u64, new: u64) -> Result<(), SysctlError> {
+    if new == 0 {
+        flush();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_hot_orders_by_hits() {
+        let mut hits = [0u32; 64];
+        hits[0] = 500; // read
+        hits[1] = 900; // write
+        hits[7] = PROMOTE_MIN_HITS - 1; // poll, sous le seuil
+        hits[23] = 70; // select
+        let top = select_hot(hits.iter().copied());
+        assert_eq!(top[0], (900, 1));
+        assert_eq!(top[1], (500, 0));
+        assert_eq!(top[2], (70, 23));
+        assert_eq!(top[3], (0, 0));
+    }
+
+    #[test]
+    fn test_select_hot_keeps_hottest_slots() {
+        let hits: [u32; 40] = core::array::from_fn(|nr| 100 + nr as u32);
+        let top = select_hot(hits.iter().copied());
+        assert_eq!(top[0], (139, 39));
+        assert_eq!(top[HOT_SLOTS - 1], (124, 24));
+    }
+}
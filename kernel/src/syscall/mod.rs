@@ -47,6 +47,7 @@ pub mod dispatch;
 pub mod fast_path;
 pub mod fixup;
 pub mod fs_bridge;
+pub mod hot_path;
 pub mod net_bridge;
 pub mod numbers;
 pub mod table;
@@ -128,6 +129,7 @@ pub use numbers::{
     SYS_EXO_PHOENIX_STATE_GET,
     SYS_EXO_PHOENIX_STATE_SET,
     SYS_EXO_PROCESS_LIST,
+    SYS_EXO_SYSCTL,
     SYS_FORK,
     SYS_FRAMEBUFFER_INFO,
     SYS_FSTAT,
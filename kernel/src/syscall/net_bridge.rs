@@ -28,6 +28,7 @@ pub const NET_OP_SETSOCKOPT: u32 = 0x4E0C;
 pub const NET_OP_GETSOCKOPT: u32 = 0x4E0D;
 pub const NET_OP_CLOSE: u32 = 0x4E0E;
 pub const NET_OP_GETPEERNAME: u32 = 0x4E0F;
+pub const NET_OP_APP_USAGE: u32 = 0x4E10;
 
 const AF_UNIX: i32 = 1;
 const AF_INET: u16 = 2;
@@ -77,6 +78,22 @@ pub struct NetReply {
 
 const _: () = assert!(core::mem::size_of::<NetReply>() == 48);
 
+pub const NET_APP_NAME_LEN: usize = 16;
+
+/// Consommation réseau cumulée d'une application (`exo_net_usage`).
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct NetAppUsage {
+    /// Nom du processus, vide pour le groupe « autres ».
+    pub name: [u8; NET_APP_NAME_LEN],
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub sockets: u32,
+    pub _pad: u32,
+}
+
+const _: () = assert!(core::mem::size_of::<NetAppUsage>() == 40);
+
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
 struct LinuxIovec {
@@ -398,6 +415,22 @@ pub fn net_socket(domain: i32, ty: i32, protocol: i32) -> Result<i64, i64> {
     Ok(reply_u64(&reply, 0) as i64)
 }
 
+/// Entrée `index` de la comptabilité par application et index suivant.
+pub fn net_app_usage(index: u64) -> Result<(NetAppUsage, u64), i64> {
+    let reply = dispatch(NET_OP_APP_USAGE, 0, index, 0, 0, 0)?;
+    let mut usage = NetAppUsage {
+        name: [0; NET_APP_NAME_LEN],
+        rx_bytes: reply_u64(&reply, 16),
+        tx_bytes: reply_u64(&reply, 24),
+        sockets: reply_u32(&reply, 32),
+        _pad: 0,
+    };
+    usage
+        .name
+        .copy_from_slice(&reply.payload[..NET_APP_NAME_LEN]);
+    Ok((usage, reply.status as u64))
+}
+
 pub fn socket_handle_from_raw(raw: u64) -> Option<i32> {
     if raw > i32::MAX as u64 {
         return None;
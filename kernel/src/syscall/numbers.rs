@@ -12,7 +12,9 @@
 //! - [442..499] : réservés pour usage futur
 //! - [500..520] : ExoFS syscalls natifs
 //! - [521]      : informations framebuffer de boot pour fb_server Ring1
-//! - [522..529] : réservés pour usage futur
+//! - [522]      : EDID de l'écran pour la couche d'affichage
+//! - [523]      : boot réseau (PXE / HTTP) transmis par exo-boot
+//! - [524..529] : réservés pour usage futur
 //! - [530..549] : GI-03 drivers syscalls
 //! - 550        : SYSCALL_TABLE_SIZE (taille totale de la table)
 //!
@@ -35,8 +37,8 @@
 // ─────────────────────────────────────────────────────────────────────────────
 
 /// Taille de la table syscall (un slot par numéro possible).
-/// 550 = couvre POSIX (0–499) + ExoFS (500–520) + framebuffer (521)
-/// + GI-03 drivers (530–549).
+/// 550 = couvre POSIX (0–499) + ExoFS (500–520) + affichage (521–522)
+/// + boot réseau (523) + GI-03 drivers (530–549).
 pub const SYSCALL_TABLE_SIZE: usize = 550;
 
 /// Numéro invalide (retourne -ENOSYS)
@@ -230,6 +232,8 @@ pub const SYS_DELETE_MODULE: u64 = 176;
 pub const SYS_QUERY_MODULE: u64 = 177;
 pub const SYS_QUOTACTL: u64 = 179;
 pub const SYS_GETTID: u64 = 186;
+/// `readahead(fd, offset, count)` — ramener le fichier dans le cache
+pub const SYS_READAHEAD: u64 = 187;
 pub const SYS_TKILL: u64 = 200;
 pub const SYS_TIME: u64 = 201;
 pub const SYS_FUTEX: u64 = 202;
@@ -255,6 +259,20 @@ pub const SYS_EPOLL_CTL: u64 = 233;
 pub const SYS_TGKILL: u64 = 234;
 pub const SYS_UTIMES: u64 = 235;
 pub const SYS_WAITID: u64 = 247;
+/// `ioprio_set(which, who, ioprio)` — classe/niveau d'I/O disque
+pub const SYS_IOPRIO_SET: u64 = 251;
+/// `ioprio_get(which, who)` → valeur `ioprio` brute
+pub const SYS_IOPRIO_GET: u64 = 252;
+
+/// `which` d'ioprio : `who` est un pid (0 = appelant). Seule cible supportée.
+pub const IOPRIO_WHO_PROCESS: u64 = 1;
+/// `ioprio = classe << 13 | niveau` ; classe 0 = jamais fixée (BestEffort 4).
+pub const IOPRIO_CLASS_NONE: u64 = 0;
+/// Servi avant tout le monde — root uniquement.
+pub const IOPRIO_CLASS_RT: u64 = 1;
+pub const IOPRIO_CLASS_BE: u64 = 2;
+/// Servi seulement quand le disque est libre.
+pub const IOPRIO_CLASS_IDLE: u64 = 3;
 pub const SYS_OPENAT: u64 = 257;
 pub const SYS_MKDIRAT: u64 = 258;
 pub const SYS_MKNODAT: u64 = 259;
@@ -299,7 +317,8 @@ pub const SYS_EPOLL_PWAIT2: u64 = 441;
 // Bloc 300–399 : Syscalls natifs Exo-OS
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Envoyer un message IPC natif Exo-OS
+/// Envoyer un message IPC natif Exo-OS (classe de priorité et échéance
+/// optionnelles en arguments 5 et 6)
 pub const SYS_EXO_IPC_SEND: u64 = 300;
 /// Recevoir un message IPC natif Exo-OS
 pub const SYS_EXO_IPC_RECV: u64 = 301;
@@ -374,9 +393,131 @@ pub const SYS_EXO_PROCESS_LIST: u64 = 351;
 pub const SYS_EXO_PHOENIX_STATE_SET: u64 = 352;
 /// Lire l'état Phoenix global
 pub const SYS_EXO_PHOENIX_STATE_GET: u64 = 353;
-/// Sonde eBPF Exo-OS
+/// Lire / écrire / énumérer les tunables runtime (`/proc/sys`)
+pub const SYS_EXO_SYSCTL: u64 = 354;
+
+/// `exo_sysctl(READ, name, name_len, buf, buf_len)` → octets écrits (`"valeur\n"`)
+pub const EXO_SYSCTL_READ: u64 = 0;
+/// `exo_sysctl(WRITE, name, name_len, text, text_len)` → 0 (root requis)
+pub const EXO_SYSCTL_WRITE: u64 = 1;
+/// `exo_sysctl(LIST, index, 0, buf, buf_len)` → longueur du nom, ENOENT en fin
+pub const EXO_SYSCTL_LIST: u64 = 2;
+/// Trace des fichiers ouverts au boot et préchargement (service d'init)
+pub const SYS_EXO_PRELOAD: u64 = 355;
+
+/// `exo_preload(START)` → 0 : nouvelle fenêtre d'enregistrement (root)
+pub const EXO_PRELOAD_START: u64 = 0;
+/// `exo_preload(STOP)` → 0 : ferme la fenêtre (root)
+pub const EXO_PRELOAD_STOP: u64 = 1;
+/// `exo_preload(TRACE, offset, buf, buf_len)` → octets (`chemin\n`…), 0 en fin (root)
+pub const EXO_PRELOAD_TRACE: u64 = 2;
+/// `exo_preload(FETCH, path)` → 1 lu sur disque, 0 déjà en cache (root)
+pub const EXO_PRELOAD_FETCH: u64 = 3;
+/// `exo_preload(STATS, buf, buf_len)` → taille de `PreloadSnapshot`
+pub const EXO_PRELOAD_STATS: u64 = 4;
+/// Régions de mémoire partagée créées depuis userspace (caches, atlas)
+pub const SYS_EXO_SHM: u64 = 356;
+
+/// `exo_shm(CREATE, size, flags)` → identifiant de région (pages remises à zéro)
+pub const EXO_SHM_CREATE: u64 = 0;
+/// `exo_shm(MAP, id, writable)` → adresse ; écriture réservée au créateur
+pub const EXO_SHM_MAP: u64 = 1;
+/// `exo_shm(UNMAP, addr)` → 0
+pub const EXO_SHM_UNMAP: u64 = 2;
+/// `exo_shm(DESTROY, id)` → 0, EBUSY tant qu'un mapping reste actif
+pub const EXO_SHM_DESTROY: u64 = 3;
+/// Flag de CREATE : tout processus peut mapper la région en lecture seule
+pub const EXO_SHM_PUBLIC_READ: u64 = 1;
+/// Pression mémoire façon PSI et OOM à la demande (démon de pression)
+pub const SYS_EXO_PSI: u64 = 357;
+
+/// `exo_psi(OPEN, flags)` → fd lisible à chaque changement de niveau
+pub const EXO_PSI_OPEN: u64 = 0;
+/// `exo_psi(READ, buf, buf_len)` → taille de `PressureReport`
+pub const EXO_PSI_READ: u64 = 1;
+/// `exo_psi(OOM_KILL)` → 0 si une victime a été tuée, EAGAIN sinon (root)
+pub const EXO_PSI_OOM_KILL: u64 = 2;
+/// Gel des applications d'arrière-plan (démon de session)
+pub const SYS_EXO_FREEZE: u64 = 358;
+
+/// `exo_freeze(FREEZE, pid)` → 1 si gelé, 0 s'il l'était déjà
+pub const EXO_FREEZE_FREEZE: u64 = 0;
+/// `exo_freeze(THAW, pid)` → 1 si dégelé, 0 s'il n'était pas gelé
+pub const EXO_FREEZE_THAW: u64 = 1;
+/// `exo_freeze(STATE, pid)` → 1 si gelé, 0 sinon
+pub const EXO_FREEZE_STATE: u64 = 2;
+/// Consommation réseau cumulée par application (network_server)
+///
+/// `exo_net_usage(index, buf, buf_len)` → index suivant, ENOENT en fin
+pub const SYS_EXO_NET_USAGE: u64 = 359;
+/// Programmes bpf vérifiés et maps partagées (cf. `bpf`), root uniquement
 pub const SYS_EXO_BPF: u64 = 360;
 
+/// `exo_bpf(MAP_CREATE, kind, key_size, value_size, max_entries)` → id de map
+pub const EXO_BPF_MAP_CREATE: u64 = 0;
+/// `exo_bpf(MAP_LOOKUP, map, key, value_out)` → 0, ENOENT si absente
+pub const EXO_BPF_MAP_LOOKUP: u64 = 1;
+/// `exo_bpf(MAP_UPDATE, map, key, value)` → 0
+pub const EXO_BPF_MAP_UPDATE: u64 = 2;
+/// `exo_bpf(MAP_DELETE, map, key)` → 0
+pub const EXO_BPF_MAP_DELETE: u64 = 3;
+/// `exo_bpf(MAP_NEXT_KEY, map, key | 0, next_out)` → 0, ENOENT en fin
+pub const EXO_BPF_MAP_NEXT_KEY: u64 = 4;
+/// `exo_bpf(MAP_FREE, map)` → 0
+pub const EXO_BPF_MAP_FREE: u64 = 5;
+/// `exo_bpf(PROG_LOAD, type, insns, insn_count, log, log_len)` → id de
+/// programme, EACCES si le vérificateur le refuse
+pub const EXO_BPF_PROG_LOAD: u64 = 6;
+/// `exo_bpf(PROG_UNLOAD, prog)` → 0
+pub const EXO_BPF_PROG_UNLOAD: u64 = 7;
+/// `exo_bpf(PROG_ATTACH, prog, point)` → 0, EEXIST si le point est occupé
+pub const EXO_BPF_PROG_ATTACH: u64 = 8;
+/// `exo_bpf(PROG_DETACH, point)` → 0
+pub const EXO_BPF_PROG_DETACH: u64 = 9;
+/// Introspection des mailboxes IPC raw (profondeur, débit, pertes, latence)
+///
+/// `exo_ipc_stat(index, buf, buf_len)` → index suivant, ENOENT en fin ;
+/// hors root, seules les mailboxes de l'appelant
+pub const SYS_EXO_IPC_STAT: u64 = 361;
+/// Traçage noyau : marqueurs utilisateur et export Chrome trace-event
+pub const SYS_EXO_TRACE: u64 = 362;
+
+/// `exo_trace(MARK, name, name_len, phase)` → 0 (ignoré si la trace est coupée)
+pub const EXO_TRACE_MARK: u64 = 0;
+/// `exo_trace(EXPORT)` → événements écrits sur la console de debug (root)
+pub const EXO_TRACE_EXPORT: u64 = 1;
+/// Phases de `EXO_TRACE_MARK` : instant, début et fin de tranche.
+pub const EXO_TRACE_INSTANT: u64 = 0;
+pub const EXO_TRACE_BEGIN: u64 = 1;
+pub const EXO_TRACE_END: u64 = 2;
+/// État de reprise après veille (cf. `scheduler::timer::clock::note_resume`)
+///
+/// `exo_sleep_state(buf, buf_len)` → 24 octets : génération, dernière
+/// veille (ns), veille cumulée (ns), u64 LE
+pub const SYS_EXO_SLEEP_STATE: u64 = 363;
+/// Drivers Ring 3 à droits par claim (GI-03 étendu, bloc 530–549 plein)
+///
+/// `pci_claim_device(bdf, owner_pid, rights)` → nombre de BARs claimés (root)
+pub const SYS_PCI_CLAIM_DEVICE: u64 = 364;
+/// `pci_map_bar(bar, size_out)` → adresse virtuelle du BAR (`DEV_MMIO`)
+pub const SYS_PCI_MAP_BAR: u64 = 365;
+/// `irq_eventfd(irq, source_kind, eventfd)` → reg_id (`DEV_IRQ`, edge/MSI)
+pub const SYS_IRQ_EVENTFD: u64 = 366;
+/// `dma_buffer(size, direction, virt_out)` → IOVA dans le domaine IOMMU (`DEV_DMA`)
+pub const SYS_DMA_BUFFER: u64 = 367;
+/// Correctifs à chaud de fonctions noyau (modules signés, cf. `livepatch`)
+pub const SYS_EXO_LIVEPATCH: u64 = 368;
+
+/// `exo_livepatch(APPLY, module, module_len)` → index du site patché (root)
+pub const EXO_LIVEPATCH_APPLY: u64 = 0;
+/// `exo_livepatch(REVERT, name, name_len)` → 0 (root)
+pub const EXO_LIVEPATCH_REVERT: u64 = 1;
+/// Inspection de la table de capabilities d'un processus (outil `exo-caps`)
+///
+/// `exo_cap_list(pid, index, buf, buf_len)` → slot suivant, ENOENT en fin de
+/// table ; `pid = 0` désigne l'appelant, un autre processus exige root
+pub const SYS_EXO_CAP_LIST: u64 = 369;
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Bloc 500–518 : ExoFS natif (filesystem objet ExoOS)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -440,6 +581,15 @@ pub const SYS_EXOFS_READDIR: u64 = 520;
 /// Signature : (out_ptr: *mut FramebufferInfoWire) -> 0
 pub const SYS_FRAMEBUFFER_INFO: u64 = 521;
 
+/// EDID de l'écran transmis par exo-boot (modes, taille physique, HDR).
+/// Signature : (buf_ptr, buf_len) → taille de l'EDID, ENOENT sans EDID
+pub const SYS_DISPLAY_EDID: u64 = 522;
+
+/// Boot réseau (PXE/TFTP ou UEFI HTTP) signalé par exo-boot.
+/// Signature : () → IPv4 du serveur de boot (u32 big-endian, 0 si inconnue),
+/// ENOENT en boot disque
+pub const SYS_NETBOOT_INFO: u64 = 523;
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Bloc 530–549 : GI-03 Drivers (IRQ / DMA / PCI / IOMMU / I/O ports)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -24,8 +24,8 @@
 extern crate alloc;
 
 use crate::syscall::errno::{
-    E2BIG, EACCES, EAGAIN, EBUSY, EEXIST, EFAULT, EINTR, EINVAL, EMSGSIZE, ENOENT, ENOMEM, ENOSYS,
-    EPERM,
+    E2BIG, EACCES, EAGAIN, EBADMSG, EBUSY, EEXIST, EFAULT, EINTR, EINVAL, EMSGSIZE, ENOENT, ENOMEM,
+    ENOSPC, ENOSYS, EPERM, ERANGE, ESRCH,
 };
 use crate::syscall::fast_path::Timespec;
 use crate::syscall::numbers::*;
@@ -743,6 +743,23 @@ pub fn sys_ioctl(fd: u64, request: u64, arg: u64, _a4: u64, _a5: u64, _a6: u64)
     };
     use crate::syscall::fs_bridge;
     let pid = current_pid_u32();
+    #[cfg(feature = "kvm")]
+    match fs_bridge::fs_kvm_handle(fd as u32, pid) {
+        Ok(Some(handle)) => {
+            use crate::arch::x86_64::kvm::{self, IoctlOutcome};
+            // Descripteurs de VM/vCPU close-on-exec, comme sous Linux.
+            const O_CLOEXEC: u32 = 0o2000000;
+            return match kvm::ioctl(handle, request, arg, pid) {
+                Ok(IoctlOutcome::Value(v)) => v,
+                Ok(IoctlOutcome::NewFd(h)) => {
+                    fs_bridge::bridge_result(fs_bridge::fs_kvm_open(h, O_CLOEXEC, pid))
+                }
+                Err(e) => e.to_errno(),
+            };
+        }
+        Ok(None) => {}
+        Err(e) => return fs_bridge::bridge_result(Err(e)),
+    }
     fs_bridge::bridge_result(fs_bridge::fs_ioctl(fd as u32, request, arg, pid))
 }
 
@@ -1628,6 +1645,18 @@ pub fn sys_fadvise64(fd: u64, offset: u64, len: u64, advice: u64, _a5: u64, _a6:
     ))
 }
 
+/// `readahead(fd, offset, count)`.
+pub fn sys_readahead(fd: u64, offset: u64, count: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_READAHEAD);
+    let fd = match validate_fd(fd) {
+        Ok(f) => f,
+        Err(e) => return e.to_errno(),
+    };
+    use crate::syscall::fs_bridge;
+    let pid = current_pid_u32();
+    fs_bridge::bridge_result(fs_bridge::fs_readahead(fd as u32, offset, count, pid))
+}
+
 /// `mknod(path, mode, dev)`.
 pub fn sys_mknod(path_ptr: u64, mode: u64, dev: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
     stat_inc(SYS_MKNOD);
@@ -2420,6 +2449,9 @@ pub fn sys_exit_group(status: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64, _a6:
         }
         crate::process::lifecycle::exit::close_all_pid_vfs(pcb.pid.0);
         crate::drivers::driver_do_exit(pcb.pid.0);
+        #[cfg(feature = "kvm")]
+        crate::arch::x86_64::kvm::release_for_pid(pcb.pid.0);
+        crate::ipc::shared_memory::user_region::release_for_pid(pcb.pid.0);
 
         pcb.for_each_thread_ptr(|thread_ptr| {
             let thread = &mut *thread_ptr;
@@ -2488,6 +2520,61 @@ pub fn sys_waitid(
     crate::syscall::handlers::process::sys_waitid(idtype, id, infop, options, rusage_ptr, 0)
 }
 
+/// Cible d'`ioprio_set`/`ioprio_get` : seul `IOPRIO_WHO_PROCESS` est géré
+/// (pas de groupes ni d'utilisateurs). Agir sur un autre processus exige
+/// root ou le même uid réel.
+fn ioprio_target(
+    which: u64,
+    who: u64,
+) -> Result<&'static crate::process::core::pcb::ProcessControlBlock, i64> {
+    if which != IOPRIO_WHO_PROCESS || who > u32::MAX as u64 {
+        return Err(EINVAL);
+    }
+    let caller = current_pid_u32();
+    let target = if who == 0 { caller } else { who as u32 };
+    let pcb = PROCESS_REGISTRY.find_by_pid(Pid(target)).ok_or(ESRCH)?;
+    if target != caller && caller != 0 {
+        let me = PROCESS_REGISTRY.find_by_pid(Pid(caller)).ok_or(ESRCH)?;
+        let same_user = me.creds.lock().uid == pcb.creds.lock().uid;
+        if !same_user && !me.is_root() {
+            return Err(EPERM);
+        }
+    }
+    Ok(pcb)
+}
+
+/// `ioprio_set(which, who, ioprio)` — priorité d'I/O disque du processus,
+/// appliquée par l'ordonnanceur de `fs::exofs::storage::io_scheduler`.
+/// La classe RealTime est réservée à root. Héritée au fork.
+pub fn sys_ioprio_set(which: u64, who: u64, ioprio: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_IOPRIO_SET);
+    use crate::fs::exofs::storage::io_scheduler::{IoClass, IoPriority};
+    let Some(prio) = u32::try_from(ioprio).ok().and_then(IoPriority::from_raw) else {
+        return EINVAL;
+    };
+    let pcb = match ioprio_target(which, who) {
+        Ok(pcb) => pcb,
+        Err(e) => return e,
+    };
+    if prio.class == IoClass::RealTime {
+        if !caller_is_root() {
+            return EPERM;
+        }
+    }
+    // Classe « none » conservée telle quelle : ioprio_get la rend à l'identique.
+    pcb.set_io_priority(if ioprio == 0 { 0 } else { prio.to_raw() });
+    0
+}
+
+/// `ioprio_get(which, who)` → valeur brute (0 : jamais fixée).
+pub fn sys_ioprio_get(which: u64, who: u64, _a3: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_IOPRIO_GET);
+    match ioprio_target(which, who) {
+        Ok(pcb) => pcb.io_priority() as i64,
+        Err(e) => e,
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Handlers Signaux (délégués vers process/signal/)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -2606,9 +2693,13 @@ pub fn sys_clock_nanosleep(
     if ts.tv_sec < 0 || ts.tv_nsec < 0 || ts.tv_nsec >= 1_000_000_000 {
         return EINVAL;
     }
-    let target_ns = (ts.tv_sec as u64)
+    let mut target_ns = (ts.tv_sec as u64)
         .saturating_mul(1_000_000_000)
         .saturating_add(ts.tv_nsec as u64);
+    if clk_id == CLOCK_BOOTTIME {
+        // Échéance veille comprise → échéance ktime.
+        target_ns = crate::scheduler::timer::clock::boottime_to_monotonic_ns(target_ns);
+    }
     if !crate::scheduler::timer::sleep_until_ns(target_ns) {
         return EINTR;
     }
@@ -2753,6 +2844,40 @@ pub fn sys_framebuffer_info(out_ptr: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64
     }
 }
 
+/// `display_edid(buf, buf_len)` -> taille de l'EDID de boot, copié dans `buf`
+/// jusqu'à `buf_len` octets. Lisible par tout processus (compositeur,
+/// réglages) : l'EDID ne décrit que l'écran.
+pub fn sys_display_edid(buf_ptr: u64, buf_len: u64, _a3: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_DISPLAY_EDID);
+    let mut edid = [0u8; crate::arch::x86_64::framebuffer_early::BOOT_EDID_MAX];
+    let size = crate::arch::x86_64::framebuffer_early::boot_edid(&mut edid);
+    if size == 0 {
+        return ENOENT;
+    }
+    let len = (buf_len as usize).min(size);
+    if len == 0 {
+        return size as i64;
+    }
+    let buf = match UserBuf::validate(buf_ptr, len, edid.len()) {
+        Ok(buf) => buf,
+        Err(e) => return e.to_errno(),
+    };
+    match buf.write_from(&edid[..len]) {
+        Ok(()) => size as i64,
+        Err(e) => e.to_errno(),
+    }
+}
+
+/// `netboot_info()` -> IPv4 du serveur de boot (big-endian, 0 si inconnue),
+/// `ENOENT` en boot disque. init choisit ainsi une racine réseau.
+pub fn sys_netboot_info(_a1: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_NETBOOT_INFO);
+    match crate::arch::x86_64::boot::netboot::server() {
+        Some(addr) => u32::from_be_bytes(addr) as i64,
+        None => ENOENT,
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Handlers IPC natifs Exo-OS (bloc 300+)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -2911,20 +3036,27 @@ fn primary_ipc_endpoint_for_owner(owner_pid: u32) -> Option<u64> {
         .map(|entry| entry.endpoint)
 }
 
-/// `exo_ipc_send(endpoint, msg_ptr, msg_len, flags)`.
+/// `exo_ipc_send(endpoint, msg_ptr, msg_len, flags, priority, deadline_ms)`.
+///
+/// `priority` : 0 normal, 1 haute (contrôle temps réel), 2 bulk.
+/// `deadline_ms` : 0 = aucune ; sinon le message est abandonné s'il n'a pas
+/// été reçu dans ce délai.
 pub fn sys_exo_ipc_send(
     endpoint: u64,
     msg_ptr: u64,
     msg_len: u64,
     flags: u64,
-    _a5: u64,
-    _a6: u64,
+    priority: u64,
+    deadline_ms: u64,
 ) -> i64 {
     stat_inc(SYS_EXO_IPC_SEND);
     let len = msg_len as usize;
     if len > crate::ipc::core::constants::MAX_MSG_SIZE {
         return E2BIG;
     }
+    let Some(priority) = crate::ipc::channel::RawPriority::from_u64(priority) else {
+        return EINVAL;
+    };
     if let Err(errno) = enforce_direct_ipc_policy(endpoint) {
         return errno;
     }
@@ -2999,7 +3131,13 @@ pub fn sys_exo_ipc_send(
     // est encapsulée dans validate_ipc_envelope_auth() qui retourne IpcEnvelopeAuth::ValidToken
     // seulement si le token a passé check_token_owner(). Ici on utilise send_raw car
     // la vérification de capability a déjà été faite dans la fonction validate.
-    match crate::ipc::channel::raw::send_raw(endpoint_id, &payload, raw_flags) {
+    match crate::ipc::channel::raw::send_raw_prio(
+        endpoint_id,
+        &payload,
+        raw_flags,
+        priority,
+        crate::ipc::channel::raw::deadline_after_ms(deadline_ms),
+    ) {
         Ok(_) => 0,
         Err(err) => ipc_error_to_errno(err),
     }
@@ -3146,6 +3284,7 @@ pub fn sys_exo_ipc_create(
             let _ = release_ipc_endpoint_owner(endpoint, caller_pid);
             return ipc_error_to_errno(err);
         }
+        crate::ipc::channel::raw::mailbox_set_label(ep, &name);
         if let Some(class) = service_class_for_endpoint_name(&name) {
             let _ = crate::security::register_service_class(Pid(caller_pid), class);
         }
@@ -3249,6 +3388,15 @@ fn current_pid_u32() -> u32 {
     }
 }
 
+/// Appelant noyau (pid 0) ou processus root.
+fn caller_is_root() -> bool {
+    let caller = current_pid_u32();
+    caller == 0
+        || PROCESS_REGISTRY
+            .find_by_pid(Pid(caller))
+            .is_some_and(|pcb| pcb.is_root())
+}
+
 fn process_name_eq(name: &[u8; EXO_PROCESS_NAME_LEN], expected: &[u8]) -> bool {
     let mut len = 0usize;
     while len < name.len() && name[len] != 0 {
@@ -4297,6 +4445,756 @@ pub fn sys_exo_phoenix_state_get(
     crate::exophoenix::state() as u8 as i64
 }
 
+fn sysctl_errno(err: crate::sysctl::SysctlError) -> i64 {
+    use crate::sysctl::SysctlError;
+    match err {
+        SysctlError::NotFound => ENOENT,
+        SysctlError::ReadOnly | SysctlError::PermissionDenied => EPERM,
+        SysctlError::InvalidValue | SysctlError::InvalidName => EINVAL,
+        SysctlError::OutOfRange => ERANGE,
+        SysctlError::Rejected => EBUSY,
+        SysctlError::RegistryFull | SysctlError::Duplicate => EINVAL,
+    }
+}
+
+/// Nom de tunable (pointé ou `/proc/sys/...`) copié depuis userspace.
+const SYSCTL_PATH_MAX: usize = 9 + crate::sysctl::SYSCTL_NAME_MAX;
+/// Texte de valeur accepté en écriture (`0x` + 16 chiffres + blancs).
+const SYSCTL_TEXT_MAX: usize = 32;
+
+/// `exo_sysctl(op, a2, a3, buf, buf_len)` — accès aux tunables runtime.
+///
+/// Lecture et énumération ouvertes à tous ; écriture réservée à root.
+pub fn sys_exo_sysctl(op: u64, a2: u64, a3: u64, buf_ptr: u64, buf_len: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_EXO_SYSCTL);
+    let buf_len = buf_len as usize;
+
+    if op == EXO_SYSCTL_LIST {
+        let mut name = [0u8; crate::sysctl::SYSCTL_NAME_MAX];
+        let mut name_len = None;
+        crate::sysctl::for_each(|idx, tunable| {
+            if idx as u64 == a2 {
+                let bytes = tunable.name.as_bytes();
+                name[..bytes.len()].copy_from_slice(bytes);
+                name_len = Some(bytes.len());
+            }
+        });
+        let Some(len) = name_len else {
+            return ENOENT;
+        };
+        if buf_len < len {
+            return ERANGE;
+        }
+        return match UserBuf::validate(buf_ptr, len, crate::sysctl::SYSCTL_NAME_MAX) {
+            Ok(buf) => match buf.write_from(&name[..len]) {
+                Ok(()) => len as i64,
+                Err(e) => e.to_errno(),
+            },
+            Err(e) => e.to_errno(),
+        };
+    }
+
+    let name_len = a3 as usize;
+    let name_buf = match UserBuf::validate(a2, name_len, SYSCTL_PATH_MAX) {
+        Ok(b) => b,
+        Err(e) => return e.to_errno(),
+    };
+    let mut name = [0u8; SYSCTL_PATH_MAX];
+    if let Err(e) = name_buf.read_into(&mut name[..name_len]) {
+        return e.to_errno();
+    }
+    let name = &name[..name_len];
+
+    match op {
+        EXO_SYSCTL_READ => {
+            let mut text = [0u8; SYSCTL_TEXT_MAX];
+            let len = match crate::sysctl::read_by_name(name, &mut text) {
+                Ok(n) => n,
+                Err(e) => return sysctl_errno(e),
+            };
+            if buf_len < len {
+                return ERANGE;
+            }
+            match UserBuf::validate(buf_ptr, len, SYSCTL_TEXT_MAX) {
+                Ok(buf) => match buf.write_from(&text[..len]) {
+                    Ok(()) => len as i64,
+                    Err(e) => e.to_errno(),
+                },
+                Err(e) => e.to_errno(),
+            }
+        }
+        EXO_SYSCTL_WRITE => {
+            let text_buf = match UserBuf::validate(buf_ptr, buf_len, SYSCTL_TEXT_MAX) {
+                Ok(b) => b,
+                Err(e) => return e.to_errno(),
+            };
+            let mut text = [0u8; SYSCTL_TEXT_MAX];
+            if let Err(e) = text_buf.read_into(&mut text[..buf_len]) {
+                return e.to_errno();
+            }
+            let privileged = caller_is_root();
+            match crate::sysctl::write_by_name(name, &text[..buf_len], privileged) {
+                Ok(()) => 0,
+                Err(e) => sysctl_errno(e),
+            }
+        }
+        _ => EINVAL,
+    }
+}
+
+/// `exo_preload(op, a2, a3, a4)` — trace de boot et préchargement.
+///
+/// Tout sauf STATS est réservé à root : la trace liste les fichiers ouverts
+/// par tous les utilisateurs.
+pub fn sys_exo_preload(op: u64, a2: u64, a3: u64, a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_EXO_PRELOAD);
+    use crate::fs::exofs::cache::boot_preload;
+
+    if op == EXO_PRELOAD_STATS {
+        let snap = boot_preload::snapshot();
+        let size = core::mem::size_of::<boot_preload::PreloadSnapshot>();
+        if (a3 as usize) < size {
+            return ERANGE;
+        }
+        // SAFETY: PreloadSnapshot est repr(C), uniquement des u64.
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                &snap as *const boot_preload::PreloadSnapshot as *const u8,
+                size,
+            )
+        };
+        return match UserBuf::validate(a2, size, size) {
+            Ok(buf) => match buf.write_from(bytes) {
+                Ok(()) => size as i64,
+                Err(e) => e.to_errno(),
+            },
+            Err(e) => e.to_errno(),
+        };
+    }
+
+    if !caller_is_root() {
+        return EPERM;
+    }
+    match op {
+        EXO_PRELOAD_START => {
+            boot_preload::start_recording();
+            0
+        }
+        EXO_PRELOAD_STOP => {
+            boot_preload::stop_recording();
+            0
+        }
+        EXO_PRELOAD_TRACE => {
+            let mut chunk = [0u8; PRELOAD_TRACE_CHUNK];
+            let n = boot_preload::read_trace(
+                a2 as usize,
+                &mut chunk[..(a4 as usize).min(PRELOAD_TRACE_CHUNK)],
+            );
+            if n == 0 {
+                return 0;
+            }
+            match UserBuf::validate(a3, n, PRELOAD_TRACE_CHUNK) {
+                Ok(buf) => match buf.write_from(&chunk[..n]) {
+                    Ok(()) => n as i64,
+                    Err(e) => e.to_errno(),
+                },
+                Err(e) => e.to_errno(),
+            }
+        }
+        EXO_PRELOAD_FETCH => {
+            let path = match read_user_path(a2) {
+                Ok(p) => p,
+                Err(e) => return e.to_errno(),
+            };
+            use crate::syscall::fs_bridge;
+            fs_bridge::bridge_result(fs_bridge::fs_preload(path.as_bytes()))
+        }
+        _ => EINVAL,
+    }
+}
+
+/// Trace copiée vers userspace par appel (le tampon noyau est sur la pile).
+const PRELOAD_TRACE_CHUNK: usize = 4096;
+
+/// `exo_shm(op, a2, a3)` — régions partagées créées depuis userspace.
+///
+/// Le créateur seul écrit ; les autres processus ne mappent qu'en lecture
+/// une région publique (`EXO_SHM_PUBLIC_READ`).
+pub fn sys_exo_shm(op: u64, a2: u64, a3: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_EXO_SHM);
+    use crate::ipc::core::types::ProcessId;
+    use crate::ipc::shared_memory::user_region;
+
+    let caller = current_pid_u32();
+    if caller == 0 {
+        return EPERM;
+    }
+    match op {
+        EXO_SHM_CREATE => {
+            let size = match checked_usize_sysarg(a2) {
+                Ok(v) => v,
+                Err(e) => return e,
+            };
+            if a3 & !EXO_SHM_PUBLIC_READ != 0 {
+                return EINVAL;
+            }
+            let flags = if a3 & EXO_SHM_PUBLIC_READ != 0 {
+                user_region::USER_REGION_PUBLIC_READ
+            } else {
+                0
+            };
+            match user_region::create(ProcessId(caller), size, flags) {
+                Ok(id) => id as i64,
+                Err(e) => ipc_error_to_errno(e),
+            }
+        }
+        EXO_SHM_MAP => {
+            let id = match checked_u32_sysarg(a2) {
+                Ok(v) => v,
+                Err(e) => return e,
+            };
+            let writable = a3 != 0;
+            let desc_idx = match user_region::resolve(id, ProcessId(caller), writable) {
+                Ok(idx) => idx,
+                Err(e) => return ipc_error_to_errno(e),
+            };
+            let user_as = match user_as_for_pid(caller) {
+                Ok(v) => v,
+                Err(e) => return e,
+            };
+            match crate::memory::virt::map_shm_into_process(user_as, desc_idx, caller, 0, writable)
+            {
+                Ok(mapped) => mapped.virt_base as i64,
+                Err(e) => shm_map_error_to_errno(e),
+            }
+        }
+        EXO_SHM_UNMAP => {
+            let user_as = match user_as_for_pid(caller) {
+                Ok(v) => v,
+                Err(e) => return e,
+            };
+            if let Err(e) = crate::memory::virt::unmap_shm_from_process(user_as, a2) {
+                return shm_map_error_to_errno(e);
+            }
+            match user_region::forget_mapping(caller, a2) {
+                Ok(()) => 0,
+                Err(e) => ipc_error_to_errno(e),
+            }
+        }
+        EXO_SHM_DESTROY => {
+            let id = match checked_u32_sysarg(a2) {
+                Ok(v) => v,
+                Err(e) => return e,
+            };
+            match user_region::destroy(id, ProcessId(caller)) {
+                Ok(()) => 0,
+                Err(e) => ipc_error_to_errno(e),
+            }
+        }
+        _ => EINVAL,
+    }
+}
+
+/// `exo_psi(op, a2, a3)` — pression mémoire.
+///
+/// OPEN et READ sont ouverts à tous (indicateurs du bureau) ; OOM_KILL est
+/// réservé à root : le démon de pression y recourt quand demander aux
+/// applications de réduire leurs caches n'a pas suffi.
+pub fn sys_exo_psi(op: u64, a2: u64, a3: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_EXO_PSI);
+    use crate::memory::utils::pressure::{self, PressureReport};
+    use crate::scheduler::timer::clock::monotonic_ns;
+
+    let caller = current_pid_u32();
+    match op {
+        EXO_PSI_OPEN => {
+            let flags = match checked_u32_sysarg(a2) {
+                Ok(v) => v,
+                Err(e) => return e,
+            };
+            use crate::syscall::fs_bridge;
+            fs_bridge::bridge_result(fs_bridge::fs_pressure_open(flags, caller))
+        }
+        EXO_PSI_READ => {
+            let size = core::mem::size_of::<PressureReport>();
+            if (a3 as usize) < size {
+                return ERANGE;
+            }
+            let report = pressure::snapshot(monotonic_ns());
+            // SAFETY: PressureReport est repr(C), uniquement des entiers.
+            let bytes = unsafe {
+                core::slice::from_raw_parts(&report as *const PressureReport as *const u8, size)
+            };
+            match UserBuf::validate(a2, size, size) {
+                Ok(buf) => match buf.write_from(bytes) {
+                    Ok(()) => size as i64,
+                    Err(e) => e.to_errno(),
+                },
+                Err(e) => e.to_errno(),
+            }
+        }
+        EXO_PSI_OOM_KILL => {
+            if !caller_is_root() {
+                return EPERM;
+            }
+            if !crate::memory::utils::oom_kill_default() {
+                return EAGAIN;
+            }
+            pressure::note_oom(monotonic_ns());
+            0
+        }
+        _ => EINVAL,
+    }
+}
+
+/// `exo_freeze(op, pid)` — gel façon cgroup freezer, piloté par le démon de
+/// session. Droits de `kill(2)` : root ou même UID que la cible.
+pub fn sys_exo_freeze(op: u64, a2: u64, _a3: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_EXO_FREEZE);
+    use crate::process::state::freezer::{self, FreezeError};
+
+    let target = match checked_u32_sysarg(a2) {
+        Ok(v) => Pid(v),
+        Err(e) => return e,
+    };
+    let caller = Pid(current_pid_u32());
+    let result = match op {
+        EXO_FREEZE_FREEZE => freezer::freeze(caller, target),
+        EXO_FREEZE_THAW => freezer::thaw(caller, target),
+        EXO_FREEZE_STATE => freezer::frozen_state(caller, target),
+        _ => return EINVAL,
+    };
+    match result {
+        Ok(changed) => changed as i64,
+        Err(FreezeError::NoSuchProcess) => ESRCH,
+        Err(FreezeError::PermissionDenied) => EPERM,
+        Err(FreezeError::InvalidTarget) => EINVAL,
+    }
+}
+
+/// `exo_net_usage(index, buf, buf_len)` — entrée `index` de la comptabilité
+/// réseau par application, relayée depuis network_server.
+pub fn sys_exo_net_usage(
+    index: u64,
+    buf_ptr: u64,
+    buf_len: u64,
+    _a4: u64,
+    _a5: u64,
+    _a6: u64,
+) -> i64 {
+    stat_inc(SYS_EXO_NET_USAGE);
+    use crate::syscall::net_bridge::{self, NetAppUsage};
+
+    let size = core::mem::size_of::<NetAppUsage>();
+    if (buf_len as usize) < size {
+        return ERANGE;
+    }
+    let buf = match UserBuf::validate(buf_ptr, size, size) {
+        Ok(buf) => buf,
+        Err(e) => return e.to_errno(),
+    };
+    let (usage, next) = match net_bridge::net_app_usage(index) {
+        Ok(entry) => entry,
+        Err(errno) => return errno,
+    };
+    // SAFETY: NetAppUsage est repr(C), uniquement des entiers.
+    let bytes =
+        unsafe { core::slice::from_raw_parts(&usage as *const NetAppUsage as *const u8, size) };
+    match buf.write_from(bytes) {
+        Ok(()) => next as i64,
+        Err(e) => e.to_errno(),
+    }
+}
+
+const EXO_IPC_LABEL_LEN: usize = crate::ipc::channel::RAW_LABEL_LEN;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ExoIpcStat {
+    endpoint: u64,
+    owner_pid: u32,
+    depth: u32,
+    capacity: u32,
+    peak_depth: u32,
+    sent: u64,
+    received: u64,
+    dropped: u64,
+    expired: u64,
+    peak_latency_ns: u64,
+    total_latency_ns: u64,
+    name: [u8; EXO_IPC_LABEL_LEN],
+}
+
+const _: () = assert!(core::mem::size_of::<ExoIpcStat>() == 104);
+
+/// `exo_ipc_stat(index, buf, buf_len)` — première mailbox raw active à partir
+/// du slot `index` ; retourne le slot suivant, ENOENT en fin de table.
+///
+/// Hors root, seules les mailboxes du processus appelant sont listées.
+pub fn sys_exo_ipc_stat(
+    index: u64,
+    buf_ptr: u64,
+    buf_len: u64,
+    _a4: u64,
+    _a5: u64,
+    _a6: u64,
+) -> i64 {
+    stat_inc(SYS_EXO_IPC_STAT);
+    let size = core::mem::size_of::<ExoIpcStat>();
+    if (buf_len as usize) < size {
+        return ERANGE;
+    }
+    let buf = match UserBuf::validate(buf_ptr, size, size) {
+        Ok(buf) => buf,
+        Err(e) => return e.to_errno(),
+    };
+    let caller = current_pid_u32();
+    let privileged = caller_is_root();
+    let mut index = usize::try_from(index).unwrap_or(usize::MAX);
+    let (slot, stats, owner_pid) = loop {
+        let Some((slot, stats)) = crate::ipc::channel::raw_stats_at(index) else {
+            return ENOENT;
+        };
+        let owner_pid = ipc_endpoint_owner_pid(stats.endpoint_id).unwrap_or(0);
+        if privileged || owner_pid == caller {
+            break (slot, stats, owner_pid);
+        }
+        index = slot + 1;
+    };
+    let entry = ExoIpcStat {
+        endpoint: stats.endpoint_id,
+        owner_pid,
+        depth: stats.depth,
+        capacity: stats.capacity,
+        peak_depth: stats.peak_depth,
+        sent: stats.send_count,
+        received: stats.recv_count,
+        dropped: stats.drop_count,
+        expired: stats.expire_count,
+        peak_latency_ns: stats.peak_latency_ns,
+        total_latency_ns: stats.total_latency_ns,
+        name: stats.label,
+    };
+    // SAFETY: ExoIpcStat est repr(C), uniquement des entiers, sans padding.
+    let bytes =
+        unsafe { core::slice::from_raw_parts(&entry as *const ExoIpcStat as *const u8, size) };
+    match buf.write_from(bytes) {
+        Ok(()) => (slot + 1) as i64,
+        Err(e) => e.to_errno(),
+    }
+}
+
+/// `exo_trace(op, a2, a3, a4)` — marqueurs de trace et export (cf. `crate::trace`).
+pub fn sys_exo_trace(op: u64, a2: u64, a3: u64, a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_EXO_TRACE);
+    use crate::trace::{self, TraceKind, TRACE_NAME_LEN};
+
+    match op {
+        EXO_TRACE_MARK => {
+            let kind = match a4 {
+                EXO_TRACE_INSTANT => TraceKind::Mark,
+                EXO_TRACE_BEGIN => TraceKind::MarkBegin,
+                EXO_TRACE_END => TraceKind::MarkEnd,
+                _ => return EINVAL,
+            };
+            if !trace::enabled() {
+                return 0;
+            }
+            let len = (a3 as usize).min(TRACE_NAME_LEN);
+            let buf = match UserBuf::validate(a2, len, TRACE_NAME_LEN) {
+                Ok(buf) => buf,
+                Err(e) => return e.to_errno(),
+            };
+            let mut name = [0u8; TRACE_NAME_LEN];
+            if let Err(e) = buf.read_into(&mut name[..len]) {
+                return e.to_errno();
+            }
+            trace::mark(kind, &name[..len]);
+            0
+        }
+        EXO_TRACE_EXPORT => {
+            if !caller_is_root() {
+                return EPERM;
+            }
+            perf_value(trace::export_to_console())
+        }
+        _ => EINVAL,
+    }
+}
+
+fn livepatch_errno(err: crate::livepatch::LivepatchError) -> i64 {
+    use crate::livepatch::LivepatchError;
+    match err {
+        LivepatchError::Signature(_) => EACCES,
+        LivepatchError::Replayed | LivepatchError::AlreadyPatched => EEXIST,
+        LivepatchError::Malformed => EBADMSG,
+        LivepatchError::UnknownSite | LivepatchError::UnresolvedSymbol => ENOENT,
+        LivepatchError::NotPatched | LivepatchError::BadSite => EINVAL,
+        LivepatchError::RelocOverflow => ERANGE,
+        LivepatchError::OutOfSpace => ENOSPC,
+        LivepatchError::Busy => EBUSY,
+    }
+}
+
+/// `exo_livepatch(op, buf, len)` — applique un module de correctif signé ou
+/// retire le correctif d'un site. Réservé à root.
+pub fn sys_exo_livepatch(op: u64, buf_ptr: u64, len: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_EXO_LIVEPATCH);
+    if !caller_is_root() {
+        return EPERM;
+    }
+
+    let max = match op {
+        EXO_LIVEPATCH_APPLY => crate::livepatch::LIVEPATCH_MAX_IMAGE,
+        EXO_LIVEPATCH_REVERT => crate::livepatch::format::NAME_LEN,
+        _ => return EINVAL,
+    };
+    let buf = match UserBuf::validate(buf_ptr, len as usize, max) {
+        Ok(buf) => buf,
+        Err(e) => return e.to_errno(),
+    };
+    let mut data = match zeroed_user_vec(buf.len()) {
+        Ok(data) => data,
+        Err(e) => return e,
+    };
+    if let Err(e) = buf.read_into(&mut data) {
+        return e.to_errno();
+    }
+
+    let result = if op == EXO_LIVEPATCH_APPLY {
+        crate::livepatch::apply(&data).map(|site| site as i64)
+    } else {
+        crate::livepatch::revert(&data).map(|()| 0)
+    };
+    result.unwrap_or_else(livepatch_errno)
+}
+
+/// Entrée de `exo_cap_list` (miroir de `exo_syscall_abi::ExoCapEntry`).
+#[repr(C)]
+struct ExoCapEntry {
+    object_id: u64,
+    rights: u32,
+    generation: u32,
+    type_tag: u32,
+    _pad: u32,
+}
+
+const _: () = assert!(core::mem::size_of::<ExoCapEntry>() == 24);
+
+/// `exo_cap_list(pid, index, buf, buf_len)` — première capability du processus
+/// `pid` (0 = appelant) à partir du slot `index` ; retourne le slot suivant,
+/// ENOENT en fin de table. Inspecter un autre processus est réservé à root.
+pub fn sys_exo_cap_list(
+    pid: u64,
+    index: u64,
+    buf_ptr: u64,
+    buf_len: u64,
+    _a5: u64,
+    _a6: u64,
+) -> i64 {
+    stat_inc(SYS_EXO_CAP_LIST);
+    let size = core::mem::size_of::<ExoCapEntry>();
+    if (buf_len as usize) < size {
+        return ERANGE;
+    }
+    let buf = match UserBuf::validate(buf_ptr, size, size) {
+        Ok(buf) => buf,
+        Err(e) => return e.to_errno(),
+    };
+    let caller = current_pid_u32();
+    let target = match checked_u32_sysarg(pid) {
+        Ok(0) => caller,
+        Ok(other) => other,
+        Err(e) => return e,
+    };
+    if target != caller && !caller_is_root() {
+        return EPERM;
+    }
+    let Some(pcb) = PROCESS_REGISTRY.find_by_pid(Pid(target)) else {
+        return ESRCH;
+    };
+    let index = usize::try_from(index).unwrap_or(usize::MAX);
+    let Some((slot, object_id, view)) = pcb.cap_table.entry_at(index) else {
+        return ENOENT;
+    };
+    let entry = ExoCapEntry {
+        object_id: object_id.as_u64(),
+        rights: view.rights.bits(),
+        generation: view.generation,
+        type_tag: view.type_tag as u32,
+        _pad: 0,
+    };
+    // SAFETY: ExoCapEntry est repr(C), uniquement des entiers, sans padding.
+    let bytes =
+        unsafe { core::slice::from_raw_parts(&entry as *const ExoCapEntry as *const u8, size) };
+    match buf.write_from(bytes) {
+        Ok(()) => (slot + 1) as i64,
+        Err(e) => e.to_errno(),
+    }
+}
+
+fn bpf_errno(err: crate::bpf::BpfError) -> i64 {
+    use crate::bpf::BpfError;
+    match err {
+        BpfError::InvalidArgument => EINVAL,
+        BpfError::NotFound => ENOENT,
+        BpfError::NoSpace => ENOSPC,
+        BpfError::NoMemory => ENOMEM,
+        BpfError::Exists => EEXIST,
+        BpfError::Busy => EBUSY,
+        BpfError::Rejected(_) => EACCES,
+    }
+}
+
+/// Copie `len` octets utilisateur (exactement) dans un tampon noyau.
+fn bpf_read_user(ptr: u64, len: usize) -> Result<Vec<u8>, i64> {
+    let buf = UserBuf::validate(ptr, len, len).map_err(|e| e.to_errno())?;
+    let mut data = zeroed_user_vec(len)?;
+    buf.read_into(&mut data).map_err(|e| e.to_errno())?;
+    Ok(data)
+}
+
+fn bpf_write_user(ptr: u64, data: &[u8]) -> Result<(), i64> {
+    UserBuf::validate(ptr, data.len(), data.len())
+        .and_then(|buf| buf.write_from(data))
+        .map_err(|e| e.to_errno())
+}
+
+/// `PROG_LOAD` : en cas de refus, « pc N: raison » est écrit dans le journal.
+fn bpf_prog_load(kind: u64, insns_ptr: u64, count: u64, log_ptr: u64, log_len: u64) -> i64 {
+    use crate::bpf::insn::{decode_all, Insn, MAX_INSNS};
+    use crate::bpf::{BpfError, ProgType};
+
+    let Some(kind) = ProgType::from_u64(kind) else {
+        return EINVAL;
+    };
+    if count == 0 || count as usize > MAX_INSNS {
+        return E2BIG;
+    }
+    let raw = match bpf_read_user(insns_ptr, count as usize * Insn::LEN) {
+        Ok(raw) => raw,
+        Err(e) => return e,
+    };
+    let Some(insns) = decode_all(&raw) else {
+        return ENOMEM;
+    };
+    match crate::bpf::prog_load(kind, insns) {
+        Ok(id) => id as i64,
+        Err(BpfError::Rejected(err)) => {
+            if log_ptr != 0 && log_len != 0 {
+                let msg = alloc::format!("pc {}: {}\n", err.pc, err.reason);
+                let len = msg.len().min(log_len as usize);
+                if let Err(e) = bpf_write_user(log_ptr, &msg.as_bytes()[..len]) {
+                    return e;
+                }
+            }
+            EACCES
+        }
+        Err(e) => bpf_errno(e),
+    }
+}
+
+/// `exo_bpf(op, …)` — maps partagées et programmes bpf vérifiés (cf. `bpf`).
+/// Réservé à root (BPF-03).
+pub fn sys_exo_bpf(op: u64, a2: u64, a3: u64, a4: u64, a5: u64, a6: u64) -> i64 {
+    use crate::bpf::map::MapKind;
+
+    stat_inc(SYS_EXO_BPF);
+    if !caller_is_root() {
+        return EPERM;
+    }
+
+    let map = || crate::bpf::map_get(a2 as u32).map_err(bpf_errno);
+    let result: Result<i64, i64> = match op {
+        EXO_BPF_MAP_CREATE => {
+            let Some(kind) = MapKind::from_u64(a2) else {
+                return EINVAL;
+            };
+            let (key, value, max) = (a3 as u32, a4 as u32, a5 as u32);
+            crate::bpf::map_create(kind, key, value, max)
+                .map(|id| id as i64)
+                .map_err(bpf_errno)
+        }
+        EXO_BPF_MAP_LOOKUP => map().and_then(|map| {
+            let key = bpf_read_user(a3, map.key_size as usize)?;
+            let mut value = zeroed_user_vec(map.value_size as usize)?;
+            map.lookup(&key, &mut value).map_err(bpf_errno)?;
+            bpf_write_user(a4, &value).map(|()| 0)
+        }),
+        EXO_BPF_MAP_UPDATE => map().and_then(|map| {
+            let key = bpf_read_user(a3, map.key_size as usize)?;
+            let value = bpf_read_user(a4, map.value_size as usize)?;
+            map.update(&key, &value).map(|()| 0).map_err(bpf_errno)
+        }),
+        EXO_BPF_MAP_DELETE => map().and_then(|map| {
+            let key = bpf_read_user(a3, map.key_size as usize)?;
+            map.delete(&key).map(|()| 0).map_err(bpf_errno)
+        }),
+        EXO_BPF_MAP_NEXT_KEY => map().and_then(|map| {
+            let key = match a3 {
+                0 => None,
+                ptr => Some(bpf_read_user(ptr, map.key_size as usize)?),
+            };
+            let mut next = zeroed_user_vec(map.key_size as usize)?;
+            map.next_key(key.as_deref(), &mut next).map_err(bpf_errno)?;
+            bpf_write_user(a4, &next).map(|()| 0)
+        }),
+        EXO_BPF_MAP_FREE => crate::bpf::map_free(a2 as u32)
+            .map(|()| 0)
+            .map_err(bpf_errno),
+        EXO_BPF_PROG_LOAD => return bpf_prog_load(a2, a3, a4, a5, a6),
+        EXO_BPF_PROG_UNLOAD => crate::bpf::prog_unload(a2 as u32)
+            .map(|()| 0)
+            .map_err(bpf_errno),
+        EXO_BPF_PROG_ATTACH => crate::bpf::attach(a2 as u32, a3 as u32)
+            .map(|()| 0)
+            .map_err(bpf_errno),
+        EXO_BPF_PROG_DETACH => crate::bpf::detach(a2 as u32).map(|()| 0).map_err(bpf_errno),
+        _ => Err(EINVAL),
+    };
+    result.unwrap_or_else(|e| e)
+}
+
+/// `exo_sleep_state(buf, buf_len)` — génération de reprise, dernière veille
+/// et veille cumulée (ns), trois u64 LE. Lisible par tout processus.
+pub fn sys_exo_sleep_state(
+    buf_ptr: u64,
+    buf_len: u64,
+    _a3: u64,
+    _a4: u64,
+    _a5: u64,
+    _a6: u64,
+) -> i64 {
+    stat_inc(SYS_EXO_SLEEP_STATE);
+    const LEN: usize = 24;
+    if (buf_len as usize) < LEN {
+        return EINVAL;
+    }
+    let buf = match UserBuf::validate(buf_ptr, LEN, LEN) {
+        Ok(buf) => buf,
+        Err(e) => return e.to_errno(),
+    };
+    let (generation, last_ns, total_ns) = crate::scheduler::timer::clock::sleep_state();
+    let mut bytes = [0u8; LEN];
+    bytes[..8].copy_from_slice(&generation.to_le_bytes());
+    bytes[8..16].copy_from_slice(&last_ns.to_le_bytes());
+    bytes[16..].copy_from_slice(&total_ns.to_le_bytes());
+    match buf.write_from(&bytes) {
+        Ok(()) => LEN as i64,
+        Err(e) => e.to_errno(),
+    }
+}
+
+fn shm_map_error_to_errno(err: crate::memory::virt::ShmMapError) -> i64 {
+    use crate::memory::virt::ShmMapError;
+    match err {
+        ShmMapError::NoAddressSpace | ShmMapError::OutOfVirtualMemory => ENOMEM,
+        ShmMapError::AllocFailed => ENOMEM,
+        ShmMapError::InvalidRegion => EINVAL,
+        ShmMapError::PermissionDenied => EACCES,
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Handlers GI-03 Drivers (530–549)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -4370,6 +5268,32 @@ fn ps2_ioport_allowed(port: u64, width: u64) -> bool {
     width == 1 && matches!(port, 0x60 | 0x64)
 }
 
+/// COM1 (0x3F8..=0x3FF) pour `tty_server`, seulement si la console série est
+/// active : sans UART, un port flottant lu à 0xFF passerait pour des données.
+#[inline]
+fn serial_ioport_allowed(port: u64, width: u64) -> bool {
+    use crate::arch::x86_64::serial::{is_active, COM1_BASE, COM1_LAST};
+    width == 1 && (COM1_BASE as u64..=COM1_LAST as u64).contains(&port) && is_active()
+}
+
+/// Contrôle d'accès aux ports d'E/S : chaque classe Ring1 autorisée a sa
+/// fenêtre. `Err(EACCES)` hors classe, `Err(EINVAL)` hors fenêtre.
+fn check_ioport_access(caller_pid: u32, port: u64, width: u64) -> Result<(), i64> {
+    if caller_pid == 0 {
+        return Err(EACCES);
+    }
+    let allowed = match crate::security::service_class_of(Pid(caller_pid)) {
+        crate::security::ServiceClass::Ps2Driver => ps2_ioport_allowed(port, width),
+        crate::security::ServiceClass::TtyServer => serial_ioport_allowed(port, width),
+        _ => return Err(EACCES),
+    };
+    if allowed {
+        Ok(())
+    } else {
+        Err(EINVAL)
+    }
+}
+
 #[inline]
 fn ps2_keyboard_vector() -> u8 {
     IrqVector::VECTOR_IRQ_BASE + 1
@@ -4543,16 +5467,14 @@ pub fn sys_irq_ack(
 /// ABI GI-03 : `sys_ioport_read(port, width)`.
 ///
 /// Transition terminal v0.2.0: seul le driver Ring1 `ps2_driver` peut accéder
-/// aux ports i8042 clavier (0x60/0x64), en octets.
+/// aux ports i8042 clavier (0x60/0x64), en octets. `tty_server` lit COM1
+/// (0x3F8..=0x3FF) quand la console série est active (profil serveur).
 pub fn sys_ioport_read(port: u64, width: u64, _a3: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
     stat_inc(SYS_IOPORT_READ);
 
     let caller_pid = crate::syscall::fast_path::syscall_current_pid();
-    if caller_pid == 0 || !caller_is_ps2_driver(caller_pid) {
-        return EACCES;
-    }
-    if !ps2_ioport_allowed(port, width) {
-        return EINVAL;
+    if let Err(errno) = check_ioport_access(caller_pid, port, width) {
+        return errno;
     }
 
     unsafe { crate::arch::x86_64::inb(port as u16) as i64 }
@@ -4563,10 +5485,10 @@ pub fn sys_ioport_write(port: u64, value: u64, width: u64, _a4: u64, _a5: u64, _
     stat_inc(SYS_IOPORT_WRITE);
 
     let caller_pid = crate::syscall::fast_path::syscall_current_pid();
-    if caller_pid == 0 || !caller_is_ps2_driver(caller_pid) {
-        return EACCES;
+    if let Err(errno) = check_ioport_access(caller_pid, port, width) {
+        return errno;
     }
-    if !ps2_ioport_allowed(port, width) || value > u8::MAX as u64 {
+    if value > u8::MAX as u64 {
         return EINVAL;
     }
 
@@ -4821,7 +5743,11 @@ pub fn sys_pci_claim(
         ) {
             Ok(()) => {
                 if crate::drivers::iommu::ensure_domain_for_pid(owner_pid).is_err() {
-                    let _ = crate::drivers::release_claim_for_owner(owner_pid);
+                    crate::drivers::release_claim_range(
+                        owner_pid,
+                        PhysAddr::new(phys_addr),
+                        size as usize,
+                    );
                     return EAGAIN;
                 }
                 0
@@ -4838,8 +5764,14 @@ pub fn sys_pci_claim(
         caller_pid,
     ) {
         Ok(()) => {
+            // Seul le claim qui vient d'être accordé est annulé : les autres
+            // claims du propriétaire restent valides.
             if crate::drivers::iommu::ensure_domain_for_pid(owner_pid).is_err() {
-                let _ = crate::drivers::release_claim_for_owner(owner_pid);
+                crate::drivers::release_claim_range(
+                    owner_pid,
+                    PhysAddr::new(phys_addr),
+                    size as usize,
+                );
                 return EAGAIN;
             }
             0
@@ -4848,6 +5780,178 @@ pub fn sys_pci_claim(
     }
 }
 
+/// `sys_pci_claim_device(bdf, owner_pid, rights)` — claim d'une fonction PCI
+/// entière (tous ses BARs MMIO) pour un driver Ring 3, avec les droits
+/// périphérique `rights` (`DEV_MMIO`, `DEV_DMA`, `DEV_IRQ`). Réservé à
+/// root ; retourne le nombre de BARs claimés.
+pub fn sys_pci_claim_device(
+    bdf_raw: u64,
+    owner_pid: u64,
+    rights_bits: u64,
+    _a4: u64,
+    _a5: u64,
+    _a6: u64,
+) -> i64 {
+    stat_inc(SYS_PCI_CLAIM_DEVICE);
+    if owner_pid == 0 || owner_pid > u32::MAX as u64 || rights_bits > u32::MAX as u64 {
+        return EINVAL;
+    }
+    let rights = match crate::security::capability::Rights::from_bits(rights_bits as u32) {
+        Some(rights) if !rights.is_empty() => rights,
+        _ => return EINVAL,
+    };
+
+    let caller_pid = crate::syscall::fast_path::syscall_current_pid();
+    let owner_pid = owner_pid as u32;
+    let bdf = parse_pci_address(bdf_raw);
+    match crate::drivers::sys_pci_claim_device(bdf, owner_pid, rights, caller_pid) {
+        Ok(bars) => {
+            if crate::drivers::iommu::ensure_domain_for_pid(owner_pid).is_err() {
+                crate::drivers::release_device_claim(owner_pid, bdf);
+                return EAGAIN;
+            }
+            bars as i64
+        }
+        Err(err) => claim_error_to_errno(err),
+    }
+}
+
+/// `sys_pci_map_bar(bar, size_out)` — mappe le BAR `bar` (0–5) de la
+/// fonction PCI claimée par l'appelant ; retourne l'adresse virtuelle et
+/// écrit la taille du BAR dans `size_out` (optionnel). Exige `DEV_MMIO`.
+pub fn sys_pci_map_bar(bar: u64, size_out: u64, _a3: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_PCI_MAP_BAR);
+    if bar >= 6 {
+        return EINVAL;
+    }
+
+    let caller_pid = crate::syscall::fast_path::syscall_current_pid();
+    if caller_pid == 0 {
+        return EACCES;
+    }
+
+    match crate::drivers::sys_pci_map_bar_for_pid(caller_pid, bar as u8) {
+        Ok((virt, size)) => {
+            if size_out != 0 {
+                if let Err(e) = write_user_typed::<u64>(size_out, size as u64) {
+                    let _ = crate::drivers::sys_mmio_unmap_for_pid(caller_pid, virt, size);
+                    return e.to_errno();
+                }
+            }
+            virt as i64
+        }
+        Err(err) => mmio_error_to_errno(err),
+    }
+}
+
+/// `sys_irq_eventfd(irq, source_kind, eventfd)` — chaque IRQ incrémente
+/// l'eventfd `eventfd` au lieu d'un message IPC. Sources edge et MSI/MSI-X
+/// seulement : une IRQ niveau reste masquée jusqu'à `SYS_IRQ_ACK` et doit
+/// passer par `SYS_IRQ_REGISTER`. Exige `DEV_IRQ`.
+pub fn sys_irq_eventfd(irq: u64, source_kind: u64, fd: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
+    stat_inc(SYS_IRQ_EVENTFD);
+    if fd > u32::MAX as u64 {
+        return EINVAL;
+    }
+
+    let caller_pid = crate::syscall::fast_path::syscall_current_pid();
+    if caller_pid == 0 {
+        return EACCES;
+    }
+    if !crate::drivers::rights_of_pid(caller_pid)
+        .contains(crate::security::capability::Rights::DEV_IRQ)
+    {
+        return EACCES;
+    }
+
+    let source_kind = match parse_irq_source_kind(source_kind) {
+        Some(crate::arch::x86_64::irq::IrqSourceKind::IoApicLevel) | None => return EINVAL,
+        Some(kind) => kind,
+    };
+    let vector = match normalize_driver_irq(irq, source_kind) {
+        Ok(vector) => vector,
+        Err(errno) => return errno,
+    };
+    let blob_id = match crate::syscall::fs_bridge::fs_eventfd_blob(fd as u32, caller_pid) {
+        Ok(blob_id) => blob_id,
+        Err(e) => return e.to_errno(),
+    };
+    let Some((slot, created)) = crate::drivers::irq_eventfd::bind(caller_pid, blob_id) else {
+        return ENOMEM;
+    };
+
+    match crate::arch::x86_64::irq::sys_irq_register_eventfd(
+        vector,
+        IrqOwnerPid(caller_pid),
+        source_kind,
+        crate::drivers::claimed_bdf_raw(caller_pid),
+        slot,
+    ) {
+        Ok(reg_id) => reg_id as i64,
+        Err(err) => {
+            // Sans cela, chaque échec garderait un slot jusqu'à la sortie du
+            // processus et la table globale finirait pleine (ENOMEM pour tous).
+            if created {
+                crate::drivers::irq_eventfd::unbind(slot);
+            }
+            irq_error_to_errno(err)
+        }
+    }
+}
+
+/// `sys_dma_buffer(size, direction, virt_out)` — buffer DMA dans le domaine
+/// IOMMU de l'appelant, jamais en passthrough ; retourne l'IOVA à donner au
+/// périphérique et écrit l'adresse virtuelle dans `virt_out`. Exige
+/// `DEV_DMA` ; libération par `SYS_DMA_FREE`.
+pub fn sys_dma_buffer(
+    size: u64,
+    direction: u64,
+    user_virt_out: u64,
+    _a4: u64,
+    _a5: u64,
+    _a6: u64,
+) -> i64 {
+    stat_inc(SYS_DMA_BUFFER);
+    if size == 0 || size > usize::MAX as u64 || user_virt_out == 0 {
+        return EINVAL;
+    }
+    let direction = match parse_dma_direction(direction) {
+        Some(v) => v,
+        None => return EINVAL,
+    };
+
+    let caller_pid = crate::syscall::fast_path::syscall_current_pid();
+    if caller_pid == 0 {
+        return EACCES;
+    }
+    if !crate::drivers::rights_of_pid(caller_pid)
+        .contains(crate::security::capability::Rights::DEV_DMA)
+    {
+        return EACCES;
+    }
+    let domain = match crate::drivers::iommu::ensure_domain_for_pid(caller_pid) {
+        Ok(domain) => domain,
+        Err(_) => return EAGAIN,
+    };
+
+    match crate::drivers::sys_dma_alloc_for_pid(
+        caller_pid,
+        size as usize,
+        direction,
+        DmaMapFlags::NONE,
+        domain,
+    ) {
+        Ok((virt, iova)) => {
+            if let Err(e) = write_user_typed::<u64>(user_virt_out, virt) {
+                let _ = crate::drivers::sys_dma_free_for_pid(caller_pid, iova, domain);
+                return e.to_errno();
+            }
+            iova.0 as i64
+        }
+        Err(err) => dma_error_to_errno(err),
+    }
+}
+
 /// ABI GI-03 : `sys_dma_map(vaddr, size, direction)`.
 pub fn sys_dma_map(vaddr: u64, size: u64, direction: u64, _a4: u64, _a5: u64, _a6: u64) -> i64 {
     stat_inc(SYS_DMA_MAP);
@@ -5090,6 +6194,7 @@ pub fn get_handler(nr: u64) -> SyscallHandler {
         SYS_TEE => sys_tee,
         SYS_VMSPLICE => sys_vmsplice,
         SYS_FADVISE64 => sys_fadvise64,
+        SYS_READAHEAD => sys_readahead,
         SYS_CHMOD => sys_chmod,
         SYS_FCHMOD => sys_fchmod,
         SYS_FCHMODAT => sys_fchmodat,
@@ -5152,6 +6257,8 @@ pub fn get_handler(nr: u64) -> SyscallHandler {
         SYS_REBOOT => sys_reboot,
         SYS_WAIT4 => sys_wait4,
         SYS_WAITID => sys_waitid,
+        SYS_IOPRIO_SET => sys_ioprio_set,
+        SYS_IOPRIO_GET => sys_ioprio_get,
         SYS_GETPID => crate::syscall::handlers::misc::sys_getpid,
         SYS_GETPPID => crate::syscall::handlers::misc::sys_getppid,
         SYS_GETTID => crate::syscall::handlers::misc::sys_gettid,
@@ -5208,6 +6315,22 @@ pub fn get_handler(nr: u64) -> SyscallHandler {
         SYS_EXO_PROCESS_LIST => sys_exo_process_list,
         SYS_EXO_PHOENIX_STATE_SET => sys_exo_phoenix_state_set,
         SYS_EXO_PHOENIX_STATE_GET => sys_exo_phoenix_state_get,
+        SYS_EXO_SYSCTL => sys_exo_sysctl,
+        SYS_EXO_PRELOAD => sys_exo_preload,
+        SYS_EXO_SHM => sys_exo_shm,
+        SYS_EXO_PSI => sys_exo_psi,
+        SYS_EXO_FREEZE => sys_exo_freeze,
+        SYS_EXO_NET_USAGE => sys_exo_net_usage,
+        SYS_EXO_BPF => sys_exo_bpf,
+        SYS_EXO_IPC_STAT => sys_exo_ipc_stat,
+        SYS_EXO_TRACE => sys_exo_trace,
+        SYS_EXO_SLEEP_STATE => sys_exo_sleep_state,
+        SYS_PCI_CLAIM_DEVICE => sys_pci_claim_device,
+        SYS_PCI_MAP_BAR => sys_pci_map_bar,
+        SYS_IRQ_EVENTFD => sys_irq_eventfd,
+        SYS_DMA_BUFFER => sys_dma_buffer,
+        SYS_EXO_LIVEPATCH => sys_exo_livepatch,
+        SYS_EXO_CAP_LIST => sys_exo_cap_list,
         // ── ExoFS (500–518) ────────────────────────────────────────────────
         SYS_EXOFS_PATH_RESOLVE => sys_exofs_path_resolve,
         SYS_EXOFS_OBJECT_OPEN => sys_exofs_object_open,
@@ -5232,6 +6355,8 @@ pub fn get_handler(nr: u64) -> SyscallHandler {
         SYS_EXOFS_OPEN_BY_PATH => sys_exofs_open_by_path,
         SYS_EXOFS_READDIR => sys_exofs_readdir,
         SYS_FRAMEBUFFER_INFO => sys_framebuffer_info,
+        SYS_DISPLAY_EDID => sys_display_edid,
+        SYS_NETBOOT_INFO => sys_netboot_info,
         // ── GI-03 Drivers (530–549) ──────────────────────────────────────────
         SYS_IRQ_REGISTER => sys_irq_register,
         SYS_IRQ_ACK => sys_irq_ack,
@@ -0,0 +1,373 @@
+// kernel/src/sysctl/builtin.rs
+//
+// Tunables intégrés : ordonnanceur, profil d'énergie, ordonnanceur d'I/O,
+// cache de chemins ExoFS, traçage, table chaude des syscalls, santé du pool
+// d'entropie (`kernel.random.*`) et options de build (`kernel.config.*`,
+// lecture seule).
+// Les valeurs vivent dans les modules propriétaires ; ce fichier ne fait que
+// les décrire et les enregistrer.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use super::registry::{register, SysctlError, Tunable, TunableAccess, TunableKind};
+use crate::config::{
+    BuildOption, DEV_NO_VMM, IA32_COMPAT, KVM, MULTIBOOT2_COMPAT, SERIAL_CONSOLE,
+    STRICT_EXEC_SIGNATURES,
+};
+use crate::fs::exofs::path::path_cache::{CACHE_TTL_TICKS, CACHE_TTL_TUNABLE};
+use crate::fs::exofs::storage::io_scheduler::{
+    IDLE_GRACE_NS, IDLE_GRACE_TUNABLE, READ_EXPIRE_NS, READ_EXPIRE_TUNABLE, WRITE_EXPIRE_NS,
+    WRITE_EXPIRE_TUNABLE,
+};
+use crate::scheduler::energy::power_profile::{
+    POWER_PROFILE_TUNABLE, PROFILE_BALANCED, PROFILE_PERFORMANCE,
+};
+use crate::scheduler::policies::cfs::{
+    CFS_TARGET_PERIOD_NS, CFS_WAKEUP_PREEMPT_NS, CFS_WAKEUP_PREEMPT_TUNABLE,
+};
+use crate::scheduler::policies::realtime::{RR_TIMESLICE_NS, RR_TIMESLICE_TUNABLE};
+use crate::scheduler::smp::load_balance::{BALANCE_INTERVAL_TICKS, BALANCE_INTERVAL_TUNABLE};
+use crate::scheduler::timer::tick::HZ;
+use crate::security::crypto::entropy::{
+    ENTROPY_AVAIL_VALUE, FAST_RESEED_MS, FAST_RESEED_TUNABLE, HEALTH_FAILURES_VALUE,
+    IRQ_SAMPLES_VALUE, JITTER_HEALTHY_VALUE, JITTER_SAMPLES_VALUE, LAST_RESEED_MS_VALUE,
+    RESEED_COUNT_VALUE, SLOW_RESEED_MS, SLOW_RESEED_TUNABLE,
+};
+use crate::syscall::hot_path::{on_hot_table_change, HOT_TABLE_TUNABLE};
+use crate::trace::{on_enabled_change as on_trace_enabled_change, TRACE_ENABLED_TUNABLE};
+
+/// Le seuil de wakeup doit rester strictement inférieur à la période cible.
+fn check_wakeup_preempt(_old: u64, new: u64) -> Result<(), SysctlError> {
+    if new < CFS_TARGET_PERIOD_NS {
+        Ok(())
+    } else {
+        Err(SysctlError::Rejected)
+    }
+}
+
+static SCHED_CFS_WAKEUP_PREEMPT: Tunable = Tunable {
+    name: "kernel.sched.cfs_wakeup_preempt_ns",
+    description: "CFS wakeup preemption threshold (ns)",
+    kind: TunableKind::U64 {
+        min: 0,
+        max: u64::MAX,
+    },
+    access: TunableAccess::RootWrite,
+    value: &CFS_WAKEUP_PREEMPT_TUNABLE,
+    default: CFS_WAKEUP_PREEMPT_NS,
+    on_change: Some(check_wakeup_preempt),
+};
+
+static SCHED_RR_TIMESLICE: Tunable = Tunable {
+    name: "kernel.sched.rr_timeslice_ns",
+    description: "SCHED_RR quantum (ns)",
+    kind: TunableKind::U64 {
+        min: 100_000,
+        max: 1_000_000_000,
+    },
+    access: TunableAccess::RootWrite,
+    value: &RR_TIMESLICE_TUNABLE,
+    default: RR_TIMESLICE_NS,
+    on_change: None,
+};
+
+static SCHED_BALANCE_INTERVAL: Tunable = Tunable {
+    name: "kernel.sched.balance_interval_ticks",
+    description: "Ticks between two load-balancing passes",
+    kind: TunableKind::U64 { min: 1, max: HZ },
+    access: TunableAccess::RootWrite,
+    value: &BALANCE_INTERVAL_TUNABLE,
+    default: BALANCE_INTERVAL_TICKS,
+    on_change: None,
+};
+
+static POWER_PROFILE: Tunable = Tunable {
+    name: "kernel.power.profile",
+    description: "Power profile: 0 = balanced, 1 = performance (no idle sleep)",
+    kind: TunableKind::U64 {
+        min: PROFILE_BALANCED,
+        max: PROFILE_PERFORMANCE,
+    },
+    access: TunableAccess::RootWrite,
+    value: &POWER_PROFILE_TUNABLE,
+    default: PROFILE_BALANCED,
+    on_change: None,
+};
+
+static EXOFS_PATH_CACHE_TTL: Tunable = Tunable {
+    name: "fs.exofs.path_cache_ttl_ticks",
+    description: "ExoFS path cache entry lifetime (ticks)",
+    kind: TunableKind::U64 {
+        min: 0,
+        max: u64::MAX,
+    },
+    access: TunableAccess::RootWrite,
+    value: &CACHE_TTL_TUNABLE,
+    default: CACHE_TTL_TICKS,
+    on_change: None,
+};
+
+static IOSCHED_READ_EXPIRE: Tunable = Tunable {
+    name: "fs.iosched.read_expire_ns",
+    description: "Block read deadline before it overtakes priorities (ns)",
+    kind: TunableKind::U64 {
+        min: 1_000_000,
+        max: 10_000_000_000,
+    },
+    access: TunableAccess::RootWrite,
+    value: &READ_EXPIRE_TUNABLE,
+    default: READ_EXPIRE_NS,
+    on_change: None,
+};
+
+static IOSCHED_WRITE_EXPIRE: Tunable = Tunable {
+    name: "fs.iosched.write_expire_ns",
+    description: "Block write deadline before it overtakes priorities (ns)",
+    kind: TunableKind::U64 {
+        min: 1_000_000,
+        max: 60_000_000_000,
+    },
+    access: TunableAccess::RootWrite,
+    value: &WRITE_EXPIRE_TUNABLE,
+    default: WRITE_EXPIRE_NS,
+    on_change: None,
+};
+
+static IOSCHED_IDLE_GRACE: Tunable = Tunable {
+    name: "fs.iosched.idle_grace_ns",
+    description: "Disk idle time required before serving the idle class (ns)",
+    kind: TunableKind::U64 {
+        min: 0,
+        max: 1_000_000_000,
+    },
+    access: TunableAccess::RootWrite,
+    value: &IDLE_GRACE_TUNABLE,
+    default: IDLE_GRACE_NS,
+    on_change: None,
+};
+
+static KERNEL_TRACE_ENABLED: Tunable = Tunable {
+    name: "kernel.trace.enabled",
+    description: "Record scheduler, IPC and user marker trace events",
+    kind: TunableKind::Bool,
+    access: TunableAccess::RootWrite,
+    value: &TRACE_ENABLED_TUNABLE,
+    default: 0,
+    on_change: Some(on_trace_enabled_change),
+};
+
+static KERNEL_SYSCALL_HOT_TABLE: Tunable = Tunable {
+    name: "kernel.syscall.hot_table",
+    description: "Promote profiled hot syscalls into the direct dispatch table",
+    kind: TunableKind::Bool,
+    access: TunableAccess::RootWrite,
+    value: &HOT_TABLE_TUNABLE,
+    default: 1,
+    on_change: Some(on_hot_table_change),
+};
+
+/// L'intervalle rapide ne dépasse jamais l'intervalle lent.
+fn check_fast_reseed(_old: u64, new: u64) -> Result<(), SysctlError> {
+    if new <= SLOW_RESEED_TUNABLE.load(Ordering::Relaxed) {
+        Ok(())
+    } else {
+        Err(SysctlError::Rejected)
+    }
+}
+
+fn check_slow_reseed(_old: u64, new: u64) -> Result<(), SysctlError> {
+    if new >= FAST_RESEED_TUNABLE.load(Ordering::Relaxed) {
+        Ok(())
+    } else {
+        Err(SysctlError::Rejected)
+    }
+}
+
+static RANDOM_FAST_RESEED: Tunable = Tunable {
+    name: "kernel.random.fast_reseed_ms",
+    description: "Entropy pool reseed interval for the first reseeds (ms)",
+    kind: TunableKind::U64 {
+        min: 100,
+        max: 3_600_000,
+    },
+    access: TunableAccess::RootWrite,
+    value: &FAST_RESEED_TUNABLE,
+    default: FAST_RESEED_MS,
+    on_change: Some(check_fast_reseed),
+};
+
+static RANDOM_SLOW_RESEED: Tunable = Tunable {
+    name: "kernel.random.slow_reseed_ms",
+    description: "Entropy pool reseed interval once settled (ms)",
+    kind: TunableKind::U64 {
+        min: 100,
+        max: 3_600_000,
+    },
+    access: TunableAccess::RootWrite,
+    value: &SLOW_RESEED_TUNABLE,
+    default: SLOW_RESEED_MS,
+    on_change: Some(check_slow_reseed),
+};
+
+/// Compteur de santé du pool d'entropie `kernel.random.<nom>`, lecture seule.
+const fn random_stat(
+    name: &'static str,
+    description: &'static str,
+    kind: TunableKind,
+    value: &'static AtomicU64,
+) -> Tunable {
+    Tunable {
+        name,
+        description,
+        kind,
+        access: TunableAccess::ReadOnly,
+        value,
+        default: 0,
+        on_change: None,
+    }
+}
+
+const COUNTER: TunableKind = TunableKind::U64 {
+    min: 0,
+    max: u64::MAX,
+};
+
+static RANDOM_ENTROPY_AVAIL: Tunable = random_stat(
+    "kernel.random.entropy_avail",
+    "Entropy credited to the input pool (bits)",
+    TunableKind::U64 { min: 0, max: 256 },
+    &ENTROPY_AVAIL_VALUE,
+);
+static RANDOM_RESEED_COUNT: Tunable = random_stat(
+    "kernel.random.reseed_count",
+    "CSPRNG reseeds from the entropy pool",
+    COUNTER,
+    &RESEED_COUNT_VALUE,
+);
+static RANDOM_LAST_RESEED: Tunable = random_stat(
+    "kernel.random.last_reseed_ms",
+    "Monotonic time of the last reseed (ms, 0 = never)",
+    COUNTER,
+    &LAST_RESEED_MS_VALUE,
+);
+static RANDOM_IRQ_SAMPLES: Tunable = random_stat(
+    "kernel.random.irq_samples",
+    "Interrupt timings mixed into the pool",
+    COUNTER,
+    &IRQ_SAMPLES_VALUE,
+);
+static RANDOM_JITTER_SAMPLES: Tunable = random_stat(
+    "kernel.random.jitter_samples",
+    "CPU jitter samples mixed into the pool",
+    COUNTER,
+    &JITTER_SAMPLES_VALUE,
+);
+static RANDOM_JITTER_HEALTHY: Tunable = random_stat(
+    "kernel.random.jitter_healthy",
+    "Last jitter block passed the health tests",
+    TunableKind::Bool,
+    &JITTER_HEALTHY_VALUE,
+);
+static RANDOM_HEALTH_FAILURES: Tunable = random_stat(
+    "kernel.random.health_failures",
+    "Jitter blocks rejected by the health tests",
+    COUNTER,
+    &HEALTH_FAILURES_VALUE,
+);
+
+static KERNEL_HZ_VALUE: AtomicU64 = AtomicU64::new(HZ);
+static KERNEL_HZ: Tunable = Tunable {
+    name: "kernel.hz",
+    description: "Scheduler tick frequency (read-only)",
+    kind: TunableKind::U64 { min: HZ, max: HZ },
+    access: TunableAccess::ReadOnly,
+    value: &KERNEL_HZ_VALUE,
+    default: HZ,
+    on_change: None,
+};
+
+/// Option de build `kernel.config.<nom>` : 1 si compilée, lecture seule.
+const fn config_tunable(
+    name: &'static str,
+    option: BuildOption,
+    value: &'static AtomicU64,
+) -> Tunable {
+    Tunable {
+        name,
+        description: option.description,
+        kind: TunableKind::Bool,
+        access: TunableAccess::ReadOnly,
+        value,
+        default: option.enabled as u64,
+        on_change: None,
+    }
+}
+
+static CONFIG_MULTIBOOT2_COMPAT_VALUE: AtomicU64 = AtomicU64::new(0);
+static CONFIG_MULTIBOOT2_COMPAT: Tunable = config_tunable(
+    "kernel.config.multiboot2_compat",
+    MULTIBOOT2_COMPAT,
+    &CONFIG_MULTIBOOT2_COMPAT_VALUE,
+);
+static CONFIG_KVM_VALUE: AtomicU64 = AtomicU64::new(0);
+static CONFIG_KVM: Tunable = config_tunable("kernel.config.kvm", KVM, &CONFIG_KVM_VALUE);
+static CONFIG_IA32_COMPAT_VALUE: AtomicU64 = AtomicU64::new(0);
+static CONFIG_IA32_COMPAT: Tunable = config_tunable(
+    "kernel.config.ia32_compat",
+    IA32_COMPAT,
+    &CONFIG_IA32_COMPAT_VALUE,
+);
+static CONFIG_STRICT_EXEC_SIGNATURES_VALUE: AtomicU64 = AtomicU64::new(0);
+static CONFIG_STRICT_EXEC_SIGNATURES: Tunable = config_tunable(
+    "kernel.config.strict_exec_signatures",
+    STRICT_EXEC_SIGNATURES,
+    &CONFIG_STRICT_EXEC_SIGNATURES_VALUE,
+);
+static CONFIG_SERIAL_CONSOLE_VALUE: AtomicU64 = AtomicU64::new(0);
+static CONFIG_SERIAL_CONSOLE: Tunable = config_tunable(
+    "kernel.config.serial_console",
+    SERIAL_CONSOLE,
+    &CONFIG_SERIAL_CONSOLE_VALUE,
+);
+static CONFIG_DEV_NO_VMM_VALUE: AtomicU64 = AtomicU64::new(0);
+static CONFIG_DEV_NO_VMM: Tunable = config_tunable(
+    "kernel.config.dev_no_vmm",
+    DEV_NO_VMM,
+    &CONFIG_DEV_NO_VMM_VALUE,
+);
+
+static BUILTIN: [&Tunable; 26] = [
+    &KERNEL_HZ,
+    &KERNEL_TRACE_ENABLED,
+    &KERNEL_SYSCALL_HOT_TABLE,
+    &SCHED_CFS_WAKEUP_PREEMPT,
+    &SCHED_RR_TIMESLICE,
+    &SCHED_BALANCE_INTERVAL,
+    &POWER_PROFILE,
+    &EXOFS_PATH_CACHE_TTL,
+    &IOSCHED_READ_EXPIRE,
+    &IOSCHED_WRITE_EXPIRE,
+    &IOSCHED_IDLE_GRACE,
+    &RANDOM_FAST_RESEED,
+    &RANDOM_SLOW_RESEED,
+    &RANDOM_ENTROPY_AVAIL,
+    &RANDOM_RESEED_COUNT,
+    &RANDOM_LAST_RESEED,
+    &RANDOM_IRQ_SAMPLES,
+    &RANDOM_JITTER_SAMPLES,
+    &RANDOM_JITTER_HEALTHY,
+    &RANDOM_HEALTH_FAILURES,
+    &CONFIG_MULTIBOOT2_COMPAT,
+    &CONFIG_KVM,
+    &CONFIG_IA32_COMPAT,
+    &CONFIG_STRICT_EXEC_SIGNATURES,
+    &CONFIG_SERIAL_CONSOLE,
+    &CONFIG_DEV_NO_VMM,
+];
+
+/// Enregistre les tunables intégrés. Les doublons (second appel) sont ignorés.
+pub fn register_all() {
+    for tunable in BUILTIN.iter() {
+        let _ = register(tunable);
+    }
+}
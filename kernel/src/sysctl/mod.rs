@@ -0,0 +1,45 @@
+// kernel/src/sysctl/mod.rs
+//
+// ═══════════════════════════════════════════════════════════════════════════════
+// sysctl — registre de tunables runtime (Exo-OS · Transverse)
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Beaucoup de réglages du noyau étaient des constantes de compilation (quantum
+// RR, intervalle d'équilibrage, TTL du cache de chemins…). Ce module les expose
+// comme des TUNABLES nommés, lisibles/écrivables à chaud via une arborescence
+// façon `/proc/sys` :
+//
+//   /proc/sys/kernel/sched/rr_timeslice_ns  ⇔  "kernel.sched.rr_timeslice_ns"
+//
+// Chaque tunable déclare :
+//   • son type (booléen ou entier borné [min, max]) — vérifié à l'écriture ;
+//   • son mode d'accès (lecture seule, écriture root) ;
+//   • un callback optionnel `on_change(old, new)` pouvant REFUSER la valeur
+//     (invariants croisés) ou propager le changement au sous-système.
+//
+// La valeur vit dans un `AtomicU64` détenu par le sous-système propriétaire :
+// le chemin chaud ne fait qu'un `load(Relaxed)`, jamais de lock ni d'alloc.
+//
+// RÈGLE SYSCTL-01 : le registre est NO-ALLOC (table statique bornée).
+// RÈGLE SYSCTL-02 : les écritures sont sérialisées ; le callback voit toujours
+//   l'ancienne valeur réellement en place.
+// RÈGLE SYSCTL-03 : la valeur par défaut d'un tunable est la constante de
+//   compilation historique — sans écriture, le comportement est inchangé.
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub mod builtin;
+pub mod proc_sys;
+pub mod registry;
+
+pub use proc_sys::{format_value, normalize_name, parse_value, PROC_SYS_PREFIX};
+pub use registry::{
+    for_each, read, read_by_name, register, write, write_by_name, SysctlError, Tunable,
+    TunableAccess, TunableHook, TunableKind, MAX_TUNABLES, SYSCTL_NAME_MAX,
+};
+
+/// Enregistre les tunables intégrés au noyau.
+///
+/// Idempotent : un second appel ne fait rien (les doublons sont ignorés).
+pub fn init() {
+    builtin::register_all();
+}
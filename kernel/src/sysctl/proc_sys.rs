@@ -0,0 +1,143 @@
+// kernel/src/sysctl/proc_sys.rs
+//
+// Encodage texte façon `/proc/sys` : conversion chemin ⇔ nom pointé,
+// formatage et analyse des valeurs (décimal, hexadécimal `0x`).
+
+use super::registry::{name_is_valid, SysctlError, TunableKind, SYSCTL_NAME_MAX};
+
+/// Préfixe des chemins acceptés en plus de la forme pointée.
+pub const PROC_SYS_PREFIX: &[u8] = b"/proc/sys/";
+
+/// Convertit `input` (`/proc/sys/kernel/hz` ou `kernel.hz`) en nom pointé.
+///
+/// Retourne la longueur écrite dans `out`.
+pub fn normalize_name(input: &[u8], out: &mut [u8; SYSCTL_NAME_MAX]) -> Result<usize, SysctlError> {
+    let (body, from_path) = match input.strip_prefix(PROC_SYS_PREFIX) {
+        Some(rest) => (rest, true),
+        None => (input, false),
+    };
+    if body.len() > SYSCTL_NAME_MAX {
+        return Err(SysctlError::InvalidName);
+    }
+    for (dst, &b) in out.iter_mut().zip(body) {
+        *dst = if from_path && b == b'/' { b'.' } else { b };
+    }
+    if !name_is_valid(&out[..body.len()]) {
+        return Err(SysctlError::InvalidName);
+    }
+    Ok(body.len())
+}
+
+/// Encode `value` en décimal suivi de `\n`. `None` si `out` est trop petit.
+pub fn format_value(_kind: TunableKind, value: u64, out: &mut [u8]) -> Option<usize> {
+    let mut digits = [0u8; 20];
+    let mut n = value;
+    let mut len = 0;
+    loop {
+        digits[len] = b'0' + (n % 10) as u8;
+        len += 1;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    if out.len() < len + 1 {
+        return None;
+    }
+    for (dst, &d) in out.iter_mut().zip(digits[..len].iter().rev()) {
+        *dst = d;
+    }
+    out[len] = b'\n';
+    Some(len + 1)
+}
+
+/// Analyse le texte écrit dans un fichier `/proc/sys`.
+///
+/// Les blancs ASCII en tête/queue sont ignorés ; décimal ou `0x` hexadécimal.
+pub fn parse_value(kind: TunableKind, text: &[u8]) -> Result<u64, SysctlError> {
+    let text = text.trim_ascii();
+    let (digits, radix) = match text
+        .strip_prefix(b"0x")
+        .or_else(|| text.strip_prefix(b"0X"))
+    {
+        Some(hex) => (hex, 16u64),
+        None => (text, 10u64),
+    };
+    if digits.is_empty() {
+        return Err(SysctlError::InvalidValue);
+    }
+    let mut value: u64 = 0;
+    for &b in digits {
+        let d = match b {
+            b'0'..=b'9' => (b - b'0') as u64,
+            b'a'..=b'f' if radix == 16 => (b - b'a' + 10) as u64,
+            b'A'..=b'F' if radix == 16 => (b - b'A' + 10) as u64,
+            _ => return Err(SysctlError::InvalidValue),
+        };
+        value = value
+            .checked_mul(radix)
+            .and_then(|v| v.checked_add(d))
+            .ok_or(SysctlError::OutOfRange)?;
+    }
+    if !kind.accepts(value) {
+        return Err(SysctlError::OutOfRange);
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_is_mapped_to_dotted_name() {
+        let mut out = [0u8; SYSCTL_NAME_MAX];
+        let n = normalize_name(b"/proc/sys/kernel/sched/rr_timeslice_ns", &mut out).unwrap();
+        assert_eq!(&out[..n], b"kernel.sched.rr_timeslice_ns");
+        let n = normalize_name(b"kernel.hz", &mut out).unwrap();
+        assert_eq!(&out[..n], b"kernel.hz");
+        assert_eq!(
+            normalize_name(b"kernel/hz", &mut out),
+            Err(SysctlError::InvalidName)
+        );
+    }
+
+    #[test]
+    fn format_writes_decimal_and_newline() {
+        let mut out = [0u8; 24];
+        let n = format_value(TunableKind::Bool, 0, &mut out).unwrap();
+        assert_eq!(&out[..n], b"0\n");
+        let n = format_value(
+            TunableKind::U64 {
+                min: 0,
+                max: u64::MAX,
+            },
+            u64::MAX,
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(&out[..n], b"18446744073709551615\n");
+        assert_eq!(format_value(TunableKind::Bool, 10, &mut [0u8; 2]), None);
+    }
+
+    #[test]
+    fn parse_accepts_decimal_hex_and_whitespace() {
+        let kind = TunableKind::U64 {
+            min: 0,
+            max: 1 << 20,
+        };
+        assert_eq!(parse_value(kind, b" 4000\n"), Ok(4000));
+        assert_eq!(parse_value(kind, b"0x10"), Ok(16));
+        assert_eq!(parse_value(kind, b""), Err(SysctlError::InvalidValue));
+        assert_eq!(parse_value(kind, b"12a"), Err(SysctlError::InvalidValue));
+        assert_eq!(parse_value(kind, b"99999999"), Err(SysctlError::OutOfRange));
+        assert_eq!(
+            parse_value(kind, b"99999999999999999999999"),
+            Err(SysctlError::OutOfRange)
+        );
+        assert_eq!(
+            parse_value(TunableKind::Bool, b"2"),
+            Err(SysctlError::OutOfRange)
+        );
+    }
+}
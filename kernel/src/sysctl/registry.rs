@@ -0,0 +1,341 @@
+// kernel/src/sysctl/registry.rs
+//
+// Registre statique des tunables : enregistrement, recherche par nom,
+// lecture et écriture contrôlées (type, bornes, droits, callback).
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use spin::Mutex;
+
+/// Nombre maximal de tunables enregistrables (table statique, NO-ALLOC).
+pub const MAX_TUNABLES: usize = 64;
+/// Longueur maximale d'un nom pointé (`kernel.sched.rr_timeslice_ns`).
+pub const SYSCTL_NAME_MAX: usize = 64;
+
+/// Type d'un tunable — détermine l'encodage texte et la validation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TunableKind {
+    /// Booléen encodé `0` / `1`.
+    Bool,
+    /// Entier non signé borné (bornes incluses).
+    U64 { min: u64, max: u64 },
+}
+
+impl TunableKind {
+    /// Vérifie qu'une valeur respecte le type.
+    pub const fn accepts(self, value: u64) -> bool {
+        match self {
+            TunableKind::Bool => value <= 1,
+            TunableKind::U64 { min, max } => value >= min && value <= max,
+        }
+    }
+}
+
+/// Mode d'accès d'un tunable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TunableAccess {
+    /// Lisible par tous, jamais modifiable (constante exposée pour audit).
+    ReadOnly,
+    /// Lisible par tous, modifiable uniquement par un appelant privilégié.
+    RootWrite,
+}
+
+/// Callback de changement : `(ancienne, nouvelle)`. Un `Err` annule l'écriture.
+pub type TunableHook = fn(old: u64, new: u64) -> Result<(), SysctlError>;
+
+/// Descripteur d'un tunable. Toujours `'static` : déclaré par le sous-système.
+pub struct Tunable {
+    /// Nom pointé unique (`kernel.sched.rr_timeslice_ns`).
+    pub name: &'static str,
+    /// Description courte (affichée par les outils d'administration).
+    pub description: &'static str,
+    pub kind: TunableKind,
+    pub access: TunableAccess,
+    /// Stockage détenu par le sous-système (lu sur son chemin chaud).
+    pub value: &'static AtomicU64,
+    /// Valeur de compilation historique (RÈGLE SYSCTL-03).
+    pub default: u64,
+    pub on_change: Option<TunableHook>,
+}
+
+impl Tunable {
+    #[inline]
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+/// Erreurs du registre sysctl.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SysctlError {
+    /// Aucun tunable de ce nom.
+    NotFound,
+    /// Tunable en lecture seule.
+    ReadOnly,
+    /// Appelant non privilégié.
+    PermissionDenied,
+    /// Texte non analysable pour le type du tunable.
+    InvalidValue,
+    /// Valeur hors bornes.
+    OutOfRange,
+    /// Le callback du sous-système a refusé la valeur.
+    Rejected,
+    /// Nom vide, trop long ou contenant des caractères interdits.
+    InvalidName,
+    /// Table pleine.
+    RegistryFull,
+    /// Un tunable de ce nom existe déjà.
+    Duplicate,
+}
+
+static REGISTRY: Mutex<[Option<&'static Tunable>; MAX_TUNABLES]> = Mutex::new([None; MAX_TUNABLES]);
+/// Sérialise les écritures (RÈGLE SYSCTL-02) sans tenir REGISTRY pendant le callback.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Enregistre un tunable. La valeur courante est initialisée à `default`.
+pub fn register(tunable: &'static Tunable) -> Result<(), SysctlError> {
+    if !name_is_valid(tunable.name.as_bytes()) {
+        return Err(SysctlError::InvalidName);
+    }
+    if !tunable.kind.accepts(tunable.default) {
+        return Err(SysctlError::OutOfRange);
+    }
+    let mut table = REGISTRY.lock();
+    let mut free = None;
+    for (idx, slot) in table.iter().enumerate() {
+        match slot {
+            Some(existing) if existing.name == tunable.name => {
+                return Err(SysctlError::Duplicate);
+            }
+            None if free.is_none() => free = Some(idx),
+            _ => {}
+        }
+    }
+    let idx = free.ok_or(SysctlError::RegistryFull)?;
+    tunable.value.store(tunable.default, Ordering::Relaxed);
+    table[idx] = Some(tunable);
+    Ok(())
+}
+
+fn find(name: &[u8]) -> Option<&'static Tunable> {
+    REGISTRY
+        .lock()
+        .iter()
+        .flatten()
+        .copied()
+        .find(|t| t.name.as_bytes() == name)
+}
+
+/// Lit la valeur courante d'un tunable.
+pub fn read(name: &[u8]) -> Result<u64, SysctlError> {
+    find(name).map(Tunable::get).ok_or(SysctlError::NotFound)
+}
+
+/// Écrit un tunable. `privileged` = appelant root ou kernel.
+pub fn write(name: &[u8], value: u64, privileged: bool) -> Result<(), SysctlError> {
+    let tunable = find(name).ok_or(SysctlError::NotFound)?;
+    match tunable.access {
+        TunableAccess::ReadOnly => return Err(SysctlError::ReadOnly),
+        TunableAccess::RootWrite if !privileged => return Err(SysctlError::PermissionDenied),
+        TunableAccess::RootWrite => {}
+    }
+    if !tunable.kind.accepts(value) {
+        return Err(SysctlError::OutOfRange);
+    }
+    let _serial = WRITE_LOCK.lock();
+    let old = tunable.get();
+    if old == value {
+        return Ok(());
+    }
+    if let Some(hook) = tunable.on_change {
+        hook(old, value)?;
+    }
+    tunable.value.store(value, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Lit un tunable et l'encode en texte `/proc/sys` dans `out`.
+///
+/// `name` accepte la forme pointée ou un chemin `/proc/sys/...`.
+/// Retourne le nombre d'octets écrits.
+pub fn read_by_name(name: &[u8], out: &mut [u8]) -> Result<usize, SysctlError> {
+    let mut key = [0u8; SYSCTL_NAME_MAX];
+    let len = super::proc_sys::normalize_name(name, &mut key)?;
+    let tunable = find(&key[..len]).ok_or(SysctlError::NotFound)?;
+    super::proc_sys::format_value(tunable.kind, tunable.get(), out).ok_or(SysctlError::InvalidValue)
+}
+
+/// Analyse `text` (contenu écrit dans le fichier `/proc/sys`) et l'applique.
+pub fn write_by_name(name: &[u8], text: &[u8], privileged: bool) -> Result<(), SysctlError> {
+    let mut key = [0u8; SYSCTL_NAME_MAX];
+    let len = super::proc_sys::normalize_name(name, &mut key)?;
+    let tunable = find(&key[..len]).ok_or(SysctlError::NotFound)?;
+    let value = super::proc_sys::parse_value(tunable.kind, text)?;
+    write(&key[..len], value, privileged)
+}
+
+/// Itère sur les tunables enregistrés, dans l'ordre de la table.
+///
+/// Le callback est appelé sous le verrou du registre : il ne doit ni écrire de
+/// tunable ni bloquer.
+pub fn for_each(mut f: impl FnMut(usize, &'static Tunable)) {
+    let table = REGISTRY.lock();
+    for (idx, tunable) in table.iter().flatten().enumerate() {
+        f(idx, tunable);
+    }
+}
+
+/// Nom pointé valide : `[a-z0-9_]+(\.[a-z0-9_]+)*`, borné à SYSCTL_NAME_MAX.
+pub(crate) fn name_is_valid(name: &[u8]) -> bool {
+    if name.is_empty() || name.len() > SYSCTL_NAME_MAX {
+        return false;
+    }
+    if name[0] == b'.' || name[name.len() - 1] == b'.' {
+        return false;
+    }
+    let mut prev_dot = false;
+    for &b in name {
+        let dot = b == b'.';
+        if dot && prev_dot {
+            return false;
+        }
+        if !(dot || b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_') {
+            return false;
+        }
+        prev_dot = dot;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static T_RANGE: AtomicU64 = AtomicU64::new(0);
+    static TUNABLE_RANGE: Tunable = Tunable {
+        name: "test.registry.range",
+        description: "test",
+        kind: TunableKind::U64 { min: 10, max: 100 },
+        access: TunableAccess::RootWrite,
+        value: &T_RANGE,
+        default: 50,
+        on_change: None,
+    };
+
+    static T_RO: AtomicU64 = AtomicU64::new(0);
+    static TUNABLE_RO: Tunable = Tunable {
+        name: "test.registry.readonly",
+        description: "test",
+        kind: TunableKind::Bool,
+        access: TunableAccess::ReadOnly,
+        value: &T_RO,
+        default: 1,
+        on_change: None,
+    };
+
+    fn reject_odd(_old: u64, new: u64) -> Result<(), SysctlError> {
+        if new % 2 == 1 {
+            Err(SysctlError::Rejected)
+        } else {
+            Ok(())
+        }
+    }
+
+    static T_HOOK: AtomicU64 = AtomicU64::new(0);
+    static TUNABLE_HOOK: Tunable = Tunable {
+        name: "test.registry.even_only",
+        description: "test",
+        kind: TunableKind::U64 { min: 0, max: 1000 },
+        access: TunableAccess::RootWrite,
+        value: &T_HOOK,
+        default: 2,
+        on_change: Some(reject_odd),
+    };
+
+    static T_TEXT: AtomicU64 = AtomicU64::new(0);
+    static TUNABLE_TEXT: Tunable = Tunable {
+        name: "test.registry.text",
+        description: "test",
+        kind: TunableKind::U64 { min: 0, max: 100 },
+        access: TunableAccess::RootWrite,
+        value: &T_TEXT,
+        default: 0,
+        on_change: None,
+    };
+
+    fn setup() {
+        for t in [&TUNABLE_RANGE, &TUNABLE_RO, &TUNABLE_HOOK, &TUNABLE_TEXT] {
+            let _ = register(t);
+        }
+    }
+
+    #[test]
+    fn duplicate_registration_is_refused() {
+        setup();
+        assert_eq!(register(&TUNABLE_RANGE), Err(SysctlError::Duplicate));
+    }
+
+    #[test]
+    fn write_enforces_bounds_and_privilege() {
+        setup();
+        assert_eq!(
+            write(b"test.registry.range", 500, true),
+            Err(SysctlError::OutOfRange)
+        );
+        assert_eq!(
+            write(b"test.registry.range", 20, false),
+            Err(SysctlError::PermissionDenied)
+        );
+        assert_eq!(write(b"test.registry.range", 20, true), Ok(()));
+        assert_eq!(read(b"test.registry.range"), Ok(20));
+    }
+
+    #[test]
+    fn readonly_tunable_cannot_be_written() {
+        setup();
+        assert_eq!(
+            write(b"test.registry.readonly", 0, true),
+            Err(SysctlError::ReadOnly)
+        );
+        assert_eq!(read(b"test.registry.readonly"), Ok(1));
+    }
+
+    #[test]
+    fn hook_can_veto_a_change() {
+        setup();
+        assert_eq!(
+            write(b"test.registry.even_only", 7, true),
+            Err(SysctlError::Rejected)
+        );
+        assert_eq!(read(b"test.registry.even_only"), Ok(2));
+        assert_eq!(write(b"test.registry.even_only", 8, true), Ok(()));
+        assert_eq!(read(b"test.registry.even_only"), Ok(8));
+    }
+
+    #[test]
+    fn text_round_trip_through_proc_sys_path() {
+        setup();
+        assert_eq!(
+            write_by_name(b"/proc/sys/test/registry/text", b"42\n", true),
+            Ok(())
+        );
+        let mut out = [0u8; 32];
+        let n = read_by_name(b"test.registry.text", &mut out).unwrap();
+        assert_eq!(&out[..n], b"42\n");
+    }
+
+    #[test]
+    fn unknown_name_is_not_found() {
+        setup();
+        assert_eq!(read(b"test.registry.missing"), Err(SysctlError::NotFound));
+    }
+
+    #[test]
+    fn name_validation() {
+        assert!(name_is_valid(b"kernel.sched.rr_timeslice_ns"));
+        assert!(!name_is_valid(b""));
+        assert!(!name_is_valid(b".kernel"));
+        assert!(!name_is_valid(b"kernel..sched"));
+        assert!(!name_is_valid(b"Kernel.sched"));
+        assert!(!name_is_valid(b"kernel/sched"));
+    }
+}
@@ -0,0 +1,163 @@
+// kernel/src/trace/buffer.rs
+//
+// Anneaux de trace par CPU : enregistrements de taille fixe, écrasement des
+// plus anciens quand l'anneau est plein.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Nombre de CPUs tracés ; les événements des CPUs suivants sont comptés
+/// comme perdus.
+pub const TRACE_MAX_CPUS: usize = 8;
+/// Capacité d'un anneau (événements par CPU).
+pub const TRACE_RING_EVENTS: usize = 2048;
+/// Longueur maximale du nom d'un marqueur.
+pub const TRACE_NAME_LEN: usize = 16;
+
+/// Type d'événement.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceKind {
+    /// `pid`/`tid` : thread entrant ; `arg0` = `(pid << 32) | tid` du sortant.
+    SchedSwitch = 1,
+    /// `arg0` = endpoint, `arg1` = taille du message.
+    IpcSend = 2,
+    /// `arg0` = endpoint, `arg1` = séjour du message dans la mailbox (ns).
+    IpcRecv = 3,
+    /// Marqueur ponctuel posé depuis l'espace utilisateur.
+    Mark = 4,
+    /// Début d'une tranche nommée (`Mark` apparié par `MarkEnd`).
+    MarkBegin = 5,
+    MarkEnd = 6,
+}
+
+/// Un événement de trace.
+#[derive(Clone, Copy, Debug)]
+pub struct TraceRecord {
+    /// Horloge monotone (ns).
+    pub ts_ns: u64,
+    pub kind: TraceKind,
+    pub cpu: u16,
+    pub pid: u32,
+    pub tid: u32,
+    pub arg0: u64,
+    pub arg1: u64,
+    /// Nom du marqueur, complété de NUL.
+    pub name: [u8; TRACE_NAME_LEN],
+}
+
+impl TraceRecord {
+    pub const fn empty() -> Self {
+        Self {
+            ts_ns: 0,
+            kind: TraceKind::Mark,
+            cpu: 0,
+            pid: 0,
+            tid: 0,
+            arg0: 0,
+            arg1: 0,
+            name: [0u8; TRACE_NAME_LEN],
+        }
+    }
+}
+
+/// Anneau d'un CPU.
+pub struct CpuRing<const N: usize> {
+    /// Événements écrits depuis le dernier `reset()` ; le prochain slot est
+    /// `head % N`.
+    head: AtomicU64,
+    records: UnsafeCell<[TraceRecord; N]>,
+}
+
+// SAFETY: seul le CPU propriétaire écrit dans son anneau ; une interruption
+// imbriquée réserve un autre slot par `fetch_add`. La lecture n'a lieu
+// qu'une fois la trace figée (RÈGLE TRACE-03).
+unsafe impl<const N: usize> Sync for CpuRing<N> {}
+
+impl<const N: usize> CpuRing<N> {
+    pub const fn new() -> Self {
+        Self {
+            head: AtomicU64::new(0),
+            records: UnsafeCell::new([TraceRecord::empty(); N]),
+        }
+    }
+
+    #[inline]
+    pub fn push(&self, record: TraceRecord) {
+        let idx = self.head.fetch_add(1, Ordering::AcqRel) as usize % N;
+        // SAFETY: slot réservé par fetch_add ; aucun autre écrivain ne le
+        // reprendra avant N événements (cf. impl Sync).
+        unsafe {
+            (*self.records.get())[idx] = record;
+        }
+    }
+
+    pub fn reset(&self) {
+        self.head.store(0, Ordering::Release);
+    }
+
+    /// Événements conservés.
+    pub fn len(&self) -> usize {
+        core::cmp::min(self.head.load(Ordering::Acquire), N as u64) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Événements écrasés faute de place.
+    pub fn overwritten(&self) -> u64 {
+        self.head.load(Ordering::Acquire).saturating_sub(N as u64)
+    }
+
+    /// Parcourt les événements conservés, du plus ancien au plus récent.
+    pub fn for_each(&self, mut f: impl FnMut(&TraceRecord)) {
+        let head = self.head.load(Ordering::Acquire);
+        for seq in head.saturating_sub(N as u64)..head {
+            // SAFETY: lecture sur trace figée (RÈGLE TRACE-03) ; au pire un
+            // enregistrement en cours d'écriture est lu incomplet.
+            let record = unsafe { &(*self.records.get())[seq as usize % N] };
+            f(record);
+        }
+    }
+}
+
+impl<const N: usize> Default for CpuRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ts_ns: u64) -> TraceRecord {
+        TraceRecord {
+            ts_ns,
+            ..TraceRecord::empty()
+        }
+    }
+
+    #[test]
+    fn ring_keeps_most_recent_events_in_order() {
+        let ring = CpuRing::<4>::new();
+        assert!(ring.is_empty());
+        for ts in 1..=6 {
+            ring.push(at(ts));
+        }
+        assert_eq!(ring.len(), 4);
+        assert_eq!(ring.overwritten(), 2);
+        let mut seen = [0u64; 4];
+        let mut n = 0;
+        ring.for_each(|r| {
+            seen[n] = r.ts_ns;
+            n += 1;
+        });
+        assert_eq!(seen, [3, 4, 5, 6]);
+
+        ring.reset();
+        assert!(ring.is_empty());
+        ring.for_each(|_| panic!("ring should be empty"));
+    }
+}
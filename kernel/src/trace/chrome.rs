@@ -0,0 +1,326 @@
+// kernel/src/trace/chrome.rs
+//
+// Export au format Chrome trace-event (JSON), lisible par chrome://tracing et
+// ui.perfetto.dev.
+//
+//   • une piste par CPU (processus synthétique « CPUs ») : tranches `X` des
+//     threads ordonnancés, reconstruites à partir des SchedSwitch ;
+//   • les événements IPC sont des instants `i` sur la piste du thread
+//     (pid/tid réels) ;
+//   • les marqueurs utilisateur deviennent des tranches `B`/`E` ou des
+//     instants sur la piste du thread qui les pose.
+//
+// Horodatage en microsecondes avec trois décimales (résolution ns).
+// Sortie par morceaux via un callback : aucune allocation.
+
+use super::buffer::{TraceKind, TraceRecord, TRACE_MAX_CPUS};
+
+/// pid des pistes CPU, hors de l'espace des PIDs réels.
+pub const CPU_TRACK_PID: u32 = u32::MAX;
+
+#[derive(Clone, Copy)]
+struct Running {
+    pid: u32,
+    tid: u32,
+    since_ns: u64,
+}
+
+/// Convertit un flux d'enregistrements en document JSON.
+///
+/// Les enregistrements d'un même CPU doivent arriver dans l'ordre ; les CPUs
+/// peuvent être entrelacés.
+pub struct ChromeWriter<F: FnMut(&[u8])> {
+    out: F,
+    first: bool,
+    running: [Option<Running>; TRACE_MAX_CPUS],
+    seen_cpus: u32,
+    last_ns: u64,
+    events: u64,
+}
+
+impl<F: FnMut(&[u8])> ChromeWriter<F> {
+    pub fn new(mut out: F) -> Self {
+        out(b"{\"traceEvents\":[");
+        Self {
+            out,
+            first: true,
+            running: [None; TRACE_MAX_CPUS],
+            seen_cpus: 0,
+            last_ns: 0,
+            events: 0,
+        }
+    }
+
+    pub fn record(&mut self, r: &TraceRecord) {
+        self.last_ns = self.last_ns.max(r.ts_ns);
+        match r.kind {
+            TraceKind::SchedSwitch => {
+                let cpu = r.cpu as usize;
+                if cpu >= TRACE_MAX_CPUS {
+                    return;
+                }
+                self.seen_cpus |= 1 << cpu;
+                if let Some(prev) = self.running[cpu].take() {
+                    self.slice(cpu, prev, r.ts_ns);
+                }
+                self.running[cpu] = Some(Running {
+                    pid: r.pid,
+                    tid: r.tid,
+                    since_ns: r.ts_ns,
+                });
+            }
+            TraceKind::IpcSend | TraceKind::IpcRecv => {
+                let (name, arg1): (&[u8], &[u8]) = if r.kind == TraceKind::IpcSend {
+                    (b"ipc_send", b"len")
+                } else {
+                    (b"ipc_recv", b"latency_ns")
+                };
+                self.open(name, b"ipc", b"i", r.ts_ns, r.pid, r.tid);
+                self.raw(b",\"s\":\"t\",\"args\":{\"endpoint\":");
+                self.num(r.arg0);
+                self.raw(b",\"");
+                self.raw(arg1);
+                self.raw(b"\":");
+                self.num(r.arg1);
+                self.raw(b"}}");
+            }
+            TraceKind::Mark | TraceKind::MarkBegin | TraceKind::MarkEnd => {
+                let phase: &[u8] = match r.kind {
+                    TraceKind::MarkBegin => b"B",
+                    TraceKind::MarkEnd => b"E",
+                    _ => b"i",
+                };
+                let len = r.name.iter().position(|&b| b == 0).unwrap_or(r.name.len());
+                self.open(&r.name[..len], b"mark", phase, r.ts_ns, r.pid, r.tid);
+                if r.kind == TraceKind::Mark {
+                    self.raw(b",\"s\":\"t\"");
+                }
+                self.raw(b"}");
+            }
+        }
+    }
+
+    /// Ferme les tranches en cours, nomme les pistes CPU et termine le
+    /// document (`lost` : événements perdus à l'enregistrement). Retourne le
+    /// nombre d'événements écrits.
+    pub fn finish(mut self, lost: u64) -> u64 {
+        for cpu in 0..TRACE_MAX_CPUS {
+            if let Some(running) = self.running[cpu].take() {
+                let end = self.last_ns;
+                self.slice(cpu, running, end);
+            }
+        }
+        self.meta(b"process_name", CPU_TRACK_PID, 0, b"CPUs");
+        for cpu in 0..TRACE_MAX_CPUS {
+            if self.seen_cpus & (1 << cpu) != 0 {
+                let mut name = [0u8; 8];
+                let mut w = ByteWriter::new(&mut name);
+                w.push(b"cpu ");
+                w.num(cpu as u64);
+                let len = w.len;
+                self.meta(b"thread_name", CPU_TRACK_PID, cpu as u32, &name[..len]);
+            }
+        }
+        self.raw(b"],\"displayTimeUnit\":\"ns\",\"otherData\":{\"lost_events\":");
+        self.num(lost);
+        self.raw(b"}}\n");
+        self.events
+    }
+
+    fn slice(&mut self, cpu: usize, running: Running, end_ns: u64) {
+        let mut name = [0u8; 24];
+        let len = {
+            let mut w = ByteWriter::new(&mut name);
+            if running.pid == 0 {
+                w.push(b"kernel ");
+                w.num(running.tid as u64);
+            } else {
+                w.push(b"pid ");
+                w.num(running.pid as u64);
+            }
+            w.len
+        };
+        self.open(
+            &name[..len],
+            b"sched",
+            b"X",
+            running.since_ns,
+            CPU_TRACK_PID,
+            cpu as u32,
+        );
+        self.raw(b",\"dur\":");
+        self.ts(end_ns.saturating_sub(running.since_ns));
+        self.raw(b",\"args\":{\"pid\":");
+        self.num(running.pid as u64);
+        self.raw(b",\"tid\":");
+        self.num(running.tid as u64);
+        self.raw(b"}}");
+    }
+
+    fn meta(&mut self, name: &[u8], pid: u32, tid: u32, value: &[u8]) {
+        self.open(name, b"", b"M", 0, pid, tid);
+        self.raw(b",\"args\":{\"name\":");
+        self.string(value);
+        self.raw(b"}}");
+    }
+
+    /// Début commun d'un événement, objet laissé ouvert.
+    fn open(&mut self, name: &[u8], cat: &[u8], phase: &[u8], ts_ns: u64, pid: u32, tid: u32) {
+        if !self.first {
+            self.raw(b",\n");
+        }
+        self.first = false;
+        self.events += 1;
+        self.raw(b"{\"name\":");
+        self.string(name);
+        self.raw(b",\"cat\":\"");
+        self.raw(cat);
+        self.raw(b"\",\"ph\":\"");
+        self.raw(phase);
+        self.raw(b"\",\"ts\":");
+        self.ts(ts_ns);
+        self.raw(b",\"pid\":");
+        self.num(pid as u64);
+        self.raw(b",\"tid\":");
+        self.num(tid as u64);
+    }
+
+    fn raw(&mut self, bytes: &[u8]) {
+        (self.out)(bytes);
+    }
+
+    fn num(&mut self, value: u64) {
+        let mut buf = [0u8; 20];
+        let len = {
+            let mut w = ByteWriter::new(&mut buf);
+            w.num(value);
+            w.len
+        };
+        (self.out)(&buf[..len]);
+    }
+
+    /// Nanosecondes → microsecondes, trois décimales.
+    fn ts(&mut self, ns: u64) {
+        self.num(ns / 1000);
+        let frac = ns % 1000;
+        (self.out)(&[
+            b'.',
+            b'0' + (frac / 100) as u8,
+            b'0' + (frac / 10 % 10) as u8,
+            b'0' + (frac % 10) as u8,
+        ]);
+    }
+
+    /// Chaîne JSON ; octets non ASCII ou de contrôle remplacés par `?`.
+    fn string(&mut self, bytes: &[u8]) {
+        self.raw(b"\"");
+        for &b in bytes {
+            match b {
+                b'"' => self.raw(b"\\\""),
+                b'\\' => self.raw(b"\\\\"),
+                0x20..=0x7e => self.raw(&[b]),
+                _ => self.raw(b"?"),
+            }
+        }
+        self.raw(b"\"");
+    }
+}
+
+struct ByteWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> ByteWriter<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        let n = bytes.len().min(self.buf.len() - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&bytes[..n]);
+        self.len += n;
+    }
+
+    fn num(&mut self, mut value: u64) {
+        let mut digits = [0u8; 20];
+        let mut pos = digits.len();
+        loop {
+            pos -= 1;
+            digits[pos] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        self.push(&digits[pos..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::String;
+    use std::vec::Vec;
+
+    fn record(ts_ns: u64, kind: TraceKind, cpu: u16, pid: u32, tid: u32) -> TraceRecord {
+        TraceRecord {
+            ts_ns,
+            kind,
+            cpu,
+            pid,
+            tid,
+            ..TraceRecord::empty()
+        }
+    }
+
+    fn export(records: &[TraceRecord]) -> (String, u64) {
+        let mut out = Vec::new();
+        let mut writer = ChromeWriter::new(|b: &[u8]| out.extend_from_slice(b));
+        for r in records {
+            writer.record(r);
+        }
+        let events = writer.finish(0);
+        (String::from_utf8(out).unwrap(), events)
+    }
+
+    #[test]
+    fn switches_become_cpu_slices() {
+        let (json, events) = export(&[
+            record(1_000, TraceKind::SchedSwitch, 1, 7, 9),
+            record(3_500, TraceKind::SchedSwitch, 1, 0, 2),
+            record(4_000, TraceKind::SchedSwitch, 0, 7, 9),
+        ]);
+        assert!(json.starts_with("{\"traceEvents\":["));
+        assert!(json.ends_with("],\"displayTimeUnit\":\"ns\",\"otherData\":{\"lost_events\":0}}\n"));
+        assert!(json.contains(
+            "{\"name\":\"pid 7\",\"cat\":\"sched\",\"ph\":\"X\",\"ts\":1.000,\
+             \"pid\":4294967295,\"tid\":1,\"dur\":2.500,\"args\":{\"pid\":7,\"tid\":9}}"
+        ));
+        // Tranches encore ouvertes : closes au dernier horodatage vu.
+        assert!(json.contains("\"name\":\"kernel 2\",\"cat\":\"sched\",\"ph\":\"X\",\"ts\":3.500"));
+        assert!(json.contains("\"ts\":4.000,\"pid\":4294967295,\"tid\":0,\"dur\":0.000"));
+        assert!(json.contains("\"args\":{\"name\":\"cpu 1\"}"));
+        // 3 tranches + process_name + 2 thread_name.
+        assert_eq!(events, 6);
+    }
+
+    #[test]
+    fn ipc_and_marks_land_on_thread_tracks() {
+        let mut send = record(2_000, TraceKind::IpcSend, 0, 3, 4);
+        send.arg0 = 0x10;
+        send.arg1 = 64;
+        let mut begin = record(2_100, TraceKind::MarkBegin, 0, 3, 4);
+        begin.name[..7].copy_from_slice(b"frame\"\x01");
+        let mut end = begin;
+        end.kind = TraceKind::MarkEnd;
+        end.ts_ns = 18_700;
+        let (json, _) = export(&[send, begin, end]);
+        assert!(json.contains(
+            "{\"name\":\"ipc_send\",\"cat\":\"ipc\",\"ph\":\"i\",\"ts\":2.000,\"pid\":3,\
+             \"tid\":4,\"s\":\"t\",\"args\":{\"endpoint\":16,\"len\":64}}"
+        ));
+        assert!(json.contains("{\"name\":\"frame\\\"?\",\"cat\":\"mark\",\"ph\":\"B\""));
+        assert!(json.contains("\"ph\":\"E\",\"ts\":18.700,\"pid\":3,\"tid\":4}"));
+    }
+}
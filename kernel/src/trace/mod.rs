@@ -0,0 +1,215 @@
+// kernel/src/trace/mod.rs
+//
+// ═══════════════════════════════════════════════════════════════════════════════
+// trace — points de trace noyau et export Chrome trace-event (Exo-OS · Transverse)
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Le context switch, l'IPC raw (envoi / réception) et les marqueurs posés
+// depuis l'espace utilisateur (`exo_trace(MARK)` : trames d'un compositeur,
+// phases d'un serveur…) alimentent un anneau par CPU.
+//
+// À l'export, la trace est figée puis convertie en JSON Chrome trace-event,
+// écrit sur la console de debug (port 0xE9, le « serial » de QEMU) entre
+// `EXPORT_BEGIN` et `EXPORT_END`. Côté hôte, `tools/trace_extract.py`
+// récupère le document pour chrome://tracing ou ui.perfetto.dev.
+//
+// Activation : sysctl `kernel.trace.enabled` (root) ; activer vide les anneaux.
+// Un programme bpf attaché à un point de trace s'y exécute même trace
+// désactivée (`bpf::run_trace`), sans rien écrire dans les anneaux.
+//
+// RÈGLE TRACE-01 : NO-ALLOC — anneaux statiques, export par morceaux.
+// RÈGLE TRACE-02 : trace désactivée et point sans programme bpf, un point de
+//   trace coûte deux load(Relaxed).
+// RÈGLE TRACE-03 : un CPU n'écrit que dans son anneau ; la lecture se fait
+//   trace figée (export) et ne verrouille jamais les anneaux.
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub mod buffer;
+pub mod chrome;
+
+pub use buffer::{
+    CpuRing, TraceKind, TraceRecord, TRACE_MAX_CPUS, TRACE_NAME_LEN, TRACE_RING_EVENTS,
+};
+pub use chrome::ChromeWriter;
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::sysctl::SysctlError;
+
+/// Valeur du sysctl `kernel.trace.enabled`.
+pub static TRACE_ENABLED_TUNABLE: AtomicU64 = AtomicU64::new(0);
+
+static RINGS: [CpuRing<TRACE_RING_EVENTS>; TRACE_MAX_CPUS] =
+    [const { CpuRing::new() }; TRACE_MAX_CPUS];
+
+/// Événements des CPUs au-delà de `TRACE_MAX_CPUS`, non enregistrés.
+static LOST: AtomicU64 = AtomicU64::new(0);
+
+pub const EXPORT_BEGIN: &[u8] = b"\n=== EXO-TRACE BEGIN ===\n";
+pub const EXPORT_END: &[u8] = b"\n=== EXO-TRACE END ===\n";
+
+#[inline(always)]
+pub fn enabled() -> bool {
+    TRACE_ENABLED_TUNABLE.load(Ordering::Relaxed) != 0
+}
+
+/// Hook sysctl : une nouvelle session de trace repart d'anneaux vides.
+pub fn on_enabled_change(_old: u64, new: u64) -> Result<(), SysctlError> {
+    if new != 0 {
+        reset();
+    }
+    Ok(())
+}
+
+pub fn reset() {
+    for ring in RINGS.iter() {
+        ring.reset();
+    }
+    LOST.store(0, Ordering::Relaxed);
+}
+
+/// Événements perdus : CPUs non tracés et anneaux débordés.
+pub fn lost() -> u64 {
+    RINGS
+        .iter()
+        .fold(LOST.load(Ordering::Relaxed), |acc, ring| {
+            acc + ring.overwritten()
+        })
+}
+
+/// Le point de trace `kind` doit-il produire un événement ?
+#[inline(always)]
+fn active(kind: TraceKind) -> bool {
+    enabled() || crate::bpf::trace_probed(kind)
+}
+
+#[inline]
+fn emit(cpu: usize, record: TraceRecord) {
+    if crate::bpf::trace_probed(record.kind) {
+        crate::bpf::run_trace(&record);
+    }
+    if !enabled() {
+        return;
+    }
+    match RINGS.get(cpu) {
+        Some(ring) => ring.push(record),
+        None => {
+            LOST.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Point de trace du context switch (préemption désactivée).
+#[inline]
+pub fn sched_switch(cpu: usize, prev_pid: u32, prev_tid: u64, next_pid: u32, next_tid: u64) {
+    if !active(TraceKind::SchedSwitch) {
+        return;
+    }
+    emit(
+        cpu,
+        TraceRecord {
+            ts_ns: now_ns(),
+            kind: TraceKind::SchedSwitch,
+            cpu: cpu as u16,
+            pid: next_pid,
+            tid: next_tid as u32,
+            arg0: ((prev_pid as u64) << 32) | (prev_tid & 0xffff_ffff),
+            ..TraceRecord::empty()
+        },
+    );
+}
+
+/// Point de trace IPC (`IpcSend` / `IpcRecv`) pour le thread courant.
+#[inline]
+pub fn ipc(kind: TraceKind, endpoint: u64, arg: u64) {
+    if !active(kind) {
+        return;
+    }
+    let (cpu, pid, tid) = current();
+    emit(
+        cpu,
+        TraceRecord {
+            ts_ns: now_ns(),
+            kind,
+            cpu: cpu as u16,
+            pid,
+            tid,
+            arg0: endpoint,
+            arg1: arg,
+            ..TraceRecord::empty()
+        },
+    );
+}
+
+/// Marqueur du thread courant ; `name` est tronqué à `TRACE_NAME_LEN`.
+pub fn mark(kind: TraceKind, name: &[u8]) {
+    if !active(kind) {
+        return;
+    }
+    let (cpu, pid, tid) = current();
+    let mut record = TraceRecord {
+        ts_ns: now_ns(),
+        kind,
+        cpu: cpu as u16,
+        pid,
+        tid,
+        ..TraceRecord::empty()
+    };
+    let len = name.len().min(TRACE_NAME_LEN);
+    record.name[..len].copy_from_slice(&name[..len]);
+    emit(cpu, record);
+}
+
+/// Fige la trace et l'écrit au format Chrome trace-event dans `out`.
+/// Retourne le nombre d'événements JSON produits.
+pub fn export(out: impl FnMut(&[u8])) -> u64 {
+    TRACE_ENABLED_TUNABLE.store(0, Ordering::Relaxed);
+    let mut writer = ChromeWriter::new(out);
+    for ring in RINGS.iter() {
+        ring.for_each(|record| writer.record(record));
+    }
+    writer.finish(lost())
+}
+
+/// `export()` vers la console de debug, entre `EXPORT_BEGIN` et `EXPORT_END`.
+pub fn export_to_console() -> u64 {
+    use crate::arch::x86_64::terminal::debug_write;
+
+    debug_write(EXPORT_BEGIN);
+    let events = export(debug_write);
+    debug_write(EXPORT_END);
+    events
+}
+
+#[cfg(not(test))]
+#[inline]
+fn now_ns() -> u64 {
+    crate::scheduler::timer::clock::monotonic_ns()
+}
+
+#[cfg(test)]
+#[inline]
+fn now_ns() -> u64 {
+    0
+}
+
+/// (cpu, pid, tid) du thread courant.
+#[cfg(not(test))]
+#[inline]
+fn current() -> (usize, u32, u32) {
+    let cpu = crate::arch::x86_64::smp::percpu::current_cpu_id() as usize;
+    let tcb = crate::scheduler::core::switch::current_thread_raw();
+    if tcb.is_null() {
+        return (cpu, 0, 0);
+    }
+    // SAFETY: TCB du thread en cours d'exécution sur ce CPU, vivant tant
+    // qu'il s'exécute ; lecture de deux champs simples.
+    let tcb = unsafe { &*tcb };
+    (cpu, tcb.pid.0, tcb.tid as u32)
+}
+
+#[cfg(test)]
+#[inline]
+fn current() -> (usize, u32, u32) {
+    (0, 0, 0)
+}
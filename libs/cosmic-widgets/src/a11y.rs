@@ -0,0 +1,319 @@
+//! Accessibility tree. [`Ui::accessibility`] flattens the widget tree into
+//! the nodes a screen reader walks: a role, an accessible name, the value
+//! of editable widgets, screen bounds and the interaction state. Layout-only
+//! widgets (spacers) are left out; list rows become children of their list.
+//!
+//! [`publish`] sends an export to the `screen_reader` daemon over the
+//! `exo_services::a11y` protocol and [`events`] compares two exports to
+//! report what the user should hear about.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub use exo_services::a11y::Role;
+use exo_services::a11y::{
+    self as wire, encode_begin, Event, EventKind, NodeRecord, A11Y_MSG_BEGIN, A11Y_MSG_EVENT,
+    A11Y_MSG_NODE, NO_PARENT,
+};
+
+use crate::geometry::Rect;
+use crate::theme::State;
+use crate::ui::Ui;
+use crate::widget::{Kind, WidgetId};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct A11yNode {
+    pub widget: WidgetId,
+    /// Row of a `ListItem` in its list.
+    pub item: Option<usize>,
+    /// Index of the parent in the exported vector.
+    pub parent: Option<usize>,
+    pub role: Role,
+    pub name: String,
+    /// Current text of an input.
+    pub value: Option<String>,
+    /// Empty for list rows scrolled out of view.
+    pub bounds: Rect,
+    pub state: State,
+}
+
+impl Ui {
+    /// Exports the tree in pre-order: a parent always precedes its
+    /// children.
+    pub fn accessibility(&self) -> Vec<A11yNode> {
+        let mut out = Vec::new();
+        self.export(self.root, None, &mut out);
+        out
+    }
+
+    fn export(&self, id: WidgetId, parent: Option<usize>, out: &mut Vec<A11yNode>) {
+        let Some(node) = self.node(id) else {
+            return;
+        };
+        let state = self.state(id);
+        let (role, name, value) = match &node.kind {
+            Kind::Spacer => return,
+            Kind::Row | Kind::Column if id == self.root => (Role::Window, String::new(), None),
+            Kind::Row | Kind::Column => (Role::Group, String::new(), None),
+            Kind::Label { text } => (Role::Label, text.clone(), None),
+            Kind::Icon { name, .. } => (Role::Image, name.clone(), None),
+            Kind::Button { label } => (Role::Button, label.clone(), None),
+            Kind::Checkbox { label, .. } => (Role::CheckBox, label.clone(), None),
+            Kind::TextInput {
+                text, placeholder, ..
+            } => (Role::TextInput, placeholder.clone(), Some(text.clone())),
+            Kind::List { .. } => (Role::List, String::new(), None),
+        };
+        let at = out.len();
+        out.push(A11yNode {
+            widget: id,
+            item: None,
+            parent,
+            role,
+            name: node.a11y_label.clone().unwrap_or(name),
+            value,
+            bounds: node.bounds,
+            state,
+        });
+
+        if let Kind::List {
+            items,
+            selected,
+            scroll,
+        } = &node.kind
+        {
+            let row_h = self.row_height();
+            let r = node.bounds;
+            for (i, item) in items.iter().enumerate() {
+                let visible = i >= *scroll && i < scroll + node.visible_rows;
+                let bounds = if visible {
+                    Rect::new(r.x, r.y + ((i - scroll) as u32 * row_h) as i32, r.w, row_h)
+                } else {
+                    Rect::default()
+                };
+                out.push(A11yNode {
+                    widget: id,
+                    item: Some(i),
+                    parent: Some(at),
+                    role: Role::ListItem,
+                    name: item.clone(),
+                    value: None,
+                    bounds,
+                    state: State(state.0 & State::DISABLED)
+                        .with(State::SELECTED, *selected == Some(i))
+                        .with(
+                            State::FOCUSED,
+                            state.has(State::FOCUSED) && *selected == Some(i),
+                        ),
+                });
+            }
+        }
+        for &child in &node.children {
+            self.export(child, Some(at), out);
+        }
+    }
+}
+
+impl A11yNode {
+    /// `A11Y_MSG_NODE` record of the node at `index` in its export.
+    pub fn record(&self, index: usize) -> NodeRecord<'_> {
+        let mut state = 0;
+        for (bit, on) in [
+            (wire::STATE_FOCUSED, State::FOCUSED),
+            (wire::STATE_DISABLED, State::DISABLED),
+            (wire::STATE_CHECKED, State::CHECKED),
+            (wire::STATE_SELECTED, State::SELECTED),
+        ] {
+            if self.state.has(on) {
+                state |= bit;
+            }
+        }
+        if self.role == Role::CheckBox {
+            state |= wire::STATE_CHECKABLE;
+        }
+        let r = self.bounds;
+        NodeRecord {
+            index: index as u16,
+            parent: self.parent.map_or(NO_PARENT, |p| p as u16),
+            role: self.role,
+            state,
+            bounds: (r.x, r.y, r.w, r.h),
+            name: &self.name,
+            value: self.value.as_deref(),
+        }
+    }
+
+    fn same_node(&self, other: &A11yNode) -> bool {
+        self.widget == other.widget && self.item == other.item
+    }
+}
+
+/// Sends `nodes` as `window`'s tree: `send(msg_type, payload)` is called
+/// for `A11Y_MSG_BEGIN`, then once per node. Nodes past
+/// [`wire::MAX_NODES`] are not sent; the daemon would drop them.
+pub fn publish(window: u32, nodes: &[A11yNode], mut send: impl FnMut(u32, &[u8])) {
+    let nodes = &nodes[..nodes.len().min(wire::MAX_NODES)];
+    let mut buf = [0u8; NodeRecord::MAX_LEN];
+    if let Some(len) = encode_begin(window, nodes.len() as u16, &mut buf) {
+        send(A11Y_MSG_BEGIN, &buf[..len]);
+    }
+    for (i, node) in nodes.iter().enumerate() {
+        if let Some(len) = node.record(i).encode(&mut buf) {
+            send(A11Y_MSG_NODE, &buf[..len]);
+        }
+    }
+}
+
+/// `A11Y_MSG_EVENT` payloads telling the screen reader how `new` differs
+/// from the previous export `old`: a focus move, then name, value and
+/// state changes of nodes present in both.
+pub fn events(old: &[A11yNode], new: &[A11yNode]) -> Vec<Event> {
+    let mut out = Vec::new();
+    let focus = |nodes: &[A11yNode]| nodes.iter().rposition(|n| n.state.has(State::FOCUSED));
+    if let Some(now) = focus(new) {
+        let before = focus(old).map(|i| &old[i]);
+        if !before.is_some_and(|b| b.same_node(&new[now])) {
+            out.push(Event {
+                kind: EventKind::Focus,
+                node: now as u16,
+            });
+        }
+    }
+    for (i, node) in new.iter().enumerate() {
+        let Some(prev) = old.iter().find(|o| o.same_node(node)) else {
+            continue;
+        };
+        let mut changed = |differs: bool, kind| {
+            if differs {
+                out.push(Event {
+                    kind,
+                    node: i as u16,
+                });
+            }
+        };
+        changed(prev.name != node.name, EventKind::NameChanged);
+        changed(prev.value != node.value, EventKind::ValueChanged);
+        // Focus moves are reported above.
+        let relevant = |s: State| s.0 & (State::CHECKED | State::SELECTED | State::DISABLED);
+        changed(
+            relevant(prev.state) != relevant(node.state),
+            EventKind::StateChanged,
+        );
+    }
+    out
+}
+
+/// Encodes an event for [`publish`]'s `send`.
+pub fn send_event(event: Event, mut send: impl FnMut(u32, &[u8])) {
+    let mut buf = [0u8; Event::LEN];
+    if let Some(len) = event.encode(&mut buf) {
+        send(A11Y_MSG_EVENT, &buf[..len]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Size;
+    use crate::text::Monospace;
+    use crate::theme::StockTheme;
+    use crate::widget::Length;
+    use alloc::string::ToString;
+
+    #[test]
+    fn exports_roles_names_and_list_rows() {
+        let mut ui = Ui::new();
+        let root = ui.root();
+        ui.set_a11y_label(root, "Files");
+        let bar = ui.push(root, Kind::Row).unwrap();
+        let back = ui.push(bar, Kind::icon("go-previous", 24)).unwrap();
+        ui.set_a11y_label(back, "Back");
+        ui.push(bar, Kind::Spacer).unwrap();
+        let search = ui.push(bar, Kind::text_input("Search")).unwrap();
+        ui.set_text(search, "notes");
+        let items = (0..10).map(|i| i.to_string()).collect();
+        let list = ui.push(root, Kind::list(items)).unwrap();
+        ui.set_height(list, Length::Fixed(ui.row_height() * 3));
+        ui.layout(Size::new(300, 200), &StockTheme::light(), &Monospace);
+        ui.select(list, Some(1));
+        ui.focus(list);
+
+        let tree = ui.accessibility();
+        assert_eq!(tree.len(), 5 + 10);
+        assert_eq!(
+            (tree[0].role, tree[0].name.as_str()),
+            (Role::Window, "Files")
+        );
+        assert_eq!((tree[1].role, tree[1].parent), (Role::Group, Some(0)));
+        assert_eq!((tree[2].role, tree[2].name.as_str()), (Role::Image, "Back"));
+        assert_eq!(tree[3].role, Role::TextInput);
+        assert_eq!(tree[3].name, "Search");
+        assert_eq!(tree[3].value.as_deref(), Some("notes"));
+        assert_eq!((tree[4].role, tree[4].parent), (Role::List, Some(0)));
+        assert!(tree[4].state.has(State::FOCUSED));
+
+        let rows = &tree[5..];
+        assert!(rows
+            .iter()
+            .all(|n| n.role == Role::ListItem && n.parent == Some(4)));
+        assert_eq!(rows[1].name, "1");
+        assert!(rows[1].state.has(State::SELECTED) && rows[1].state.has(State::FOCUSED));
+        assert!(!rows[0].state.has(State::SELECTED));
+        let list_y = ui.bounds(list).unwrap().y;
+        assert_eq!(rows[2].bounds.y, list_y + 2 * ui.row_height() as i32);
+        assert_eq!(rows[3].bounds, Rect::default());
+    }
+
+    #[test]
+    fn publishes_records_and_reports_changes() {
+        let mut ui = Ui::new();
+        let root = ui.root();
+        let ok = ui.push(root, Kind::button("OK")).unwrap();
+        let hidden = ui.push(root, Kind::checkbox("Hidden", false)).unwrap();
+        let name = ui.push(root, Kind::text_input("Name")).unwrap();
+        ui.layout(Size::new(300, 200), &StockTheme::light(), &Monospace);
+        ui.focus(ok);
+        let before = ui.accessibility();
+
+        let mut sent = Vec::new();
+        publish(3, &before, |msg, payload| {
+            sent.push((msg, payload.to_vec()))
+        });
+        assert_eq!(sent.len(), 1 + before.len());
+        assert_eq!(sent[0].0, A11Y_MSG_BEGIN);
+        assert_eq!(wire::decode_begin(&sent[0].1), Some((3, 4)));
+        let record = NodeRecord::decode(&sent[3].1).unwrap();
+        assert_eq!((record.index, record.parent), (2, 0));
+        assert_eq!((record.role, record.name), (Role::CheckBox, "Hidden"));
+        assert_eq!(record.state, wire::STATE_CHECKABLE);
+        assert!(events(&before, &before).is_empty());
+
+        ui.focus(hidden);
+        ui.set_checked(hidden, true);
+        ui.set_text(name, "Ada");
+        let after = ui.accessibility();
+        let kinds: Vec<_> = events(&before, &after)
+            .iter()
+            .map(|e| (e.kind, e.node))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (EventKind::Focus, 2),
+                (EventKind::StateChanged, 2),
+                (EventKind::ValueChanged, 3),
+            ]
+        );
+        let record = after[2].record(2);
+        assert_eq!(
+            record.state,
+            wire::STATE_CHECKABLE | wire::STATE_CHECKED | wire::STATE_FOCUSED
+        );
+
+        let mut sent = Vec::new();
+        send_event(events(&before, &after)[0], |msg, p| {
+            sent.push((msg, p.to_vec()))
+        });
+        assert_eq!(sent[0].0, A11Y_MSG_EVENT);
+    }
+}
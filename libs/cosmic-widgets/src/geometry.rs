@@ -0,0 +1,80 @@
+//! Logical-pixel geometry. Scaling to buffer pixels happens in the renderer
+//! (`exo_services::scale`), never in widget code.
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Size {
+    pub w: u32,
+    pub h: u32,
+}
+
+impl Size {
+    pub const fn new(w: u32, h: u32) -> Self {
+        Self { w, h }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: u32,
+    pub h: u32,
+}
+
+impl Rect {
+    pub const fn new(x: i32, y: i32, w: u32, h: u32) -> Self {
+        Self { x, y, w, h }
+    }
+
+    pub fn right(&self) -> i64 {
+        self.x as i64 + self.w as i64
+    }
+
+    pub fn bottom(&self) -> i64 {
+        self.y as i64 + self.h as i64
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.x <= p.x
+            && self.y <= p.y
+            && (p.x as i64) < self.right()
+            && (p.y as i64) < self.bottom()
+    }
+
+    /// `self` shrunk by `pad` on every side, empty if too small.
+    pub fn inset(&self, pad: u32) -> Rect {
+        Rect {
+            x: self.x.saturating_add(pad as i32),
+            y: self.y.saturating_add(pad as i32),
+            w: self.w.saturating_sub(2 * pad),
+            h: self.h.saturating_sub(2 * pad),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_is_half_open_and_inset_saturates() {
+        let r = Rect::new(10, 10, 20, 5);
+        assert!(r.contains(Point::new(10, 14)));
+        assert!(!r.contains(Point::new(30, 10)));
+        assert!(!r.contains(Point::new(10, 15)));
+        assert_eq!(r.inset(2), Rect::new(12, 12, 16, 1));
+        assert_eq!(r.inset(4).h, 0);
+    }
+}
@@ -0,0 +1,245 @@
+//! Grapheme clusters: what a user perceives as one character, so the caret
+//! never lands inside "é" written as `e` + U+0301, a flag, or an emoji
+//! sequence such as 👩‍💻.
+//!
+//! The rules are those of UAX #29 for extended grapheme clusters except
+//! prepended concatenation marks, with the character properties
+//! approximated by block ranges (combining marks, Hangul jamo, emoji).
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Cat {
+    Cr,
+    Lf,
+    Control,
+    /// Combining marks, variation selectors, emoji modifiers and tags.
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    /// Extended pictographic (emoji).
+    Pictographic,
+    L,
+    V,
+    T,
+    Lv,
+    Lvt,
+    Other,
+}
+
+fn category(ch: char) -> Cat {
+    let c = ch as u32;
+    match c {
+        0x0d => Cat::Cr,
+        0x0a => Cat::Lf,
+        0x00..=0x1f | 0x7f..=0x9f | 0x200b | 0x2028 | 0x2029 => Cat::Control,
+        0x200d => Cat::Zwj,
+        0x300..=0x36f
+        | 0x483..=0x489
+        | 0x591..=0x5bd
+        | 0x5bf
+        | 0x5c1..=0x5c2
+        | 0x5c4..=0x5c5
+        | 0x5c7
+        | 0x610..=0x61a
+        | 0x64b..=0x65f
+        | 0x670
+        | 0x6d6..=0x6dc
+        | 0x6df..=0x6e4
+        | 0x6e7..=0x6e8
+        | 0x6ea..=0x6ed
+        | 0x900..=0x903
+        | 0x93a..=0x94f
+        | 0x951..=0x957
+        | 0x962..=0x963
+        | 0xe31
+        | 0xe34..=0xe3a
+        | 0xe47..=0xe4e
+        | 0x1ab0..=0x1aff
+        | 0x1dc0..=0x1dff
+        | 0x200c
+        | 0x20d0..=0x20ff
+        | 0xfe00..=0xfe0f
+        | 0xfe20..=0xfe2f
+        | 0x1f3fb..=0x1f3ff
+        | 0xe0020..=0xe007f
+        | 0xe0100..=0xe01ef => Cat::Extend,
+        0x1f1e6..=0x1f1ff => Cat::RegionalIndicator,
+        0xa9
+        | 0xae
+        | 0x203c
+        | 0x2049
+        | 0x2122
+        | 0x2139
+        | 0x2194..=0x21aa
+        | 0x231a..=0x23ff
+        | 0x24c2
+        | 0x25aa..=0x25fe
+        | 0x2600..=0x27bf
+        | 0x2934..=0x2935
+        | 0x2b05..=0x2b55
+        | 0x3030
+        | 0x303d
+        | 0x3297
+        | 0x3299
+        | 0x1f000..=0x1f1e5
+        | 0x1f200..=0x1f3fa
+        | 0x1f400..=0x1faff
+        | 0x1fc00..=0x1fffd => Cat::Pictographic,
+        0x1100..=0x115f | 0xa960..=0xa97c => Cat::L,
+        0x1160..=0x11a7 | 0xd7b0..=0xd7c6 => Cat::V,
+        0x11a8..=0x11ff | 0xd7cb..=0xd7fb => Cat::T,
+        0xac00..=0xd7a3 if (c - 0xac00).is_multiple_of(28) => Cat::Lv,
+        0xac00..=0xd7a3 => Cat::Lvt,
+        _ => Cat::Other,
+    }
+}
+
+/// Characters drawn over the previous one rather than next to it.
+pub fn is_zero_width(ch: char) -> bool {
+    matches!(category(ch), Cat::Extend | Cat::Zwj)
+}
+
+/// What the rules need to know about the cluster so far.
+struct State {
+    prev: Cat,
+    /// Regional indicators in a row, ending at `prev`.
+    indicators: usize,
+    /// The text so far ends with a pictograph and extenders.
+    pictograph: bool,
+    /// ... followed by a ZWJ (`prev`).
+    pictograph_zwj: bool,
+}
+
+impl State {
+    fn new(first: char) -> Self {
+        let mut state = Self {
+            prev: Cat::Other,
+            indicators: 0,
+            pictograph: false,
+            pictograph_zwj: false,
+        };
+        state.push(category(first));
+        state
+    }
+
+    fn push(&mut self, cat: Cat) {
+        self.indicators = if cat == Cat::RegionalIndicator {
+            self.indicators + 1
+        } else {
+            0
+        };
+        self.pictograph_zwj = cat == Cat::Zwj && self.pictograph;
+        self.pictograph = match cat {
+            Cat::Pictographic => true,
+            Cat::Extend => self.pictograph,
+            _ => false,
+        };
+        self.prev = cat;
+    }
+
+    fn breaks_before(&self, cat: Cat) -> bool {
+        use Cat::*;
+        match (self.prev, cat) {
+            (Cr, Lf) => false,
+            (Control | Cr | Lf, _) | (_, Control | Cr | Lf) => true,
+            (L, L | V | Lv | Lvt) | (Lv | V, V | T) | (Lvt | T, T) => false,
+            (_, Extend | Zwj) => false,
+            (Zwj, Pictographic) if self.pictograph_zwj => false,
+            (RegionalIndicator, RegionalIndicator) => self.indicators.is_multiple_of(2),
+            _ => true,
+        }
+    }
+}
+
+/// End of the cluster starting at byte `at` (`text.len()` at the end).
+pub fn next_boundary(text: &str, at: usize) -> usize {
+    let mut chars = text[at..].char_indices();
+    let Some((_, first)) = chars.next() else {
+        return text.len();
+    };
+    let mut state = State::new(first);
+    for (i, ch) in chars {
+        let cat = category(ch);
+        if state.breaks_before(cat) {
+            return at + i;
+        }
+        state.push(cat);
+    }
+    text.len()
+}
+
+/// Start of the cluster ending at byte `at` (0 at the start). Scans from
+/// the start of `text`: meant for single lines.
+pub fn prev_boundary(text: &str, at: usize) -> usize {
+    let mut start = 0;
+    while start < text.len() {
+        let end = next_boundary(text, start);
+        if end >= at {
+            return start;
+        }
+        start = end;
+    }
+    start
+}
+
+/// The clusters of `text`, in order.
+pub fn clusters(text: &str) -> impl Iterator<Item = &str> {
+    let mut at = 0;
+    core::iter::from_fn(move || {
+        if at == text.len() {
+            return None;
+        }
+        let end = next_boundary(text, at);
+        let cluster = &text[at..end];
+        at = end;
+        Some(cluster)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn split(text: &str) -> Vec<&str> {
+        clusters(text).collect()
+    }
+
+    #[test]
+    fn splits_extended_clusters() {
+        assert_eq!(split("ab"), ["a", "b"]);
+        assert_eq!(split("e\u{301}x"), ["e\u{301}", "x"]);
+        assert_eq!(split("\r\n\n"), ["\r\n", "\n"]);
+        assert_eq!(split("a\u{301}\n\u{301}"), ["a\u{301}", "\n", "\u{301}"]);
+        // Flags pair regional indicators two by two.
+        assert_eq!(split("🇫🇷🇩🇪🇮"), ["🇫🇷", "🇩🇪", "🇮"]);
+        // ZWJ sequences, skin tones and presentation selectors.
+        assert_eq!(split("👩‍💻!"), ["👩‍💻", "!"]);
+        assert_eq!(split("👍🏽👍"), ["👍🏽", "👍"]);
+        assert_eq!(split("❤️‍🔥"), ["❤️‍🔥"]);
+        assert_eq!(split("a\u{200d}💻"), ["a\u{200d}", "💻"]);
+        // Hangul jamo compose, precomposed syllables stay apart.
+        assert_eq!(
+            split("\u{1100}\u{1161}\u{11a8}한"),
+            ["\u{1100}\u{1161}\u{11a8}", "한"]
+        );
+        assert_eq!(split("가\u{11a8}나"), ["가\u{11a8}", "나"]);
+        assert_eq!(split(""), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn boundaries_step_over_whole_clusters() {
+        let text = "ae\u{301}🇫🇷b";
+        assert_eq!(next_boundary(text, 0), 1);
+        assert_eq!(next_boundary(text, 1), 4);
+        assert_eq!(next_boundary(text, 4), 12);
+        assert_eq!(next_boundary(text, 12), 13);
+        assert_eq!(next_boundary(text, 13), 13);
+        assert_eq!(prev_boundary(text, 13), 12);
+        assert_eq!(prev_boundary(text, 12), 4);
+        assert_eq!(prev_boundary(text, 4), 1);
+        assert_eq!(prev_boundary(text, 1), 0);
+        assert_eq!(prev_boundary(text, 0), 0);
+        assert!(is_zero_width('\u{301}') && is_zero_width('\u{200d}'));
+        assert!(!is_zero_width('e'));
+    }
+}
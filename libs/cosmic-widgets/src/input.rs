@@ -0,0 +1,111 @@
+//! Input events as the Wayland client layer delivers them: pointer and touch
+//! coordinates in surface-local logical pixels, evdev button codes
+//! (`wl_pointer.button`), keys already translated to XKB keysyms through the
+//! keymap, and text as committed characters.
+
+use crate::geometry::Point;
+
+/// `BTN_LEFT` from `linux/input-event-codes.h`.
+pub const BTN_LEFT: u32 = 0x110;
+pub const BTN_RIGHT: u32 = 0x111;
+
+/// Two clicks on the same target closer than this are a double click.
+pub const DOUBLE_CLICK_MS: u32 = 400;
+
+/// Pinch scale of fingers back at their starting spread.
+pub const PINCH_UNIT: u32 = 256;
+
+/// Full pressure of a stylus (`zwp_tablet_tool_v2.pressure`).
+pub const PRESSURE_MAX: u32 = 65535;
+
+/// XKB keysyms handled by the toolkit.
+pub mod keysym {
+    pub const BACKSPACE: u32 = 0xff08;
+    pub const TAB: u32 = 0xff09;
+    pub const RETURN: u32 = 0xff0d;
+    pub const ESCAPE: u32 = 0xff1b;
+    pub const HOME: u32 = 0xff50;
+    pub const LEFT: u32 = 0xff51;
+    pub const UP: u32 = 0xff52;
+    pub const RIGHT: u32 = 0xff53;
+    pub const DOWN: u32 = 0xff54;
+    pub const PAGE_UP: u32 = 0xff55;
+    pub const PAGE_DOWN: u32 = 0xff56;
+    pub const END: u32 = 0xff57;
+    pub const DELETE: u32 = 0xffff;
+    pub const SPACE: u32 = 0x0020;
+    /// `ISO_Left_Tab`, what Shift+Tab produces with most keymaps.
+    pub const LEFT_TAB: u32 = 0xfe20;
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+/// Stylus state after a `zwp_tablet_tool_v2.frame`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Tool {
+    pub pos: Point,
+    /// 0..=[`PRESSURE_MAX`].
+    pub pressure: u32,
+    /// Hundredths of a degree, positive towards the right and the bottom.
+    pub tilt_x: i32,
+    pub tilt_y: i32,
+    /// Touching the surface.
+    pub tip: bool,
+    pub eraser: bool,
+    pub time_ms: u32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Event {
+    PointerMotion(Point),
+    PointerButton {
+        button: u32,
+        pressed: bool,
+        /// `wl_pointer.button` timestamp, for double clicks.
+        time_ms: u32,
+    },
+    PointerLeave,
+    /// Wheel steps, positive downwards (`wl_pointer.axis_discrete`).
+    Scroll(i32),
+    Key {
+        keysym: u32,
+        pressed: bool,
+        modifiers: Modifiers,
+    },
+    /// Character committed by the keymap (key repeat included).
+    Text(char),
+    /// The surface lost keyboard focus.
+    KeyboardLeave,
+    /// A finger touched the surface (`wl_touch.down`); `id` names it until
+    /// it lifts.
+    TouchDown {
+        id: i32,
+        pos: Point,
+        time_ms: u32,
+    },
+    TouchMotion {
+        id: i32,
+        pos: Point,
+    },
+    TouchUp {
+        id: i32,
+        time_ms: u32,
+    },
+    /// The compositor took the fingers for a gesture (`wl_touch.cancel`).
+    TouchCancel,
+    /// Two-finger pinch (`zwp_pointer_gesture_pinch_v1`): spread relative to
+    /// the start, in [`PINCH_UNIT`]s; `end` on the last update.
+    Pinch {
+        scale: u32,
+        end: bool,
+    },
+    /// A stylus in proximity of the surface moved, pressed or lifted.
+    Tablet(Tool),
+    /// The stylus left proximity (`zwp_tablet_tool_v2.proximity_out`).
+    TabletLeave,
+}
@@ -0,0 +1,197 @@
+//! Box layout. A row or column gives every `Shrink` and `Fixed` child its
+//! size along the main axis, then shares what is left between the `Fill`
+//! children by weight. Across, a `Fill` child stretches and the others keep
+//! their natural size, aligned to the start.
+
+use alloc::vec::Vec;
+
+use crate::geometry::{Rect, Size};
+use crate::text::TextMeasure;
+use crate::theme::{Metrics, Theme};
+use crate::ui::Ui;
+use crate::widget::{Kind, Length, WidgetId};
+
+fn resolve(length: Length, natural: u32) -> u32 {
+    match length {
+        Length::Fixed(v) => v,
+        Length::Shrink | Length::Fill(_) => natural,
+    }
+}
+
+impl Ui {
+    /// Lays the tree out in a surface of `size` logical pixels.
+    pub fn layout(&mut self, size: Size, theme: &dyn Theme, text: &dyn TextMeasure) {
+        self.metrics = *theme.metrics();
+        let m = self.metrics;
+        self.place(self.root, Rect::new(0, 0, size.w, size.h), &m, text);
+        self.layout_dirty = false;
+        self.paint_dirty = true;
+    }
+
+    /// Natural size of `id`, its `Fixed` lengths applied.
+    fn natural(&self, id: WidgetId, m: &Metrics, text: &dyn TextMeasure) -> Size {
+        let Some(node) = self.node(id) else {
+            return Size::default();
+        };
+        let px = m.text_px;
+        let line = m.line_height();
+        let pad = m.padding;
+        let natural = match &node.kind {
+            Kind::Row | Kind::Column => {
+                let row = node.kind == Kind::Row;
+                let (mut main, mut cross) = (0u32, 0u32);
+                for (i, &child) in node.children.iter().enumerate() {
+                    let s = self.natural(child, m, text);
+                    let (cm, cc) = if row { (s.w, s.h) } else { (s.h, s.w) };
+                    main += cm + if i > 0 { m.spacing } else { 0 };
+                    cross = cross.max(cc);
+                }
+                let (w, h) = if row { (main, cross) } else { (cross, main) };
+                Size::new(w + 2 * node.padding, h + 2 * node.padding)
+            }
+            Kind::Label { text: t } => Size::new(text.width(t, px), line),
+            Kind::Icon { px, .. } => Size::new(*px as u32, *px as u32),
+            Kind::Button { label } => Size::new(text.width(label, px) + 2 * pad, line + 2 * pad),
+            Kind::Checkbox { label, .. } => {
+                Size::new(line + m.spacing + text.width(label, px), line)
+            }
+            Kind::TextInput { .. } => Size::new(m.input_width, line + 2 * pad),
+            Kind::List { items, .. } => {
+                let widest = items.iter().map(|i| text.width(i, px)).max().unwrap_or(0);
+                Size::new(widest + 2 * pad, items.len() as u32 * (line + pad))
+            }
+            Kind::Spacer => Size::default(),
+        };
+        Size::new(
+            resolve(node.width, natural.w),
+            resolve(node.height, natural.h),
+        )
+    }
+
+    fn place(&mut self, id: WidgetId, rect: Rect, m: &Metrics, text: &dyn TextMeasure) {
+        let row_h = self.row_height().max(1);
+        let Some(node) = self.nodes[id.0 as usize].as_mut() else {
+            return;
+        };
+        node.bounds = rect;
+        let row = match &mut node.kind {
+            Kind::List { items, scroll, .. } => {
+                node.visible_rows = (rect.h / row_h) as usize;
+                *scroll = (*scroll).min(items.len().saturating_sub(node.visible_rows));
+                return;
+            }
+            Kind::Row => true,
+            Kind::Column => false,
+            _ => return,
+        };
+        let inner = rect.inset(node.padding);
+        let children = node.children.clone();
+        if children.is_empty() {
+            return;
+        }
+
+        let (avail_main, avail_cross) = if row {
+            (inner.w, inner.h)
+        } else {
+            (inner.h, inner.w)
+        };
+        let gaps = m.spacing * (children.len() as u32 - 1);
+        let mut plan: Vec<(Size, Length, Length)> = Vec::with_capacity(children.len());
+        let (mut rigid, mut weights) = (0u32, 0u32);
+        for &child in &children {
+            let s = self.natural(child, m, text);
+            let n = self.node(child).expect("child of a live node");
+            let (main_len, cross_len) = if row {
+                (n.width, n.height)
+            } else {
+                (n.height, n.width)
+            };
+            match main_len {
+                Length::Fill(w) => weights += w.max(1) as u32,
+                _ => rigid += if row { s.w } else { s.h },
+            }
+            plan.push((s, main_len, cross_len));
+        }
+
+        let mut free = avail_main.saturating_sub(gaps).saturating_sub(rigid);
+        let mut left = avail_main;
+        let mut pen = 0u32;
+        for (&child, (s, main_len, cross_len)) in children.iter().zip(plan) {
+            let natural_main = if row { s.w } else { s.h };
+            let main = match main_len {
+                Length::Fill(w) => {
+                    let w = w.max(1) as u32;
+                    let share = (free as u64 * w as u64 / weights.max(1) as u64) as u32;
+                    free -= share;
+                    weights -= w;
+                    share
+                }
+                _ => natural_main.min(left),
+            };
+            let cross = match cross_len {
+                Length::Fill(_) => avail_cross,
+                _ => (if row { s.h } else { s.w }).min(avail_cross),
+            };
+            let r = if row {
+                Rect::new(inner.x + pen as i32, inner.y, main, cross)
+            } else {
+                Rect::new(inner.x, inner.y + pen as i32, cross, main)
+            };
+            self.place(child, r, m, text);
+            pen += main + m.spacing;
+            left = left.saturating_sub(main + m.spacing);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::Monospace;
+    use crate::theme::StockTheme;
+
+    #[test]
+    fn fill_children_share_the_leftover_by_weight() {
+        let theme = StockTheme::light();
+        let mut ui = Ui::new();
+        let root = ui.root();
+        ui.set_padding(root, 10);
+        let bar = ui.push(root, Kind::Row).unwrap();
+        ui.set_width(bar, Length::Fill(1));
+        let icon = ui.push(bar, Kind::icon("folder", 24)).unwrap();
+        let a = ui.push(bar, Kind::Spacer).unwrap();
+        ui.set_width(a, Length::Fill(1));
+        let b = ui.push(bar, Kind::Spacer).unwrap();
+        ui.set_width(b, Length::Fill(3));
+        let fixed = ui.push(bar, Kind::button("Open")).unwrap();
+        ui.set_width(fixed, Length::Fixed(60));
+        let body = ui.push(root, Kind::Column).unwrap();
+        ui.set_height(body, Length::Fill(1));
+        ui.set_width(body, Length::Fill(1));
+
+        ui.layout(Size::new(420, 300), &theme, &Monospace);
+        assert!(!ui.needs_layout());
+        // 400 inside the padding, 3 gaps of 8, 24 + 60 rigid: 292 to share.
+        assert_eq!(ui.bounds(icon), Some(Rect::new(10, 10, 24, 24)));
+        assert_eq!(ui.bounds(a).unwrap().w, 73);
+        assert_eq!(ui.bounds(b).unwrap().w, 219);
+        assert_eq!(ui.bounds(fixed).unwrap().x, 10 + 24 + 8 + 73 + 8 + 219 + 8);
+        assert_eq!(ui.bounds(fixed).unwrap().right(), 410);
+        // The button (32 high) sets the row height; the body takes the rest.
+        assert_eq!(ui.bounds(bar).unwrap().h, 32);
+        assert_eq!(ui.bounds(body), Some(Rect::new(10, 50, 400, 240)));
+    }
+
+    #[test]
+    fn overflowing_rigid_children_are_clipped_to_the_box() {
+        let mut ui = Ui::new();
+        let root = ui.root();
+        let row = ui.push(root, Kind::Row).unwrap();
+        let wide = ui.push(row, Kind::button("x")).unwrap();
+        ui.set_width(wide, Length::Fixed(500));
+        let after = ui.push(row, Kind::label("y")).unwrap();
+        ui.layout(Size::new(100, 100), &StockTheme::dark(), &Monospace);
+        assert_eq!(ui.bounds(wide).unwrap().w, 100);
+        assert_eq!(ui.bounds(after).unwrap().w, 0);
+    }
+}
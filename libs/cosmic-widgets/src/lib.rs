@@ -0,0 +1,35 @@
+//! Retained-mode widget toolkit shared by the cosmic desktop apps.
+//!
+//! An app builds a [`Ui`] tree once and keeps it. Each frame it feeds the
+//! Wayland input events to [`Ui::handle`] and reacts to the returned
+//! [`Action`]s, calls [`Ui::layout`] when [`Ui::needs_layout`] (or the
+//! surface was resized), then [`Ui::paint`] when [`Ui::needs_paint`] and
+//! submits the display list to the renderer. [`Ui::accessibility`] exports
+//! the same tree for the screen reader.
+//!
+//! Instead of building the tree in code, an app can describe it in a
+//! [`markup`] document loaded at runtime and bound to its state through a
+//! [`View`], which also hot-reloads the document during development.
+
+#![no_std]
+
+extern crate alloc;
+#[cfg(test)]
+extern crate std;
+
+pub mod a11y;
+pub mod geometry;
+pub mod grapheme;
+pub mod input;
+mod layout;
+pub mod markup;
+pub mod paint;
+pub mod text;
+pub mod theme;
+pub mod ui;
+pub mod view;
+pub mod widget;
+
+pub use ui::{Action, Ui};
+pub use view::{Model, Value, View};
+pub use widget::{Kind, Length, WidgetId};
@@ -0,0 +1,643 @@
+//! Declarative UI description. A document is one element: a widget kind
+//! with named properties in parentheses, containers listing their children.
+//!
+//! ```text
+//! // Files window.
+//! Column(padding: 10, children: [
+//!     Row(width: fill, children: [
+//!         Icon(name: "folder", size: 24, a11y: "Files"),
+//!         Label(text: $title),
+//!         Spacer(width: fill),
+//!         Button(id: "open", label: "Open", enabled: $can_open),
+//!     ]),
+//!     TextInput(id: "search", placeholder: "Search", text: $query),
+//!     List(id: "files", items: $files, selected: $selection, height: fill(2)),
+//! ])
+//! ```
+//!
+//! Values are strings, non-negative integers, `true`/`false`, lengths
+//! (`shrink`, `fill`, `fill(weight)` or a pixel count), string lists and
+//! `$key` bindings to the app model (see [`crate::view`]). Every widget
+//! takes `id`, `width`, `height`, `enabled` and `a11y`. A document is
+//! checked completely before anything is built, so a typo never leaves half
+//! a window on screen.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::widget::{Kind, Length};
+
+/// Nesting limit of elements.
+pub const MAX_DEPTH: usize = 32;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorKind {
+    UnexpectedChar,
+    UnterminatedString,
+    BadEscape,
+    UnexpectedToken,
+    TrailingInput,
+    TooDeep,
+    UnknownElement,
+    UnknownProperty,
+    DuplicateProperty,
+    DuplicateId,
+    WrongType,
+    /// `$key` on a property that cannot be bound.
+    NotBindable,
+    /// The view's parent is gone or not a row or column.
+    BadParent,
+    /// The source could not be read.
+    Unreadable,
+}
+
+/// Error with the 1-based position it was found at (0:0 when it is not
+/// about the text).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Error {
+    pub line: u32,
+    pub col: u32,
+    pub kind: ErrorKind,
+}
+
+impl Error {
+    pub(crate) const fn new(kind: ErrorKind) -> Self {
+        Self {
+            line: 0,
+            col: 0,
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.kind {
+            ErrorKind::UnexpectedChar => "unexpected character",
+            ErrorKind::UnterminatedString => "unterminated string",
+            ErrorKind::BadEscape => "bad escape sequence",
+            ErrorKind::UnexpectedToken => "unexpected token",
+            ErrorKind::TrailingInput => "input after the root element",
+            ErrorKind::TooDeep => "elements nested too deep",
+            ErrorKind::UnknownElement => "unknown element",
+            ErrorKind::UnknownProperty => "unknown property",
+            ErrorKind::DuplicateProperty => "property set twice",
+            ErrorKind::DuplicateId => "id already used",
+            ErrorKind::WrongType => "value of the wrong type",
+            ErrorKind::NotBindable => "property cannot be bound",
+            ErrorKind::BadParent => "parent is not a row or column",
+            ErrorKind::Unreadable => "source unreadable",
+        };
+        write!(f, "{}:{}: {}", self.line, self.col, what)
+    }
+}
+
+/// Widget property a `$key` binding drives.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Prop {
+    /// Label, button or checkbox text, text input content.
+    Text,
+    Checked,
+    Enabled,
+    Items,
+    Selected,
+}
+
+/// A compiled document, ready to be built into a [`crate::Ui`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Template {
+    pub(crate) kind: Kind,
+    pub(crate) id: Option<String>,
+    pub(crate) width: Length,
+    pub(crate) height: Length,
+    pub(crate) padding: u32,
+    pub(crate) enabled: bool,
+    pub(crate) a11y: Option<String>,
+    pub(crate) bindings: Vec<(Prop, String)>,
+    pub(crate) children: Vec<Template>,
+}
+
+impl Template {
+    fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            id: None,
+            width: Length::Shrink,
+            height: Length::Shrink,
+            padding: 0,
+            enabled: true,
+            a11y: None,
+            bindings: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    pub fn children(&self) -> &[Template] {
+        &self.children
+    }
+}
+
+/// Compiles a document.
+pub fn parse(src: &str) -> Result<Template, Error> {
+    let mut p = Parser {
+        src,
+        pos: 0,
+        line: 1,
+        col: 1,
+        peeked: None,
+        ids: Vec::new(),
+    };
+    let root = p.element(0)?;
+    let (tok, at) = p.next()?;
+    if tok != Tok::End {
+        return Err(at.error(ErrorKind::TrailingInput));
+    }
+    Ok(root)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Ty {
+    Str,
+    Int,
+    Bool,
+    Length,
+    Items,
+}
+
+/// Type of `key` on `kind`, and the property a binding of it drives.
+fn property(kind: &Kind, key: &str) -> Option<(Ty, Option<Prop>)> {
+    Some(match (kind, key) {
+        (_, "width" | "height") => (Ty::Length, None),
+        (_, "enabled") => (Ty::Bool, Some(Prop::Enabled)),
+        (_, "a11y") => (Ty::Str, None),
+        (Kind::Row | Kind::Column, "padding") => (Ty::Int, None),
+        (Kind::Label { .. }, "text") => (Ty::Str, Some(Prop::Text)),
+        (Kind::Button { .. } | Kind::Checkbox { .. }, "label") => (Ty::Str, Some(Prop::Text)),
+        (Kind::Icon { .. }, "name") => (Ty::Str, None),
+        (Kind::Icon { .. }, "size") => (Ty::Int, None),
+        (Kind::Checkbox { .. }, "checked") => (Ty::Bool, Some(Prop::Checked)),
+        (Kind::TextInput { .. }, "placeholder") => (Ty::Str, None),
+        (Kind::TextInput { .. }, "text") => (Ty::Str, Some(Prop::Text)),
+        (Kind::List { .. }, "items") => (Ty::Items, Some(Prop::Items)),
+        (Kind::List { .. }, "selected") => (Ty::Int, Some(Prop::Selected)),
+        _ => return None,
+    })
+}
+
+enum Val {
+    Str(String),
+    Int(u32),
+    Bool(bool),
+    Length(Length),
+    Items(Vec<String>),
+}
+
+/// Sets a literal property already checked by [`property`].
+fn apply(t: &mut Template, key: &str, val: Val) {
+    match (key, val) {
+        ("width", Val::Length(v)) => t.width = v,
+        ("height", Val::Length(v)) => t.height = v,
+        ("enabled", Val::Bool(v)) => t.enabled = v,
+        ("a11y", Val::Str(v)) => t.a11y = Some(v),
+        ("padding", Val::Int(v)) => t.padding = v,
+        (key, val) => match (&mut t.kind, val) {
+            (
+                Kind::Label { text: s }
+                | Kind::Button { label: s }
+                | Kind::Checkbox { label: s, .. },
+                Val::Str(v),
+            ) => *s = v,
+            (Kind::Icon { name, .. }, Val::Str(v)) => *name = v,
+            (Kind::Icon { px, .. }, Val::Int(v)) => *px = v.min(u16::MAX as u32) as u16,
+            (Kind::Checkbox { checked, .. }, Val::Bool(v)) => *checked = v,
+            (Kind::TextInput { placeholder, .. }, Val::Str(v)) if key == "placeholder" => {
+                *placeholder = v
+            }
+            (
+                Kind::TextInput {
+                    text,
+                    cursor,
+                    anchor,
+                    ..
+                },
+                Val::Str(v),
+            ) => {
+                *cursor = v.len();
+                *anchor = v.len();
+                *text = v;
+            }
+            (Kind::List { items, .. }, Val::Items(v)) => *items = v,
+            (Kind::List { selected, .. }, Val::Int(v)) => *selected = Some(v as usize),
+            _ => {}
+        },
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Pos {
+    line: u32,
+    col: u32,
+}
+
+impl Pos {
+    fn error(self, kind: ErrorKind) -> Error {
+        Error {
+            line: self.line,
+            col: self.col,
+            kind,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Tok<'a> {
+    Ident(&'a str),
+    Str(String),
+    Int(u32),
+    Bind(&'a str),
+    Open,
+    Close,
+    LBracket,
+    RBracket,
+    Colon,
+    Comma,
+    End,
+}
+
+fn is_ident(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    line: u32,
+    col: u32,
+    peeked: Option<(Tok<'a>, Pos)>,
+    ids: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn here(&self) -> Pos {
+        Pos {
+            line: self.line,
+            col: self.col,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.src[self.pos..].chars().next()?;
+        self.pos += ch.len_utf8();
+        if ch == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(ch)
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    /// Consumes the run of identifier characters at the cursor.
+    fn word(&mut self, also: char) -> &'a str {
+        let start = self.pos;
+        while self.peek_char().is_some_and(|c| is_ident(c) || c == also) {
+            self.bump();
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn skip_blank(&mut self) {
+        loop {
+            match self.peek_char() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('/') if self.src[self.pos..].starts_with("//") => {
+                    while self.peek_char().is_some_and(|c| c != '\n') {
+                        self.bump();
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn string(&mut self, at: Pos) -> Result<String, Error> {
+        let mut out = String::new();
+        loop {
+            let esc = self.here();
+            match self.bump() {
+                None => return Err(at.error(ErrorKind::UnterminatedString)),
+                Some('"') => return Ok(out),
+                Some('\\') => out.push(match self.bump() {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some(c @ ('"' | '\\')) => c,
+                    _ => return Err(esc.error(ErrorKind::BadEscape)),
+                }),
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn lex(&mut self) -> Result<(Tok<'a>, Pos), Error> {
+        self.skip_blank();
+        let at = self.here();
+        let Some(ch) = self.peek_char() else {
+            return Ok((Tok::End, at));
+        };
+        let simple = match ch {
+            '(' => Some(Tok::Open),
+            ')' => Some(Tok::Close),
+            '[' => Some(Tok::LBracket),
+            ']' => Some(Tok::RBracket),
+            ':' => Some(Tok::Colon),
+            ',' => Some(Tok::Comma),
+            _ => None,
+        };
+        if let Some(tok) = simple {
+            self.bump();
+            return Ok((tok, at));
+        }
+        let tok = match ch {
+            '"' => {
+                self.bump();
+                Tok::Str(self.string(at)?)
+            }
+            '$' => {
+                self.bump();
+                match self.word('.') {
+                    "" => return Err(at.error(ErrorKind::UnexpectedChar)),
+                    key => Tok::Bind(key),
+                }
+            }
+            '0'..='9' => {
+                let digits = self.word('_');
+                let mut n = 0u32;
+                for c in digits.chars().filter(|&c| c != '_') {
+                    let d = c.to_digit(10).ok_or(at.error(ErrorKind::UnexpectedChar))?;
+                    n = n
+                        .checked_mul(10)
+                        .and_then(|n| n.checked_add(d))
+                        .ok_or(at.error(ErrorKind::WrongType))?;
+                }
+                Tok::Int(n)
+            }
+            c if is_ident(c) => Tok::Ident(self.word('_')),
+            _ => return Err(at.error(ErrorKind::UnexpectedChar)),
+        };
+        Ok((tok, at))
+    }
+
+    fn next(&mut self) -> Result<(Tok<'a>, Pos), Error> {
+        match self.peeked.take() {
+            Some(t) => Ok(t),
+            None => self.lex(),
+        }
+    }
+
+    /// Consumes `tok` if it is next.
+    fn eat(&mut self, tok: Tok<'a>) -> Result<bool, Error> {
+        let next = self.next()?;
+        if next.0 == tok {
+            return Ok(true);
+        }
+        self.peeked = Some(next);
+        Ok(false)
+    }
+
+    fn expect(&mut self, tok: Tok<'a>) -> Result<(), Error> {
+        let (next, at) = self.next()?;
+        if next != tok {
+            return Err(at.error(ErrorKind::UnexpectedToken));
+        }
+        Ok(())
+    }
+
+    /// `item, item, ...]` after the opening bracket, trailing comma allowed.
+    fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, Error>,
+    ) -> Result<Vec<T>, Error> {
+        let mut out = Vec::new();
+        loop {
+            if self.eat(Tok::RBracket)? {
+                return Ok(out);
+            }
+            out.push(item(self)?);
+            if !self.eat(Tok::Comma)? {
+                self.expect(Tok::RBracket)?;
+                return Ok(out);
+            }
+        }
+    }
+
+    fn element(&mut self, depth: usize) -> Result<Template, Error> {
+        let (tok, at) = self.next()?;
+        let Tok::Ident(name) = tok else {
+            return Err(at.error(ErrorKind::UnexpectedToken));
+        };
+        if depth >= MAX_DEPTH {
+            return Err(at.error(ErrorKind::TooDeep));
+        }
+        let kind = match name {
+            "Row" => Kind::Row,
+            "Column" => Kind::Column,
+            "Label" => Kind::label(""),
+            "Icon" => Kind::icon("", 16),
+            "Button" => Kind::button(""),
+            "Checkbox" => Kind::checkbox("", false),
+            "TextInput" => Kind::text_input(""),
+            "List" => Kind::list(Vec::new()),
+            "Spacer" => Kind::Spacer,
+            _ => return Err(at.error(ErrorKind::UnknownElement)),
+        };
+        let mut t = Template::new(kind);
+        self.expect(Tok::Open)?;
+        let mut seen: Vec<&str> = Vec::new();
+        loop {
+            let (tok, at) = self.next()?;
+            let key = match tok {
+                Tok::Close => break,
+                Tok::Ident(key) => key,
+                _ => return Err(at.error(ErrorKind::UnexpectedToken)),
+            };
+            if seen.contains(&key) {
+                return Err(at.error(ErrorKind::DuplicateProperty));
+            }
+            seen.push(key);
+            self.expect(Tok::Colon)?;
+            self.property(&mut t, key, at, depth)?;
+            if !self.eat(Tok::Comma)? {
+                self.expect(Tok::Close)?;
+                break;
+            }
+        }
+        Ok(t)
+    }
+
+    fn property(
+        &mut self,
+        t: &mut Template,
+        key: &str,
+        at: Pos,
+        depth: usize,
+    ) -> Result<(), Error> {
+        if key == "children" && t.kind.is_container() {
+            self.expect(Tok::LBracket)?;
+            t.children = self.list(|p| p.element(depth + 1))?;
+            return Ok(());
+        }
+        let (tok, vat) = self.next()?;
+        if key == "id" {
+            let Tok::Str(id) = tok else {
+                return Err(vat.error(ErrorKind::WrongType));
+            };
+            if self.ids.contains(&id) {
+                return Err(vat.error(ErrorKind::DuplicateId));
+            }
+            self.ids.push(id.clone());
+            t.id = Some(id);
+            return Ok(());
+        }
+        let (ty, prop) = property(&t.kind, key).ok_or(at.error(ErrorKind::UnknownProperty))?;
+        let wrong = vat.error(ErrorKind::WrongType);
+        let val = match (ty, tok) {
+            (_, Tok::Bind(name)) => {
+                let prop = prop.ok_or(vat.error(ErrorKind::NotBindable))?;
+                t.bindings.push((prop, name.into()));
+                return Ok(());
+            }
+            (Ty::Str, Tok::Str(s)) => Val::Str(s),
+            (Ty::Int, Tok::Int(n)) => Val::Int(n),
+            (Ty::Bool, Tok::Ident("true")) => Val::Bool(true),
+            (Ty::Bool, Tok::Ident("false")) => Val::Bool(false),
+            (Ty::Length, Tok::Int(n)) => Val::Length(Length::Fixed(n)),
+            (Ty::Length, Tok::Ident("shrink")) => Val::Length(Length::Shrink),
+            (Ty::Length, Tok::Ident("fill")) => {
+                let mut weight = 1;
+                if self.eat(Tok::Open)? {
+                    let (tok, at) = self.next()?;
+                    weight = match tok {
+                        Tok::Int(n) if n <= u16::MAX as u32 => n as u16,
+                        _ => return Err(at.error(ErrorKind::WrongType)),
+                    };
+                    self.expect(Tok::Close)?;
+                }
+                Val::Length(Length::Fill(weight))
+            }
+            (Ty::Items, Tok::LBracket) => Val::Items(self.list(|p| match p.next()? {
+                (Tok::Str(s), _) => Ok(s),
+                (_, at) => Err(at.error(ErrorKind::WrongType)),
+            })?),
+            _ => return Err(wrong),
+        };
+        apply(t, key, val);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    const FILES: &str = r#"
+// Files window.
+Column(padding: 10, children: [
+    Row(width: fill, children: [
+        Icon(name: "folder", size: 24, a11y: "Files"),
+        Label(text: $title),
+        Spacer(width: fill),
+        Button(id: "open", label: "Open \"it\"", enabled: $can_open),
+    ]),
+    Checkbox(label: "Hidden", checked: true, height: 30),
+    TextInput(id: "search", placeholder: "Search", text: $query),
+    List(id: "files", items: ["a", "b",], selected: 1, height: fill(2)),
+])
+"#;
+
+    #[test]
+    fn parses_a_document() {
+        let t = parse(FILES).unwrap();
+        assert_eq!(t.kind, Kind::Column);
+        assert_eq!(t.padding, 10);
+        let [bar, check, search, list] = &t.children[..] else {
+            panic!("four children");
+        };
+        assert_eq!(bar.width, Length::Fill(1));
+        assert_eq!(bar.children[0].kind, Kind::icon("folder", 24));
+        assert_eq!(bar.children[0].a11y.as_deref(), Some("Files"));
+        assert_eq!(
+            bar.children[1].bindings,
+            [(Prop::Text, "title".to_string())]
+        );
+        assert_eq!(bar.children[3].kind, Kind::button("Open \"it\""));
+        assert_eq!(bar.children[3].id.as_deref(), Some("open"));
+        assert_eq!(
+            bar.children[3].bindings,
+            [(Prop::Enabled, "can_open".to_string())]
+        );
+        assert_eq!(check.kind, Kind::checkbox("Hidden", true));
+        assert_eq!(check.height, Length::Fixed(30));
+        assert_eq!(search.bindings, [(Prop::Text, "query".to_string())]);
+        let Kind::List {
+            items, selected, ..
+        } = &list.kind
+        else {
+            panic!("list");
+        };
+        assert_eq!(
+            (&items[..], *selected),
+            (&["a".to_string(), "b".to_string()][..], Some(1))
+        );
+        assert_eq!(list.height, Length::Fill(2));
+    }
+
+    fn error(src: &str) -> (u32, u32, ErrorKind) {
+        let e = parse(src).unwrap_err();
+        (e.line, e.col, e.kind)
+    }
+
+    #[test]
+    fn errors_point_at_the_culprit() {
+        use ErrorKind::*;
+        assert_eq!(
+            error("Row(children: [\n  Label(txt: \"a\")])"),
+            (2, 9, UnknownProperty)
+        );
+        assert_eq!(error("Label(text: 3)"), (1, 13, WrongType));
+        assert_eq!(error("Icon(name: $icon)"), (1, 12, NotBindable));
+        assert_eq!(
+            error("Label(text: \"a\", text: \"b\")"),
+            (1, 18, DuplicateProperty)
+        );
+        assert_eq!(
+            error("Row(children: [Button(id: \"x\"), Button(id: \"x\")])"),
+            (1, 44, DuplicateId)
+        );
+        assert_eq!(error("Label(text: \"a)"), (1, 13, UnterminatedString));
+        assert_eq!(error("Label(text: \"\\q\")"), (1, 14, BadEscape));
+        assert_eq!(error("Label() Label()"), (1, 9, TrailingInput));
+        assert_eq!(error("Slider()"), (1, 1, UnknownElement));
+        assert_eq!(error("Label(children: [])"), (1, 7, UnknownProperty));
+        assert_eq!(error("Row(width: fill(70000))"), (1, 17, WrongType));
+        assert_eq!(error("Row(padding: 99999999999)"), (1, 14, WrongType));
+        assert_eq!(error("Row(;)"), (1, 5, UnexpectedChar));
+        let deep = "Row(children: [".repeat(MAX_DEPTH) + "Spacer()";
+        assert_eq!(error(&deep).2, TooDeep);
+        assert_eq!(
+            Error::new(BadParent).to_string(),
+            "0:0: parent is not a row or column"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Display list handed to the shared renderer. Commands are in painting
+//! order; text is drawn with the atlas glyphs at `px`, icons with the atlas
+//! icon of that name.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::geometry::{Point, Rect};
+use crate::text::TextMeasure;
+use crate::theme::{Class, Color, State, Style, Theme};
+use crate::ui::Ui;
+use crate::widget::{Kind, WidgetId};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DrawCmd {
+    Fill {
+        rect: Rect,
+        color: Color,
+        radius: u32,
+    },
+    /// Outline of `width` pixels inside `rect`.
+    Stroke {
+        rect: Rect,
+        color: Color,
+        width: u32,
+        radius: u32,
+    },
+    /// `origin` is the top-left corner of the line box.
+    Text {
+        origin: Point,
+        text: String,
+        px: u16,
+        color: Color,
+    },
+    Icon {
+        rect: Rect,
+        name: String,
+    },
+    /// Clip the following commands to `rect` (intersected with the
+    /// current clip) until the matching `PopClip`.
+    PushClip(Rect),
+    PopClip,
+}
+
+pub type DisplayList = Vec<DrawCmd>;
+
+impl Ui {
+    /// Paints the whole tree. Call after [`Ui::layout`] whenever
+    /// [`Ui::needs_paint`].
+    pub fn paint(&mut self, theme: &dyn Theme, text: &dyn TextMeasure) -> DisplayList {
+        let mut out = Vec::new();
+        let window = theme.style(Class::Window, State::default());
+        if let Some(root) = self.node(self.root) {
+            out.push(DrawCmd::Fill {
+                rect: root.bounds,
+                color: window.background,
+                radius: 0,
+            });
+        }
+        for id in self.walk() {
+            self.paint_node(id, theme, text, &mut out);
+        }
+        self.paint_dirty = false;
+        out
+    }
+
+    fn paint_node(
+        &self,
+        id: WidgetId,
+        theme: &dyn Theme,
+        text: &dyn TextMeasure,
+        out: &mut DisplayList,
+    ) {
+        let Some(node) = self.node(id) else {
+            return;
+        };
+        let m = theme.metrics();
+        let (px, line) = (m.text_px, m.line_height());
+        let r = node.bounds;
+        if r.w == 0 || r.h == 0 {
+            return;
+        }
+        let state = self.state(id);
+        // Text line vertically centered in `r`.
+        let text_at = |r: Rect, dx: u32| {
+            Point::new(r.x + dx as i32, r.y + (r.h.saturating_sub(line) / 2) as i32)
+        };
+        let label = |out: &mut DisplayList, origin: Point, s: &str, color: Color| {
+            if !s.is_empty() {
+                out.push(DrawCmd::Text {
+                    origin,
+                    text: s.into(),
+                    px,
+                    color,
+                });
+            }
+        };
+        let frame = |out: &mut DisplayList, r: Rect, style: Style| {
+            out.push(DrawCmd::Fill {
+                rect: r,
+                color: style.background,
+                radius: m.radius,
+            });
+            out.push(DrawCmd::Stroke {
+                rect: r,
+                color: style.border,
+                width: m.border,
+                radius: m.radius,
+            });
+        };
+
+        match &node.kind {
+            Kind::Row | Kind::Column | Kind::Spacer => {}
+            Kind::Label { text: t } => {
+                let style = theme.style(Class::Label, state);
+                label(out, text_at(r, 0), t, style.foreground);
+            }
+            Kind::Icon { name, .. } => out.push(DrawCmd::Icon {
+                rect: r,
+                name: name.clone(),
+            }),
+            Kind::Button { label: t } => {
+                let style = theme.style(Class::Button, state);
+                frame(out, r, style);
+                let dx = r.w.saturating_sub(text.width(t, px)) / 2;
+                out.push(DrawCmd::PushClip(r));
+                label(out, text_at(r, dx), t, style.foreground);
+                out.push(DrawCmd::PopClip);
+            }
+            Kind::Checkbox { label: t, checked } => {
+                let style = theme.style(Class::Checkbox, state);
+                let side = line.min(r.h);
+                let frame_rect = Rect::new(r.x, r.y + ((r.h - side) / 2) as i32, side, side);
+                frame(out, frame_rect, style);
+                if *checked {
+                    let dx = side.saturating_sub(text.advance('✓', px)) / 2;
+                    label(out, text_at(frame_rect, dx), "✓", style.foreground);
+                }
+                let fg = theme.style(Class::Label, state).foreground;
+                label(out, text_at(r, side + m.spacing), t, fg);
+            }
+            Kind::TextInput {
+                text: t,
+                placeholder,
+                cursor,
+                anchor,
+            } => {
+                let style = theme.style(Class::TextInput, state);
+                frame(out, r, style);
+                let inner = r.inset(m.padding);
+                out.push(DrawCmd::PushClip(inner));
+                // Scroll left so the caret stays visible.
+                let caret = text.width(&t[..*cursor], px);
+                let shift = caret.saturating_sub(inner.w.saturating_sub(1));
+                let origin = Point::new(inner.x - shift as i32, text_at(r, 0).y);
+                if anchor != cursor {
+                    let from = text.width(&t[..*cursor.min(anchor)], px);
+                    let to = text.width(&t[..*cursor.max(anchor)], px);
+                    out.push(DrawCmd::Fill {
+                        rect: Rect::new(origin.x + from as i32, origin.y, to - from, line),
+                        color: theme.palette().accent,
+                        radius: 0,
+                    });
+                }
+                if t.is_empty() {
+                    label(out, origin, placeholder, theme.palette().text_dim);
+                } else {
+                    label(out, origin, t, style.foreground);
+                }
+                if state.has(State::FOCUSED) {
+                    out.push(DrawCmd::Fill {
+                        rect: Rect::new(origin.x + caret as i32, origin.y, 1, line),
+                        color: style.foreground,
+                        radius: 0,
+                    });
+                }
+                out.push(DrawCmd::PopClip);
+            }
+            Kind::List {
+                items,
+                selected,
+                scroll,
+            } => {
+                let style = theme.style(Class::List, state);
+                frame(out, r, style);
+                out.push(DrawCmd::PushClip(r.inset(m.border)));
+                let row_h = self.row_height().max(1);
+                // Whole rows plus the partly visible one at the bottom.
+                let rows = r.h.div_ceil(row_h) as usize;
+                for (i, item) in items.iter().enumerate().skip(*scroll).take(rows) {
+                    let row_rect =
+                        Rect::new(r.x, r.y + ((i - scroll) as u32 * row_h) as i32, r.w, row_h);
+                    let row_state = State(state.0 & State::DISABLED)
+                        .with(State::SELECTED, *selected == Some(i))
+                        .with(
+                            State::HOVERED,
+                            self.hover == Some(id) && self.hover_row == Some(i),
+                        );
+                    let row_style = theme.style(Class::ListRow, row_state);
+                    if row_style.background.alpha() != 0 {
+                        out.push(DrawCmd::Fill {
+                            rect: row_rect,
+                            color: row_style.background,
+                            radius: 0,
+                        });
+                    }
+                    label(
+                        out,
+                        text_at(row_rect, m.padding),
+                        item,
+                        row_style.foreground,
+                    );
+                }
+                out.push(DrawCmd::PopClip);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Size;
+    use crate::input::Event;
+    use crate::text::Monospace;
+    use crate::theme::{StockTheme, Theme};
+    use crate::widget::Length;
+    use alloc::string::ToString;
+
+    fn texts(list: &DisplayList) -> Vec<&str> {
+        list.iter()
+            .filter_map(|c| match c {
+                DrawCmd::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn frame(ui: &mut Ui, theme: &StockTheme) -> DisplayList {
+        if ui.needs_layout() {
+            ui.layout(Size::new(200, 300), theme, &Monospace);
+        }
+        ui.paint(theme, &Monospace)
+    }
+
+    #[test]
+    fn paints_visible_rows_selection_and_focus() {
+        let theme = StockTheme::light();
+        let mut ui = Ui::new();
+        let root = ui.root();
+        let input = ui.push(root, Kind::text_input("Search")).unwrap();
+        let items = (0..50).map(|i| i.to_string()).collect();
+        let list = ui.push(root, Kind::list(items)).unwrap();
+        ui.set_height(list, Length::Fixed(ui.row_height() * 4));
+        frame(&mut ui, &theme);
+        ui.select(list, Some(10));
+        assert!(ui.needs_paint());
+
+        let dl = frame(&mut ui, &theme);
+        assert!(!ui.needs_paint());
+        // Placeholder, then the four visible rows ending on the selection.
+        assert_eq!(texts(&dl), ["Search", "7", "8", "9", "10"]);
+        let accent = theme.palette().accent;
+        assert!(dl.iter().any(|c| matches!(c, DrawCmd::Fill { color, rect, .. }
+            if *color == accent && rect.y == ui.bounds(list).unwrap().y + 3 * ui.row_height() as i32)));
+        let pushes = dl
+            .iter()
+            .filter(|c| matches!(c, DrawCmd::PushClip(_)))
+            .count();
+        let pops = dl.iter().filter(|c| **c == DrawCmd::PopClip).count();
+        assert_eq!(pushes, pops);
+
+        ui.focus(input);
+        ui.handle(Event::Text('a'));
+        let dl = frame(&mut ui, &theme);
+        let ring = theme.style(Class::TextInput, State(State::FOCUSED)).border;
+        assert!(dl
+            .iter()
+            .any(|c| matches!(c, DrawCmd::Stroke { color, .. } if *color == ring)));
+        assert_eq!(texts(&dl)[0], "a");
+        ui.handle(Event::KeyboardLeave);
+        let dl = frame(&mut ui, &theme);
+        assert!(!dl
+            .iter()
+            .any(|c| matches!(c, DrawCmd::Stroke { color, .. } if *color == ring)));
+    }
+}
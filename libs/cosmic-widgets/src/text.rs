@@ -0,0 +1,105 @@
+//! Text measurement for layout. Apps measure with the glyphs of the shared
+//! `font_cache` atlas ([`AtlasMeasure`]), the same sprites the renderer
+//! draws, so a label is laid out at exactly the width it is painted at.
+//! Emoji come from the atlas color glyphs; every other code point the
+//! fonts lack falls back to [`Monospace`].
+
+use exo_graphics::atlas::AtlasView;
+
+use crate::grapheme;
+
+pub trait TextMeasure {
+    /// Horizontal advance of `ch` at `px`.
+    fn advance(&self, ch: char, px: u16) -> u32;
+
+    fn width(&self, text: &str, px: u16) -> u32 {
+        text.chars().map(|ch| self.advance(ch, px)).sum()
+    }
+
+    /// Byte offset in `text` of the cluster boundary closest to `x`.
+    fn offset_at(&self, text: &str, px: u16, x: u32) -> usize {
+        let mut pen = 0;
+        let mut at = 0;
+        for cluster in grapheme::clusters(text) {
+            let advance = self.width(cluster, px);
+            if x < pen + advance / 2 {
+                return at;
+            }
+            pen += advance;
+            at += cluster.len();
+        }
+        text.len()
+    }
+}
+
+/// Fixed advance of 3/5 of the size, the proportions of the boot console
+/// font; used before the atlas is mapped and for glyphs it lacks.
+/// Combining marks take no room.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Monospace;
+
+impl TextMeasure for Monospace {
+    fn advance(&self, ch: char, px: u16) -> u32 {
+        if grapheme::is_zero_width(ch) {
+            return 0;
+        }
+        (px as u32 * 3).div_ceil(5)
+    }
+}
+
+/// Advances from the atlas sprites.
+pub struct AtlasMeasure<'a> {
+    pub view: AtlasView<'a>,
+}
+
+impl TextMeasure for AtlasMeasure<'_> {
+    fn advance(&self, ch: char, px: u16) -> u32 {
+        match self.view.any_glyph(ch as u32, px) {
+            Some(sprite) => sprite.width as u32,
+            None => Monospace.advance(ch, px),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exo_graphics::atlas::{AtlasWriter, ATLAS_BYTES};
+    use std::vec;
+
+    #[test]
+    fn atlas_widths_with_monospace_fallback() {
+        let mut buf = vec![0u8; ATLAS_BYTES];
+        let mut w = AtlasWriter::begin(&mut buf, 0).unwrap();
+        w.add_glyph('i' as u32, 16, 4, 16, &[0; 64]).unwrap();
+        w.add_glyph('m' as u32, 16, 14, 16, &[0; 224]).unwrap();
+        w.finish();
+        let m = AtlasMeasure {
+            view: AtlasView::open(&buf).unwrap(),
+        };
+        assert_eq!(m.width("mi", 16), 18);
+        assert_eq!(m.width("mix", 16), 28);
+        assert_eq!(m.offset_at("mim", 16, 15), 1);
+        assert_eq!(m.offset_at("mim", 16, 17), 2);
+        assert_eq!(m.offset_at("mim", 16, 200), 3);
+    }
+
+    #[test]
+    fn color_glyphs_and_clusters() {
+        let mut buf = vec![0u8; ATLAS_BYTES];
+        let mut w = AtlasWriter::begin(&mut buf, 0).unwrap();
+        w.add_glyph('e' as u32, 16, 8, 16, &[0; 128]).unwrap();
+        w.add_glyph(0x301, 16, 0, 16, &[]).unwrap();
+        w.add_color_glyph(0x1f600, 16, 16, 16, &[0; 1024]).unwrap();
+        w.finish();
+        let m = AtlasMeasure {
+            view: AtlasView::open(&buf).unwrap(),
+        };
+        assert_eq!(m.width("😀e", 16), 24);
+        // The caret snaps around "e" + U+0301, never between them.
+        let text = "e\u{301}e";
+        assert_eq!(m.offset_at(text, 16, 7), 3);
+        assert_eq!(m.offset_at(text, 16, 3), 0);
+        assert_eq!(Monospace.width(text, 10), 12);
+    }
+}
@@ -0,0 +1,275 @@
+//! Theming hooks. Widgets never pick colors themselves: they ask the
+//! [`Theme`] for the [`Style`] of their class in their current state. The
+//! stock light and dark themes cover the settings' appearance page; an app
+//! that needs a different look overrides [`Theme::style`] and keeps the
+//! rest.
+
+/// `0xRRGGBBAA`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Color(pub u32);
+
+impl Color {
+    pub const TRANSPARENT: Self = Self(0);
+
+    pub const fn rgb(rgb: u32) -> Self {
+        Self((rgb << 8) | 0xff)
+    }
+
+    pub const fn alpha(self) -> u8 {
+        self.0 as u8
+    }
+
+    /// Same color at `alpha`.
+    pub const fn with_alpha(self, alpha: u8) -> Self {
+        Self((self.0 & !0xff) | alpha as u32)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Palette {
+    /// Window background.
+    pub background: Color,
+    /// Buttons, inputs and lists.
+    pub surface: Color,
+    pub text: Color,
+    /// Placeholders and disabled text.
+    pub text_dim: Color,
+    /// Focus ring, selection, checked boxes.
+    pub accent: Color,
+    /// Text drawn over `accent`.
+    pub on_accent: Color,
+    pub border: Color,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Metrics {
+    /// Text size in logical pixels (the atlas glyph size).
+    pub text_px: u16,
+    /// Inner padding of buttons, inputs and list rows.
+    pub padding: u32,
+    /// Gap between the children of a row or column.
+    pub spacing: u32,
+    pub radius: u32,
+    pub border: u32,
+    /// Natural width of a text input.
+    pub input_width: u32,
+}
+
+impl Metrics {
+    pub const fn line_height(&self) -> u32 {
+        self.text_px as u32 * 5 / 4
+    }
+}
+
+/// Widget classes a theme styles.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Class {
+    Window,
+    Label,
+    Button,
+    Checkbox,
+    TextInput,
+    List,
+    ListRow,
+}
+
+/// Interaction state bits.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct State(pub u8);
+
+impl State {
+    pub const HOVERED: u8 = 1 << 0;
+    pub const PRESSED: u8 = 1 << 1;
+    pub const FOCUSED: u8 = 1 << 2;
+    pub const DISABLED: u8 = 1 << 3;
+    pub const CHECKED: u8 = 1 << 4;
+    pub const SELECTED: u8 = 1 << 5;
+
+    pub const fn has(self, bit: u8) -> bool {
+        self.0 & bit != 0
+    }
+
+    pub const fn with(self, bit: u8, on: bool) -> Self {
+        if on {
+            Self(self.0 | bit)
+        } else {
+            Self(self.0 & !bit)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Style {
+    pub background: Color,
+    pub foreground: Color,
+    pub border: Color,
+}
+
+pub trait Theme {
+    fn palette(&self) -> &Palette;
+    fn metrics(&self) -> &Metrics;
+
+    fn style(&self, class: Class, state: State) -> Style {
+        stock_style(self.palette(), class, state)
+    }
+}
+
+/// Styling of the stock themes, also the fallback of custom ones.
+pub fn stock_style(p: &Palette, class: Class, state: State) -> Style {
+    let disabled = state.has(State::DISABLED);
+    let foreground = if disabled { p.text_dim } else { p.text };
+    let mut style = Style {
+        background: p.surface,
+        foreground,
+        border: if state.has(State::FOCUSED) {
+            p.accent
+        } else {
+            p.border
+        },
+    };
+    match class {
+        Class::Window => {
+            style.background = p.background;
+            style.border = Color::TRANSPARENT;
+        }
+        Class::Label => {
+            style.background = Color::TRANSPARENT;
+            style.border = Color::TRANSPARENT;
+        }
+        Class::Button if disabled => {}
+        Class::Button if state.has(State::PRESSED) => {
+            style.background = p.accent;
+            style.foreground = p.on_accent;
+        }
+        Class::Button if state.has(State::HOVERED) => {
+            style.background = p.accent.with_alpha(0x40);
+        }
+        Class::Checkbox if state.has(State::CHECKED) => {
+            style.background = p.accent;
+            style.foreground = p.on_accent;
+        }
+        Class::ListRow if state.has(State::SELECTED) => {
+            style.background = p.accent;
+            style.foreground = p.on_accent;
+        }
+        Class::ListRow if state.has(State::HOVERED) => {
+            style.background = p.accent.with_alpha(0x30);
+        }
+        Class::ListRow => style.background = Color::TRANSPARENT,
+        _ => {}
+    }
+    style
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StockTheme {
+    pub palette: Palette,
+    pub metrics: Metrics,
+}
+
+impl StockTheme {
+    pub const METRICS: Metrics = Metrics {
+        text_px: 16,
+        padding: 6,
+        spacing: 8,
+        radius: 6,
+        border: 1,
+        input_width: 200,
+    };
+
+    pub const fn light() -> Self {
+        Self {
+            palette: Palette {
+                background: Color::rgb(0xf4_f4_f4),
+                surface: Color::rgb(0xff_ff_ff),
+                text: Color::rgb(0x1e_1e_1e),
+                text_dim: Color::rgb(0x8a_8a_8a),
+                accent: Color::rgb(0x3a_7c_c8),
+                on_accent: Color::rgb(0xff_ff_ff),
+                border: Color::rgb(0xc8_c8_c8),
+            },
+            metrics: Self::METRICS,
+        }
+    }
+
+    pub const fn dark() -> Self {
+        Self {
+            palette: Palette {
+                background: Color::rgb(0x1b_1b_1b),
+                surface: Color::rgb(0x2b_2b_2b),
+                text: Color::rgb(0xe6_e6_e6),
+                text_dim: Color::rgb(0x80_80_80),
+                accent: Color::rgb(0x63_d0_df),
+                on_accent: Color::rgb(0x10_10_10),
+                border: Color::rgb(0x40_40_40),
+            },
+            metrics: Self::METRICS,
+        }
+    }
+
+    /// Accent chosen on the settings' appearance page.
+    pub const fn with_accent(mut self, accent: Color) -> Self {
+        self.palette.accent = accent;
+        self
+    }
+}
+
+impl Theme for StockTheme {
+    fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Square(StockTheme);
+
+    impl Theme for Square {
+        fn palette(&self) -> &Palette {
+            &self.0.palette
+        }
+
+        fn metrics(&self) -> &Metrics {
+            &self.0.metrics
+        }
+
+        fn style(&self, class: Class, state: State) -> Style {
+            let mut style = stock_style(self.palette(), class, state);
+            if class == Class::Button {
+                style.border = Color::rgb(0xff_00_00);
+            }
+            style
+        }
+    }
+
+    #[test]
+    fn states_drive_the_stock_style() {
+        let theme = StockTheme::light();
+        let p = theme.palette;
+        let idle = theme.style(Class::Button, State::default());
+        assert_eq!(idle.background, p.surface);
+        let pressed = theme.style(Class::Button, State(State::PRESSED | State::HOVERED));
+        assert_eq!(pressed.background, p.accent);
+        let disabled = theme.style(Class::Button, State(State::DISABLED | State::PRESSED));
+        assert_eq!(disabled.foreground, p.text_dim);
+        assert_eq!(disabled.background, p.surface);
+        let focused = theme.style(Class::TextInput, State(State::FOCUSED));
+        assert_eq!(focused.border, p.accent);
+    }
+
+    #[test]
+    fn overridden_style_keeps_the_rest_of_the_theme() {
+        let theme = Square(StockTheme::dark().with_accent(Color::rgb(0x00_ff_00)));
+        let button = theme.style(Class::Button, State::default());
+        assert_eq!(button.border, Color::rgb(0xff_00_00));
+        let row = theme.style(Class::ListRow, State(State::SELECTED));
+        assert_eq!(row.background, Color::rgb(0x00_ff_00));
+        assert_eq!(Color::rgb(0x12_34_56).with_alpha(0x80), Color(0x1234_5680));
+    }
+}
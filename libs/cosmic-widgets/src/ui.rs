@@ -0,0 +1,970 @@
+//! The widget tree and its input handling.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::geometry::{Point, Rect};
+use crate::grapheme;
+use crate::input::{keysym, Event, Modifiers, BTN_LEFT, DOUBLE_CLICK_MS};
+use crate::theme::{Metrics, State, StockTheme};
+use crate::widget::{Kind, Length, Node, WidgetId};
+
+/// Rows scrolled per wheel step.
+pub const SCROLL_ROWS: usize = 3;
+
+/// What the user did, reported by [`Ui::handle`]. The tree already reflects
+/// it (checkbox toggled, text edited, row selected) when it is returned.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    Clicked(WidgetId),
+    Toggled(WidgetId, bool),
+    /// Text input content changed.
+    Edited(WidgetId),
+    /// Return in a text input.
+    Submitted(WidgetId),
+    Selected(WidgetId, usize),
+    /// Double click or Return on a list row.
+    Activated(WidgetId, usize),
+}
+
+pub struct Ui {
+    pub(crate) nodes: Vec<Option<Node>>,
+    pub(crate) root: WidgetId,
+    pub(crate) hover: Option<WidgetId>,
+    /// List row under the pointer.
+    pub(crate) hover_row: Option<usize>,
+    pub(crate) pressed: Option<WidgetId>,
+    pub(crate) focus: Option<WidgetId>,
+    /// The surface has keyboard focus: the focus ring is shown.
+    pub(crate) keyboard: bool,
+    pub(crate) pointer: Point,
+    /// Finger acting as the pointer: the first one down.
+    touch: Option<i32>,
+    /// The stylus tip is down, acting as the left button.
+    stylus_tip: bool,
+    /// Last click, for double clicks: widget, list row, timestamp.
+    last_click: Option<(WidgetId, Option<usize>, u32)>,
+    /// Metrics of the last layout, for hit tests between layouts.
+    pub(crate) metrics: Metrics,
+    pub(crate) layout_dirty: bool,
+    pub(crate) paint_dirty: bool,
+}
+
+impl Default for Ui {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ui {
+    /// Empty tree whose root is a column filling the surface.
+    pub fn new() -> Self {
+        let mut root = Node::new(Kind::Column, None);
+        root.width = Length::Fill(1);
+        root.height = Length::Fill(1);
+        Self {
+            nodes: alloc::vec![Some(root)],
+            root: WidgetId(0),
+            hover: None,
+            hover_row: None,
+            pressed: None,
+            focus: None,
+            keyboard: true,
+            pointer: Point::default(),
+            touch: None,
+            stylus_tip: false,
+            last_click: None,
+            metrics: StockTheme::METRICS,
+            layout_dirty: true,
+            paint_dirty: true,
+        }
+    }
+
+    pub fn root(&self) -> WidgetId {
+        self.root
+    }
+
+    pub(crate) fn node(&self, id: WidgetId) -> Option<&Node> {
+        self.nodes.get(id.0 as usize).and_then(Option::as_ref)
+    }
+
+    fn node_mut(&mut self, id: WidgetId) -> Option<&mut Node> {
+        self.nodes.get_mut(id.0 as usize).and_then(Option::as_mut)
+    }
+
+    /// Mutates a widget; any change asks for a new layout and paint.
+    fn update(&mut self, id: WidgetId, f: impl FnOnce(&mut Node)) -> bool {
+        let Some(node) = self.node_mut(id) else {
+            return false;
+        };
+        f(node);
+        self.layout_dirty = true;
+        self.paint_dirty = true;
+        true
+    }
+
+    /// Appends `kind` to the row or column `parent`; `None` if `parent` is
+    /// gone or not a container.
+    pub fn push(&mut self, parent: WidgetId, kind: Kind) -> Option<WidgetId> {
+        self.insert(parent, usize::MAX, kind)
+    }
+
+    /// Inserts `kind` before the child `index` of `parent`, or last if
+    /// `index` is past the end.
+    pub fn insert(&mut self, parent: WidgetId, index: usize, kind: Kind) -> Option<WidgetId> {
+        if !self.node(parent)?.kind.is_container() {
+            return None;
+        }
+        let id = WidgetId(self.nodes.len() as u32);
+        self.nodes.push(Some(Node::new(kind, Some(parent))));
+        self.update(parent, |p| {
+            let index = index.min(p.children.len());
+            p.children.insert(index, id);
+        });
+        Some(id)
+    }
+
+    /// Removes `id` and its subtree; the root cannot be removed.
+    pub fn remove(&mut self, id: WidgetId) -> bool {
+        let Some(parent) = self.node(id).and_then(|n| n.parent) else {
+            return false;
+        };
+        self.update(parent, |p| p.children.retain(|&c| c != id));
+        let mut stack = alloc::vec![id];
+        while let Some(gone) = stack.pop() {
+            if let Some(node) = self.nodes[gone.0 as usize].take() {
+                stack.extend(node.children);
+            }
+        }
+        for slot in [&mut self.hover, &mut self.pressed, &mut self.focus] {
+            if slot.is_some_and(|w| self.nodes[w.0 as usize].is_none()) {
+                *slot = None;
+            }
+        }
+        true
+    }
+
+    pub fn kind(&self, id: WidgetId) -> Option<&Kind> {
+        self.node(id).map(|n| &n.kind)
+    }
+
+    pub fn children(&self, id: WidgetId) -> &[WidgetId] {
+        self.node(id).map_or(&[], |n| &n.children)
+    }
+
+    /// Bounds from the last layout.
+    pub fn bounds(&self, id: WidgetId) -> Option<Rect> {
+        self.node(id).map(|n| n.bounds)
+    }
+
+    pub fn set_width(&mut self, id: WidgetId, width: Length) -> bool {
+        self.update(id, |n| n.width = width)
+    }
+
+    pub fn set_height(&mut self, id: WidgetId, height: Length) -> bool {
+        self.update(id, |n| n.height = height)
+    }
+
+    pub fn set_padding(&mut self, id: WidgetId, padding: u32) -> bool {
+        self.update(id, |n| n.padding = padding)
+    }
+
+    /// A disabled widget is greyed out and ignores input.
+    pub fn set_enabled(&mut self, id: WidgetId, enabled: bool) -> bool {
+        if !enabled && self.focus == Some(id) {
+            self.focus = None;
+        }
+        self.update(id, |n| n.enabled = enabled)
+    }
+
+    pub fn set_a11y_label(&mut self, id: WidgetId, label: &str) -> bool {
+        self.update(id, |n| n.a11y_label = Some(label.into()))
+    }
+
+    /// Text of a label, button or checkbox, or content of a text input
+    /// (caret moved to the end).
+    pub fn set_text(&mut self, id: WidgetId, value: &str) -> bool {
+        self.update(id, |n| match &mut n.kind {
+            Kind::Label { text }
+            | Kind::Button { label: text }
+            | Kind::Checkbox { label: text, .. } => *text = value.into(),
+            Kind::TextInput {
+                text,
+                cursor,
+                anchor,
+                ..
+            } => {
+                *text = value.into();
+                *cursor = text.len();
+                *anchor = text.len();
+            }
+            _ => {}
+        })
+    }
+
+    /// Content of a text input.
+    pub fn text(&self, id: WidgetId) -> Option<&str> {
+        match self.kind(id)? {
+            Kind::TextInput { text, .. } => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Selected part of a text input, `None` if nothing is selected.
+    pub fn selection(&self, id: WidgetId) -> Option<&str> {
+        match self.kind(id)? {
+            Kind::TextInput {
+                text,
+                cursor,
+                anchor,
+                ..
+            } if cursor != anchor => Some(&text[(*cursor).min(*anchor)..(*cursor).max(*anchor)]),
+            _ => None,
+        }
+    }
+
+    pub fn set_checked(&mut self, id: WidgetId, value: bool) -> bool {
+        self.update(id, |n| {
+            if let Kind::Checkbox { checked, .. } = &mut n.kind {
+                *checked = value;
+            }
+        })
+    }
+
+    pub fn checked(&self, id: WidgetId) -> Option<bool> {
+        match self.kind(id)? {
+            Kind::Checkbox { checked, .. } => Some(*checked),
+            _ => None,
+        }
+    }
+
+    /// Replaces the rows of a list, keeping the selection if still valid.
+    pub fn set_items(&mut self, id: WidgetId, new: Vec<String>) -> bool {
+        self.update(id, |n| {
+            if let Kind::List {
+                items, selected, ..
+            } = &mut n.kind
+            {
+                *items = new;
+                *selected = selected.filter(|&s| s < items.len());
+            }
+        })
+    }
+
+    pub fn select(&mut self, id: WidgetId, row: Option<usize>) -> bool {
+        self.update(id, |n| {
+            if let Kind::List {
+                items, selected, ..
+            } = &mut n.kind
+            {
+                *selected = row.filter(|&r| r < items.len());
+            }
+        });
+        self.scroll_to_selection(id);
+        true
+    }
+
+    pub fn selected(&self, id: WidgetId) -> Option<usize> {
+        match self.kind(id)? {
+            Kind::List { selected, .. } => *selected,
+            _ => None,
+        }
+    }
+
+    pub fn focused(&self) -> Option<WidgetId> {
+        self.focus
+    }
+
+    pub fn focus(&mut self, id: WidgetId) -> bool {
+        if !self.is_focusable(id) {
+            return false;
+        }
+        self.focus = Some(id);
+        self.paint_dirty = true;
+        true
+    }
+
+    /// The tree changed since the last [`Ui::layout`].
+    pub fn needs_layout(&self) -> bool {
+        self.layout_dirty
+    }
+
+    /// Something visible changed since the last [`Ui::paint`].
+    pub fn needs_paint(&self) -> bool {
+        self.paint_dirty || self.layout_dirty
+    }
+
+    /// Enabled along the whole path to the root.
+    pub(crate) fn is_enabled(&self, id: WidgetId) -> bool {
+        let mut cur = Some(id);
+        while let Some(w) = cur {
+            match self.node(w) {
+                Some(n) if n.enabled => cur = n.parent,
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    fn is_focusable(&self, id: WidgetId) -> bool {
+        self.kind(id).is_some_and(Kind::is_focusable) && self.is_enabled(id)
+    }
+
+    /// Interaction state of `id` for styling.
+    pub(crate) fn state(&self, id: WidgetId) -> State {
+        State::default()
+            .with(State::HOVERED, self.hover == Some(id))
+            .with(
+                State::PRESSED,
+                self.pressed == Some(id) && self.hover == Some(id),
+            )
+            .with(State::FOCUSED, self.keyboard && self.focus == Some(id))
+            .with(State::DISABLED, !self.is_enabled(id))
+            .with(State::CHECKED, self.checked(id) == Some(true))
+    }
+
+    /// Widgets in tree order (pre-order).
+    pub(crate) fn walk(&self) -> Vec<WidgetId> {
+        let mut out = Vec::new();
+        let mut stack = alloc::vec![self.root];
+        while let Some(id) = stack.pop() {
+            out.push(id);
+            stack.extend(self.children(id).iter().rev());
+        }
+        out
+    }
+
+    /// Deepest widget under `p`.
+    fn hit(&self, p: Point) -> Option<WidgetId> {
+        let mut cur = self.root;
+        if !self.node(cur)?.bounds.contains(p) {
+            return None;
+        }
+        'descend: loop {
+            for &child in self.children(cur).iter().rev() {
+                if self.node(child).is_some_and(|n| n.bounds.contains(p)) {
+                    cur = child;
+                    continue 'descend;
+                }
+            }
+            return Some(cur);
+        }
+    }
+
+    pub(crate) fn row_height(&self) -> u32 {
+        self.metrics.line_height() + self.metrics.padding
+    }
+
+    /// List row under `p`, if `id` is a list.
+    fn row_at(&self, id: WidgetId, p: Point) -> Option<usize> {
+        let node = self.node(id)?;
+        let Kind::List { items, scroll, .. } = &node.kind else {
+            return None;
+        };
+        let dy = p.y.checked_sub(node.bounds.y)?;
+        let row = scroll + dy as usize / self.row_height().max(1) as usize;
+        (row < items.len()).then_some(row)
+    }
+
+    fn scroll_to_selection(&mut self, id: WidgetId) {
+        let Some(node) = self.node_mut(id) else {
+            return;
+        };
+        let rows = node.visible_rows.max(1);
+        if let Kind::List {
+            selected: Some(sel),
+            scroll,
+            ..
+        } = &mut node.kind
+        {
+            if *sel < *scroll {
+                *scroll = *sel;
+            } else if *sel >= *scroll + rows {
+                *scroll = *sel + 1 - rows;
+            }
+        }
+    }
+
+    /// Feeds one input event; returns the resulting action, if any.
+    pub fn handle(&mut self, event: Event) -> Option<Action> {
+        match event {
+            Event::PointerMotion(p) => {
+                self.pointer = p;
+                let hover = self.hit(p);
+                let row = hover.and_then(|id| self.row_at(id, p));
+                if (hover, row) != (self.hover, self.hover_row) {
+                    self.hover = hover;
+                    self.hover_row = row;
+                    self.paint_dirty = true;
+                }
+                None
+            }
+            Event::PointerLeave => {
+                self.hover = None;
+                self.hover_row = None;
+                self.paint_dirty = true;
+                None
+            }
+            Event::PointerButton {
+                button: BTN_LEFT,
+                pressed,
+                time_ms,
+            } => self.left_button(pressed, time_ms),
+            Event::PointerButton { .. } => None,
+            Event::Scroll(steps) => self.scroll(steps),
+            Event::Key {
+                keysym,
+                pressed: true,
+                modifiers,
+            } => {
+                self.keyboard = true;
+                if keysym == keysym::TAB || keysym == keysym::LEFT_TAB {
+                    self.cycle_focus(modifiers.shift || keysym == keysym::LEFT_TAB);
+                    return None;
+                }
+                self.key(keysym, modifiers)
+            }
+            Event::Key { .. } => None,
+            Event::Text(ch) => self.insert_text(ch),
+            Event::KeyboardLeave => {
+                self.keyboard = false;
+                self.paint_dirty = true;
+                None
+            }
+            Event::TouchDown { id, pos, time_ms } if self.touch.is_none() => {
+                self.touch = Some(id);
+                self.handle(Event::PointerMotion(pos));
+                self.left_button(true, time_ms)
+            }
+            Event::TouchMotion { id, pos } if self.touch == Some(id) => {
+                self.handle(Event::PointerMotion(pos))
+            }
+            Event::TouchUp { id, time_ms } if self.touch == Some(id) => {
+                self.touch = None;
+                let action = self.left_button(false, time_ms);
+                // Nothing stays hovered under a lifted finger.
+                self.handle(Event::PointerLeave);
+                action
+            }
+            Event::TouchCancel => {
+                self.touch = None;
+                self.pressed = None;
+                self.handle(Event::PointerLeave)
+            }
+            // Other fingers, and pinches: the app zooms if it wants to.
+            Event::TouchDown { .. }
+            | Event::TouchMotion { .. }
+            | Event::TouchUp { .. }
+            | Event::Pinch { .. } => None,
+            // Pressure and tilt are for drawing surfaces; widgets only see
+            // the tip as a button.
+            Event::Tablet(tool) => {
+                self.handle(Event::PointerMotion(tool.pos));
+                if tool.tip == self.stylus_tip {
+                    return None;
+                }
+                self.stylus_tip = tool.tip;
+                self.left_button(tool.tip, tool.time_ms)
+            }
+            Event::TabletLeave => {
+                if self.stylus_tip {
+                    self.stylus_tip = false;
+                    self.pressed = None;
+                }
+                self.handle(Event::PointerLeave)
+            }
+        }
+    }
+
+    fn left_button(&mut self, pressed: bool, time_ms: u32) -> Option<Action> {
+        let target = self.hit(self.pointer).filter(|&id| self.is_enabled(id));
+        self.paint_dirty = true;
+        if !pressed {
+            let was = self.pressed.take()?;
+            if target != Some(was) {
+                return None;
+            }
+            return match self.kind(was)? {
+                Kind::Button { .. } => Some(Action::Clicked(was)),
+                Kind::Checkbox { checked, .. } => {
+                    let now = !checked;
+                    self.set_checked(was, now);
+                    Some(Action::Toggled(was, now))
+                }
+                _ => None,
+            };
+        }
+
+        let id = target?;
+        self.pressed = Some(id);
+        if self.is_focusable(id) {
+            self.focus = Some(id);
+        }
+        match self.kind(id)? {
+            Kind::TextInput { text, .. } => {
+                let end = text.len();
+                self.update(id, |n| {
+                    if let Kind::TextInput { cursor, anchor, .. } = &mut n.kind {
+                        *cursor = end;
+                        *anchor = end;
+                    }
+                });
+                None
+            }
+            Kind::List { .. } => {
+                let row = self.row_at(id, self.pointer)?;
+                let double = self.last_click.is_some_and(|(w, r, t)| {
+                    w == id && r == Some(row) && time_ms.wrapping_sub(t) <= DOUBLE_CLICK_MS
+                });
+                self.last_click = (!double).then_some((id, Some(row), time_ms));
+                self.select(id, Some(row));
+                Some(if double {
+                    Action::Activated(id, row)
+                } else {
+                    Action::Selected(id, row)
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn scroll(&mut self, steps: i32) -> Option<Action> {
+        // Nearest list around the pointer.
+        let mut cur = self.hover;
+        while let Some(id) = cur {
+            if matches!(self.kind(id), Some(Kind::List { .. })) {
+                break;
+            }
+            cur = self.node(id).and_then(|n| n.parent);
+        }
+        let id = cur?;
+        let node = self.node_mut(id)?;
+        let rows = node.visible_rows;
+        if let Kind::List { items, scroll, .. } = &mut node.kind {
+            let max = items.len().saturating_sub(rows);
+            let delta = steps.unsigned_abs() as usize * SCROLL_ROWS;
+            *scroll = if steps < 0 {
+                scroll.saturating_sub(delta)
+            } else {
+                (*scroll + delta).min(max)
+            };
+        }
+        self.hover_row = self.row_at(id, self.pointer);
+        self.paint_dirty = true;
+        None
+    }
+
+    fn cycle_focus(&mut self, backwards: bool) {
+        let order: Vec<WidgetId> = self
+            .walk()
+            .into_iter()
+            .filter(|&id| self.is_focusable(id))
+            .collect();
+        if order.is_empty() {
+            return;
+        }
+        let next = match self.focus.and_then(|f| order.iter().position(|&w| w == f)) {
+            None if backwards => order.len() - 1,
+            None => 0,
+            Some(i) if backwards => (i + order.len() - 1) % order.len(),
+            Some(i) => (i + 1) % order.len(),
+        };
+        self.focus = Some(order[next]);
+        self.paint_dirty = true;
+    }
+
+    fn key(&mut self, sym: u32, modifiers: Modifiers) -> Option<Action> {
+        let id = self.focus.filter(|&id| self.is_enabled(id))?;
+        match self.kind(id)? {
+            Kind::Button { .. } if sym == keysym::RETURN || sym == keysym::SPACE => {
+                Some(Action::Clicked(id))
+            }
+            Kind::Checkbox { checked, .. } if sym == keysym::SPACE => {
+                let now = !checked;
+                self.set_checked(id, now);
+                Some(Action::Toggled(id, now))
+            }
+            Kind::TextInput { .. } => self.edit(id, sym, modifiers.shift),
+            Kind::List {
+                items, selected, ..
+            } => {
+                let (len, sel) = (items.len(), *selected);
+                if len == 0 {
+                    return None;
+                }
+                if sym == keysym::RETURN {
+                    return sel.map(|row| Action::Activated(id, row));
+                }
+                let page = self.node(id)?.visible_rows.max(1);
+                let cur = sel.unwrap_or(0);
+                let row = match sym {
+                    keysym::UP if sel.is_some() => cur.saturating_sub(1),
+                    keysym::DOWN if sel.is_some() => (cur + 1).min(len - 1),
+                    keysym::UP | keysym::DOWN => 0,
+                    keysym::PAGE_UP => cur.saturating_sub(page),
+                    keysym::PAGE_DOWN => (cur + page).min(len - 1),
+                    keysym::HOME => 0,
+                    keysym::END => len - 1,
+                    _ => return None,
+                };
+                self.select(id, Some(row));
+                (sel != Some(row)).then_some(Action::Selected(id, row))
+            }
+            _ => None,
+        }
+    }
+
+    /// Arrows, Home and End move by cluster, extending the selection with
+    /// Shift; Backspace and Delete remove the selection or one cluster.
+    fn edit(&mut self, id: WidgetId, sym: u32, extend: bool) -> Option<Action> {
+        if sym == keysym::RETURN {
+            return Some(Action::Submitted(id));
+        }
+        let mut edited = false;
+        self.update(id, |n| {
+            let Kind::TextInput {
+                text,
+                cursor,
+                anchor,
+                ..
+            } = &mut n.kind
+            else {
+                return;
+            };
+            let (start, end) = ((*cursor).min(*anchor), (*cursor).max(*anchor));
+            let to = match sym {
+                keysym::LEFT if start != end && !extend => start,
+                keysym::RIGHT if start != end && !extend => end,
+                keysym::LEFT => grapheme::prev_boundary(text, *cursor),
+                keysym::RIGHT => grapheme::next_boundary(text, *cursor),
+                keysym::HOME => 0,
+                keysym::END => text.len(),
+                keysym::BACKSPACE | keysym::DELETE => {
+                    let (from, to) = match sym {
+                        _ if start != end => (start, end),
+                        keysym::BACKSPACE => (grapheme::prev_boundary(text, start), start),
+                        _ => (start, grapheme::next_boundary(text, start)),
+                    };
+                    edited = from != to;
+                    text.replace_range(from..to, "");
+                    *anchor = from;
+                    *cursor = from;
+                    return;
+                }
+                _ => return,
+            };
+            *cursor = to;
+            if !extend {
+                *anchor = to;
+            }
+        });
+        edited.then_some(Action::Edited(id))
+    }
+
+    fn insert_text(&mut self, ch: char) -> Option<Action> {
+        let id = self.focus.filter(|&id| self.is_enabled(id))?;
+        if ch.is_control() || !matches!(self.kind(id)?, Kind::TextInput { .. }) {
+            return None;
+        }
+        self.update(id, |n| {
+            if let Kind::TextInput {
+                text,
+                cursor,
+                anchor,
+                ..
+            } = &mut n.kind
+            {
+                let start = (*cursor).min(*anchor);
+                text.replace_range(start..(*cursor).max(*anchor), "");
+                text.insert(start, ch);
+                *cursor = start + ch.len_utf8();
+                *anchor = *cursor;
+            }
+        });
+        Some(Action::Edited(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Size;
+    use crate::input::{Tool, PRESSURE_MAX};
+    use crate::text::Monospace;
+    use alloc::string::ToString;
+
+    fn key(keysym: u32) -> Event {
+        Event::Key {
+            keysym,
+            pressed: true,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    fn click(ui: &mut Ui, at: Point, time_ms: u32) -> Option<Action> {
+        ui.handle(Event::PointerMotion(at));
+        let press = ui.handle(Event::PointerButton {
+            button: BTN_LEFT,
+            pressed: true,
+            time_ms,
+        });
+        let release = ui.handle(Event::PointerButton {
+            button: BTN_LEFT,
+            pressed: false,
+            time_ms,
+        });
+        press.or(release)
+    }
+
+    fn click_on(ui: &mut Ui, id: WidgetId) -> Option<Action> {
+        let at = center(ui, id);
+        click(ui, at, 0)
+    }
+
+    fn center(ui: &Ui, id: WidgetId) -> Point {
+        let r = ui.bounds(id).unwrap();
+        Point::new(r.x + r.w as i32 / 2, r.y + r.h as i32 / 2)
+    }
+
+    fn form() -> (Ui, [WidgetId; 4]) {
+        let mut ui = Ui::new();
+        let root = ui.root();
+        let name = ui.push(root, Kind::text_input("Name")).unwrap();
+        let check = ui.push(root, Kind::checkbox("Remember", false)).unwrap();
+        let items = (0..20).map(|i| i.to_string()).collect();
+        let list = ui.push(root, Kind::list(items)).unwrap();
+        ui.set_height(list, Length::Fill(1));
+        let ok = ui.push(root, Kind::button("OK")).unwrap();
+        ui.layout(Size::new(300, 400), &StockTheme::light(), &Monospace);
+        (ui, [name, check, list, ok])
+    }
+
+    #[test]
+    fn pointer_clicks_and_toggles() {
+        let (mut ui, [_, check, _, ok]) = form();
+        assert_eq!(click_on(&mut ui, ok), Some(Action::Clicked(ok)));
+        assert_eq!(click_on(&mut ui, check), Some(Action::Toggled(check, true)));
+        assert_eq!(ui.checked(check), Some(true));
+        // Press on the button, release elsewhere: no click.
+        ui.handle(Event::PointerMotion(center(&ui, ok)));
+        ui.handle(Event::PointerButton {
+            button: BTN_LEFT,
+            pressed: true,
+            time_ms: 0,
+        });
+        ui.handle(Event::PointerMotion(Point::new(-5, -5)));
+        let release = Event::PointerButton {
+            button: BTN_LEFT,
+            pressed: false,
+            time_ms: 0,
+        };
+        assert_eq!(ui.handle(release), None);
+        ui.set_enabled(ok, false);
+        assert_eq!(click_on(&mut ui, ok), None);
+    }
+
+    #[test]
+    fn first_finger_taps_like_the_pointer() {
+        let (mut ui, [_, check, _, ok]) = form();
+        let tap = |ui: &mut Ui, id: i32, on: WidgetId| {
+            let down = ui.handle(Event::TouchDown {
+                id,
+                pos: center(ui, on),
+                time_ms: 0,
+            });
+            down.or(ui.handle(Event::TouchUp { id, time_ms: 0 }))
+        };
+        assert_eq!(tap(&mut ui, 4, ok), Some(Action::Clicked(ok)));
+        assert_eq!(ui.hover, None);
+
+        // A second finger does nothing while the first one is down.
+        ui.handle(Event::TouchDown {
+            id: 1,
+            pos: center(&ui, ok),
+            time_ms: 0,
+        });
+        assert_eq!(tap(&mut ui, 2, check), None);
+        assert_eq!(ui.checked(check), Some(false));
+        // Taken by a compositor gesture: lifting clicks nothing.
+        ui.handle(Event::TouchCancel);
+        assert_eq!(ui.handle(Event::TouchUp { id: 1, time_ms: 0 }), None);
+        let pinch = Event::Pinch {
+            scale: 512,
+            end: false,
+        };
+        assert_eq!(ui.handle(pinch), None);
+    }
+
+    #[test]
+    fn stylus_tip_clicks() {
+        let (mut ui, [_, check, _, ok]) = form();
+        let mut tool = Tool {
+            pos: center(&ui, ok),
+            ..Tool::default()
+        };
+        assert_eq!(ui.handle(Event::Tablet(tool)), None);
+        assert_eq!(ui.hover, Some(ok));
+        tool.tip = true;
+        tool.pressure = PRESSURE_MAX / 2;
+        assert_eq!(ui.handle(Event::Tablet(tool)), None);
+        tool.pressure = PRESSURE_MAX;
+        assert_eq!(ui.handle(Event::Tablet(tool)), None);
+        tool.tip = false;
+        assert_eq!(ui.handle(Event::Tablet(tool)), Some(Action::Clicked(ok)));
+
+        // Pressed, then out of proximity: nothing is clicked.
+        tool.pos = center(&ui, check);
+        tool.tip = true;
+        ui.handle(Event::Tablet(tool));
+        ui.handle(Event::TabletLeave);
+        assert_eq!(ui.hover, None);
+        tool.tip = false;
+        assert_eq!(ui.handle(Event::Tablet(tool)), None);
+        assert_eq!(ui.checked(check), Some(false));
+    }
+
+    #[test]
+    fn tab_focus_and_text_editing() {
+        let (mut ui, [name, check, list, ok]) = form();
+        ui.handle(key(keysym::TAB));
+        assert_eq!(ui.focused(), Some(name));
+        for ch in "héllo".chars() {
+            assert_eq!(ui.handle(Event::Text(ch)), Some(Action::Edited(name)));
+        }
+        ui.handle(key(keysym::LEFT));
+        ui.handle(key(keysym::LEFT));
+        ui.handle(key(keysym::LEFT));
+        assert_eq!(
+            ui.handle(key(keysym::BACKSPACE)),
+            Some(Action::Edited(name))
+        );
+        assert_eq!(ui.text(name), Some("hllo"));
+        ui.handle(key(keysym::HOME));
+        assert_eq!(ui.handle(key(keysym::BACKSPACE)), None);
+        assert_eq!(ui.handle(Event::Text('\u{8}')), None);
+        assert_eq!(
+            ui.handle(key(keysym::RETURN)),
+            Some(Action::Submitted(name))
+        );
+
+        ui.handle(key(keysym::TAB));
+        assert_eq!(ui.focused(), Some(check));
+        assert_eq!(
+            ui.handle(key(keysym::SPACE)),
+            Some(Action::Toggled(check, true))
+        );
+        ui.handle(key(keysym::TAB));
+        ui.handle(key(keysym::TAB));
+        assert_eq!(ui.focused(), Some(ok));
+        ui.handle(key(keysym::TAB));
+        assert_eq!(ui.focused(), Some(name));
+        ui.handle(key(keysym::LEFT_TAB));
+        assert_eq!(ui.focused(), Some(ok));
+        ui.handle(key(keysym::LEFT_TAB));
+        assert_eq!(ui.focused(), Some(list));
+    }
+
+    #[test]
+    fn editing_steps_over_clusters_and_selects_with_shift() {
+        let (mut ui, [name, ..]) = form();
+        let shift = |keysym| Event::Key {
+            keysym,
+            pressed: true,
+            modifiers: Modifiers {
+                shift: true,
+                ..Modifiers::default()
+            },
+        };
+        ui.focus(name);
+        for ch in "ae\u{301}🇫🇷b".chars() {
+            ui.handle(Event::Text(ch));
+        }
+        ui.handle(key(keysym::LEFT));
+        ui.handle(key(keysym::LEFT));
+        assert_eq!(
+            ui.handle(key(keysym::BACKSPACE)),
+            Some(Action::Edited(name))
+        );
+        assert_eq!(ui.text(name), Some("a🇫🇷b"));
+        assert_eq!(ui.handle(key(keysym::DELETE)), Some(Action::Edited(name)));
+        assert_eq!(ui.text(name), Some("ab"));
+
+        ui.set_text(name, "👩‍💻 ok");
+        ui.handle(key(keysym::HOME));
+        assert_eq!(ui.selection(name), None);
+        ui.handle(shift(keysym::RIGHT));
+        assert_eq!(ui.selection(name), Some("👩‍💻"));
+        ui.handle(shift(keysym::END));
+        assert_eq!(ui.selection(name), Some("👩‍💻 ok"));
+        ui.handle(shift(keysym::LEFT));
+        ui.handle(shift(keysym::LEFT));
+        assert_eq!(ui.selection(name), Some("👩‍💻 "));
+        // Typing replaces the selection; arrows collapse it first.
+        ui.handle(Event::Text('X'));
+        assert_eq!((ui.text(name), ui.selection(name)), (Some("Xok"), None));
+        ui.handle(shift(keysym::RIGHT));
+        ui.handle(key(keysym::LEFT));
+        assert_eq!(ui.selection(name), None);
+        ui.handle(shift(keysym::END));
+        assert_eq!(ui.handle(key(keysym::DELETE)), Some(Action::Edited(name)));
+        assert_eq!(ui.text(name), Some("X"));
+    }
+
+    #[test]
+    fn list_keyboard_scroll_and_double_click() {
+        let (mut ui, [_, _, list, _]) = form();
+        let rows = ui.node(list).unwrap().visible_rows;
+        assert!(rows > 0 && rows < 20);
+        ui.focus(list);
+        assert_eq!(
+            ui.handle(key(keysym::DOWN)),
+            Some(Action::Selected(list, 0))
+        );
+        assert_eq!(
+            ui.handle(key(keysym::END)),
+            Some(Action::Selected(list, 19))
+        );
+        assert_eq!(ui.handle(key(keysym::DOWN)), None);
+        let Some(Kind::List { scroll, .. }) = ui.kind(list) else {
+            panic!("list");
+        };
+        assert_eq!(*scroll, 20 - rows);
+        assert_eq!(
+            ui.handle(key(keysym::RETURN)),
+            Some(Action::Activated(list, 19))
+        );
+
+        ui.handle(key(keysym::HOME));
+        let top = ui.bounds(list).unwrap();
+        let row1 = Point::new(top.x + 4, top.y + ui.row_height() as i32 + 1);
+        assert_eq!(click(&mut ui, row1, 1000), Some(Action::Selected(list, 1)));
+        assert_eq!(click(&mut ui, row1, 1200), Some(Action::Activated(list, 1)));
+        assert_eq!(click(&mut ui, row1, 1300), Some(Action::Selected(list, 1)));
+
+        ui.handle(Event::Scroll(1));
+        assert_eq!(ui.row_at(list, row1), Some(1 + SCROLL_ROWS));
+        ui.handle(Event::Scroll(100));
+        ui.handle(Event::Scroll(-1));
+        let Some(Kind::List { scroll, .. }) = ui.kind(list) else {
+            panic!("list");
+        };
+        assert_eq!(*scroll, 20 - rows - SCROLL_ROWS);
+    }
+
+    #[test]
+    fn removing_a_subtree_clears_focus_and_ids() {
+        let mut ui = Ui::new();
+        let root = ui.root();
+        let row = ui.push(root, Kind::Row).unwrap();
+        let b = ui.push(row, Kind::button("x")).unwrap();
+        assert_eq!(ui.push(b, Kind::Spacer), None);
+        ui.focus(b);
+        assert!(ui.remove(row));
+        assert_eq!(ui.focused(), None);
+        assert!(ui.kind(b).is_none());
+        assert!(!ui.remove(root));
+        let c = ui.push(root, Kind::Spacer).unwrap();
+        assert_ne!(c, b);
+        assert_eq!(ui.children(root), &[c]);
+    }
+}
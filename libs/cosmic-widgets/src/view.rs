@@ -0,0 +1,447 @@
+//! A markup document built into a [`Ui`] and bound to the app state.
+//!
+//! `$key` properties read the app's [`Model`]: [`View::sync`] pushes it
+//! into the widgets after the app changed its state, [`View::apply`] writes
+//! what the user toggled, typed or selected back into it. Widgets with an
+//! `id` are found by name, which is how an app tells its actions apart.
+//!
+//! During development [`View::poll`] rebuilds the view whenever the
+//! document's [`Source`] changes, so a layout or label is tuned without
+//! rebuilding the app. Bound values come back from the model and focus
+//! stays on the widget with the same `id`; unbound widget state (an
+//! unbound checkbox, scroll positions) starts over.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::markup::{self, Error, ErrorKind, Prop, Template};
+use crate::ui::{Action, Ui};
+use crate::widget::{Kind, WidgetId};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Text(String),
+    Items(Vec<String>),
+    Row(Option<usize>),
+}
+
+/// The app state seen by the bindings.
+pub trait Model {
+    /// Value of `key`; `None` leaves the bound widgets as they are.
+    fn get(&self, key: &str) -> Option<Value>;
+
+    /// The user changed a widget bound to `key`. Read-only models ignore
+    /// it.
+    fn set(&mut self, key: &str, value: Value) {
+        let _ = (key, value);
+    }
+}
+
+/// Where a document comes from, for [`View::poll`].
+pub trait Source {
+    /// Changes whenever the content does (file modification time, a
+    /// counter bumped by the editor...).
+    fn revision(&self) -> u64;
+
+    /// Current content; `None` while it cannot be read (an editor halfway
+    /// through replacing the file).
+    fn read(&self) -> Option<String>;
+}
+
+struct Binding {
+    widget: WidgetId,
+    prop: Prop,
+    key: String,
+}
+
+pub struct View {
+    parent: WidgetId,
+    root: WidgetId,
+    names: Vec<(String, WidgetId)>,
+    bindings: Vec<Binding>,
+    revision: Option<u64>,
+}
+
+impl View {
+    /// Builds `src` as the last child of `parent` and syncs it with
+    /// `model`.
+    pub fn load(
+        ui: &mut Ui,
+        parent: WidgetId,
+        src: &str,
+        model: &dyn Model,
+    ) -> Result<Self, Error> {
+        let template = markup::parse(src)?;
+        let mut view = Self {
+            parent,
+            root: parent,
+            names: Vec::new(),
+            bindings: Vec::new(),
+            revision: None,
+        };
+        view.root = view
+            .build(ui, parent, usize::MAX, &template)
+            .ok_or(Error::new(ErrorKind::BadParent))?;
+        view.sync(ui, model);
+        Ok(view)
+    }
+
+    /// [`View::load`] from a source [`View::poll`] then watches.
+    pub fn load_source(
+        ui: &mut Ui,
+        parent: WidgetId,
+        source: &dyn Source,
+        model: &dyn Model,
+    ) -> Result<Self, Error> {
+        let revision = source.revision();
+        let src = source.read().ok_or(Error::new(ErrorKind::Unreadable))?;
+        let mut view = Self::load(ui, parent, &src, model)?;
+        view.revision = Some(revision);
+        Ok(view)
+    }
+
+    /// Top widget of the view.
+    pub fn root(&self) -> WidgetId {
+        self.root
+    }
+
+    /// Widget declared with `id: "name"`.
+    pub fn widget(&self, name: &str) -> Option<WidgetId> {
+        self.names
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|&(_, id)| id)
+    }
+
+    /// `id` of a widget, if it has one.
+    pub fn name(&self, widget: WidgetId) -> Option<&str> {
+        self.names
+            .iter()
+            .find(|&&(_, id)| id == widget)
+            .map(|(n, _)| n.as_str())
+    }
+
+    fn build(
+        &mut self,
+        ui: &mut Ui,
+        parent: WidgetId,
+        index: usize,
+        t: &Template,
+    ) -> Option<WidgetId> {
+        let id = ui.insert(parent, index, t.kind.clone())?;
+        ui.set_width(id, t.width);
+        ui.set_height(id, t.height);
+        ui.set_padding(id, t.padding);
+        if !t.enabled {
+            ui.set_enabled(id, false);
+        }
+        if let Some(label) = &t.a11y {
+            ui.set_a11y_label(id, label);
+        }
+        if let Kind::List { selected, .. } = t.kind {
+            // Drops a literal selection past the literal items.
+            ui.select(id, selected);
+        }
+        if let Some(name) = &t.id {
+            self.names.push((name.clone(), id));
+        }
+        for (prop, key) in &t.bindings {
+            self.bindings.push(Binding {
+                widget: id,
+                prop: *prop,
+                key: key.clone(),
+            });
+        }
+        for child in t.children() {
+            self.build(ui, id, usize::MAX, child);
+        }
+        Some(id)
+    }
+
+    /// Replaces the view with `src`, in place. On error the current tree
+    /// stays untouched.
+    pub fn reload(&mut self, ui: &mut Ui, src: &str, model: &dyn Model) -> Result<(), Error> {
+        let template = markup::parse(src)?;
+        let index = ui
+            .children(self.parent)
+            .iter()
+            .position(|&c| c == self.root);
+        let focus: Option<String> = ui.focused().and_then(|f| self.name(f)).map(Into::into);
+        ui.remove(self.root);
+        self.names.clear();
+        self.bindings.clear();
+        self.root = self
+            .build(ui, self.parent, index.unwrap_or(usize::MAX), &template)
+            .ok_or(Error::new(ErrorKind::BadParent))?;
+        self.sync(ui, model);
+        if let Some(w) = focus.and_then(|name| self.widget(&name)) {
+            ui.focus(w);
+        }
+        Ok(())
+    }
+
+    /// Hot reload: rebuilds the view if `source` changed since it was last
+    /// read. Returns the outcome, `None` when nothing changed. A broken
+    /// document is reported once per revision while the previous tree
+    /// stays up.
+    pub fn poll(
+        &mut self,
+        ui: &mut Ui,
+        source: &dyn Source,
+        model: &dyn Model,
+    ) -> Option<Result<(), Error>> {
+        let revision = source.revision();
+        if self.revision == Some(revision) {
+            return None;
+        }
+        let src = source.read()?;
+        self.revision = Some(revision);
+        Some(self.reload(ui, &src, model))
+    }
+
+    /// Pushes the model into the bound widgets. Call after the app changed
+    /// its state; widgets already showing the value are left alone, so an
+    /// unchanged model costs no layout.
+    pub fn sync(&self, ui: &mut Ui, model: &dyn Model) {
+        for b in &self.bindings {
+            let Some(value) = model.get(&b.key) else {
+                continue;
+            };
+            if shows(ui, b.widget, b.prop, &value) {
+                continue;
+            }
+            match (b.prop, value) {
+                (Prop::Text, Value::Text(v)) => ui.set_text(b.widget, &v),
+                (Prop::Checked, Value::Bool(v)) => ui.set_checked(b.widget, v),
+                (Prop::Enabled, Value::Bool(v)) => ui.set_enabled(b.widget, v),
+                (Prop::Items, Value::Items(v)) => ui.set_items(b.widget, v),
+                (Prop::Selected, Value::Row(v)) => ui.select(b.widget, v),
+                // A value of the wrong type is ignored.
+                _ => false,
+            };
+        }
+    }
+
+    /// Writes a user change back to the model if the widget is bound.
+    /// Returns whether a binding was written.
+    pub fn apply(&self, ui: &Ui, action: Action, model: &mut dyn Model) -> bool {
+        let (widget, prop, value) = match action {
+            Action::Toggled(id, v) => (id, Prop::Checked, Value::Bool(v)),
+            Action::Edited(id) => (
+                id,
+                Prop::Text,
+                Value::Text(ui.text(id).unwrap_or_default().into()),
+            ),
+            Action::Selected(id, row) => (id, Prop::Selected, Value::Row(Some(row))),
+            _ => return false,
+        };
+        let mut written = false;
+        for b in self
+            .bindings
+            .iter()
+            .filter(|b| b.widget == widget && b.prop == prop)
+        {
+            model.set(&b.key, value.clone());
+            written = true;
+        }
+        written
+    }
+}
+
+/// `widget` already shows `value` for `prop`.
+fn shows(ui: &Ui, widget: WidgetId, prop: Prop, value: &Value) -> bool {
+    let Some(node) = ui.node(widget) else {
+        return true;
+    };
+    match (prop, &node.kind, value) {
+        (
+            Prop::Text,
+            Kind::Label { text }
+            | Kind::Button { label: text }
+            | Kind::Checkbox { label: text, .. }
+            | Kind::TextInput { text, .. },
+            Value::Text(v),
+        ) => text == v,
+        (Prop::Checked, Kind::Checkbox { checked, .. }, Value::Bool(v)) => checked == v,
+        (Prop::Enabled, _, Value::Bool(v)) => node.enabled == *v,
+        (Prop::Items, Kind::List { items, .. }, Value::Items(v)) => items == v,
+        (Prop::Selected, Kind::List { selected, .. }, Value::Row(v)) => selected == v,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Size;
+    use crate::input::{keysym, Event, Modifiers};
+    use crate::text::Monospace;
+    use crate::theme::StockTheme;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use core::cell::{Cell, RefCell};
+
+    #[derive(Default)]
+    struct Prefs {
+        dark: bool,
+        name: String,
+        fonts: Vec<String>,
+        font: Option<usize>,
+    }
+
+    impl Model for Prefs {
+        fn get(&self, key: &str) -> Option<Value> {
+            Some(match key {
+                "dark" => Value::Bool(self.dark),
+                "name" => Value::Text(self.name.clone()),
+                "fonts" => Value::Items(self.fonts.clone()),
+                "font" => Value::Row(self.font),
+                _ => return None,
+            })
+        }
+
+        fn set(&mut self, key: &str, value: Value) {
+            match (key, value) {
+                ("dark", Value::Bool(v)) => self.dark = v,
+                ("name", Value::Text(v)) => self.name = v,
+                ("font", Value::Row(v)) => self.font = v,
+                _ => {}
+            }
+        }
+    }
+
+    const PREFS: &str = r#"Column(children: [
+        Checkbox(id: "dark", label: "Dark style", checked: $dark),
+        TextInput(id: "name", placeholder: "Device name", text: $name),
+        List(id: "fonts", items: $fonts, selected: $font, height: fill),
+    ])"#;
+
+    fn prefs() -> Prefs {
+        Prefs {
+            name: "exo".into(),
+            fonts: vec!["Sans".into(), "Mono".into()],
+            ..Prefs::default()
+        }
+    }
+
+    #[test]
+    fn bindings_flow_both_ways() {
+        let mut model = prefs();
+        let mut ui = Ui::new();
+        let root = ui.root();
+        let view = View::load(&mut ui, root, PREFS, &model).unwrap();
+        let (dark, name, fonts) = (
+            view.widget("dark").unwrap(),
+            view.widget("name").unwrap(),
+            view.widget("fonts").unwrap(),
+        );
+        assert_eq!(view.name(name), Some("name"));
+        assert_eq!(ui.text(name), Some("exo"));
+        assert_eq!(ui.checked(dark), Some(false));
+        assert_eq!(ui.selected(fonts), None);
+
+        ui.layout(Size::new(300, 300), &StockTheme::light(), &Monospace);
+        model.dark = true;
+        model.font = Some(1);
+        view.sync(&mut ui, &model);
+        assert!(ui.needs_layout());
+        assert_eq!(
+            (ui.checked(dark), ui.selected(fonts)),
+            (Some(true), Some(1))
+        );
+        ui.layout(Size::new(300, 300), &StockTheme::light(), &Monospace);
+        view.sync(&mut ui, &model);
+        assert!(!ui.needs_layout());
+
+        ui.focus(name);
+        let action = ui.handle(Event::Text('!')).unwrap();
+        assert!(view.apply(&ui, action, &mut model));
+        assert_eq!(model.name, "exo!");
+        ui.focus(dark);
+        let space = Event::Key {
+            keysym: keysym::SPACE,
+            pressed: true,
+            modifiers: Modifiers::default(),
+        };
+        let action = ui.handle(space).unwrap();
+        assert!(view.apply(&ui, action, &mut model));
+        assert!(!model.dark);
+        assert!(!view.apply(&ui, Action::Clicked(dark), &mut model));
+    }
+
+    struct Editor {
+        revision: Cell<u64>,
+        text: RefCell<Option<String>>,
+    }
+
+    impl Source for Editor {
+        fn revision(&self) -> u64 {
+            self.revision.get()
+        }
+
+        fn read(&self) -> Option<String> {
+            self.text.borrow().clone()
+        }
+    }
+
+    impl Editor {
+        fn save(&self, text: Option<&str>) {
+            self.revision.set(self.revision.get() + 1);
+            *self.text.borrow_mut() = text.map(ToString::to_string);
+        }
+    }
+
+    #[test]
+    fn hot_reload_keeps_place_focus_and_bound_state() {
+        let model = prefs();
+        let mut ui = Ui::new();
+        let root = ui.root();
+        let header = ui.push(root, Kind::label("Settings")).unwrap();
+        let editor = Editor {
+            revision: Cell::new(1),
+            text: RefCell::new(Some(PREFS.into())),
+        };
+        let mut view = View::load_source(&mut ui, root, &editor, &model).unwrap();
+        let footer = ui.push(root, Kind::button("Close")).unwrap();
+        assert_eq!(view.poll(&mut ui, &editor, &model), None);
+
+        let old = view.root();
+        ui.focus(view.widget("name").unwrap());
+        editor.save(Some(&PREFS.replace("Device name", "Host name")));
+        assert_eq!(view.poll(&mut ui, &editor, &model), Some(Ok(())));
+        assert_ne!(view.root(), old);
+        assert!(ui.kind(old).is_none());
+        assert_eq!(ui.children(root), &[header, view.root(), footer]);
+        let name = view.widget("name").unwrap();
+        assert_eq!(ui.focused(), Some(name));
+        assert_eq!(ui.text(name), Some("exo"));
+        assert!(
+            matches!(ui.kind(name), Some(Kind::TextInput { placeholder, .. }) if placeholder == "Host name")
+        );
+
+        // Half-written file, then a typo: the tree stays, reported once.
+        editor.save(None);
+        assert_eq!(view.poll(&mut ui, &editor, &model), None);
+        editor.save(Some("Column(children: [Lable()])"));
+        let err = view.poll(&mut ui, &editor, &model).unwrap().unwrap_err();
+        assert_eq!(
+            (err.line, err.col, err.kind),
+            (1, 19, ErrorKind::UnknownElement)
+        );
+        assert_eq!(view.poll(&mut ui, &editor, &model), None);
+        assert_eq!(view.widget("name"), Some(name));
+        assert!(ui.kind(name).is_some());
+    }
+
+    #[test]
+    fn load_into_a_leaf_fails() {
+        let mut ui = Ui::new();
+        let root = ui.root();
+        let leaf = ui.push(root, Kind::Spacer).unwrap();
+        let err = View::load(&mut ui, leaf, "Spacer()", &prefs())
+            .err()
+            .unwrap();
+        assert_eq!(err.kind, ErrorKind::BadParent);
+    }
+}
@@ -0,0 +1,155 @@
+//! Widget kinds and per-node data. Widgets are plain data owned by the
+//! [`crate::Ui`] tree; the app refers to them by [`WidgetId`].
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::geometry::Rect;
+
+/// Handle of a widget. Slots are never reused, so a stale id of a removed
+/// widget stays invalid instead of aliasing a new one.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct WidgetId(pub(crate) u32);
+
+/// Size request along one axis.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Length {
+    /// Natural size of the content.
+    #[default]
+    Shrink,
+    Fixed(u32),
+    /// Share of the space left by the siblings, by weight.
+    Fill(u16),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Kind {
+    /// Children side by side, left to right.
+    Row,
+    /// Children stacked, top to bottom.
+    Column,
+    Label {
+        text: String,
+    },
+    /// Atlas icon, square.
+    Icon {
+        name: String,
+        px: u16,
+    },
+    Button {
+        label: String,
+    },
+    Checkbox {
+        label: String,
+        checked: bool,
+    },
+    TextInput {
+        text: String,
+        placeholder: String,
+        /// Byte offset of the caret, on a cluster boundary.
+        cursor: usize,
+        /// Other end of the selection, `cursor` when nothing is selected.
+        anchor: usize,
+    },
+    List {
+        items: Vec<String>,
+        selected: Option<usize>,
+        /// First visible row.
+        scroll: usize,
+    },
+    /// Empty space, usually `Fill` to push siblings apart.
+    Spacer,
+}
+
+impl Kind {
+    pub fn label(text: &str) -> Self {
+        Kind::Label { text: text.into() }
+    }
+
+    pub fn icon(name: &str, px: u16) -> Self {
+        Kind::Icon {
+            name: name.into(),
+            px,
+        }
+    }
+
+    pub fn button(label: &str) -> Self {
+        Kind::Button {
+            label: label.into(),
+        }
+    }
+
+    pub fn checkbox(label: &str, checked: bool) -> Self {
+        Kind::Checkbox {
+            label: label.into(),
+            checked,
+        }
+    }
+
+    pub fn text_input(placeholder: &str) -> Self {
+        Kind::TextInput {
+            text: String::new(),
+            placeholder: placeholder.into(),
+            cursor: 0,
+            anchor: 0,
+        }
+    }
+
+    pub fn list(items: Vec<String>) -> Self {
+        Kind::List {
+            items,
+            selected: None,
+            scroll: 0,
+        }
+    }
+
+    pub fn is_container(&self) -> bool {
+        matches!(self, Kind::Row | Kind::Column)
+    }
+
+    /// Takes keyboard focus (Tab order).
+    pub fn is_focusable(&self) -> bool {
+        matches!(
+            self,
+            Kind::Button { .. }
+                | Kind::Checkbox { .. }
+                | Kind::TextInput { .. }
+                | Kind::List { .. }
+        )
+    }
+}
+
+pub(crate) struct Node {
+    pub kind: Kind,
+    pub parent: Option<WidgetId>,
+    pub children: Vec<WidgetId>,
+    pub width: Length,
+    pub height: Length,
+    /// Inner padding of a row or column.
+    pub padding: u32,
+    pub enabled: bool,
+    /// Accessible name when the content does not say it (icon buttons,
+    /// window title on the root).
+    pub a11y_label: Option<String>,
+    /// Set by layout.
+    pub bounds: Rect,
+    /// Rows a list shows at its current size, set by layout.
+    pub visible_rows: usize,
+}
+
+impl Node {
+    pub fn new(kind: Kind, parent: Option<WidgetId>) -> Self {
+        Self {
+            kind,
+            parent,
+            children: Vec::new(),
+            width: Length::Shrink,
+            height: Length::Shrink,
+            padding: 0,
+            enabled: true,
+            a11y_label: None,
+            bounds: Rect::default(),
+            visible_rows: 0,
+        }
+    }
+}
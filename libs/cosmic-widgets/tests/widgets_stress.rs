@@ -0,0 +1,170 @@
+use cosmic_widgets::geometry::{Point, Size};
+use cosmic_widgets::input::{keysym, Event, Modifiers, BTN_LEFT};
+use cosmic_widgets::paint::DrawCmd;
+use cosmic_widgets::text::Monospace;
+use cosmic_widgets::theme::StockTheme;
+use cosmic_widgets::{Kind, Length, Ui};
+
+#[test]
+fn random_input_keeps_the_tree_consistent() {
+    let theme = StockTheme::dark();
+    let mut ui = Ui::new();
+    let root = ui.root();
+    let bar = ui.push(root, Kind::Row).unwrap();
+    let input = ui.push(bar, Kind::text_input("Name")).unwrap();
+    let check = ui.push(bar, Kind::checkbox("Hidden", false)).unwrap();
+    let ok = ui.push(bar, Kind::button("OK")).unwrap();
+    let items = (0..200).map(|i| format!("item {i}")).collect();
+    let list = ui.push(root, Kind::list(items)).unwrap();
+    ui.set_height(list, Length::Fill(1));
+    ui.set_width(list, Length::Fill(1));
+
+    let keys = [
+        keysym::TAB,
+        keysym::LEFT_TAB,
+        keysym::UP,
+        keysym::DOWN,
+        keysym::PAGE_UP,
+        keysym::PAGE_DOWN,
+        keysym::HOME,
+        keysym::END,
+        keysym::LEFT,
+        keysym::RIGHT,
+        keysym::BACKSPACE,
+        keysym::DELETE,
+        keysym::RETURN,
+        keysym::SPACE,
+    ];
+    let mut seed = 0x2545_f491u32;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed
+    };
+    let mut size = Size::new(480, 320);
+    for step in 0..20_000u32 {
+        let r = next();
+        let event = match r % 9 {
+            0 | 1 => Event::PointerMotion(Point::new(
+                (next() % 520) as i32 - 20,
+                (next() % 360) as i32 - 20,
+            )),
+            2 => Event::PointerButton {
+                button: BTN_LEFT,
+                pressed: r & 0x100 != 0,
+                time_ms: step * 50,
+            },
+            3 => Event::Scroll((next() % 7) as i32 - 3),
+            4 | 5 => Event::Key {
+                keysym: keys[(next() as usize) % keys.len()],
+                pressed: true,
+                modifiers: Modifiers::default(),
+            },
+            6 => Event::Text(['a', 'é', '→', ' '][(next() % 4) as usize]),
+            7 => Event::PointerLeave,
+            _ => Event::KeyboardLeave,
+        };
+        ui.handle(event);
+
+        if step % 1000 == 0 {
+            size = Size::new(200 + next() % 400, 100 + next() % 300);
+            ui.set_enabled(check, step % 3000 != 0);
+        }
+        if ui.needs_layout() {
+            ui.layout(size, &theme, &Monospace);
+        }
+        if ui.needs_paint() {
+            let dl = ui.paint(&theme, &Monospace);
+            let mut depth = 0i32;
+            for cmd in &dl {
+                match cmd {
+                    DrawCmd::PushClip(_) => depth += 1,
+                    DrawCmd::PopClip => depth -= 1,
+                    _ => {}
+                }
+                assert!(depth >= 0);
+            }
+            assert_eq!(depth, 0);
+        }
+
+        if let Some(f) = ui.focused() {
+            assert!([input, check, ok, list].contains(&f));
+        }
+        assert!(ui.selected(list).is_none_or(|row| row < 200));
+        let text = ui.text(input).unwrap();
+        assert!(text.chars().count() <= step as usize + 1);
+    }
+
+    let tree = ui.accessibility();
+    assert_eq!(tree.len(), 6 + 200);
+    assert!(tree
+        .iter()
+        .skip(1)
+        .all(|n| n.parent.is_some_and(|p| p < tree.len())));
+}
+
+#[test]
+fn mangled_documents_never_break_a_live_view() {
+    use cosmic_widgets::view::{Model, Value, View};
+
+    struct Empty;
+    impl Model for Empty {
+        fn get(&self, key: &str) -> Option<Value> {
+            Some(match key {
+                "items" => Value::Items(vec!["x".into(); 3]),
+                "flag" => Value::Bool(true),
+                _ => Value::Text(key.into()),
+            })
+        }
+    }
+
+    const DOC: &str = r#"Column(padding: 4, children: [
+        Row(width: fill(2), children: [Label(text: $title), Spacer(width: fill), Button(id: "ok", label: "OK")]),
+        Checkbox(id: "c", label: "Flag", checked: $flag, enabled: false),
+        TextInput(id: "t", placeholder: "…\"q\"", text: $text),
+        List(id: "l", items: $items, selected: 2, height: 40),
+        Icon(name: "x", size: 16, a11y: "X"),
+    ])"#;
+    let alphabet: Vec<char> = "()[]:,$\"\\ \n/abfilx0129_é".chars().collect();
+
+    let mut ui = Ui::new();
+    let root = ui.root();
+    let mut view = View::load(&mut ui, root, DOC, &Empty).unwrap();
+    let mut seed = 0x9e37_79b9u32;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed as usize
+    };
+    let mut failures = 0;
+    for _ in 0..5_000 {
+        let mut doc: Vec<char> = DOC.chars().collect();
+        for _ in 0..1 + next() % 3 {
+            let at = next() % (doc.len() + 1);
+            match next() % 3 {
+                0 if at < doc.len() => {
+                    doc.remove(at);
+                }
+                1 if at < doc.len() => doc[at] = alphabet[next() % alphabet.len()],
+                _ => doc.insert(at, alphabet[next() % alphabet.len()]),
+            }
+        }
+        let doc: String = doc.into_iter().collect();
+        match view.reload(&mut ui, &doc, &Empty) {
+            Ok(()) => {}
+            Err(e) => {
+                failures += 1;
+                assert!(e.line >= 1 && e.line as usize <= doc.lines().count() + 1);
+            }
+        }
+        // Whatever happened, exactly one live view hangs off the root.
+        assert_eq!(ui.children(root), &[view.root()]);
+        ui.layout(Size::new(320, 240), &StockTheme::light(), &Monospace);
+        ui.paint(&StockTheme::light(), &Monospace);
+    }
+    assert!(failures > 0 && failures < 5_000);
+    view.reload(&mut ui, DOC, &Empty).unwrap();
+    assert_eq!(ui.text(view.widget("t").unwrap()), Some("text"));
+}
@@ -1,5 +1,7 @@
 #![no_std]
 
+pub mod rules;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct DevicePort {
     pub name: &'static str,
@@ -0,0 +1,660 @@
+//! udev-style device rules and the normalized hotplug event record.
+//!
+//! device_server evaluates `/etc/exo/device.rules` for every hotplug event
+//! (PCI, USB, block). A rule is one line of comma-separated keys:
+//!
+//! ```text
+//! SUBSYSTEM=="block", REMOVABLE=="1", GROUP="100", MODE="0660", SYMLINK+="disk/by-label/%l", AUTOMOUNT="1"
+//! SUBSYSTEM=="pci", CLASS=="0403*", LOAD="/drivers/exo-hda"
+//! ```
+//!
+//! `KEY=="glob"` and `KEY!="glob"` match (`*` and `?` wildcards); `KEY="v"`
+//! assigns and `SYMLINK+="v"` appends. Every matching rule contributes, in
+//! file order, until one sets `OPTIONS="last_rule"`. Lines that fail to
+//! parse are skipped; [`first_error`] reports them.
+//!
+//! Match keys: `ACTION`, `SUBSYSTEM`, `KERNEL`, `VENDOR`, `PRODUCT`
+//! (4 lowercase hex digits), `CLASS` (6), `REMOVABLE` (`0`/`1`), `LABEL`,
+//! `FSTYPE`. Assignments: `MODE` (octal), `OWNER`, `GROUP` (numeric or
+//! `root`), `SYMLINK` (relative to `/dev`), `LOAD` (driver binary started
+//! with the kernel name as argument), `AUTOMOUNT`, `OPTIONS` (`last_rule`,
+//! `ignore`). `SYMLINK` and `LOAD` values expand `%k` (kernel name), `%n`
+//! (its trailing number), `%l` (filesystem label, made path-safe) and `%%`.
+
+/// Longest kernel name, symlink or driver path carried by an event.
+pub const NAME_MAX: usize = 64;
+pub const MAX_SYMLINKS: usize = 4;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RuleError {
+    Syntax,
+    UnknownKey,
+    BadValue,
+    /// Assignment keys only take `=` (or `+=` for `SYMLINK`).
+    BadOperator,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Action {
+    Add = 0,
+    Remove = 1,
+    Change = 2,
+}
+
+impl Action {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Action::Add => "add",
+            Action::Remove => "remove",
+            Action::Change => "change",
+        }
+    }
+
+    fn from_u8(v: u8) -> Option<Self> {
+        [Action::Add, Action::Remove, Action::Change]
+            .into_iter()
+            .find(|a| *a as u8 == v)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Subsystem {
+    Pci = 0,
+    Usb = 1,
+    Block = 2,
+}
+
+impl Subsystem {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Subsystem::Pci => "pci",
+            Subsystem::Usb => "usb",
+            Subsystem::Block => "block",
+        }
+    }
+
+    fn from_u8(v: u8) -> Option<Self> {
+        [Subsystem::Pci, Subsystem::Usb, Subsystem::Block]
+            .into_iter()
+            .find(|s| *s as u8 == v)
+    }
+}
+
+/// What the kernel (or a bus driver) knows about a device.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Device<'a> {
+    pub action: Action,
+    pub subsystem: Subsystem,
+    /// Node name under `/dev`, e.g. `sdb1`.
+    pub kernel: &'a str,
+    pub vendor: u16,
+    pub product: u16,
+    /// PCI class/subclass/prog-if or USB class/subclass/protocol.
+    pub class: u32,
+    pub removable: bool,
+    pub label: &'a str,
+    pub fs_type: &'a str,
+}
+
+/// Fixed-size path produced by a rule.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Name {
+    buf: [u8; NAME_MAX],
+    len: usize,
+}
+
+impl Name {
+    pub const fn empty() -> Self {
+        Self {
+            buf: [0; NAME_MAX],
+            len: 0,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+
+    fn push(&mut self, bytes: &[u8]) -> Result<(), RuleError> {
+        self.buf
+            .get_mut(self.len..self.len + bytes.len())
+            .ok_or(RuleError::BadValue)?
+            .copy_from_slice(bytes);
+        self.len += bytes.len();
+        Ok(())
+    }
+}
+
+/// Result of running every rule against one device.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Outcome {
+    pub mode: Option<u32>,
+    pub owner: Option<u32>,
+    pub group: Option<u32>,
+    symlinks: [Name; MAX_SYMLINKS],
+    symlink_count: usize,
+    pub load: Option<Name>,
+    pub automount: bool,
+    /// Drop the event entirely: no node changes, nothing published.
+    pub ignore: bool,
+}
+
+impl Default for Outcome {
+    fn default() -> Self {
+        Self {
+            mode: None,
+            owner: None,
+            group: None,
+            symlinks: [Name::empty(); MAX_SYMLINKS],
+            symlink_count: 0,
+            load: None,
+            automount: false,
+            ignore: false,
+        }
+    }
+}
+
+impl Outcome {
+    pub fn symlinks(&self) -> &[Name] {
+        &self.symlinks[..self.symlink_count]
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Op {
+    Match,
+    NoMatch,
+    Assign,
+    Append,
+}
+
+/// Splits the next `KEY<op>"value"` off a rule line.
+fn next_key(rest: &str) -> Result<Option<(&str, Op, &str, &str)>, RuleError> {
+    let rest = rest.trim_start_matches(|c: char| c == ',' || c.is_ascii_whitespace());
+    if rest.is_empty() {
+        return Ok(None);
+    }
+    let key_end = rest.find(['=', '!', '+']).ok_or(RuleError::Syntax)?;
+    let key = rest[..key_end].trim_end();
+    let after = &rest[key_end..];
+    let (op, after) = if let Some(a) = after.strip_prefix("==") {
+        (Op::Match, a)
+    } else if let Some(a) = after.strip_prefix("!=") {
+        (Op::NoMatch, a)
+    } else if let Some(a) = after.strip_prefix("+=") {
+        (Op::Append, a)
+    } else if let Some(a) = after.strip_prefix('=') {
+        (Op::Assign, a)
+    } else {
+        return Err(RuleError::Syntax);
+    };
+    let body = after
+        .trim_start()
+        .strip_prefix('"')
+        .ok_or(RuleError::Syntax)?;
+    let close = body.find('"').ok_or(RuleError::Syntax)?;
+    if key.is_empty() {
+        return Err(RuleError::Syntax);
+    }
+    Ok(Some((key, op, &body[..close], &body[close + 1..])))
+}
+
+/// `*` and `?` wildcards, iterative with single-star backtracking.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let (p, t) = (pattern.as_bytes(), text.as_bytes());
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == b'?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == b'*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == b'*')
+}
+
+fn hex(v: u32, digits: usize, buf: &mut [u8; 8]) -> &str {
+    for (i, b) in buf[..digits].iter_mut().enumerate() {
+        *b = b"0123456789abcdef"[(v >> ((digits - 1 - i) * 4)) as usize & 0xf];
+    }
+    core::str::from_utf8(&buf[..digits]).unwrap_or("")
+}
+
+fn matches(key: &str, pattern: &str, dev: &Device<'_>) -> Result<bool, RuleError> {
+    let mut buf = [0u8; 8];
+    let value = match key {
+        "ACTION" => dev.action.as_str(),
+        "SUBSYSTEM" => dev.subsystem.as_str(),
+        "KERNEL" => dev.kernel,
+        "VENDOR" => hex(dev.vendor as u32, 4, &mut buf),
+        "PRODUCT" => hex(dev.product as u32, 4, &mut buf),
+        "CLASS" => hex(dev.class, 6, &mut buf),
+        "REMOVABLE" => {
+            if dev.removable {
+                "1"
+            } else {
+                "0"
+            }
+        }
+        "LABEL" => dev.label,
+        "FSTYPE" => dev.fs_type,
+        _ => return Err(RuleError::UnknownKey),
+    };
+    Ok(glob_match(pattern, value))
+}
+
+/// Trailing decimal digits of the kernel name (`sdb1` → `1`).
+fn kernel_number(kernel: &str) -> &str {
+    let digits = kernel.bytes().rev().take_while(u8::is_ascii_digit).count();
+    &kernel[kernel.len() - digits..]
+}
+
+/// Expands `%k`/`%n`/`%l`/`%%` into a `/dev`-relative path, refusing
+/// anything that could escape `/dev`.
+fn expand(template: &str, dev: &Device<'_>) -> Result<Name, RuleError> {
+    let mut out = Name::empty();
+    let mut bytes = template.bytes();
+    while let Some(b) = bytes.next() {
+        if b != b'%' {
+            out.push(&[b])?;
+            continue;
+        }
+        match bytes.next() {
+            Some(b'k') => out.push(dev.kernel.as_bytes())?,
+            Some(b'n') => out.push(kernel_number(dev.kernel).as_bytes())?,
+            Some(b'l') => {
+                for c in dev.label.bytes() {
+                    let safe = c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_' | b'.');
+                    out.push(&[if safe { c } else { b'_' }])?;
+                }
+            }
+            Some(b'%') => out.push(b"%")?,
+            _ => return Err(RuleError::BadValue),
+        }
+    }
+    let path = out.as_str();
+    if path.is_empty()
+        || path
+            .split('/')
+            .any(|c| c.is_empty() || c == "." || c == "..")
+    {
+        return Err(RuleError::BadValue);
+    }
+    Ok(out)
+}
+
+fn parse_id(v: &str) -> Result<u32, RuleError> {
+    match v {
+        "root" => Ok(0),
+        _ => v.parse().map_err(|_| RuleError::BadValue),
+    }
+}
+
+/// Parses one line and, if every match key holds, applies its assignments.
+/// Returns whether the rule matched.
+fn apply_line(line: &str, dev: &Device<'_>, out: &mut Outcome) -> Result<bool, RuleError> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(false);
+    }
+    // Matching first, on the whole line, so a half-applied rule never leaks.
+    let mut rest = line;
+    let mut matched = true;
+    while let Some((key, op, value, tail)) = next_key(rest)? {
+        match op {
+            Op::Match => matched &= matches(key, value, dev)?,
+            Op::NoMatch => matched &= !matches(key, value, dev)?,
+            Op::Assign | Op::Append => {}
+        }
+        rest = tail;
+    }
+    let mut staged = *out;
+    let mut rest = line;
+    let mut last = false;
+    while let Some((key, op, value, tail)) = next_key(rest)? {
+        rest = tail;
+        if matches!(op, Op::Match | Op::NoMatch) {
+            continue;
+        }
+        if op == Op::Append && key != "SYMLINK" {
+            return Err(RuleError::BadOperator);
+        }
+        match key {
+            "MODE" => {
+                staged.mode =
+                    Some(u32::from_str_radix(value, 8).map_err(|_| RuleError::BadValue)? & 0o7777)
+            }
+            "OWNER" => staged.owner = Some(parse_id(value)?),
+            "GROUP" => staged.group = Some(parse_id(value)?),
+            "SYMLINK" => {
+                if op == Op::Assign {
+                    staged.symlink_count = 0;
+                }
+                for link in value.split_ascii_whitespace() {
+                    expand(link, &PROBE)?;
+                    // A label that expands to nothing usable only drops this link.
+                    let Ok(name) = expand(link, dev) else {
+                        continue;
+                    };
+                    if staged.symlinks().contains(&name) {
+                        continue;
+                    }
+                    let slot = staged
+                        .symlinks
+                        .get_mut(staged.symlink_count)
+                        .ok_or(RuleError::BadValue)?;
+                    *slot = name;
+                    staged.symlink_count += 1;
+                }
+            }
+            "LOAD" => {
+                staged.load = match value {
+                    "" => None,
+                    _ if !value.starts_with('/') => return Err(RuleError::BadValue),
+                    _ => {
+                        let mut path = Name::empty();
+                        path.push(b"/")?;
+                        let expanded = expand(&value[1..], dev)?;
+                        path.push(expanded.as_str().as_bytes())?;
+                        Some(path)
+                    }
+                }
+            }
+            "AUTOMOUNT" => {
+                staged.automount = match value {
+                    "1" => true,
+                    "0" => false,
+                    _ => return Err(RuleError::BadValue),
+                }
+            }
+            "OPTIONS" => {
+                for opt in value.split(',') {
+                    match opt.trim() {
+                        "last_rule" => last = true,
+                        "ignore" => staged.ignore = true,
+                        _ => return Err(RuleError::BadValue),
+                    }
+                }
+            }
+            "ACTION" | "SUBSYSTEM" | "KERNEL" | "VENDOR" | "PRODUCT" | "CLASS" | "REMOVABLE"
+            | "LABEL" | "FSTYPE" => return Err(RuleError::BadOperator),
+            _ => return Err(RuleError::UnknownKey),
+        }
+    }
+    if matched {
+        *out = staged;
+    }
+    Ok(matched && last)
+}
+
+/// Runs the rules file against `dev`. Invalid lines are skipped.
+pub fn evaluate(rules: &str, dev: &Device<'_>) -> Outcome {
+    let mut out = Outcome::default();
+    for line in rules.lines() {
+        if let Ok(true) = apply_line(line, dev, &mut out) {
+            break;
+        }
+    }
+    out
+}
+
+/// Stand-in device used to check templates independently of real names.
+const PROBE: Device<'static> = Device {
+    action: Action::Add,
+    subsystem: Subsystem::Block,
+    kernel: "sda1",
+    vendor: 0,
+    product: 0,
+    class: 0,
+    removable: false,
+    label: "label",
+    fs_type: "",
+};
+
+/// First invalid line (1-based) of a rules file.
+pub fn first_error(rules: &str) -> Option<(usize, RuleError)> {
+    let mut out = Outcome::default();
+    rules
+        .lines()
+        .enumerate()
+        .find_map(|(i, line)| apply_line(line, &PROBE, &mut out).err().map(|e| (i + 1, e)))
+}
+
+// ── Normalized event record ─────────────────────────────────────────────────
+
+/// Encoded size of a [`DeviceEvent`]; fits one inline IPC payload.
+pub const EVENT_RECORD_BYTES: usize = 176;
+const KERNEL_FIELD: usize = 32;
+const LABEL_FIELD: usize = 48;
+const LINK_FIELD: usize = NAME_MAX;
+const FSTYPE_FIELD: usize = 16;
+const KERNEL_OFF: usize = 16;
+const LABEL_OFF: usize = KERNEL_OFF + KERNEL_FIELD;
+const LINK_OFF: usize = LABEL_OFF + LABEL_FIELD;
+const FSTYPE_OFF: usize = LINK_OFF + LINK_FIELD;
+const _: () = assert!(FSTYPE_OFF + FSTYPE_FIELD == EVENT_RECORD_BYTES);
+
+const FLAG_REMOVABLE: u8 = 1 << 0;
+const FLAG_AUTOMOUNT: u8 = 1 << 1;
+
+/// Event as published to subscribers (file manager, audio, automount), and
+/// as submitted by the kernel or bus drivers with `link` empty.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DeviceEvent<'a> {
+    pub device: Device<'a>,
+    /// The rules asked for the medium to be mounted.
+    pub automount: bool,
+    /// First symlink assigned by the rules, the stable name to show users.
+    pub link: &'a str,
+}
+
+impl<'a> DeviceEvent<'a> {
+    /// Little-endian layout: action, subsystem, flags, pad, vendor, product,
+    /// class, four length bytes, then the fixed-width name fields.
+    pub fn encode(&self, out: &mut [u8]) -> Option<usize> {
+        let out = out.get_mut(..EVENT_RECORD_BYTES)?;
+        out.fill(0);
+        let d = &self.device;
+        out[0] = d.action as u8;
+        out[1] = d.subsystem as u8;
+        out[2] = if d.removable { FLAG_REMOVABLE } else { 0 }
+            | if self.automount { FLAG_AUTOMOUNT } else { 0 };
+        out[4..6].copy_from_slice(&d.vendor.to_le_bytes());
+        out[6..8].copy_from_slice(&d.product.to_le_bytes());
+        out[8..12].copy_from_slice(&d.class.to_le_bytes());
+        let fields = [
+            (d.kernel, KERNEL_OFF, KERNEL_FIELD),
+            (d.label, LABEL_OFF, LABEL_FIELD),
+            (self.link, LINK_OFF, LINK_FIELD),
+            (d.fs_type, FSTYPE_OFF, FSTYPE_FIELD),
+        ];
+        for (i, (text, off, width)) in fields.into_iter().enumerate() {
+            if text.len() > width {
+                return None;
+            }
+            out[12 + i] = text.len() as u8;
+            out[off..off + text.len()].copy_from_slice(text.as_bytes());
+        }
+        Some(EVENT_RECORD_BYTES)
+    }
+
+    pub fn decode(buf: &'a [u8]) -> Option<Self> {
+        let buf = buf.get(..EVENT_RECORD_BYTES)?;
+        let field = |i: usize, off: usize, width: usize| {
+            let len = buf[12 + i] as usize;
+            if len > width {
+                return None;
+            }
+            core::str::from_utf8(&buf[off..off + len]).ok()
+        };
+        let kernel = field(0, KERNEL_OFF, KERNEL_FIELD)?;
+        // Node names never contain a path separator.
+        if kernel.is_empty() || kernel.contains('/') || kernel.starts_with('.') {
+            return None;
+        }
+        Some(Self {
+            device: Device {
+                action: Action::from_u8(buf[0])?,
+                subsystem: Subsystem::from_u8(buf[1])?,
+                kernel,
+                vendor: u16::from_le_bytes([buf[4], buf[5]]),
+                product: u16::from_le_bytes([buf[6], buf[7]]),
+                class: u32::from_le_bytes([buf[8], buf[9], buf[10], buf[11]]),
+                removable: buf[2] & FLAG_REMOVABLE != 0,
+                label: field(1, LABEL_OFF, LABEL_FIELD)?,
+                fs_type: field(3, FSTYPE_OFF, FSTYPE_FIELD)?,
+            },
+            automount: buf[2] & FLAG_AUTOMOUNT != 0,
+            link: field(2, LINK_OFF, LINK_FIELD)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+# Removable media: group 100 may read/write, stable name by label.
+SUBSYSTEM=="block", REMOVABLE=="1", GROUP="100", MODE="0660", SYMLINK+="disk/by-label/%l", AUTOMOUNT="1"
+SUBSYSTEM=="block", KERNEL=="sd*", SYMLINK+="disk/by-kname/%k"
+SUBSYSTEM=="pci", CLASS=="0403*", LOAD="/drivers/exo-hda"
+SUBSYSTEM=="usb", VENDOR=="dead", OPTIONS="ignore"
+KERNEL=="sr?", OPTIONS="last_rule"
+KERNEL=="sr*", MODE="0600"
+"#;
+
+    fn stick(kernel: &'static str, label: &'static str) -> Device<'static> {
+        Device {
+            action: Action::Add,
+            subsystem: Subsystem::Block,
+            kernel,
+            vendor: 0x0781,
+            product: 0x5581,
+            class: 0,
+            removable: true,
+            label,
+            fs_type: "vfat",
+        }
+    }
+
+    #[test]
+    fn globs() {
+        assert!(glob_match("sd*", "sdb1"));
+        assert!(glob_match("sd?1", "sdb1"));
+        assert!(glob_match("*b*1", "sdb1"));
+        assert!(!glob_match("sd?", "sdb1"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("nvme*", "sda"));
+    }
+
+    #[test]
+    fn rules_accumulate_in_order() {
+        let out = evaluate(RULES, &stick("sdb1", "MY STICK/2"));
+        assert_eq!(
+            (out.mode, out.group, out.owner),
+            (Some(0o660), Some(100), None)
+        );
+        assert!(out.automount && !out.ignore);
+        let links: [&str; 2] = [out.symlinks()[0].as_str(), out.symlinks()[1].as_str()];
+        assert_eq!(links, ["disk/by-label/MY_STICK_2", "disk/by-kname/sdb1"]);
+
+        let hda = Device {
+            subsystem: Subsystem::Pci,
+            class: 0x040300,
+            removable: false,
+            ..stick("hda0", "")
+        };
+        let out = evaluate(RULES, &hda);
+        assert_eq!(
+            out.load.map(|p| p.as_str() == "/drivers/exo-hda"),
+            Some(true)
+        );
+        assert!(out.symlinks().is_empty());
+
+        let usb = Device {
+            subsystem: Subsystem::Usb,
+            vendor: 0xdead,
+            ..stick("usb3", "")
+        };
+        assert!(evaluate(RULES, &usb).ignore);
+    }
+
+    #[test]
+    fn last_rule_stops_evaluation() {
+        let out = evaluate(
+            RULES,
+            &Device {
+                removable: false,
+                ..stick("sr0", "")
+            },
+        );
+        assert_eq!(out.mode, None);
+        let out = evaluate(
+            RULES,
+            &Device {
+                removable: false,
+                ..stick("sr10", "")
+            },
+        );
+        assert_eq!(out.mode, Some(0o600));
+    }
+
+    #[test]
+    fn unsafe_expansions_and_bad_lines_are_rejected() {
+        // An empty label would leave an empty path component: only that
+        // link is dropped, the rest of the rule still applies.
+        let out = evaluate(RULES, &stick("sdc", ""));
+        assert_eq!(out.mode, Some(0o660));
+        assert_eq!(out.symlinks().len(), 1);
+        assert_eq!(out.symlinks()[0].as_str(), "disk/by-kname/sdc");
+        // A rule that would escape /dev is skipped as a whole.
+        let out = evaluate(
+            r#"KERNEL=="sd*", MODE="0666", SYMLINK="../etc/x""#,
+            &stick("sdb", ""),
+        );
+        assert_eq!(out.mode, None);
+        assert_eq!(first_error(RULES), None);
+        assert_eq!(
+            first_error("KERNEL==\"a\"\nMODE=\"99\""),
+            Some((2, RuleError::BadValue))
+        );
+        assert_eq!(
+            first_error("COLOR==\"red\""),
+            Some((1, RuleError::UnknownKey))
+        );
+        assert_eq!(
+            first_error("MODE+=\"0600\""),
+            Some((1, RuleError::BadOperator))
+        );
+        assert_eq!(first_error("KERNEL==sda"), Some((1, RuleError::Syntax)));
+    }
+
+    #[test]
+    fn event_record_roundtrips() {
+        let ev = DeviceEvent {
+            device: stick("sdb1", "photos"),
+            automount: true,
+            link: "disk/by-label/photos",
+        };
+        let mut buf = [0u8; EVENT_RECORD_BYTES];
+        assert_eq!(ev.encode(&mut buf), Some(EVENT_RECORD_BYTES));
+        assert_eq!(DeviceEvent::decode(&buf), Some(ev));
+        assert_eq!(ev.encode(&mut [0u8; 100]), None);
+        buf[KERNEL_OFF] = b'.';
+        buf[KERNEL_OFF + 1] = b'.';
+        buf[12] = 2;
+        assert_eq!(DeviceEvent::decode(&buf), None);
+    }
+}
@@ -3,3 +3,40 @@ fn device_ports_stress() {
     assert_eq!(exo_device::DEVICE_PORTS.len(), 1);
     assert_ne!(exo_device::device_stress_signature(100_000), 0);
 }
+
+#[test]
+fn rules_stress_many_events() {
+    use exo_device::rules::{evaluate, Action, Device, DeviceEvent, Subsystem, EVENT_RECORD_BYTES};
+
+    let rules = "SUBSYSTEM==\"block\", KERNEL==\"sd*\", MODE=\"0660\", SYMLINK+=\"disk/by-kname/%k\"\n\
+                 SUBSYSTEM==\"block\", REMOVABLE==\"1\", AUTOMOUNT=\"1\", SYMLINK+=\"disk/by-label/%l\"\n";
+    let names = ["sda", "sdb1", "nvme0n1p2", "sr0"];
+    let mut buf = [0u8; EVENT_RECORD_BYTES];
+    for i in 0..10_000usize {
+        let device = Device {
+            action: if i % 2 == 0 {
+                Action::Add
+            } else {
+                Action::Remove
+            },
+            subsystem: Subsystem::Block,
+            kernel: names[i % names.len()],
+            vendor: i as u16,
+            product: (i >> 16) as u16,
+            class: 0,
+            removable: i % 3 == 0,
+            label: "DATA",
+            fs_type: "exofs",
+        };
+        let out = evaluate(rules, &device);
+        assert_eq!(out.mode.is_some(), device.kernel.starts_with("sd"));
+        assert_eq!(out.automount, device.removable);
+        let event = DeviceEvent {
+            device,
+            automount: out.automount,
+            link: out.symlinks().first().map_or("", |l| l.as_str()),
+        };
+        event.encode(&mut buf).unwrap();
+        assert_eq!(DeviceEvent::decode(&buf), Some(event));
+    }
+}
@@ -0,0 +1,681 @@
+//! Read-only browsing of tar (ustar) and zip images as directory trees.
+//!
+//! The archive is handed over as one byte image (mapped or read by the
+//! caller); nothing is extracted. Directories that only exist implicitly
+//! (`a/b.txt` without an `a/` record) are still listed. Zip members are
+//! readable when stored; compressed members are listed but report
+//! [`ArchiveError::Unsupported`] on read. pax and GNU long-name records are
+//! skipped, names come from the ustar header.
+
+use crate::dir::{DirEntry, EntryKind};
+use crate::location::trim_slashes;
+
+pub const ENTRY_PATH_MAX: usize = 256;
+
+const TAR_BLOCK: usize = 512;
+const ZIP_LOCAL_SIG: &[u8] = b"PK\x03\x04";
+const ZIP_CENTRAL_SIG: &[u8] = b"PK\x01\x02";
+const ZIP_EOCD_SIG: &[u8] = b"PK\x05\x06";
+const ZIP_EOCD_LEN: usize = 22;
+const ZIP_METHOD_STORED: u16 = 0;
+const S_IFMT: u32 = 0o170000;
+const S_IFLNK: u32 = 0o120000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ArchiveFormat {
+    Tar,
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Format suggested by the file name (`.tar`, `.zip`, any case).
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        let ext = |suffix: &[u8]| {
+            name.len() > suffix.len()
+                && name[name.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+        };
+        if ext(b".tar") {
+            Some(ArchiveFormat::Tar)
+        } else if ext(b".zip") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+
+    pub fn detect(image: &[u8]) -> Option<Self> {
+        if image.starts_with(ZIP_LOCAL_SIG) || image.starts_with(ZIP_EOCD_SIG) {
+            return Some(ArchiveFormat::Zip);
+        }
+        let header = image.get(..TAR_BLOCK)?;
+        if &header[257..262] == b"ustar" || tar_checksum_ok(header) {
+            return Some(ArchiveFormat::Tar);
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ArchiveError {
+    NotAnArchive,
+    Truncated,
+    BadChecksum,
+    BadFormat,
+    PathTooLong,
+    Unsupported,
+    NotFound,
+    NotADirectory,
+    IsADirectory,
+}
+
+#[derive(Clone, Copy)]
+pub struct ArchiveEntry<'a> {
+    path: [u8; ENTRY_PATH_MAX],
+    path_len: usize,
+    pub kind: EntryKind,
+    pub size: u64,
+    data: Option<&'a [u8]>,
+}
+
+impl<'a> ArchiveEntry<'a> {
+    /// Member path without leading `./` or `/` and without trailing `/`.
+    pub fn path(&self) -> &[u8] {
+        &self.path[..self.path_len]
+    }
+
+    /// Raw member bytes; `None` when the member is compressed.
+    pub fn data(&self) -> Option<&'a [u8]> {
+        self.data
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Archive<'a> {
+    image: &'a [u8],
+    format: ArchiveFormat,
+}
+
+impl<'a> Archive<'a> {
+    pub fn open(image: &'a [u8]) -> Result<Self, ArchiveError> {
+        let format = ArchiveFormat::detect(image).ok_or(ArchiveError::NotAnArchive)?;
+        Ok(Self { image, format })
+    }
+
+    pub fn format(&self) -> ArchiveFormat {
+        self.format
+    }
+
+    pub fn entries(&self) -> Entries<'a> {
+        let cursor = match self.format {
+            ArchiveFormat::Tar => Cursor::Tar { offset: 0 },
+            ArchiveFormat::Zip => match zip_central_directory(self.image) {
+                Ok((offset, remaining)) => Cursor::Zip { offset, remaining },
+                Err(err) => Cursor::Failed(err),
+            },
+        };
+        Entries {
+            image: self.image,
+            cursor,
+        }
+    }
+
+    pub fn find(&self, path: &[u8]) -> Result<ArchiveEntry<'a>, ArchiveError> {
+        let path = trim_slashes(path);
+        for entry in self.entries() {
+            let entry = entry?;
+            if entry.path() == path {
+                return Ok(entry);
+            }
+        }
+        Err(ArchiveError::NotFound)
+    }
+
+    pub fn read_file(&self, path: &[u8]) -> Result<&'a [u8], ArchiveError> {
+        let entry = self.find(path)?;
+        if entry.kind == EntryKind::Directory {
+            return Err(ArchiveError::IsADirectory);
+        }
+        entry.data().ok_or(ArchiveError::Unsupported)
+    }
+
+    /// Calls `visit` once per direct child of `dir` (`""` = archive root),
+    /// in archive order. Returns the number of children.
+    pub fn list_dir(
+        &self,
+        dir: &[u8],
+        mut visit: impl FnMut(DirEntry<'_>),
+    ) -> Result<usize, ArchiveError> {
+        let dir = trim_slashes(dir);
+        let mut dir_seen = dir.is_empty();
+        let mut count = 0usize;
+        for (idx, entry) in self.entries().enumerate() {
+            let entry = entry?;
+            if entry.path() == dir {
+                if entry.kind != EntryKind::Directory {
+                    return Err(ArchiveError::NotADirectory);
+                }
+                dir_seen = true;
+                continue;
+            }
+            let Some((name, implicit_dir)) = child_of(dir, entry.path()) else {
+                continue;
+            };
+            dir_seen = true;
+            if self.child_seen_before(dir, name, idx)? {
+                continue;
+            }
+            let kind = if implicit_dir {
+                EntryKind::Directory
+            } else {
+                entry.kind
+            };
+            visit(DirEntry {
+                name,
+                kind,
+                size: if kind == EntryKind::Directory {
+                    0
+                } else {
+                    entry.size
+                },
+            });
+            count += 1;
+        }
+        if dir_seen {
+            Ok(count)
+        } else {
+            Err(ArchiveError::NotFound)
+        }
+    }
+
+    fn child_seen_before(
+        &self,
+        dir: &[u8],
+        name: &[u8],
+        before: usize,
+    ) -> Result<bool, ArchiveError> {
+        for entry in self.entries().take(before) {
+            let entry = entry?;
+            if child_of(dir, entry.path()).is_some_and(|(n, _)| n == name) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// `(child name, child is an implicit directory)` if `path` lives under `dir`.
+fn child_of<'p>(dir: &[u8], path: &'p [u8]) -> Option<(&'p [u8], bool)> {
+    let rest = if dir.is_empty() {
+        path
+    } else {
+        path.strip_prefix(dir)?.strip_prefix(b"/")?
+    };
+    if rest.is_empty() {
+        return None;
+    }
+    match rest.iter().position(|&b| b == b'/') {
+        Some(slash) => Some((&rest[..slash], true)),
+        None => Some((rest, false)),
+    }
+}
+
+enum Cursor {
+    Tar { offset: usize },
+    Zip { offset: usize, remaining: usize },
+    Failed(ArchiveError),
+    Done,
+}
+
+pub struct Entries<'a> {
+    image: &'a [u8],
+    cursor: Cursor,
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = Result<ArchiveEntry<'a>, ArchiveError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = match &mut self.cursor {
+            Cursor::Tar { offset } => next_tar(self.image, offset),
+            Cursor::Zip { offset, remaining } => {
+                if *remaining == 0 {
+                    None
+                } else {
+                    *remaining -= 1;
+                    Some(next_zip(self.image, offset))
+                }
+            }
+            Cursor::Failed(err) => Some(Err(*err)),
+            Cursor::Done => None,
+        };
+        if !matches!(item, Some(Ok(_))) {
+            self.cursor = Cursor::Done;
+        }
+        item
+    }
+}
+
+fn entry_from_parts<'a>(
+    prefix: &[u8],
+    name: &[u8],
+    kind: EntryKind,
+    size: u64,
+    data: Option<&'a [u8]>,
+) -> Result<ArchiveEntry<'a>, ArchiveError> {
+    let mut path = [0u8; ENTRY_PATH_MAX];
+    let mut len = 0usize;
+    for part in [prefix, if prefix.is_empty() { b"" } else { b"/" }, name] {
+        let end = len + part.len();
+        if end > ENTRY_PATH_MAX {
+            return Err(ArchiveError::PathTooLong);
+        }
+        path[len..end].copy_from_slice(part);
+        len = end;
+    }
+    let mut start = 0usize;
+    while path[start..len].starts_with(b"./") {
+        start += 2;
+    }
+    while start < len && path[start] == b'/' {
+        start += 1;
+    }
+    let mut end = len;
+    while end > start && path[end - 1] == b'/' {
+        end -= 1;
+    }
+    if &path[start..end] == b"." {
+        end = start;
+    }
+    path.copy_within(start..end, 0);
+    Ok(ArchiveEntry {
+        path,
+        path_len: end - start,
+        kind,
+        size,
+        data,
+    })
+}
+
+fn cstr(field: &[u8]) -> &[u8] {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    &field[..end]
+}
+
+fn parse_octal(field: &[u8]) -> Option<u64> {
+    let mut value: u64 = 0;
+    let mut digits = 0usize;
+    for &b in field {
+        match b {
+            b'0'..=b'7' => {
+                value = value.checked_mul(8)?.checked_add((b - b'0') as u64)?;
+                digits += 1;
+            }
+            b' ' | 0 if digits > 0 => break,
+            b' ' | 0 => {}
+            _ => return None,
+        }
+    }
+    Some(value)
+}
+
+fn tar_checksum_ok(header: &[u8]) -> bool {
+    let Some(expected) = parse_octal(&header[148..156]) else {
+        return false;
+    };
+    let sum: u64 = header
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| {
+            if (148..156).contains(&i) {
+                b' ' as u64
+            } else {
+                b as u64
+            }
+        })
+        .sum();
+    sum == expected
+}
+
+fn next_tar<'a>(
+    image: &'a [u8],
+    offset: &mut usize,
+) -> Option<Result<ArchiveEntry<'a>, ArchiveError>> {
+    loop {
+        let header = image.get(*offset..*offset + TAR_BLOCK)?;
+        if header.iter().all(|&b| b == 0) {
+            return None;
+        }
+        if !tar_checksum_ok(header) {
+            return Some(Err(ArchiveError::BadChecksum));
+        }
+        let Some(size) = parse_octal(&header[124..136]) else {
+            return Some(Err(ArchiveError::BadFormat));
+        };
+        let data_start = *offset + TAR_BLOCK;
+        let Some(data) = usize::try_from(size)
+            .ok()
+            .and_then(|size| image.get(data_start..data_start.checked_add(size)?))
+        else {
+            return Some(Err(ArchiveError::Truncated));
+        };
+        *offset = data_start + data.len().div_ceil(TAR_BLOCK) * TAR_BLOCK;
+        let kind = match header[156] {
+            0 | b'0' | b'7' => EntryKind::File,
+            b'5' => EntryKind::Directory,
+            b'2' => EntryKind::Symlink,
+            _ => continue,
+        };
+        let prefix = if &header[257..262] == b"ustar" {
+            cstr(&header[345..500])
+        } else {
+            &[]
+        };
+        let (size, data) = match kind {
+            EntryKind::File => (size, Some(data)),
+            _ => (0, None),
+        };
+        return Some(entry_from_parts(
+            prefix,
+            cstr(&header[..100]),
+            kind,
+            size,
+            data,
+        ));
+    }
+}
+
+fn le16(image: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(image.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le32(image: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(image.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// `(offset of the first central record, record count)`.
+fn zip_central_directory(image: &[u8]) -> Result<(usize, usize), ArchiveError> {
+    if image.len() < ZIP_EOCD_LEN {
+        return Err(ArchiveError::Truncated);
+    }
+    let last = image.len() - ZIP_EOCD_LEN;
+    let first = last.saturating_sub(u16::MAX as usize);
+    let eocd = (first..=last)
+        .rev()
+        .find(|&at| image[at..].starts_with(ZIP_EOCD_SIG))
+        .ok_or(ArchiveError::BadFormat)?;
+    let count = le16(image, eocd + 10).ok_or(ArchiveError::Truncated)? as usize;
+    let size = le32(image, eocd + 12).ok_or(ArchiveError::Truncated)? as usize;
+    let offset = le32(image, eocd + 16).ok_or(ArchiveError::Truncated)? as usize;
+    if offset.checked_add(size).is_none_or(|end| end > eocd) {
+        return Err(ArchiveError::BadFormat);
+    }
+    Ok((offset, count))
+}
+
+fn next_zip<'a>(image: &'a [u8], offset: &mut usize) -> Result<ArchiveEntry<'a>, ArchiveError> {
+    let at = *offset;
+    if !image
+        .get(at..)
+        .is_some_and(|rest| rest.starts_with(ZIP_CENTRAL_SIG))
+    {
+        return Err(ArchiveError::BadFormat);
+    }
+    let field16 = |rel| le16(image, at + rel).ok_or(ArchiveError::Truncated);
+    let field32 = |rel| le32(image, at + rel).ok_or(ArchiveError::Truncated);
+    let method = field16(10)?;
+    let compressed = field32(20)? as usize;
+    let size = field32(24)? as u64;
+    let name_len = field16(28)? as usize;
+    let extra_len = field16(30)? as usize;
+    let comment_len = field16(32)? as usize;
+    let mode = field32(38)? >> 16;
+    let local = field32(42)? as usize;
+    let name = image
+        .get(at + 46..at + 46 + name_len)
+        .ok_or(ArchiveError::Truncated)?;
+    *offset = at + 46 + name_len + extra_len + comment_len;
+
+    let kind = if name.ends_with(b"/") {
+        EntryKind::Directory
+    } else if mode & S_IFMT == S_IFLNK {
+        EntryKind::Symlink
+    } else {
+        EntryKind::File
+    };
+    let data = if kind == EntryKind::File && method == ZIP_METHOD_STORED {
+        if !image
+            .get(local..)
+            .is_some_and(|rest| rest.starts_with(ZIP_LOCAL_SIG))
+        {
+            return Err(ArchiveError::BadFormat);
+        }
+        let local_name = le16(image, local + 26).ok_or(ArchiveError::Truncated)? as usize;
+        let local_extra = le16(image, local + 28).ok_or(ArchiveError::Truncated)? as usize;
+        let start = local + 30 + local_name + local_extra;
+        Some(
+            image
+                .get(start..start + compressed)
+                .ok_or(ArchiveError::Truncated)?,
+        )
+    } else {
+        None
+    };
+    let size = if kind == EntryKind::Directory {
+        0
+    } else {
+        size
+    };
+    entry_from_parts(&[], name, kind, size, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Image {
+        buf: [u8; 8192],
+        len: usize,
+    }
+
+    impl Image {
+        fn new() -> Self {
+            Self {
+                buf: [0; 8192],
+                len: 0,
+            }
+        }
+
+        fn put(&mut self, bytes: &[u8]) {
+            self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+            self.len += bytes.len();
+        }
+
+        fn bytes(&self) -> &[u8] {
+            &self.buf[..self.len]
+        }
+
+        fn tar(&mut self, name: &[u8], typeflag: u8, data: &[u8]) {
+            let mut h = [0u8; TAR_BLOCK];
+            h[..name.len()].copy_from_slice(name);
+            h[100..107].copy_from_slice(b"0000644");
+            write_octal(&mut h[124..135], data.len() as u64);
+            h[156] = typeflag;
+            h[257..263].copy_from_slice(b"ustar\0");
+            h[263..265].copy_from_slice(b"00");
+            h[148..156].copy_from_slice(b"        ");
+            let sum: u64 = h.iter().map(|&b| b as u64).sum();
+            write_octal(&mut h[148..154], sum);
+            h[154] = 0;
+            self.put(&h);
+            self.put(data);
+            let pad = data.len().div_ceil(TAR_BLOCK) * TAR_BLOCK - data.len();
+            self.len += pad;
+        }
+
+        fn tar_end(&mut self) {
+            self.len += 2 * TAR_BLOCK;
+        }
+    }
+
+    fn write_octal(field: &mut [u8], mut value: u64) {
+        for slot in field.iter_mut().rev() {
+            *slot = b'0' + (value & 7) as u8;
+            value >>= 3;
+        }
+    }
+
+    fn sample_tar() -> Image {
+        let mut img = Image::new();
+        img.tar(b"./src/", b'5', b"");
+        img.tar(b"./src/main.rs", b'0', b"fn main() {}\n");
+        img.tar(b"./src/util/mod.rs", b'0', b"");
+        img.tar(b"./README", b'0', b"exo\n");
+        img.tar(b"./docs/guide.md", b'0', b"# guide\n");
+        img.tar_end();
+        img
+    }
+
+    fn names(
+        archive: &Archive,
+        dir: &[u8],
+    ) -> Result<[([u8; 16], usize, EntryKind); 4], ArchiveError> {
+        let mut out = [([0u8; 16], 0usize, EntryKind::File); 4];
+        let mut i = 0;
+        archive.list_dir(dir, |e| {
+            out[i].0[..e.name.len()].copy_from_slice(e.name);
+            out[i].1 = e.name.len();
+            out[i].2 = e.kind;
+            i += 1;
+        })?;
+        Ok(out)
+    }
+
+    #[test]
+    fn tar_root_lists_explicit_and_implicit_directories() {
+        let img = sample_tar();
+        let archive = Archive::open(img.bytes()).unwrap();
+        assert_eq!(archive.format(), ArchiveFormat::Tar);
+        let listed = names(&archive, b"").unwrap();
+        assert_eq!(&listed[0].0[..listed[0].1], b"src");
+        assert_eq!(listed[0].2, EntryKind::Directory);
+        assert_eq!(&listed[1].0[..listed[1].1], b"README");
+        assert_eq!(&listed[2].0[..listed[2].1], b"docs");
+        assert_eq!(listed[2].2, EntryKind::Directory);
+        assert_eq!(archive.list_dir(b"/", |_| {}), Ok(3));
+    }
+
+    #[test]
+    fn tar_subdirectory_and_file_access() {
+        let img = sample_tar();
+        let archive = Archive::open(img.bytes()).unwrap();
+        assert_eq!(archive.list_dir(b"src/", |_| {}), Ok(2));
+        assert_eq!(
+            archive.read_file(b"/src/main.rs"),
+            Ok(&b"fn main() {}\n"[..])
+        );
+        assert_eq!(archive.read_file(b"src"), Err(ArchiveError::IsADirectory));
+        assert_eq!(
+            archive.list_dir(b"README", |_| {}),
+            Err(ArchiveError::NotADirectory)
+        );
+        assert_eq!(
+            archive.list_dir(b"nope", |_| {}),
+            Err(ArchiveError::NotFound)
+        );
+    }
+
+    #[test]
+    fn tar_checksum_is_verified() {
+        let mut img = sample_tar();
+        img.buf[0] = b'X';
+        let archive = Archive::open(img.bytes()).unwrap();
+        assert!(matches!(
+            archive.entries().next(),
+            Some(Err(ArchiveError::BadChecksum))
+        ));
+        assert_eq!(
+            archive.list_dir(b"", |_| {}),
+            Err(ArchiveError::BadChecksum)
+        );
+    }
+
+    fn sample_zip() -> Image {
+        // (name, data, method)
+        let members: [(&[u8], &[u8], u16); 3] = [
+            (b"photos/", b"", 0),
+            (b"photos/a.txt", b"hello", 0),
+            (b"photos/b.bin", b"\x78\x9c", 8),
+        ];
+        let mut img = Image::new();
+        let mut offsets = [0usize; 3];
+        for (i, (name, data, method)) in members.iter().enumerate() {
+            offsets[i] = img.len;
+            img.put(ZIP_LOCAL_SIG);
+            img.put(&[20, 0, 0, 0]);
+            img.put(&method.to_le_bytes());
+            img.put(&[0; 8]);
+            img.put(&(data.len() as u32).to_le_bytes());
+            img.put(&(data.len() as u32).to_le_bytes());
+            img.put(&(name.len() as u16).to_le_bytes());
+            img.put(&[0, 0]);
+            img.put(name);
+            img.put(data);
+        }
+        let cd_start = img.len;
+        for (i, (name, data, method)) in members.iter().enumerate() {
+            img.put(ZIP_CENTRAL_SIG);
+            img.put(&[20, 3, 20, 0, 0, 0]);
+            img.put(&method.to_le_bytes());
+            img.put(&[0; 8]);
+            img.put(&(data.len() as u32).to_le_bytes());
+            img.put(&(data.len() as u32 * 4).to_le_bytes());
+            img.put(&(name.len() as u16).to_le_bytes());
+            img.put(&[0; 8]);
+            img.put(&((0o100644u32) << 16).to_le_bytes());
+            img.put(&(offsets[i] as u32).to_le_bytes());
+            img.put(name);
+        }
+        let cd_len = img.len - cd_start;
+        img.put(ZIP_EOCD_SIG);
+        img.put(&[0; 4]);
+        img.put(&3u16.to_le_bytes());
+        img.put(&3u16.to_le_bytes());
+        img.put(&(cd_len as u32).to_le_bytes());
+        img.put(&(cd_start as u32).to_le_bytes());
+        img.put(&[0, 0]);
+        img
+    }
+
+    #[test]
+    fn zip_members_are_listed_and_stored_ones_readable() {
+        let img = sample_zip();
+        let archive = Archive::open(img.bytes()).unwrap();
+        assert_eq!(archive.format(), ArchiveFormat::Zip);
+        assert_eq!(archive.list_dir(b"", |_| {}), Ok(1));
+        let mut sizes = [0u64; 2];
+        let mut i = 0;
+        archive
+            .list_dir(b"photos", |e| {
+                sizes[i] = e.size;
+                i += 1;
+            })
+            .unwrap();
+        assert_eq!(sizes, [20, 8]);
+        assert_eq!(archive.read_file(b"photos/a.txt"), Ok(&b"hello"[..]));
+        assert_eq!(
+            archive.read_file(b"photos/b.bin"),
+            Err(ArchiveError::Unsupported)
+        );
+    }
+
+    #[test]
+    fn format_from_name() {
+        assert_eq!(
+            ArchiveFormat::from_name(b"/x/src.TAR"),
+            Some(ArchiveFormat::Tar)
+        );
+        assert_eq!(ArchiveFormat::from_name(b"a.zip"), Some(ArchiveFormat::Zip));
+        assert_eq!(ArchiveFormat::from_name(b".zip"), None);
+        assert_eq!(ArchiveFormat::from_name(b"notes.txt"), None);
+    }
+}
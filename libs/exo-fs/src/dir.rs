@@ -0,0 +1,24 @@
+//! Directory model shared by every backend (local VFS, archives, remotes),
+//! so the file manager renders all of them with the same view.
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EntryKind {
+    File,
+    Directory,
+    Symlink,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DirEntry<'a> {
+    /// Single path component, never contains `/`.
+    pub name: &'a [u8],
+    pub kind: EntryKind,
+    /// Bytes for files, 0 for directories.
+    pub size: u64,
+}
+
+impl DirEntry<'_> {
+    pub fn is_dir(&self) -> bool {
+        self.kind == EntryKind::Directory
+    }
+}
@@ -0,0 +1,493 @@
+//! Disk usage engine behind `du` and the storage breakdown view.
+//!
+//! A [`Breakdown`] lists the scanned root once, keeps one slot per child,
+//! then any number of workers (one per thread, each with its own
+//! [`DuTree`] handle) claim child directories and walk them depth-first.
+//! Totals live in atomics so workers only share `&Breakdown` and a
+//! [`HardlinkSet`]; a file with several links is counted once across all
+//! workers.
+
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::dir::EntryKind;
+
+/// Longest single path component a tree may return.
+pub const NAME_MAX: usize = 255;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DuEntry {
+    pub kind: EntryKind,
+    /// Apparent size (`st_size`).
+    pub size: u64,
+    /// Bytes actually allocated on disk (`st_blocks * 512`).
+    pub allocated: u64,
+    pub dev: u64,
+    pub ino: u64,
+    pub nlink: u64,
+}
+
+/// Directory source walked by the engine, e.g. getdents64 + fstatat on
+/// an open directory fd.
+pub trait DuTree {
+    /// An open directory being listed.
+    type Dir;
+    type Error;
+
+    fn open(&mut self, parent: &Self::Dir, name: &[u8]) -> Result<Self::Dir, Self::Error>;
+
+    /// Next entry of `dir`, its name copied into `name`; `None` once the
+    /// directory is exhausted. `.` and `..` are skipped by the engine.
+    fn next(
+        &mut self,
+        dir: &mut Self::Dir,
+        name: &mut [u8; NAME_MAX],
+    ) -> Result<Option<(usize, DuEntry)>, Self::Error>;
+
+    fn close(&mut self, dir: Self::Dir);
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Usage {
+    pub apparent: u64,
+    pub allocated: u64,
+    pub files: u64,
+    pub dirs: u64,
+    /// Entries that could not be listed or opened.
+    pub errors: u64,
+    /// Directories left unvisited because the walk stack was full.
+    pub truncated: u64,
+}
+
+impl Usage {
+    /// Adds one entry; a hard link already seen by any worker is skipped.
+    fn account<const L: usize>(&mut self, entry: &DuEntry, links: &HardlinkSet<L>) -> bool {
+        if entry.kind != EntryKind::Directory
+            && entry.nlink > 1
+            && !links.insert(entry.dev, entry.ino)
+        {
+            return false;
+        }
+        self.apparent = self.apparent.saturating_add(entry.size);
+        self.allocated = self.allocated.saturating_add(entry.allocated);
+        if entry.kind == EntryKind::Directory {
+            self.dirs += 1;
+        } else {
+            self.files += 1;
+        }
+        true
+    }
+
+    pub fn merge(&mut self, other: &Usage) {
+        self.apparent = self.apparent.saturating_add(other.apparent);
+        self.allocated = self.allocated.saturating_add(other.allocated);
+        self.files += other.files;
+        self.dirs += other.dirs;
+        self.errors += other.errors;
+        self.truncated += other.truncated;
+    }
+}
+
+#[derive(Debug, Default)]
+struct AtomicUsage {
+    apparent: AtomicU64,
+    allocated: AtomicU64,
+    files: AtomicU64,
+    dirs: AtomicU64,
+    errors: AtomicU64,
+    truncated: AtomicU64,
+}
+
+impl AtomicUsage {
+    const fn new() -> Self {
+        Self {
+            apparent: AtomicU64::new(0),
+            allocated: AtomicU64::new(0),
+            files: AtomicU64::new(0),
+            dirs: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            truncated: AtomicU64::new(0),
+        }
+    }
+
+    fn add(&self, usage: &Usage) {
+        self.apparent.fetch_add(usage.apparent, Ordering::Relaxed);
+        self.allocated.fetch_add(usage.allocated, Ordering::Relaxed);
+        self.files.fetch_add(usage.files, Ordering::Relaxed);
+        self.dirs.fetch_add(usage.dirs, Ordering::Relaxed);
+        self.errors.fetch_add(usage.errors, Ordering::Relaxed);
+        self.truncated.fetch_add(usage.truncated, Ordering::Relaxed);
+    }
+
+    fn load(&self) -> Usage {
+        Usage {
+            apparent: self.apparent.load(Ordering::Relaxed),
+            allocated: self.allocated.load(Ordering::Relaxed),
+            files: self.files.load(Ordering::Relaxed),
+            dirs: self.dirs.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            truncated: self.truncated.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Lock-free set of `(dev, ino)` pairs for files with several links.
+/// Pairs are stored as a 64-bit hash: a collision only under-counts one
+/// file. When full, further links are counted again rather than dropped.
+pub struct HardlinkSet<const L: usize> {
+    slots: [AtomicU64; L],
+    overflow: AtomicU64,
+}
+
+impl<const L: usize> HardlinkSet<L> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { AtomicU64::new(0) }; L],
+            overflow: AtomicU64::new(0),
+        }
+    }
+
+    /// `true` the first time a file is seen.
+    pub fn insert(&self, dev: u64, ino: u64) -> bool {
+        let key = (dev.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ ino)
+            .wrapping_mul(0xbf58_476d_1ce4_e5b9)
+            .max(1);
+        let start = (key % L.max(1) as u64) as usize;
+        for probe in 0..L {
+            let slot = &self.slots[(start + probe) % L];
+            match slot.compare_exchange(0, key, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return true,
+                Err(seen) if seen == key => return false,
+                Err(_) => {}
+            }
+        }
+        self.overflow.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    /// Links counted more than once because the set was full.
+    pub fn overflow(&self) -> u64 {
+        self.overflow.load(Ordering::Relaxed)
+    }
+}
+
+impl<const L: usize> Default for HardlinkSet<L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_dot(name: &[u8]) -> bool {
+    name == b"." || name == b".."
+}
+
+/// Walks everything below `dir` (which is closed on return). `stack`
+/// bounds the depth: one open directory per level.
+pub fn scan<T: DuTree, const L: usize>(
+    tree: &mut T,
+    dir: T::Dir,
+    stack: &mut [Option<T::Dir>],
+    links: &HardlinkSet<L>,
+) -> Usage {
+    let mut usage = Usage::default();
+    if stack.is_empty() {
+        usage.truncated += 1;
+        tree.close(dir);
+        return usage;
+    }
+    stack[0] = Some(dir);
+    let mut depth = 1;
+    let mut name = [0u8; NAME_MAX];
+    while depth > 0 {
+        let Some(top) = stack[depth - 1].as_mut() else {
+            depth -= 1;
+            continue;
+        };
+        let (len, entry) = match tree.next(top, &mut name) {
+            Ok(Some(next)) => next,
+            listed => {
+                if listed.is_err() {
+                    usage.errors += 1;
+                }
+                if let Some(done) = stack[depth - 1].take() {
+                    tree.close(done);
+                }
+                depth -= 1;
+                continue;
+            }
+        };
+        let name = &name[..len.min(NAME_MAX)];
+        if is_dot(name) || !usage.account(&entry, links) {
+            continue;
+        }
+        if entry.kind != EntryKind::Directory {
+            continue;
+        }
+        if depth == stack.len() {
+            usage.truncated += 1;
+            continue;
+        }
+        let Some(parent) = stack[depth - 1].as_ref() else {
+            continue;
+        };
+        match tree.open(parent, name) {
+            Ok(child) => {
+                stack[depth] = Some(child);
+                depth += 1;
+            }
+            Err(_) => usage.errors += 1,
+        }
+    }
+    usage
+}
+
+pub struct Slot {
+    name: [u8; NAME_MAX],
+    name_len: usize,
+    kind: EntryKind,
+    usage: AtomicUsage,
+}
+
+impl Slot {
+    const fn empty() -> Self {
+        Slot {
+            name: [0; NAME_MAX],
+            name_len: 0,
+            kind: EntryKind::File,
+            usage: AtomicUsage::new(),
+        }
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name[..self.name_len]
+    }
+
+    pub fn kind(&self) -> EntryKind {
+        self.kind
+    }
+
+    pub fn usage(&self) -> Usage {
+        self.usage.load()
+    }
+}
+
+/// Per-child usage of one directory, filled by parallel workers.
+/// Children beyond `N` are folded into [`Breakdown::other`].
+pub struct Breakdown<const N: usize> {
+    slots: [Slot; N],
+    len: usize,
+    next_job: AtomicUsize,
+    other: AtomicUsage,
+}
+
+impl<const N: usize> Breakdown<N> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { Slot::empty() }; N],
+            len: 0,
+            next_job: AtomicUsize::new(0),
+            other: AtomicUsage::new(),
+        }
+    }
+
+    /// Lists `root` into slots. Files are accounted right away, child
+    /// directories become jobs for [`Breakdown::work`]; directories that
+    /// do not fit in a slot are walked here into `other`.
+    pub fn plan<T: DuTree, const L: usize>(
+        &mut self,
+        tree: &mut T,
+        root: &mut T::Dir,
+        stack: &mut [Option<T::Dir>],
+        links: &HardlinkSet<L>,
+    ) -> Result<(), T::Error> {
+        let mut name = [0u8; NAME_MAX];
+        while let Some((len, entry)) = tree.next(root, &mut name)? {
+            let name = &name[..len.min(NAME_MAX)];
+            let mut usage = Usage::default();
+            if is_dot(name) || !usage.account(&entry, links) {
+                continue;
+            }
+            if self.len < N {
+                let slot = &mut self.slots[self.len];
+                slot.name[..name.len()].copy_from_slice(name);
+                slot.name_len = name.len();
+                slot.kind = entry.kind;
+                slot.usage.add(&usage);
+                self.len += 1;
+                continue;
+            }
+            if entry.kind == EntryKind::Directory {
+                match tree.open(root, name) {
+                    Ok(dir) => usage.merge(&scan(tree, dir, stack, links)),
+                    Err(_) => usage.errors += 1,
+                }
+            }
+            self.other.add(&usage);
+        }
+        Ok(())
+    }
+
+    /// Claims planned directories until none is left. Each worker passes
+    /// its own tree handle and an open handle on the same root.
+    pub fn work<T: DuTree, const L: usize>(
+        &self,
+        tree: &mut T,
+        root: &T::Dir,
+        stack: &mut [Option<T::Dir>],
+        links: &HardlinkSet<L>,
+    ) {
+        loop {
+            let job = self.next_job.fetch_add(1, Ordering::Relaxed);
+            let Some(slot) = self.slots[..self.len].get(job) else {
+                return;
+            };
+            if slot.kind != EntryKind::Directory {
+                continue;
+            }
+            let usage = match tree.open(root, slot.name()) {
+                Ok(dir) => scan(tree, dir, stack, links),
+                Err(_) => Usage {
+                    errors: 1,
+                    ..Usage::default()
+                },
+            };
+            slot.usage.add(&usage);
+        }
+    }
+
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots[..self.len]
+    }
+
+    pub fn other(&self) -> Usage {
+        self.other.load()
+    }
+
+    pub fn total(&self) -> Usage {
+        let mut total = self.other();
+        for slot in self.slots() {
+            total.merge(&slot.usage());
+        }
+        total
+    }
+
+    /// Slot indices from largest to smallest allocated size.
+    pub fn largest_first(&self, order: &mut [usize; N]) -> usize {
+        for (i, idx) in order.iter_mut().enumerate().take(self.len) {
+            *idx = i;
+        }
+        let order = &mut order[..self.len];
+        for i in 1..order.len() {
+            let mut j = i;
+            while j > 0
+                && self.slots[order[j - 1]].usage().allocated
+                    < self.slots[order[j]].usage().allocated
+            {
+                order.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+        self.len
+    }
+}
+
+impl<const N: usize> Default for Breakdown<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(parent, name, kind, size, ino, nlink)`; node 0 is the root.
+    type Node = (usize, &'static [u8], EntryKind, u64, u64, u64);
+
+    const TREE: &[Node] = &[
+        (0, b"docs", EntryKind::Directory, 0, 1, 2),
+        (0, b"big.iso", EntryKind::File, 4096, 2, 1),
+        (0, b"src", EntryKind::Directory, 0, 3, 2),
+        (1, b"a.txt", EntryKind::File, 100, 4, 2),
+        (3, b"a-link.txt", EntryKind::File, 100, 4, 2),
+        (3, b"deep", EntryKind::Directory, 0, 6, 2),
+        (6, b"deeper", EntryKind::Directory, 0, 7, 2),
+        (7, b"leaf", EntryKind::File, 10, 8, 1),
+    ];
+
+    struct Fake;
+
+    /// Directory node (0 = root, else 1-based index into `TREE`) and cursor.
+    struct Cursor {
+        node: usize,
+        pos: usize,
+    }
+
+    impl DuTree for Fake {
+        type Dir = Cursor;
+        type Error = ();
+
+        fn open(&mut self, parent: &Cursor, name: &[u8]) -> Result<Cursor, ()> {
+            let idx = TREE
+                .iter()
+                .position(|e| e.0 == parent.node && e.1 == name)
+                .ok_or(())?;
+            Ok(Cursor {
+                node: idx + 1,
+                pos: 0,
+            })
+        }
+
+        fn next(
+            &mut self,
+            dir: &mut Cursor,
+            name: &mut [u8; NAME_MAX],
+        ) -> Result<Option<(usize, DuEntry)>, ()> {
+            while dir.pos < TREE.len() {
+                let (parent, entry_name, kind, size, ino, nlink) = TREE[dir.pos];
+                dir.pos += 1;
+                if parent == dir.node {
+                    name[..entry_name.len()].copy_from_slice(entry_name);
+                    let entry = DuEntry {
+                        kind,
+                        size,
+                        allocated: size.div_ceil(512) * 512,
+                        dev: 1,
+                        ino,
+                        nlink,
+                    };
+                    return Ok(Some((entry_name.len(), entry)));
+                }
+            }
+            Ok(None)
+        }
+
+        fn close(&mut self, _dir: Cursor) {}
+    }
+
+    #[test]
+    fn breakdown_dedups_links_and_bounds_depth() {
+        let links = HardlinkSet::<16>::new();
+        let mut breakdown = Breakdown::<4>::new();
+        let mut root = Cursor { node: 0, pos: 0 };
+        let mut stack: [Option<Cursor>; 2] = [None, None];
+        breakdown
+            .plan(&mut Fake, &mut root, &mut stack, &links)
+            .unwrap();
+        assert_eq!(breakdown.slots().len(), 3);
+        // Two workers sharing the breakdown; the second finds no job left.
+        breakdown.work(&mut Fake, &root, &mut stack, &links);
+        breakdown.work(&mut Fake, &root, &mut stack, &links);
+
+        let total = breakdown.total();
+        assert_eq!(total.files, 2);
+        assert_eq!(total.apparent, 4096 + 100);
+        // `deeper` is counted but its `leaf` lies beyond a two-level stack.
+        assert_eq!(total.dirs, 4);
+        assert_eq!(total.truncated, 1);
+
+        let mut order = [0usize; 4];
+        let n = breakdown.largest_first(&mut order);
+        assert_eq!(breakdown.slots()[order[0]].name(), b"big.iso");
+        assert_eq!(breakdown.slots()[order[n - 1]].usage().allocated, 0);
+    }
+}
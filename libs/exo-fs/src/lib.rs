@@ -1,5 +1,15 @@
 #![no_std]
 
+pub mod archive;
+pub mod dir;
+pub mod du;
+pub mod location;
+
+pub use archive::{Archive, ArchiveEntry, ArchiveError, ArchiveFormat};
+pub use dir::{DirEntry, EntryKind};
+pub use du::{Breakdown, DuEntry, DuTree, HardlinkSet, Usage};
+pub use location::{Location, LocationError, Remote};
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum FsPortKind {
     BlockFilesystem,
@@ -0,0 +1,269 @@
+//! Virtual locations: the file manager addresses local paths, paths inside
+//! archives and remote hosts with one string syntax.
+//!
+//! | Syntax                                  | Location              |
+//! |-----------------------------------------|-----------------------|
+//! | `/home/user/doc.txt`                    | [`Location::Local`]   |
+//! | `/home/user/src.tar!/include/exo.h`     | [`Location::Archive`] |
+//! | `sftp://user@host:2222/srv/www`         | [`Location::Sftp`]    |
+//!
+//! `sftp://` is served by `exo_ssh::sftp_fs`. There is no SMB client, so
+//! `smb://` is an unknown scheme.
+
+/// Separator between an archive file and the path inside it. Only honoured
+/// after a recognised archive name, so `/tmp/wow!/x` stays a local path.
+pub const ARCHIVE_SEPARATOR: u8 = b'!';
+pub const SFTP_DEFAULT_PORT: u16 = 22;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LocationError {
+    Empty,
+    RelativePath,
+    UnknownScheme,
+    MissingHost,
+    BadPort,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Remote<'a> {
+    pub user: Option<&'a [u8]>,
+    pub host: &'a [u8],
+    pub port: u16,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Location<'a> {
+    Local {
+        path: &'a [u8],
+    },
+    /// `inner` has no leading `/`; empty means the archive root.
+    Archive {
+        archive: &'a [u8],
+        inner: &'a [u8],
+    },
+    Sftp {
+        remote: Remote<'a>,
+        path: &'a [u8],
+    },
+}
+
+impl<'a> Location<'a> {
+    pub fn parse(text: &'a [u8]) -> Result<Self, LocationError> {
+        if text.is_empty() {
+            return Err(LocationError::Empty);
+        }
+        if let Some(rest) = text.strip_prefix(b"sftp://") {
+            let (remote, path) = parse_remote(rest, SFTP_DEFAULT_PORT)?;
+            return Ok(Location::Sftp {
+                remote,
+                path: if path.is_empty() { b"/" } else { path },
+            });
+        }
+        if contains_scheme(text) {
+            return Err(LocationError::UnknownScheme);
+        }
+        if text[0] != b'/' {
+            return Err(LocationError::RelativePath);
+        }
+        match text
+            .iter()
+            .position(|&b| b == ARCHIVE_SEPARATOR)
+            .filter(|&sep| crate::archive::ArchiveFormat::from_name(&text[..sep]).is_some())
+        {
+            Some(sep) => Ok(Location::Archive {
+                archive: &text[..sep],
+                inner: trim_slashes(&text[sep + 1..]),
+            }),
+            None => Ok(Location::Local { path: text }),
+        }
+    }
+
+    pub fn is_remote(&self) -> bool {
+        matches!(self, Location::Sftp { .. })
+    }
+
+    /// Keyring lookup key for remote credentials (`sftp:user@host:22`).
+    /// Returns the length written, or `None` for local locations.
+    pub fn credential_key(&self, out: &mut [u8]) -> Option<usize> {
+        let (scheme, remote): (&[u8], _) = match self {
+            Location::Sftp { remote, .. } => (b"sftp:", remote),
+            _ => return None,
+        };
+        let mut w = Cursor { out, len: 0 };
+        w.put(scheme)?;
+        if let Some(user) = remote.user {
+            w.put(user)?;
+            w.put(b"@")?;
+        }
+        w.put(remote.host)?;
+        w.put(b":")?;
+        let mut digits = [0u8; 5];
+        let mut pos = digits.len();
+        let mut port = remote.port;
+        loop {
+            pos -= 1;
+            digits[pos] = b'0' + (port % 10) as u8;
+            port /= 10;
+            if port == 0 {
+                break;
+            }
+        }
+        w.put(&digits[pos..])?;
+        Some(w.len)
+    }
+}
+
+struct Cursor<'a> {
+    out: &'a mut [u8],
+    len: usize,
+}
+
+impl Cursor<'_> {
+    fn put(&mut self, bytes: &[u8]) -> Option<()> {
+        let end = self.len.checked_add(bytes.len())?;
+        self.out.get_mut(self.len..end)?.copy_from_slice(bytes);
+        self.len = end;
+        Some(())
+    }
+}
+
+fn contains_scheme(text: &[u8]) -> bool {
+    let Some(colon) = text.iter().position(|&b| b == b':') else {
+        return false;
+    };
+    text[colon..].starts_with(b"://")
+        && colon > 0
+        && text[..colon].iter().all(|b| b.is_ascii_alphanumeric())
+}
+
+fn parse_remote(rest: &[u8], default_port: u16) -> Result<(Remote<'_>, &[u8]), LocationError> {
+    let slash = rest.iter().position(|&b| b == b'/').unwrap_or(rest.len());
+    let (authority, path) = rest.split_at(slash);
+    let (user, hostport) = match authority.iter().rposition(|&b| b == b'@') {
+        Some(at) => (Some(&authority[..at]), &authority[at + 1..]),
+        None => (None, authority),
+    };
+    let (host, port) = match hostport.iter().rposition(|&b| b == b':') {
+        Some(colon) => (&hostport[..colon], parse_port(&hostport[colon + 1..])?),
+        None => (hostport, default_port),
+    };
+    if host.is_empty() {
+        return Err(LocationError::MissingHost);
+    }
+    Ok((
+        Remote {
+            user: user.filter(|u| !u.is_empty()),
+            host,
+            port,
+        },
+        path,
+    ))
+}
+
+fn parse_port(text: &[u8]) -> Result<u16, LocationError> {
+    if text.is_empty() {
+        return Err(LocationError::BadPort);
+    }
+    let mut port: u32 = 0;
+    for &b in text {
+        if !b.is_ascii_digit() {
+            return Err(LocationError::BadPort);
+        }
+        port = port * 10 + (b - b'0') as u32;
+        if port > u16::MAX as u32 {
+            return Err(LocationError::BadPort);
+        }
+    }
+    if port == 0 {
+        return Err(LocationError::BadPort);
+    }
+    Ok(port as u16)
+}
+
+pub(crate) fn trim_slashes(mut path: &[u8]) -> &[u8] {
+    while let Some(rest) = path.strip_prefix(b"/") {
+        path = rest;
+    }
+    while let Some(rest) = path.strip_suffix(b"/") {
+        path = rest;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_local_and_archive_paths() {
+        assert_eq!(
+            Location::parse(b"/home/user/doc.txt"),
+            Ok(Location::Local {
+                path: b"/home/user/doc.txt"
+            })
+        );
+        assert_eq!(
+            Location::parse(b"/tmp/src.tar!/include/"),
+            Ok(Location::Archive {
+                archive: b"/tmp/src.tar",
+                inner: b"include"
+            })
+        );
+        assert_eq!(
+            Location::parse(b"/tmp/wow!/x"),
+            Ok(Location::Local {
+                path: b"/tmp/wow!/x"
+            })
+        );
+        assert_eq!(Location::parse(b"docs/a"), Err(LocationError::RelativePath));
+        assert_eq!(
+            Location::parse(b"webdav://x/y"),
+            Err(LocationError::UnknownScheme)
+        );
+    }
+
+    #[test]
+    fn parses_remote_locations() {
+        let sftp = Location::parse(b"sftp://alice@build.lan:2222/srv/www").unwrap();
+        assert_eq!(
+            sftp,
+            Location::Sftp {
+                remote: Remote {
+                    user: Some(b"alice"),
+                    host: b"build.lan",
+                    port: 2222
+                },
+                path: b"/srv/www"
+            }
+        );
+        assert_eq!(
+            Location::parse(b"sftp://nas").unwrap(),
+            Location::Sftp {
+                remote: Remote {
+                    user: None,
+                    host: b"nas",
+                    port: SFTP_DEFAULT_PORT
+                },
+                path: b"/"
+            }
+        );
+        assert_eq!(
+            Location::parse(b"smb://nas/public/photos"),
+            Err(LocationError::UnknownScheme)
+        );
+        assert_eq!(
+            Location::parse(b"sftp://host:99999/"),
+            Err(LocationError::BadPort)
+        );
+    }
+
+    #[test]
+    fn credential_keys_identify_the_remote() {
+        let mut key = [0u8; 64];
+        let loc = Location::parse(b"sftp://alice@build.lan/srv").unwrap();
+        let n = loc.credential_key(&mut key).unwrap();
+        assert_eq!(&key[..n], b"sftp:alice@build.lan:22");
+        let local = Location::parse(b"/srv").unwrap();
+        assert_eq!(local.credential_key(&mut key), None);
+    }
+}
@@ -3,3 +3,18 @@ fn fs_ports_stress() {
     assert_eq!(exo_fs::FS_PORTS.len(), 3);
     assert_ne!(exo_fs::fs_stress_signature(100_000), 0);
 }
+
+#[test]
+fn du_hardlink_set_counts_each_inode_once_across_threads() {
+    static LINKS: exo_fs::HardlinkSet<16_384> = exo_fs::HardlinkSet::new();
+    let first_seen: u64 = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                scope.spawn(|| (0..10_000u64).filter(|&ino| LINKS.insert(1, ino)).count() as u64)
+            })
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).sum()
+    });
+    assert_eq!(first_seen, 10_000);
+    assert_eq!(LINKS.overflow(), 0);
+}
@@ -0,0 +1,621 @@
+//! Shared glyph and icon atlas published by the `font_cache` daemon.
+//!
+//! The daemon rasterizes glyphs and icons once into a shared-memory region
+//! (`exo_shm(2)`, public read-only) and every client — panel, launcher,
+//! terminal, file manager — maps the same pages instead of keeping its own
+//! copy. The region is a header, a table of entries sorted by
+//! `(kind, key, px)` and a pixel heap:
+//!
+//! ```text
+//! 0      header   magic, version, generation, entry count, heap length
+//! 64     entries  MAX_ENTRIES × 16 bytes
+//! 16448  pixels   tightly packed sprites (alpha8 glyphs, RGBA8888 icons
+//!                 and color glyphs)
+//! ```
+//!
+//! The generation works as a seqlock. A rebuild (theme or font change)
+//! first publishes an odd generation, rewrites the region in place, then
+//! publishes the next even one. Readers open a view only on an even
+//! generation, copy what they need, and check [`AtlasView::is_current`]
+//! afterwards; any sprite cached under an older generation is stale.
+
+use core::sync::atomic::{fence, Ordering};
+
+pub const ATLAS_MAGIC: [u8; 4] = *b"EXAT";
+pub const ATLAS_VERSION: u32 = 1;
+/// Size of the region created by the daemon (the `exo_shm` per-region cap).
+pub const ATLAS_BYTES: usize = 256 * 1024;
+pub const HEADER_BYTES: usize = 64;
+pub const ENTRY_BYTES: usize = 16;
+pub const MAX_ENTRIES: usize = 1024;
+pub const PIXELS_OFFSET: usize = HEADER_BYTES + MAX_ENTRIES * ENTRY_BYTES;
+
+const GENERATION_OFFSET: usize = 8;
+const COUNT_OFFSET: usize = 16;
+const PIXELS_LEN_OFFSET: usize = 20;
+
+/// Liveness probe; reply `(region, generation, entries)`.
+pub const FONT_CACHE_MSG_HEARTBEAT: u32 = 0;
+/// Reply `handle` = region id to map read-only, `value0` = region size,
+/// `value1` = current generation.
+pub const FONT_CACHE_MSG_ATTACH: u32 = 1;
+/// Payload: NUL-terminated icon theme name. Rebuilds the atlas; reply
+/// `value1` = new generation.
+pub const FONT_CACHE_MSG_SET_THEME: u32 = 2;
+/// Payload: `u32` little-endian glyph scale (1..=MAX_FONT_SCALE). Rebuilds
+/// the atlas; reply `value1` = new generation.
+pub const FONT_CACHE_MSG_SET_FONT: u32 = 3;
+/// Reply `(rebuilds, glyphs, icons)`.
+pub const FONT_CACHE_MSG_STATS: u32 = 4;
+
+pub const MAX_FONT_SCALE: u32 = 4;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[repr(u8)]
+pub enum Kind {
+    Glyph = 1,
+    Icon = 2,
+    /// RGBA glyph from a color font (emoji), keyed by code point.
+    ColorGlyph = 3,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum PixelFormat {
+    Alpha8 = 1,
+    Rgba8888 = 2,
+}
+
+impl PixelFormat {
+    pub const fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Alpha8 => 1,
+            PixelFormat::Rgba8888 => 4,
+        }
+    }
+
+    fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            1 => Some(PixelFormat::Alpha8),
+            2 => Some(PixelFormat::Rgba8888),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AtlasError {
+    TooSmall,
+    BadMagic,
+    /// A rebuild is in progress (odd generation); retry later.
+    Unstable,
+    Full,
+    Duplicate,
+    BadSprite,
+}
+
+/// One sprite; rows are `width * bytes_per_pixel` bytes, no padding.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Sprite<'a> {
+    pub width: u16,
+    pub height: u16,
+    pub format: PixelFormat,
+    pub pixels: &'a [u8],
+}
+
+/// Icons are keyed by the FNV-1a hash of their name; the writer rejects
+/// a second icon hashing to the same key and size.
+pub fn icon_key(name: &str) -> u32 {
+    name.bytes()
+        .fold(0x811c_9dc5, |h, b| (h ^ b as u32).wrapping_mul(0x0100_0193))
+}
+
+pub const fn is_stable(generation: u64) -> bool {
+    generation & 1 == 0
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    let mut raw = [0u8; 4];
+    raw.copy_from_slice(&bytes[at..at + 4]);
+    u32::from_le_bytes(raw)
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+/// Generation stored in the header, `None` if `atlas` is not an atlas.
+pub fn generation(atlas: &[u8]) -> Option<u64> {
+    if atlas.len() < PIXELS_OFFSET
+        || atlas[..4] != ATLAS_MAGIC
+        || read_u32(atlas, 4) != ATLAS_VERSION
+    {
+        return None;
+    }
+    let mut raw = [0u8; 8];
+    raw.copy_from_slice(&atlas[GENERATION_OFFSET..GENERATION_OFFSET + 8]);
+    let generation = u64::from_le_bytes(raw);
+    fence(Ordering::Acquire);
+    Some(generation)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Entry {
+    kind: u8,
+    format: u8,
+    width: u16,
+    height: u16,
+    px: u16,
+    key: u32,
+    offset: u32,
+}
+
+impl Entry {
+    fn read(bytes: &[u8], index: usize) -> Self {
+        let at = HEADER_BYTES + index * ENTRY_BYTES;
+        Self {
+            kind: bytes[at],
+            format: bytes[at + 1],
+            width: read_u16(bytes, at + 2),
+            height: read_u16(bytes, at + 4),
+            px: read_u16(bytes, at + 6),
+            key: read_u32(bytes, at + 8),
+            offset: read_u32(bytes, at + 12),
+        }
+    }
+
+    fn write(&self, bytes: &mut [u8], index: usize) {
+        let at = HEADER_BYTES + index * ENTRY_BYTES;
+        bytes[at] = self.kind;
+        bytes[at + 1] = self.format;
+        bytes[at + 2..at + 4].copy_from_slice(&self.width.to_le_bytes());
+        bytes[at + 4..at + 6].copy_from_slice(&self.height.to_le_bytes());
+        bytes[at + 6..at + 8].copy_from_slice(&self.px.to_le_bytes());
+        bytes[at + 8..at + 12].copy_from_slice(&self.key.to_le_bytes());
+        bytes[at + 12..at + 16].copy_from_slice(&self.offset.to_le_bytes());
+    }
+
+    fn sort_key(&self) -> (u8, u32, u16) {
+        (self.kind, self.key, self.px)
+    }
+}
+
+/// Read side, opened on a stable generation.
+pub struct AtlasView<'a> {
+    bytes: &'a [u8],
+    generation: u64,
+    count: usize,
+    pixels_len: usize,
+}
+
+impl<'a> AtlasView<'a> {
+    pub fn open(bytes: &'a [u8]) -> Result<Self, AtlasError> {
+        if bytes.len() < PIXELS_OFFSET {
+            return Err(AtlasError::TooSmall);
+        }
+        let generation = generation(bytes).ok_or(AtlasError::BadMagic)?;
+        if !is_stable(generation) {
+            return Err(AtlasError::Unstable);
+        }
+        let count = (read_u32(bytes, COUNT_OFFSET) as usize).min(MAX_ENTRIES);
+        let pixels_len =
+            (read_u32(bytes, PIXELS_LEN_OFFSET) as usize).min(bytes.len() - PIXELS_OFFSET);
+        Ok(Self {
+            bytes,
+            generation,
+            count,
+            pixels_len,
+        })
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// `false` once the daemon has started a rebuild: everything read
+    /// through this view must be dropped.
+    pub fn is_current(&self) -> bool {
+        generation(self.bytes) == Some(self.generation)
+    }
+
+    pub fn glyph(&self, codepoint: u32, px: u16) -> Option<Sprite<'a>> {
+        self.find(Kind::Glyph, codepoint, px)
+    }
+
+    pub fn icon(&self, name: &str, px: u16) -> Option<Sprite<'a>> {
+        self.find(Kind::Icon, icon_key(name), px)
+    }
+
+    /// RGBA glyph, drawn as is instead of tinted with the text color.
+    pub fn color_glyph(&self, codepoint: u32, px: u16) -> Option<Sprite<'a>> {
+        self.find(Kind::ColorGlyph, codepoint, px)
+    }
+
+    /// Alpha8 glyph if there is one, else the color glyph.
+    pub fn any_glyph(&self, codepoint: u32, px: u16) -> Option<Sprite<'a>> {
+        self.glyph(codepoint, px)
+            .or_else(|| self.color_glyph(codepoint, px))
+    }
+
+    fn find(&self, kind: Kind, key: u32, px: u16) -> Option<Sprite<'a>> {
+        let wanted = (kind as u8, key, px);
+        let (mut lo, mut hi) = (0usize, self.count);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let entry = Entry::read(self.bytes, mid);
+            match entry.sort_key().cmp(&wanted) {
+                core::cmp::Ordering::Less => lo = mid + 1,
+                core::cmp::Ordering::Greater => hi = mid,
+                core::cmp::Ordering::Equal => return self.sprite(&entry),
+            }
+        }
+        None
+    }
+
+    /// Bounds come from shared memory: a torn read yields `None`, never a
+    /// slice outside the heap.
+    fn sprite(&self, entry: &Entry) -> Option<Sprite<'a>> {
+        let format = PixelFormat::from_u8(entry.format)?;
+        let len = entry.width as usize * entry.height as usize * format.bytes_per_pixel();
+        let start = entry.offset as usize;
+        let end = start
+            .checked_add(len)
+            .filter(|&end| end <= self.pixels_len)?;
+        Some(Sprite {
+            width: entry.width,
+            height: entry.height,
+            format,
+            pixels: &self.bytes[PIXELS_OFFSET + start..PIXELS_OFFSET + end],
+        })
+    }
+}
+
+/// Write side, used by the daemon for each rebuild.
+pub struct AtlasWriter<'a> {
+    bytes: &'a mut [u8],
+    generation: u64,
+    count: usize,
+    pixels_len: usize,
+}
+
+impl<'a> AtlasWriter<'a> {
+    /// Publishes an odd generation after `previous` before touching the
+    /// content, so readers stop trusting what they copied.
+    pub fn begin(bytes: &'a mut [u8], previous: u64) -> Result<Self, AtlasError> {
+        if bytes.len() < PIXELS_OFFSET {
+            return Err(AtlasError::TooSmall);
+        }
+        let generation = if is_stable(previous) {
+            previous.wrapping_add(1)
+        } else {
+            previous
+        };
+        bytes[GENERATION_OFFSET..GENERATION_OFFSET + 8].copy_from_slice(&generation.to_le_bytes());
+        fence(Ordering::Release);
+        bytes[..4].copy_from_slice(&ATLAS_MAGIC);
+        bytes[4..8].copy_from_slice(&ATLAS_VERSION.to_le_bytes());
+        bytes[COUNT_OFFSET..COUNT_OFFSET + 4].fill(0);
+        bytes[PIXELS_LEN_OFFSET..PIXELS_LEN_OFFSET + 4].fill(0);
+        Ok(Self {
+            bytes,
+            generation,
+            count: 0,
+            pixels_len: 0,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn add_glyph(
+        &mut self,
+        codepoint: u32,
+        px: u16,
+        width: u16,
+        height: u16,
+        alpha: &[u8],
+    ) -> Result<(), AtlasError> {
+        self.add(
+            Kind::Glyph,
+            codepoint,
+            px,
+            width,
+            height,
+            PixelFormat::Alpha8,
+            alpha,
+        )
+    }
+
+    pub fn add_color_glyph(
+        &mut self,
+        codepoint: u32,
+        px: u16,
+        width: u16,
+        height: u16,
+        rgba: &[u8],
+    ) -> Result<(), AtlasError> {
+        self.add(
+            Kind::ColorGlyph,
+            codepoint,
+            px,
+            width,
+            height,
+            PixelFormat::Rgba8888,
+            rgba,
+        )
+    }
+
+    pub fn add_icon(
+        &mut self,
+        name: &str,
+        px: u16,
+        width: u16,
+        height: u16,
+        rgba: &[u8],
+    ) -> Result<(), AtlasError> {
+        self.add(
+            Kind::Icon,
+            icon_key(name),
+            px,
+            width,
+            height,
+            PixelFormat::Rgba8888,
+            rgba,
+        )
+    }
+
+    /// Inserts in sorted position so [`finish`](Self::finish) has nothing
+    /// left to do but publish.
+    #[allow(clippy::too_many_arguments)]
+    fn add(
+        &mut self,
+        kind: Kind,
+        key: u32,
+        px: u16,
+        width: u16,
+        height: u16,
+        format: PixelFormat,
+        pixels: &[u8],
+    ) -> Result<(), AtlasError> {
+        if pixels.len() != width as usize * height as usize * format.bytes_per_pixel() {
+            return Err(AtlasError::BadSprite);
+        }
+        if self.count == MAX_ENTRIES
+            || pixels.len() > self.bytes.len() - PIXELS_OFFSET - self.pixels_len
+        {
+            return Err(AtlasError::Full);
+        }
+        let entry = Entry {
+            kind: kind as u8,
+            format: format as u8,
+            width,
+            height,
+            px,
+            key,
+            offset: self.pixels_len as u32,
+        };
+        let mut at = self.count;
+        while at > 0 {
+            let prev = Entry::read(self.bytes, at - 1);
+            if prev.sort_key() == entry.sort_key() {
+                return Err(AtlasError::Duplicate);
+            }
+            if prev.sort_key() < entry.sort_key() {
+                break;
+            }
+            at -= 1;
+        }
+        let from = HEADER_BYTES + at * ENTRY_BYTES;
+        let to = HEADER_BYTES + self.count * ENTRY_BYTES;
+        self.bytes.copy_within(from..to, from + ENTRY_BYTES);
+        entry.write(self.bytes, at);
+
+        let start = PIXELS_OFFSET + self.pixels_len;
+        self.bytes[start..start + pixels.len()].copy_from_slice(pixels);
+        self.pixels_len += pixels.len();
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Publishes the rebuilt atlas; returns the new (even) generation.
+    pub fn finish(self) -> u64 {
+        self.bytes[COUNT_OFFSET..COUNT_OFFSET + 4]
+            .copy_from_slice(&(self.count as u32).to_le_bytes());
+        self.bytes[PIXELS_LEN_OFFSET..PIXELS_LEN_OFFSET + 4]
+            .copy_from_slice(&(self.pixels_len as u32).to_le_bytes());
+        let generation = self.generation.wrapping_add(1);
+        fence(Ordering::Release);
+        self.bytes[GENERATION_OFFSET..GENERATION_OFFSET + 8]
+            .copy_from_slice(&generation.to_le_bytes());
+        generation
+    }
+}
+
+/// Expands a 1-bit-per-pixel glyph (MSB first, one byte per row, as in the
+/// boot console font) to alpha8 at an integer `scale`. Returns the number
+/// of bytes written, `0` if `out` is too small.
+pub fn rasterize_bitmap_glyph(rows: &[u8], width: usize, scale: usize, out: &mut [u8]) -> usize {
+    let (w, h) = (width * scale, rows.len() * scale);
+    if width > 8 || scale == 0 || out.len() < w * h {
+        return 0;
+    }
+    for (y, line) in out[..w * h].chunks_exact_mut(w).enumerate() {
+        let bits = rows[y / scale];
+        for (x, px) in line.iter_mut().enumerate() {
+            *px = if bits & (0x80 >> (x / scale)) != 0 {
+                0xff
+            } else {
+                0
+            };
+        }
+    }
+    w * h
+}
+
+/// Parses a farbfeld image (`"farbfeld"`, big-endian `u32` width and
+/// height, then 16-bit RGBA) and returns `(width, height, pixels)`.
+pub fn parse_farbfeld(bytes: &[u8]) -> Option<(u16, u16, &[u8])> {
+    if bytes.len() < 16 || &bytes[..8] != b"farbfeld" {
+        return None;
+    }
+    let width = u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+    let height = u32::from_be_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
+    let (width, height) = (u16::try_from(width).ok()?, u16::try_from(height).ok()?);
+    let len = width as usize * height as usize * 8;
+    let pixels = bytes.get(16..16 + len)?;
+    Some((width, height, pixels))
+}
+
+/// Keeps the high byte of each farbfeld channel. Returns the number of
+/// bytes written to `out`, `0` if it is too small.
+pub fn farbfeld_to_rgba8(pixels: &[u8], out: &mut [u8]) -> usize {
+    let len = pixels.len() / 2;
+    if out.len() < len {
+        return 0;
+    }
+    for (dst, src) in out.iter_mut().zip(pixels.chunks_exact(2)) {
+        *dst = src[0];
+    }
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL: usize = PIXELS_OFFSET + 4096;
+
+    #[test]
+    fn lookup_finds_glyphs_and_icons_in_any_insertion_order() {
+        let mut buf = [0u8; SMALL];
+        let mut w = AtlasWriter::begin(&mut buf, 0).unwrap();
+        w.add_icon("folder", 16, 2, 1, &[1, 2, 3, 4, 5, 6, 7, 8])
+            .unwrap();
+        w.add_glyph(b'b' as u32, 16, 2, 2, &[9; 4]).unwrap();
+        w.add_glyph(b'a' as u32, 16, 1, 2, &[7, 8]).unwrap();
+        w.add_glyph(b'a' as u32, 32, 1, 1, &[6]).unwrap();
+        assert_eq!(w.finish(), 2);
+
+        let view = AtlasView::open(&buf).unwrap();
+        assert_eq!(view.len(), 4);
+        assert_eq!(view.glyph(b'a' as u32, 16).unwrap().pixels, &[7, 8]);
+        assert_eq!(view.glyph(b'a' as u32, 32).unwrap().pixels, &[6]);
+        assert_eq!(view.glyph(b'b' as u32, 16).unwrap().pixels, &[9; 4]);
+        let icon = view.icon("folder", 16).unwrap();
+        assert_eq!((icon.width, icon.format), (2, PixelFormat::Rgba8888));
+        assert_eq!(icon.pixels, &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(view.icon("folder", 32), None);
+        assert_eq!(view.glyph(b'c' as u32, 16), None);
+    }
+
+    #[test]
+    fn color_glyphs_are_a_separate_kind() {
+        let mut buf = [0u8; SMALL];
+        let mut w = AtlasWriter::begin(&mut buf, 0).unwrap();
+        w.add_color_glyph(0x1f600, 16, 1, 1, &[1, 2, 3, 4]).unwrap();
+        w.add_glyph(0x1f600, 16, 1, 1, &[9]).unwrap();
+        w.add_color_glyph(0x1f601, 16, 1, 1, &[5, 6, 7, 8]).unwrap();
+        assert_eq!(
+            w.add_color_glyph(0x1f601, 16, 1, 1, &[0; 4]),
+            Err(AtlasError::Duplicate)
+        );
+        w.finish();
+
+        let view = AtlasView::open(&buf).unwrap();
+        let color = view.color_glyph(0x1f600, 16).unwrap();
+        assert_eq!(
+            (color.format, color.pixels),
+            (PixelFormat::Rgba8888, &[1, 2, 3, 4][..])
+        );
+        assert_eq!(view.any_glyph(0x1f600, 16).unwrap().pixels, &[9]);
+        assert_eq!(view.any_glyph(0x1f601, 16).unwrap().pixels, &[5, 6, 7, 8]);
+        assert_eq!(view.glyph(0x1f601, 16), None);
+    }
+
+    #[test]
+    fn rebuild_bumps_generation_through_an_odd_phase() {
+        let mut buf = [0u8; SMALL];
+        let mut w = AtlasWriter::begin(&mut buf, 0).unwrap();
+        w.add_glyph(1, 16, 1, 1, &[1]).unwrap();
+        let first = w.finish();
+        let before = buf;
+        assert!(AtlasView::open(&before).unwrap().is_current());
+
+        let w = AtlasWriter::begin(&mut buf, first).unwrap();
+        assert_eq!(AtlasView::open(w.bytes).err(), Some(AtlasError::Unstable));
+        let second = w.finish();
+        assert_eq!(second, first + 2);
+        assert!(AtlasView::open(&buf).unwrap().is_empty());
+        // Sprites copied under `first` are stale.
+        assert_ne!(generation(&buf), generation(&before));
+    }
+
+    #[test]
+    fn writer_rejects_duplicates_bad_sizes_and_overflow() {
+        let mut buf = [0u8; SMALL];
+        let mut w = AtlasWriter::begin(&mut buf, 0).unwrap();
+        w.add_glyph(1, 16, 1, 1, &[1]).unwrap();
+        assert_eq!(w.add_glyph(1, 16, 1, 1, &[2]), Err(AtlasError::Duplicate));
+        assert_eq!(
+            w.add_glyph(2, 16, 2, 2, &[0; 3]),
+            Err(AtlasError::BadSprite)
+        );
+        assert_eq!(
+            w.add_glyph(3, 16, 64, 64, &[0; 4096]),
+            Err(AtlasError::Full)
+        );
+        assert_eq!(w.len(), 1);
+    }
+
+    #[test]
+    fn torn_entries_never_escape_the_heap() {
+        let mut buf = [0u8; SMALL];
+        let mut w = AtlasWriter::begin(&mut buf, 0).unwrap();
+        w.add_glyph(1, 16, 1, 1, &[1]).unwrap();
+        w.finish();
+        // Corrupt the offset of the only entry.
+        buf[HEADER_BYTES + 12..HEADER_BYTES + 16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(AtlasView::open(&buf).unwrap().glyph(1, 16), None);
+        assert_eq!(
+            AtlasView::open(&buf[..64]).err(),
+            Some(AtlasError::TooSmall)
+        );
+    }
+
+    #[test]
+    fn bitmap_glyphs_scale_by_pixel_replication() {
+        let mut out = [0u8; 8];
+        // One row `10......`, width 2, scale 2.
+        assert_eq!(rasterize_bitmap_glyph(&[0x80], 2, 2, &mut out), 8);
+        assert_eq!(&out[..8], &[0xff, 0xff, 0, 0, 0xff, 0xff, 0, 0]);
+        assert_eq!(rasterize_bitmap_glyph(&[0x80], 2, 3, &mut out), 0);
+    }
+
+    #[test]
+    fn farbfeld_keeps_high_bytes() {
+        let mut image = [0u8; 24];
+        image[..8].copy_from_slice(b"farbfeld");
+        image[11] = 1;
+        image[15] = 1;
+        image[16..24].copy_from_slice(&[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0]);
+        let (w, h, pixels) = parse_farbfeld(&image).unwrap();
+        assert_eq!((w, h), (1, 1));
+        let mut out = [0u8; 4];
+        assert_eq!(farbfeld_to_rgba8(pixels, &mut out), 4);
+        assert_eq!(out, [0x12, 0x56, 0x9a, 0xde]);
+        assert_eq!(parse_farbfeld(&image[..20]), None);
+    }
+}
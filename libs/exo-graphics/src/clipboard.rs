@@ -0,0 +1,622 @@
+//! Clipboard selection with several representations, following the
+//! `wl_data_device` selection model of [`dnd`](crate::dnd).
+//!
+//! The holder of the selection keeps a copy of every representation the
+//! source offered, so a paste still works after the source exits, and
+//! derives the usual missing ones on request:
+//!
+//! - `text/plain` from `text/html` ([`html_to_text`]) or from a
+//!   `text/uri-list` (one decoded path per line);
+//! - `text/html` from `text/plain` ([`text_to_html`]);
+//! - `text/uri-list` from `text/plain` when every line is an absolute path.
+//!
+//! `image/png` is stored as is, after a signature check. Pasting sides
+//! pick the first representation they understand with
+//! [`Clipboard::pick`]: a file manager asks for
+//! `[UriList, Text]`, the terminal for `[UriList, Text]` and quotes the
+//! paths with [`dnd::paste_paths`](crate::dnd::paste_paths), a rich editor
+//! for `[Html, Png, Text]`.
+
+use crate::dnd::{self, Writer, MIME_TEXT, MIME_URI_LIST};
+
+pub const MIME_HTML: &[u8] = b"text/html";
+pub const MIME_PNG: &[u8] = b"image/png";
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Target {
+    Text = 0,
+    Html = 1,
+    Png = 2,
+    UriList = 3,
+}
+
+pub const TARGETS: usize = 4;
+
+impl Target {
+    pub const ALL: [Target; TARGETS] = [Target::Text, Target::Html, Target::Png, Target::UriList];
+
+    /// MIME type advertised in offers.
+    pub fn mime(self) -> &'static [u8] {
+        match self {
+            Target::Text => MIME_TEXT,
+            Target::Html => MIME_HTML,
+            Target::Png => MIME_PNG,
+            Target::UriList => MIME_URI_LIST,
+        }
+    }
+
+    /// Also accepts the legacy plain text names X11 clients offer; MIME
+    /// parameters other than the UTF-8 charset are not understood.
+    pub fn from_mime(mime: &[u8]) -> Option<Self> {
+        match mime {
+            b"text/plain;charset=utf-8" | b"text/plain" | b"UTF8_STRING" | b"STRING" | b"TEXT" => {
+                Some(Target::Text)
+            }
+            b"text/html" | b"text/html;charset=utf-8" => Some(Target::Html),
+            b"image/png" => Some(Target::Png),
+            b"text/uri-list" => Some(Target::UriList),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClipboardError {
+    /// The representations do not fit in the store.
+    TooLarge,
+    /// `image/png` data without a PNG header.
+    BadImage,
+    /// Not offered and not derivable from what was offered.
+    Unavailable,
+    /// The output buffer is too small.
+    ShortBuffer,
+}
+
+/// `true` for a PNG stream starting with its `IHDR` chunk.
+pub fn is_png(bytes: &[u8]) -> bool {
+    png_size(bytes).is_some()
+}
+
+/// Width and height from the `IHDR` chunk.
+pub fn png_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    let header = bytes.get(..24)?;
+    if header[..8] != PNG_SIGNATURE || &header[12..16] != b"IHDR" {
+        return None;
+    }
+    let be = |at: usize| {
+        u32::from_be_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]])
+    };
+    Some((be(16), be(20)))
+}
+
+/// Elements whose start and end break the line; the value is the number
+/// of line breaks (2 leaves a blank line).
+fn block_breaks(name: &[u8]) -> u8 {
+    match name {
+        b"p" | b"h1" | b"h2" | b"h3" | b"h4" | b"h5" | b"h6" | b"pre" | b"blockquote"
+        | b"table" => 2,
+        b"div" | b"li" | b"tr" | b"ul" | b"ol" | b"dl" | b"dt" | b"dd" | b"hr" | b"section"
+        | b"article" | b"header" | b"footer" => 1,
+        _ => 0,
+    }
+}
+
+/// Character of a named or numeric entity (`amp`, `#233`, `#xe9`).
+fn entity(name: &[u8]) -> Option<char> {
+    if let Some(num) = name.strip_prefix(b"#") {
+        let (digits, radix) = match num {
+            [b'x' | b'X', hex @ ..] => (hex, 16),
+            _ => (num, 10),
+        };
+        let digits = core::str::from_utf8(digits).ok()?;
+        return char::from_u32(u32::from_str_radix(digits, radix).ok()?);
+    }
+    Some(match name {
+        b"amp" => '&',
+        b"lt" => '<',
+        b"gt" => '>',
+        b"quot" => '"',
+        b"apos" => '\'',
+        b"nbsp" => ' ',
+        b"copy" => '©',
+        b"reg" => '®',
+        b"ndash" => '–',
+        b"mdash" => '—',
+        b"hellip" => '…',
+        b"laquo" => '«',
+        b"raquo" => '»',
+        b"euro" => '€',
+        _ => return None,
+    })
+}
+
+/// Plain text writer: collapses whitespace and defers line breaks until
+/// the next visible character, so blocks never leave leading or trailing
+/// blank lines.
+struct TextWriter<'a> {
+    w: Writer<'a>,
+    breaks: u8,
+    space: bool,
+}
+
+impl TextWriter<'_> {
+    fn block(&mut self, breaks: u8) {
+        self.breaks = self.breaks.max(breaks);
+        self.space = false;
+    }
+
+    fn text(&mut self, bytes: &[u8], verbatim: bool) -> Option<()> {
+        for &b in bytes {
+            if !verbatim && b.is_ascii_whitespace() {
+                self.space = true;
+                continue;
+            }
+            if self.w.len > 0 {
+                for _ in 0..self.breaks {
+                    self.w.put(b"\n")?;
+                }
+                if self.breaks == 0 && self.space {
+                    self.w.put(b" ")?;
+                }
+            }
+            self.breaks = 0;
+            self.space = false;
+            self.w.put(&[b])?;
+        }
+        Some(())
+    }
+}
+
+/// Converts an HTML fragment to the text a user expects to paste: tags
+/// dropped, entities decoded, whitespace collapsed except in `<pre>`,
+/// blocks on their own lines, list items prefixed with `- `, and
+/// scripts, styles and comments removed. Returns the length written.
+pub fn html_to_text(html: &[u8], out: &mut [u8]) -> Option<usize> {
+    let mut t = TextWriter {
+        w: Writer { out, len: 0 },
+        breaks: 0,
+        space: false,
+    };
+    let mut pre = 0u32;
+    let mut i = 0;
+    while i < html.len() {
+        let rest = &html[i..];
+        if rest.starts_with(b"<!--") {
+            i += rest
+                .windows(3)
+                .position(|w| w == b"-->")
+                .map_or(rest.len(), |at| at + 3);
+            continue;
+        }
+        let tag_start = rest
+            .get(1)
+            .is_some_and(|&b| b.is_ascii_alphabetic() || b == b'/' || b == b'!');
+        if rest[0] == b'<' && tag_start {
+            // End of the tag, skipping quoted attribute values.
+            let mut quote = None;
+            let end = rest
+                .iter()
+                .enumerate()
+                .skip(1)
+                .find(|&(_, &b)| match quote {
+                    Some(q) if b == q => {
+                        quote = None;
+                        false
+                    }
+                    Some(_) => false,
+                    None if b == b'"' || b == b'\'' => {
+                        quote = Some(b);
+                        false
+                    }
+                    None => b == b'>',
+                })
+                .map_or(rest.len(), |(at, _)| at + 1);
+            let tag = &rest[1..end.saturating_sub(1).max(1)];
+            let closing = tag.first() == Some(&b'/');
+            let name = tag.strip_prefix(b"/").unwrap_or(tag);
+            let name_len = name
+                .iter()
+                .position(|b| !b.is_ascii_alphanumeric())
+                .unwrap_or(name.len());
+            let mut lower = [0u8; 12];
+            let name = &name[..name_len.min(lower.len())];
+            lower[..name.len()].copy_from_slice(name);
+            lower[..name.len()].make_ascii_lowercase();
+            let name = &lower[..name.len()];
+            i += end;
+
+            match name {
+                b"script" | b"style" if !closing => {
+                    let mut close = [0u8; 8];
+                    close[0] = b'<';
+                    close[1] = b'/';
+                    close[2..2 + name.len()].copy_from_slice(name);
+                    let close = &close[..2 + name.len()];
+                    let skip = html[i..]
+                        .windows(close.len())
+                        .position(|w| w.eq_ignore_ascii_case(close))
+                        .unwrap_or(html.len() - i);
+                    i += skip;
+                }
+                b"br" => {
+                    t.breaks = t.breaks.saturating_add(1);
+                    t.space = false;
+                }
+                b"pre" if closing => pre = pre.saturating_sub(1),
+                b"pre" => pre += 1,
+                _ => {}
+            }
+            if name == b"pre" || block_breaks(name) > 0 {
+                t.block(block_breaks(name));
+            }
+            if name == b"li" && !closing {
+                t.text(b"-", false)?;
+                t.space = true;
+            }
+            if (name == b"td" || name == b"th") && closing {
+                t.space = true;
+            }
+            continue;
+        }
+        if rest[0] == b'&' {
+            let name_end = rest.iter().take(12).position(|&b| b == b';');
+            if let Some(ch) = name_end.and_then(|end| entity(&rest[1..end])) {
+                let mut buf = [0u8; 4];
+                t.text(ch.encode_utf8(&mut buf).as_bytes(), true)?;
+                i += name_end.unwrap_or(0) + 1;
+                continue;
+            }
+        }
+        let run = rest
+            .iter()
+            .position(|&b| b == b'<' || b == b'&')
+            .unwrap_or(rest.len())
+            .max(1);
+        t.text(&rest[..run], pre > 0)?;
+        i += run;
+    }
+    Some(t.w.len)
+}
+
+/// Escapes text for `text/html`: markup characters become entities and
+/// line breaks `<br>`. Returns the length written.
+pub fn text_to_html(text: &[u8], out: &mut [u8]) -> Option<usize> {
+    let mut w = Writer { out, len: 0 };
+    for &b in text {
+        match b {
+            b'&' => w.put(b"&amp;")?,
+            b'<' => w.put(b"&lt;")?,
+            b'>' => w.put(b"&gt;")?,
+            b'"' => w.put(b"&quot;")?,
+            b'\n' => w.put(b"<br>\n")?,
+            b'\r' => {}
+            _ => w.put(&[b])?,
+        }
+    }
+    Some(w.len)
+}
+
+/// One line per URI: local files as decoded paths, other URIs as is.
+pub fn uri_list_to_text(list: &[u8], out: &mut [u8]) -> Option<usize> {
+    let mut path = [0u8; 512];
+    let mut w = Writer { out, len: 0 };
+    for (i, uri) in dnd::uris(list).enumerate() {
+        if i > 0 {
+            w.put(b"\n")?;
+        }
+        match dnd::file_uri_path(uri, &mut path) {
+            Some(n) => w.put(&path[..n])?,
+            None => w.put(uri)?,
+        }
+    }
+    Some(w.len)
+}
+
+fn text_lines(text: &[u8]) -> impl Iterator<Item = &[u8]> {
+    text.split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(|line| !line.is_empty())
+}
+
+/// `text/uri-list` for text made only of absolute paths, one per line, as
+/// copied from a terminal; `None` for any other text.
+pub fn text_to_uri_list(text: &[u8], out: &mut [u8]) -> Option<usize> {
+    let mut len = 0;
+    for line in text_lines(text) {
+        len += dnd::encode_uri_list(&[line], out.get_mut(len..)?)?;
+    }
+    (len > 0).then_some(len)
+}
+
+fn is_path_list(text: &[u8]) -> bool {
+    let mut lines = text_lines(text).peekable();
+    lines.peek().is_some() && lines.all(|line| line.first() == Some(&b'/'))
+}
+
+/// The current selection; `BYTES` bounds all its representations together.
+pub struct Clipboard<const BYTES: usize> {
+    data: [u8; BYTES],
+    /// Offset and length per [`Target`], `None` if not offered.
+    entries: [Option<(usize, usize)>; TARGETS],
+    serial: u32,
+}
+
+impl<const BYTES: usize> Default for Clipboard<BYTES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BYTES: usize> Clipboard<BYTES> {
+    pub const fn new() -> Self {
+        Self {
+            data: [0; BYTES],
+            entries: [None; TARGETS],
+            serial: 0,
+        }
+    }
+
+    /// Bumped by every [`set`](Self::set) and [`clear`](Self::clear), so
+    /// clients can tell their offer is stale.
+    pub fn serial(&self) -> u32 {
+        self.serial
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(Option::is_none)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries = [None; TARGETS];
+        self.serial = self.serial.wrapping_add(1);
+    }
+
+    /// Replaces the selection with `(mime, data)` representations. Unknown
+    /// MIME types are skipped and the first of duplicates wins; on error
+    /// the previous selection is kept.
+    pub fn set(&mut self, offers: &[(&[u8], &[u8])]) -> Result<(), ClipboardError> {
+        let mut entries = [None; TARGETS];
+        let mut len = 0;
+        for &(mime, data) in offers {
+            let Some(target) = Target::from_mime(mime) else {
+                continue;
+            };
+            if entries[target as usize].is_some() {
+                continue;
+            }
+            if target == Target::Png && !is_png(data) {
+                return Err(ClipboardError::BadImage);
+            }
+            if data.len() > BYTES - len {
+                return Err(ClipboardError::TooLarge);
+            }
+            entries[target as usize] = Some((len, data.len()));
+            len += data.len();
+        }
+        let mut written = [false; TARGETS];
+        for &(mime, data) in offers {
+            let Some(target) = Target::from_mime(mime) else {
+                continue;
+            };
+            if !core::mem::replace(&mut written[target as usize], true) {
+                if let Some((at, n)) = entries[target as usize] {
+                    self.data[at..at + n].copy_from_slice(data);
+                }
+            }
+        }
+        self.entries = entries;
+        self.serial = self.serial.wrapping_add(1);
+        Ok(())
+    }
+
+    fn stored(&self, target: Target) -> Option<&[u8]> {
+        self.entries[target as usize].map(|(at, n)| &self.data[at..at + n])
+    }
+
+    /// `true` if `target` was offered or can be derived.
+    pub fn has(&self, target: Target) -> bool {
+        if self.stored(target).is_some() {
+            return true;
+        }
+        match target {
+            Target::Text => self
+                .stored(Target::Html)
+                .or(self.stored(Target::UriList))
+                .is_some(),
+            Target::Html => self.stored(Target::Text).is_some(),
+            Target::UriList => self.stored(Target::Text).is_some_and(is_path_list),
+            Target::Png => false,
+        }
+    }
+
+    /// Targets to advertise in the offer.
+    pub fn targets(&self) -> impl Iterator<Item = Target> + '_ {
+        Target::ALL.into_iter().filter(|&t| self.has(t))
+    }
+
+    /// First of `preferred` the source offered, else the first that can be
+    /// derived: an image with a text caption pastes as the image even in
+    /// an editor preferring HTML.
+    pub fn pick(&self, preferred: &[Target]) -> Option<Target> {
+        let mut preferred = preferred.iter().copied();
+        preferred
+            .clone()
+            .find(|&t| self.stored(t).is_some())
+            .or_else(|| preferred.find(|&t| self.has(t)))
+    }
+
+    /// Copies or derives `target` into `out`; returns the length written.
+    pub fn get(&self, target: Target, out: &mut [u8]) -> Result<usize, ClipboardError> {
+        if let Some(data) = self.stored(target) {
+            let out = out
+                .get_mut(..data.len())
+                .ok_or(ClipboardError::ShortBuffer)?;
+            out.copy_from_slice(data);
+            return Ok(data.len());
+        }
+        if !self.has(target) {
+            return Err(ClipboardError::Unavailable);
+        }
+        let converted = match target {
+            Target::Text => match self.stored(Target::Html) {
+                Some(html) => html_to_text(html, out),
+                None => uri_list_to_text(self.stored(Target::UriList).unwrap_or(&[]), out),
+            },
+            Target::Html => text_to_html(self.stored(Target::Text).unwrap_or(&[]), out),
+            Target::UriList => text_to_uri_list(self.stored(Target::Text).unwrap_or(&[]), out),
+            Target::Png => None,
+        };
+        converted.ok_or(ClipboardError::ShortBuffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(html: &str) -> ([u8; 256], usize) {
+        let mut out = [0u8; 256];
+        let n = html_to_text(html.as_bytes(), &mut out).unwrap();
+        (out, n)
+    }
+
+    fn assert_text(html: &str, expected: &str) {
+        let (out, n) = text(html);
+        assert_eq!(core::str::from_utf8(&out[..n]).unwrap(), expected);
+    }
+
+    #[test]
+    fn html_becomes_readable_text() {
+        assert_text("<b>Hello</b>,  <i>world</i>!", "Hello, world!");
+        assert_text(
+            "<h1>Title</h1><p>First\n  line.</p><p>Second &amp; last</p>",
+            "Title\n\nFirst line.\n\nSecond & last",
+        );
+        assert_text("<ul><li>one</li><li>two</li></ul>", "- one\n- two");
+        assert_text("a<br>b<br><br>c", "a\nb\n\nc");
+        assert_text("<pre>  x = 1;\n  y</pre>after", "  x = 1;\n  y\n\nafter");
+        assert_text(
+            "<style>p { color: red }</style><SCRIPT>if (a<b) {}</SCRIPT>ok<!-- <p>gone</p> -->",
+            "ok",
+        );
+        assert_text("<a href=\"x>y\" title='>'>link</a>", "link");
+        assert_text(
+            "caf&#233; &#x263A; &lt;3 &bogus; a < b",
+            "café ☺ <3 &bogus; a < b",
+        );
+        assert_text(
+            "<table><tr><td>1</td><td>2</td></tr><tr><td>3</td></tr></table>",
+            "1 2\n3",
+        );
+        let mut small = [0u8; 4];
+        assert_eq!(html_to_text(b"<p>too long</p>", &mut small), None);
+    }
+
+    #[test]
+    fn text_html_and_uri_list_conversions() {
+        let mut out = [0u8; 128];
+        let n = text_to_html(b"a<b & \"c\"\nd", &mut out).unwrap();
+        assert_eq!(&out[..n], b"a&lt;b &amp; &quot;c&quot;<br>\nd");
+
+        let list = b"# comment\r\nfile:///home/me/a%20b.txt\r\nhttps://exo.example/\r\n";
+        let n = uri_list_to_text(list, &mut out).unwrap();
+        assert_eq!(&out[..n], b"/home/me/a b.txt\nhttps://exo.example/");
+
+        let n = text_to_uri_list(b"/tmp/x\n/home/me/a b\n", &mut out).unwrap();
+        assert_eq!(&out[..n], b"file:///tmp/x\r\nfile:///home/me/a%20b\r\n");
+        assert_eq!(text_to_uri_list(b"/tmp/x\nnot a path", &mut out), None);
+    }
+
+    fn png(width: u32, height: u32) -> [u8; 33] {
+        let mut png = [0u8; 33];
+        png[..8].copy_from_slice(&PNG_SIGNATURE);
+        png[8..12].copy_from_slice(&13u32.to_be_bytes());
+        png[12..16].copy_from_slice(b"IHDR");
+        png[16..20].copy_from_slice(&width.to_be_bytes());
+        png[20..24].copy_from_slice(&height.to_be_bytes());
+        png
+    }
+
+    #[test]
+    fn store_keeps_offers_and_derives_missing_targets() {
+        let mut clip = Clipboard::<256>::new();
+        assert!(clip.is_empty());
+        assert_eq!(clip.pick(&[Target::Text]), None);
+
+        clip.set(&[
+            (b"text/html", b"<p>Hi &amp; bye</p>"),
+            (b"application/x-private", b"ignored"),
+        ])
+        .unwrap();
+        assert_eq!(clip.serial(), 1);
+        let mut targets = [None; TARGETS];
+        for (slot, t) in targets.iter_mut().zip(clip.targets()) {
+            *slot = Some(t);
+        }
+        assert_eq!(
+            targets,
+            [Some(Target::Text), Some(Target::Html), None, None]
+        );
+        let mut out = [0u8; 64];
+        let n = clip.get(Target::Text, &mut out).unwrap();
+        assert_eq!(&out[..n], b"Hi & bye");
+        assert_eq!(
+            clip.get(Target::Png, &mut out),
+            Err(ClipboardError::Unavailable)
+        );
+        assert_eq!(
+            clip.get(Target::Html, &mut out[..3]),
+            Err(ClipboardError::ShortBuffer)
+        );
+
+        // A file manager copy: the terminal and the file manager both pick
+        // the list, a text editor the decoded paths.
+        clip.set(&[(MIME_URI_LIST, b"file:///srv/a%27b\r\n")])
+            .unwrap();
+        assert_eq!(
+            clip.pick(&[Target::UriList, Target::Text]),
+            Some(Target::UriList)
+        );
+        let n = clip.get(Target::Text, &mut out).unwrap();
+        assert_eq!(&out[..n], b"/srv/a'b");
+        let mut pasted = [0u8; 64];
+        let n = clip.get(Target::UriList, &mut out).unwrap();
+        let m = dnd::paste_paths(&out[..n], &mut pasted).unwrap();
+        assert_eq!(&pasted[..m], b"'/srv/a'\\''b'");
+
+        // Paths copied as text from the terminal paste as files.
+        clip.set(&[(b"UTF8_STRING", b"/tmp/report.pdf")]).unwrap();
+        assert!(clip.has(Target::UriList) && clip.has(Target::Html));
+        let n = clip.get(Target::UriList, &mut out).unwrap();
+        assert_eq!(&out[..n], b"file:///tmp/report.pdf\r\n");
+
+        let image = png(640, 480);
+        assert_eq!(png_size(&image), Some((640, 480)));
+        clip.set(&[(MIME_PNG, &image), (MIME_TEXT, b"alt"), (MIME_TEXT, b"dup")])
+            .unwrap();
+        assert_eq!(
+            clip.pick(&[Target::Html, Target::Png, Target::Text]),
+            Some(Target::Png)
+        );
+        let n = clip.get(Target::Text, &mut out).unwrap();
+        assert_eq!(&out[..n], b"alt");
+
+        // Failures keep the previous selection.
+        let serial = clip.serial();
+        assert_eq!(
+            clip.set(&[(MIME_PNG, b"GIF89a")]),
+            Err(ClipboardError::BadImage)
+        );
+        assert_eq!(
+            clip.set(&[(MIME_TEXT, &[b'x'; 300])]),
+            Err(ClipboardError::TooLarge)
+        );
+        assert_eq!(clip.serial(), serial);
+        assert!(clip.has(Target::Png));
+        clip.clear();
+        assert!(clip.is_empty() && clip.serial() == serial + 1);
+    }
+}
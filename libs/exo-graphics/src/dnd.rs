@@ -0,0 +1,295 @@
+//! Drag-and-drop helpers following the `wl_data_device` model: the source
+//! offers MIME types and actions, the target accepts one type and its own
+//! actions, and the compositor picks the action from both sides plus the
+//! keyboard modifiers held during the drag.
+
+pub const MIME_URI_LIST: &[u8] = b"text/uri-list";
+pub const MIME_TEXT: &[u8] = b"text/plain;charset=utf-8";
+
+pub const MAX_OFFERED_MIMES: usize = 4;
+
+/// Bit values match `wl_data_device_manager.dnd_action`.
+pub mod action {
+    pub const NONE: u32 = 0;
+    pub const COPY: u32 = 1;
+    pub const MOVE: u32 = 2;
+    pub const ASK: u32 = 4;
+}
+
+/// Ctrl forces copy, Shift forces move, both ask — the file-manager
+/// convention. No modifier leaves the choice to the target.
+pub fn preferred_action(ctrl: bool, shift: bool) -> u32 {
+    match (ctrl, shift) {
+        (true, true) => action::ASK,
+        (true, false) => action::COPY,
+        (false, true) => action::MOVE,
+        (false, false) => action::NONE,
+    }
+}
+
+/// Action selection done by the compositor: the forced action if both sides
+/// support it, otherwise the target's preference, otherwise the first common
+/// action in copy, move, ask order.
+pub fn negotiate(source: u32, target: u32, forced: u32, target_preferred: u32) -> u32 {
+    let common = source & target;
+    if forced != action::NONE && common & forced == forced {
+        return forced;
+    }
+    if target_preferred != action::NONE && common & target_preferred == target_preferred {
+        return target_preferred;
+    }
+    [action::COPY, action::MOVE, action::ASK]
+        .into_iter()
+        .find(|&a| common & a != 0)
+        .unwrap_or(action::NONE)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DndError {
+    TooManyMimes,
+    NotAccepted,
+    NoAction,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DropOutcome {
+    /// Index of the accepted MIME type in the source offer.
+    pub mime: usize,
+    pub action: u32,
+}
+
+/// One drag from `start_drag` to `drop`, as tracked by the compositor.
+#[derive(Clone, Copy, Debug)]
+pub struct DragSession<'a> {
+    mimes: [&'a [u8]; MAX_OFFERED_MIMES],
+    mime_count: usize,
+    source_actions: u32,
+    target_actions: u32,
+    target_preferred: u32,
+    forced: u32,
+    accepted: Option<usize>,
+}
+
+impl<'a> DragSession<'a> {
+    pub fn start(mimes: &[&'a [u8]], source_actions: u32) -> Result<Self, DndError> {
+        if mimes.len() > MAX_OFFERED_MIMES {
+            return Err(DndError::TooManyMimes);
+        }
+        let mut offered: [&[u8]; MAX_OFFERED_MIMES] = [&[]; MAX_OFFERED_MIMES];
+        offered[..mimes.len()].copy_from_slice(mimes);
+        Ok(Self {
+            mimes: offered,
+            mime_count: mimes.len(),
+            source_actions,
+            target_actions: action::NONE,
+            target_preferred: action::NONE,
+            forced: action::NONE,
+            accepted: None,
+        })
+    }
+
+    pub fn mimes(&self) -> &[&'a [u8]] {
+        &self.mimes[..self.mime_count]
+    }
+
+    /// Pointer entered a new surface: previous acceptance no longer holds.
+    pub fn enter(&mut self) {
+        self.accepted = None;
+        self.target_actions = action::NONE;
+        self.target_preferred = action::NONE;
+    }
+
+    /// `wl_data_offer.accept`; `None` rejects the drag.
+    pub fn accept(&mut self, mime: Option<&[u8]>) -> bool {
+        self.accepted = mime.and_then(|m| self.mimes().iter().position(|&o| o == m));
+        self.accepted.is_some()
+    }
+
+    /// `wl_data_offer.set_actions`.
+    pub fn set_target_actions(&mut self, actions: u32, preferred: u32) {
+        self.target_actions = actions;
+        self.target_preferred = preferred;
+    }
+
+    pub fn set_modifiers(&mut self, ctrl: bool, shift: bool) {
+        self.forced = preferred_action(ctrl, shift);
+    }
+
+    /// Action the cursor should currently show.
+    pub fn current_action(&self) -> u32 {
+        if self.accepted.is_none() {
+            return action::NONE;
+        }
+        negotiate(
+            self.source_actions,
+            self.target_actions,
+            self.forced,
+            self.target_preferred,
+        )
+    }
+
+    pub fn drop(&self) -> Result<DropOutcome, DndError> {
+        let mime = self.accepted.ok_or(DndError::NotAccepted)?;
+        match self.current_action() {
+            action::NONE => Err(DndError::NoAction),
+            action => Ok(DropOutcome { mime, action }),
+        }
+    }
+}
+
+fn is_unreserved(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~' | b'/')
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+pub(crate) struct Writer<'a> {
+    pub(crate) out: &'a mut [u8],
+    pub(crate) len: usize,
+}
+
+impl Writer<'_> {
+    pub(crate) fn put(&mut self, bytes: &[u8]) -> Option<()> {
+        let end = self.len.checked_add(bytes.len())?;
+        self.out.get_mut(self.len..end)?.copy_from_slice(bytes);
+        self.len = end;
+        Some(())
+    }
+}
+
+/// Encodes absolute paths as a `text/uri-list` payload (`file://` URIs,
+/// CRLF separated). Returns the length written.
+pub fn encode_uri_list(paths: &[&[u8]], out: &mut [u8]) -> Option<usize> {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+    let mut w = Writer { out, len: 0 };
+    for path in paths {
+        if path.first() != Some(&b'/') {
+            return None;
+        }
+        w.put(b"file://")?;
+        for &b in path.iter() {
+            if is_unreserved(b) {
+                w.put(&[b])?;
+            } else {
+                w.put(&[b'%', HEX[(b >> 4) as usize], HEX[(b & 0xf) as usize]])?;
+            }
+        }
+        w.put(b"\r\n")?;
+    }
+    Some(w.len)
+}
+
+/// Iterates the URIs of a `text/uri-list` payload, skipping comments.
+pub fn uris(list: &[u8]) -> impl Iterator<Item = &[u8]> {
+    list.split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line).trim_ascii())
+        .filter(|line| !line.is_empty() && line[0] != b'#')
+}
+
+/// Decodes a local `file://` URI to its path. Remote hosts are rejected.
+pub fn file_uri_path(uri: &[u8], out: &mut [u8]) -> Option<usize> {
+    let rest = uri.strip_prefix(b"file://")?;
+    let rest = rest.strip_prefix(b"localhost").unwrap_or(rest);
+    if rest.first() != Some(&b'/') {
+        return None;
+    }
+    let mut len = 0usize;
+    let mut i = 0usize;
+    while i < rest.len() {
+        let b = if rest[i] == b'%' {
+            let hi = hex_value(*rest.get(i + 1)?)?;
+            let lo = hex_value(*rest.get(i + 2)?)?;
+            i += 3;
+            (hi << 4) | lo
+        } else {
+            i += 1;
+            rest[i - 1]
+        };
+        *out.get_mut(len)? = b;
+        len += 1;
+    }
+    Some(len)
+}
+
+/// Terminal drop target: turns a `text/uri-list` into shell-quoted paths
+/// separated by spaces, ready to be pasted at the prompt.
+pub fn paste_paths(list: &[u8], out: &mut [u8]) -> Option<usize> {
+    let mut path = [0u8; 512];
+    let mut w = Writer { out, len: 0 };
+    for (i, uri) in uris(list).enumerate() {
+        let n = file_uri_path(uri, &mut path)?;
+        if i > 0 {
+            w.put(b" ")?;
+        }
+        if path[..n].iter().all(|&b| is_unreserved(b)) {
+            w.put(&path[..n])?;
+            continue;
+        }
+        w.put(b"'")?;
+        for &b in &path[..n] {
+            if b == b'\'' {
+                w.put(b"'\\''")?;
+            } else {
+                w.put(&[b])?;
+            }
+        }
+        w.put(b"'")?;
+    }
+    Some(w.len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifiers_override_target_preference() {
+        let mut drag =
+            DragSession::start(&[MIME_URI_LIST, MIME_TEXT], action::COPY | action::MOVE).unwrap();
+        drag.enter();
+        assert_eq!(drag.current_action(), action::NONE);
+        assert!(drag.accept(Some(MIME_URI_LIST)));
+        drag.set_target_actions(action::COPY | action::MOVE, action::MOVE);
+        assert_eq!(drag.current_action(), action::MOVE);
+        drag.set_modifiers(true, false);
+        assert_eq!(
+            drag.drop(),
+            Ok(DropOutcome {
+                mime: 0,
+                action: action::COPY
+            })
+        );
+        // Source cannot ask: falls back to the target preference.
+        drag.set_modifiers(true, true);
+        assert_eq!(drag.current_action(), action::MOVE);
+        drag.enter();
+        assert!(!drag.accept(Some(b"image/png")));
+        assert_eq!(drag.drop(), Err(DndError::NotAccepted));
+    }
+
+    #[test]
+    fn uri_list_round_trips_and_pastes_quoted() {
+        let mut list = [0u8; 128];
+        let n = encode_uri_list(&[b"/home/user/a b.txt", b"/tmp/it's"], &mut list).unwrap();
+        assert_eq!(
+            &list[..n],
+            b"file:///home/user/a%20b.txt\r\nfile:///tmp/it%27s\r\n"
+        );
+        let mut path = [0u8; 64];
+        let first = uris(&list[..n]).next().unwrap();
+        let p = file_uri_path(first, &mut path).unwrap();
+        assert_eq!(&path[..p], b"/home/user/a b.txt");
+
+        let mut out = [0u8; 64];
+        let m = paste_paths(&list[..n], &mut out).unwrap();
+        assert_eq!(&out[..m], b"'/home/user/a b.txt' '/tmp/it'\\''s'");
+        assert_eq!(file_uri_path(b"file://nas/share", &mut path), None);
+    }
+}
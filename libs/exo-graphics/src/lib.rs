@@ -1,5 +1,11 @@
 #![no_std]
 
+pub mod atlas;
+pub mod clipboard;
+pub mod dnd;
+pub mod pacing;
+pub mod sfnt;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum GraphicsPortKind {
     Windowing,
@@ -0,0 +1,204 @@
+//! Frame pacing for the display layer. Instead of compositing at a fixed
+//! 60 Hz, the scheduler wakes the compositor only when a client committed
+//! new content and, on variable refresh rate (VRR) outputs, lets the panel
+//! follow the content rate.
+
+pub const NS_PER_SEC: u64 = 1_000_000_000;
+
+/// Safety margin added to the measured composition time.
+const RENDER_MARGIN_NS: u64 = 1_000_000;
+/// Weight of the newest sample in the moving averages, in 1/8ths.
+const EWMA_NEW_EIGHTHS: u64 = 2;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RefreshRange {
+    pub min_mhz: u32,
+    pub max_mhz: u32,
+}
+
+impl RefreshRange {
+    /// Longest frame time the panel tolerates (lowest refresh rate).
+    pub fn max_interval_ns(&self) -> u64 {
+        interval_ns(self.min_mhz)
+    }
+
+    /// Shortest frame time the panel supports (highest refresh rate).
+    pub fn min_interval_ns(&self) -> u64 {
+        interval_ns(self.max_mhz)
+    }
+}
+
+/// Refresh rates are kept in millihertz so 59.94 Hz modes are exact.
+pub fn interval_ns(mhz: u32) -> u64 {
+    if mhz == 0 {
+        return 0;
+    }
+    NS_PER_SEC * 1000 / mhz as u64
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OutputTiming {
+    pub refresh_mhz: u32,
+    /// `Some` when the output supports adaptive sync and it is enabled.
+    pub vrr: Option<RefreshRange>,
+}
+
+impl OutputTiming {
+    pub const fn fixed(refresh_mhz: u32) -> Self {
+        Self {
+            refresh_mhz,
+            vrr: None,
+        }
+    }
+}
+
+/// Low framerate compensation: how many times a frame must be scanned out
+/// so that the panel stays above its minimum refresh rate.
+pub fn lfc_multiplier(content_interval_ns: u64, range: &RefreshRange) -> u64 {
+    let max = range.max_interval_ns().max(1);
+    content_interval_ns.div_ceil(max).max(1)
+}
+
+fn ewma(old: u64, sample: u64) -> u64 {
+    if old == 0 {
+        return sample;
+    }
+    (old * (8 - EWMA_NEW_EIGHTHS) + sample * EWMA_NEW_EIGHTHS) / 8
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FrameScheduler {
+    timing: OutputTiming,
+    last_vblank_ns: u64,
+    last_commit_ns: u64,
+    content_interval_ns: u64,
+    render_ns: u64,
+    damage_pending: bool,
+}
+
+impl FrameScheduler {
+    pub const fn new(timing: OutputTiming) -> Self {
+        Self {
+            timing,
+            last_vblank_ns: 0,
+            last_commit_ns: 0,
+            content_interval_ns: 0,
+            render_ns: 0,
+            damage_pending: false,
+        }
+    }
+
+    pub fn timing(&self) -> OutputTiming {
+        self.timing
+    }
+
+    pub fn set_timing(&mut self, timing: OutputTiming) {
+        self.timing = timing;
+    }
+
+    /// A client committed new content.
+    pub fn on_commit(&mut self, now_ns: u64) {
+        if self.last_commit_ns != 0 && now_ns > self.last_commit_ns {
+            self.content_interval_ns = ewma(self.content_interval_ns, now_ns - self.last_commit_ns);
+        }
+        self.last_commit_ns = now_ns;
+        self.damage_pending = true;
+    }
+
+    pub fn on_composited(&mut self, start_ns: u64, end_ns: u64) {
+        self.render_ns = ewma(self.render_ns, end_ns.saturating_sub(start_ns));
+        self.damage_pending = false;
+    }
+
+    pub fn on_vblank(&mut self, now_ns: u64) {
+        self.last_vblank_ns = now_ns;
+    }
+
+    /// Measured content rate, 0 until two commits were seen.
+    pub fn content_interval_ns(&self) -> u64 {
+        self.content_interval_ns
+    }
+
+    /// When the compositor should start compositing, or `None` when nothing
+    /// changed and it can stay asleep.
+    pub fn next_wakeup(&self, now_ns: u64) -> Option<u64> {
+        if !self.damage_pending {
+            return None;
+        }
+        let budget = self.render_ns + RENDER_MARGIN_NS;
+        let present = match self.timing.vrr {
+            // The panel waits for us: present as soon as the minimum frame
+            // time since the last scanout has elapsed.
+            Some(range) => (self.last_vblank_ns + range.min_interval_ns()).max(now_ns + budget),
+            None => self.next_fixed_vblank(now_ns + budget),
+        };
+        Some(present.saturating_sub(budget).max(now_ns))
+    }
+
+    fn next_fixed_vblank(&self, at_or_after_ns: u64) -> u64 {
+        let interval = interval_ns(self.timing.refresh_mhz).max(1);
+        if at_or_after_ns <= self.last_vblank_ns {
+            return self.last_vblank_ns;
+        }
+        let periods = (at_or_after_ns - self.last_vblank_ns).div_ceil(interval);
+        self.last_vblank_ns + periods * interval
+    }
+
+    /// Frame callback period: on VRR outputs clients are paced to their own
+    /// content rate (never faster than the panel maximum), LFC covers rates
+    /// below the panel minimum.
+    pub fn frame_callback_interval_ns(&self) -> u64 {
+        match self.timing.vrr {
+            Some(range) => self.content_interval_ns.max(range.min_interval_ns()),
+            None => interval_ns(self.timing.refresh_mhz),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VRR_48_144: RefreshRange = RefreshRange {
+        min_mhz: 48_000,
+        max_mhz: 144_000,
+    };
+
+    #[test]
+    fn idle_output_does_not_wake_the_compositor() {
+        let mut s = FrameScheduler::new(OutputTiming::fixed(60_000));
+        assert_eq!(s.next_wakeup(1_000), None);
+        s.on_commit(1_000);
+        assert!(s.next_wakeup(1_000).is_some());
+        s.on_composited(1_000, 3_000_000);
+        assert_eq!(s.next_wakeup(4_000_000), None);
+    }
+
+    #[test]
+    fn fixed_refresh_aligns_to_vblank() {
+        let mut s = FrameScheduler::new(OutputTiming::fixed(60_000));
+        s.on_vblank(0);
+        s.on_commit(2_000_000);
+        // 16.67 ms vblank minus the 1 ms margin.
+        assert_eq!(
+            s.next_wakeup(2_000_000),
+            Some(16_666_666 - RENDER_MARGIN_NS)
+        );
+    }
+
+    #[test]
+    fn vrr_follows_content_rate_with_lfc() {
+        let mut s = FrameScheduler::new(OutputTiming {
+            refresh_mhz: 144_000,
+            vrr: Some(VRR_48_144),
+        });
+        s.on_vblank(0);
+        s.on_commit(1_000);
+        s.on_commit(33_334_333);
+        // 30 fps content on a 48 Hz minimum panel: each frame shown twice.
+        assert_eq!(lfc_multiplier(s.content_interval_ns(), &VRR_48_144), 2);
+        assert_eq!(s.frame_callback_interval_ns(), 33_333_333);
+        // Ready right away: presents without waiting for a fixed vblank.
+        assert_eq!(s.next_wakeup(40_000_000), Some(40_000_000));
+    }
+}
@@ -0,0 +1,984 @@
+//! Minimal TrueType reader and rasterizer for the glyph atlas.
+//!
+//! `font_cache` loads the fonts of the fallback chains
+//! (`exo_services::fonts`) and renders their glyphs into the shared atlas
+//! with this module; clients never parse fonts themselves. Supported:
+//!
+//! - `glyf` outlines (quadratic), simple and composite; component offsets
+//!   are honoured, component scales are not;
+//! - `cmap` formats 4 (BMP) and 12 (full Unicode);
+//! - color glyphs from `COLR` version 0 layers and the first `CPAL`
+//!   palette, as used by color emoji fonts.
+//!
+//! Not supported: CFF outlines, font collections, hinting, and bitmap
+//! color formats (`CBDT`, `sbix`), which need a PNG decoder.
+//!
+//! Glyphs are rendered into a cell `advance × px`, where `px` covers the
+//! font's ascender to descender and the baseline sits at the ascender.
+//! Coverage is exact horizontally and sampled [`SUB_Y`] times per row,
+//! with the nonzero winding rule.
+
+/// Largest cell height.
+pub const MAX_PX: u16 = 128;
+/// Largest cell width.
+pub const MAX_WIDTH: u16 = 256;
+/// Vertical samples per pixel row.
+pub const SUB_Y: i32 = 4;
+/// Points of one simple glyph.
+pub const MAX_POINTS: usize = 1024;
+pub const MAX_CONTOURS: usize = 128;
+/// Flattened segments of one glyph, components included.
+pub const MAX_LINES: usize = 4096;
+const MAX_CROSSINGS: usize = 256;
+/// Nesting of composite glyphs.
+const MAX_DEPTH: u32 = 4;
+/// Segments per quadratic curve.
+const CURVE_STEPS: i64 = 8;
+/// Palette index standing for the text color.
+pub const FOREGROUND: u16 = 0xffff;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SfntError {
+    Truncated,
+    /// CFF font, font collection or not a font at all.
+    NotTrueType,
+    MissingTable,
+    /// No `cmap` subtable in format 4 or 12.
+    NoUnicodeCmap,
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    let b = data.get(at..at.checked_add(2)?)?;
+    Some(u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn i16_at(data: &[u8], at: usize) -> Option<i16> {
+    u16_at(data, at).map(|v| v as i16)
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    let b = data.get(at..at.checked_add(4)?)?;
+    Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn table<'a>(data: &'a [u8], tag: &[u8; 4]) -> Option<&'a [u8]> {
+    let count = u16_at(data, 4)? as usize;
+    (0..count).find_map(|i| {
+        let at = 12 + i * 16;
+        if data.get(at..at + 4)? != tag {
+            return None;
+        }
+        let offset = u32_at(data, at + 8)? as usize;
+        let len = u32_at(data, at + 12)? as usize;
+        data.get(offset..offset.checked_add(len)?)
+    })
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Cmap<'a> {
+    Format4(&'a [u8]),
+    Format12(&'a [u8]),
+}
+
+/// A parsed font; borrows the file contents.
+#[derive(Clone, Copy, Debug)]
+pub struct Font<'a> {
+    ascender: i32,
+    /// Ascender minus descender, in font units.
+    height: i32,
+    num_glyphs: u16,
+    num_hmetrics: u16,
+    long_loca: bool,
+    cmap: Cmap<'a>,
+    hmtx: &'a [u8],
+    loca: &'a [u8],
+    glyf: &'a [u8],
+    colr: Option<&'a [u8]>,
+    cpal: Option<&'a [u8]>,
+}
+
+impl<'a> Font<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, SfntError> {
+        match u32_at(data, 0).ok_or(SfntError::Truncated)? {
+            0x0001_0000 | 0x7472_7565 => {}
+            _ => return Err(SfntError::NotTrueType),
+        }
+        let head = table(data, b"head").ok_or(SfntError::MissingTable)?;
+        let hhea = table(data, b"hhea").ok_or(SfntError::MissingTable)?;
+        let maxp = table(data, b"maxp").ok_or(SfntError::MissingTable)?;
+        let cmap = table(data, b"cmap").ok_or(SfntError::MissingTable)?;
+        let hmtx = table(data, b"hmtx").ok_or(SfntError::MissingTable)?;
+        let loca = table(data, b"loca").ok_or(SfntError::MissingTable)?;
+        let glyf = table(data, b"glyf").ok_or(SfntError::MissingTable)?;
+
+        let units_per_em = u16_at(head, 18).ok_or(SfntError::Truncated)? as i32;
+        let long_loca = i16_at(head, 50).ok_or(SfntError::Truncated)? == 1;
+        let ascender = i16_at(hhea, 4).ok_or(SfntError::Truncated)? as i32;
+        let descender = i16_at(hhea, 6).ok_or(SfntError::Truncated)? as i32;
+        let num_hmetrics = u16_at(hhea, 34).ok_or(SfntError::Truncated)?;
+        let num_glyphs = u16_at(maxp, 4).ok_or(SfntError::Truncated)?;
+        if num_hmetrics == 0 || hmtx.len() < num_hmetrics as usize * 4 {
+            return Err(SfntError::Truncated);
+        }
+        let height = match ascender - descender {
+            h if h > 0 => h,
+            _ => units_per_em.max(1),
+        };
+
+        Ok(Self {
+            ascender,
+            height,
+            num_glyphs,
+            num_hmetrics,
+            long_loca,
+            cmap: unicode_cmap(cmap).ok_or(SfntError::NoUnicodeCmap)?,
+            hmtx,
+            loca,
+            glyf,
+            colr: table(data, b"COLR").filter(|colr| u16_at(colr, 0) == Some(0)),
+            cpal: table(data, b"CPAL"),
+        })
+    }
+
+    /// Glyph for `ch`, `None` if the font has none (glyph 0 is `.notdef`).
+    pub fn glyph_id(&self, ch: char) -> Option<u16> {
+        let c = ch as u32;
+        let gid = match self.cmap {
+            Cmap::Format4(sub) => cmap4(sub, c)?,
+            Cmap::Format12(sub) => cmap12(sub, c)?,
+        };
+        (gid != 0 && gid < self.num_glyphs).then_some(gid)
+    }
+
+    /// Cell width of `gid` at `px`: its advance, rounded.
+    pub fn advance(&self, gid: u16, px: u16) -> u16 {
+        let index = gid.min(self.num_hmetrics - 1) as usize;
+        let units = u16_at(self.hmtx, index * 4).unwrap_or(0) as i64;
+        let width = (units * px as i64 + self.height as i64 / 2) / self.height as i64;
+        width.min(MAX_WIDTH as i64) as u16
+    }
+
+    /// `true` if `gid` has color layers.
+    pub fn is_color(&self, gid: u16) -> bool {
+        self.layers(gid).is_some()
+    }
+
+    /// First layer record and layer count of a color glyph.
+    fn layers(&self, gid: u16) -> Option<(usize, usize)> {
+        let colr = self.colr?;
+        let count = u16_at(colr, 2)? as usize;
+        let base = u32_at(colr, 4)? as usize;
+        let (mut lo, mut hi) = (0, count);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let at = base + mid * 6;
+            match u16_at(colr, at)?.cmp(&gid) {
+                core::cmp::Ordering::Less => lo = mid + 1,
+                core::cmp::Ordering::Greater => hi = mid,
+                core::cmp::Ordering::Equal => {
+                    let first = u16_at(colr, at + 2)? as usize;
+                    let layers = u16_at(colr, at + 4)? as usize;
+                    return (layers > 0).then_some((first, layers));
+                }
+            }
+        }
+        None
+    }
+
+    /// Layer `index`: glyph and palette index.
+    fn layer(&self, index: usize) -> Option<(u16, u16)> {
+        let colr = self.colr?;
+        let records = u32_at(colr, 8)? as usize;
+        if index >= u16_at(colr, 12)? as usize {
+            return None;
+        }
+        let at = records + index * 4;
+        Some((u16_at(colr, at)?, u16_at(colr, at + 2)?))
+    }
+
+    /// RGBA color of palette entry `index` in the first palette.
+    fn color(&self, index: u16) -> Option<[u8; 4]> {
+        let cpal = self.cpal?;
+        if index >= u16_at(cpal, 2)? {
+            return None;
+        }
+        let records = u32_at(cpal, 8)? as usize;
+        let first = u16_at(cpal, 12)? as usize;
+        let at = records + (first + index as usize) * 4;
+        let bgra = cpal.get(at..at + 4)?;
+        Some([bgra[2], bgra[1], bgra[0], bgra[3]])
+    }
+
+    fn outline(&self, gid: u16) -> Option<&'a [u8]> {
+        let (start, end) = if self.long_loca {
+            let at = gid as usize * 4;
+            (
+                u32_at(self.loca, at)? as usize,
+                u32_at(self.loca, at + 4)? as usize,
+            )
+        } else {
+            let at = gid as usize * 2;
+            (
+                u16_at(self.loca, at)? as usize * 2,
+                u16_at(self.loca, at + 2)? as usize * 2,
+            )
+        };
+        self.glyf.get(start..end)
+    }
+
+    /// Font units to 26.6 cell coordinates (y down).
+    fn scale(&self, x: i32, y: i32, px: u16) -> (i32, i32) {
+        let k = px as i64 * 64;
+        let x = x as i64 * k / self.height as i64;
+        let y = (self.ascender - y) as i64 * k / self.height as i64;
+        (x as i32, y as i32)
+    }
+}
+
+fn unicode_cmap(cmap: &[u8]) -> Option<Cmap<'_>> {
+    let count = u16_at(cmap, 2)? as usize;
+    let mut format4 = None;
+    for i in 0..count {
+        let at = 4 + i * 8;
+        let (platform, encoding) = (u16_at(cmap, at)?, u16_at(cmap, at + 2)?);
+        let unicode = matches!((platform, encoding), (0, _) | (3, 1) | (3, 10));
+        let Some(sub) = cmap.get(u32_at(cmap, at + 4)? as usize..) else {
+            continue;
+        };
+        match u16_at(sub, 0) {
+            Some(12) if unicode => return Some(Cmap::Format12(sub)),
+            Some(4) if unicode => format4 = Some(Cmap::Format4(sub)),
+            _ => {}
+        }
+    }
+    format4
+}
+
+fn cmap4(sub: &[u8], c: u32) -> Option<u16> {
+    if c > 0xffff {
+        return None;
+    }
+    let c = c as u16;
+    let seg_x2 = u16_at(sub, 6)? as usize;
+    let ends = 14;
+    let starts = ends + seg_x2 + 2;
+    let deltas = starts + seg_x2;
+    let ranges = deltas + seg_x2;
+    for seg in (0..seg_x2).step_by(2) {
+        if u16_at(sub, ends + seg)? < c {
+            continue;
+        }
+        let start = u16_at(sub, starts + seg)?;
+        if start > c {
+            return None;
+        }
+        let delta = u16_at(sub, deltas + seg)?;
+        let range = u16_at(sub, ranges + seg)? as usize;
+        if range == 0 {
+            return Some(c.wrapping_add(delta));
+        }
+        let at = ranges + seg + range + 2 * (c - start) as usize;
+        return match u16_at(sub, at)? {
+            0 => None,
+            gid => Some(gid.wrapping_add(delta)),
+        };
+    }
+    None
+}
+
+fn cmap12(sub: &[u8], c: u32) -> Option<u16> {
+    let groups = u32_at(sub, 12)? as usize;
+    let (mut lo, mut hi) = (0, groups);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let at = 16 + mid * 12;
+        if u32_at(sub, at + 4)? < c {
+            lo = mid + 1;
+        } else if u32_at(sub, at)? > c {
+            hi = mid;
+        } else {
+            let gid = u32_at(sub, at + 8)? + (c - u32_at(sub, at)?);
+            return u16::try_from(gid).ok();
+        }
+    }
+    None
+}
+
+#[derive(Clone, Copy, Default)]
+struct Point {
+    x: i32,
+    y: i32,
+    on: bool,
+}
+
+impl Point {
+    fn mid(a: Point, b: Point) -> Point {
+        Point {
+            x: (a.x + b.x) / 2,
+            y: (a.y + b.y) / 2,
+            on: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Line {
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+}
+
+/// Scratch space for rendering; large, keep one per daemon in a static.
+pub struct Rasterizer {
+    points: [Point; MAX_POINTS],
+    flags: [u8; MAX_POINTS],
+    ends: [u16; MAX_CONTOURS],
+    lines: [Line; MAX_LINES],
+    n_lines: usize,
+    overflow: bool,
+    crossings: [(i32, i32); MAX_CROSSINGS],
+    coverage: [u32; MAX_WIDTH as usize],
+    mask: [u8; MAX_PX as usize * MAX_WIDTH as usize],
+}
+
+impl Default for Rasterizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rasterizer {
+    pub const fn new() -> Self {
+        Self {
+            points: [Point {
+                x: 0,
+                y: 0,
+                on: false,
+            }; MAX_POINTS],
+            flags: [0; MAX_POINTS],
+            ends: [0; MAX_CONTOURS],
+            lines: [Line {
+                x0: 0,
+                y0: 0,
+                x1: 0,
+                y1: 0,
+            }; MAX_LINES],
+            n_lines: 0,
+            overflow: false,
+            crossings: [(0, 0); MAX_CROSSINGS],
+            coverage: [0; MAX_WIDTH as usize],
+            mask: [0; MAX_PX as usize * MAX_WIDTH as usize],
+        }
+    }
+
+    /// Renders `gid` as alpha8 into `out`; returns the cell size. `None`
+    /// if `px` is out of range, `out` too small, or the outline is
+    /// malformed or too complex.
+    pub fn glyph(&mut self, font: &Font, gid: u16, px: u16, out: &mut [u8]) -> Option<(u16, u16)> {
+        let (width, height) = cell(font, gid, px)?;
+        let out = out.get_mut(..width as usize * height as usize)?;
+        self.n_lines = 0;
+        self.overflow = false;
+        self.outline(font, gid, px, (0, 0), 0)?;
+        if self.overflow {
+            return None;
+        }
+        self.fill(width, height);
+        out.copy_from_slice(&self.mask[..out.len()]);
+        Some((width, height))
+    }
+
+    /// Renders the color layers of `gid` as RGBA8888 (straight alpha) into
+    /// `out`; layers using the text color get `foreground`. Returns the
+    /// cell size, `None` as for [`glyph`](Self::glyph) or if `gid` has no
+    /// color layers.
+    pub fn color_glyph(
+        &mut self,
+        font: &Font,
+        gid: u16,
+        px: u16,
+        foreground: [u8; 4],
+        out: &mut [u8],
+    ) -> Option<(u16, u16)> {
+        let (first, layers) = font.layers(gid)?;
+        let (width, height) = cell(font, gid, px)?;
+        let len = width as usize * height as usize;
+        let out = out.get_mut(..len * 4)?;
+        out.fill(0);
+        for index in first..first + layers {
+            let (layer, palette) = font.layer(index)?;
+            let color = match palette {
+                FOREGROUND => foreground,
+                index => font.color(index)?,
+            };
+            self.n_lines = 0;
+            self.overflow = false;
+            self.outline(font, layer, px, (0, 0), 0)?;
+            if self.overflow {
+                return None;
+            }
+            self.fill(width, height);
+            for (dst, &coverage) in out.chunks_exact_mut(4).zip(&self.mask[..len]) {
+                blend(dst, color, coverage);
+            }
+        }
+        Some((width, height))
+    }
+
+    /// Appends the flattened outline of `gid`, moved by `offset` font units.
+    fn outline(
+        &mut self,
+        font: &Font,
+        gid: u16,
+        px: u16,
+        offset: (i32, i32),
+        depth: u32,
+    ) -> Option<()> {
+        let data = font.outline(gid)?;
+        if data.is_empty() {
+            return Some(());
+        }
+        let contours = i16_at(data, 0)?;
+        if contours >= 0 {
+            return self.simple(font, data, contours as usize, px, offset);
+        }
+        if depth == MAX_DEPTH {
+            return None;
+        }
+        let mut at = 10;
+        loop {
+            let flags = u16_at(data, at)?;
+            let component = u16_at(data, at + 2)?;
+            at += 4;
+            let (dx, dy) = if flags & 0x0001 != 0 {
+                at += 4;
+                (i16_at(data, at - 4)? as i32, i16_at(data, at - 2)? as i32)
+            } else {
+                at += 2;
+                (
+                    *data.get(at - 2)? as i8 as i32,
+                    *data.get(at - 1)? as i8 as i32,
+                )
+            };
+            // Arguments that are point numbers (anchoring) are not supported.
+            let (dx, dy) = if flags & 0x0002 != 0 {
+                (dx, dy)
+            } else {
+                (0, 0)
+            };
+            at += match flags {
+                f if f & 0x0008 != 0 => 2,
+                f if f & 0x0040 != 0 => 4,
+                f if f & 0x0080 != 0 => 8,
+                _ => 0,
+            };
+            self.outline(
+                font,
+                component,
+                px,
+                (offset.0 + dx, offset.1 + dy),
+                depth + 1,
+            )?;
+            if flags & 0x0020 == 0 {
+                return Some(());
+            }
+        }
+    }
+
+    fn simple(
+        &mut self,
+        font: &Font,
+        data: &[u8],
+        contours: usize,
+        px: u16,
+        offset: (i32, i32),
+    ) -> Option<()> {
+        if contours > MAX_CONTOURS {
+            return None;
+        }
+        let mut count = 0;
+        for i in 0..contours {
+            let end = u16_at(data, 10 + i * 2)?;
+            if (end as usize) < count && i > 0 {
+                return None;
+            }
+            self.ends[i] = end;
+            count = end as usize + 1;
+        }
+        if count > MAX_POINTS {
+            return None;
+        }
+        let instructions = 10 + contours * 2;
+        let mut at = instructions + 2 + u16_at(data, instructions)? as usize;
+
+        let mut i = 0;
+        while i < count {
+            let flag = *data.get(at)?;
+            at += 1;
+            let repeat = if flag & 0x08 != 0 {
+                at += 1;
+                *data.get(at - 1)? as usize
+            } else {
+                0
+            };
+            for _ in 0..=repeat {
+                if i < count {
+                    self.flags[i] = flag;
+                    i += 1;
+                }
+            }
+        }
+        let mut x = offset.0;
+        for i in 0..count {
+            let flag = self.flags[i];
+            x += coordinate(data, &mut at, flag, 0x02, 0x10)?;
+            self.points[i].x = x;
+            self.points[i].on = flag & 0x01 != 0;
+        }
+        let mut y = offset.1;
+        for i in 0..count {
+            y += coordinate(data, &mut at, self.flags[i], 0x04, 0x20)?;
+            let (sx, sy) = font.scale(self.points[i].x, y, px);
+            self.points[i].x = sx;
+            self.points[i].y = sy;
+        }
+
+        let mut start = 0;
+        for c in 0..contours {
+            let end = self.ends[c] as usize + 1;
+            self.contour(start, end);
+            start = end;
+        }
+        Some(())
+    }
+
+    /// Flattens points `start..end`; off-curve neighbours imply an
+    /// on-curve point halfway between them.
+    fn contour(&mut self, start: usize, end: usize) {
+        let n = end - start;
+        if n < 2 {
+            return;
+        }
+        let (first, last) = (self.points[start], self.points[end - 1]);
+        let (origin, skip_first, skip_last) = if first.on {
+            (first, true, false)
+        } else if last.on {
+            (last, false, true)
+        } else {
+            (Point::mid(last, first), false, false)
+        };
+        let mut current = origin;
+        let mut control: Option<Point> = None;
+        for i in start..end {
+            if (skip_first && i == start) || (skip_last && i == end - 1) {
+                continue;
+            }
+            let p = self.points[i];
+            match (p.on, control) {
+                (true, Some(c)) => {
+                    self.quad(current, c, p);
+                    current = p;
+                    control = None;
+                }
+                (true, None) => {
+                    self.line(current, p);
+                    current = p;
+                }
+                (false, Some(c)) => {
+                    let m = Point::mid(c, p);
+                    self.quad(current, c, m);
+                    current = m;
+                    control = Some(p);
+                }
+                (false, None) => control = Some(p),
+            }
+        }
+        match control {
+            Some(c) => self.quad(current, c, origin),
+            None => self.line(current, origin),
+        }
+    }
+
+    fn quad(&mut self, p0: Point, c: Point, p2: Point) {
+        let mut prev = p0;
+        for k in 1..=CURVE_STEPS {
+            let (a, b, t) = ((CURVE_STEPS - k).pow(2), 2 * k * (CURVE_STEPS - k), k * k);
+            let at = |p0: i32, c: i32, p2: i32| {
+                ((a * p0 as i64 + b * c as i64 + t * p2 as i64) / (CURVE_STEPS * CURVE_STEPS))
+                    as i32
+            };
+            let next = Point {
+                x: at(p0.x, c.x, p2.x),
+                y: at(p0.y, c.y, p2.y),
+                on: true,
+            };
+            self.line(prev, next);
+            prev = next;
+        }
+    }
+
+    fn line(&mut self, a: Point, b: Point) {
+        if a.y == b.y {
+            return;
+        }
+        match self.lines.get_mut(self.n_lines) {
+            Some(line) => {
+                *line = Line {
+                    x0: a.x,
+                    y0: a.y,
+                    x1: b.x,
+                    y1: b.y,
+                };
+                self.n_lines += 1;
+            }
+            None => self.overflow = true,
+        }
+    }
+
+    /// Scan-converts the collected segments into the mask (`width × height`).
+    fn fill(&mut self, width: u16, height: u16) {
+        let (w, w64) = (width as usize, width as i32 * 64);
+        let mask = &mut self.mask[..w * height as usize];
+        for (row, line) in mask.chunks_exact_mut(w.max(1)).enumerate() {
+            self.coverage[..w].fill(0);
+            for s in 0..SUB_Y {
+                let sy = row as i32 * 64 + (s * 64 + 32) / SUB_Y;
+                let mut n = 0;
+                for l in &self.lines[..self.n_lines] {
+                    let (lo, hi, dir) = if l.y0 < l.y1 {
+                        (l.y0, l.y1, 1)
+                    } else {
+                        (l.y1, l.y0, -1)
+                    };
+                    if sy < lo || sy >= hi || n == MAX_CROSSINGS {
+                        continue;
+                    }
+                    let x = l.x0 as i64
+                        + (sy - l.y0) as i64 * (l.x1 - l.x0) as i64 / (l.y1 - l.y0) as i64;
+                    self.crossings[n] = (x as i32, dir);
+                    n += 1;
+                }
+                let crossings = &mut self.crossings[..n];
+                crossings.sort_unstable_by_key(|&(x, _)| x);
+                let mut winding = 0;
+                let mut from = 0;
+                for &(x, dir) in crossings.iter() {
+                    let before = winding;
+                    winding += dir;
+                    if before == 0 && winding != 0 {
+                        from = x;
+                    } else if before != 0 && winding == 0 {
+                        let (mut x0, x1) = (from.max(0), x.min(w64));
+                        while x0 < x1 {
+                            let cell = (x0 >> 6) as usize;
+                            let next = ((x0 & !63) + 64).min(x1);
+                            self.coverage[cell] += (next - x0) as u32;
+                            x0 = next;
+                        }
+                    }
+                }
+            }
+            for (dst, &c) in line.iter_mut().zip(&self.coverage[..w]) {
+                *dst = (c * 255 / (64 * SUB_Y as u32)).min(255) as u8;
+            }
+        }
+    }
+}
+
+fn cell(font: &Font, gid: u16, px: u16) -> Option<(u16, u16)> {
+    if px == 0 || px > MAX_PX || gid >= font.num_glyphs {
+        return None;
+    }
+    Some((font.advance(gid, px), px))
+}
+
+/// One coordinate delta: `short` selects a byte whose sign is given by
+/// `same`, otherwise `same` repeats the previous coordinate.
+fn coordinate(data: &[u8], at: &mut usize, flag: u8, short: u8, same: u8) -> Option<i32> {
+    if flag & short != 0 {
+        let v = *data.get(*at)? as i32;
+        *at += 1;
+        Some(if flag & same != 0 { v } else { -v })
+    } else if flag & same != 0 {
+        Some(0)
+    } else {
+        let v = i16_at(data, *at)? as i32;
+        *at += 2;
+        Some(v)
+    }
+}
+
+/// Source-over of `color` at `coverage` onto a straight-alpha pixel.
+fn blend(dst: &mut [u8], color: [u8; 4], coverage: u8) {
+    let src_a = color[3] as u32 * coverage as u32 / 255;
+    if src_a == 0 {
+        return;
+    }
+    let dst_a = dst[3] as u32 * (255 - src_a) / 255;
+    let out_a = src_a + dst_a;
+    for c in 0..3 {
+        dst[c] = ((color[c] as u32 * src_a + dst[c] as u32 * dst_a) / out_a) as u8;
+    }
+    dst[3] = out_a as u8;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Big-endian writer over a fixed buffer.
+    struct Buf {
+        bytes: [u8; 1024],
+        len: usize,
+    }
+
+    impl Buf {
+        fn u16(&mut self, v: u16) -> &mut Self {
+            self.bytes[self.len..self.len + 2].copy_from_slice(&v.to_be_bytes());
+            self.len += 2;
+            self
+        }
+
+        fn i16(&mut self, v: i16) -> &mut Self {
+            self.u16(v as u16)
+        }
+
+        fn u32(&mut self, v: u32) -> &mut Self {
+            self.bytes[self.len..self.len + 4].copy_from_slice(&v.to_be_bytes());
+            self.len += 4;
+            self
+        }
+
+        fn raw(&mut self, v: &[u8]) -> &mut Self {
+            self.bytes[self.len..self.len + v.len()].copy_from_slice(v);
+            self.len += v.len();
+            self
+        }
+
+        fn set_u16(&mut self, at: usize, v: u16) {
+            self.bytes[at..at + 2].copy_from_slice(&v.to_be_bytes());
+        }
+
+        fn set_u32(&mut self, at: usize, v: u32) {
+            self.bytes[at..at + 4].copy_from_slice(&v.to_be_bytes());
+        }
+
+        /// Fills directory record `index` with the bytes since `start`.
+        fn table(&mut self, index: usize, tag: &[u8; 4], start: usize) {
+            let at = 12 + index * 16;
+            self.bytes[at..at + 4].copy_from_slice(tag);
+            self.set_u32(at + 8, start as u32);
+            self.set_u32(at + 12, (self.len - start) as u32);
+        }
+
+        /// One four-point contour.
+        fn quad_glyph(&mut self, flags: &[u8], xs: [i16; 4], ys: [i16; 4]) {
+            self.i16(1).raw(&[0; 8]).u16(3).u16(0).raw(flags);
+            xs.iter().for_each(|&x| _ = self.i16(x));
+            ys.iter().for_each(|&y| _ = self.i16(y));
+        }
+    }
+
+    const GLYPHS: u16 = 6;
+    const A_SQUARE: u16 = 1;
+    const ROUND: u16 = 2;
+    const EMOJI: u16 = 3;
+    const B_COMPOSITE: u16 = 4;
+    const SMALL: u16 = 5;
+
+    /// Em box 1000 units (ascender 800, descender -200); 'A' is a square
+    /// (100,0)-(500,400), 'B' the same square moved 100 units right (a
+    /// composite), 'C' a rounded shape made only of off-curve points, and
+    /// U+1F600 a color glyph: 'A' in red under a small square in the text
+    /// color. `format4` builds a BMP-only cmap without the emoji.
+    fn font(format4: bool) -> Buf {
+        let mut f = Buf {
+            bytes: [0; 1024],
+            len: 0,
+        };
+        f.u32(0x0001_0000).u16(9).raw(&[0; 6]).raw(&[0; 9 * 16]);
+
+        let head = f.len;
+        f.raw(&[0; 54]);
+        f.set_u16(head + 18, 1000);
+        f.set_u16(head + 50, 1);
+        f.table(0, b"head", head);
+
+        let hhea = f.len;
+        f.raw(&[0; 36]);
+        f.set_u16(hhea + 4, 800);
+        f.set_u16(hhea + 6, -200i16 as u16);
+        f.set_u16(hhea + 34, GLYPHS);
+        f.table(1, b"hhea", hhea);
+
+        let maxp = f.len;
+        f.u32(0x5000).u16(GLYPHS);
+        f.table(2, b"maxp", maxp);
+
+        let hmtx = f.len;
+        for advance in [500, 600, 600, 1000, 700, 600] {
+            f.u16(advance).u16(0);
+        }
+        f.table(3, b"hmtx", hmtx);
+
+        let glyf = f.len;
+        let mut loca = [0u32; GLYPHS as usize + 1];
+        loca[1] = 0;
+        f.quad_glyph(&[0x09, 3], [100, 400, 0, -400], [0, 0, 400, 0]);
+        loca[2] = (f.len - glyf) as u32;
+        f.quad_glyph(&[0; 4], [100, 400, 0, -400], [100, 0, 400, 0]);
+        loca[3] = (f.len - glyf) as u32;
+        loca[4] = loca[3];
+        f.i16(-1)
+            .raw(&[0; 8])
+            .u16(0x0003)
+            .u16(A_SQUARE)
+            .i16(100)
+            .i16(0);
+        loca[5] = (f.len - glyf) as u32;
+        f.quad_glyph(&[0x09, 3], [200, 100, 0, -100], [100, 0, 100, 0]);
+        loca[6] = (f.len - glyf) as u32;
+        f.table(4, b"glyf", glyf);
+
+        let loca_start = f.len;
+        loca.iter().for_each(|&offset| _ = f.u32(offset));
+        f.table(5, b"loca", loca_start);
+
+        let cmap = f.len;
+        if format4 {
+            f.u16(0).u16(1).u16(3).u16(1).u32(12);
+            f.u16(4).u16(0).u16(0).u16(8).raw(&[0; 6]);
+            f.u16(0x41).u16(0x42).u16(0x43).u16(0xffff).u16(0);
+            f.u16(0x41).u16(0x42).u16(0x43).u16(0xffff);
+            f.u16(1u16.wrapping_sub(0x41))
+                .u16(0)
+                .u16(2u16.wrapping_sub(0x43))
+                .u16(1);
+            f.u16(0).u16(6).u16(0).u16(0);
+            f.u16(B_COMPOSITE);
+        } else {
+            f.u16(0).u16(1).u16(3).u16(10).u32(12);
+            f.u16(12).u16(0).u32(0).u32(0).u32(4);
+            for (c, gid) in [(0x41, A_SQUARE), (0x42, B_COMPOSITE), (0x43, ROUND)] {
+                f.u32(c).u32(c).u32(gid as u32);
+            }
+            f.u32(0x1f600).u32(0x1f600).u32(EMOJI as u32);
+        }
+        f.table(6, b"cmap", cmap);
+
+        let colr = f.len;
+        f.u16(0).u16(1).u32(14).u32(20).u16(2);
+        f.u16(EMOJI).u16(0).u16(2);
+        f.u16(A_SQUARE).u16(0).u16(SMALL).u16(FOREGROUND);
+        f.table(7, b"COLR", colr);
+
+        let cpal = f.len;
+        f.u16(0).u16(1).u16(1).u16(1).u32(14).u16(0);
+        f.raw(&[0, 0, 255, 255]);
+        f.table(8, b"CPAL", cpal);
+        f
+    }
+
+    #[test]
+    fn maps_characters_through_both_cmap_formats() {
+        for format4 in [false, true] {
+            let data = font(format4);
+            let font = Font::parse(&data.bytes[..data.len]).unwrap();
+            assert_eq!(font.glyph_id('A'), Some(A_SQUARE));
+            assert_eq!(font.glyph_id('B'), Some(B_COMPOSITE));
+            assert_eq!(font.glyph_id('C'), Some(ROUND));
+            assert_eq!(font.glyph_id('D'), None);
+            let emoji = font.glyph_id('😀');
+            assert_eq!(emoji, (!format4).then_some(EMOJI));
+        }
+        let data = font(false);
+        let font = Font::parse(&data.bytes[..data.len]).unwrap();
+        assert_eq!(font.advance(A_SQUARE, 20), 12);
+        assert_eq!(font.advance(EMOJI, 20), 20);
+        assert!(font.is_color(EMOJI));
+        assert!(!font.is_color(A_SQUARE));
+        assert_eq!(
+            Font::parse(b"OTTO\0\0\0\0").err(),
+            Some(SfntError::NotTrueType)
+        );
+        assert_eq!(
+            Font::parse(&data.bytes[..3]).err(),
+            Some(SfntError::Truncated)
+        );
+    }
+
+    #[test]
+    fn rasterizes_lines_curves_and_composites() {
+        let data = font(false);
+        let font = Font::parse(&data.bytes[..data.len]).unwrap();
+        let mut r = Rasterizer::new();
+        let mut out = [0u8; 20 * 20];
+
+        assert_eq!(r.glyph(&font, A_SQUARE, 20, &mut out), Some((12, 20)));
+        let at = |x: usize, y: usize| y * 12 + x;
+        assert_eq!(out[at(5, 9)], 255);
+        assert_eq!(out[at(2, 8)], 255);
+        assert_eq!(out[at(9, 15)], 255);
+        assert_eq!(out[at(1, 9)], 0);
+        assert_eq!(out[at(5, 7)], 0);
+        assert_eq!(out[at(5, 16)], 0);
+        let total: u32 = out[..12 * 20].iter().map(|&a| a as u32).sum();
+        assert_eq!(total, 8 * 8 * 255);
+
+        assert_eq!(r.glyph(&font, B_COMPOSITE, 20, &mut out), Some((14, 20)));
+        assert_eq!(out[9 * 14 + 11], 255);
+        assert_eq!(out[9 * 14 + 3], 0);
+
+        assert_eq!(r.glyph(&font, ROUND, 20, &mut out), Some((12, 20)));
+        assert_eq!(out[at(6, 10)], 255);
+        assert_eq!(out[at(2, 13)], 0);
+        assert_eq!(out[at(1, 10)], 0);
+        assert!(out[at(3, 10)] > 0);
+
+        assert_eq!(r.glyph(&font, A_SQUARE, 20, &mut out[..10]), None);
+        assert_eq!(r.glyph(&font, A_SQUARE, MAX_PX + 1, &mut out), None);
+        assert_eq!(r.glyph(&font, GLYPHS, 20, &mut out), None);
+    }
+
+    #[test]
+    fn composites_color_layers() {
+        let data = font(false);
+        let font = Font::parse(&data.bytes[..data.len]).unwrap();
+        let mut r = Rasterizer::new();
+        let mut out = [0u8; 20 * 20 * 4];
+        let blue = [0, 0, 255, 255];
+        assert_eq!(
+            r.color_glyph(&font, EMOJI, 20, blue, &mut out),
+            Some((20, 20))
+        );
+        let at = |x: usize, y: usize| &out[(y * 20 + x) * 4..][..4];
+        assert_eq!(at(8, 9), [255, 0, 0, 255]);
+        assert_eq!(at(5, 13), blue);
+        assert_eq!(at(0, 0), [0; 4]);
+        assert_eq!(r.color_glyph(&font, A_SQUARE, 20, blue, &mut out), None);
+    }
+
+    #[test]
+    fn corrupted_fonts_never_panic() {
+        let clean = font(false);
+        let mut r = Rasterizer::new();
+        let mut out = [0u8; 32 * 32 * 4];
+        for i in 0..clean.len {
+            for flip in [0x01, 0x80, 0xff] {
+                let mut data = clean.bytes;
+                data[i] ^= flip;
+                for len in [clean.len, i] {
+                    let Ok(font) = Font::parse(&data[..len]) else {
+                        continue;
+                    };
+                    for ch in ['A', 'B', 'C', '😀'] {
+                        let gid = font.glyph_id(ch).unwrap_or(1);
+                        let _ = r.glyph(&font, gid, 32, &mut out);
+                        let _ = r.color_glyph(&font, gid, 32, [255; 4], &mut out);
+                    }
+                }
+            }
+        }
+    }
+}
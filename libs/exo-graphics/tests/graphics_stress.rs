@@ -3,3 +3,30 @@ fn graphics_ports_stress() {
     assert_eq!(exo_graphics::GRAPHICS_PORTS.len(), 3);
     assert_ne!(exo_graphics::graphics_stress_signature(100_000), 0);
 }
+
+#[test]
+fn atlas_fills_and_resolves_every_entry() {
+    use exo_graphics::atlas::{AtlasView, AtlasWriter, ATLAS_BYTES, MAX_ENTRIES};
+
+    let mut buf = vec![0u8; ATLAS_BYTES];
+    let mut generation = 0;
+    for round in 0..4u32 {
+        let mut w = AtlasWriter::begin(&mut buf, generation).unwrap();
+        // Pseudo-random insertion order over the whole table.
+        for i in 0..MAX_ENTRIES as u32 {
+            let codepoint = (i * 769 + round) % MAX_ENTRIES as u32;
+            w.add_glyph(codepoint, 16, 2, 2, &[codepoint as u8; 4])
+                .unwrap();
+        }
+        assert!(w.add_glyph(u32::MAX, 16, 1, 1, &[0]).is_err());
+        generation = w.finish();
+
+        let view = AtlasView::open(&buf).unwrap();
+        assert_eq!(view.generation(), generation);
+        for codepoint in 0..MAX_ENTRIES as u32 {
+            let sprite = view.glyph(codepoint, 16).unwrap();
+            assert_eq!(sprite.pixels, &[codepoint as u8; 4]);
+        }
+    }
+    assert_eq!(generation, 8);
+}
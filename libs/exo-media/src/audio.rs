@@ -0,0 +1,220 @@
+//! Streaming audio decode for the native player and notification sounds:
+//! compressed bytes go in, interleaved signed 16-bit PCM comes out and is
+//! written to an audio-service stream.
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PcmFormat {
+    pub sample_rate: u32,
+    pub channels: u8,
+}
+
+impl PcmFormat {
+    /// Duration of `frames` sample frames, in nanoseconds.
+    pub fn frames_ns(&self, frames: u64) -> u64 {
+        if self.sample_rate == 0 {
+            return 0;
+        }
+        (frames as u128 * 1_000_000_000 / self.sample_rate as u128) as u64
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AudioError {
+    /// The input stops inside a header or frame: call again with more bytes.
+    NeedMoreData,
+    /// A well-formed stream this decoder does not handle (MPEG layer I/II,
+    /// free-format MP3, FLAC blocks larger than the subset limit...).
+    Unsupported,
+    /// `pcm` cannot hold the next frame, see `AudioDecoder::max_samples`.
+    BufferTooSmall,
+    /// The audio-service stream refused the samples.
+    SinkClosed,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Decoded {
+    /// Input bytes used up; the caller drops them before the next call.
+    pub consumed: usize,
+    /// Sample frames (one sample per channel) written to `pcm`.
+    pub frames: usize,
+}
+
+pub trait AudioDecoder {
+    /// Format of the last decoded frame, `None` before the first one.
+    fn format(&self) -> Option<PcmFormat>;
+
+    /// Interleaved samples a single `decode` call may write.
+    fn max_samples(&self) -> usize;
+
+    /// Decodes at most one frame from the front of `input`. Metadata, tags
+    /// and corrupt frames are consumed with `frames == 0`, so a damaged
+    /// file resynchronises instead of stopping playback.
+    fn decode(&mut self, input: &[u8], pcm: &mut [i16]) -> Result<Decoded, AudioError>;
+}
+
+/// Receiving end of decoded audio; in the system this is the client side of
+/// an audio-service stream.
+pub trait PcmSink {
+    fn write(&mut self, format: PcmFormat, pcm: &[i16]) -> Result<(), AudioError>;
+}
+
+/// Decodes every complete frame of `input` into `sink` and returns the
+/// number of bytes consumed. The tail that was not consumed belongs in front
+/// of the next read.
+pub fn pump<D, S>(
+    decoder: &mut D,
+    input: &[u8],
+    pcm: &mut [i16],
+    sink: &mut S,
+) -> Result<usize, AudioError>
+where
+    D: AudioDecoder + ?Sized,
+    S: PcmSink + ?Sized,
+{
+    let mut pos = 0;
+    while pos < input.len() {
+        let decoded = match decoder.decode(&input[pos..], pcm) {
+            Ok(decoded) => decoded,
+            Err(AudioError::NeedMoreData) => break,
+            Err(err) => return Err(err),
+        };
+        pos += decoded.consumed;
+        if decoded.frames > 0 {
+            let format = decoder.format().ok_or(AudioError::Unsupported)?;
+            sink.write(format, &pcm[..decoded.frames * format.channels as usize])?;
+        } else if decoded.consumed == 0 {
+            break;
+        }
+    }
+    Ok(pos)
+}
+
+/// Fixed read buffer between the file (or IPC) reads and `pump`.
+pub struct ByteQueue<const N: usize> {
+    buf: [u8; N],
+    start: usize,
+    end: usize,
+}
+
+impl<const N: usize> ByteQueue<N> {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            start: 0,
+            end: 0,
+        }
+    }
+
+    /// Bytes received but not consumed yet.
+    pub fn pending(&self) -> &[u8] {
+        &self.buf[self.start..self.end]
+    }
+
+    pub fn consume(&mut self, n: usize) {
+        self.start = (self.start + n).min(self.end);
+    }
+
+    /// Free space to read into, after moving the pending bytes to the front.
+    /// Empty when a single frame fills the whole queue.
+    pub fn spare_mut(&mut self) -> &mut [u8] {
+        if self.start > 0 {
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        &mut self.buf[self.end..]
+    }
+
+    /// Marks `n` bytes of `spare_mut` as filled.
+    pub fn commit(&mut self, n: usize) {
+        self.end = (self.end + n).min(N);
+    }
+}
+
+impl<const N: usize> Default for ByteQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two-byte "frames": a length byte then a sample value.
+    struct Toy;
+
+    impl AudioDecoder for Toy {
+        fn format(&self) -> Option<PcmFormat> {
+            Some(PcmFormat {
+                sample_rate: 8000,
+                channels: 1,
+            })
+        }
+
+        fn max_samples(&self) -> usize {
+            4
+        }
+
+        fn decode(&mut self, input: &[u8], pcm: &mut [i16]) -> Result<Decoded, AudioError> {
+            match input {
+                [0, ..] => Ok(Decoded {
+                    consumed: 1,
+                    frames: 0,
+                }),
+                [n, v, ..] => {
+                    pcm[..*n as usize].fill(*v as i16);
+                    Ok(Decoded {
+                        consumed: 2,
+                        frames: *n as usize,
+                    })
+                }
+                _ => Err(AudioError::NeedMoreData),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Collect {
+        samples: usize,
+        last: i16,
+    }
+
+    impl PcmSink for Collect {
+        fn write(&mut self, _: PcmFormat, pcm: &[i16]) -> Result<(), AudioError> {
+            self.samples += pcm.len();
+            self.last = *pcm.last().unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pump_stops_at_partial_frame() {
+        let mut sink = Collect::default();
+        let mut pcm = [0; 4];
+        let consumed = pump(&mut Toy, &[2, 5, 0, 3, 7, 4], &mut pcm, &mut sink).unwrap();
+        assert_eq!(consumed, 5);
+        assert_eq!(sink.samples, 5);
+        assert_eq!(sink.last, 7);
+    }
+
+    #[test]
+    fn byte_queue_compacts_pending_bytes() {
+        let mut queue = ByteQueue::<8>::new();
+        queue.spare_mut()[..6].copy_from_slice(b"abcdef");
+        queue.commit(6);
+        queue.consume(4);
+        assert_eq!(queue.pending(), b"ef");
+        assert_eq!(queue.spare_mut().len(), 6);
+        assert_eq!(queue.pending(), b"ef");
+    }
+
+    #[test]
+    fn frames_ns_uses_sample_rate() {
+        let format = PcmFormat {
+            sample_rate: 48_000,
+            channels: 2,
+        };
+        assert_eq!(format.frames_ns(960), 20_000_000);
+    }
+}
@@ -0,0 +1,112 @@
+//! Audio/video synchronisation. The audio service clock is the master:
+//! video frames are presented, held or dropped against it.
+
+/// Frames closer than this to the clock are presented immediately.
+pub const SYNC_THRESHOLD_NS: u64 = 10_000_000;
+/// A frame later than this (or one frame duration, if longer) is dropped.
+pub const DROP_THRESHOLD_NS: u64 = 40_000_000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FrameAction {
+    Present,
+    /// Too early: wait this many nanoseconds before presenting.
+    Wait(u64),
+    /// Too late to be worth showing.
+    Drop,
+}
+
+/// Media timeline anchored on the last position reported by the audio
+/// service, extrapolated with the monotonic clock between reports.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MediaClock {
+    anchor_media_ns: u64,
+    anchor_mono_ns: u64,
+    paused: bool,
+}
+
+impl MediaClock {
+    /// Starts a clock without audio: media time 0 at `mono_ns`.
+    pub const fn start(mono_ns: u64) -> Self {
+        Self {
+            anchor_media_ns: 0,
+            anchor_mono_ns: mono_ns,
+            paused: false,
+        }
+    }
+
+    /// Audio service reported that sample at `media_ns` was played at `mono_ns`.
+    pub fn sync_to_audio(&mut self, media_ns: u64, mono_ns: u64) {
+        self.anchor_media_ns = media_ns;
+        self.anchor_mono_ns = mono_ns;
+    }
+
+    pub fn pause(&mut self, mono_ns: u64) {
+        if !self.paused {
+            self.anchor_media_ns = self.now(mono_ns);
+            self.anchor_mono_ns = mono_ns;
+            self.paused = true;
+        }
+    }
+
+    pub fn resume(&mut self, mono_ns: u64) {
+        if self.paused {
+            self.anchor_mono_ns = mono_ns;
+            self.paused = false;
+        }
+    }
+
+    pub fn now(&self, mono_ns: u64) -> u64 {
+        if self.paused {
+            return self.anchor_media_ns;
+        }
+        self.anchor_media_ns + mono_ns.saturating_sub(self.anchor_mono_ns)
+    }
+
+    pub fn decide(&self, pts_ns: u64, frame_duration_ns: u64, mono_ns: u64) -> FrameAction {
+        decide(pts_ns, self.now(mono_ns), frame_duration_ns)
+    }
+}
+
+pub fn decide(pts_ns: u64, clock_ns: u64, frame_duration_ns: u64) -> FrameAction {
+    if pts_ns > clock_ns + SYNC_THRESHOLD_NS {
+        return FrameAction::Wait(pts_ns - clock_ns);
+    }
+    if clock_ns > pts_ns + DROP_THRESHOLD_NS.max(frame_duration_ns) {
+        return FrameAction::Drop;
+    }
+    FrameAction::Present
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_the_audio_clock() {
+        let mut clock = MediaClock::start(1_000);
+        assert_eq!(clock.now(1_000 + 5_000_000), 5_000_000);
+        // Audio is behind the monotonic estimate: video must wait for it.
+        clock.sync_to_audio(100_000_000, 200_000_000);
+        assert_eq!(
+            clock.decide(150_000_000, 33_000_000, 200_000_000),
+            FrameAction::Wait(50_000_000)
+        );
+        assert_eq!(
+            clock.decide(105_000_000, 33_000_000, 200_000_000),
+            FrameAction::Present
+        );
+        assert_eq!(
+            clock.decide(40_000_000, 33_000_000, 200_000_000),
+            FrameAction::Drop
+        );
+    }
+
+    #[test]
+    fn paused_clock_stands_still() {
+        let mut clock = MediaClock::start(0);
+        clock.pause(10);
+        assert_eq!(clock.now(1_000_000), 10);
+        clock.resume(1_000_000);
+        assert_eq!(clock.now(1_000_005), 15);
+    }
+}
@@ -0,0 +1,163 @@
+//! Zero-copy frame handoff. Decoded frames live in shared-memory buffers
+//! that are passed to the compositor by handle; a buffer returns to the
+//! decoder only when the compositor releases it.
+
+pub const MAX_FRAME_BUFFERS: usize = 8;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BufferState {
+    Free,
+    /// Being written by the decoder / converter.
+    Decoding,
+    /// Complete, waiting for its presentation time.
+    Ready {
+        pts_ns: u64,
+    },
+    /// Attached to a compositor surface; must not be written.
+    OnScreen,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BufferError {
+    Exhausted,
+    BadId,
+    WrongState,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Slot {
+    shm_handle: u64,
+    state: BufferState,
+}
+
+/// Fixed set of shared-memory buffers allocated once per stream.
+#[derive(Clone, Copy, Debug)]
+pub struct FramePool {
+    slots: [Slot; MAX_FRAME_BUFFERS],
+    count: usize,
+}
+
+impl FramePool {
+    pub fn new(shm_handles: &[u64]) -> Result<Self, BufferError> {
+        if shm_handles.is_empty() || shm_handles.len() > MAX_FRAME_BUFFERS {
+            return Err(BufferError::Exhausted);
+        }
+        let mut slots = [Slot {
+            shm_handle: 0,
+            state: BufferState::Free,
+        }; MAX_FRAME_BUFFERS];
+        for (slot, &handle) in slots.iter_mut().zip(shm_handles) {
+            slot.shm_handle = handle;
+        }
+        Ok(Self {
+            slots,
+            count: shm_handles.len(),
+        })
+    }
+
+    pub fn state(&self, id: usize) -> Option<BufferState> {
+        self.slots[..self.count].get(id).map(|s| s.state)
+    }
+
+    pub fn shm_handle(&self, id: usize) -> Option<u64> {
+        self.slots[..self.count].get(id).map(|s| s.shm_handle)
+    }
+
+    /// Claims a free buffer for the decoder.
+    pub fn acquire(&mut self) -> Result<usize, BufferError> {
+        let id = self.slots[..self.count]
+            .iter()
+            .position(|s| s.state == BufferState::Free)
+            .ok_or(BufferError::Exhausted)?;
+        self.slots[id].state = BufferState::Decoding;
+        Ok(id)
+    }
+
+    pub fn mark_ready(&mut self, id: usize, pts_ns: u64) -> Result<(), BufferError> {
+        self.transition(id, BufferState::Decoding, BufferState::Ready { pts_ns })
+    }
+
+    /// Earliest ready frame by presentation time.
+    pub fn next_ready(&self) -> Option<(usize, u64)> {
+        self.slots[..self.count]
+            .iter()
+            .enumerate()
+            .filter_map(|(id, s)| match s.state {
+                BufferState::Ready { pts_ns } => Some((id, pts_ns)),
+                _ => None,
+            })
+            .min_by_key(|&(_, pts)| pts)
+    }
+
+    /// Hands the buffer to the compositor. Returns the handle to attach.
+    pub fn attach(&mut self, id: usize) -> Result<u64, BufferError> {
+        match self.state(id) {
+            Some(BufferState::Ready { .. }) => {
+                self.slots[id].state = BufferState::OnScreen;
+                Ok(self.slots[id].shm_handle)
+            }
+            Some(_) => Err(BufferError::WrongState),
+            None => Err(BufferError::BadId),
+        }
+    }
+
+    /// Frame dropped by A/V sync before reaching the screen.
+    pub fn discard(&mut self, id: usize) -> Result<(), BufferError> {
+        match self.state(id) {
+            Some(BufferState::Ready { .. } | BufferState::Decoding) => {
+                self.slots[id].state = BufferState::Free;
+                Ok(())
+            }
+            Some(_) => Err(BufferError::WrongState),
+            None => Err(BufferError::BadId),
+        }
+    }
+
+    /// Compositor released the buffer (`wl_buffer.release`).
+    pub fn release(&mut self, shm_handle: u64) -> Result<(), BufferError> {
+        let id = self.slots[..self.count]
+            .iter()
+            .position(|s| s.shm_handle == shm_handle)
+            .ok_or(BufferError::BadId)?;
+        self.transition(id, BufferState::OnScreen, BufferState::Free)
+    }
+
+    fn transition(
+        &mut self,
+        id: usize,
+        from: BufferState,
+        to: BufferState,
+    ) -> Result<(), BufferError> {
+        let slot = self.slots[..self.count]
+            .get_mut(id)
+            .ok_or(BufferError::BadId)?;
+        if slot.state != from {
+            return Err(BufferError::WrongState);
+        }
+        slot.state = to;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffers_cycle_through_the_compositor() {
+        let mut pool = FramePool::new(&[0x10, 0x11]).unwrap();
+        let a = pool.acquire().unwrap();
+        let b = pool.acquire().unwrap();
+        assert_eq!(pool.acquire(), Err(BufferError::Exhausted));
+        pool.mark_ready(b, 2_000).unwrap();
+        pool.mark_ready(a, 1_000).unwrap();
+        assert_eq!(pool.next_ready(), Some((a, 1_000)));
+        assert_eq!(pool.attach(a), Ok(0x10));
+        // On-screen buffers are never handed back to the decoder.
+        assert_eq!(pool.discard(a), Err(BufferError::WrongState));
+        pool.discard(b).unwrap();
+        assert_eq!(pool.acquire(), Ok(b));
+        pool.release(0x10).unwrap();
+        assert_eq!(pool.state(a), Some(BufferState::Free));
+    }
+}
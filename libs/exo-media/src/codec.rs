@@ -0,0 +1,89 @@
+//! Codec identification for the software decode path.
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Codec {
+    Vp9,
+    Av1,
+    /// H.264 constrained baseline profile only.
+    H264Baseline,
+}
+
+impl Codec {
+    pub fn from_fourcc(fourcc: &[u8; 4]) -> Option<Self> {
+        match fourcc {
+            b"VP90" | b"vp09" => Some(Codec::Vp9),
+            b"AV01" | b"av01" => Some(Codec::Av1),
+            b"H264" | b"avc1" => Some(Codec::H264Baseline),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Codec::Vp9 => "vp9",
+            Codec::Av1 => "av1",
+            Codec::H264Baseline => "h264-baseline",
+        }
+    }
+}
+
+/// H.264 `profile_idc` values the baseline decoder accepts: baseline (66)
+/// and constrained baseline signalled through `constraint_set1_flag`.
+pub fn h264_profile_supported(profile_idc: u8, constraint_flags: u8) -> bool {
+    profile_idc == 66 || (profile_idc == 77 && constraint_flags & 0x40 != 0)
+}
+
+/// Splits an Annex-B byte stream into NAL units (start codes removed).
+pub fn annexb_nal_units(stream: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut pos = find_start_code(stream, 0).map_or(stream.len(), |(_, end)| end);
+    core::iter::from_fn(move || {
+        if pos >= stream.len() {
+            return None;
+        }
+        let start = pos;
+        let (end, next) = match find_start_code(stream, start) {
+            Some((code_start, code_end)) => (code_start, code_end),
+            None => (stream.len(), stream.len()),
+        };
+        pos = next;
+        let mut nal = &stream[start..end];
+        // Trailing zero bytes belong to the next 4-byte start code.
+        while let Some(rest) = nal.strip_suffix(&[0]) {
+            nal = rest;
+        }
+        Some(nal)
+    })
+}
+
+fn find_start_code(stream: &[u8], from: usize) -> Option<(usize, usize)> {
+    stream
+        .get(from..)?
+        .windows(3)
+        .position(|w| w == [0, 0, 1])
+        .map(|i| (from + i, from + i + 3))
+}
+
+/// `nal_unit_type` of an H.264 NAL unit header.
+pub fn h264_nal_type(nal: &[u8]) -> Option<u8> {
+    nal.first().map(|b| b & 0x1f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_annexb_stream() {
+        let stream = [
+            0, 0, 0, 1, 0x67, 66, 0xc0, 0, 0, 1, 0x68, 0xce, 0, 0, 0, 1, 0x65, 0x88,
+        ];
+        let mut nals = annexb_nal_units(&stream);
+        let sps = nals.next().unwrap();
+        assert_eq!(h264_nal_type(sps), Some(7));
+        assert!(h264_profile_supported(sps[1], sps[2]));
+        assert_eq!(nals.next(), Some(&[0x68, 0xce][..]));
+        assert_eq!(h264_nal_type(nals.next().unwrap()), Some(5));
+        assert_eq!(nals.next(), None);
+        assert!(!h264_profile_supported(100, 0));
+    }
+}
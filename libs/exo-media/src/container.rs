@@ -0,0 +1,150 @@
+//! IVF demuxer, the raw container VP9 and AV1 elementary streams ship in.
+
+use crate::codec::Codec;
+
+pub const IVF_HEADER_LEN: usize = 32;
+const IVF_FRAME_HEADER_LEN: usize = 12;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IvfError {
+    BadMagic,
+    Truncated,
+    UnknownCodec([u8; 4]),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IvfHeader {
+    pub codec: Codec,
+    pub width: u16,
+    pub height: u16,
+    pub timebase_num: u32,
+    pub timebase_den: u32,
+    pub frame_count: u32,
+}
+
+impl IvfHeader {
+    /// Converts a timestamp in stream timebase units to nanoseconds.
+    pub fn pts_ns(&self, pts: u64) -> u64 {
+        if self.timebase_den == 0 {
+            return 0;
+        }
+        (pts as u128 * self.timebase_num as u128 * 1_000_000_000 / self.timebase_den as u128) as u64
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IvfFrame<'a> {
+    pub pts: u64,
+    pub data: &'a [u8],
+}
+
+fn le16(b: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([b[at], b[at + 1]])
+}
+
+fn le32(b: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]])
+}
+
+pub struct IvfReader<'a> {
+    header: IvfHeader,
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> IvfReader<'a> {
+    pub fn new(data: &'a [u8]) -> Result<Self, IvfError> {
+        if data.len() < IVF_HEADER_LEN {
+            return Err(IvfError::Truncated);
+        }
+        if &data[..4] != b"DKIF" {
+            return Err(IvfError::BadMagic);
+        }
+        let header_len = le16(data, 6) as usize;
+        if header_len < IVF_HEADER_LEN || header_len > data.len() {
+            return Err(IvfError::Truncated);
+        }
+        let fourcc = [data[8], data[9], data[10], data[11]];
+        let codec = Codec::from_fourcc(&fourcc).ok_or(IvfError::UnknownCodec(fourcc))?;
+        Ok(Self {
+            header: IvfHeader {
+                codec,
+                width: le16(data, 12),
+                height: le16(data, 14),
+                timebase_den: le32(data, 16),
+                timebase_num: le32(data, 20),
+                frame_count: le32(data, 24),
+            },
+            data,
+            pos: header_len,
+        })
+    }
+
+    pub fn header(&self) -> &IvfHeader {
+        &self.header
+    }
+
+    pub fn next_frame(&mut self) -> Option<Result<IvfFrame<'a>, IvfError>> {
+        if self.pos == self.data.len() {
+            return None;
+        }
+        let rest = &self.data[self.pos..];
+        if rest.len() < IVF_FRAME_HEADER_LEN {
+            self.pos = self.data.len();
+            return Some(Err(IvfError::Truncated));
+        }
+        let size = le32(rest, 0) as usize;
+        let pts = le32(rest, 4) as u64 | (le32(rest, 8) as u64) << 32;
+        let Some(data) = rest.get(IVF_FRAME_HEADER_LEN..IVF_FRAME_HEADER_LEN + size) else {
+            self.pos = self.data.len();
+            return Some(Err(IvfError::Truncated));
+        };
+        self.pos += IVF_FRAME_HEADER_LEN + size;
+        Some(Ok(IvfFrame { pts, data }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_header_and_frames() {
+        let mut file = [0u8; IVF_HEADER_LEN + 2 * IVF_FRAME_HEADER_LEN + 5];
+        file[..4].copy_from_slice(b"DKIF");
+        file[6] = IVF_HEADER_LEN as u8;
+        file[8..12].copy_from_slice(b"VP90");
+        file[12..14].copy_from_slice(&640u16.to_le_bytes());
+        file[14..16].copy_from_slice(&360u16.to_le_bytes());
+        file[16..20].copy_from_slice(&30u32.to_le_bytes());
+        file[20..24].copy_from_slice(&1u32.to_le_bytes());
+        file[24] = 2;
+        let f = IVF_HEADER_LEN;
+        file[f] = 3;
+        file[f + IVF_FRAME_HEADER_LEN..f + IVF_FRAME_HEADER_LEN + 3].copy_from_slice(b"abc");
+        let g = f + IVF_FRAME_HEADER_LEN + 3;
+        file[g] = 2;
+        file[g + 4] = 1;
+
+        let mut reader = IvfReader::new(&file).unwrap();
+        let header = *reader.header();
+        assert_eq!(header.codec, Codec::Vp9);
+        assert_eq!((header.width, header.height), (640, 360));
+        assert_eq!(header.pts_ns(1), 33_333_333);
+        assert_eq!(
+            reader.next_frame(),
+            Some(Ok(IvfFrame {
+                pts: 0,
+                data: b"abc"
+            }))
+        );
+        assert_eq!(reader.next_frame().unwrap().unwrap().pts, 1);
+        assert_eq!(reader.next_frame(), None);
+
+        file[8..12].copy_from_slice(b"XVID");
+        assert!(matches!(
+            IvfReader::new(&file),
+            Err(IvfError::UnknownCodec(_))
+        ));
+    }
+}
@@ -0,0 +1,852 @@
+//! Native FLAC stream decoder: `fLaC` marker, metadata blocks and audio
+//! frames with every subframe type, Rice-coded residuals, inter-channel
+//! decorrelation and the CRC-8/CRC-16 checks. Samples are scaled to 16
+//! bits for the audio service.
+
+use crate::audio::{AudioDecoder, AudioError, Decoded, PcmFormat};
+
+/// Largest block the FLAC streamable subset allows.
+pub const MAX_BLOCK_SIZE: usize = 16384;
+pub const MAX_CHANNELS: usize = 8;
+const MAX_LPC_ORDER: usize = 32;
+const BLOCK_STREAMINFO: u8 = 0;
+const STREAMINFO_LEN: usize = 34;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StreamInfo {
+    pub min_block_size: u16,
+    pub max_block_size: u16,
+    /// Largest frame in bytes, 0 if the encoder did not know.
+    pub max_frame_size: u32,
+    pub sample_rate: u32,
+    pub channels: u8,
+    pub bits_per_sample: u8,
+    /// Samples per channel, 0 if unknown.
+    pub total_samples: u64,
+    pub md5: [u8; 16],
+}
+
+impl StreamInfo {
+    fn parse(b: &[u8; STREAMINFO_LEN]) -> Result<Self, AudioError> {
+        let be = |range: core::ops::Range<usize>| {
+            b[range].iter().fold(0u64, |acc, &x| acc << 8 | x as u64)
+        };
+        let packed = be(10..18);
+        let mut md5 = [0; 16];
+        md5.copy_from_slice(&b[18..34]);
+        let info = Self {
+            min_block_size: be(0..2) as u16,
+            max_block_size: be(2..4) as u16,
+            max_frame_size: be(7..10) as u32,
+            sample_rate: (packed >> 44) as u32,
+            channels: (packed >> 41 & 7) as u8 + 1,
+            bits_per_sample: (packed >> 36 & 31) as u8 + 1,
+            total_samples: packed & 0xf_ffff_ffff,
+            md5,
+        };
+        if info.max_block_size as usize > MAX_BLOCK_SIZE || info.bits_per_sample > 24 {
+            return Err(AudioError::Unsupported);
+        }
+        Ok(info)
+    }
+
+    /// Stream duration in nanoseconds, 0 if the sample count is unknown.
+    pub fn duration_ns(&self) -> u64 {
+        PcmFormat {
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+        }
+        .frames_ns(self.total_samples)
+    }
+}
+
+enum Failure {
+    /// The frame runs past the end of the input.
+    Short,
+    Corrupt,
+    Unsupported,
+    BufferTooSmall,
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// Position in bits.
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bit(&mut self) -> Result<u32, Failure> {
+        let byte = *self.data.get(self.pos / 8).ok_or(Failure::Short)?;
+        let bit = byte >> (7 - self.pos % 8) & 1;
+        self.pos += 1;
+        Ok(bit as u32)
+    }
+
+    /// Reads `n` (0..=32) bits, most significant first.
+    fn read(&mut self, n: u32) -> Result<u32, Failure> {
+        if self.pos + n as usize > self.data.len() * 8 {
+            return Err(Failure::Short);
+        }
+        let mut value = 0u64;
+        let mut left = n;
+        while left > 0 {
+            let byte = self.data[self.pos / 8];
+            let offset = (self.pos % 8) as u32;
+            let take = (8 - offset).min(left);
+            let bits = (byte >> (8 - offset - take)) as u64 & ((1 << take) - 1);
+            value = value << take | bits;
+            self.pos += take as usize;
+            left -= take;
+        }
+        Ok(value as u32)
+    }
+
+    fn read_signed(&mut self, n: u32) -> Result<i32, Failure> {
+        if n == 0 {
+            return Ok(0);
+        }
+        let v = self.read(n)?;
+        Ok(((v << (32 - n)) as i32) >> (32 - n))
+    }
+
+    /// Counts zero bits up to and including the terminating one.
+    fn unary(&mut self) -> Result<u32, Failure> {
+        let mut zeros = 0;
+        loop {
+            let byte = *self.data.get(self.pos / 8).ok_or(Failure::Short)?;
+            let rest = (byte << (self.pos % 8)) as u32;
+            if rest != 0 {
+                let lz = rest.leading_zeros() - 24;
+                self.pos += lz as usize + 1;
+                return Ok(zeros + lz);
+            }
+            let skipped = 8 - self.pos % 8;
+            zeros += skipped as u32;
+            self.pos += skipped;
+        }
+    }
+
+    fn align(&mut self) {
+        self.pos = self.pos.div_ceil(8) * 8;
+    }
+
+    fn byte_pos(&self) -> usize {
+        self.pos / 8
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |mut crc, &b| {
+        crc ^= b;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                crc << 1 ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |mut crc, &b| {
+        crc ^= (b as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                crc << 1 ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ChannelLayout {
+    Independent(u8),
+    LeftSide,
+    SideRight,
+    MidSide,
+}
+
+impl ChannelLayout {
+    fn channels(self) -> usize {
+        match self {
+            ChannelLayout::Independent(n) => n as usize,
+            _ => 2,
+        }
+    }
+
+    /// The side channel needs one more bit than the others.
+    fn is_side(self, ch: usize) -> bool {
+        matches!(
+            (self, ch),
+            (ChannelLayout::LeftSide, 1)
+                | (ChannelLayout::SideRight, 0)
+                | (ChannelLayout::MidSide, 1)
+        )
+    }
+}
+
+struct FrameHeader {
+    block_size: usize,
+    sample_rate: u32,
+    layout: ChannelLayout,
+    bits_per_sample: u32,
+}
+
+fn is_sync(b: &[u8]) -> bool {
+    b.len() >= 2 && b[0] == 0xff && b[1] & 0xfe == 0xf8
+}
+
+fn read_frame_header(r: &mut BitReader, info: &StreamInfo) -> Result<FrameHeader, Failure> {
+    if r.read(15)? != 0x7ffc {
+        return Err(Failure::Corrupt);
+    }
+    let _variable_blocks = r.bit()?;
+    let bs_code = r.read(4)?;
+    let sr_code = r.read(4)?;
+    let ch_code = r.read(4)?;
+    let size_code = r.read(3)?;
+    if r.bit()? != 0 {
+        return Err(Failure::Corrupt);
+    }
+    // Frame or sample number, UTF-8 style; only its length matters here.
+    let first = r.read(8)?;
+    let extra = match (first as u8).leading_ones() {
+        0 => 0,
+        n @ 2..=7 => n - 1,
+        _ => return Err(Failure::Corrupt),
+    };
+    for _ in 0..extra {
+        if r.read(8)? & 0xc0 != 0x80 {
+            return Err(Failure::Corrupt);
+        }
+    }
+
+    let block_size = match bs_code {
+        0 => return Err(Failure::Corrupt),
+        1 => 192,
+        2..=5 => 576 << (bs_code - 2),
+        6 => r.read(8)? as usize + 1,
+        7 => r.read(16)? as usize + 1,
+        _ => 256 << (bs_code - 8),
+    };
+    const RATES: [u32; 12] = [
+        0, 88200, 176400, 192000, 8000, 16000, 22050, 24000, 32000, 44100, 48000, 96000,
+    ];
+    let sample_rate = match sr_code {
+        0 => info.sample_rate,
+        1..=11 => RATES[sr_code as usize],
+        12 => r.read(8)? * 1000,
+        13 => r.read(16)?,
+        14 => r.read(16)? * 10,
+        _ => return Err(Failure::Corrupt),
+    };
+    let layout = match ch_code {
+        0..=7 => ChannelLayout::Independent(ch_code as u8 + 1),
+        8 => ChannelLayout::LeftSide,
+        9 => ChannelLayout::SideRight,
+        10 => ChannelLayout::MidSide,
+        _ => return Err(Failure::Corrupt),
+    };
+    let bits_per_sample = match size_code {
+        0 => info.bits_per_sample as u32,
+        1 => 8,
+        2 => 12,
+        4 => 16,
+        5 => 20,
+        6 => 24,
+        7 => return Err(Failure::Unsupported),
+        _ => return Err(Failure::Corrupt),
+    };
+    let header_len = r.byte_pos();
+    let crc = r.read(8)? as u8;
+    if crc8(&r.data[..header_len]) != crc {
+        return Err(Failure::Corrupt);
+    }
+    if block_size > MAX_BLOCK_SIZE {
+        return Err(Failure::Unsupported);
+    }
+    Ok(FrameHeader {
+        block_size,
+        sample_rate,
+        layout,
+        bits_per_sample,
+    })
+}
+
+fn read_residual(r: &mut BitReader, order: usize, out: &mut [i32]) -> Result<(), Failure> {
+    let (param_bits, escape) = match r.read(2)? {
+        0 => (4, 15),
+        1 => (5, 31),
+        _ => return Err(Failure::Corrupt),
+    };
+    let partition_order = r.read(4)?;
+    let partitions = 1usize << partition_order;
+    let per_partition = out.len() / partitions;
+    if per_partition * partitions != out.len() || per_partition < order {
+        return Err(Failure::Corrupt);
+    }
+    let mut pos = order;
+    for p in 0..partitions {
+        let end = (p + 1) * per_partition;
+        let param = r.read(param_bits)?;
+        if param == escape {
+            let bits = r.read(5)?;
+            for s in &mut out[pos..end] {
+                *s = r.read_signed(bits)?;
+            }
+        } else {
+            for s in &mut out[pos..end] {
+                let q = (r.unary()? as u64) << param;
+                let v = u32::try_from(q | r.read(param)? as u64).map_err(|_| Failure::Corrupt)?;
+                *s = (v >> 1) as i32 ^ -((v & 1) as i32);
+            }
+        }
+        pos = end;
+    }
+    Ok(())
+}
+
+fn restore_fixed(order: usize, s: &mut [i32]) {
+    for i in order..s.len() {
+        let r = s[i];
+        s[i] = match order {
+            0 => r,
+            1 => r.wrapping_add(s[i - 1]),
+            2 => r.wrapping_add(s[i - 1].wrapping_mul(2).wrapping_sub(s[i - 2])),
+            3 => r.wrapping_add(
+                s[i - 1]
+                    .wrapping_sub(s[i - 2])
+                    .wrapping_mul(3)
+                    .wrapping_add(s[i - 3]),
+            ),
+            _ => r.wrapping_add(
+                s[i - 1]
+                    .wrapping_add(s[i - 3])
+                    .wrapping_mul(4)
+                    .wrapping_sub(s[i - 2].wrapping_mul(6))
+                    .wrapping_sub(s[i - 4]),
+            ),
+        };
+    }
+}
+
+fn restore_lpc(coefs: &[i32], shift: u32, s: &mut [i32]) {
+    let order = coefs.len();
+    for i in order..s.len() {
+        let prediction = coefs
+            .iter()
+            .zip(s[i - order..i].iter().rev())
+            .fold(0i64, |acc, (&c, &x)| acc + c as i64 * x as i64);
+        s[i] = s[i].wrapping_add((prediction >> shift) as i32);
+    }
+}
+
+fn read_subframe(r: &mut BitReader, bps: u32, out: &mut [i32]) -> Result<(), Failure> {
+    if r.bit()? != 0 {
+        return Err(Failure::Corrupt);
+    }
+    let kind = r.read(6)?;
+    let wasted = if r.bit()? != 0 { r.unary()? + 1 } else { 0 };
+    if wasted >= bps {
+        return Err(Failure::Corrupt);
+    }
+    let bps = bps - wasted;
+    match kind {
+        0 => {
+            let v = r.read_signed(bps)?;
+            out.fill(v);
+        }
+        1 => {
+            for s in out.iter_mut() {
+                *s = r.read_signed(bps)?;
+            }
+        }
+        8..=12 => {
+            let order = (kind - 8) as usize;
+            if order > out.len() {
+                return Err(Failure::Corrupt);
+            }
+            for s in &mut out[..order] {
+                *s = r.read_signed(bps)?;
+            }
+            read_residual(r, order, out)?;
+            restore_fixed(order, out);
+        }
+        32..=63 => {
+            let order = (kind - 31) as usize;
+            if order > out.len() {
+                return Err(Failure::Corrupt);
+            }
+            for s in &mut out[..order] {
+                *s = r.read_signed(bps)?;
+            }
+            let precision = r.read(4)? + 1;
+            if precision == 16 {
+                return Err(Failure::Corrupt);
+            }
+            let shift = r.read_signed(5)?;
+            if shift < 0 {
+                return Err(Failure::Corrupt);
+            }
+            let mut coefs = [0i32; MAX_LPC_ORDER];
+            for c in &mut coefs[..order] {
+                *c = r.read_signed(precision)?;
+            }
+            read_residual(r, order, out)?;
+            restore_lpc(&coefs[..order], shift as u32, out);
+        }
+        _ => return Err(Failure::Corrupt),
+    }
+    if wasted > 0 {
+        for s in out.iter_mut() {
+            *s <<= wasted;
+        }
+    }
+    Ok(())
+}
+
+enum State {
+    Marker,
+    Metadata,
+    Frames,
+}
+
+pub struct FlacDecoder {
+    state: State,
+    info: Option<StreamInfo>,
+    /// Bytes left of a metadata block that is being skipped.
+    skip: usize,
+    format: Option<PcmFormat>,
+    /// Decoded samples of the (up to) two channels being decorrelated.
+    work: [[i32; MAX_BLOCK_SIZE]; 2],
+}
+
+impl FlacDecoder {
+    pub const fn new() -> Self {
+        Self {
+            state: State::Marker,
+            info: None,
+            skip: 0,
+            format: None,
+            work: [[0; MAX_BLOCK_SIZE]; 2],
+        }
+    }
+
+    /// `STREAMINFO`, once the metadata has been read.
+    pub fn stream_info(&self) -> Option<&StreamInfo> {
+        self.info.as_ref()
+    }
+
+    fn read_metadata(&mut self, input: &[u8]) -> Result<Decoded, AudioError> {
+        if input.len() < 4 {
+            return Err(AudioError::NeedMoreData);
+        }
+        let last = input[0] & 0x80 != 0;
+        let kind = input[0] & 0x7f;
+        let len = (input[1] as usize) << 16 | (input[2] as usize) << 8 | input[3] as usize;
+        if kind == BLOCK_STREAMINFO {
+            let block: &[u8; STREAMINFO_LEN] = input
+                .get(4..4 + STREAMINFO_LEN)
+                .and_then(|b| b.try_into().ok())
+                .ok_or(AudioError::NeedMoreData)?;
+            if len != STREAMINFO_LEN {
+                return Err(AudioError::Unsupported);
+            }
+            self.info = Some(StreamInfo::parse(block)?);
+        } else if kind == 127 || self.info.is_none() {
+            // STREAMINFO must come first; 127 is reserved as invalid.
+            return Err(AudioError::Unsupported);
+        } else {
+            self.skip = len;
+        }
+        if last {
+            self.state = State::Frames;
+        }
+        let consumed = if kind == BLOCK_STREAMINFO { 4 + len } else { 4 };
+        Ok(Decoded {
+            consumed,
+            frames: 0,
+        })
+    }
+
+    /// Upper bound of a frame's size, used to tell a truncated frame from a
+    /// corrupt one once the caller's buffer is full.
+    fn max_frame_len(&self) -> usize {
+        match self.info {
+            Some(info) if info.max_frame_size > 0 => info.max_frame_size as usize,
+            Some(info) => {
+                let block = match info.max_block_size {
+                    0 => MAX_BLOCK_SIZE,
+                    n => n as usize,
+                };
+                // Verbatim subframes, one more bit for a side channel, and
+                // room for the frame and subframe headers.
+                block * info.channels as usize * (info.bits_per_sample as usize + 1) / 8 + 64
+            }
+            None => 0,
+        }
+    }
+
+    fn decode_frame(&mut self, input: &[u8], pcm: &mut [i16]) -> Result<Decoded, Failure> {
+        let info = self.info.ok_or(Failure::Corrupt)?;
+        let mut r = BitReader::new(input);
+        let header = read_frame_header(&mut r, &info)?;
+        let channels = header.layout.channels();
+        let block = header.block_size;
+        if pcm.len() < block * channels {
+            return Err(Failure::BufferTooSmall);
+        }
+        if let ChannelLayout::Independent(_) = header.layout {
+            for ch in 0..channels {
+                read_subframe(&mut r, header.bits_per_sample, &mut self.work[0][..block])?;
+                store(
+                    &self.work[0][..block],
+                    header.bits_per_sample,
+                    ch,
+                    channels,
+                    pcm,
+                );
+            }
+        } else {
+            let [a, b] = &mut self.work;
+            for (ch, buf) in [&mut a[..block], &mut b[..block]].into_iter().enumerate() {
+                let bps = header.bits_per_sample + header.layout.is_side(ch) as u32;
+                read_subframe(&mut r, bps, buf)?;
+            }
+            let (a, b) = (&mut a[..block], &mut b[..block]);
+            for (x, y) in a.iter_mut().zip(b.iter_mut()) {
+                (*x, *y) = match header.layout {
+                    ChannelLayout::LeftSide => (*x, x.wrapping_sub(*y)),
+                    ChannelLayout::SideRight => (x.wrapping_add(*y), *y),
+                    _ => {
+                        let mid = (*x << 1) | (*y & 1);
+                        (mid.wrapping_add(*y) >> 1, mid.wrapping_sub(*y) >> 1)
+                    }
+                };
+            }
+            store(a, header.bits_per_sample, 0, 2, pcm);
+            store(b, header.bits_per_sample, 1, 2, pcm);
+        }
+        r.align();
+        let end = r.byte_pos();
+        let crc = r.read(16)? as u16;
+        if crc16(&input[..end]) != crc {
+            return Err(Failure::Corrupt);
+        }
+        self.format = Some(PcmFormat {
+            sample_rate: header.sample_rate,
+            channels: channels as u8,
+        });
+        Ok(Decoded {
+            consumed: end + 2,
+            frames: block,
+        })
+    }
+}
+
+impl Default for FlacDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Writes one channel into the interleaved 16-bit output.
+fn store(samples: &[i32], bps: u32, ch: usize, channels: usize, pcm: &mut [i16]) {
+    for (i, &s) in samples.iter().enumerate() {
+        let scaled = if bps > 16 {
+            s >> (bps - 16)
+        } else {
+            s << (16 - bps)
+        };
+        pcm[i * channels + ch] = scaled.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+    }
+}
+
+impl AudioDecoder for FlacDecoder {
+    fn format(&self) -> Option<PcmFormat> {
+        self.format
+    }
+
+    fn max_samples(&self) -> usize {
+        match self.info {
+            Some(info) if info.max_block_size > 0 => {
+                info.max_block_size as usize * info.channels as usize
+            }
+            _ => MAX_BLOCK_SIZE * MAX_CHANNELS,
+        }
+    }
+
+    fn decode(&mut self, input: &[u8], pcm: &mut [i16]) -> Result<Decoded, AudioError> {
+        if self.skip > 0 {
+            if input.is_empty() {
+                return Err(AudioError::NeedMoreData);
+            }
+            let consumed = self.skip.min(input.len());
+            self.skip -= consumed;
+            return Ok(Decoded {
+                consumed,
+                frames: 0,
+            });
+        }
+        match self.state {
+            State::Marker => {
+                if input.len() < 4 {
+                    return Err(AudioError::NeedMoreData);
+                }
+                if &input[..4] != b"fLaC" {
+                    return Err(AudioError::Unsupported);
+                }
+                self.state = State::Metadata;
+                return Ok(Decoded {
+                    consumed: 4,
+                    frames: 0,
+                });
+            }
+            State::Metadata => return self.read_metadata(input),
+            State::Frames => {}
+        }
+
+        if !is_sync(input) {
+            if input.len() < 2 {
+                return Err(AudioError::NeedMoreData);
+            }
+            let consumed = (1..input.len())
+                .find(|&i| is_sync(&input[i..]))
+                .unwrap_or(input.len() - 1);
+            return Ok(Decoded {
+                consumed,
+                frames: 0,
+            });
+        }
+        match self.decode_frame(input, pcm) {
+            Ok(decoded) => Ok(decoded),
+            Err(Failure::Short) if input.len() < self.max_frame_len() => {
+                Err(AudioError::NeedMoreData)
+            }
+            Err(Failure::BufferTooSmall) => Err(AudioError::BufferTooSmall),
+            Err(Failure::Unsupported) => Err(AudioError::Unsupported),
+            // Corrupt (or impossibly long) frame: drop the sync code and
+            // look for the next one.
+            Err(_) => Ok(Decoded {
+                consumed: 2,
+                frames: 0,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Writer {
+        buf: [u8; 512],
+        bits: usize,
+    }
+
+    impl Writer {
+        fn new() -> Self {
+            Self {
+                buf: [0; 512],
+                bits: 0,
+            }
+        }
+
+        fn put(&mut self, value: i64, n: u32) {
+            for i in (0..n).rev() {
+                if value >> i & 1 != 0 {
+                    self.buf[self.bits / 8] |= 0x80 >> (self.bits % 8);
+                }
+                self.bits += 1;
+            }
+        }
+
+        fn bytes(&mut self, data: &[u8]) {
+            for &b in data {
+                self.put(b as i64, 8);
+            }
+        }
+
+        fn len(&self) -> usize {
+            self.bits / 8
+        }
+
+        /// Pads the current frame to a byte and appends its CRC-16.
+        fn end_frame(&mut self, start: usize) {
+            self.bits = self.bits.div_ceil(8) * 8;
+            let crc = crc16(&self.buf[start..self.len()]);
+            self.put(crc as i64, 16);
+        }
+
+        /// Frame header for a 16-sample, 16-bit, 44.1 kHz block.
+        fn frame_header(&mut self, channels: u8) {
+            let start = self.len();
+            self.bytes(&[0xff, 0xf8, 0x69, channels << 4 | 0x08, 0, 15]);
+            let crc = crc8(&self.buf[start..self.len()]);
+            self.bytes(&[crc]);
+        }
+    }
+
+    /// `fLaC`, STREAMINFO (16-sample blocks, 44.1 kHz stereo, 16 bits) and
+    /// a 5-byte APPLICATION block.
+    fn stream_start(w: &mut Writer) {
+        w.bytes(b"fLaC");
+        w.bytes(&[0, 0, 0, 34]);
+        w.put(16, 16);
+        w.put(16, 16);
+        w.put(0, 48);
+        w.put(44100, 20);
+        w.put(1, 3);
+        w.put(15, 5);
+        w.put(32, 36);
+        w.bytes(&[0; 16]);
+        w.bytes(&[0x82, 0, 0, 5, 1, 2, 3, 4, 5]);
+    }
+
+    /// Mid/side frame: constant mid, verbatim side.
+    fn mid_side_frame(w: &mut Writer) {
+        let start = w.len();
+        w.frame_header(10);
+        w.put(0, 8);
+        w.put(100, 16);
+        w.put(0b10, 8);
+        for i in 0..16 {
+            w.put(i * 2 - 16, 17);
+        }
+        w.end_frame(start);
+    }
+
+    /// Left/side frame: left is a fixed order-2 ramp with Rice residuals
+    /// over two partitions, side is an order-1 LPC with wasted bits.
+    fn left_side_frame(w: &mut Writer) {
+        let start = w.len();
+        w.frame_header(8);
+        w.put(0b0001_0100, 8);
+        w.put(10, 16);
+        w.put(20, 16);
+        w.put(0, 2);
+        w.put(1, 4);
+        for count in [6, 8] {
+            w.put(0, 4);
+            for _ in 0..count {
+                // Residual 0 with a zero Rice parameter: a single stop bit.
+                w.put(1, 1);
+            }
+        }
+        w.put(0b0100_0001, 8);
+        w.put(1, 1);
+        w.put(-2, 16);
+        w.put(1, 4);
+        w.put(0, 5);
+        w.put(1, 2);
+        w.put(0, 2);
+        w.put(0, 4);
+        // Escaped partition: raw 3-bit residuals of +1.
+        w.put(15, 4);
+        w.put(3, 5);
+        for _ in 0..15 {
+            w.put(1, 3);
+        }
+        w.end_frame(start);
+    }
+
+    fn decode_all(input: &[u8], pcm: &mut [i16; 64]) -> (usize, [i16; 64]) {
+        let mut decoder = FlacDecoder::new();
+        let mut out = [0; 64];
+        let (mut pos, mut total) = (0, 0);
+        while pos < input.len() {
+            let decoded = decoder.decode(&input[pos..], pcm).unwrap();
+            pos += decoded.consumed;
+            out[total..total + decoded.frames * 2].copy_from_slice(&pcm[..decoded.frames * 2]);
+            total += decoded.frames * 2;
+        }
+        (total, out)
+    }
+
+    #[test]
+    fn decodes_stereo_modes() {
+        let mut w = Writer::new();
+        stream_start(&mut w);
+        mid_side_frame(&mut w);
+        let mut pcm = [0; 64];
+        let (total, out) = decode_all(&w.buf[..w.len()], &mut pcm);
+        assert_eq!(total, 32);
+        for i in 0..16 {
+            let side = i as i16 * 2 - 16;
+            let mid = 100 << 1 | side & 1;
+            assert_eq!(out[2 * i], (mid + side) >> 1);
+            assert_eq!(out[2 * i + 1], (mid - side) >> 1);
+        }
+    }
+
+    #[test]
+    fn decodes_fixed_lpc_and_escaped_residuals() {
+        let mut w = Writer::new();
+        stream_start(&mut w);
+        left_side_frame(&mut w);
+        let mut pcm = [0; 64];
+        let (total, out) = decode_all(&w.buf[..w.len()], &mut pcm);
+        assert_eq!(total, 32);
+        for i in 0..16 {
+            let left = 10 + 10 * i as i16;
+            // Side doubles through the wasted bit: -4, -2, 0, 2...
+            let side = 2 * (i as i16 - 2);
+            assert_eq!(out[2 * i], left);
+            assert_eq!(out[2 * i + 1], left - side);
+        }
+    }
+
+    #[test]
+    fn resyncs_after_corrupt_frame() {
+        let mut w = Writer::new();
+        stream_start(&mut w);
+        let bad = w.len();
+        mid_side_frame(&mut w);
+        mid_side_frame(&mut w);
+        w.buf[bad + 10] ^= 0x40;
+        let mut pcm = [0; 64];
+        let (total, _) = decode_all(&w.buf[..w.len()], &mut pcm);
+        assert_eq!(total, 32);
+    }
+
+    #[test]
+    fn reports_stream_info_and_truncation() {
+        let mut w = Writer::new();
+        stream_start(&mut w);
+        mid_side_frame(&mut w);
+        let data = &w.buf[..w.len()];
+        let mut decoder = FlacDecoder::new();
+        let mut pcm = [0; 64];
+        let mut pos = 0;
+        while decoder.stream_info().is_none() {
+            pos += decoder.decode(&data[pos..], &mut pcm).unwrap().consumed;
+        }
+        let info = *decoder.stream_info().unwrap();
+        assert_eq!(
+            (info.sample_rate, info.channels, info.bits_per_sample),
+            (44100, 2, 16)
+        );
+        assert_eq!(info.duration_ns(), 725_623);
+        // The APPLICATION block: its header, then the skipped body.
+        while !is_sync(&data[pos..]) {
+            pos += decoder.decode(&data[pos..], &mut pcm).unwrap().consumed;
+        }
+        assert_eq!(
+            decoder.decode(&data[pos..data.len() - 1], &mut pcm),
+            Err(AudioError::NeedMoreData)
+        );
+        assert_eq!(
+            decoder.decode(&data[pos..], &mut pcm[..8]),
+            Err(AudioError::BufferTooSmall)
+        );
+    }
+}
@@ -0,0 +1,20 @@
+#![no_std]
+
+//! Media playback building blocks. Video: container parsing, codec
+//! selection, YUV conversion, A/V sync against the audio clock and the
+//! zero-copy buffer handoff to the compositor. Audio: FLAC, MP3 and WAV
+//! decoding, Ogg Opus framing, the streaming path into an audio-service
+//! stream and themed event sounds.
+
+pub mod audio;
+pub mod avsync;
+pub mod buffer;
+pub mod codec;
+pub mod container;
+pub mod flac;
+pub mod mp3;
+pub mod ogg;
+pub mod opus;
+pub mod sound;
+pub mod wav;
+pub mod yuv;
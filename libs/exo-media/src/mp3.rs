@@ -0,0 +1,1518 @@
+//! MPEG-1/2/2.5 Layer III decoder in safe Rust, ported from the scalar path
+//! of the public-domain minimp3 decoder. Layer I/II and free-format streams
+//! are reported as `Unsupported`.
+
+// The constants are the decoder's own single-precision values, kept digit
+// for digit so the output matches the reference bit for bit.
+#![allow(clippy::excessive_precision)]
+
+mod tables;
+
+use crate::audio::{AudioDecoder, AudioError, Decoded, PcmFormat};
+use tables::*;
+
+const HDR_SIZE: usize = 4;
+const MAX_BITRESERVOIR_BYTES: usize = 511;
+const MAX_FRAME_PAYLOAD_BYTES: usize = 2304;
+const SHORT_BLOCK_TYPE: u8 = 2;
+const STOP_BLOCK_TYPE: u8 = 3;
+/// `MAX_SCF` of the reference, rounded up to a multiple of 4.
+const MAX_SCFI: i32 = 44;
+/// Interleaved samples of the largest frame: 1152 stereo samples.
+pub const MAX_SAMPLES_PER_FRAME: usize = 1152 * 2;
+
+fn is_mono(h: &[u8]) -> bool {
+    h[3] & 0xc0 == 0xc0
+}
+
+fn is_ms_stereo(h: &[u8]) -> bool {
+    h[3] & 0xe0 == 0x60
+}
+
+fn ms_flag(h: &[u8]) -> bool {
+    h[3] & 0x20 != 0
+}
+
+fn i_stereo_flag(h: &[u8]) -> bool {
+    h[3] & 0x10 != 0
+}
+
+fn is_free_format(h: &[u8]) -> bool {
+    h[2] & 0xf0 == 0
+}
+
+fn has_crc(h: &[u8]) -> bool {
+    h[1] & 1 == 0
+}
+
+fn is_mpeg1(h: &[u8]) -> bool {
+    h[1] & 0x08 != 0
+}
+
+fn layer_bits(h: &[u8]) -> usize {
+    (h[1] as usize >> 1) & 3
+}
+
+fn is_layer1(h: &[u8]) -> bool {
+    h[1] & 6 == 6
+}
+
+fn sample_rate_index(h: &[u8]) -> usize {
+    (h[2] as usize >> 2) & 3
+}
+
+/// Sample-rate index across MPEG-1, 2 and 2.5 (0..9).
+fn sample_rate_slot(h: &[u8]) -> usize {
+    sample_rate_index(h) + ((h[1] as usize >> 3 & 1) + (h[1] as usize >> 4 & 1)) * 3
+}
+
+fn header_valid(h: &[u8]) -> bool {
+    h[0] == 0xff
+        && (h[1] & 0xf0 == 0xf0 || h[1] & 0xfe == 0xe2)
+        && layer_bits(h) != 0
+        && h[2] >> 4 != 15
+        && sample_rate_index(h) != 3
+}
+
+/// Same version, layer and sample rate: `h2` continues the stream of `h1`.
+fn header_compatible(h1: &[u8], h2: &[u8]) -> bool {
+    header_valid(h2)
+        && (h1[1] ^ h2[1]) & 0xfe == 0
+        && (h1[2] ^ h2[2]) & 0x0c == 0
+        && is_free_format(h1) == is_free_format(h2)
+}
+
+fn bitrate_kbps(h: &[u8]) -> usize {
+    const HALFRATE: [[[u8; 15]; 3]; 2] = [
+        [
+            [0, 4, 8, 12, 16, 20, 24, 28, 32, 40, 48, 56, 64, 72, 80],
+            [0, 4, 8, 12, 16, 20, 24, 28, 32, 40, 48, 56, 64, 72, 80],
+            [0, 16, 24, 28, 32, 40, 48, 56, 64, 72, 80, 88, 96, 112, 128],
+        ],
+        [
+            [0, 16, 20, 24, 28, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160],
+            [
+                0, 16, 24, 28, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192,
+            ],
+            [
+                0, 16, 32, 48, 64, 80, 96, 112, 128, 144, 160, 176, 192, 208, 224,
+            ],
+        ],
+    ];
+    2 * HALFRATE[is_mpeg1(h) as usize][layer_bits(h) - 1][(h[2] >> 4) as usize] as usize
+}
+
+fn sample_rate_hz(h: &[u8]) -> u32 {
+    const HZ: [u32; 3] = [44100, 48000, 32000];
+    HZ[sample_rate_index(h)] >> (!is_mpeg1(h)) as u32 >> (h[1] & 0x10 == 0) as u32
+}
+
+fn frame_samples(h: &[u8]) -> usize {
+    if is_layer1(h) {
+        384
+    } else {
+        1152 >> (h[1] & 14 == 2) as usize
+    }
+}
+
+/// Frame length including the padding slot; 0 for free-format streams.
+fn frame_len(h: &[u8]) -> usize {
+    let mut bytes = frame_samples(h) * bitrate_kbps(h) * 125 / sample_rate_hz(h) as usize;
+    if is_layer1(h) {
+        bytes &= !3;
+    }
+    if bytes == 0 {
+        return 0;
+    }
+    let padding = match (h[2] & 2 != 0, is_layer1(h)) {
+        (false, _) => 0,
+        (true, true) => 4,
+        (true, false) => 1,
+    };
+    bytes + padding
+}
+
+struct Bits<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    limit: usize,
+}
+
+impl<'a> Bits<'a> {
+    fn new(buf: &'a [u8], bytes: usize) -> Self {
+        Self {
+            buf,
+            pos: 0,
+            limit: bytes * 8,
+        }
+    }
+
+    /// Reads `n` (1..=24) bits; past the limit the position still advances
+    /// and 0 is returned, which the callers detect afterwards.
+    fn get(&mut self, n: u32) -> u32 {
+        let s = self.pos & 7;
+        let mut shl = n as i32 + s as i32;
+        let mut p = self.pos >> 3;
+        self.pos += n as usize;
+        if self.pos > self.limit {
+            return 0;
+        }
+        let mut next = (self.buf[p] & (255 >> s)) as u32;
+        p += 1;
+        let mut cache = 0;
+        loop {
+            shl -= 8;
+            if shl <= 0 {
+                break;
+            }
+            cache |= next << shl;
+            next = self.buf[p] as u32;
+            p += 1;
+        }
+        cache | next >> -shl
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Granule {
+    sfbtab: &'static [u8],
+    part_23_length: u16,
+    big_values: u16,
+    scalefac_compress: u16,
+    global_gain: u8,
+    block_type: u8,
+    mixed_block_flag: u8,
+    n_long_sfb: u8,
+    n_short_sfb: u8,
+    table_select: [u8; 3],
+    region_count: [u8; 3],
+    subblock_gain: [u8; 3],
+    preflag: u8,
+    scalefac_scale: u8,
+    count1_table: u8,
+    scfsi: u8,
+}
+
+/// Reads the side information; returns `main_data_begin`, or `None` if the
+/// granules cannot be valid.
+fn read_side_info(bs: &mut Bits, gr: &mut [Granule; 4], h: &[u8]) -> Option<usize> {
+    let sr_idx = sample_rate_slot(h).saturating_sub(1);
+    let mut gr_count = if is_mono(h) { 1 } else { 2 };
+    let mut scfsi = 0u32;
+    let main_data_begin;
+    if is_mpeg1(h) {
+        gr_count *= 2;
+        main_data_begin = bs.get(9) as usize;
+        scfsi = bs.get(7 + gr_count as u32);
+    } else {
+        main_data_begin = (bs.get(8 + gr_count as u32) >> gr_count) as usize;
+    }
+
+    let mut part_23_sum = 0;
+    for gr in gr.iter_mut().take(gr_count) {
+        if is_mono(h) {
+            scfsi <<= 4;
+        }
+        gr.part_23_length = bs.get(12) as u16;
+        part_23_sum += gr.part_23_length as usize;
+        gr.big_values = bs.get(9) as u16;
+        if gr.big_values > 288 {
+            return None;
+        }
+        gr.global_gain = bs.get(8) as u8;
+        gr.scalefac_compress = bs.get(if is_mpeg1(h) { 4 } else { 9 }) as u16;
+        gr.sfbtab = &SCF_LONG[sr_idx];
+        gr.n_long_sfb = 22;
+        gr.n_short_sfb = 0;
+        let mut tables;
+        if bs.get(1) != 0 {
+            gr.block_type = bs.get(2) as u8;
+            if gr.block_type == 0 {
+                return None;
+            }
+            gr.mixed_block_flag = bs.get(1) as u8;
+            gr.region_count = [7, 255, 0];
+            if gr.block_type == SHORT_BLOCK_TYPE {
+                scfsi &= 0x0f0f;
+                if gr.mixed_block_flag == 0 {
+                    gr.region_count[0] = 8;
+                    gr.sfbtab = &SCF_SHORT[sr_idx];
+                    gr.n_long_sfb = 0;
+                    gr.n_short_sfb = 39;
+                } else {
+                    gr.sfbtab = &SCF_MIXED[sr_idx];
+                    gr.n_long_sfb = if is_mpeg1(h) { 8 } else { 6 };
+                    gr.n_short_sfb = 30;
+                }
+            }
+            tables = bs.get(10);
+            tables <<= 5;
+            for gain in gr.subblock_gain.iter_mut() {
+                *gain = bs.get(3) as u8;
+            }
+        } else {
+            gr.block_type = 0;
+            gr.mixed_block_flag = 0;
+            tables = bs.get(15);
+            gr.region_count[0] = bs.get(4) as u8;
+            gr.region_count[1] = bs.get(3) as u8;
+            gr.region_count[2] = 255;
+        }
+        gr.table_select = [
+            (tables >> 10) as u8,
+            (tables >> 5 & 31) as u8,
+            (tables & 31) as u8,
+        ];
+        gr.preflag = if is_mpeg1(h) {
+            bs.get(1) as u8
+        } else {
+            (gr.scalefac_compress >= 500) as u8
+        };
+        gr.scalefac_scale = bs.get(1) as u8;
+        gr.count1_table = bs.get(1) as u8;
+        gr.scfsi = (scfsi >> 12 & 15) as u8;
+        scfsi <<= 4;
+    }
+
+    if part_23_sum + bs.pos > bs.limit + main_data_begin * 8 {
+        return None;
+    }
+    Some(main_data_begin)
+}
+
+fn read_scalefactors(
+    scf: &mut [u8; 40],
+    ist_pos: &mut [u8; 39],
+    scf_size: &[u8; 4],
+    scf_count: &[u8],
+    bs: &mut Bits,
+    mut scfsi: i32,
+) {
+    let mut off = 0;
+    for (&bits, &count) in scf_size.iter().zip(scf_count).take_while(|&(_, &c)| c != 0) {
+        let cnt = count as usize;
+        if scfsi & 8 != 0 {
+            scf[off..off + cnt].copy_from_slice(&ist_pos[off..off + cnt]);
+        } else if bits == 0 {
+            scf[off..off + cnt].fill(0);
+            ist_pos[off..off + cnt].fill(0);
+        } else {
+            let max_scf = if scfsi < 0 { (1 << bits) - 1 } else { -1 };
+            for k in off..off + cnt {
+                let s = bs.get(bits as u32) as i32;
+                ist_pos[k] = if s == max_scf { 255 } else { s as u8 };
+                scf[k] = s as u8;
+            }
+        }
+        off += cnt;
+        scfsi *= 2;
+    }
+    scf[off..off + 3].fill(0);
+}
+
+/// `y * 2^(-exp_q2 / 4)`.
+fn ldexp_q2(mut y: f32, mut exp_q2: i32) -> f32 {
+    const EXPFRAC: [f32; 4] = [
+        9.31322575e-10,
+        7.83145814e-10,
+        6.58544508e-10,
+        5.53767716e-10,
+    ];
+    loop {
+        let e = exp_q2.min(30 * 4);
+        y *= EXPFRAC[(e & 3) as usize] * ((1i32 << 30) >> (e >> 2).clamp(0, 30)) as f32;
+        exp_q2 -= e;
+        if exp_q2 <= 0 {
+            return y;
+        }
+    }
+}
+
+fn decode_scalefactors(
+    h: &[u8],
+    ist_pos: &mut [u8; 39],
+    bs: &mut Bits,
+    gr: &Granule,
+    scf: &mut [f32; 40],
+    ch: usize,
+) {
+    const PARTITIONS: [[u8; 28]; 3] = [
+        [
+            6, 5, 5, 5, 6, 5, 5, 5, 6, 5, 7, 3, 11, 10, 0, 0, 7, 7, 7, 0, 6, 6, 6, 3, 8, 8, 5, 0,
+        ],
+        [
+            8, 9, 6, 12, 6, 9, 9, 9, 6, 9, 12, 6, 15, 18, 0, 0, 6, 15, 12, 0, 6, 12, 9, 6, 6, 18,
+            9, 0,
+        ],
+        [
+            9, 9, 6, 12, 9, 9, 9, 9, 9, 9, 12, 6, 18, 18, 0, 0, 12, 12, 12, 0, 12, 9, 9, 6, 15, 12,
+            9, 0,
+        ],
+    ];
+    let row = (gr.n_short_sfb != 0) as usize + (gr.n_long_sfb == 0) as usize;
+    let mut partition: &[u8] = &PARTITIONS[row];
+    let mut scf_size = [0u8; 4];
+    let mut iscf = [0u8; 40];
+    let scf_shift = gr.scalefac_scale as u32 + 1;
+    let mut scfsi = gr.scfsi as i32;
+
+    if is_mpeg1(h) {
+        const SCFC_DECODE: [u8; 16] = [0, 1, 2, 3, 12, 5, 6, 7, 9, 10, 11, 13, 14, 15, 18, 19];
+        let part = SCFC_DECODE[gr.scalefac_compress as usize];
+        scf_size = [part >> 2, part >> 2, part & 3, part & 3];
+    } else {
+        const MODS: [u8; 24] = [
+            5, 5, 4, 4, 5, 5, 4, 1, 4, 3, 1, 1, 5, 6, 6, 1, 4, 4, 4, 1, 4, 3, 1, 1,
+        ];
+        let ist = (i_stereo_flag(h) && ch != 0) as usize;
+        let mut sfc = (gr.scalefac_compress >> ist) as i32;
+        let mut k = ist * 3 * 4;
+        while sfc >= 0 {
+            let mut modprod = 1;
+            for i in (0..4).rev() {
+                scf_size[i] = (sfc / modprod % MODS[k + i] as i32) as u8;
+                modprod *= MODS[k + i] as i32;
+            }
+            sfc -= modprod;
+            k += 4;
+        }
+        partition = &partition[k..];
+        scfsi = -16;
+    }
+    read_scalefactors(&mut iscf, ist_pos, &scf_size, partition, bs, scfsi);
+
+    let n_long = gr.n_long_sfb as usize;
+    if gr.n_short_sfb != 0 {
+        let sh = 3 - scf_shift;
+        for i in (0..gr.n_short_sfb as usize).step_by(3) {
+            for (w, &gain) in gr.subblock_gain.iter().enumerate() {
+                iscf[n_long + i + w] = iscf[n_long + i + w].wrapping_add(gain << sh);
+            }
+        }
+    } else if gr.preflag != 0 {
+        const PREAMP: [u8; 10] = [1, 1, 1, 1, 2, 2, 3, 3, 3, 2];
+        for (s, &amp) in iscf[11..21].iter_mut().zip(&PREAMP) {
+            *s = s.wrapping_add(amp);
+        }
+    }
+
+    let gain_exp = gr.global_gain as i32 - 4 - 210 - if is_ms_stereo(h) { 2 } else { 0 };
+    let gain = ldexp_q2((1 << (MAX_SCFI / 4)) as f32, MAX_SCFI - gain_exp);
+    for (s, &i) in scf
+        .iter_mut()
+        .zip(&iscf)
+        .take(n_long + gr.n_short_sfb as usize)
+    {
+        *s = ldexp_q2(gain, (i as i32) << scf_shift);
+    }
+}
+
+fn pow_43(mut x: i32) -> f32 {
+    if x < 129 {
+        return POW43[16 + x as usize];
+    }
+    let mut mult = 256.0;
+    if x < 1024 {
+        mult = 16.0;
+        x <<= 3;
+    }
+    let sign = (2 * x) & 64;
+    let frac = ((x & 63) - sign) as f32 / ((x & !63) + sign) as f32;
+    POW43[16 + ((x + sign) >> 6) as usize]
+        * (1.0 + frac * ((4.0 / 3.0) + frac * (2.0 / 9.0)))
+        * mult
+}
+
+/// Big-endian bit cache over the main data, read ahead of the bit position.
+struct HuffBits<'a> {
+    buf: &'a [u8],
+    next: usize,
+    cache: u32,
+    sh: i32,
+}
+
+impl<'a> HuffBits<'a> {
+    fn new(buf: &'a [u8], pos: usize) -> Self {
+        let byte = |i: usize| buf.get(i).copied().unwrap_or(0) as u32;
+        let at = pos / 8;
+        let cache = (((byte(at) * 256 + byte(at + 1)) * 256 + byte(at + 2)) * 256 + byte(at + 3))
+            << (pos & 7);
+        Self {
+            buf,
+            next: at + 4,
+            cache,
+            sh: (pos & 7) as i32 - 8,
+        }
+    }
+
+    fn peek(&self, n: u32) -> u32 {
+        self.cache.checked_shr(32 - n).unwrap_or(0)
+    }
+
+    fn flush(&mut self, n: u32) {
+        self.cache <<= n;
+        self.sh += n as i32;
+    }
+
+    fn refill(&mut self) {
+        while self.sh >= 0 {
+            self.cache |= (self.buf.get(self.next).copied().unwrap_or(0) as u32) << self.sh;
+            self.next += 1;
+            self.sh -= 8;
+        }
+    }
+
+    fn negative(&self) -> bool {
+        (self.cache as i32) < 0
+    }
+
+    fn pos(&self) -> i64 {
+        self.next as i64 * 8 - 24 + self.sh as i64
+    }
+}
+
+fn huffman(dst: &mut [f32], bs: &mut Bits, gr: &Granule, scf: &[f32; 40], limit: usize) {
+    let mut r = HuffBits::new(bs.buf, bs.pos);
+    let mut one = 0.0f32;
+    let mut ireg = 0;
+    let mut big_val_cnt = gr.big_values as i32;
+    let (mut sfb, mut s, mut d) = (0, 0, 0);
+
+    while big_val_cnt > 0 {
+        let tab_num = gr.table_select[ireg] as usize;
+        let mut sfb_cnt = gr.region_count[ireg] as i32;
+        ireg += 1;
+        let codebook = &HUFFMAN[HUFFMAN_INDEX[tab_num] as usize..];
+        let linbits = LINBITS[tab_num] as u32;
+        loop {
+            let np = (gr.sfbtab[sfb] / 2) as i32;
+            sfb += 1;
+            let mut pairs = big_val_cnt.min(np);
+            one = scf[s];
+            s += 1;
+            while pairs > 0 {
+                let mut w = 5;
+                let mut leaf = codebook[r.peek(w) as usize] as i32;
+                while leaf < 0 {
+                    r.flush(w);
+                    w = (leaf & 7) as u32;
+                    leaf = codebook[(r.peek(w) as i32 - (leaf >> 3)) as usize] as i32;
+                }
+                r.flush((leaf >> 8) as u32);
+                for _ in 0..2 {
+                    let mut lsb = leaf & 0x0f;
+                    if linbits != 0 && lsb == 15 {
+                        lsb += r.peek(linbits) as i32;
+                        r.flush(linbits);
+                        r.refill();
+                        dst[d] = one * pow_43(lsb) * if r.negative() { -1.0 } else { 1.0 };
+                    } else {
+                        dst[d] = POW43[(16 + lsb - 16 * (r.cache >> 31) as i32) as usize] * one;
+                    }
+                    r.flush((lsb != 0) as u32);
+                    d += 1;
+                    leaf >>= 4;
+                }
+                r.refill();
+                pairs -= 1;
+            }
+            big_val_cnt -= np;
+            sfb_cnt -= 1;
+            if big_val_cnt <= 0 || sfb_cnt < 0 {
+                break;
+            }
+        }
+    }
+
+    let codebook: &[u8] = if gr.count1_table != 0 {
+        &COUNT1_B
+    } else {
+        &COUNT1_A
+    };
+    let mut np = 1 - big_val_cnt;
+    'quads: loop {
+        let mut leaf = codebook[r.peek(4) as usize] as u32;
+        if leaf & 8 == 0 {
+            let extra = (r.cache << 4).checked_shr(32 - (leaf & 3)).unwrap_or(0);
+            leaf = codebook[((leaf >> 3) + extra) as usize] as u32;
+        }
+        r.flush(leaf & 7);
+        if r.pos() > limit as i64 {
+            break;
+        }
+        for pair in 0..2 {
+            np -= 1;
+            if np == 0 {
+                np = (gr.sfbtab[sfb] / 2) as i32;
+                sfb += 1;
+                if np == 0 {
+                    break 'quads;
+                }
+                one = scf[s];
+                s += 1;
+            }
+            for k in 2 * pair..2 * pair + 2 {
+                if leaf & (128 >> k) != 0 {
+                    dst[d + k] = if r.negative() { -one } else { one };
+                    r.flush(1);
+                }
+            }
+        }
+        r.refill();
+        d += 4;
+    }
+    bs.pos = limit;
+}
+
+fn midside_stereo(buf: &mut [f32], start: usize, n: usize) {
+    for i in start..start + n {
+        let (a, b) = (buf[i], buf[i + 576]);
+        buf[i] = a + b;
+        buf[i + 576] = a - b;
+    }
+}
+
+fn intensity_stereo_band(buf: &mut [f32], start: usize, n: usize, kl: f32, kr: f32) {
+    for i in start..start + n {
+        buf[i + 576] = buf[i] * kr;
+        buf[i] *= kl;
+    }
+}
+
+/// Highest band of each short window (or of the long block) where the right
+/// channel still carries its own signal.
+fn stereo_top_band(right: &[f32], sfb: &[u8], nbands: usize) -> [i32; 3] {
+    let mut max_band = [-1; 3];
+    let mut off = 0;
+    for (i, &width) in sfb.iter().take(nbands).enumerate() {
+        let width = width as usize;
+        if right[off..off + width].iter().any(|&x| x != 0.0) {
+            max_band[i % 3] = i as i32;
+        }
+        off += width;
+    }
+    max_band
+}
+
+fn stereo_process(
+    buf: &mut [f32],
+    ist_pos: &[u8; 39],
+    sfb: &[u8],
+    h: &[u8],
+    max_band: &[i32; 3],
+    mpeg2_sh: u32,
+) {
+    const PAN: [f32; 14] = [
+        0.0, 1.0, 0.21132487, 0.78867513, 0.36602540, 0.63397460, 0.5, 0.5, 0.63397460, 0.36602540,
+        0.78867513, 0.21132487, 1.0, 0.0,
+    ];
+    let max_pos = if is_mpeg1(h) { 7 } else { 64 };
+    let mut off = 0;
+    for (i, &width) in sfb.iter().take_while(|&&w| w != 0).enumerate() {
+        let width = width as usize;
+        let ipos = ist_pos[i] as usize;
+        if i as i32 > max_band[i % 3] && ipos < max_pos {
+            let s = if ms_flag(h) {
+                core::f32::consts::SQRT_2
+            } else {
+                1.0
+            };
+            let (kl, kr) = if is_mpeg1(h) {
+                (PAN[2 * ipos], PAN[2 * ipos + 1])
+            } else {
+                let k = ldexp_q2(1.0, (((ipos + 1) >> 1) << mpeg2_sh) as i32);
+                if ipos & 1 != 0 {
+                    (k, 1.0)
+                } else {
+                    (1.0, k)
+                }
+            };
+            intensity_stereo_band(buf, off, width, kl * s, kr * s);
+        } else if ms_flag(h) {
+            midside_stereo(buf, off, width);
+        }
+        off += width;
+    }
+}
+
+fn intensity_stereo(
+    buf: &mut [f32],
+    ist_pos: &mut [u8; 39],
+    gr: &Granule,
+    right: &Granule,
+    h: &[u8],
+) {
+    let n_sfb = gr.n_long_sfb as usize + gr.n_short_sfb as usize;
+    let max_blocks = if gr.n_short_sfb != 0 { 3 } else { 1 };
+    let mut max_band = stereo_top_band(&buf[576..], gr.sfbtab, n_sfb);
+    if gr.n_long_sfb != 0 {
+        max_band = [max_band[0].max(max_band[1]).max(max_band[2]); 3];
+    }
+    for (i, &band) in max_band.iter().enumerate().take(max_blocks) {
+        let default_pos = if is_mpeg1(h) { 3 } else { 0 };
+        let itop = n_sfb - max_blocks + i;
+        let prev = itop - max_blocks;
+        ist_pos[itop] = if band >= prev as i32 {
+            default_pos
+        } else {
+            ist_pos[prev]
+        };
+    }
+    stereo_process(
+        buf,
+        ist_pos,
+        gr.sfbtab,
+        h,
+        &max_band,
+        (right.scalefac_compress & 1) as u32,
+    );
+}
+
+/// Short blocks arrive band by band, window after window; the IMDCT wants
+/// the three windows of each line side by side.
+fn reorder(grbuf: &mut [f32], sfb: &[u8]) {
+    let mut scratch = [0f32; 576];
+    let (mut src, mut dst) = (0, 0);
+    for &len in sfb.iter().step_by(3).take_while(|&&len| len != 0) {
+        let len = len as usize;
+        // MPEG-2.5 8 kHz mixed blocks describe more lines than remain.
+        if src + 3 * len > grbuf.len() {
+            break;
+        }
+        for _ in 0..len {
+            scratch[dst] = grbuf[src];
+            scratch[dst + 1] = grbuf[src + len];
+            scratch[dst + 2] = grbuf[src + 2 * len];
+            dst += 3;
+            src += 1;
+        }
+        src += 2 * len;
+    }
+    grbuf[..dst].copy_from_slice(&scratch[..dst]);
+}
+
+fn antialias(grbuf: &mut [f32], nbands: i32) {
+    const AA: [[f32; 8]; 2] = [
+        [
+            0.85749293, 0.88174200, 0.94962865, 0.98331459, 0.99551782, 0.99916056, 0.99989920,
+            0.99999316,
+        ],
+        [
+            0.51449576, 0.47173197, 0.31337745, 0.18191320, 0.09457419, 0.04096558, 0.01419856,
+            0.00369997,
+        ],
+    ];
+    for band in 0..nbands.max(0) as usize {
+        let g = &mut grbuf[band * 18..];
+        for i in 0..8 {
+            let u = g[18 + i];
+            let d = g[17 - i];
+            g[18 + i] = u * AA[0][i] - d * AA[1][i];
+            g[17 - i] = u * AA[1][i] + d * AA[0][i];
+        }
+    }
+}
+
+fn dct3_9(y: &mut [f32; 9]) {
+    let (mut s0, mut s2, mut s4, mut s6, mut s8) = (y[0], y[2], y[4], y[6], y[8]);
+    let mut t0 = s0 + s6 * 0.5;
+    s0 -= s6;
+    let mut t4 = (s4 + s2) * 0.93969262;
+    let mut t2 = (s8 + s2) * 0.76604444;
+    s6 = (s4 - s8) * 0.17364818;
+    s4 += s8 - s2;
+
+    s2 = s0 - s4 * 0.5;
+    y[4] = s4 + s0;
+    s8 = t0 - t2 + s6;
+    s0 = t0 - t4 + t2;
+    s4 = t0 + t4 - s6;
+
+    let (mut s1, mut s3, mut s5, mut s7) = (y[1], y[3], y[5], y[7]);
+
+    s3 *= 0.86602540;
+    t0 = (s5 + s1) * 0.98480775;
+    t4 = (s5 - s7) * 0.34202014;
+    t2 = (s1 + s7) * 0.64278761;
+    s1 = (s1 - s5 - s7) * 0.86602540;
+
+    s5 = t0 - s3 - t2;
+    s7 = t4 - s3 - t0;
+    s3 = t4 + s3 - t2;
+
+    y[0] = s4 - s7;
+    y[1] = s2 + s1;
+    y[2] = s0 - s3;
+    y[3] = s8 + s5;
+    y[5] = s8 - s5;
+    y[6] = s0 + s3;
+    y[7] = s2 - s1;
+    y[8] = s4 + s7;
+}
+
+const TWID9: [f32; 18] = [
+    0.73727734, 0.79335334, 0.84339145, 0.88701083, 0.92387953, 0.95371695, 0.97629601, 0.99144486,
+    0.99904822, 0.67559021, 0.60876143, 0.53729961, 0.46174861, 0.38268343, 0.30070580, 0.21643961,
+    0.13052619, 0.04361938,
+];
+
+fn imdct36(grbuf: &mut [f32], overlap: &mut [f32], window: &[f32; 18], nbands: usize) {
+    for j in 0..nbands {
+        let g = &mut grbuf[j * 18..j * 18 + 18];
+        let ov = &mut overlap[j * 9..j * 9 + 9];
+        let mut co = [0f32; 9];
+        let mut si = [0f32; 9];
+        co[0] = -g[0];
+        si[0] = g[17];
+        for i in 0..4 {
+            si[8 - 2 * i] = g[4 * i + 1] - g[4 * i + 2];
+            co[1 + 2 * i] = g[4 * i + 1] + g[4 * i + 2];
+            si[7 - 2 * i] = g[4 * i + 4] - g[4 * i + 3];
+            co[2 + 2 * i] = -(g[4 * i + 3] + g[4 * i + 4]);
+        }
+        dct3_9(&mut co);
+        dct3_9(&mut si);
+        for k in [1, 3, 5, 7] {
+            si[k] = -si[k];
+        }
+        for i in 0..9 {
+            let ovl = ov[i];
+            let sum = co[i] * TWID9[9 + i] + si[i] * TWID9[i];
+            ov[i] = co[i] * TWID9[i] - si[i] * TWID9[9 + i];
+            g[i] = ovl * window[i] - sum * window[9 + i];
+            g[17 - i] = ovl * window[9 + i] + sum * window[i];
+        }
+    }
+}
+
+fn idct3(x0: f32, x1: f32, x2: f32) -> [f32; 3] {
+    let m1 = x1 * 0.86602540;
+    let a1 = x0 - x2 * 0.5;
+    [a1 + m1, x0 + x2, a1 - m1]
+}
+
+/// One short window: `x` holds the window's lines at a stride of 3.
+fn imdct12(x: &[f32], dst: &mut [f32], overlap: &mut [f32]) {
+    const TWID3: [f32; 6] = [
+        0.79335334, 0.92387953, 0.99144486, 0.60876143, 0.38268343, 0.13052619,
+    ];
+    let co = idct3(-x[0], x[6] + x[3], x[12] + x[9]);
+    let mut si = idct3(x[15], x[12] - x[9], x[6] - x[3]);
+    si[1] = -si[1];
+    for i in 0..3 {
+        let ovl = overlap[i];
+        let sum = co[i] * TWID3[3 + i] + si[i] * TWID3[i];
+        overlap[i] = co[i] * TWID3[i] - si[i] * TWID3[3 + i];
+        dst[i] = ovl * TWID3[2 - i] - sum * TWID3[5 - i];
+        dst[5 - i] = ovl * TWID3[5 - i] + sum * TWID3[2 - i];
+    }
+}
+
+fn imdct_short(grbuf: &mut [f32], overlap: &mut [f32], nbands: usize) {
+    for band in 0..nbands {
+        let g = &mut grbuf[band * 18..band * 18 + 18];
+        let (ov_lo, ov_hi) = overlap[band * 9..band * 9 + 9].split_at_mut(6);
+        let mut tmp = [0f32; 18];
+        tmp.copy_from_slice(g);
+        g[..6].copy_from_slice(ov_lo);
+        imdct12(&tmp, &mut g[6..12], ov_hi);
+        imdct12(&tmp[1..], &mut g[12..18], ov_hi);
+        imdct12(&tmp[2..], ov_lo, ov_hi);
+    }
+}
+
+fn change_sign(grbuf: &mut [f32]) {
+    for band in (1..32).step_by(2) {
+        for i in (1..18).step_by(2) {
+            grbuf[band * 18 + i] = -grbuf[band * 18 + i];
+        }
+    }
+}
+
+fn imdct_granule(grbuf: &mut [f32], overlap: &mut [f32], block_type: u8, n_long_bands: usize) {
+    const MDCT_WINDOW: [[f32; 18]; 2] = [
+        [
+            0.99904822, 0.99144486, 0.97629601, 0.95371695, 0.92387953, 0.88701083, 0.84339145,
+            0.79335334, 0.73727734, 0.04361938, 0.13052619, 0.21643961, 0.30070580, 0.38268343,
+            0.46174861, 0.53729961, 0.60876143, 0.67559021,
+        ],
+        [
+            1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.99144486, 0.92387953, 0.79335334, 0.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 0.13052619, 0.38268343, 0.60876143,
+        ],
+    ];
+    if n_long_bands > 0 {
+        imdct36(grbuf, overlap, &MDCT_WINDOW[0], n_long_bands);
+    }
+    let grbuf = &mut grbuf[18 * n_long_bands..];
+    let overlap = &mut overlap[9 * n_long_bands..];
+    if block_type == SHORT_BLOCK_TYPE {
+        imdct_short(grbuf, overlap, 32 - n_long_bands);
+    } else {
+        let window = &MDCT_WINDOW[(block_type == STOP_BLOCK_TYPE) as usize];
+        imdct36(grbuf, overlap, window, 32 - n_long_bands);
+    }
+}
+
+/// 32-point DCT-II across the subbands of each of the first `n` time slots.
+fn dct_ii(grbuf: &mut [f32], n: usize) {
+    const SEC: [f32; 24] = [
+        10.19000816,
+        0.50060302,
+        0.50241929,
+        3.40760851,
+        0.50547093,
+        0.52249861,
+        2.05778098,
+        0.51544732,
+        0.56694406,
+        1.48416460,
+        0.53104258,
+        0.64682180,
+        1.16943991,
+        0.55310392,
+        0.78815460,
+        0.97256821,
+        0.58293498,
+        1.06067765,
+        0.83934963,
+        0.62250412,
+        1.72244716,
+        0.74453628,
+        0.67480832,
+        5.10114861,
+    ];
+    for k in 0..n {
+        let mut t = [[0f32; 8]; 4];
+        for i in 0..8 {
+            let x0 = grbuf[k + i * 18];
+            let x1 = grbuf[k + (15 - i) * 18];
+            let x2 = grbuf[k + (16 + i) * 18];
+            let x3 = grbuf[k + (31 - i) * 18];
+            let t0 = x0 + x3;
+            let t1 = x1 + x2;
+            let t2 = (x1 - x2) * SEC[3 * i];
+            let t3 = (x0 - x3) * SEC[3 * i + 1];
+            t[0][i] = t0 + t1;
+            t[1][i] = (t0 - t1) * SEC[3 * i + 2];
+            t[2][i] = t3 + t2;
+            t[3][i] = (t3 - t2) * SEC[3 * i + 2];
+        }
+        for x in t.iter_mut() {
+            let [mut x0, mut x1, mut x2, mut x3, mut x4, mut x5, mut x6, mut x7] = *x;
+            let mut xt = x0 - x7;
+            x0 += x7;
+            x7 = x1 - x6;
+            x1 += x6;
+            x6 = x2 - x5;
+            x2 += x5;
+            x5 = x3 - x4;
+            x3 += x4;
+            x4 = x0 - x3;
+            x0 += x3;
+            x3 = x1 - x2;
+            x1 += x2;
+            x[0] = x0 + x1;
+            x[4] = (x0 - x1) * 0.70710677;
+            x5 += x6;
+            x6 = (x6 + x7) * 0.70710677;
+            x7 += xt;
+            x3 = (x3 + x4) * 0.70710677;
+            // Rotation by pi/8.
+            x5 -= x7 * 0.198912367;
+            x7 += x5 * 0.382683432;
+            x5 -= x7 * 0.198912367;
+            x0 = xt - x6;
+            xt += x6;
+            x[1] = (xt + x7) * 0.50979561;
+            x[2] = (x4 + x3) * 0.54119611;
+            x[3] = (x0 - x5) * 0.60134488;
+            x[5] = (x0 + x5) * 0.89997619;
+            x[6] = (x4 - x3) * 1.30656302;
+            x[7] = (xt - x7) * 2.56291556;
+        }
+        let mut y = k;
+        for i in 0..7 {
+            grbuf[y] = t[0][i];
+            grbuf[y + 18] = t[2][i] + t[3][i] + t[3][i + 1];
+            grbuf[y + 36] = t[1][i] + t[1][i + 1];
+            grbuf[y + 54] = t[2][i + 1] + t[3][i] + t[3][i + 1];
+            y += 72;
+        }
+        grbuf[y] = t[0][7];
+        grbuf[y + 18] = t[2][7] + t[3][7];
+        grbuf[y + 36] = t[1][7];
+        grbuf[y + 54] = t[3][7];
+    }
+}
+
+fn scale_pcm(sample: f32) -> i16 {
+    if sample >= 32766.5 {
+        return i16::MAX;
+    }
+    if sample <= -32767.5 {
+        return i16::MIN;
+    }
+    let s = (sample + 0.5) as i16;
+    // Round half away from zero.
+    s - (s < 0) as i16
+}
+
+fn synth_pair(pcm: &mut [i16], at: usize, nch: usize, z: &[f32], zo: usize) {
+    let mut a = (z[zo + 14 * 64] - z[zo]) * 29.0;
+    a += (z[zo + 64] + z[zo + 13 * 64]) * 213.0;
+    a += (z[zo + 12 * 64] - z[zo + 2 * 64]) * 459.0;
+    a += (z[zo + 3 * 64] + z[zo + 11 * 64]) * 2037.0;
+    a += (z[zo + 10 * 64] - z[zo + 4 * 64]) * 5153.0;
+    a += (z[zo + 5 * 64] + z[zo + 9 * 64]) * 6574.0;
+    a += (z[zo + 8 * 64] - z[zo + 6 * 64]) * 37489.0;
+    a += z[zo + 7 * 64] * 75038.0;
+    pcm[at] = scale_pcm(a);
+
+    let zo = zo + 2;
+    a = z[zo + 14 * 64] * 104.0;
+    a += z[zo + 12 * 64] * 1567.0;
+    a += z[zo + 10 * 64] * 9727.0;
+    a += z[zo + 8 * 64] * 64019.0;
+    a += z[zo + 6 * 64] * -9975.0;
+    a += z[zo + 4 * 64] * -45.0;
+    a += z[zo + 2 * 64] * 146.0;
+    a += z[zo] * -5.0;
+    pcm[at + 16 * nch] = scale_pcm(a);
+}
+
+/// Polyphase synthesis of two time slots (64 output samples per channel).
+/// `lins` is the windowed history, `lb` the offset of this slot pair in it.
+fn synth(
+    grbuf: &[f32],
+    xl: usize,
+    pcm: &mut [i16],
+    dstl: usize,
+    nch: usize,
+    lins: &mut [f32],
+    lb: usize,
+) {
+    let xr = xl + 576 * (nch - 1);
+    let dstr = dstl + nch - 1;
+    let zlin = lb + 15 * 64;
+
+    lins[zlin + 60] = grbuf[xl + 18 * 16];
+    lins[zlin + 61] = grbuf[xr + 18 * 16];
+    lins[zlin + 62] = grbuf[xl];
+    lins[zlin + 63] = grbuf[xr];
+
+    lins[zlin + 124] = grbuf[xl + 1 + 18 * 16];
+    lins[zlin + 125] = grbuf[xr + 1 + 18 * 16];
+    lins[zlin + 126] = grbuf[xl + 1];
+    lins[zlin + 127] = grbuf[xr + 1];
+
+    synth_pair(pcm, dstr, nch, lins, lb + 60 + 1);
+    synth_pair(pcm, dstr + 32 * nch, nch, lins, lb + 60 + 64 + 1);
+    synth_pair(pcm, dstl, nch, lins, lb + 60);
+    synth_pair(pcm, dstl + 32 * nch, nch, lins, lb + 60 + 64);
+
+    let mut w = 0;
+    for i in (0..15).rev() {
+        let (src_hi, src_lo) = (18 * (31 - i), 18 * (1 + i));
+        lins[zlin + 4 * i] = grbuf[xl + src_hi];
+        lins[zlin + 4 * i + 1] = grbuf[xr + src_hi];
+        lins[zlin + 4 * i + 2] = grbuf[xl + 1 + src_hi];
+        lins[zlin + 4 * i + 3] = grbuf[xr + 1 + src_hi];
+        lins[zlin + 4 * (i + 16)] = grbuf[xl + 1 + src_lo];
+        lins[zlin + 4 * (i + 16) + 1] = grbuf[xr + 1 + src_lo];
+        lins[zlin + 4 * i - 64 + 2] = grbuf[xl + src_lo];
+        lins[zlin + 4 * i - 64 + 3] = grbuf[xr + src_lo];
+
+        let mut a = [0f32; 4];
+        let mut b = [0f32; 4];
+        for k in 0..8 {
+            let (w0, w1) = (SYNTH_WINDOW[w], SYNTH_WINDOW[w + 1]);
+            w += 2;
+            let vz = zlin + 4 * i - k * 64;
+            let vy = zlin + 4 * i - (15 - k) * 64;
+            for j in 0..4 {
+                let (z, y) = (lins[vz + j], lins[vy + j]);
+                if k == 0 {
+                    b[j] = z * w1 + y * w0;
+                    a[j] = z * w0 - y * w1;
+                } else if k % 2 == 0 {
+                    b[j] += z * w1 + y * w0;
+                    a[j] += z * w0 - y * w1;
+                } else {
+                    b[j] += z * w1 + y * w0;
+                    a[j] += y * w1 - z * w0;
+                }
+            }
+        }
+
+        pcm[dstr + (15 - i) * nch] = scale_pcm(a[1]);
+        pcm[dstr + (17 + i) * nch] = scale_pcm(b[1]);
+        pcm[dstl + (15 - i) * nch] = scale_pcm(a[0]);
+        pcm[dstl + (17 + i) * nch] = scale_pcm(b[0]);
+        pcm[dstr + (47 - i) * nch] = scale_pcm(a[3]);
+        pcm[dstr + (49 + i) * nch] = scale_pcm(b[3]);
+        pcm[dstl + (47 - i) * nch] = scale_pcm(a[2]);
+        pcm[dstl + (49 + i) * nch] = scale_pcm(b[2]);
+    }
+}
+
+fn synth_granule(
+    qmf_state: &mut [f32; 15 * 64],
+    grbuf: &mut [f32; 1152],
+    nch: usize,
+    pcm: &mut [i16],
+    pcm_at: usize,
+    lins: &mut [f32; 33 * 64],
+) {
+    const NBANDS: usize = 18;
+    for ch in 0..nch {
+        dct_ii(&mut grbuf[576 * ch..576 * ch + 576], NBANDS);
+    }
+    lins[..15 * 64].copy_from_slice(qmf_state);
+    for i in (0..NBANDS).step_by(2) {
+        synth(grbuf, i, pcm, pcm_at + 32 * nch * i, nch, lins, i * 64);
+    }
+    let tail = &lins[NBANDS * 64..NBANDS * 64 + 15 * 64];
+    if nch == 1 {
+        // The reference keeps only the even lanes of a mono history.
+        for i in (0..15 * 64).step_by(2) {
+            qmf_state[i] = tail[i];
+        }
+    } else {
+        qmf_state.copy_from_slice(tail);
+    }
+}
+
+/// Per-frame working set, rebuilt for every frame.
+struct Scratch {
+    gr: [Granule; 4],
+    grbuf: [f32; 1152],
+    scf: [f32; 40],
+    syn: [f32; 33 * 64],
+    ist_pos: [[u8; 39]; 2],
+}
+
+enum Sync {
+    /// A frame starts at `offset` and is `len` bytes long.
+    Frame {
+        offset: usize,
+        len: usize,
+        resync: bool,
+    },
+    /// Bytes that cannot start a frame.
+    Garbage(usize),
+}
+
+pub struct Mp3Decoder {
+    /// Header of the stream the decoder is locked on; `[0; 4]` while searching.
+    header: [u8; 4],
+    mdct_overlap: [[f32; 9 * 32]; 2],
+    qmf_state: [f32; 15 * 64],
+    reserv: usize,
+    reserv_buf: [u8; MAX_BITRESERVOIR_BYTES],
+    /// ID3v2 tag bytes still to drop.
+    skip: usize,
+    format: Option<PcmFormat>,
+}
+
+impl Mp3Decoder {
+    pub const fn new() -> Self {
+        Self {
+            header: [0; 4],
+            mdct_overlap: [[0.0; 9 * 32]; 2],
+            qmf_state: [0.0; 15 * 64],
+            reserv: 0,
+            reserv_buf: [0; MAX_BITRESERVOIR_BYTES],
+            skip: 0,
+            format: None,
+        }
+    }
+
+    /// Forgets the decoding history, e.g. after a seek.
+    pub fn reset(&mut self) {
+        let skip = self.skip;
+        *self = Self::new();
+        self.skip = skip;
+    }
+
+    fn sync(&self, input: &[u8]) -> Result<Sync, AudioError> {
+        if input.len() < HDR_SIZE {
+            return Err(AudioError::NeedMoreData);
+        }
+        if self.header[0] == 0xff && header_compatible(&self.header, input) {
+            let len = frame_len(input);
+            if len > input.len() {
+                return Err(AudioError::NeedMoreData);
+            }
+            return Ok(Sync::Frame {
+                offset: 0,
+                len,
+                resync: false,
+            });
+        }
+        // Lost or not yet found: accept a header only if the next frame
+        // confirms it, or if it fills the input exactly.
+        for offset in 0..=input.len() - HDR_SIZE {
+            let h = &input[offset..];
+            if !header_valid(h) {
+                continue;
+            }
+            let len = frame_len(h);
+            if len == 0 {
+                continue;
+            }
+            let partial = match offset {
+                0 => Err(AudioError::NeedMoreData),
+                _ => Ok(Sync::Garbage(offset)),
+            };
+            let Some(next) = h.get(len..) else {
+                return partial;
+            };
+            let confirmed = match next.len() {
+                0 => offset == 0,
+                1..=3 => return partial,
+                _ => header_compatible(h, next),
+            };
+            if confirmed {
+                return Ok(Sync::Frame {
+                    offset,
+                    len,
+                    resync: true,
+                });
+            }
+        }
+        match input.len() - (HDR_SIZE - 1) {
+            0 => Err(AudioError::NeedMoreData),
+            n => Ok(Sync::Garbage(n)),
+        }
+    }
+
+    /// Puts the `main_data_begin` bytes kept from earlier frames in front of
+    /// this frame's payload. Returns the main data length and whether the
+    /// reservoir held enough bytes to decode.
+    fn restore_reservoir(
+        &self,
+        payload: &[u8],
+        maindata: &mut [u8],
+        main_data_begin: usize,
+    ) -> (usize, bool) {
+        let have = self.reserv.min(main_data_begin);
+        let from = self.reserv - have;
+        maindata[..have].copy_from_slice(&self.reserv_buf[from..self.reserv]);
+        maindata[have..have + payload.len()].copy_from_slice(payload);
+        (have + payload.len(), self.reserv >= main_data_begin)
+    }
+
+    fn save_reservoir(&mut self, maindata: &[u8], bs_pos: usize, limit: usize) {
+        let mut pos = bs_pos.div_ceil(8);
+        let mut remains = (limit / 8).saturating_sub(pos);
+        if remains > MAX_BITRESERVOIR_BYTES {
+            pos += remains - MAX_BITRESERVOIR_BYTES;
+            remains = MAX_BITRESERVOIR_BYTES;
+        }
+        self.reserv_buf[..remains].copy_from_slice(&maindata[pos..pos + remains]);
+        self.reserv = remains;
+    }
+
+    fn decode_granule(&mut self, s: &mut Scratch, bs: &mut Bits, first: usize, nch: usize) {
+        let h = self.header;
+        for ch in 0..nch {
+            let gr = &s.gr[first + ch];
+            let limit = bs.pos + gr.part_23_length as usize;
+            decode_scalefactors(&h, &mut s.ist_pos[ch], bs, gr, &mut s.scf, ch);
+            huffman(
+                &mut s.grbuf[576 * ch..576 * ch + 576],
+                bs,
+                gr,
+                &s.scf,
+                limit,
+            );
+        }
+        if i_stereo_flag(&h) {
+            let (left, right) = (&s.gr[first], &s.gr[first + 1]);
+            intensity_stereo(&mut s.grbuf, &mut s.ist_pos[1], left, right, &h);
+        } else if is_ms_stereo(&h) {
+            midside_stereo(&mut s.grbuf, 0, 576);
+        }
+        for ch in 0..nch {
+            let gr = &s.gr[first + ch];
+            let grbuf = &mut s.grbuf[576 * ch..576 * ch + 576];
+            let mixed_long = if gr.mixed_block_flag != 0 { 2 } else { 0 };
+            let n_long_bands = mixed_long << (sample_rate_slot(&h) == 2) as usize;
+            let mut aa_bands = 31;
+            if gr.n_short_sfb != 0 {
+                aa_bands = n_long_bands as i32 - 1;
+                reorder(
+                    &mut grbuf[n_long_bands * 18..],
+                    &gr.sfbtab[gr.n_long_sfb as usize..],
+                );
+            }
+            antialias(grbuf, aa_bands);
+            imdct_granule(
+                grbuf,
+                &mut self.mdct_overlap[ch],
+                gr.block_type,
+                n_long_bands,
+            );
+            change_sign(grbuf);
+        }
+    }
+
+    /// Decodes one complete frame; returns the sample frames written.
+    fn decode_frame(&mut self, frame: &[u8], pcm: &mut [i16]) -> usize {
+        let h = self.header;
+        let nch = if is_mono(&h) { 1 } else { 2 };
+        let mut bs = Bits::new(&frame[HDR_SIZE..], frame.len() - HDR_SIZE);
+        if has_crc(&h) {
+            bs.get(16);
+        }
+        let mut s = Scratch {
+            gr: [Granule::default(); 4],
+            grbuf: [0.0; 1152],
+            scf: [0.0; 40],
+            syn: [0.0; 33 * 64],
+            ist_pos: [[0; 39]; 2],
+        };
+        let Some(main_data_begin) = read_side_info(&mut bs, &mut s.gr, &h) else {
+            self.header = [0; 4];
+            return 0;
+        };
+        if bs.pos > bs.limit {
+            self.header = [0; 4];
+            return 0;
+        }
+
+        let payload = &frame[HDR_SIZE + bs.pos / 8..];
+        let mut maindata = [0; MAX_BITRESERVOIR_BYTES + MAX_FRAME_PAYLOAD_BYTES];
+        let (main_len, restored) = self.restore_reservoir(payload, &mut maindata, main_data_begin);
+        let mut bs = Bits::new(&maindata, main_len);
+        let granules = if is_mpeg1(&h) { 2 } else { 1 };
+        if restored {
+            for igr in 0..granules {
+                s.grbuf = [0.0; 1152];
+                self.decode_granule(&mut s, &mut bs, igr * nch, nch);
+                synth_granule(
+                    &mut self.qmf_state,
+                    &mut s.grbuf,
+                    nch,
+                    pcm,
+                    igr * 576 * nch,
+                    &mut s.syn,
+                );
+            }
+        }
+        self.save_reservoir(&maindata, bs.pos, bs.limit);
+        if restored {
+            frame_samples(&h)
+        } else {
+            0
+        }
+    }
+}
+
+impl Default for Mp3Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Length of an ID3v2 tag at the front of `input`, header and footer included.
+fn id3v2_len(input: &[u8]) -> Option<Result<usize, AudioError>> {
+    if input.len() < 3 || &input[..3] != b"ID3" {
+        return None;
+    }
+    if input.len() < 10 {
+        return Some(Err(AudioError::NeedMoreData));
+    }
+    let size = input[6..10]
+        .iter()
+        .fold(0usize, |acc, &b| acc << 7 | (b & 0x7f) as usize);
+    let footer = if input[5] & 0x10 != 0 { 10 } else { 0 };
+    Some(Ok(10 + size + footer))
+}
+
+/// The LAME/Xing information frame that opens VBR files carries no audio.
+fn is_info_frame(frame: &[u8]) -> bool {
+    let side_info = match (is_mpeg1(frame), is_mono(frame)) {
+        (true, false) => 32,
+        (true, true) | (false, false) => 17,
+        (false, true) => 9,
+    };
+    let at = HDR_SIZE + if has_crc(frame) { 2 } else { 0 } + side_info;
+    matches!(frame.get(at..at + 4), Some(b"Xing") | Some(b"Info"))
+}
+
+impl AudioDecoder for Mp3Decoder {
+    fn format(&self) -> Option<PcmFormat> {
+        self.format
+    }
+
+    fn max_samples(&self) -> usize {
+        MAX_SAMPLES_PER_FRAME
+    }
+
+    fn decode(&mut self, input: &[u8], pcm: &mut [i16]) -> Result<Decoded, AudioError> {
+        if self.skip == 0 {
+            if let Some(len) = id3v2_len(input) {
+                self.skip = len?;
+            }
+        }
+        if self.skip > 0 {
+            if input.is_empty() {
+                return Err(AudioError::NeedMoreData);
+            }
+            let consumed = self.skip.min(input.len());
+            self.skip -= consumed;
+            return Ok(Decoded {
+                consumed,
+                frames: 0,
+            });
+        }
+
+        let (offset, len, resync) = match self.sync(input)? {
+            Sync::Garbage(consumed) => {
+                return Ok(Decoded {
+                    consumed,
+                    frames: 0,
+                })
+            }
+            Sync::Frame {
+                offset,
+                len,
+                resync,
+            } => (offset, len, resync),
+        };
+        let frame = &input[offset..offset + len];
+        if layer_bits(frame) != 1 {
+            return Err(AudioError::Unsupported);
+        }
+        let channels = if is_mono(frame) { 1 } else { 2 };
+        if pcm.len() < frame_samples(frame) * channels {
+            return Err(AudioError::BufferTooSmall);
+        }
+        let consumed = offset + len;
+        if resync {
+            self.reset();
+            self.header.copy_from_slice(&frame[..HDR_SIZE]);
+            if is_info_frame(frame) {
+                return Ok(Decoded {
+                    consumed,
+                    frames: 0,
+                });
+            }
+        }
+        self.header.copy_from_slice(&frame[..HDR_SIZE]);
+        self.format = Some(PcmFormat {
+            sample_rate: sample_rate_hz(frame),
+            channels: channels as u8,
+        });
+        let frames = self.decode_frame(frame, pcm);
+        Ok(Decoded { consumed, frames })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MPEG-1 layer III, 128 kbit/s, 44.1 kHz, mono, no CRC.
+    const HEADER: [u8; 4] = [0xff, 0xfb, 0x90, 0xc0];
+    const FRAME_LEN: usize = 417;
+    const TAG_LEN: usize = 10 + 20;
+
+    /// An ID3v2 tag followed by three frames whose zero side info encodes
+    /// silence.
+    fn stream() -> [u8; TAG_LEN + 3 * FRAME_LEN] {
+        let mut data = [0; TAG_LEN + 3 * FRAME_LEN];
+        data[..10].copy_from_slice(b"ID3\x04\x00\x00\x00\x00\x00\x14");
+        for frame in data[TAG_LEN..].chunks_mut(FRAME_LEN) {
+            frame[..4].copy_from_slice(&HEADER);
+        }
+        data
+    }
+
+    #[test]
+    fn skips_tag_and_decodes_silence() {
+        let data = stream();
+        let mut decoder = Mp3Decoder::new();
+        let mut pcm = [1; MAX_SAMPLES_PER_FRAME];
+        let tag = decoder.decode(&data, &mut pcm).unwrap();
+        assert_eq!(
+            tag,
+            Decoded {
+                consumed: TAG_LEN,
+                frames: 0
+            }
+        );
+
+        let mut pos = TAG_LEN;
+        let mut frames = 0;
+        while pos < data.len() {
+            let decoded = decoder.decode(&data[pos..], &mut pcm).unwrap();
+            pos += decoded.consumed;
+            frames += decoded.frames;
+        }
+        assert_eq!(frames, 3 * 1152);
+        assert!(pcm[..1152].iter().all(|&s| s == 0));
+        assert_eq!(
+            decoder.format(),
+            Some(PcmFormat {
+                sample_rate: 44100,
+                channels: 1
+            })
+        );
+    }
+
+    #[test]
+    fn skips_info_frame_and_garbage() {
+        let mut data = stream();
+        let first = TAG_LEN;
+        // Side info of a mono MPEG-1 frame is 17 bytes.
+        data[first + 4 + 17..first + 4 + 21].copy_from_slice(b"Info");
+        data[first - 3..first].copy_from_slice(&[0xff, 0x00, 0x12]);
+        let mut decoder = Mp3Decoder::new();
+        let mut pcm = [0; MAX_SAMPLES_PER_FRAME];
+
+        // The frame after the garbage is the information frame: both go.
+        let skipped = decoder.decode(&data[first - 3..], &mut pcm).unwrap();
+        assert_eq!(
+            skipped,
+            Decoded {
+                consumed: 3 + FRAME_LEN,
+                frames: 0
+            }
+        );
+        let audio = decoder
+            .decode(&data[first + FRAME_LEN..], &mut pcm)
+            .unwrap();
+        assert_eq!(audio.frames, 1152);
+    }
+
+    #[test]
+    fn reports_partial_frames_and_other_layers() {
+        let data = stream();
+        let frames = &data[TAG_LEN..];
+        let mut decoder = Mp3Decoder::new();
+        let mut pcm = [0; MAX_SAMPLES_PER_FRAME];
+        assert_eq!(
+            decoder.decode(&frames[..FRAME_LEN - 1], &mut pcm),
+            Err(AudioError::NeedMoreData)
+        );
+        assert_eq!(
+            decoder.decode(frames, &mut pcm[..1000]),
+            Err(AudioError::BufferTooSmall)
+        );
+
+        // Layer II at 160 kbit/s: 522-byte frames.
+        let header = [0xff, 0xfd, 0x90, 0xc0];
+        assert_eq!(frame_len(&header), 522);
+        let mut layer2 = [0; 2 * 522];
+        for frame in layer2.chunks_mut(522) {
+            frame[..4].copy_from_slice(&header);
+        }
+        assert_eq!(
+            Mp3Decoder::new().decode(&layer2, &mut pcm),
+            Err(AudioError::Unsupported)
+        );
+    }
+}
@@ -0,0 +1,449 @@
+//! Ogg page framing (RFC 3533): page headers, the page CRC and the
+//! reassembly of packets that span several pages.
+
+use crate::audio::AudioError;
+
+const CAPTURE: &[u8; 4] = b"OggS";
+const HEADER_LEN: usize = 27;
+/// Header, 255 lacing values and 255 full segments.
+pub const MAX_PAGE_LEN: usize = HEADER_LEN + 255 + 255 * 255;
+
+const FLAG_CONTINUED: u8 = 0x01;
+const FLAG_BOS: u8 = 0x02;
+const FLAG_EOS: u8 = 0x04;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PageError {
+    NeedMoreData,
+    /// No valid page at the front of the input: drop this many bytes, which
+    /// brings the next capture pattern (if any) to the front.
+    Garbage(usize),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Page<'a> {
+    flags: u8,
+    /// Position after the last packet that ends on this page, -1 if none.
+    pub granule: i64,
+    pub serial: u32,
+    pub sequence: u32,
+    lacing: &'a [u8],
+    body: &'a [u8],
+}
+
+impl<'a> Page<'a> {
+    /// Parses and checks the page at the front of `input`; returns it with
+    /// its length in bytes.
+    pub fn parse(input: &'a [u8]) -> Result<(Self, usize), PageError> {
+        let garbage = || {
+            let next = input
+                .windows(4)
+                .skip(1)
+                .position(|w| w == CAPTURE)
+                .map_or(input.len().saturating_sub(3).max(1), |i| i + 1);
+            PageError::Garbage(next)
+        };
+        if input.len() < HEADER_LEN {
+            if !CAPTURE.starts_with(&input[..input.len().min(4)]) {
+                return Err(garbage());
+            }
+            return Err(PageError::NeedMoreData);
+        }
+        if &input[..4] != CAPTURE || input[4] != 0 {
+            return Err(garbage());
+        }
+        let segments = input[26] as usize;
+        let lacing = input
+            .get(HEADER_LEN..HEADER_LEN + segments)
+            .ok_or(PageError::NeedMoreData)?;
+        let body_len: usize = lacing.iter().map(|&l| l as usize).sum();
+        let len = HEADER_LEN + segments + body_len;
+        let page = input.get(..len).ok_or(PageError::NeedMoreData)?;
+
+        let le32 =
+            |at: usize| u32::from_le_bytes([page[at], page[at + 1], page[at + 2], page[at + 3]]);
+        let stored = le32(22);
+        let crc = [&page[..22], &[0; 4], &page[26..]]
+            .iter()
+            .fold(0u32, |crc, part| crc32(crc, part));
+        if crc != stored {
+            return Err(garbage());
+        }
+        let granule = le32(6) as u64 | (le32(10) as u64) << 32;
+        Ok((
+            Self {
+                flags: page[5],
+                granule: granule as i64,
+                serial: le32(14),
+                sequence: le32(18),
+                lacing,
+                body: &page[HEADER_LEN + segments..],
+            },
+            len,
+        ))
+    }
+
+    /// The first segment continues a packet from the previous page.
+    pub fn continued(&self) -> bool {
+        self.flags & FLAG_CONTINUED != 0
+    }
+
+    /// First page of a logical stream.
+    pub fn bos(&self) -> bool {
+        self.flags & FLAG_BOS != 0
+    }
+
+    /// Last page of a logical stream.
+    pub fn eos(&self) -> bool {
+        self.flags & FLAG_EOS != 0
+    }
+
+    /// Packet pieces of the page in order; the flag tells whether the piece
+    /// finishes its packet (the last one may continue on the next page).
+    pub fn segments(&self) -> impl Iterator<Item = (&'a [u8], bool)> {
+        let (lacing, body) = (self.lacing, self.body);
+        let mut index = 0;
+        let mut offset = 0;
+        core::iter::from_fn(move || {
+            if index >= lacing.len() {
+                return None;
+            }
+            let start = offset;
+            while index < lacing.len() {
+                let l = lacing[index];
+                offset += l as usize;
+                index += 1;
+                if l < 255 {
+                    return Some((&body[start..offset], true));
+                }
+            }
+            Some((&body[start..offset], false))
+        })
+    }
+}
+
+fn crc32(crc: u32, data: &[u8]) -> u32 {
+    data.iter().fold(crc, |crc, &b| {
+        let mut crc = crc ^ (b as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                crc << 1 ^ 0x04c1_1db7
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Packet<'a> {
+    pub data: &'a [u8],
+    pub serial: u32,
+    /// First packet of the logical stream (the codec identification header).
+    pub bos: bool,
+    /// Last packet of the logical stream.
+    pub eos: bool,
+    /// Granule position of the page, on the last packet finishing there.
+    pub granule: Option<i64>,
+}
+
+/// Pulls whole packets out of a stream of pages, following the first
+/// logical stream and the ones chained after it. Pieces of packets that
+/// span pages are gathered in an `N`-byte buffer; larger packets (cover art
+/// in comment headers...) and packets broken by a lost page are dropped.
+pub struct PacketReader<const N: usize> {
+    serial: Option<u32>,
+    next_sequence: u32,
+    /// Index of the next piece of the page at the front of the input.
+    piece: usize,
+    partial: [u8; N],
+    partial_len: usize,
+    /// A packet is being gathered and none of its pieces was lost.
+    partial_ok: bool,
+    ended: bool,
+}
+
+impl<const N: usize> PacketReader<N> {
+    pub const fn new() -> Self {
+        Self {
+            serial: None,
+            next_sequence: 0,
+            piece: 0,
+            partial: [0; N],
+            partial_len: 0,
+            partial_ok: false,
+            ended: false,
+        }
+    }
+
+    /// Next packet from the page at the front of `input`, and the bytes to
+    /// consume: 0 while the page still holds packets, the page length once
+    /// it is exhausted. `None` with a non-zero count means nothing usable
+    /// (foreign stream, garbage, a packet continuing on the next page).
+    pub fn read<'a>(
+        &'a mut self,
+        input: &'a [u8],
+    ) -> Result<(Option<Packet<'a>>, usize), AudioError> {
+        let (page, len) = match Page::parse(input) {
+            Ok(page) => page,
+            Err(PageError::NeedMoreData) => return Err(AudioError::NeedMoreData),
+            Err(PageError::Garbage(skip)) => {
+                self.piece = 0;
+                return Ok((None, skip));
+            }
+        };
+        if self.piece == 0 && !self.enter(&page) {
+            return Ok((None, len));
+        }
+
+        let count = page.segments().count();
+        let last_finished = page
+            .segments()
+            .enumerate()
+            .filter(|(_, (_, complete))| *complete)
+            .last()
+            .map(|(i, _)| i);
+        let Some((index, (data, complete))) = page.segments().enumerate().nth(self.piece) else {
+            self.piece = 0;
+            return Ok((None, len));
+        };
+        let last = index + 1 == count;
+        self.piece = if last { 0 } else { index + 1 };
+        let consumed = if last { len } else { 0 };
+        let continues = index == 0 && page.continued();
+
+        let mut data = data;
+        if continues || !complete {
+            if !continues {
+                self.partial_len = 0;
+                self.partial_ok = true;
+            }
+            let end = self.partial_len + data.len();
+            if self.partial_ok && end <= N {
+                self.partial[self.partial_len..end].copy_from_slice(data);
+                self.partial_len = end;
+            } else {
+                self.partial_ok = false;
+            }
+            if !complete {
+                return Ok((None, consumed));
+            }
+            if !core::mem::take(&mut self.partial_ok) {
+                return Ok((None, consumed));
+            }
+            data = &self.partial[..self.partial_len];
+        }
+        let bos = page.bos() && index == 0;
+        let packet = Packet {
+            data,
+            serial: page.serial,
+            bos,
+            eos: page.eos() && last,
+            granule: (Some(index) == last_finished).then_some(page.granule),
+        };
+        Ok((Some(packet), consumed))
+    }
+
+    /// Forgets the stream position, after a seek.
+    pub fn reset(&mut self) {
+        self.piece = 0;
+        self.partial_ok = false;
+        self.next_sequence = 0;
+    }
+
+    /// Bookkeeping when a page is first seen; false if it is to be skipped.
+    fn enter(&mut self, page: &Page) -> bool {
+        if page.bos() && (self.serial.is_none() || self.ended) {
+            self.serial = Some(page.serial);
+            self.ended = false;
+            self.partial_ok = false;
+        } else if self.serial != Some(page.serial) {
+            return false;
+        }
+        if page.sequence != self.next_sequence && !page.bos() {
+            // Lost page: whatever was being gathered is incomplete.
+            self.partial_ok = false;
+        }
+        self.next_sequence = page.sequence.wrapping_add(1);
+        if page.eos() {
+            self.ended = true;
+        }
+        if !page.continued() {
+            self.partial_ok = false;
+        } else if !self.partial_ok {
+            // Joined mid-packet: skip the piece.
+            self.piece = 1;
+        }
+        true
+    }
+}
+
+impl<const N: usize> Default for PacketReader<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Writes a page holding `packets` (the first continuing a packet if
+    /// `flags` says so; the last left open if `open`), returns its length.
+    pub(crate) fn write_page(
+        out: &mut [u8],
+        flags: u8,
+        granule: i64,
+        serial: u32,
+        sequence: u32,
+        packets: &[&[u8]],
+        open: bool,
+    ) -> usize {
+        let mut segments = 0;
+        let mut body = HEADER_LEN + 255;
+        let mut lacing = [0u8; 255];
+        for (i, packet) in packets.iter().enumerate() {
+            let mut left = packet.len();
+            loop {
+                let l = left.min(255);
+                lacing[segments] = l as u8;
+                segments += 1;
+                left -= l;
+                if left == 0 && (l < 255 || (open && i + 1 == packets.len())) {
+                    break;
+                }
+            }
+            out[body..body + packet.len()].copy_from_slice(packet);
+            body += packet.len();
+        }
+        let body_len = body - HEADER_LEN - 255;
+        out.copy_within(HEADER_LEN + 255..body, HEADER_LEN + segments);
+        out[..4].copy_from_slice(CAPTURE);
+        out[4] = 0;
+        out[5] = flags;
+        out[6..14].copy_from_slice(&granule.to_le_bytes());
+        out[14..18].copy_from_slice(&serial.to_le_bytes());
+        out[18..22].copy_from_slice(&sequence.to_le_bytes());
+        out[22..26].fill(0);
+        out[26] = segments as u8;
+        out[HEADER_LEN..HEADER_LEN + segments].copy_from_slice(&lacing[..segments]);
+        let len = HEADER_LEN + segments + body_len;
+        let crc = crc32(0, &out[..len]);
+        out[22..26].copy_from_slice(&crc.to_le_bytes());
+        len
+    }
+
+    #[test]
+    fn checks_page_crc_and_skips_to_capture() {
+        let mut data = [0u8; 400];
+        data[..3].copy_from_slice(b"xyz");
+        let len = write_page(&mut data[3..], FLAG_BOS, 0, 7, 0, &[b"hello"], false);
+        let (page, page_len) = Page::parse(&data[3..]).unwrap();
+        assert_eq!((page_len, page.serial, page.bos()), (len, 7, true));
+        assert_eq!(page.segments().next(), Some((&b"hello"[..], true)));
+        assert_eq!(Page::parse(&data).err(), Some(PageError::Garbage(3)));
+        assert_eq!(
+            Page::parse(&data[3..3 + len - 1]).err(),
+            Some(PageError::NeedMoreData)
+        );
+        data[3 + len - 1] ^= 1;
+        assert!(matches!(
+            Page::parse(&data[3..]),
+            Err(PageError::Garbage(_))
+        ));
+    }
+
+    fn spanning_stream(out: &mut [u8], second_sequence: u32) -> usize {
+        let big = [0x55u8; 600];
+        let mut len = write_page(out, FLAG_BOS, 0, 9, 0, &[b"head", &big[..510]], true);
+        len += write_page(
+            &mut out[len..],
+            FLAG_CONTINUED | FLAG_EOS,
+            1000,
+            9,
+            second_sequence,
+            &[&big[510..], b"tail"],
+            false,
+        );
+        len
+    }
+
+    fn next<'a, const N: usize>(
+        reader: &'a mut PacketReader<N>,
+        data: &'a [u8],
+        pos: &mut usize,
+    ) -> Option<Packet<'a>> {
+        let (packet, consumed) = reader.read(&data[*pos..]).unwrap();
+        *pos += consumed;
+        packet
+    }
+
+    #[test]
+    fn joins_packets_across_pages() {
+        let mut data = [0u8; 2048];
+        let len = spanning_stream(&mut data, 1);
+        let mut reader = PacketReader::<1024>::new();
+        let mut pos = 0;
+
+        let head = next(&mut reader, &data[..len], &mut pos).unwrap();
+        assert_eq!(
+            (head.data, head.bos, head.granule),
+            (&b"head"[..], true, Some(0))
+        );
+        assert!(next(&mut reader, &data[..len], &mut pos).is_none());
+        let big = next(&mut reader, &data[..len], &mut pos).unwrap();
+        assert_eq!(big.data.len(), 600);
+        assert!(big.data.iter().all(|&b| b == 0x55));
+        let tail = next(&mut reader, &data[..len], &mut pos).unwrap();
+        assert_eq!(
+            (tail.data, tail.eos, tail.granule),
+            (&b"tail"[..], true, Some(1000))
+        );
+        assert_eq!(pos, len);
+    }
+
+    #[test]
+    fn drops_packets_broken_by_lost_page_or_too_large() {
+        let mut data = [0u8; 2048];
+        let len = spanning_stream(&mut data, 2);
+        let mut reader = PacketReader::<1024>::new();
+        let mut pos = 0;
+        let mut seen = 0;
+        while pos < len {
+            if let Some(packet) = next(&mut reader, &data[..len], &mut pos) {
+                assert!(packet.data.len() < 600);
+                seen += 1;
+            }
+        }
+        assert_eq!(seen, 2);
+
+        let len = spanning_stream(&mut data, 1);
+        let mut reader = PacketReader::<512>::new();
+        let (mut pos, mut seen) = (0, 0);
+        while pos < len {
+            if let Some(packet) = next(&mut reader, &data[..len], &mut pos) {
+                assert!(packet.data.len() < 600);
+                seen += 1;
+            }
+        }
+        assert_eq!(seen, 2);
+    }
+
+    #[test]
+    fn follows_first_logical_stream() {
+        let mut data = [0u8; 512];
+        let mut len = write_page(&mut data, FLAG_BOS, 0, 1, 0, &[b"one"], false);
+        len += write_page(&mut data[len..], FLAG_BOS, 0, 2, 0, &[b"two"], false);
+        len += write_page(&mut data[len..], 0, 5, 1, 1, &[b"three"], false);
+        let mut reader = PacketReader::<64>::new();
+        let mut pos = 0;
+        assert_eq!(
+            next(&mut reader, &data[..len], &mut pos).unwrap().data,
+            b"one"
+        );
+        assert!(next(&mut reader, &data[..len], &mut pos).is_none());
+        let three = next(&mut reader, &data[..len], &mut pos).unwrap();
+        assert_eq!((three.data, three.granule), (&b"three"[..], Some(5)));
+    }
+}
@@ -0,0 +1,585 @@
+//! Ogg Opus (RFC 7845) and the Opus packet layer (RFC 6716 §3): the
+//! identification and comment headers, the TOC byte, splitting packets
+//! into frames and the pre-skip/end-trim timing. SILK/CELT synthesis is
+//! not part of this library; it plugs in through `OpusBackend`.
+
+use crate::audio::{AudioDecoder, AudioError, Decoded, PcmFormat};
+use crate::ogg::PacketReader;
+
+/// Opus always decodes at 48 kHz; granule positions count at this rate.
+pub const SAMPLE_RATE: u32 = 48_000;
+pub const MAX_CHANNELS: usize = 8;
+/// 120 ms, the longest packet.
+pub const MAX_PACKET_SAMPLES: usize = 5760;
+const MAX_FRAMES: usize = 48;
+const MAX_FRAME_LEN: usize = 1275;
+/// RFC 7845 lets demuxers drop larger packets.
+pub const MAX_PACKET_LEN: usize = 61_440;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OpusHead {
+    pub version: u8,
+    pub channels: u8,
+    /// Samples at 48 kHz to drop at the start of the stream.
+    pub pre_skip: u16,
+    /// Sample rate of the original input, for information only.
+    pub input_sample_rate: u32,
+    /// Q7.8 dB gain to apply to the output.
+    pub output_gain: i16,
+    pub mapping_family: u8,
+    pub streams: u8,
+    pub coupled_streams: u8,
+    pub mapping: [u8; MAX_CHANNELS],
+}
+
+impl OpusHead {
+    pub fn parse(packet: &[u8]) -> Result<Self, AudioError> {
+        if packet.len() < 19 || &packet[..8] != b"OpusHead" {
+            return Err(AudioError::Unsupported);
+        }
+        let version = packet[8];
+        // Major version 0 only; minor versions stay compatible.
+        if version >> 4 != 0 {
+            return Err(AudioError::Unsupported);
+        }
+        let channels = packet[9];
+        if channels == 0 || channels as usize > MAX_CHANNELS {
+            return Err(AudioError::Unsupported);
+        }
+        let mut head = Self {
+            version,
+            channels,
+            pre_skip: u16::from_le_bytes([packet[10], packet[11]]),
+            input_sample_rate: u32::from_le_bytes([packet[12], packet[13], packet[14], packet[15]]),
+            output_gain: i16::from_le_bytes([packet[16], packet[17]]),
+            mapping_family: packet[18],
+            streams: 1,
+            coupled_streams: channels - 1,
+            mapping: [0, 1, 0, 0, 0, 0, 0, 0],
+        };
+        if head.mapping_family == 0 {
+            if channels > 2 {
+                return Err(AudioError::Unsupported);
+            }
+            return Ok(head);
+        }
+        let table = packet
+            .get(19..21 + channels as usize)
+            .ok_or(AudioError::Unsupported)?;
+        head.streams = table[0];
+        head.coupled_streams = table[1];
+        let total = head.streams as usize + head.coupled_streams as usize;
+        if head.streams == 0 || head.coupled_streams > head.streams || total > 255 {
+            return Err(AudioError::Unsupported);
+        }
+        for (slot, &index) in head.mapping.iter_mut().zip(&table[2..]) {
+            // 255 is a silent channel.
+            if index != 255 && index as usize >= total {
+                return Err(AudioError::Unsupported);
+            }
+            *slot = index;
+        }
+        Ok(head)
+    }
+
+    /// Playback position, in 48 kHz samples, of a granule position.
+    pub fn pcm_position(&self, granule: i64) -> u64 {
+        (granule.max(0) as u64).saturating_sub(self.pre_skip as u64)
+    }
+}
+
+/// `OpusTags` comment header: vendor string and `KEY=value` comments.
+#[derive(Clone, Copy, Debug)]
+pub struct OpusTags<'a> {
+    pub vendor: &'a str,
+    comments: &'a [u8],
+    count: u32,
+}
+
+impl<'a> OpusTags<'a> {
+    pub fn parse(packet: &'a [u8]) -> Result<Self, AudioError> {
+        let rest = packet
+            .strip_prefix(b"OpusTags")
+            .ok_or(AudioError::Unsupported)?;
+        let (vendor, rest) = take_string(rest).ok_or(AudioError::Unsupported)?;
+        let count = rest.get(..4).ok_or(AudioError::Unsupported)?;
+        Ok(Self {
+            vendor: core::str::from_utf8(vendor).map_err(|_| AudioError::Unsupported)?,
+            comments: &rest[4..],
+            count: u32::from_le_bytes([count[0], count[1], count[2], count[3]]),
+        })
+    }
+
+    /// `(key, value)` pairs; malformed entries end the iteration.
+    pub fn comments(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        let mut rest = self.comments;
+        (0..self.count).map_while(move |_| {
+            let (comment, tail) = take_string(rest)?;
+            rest = tail;
+            let comment = core::str::from_utf8(comment).ok()?;
+            comment.split_once('=')
+        })
+    }
+
+    /// First value of `key`, compared case-insensitively as the format
+    /// requires (`TITLE`, `ARTIST`...).
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.comments()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+}
+
+fn take_string(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let len = data.get(..4)?;
+    let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+    let rest = &data[4..];
+    (len <= rest.len()).then(|| rest.split_at(len))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    Silk,
+    Hybrid,
+    Celt,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Bandwidth {
+    /// 4 kHz.
+    Narrow,
+    /// 6 kHz.
+    Medium,
+    /// 8 kHz.
+    Wide,
+    /// 12 kHz.
+    SuperWide,
+    /// 20 kHz.
+    Full,
+}
+
+/// Table-of-contents byte opening every Opus packet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Toc {
+    pub mode: Mode,
+    pub bandwidth: Bandwidth,
+    /// Samples per frame at 48 kHz (120 to 2880).
+    pub frame_samples: u16,
+    pub stereo: bool,
+    /// Frame count code, 0 to 3.
+    pub code: u8,
+}
+
+impl Toc {
+    pub fn parse(toc: u8) -> Self {
+        use Bandwidth::*;
+        let config = toc >> 3;
+        let size = config & 3;
+        let (mode, bandwidth, frame_samples) = match config {
+            0..=11 => (
+                Mode::Silk,
+                [Narrow, Medium, Wide][config as usize / 4],
+                [480, 960, 1920, 2880][size as usize],
+            ),
+            12..=15 => (
+                Mode::Hybrid,
+                if config < 14 { SuperWide } else { Full },
+                [480, 960][size as usize & 1],
+            ),
+            _ => (
+                Mode::Celt,
+                [Narrow, Wide, SuperWide, Full][(config as usize - 16) / 4],
+                [120, 240, 480, 960][size as usize],
+            ),
+        };
+        Self {
+            mode,
+            bandwidth,
+            frame_samples,
+            stereo: toc & 0x04 != 0,
+            code: toc & 3,
+        }
+    }
+}
+
+/// An Opus packet split into its compressed frames.
+#[derive(Clone, Copy, Debug)]
+pub struct OpusPacket<'a> {
+    pub toc: Toc,
+    frames: [&'a [u8]; MAX_FRAMES],
+    count: usize,
+}
+
+impl<'a> OpusPacket<'a> {
+    pub fn parse(packet: &'a [u8]) -> Result<Self, AudioError> {
+        let (&toc_byte, mut rest) = packet.split_first().ok_or(AudioError::Unsupported)?;
+        let toc = Toc::parse(toc_byte);
+        let mut frames: [&[u8]; MAX_FRAMES] = [&[]; MAX_FRAMES];
+        let count = match toc.code {
+            0 => {
+                frames[0] = rest;
+                1
+            }
+            1 => {
+                if rest.len() % 2 != 0 {
+                    return Err(AudioError::Unsupported);
+                }
+                let (a, b) = rest.split_at(rest.len() / 2);
+                frames[0] = a;
+                frames[1] = b;
+                2
+            }
+            2 => {
+                let len = take_frame_len(&mut rest)?;
+                if len > rest.len() {
+                    return Err(AudioError::Unsupported);
+                }
+                let (a, b) = rest.split_at(len);
+                frames[0] = a;
+                frames[1] = b;
+                2
+            }
+            _ => {
+                let (&header, tail) = rest.split_first().ok_or(AudioError::Unsupported)?;
+                rest = tail;
+                let count = (header & 0x3f) as usize;
+                if count == 0 || count * toc.frame_samples as usize > MAX_PACKET_SAMPLES {
+                    return Err(AudioError::Unsupported);
+                }
+                if header & 0x40 != 0 {
+                    let mut padding = 0;
+                    loop {
+                        let (&p, tail) = rest.split_first().ok_or(AudioError::Unsupported)?;
+                        rest = tail;
+                        padding += if p == 255 { 254 } else { p as usize };
+                        if p != 255 {
+                            break;
+                        }
+                    }
+                    rest = rest
+                        .get(..rest.len().wrapping_sub(padding))
+                        .ok_or(AudioError::Unsupported)?;
+                }
+                if header & 0x80 != 0 {
+                    let mut lens = [0usize; MAX_FRAMES];
+                    for len in &mut lens[..count - 1] {
+                        *len = take_frame_len(&mut rest)?;
+                    }
+                    for (frame, &len) in frames.iter_mut().zip(&lens[..count - 1]) {
+                        if len > rest.len() {
+                            return Err(AudioError::Unsupported);
+                        }
+                        (*frame, rest) = rest.split_at(len);
+                    }
+                    frames[count - 1] = rest;
+                } else {
+                    if rest.len() % count != 0 {
+                        return Err(AudioError::Unsupported);
+                    }
+                    let len = rest.len() / count;
+                    for (frame, chunk) in frames.iter_mut().zip(rest.chunks_exact(len.max(1))) {
+                        *frame = chunk;
+                    }
+                }
+                count
+            }
+        };
+        if frames[..count].iter().any(|f| f.len() > MAX_FRAME_LEN) {
+            return Err(AudioError::Unsupported);
+        }
+        Ok(Self { toc, frames, count })
+    }
+
+    /// Compressed frames; an empty one is a lost frame to conceal.
+    pub fn frames(&self) -> &[&'a [u8]] {
+        &self.frames[..self.count]
+    }
+
+    /// Samples per channel the packet decodes to, at 48 kHz.
+    pub fn samples(&self) -> usize {
+        self.count * self.toc.frame_samples as usize
+    }
+}
+
+/// Frame length coding of RFC 6716 §3.2.1: one byte below 252, else two.
+fn take_frame_len(data: &mut &[u8]) -> Result<usize, AudioError> {
+    let (&first, rest) = data.split_first().ok_or(AudioError::Unsupported)?;
+    if first < 252 {
+        *data = rest;
+        return Ok(first as usize);
+    }
+    let (&second, rest) = rest.split_first().ok_or(AudioError::Unsupported)?;
+    *data = rest;
+    Ok(first as usize + 4 * second as usize)
+}
+
+/// SILK/CELT synthesis for one stream configuration.
+pub trait OpusBackend {
+    /// Starts a new logical stream described by `head`.
+    fn configure(&mut self, head: &OpusHead) -> Result<(), AudioError>;
+
+    /// Decodes `packet` into `pcm`, interleaved with `head.channels`
+    /// channels at 48 kHz, and returns the samples written per channel
+    /// (`packet.samples()`).
+    fn decode(&mut self, packet: &OpusPacket, pcm: &mut [i16]) -> Result<usize, AudioError>;
+}
+
+enum Expect {
+    Head,
+    Tags,
+    Audio,
+}
+
+/// `AudioDecoder` over an Ogg Opus stream: demuxes the pages, checks the
+/// headers, hands packets to the backend and applies pre-skip and the
+/// end trim from the last granule position.
+pub struct OggOpusDecoder<B> {
+    backend: B,
+    packets: PacketReader<MAX_PACKET_LEN>,
+    expect: Expect,
+    head: Option<OpusHead>,
+    /// Samples (per channel, pre-skip included) handed out so far.
+    position: u64,
+    skip: u64,
+}
+
+impl<B: OpusBackend> OggOpusDecoder<B> {
+    pub const fn new(backend: B) -> Self {
+        Self {
+            backend,
+            packets: PacketReader::new(),
+            expect: Expect::Head,
+            head: None,
+            position: 0,
+            skip: 0,
+        }
+    }
+
+    pub fn head(&self) -> Option<&OpusHead> {
+        self.head.as_ref()
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+}
+
+impl<B: OpusBackend> AudioDecoder for OggOpusDecoder<B> {
+    fn format(&self) -> Option<PcmFormat> {
+        self.head.map(|head| PcmFormat {
+            sample_rate: SAMPLE_RATE,
+            channels: head.channels,
+        })
+    }
+
+    fn max_samples(&self) -> usize {
+        MAX_PACKET_SAMPLES * self.head.map_or(MAX_CHANNELS, |h| h.channels as usize)
+    }
+
+    fn decode(&mut self, input: &[u8], pcm: &mut [i16]) -> Result<Decoded, AudioError> {
+        let (packet, consumed) = self.packets.read(input)?;
+        let Some(packet) = packet else {
+            return Ok(Decoded {
+                consumed,
+                frames: 0,
+            });
+        };
+        let nothing = Decoded {
+            consumed,
+            frames: 0,
+        };
+        if packet.bos {
+            let head = OpusHead::parse(packet.data)?;
+            self.backend.configure(&head)?;
+            self.head = Some(head);
+            self.expect = Expect::Tags;
+            self.position = 0;
+            self.skip = head.pre_skip as u64;
+            return Ok(nothing);
+        }
+        match self.expect {
+            // Joined a stream without its headers.
+            Expect::Head => return Ok(nothing),
+            Expect::Tags => {
+                self.expect = Expect::Audio;
+                return Ok(nothing);
+            }
+            Expect::Audio => {}
+        }
+        let head = self.head.ok_or(AudioError::Unsupported)?;
+        let channels = head.channels as usize;
+        let Ok(opus) = OpusPacket::parse(packet.data) else {
+            return Ok(nothing);
+        };
+        if pcm.len() < opus.samples() * channels {
+            return Err(AudioError::BufferTooSmall);
+        }
+        let mut frames = self.backend.decode(&opus, pcm)?.min(opus.samples());
+        let start = self.position;
+        self.position += frames as u64;
+        if packet.eos {
+            if let Some(end) = packet.granule.filter(|&g| g >= 0) {
+                // The last page's granule position marks where audio ends.
+                let keep = (end as u64).saturating_sub(start);
+                frames = frames.min(keep as usize);
+            }
+        }
+        let dropped = (self.skip as usize).min(frames);
+        self.skip -= dropped as u64;
+        if dropped > 0 {
+            pcm.copy_within(dropped * channels..frames * channels, 0);
+        }
+        Ok(Decoded {
+            consumed,
+            frames: frames - dropped,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ogg::tests::write_page;
+
+    #[test]
+    fn parses_toc() {
+        let silk = Toc::parse(0x00);
+        assert_eq!(
+            (silk.mode, silk.bandwidth, silk.frame_samples, silk.stereo),
+            (Mode::Silk, Bandwidth::Narrow, 480, false)
+        );
+        let hybrid = Toc::parse(15 << 3 | 0x04 | 1);
+        assert_eq!(
+            (
+                hybrid.mode,
+                hybrid.bandwidth,
+                hybrid.frame_samples,
+                hybrid.code
+            ),
+            (Mode::Hybrid, Bandwidth::Full, 960, 1)
+        );
+        let celt = Toc::parse(16 << 3);
+        assert_eq!(
+            (celt.mode, celt.bandwidth, celt.frame_samples),
+            (Mode::Celt, Bandwidth::Narrow, 120)
+        );
+    }
+
+    #[test]
+    fn splits_frames() {
+        let cbr = OpusPacket::parse(&[0x01, 1, 2, 3, 4]).unwrap();
+        assert_eq!(cbr.frames(), &[&[1u8, 2][..], &[3, 4]]);
+        assert!(OpusPacket::parse(&[0x01, 1, 2, 3]).is_err());
+        let two = OpusPacket::parse(&[0x02, 1, 9, 8, 7]).unwrap();
+        assert_eq!(two.frames(), &[&[9u8][..], &[8, 7]]);
+        assert_eq!(two.samples(), 960);
+
+        // Code 3, VBR with 255 bytes of padding: 2, 300 and 3 bytes.
+        let mut packet = [0u8; 7 + 305 + 255];
+        packet[..7].copy_from_slice(&[16 << 3 | 3, 0xc3, 255, 1, 2, 252, 12]);
+        packet[7..9].fill(0xaa);
+        packet[9..309].fill(0xbb);
+        packet[309..312].fill(0xcc);
+        let vbr = OpusPacket::parse(&packet).unwrap();
+        let lens = [
+            vbr.frames()[0].len(),
+            vbr.frames()[1].len(),
+            vbr.frames()[2].len(),
+        ];
+        assert_eq!(lens, [2, 300, 3]);
+        assert_eq!(vbr.frames()[2], &[0xcc; 3]);
+        assert_eq!(vbr.samples(), 360);
+
+        // 49 frames, and 3 x 60 ms, exceed 120 ms.
+        assert!(OpusPacket::parse(&[16 << 3 | 3, 49]).is_err());
+        assert!(OpusPacket::parse(&[3 << 3 | 3, 3]).is_err());
+    }
+
+    fn head(channels: u8, pre_skip: u16) -> [u8; 19] {
+        let mut head = [0; 19];
+        head[..8].copy_from_slice(b"OpusHead");
+        head[8] = 1;
+        head[9] = channels;
+        head[10..12].copy_from_slice(&pre_skip.to_le_bytes());
+        head[12..16].copy_from_slice(&44100u32.to_le_bytes());
+        head
+    }
+
+    #[test]
+    fn parses_headers() {
+        let parsed = OpusHead::parse(&head(2, 312)).unwrap();
+        assert_eq!((parsed.channels, parsed.pre_skip), (2, 312));
+        assert_eq!(parsed.input_sample_rate, 44100);
+        assert_eq!(parsed.pcm_position(48_312), 48_000);
+        assert!(OpusHead::parse(&head(3, 0)).is_err());
+
+        let mut surround = [0u8; 27];
+        surround[..19].copy_from_slice(&head(6, 0));
+        surround[18] = 1;
+        surround[19..27].copy_from_slice(&[4, 2, 0, 4, 1, 2, 3, 5]);
+        let parsed = OpusHead::parse(&surround).unwrap();
+        assert_eq!((parsed.streams, parsed.coupled_streams), (4, 2));
+        assert_eq!(&parsed.mapping[..6], &[0, 4, 1, 2, 3, 5]);
+
+        let tags = b"OpusTags\x03\0\0\0lib\x02\0\0\0\x0b\0\0\0TITLE=Intro\x08\0\0\0artist=X";
+        let tags = OpusTags::parse(tags).unwrap();
+        assert_eq!(tags.vendor, "lib");
+        assert_eq!(tags.get("title"), Some("Intro"));
+        assert_eq!(tags.get("ARTIST"), Some("X"));
+    }
+
+    /// Fills every decoded sample with the packet's first payload byte.
+    struct Fill;
+
+    impl OpusBackend for Fill {
+        fn configure(&mut self, _: &OpusHead) -> Result<(), AudioError> {
+            Ok(())
+        }
+
+        fn decode(&mut self, packet: &OpusPacket, pcm: &mut [i16]) -> Result<usize, AudioError> {
+            let value = packet.frames()[0].first().copied().unwrap_or(0);
+            pcm[..packet.samples()].fill(value as i16);
+            Ok(packet.samples())
+        }
+    }
+
+    #[test]
+    fn applies_pre_skip_and_end_trim() {
+        let mut data = [0u8; 1024];
+        let mut len = write_page(&mut data, 0x02, 0, 3, 0, &[&head(1, 312)], false);
+        len += write_page(
+            &mut data[len..],
+            0,
+            0,
+            3,
+            1,
+            &[b"OpusTags\0\0\0\0\0\0\0\0"],
+            false,
+        );
+        // Three 20 ms CELT packets; the stream ends 2000 samples in.
+        let packets: [&[u8]; 3] = [&[31 << 3, 1], &[31 << 3, 2], &[31 << 3, 3]];
+        len += write_page(&mut data[len..], 0x04, 312 + 2000, 3, 2, &packets, false);
+
+        let mut decoder = OggOpusDecoder::new(Fill);
+        let mut pcm = [0; MAX_PACKET_SAMPLES];
+        let (mut pos, mut total) = (0, 0);
+        let mut counts = [0usize; 4];
+        while pos < len {
+            let decoded = decoder.decode(&data[pos..len], &mut pcm).unwrap();
+            pos += decoded.consumed;
+            for &s in &pcm[..decoded.frames] {
+                counts[s as usize] += 1;
+            }
+            total += decoded.frames;
+        }
+        assert_eq!(total, 2000);
+        assert_eq!(counts, [0, 960 - 312, 960, 2000 - (960 - 312) - 960]);
+        assert_eq!(
+            decoder.format(),
+            Some(PcmFormat {
+                sample_rate: 48_000,
+                channels: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Event sounds ("message-new", "battery-low"...) after the freedesktop
+//! sound theme and sound naming specifications: a name resolves through the
+//! theme, its parents and `freedesktop`, dropping `-suffix` parts until a
+//! file exists. Sounds are decoded once into an `EventSounds` cache so that
+//! playing one is a single write to the audio-service stream; a global mute
+//! turns every `play` into a no-op.
+//!
+//! Only the formats decoded in this crate are looked up (`.wav`, `.flac`,
+//! `.mp3`); the Vorbis `.oga` files of the reference theme are not.
+
+use crate::audio::{AudioDecoder, AudioError, PcmFormat, PcmSink};
+
+pub const SOUNDS_DIR: &str = "/usr/share/sounds";
+/// Fallback theme every lookup ends with.
+pub const DEFAULT_THEME: &str = "freedesktop";
+/// Output profile tried before the theme's top directory.
+const PROFILE: &str = "stereo";
+/// `.disabled` turns a sound off in a theme without falling back.
+const EXTENSIONS: [&str; 4] = ["disabled", "wav", "flac", "mp3"];
+pub const MAX_PATH: usize = 256;
+pub const MAX_NAME: usize = 64;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SoundError {
+    /// No such sound in the cache.
+    NotLoaded,
+    /// The cache has no slot or sample space left.
+    CacheFull,
+    NameTooLong,
+    Audio(AudioError),
+}
+
+impl From<AudioError> for SoundError {
+    fn from(err: AudioError) -> Self {
+        SoundError::Audio(err)
+    }
+}
+
+/// Path of a theme file, built without allocating.
+#[derive(Clone, Copy)]
+pub struct SoundPath {
+    buf: [u8; MAX_PATH],
+    len: usize,
+}
+
+impl SoundPath {
+    fn build(parts: &[&str]) -> Option<Self> {
+        let mut path = Self {
+            buf: [0; MAX_PATH],
+            len: 0,
+        };
+        for part in parts {
+            let end = path.len + part.len();
+            path.buf
+                .get_mut(path.len..end)?
+                .copy_from_slice(part.as_bytes());
+            path.len = end;
+        }
+        Some(path)
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only whole `&str`s are copied in.
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+}
+
+impl core::fmt::Debug for SoundPath {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// `message-new-instant`, then `message-new`, then `message`.
+pub fn name_fallbacks(name: &str) -> impl Iterator<Item = &str> {
+    let mut next = Some(name);
+    core::iter::from_fn(move || {
+        let current = next?;
+        next = current.rsplit_once('-').map(|(head, _)| head);
+        Some(current)
+    })
+}
+
+/// Parent themes listed by `Inherits=` in a theme's `index.theme`.
+pub fn theme_parents(index_theme: &str) -> impl Iterator<Item = &str> {
+    let mut in_theme = false;
+    let inherits = index_theme.lines().find_map(move |line| {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_theme = line == "[Sound Theme]";
+            return None;
+        }
+        let (key, value) = line.split_once('=')?;
+        (in_theme && key.trim() == "Inherits").then(|| value.trim())
+    });
+    inherits
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+/// Resolves `name` in `themes` (the selected theme followed by its parents)
+/// and then in `freedesktop`. `exists` probes a path. `None` if no theme
+/// has the sound or the first match is `.disabled`.
+pub fn lookup(
+    themes: &[&str],
+    name: &str,
+    mut exists: impl FnMut(&str) -> bool,
+) -> Option<SoundPath> {
+    let fallback = [DEFAULT_THEME];
+    let chain = themes
+        .iter()
+        .chain(fallback.iter().filter(|t| !themes.contains(t)));
+    for theme in chain {
+        for candidate in name_fallbacks(name) {
+            for dir in [PROFILE, ""] {
+                for ext in EXTENSIONS {
+                    let path = match dir {
+                        "" => SoundPath::build(&[SOUNDS_DIR, "/", theme, "/", candidate, ".", ext]),
+                        _ => SoundPath::build(&[
+                            SOUNDS_DIR, "/", theme, "/", dir, "/", candidate, ".", ext,
+                        ]),
+                    }?;
+                    if exists(path.as_str()) {
+                        return (ext != "disabled").then_some(path);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    name: [u8; MAX_NAME],
+    name_len: usize,
+    format: PcmFormat,
+    start: usize,
+    len: usize,
+}
+
+/// Decoded event sounds: up to `SLOTS` sounds sharing `SAMPLES` samples.
+pub struct EventSounds<const SLOTS: usize, const SAMPLES: usize> {
+    entries: [Option<Entry>; SLOTS],
+    pcm: [i16; SAMPLES],
+    used: usize,
+    muted: bool,
+}
+
+impl<const SLOTS: usize, const SAMPLES: usize> EventSounds<SLOTS, SAMPLES> {
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; SLOTS],
+            pcm: [0; SAMPLES],
+            used: 0,
+            muted: false,
+        }
+    }
+
+    fn find(&self, name: &str) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|e| &e.name[..e.name_len] == name.as_bytes())
+    }
+
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.find(name).is_some()
+    }
+
+    /// Decodes the whole file `data` with `decoder` and keeps it as `name`.
+    /// A sound that is already loaded is kept as it is.
+    pub fn preload<D: AudioDecoder + ?Sized>(
+        &mut self,
+        name: &str,
+        decoder: &mut D,
+        data: &[u8],
+    ) -> Result<(), SoundError> {
+        if name.len() > MAX_NAME {
+            return Err(SoundError::NameTooLong);
+        }
+        if self.is_loaded(name) {
+            return Ok(());
+        }
+        let slot = self
+            .entries
+            .iter()
+            .position(Option::is_none)
+            .ok_or(SoundError::CacheFull)?;
+        let start = self.used;
+        let mut end = start;
+        let mut format = None;
+        let mut pos = 0;
+        while pos < data.len() {
+            let decoded = match decoder.decode(&data[pos..], &mut self.pcm[end..]) {
+                Ok(decoded) => decoded,
+                Err(AudioError::NeedMoreData) => break,
+                Err(AudioError::BufferTooSmall) => return Err(SoundError::CacheFull),
+                Err(err) => return Err(err.into()),
+            };
+            pos += decoded.consumed;
+            if decoded.frames > 0 {
+                let now = decoder.format().ok_or(AudioError::Unsupported)?;
+                // One stream per sound: a format change cannot be played.
+                if format.is_some_and(|f| f != now) {
+                    return Err(AudioError::Unsupported.into());
+                }
+                format = Some(now);
+                end += decoded.frames * now.channels as usize;
+            } else if decoded.consumed == 0 {
+                break;
+            }
+        }
+        let format = format.ok_or(AudioError::Unsupported)?;
+        let mut entry = Entry {
+            name: [0; MAX_NAME],
+            name_len: name.len(),
+            format,
+            start,
+            len: end - start,
+        };
+        entry.name[..name.len()].copy_from_slice(name.as_bytes());
+        self.entries[slot] = Some(entry);
+        self.used = end;
+        Ok(())
+    }
+
+    /// Starts `name` on `sink`; `Ok(false)` when muted.
+    pub fn play<S: PcmSink + ?Sized>(&self, name: &str, sink: &mut S) -> Result<bool, SoundError> {
+        let entry = self.find(name).ok_or(SoundError::NotLoaded)?;
+        if self.muted {
+            return Ok(false);
+        }
+        sink.write(
+            entry.format,
+            &self.pcm[entry.start..entry.start + entry.len],
+        )?;
+        Ok(true)
+    }
+
+    /// Length of a loaded sound, in nanoseconds.
+    pub fn duration_ns(&self, name: &str) -> Option<u64> {
+        let entry = self.find(name)?;
+        Some(
+            entry
+                .format
+                .frames_ns((entry.len / entry.format.channels as usize) as u64),
+        )
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    pub fn muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Drops every sound, e.g. when the theme changes.
+    pub fn clear(&mut self) {
+        self.entries = [None; SLOTS];
+        self.used = 0;
+    }
+}
+
+impl<const SLOTS: usize, const SAMPLES: usize> Default for EventSounds<SLOTS, SAMPLES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wav::WavDecoder;
+
+    #[test]
+    fn resolves_through_fallbacks_and_parents() {
+        let index = "[Sound Theme]\nName=Exo\nInherits= exo-base , freedesktop\n";
+        let mut parents = theme_parents(index);
+        assert_eq!(parents.next(), Some("exo-base"));
+        assert_eq!(parents.next(), Some("freedesktop"));
+        assert_eq!(parents.next(), None);
+        assert!(name_fallbacks("message-new-instant").eq([
+            "message-new-instant",
+            "message-new",
+            "message"
+        ]));
+
+        let files = [
+            "/usr/share/sounds/exo-base/stereo/message.wav",
+            "/usr/share/sounds/freedesktop/stereo/message-new-instant.flac",
+            "/usr/share/sounds/exo/battery-low.disabled",
+            "/usr/share/sounds/freedesktop/stereo/battery-low.wav",
+        ];
+        let exists = |path: &str| files.contains(&path);
+        let themes = ["exo", "exo-base"];
+        let found = lookup(&themes, "message-new-instant", exists).unwrap();
+        // The selected theme family wins over an exact name in freedesktop.
+        assert_eq!(found.as_str(), files[0]);
+        assert!(lookup(&themes, "battery-low", exists).is_none());
+        assert!(lookup(&themes, "bell", exists).is_none());
+        let found = lookup(&[], "message-new-instant", exists).unwrap();
+        assert_eq!(found.as_str(), files[1]);
+    }
+
+    fn wav(samples: &[i16], out: &mut [u8]) -> usize {
+        let len = 44 + samples.len() * 2;
+        out[..12].copy_from_slice(b"RIFF\0\0\0\0WAVE");
+        out[12..20].copy_from_slice(b"fmt \x10\0\0\0");
+        out[20..36].copy_from_slice(&[1, 0, 1, 0, 0x80, 0xbb, 0, 0, 0, 0, 0, 0, 2, 0, 16, 0]);
+        out[36..40].copy_from_slice(b"data");
+        out[40..44].copy_from_slice(&((samples.len() * 2) as u32).to_le_bytes());
+        for (chunk, s) in out[44..len].chunks_exact_mut(2).zip(samples) {
+            chunk.copy_from_slice(&s.to_le_bytes());
+        }
+        len
+    }
+
+    struct Stream {
+        writes: usize,
+        last: [i16; 4],
+    }
+
+    impl PcmSink for Stream {
+        fn write(&mut self, format: PcmFormat, pcm: &[i16]) -> Result<(), AudioError> {
+            assert_eq!(format.sample_rate, 48_000);
+            self.writes += 1;
+            self.last[..pcm.len()].copy_from_slice(pcm);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn preloads_and_plays_one_shots() {
+        let mut sounds = EventSounds::<2, 6>::new();
+        let mut file = [0u8; 64];
+        let len = wav(&[1, 2, 3], &mut file);
+        sounds
+            .preload("message-new", &mut WavDecoder::new(), &file[..len])
+            .unwrap();
+        let len = wav(&[7, 8, 9], &mut file);
+        sounds
+            .preload("battery-low", &mut WavDecoder::new(), &file[..len])
+            .unwrap();
+        assert_eq!(
+            sounds.preload("bell", &mut WavDecoder::new(), &file[..len]),
+            Err(SoundError::CacheFull)
+        );
+        assert_eq!(sounds.duration_ns("battery-low"), Some(62_500));
+
+        let mut stream = Stream {
+            writes: 0,
+            last: [0; 4],
+        };
+        assert_eq!(sounds.play("message-new", &mut stream), Ok(true));
+        assert_eq!(&stream.last[..3], &[1, 2, 3]);
+        assert_eq!(sounds.play("battery-low", &mut stream), Ok(true));
+        assert_eq!(&stream.last[..3], &[7, 8, 9]);
+        assert_eq!(sounds.play("bell", &mut stream), Err(SoundError::NotLoaded));
+
+        sounds.set_muted(true);
+        assert_eq!(sounds.play("message-new", &mut stream), Ok(false));
+        assert_eq!(stream.writes, 2);
+
+        sounds.clear();
+        assert!(!sounds.is_loaded("message-new"));
+    }
+}
@@ -0,0 +1,229 @@
+//! RIFF/WAVE reader for integer PCM (8 to 32 bits, plain or
+//! `WAVE_FORMAT_EXTENSIBLE`), the usual format of theme event sounds.
+
+use crate::audio::{AudioDecoder, AudioError, Decoded, PcmFormat};
+
+pub const MAX_CHANNELS: usize = 8;
+/// Sample frames converted per `decode` call.
+pub const FRAMES_PER_CALL: usize = 1024;
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+enum State {
+    Riff,
+    Chunks,
+    /// Bytes left of a chunk that is being skipped, pad byte included.
+    Skip(usize),
+    /// Audio bytes left in the `data` chunk.
+    Data(usize),
+    Done,
+}
+
+pub struct WavDecoder {
+    state: State,
+    format: Option<PcmFormat>,
+    bytes_per_sample: usize,
+}
+
+impl WavDecoder {
+    pub const fn new() -> Self {
+        Self {
+            state: State::Riff,
+            format: None,
+            bytes_per_sample: 0,
+        }
+    }
+
+    fn read_fmt(&mut self, body: &[u8]) -> Result<(), AudioError> {
+        if body.len() < 16 {
+            return Err(AudioError::Unsupported);
+        }
+        let le16 = |at: usize| u16::from_le_bytes([body[at], body[at + 1]]);
+        let mut tag = le16(0);
+        if tag == FORMAT_EXTENSIBLE {
+            // The sub-format GUID starts with the plain format tag.
+            tag = body.get(24..26).map_or(0, |_| le16(24));
+        }
+        let channels = le16(2);
+        let sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+        let bits = le16(14);
+        if tag != FORMAT_PCM
+            || channels == 0
+            || channels as usize > MAX_CHANNELS
+            || !matches!(bits, 8 | 16 | 24 | 32)
+        {
+            return Err(AudioError::Unsupported);
+        }
+        self.bytes_per_sample = bits as usize / 8;
+        self.format = Some(PcmFormat {
+            sample_rate,
+            channels: channels as u8,
+        });
+        Ok(())
+    }
+}
+
+impl Default for WavDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn skipped(consumed: usize) -> Result<Decoded, AudioError> {
+    Ok(Decoded {
+        consumed,
+        frames: 0,
+    })
+}
+
+impl AudioDecoder for WavDecoder {
+    fn format(&self) -> Option<PcmFormat> {
+        self.format
+    }
+
+    fn max_samples(&self) -> usize {
+        FRAMES_PER_CALL * self.format.map_or(MAX_CHANNELS, |f| f.channels as usize)
+    }
+
+    fn decode(&mut self, input: &[u8], pcm: &mut [i16]) -> Result<Decoded, AudioError> {
+        match self.state {
+            State::Riff => {
+                let header = input.get(..12).ok_or(AudioError::NeedMoreData)?;
+                if &header[..4] != b"RIFF" || &header[8..] != b"WAVE" {
+                    return Err(AudioError::Unsupported);
+                }
+                self.state = State::Chunks;
+                skipped(12)
+            }
+            State::Chunks => {
+                let header = input.get(..8).ok_or(AudioError::NeedMoreData)?;
+                let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+                match &header[..4] {
+                    b"fmt " => {
+                        let padded = len + (len & 1);
+                        let body = input.get(8..8 + padded).ok_or(AudioError::NeedMoreData)?;
+                        self.read_fmt(&body[..len])?;
+                        skipped(8 + padded)
+                    }
+                    b"data" => {
+                        let format = self.format.ok_or(AudioError::Unsupported)?;
+                        let frame = self.bytes_per_sample * format.channels as usize;
+                        self.state = match len - len % frame {
+                            0 => State::Done,
+                            audio => State::Data(audio),
+                        };
+                        skipped(8)
+                    }
+                    _ => {
+                        self.state = State::Skip(len + (len & 1));
+                        skipped(8)
+                    }
+                }
+            }
+            State::Skip(left) => {
+                if input.is_empty() {
+                    return Err(AudioError::NeedMoreData);
+                }
+                let n = left.min(input.len());
+                self.state = match left - n {
+                    0 => State::Chunks,
+                    left => State::Skip(left),
+                };
+                skipped(n)
+            }
+            State::Data(left) => {
+                let format = self.format.ok_or(AudioError::Unsupported)?;
+                let channels = format.channels as usize;
+                let frame = self.bytes_per_sample * channels;
+                let frames = (left.min(input.len()) / frame)
+                    .min(FRAMES_PER_CALL)
+                    .min(pcm.len() / channels);
+                if frames == 0 {
+                    return Err(if pcm.len() < channels {
+                        AudioError::BufferTooSmall
+                    } else {
+                        AudioError::NeedMoreData
+                    });
+                }
+                let bytes = &input[..frames * frame];
+                for (out, sample) in pcm
+                    .iter_mut()
+                    .zip(bytes.chunks_exact(self.bytes_per_sample))
+                {
+                    // Keep the top 16 bits; 8-bit WAV is unsigned.
+                    *out = match sample.len() {
+                        1 => ((sample[0] as i16) - 128) << 8,
+                        n => i16::from_le_bytes([sample[n - 2], sample[n - 1]]),
+                    };
+                }
+                // Chunks after the audio (`LIST` tags...) are ignored.
+                self.state = match left - bytes.len() {
+                    0 => State::Done,
+                    left => State::Data(left),
+                };
+                Ok(Decoded {
+                    consumed: bytes.len(),
+                    frames,
+                })
+            }
+            State::Done if input.is_empty() => Err(AudioError::NeedMoreData),
+            State::Done => skipped(input.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_pcm_after_extra_chunks() {
+        let mut data = [0u8; 12 + 24 + 12 + 8 + 12];
+        data[..12].copy_from_slice(b"RIFF\0\0\0\0WAVE");
+        data[12..20].copy_from_slice(b"fmt \x10\0\0\0");
+        // PCM, stereo, 8 kHz, 24 bits.
+        data[20..36].copy_from_slice(&[1, 0, 2, 0, 0x40, 0x1f, 0, 0, 0, 0, 0, 0, 6, 0, 24, 0]);
+        data[36..48].copy_from_slice(b"LIST\x03\0\0\0abc\0");
+        data[48..56].copy_from_slice(b"data\x0c\0\0\0");
+        data[56..68].copy_from_slice(&[0, 0x34, 0x12, 0, 0xff, 0xff, 0, 0, 0x80, 0, 0, 0]);
+
+        let mut decoder = WavDecoder::new();
+        let mut pcm = [0i16; 8];
+        let mut out = [0i16; 4];
+        let (mut pos, mut total) = (0, 0);
+        while pos < data.len() {
+            let decoded = decoder.decode(&data[pos..], &mut pcm).unwrap();
+            pos += decoded.consumed;
+            out[total..total + decoded.frames * 2].copy_from_slice(&pcm[..decoded.frames * 2]);
+            total += decoded.frames * 2;
+        }
+        assert_eq!(out, [0x1234, -1, -32768, 0]);
+        assert_eq!(
+            decoder.format(),
+            Some(PcmFormat {
+                sample_rate: 8000,
+                channels: 2
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_float_and_waits_for_headers() {
+        let mut decoder = WavDecoder::new();
+        let mut pcm = [0i16; 8];
+        assert_eq!(
+            decoder.decode(b"RIFF", &mut pcm),
+            Err(AudioError::NeedMoreData)
+        );
+        decoder.decode(b"RIFF\0\0\0\0WAVE", &mut pcm).unwrap();
+        let mut fmt = [0u8; 24];
+        fmt[..8].copy_from_slice(b"fmt \x10\0\0\0");
+        fmt[8..24].copy_from_slice(&[3, 0, 1, 0, 0x44, 0xac, 0, 0, 0, 0, 0, 0, 4, 0, 32, 0]);
+        assert_eq!(
+            decoder.decode(&fmt[..20], &mut pcm),
+            Err(AudioError::NeedMoreData)
+        );
+        assert_eq!(decoder.decode(&fmt, &mut pcm), Err(AudioError::Unsupported));
+    }
+}
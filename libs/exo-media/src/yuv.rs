@@ -0,0 +1,352 @@
+//! YUV → XRGB8888 conversion for decoded video frames.
+//!
+//! Limited-range BT.601 / BT.709 in fixed point. Every term is computed as
+//! `((x << 6) * c) >> 16` with `c` the coefficient scaled by 1024, which is
+//! exactly what `pmulhw` does, so the SSE2 path and the scalar tail produce
+//! identical pixels.
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Matrix {
+    Bt601,
+    Bt709,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Coeffs {
+    y: i16,
+    rv: i16,
+    gu: i16,
+    gv: i16,
+    bu: i16,
+}
+
+impl Matrix {
+    fn coeffs(self) -> Coeffs {
+        match self {
+            Matrix::Bt601 => Coeffs {
+                y: 1193,
+                rv: 1634,
+                gu: 401,
+                gv: 833,
+                bu: 2066,
+            },
+            Matrix::Bt709 => Coeffs {
+                y: 1193,
+                rv: 1836,
+                gu: 218,
+                gv: 546,
+                bu: 2163,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConvertError {
+    /// Odd or zero dimensions (4:2:0 needs even sizes).
+    BadDimensions,
+    PlaneTooSmall,
+    OutputTooSmall,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Plane<'a> {
+    pub data: &'a [u8],
+    pub stride: usize,
+}
+
+impl Plane<'_> {
+    fn check(&self, row_bytes: usize, rows: usize) -> Result<(), ConvertError> {
+        let needed = self
+            .stride
+            .checked_mul(rows - 1)
+            .and_then(|n| n.checked_add(row_bytes))
+            .ok_or(ConvertError::PlaneTooSmall)?;
+        if self.stride < row_bytes || self.data.len() < needed {
+            return Err(ConvertError::PlaneTooSmall);
+        }
+        Ok(())
+    }
+
+    fn row(&self, idx: usize) -> &[u8] {
+        &self.data[idx * self.stride..]
+    }
+}
+
+/// 4:2:0 frame layout as produced by the decoders.
+#[derive(Clone, Copy, Debug)]
+pub enum Yuv420<'a> {
+    /// Three planes (VP9, AV1, H.264 software output).
+    I420 {
+        y: Plane<'a>,
+        u: Plane<'a>,
+        v: Plane<'a>,
+    },
+    /// Interleaved chroma (hardware decoders, camera capture).
+    Nv12 { y: Plane<'a>, uv: Plane<'a> },
+}
+
+#[inline(always)]
+fn term(x: i16, c: i16) -> i16 {
+    ((((x as i32) << 6) * c as i32) >> 16) as i16
+}
+
+#[inline(always)]
+fn clamp_u8(v: i16) -> u32 {
+    v.clamp(0, 255) as u32
+}
+
+#[inline(always)]
+fn pixel(c: &Coeffs, y: u8, u: u8, v: u8) -> u32 {
+    let yt = term(y as i16 - 16, c.y);
+    let u = u as i16 - 128;
+    let v = v as i16 - 128;
+    let r = yt + term(v, c.rv);
+    let g = yt - term(u, c.gu) - term(v, c.gv);
+    let b = yt + term(u, c.bu);
+    0xff00_0000 | (clamp_u8(r) << 16) | (clamp_u8(g) << 8) | clamp_u8(b)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod sse2 {
+    use super::Coeffs;
+    use core::arch::x86_64::*;
+
+    /// Converts 8 pixels. `y`, `u`, `v` hold 8 zero-extended bytes each,
+    /// chroma already duplicated horizontally.
+    #[inline(always)]
+    pub unsafe fn kernel8(c: &Coeffs, y: __m128i, u: __m128i, v: __m128i, out: *mut u32) {
+        let y = _mm_slli_epi16(_mm_sub_epi16(y, _mm_set1_epi16(16)), 6);
+        let u = _mm_slli_epi16(_mm_sub_epi16(u, _mm_set1_epi16(128)), 6);
+        let v = _mm_slli_epi16(_mm_sub_epi16(v, _mm_set1_epi16(128)), 6);
+        let yt = _mm_mulhi_epi16(y, _mm_set1_epi16(c.y));
+        let r = _mm_add_epi16(yt, _mm_mulhi_epi16(v, _mm_set1_epi16(c.rv)));
+        let g = _mm_sub_epi16(
+            _mm_sub_epi16(yt, _mm_mulhi_epi16(u, _mm_set1_epi16(c.gu))),
+            _mm_mulhi_epi16(v, _mm_set1_epi16(c.gv)),
+        );
+        let b = _mm_add_epi16(yt, _mm_mulhi_epi16(u, _mm_set1_epi16(c.bu)));
+        let zero = _mm_setzero_si128();
+        let r8 = _mm_packus_epi16(r, zero);
+        let g8 = _mm_packus_epi16(g, zero);
+        let b8 = _mm_packus_epi16(b, zero);
+        let bg = _mm_unpacklo_epi8(b8, g8);
+        let ra = _mm_unpacklo_epi8(r8, _mm_set1_epi8(-1));
+        _mm_storeu_si128(out as *mut __m128i, _mm_unpacklo_epi16(bg, ra));
+        _mm_storeu_si128(out.add(4) as *mut __m128i, _mm_unpackhi_epi16(bg, ra));
+    }
+
+    #[inline(always)]
+    pub unsafe fn load8(p: *const u8) -> __m128i {
+        _mm_unpacklo_epi8(_mm_loadl_epi64(p as *const __m128i), _mm_setzero_si128())
+    }
+
+    /// 4 chroma bytes → 8 lanes, each sample duplicated.
+    #[inline(always)]
+    pub unsafe fn load4_dup(p: *const u8) -> __m128i {
+        let raw = _mm_cvtsi32_si128((p as *const i32).read_unaligned());
+        _mm_unpacklo_epi8(_mm_unpacklo_epi8(raw, raw), _mm_setzero_si128())
+    }
+
+    /// 8 interleaved UV bytes → (u, v) with each sample duplicated.
+    #[inline(always)]
+    pub unsafe fn load_uv_dup(p: *const u8) -> (__m128i, __m128i) {
+        let uv = load8(p);
+        let u = _mm_shufflehi_epi16(_mm_shufflelo_epi16(uv, 0b10_10_00_00), 0b10_10_00_00);
+        let v = _mm_shufflehi_epi16(_mm_shufflelo_epi16(uv, 0b11_11_01_01), 0b11_11_01_01);
+        (u, v)
+    }
+}
+
+fn convert_row_i420(c: &Coeffs, y: &[u8], u: &[u8], v: &[u8], out: &mut [u32], width: usize) {
+    let mut x = 0usize;
+    #[cfg(target_arch = "x86_64")]
+    while x + 8 <= width {
+        // SAFETY: bounds were validated by `Plane::check` and the output
+        // check in `to_xrgb`; SSE2 is part of the x86_64 baseline.
+        unsafe {
+            sse2::kernel8(
+                c,
+                sse2::load8(y.as_ptr().add(x)),
+                sse2::load4_dup(u.as_ptr().add(x / 2)),
+                sse2::load4_dup(v.as_ptr().add(x / 2)),
+                out.as_mut_ptr().add(x),
+            );
+        }
+        x += 8;
+    }
+    while x < width {
+        out[x] = pixel(c, y[x], u[x / 2], v[x / 2]);
+        x += 1;
+    }
+}
+
+fn convert_row_nv12(c: &Coeffs, y: &[u8], uv: &[u8], out: &mut [u32], width: usize) {
+    let mut x = 0usize;
+    #[cfg(target_arch = "x86_64")]
+    while x + 8 <= width {
+        // SAFETY: see `convert_row_i420`; 8 pixels read 8 UV bytes at `x`.
+        unsafe {
+            let (u, v) = sse2::load_uv_dup(uv.as_ptr().add(x));
+            sse2::kernel8(
+                c,
+                sse2::load8(y.as_ptr().add(x)),
+                u,
+                v,
+                out.as_mut_ptr().add(x),
+            );
+        }
+        x += 8;
+    }
+    while x < width {
+        let chroma = x & !1;
+        out[x] = pixel(c, y[x], uv[chroma], uv[chroma + 1]);
+        x += 1;
+    }
+}
+
+/// Converts a 4:2:0 frame into `out` (XRGB8888, `out_stride` pixels per row),
+/// the format the compositor scans out.
+pub fn to_xrgb(
+    frame: &Yuv420<'_>,
+    width: usize,
+    height: usize,
+    matrix: Matrix,
+    out: &mut [u32],
+    out_stride: usize,
+) -> Result<(), ConvertError> {
+    if width == 0 || height == 0 || !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+        return Err(ConvertError::BadDimensions);
+    }
+    if out_stride < width || out.len() < out_stride * (height - 1) + width {
+        return Err(ConvertError::OutputTooSmall);
+    }
+    let c = matrix.coeffs();
+    match frame {
+        Yuv420::I420 { y, u, v } => {
+            y.check(width, height)?;
+            u.check(width / 2, height / 2)?;
+            v.check(width / 2, height / 2)?;
+            for row in 0..height {
+                convert_row_i420(
+                    &c,
+                    y.row(row),
+                    u.row(row / 2),
+                    v.row(row / 2),
+                    &mut out[row * out_stride..],
+                    width,
+                );
+            }
+        }
+        Yuv420::Nv12 { y, uv } => {
+            y.check(width, height)?;
+            uv.check(width, height / 2)?;
+            for row in 0..height {
+                convert_row_nv12(
+                    &c,
+                    y.row(row),
+                    uv.row(row / 2),
+                    &mut out[row * out_stride..],
+                    width,
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_reference_colours() {
+        let c = Matrix::Bt601.coeffs();
+        assert_eq!(pixel(&c, 16, 128, 128), 0xff00_0000);
+        assert_eq!(pixel(&c, 235, 128, 128), 0xffff_ffff);
+        // BT.601 pure red is (81, 90, 240).
+        let red = pixel(&c, 81, 90, 240);
+        assert!(red >> 16 & 0xff >= 0xfc && red >> 8 & 0xff <= 2 && red & 0xff <= 2);
+    }
+
+    #[test]
+    fn simd_and_scalar_paths_agree() {
+        const W: usize = 20;
+        const H: usize = 2;
+        let mut y = [0u8; W * H];
+        let mut u = [0u8; W / 2];
+        let mut v = [0u8; W / 2];
+        let mut uv = [0u8; W];
+        for (i, b) in y.iter_mut().enumerate() {
+            *b = (i * 37 % 256) as u8;
+        }
+        for i in 0..W / 2 {
+            u[i] = (i * 53 % 256) as u8;
+            v[i] = (255 - i * 29 % 256) as u8;
+            uv[2 * i] = u[i];
+            uv[2 * i + 1] = v[i];
+        }
+        let i420 = Yuv420::I420 {
+            y: Plane {
+                data: &y,
+                stride: W,
+            },
+            u: Plane {
+                data: &u,
+                stride: W / 2,
+            },
+            v: Plane {
+                data: &v,
+                stride: W / 2,
+            },
+        };
+        let nv12 = Yuv420::Nv12 {
+            y: Plane {
+                data: &y,
+                stride: W,
+            },
+            uv: Plane {
+                data: &uv,
+                stride: W,
+            },
+        };
+        let mut a = [0u32; W * H];
+        let mut b = [0u32; W * H];
+        to_xrgb(&i420, W, H, Matrix::Bt709, &mut a, W).unwrap();
+        to_xrgb(&nv12, W, H, Matrix::Bt709, &mut b, W).unwrap();
+        assert_eq!(a, b);
+        let c = Matrix::Bt709.coeffs();
+        for (x, &px) in a.iter().enumerate() {
+            let col = x % W;
+            assert_eq!(px, pixel(&c, y[x], u[col / 2], v[col / 2]), "pixel {x}");
+        }
+    }
+
+    #[test]
+    fn rejects_short_planes() {
+        let y = [0u8; 4];
+        let frame = Yuv420::I420 {
+            y: Plane {
+                data: &y,
+                stride: 2,
+            },
+            u: Plane {
+                data: &[],
+                stride: 1,
+            },
+            v: Plane {
+                data: &[0],
+                stride: 1,
+            },
+        };
+        let mut out = [0u32; 4];
+        assert_eq!(
+            to_xrgb(&frame, 2, 2, Matrix::Bt601, &mut out, 2),
+            Err(ConvertError::PlaneTooSmall)
+        );
+        assert_eq!(
+            to_xrgb(&frame, 3, 2, Matrix::Bt601, &mut out, 3),
+            Err(ConvertError::BadDimensions)
+        );
+    }
+}
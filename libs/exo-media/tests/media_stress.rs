@@ -0,0 +1,102 @@
+use exo_media::audio::{pump, AudioError, ByteQueue, PcmFormat, PcmSink};
+use exo_media::avsync::{FrameAction, MediaClock};
+use exo_media::buffer::FramePool;
+use exo_media::mp3::{Mp3Decoder, MAX_SAMPLES_PER_FRAME};
+use exo_media::yuv::{to_xrgb, Matrix, Plane, Yuv420};
+
+#[test]
+fn media_playback_stress() {
+    const W: usize = 64;
+    const H: usize = 16;
+    const FRAME_NS: u64 = 16_666_667;
+    let y = [128u8; W * H];
+    let u = [128u8; W * H / 4];
+    let v = [128u8; W * H / 4];
+    let frame = Yuv420::I420 {
+        y: Plane {
+            data: &y,
+            stride: W,
+        },
+        u: Plane {
+            data: &u,
+            stride: W / 2,
+        },
+        v: Plane {
+            data: &v,
+            stride: W / 2,
+        },
+    };
+    let mut out = [0u32; W * H];
+    let mut pool = FramePool::new(&[1, 2, 3]).unwrap();
+    let clock = MediaClock::start(0);
+    let (mut presented, mut dropped) = (0u32, 0u32);
+
+    for n in 0..10_000u64 {
+        let id = pool.acquire().unwrap();
+        to_xrgb(&frame, W, H, Matrix::Bt709, &mut out, W).unwrap();
+        pool.mark_ready(id, n * FRAME_NS).unwrap();
+        let (id, pts) = pool.next_ready().unwrap();
+        // Scheduling jitter of up to 60 ms pushes some frames past the drop threshold.
+        let jitter = (n % 7) * 10_000_000;
+        match clock.decide(pts, FRAME_NS, n * FRAME_NS + jitter) {
+            FrameAction::Drop => {
+                pool.discard(id).unwrap();
+                dropped += 1;
+            }
+            _ => {
+                let handle = pool.attach(id).unwrap();
+                pool.release(handle).unwrap();
+                presented += 1;
+            }
+        }
+    }
+    assert_eq!(presented + dropped, 10_000);
+    assert!(dropped > 0 && presented > dropped);
+    assert_eq!(out[0], out[W * H - 1]);
+}
+
+struct CountingSink {
+    samples: usize,
+}
+
+impl PcmSink for CountingSink {
+    fn write(&mut self, format: PcmFormat, pcm: &[i16]) -> Result<(), AudioError> {
+        assert_eq!(format.sample_rate, 44100);
+        assert!(pcm.iter().all(|&s| s == 0));
+        self.samples += pcm.len();
+        Ok(())
+    }
+}
+
+#[test]
+fn audio_pump_stress() {
+    // Silent MPEG-1 layer III frames, 128 kbit/s mono: 417 bytes each.
+    const FRAME_LEN: usize = 417;
+    const FRAMES: usize = 500;
+    let mut stream = [0u8; FRAMES * FRAME_LEN];
+    for frame in stream.chunks_mut(FRAME_LEN) {
+        frame[..4].copy_from_slice(&[0xff, 0xfb, 0x90, 0xc0]);
+    }
+    let mut decoder = Mp3Decoder::new();
+    let mut queue = ByteQueue::<2048>::new();
+    let mut pcm = [0i16; MAX_SAMPLES_PER_FRAME];
+    let mut sink = CountingSink { samples: 0 };
+    let mut read = 0;
+    let mut step = 1;
+
+    while read < stream.len() || !queue.pending().is_empty() {
+        // Reads of uneven sizes split frames at every offset.
+        let spare = queue.spare_mut();
+        let n = spare.len().min(stream.len() - read).min(step);
+        spare[..n].copy_from_slice(&stream[read..read + n]);
+        queue.commit(n);
+        read += n;
+        step = step * 7 % 997 + 1;
+        let consumed = pump(&mut decoder, queue.pending(), &mut pcm, &mut sink).unwrap();
+        queue.consume(consumed);
+        if read == stream.len() && consumed == 0 {
+            break;
+        }
+    }
+    assert_eq!(sink.samples, FRAMES * 1152);
+}
@@ -0,0 +1,870 @@
+//! IPP Everywhere client side (RFC 8010/8011, PWG 5100.14): request
+//! encoding, response parsing, printer capabilities and job tracking.
+//!
+//! Everything encodes into caller buffers; the HTTP transport is the
+//! network_server TCP socket the print service already owns.
+
+use core::fmt::Write;
+
+pub const IPP_VERSION: [u8; 2] = [2, 0];
+
+pub mod op {
+    pub const PRINT_JOB: u16 = 0x0002;
+    pub const VALIDATE_JOB: u16 = 0x0004;
+    pub const CANCEL_JOB: u16 = 0x0008;
+    pub const GET_JOB_ATTRIBUTES: u16 = 0x0009;
+    pub const GET_PRINTER_ATTRIBUTES: u16 = 0x000b;
+}
+
+pub mod tag {
+    pub const OPERATION_ATTRIBUTES: u8 = 0x01;
+    pub const JOB_ATTRIBUTES: u8 = 0x02;
+    pub const END_OF_ATTRIBUTES: u8 = 0x03;
+    pub const PRINTER_ATTRIBUTES: u8 = 0x04;
+    pub const INTEGER: u8 = 0x21;
+    pub const BOOLEAN: u8 = 0x22;
+    pub const ENUM: u8 = 0x23;
+    pub const NAME: u8 = 0x42;
+    pub const KEYWORD: u8 = 0x44;
+    pub const URI: u8 = 0x45;
+    pub const CHARSET: u8 = 0x47;
+    pub const NATURAL_LANGUAGE: u8 = 0x48;
+    pub const MIME_MEDIA_TYPE: u8 = 0x49;
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IppError {
+    BufferTooSmall,
+    Truncated,
+    /// status-code >= 0x0400 (client or server error).
+    Status(u16),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DocumentFormat {
+    Pdf,
+    PwgRaster,
+    /// Apple raster, mandatory for AirPrint-only devices.
+    Urf,
+}
+
+impl DocumentFormat {
+    pub const fn mime(self) -> &'static str {
+        match self {
+            DocumentFormat::Pdf => "application/pdf",
+            DocumentFormat::PwgRaster => "image/pwg-raster",
+            DocumentFormat::Urf => "image/urf",
+        }
+    }
+
+    const fn bit(self) -> u8 {
+        match self {
+            DocumentFormat::Pdf => 1,
+            DocumentFormat::PwgRaster => 2,
+            DocumentFormat::Urf => 4,
+        }
+    }
+
+    fn from_mime(mime: &[u8]) -> Option<Self> {
+        [
+            DocumentFormat::Pdf,
+            DocumentFormat::PwgRaster,
+            DocumentFormat::Urf,
+        ]
+        .into_iter()
+        .find(|f| f.mime().as_bytes() == mime)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Sides {
+    OneSided,
+    LongEdge,
+    ShortEdge,
+}
+
+impl Sides {
+    pub const fn keyword(self) -> &'static str {
+        match self {
+            Sides::OneSided => "one-sided",
+            Sides::LongEdge => "two-sided-long-edge",
+            Sides::ShortEdge => "two-sided-short-edge",
+        }
+    }
+}
+
+/// Job template chosen in the print dialog.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PrintOptions<'a> {
+    pub copies: u16,
+    pub sides: Sides,
+    pub color: bool,
+    /// PWG media name, e.g. `iso_a4_210x297mm`.
+    pub media: &'a str,
+}
+
+impl Default for PrintOptions<'_> {
+    fn default() -> Self {
+        Self {
+            copies: 1,
+            sides: Sides::OneSided,
+            color: true,
+            media: "iso_a4_210x297mm",
+        }
+    }
+}
+
+pub struct IppWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> IppWriter<'a> {
+    pub fn new(buf: &'a mut [u8], operation: u16, request_id: u32) -> Result<Self, IppError> {
+        let mut w = Self { buf, len: 0 };
+        w.put(&IPP_VERSION)?;
+        w.put(&operation.to_be_bytes())?;
+        w.put(&request_id.to_be_bytes())?;
+        Ok(w)
+    }
+
+    fn put(&mut self, bytes: &[u8]) -> Result<(), IppError> {
+        let dst = self
+            .buf
+            .get_mut(self.len..self.len + bytes.len())
+            .ok_or(IppError::BufferTooSmall)?;
+        dst.copy_from_slice(bytes);
+        self.len += bytes.len();
+        Ok(())
+    }
+
+    pub fn group(&mut self, group_tag: u8) -> Result<&mut Self, IppError> {
+        self.put(&[group_tag])?;
+        Ok(self)
+    }
+
+    pub fn attr(&mut self, value_tag: u8, name: &str, value: &[u8]) -> Result<&mut Self, IppError> {
+        if name.len() > u16::MAX as usize || value.len() > u16::MAX as usize {
+            return Err(IppError::BufferTooSmall);
+        }
+        self.put(&[value_tag])?;
+        self.put(&(name.len() as u16).to_be_bytes())?;
+        self.put(name.as_bytes())?;
+        self.put(&(value.len() as u16).to_be_bytes())?;
+        self.put(value)?;
+        Ok(self)
+    }
+
+    /// Extra value of a 1setOf attribute (empty name).
+    pub fn value(&mut self, value_tag: u8, value: &[u8]) -> Result<&mut Self, IppError> {
+        self.attr(value_tag, "", value)
+    }
+
+    pub fn str(&mut self, value_tag: u8, name: &str, value: &str) -> Result<&mut Self, IppError> {
+        self.attr(value_tag, name, value.as_bytes())
+    }
+
+    pub fn int(&mut self, value_tag: u8, name: &str, value: i32) -> Result<&mut Self, IppError> {
+        self.attr(value_tag, name, &value.to_be_bytes())
+    }
+
+    /// The operation attributes every request starts with.
+    pub fn operation_attrs(
+        &mut self,
+        printer_uri: &str,
+        user: &str,
+    ) -> Result<&mut Self, IppError> {
+        self.group(tag::OPERATION_ATTRIBUTES)?
+            .str(tag::CHARSET, "attributes-charset", "utf-8")?
+            .str(tag::NATURAL_LANGUAGE, "attributes-natural-language", "en")?
+            .str(tag::URI, "printer-uri", printer_uri)?
+            .str(tag::NAME, "requesting-user-name", user)
+    }
+
+    /// Terminates the attribute section; the document data follows.
+    pub fn finish(mut self) -> Result<usize, IppError> {
+        self.put(&[tag::END_OF_ATTRIBUTES])?;
+        Ok(self.len)
+    }
+}
+
+pub fn encode_get_printer_attributes(
+    buf: &mut [u8],
+    request_id: u32,
+    printer_uri: &str,
+    user: &str,
+) -> Result<usize, IppError> {
+    let mut w = IppWriter::new(buf, op::GET_PRINTER_ATTRIBUTES, request_id)?;
+    w.operation_attrs(printer_uri, user)?
+        .str(tag::KEYWORD, "requested-attributes", "printer-description")?
+        .value(tag::KEYWORD, b"job-template")?;
+    w.finish()
+}
+
+/// Print-Job request header; the document bytes are sent right after it
+/// in the same HTTP body.
+pub fn encode_print_job(
+    buf: &mut [u8],
+    request_id: u32,
+    printer_uri: &str,
+    user: &str,
+    job_name: &str,
+    format: DocumentFormat,
+    options: &PrintOptions<'_>,
+) -> Result<usize, IppError> {
+    let mut w = IppWriter::new(buf, op::PRINT_JOB, request_id)?;
+    w.operation_attrs(printer_uri, user)?
+        .str(tag::NAME, "job-name", job_name)?
+        .str(tag::MIME_MEDIA_TYPE, "document-format", format.mime())?
+        .group(tag::JOB_ATTRIBUTES)?
+        .int(tag::INTEGER, "copies", options.copies.max(1) as i32)?
+        .str(tag::KEYWORD, "sides", options.sides.keyword())?
+        .str(
+            tag::KEYWORD,
+            "print-color-mode",
+            if options.color { "color" } else { "monochrome" },
+        )?
+        .str(tag::KEYWORD, "media", options.media)?;
+    w.finish()
+}
+
+fn encode_job_op(
+    buf: &mut [u8],
+    operation: u16,
+    request_id: u32,
+    printer_uri: &str,
+    user: &str,
+    job_id: i32,
+) -> Result<usize, IppError> {
+    let mut w = IppWriter::new(buf, operation, request_id)?;
+    w.operation_attrs(printer_uri, user)?
+        .int(tag::INTEGER, "job-id", job_id)?;
+    w.finish()
+}
+
+pub fn encode_get_job_attributes(
+    buf: &mut [u8],
+    request_id: u32,
+    printer_uri: &str,
+    user: &str,
+    job_id: i32,
+) -> Result<usize, IppError> {
+    encode_job_op(
+        buf,
+        op::GET_JOB_ATTRIBUTES,
+        request_id,
+        printer_uri,
+        user,
+        job_id,
+    )
+}
+
+pub fn encode_cancel_job(
+    buf: &mut [u8],
+    request_id: u32,
+    printer_uri: &str,
+    user: &str,
+    job_id: i32,
+) -> Result<usize, IppError> {
+    encode_job_op(buf, op::CANCEL_JOB, request_id, printer_uri, user, job_id)
+}
+
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let dst = self
+            .buf
+            .get_mut(self.len..self.len + s.len())
+            .ok_or(core::fmt::Error)?;
+        dst.copy_from_slice(s.as_bytes());
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+/// HTTP/1.1 request line and headers for an IPP POST of `body_len` bytes.
+pub fn encode_http_post(
+    buf: &mut [u8],
+    host: &str,
+    path: &str,
+    body_len: usize,
+) -> Result<usize, IppError> {
+    let mut w = SliceWriter { buf, len: 0 };
+    write!(
+        w,
+        "POST {path} HTTP/1.1\r\nHost: {host}\r\nContent-Type: application/ipp\r\nContent-Length: {body_len}\r\n\r\n"
+    )
+    .map_err(|_| IppError::BufferTooSmall)?;
+    Ok(w.len)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IppAttribute<'a> {
+    pub group: u8,
+    pub value_tag: u8,
+    pub name: &'a [u8],
+    pub value: &'a [u8],
+}
+
+impl IppAttribute<'_> {
+    pub fn int(&self) -> Option<i32> {
+        let b: [u8; 4] = self.value.try_into().ok()?;
+        Some(i32::from_be_bytes(b))
+    }
+
+    pub fn bool(&self) -> Option<bool> {
+        match self.value {
+            [b] if self.value_tag == tag::BOOLEAN => Some(*b != 0),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct IppResponse<'a> {
+    pub status: u16,
+    pub request_id: u32,
+    attrs: &'a [u8],
+}
+
+impl<'a> IppResponse<'a> {
+    /// Parses the IPP body of an HTTP response. Error statuses are
+    /// returned as `IppError::Status`.
+    pub fn parse(body: &'a [u8]) -> Result<Self, IppError> {
+        if body.len() < 8 {
+            return Err(IppError::Truncated);
+        }
+        let status = u16::from_be_bytes([body[2], body[3]]);
+        if status >= 0x0400 {
+            return Err(IppError::Status(status));
+        }
+        Ok(Self {
+            status,
+            request_id: u32::from_be_bytes([body[4], body[5], body[6], body[7]]),
+            attrs: &body[8..],
+        })
+    }
+
+    /// Attributes in wire order. Additional values of a 1setOf carry
+    /// the name of the attribute they belong to.
+    pub fn attributes(&self) -> impl Iterator<Item = Result<IppAttribute<'a>, IppError>> {
+        let data = self.attrs;
+        let mut pos = 0usize;
+        let mut group = 0u8;
+        let mut last_name: &'a [u8] = &[];
+        let mut done = false;
+        core::iter::from_fn(move || loop {
+            if done {
+                return None;
+            }
+            let Some(&t) = data.get(pos) else {
+                done = true;
+                return Some(Err(IppError::Truncated));
+            };
+            if t == tag::END_OF_ATTRIBUTES {
+                done = true;
+                return None;
+            }
+            if t < 0x10 {
+                group = t;
+                pos += 1;
+                continue;
+            }
+            let field = |at: usize| -> Option<(&'a [u8], usize)> {
+                let len = u16::from_be_bytes([*data.get(at)?, *data.get(at + 1)?]) as usize;
+                Some((data.get(at + 2..at + 2 + len)?, at + 2 + len))
+            };
+            let Some((name, next)) = field(pos + 1) else {
+                done = true;
+                return Some(Err(IppError::Truncated));
+            };
+            let Some((value, next)) = field(next) else {
+                done = true;
+                return Some(Err(IppError::Truncated));
+            };
+            pos = next;
+            if !name.is_empty() {
+                last_name = name;
+            }
+            return Some(Ok(IppAttribute {
+                group,
+                value_tag: t,
+                name: last_name,
+                value,
+            }));
+        })
+    }
+
+    pub fn find(&self, name: &str) -> Option<IppAttribute<'a>> {
+        self.attributes()
+            .map_while(Result::ok)
+            .find(|a| a.name == name.as_bytes())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PrinterState {
+    Idle,
+    Processing,
+    Stopped,
+}
+
+impl PrinterState {
+    pub fn from_enum(v: i32) -> Option<Self> {
+        match v {
+            3 => Some(PrinterState::Idle),
+            4 => Some(PrinterState::Processing),
+            5 => Some(PrinterState::Stopped),
+            _ => None,
+        }
+    }
+}
+
+/// What the print dialog needs to know about a printer.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PrinterCaps {
+    formats: u8,
+    pub color: bool,
+    pub duplex: bool,
+}
+
+impl PrinterCaps {
+    pub fn from_response(resp: &IppResponse<'_>) -> Self {
+        let mut caps = Self::default();
+        for attr in resp.attributes().map_while(Result::ok) {
+            match attr.name {
+                b"document-format-supported" => caps.add_format(attr.value),
+                b"color-supported" => caps.color |= attr.bool().unwrap_or(false),
+                b"sides-supported" => caps.duplex |= attr.value.starts_with(b"two-sided"),
+                _ => {}
+            }
+        }
+        caps
+    }
+
+    /// Capabilities advertised in the `_ipp._tcp` DNS-SD TXT record
+    /// (`pdl=`, `Color=`, `Duplex=`), before the printer is queried.
+    pub fn from_txt<'t>(entries: impl IntoIterator<Item = &'t [u8]>) -> Self {
+        let mut caps = Self::default();
+        for entry in entries {
+            let mut kv = entry.splitn(2, |&b| b == b'=');
+            let (Some(key), Some(value)) = (kv.next(), kv.next()) else {
+                continue;
+            };
+            match key {
+                b"pdl" => value.split(|&b| b == b',').for_each(|m| caps.add_format(m)),
+                b"Color" => caps.color = value == b"T",
+                b"Duplex" => caps.duplex = value == b"T",
+                _ => {}
+            }
+        }
+        caps
+    }
+
+    fn add_format(&mut self, mime: &[u8]) {
+        if let Some(f) = DocumentFormat::from_mime(mime) {
+            self.formats |= f.bit();
+        }
+    }
+
+    pub fn supports(&self, format: DocumentFormat) -> bool {
+        self.formats & format.bit() != 0
+    }
+
+    /// PDF is sent untouched; otherwise the print service rasterises.
+    pub fn preferred_format(&self) -> Option<DocumentFormat> {
+        [
+            DocumentFormat::Pdf,
+            DocumentFormat::PwgRaster,
+            DocumentFormat::Urf,
+        ]
+        .into_iter()
+        .find(|&f| self.supports(f))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum JobState {
+    Pending,
+    Held,
+    Processing,
+    Stopped,
+    Canceled,
+    Aborted,
+    Completed,
+}
+
+impl JobState {
+    pub fn from_enum(v: i32) -> Option<Self> {
+        Some(match v {
+            3 => JobState::Pending,
+            4 => JobState::Held,
+            5 => JobState::Processing,
+            6 => JobState::Stopped,
+            7 => JobState::Canceled,
+            8 => JobState::Aborted,
+            9 => JobState::Completed,
+            _ => return None,
+        })
+    }
+
+    pub const fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            JobState::Canceled | JobState::Aborted | JobState::Completed
+        )
+    }
+}
+
+pub const MAX_TRACKED_JOBS: usize = 16;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TrackedJob {
+    pub job_id: i32,
+    pub printer: u16,
+    pub owner: u32,
+    pub state: JobState,
+}
+
+/// Jobs submitted by this machine, polled with Get-Job-Attributes until
+/// they reach a terminal state.
+#[derive(Clone, Copy, Debug)]
+pub struct JobTracker {
+    jobs: [Option<TrackedJob>; MAX_TRACKED_JOBS],
+}
+
+impl Default for JobTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobTracker {
+    pub const fn new() -> Self {
+        Self {
+            jobs: [None; MAX_TRACKED_JOBS],
+        }
+    }
+
+    /// Records the job created by a Print-Job response. Finished jobs are
+    /// evicted first when the table is full.
+    pub fn submitted(
+        &mut self,
+        printer: u16,
+        owner: u32,
+        resp: &IppResponse<'_>,
+    ) -> Option<TrackedJob> {
+        let job_id = resp.find("job-id")?.int()?;
+        let state = resp
+            .find("job-state")
+            .and_then(|a| a.int())
+            .and_then(JobState::from_enum)
+            .unwrap_or(JobState::Pending);
+        let slot = match self.jobs.iter().position(Option::is_none) {
+            Some(i) => i,
+            None => self
+                .jobs
+                .iter()
+                .position(|j| j.is_some_and(|j| j.state.is_terminal()))?,
+        };
+        let job = TrackedJob {
+            job_id,
+            printer,
+            owner,
+            state,
+        };
+        self.jobs[slot] = Some(job);
+        Some(job)
+    }
+
+    /// Applies a Get-Job-Attributes response. Returns the job if its
+    /// state changed.
+    pub fn update(&mut self, printer: u16, resp: &IppResponse<'_>) -> Option<TrackedJob> {
+        let job_id = resp.find("job-id")?.int()?;
+        let state = JobState::from_enum(resp.find("job-state")?.int()?)?;
+        let job = self
+            .jobs
+            .iter_mut()
+            .flatten()
+            .find(|j| j.job_id == job_id && j.printer == printer)?;
+        if job.state == state {
+            return None;
+        }
+        job.state = state;
+        Some(*job)
+    }
+
+    /// Jobs still worth polling.
+    pub fn active(&self) -> impl Iterator<Item = &TrackedJob> {
+        self.jobs
+            .iter()
+            .flatten()
+            .filter(|j| !j.state.is_terminal())
+    }
+
+    pub fn get(&self, printer: u16, job_id: i32) -> Option<TrackedJob> {
+        self.jobs
+            .iter()
+            .flatten()
+            .find(|j| j.job_id == job_id && j.printer == printer)
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(buf: &mut [u8], job_state: i32) -> usize {
+        // Responses share the request encoding, with a status code in
+        // place of the operation id.
+        let mut w = IppWriter::new(buf, 0x0000, 7).unwrap();
+        w.group(tag::OPERATION_ATTRIBUTES)
+            .unwrap()
+            .str(tag::CHARSET, "attributes-charset", "utf-8")
+            .unwrap()
+            .group(tag::JOB_ATTRIBUTES)
+            .unwrap()
+            .int(tag::INTEGER, "job-id", 42)
+            .unwrap()
+            .int(tag::ENUM, "job-state", job_state)
+            .unwrap();
+        w.finish().unwrap()
+    }
+
+    #[test]
+    fn encodes_print_job_and_tracks_it() {
+        let mut req = [0u8; 512];
+        let n = encode_print_job(
+            &mut req,
+            1,
+            "ipp://printer.local/ipp/print",
+            "alice",
+            "report.pdf",
+            DocumentFormat::Pdf,
+            &PrintOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(&req[..8], &[2, 0, 0, 2, 0, 0, 0, 1]);
+        assert_eq!(req[n - 1], tag::END_OF_ATTRIBUTES);
+        let parsed = IppResponse::parse(&req[..n]).unwrap();
+        assert_eq!(parsed.find("copies").unwrap().int(), Some(1));
+        assert_eq!(
+            parsed.find("document-format").unwrap().value,
+            b"application/pdf"
+        );
+
+        let mut http = [0u8; 128];
+        let h = encode_http_post(&mut http, "printer.local:631", "/ipp/print", n).unwrap();
+        assert!(http[..h].starts_with(b"POST /ipp/print HTTP/1.1\r\nHost: printer.local:631\r\n"));
+        assert!(http[..h].ends_with(b"\r\n\r\n"));
+
+        let mut tracker = JobTracker::new();
+        let mut buf = [0u8; 128];
+        let len = response(&mut buf, 3);
+        let job = tracker
+            .submitted(0, 1000, &IppResponse::parse(&buf[..len]).unwrap())
+            .unwrap();
+        assert_eq!((job.job_id, job.state), (42, JobState::Pending));
+        let len = response(&mut buf, 9);
+        let done = tracker
+            .update(0, &IppResponse::parse(&buf[..len]).unwrap())
+            .unwrap();
+        assert_eq!(done.state, JobState::Completed);
+        assert_eq!(tracker.active().count(), 0);
+    }
+
+    #[test]
+    fn reads_capabilities_from_attributes_and_txt() {
+        let mut buf = [0u8; 256];
+        let mut w = IppWriter::new(&mut buf, 0x0000, 1).unwrap();
+        w.group(tag::PRINTER_ATTRIBUTES)
+            .unwrap()
+            .str(
+                tag::MIME_MEDIA_TYPE,
+                "document-format-supported",
+                "image/urf",
+            )
+            .unwrap()
+            .value(tag::MIME_MEDIA_TYPE, b"image/pwg-raster")
+            .unwrap()
+            .attr(tag::BOOLEAN, "color-supported", &[1])
+            .unwrap()
+            .str(tag::KEYWORD, "sides-supported", "one-sided")
+            .unwrap();
+        let n = w.finish().unwrap();
+        let caps = PrinterCaps::from_response(&IppResponse::parse(&buf[..n]).unwrap());
+        assert!(caps.color && !caps.duplex);
+        assert_eq!(caps.preferred_format(), Some(DocumentFormat::PwgRaster));
+
+        let txt: [&[u8]; 3] = [
+            b"rp=ipp/print",
+            b"pdl=application/pdf,image/urf",
+            b"Duplex=T",
+        ];
+        let caps = PrinterCaps::from_txt(txt);
+        assert!(caps.duplex && !caps.color);
+        assert_eq!(caps.preferred_format(), Some(DocumentFormat::Pdf));
+
+        assert_eq!(
+            IppResponse::parse(&[2, 0, 0x04, 0x06, 0, 0, 0, 1, 3]).err(),
+            Some(IppError::Status(0x0406))
+        );
+    }
+
+    /// Header of a successful response followed by `attrs`.
+    fn body(attrs: &[u8]) -> [u8; 64] {
+        let mut buf = [0u8; 64];
+        buf[..8].copy_from_slice(&[2, 0, 0, 0, 0, 0, 0, 5]);
+        buf[8..8 + attrs.len()].copy_from_slice(attrs);
+        buf
+    }
+
+    #[test]
+    fn truncated_attribute_groups_end_in_an_error() {
+        let mut buf = [0u8; 128];
+        let n = response(&mut buf, 5);
+        // Every cut before the end-of-attributes tag is reported once,
+        // after the attributes that were complete.
+        for cut in 8..n - 1 {
+            let resp = IppResponse::parse(&buf[..cut]).unwrap();
+            let mut attrs = resp.attributes();
+            let mut complete = 0;
+            loop {
+                match attrs.next() {
+                    Some(Ok(_)) => complete += 1,
+                    Some(Err(e)) => {
+                        assert_eq!(e, IppError::Truncated);
+                        break;
+                    }
+                    None => panic!("cut at {cut} ended without an error"),
+                }
+            }
+            assert!(complete <= 3);
+            assert!(attrs.next().is_none());
+        }
+        // A body cut inside the header is refused outright.
+        assert_eq!(
+            IppResponse::parse(&buf[..7]).err(),
+            Some(IppError::Truncated)
+        );
+        // `find` stops at the damage instead of reading past it.
+        let resp = IppResponse::parse(&buf[..n - 3]).unwrap();
+        assert_eq!(resp.find("job-id").unwrap().int(), Some(42));
+        assert!(resp.find("job-state").is_none());
+    }
+
+    #[test]
+    fn oversized_lengths_do_not_read_past_the_body() {
+        // Name length 0xffff with four bytes left.
+        let buf = body(&[tag::JOB_ATTRIBUTES, tag::INTEGER, 0xff, 0xff, b'j', b'o']);
+        let resp = IppResponse::parse(&buf[..14]).unwrap();
+        let mut attrs = resp.attributes();
+        assert_eq!(attrs.next(), Some(Err(IppError::Truncated)));
+        assert_eq!(attrs.next(), None);
+
+        // Valid name, value length larger than what follows.
+        let raw = [
+            tag::JOB_ATTRIBUTES,
+            tag::INTEGER,
+            0,
+            2,
+            b'i',
+            b'd',
+            0x80,
+            0x00,
+            0,
+            0,
+            0,
+            1,
+            tag::END_OF_ATTRIBUTES,
+        ];
+        let buf = body(&raw);
+        let resp = IppResponse::parse(&buf[..8 + raw.len()]).unwrap();
+        assert_eq!(resp.attributes().next(), Some(Err(IppError::Truncated)));
+        assert!(resp.find("id").is_none());
+    }
+
+    #[test]
+    fn unknown_tags_are_passed_through() {
+        let mut buf = [0u8; 128];
+        let mut w = IppWriter::new(&mut buf, 0x0000, 9).unwrap();
+        // Unassigned group tag, unassigned and out-of-band value tags.
+        w.group(0x0f)
+            .unwrap()
+            .attr(0x5f, "vendor-extension", b"\x01\x02")
+            .unwrap()
+            .attr(0x13, "printer-alert", &[])
+            .unwrap()
+            .group(tag::PRINTER_ATTRIBUTES)
+            .unwrap()
+            .attr(tag::BOOLEAN, "color-supported", &[1])
+            .unwrap();
+        let n = w.finish().unwrap();
+        let resp = IppResponse::parse(&buf[..n]).unwrap();
+        let attrs: [IppAttribute<'_>; 3] = {
+            let mut it = resp.attributes().map(Result::unwrap);
+            [it.next().unwrap(), it.next().unwrap(), it.next().unwrap()]
+        };
+        assert_eq!((attrs[0].group, attrs[0].value_tag), (0x0f, 0x5f));
+        assert_eq!(attrs[0].value, b"\x01\x02");
+        assert_eq!((attrs[1].value_tag, attrs[1].value), (0x13, &[][..]));
+        assert_eq!(attrs[2].group, tag::PRINTER_ATTRIBUTES);
+        assert_eq!(resp.attributes().count(), 3);
+        // Unknown values never satisfy typed accessors.
+        assert_eq!(attrs[0].int(), None);
+        assert_eq!(attrs[0].bool(), None);
+        let caps = PrinterCaps::from_response(&resp);
+        assert!(caps.color && caps.preferred_format().is_none());
+    }
+
+    #[test]
+    fn error_status_codes_are_reported() {
+        let status = |code: u16| {
+            let [hi, lo] = code.to_be_bytes();
+            let body = [2, 0, hi, lo, 0, 0, 0, 3, tag::END_OF_ATTRIBUTES];
+            IppResponse::parse(&body)
+                .map(|resp| (resp.status, resp.request_id, resp.attributes().count()))
+        };
+        // client-error-bad-request, client-error-not-found,
+        // server-error-internal-error, server-error-busy.
+        for code in [0x0400, 0x0406, 0x0500, 0x0507] {
+            assert_eq!(status(code), Err(IppError::Status(code)));
+        }
+        // successful-ok-* statuses still carry their attributes.
+        for code in [0x0000, 0x0001, 0x0002] {
+            assert_eq!(status(code), Ok((code, 3, 0)));
+        }
+        // A Print-Job refused by the printer leaves nothing to track.
+        let mut tracker = JobTracker::new();
+        let mut buf = [0u8; 128];
+        let n = response(&mut buf, 3);
+        buf[2..4].copy_from_slice(&0x040au16.to_be_bytes());
+        assert_eq!(
+            IppResponse::parse(&buf[..n]).err(),
+            Some(IppError::Status(0x040a))
+        );
+        assert_eq!(tracker.active().count(), 0);
+        let missing_job_id = body(&[tag::END_OF_ATTRIBUTES]);
+        let resp = IppResponse::parse(&missing_job_id[..9]).unwrap();
+        assert!(tracker.submitted(0, 1000, &resp).is_none());
+    }
+
+    #[test]
+    fn writer_refuses_buffers_that_are_too_small() {
+        let mut buf = [0u8; 7];
+        assert_eq!(
+            IppWriter::new(&mut buf, op::PRINT_JOB, 1).err(),
+            Some(IppError::BufferTooSmall)
+        );
+        let mut buf = [0u8; 64];
+        assert_eq!(
+            encode_get_printer_attributes(&mut buf, 1, "ipp://printer.local/ipp/print", "alice"),
+            Err(IppError::BufferTooSmall)
+        );
+    }
+}
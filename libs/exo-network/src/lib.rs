@@ -1,6 +1,9 @@
 #![no_std]
 #![deny(unsafe_op_in_unsafe_fn)]
 
+pub mod ipp;
+pub mod mdns;
+
 use smoltcp::time::Instant;
 use smoltcp::wire::{Ipv4Address, Ipv4Cidr};
 
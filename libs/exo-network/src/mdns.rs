@@ -0,0 +1,1205 @@
+//! Multicast DNS (RFC 6762) and DNS-SD (RFC 6763): a responder announcing
+//! this host and its services, and a browser cache of services seen on the
+//! link (printers, file shares, other Exo machines).
+//!
+//! Packets go through a UDP socket bound to 5353 joined to 224.0.0.251;
+//! this module only builds and parses them.
+
+pub const MDNS_PORT: u16 = 5353;
+pub const MDNS_IPV4_GROUP: [u8; 4] = [224, 0, 0, 251];
+
+pub const TYPE_A: u16 = 1;
+pub const TYPE_PTR: u16 = 12;
+pub const TYPE_TXT: u16 = 16;
+pub const TYPE_SRV: u16 = 33;
+pub const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+const CACHE_FLUSH: u16 = 0x8000;
+
+/// Host records (A, SRV) use 120 s, the rest 75 min (RFC 6762 §10).
+pub const HOST_TTL: u32 = 120;
+pub const SERVICE_TTL: u32 = 4500;
+
+const SERVICES_META: &str = "_services._dns-sd._udp.local";
+
+pub const NAME_MAX: usize = 128;
+pub const TXT_MAX: usize = 200;
+pub const MAX_LOCAL_SERVICES: usize = 8;
+pub const MAX_DISCOVERED: usize = 16;
+const MAX_HOSTS: usize = 8;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MdnsError {
+    BufferTooSmall,
+    Malformed,
+    NameTooLong,
+    Full,
+}
+
+/// Dotted domain name in a fixed buffer.
+#[derive(Clone, Copy)]
+pub struct NameBuf {
+    buf: [u8; NAME_MAX],
+    len: u8,
+}
+
+impl NameBuf {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; NAME_MAX],
+            len: 0,
+        }
+    }
+
+    pub fn from_parts(parts: &[&str]) -> Result<Self, MdnsError> {
+        let mut name = Self::new();
+        for part in parts.iter().filter(|p| !p.is_empty()) {
+            if name.len != 0 {
+                name.push(b".")?;
+            }
+            name.push(part.as_bytes())?;
+        }
+        Ok(name)
+    }
+
+    fn push(&mut self, bytes: &[u8]) -> Result<(), MdnsError> {
+        let at = self.len as usize;
+        let dst = self
+            .buf
+            .get_mut(at..at + bytes.len())
+            .ok_or(MdnsError::NameTooLong)?;
+        dst.copy_from_slice(bytes);
+        self.len += bytes.len() as u8;
+        Ok(())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len as usize]
+    }
+
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(self.as_bytes()).unwrap_or("")
+    }
+
+    /// DNS names compare case-insensitively.
+    pub fn eq_name(&self, other: &str) -> bool {
+        self.as_bytes().eq_ignore_ascii_case(other.as_bytes())
+    }
+
+    /// `true` if the name is `<label>.<suffix>` with a single leading label.
+    fn is_child_of(&self, suffix: &[u8]) -> bool {
+        let name = self.as_bytes();
+        name.len() > suffix.len() + 1
+            && name[name.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+            && name[name.len() - suffix.len() - 1] == b'.'
+            && !name[..name.len() - suffix.len() - 1].contains(&b'.')
+    }
+
+    /// First label, i.e. the human-readable instance name.
+    pub fn first_label(&self) -> &str {
+        self.as_str().split('.').next().unwrap_or("")
+    }
+}
+
+impl Default for NameBuf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for NameBuf {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+fn be16(b: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([*b.get(at)?, *b.get(at + 1)?]))
+}
+
+fn be32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Reads a possibly compressed name starting at `pos`. Returns the name and
+/// the offset just past it in the original record.
+fn read_name(msg: &[u8], mut pos: usize) -> Result<(NameBuf, usize), MdnsError> {
+    let mut name = NameBuf::new();
+    let mut end = None;
+    let mut jumps = 0;
+    loop {
+        let len = *msg.get(pos).ok_or(MdnsError::Malformed)? as usize;
+        if len == 0 {
+            return Ok((name, end.unwrap_or(pos + 1)));
+        }
+        if len & 0xc0 == 0xc0 {
+            let target = (be16(msg, pos).ok_or(MdnsError::Malformed)? & 0x3fff) as usize;
+            end.get_or_insert(pos + 2);
+            jumps += 1;
+            if jumps > 16 || target >= msg.len() {
+                return Err(MdnsError::Malformed);
+            }
+            pos = target;
+            continue;
+        }
+        // 0x40 and 0x80 label types are unused on the wire (RFC 6891 §5).
+        if len & 0xc0 != 0 {
+            return Err(MdnsError::Malformed);
+        }
+        let label = msg
+            .get(pos + 1..pos + 1 + len)
+            .ok_or(MdnsError::Malformed)?;
+        if name.len != 0 {
+            name.push(b".")?;
+        }
+        name.push(label)?;
+        pos += 1 + len;
+    }
+}
+
+struct Record<'a> {
+    name: NameBuf,
+    rtype: u16,
+    ttl: u32,
+    rdata_at: usize,
+    rdata: &'a [u8],
+}
+
+/// Calls `f` on every answer, authority and additional record of a
+/// response. Queries are skipped.
+fn for_each_record<'a>(
+    packet: &'a [u8],
+    mut f: impl FnMut(&Record<'a>) -> Result<(), MdnsError>,
+) -> Result<(), MdnsError> {
+    let flags = be16(packet, 2).ok_or(MdnsError::Malformed)?;
+    if flags & 0x8000 == 0 {
+        return Ok(());
+    }
+    let qd = be16(packet, 4).ok_or(MdnsError::Malformed)?;
+    let records = [6, 8, 10]
+        .iter()
+        .map(|&at| be16(packet, at).map(|n| n as usize))
+        .sum::<Option<usize>>()
+        .ok_or(MdnsError::Malformed)?;
+    let mut pos = 12usize;
+    for _ in 0..qd {
+        pos = read_name(packet, pos)?.1 + 4;
+    }
+    for _ in 0..records {
+        let (name, next) = read_name(packet, pos)?;
+        let rtype = be16(packet, next).ok_or(MdnsError::Malformed)?;
+        let ttl = be32(packet, next + 4).ok_or(MdnsError::Malformed)?;
+        let rdlen = be16(packet, next + 8).ok_or(MdnsError::Malformed)? as usize;
+        let rdata_at = next + 10;
+        let rdata = packet
+            .get(rdata_at..rdata_at + rdlen)
+            .ok_or(MdnsError::Malformed)?;
+        pos = rdata_at + rdlen;
+        f(&Record {
+            name,
+            rtype,
+            ttl,
+            rdata_at,
+            rdata,
+        })?;
+    }
+    Ok(())
+}
+
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn new(buf: &'a mut [u8], flags: u16) -> Result<Self, MdnsError> {
+        let mut w = Self { buf, len: 0 };
+        w.put(&[0, 0])?;
+        w.put(&flags.to_be_bytes())?;
+        w.put(&[0; 8])?;
+        Ok(w)
+    }
+
+    fn put(&mut self, bytes: &[u8]) -> Result<(), MdnsError> {
+        let dst = self
+            .buf
+            .get_mut(self.len..self.len + bytes.len())
+            .ok_or(MdnsError::BufferTooSmall)?;
+        dst.copy_from_slice(bytes);
+        self.len += bytes.len();
+        Ok(())
+    }
+
+    fn bump(&mut self, section: usize) {
+        let at = 4 + section * 2;
+        let n = u16::from_be_bytes([self.buf[at], self.buf[at + 1]]) + 1;
+        self.buf[at..at + 2].copy_from_slice(&n.to_be_bytes());
+    }
+
+    fn name(&mut self, name: &[u8]) -> Result<(), MdnsError> {
+        for label in name.split(|&b| b == b'.').filter(|l| !l.is_empty()) {
+            if label.len() > 63 {
+                return Err(MdnsError::NameTooLong);
+            }
+            self.put(&[label.len() as u8])?;
+            self.put(label)?;
+        }
+        self.put(&[0])
+    }
+
+    fn question(&mut self, name: &[u8], qtype: u16) -> Result<(), MdnsError> {
+        self.name(name)?;
+        self.put(&qtype.to_be_bytes())?;
+        self.put(&CLASS_IN.to_be_bytes())?;
+        self.bump(0);
+        Ok(())
+    }
+
+    /// Writes a record header and returns the offset of its RDLENGTH.
+    fn record_head(
+        &mut self,
+        section: usize,
+        name: &[u8],
+        rtype: u16,
+        flush: bool,
+        ttl: u32,
+    ) -> Result<usize, MdnsError> {
+        self.name(name)?;
+        self.put(&rtype.to_be_bytes())?;
+        let class = if flush {
+            CLASS_IN | CACHE_FLUSH
+        } else {
+            CLASS_IN
+        };
+        self.put(&class.to_be_bytes())?;
+        self.put(&ttl.to_be_bytes())?;
+        let rdlen_at = self.len;
+        self.put(&[0, 0])?;
+        self.bump(section);
+        Ok(rdlen_at)
+    }
+
+    fn record_end(&mut self, rdlen_at: usize) {
+        let rdlen = (self.len - rdlen_at - 2) as u16;
+        self.buf[rdlen_at..rdlen_at + 2].copy_from_slice(&rdlen.to_be_bytes());
+    }
+}
+
+/// Builds a one-shot browse query for `service_type` (e.g. `_ipp._tcp.local`).
+pub fn build_query(service_type: &str, out: &mut [u8]) -> Result<usize, MdnsError> {
+    let mut w = Writer::new(out, 0)?;
+    w.question(service_type.as_bytes(), TYPE_PTR)?;
+    Ok(w.len)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LocalService {
+    /// `<instance>.<type>.local`
+    pub instance: NameBuf,
+    /// `<type>.local`, e.g. `_smb._tcp.local`.
+    pub service_type: NameBuf,
+    pub port: u16,
+    txt: [u8; TXT_MAX],
+    txt_len: u8,
+}
+
+impl LocalService {
+    pub fn txt(&self) -> &[u8] {
+        &self.txt[..self.txt_len as usize]
+    }
+}
+
+/// One of our names is in use by another host.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Conflict {
+    Host,
+    /// Index returned by [`MdnsResponder::register`].
+    Service(usize),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Rec {
+    Meta(usize),
+    Ptr(usize),
+    Srv(usize),
+    Txt(usize),
+    A,
+}
+
+const MAX_RECS: usize = 4 * MAX_LOCAL_SERVICES + 1;
+
+struct RecList {
+    recs: [Option<Rec>; MAX_RECS],
+    len: usize,
+}
+
+impl RecList {
+    const fn new() -> Self {
+        Self {
+            recs: [None; MAX_RECS],
+            len: 0,
+        }
+    }
+
+    fn add(&mut self, rec: Rec) {
+        if self.len < MAX_RECS && !self.contains(rec) {
+            self.recs[self.len] = Some(rec);
+            self.len += 1;
+        }
+    }
+
+    fn contains(&self, rec: Rec) -> bool {
+        self.recs[..self.len].contains(&Some(rec))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = Rec> + '_ {
+        self.recs[..self.len].iter().flatten().copied()
+    }
+}
+
+/// Announces this host (`<hostname>.local` A record) and registered services.
+pub struct MdnsResponder {
+    hostname: NameBuf,
+    addr: [u8; 4],
+    services: [Option<LocalService>; MAX_LOCAL_SERVICES],
+}
+
+impl MdnsResponder {
+    pub fn new(hostname: &str, addr: [u8; 4]) -> Result<Self, MdnsError> {
+        Ok(Self {
+            hostname: NameBuf::from_parts(&[hostname, "local"])?,
+            addr,
+            services: [None; MAX_LOCAL_SERVICES],
+        })
+    }
+
+    pub fn hostname(&self) -> &NameBuf {
+        &self.hostname
+    }
+
+    /// New host name after a conflict; the caller probes again.
+    pub fn set_hostname(&mut self, hostname: &str) -> Result<(), MdnsError> {
+        self.hostname = NameBuf::from_parts(&[hostname, "local"])?;
+        Ok(())
+    }
+
+    /// New address after a DHCP lease; the caller re-announces.
+    pub fn set_addr(&mut self, addr: [u8; 4]) {
+        self.addr = addr;
+    }
+
+    /// Registers `<instance>.<service_type>.local`. `txt` holds
+    /// `key=value` strings, stored in wire format.
+    pub fn register(
+        &mut self,
+        instance: &str,
+        service_type: &str,
+        port: u16,
+        txt: &[&str],
+    ) -> Result<usize, MdnsError> {
+        let mut svc = LocalService {
+            instance: NameBuf::from_parts(&[instance, service_type, "local"])?,
+            service_type: NameBuf::from_parts(&[service_type, "local"])?,
+            port,
+            txt: [0; TXT_MAX],
+            txt_len: 0,
+        };
+        let mut len = 0usize;
+        for entry in txt {
+            let dst = svc
+                .txt
+                .get_mut(len..len + 1 + entry.len())
+                .filter(|_| entry.len() <= 255)
+                .ok_or(MdnsError::BufferTooSmall)?;
+            dst[0] = entry.len() as u8;
+            dst[1..].copy_from_slice(entry.as_bytes());
+            len += 1 + entry.len();
+        }
+        svc.txt_len = len as u8;
+        if let Some(i) = self
+            .services
+            .iter()
+            .position(|s| s.is_some_and(|s| s.instance.eq_name(svc.instance.as_str())))
+        {
+            self.services[i] = Some(svc);
+            return Ok(i);
+        }
+        let slot = self
+            .services
+            .iter()
+            .position(Option::is_none)
+            .ok_or(MdnsError::Full)?;
+        self.services[slot] = Some(svc);
+        Ok(slot)
+    }
+
+    /// Removes a service and builds its goodbye packet (TTL 0).
+    pub fn unregister(&mut self, id: usize, out: &mut [u8]) -> Result<usize, MdnsError> {
+        let svc = self
+            .services
+            .get(id)
+            .copied()
+            .flatten()
+            .ok_or(MdnsError::Malformed)?;
+        let mut w = Writer::new(out, 0x8400)?;
+        let at = w.record_head(1, svc.service_type.as_bytes(), TYPE_PTR, false, 0)?;
+        w.name(svc.instance.as_bytes())?;
+        w.record_end(at);
+        self.services[id] = None;
+        Ok(w.len)
+    }
+
+    pub fn services(&self) -> impl Iterator<Item = &LocalService> {
+        self.services.iter().flatten()
+    }
+
+    /// Unsolicited response announcing everything, sent at startup and
+    /// after an address change (RFC 6762 §8.3).
+    pub fn announce(&self, out: &mut [u8]) -> Result<usize, MdnsError> {
+        let mut answers = RecList::new();
+        answers.add(Rec::A);
+        for (i, _) in self
+            .services
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.is_some())
+        {
+            answers.add(Rec::Ptr(i));
+            answers.add(Rec::Srv(i));
+            answers.add(Rec::Txt(i));
+        }
+        self.write_response(out, &answers, &RecList::new())
+    }
+
+    /// Probe sent three times, 250 ms apart, before the first announce
+    /// (RFC 6762 §8.1): ANY questions for our unique names, with the
+    /// proposed records in the authority section.
+    pub fn probe(&self, out: &mut [u8]) -> Result<usize, MdnsError> {
+        let mut w = Writer::new(out, 0)?;
+        w.question(self.hostname.as_bytes(), TYPE_ANY)?;
+        let mut authority = RecList::new();
+        authority.add(Rec::A);
+        for (i, svc) in self.services.iter().enumerate() {
+            let Some(svc) = svc else { continue };
+            w.question(svc.instance.as_bytes(), TYPE_ANY)?;
+            authority.add(Rec::Srv(i));
+            authority.add(Rec::Txt(i));
+        }
+        for rec in authority.iter() {
+            self.write_rec(&mut w, 2, rec)?;
+        }
+        Ok(w.len)
+    }
+
+    /// Looks for another host claiming one of our names with different
+    /// data in a response heard on the link (RFC 6762 §9). Our own looped
+    /// back announces match and are not conflicts.
+    pub fn conflict(&self, packet: &[u8]) -> Result<Option<Conflict>, MdnsError> {
+        let mut found = None;
+        for_each_record(packet, |rec| {
+            if found.is_some() || rec.ttl == 0 {
+                return Ok(());
+            }
+            if rec.rtype == TYPE_A && rec.name.eq_name(self.hostname.as_str()) {
+                if rec.rdata != self.addr {
+                    found = Some(Conflict::Host);
+                }
+                return Ok(());
+            }
+            if rec.rtype != TYPE_SRV || rec.rdata.len() < 7 {
+                return Ok(());
+            }
+            let Some(i) = self
+                .services
+                .iter()
+                .position(|s| s.is_some_and(|s| rec.name.eq_name(s.instance.as_str())))
+            else {
+                return Ok(());
+            };
+            let port = u16::from_be_bytes([rec.rdata[4], rec.rdata[5]]);
+            let (target, _) = read_name(packet, rec.rdata_at + 6)?;
+            let ours = self.services[i].is_some_and(|s| s.port == port);
+            if !ours || !target.eq_name(self.hostname.as_str()) {
+                found = Some(Conflict::Service(i));
+            }
+            Ok(())
+        })?;
+        Ok(found)
+    }
+
+    /// Answers the questions of a query packet. Returns `Ok(0)` when
+    /// nothing asked is ours.
+    pub fn respond(&self, query: &[u8], out: &mut [u8]) -> Result<usize, MdnsError> {
+        let flags = be16(query, 2).ok_or(MdnsError::Malformed)?;
+        if flags & 0x8000 != 0 {
+            return Ok(0);
+        }
+        let qdcount = be16(query, 4).ok_or(MdnsError::Malformed)?;
+        let mut answers = RecList::new();
+        let mut pos = 12usize;
+        for _ in 0..qdcount {
+            let (name, next) = read_name(query, pos)?;
+            let qtype = be16(query, next).ok_or(MdnsError::Malformed)?;
+            pos = next + 4;
+            self.match_question(&name, qtype, &mut answers);
+        }
+        if answers.len == 0 {
+            return Ok(0);
+        }
+        let mut additional = RecList::new();
+        for rec in answers.iter() {
+            match rec {
+                Rec::Ptr(i) => {
+                    additional.add(Rec::Srv(i));
+                    additional.add(Rec::Txt(i));
+                    additional.add(Rec::A);
+                }
+                Rec::Srv(_) => additional.add(Rec::A),
+                _ => {}
+            }
+        }
+        self.write_response(out, &answers, &additional)
+    }
+
+    fn match_question(&self, name: &NameBuf, qtype: u16, answers: &mut RecList) {
+        let any = qtype == TYPE_ANY;
+        if (any || qtype == TYPE_A) && name.eq_name(self.hostname.as_str()) {
+            answers.add(Rec::A);
+        }
+        for (i, svc) in self.services.iter().enumerate() {
+            let Some(svc) = svc else { continue };
+            if (any || qtype == TYPE_PTR) && name.eq_name(SERVICES_META) {
+                // One enumeration record per distinct type.
+                let first = self.services[..i]
+                    .iter()
+                    .flatten()
+                    .all(|s| !s.service_type.eq_name(svc.service_type.as_str()));
+                if first {
+                    answers.add(Rec::Meta(i));
+                }
+            }
+            if (any || qtype == TYPE_PTR) && name.eq_name(svc.service_type.as_str()) {
+                answers.add(Rec::Ptr(i));
+            }
+            if name.eq_name(svc.instance.as_str()) {
+                if any || qtype == TYPE_SRV {
+                    answers.add(Rec::Srv(i));
+                }
+                if any || qtype == TYPE_TXT {
+                    answers.add(Rec::Txt(i));
+                }
+            }
+        }
+    }
+
+    fn write_response(
+        &self,
+        out: &mut [u8],
+        answers: &RecList,
+        additional: &RecList,
+    ) -> Result<usize, MdnsError> {
+        let mut w = Writer::new(out, 0x8400)?;
+        for rec in answers.iter() {
+            self.write_rec(&mut w, 1, rec)?;
+        }
+        for rec in additional.iter().filter(|&r| !answers.contains(r)) {
+            self.write_rec(&mut w, 3, rec)?;
+        }
+        Ok(w.len)
+    }
+
+    fn write_rec(&self, w: &mut Writer<'_>, section: usize, rec: Rec) -> Result<(), MdnsError> {
+        let svc = |i: usize| self.services[i].as_ref().ok_or(MdnsError::Malformed);
+        let at = match rec {
+            Rec::A => {
+                let at =
+                    w.record_head(section, self.hostname.as_bytes(), TYPE_A, true, HOST_TTL)?;
+                w.put(&self.addr)?;
+                at
+            }
+            Rec::Meta(i) => {
+                let at = w.record_head(
+                    section,
+                    SERVICES_META.as_bytes(),
+                    TYPE_PTR,
+                    false,
+                    SERVICE_TTL,
+                )?;
+                w.name(svc(i)?.service_type.as_bytes())?;
+                at
+            }
+            Rec::Ptr(i) => {
+                let s = svc(i)?;
+                let at = w.record_head(
+                    section,
+                    s.service_type.as_bytes(),
+                    TYPE_PTR,
+                    false,
+                    SERVICE_TTL,
+                )?;
+                w.name(s.instance.as_bytes())?;
+                at
+            }
+            Rec::Srv(i) => {
+                let s = svc(i)?;
+                let at = w.record_head(section, s.instance.as_bytes(), TYPE_SRV, true, HOST_TTL)?;
+                w.put(&[0, 0, 0, 0])?;
+                w.put(&s.port.to_be_bytes())?;
+                w.name(self.hostname.as_bytes())?;
+                at
+            }
+            Rec::Txt(i) => {
+                let s = svc(i)?;
+                let at =
+                    w.record_head(section, s.instance.as_bytes(), TYPE_TXT, true, SERVICE_TTL)?;
+                // An empty TXT record is a single zero byte (RFC 6763 §6.1).
+                if s.txt().is_empty() {
+                    w.put(&[0])?;
+                } else {
+                    w.put(s.txt())?;
+                }
+                at
+            }
+        };
+        w.record_end(at);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DiscoveredService {
+    pub instance: NameBuf,
+    pub host: NameBuf,
+    pub port: u16,
+    pub addr: Option<[u8; 4]>,
+    txt: [u8; TXT_MAX],
+    txt_len: u8,
+    expires_ms: u64,
+}
+
+impl DiscoveredService {
+    fn empty(instance: NameBuf) -> Self {
+        Self {
+            instance,
+            host: NameBuf::new(),
+            port: 0,
+            addr: None,
+            txt: [0; TXT_MAX],
+            txt_len: 0,
+            expires_ms: 0,
+        }
+    }
+
+    /// Usable once SRV and the host address are known.
+    pub fn is_resolved(&self) -> bool {
+        self.port != 0 && self.addr.is_some()
+    }
+
+    /// TXT `key=value` entries, e.g. for `PrinterCaps::from_txt`.
+    pub fn txt_entries(&self) -> impl Iterator<Item = &[u8]> {
+        let txt = &self.txt[..self.txt_len as usize];
+        let mut pos = 0usize;
+        core::iter::from_fn(move || {
+            let len = *txt.get(pos)? as usize;
+            let entry = txt.get(pos + 1..pos + 1 + len)?;
+            pos += 1 + len;
+            Some(entry)
+        })
+        .filter(|e| !e.is_empty())
+    }
+
+    pub fn txt_value(&self, key: &str) -> Option<&[u8]> {
+        self.txt_entries().find_map(|e| {
+            let (k, v) = e.split_at(e.iter().position(|&b| b == b'=')?);
+            k.eq_ignore_ascii_case(key.as_bytes()).then(|| &v[1..])
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct HostAddr {
+    host: NameBuf,
+    addr: [u8; 4],
+    expires_ms: u64,
+}
+
+/// What has been heard on the link, with TTL-based expiry.
+pub struct ServiceBrowser {
+    services: [Option<DiscoveredService>; MAX_DISCOVERED],
+    hosts: [Option<HostAddr>; MAX_HOSTS],
+}
+
+impl Default for ServiceBrowser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServiceBrowser {
+    pub const fn new() -> Self {
+        Self {
+            services: [None; MAX_DISCOVERED],
+            hosts: [None; MAX_HOSTS],
+        }
+    }
+
+    /// Feeds a response packet. Returns the number of records applied.
+    pub fn ingest(&mut self, packet: &[u8], now_ms: u64) -> Result<usize, MdnsError> {
+        let mut applied = 0usize;
+        for_each_record(packet, |rec| {
+            let expires_ms = now_ms.saturating_add(rec.ttl as u64 * 1000);
+            applied += self.apply(
+                packet,
+                &rec.name,
+                rec.rtype,
+                rec.rdata_at,
+                rec.rdata,
+                rec.ttl,
+                expires_ms,
+            )? as usize;
+            Ok(())
+        })?;
+        self.link_addresses();
+        Ok(applied)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn apply(
+        &mut self,
+        packet: &[u8],
+        name: &NameBuf,
+        rtype: u16,
+        rdata_at: usize,
+        rdata: &[u8],
+        ttl: u32,
+        expires_ms: u64,
+    ) -> Result<bool, MdnsError> {
+        match rtype {
+            TYPE_PTR if !name.eq_name(SERVICES_META) => {
+                let (instance, _) = read_name(packet, rdata_at)?;
+                if ttl == 0 {
+                    self.remove(&instance);
+                    return Ok(true);
+                }
+                let Some(svc) = self.entry(instance) else {
+                    return Ok(false);
+                };
+                svc.expires_ms = svc.expires_ms.max(expires_ms);
+                Ok(true)
+            }
+            TYPE_SRV if rdata.len() >= 7 => {
+                let Some(svc) = self.find_mut(name) else {
+                    return Ok(false);
+                };
+                svc.port = u16::from_be_bytes([rdata[4], rdata[5]]);
+                svc.host = read_name(packet, rdata_at + 6)?.0;
+                Ok(true)
+            }
+            TYPE_TXT => {
+                let Some(svc) = self.find_mut(name) else {
+                    return Ok(false);
+                };
+                let len = rdata.len().min(TXT_MAX);
+                svc.txt[..len].copy_from_slice(&rdata[..len]);
+                svc.txt_len = len as u8;
+                Ok(true)
+            }
+            TYPE_A if rdata.len() == 4 => {
+                let addr = [rdata[0], rdata[1], rdata[2], rdata[3]];
+                let slot = self
+                    .hosts
+                    .iter()
+                    .position(|h| h.is_some_and(|h| h.host.eq_name(name.as_str())))
+                    .or_else(|| self.hosts.iter().position(Option::is_none))
+                    .unwrap_or_else(|| oldest(&self.hosts, |h| h.expires_ms));
+                self.hosts[slot] = (ttl != 0).then_some(HostAddr {
+                    host: *name,
+                    addr,
+                    expires_ms,
+                });
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn entry(&mut self, instance: NameBuf) -> Option<&mut DiscoveredService> {
+        let idx = match self
+            .services
+            .iter()
+            .position(|s| s.is_some_and(|s| s.instance.eq_name(instance.as_str())))
+        {
+            Some(i) => i,
+            None => {
+                let i = self
+                    .services
+                    .iter()
+                    .position(Option::is_none)
+                    .unwrap_or_else(|| oldest(&self.services, |s| s.expires_ms));
+                self.services[i] = Some(DiscoveredService::empty(instance));
+                i
+            }
+        };
+        self.services[idx].as_mut()
+    }
+
+    fn find_mut(&mut self, instance: &NameBuf) -> Option<&mut DiscoveredService> {
+        self.services
+            .iter_mut()
+            .flatten()
+            .find(|s| s.instance.eq_name(instance.as_str()))
+    }
+
+    fn remove(&mut self, instance: &NameBuf) {
+        for slot in self.services.iter_mut() {
+            if slot.is_some_and(|s| s.instance.eq_name(instance.as_str())) {
+                *slot = None;
+            }
+        }
+    }
+
+    fn link_addresses(&mut self) {
+        for svc in self.services.iter_mut().flatten() {
+            svc.addr = self
+                .hosts
+                .iter()
+                .flatten()
+                .find(|h| h.host.eq_name(svc.host.as_str()))
+                .map(|h| h.addr);
+        }
+    }
+
+    /// Drops entries whose TTL has run out.
+    pub fn expire(&mut self, now_ms: u64) {
+        for slot in self.services.iter_mut() {
+            if slot.is_some_and(|s| s.expires_ms <= now_ms) {
+                *slot = None;
+            }
+        }
+        for slot in self.hosts.iter_mut() {
+            if slot.is_some_and(|h| h.expires_ms <= now_ms) {
+                *slot = None;
+            }
+        }
+        self.link_addresses();
+    }
+
+    /// Known instances of `service_type` (e.g. `_ipp._tcp.local`).
+    pub fn browse<'a>(
+        &'a self,
+        service_type: &'a str,
+    ) -> impl Iterator<Item = &'a DiscoveredService> + 'a {
+        self.services
+            .iter()
+            .flatten()
+            .filter(move |s| s.instance.is_child_of(service_type.as_bytes()))
+    }
+}
+
+fn oldest<T: Copy>(slots: &[Option<T>], expiry: impl Fn(&T) -> u64) -> usize {
+    slots
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, s)| s.as_ref().map_or(0, &expiry))
+        .map_or(0, |(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipp::{DocumentFormat, PrinterCaps};
+
+    #[test]
+    fn responder_answers_browse_and_browser_resolves() {
+        let mut responder = MdnsResponder::new("office-printer", [192, 168, 1, 20]).unwrap();
+        responder
+            .register(
+                "Office",
+                "_ipp._tcp",
+                631,
+                &["rp=ipp/print", "pdl=application/pdf", "Color=T"],
+            )
+            .unwrap();
+        responder.register("Share", "_smb._tcp", 445, &[]).unwrap();
+
+        let mut query = [0u8; 64];
+        let n = build_query("_ipp._tcp.local", &mut query).unwrap();
+        let mut resp = [0u8; 512];
+        let len = responder.respond(&query[..n], &mut resp).unwrap();
+        assert!(len > 0);
+        // PTR answer, SRV + TXT + A additionals.
+        assert_eq!(be16(&resp, 6), Some(1));
+        assert_eq!(be16(&resp, 10), Some(3));
+
+        let mut browser = ServiceBrowser::new();
+        assert_eq!(browser.ingest(&resp[..len], 1_000).unwrap(), 4);
+        let printer = browser.browse("_ipp._tcp.local").next().unwrap();
+        assert!(printer.is_resolved());
+        assert_eq!(printer.instance.first_label(), "Office");
+        assert_eq!(printer.addr, Some([192, 168, 1, 20]));
+        assert_eq!(printer.port, 631);
+        assert_eq!(printer.txt_value("rp"), Some(&b"ipp/print"[..]));
+        let caps = PrinterCaps::from_txt(printer.txt_entries());
+        assert_eq!(caps.preferred_format(), Some(DocumentFormat::Pdf));
+        assert_eq!(browser.browse("_smb._tcp.local").count(), 0);
+
+        // A query for something we do not own gets no answer.
+        let n = build_query("_ssh._tcp.local", &mut query).unwrap();
+        assert_eq!(responder.respond(&query[..n], &mut resp), Ok(0));
+
+        // Goodbye removes the instance, TTL expiry removes the rest.
+        let len = responder.unregister(0, &mut resp).unwrap();
+        browser.ingest(&resp[..len], 2_000).unwrap();
+        assert_eq!(browser.browse("_ipp._tcp.local").count(), 0);
+        let len = responder.announce(&mut resp).unwrap();
+        browser.ingest(&resp[..len], 3_000).unwrap();
+        assert_eq!(browser.browse("_smb._tcp.local").count(), 1);
+        browser.expire(3_000 + SERVICE_TTL as u64 * 1000);
+        assert_eq!(browser.browse("_smb._tcp.local").count(), 0);
+    }
+
+    #[test]
+    fn enumerates_service_types_and_follows_compression() {
+        let mut responder = MdnsResponder::new("exo", [10, 0, 0, 2]).unwrap();
+        responder.register("A", "_sftp-ssh._tcp", 22, &[]).unwrap();
+        responder
+            .register("B", "_sftp-ssh._tcp", 2222, &[])
+            .unwrap();
+        responder.register("exo", "_exo._tcp", 7000, &[]).unwrap();
+        let mut query = [0u8; 64];
+        let n = build_query(SERVICES_META, &mut query).unwrap();
+        let mut resp = [0u8; 512];
+        let len = responder.respond(&query[..n], &mut resp).unwrap();
+        assert_eq!(be16(&resp, 6), Some(2));
+
+        // "exo.local" written with a pointer into the question.
+        let msg = [
+            0, 0, 0x84, 0, 0, 0, 0, 1, 0, 0, 0, 0, 3, b'e', b'x', b'o', 5, b'l', b'o', b'c', b'a',
+            b'l', 0, 0xc0, 12, 0, 1, 0, 1, 0, 0, 0, 120, 0, 4, 10, 0, 0, 2,
+        ];
+        assert_eq!(read_name(&msg, 23).unwrap().0.as_str(), "exo.local");
+        assert_eq!(read_name(&[0xc0, 0], 0).err(), Some(MdnsError::Malformed));
+        assert!(len > 12);
+    }
+
+    #[test]
+    fn malformed_headers_and_labels_are_rejected() {
+        let responder = MdnsResponder::new("exo", [10, 0, 0, 2]).unwrap();
+        let mut browser = ServiceBrowser::new();
+        let mut out = [0u8; 512];
+        assert_eq!(
+            responder.respond(&[0; 3], &mut out),
+            Err(MdnsError::Malformed)
+        );
+        assert_eq!(browser.ingest(&[0, 0, 0x84], 0), Err(MdnsError::Malformed));
+        assert_eq!(
+            browser.ingest(&[0, 0, 0x84, 0, 0, 0, 0, 1], 0),
+            Err(MdnsError::Malformed)
+        );
+        // One question announced, none present.
+        let header = [0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+        assert_eq!(
+            responder.respond(&header, &mut out),
+            Err(MdnsError::Malformed)
+        );
+
+        // Reserved label types, labels running past the end, pointers
+        // past the end.
+        assert_eq!(
+            read_name(&[0x41, b'a', 0], 0).err(),
+            Some(MdnsError::Malformed)
+        );
+        assert_eq!(
+            read_name(&[0x81, b'a', 0], 0).err(),
+            Some(MdnsError::Malformed)
+        );
+        assert_eq!(
+            read_name(&[5, b'a', b'b'], 0).err(),
+            Some(MdnsError::Malformed)
+        );
+        assert_eq!(
+            read_name(&[3, b'e', b'x', b'o'], 0).err(),
+            Some(MdnsError::Malformed)
+        );
+        assert_eq!(
+            read_name(&[0xc0, 0x10], 0).err(),
+            Some(MdnsError::Malformed)
+        );
+        assert_eq!(read_name(&[0xc0], 0).err(), Some(MdnsError::Malformed));
+
+        let mut long = [0u8; 3 * 64 + 1];
+        for label in long.chunks_mut(64).take(3) {
+            label[0] = 63;
+            label[1..].fill(b'x');
+        }
+        assert_eq!(read_name(&long, 0).err(), Some(MdnsError::NameTooLong));
+        assert_eq!(browser.browse("_ipp._tcp.local").count(), 0);
+    }
+
+    #[test]
+    fn compression_pointer_loops_are_cut() {
+        assert_eq!(read_name(&[0xc0, 0], 0).err(), Some(MdnsError::Malformed));
+        assert_eq!(
+            read_name(&[0xc0, 2, 0xc0, 0], 0).err(),
+            Some(MdnsError::Malformed)
+        );
+        // A label followed by a pointer back to it grows the name on every
+        // pass: cut by the jump limit before the name overflows.
+        assert_eq!(
+            read_name(&[1, b'a', 0xc0, 0], 0).err(),
+            Some(MdnsError::Malformed)
+        );
+
+        // The same loop as an answer record name and as SRV target.
+        let looped = [
+            0, 0, 0x84, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0xc0, 12, 0, 1, 0, 1, 0, 0, 0, 120, 0, 4, 10, 0,
+            0, 9,
+        ];
+        let mut browser = ServiceBrowser::new();
+        assert_eq!(browser.ingest(&looped, 0), Err(MdnsError::Malformed));
+        let mut responder = MdnsResponder::new("exo", [10, 0, 0, 2]).unwrap();
+        responder.register("Office", "_ipp._tcp", 631, &[]).unwrap();
+        let mut resp = [0u8; 128];
+        let mut w = Writer::new(&mut resp, 0x8400).unwrap();
+        let at = w
+            .record_head(1, b"Office._ipp._tcp.local", TYPE_SRV, true, HOST_TTL)
+            .unwrap();
+        w.put(&[0, 0, 0, 0, 2, 0x77]).unwrap();
+        let target = w.len as u8;
+        w.put(&[0xc0, target]).unwrap();
+        w.record_end(at);
+        let len = w.len;
+        assert_eq!(responder.conflict(&resp[..len]), Err(MdnsError::Malformed));
+    }
+
+    #[test]
+    fn truncated_records_are_rejected() {
+        let mut responder = MdnsResponder::new("exo", [10, 0, 0, 2]).unwrap();
+        responder
+            .register("Office", "_ipp._tcp", 631, &["rp=ipp/print"])
+            .unwrap();
+        let mut packet = [0u8; 512];
+        let len = responder.announce(&mut packet).unwrap();
+        for cut in 0..len {
+            let mut browser = ServiceBrowser::new();
+            assert_eq!(
+                browser.ingest(&packet[..cut], 0),
+                Err(MdnsError::Malformed),
+                "cut at {cut}"
+            );
+        }
+        assert_eq!(ServiceBrowser::new().ingest(&packet[..len], 0), Ok(4));
+
+        // One more answer announced than present.
+        let count = be16(&packet, 6).unwrap() + 1;
+        packet[6..8].copy_from_slice(&count.to_be_bytes());
+        assert_eq!(
+            ServiceBrowser::new().ingest(&packet[..len], 0),
+            Err(MdnsError::Malformed)
+        );
+
+        // RDLENGTH past the end of the packet.
+        let mut msg = [0u8; 64];
+        let mut w = Writer::new(&mut msg, 0x8400).unwrap();
+        let at = w
+            .record_head(1, b"pc1.local", TYPE_A, true, HOST_TTL)
+            .unwrap();
+        w.put(&[10, 0, 0, 9]).unwrap();
+        let len = w.len;
+        msg[at..at + 2].copy_from_slice(&0xffu16.to_be_bytes());
+        assert_eq!(
+            ServiceBrowser::new().ingest(&msg[..len], 0),
+            Err(MdnsError::Malformed)
+        );
+
+        // A short A record is skipped, not applied.
+        let mut w = Writer::new(&mut msg, 0x8400).unwrap();
+        let at = w
+            .record_head(1, b"pc1.local", TYPE_A, true, HOST_TTL)
+            .unwrap();
+        w.put(&[10, 0, 0]).unwrap();
+        w.record_end(at);
+        let len = w.len;
+        assert_eq!(ServiceBrowser::new().ingest(&msg[..len], 0), Ok(0));
+    }
+
+    #[test]
+    fn probe_proposes_our_records_and_answers_reveal_conflicts() {
+        let mut responder = MdnsResponder::new("exo", [10, 0, 0, 2]).unwrap();
+        responder.register("Office", "_ipp._tcp", 631, &[]).unwrap();
+        let mut probe = [0u8; 512];
+        let n = responder.probe(&mut probe).unwrap();
+        // A query: host + instance questions, A + SRV + TXT in authority.
+        assert_eq!(be16(&probe, 2), Some(0));
+        assert_eq!(be16(&probe, 4), Some(2));
+        assert_eq!(be16(&probe, 6), Some(0));
+        assert_eq!(be16(&probe, 8), Some(3));
+        assert_eq!(ServiceBrowser::new().ingest(&probe[..n], 0), Ok(0));
+
+        // Nobody else uses our names: no answer, nothing to resolve.
+        let stranger = MdnsResponder::new("laptop", [10, 0, 0, 3]).unwrap();
+        let mut resp = [0u8; 512];
+        assert_eq!(stranger.respond(&probe[..n], &mut resp), Ok(0));
+
+        // Our own announce looped back is not a conflict.
+        let len = responder.announce(&mut resp).unwrap();
+        assert_eq!(responder.conflict(&resp[..len]), Ok(None));
+        // Neither is a query, even one asking for our names.
+        assert_eq!(responder.conflict(&probe[..n]), Ok(None));
+
+        // Same host name elsewhere.
+        let twin = MdnsResponder::new("EXO", [10, 0, 0, 4]).unwrap();
+        let len = twin.respond(&probe[..n], &mut resp).unwrap();
+        assert!(len > 0);
+        assert_eq!(responder.conflict(&resp[..len]), Ok(Some(Conflict::Host)));
+        responder.set_hostname("exo-2").unwrap();
+        assert_eq!(responder.conflict(&resp[..len]), Ok(None));
+        assert_eq!(responder.hostname().as_str(), "exo-2.local");
+
+        // Same service instance on another host.
+        let mut printer = MdnsResponder::new("laptop", [10, 0, 0, 3]).unwrap();
+        printer.register("office", "_ipp._tcp", 631, &[]).unwrap();
+        let n = responder.probe(&mut probe).unwrap();
+        let len = printer.respond(&probe[..n], &mut resp).unwrap();
+        assert_eq!(
+            responder.conflict(&resp[..len]),
+            Ok(Some(Conflict::Service(0)))
+        );
+
+        // A goodbye for our name is not a claim.
+        let len = printer.unregister(0, &mut resp).unwrap();
+        assert_eq!(responder.conflict(&resp[..len]), Ok(None));
+    }
+
+    #[test]
+    fn refreshed_records_outlive_their_first_ttl() {
+        let mut responder = MdnsResponder::new("nas", [10, 0, 0, 5]).unwrap();
+        responder.register("Files", "_smb._tcp", 445, &[]).unwrap();
+        let mut packet = [0u8; 512];
+        let len = responder.announce(&mut packet).unwrap();
+        let mut browser = ServiceBrowser::new();
+        browser.ingest(&packet[..len], 0).unwrap();
+        let resolved = |b: &ServiceBrowser| {
+            b.browse("_smb._tcp.local")
+                .next()
+                .map(DiscoveredService::is_resolved)
+        };
+        assert_eq!(resolved(&browser), Some(true));
+
+        browser.ingest(&packet[..len], 60_000).unwrap();
+        // The first A record would have run out here.
+        browser.expire(HOST_TTL as u64 * 1000);
+        assert_eq!(resolved(&browser), Some(true));
+        // The refreshed one runs out, the service stays without address.
+        browser.expire(60_000 + HOST_TTL as u64 * 1000);
+        assert_eq!(resolved(&browser), Some(false));
+        browser.expire(SERVICE_TTL as u64 * 1000);
+        assert_eq!(resolved(&browser), Some(false));
+        browser.expire(60_000 + SERVICE_TTL as u64 * 1000);
+        assert_eq!(resolved(&browser), None);
+
+        // A fresh A record resolves the service again.
+        browser.ingest(&packet[..len], 100_000_000).unwrap();
+        assert_eq!(resolved(&browser), Some(true));
+    }
+}
@@ -0,0 +1,865 @@
+//! Accessibility tree export and the screen reader.
+//!
+//! Toolkit applications publish their accessibility tree to the
+//! `screen_reader` daemon: `A11Y_MSG_BEGIN` resets the sender's tree, then
+//! one `A11Y_MSG_NODE` per node follows in pre-order (a parent before its
+//! children), each carrying a role, a name, an optional value, screen
+//! bounds and state bits. `A11Y_MSG_EVENT` reports focus moves and changes
+//! to the published nodes; none of these messages gets a reply.
+//!
+//! The daemon keeps the trees of the last few applications ([`Reader`]) and
+//! turns events of the focused one into utterances for a [`Speech`]
+//! backend: the focused node is described in full ("Back, button"), later
+//! value and state changes on it are spoken on their own. Backends are
+//! pluggable; the daemon picks one from its configuration.
+//!
+//! Configuration (`/etc/exo/screen_reader.conf`):
+//!
+//! ```text
+//! # speech console|none      console: print utterances on the console
+//! # enabled on|off
+//! speech console
+//! enabled on
+//! ```
+
+use core::fmt::{self, Write};
+
+use crate::config;
+
+pub const CONFIG_PATH: &str = "/etc/exo/screen_reader.conf";
+
+/// Longest name or value carried by a node record, in bytes.
+pub const TEXT_MAX: usize = 80;
+/// Nodes kept per application; later ones are dropped.
+pub const MAX_NODES: usize = 128;
+/// Applications whose tree is kept; the least recently updated is evicted.
+pub const MAX_APPS: usize = 4;
+/// Text kept per application tree.
+pub const TREE_TEXT_MAX: usize = 4096;
+/// Longest utterance, in bytes.
+pub const UTTERANCE_MAX: usize = 256;
+
+pub const A11Y_MSG_HEARTBEAT: u32 = 0;
+/// Payload: window id (u32 LE), node count (u16 LE). No reply.
+pub const A11Y_MSG_BEGIN: u32 = 1;
+/// Payload: see [`NodeRecord::encode`]. No reply.
+pub const A11Y_MSG_NODE: u32 = 2;
+/// Payload: see [`Event::encode`]. No reply.
+pub const A11Y_MSG_EVENT: u32 = 3;
+/// The sender closed its window. No reply.
+pub const A11Y_MSG_FORGET: u32 = 4;
+/// Reply: utterances so far, dropped nodes, known applications.
+pub const A11Y_MSG_STATS: u32 = 5;
+
+pub const STATE_FOCUSED: u8 = 1 << 0;
+pub const STATE_DISABLED: u8 = 1 << 1;
+pub const STATE_CHECKED: u8 = 1 << 2;
+pub const STATE_SELECTED: u8 = 1 << 3;
+/// Check boxes only: `STATE_CHECKED` is meaningful.
+pub const STATE_CHECKABLE: u8 = 1 << 4;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    Window = 0,
+    Group = 1,
+    Label = 2,
+    Image = 3,
+    Button = 4,
+    CheckBox = 5,
+    TextInput = 6,
+    List = 7,
+    ListItem = 8,
+}
+
+impl Role {
+    pub fn from_u8(raw: u8) -> Option<Self> {
+        Some(match raw {
+            0 => Self::Window,
+            1 => Self::Group,
+            2 => Self::Label,
+            3 => Self::Image,
+            4 => Self::Button,
+            5 => Self::CheckBox,
+            6 => Self::TextInput,
+            7 => Self::List,
+            8 => Self::ListItem,
+            _ => return None,
+        })
+    }
+
+    /// Spoken after the name; empty for roles that add nothing.
+    pub fn spoken(self) -> &'static str {
+        match self {
+            Self::Window => "window",
+            Self::Group | Self::Label | Self::ListItem => "",
+            Self::Image => "image",
+            Self::Button => "button",
+            Self::CheckBox => "check box",
+            Self::TextInput => "edit text",
+            Self::List => "list",
+        }
+    }
+}
+
+/// `A11Y_MSG_NODE` parent of the root.
+pub const NO_PARENT: u16 = u16::MAX;
+const NO_VALUE: u8 = u8::MAX;
+
+/// One node of a published tree.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NodeRecord<'a> {
+    /// Position in the pre-order export.
+    pub index: u16,
+    pub parent: u16,
+    pub role: Role,
+    pub state: u8,
+    pub bounds: (i32, i32, u32, u32),
+    pub name: &'a str,
+    pub value: Option<&'a str>,
+}
+
+/// Longest prefix of `text` that fits in `max` bytes.
+fn clip(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+impl NodeRecord<'_> {
+    const HEADER: usize = 24;
+    pub const MAX_LEN: usize = Self::HEADER + 2 * TEXT_MAX;
+
+    /// Index (u16), parent (u16), role, state, name length, value length
+    /// (0xff: none), x, y (i32), w, h (u32), name, value; all LE. Name and
+    /// value are cut to [`TEXT_MAX`] bytes.
+    pub fn encode(&self, out: &mut [u8]) -> Option<usize> {
+        let name = clip(self.name, TEXT_MAX);
+        let value = self.value.map(|v| clip(v, TEXT_MAX));
+        let len = Self::HEADER + name.len() + value.map_or(0, str::len);
+        let out = out.get_mut(..len)?;
+        out[0..2].copy_from_slice(&self.index.to_le_bytes());
+        out[2..4].copy_from_slice(&self.parent.to_le_bytes());
+        out[4] = self.role as u8;
+        out[5] = self.state;
+        out[6] = name.len() as u8;
+        out[7] = value.map_or(NO_VALUE, |v| v.len() as u8);
+        let (x, y, w, h) = self.bounds;
+        out[8..12].copy_from_slice(&x.to_le_bytes());
+        out[12..16].copy_from_slice(&y.to_le_bytes());
+        out[16..20].copy_from_slice(&w.to_le_bytes());
+        out[20..24].copy_from_slice(&h.to_le_bytes());
+        let text = &mut out[Self::HEADER..];
+        text[..name.len()].copy_from_slice(name.as_bytes());
+        if let Some(value) = value {
+            text[name.len()..].copy_from_slice(value.as_bytes());
+        }
+        Some(len)
+    }
+}
+
+impl<'a> NodeRecord<'a> {
+    pub fn decode(payload: &'a [u8]) -> Option<Self> {
+        let header = payload.get(..Self::HEADER)?;
+        let le32 = |at: usize| header[at..at + 4].try_into().unwrap();
+        let name_len = header[6] as usize;
+        let text = &payload[Self::HEADER..];
+        let name = core::str::from_utf8(text.get(..name_len)?).ok()?;
+        let value = match header[7] {
+            NO_VALUE => None,
+            len => Some(core::str::from_utf8(text.get(name_len..name_len + len as usize)?).ok()?),
+        };
+        Some(Self {
+            index: u16::from_le_bytes([header[0], header[1]]),
+            parent: u16::from_le_bytes([header[2], header[3]]),
+            role: Role::from_u8(header[4])?,
+            state: header[5],
+            bounds: (
+                i32::from_le_bytes(le32(8)),
+                i32::from_le_bytes(le32(12)),
+                u32::from_le_bytes(le32(16)),
+                u32::from_le_bytes(le32(20)),
+            ),
+            name,
+            value,
+        })
+    }
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EventKind {
+    /// The node got the keyboard focus, or its window was activated.
+    Focus = 0,
+    NameChanged = 1,
+    ValueChanged = 2,
+    StateChanged = 3,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Event {
+    pub kind: EventKind,
+    /// Index in the last published tree.
+    pub node: u16,
+}
+
+impl Event {
+    pub const LEN: usize = 3;
+
+    pub fn encode(&self, out: &mut [u8]) -> Option<usize> {
+        let out = out.get_mut(..Self::LEN)?;
+        out[0] = self.kind as u8;
+        out[1..].copy_from_slice(&self.node.to_le_bytes());
+        Some(Self::LEN)
+    }
+
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        let payload = payload.get(..Self::LEN)?;
+        let kind = match payload[0] {
+            0 => EventKind::Focus,
+            1 => EventKind::NameChanged,
+            2 => EventKind::ValueChanged,
+            3 => EventKind::StateChanged,
+            _ => return None,
+        };
+        Some(Self {
+            kind,
+            node: u16::from_le_bytes([payload[1], payload[2]]),
+        })
+    }
+}
+
+/// `A11Y_MSG_BEGIN` payload.
+pub fn encode_begin(window: u32, nodes: u16, out: &mut [u8]) -> Option<usize> {
+    let out = out.get_mut(..6)?;
+    out[..4].copy_from_slice(&window.to_le_bytes());
+    out[4..].copy_from_slice(&nodes.to_le_bytes());
+    Some(6)
+}
+
+pub fn decode_begin(payload: &[u8]) -> Option<(u32, u16)> {
+    let payload = payload.get(..6)?;
+    Some((
+        u32::from_le_bytes(payload[..4].try_into().unwrap()),
+        u16::from_le_bytes([payload[4], payload[5]]),
+    ))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Priority {
+    /// Queued after the current utterance.
+    Polite,
+    /// Interrupts the current utterance (focus moves).
+    Assertive,
+}
+
+/// Text-to-speech backend.
+pub trait Speech {
+    fn speak(&mut self, text: &str, priority: Priority);
+    /// Silences the current and queued utterances.
+    fn stop(&mut self);
+}
+
+/// Backend that drops everything, for when speech is off.
+pub struct Silent;
+
+impl Speech for Silent {
+    fn speak(&mut self, _text: &str, _priority: Priority) {}
+
+    fn stop(&mut self) {}
+}
+
+/// Utterance being built; text past [`UTTERANCE_MAX`] is dropped.
+pub struct Utterance {
+    buf: [u8; UTTERANCE_MAX],
+    len: usize,
+}
+
+impl Utterance {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; UTTERANCE_MAX],
+            len: 0,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only whole characters are copied in.
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+
+    /// Appends `part`, after a comma unless it is the first one.
+    fn part(&mut self, part: &str) {
+        if part.is_empty() {
+            return;
+        }
+        if self.len > 0 {
+            let _ = self.write_str(", ");
+        }
+        let _ = self.write_str(part);
+    }
+}
+
+impl Default for Utterance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for Utterance {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let s = clip(s, UTTERANCE_MAX - self.len);
+        self.buf[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    Syntax,
+    BadValue,
+    UnknownKey,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Backend {
+    #[default]
+    Console,
+    None,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Directive {
+    Speech(Backend),
+    Enabled(bool),
+}
+
+impl<'a> config::Directive<'a> for Directive {
+    type Error = ConfigError;
+
+    fn parse(line: &'a str) -> Result<Self, ConfigError> {
+        let (key, value) = config::key_value(line).ok_or(ConfigError::Syntax)?;
+        match (key, value) {
+            ("speech", "console") => Ok(Directive::Speech(Backend::Console)),
+            ("speech", "none") => Ok(Directive::Speech(Backend::None)),
+            ("enabled", "on" | "yes") => Ok(Directive::Enabled(true)),
+            ("enabled", "off" | "no") => Ok(Directive::Enabled(false)),
+            ("speech" | "enabled", _) => Err(ConfigError::BadValue),
+            _ => Err(ConfigError::UnknownKey),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Config {
+    pub speech: Backend,
+    pub enabled: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    pub const fn new() -> Self {
+        Self {
+            speech: Backend::Console,
+            enabled: true,
+        }
+    }
+
+    /// Builds a configuration from a file; invalid lines are skipped (see
+    /// [`config::first_error`]).
+    pub fn parse(config: &str) -> Self {
+        let mut out = Self::new();
+        for directive in config::directives::<Directive>(config) {
+            match directive {
+                Directive::Speech(backend) => out.speech = backend,
+                Directive::Enabled(on) => out.enabled = on,
+            }
+        }
+        out
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Node {
+    parent: u16,
+    role: Role,
+    state: u8,
+    name: (u16, u16),
+    value: Option<(u16, u16)>,
+}
+
+impl Node {
+    const EMPTY: Self = Self {
+        parent: NO_PARENT,
+        role: Role::Group,
+        state: 0,
+        name: (0, 0),
+        value: None,
+    };
+}
+
+struct Tree {
+    pid: u32,
+    window: u32,
+    nodes: [Node; MAX_NODES],
+    len: usize,
+    text: [u8; TREE_TEXT_MAX],
+    text_len: usize,
+    /// Update counter at the last message, for eviction.
+    stamp: u64,
+}
+
+impl Tree {
+    const EMPTY: Self = Self {
+        pid: 0,
+        window: 0,
+        nodes: [Node::EMPTY; MAX_NODES],
+        len: 0,
+        text: [0; TREE_TEXT_MAX],
+        text_len: 0,
+        stamp: 0,
+    };
+
+    fn store(&mut self, text: &str) -> Option<(u16, u16)> {
+        let start = self.text_len;
+        let end = start + text.len();
+        self.text
+            .get_mut(start..end)?
+            .copy_from_slice(text.as_bytes());
+        self.text_len = end;
+        Some((start as u16, text.len() as u16))
+    }
+
+    fn str(&self, (start, len): (u16, u16)) -> &str {
+        let (start, len) = (start as usize, len as usize);
+        core::str::from_utf8(&self.text[start..start + len]).unwrap_or("")
+    }
+
+    fn push(&mut self, record: &NodeRecord) -> bool {
+        let parent_ok = record.parent == NO_PARENT || (record.parent as usize) < self.len;
+        if record.index as usize != self.len || self.len == MAX_NODES || !parent_ok {
+            return false;
+        }
+        let saved = self.text_len;
+        let name = self.store(record.name);
+        let value = record.value.map(|v| self.store(v));
+        let (Some(name), None | Some(Some(_))) = (name, value) else {
+            self.text_len = saved;
+            return false;
+        };
+        self.nodes[self.len] = Node {
+            parent: record.parent,
+            role: record.role,
+            state: record.state,
+            name,
+            value: value.flatten(),
+        };
+        self.len += 1;
+        true
+    }
+
+    /// "<position> of <count>" among the siblings with the same role.
+    fn position(&self, index: usize) -> (usize, usize) {
+        let node = &self.nodes[index];
+        let mut position = 0;
+        let mut count = 0;
+        for (i, other) in self.nodes[..self.len].iter().enumerate() {
+            if other.parent == node.parent && other.role == node.role {
+                count += 1;
+                if i <= index {
+                    position += 1;
+                }
+            }
+        }
+        (position, count)
+    }
+
+    fn state_words(state: u8, out: &mut Utterance) {
+        if state & STATE_CHECKABLE != 0 {
+            out.part(if state & STATE_CHECKED != 0 {
+                "checked"
+            } else {
+                "not checked"
+            });
+        }
+        if state & STATE_SELECTED != 0 {
+            out.part("selected");
+        }
+        if state & STATE_DISABLED != 0 {
+            out.part("unavailable");
+        }
+    }
+
+    fn describe(&self, index: usize, out: &mut Utterance) {
+        let node = &self.nodes[index];
+        out.part(self.str(node.name));
+        out.part(node.role.spoken());
+        if let Some(value) = node.value {
+            out.part(self.str(value));
+        }
+        Self::state_words(node.state, out);
+        if node.role == Role::ListItem {
+            let (position, count) = self.position(index);
+            if out.len > 0 {
+                let _ = out.write_str(", ");
+            }
+            let _ = write!(out, "{position} of {count}");
+        }
+    }
+}
+
+/// Accessibility trees of the running applications and what to speak.
+pub struct Reader {
+    trees: [Tree; MAX_APPS],
+    /// Application whose window has the focus.
+    active: Option<u32>,
+    clock: u64,
+    /// Utterances so far.
+    pub spoken: u64,
+    /// Node records that did not fit or came out of order.
+    pub dropped: u64,
+}
+
+impl Default for Reader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reader {
+    pub const fn new() -> Self {
+        Self {
+            trees: [Tree::EMPTY; MAX_APPS],
+            active: None,
+            clock: 0,
+            spoken: 0,
+            dropped: 0,
+        }
+    }
+
+    fn tree(&self, pid: u32) -> Option<&Tree> {
+        self.trees.iter().find(|t| t.pid == pid && pid != 0)
+    }
+
+    fn tree_mut(&mut self, pid: u32) -> Option<&mut Tree> {
+        self.trees.iter_mut().find(|t| t.pid == pid && pid != 0)
+    }
+
+    pub fn apps(&self) -> usize {
+        self.trees.iter().filter(|t| t.pid != 0).count()
+    }
+
+    /// Window of `pid`'s published tree.
+    pub fn window(&self, pid: u32) -> Option<u32> {
+        self.tree(pid).map(|t| t.window)
+    }
+
+    /// `A11Y_MSG_BEGIN`: `pid` publishes a new tree.
+    pub fn begin(&mut self, pid: u32, window: u32) {
+        self.clock += 1;
+        let slot = match self.trees.iter().position(|t| t.pid == pid) {
+            Some(slot) => slot,
+            None => {
+                // Least recently updated, never the focused application.
+                let mut slot = None;
+                for (i, tree) in self.trees.iter().enumerate() {
+                    let evictable = tree.pid == 0 || Some(tree.pid) != self.active;
+                    if evictable && slot.is_none_or(|s: usize| tree.stamp < self.trees[s].stamp) {
+                        slot = Some(i);
+                    }
+                }
+                slot.unwrap_or(0)
+            }
+        };
+        let tree = &mut self.trees[slot];
+        tree.pid = pid;
+        tree.window = window;
+        tree.len = 0;
+        tree.text_len = 0;
+        tree.stamp = self.clock;
+    }
+
+    /// `A11Y_MSG_NODE`. Returns whether the node was kept.
+    pub fn node(&mut self, pid: u32, record: &NodeRecord) -> bool {
+        let kept = self.tree_mut(pid).is_some_and(|t| t.push(record));
+        if !kept {
+            self.dropped += 1;
+        }
+        kept
+    }
+
+    /// `A11Y_MSG_FORGET`.
+    pub fn forget(&mut self, pid: u32) {
+        if let Some(tree) = self.tree_mut(pid) {
+            *tree = Tree::EMPTY;
+        }
+        if self.active == Some(pid) {
+            self.active = None;
+        }
+    }
+
+    /// Full description of a node, as spoken when it gets the focus.
+    pub fn describe(&self, pid: u32, node: u16, out: &mut Utterance) -> bool {
+        match self.tree(pid) {
+            Some(tree) if (node as usize) < tree.len => {
+                tree.describe(node as usize, out);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// `A11Y_MSG_EVENT`: speaks what changed in the focused application.
+    /// A focus event makes `pid` the focused application.
+    pub fn event(&mut self, pid: u32, event: Event, speech: &mut dyn Speech) {
+        let index = event.node as usize;
+        let Some(tree) = self.tree(pid).filter(|t| index < t.len) else {
+            return;
+        };
+        let node = &tree.nodes[index];
+        let focused = node.state & STATE_FOCUSED != 0;
+        let mut out = Utterance::new();
+        let priority = match event.kind {
+            EventKind::Focus => {
+                tree.describe(index, &mut out);
+                Priority::Assertive
+            }
+            _ if self.active != Some(pid) || !focused => return,
+            EventKind::NameChanged => {
+                out.part(tree.str(node.name));
+                Priority::Polite
+            }
+            EventKind::ValueChanged => {
+                out.part(node.value.map_or("", |v| tree.str(v)));
+                Priority::Polite
+            }
+            EventKind::StateChanged => {
+                Tree::state_words(node.state, &mut out);
+                Priority::Polite
+            }
+        };
+        if event.kind == EventKind::Focus {
+            self.active = Some(pid);
+            speech.stop();
+        }
+        if !out.as_str().is_empty() {
+            speech.speak(out.as_str(), priority);
+            self.spoken += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{first_error, parse_line};
+
+    fn parse_directive(line: &str) -> Result<Option<Directive>, ConfigError> {
+        parse_line(line)
+    }
+
+    #[derive(Default)]
+    struct Log {
+        last: Utterance,
+        priority: Option<Priority>,
+        stops: usize,
+    }
+
+    impl Speech for Log {
+        fn speak(&mut self, text: &str, priority: Priority) {
+            self.last = Utterance::new();
+            let _ = self.last.write_str(text);
+            self.priority = Some(priority);
+        }
+
+        fn stop(&mut self) {
+            self.stops += 1;
+        }
+    }
+
+    fn node<'a>(index: u16, parent: u16, role: Role, state: u8, name: &'a str) -> NodeRecord<'a> {
+        NodeRecord {
+            index,
+            parent,
+            role,
+            state,
+            bounds: (0, 0, 10, 10),
+            name,
+            value: None,
+        }
+    }
+
+    fn publish(reader: &mut Reader, pid: u32, focus: Option<u16>, checked: bool) {
+        let state = |i: u16| if focus == Some(i) { STATE_FOCUSED } else { 0 };
+        let check = STATE_CHECKABLE | if checked { STATE_CHECKED } else { 0 };
+        reader.begin(pid, 7);
+        assert!(reader.node(pid, &node(0, NO_PARENT, Role::Window, 0, "Files")));
+        assert!(reader.node(pid, &node(1, 0, Role::Button, state(1), "Back")));
+        assert!(reader.node(pid, &node(2, 0, Role::CheckBox, check | state(2), "Hidden")));
+        assert!(reader.node(pid, &node(3, 0, Role::List, 0, "")));
+        for (i, name) in ["a.txt", "b.txt", "c.txt"].into_iter().enumerate() {
+            let i = 4 + i as u16;
+            let selected = if i == 5 { STATE_SELECTED } else { 0 };
+            assert!(reader.node(pid, &node(i, 3, Role::ListItem, selected | state(i), name)));
+        }
+    }
+
+    #[test]
+    fn wire_formats_roundtrip() {
+        let mut buf = [0u8; NodeRecord::MAX_LEN];
+        let mut record = node(3, 1, Role::TextInput, STATE_FOCUSED, "Search");
+        record.value = Some("notes");
+        record.bounds = (-4, 20, 300, 24);
+        let len = record.encode(&mut buf).unwrap();
+        assert_eq!(len, 24 + 6 + 5);
+        assert_eq!(NodeRecord::decode(&buf[..len]), Some(record));
+        assert_eq!(NodeRecord::decode(&buf[..len - 1]), None);
+
+        // Names are cut on a character boundary.
+        let mut long = [0u8; 1 + 2 * TEXT_MAX];
+        long[0] = b'a';
+        for pair in long[1..].chunks_exact_mut(2) {
+            pair.copy_from_slice("é".as_bytes());
+        }
+        let long = core::str::from_utf8(&long).unwrap();
+        let len = node(0, NO_PARENT, Role::Label, 0, long)
+            .encode(&mut buf)
+            .unwrap();
+        assert_eq!(len, 24 + TEXT_MAX - 1);
+        assert_eq!(
+            NodeRecord::decode(&buf[..len]).unwrap().name,
+            &long[..TEXT_MAX - 1]
+        );
+
+        let event = Event {
+            kind: EventKind::StateChanged,
+            node: 300,
+        };
+        let len = event.encode(&mut buf).unwrap();
+        assert_eq!(Event::decode(&buf[..len]), Some(event));
+        assert_eq!(Event::decode(&[9, 0, 0]), None);
+        let len = encode_begin(7, 12, &mut buf).unwrap();
+        assert_eq!(decode_begin(&buf[..len]), Some((7, 12)));
+    }
+
+    #[test]
+    fn parses_configuration() {
+        let text = "speech none\nenabled off\n";
+        assert_eq!(first_error::<Directive>(text), None);
+        let config = Config::parse(text);
+        assert_eq!((config.speech, config.enabled), (Backend::None, false));
+        assert_eq!(parse_directive("speech espeak"), Err(ConfigError::BadValue));
+        assert_eq!(parse_directive("rate 2"), Err(ConfigError::UnknownKey));
+    }
+
+    #[test]
+    fn speaks_focus_and_changes_of_the_focused_app() {
+        let mut reader = Reader::new();
+        let mut speech = Log::default();
+        publish(&mut reader, 10, Some(1), false);
+        reader.event(
+            10,
+            Event {
+                kind: EventKind::Focus,
+                node: 1,
+            },
+            &mut speech,
+        );
+        assert_eq!(speech.last.as_str(), "Back, button");
+        assert_eq!(
+            (speech.priority, speech.stops),
+            (Some(Priority::Assertive), 1)
+        );
+
+        publish(&mut reader, 10, Some(5), false);
+        reader.event(
+            10,
+            Event {
+                kind: EventKind::Focus,
+                node: 5,
+            },
+            &mut speech,
+        );
+        assert_eq!(speech.last.as_str(), "b.txt, selected, 2 of 3");
+
+        publish(&mut reader, 10, Some(2), false);
+        reader.event(
+            10,
+            Event {
+                kind: EventKind::Focus,
+                node: 2,
+            },
+            &mut speech,
+        );
+        assert_eq!(speech.last.as_str(), "Hidden, check box, not checked");
+        publish(&mut reader, 10, Some(2), true);
+        reader.event(
+            10,
+            Event {
+                kind: EventKind::StateChanged,
+                node: 2,
+            },
+            &mut speech,
+        );
+        assert_eq!(speech.last.as_str(), "checked");
+        assert_eq!(speech.priority, Some(Priority::Polite));
+
+        // Another application changes in the background: silence.
+        publish(&mut reader, 20, Some(2), false);
+        reader.event(
+            20,
+            Event {
+                kind: EventKind::StateChanged,
+                node: 2,
+            },
+            &mut speech,
+        );
+        assert_eq!(speech.last.as_str(), "checked");
+        assert_eq!(reader.spoken, 4);
+        assert_eq!(reader.apps(), 2);
+    }
+
+    #[test]
+    fn drops_bad_records_and_evicts_idle_apps() {
+        let mut reader = Reader::new();
+        assert!(!reader.node(1, &node(0, NO_PARENT, Role::Window, 0, "")));
+        reader.begin(1, 0);
+        assert!(!reader.node(1, &node(1, NO_PARENT, Role::Window, 0, "skip")));
+        assert!(!reader.node(1, &node(0, 4, Role::Window, 0, "orphan")));
+        assert!(reader.node(1, &node(0, NO_PARENT, Role::Window, 0, "")));
+        assert_eq!(reader.dropped, 3);
+
+        let mut speech = Silent;
+        reader.event(
+            1,
+            Event {
+                kind: EventKind::Focus,
+                node: 0,
+            },
+            &mut speech,
+        );
+        for pid in 2..2 + MAX_APPS as u32 {
+            reader.begin(pid, pid);
+        }
+        // The focused application survives, the oldest other one goes.
+        assert_eq!(reader.window(1), Some(0));
+        assert_eq!(reader.window(2), None);
+        assert_eq!(reader.apps(), MAX_APPS);
+        reader.forget(1);
+        assert_eq!(reader.apps(), MAX_APPS - 1);
+
+        let mut out = Utterance::new();
+        assert!(!reader.describe(3, 0, &mut out));
+    }
+}
@@ -0,0 +1,454 @@
+//! Capability manifests: the rights each service declares it needs.
+//!
+//! `exo-caps` checks them two ways:
+//!
+//! - against a live capability table (`exo_cap_list`): rights held beyond
+//!   the manifest, and declared rights the service never took;
+//! - against the service sources: every `exo_syscall_abi` right constant a
+//!   service names must fall within its manifest, so a service cannot start
+//!   asking for `EXOFS_RIGHT_ALL` without someone widening the manifest too.
+//!
+//! Manifest (`/etc/exo/caps.manifest`, `configs/caps.manifest` in the tree):
+//!
+//! ```text
+//! # <service> <kind> <right>...    rights of a kind add up across lines
+//! vfs_server    file  read write create delete stat setmeta list
+//! crypto_server ipc   send manage
+//! ```
+//!
+//! Kinds follow the kernel object types (`ipc`, `memory`, `file`, `device`,
+//! ...). File rights are the ExoFS bits, the other kinds share the kernel
+//! bits. `all` declares every right of a kind and should stay rare.
+
+use crate::config;
+
+pub const MANIFEST_PATH: &str = "/etc/exo/caps.manifest";
+
+/// Every right of a kind, as `all` declares it (the kernel's `Rights::ALL`).
+pub const ALL_RIGHTS: u32 = u32::MAX;
+
+/// Object type of a capability, numbered like the kernel's `CapObjectType`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Kind {
+    Ipc = 1,
+    Memory = 2,
+    File = 3,
+    Device = 4,
+    Thread = 5,
+    Process = 6,
+    Namespace = 7,
+    Dma = 8,
+    Iommu = 9,
+    Key = 10,
+    Cap = 11,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 11] = [
+        Kind::Ipc,
+        Kind::Memory,
+        Kind::File,
+        Kind::Device,
+        Kind::Thread,
+        Kind::Process,
+        Kind::Namespace,
+        Kind::Dma,
+        Kind::Iommu,
+        Kind::Key,
+        Kind::Cap,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Ipc => "ipc",
+            Kind::Memory => "memory",
+            Kind::File => "file",
+            Kind::Device => "device",
+            Kind::Thread => "thread",
+            Kind::Process => "process",
+            Kind::Namespace => "namespace",
+            Kind::Dma => "dma",
+            Kind::Iommu => "iommu",
+            Kind::Key => "key",
+            Kind::Cap => "cap",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// `None` for `Invalid` (0) and unknown tags.
+    pub fn from_tag(tag: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| *kind as u32 == tag)
+    }
+
+    /// Named rights of this kind, by bit.
+    pub fn rights(self) -> &'static [(&'static str, u32)] {
+        match self {
+            Kind::File => FILE_RIGHTS,
+            _ => KERNEL_RIGHTS,
+        }
+    }
+
+    /// One right by name; `all` is every right.
+    pub fn right(self, name: &str) -> Option<u32> {
+        if name == "all" {
+            return Some(ALL_RIGHTS);
+        }
+        self.rights()
+            .iter()
+            .find(|(right, _)| *right == name)
+            .map(|&(_, bit)| bit)
+    }
+
+    /// Names of the rights set in `rights`; see [`Names`].
+    pub fn names(self, rights: u32) -> Names {
+        Names {
+            table: self.rights(),
+            rights,
+        }
+    }
+}
+
+/// Kernel `Rights` bits, shared by every kind but files.
+const KERNEL_RIGHTS: &[(&str, u32)] = &[
+    ("read", 1 << 0),
+    ("write", 1 << 1),
+    ("exec", 1 << 2),
+    ("grant", 1 << 3),
+    ("revoke", 1 << 4),
+    ("delegate", 1 << 5),
+    ("connect", 1 << 6),
+    ("send", 1 << 7),
+    ("recv", 1 << 8),
+    ("manage", 1 << 9),
+    ("map", 1 << 10),
+    ("protect", 1 << 11),
+    ("unmap", 1 << 12),
+    ("mmio", 1 << 13),
+    ("dma", 1 << 14),
+    ("irq", 1 << 15),
+];
+
+/// ExoFS rights (`EXOFS_RIGHT_*`), carried by file capabilities.
+const FILE_RIGHTS: &[(&str, u32)] = &[
+    ("read", 1 << 0),
+    ("write", 1 << 1),
+    ("create", 1 << 2),
+    ("delete", 1 << 3),
+    ("stat", 1 << 4),
+    ("setmeta", 1 << 5),
+    ("list", 1 << 6),
+    ("exec", 1 << 7),
+    ("chown", 1 << 8),
+    ("chmod", 1 << 9),
+    ("inspect", 1 << 10),
+    ("snapshot", 1 << 11),
+    ("relation", 1 << 12),
+    ("gc", 1 << 13),
+    ("export", 1 << 14),
+    ("import", 1 << 15),
+    ("admin", 1 << 16),
+];
+
+/// Names of the rights in a mask, in bit order. Bits without a name are
+/// left in [`Names::unnamed`].
+pub struct Names {
+    table: &'static [(&'static str, u32)],
+    rights: u32,
+}
+
+impl Names {
+    /// Bits no name covers, once the names are consumed.
+    pub fn unnamed(&self) -> u32 {
+        self.table
+            .iter()
+            .fold(self.rights, |rest, &(_, bit)| rest & !bit)
+    }
+}
+
+impl Iterator for Names {
+    type Item = &'static str;
+
+    fn next(&mut self) -> Option<&'static str> {
+        let (index, &(name, bit)) = self
+            .table
+            .iter()
+            .enumerate()
+            .find(|(_, (_, bit))| self.rights & bit != 0)?;
+        self.rights &= !bit;
+        self.table = &self.table[index + 1..];
+        Some(name)
+    }
+}
+
+/// `exo_syscall_abi` constants through which services ask for rights.
+pub const RIGHT_CONSTANTS: &[(&str, Kind, u32)] = &[
+    ("EXO_CAP_RIGHT_IPC_CONNECT", Kind::Ipc, 1 << 6),
+    ("EXO_CAP_RIGHT_IPC_SEND", Kind::Ipc, 1 << 7),
+    ("EXO_CAP_RIGHT_IPC_RECV", Kind::Ipc, 1 << 8),
+    ("EXO_CAP_RIGHT_IPC_MANAGE", Kind::Ipc, 1 << 9),
+    ("EXOFS_RIGHT_READ", Kind::File, 1 << 0),
+    ("EXOFS_RIGHT_WRITE", Kind::File, 1 << 1),
+    ("EXOFS_RIGHT_CREATE", Kind::File, 1 << 2),
+    ("EXOFS_RIGHT_DELETE", Kind::File, 1 << 3),
+    ("EXOFS_RIGHT_STAT", Kind::File, 1 << 4),
+    ("EXOFS_RIGHT_SETMETA", Kind::File, 1 << 5),
+    ("EXOFS_RIGHT_LIST", Kind::File, 1 << 6),
+    ("EXOFS_RIGHT_EXEC", Kind::File, 1 << 7),
+    ("EXOFS_RIGHT_CHOWN", Kind::File, 1 << 8),
+    ("EXOFS_RIGHT_CHMOD", Kind::File, 1 << 9),
+    ("EXOFS_RIGHT_INSPECT_CONTENT", Kind::File, 1 << 10),
+    ("EXOFS_RIGHT_SNAPSHOT_CREATE", Kind::File, 1 << 11),
+    ("EXOFS_RIGHT_RELATION_CREATE", Kind::File, 1 << 12),
+    ("EXOFS_RIGHT_GC_TRIGGER", Kind::File, 1 << 13),
+    ("EXOFS_RIGHT_EXPORT", Kind::File, 1 << 14),
+    ("EXOFS_RIGHT_IMPORT", Kind::File, 1 << 15),
+    ("EXOFS_RIGHT_ADMIN", Kind::File, 1 << 16),
+    ("EXOFS_RIGHT_ALL", Kind::File, 0x0000_ffff),
+    (
+        "EXOFS_RIGHT_READ_ONLY",
+        Kind::File,
+        1 << 0 | 1 << 4 | 1 << 6,
+    ),
+    ("EXOFS_RIGHT_READ_WRITE", Kind::File, 0x7f),
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ManifestError {
+    /// Fewer than three fields.
+    Syntax,
+    Kind,
+    Right,
+}
+
+/// `<service> <kind> <right>...`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Grant<'a> {
+    pub service: &'a str,
+    pub kind: Kind,
+    pub rights: u32,
+}
+
+impl<'a> config::Directive<'a> for Grant<'a> {
+    type Error = ManifestError;
+
+    fn parse(line: &'a str) -> Result<Self, ManifestError> {
+        let mut fields = line.split_ascii_whitespace();
+        let (Some(service), Some(kind)) = (fields.next(), fields.next()) else {
+            return Err(ManifestError::Syntax);
+        };
+        let kind = Kind::from_name(kind).ok_or(ManifestError::Kind)?;
+        let mut rights = 0;
+        for name in fields {
+            rights |= kind.right(name).ok_or(ManifestError::Right)?;
+        }
+        if rights == 0 {
+            return Err(ManifestError::Syntax);
+        }
+        Ok(Grant {
+            service,
+            kind,
+            rights,
+        })
+    }
+}
+
+/// Rights of `kind` the manifest declares for `service`.
+pub fn declared(manifest: &str, service: &str, kind: Kind) -> u32 {
+    config::directives::<Grant>(manifest)
+        .filter(|grant| grant.service == service && grant.kind == kind)
+        .fold(0, |rights, grant| rights | grant.rights)
+}
+
+/// Rights of a held capability (kernel type tag and raw mask) beyond the
+/// manifest; all of them for an unknown tag.
+pub fn excess(manifest: &str, service: &str, type_tag: u32, rights: u32) -> u32 {
+    match Kind::from_tag(type_tag) {
+        Some(kind) => rights & !declared(manifest, service, kind),
+        None => rights,
+    }
+}
+
+/// Rights actually held by a process, per kind, for the other side of the
+/// diff: declarations nothing uses.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Usage {
+    held: [u32; Kind::ALL.len()],
+}
+
+impl Usage {
+    pub fn add(&mut self, type_tag: u32, rights: u32) {
+        if let Some(kind) = Kind::from_tag(type_tag) {
+            self.held[kind as usize - 1] |= rights;
+        }
+    }
+
+    pub fn held(&self, kind: Kind) -> u32 {
+        self.held[kind as usize - 1]
+    }
+
+    /// Declared rights of `service` no capability holds, per kind. `all`
+    /// only counts as unused when nothing of the kind is held.
+    pub fn unused<'a>(
+        &'a self,
+        manifest: &'a str,
+        service: &'a str,
+    ) -> impl Iterator<Item = (Kind, u32)> + 'a {
+        Kind::ALL.into_iter().filter_map(move |kind| {
+            let declared = declared(manifest, service, kind);
+            let held = self.held(kind);
+            let unused = if declared == ALL_RIGHTS && held != 0 {
+                0
+            } else {
+                declared & !held
+            };
+            (unused != 0).then_some((kind, unused))
+        })
+    }
+}
+
+/// A right constant named in a service source beyond its manifest.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Warning<'a> {
+    /// 1-based.
+    pub line: usize,
+    pub constant: &'a str,
+    pub kind: Kind,
+    pub excess: u32,
+}
+
+/// Right constants `source` names that `service`'s manifest does not cover.
+/// Line comments are skipped; a service missing from the manifest declares
+/// nothing.
+pub fn lint<'a>(
+    manifest: &'a str,
+    service: &'a str,
+    source: &'a str,
+) -> impl Iterator<Item = Warning<'a>> + 'a {
+    source
+        .lines()
+        .enumerate()
+        .flat_map(|(index, line)| {
+            let code = line.split("//").next().unwrap_or("");
+            code.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .map(move |word| (index + 1, word))
+        })
+        .filter_map(move |(line, word)| {
+            let &(constant, kind, rights) =
+                RIGHT_CONSTANTS.iter().find(|(name, _, _)| *name == word)?;
+            let excess = rights & !declared(manifest, service, kind);
+            (excess != 0).then_some(Warning {
+                line,
+                constant,
+                kind,
+                excess,
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = "\
+# services
+vfs_server    file read write stat list
+vfs_server    file create delete setmeta
+crypto_server ipc  send manage
+backup        file all
+";
+
+    #[test]
+    fn parses_and_accumulates_declarations() {
+        assert_eq!(
+            config::first_error::<Grant>("a file read\nb pipe read\n"),
+            Some((2, ManifestError::Kind))
+        );
+        assert_eq!(
+            config::first_error::<Grant>("a file fly"),
+            Some((1, ManifestError::Right))
+        );
+        assert_eq!(
+            config::first_error::<Grant>("a file"),
+            Some((1, ManifestError::Syntax))
+        );
+        assert_eq!(config::first_error::<Grant>(MANIFEST), None);
+
+        assert_eq!(declared(MANIFEST, "vfs_server", Kind::File), 0x7f);
+        assert_eq!(declared(MANIFEST, "vfs_server", Kind::Ipc), 0);
+        assert_eq!(
+            declared(MANIFEST, "crypto_server", Kind::Ipc),
+            1 << 7 | 1 << 9
+        );
+        assert_eq!(declared(MANIFEST, "backup", Kind::File), ALL_RIGHTS);
+        assert_eq!(declared(MANIFEST, "nobody", Kind::File), 0);
+    }
+
+    #[test]
+    fn names_rights_per_kind() {
+        assert!(Kind::File.names(1 << 0 | 1 << 16).eq(["read", "admin"]));
+        assert!(Kind::Ipc.names(1 << 7 | 1 << 9).eq(["send", "manage"]));
+        let mut names = Kind::Device.names(1 << 13 | 1 << 20);
+        assert_eq!(names.next(), Some("mmio"));
+        assert_eq!(names.next(), None);
+        assert_eq!(names.unnamed(), 1 << 20);
+        assert_eq!(Kind::from_tag(3), Some(Kind::File));
+        assert_eq!(Kind::from_tag(0), None);
+        assert_eq!(Kind::from_tag(12), None);
+        assert_eq!(Kind::Memory.right("protect"), Some(1 << 11));
+        assert_eq!(Kind::File.right("protect"), None);
+    }
+
+    #[test]
+    fn diffs_a_table_against_the_manifest() {
+        let file = Kind::File as u32;
+        let ipc = Kind::Ipc as u32;
+        assert_eq!(excess(MANIFEST, "vfs_server", file, 0b11), 0);
+        assert_eq!(excess(MANIFEST, "vfs_server", file, 1 << 16 | 1), 1 << 16);
+        assert_eq!(excess(MANIFEST, "vfs_server", ipc, 1 << 7), 1 << 7);
+        assert_eq!(excess(MANIFEST, "vfs_server", 0, 1), 1);
+        assert_eq!(excess(MANIFEST, "backup", file, ALL_RIGHTS), 0);
+
+        let mut usage = Usage::default();
+        usage.add(file, 0b1_0011);
+        usage.add(file, 1 << 6);
+        usage.add(0, ALL_RIGHTS);
+        assert!(usage
+            .unused(MANIFEST, "vfs_server")
+            .eq([(Kind::File, 1 << 2 | 1 << 3 | 1 << 5)]));
+        assert!(usage.unused(MANIFEST, "backup").eq([]));
+        assert!(Usage::default()
+            .unused(MANIFEST, "crypto_server")
+            .eq([(Kind::Ipc, 1 << 7 | 1 << 9)]));
+    }
+
+    #[test]
+    fn lint_flags_constants_beyond_the_manifest() {
+        let source = "\
+let ro = syscall::EXOFS_RIGHT_READ | syscall::EXOFS_RIGHT_STAT;
+// syscall::EXOFS_RIGHT_ADMIN in a comment is fine
+let rw = abi::EXOFS_RIGHT_READ_WRITE as u64; // EXOFS_RIGHT_ALL
+let everything = syscall::EXOFS_RIGHT_ALL;
+exo_cap_create(TYPE, syscall::EXO_CAP_RIGHT_IPC_SEND, pid, &mut t);
+";
+        let warnings = [
+            Warning {
+                line: 4,
+                constant: "EXOFS_RIGHT_ALL",
+                kind: Kind::File,
+                excess: 0xff80,
+            },
+            Warning {
+                line: 5,
+                constant: "EXO_CAP_RIGHT_IPC_SEND",
+                kind: Kind::Ipc,
+                excess: 1 << 7,
+            },
+        ];
+        assert!(lint(MANIFEST, "vfs_server", source).eq(warnings));
+        assert!(lint(MANIFEST, "backup", "EXOFS_RIGHT_ALL EXOFS_RIGHT_ADMIN").eq([]));
+        assert_eq!(lint("", "nobody", "EXOFS_RIGHT_READ_ONLY").count(), 1);
+    }
+}
@@ -0,0 +1,218 @@
+//! Display colour transforms: gamma ramps, colour matrices and colour
+//! temperature.
+//!
+//! `fb_server` passes every pixel it draws through a 3×3 colour matrix
+//! (`FB_MSG_SET_CTM`), then through a per-channel gamma ramp
+//! (`FB_MSG_SET_GAMMA`). The two have different owners: the night-light
+//! daemon sets the matrix (see [`crate::nightlight`]), the compositor sets
+//! the ramp from the calibration curves of the output's ICC profile (see
+//! [`crate::icc`]). Both travel in the compact wire formats below, which
+//! fit a single `fb_server` request.
+
+use crate::math;
+
+/// Points of a gamma ramp per channel; `fb_server` interpolates the 256
+/// entries of its lookup table between them.
+pub const RAMP_POINTS: usize = 64;
+pub const RAMP_WIRE_LEN: usize = 3 * RAMP_POINTS;
+/// Fixed-point 1.0 of matrix coefficients (s2.13).
+pub const CTM_ONE: i16 = 1 << 13;
+pub const CTM_WIRE_LEN: usize = 9 * 2;
+
+/// Daylight white point: no correction.
+pub const TEMPERATURE_NEUTRAL: u32 = 6_500;
+pub const TEMPERATURE_MIN: u32 = 1_000;
+
+/// Gamma ramp, one curve per channel (red, green, blue) sampled at
+/// `RAMP_POINTS` evenly spaced inputs.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Ramp {
+    pub channels: [[u8; RAMP_POINTS]; 3],
+}
+
+impl Default for Ramp {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Ramp {
+    pub const fn identity() -> Self {
+        let mut curve = [0u8; RAMP_POINTS];
+        let mut i = 0;
+        while i < RAMP_POINTS {
+            curve[i] = ((i * 255 + (RAMP_POINTS - 1) / 2) / (RAMP_POINTS - 1)) as u8;
+            i += 1;
+        }
+        Self {
+            channels: [curve; 3],
+        }
+    }
+
+    /// Samples `f(channel, x)`, both `x` and the result in `[0, 1]`.
+    pub fn from_fn(f: impl Fn(usize, f64) -> f64) -> Self {
+        let mut ramp = Self::identity();
+        for (channel, curve) in ramp.channels.iter_mut().enumerate() {
+            for (i, point) in curve.iter_mut().enumerate() {
+                let x = i as f64 / (RAMP_POINTS - 1) as f64;
+                *point = to_u8(f(channel, x));
+            }
+        }
+        ramp
+    }
+
+    /// `x^exponent` per channel.
+    pub fn gamma(exponents: [f64; 3]) -> Self {
+        Self::from_fn(|channel, x| math::powf(x, exponents[channel]))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    pub fn encode(&self) -> [u8; RAMP_WIRE_LEN] {
+        let mut out = [0; RAMP_WIRE_LEN];
+        for (dst, curve) in out.chunks_exact_mut(RAMP_POINTS).zip(self.channels.iter()) {
+            dst.copy_from_slice(curve);
+        }
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..RAMP_WIRE_LEN)?;
+        let mut ramp = Self::identity();
+        for (curve, src) in ramp
+            .channels
+            .iter_mut()
+            .zip(bytes.chunks_exact(RAMP_POINTS))
+        {
+            curve.copy_from_slice(src);
+        }
+        Some(ramp)
+    }
+}
+
+/// Colour matrix applied to `[r, g, b]` column vectors, row-major.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Ctm(pub [i16; 9]);
+
+impl Default for Ctm {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Ctm {
+    pub const IDENTITY: Self = Self([CTM_ONE, 0, 0, 0, CTM_ONE, 0, 0, 0, CTM_ONE]);
+
+    fn coefficient(value: f64) -> i16 {
+        let max = i16::MAX as f64 / CTM_ONE as f64;
+        let min = i16::MIN as f64 / CTM_ONE as f64;
+        (math::floor(value.clamp(min, max) * CTM_ONE as f64 + 0.5)) as i16
+    }
+
+    pub fn from_f64(m: [f64; 9]) -> Self {
+        Self(m.map(Self::coefficient))
+    }
+
+    /// Per-channel gains, as for a white point change.
+    pub fn scale(gains: [f64; 3]) -> Self {
+        let [r, g, b] = gains;
+        Self::from_f64([r, 0.0, 0.0, 0.0, g, 0.0, 0.0, 0.0, b])
+    }
+
+    pub fn apply(&self, rgb: [u8; 3]) -> [u8; 3] {
+        let m = &self.0;
+        let mut out = [0u8; 3];
+        for (row, dst) in out.iter_mut().enumerate() {
+            let acc: i32 = (0..3)
+                .map(|col| m[row * 3 + col] as i32 * rgb[col] as i32)
+                .sum();
+            *dst = ((acc + (CTM_ONE as i32 / 2)) >> 13).clamp(0, 255) as u8;
+        }
+        out
+    }
+
+    pub fn encode(&self) -> [u8; CTM_WIRE_LEN] {
+        let mut out = [0; CTM_WIRE_LEN];
+        for (dst, c) in out.chunks_exact_mut(2).zip(self.0.iter()) {
+            dst.copy_from_slice(&c.to_le_bytes());
+        }
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..CTM_WIRE_LEN)?;
+        let mut m = [0i16; 9];
+        for (c, src) in m.iter_mut().zip(bytes.chunks_exact(2)) {
+            *c = i16::from_le_bytes([src[0], src[1]]);
+        }
+        Some(Self(m))
+    }
+}
+
+fn to_u8(x: f64) -> u8 {
+    math::floor(x.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+}
+
+/// Blackbody colour at `kelvin` (Tanner Helland's fit), in `[0, 255]`.
+fn blackbody(kelvin: u32) -> [f64; 3] {
+    let t = kelvin.clamp(TEMPERATURE_MIN, TEMPERATURE_NEUTRAL) as f64 / 100.0;
+    let green = 99.470_802_586_1 * math::ln(t) - 161.119_568_166_1;
+    let blue = if t <= 19.0 {
+        0.0
+    } else {
+        138.517_731_223_1 * math::ln(t - 10.0) - 305.044_792_730_7
+    };
+    [255.0, green.clamp(0.0, 255.0), blue.clamp(0.0, 255.0)]
+}
+
+/// Channel gains turning the neutral white into the white of `kelvin`;
+/// `[1, 1, 1]` from [`TEMPERATURE_NEUTRAL`] up.
+pub fn temperature_gains(kelvin: u32) -> [f64; 3] {
+    let warm = blackbody(kelvin);
+    let neutral = blackbody(TEMPERATURE_NEUTRAL);
+    [0, 1, 2].map(|i| (warm[i] / neutral[i]).min(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramps_round_trip_and_shape() {
+        let identity = Ramp::identity();
+        assert_eq!(identity.channels[0][0], 0);
+        assert_eq!(identity.channels[1][RAMP_POINTS - 1], 255);
+        assert!(Ramp::gamma([1.0; 3]).is_identity());
+
+        let ramp = Ramp::gamma([2.2, 1.0, 0.5]);
+        assert!(ramp.channels[0][32] < identity.channels[0][32]);
+        assert!(ramp.channels[2][32] > identity.channels[2][32]);
+        assert_eq!(ramp.channels[0][RAMP_POINTS - 1], 255);
+        assert_eq!(Ramp::decode(&ramp.encode()), Some(ramp));
+        assert_eq!(Ramp::decode(&[0; 10]), None);
+    }
+
+    #[test]
+    fn matrices_apply_in_fixed_point() {
+        assert_eq!(Ctm::IDENTITY.apply([12, 200, 255]), [12, 200, 255]);
+        let half_blue = Ctm::scale([1.0, 1.0, 0.5]);
+        assert_eq!(half_blue.apply([255, 255, 255]), [255, 255, 128]);
+        // Swapped channels, saturated at 255.
+        let swap = Ctm::from_f64([0.0, 1.0, 0.0, 2.0, 0.0, 0.0, 0.0, 0.0, -1.0]);
+        assert_eq!(swap.apply([200, 10, 30]), [10, 255, 0]);
+        assert_eq!(Ctm::decode(&swap.encode()), Some(swap));
+    }
+
+    #[test]
+    fn warm_temperatures_cut_blue_first() {
+        assert_eq!(temperature_gains(TEMPERATURE_NEUTRAL), [1.0; 3]);
+        assert_eq!(temperature_gains(9_000), [1.0; 3]);
+        let [r, g, b] = temperature_gains(3_400);
+        assert_eq!(r, 1.0);
+        assert!(b < g && g < 1.0, "{g} {b}");
+        assert!((0.7..0.85).contains(&g), "{g}");
+        assert_eq!(temperature_gains(1_500)[2], 0.0);
+    }
+}
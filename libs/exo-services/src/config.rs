@@ -0,0 +1,85 @@
+//! Line-based configuration files shared by the services.
+//!
+//! One directive per line, fields separated by whitespace; blank lines and
+//! lines starting with `#` are ignored. Each service maps a line to its own
+//! [`Directive`] type; reading a file skips the invalid lines, which
+//! [`first_error`] reports.
+
+/// One line of a configuration file.
+pub trait Directive<'a>: Sized {
+    type Error;
+
+    /// Parses a trimmed line that is neither blank nor a comment.
+    fn parse(line: &'a str) -> Result<Self, Self::Error>;
+}
+
+/// One line; `Ok(None)` for blank lines and comments.
+pub fn parse_line<'a, D: Directive<'a>>(line: &'a str) -> Result<Option<D>, D::Error> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    D::parse(line).map(Some)
+}
+
+/// Valid directives of a file, in file order.
+pub fn directives<'a, D: Directive<'a> + 'a>(config: &'a str) -> impl Iterator<Item = D> + 'a {
+    config
+        .lines()
+        .filter_map(|line| parse_line(line).ok().flatten())
+}
+
+/// First invalid line (1-based) and its error.
+pub fn first_error<'a, D: Directive<'a>>(config: &'a str) -> Option<(usize, D::Error)> {
+    config
+        .lines()
+        .enumerate()
+        .find_map(|(i, line)| parse_line::<D>(line).err().map(|e| (i + 1, e)))
+}
+
+/// `key value`: exactly two fields.
+pub fn key_value(line: &str) -> Option<(&str, &str)> {
+    let mut fields = line.split_ascii_whitespace();
+    match (fields.next(), fields.next(), fields.next()) {
+        (Some(key), Some(value), None) => Some((key, value)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Eq, PartialEq)]
+    struct Limit<'a>(&'a str, u32);
+
+    impl<'a> Directive<'a> for Limit<'a> {
+        type Error = &'static str;
+
+        fn parse(line: &'a str) -> Result<Self, Self::Error> {
+            let (key, value) = key_value(line).ok_or("syntax")?;
+            value.parse().map(|v| Limit(key, v)).map_err(|_| "value")
+        }
+    }
+
+    #[test]
+    fn skips_blank_lines_and_comments() {
+        assert_eq!(parse_line::<Limit>("  \t"), Ok(None));
+        assert_eq!(parse_line::<Limit>("  # files 3"), Ok(None));
+        assert_eq!(
+            parse_line::<Limit>(" files 3 "),
+            Ok(Some(Limit("files", 3)))
+        );
+        assert_eq!(parse_line::<Limit>("files 3 4"), Err("syntax"));
+        assert_eq!(parse_line::<Limit>("files"), Err("syntax"));
+        assert_eq!(key_value("a\t b"), Some(("a", "b")));
+    }
+
+    #[test]
+    fn reads_valid_lines_and_reports_the_first_bad_one() {
+        let text = "# limits\nfiles 3\n\nprocs x\nfiles 4\nmem\n";
+        assert!(directives::<Limit>(text).eq([Limit("files", 3), Limit("files", 4)]));
+        assert_eq!(first_error::<Limit>(text), Some((4, "value")));
+        assert_eq!(first_error::<Limit>("files 1\n#\n"), None);
+    }
+}
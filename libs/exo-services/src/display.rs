@@ -0,0 +1,311 @@
+//! Display ownership in `fb_server`: who may draw, and who gets the display
+//! back when its owner goes away.
+//!
+//! Owners and lenders are kept as [`Process`] snapshots from the kernel
+//! process list (`SYS_EXO_PROCESS_LIST`), not as bare PIDs. A zombie is no
+//! longer a holder, and neither is a new process that reused the PID: a
+//! process keeps its name and its CPU time never goes backwards, so a reused
+//! PID shows up under another name or with less CPU time than last seen.
+//!
+//! A compositor may lend the display (direct scanout, see
+//! [`scanout`](crate::scanout)); the lender takes it back at will, and gets it
+//! back on its own when the borrower releases it or exits.
+
+/// Length of a process name in the kernel process list.
+pub const NAME_LEN: usize = 16;
+/// `state` of an exited process not yet reaped.
+pub const STATE_ZOMBIE: u32 = 4;
+pub const STATE_DEAD: u32 = 5;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Process {
+    pub pid: u32,
+    pub state: u32,
+    pub name: [u8; NAME_LEN],
+    /// User plus system CPU time.
+    pub cpu_ns: u64,
+}
+
+impl Process {
+    fn exited(&self) -> bool {
+        self.state == STATE_ZOMBIE || self.state == STATE_DEAD
+    }
+
+    /// Whether `now`, the process found at this PID, is still this one.
+    fn is(&self, now: &Process) -> bool {
+        now.pid == self.pid && now.name == self.name && now.cpu_ns >= self.cpu_ns && !now.exited()
+    }
+}
+
+/// Result of looking a PID up in the process list.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Lookup {
+    Found(Process),
+    Missing,
+    /// The list could not be read in full: keep the holder.
+    Unknown,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Refused {
+    /// Another live process owns the display.
+    Busy,
+    NotOwner,
+    /// The transfer target is not a live process.
+    NoTarget,
+}
+
+/// What a [`Display`] operation did to the screen.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Change {
+    None,
+    /// The lender owns the display again, at this epoch.
+    Returned(u32),
+    /// Nobody owns the display: the text console is back.
+    Console,
+}
+
+pub struct Display {
+    owner: Option<Process>,
+    lender: Option<Process>,
+    /// Bumped on every change of owner.
+    epoch: u32,
+}
+
+impl Display {
+    pub const fn new() -> Self {
+        Self {
+            owner: None,
+            lender: None,
+            epoch: 0,
+        }
+    }
+
+    /// PID of the owner, 0 for the console.
+    pub fn owner(&self) -> u32 {
+        self.owner.map_or(0, |owner| owner.pid)
+    }
+
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    pub fn is_owner(&self, pid: u32) -> bool {
+        pid != 0 && self.owner() == pid
+    }
+
+    pub fn is_lender(&self, pid: u32) -> bool {
+        pid != 0 && self.lender.is_some_and(|lender| lender.pid == pid)
+    }
+
+    fn bump(&mut self) -> u32 {
+        self.epoch = self.epoch.wrapping_add(1);
+        self.epoch
+    }
+
+    /// Hands the display back to the lender, or to the console.
+    fn give_back(&mut self) -> Change {
+        self.owner = self.lender.take();
+        match self.owner {
+            Some(_) => Change::Returned(self.bump()),
+            None => Change::Console,
+        }
+    }
+
+    /// Drops the holders that exited. Run before any decision that depends
+    /// on them, and periodically: a crashed owner never releases.
+    pub fn probe(&mut self, mut lookup: impl FnMut(u32) -> Lookup) -> Change {
+        if let Some(lender) = self.lender {
+            self.lender = refresh(lender, &mut lookup);
+        }
+        let Some(owner) = self.owner else {
+            return Change::None;
+        };
+        match refresh(owner, &mut lookup) {
+            Some(owner) => {
+                self.owner = Some(owner);
+                Change::None
+            }
+            None => self.give_back(),
+        }
+    }
+
+    /// Takes the display for `caller`, whose right to do so is checked by the
+    /// caller. The lender takes it back even from a live borrower. Returns
+    /// the new epoch.
+    pub fn acquire(&mut self, caller: Process) -> Result<u32, Refused> {
+        if self.is_lender(caller.pid) {
+            self.lender = None;
+        } else if self.owner.is_some_and(|owner| owner.pid != caller.pid) {
+            return Err(Refused::Busy);
+        }
+        self.owner = Some(caller);
+        Ok(self.bump())
+    }
+
+    pub fn release(&mut self, pid: u32) -> Result<Change, Refused> {
+        if !self.is_owner(pid) {
+            return Err(Refused::NotOwner);
+        }
+        Ok(self.give_back())
+    }
+
+    /// The owner `from` hands the display to `target`; when lending, the
+    /// display comes back to `from` afterwards. Returns the new epoch.
+    pub fn transfer(&mut self, from: u32, target: Lookup, lend: bool) -> Result<u32, Refused> {
+        if !self.is_owner(from) {
+            return Err(Refused::NotOwner);
+        }
+        let Lookup::Found(target) = target else {
+            return Err(Refused::NoTarget);
+        };
+        if target.pid == 0 || target.exited() {
+            return Err(Refused::NoTarget);
+        }
+        self.lender = if lend { self.owner } else { None };
+        self.owner = Some(target);
+        Ok(self.bump())
+    }
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn refresh(holder: Process, lookup: &mut impl FnMut(u32) -> Lookup) -> Option<Process> {
+    match lookup(holder.pid) {
+        Lookup::Found(now) if holder.is(&now) => Some(Process {
+            cpu_ns: now.cpu_ns,
+            ..holder
+        }),
+        Lookup::Found(_) | Lookup::Missing => None,
+        Lookup::Unknown => Some(holder),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &[u8], cpu_ns: u64) -> Process {
+        let mut buf = [0; NAME_LEN];
+        buf[..name.len()].copy_from_slice(name);
+        Process {
+            pid,
+            state: 1,
+            name: buf,
+            cpu_ns,
+        }
+    }
+
+    /// Process table for `probe`: the listed processes, nothing else.
+    fn table(procs: &[Process]) -> impl FnMut(u32) -> Lookup + '_ {
+        move |pid| {
+            procs
+                .iter()
+                .find(|p| p.pid == pid)
+                .map_or(Lookup::Missing, |p| Lookup::Found(*p))
+        }
+    }
+
+    #[test]
+    fn second_acquirer_is_busy_until_the_owner_exits() {
+        let splash = process(10, b"boot_splash", 100);
+        let compositor = process(20, b"compositor", 5);
+        let mut display = Display::new();
+
+        assert_eq!(display.acquire(splash), Ok(1));
+        assert_eq!(display.acquire(compositor), Err(Refused::Busy));
+        assert_eq!(display.probe(table(&[splash, compositor])), Change::None);
+        assert_eq!(display.owner(), 10);
+
+        assert_eq!(display.probe(table(&[compositor])), Change::Console);
+        assert_eq!(display.acquire(compositor), Ok(2));
+        assert_eq!(display.release(10), Err(Refused::NotOwner));
+        assert_eq!(display.release(20), Ok(Change::Console));
+        assert_eq!(display.owner(), 0);
+    }
+
+    #[test]
+    fn zombie_or_reused_pid_is_not_the_owner() {
+        let compositor = process(20, b"compositor", 500);
+        let mut display = Display::new();
+        display.acquire(compositor).unwrap();
+
+        // Still running: the CPU time seen is kept for the next probe.
+        let later = process(20, b"compositor", 800);
+        assert_eq!(display.probe(table(&[later])), Change::None);
+        let reused = process(20, b"compositor", 600);
+        assert_eq!(display.probe(table(&[reused])), Change::Console);
+
+        display.acquire(compositor).unwrap();
+        let zombie = Process {
+            state: STATE_ZOMBIE,
+            ..compositor
+        };
+        assert_eq!(display.probe(table(&[zombie])), Change::Console);
+
+        display.acquire(compositor).unwrap();
+        let other = process(20, b"game", 900);
+        assert_eq!(display.probe(table(&[other])), Change::Console);
+
+        // An unreadable process list keeps the owner.
+        display.acquire(compositor).unwrap();
+        assert_eq!(display.probe(|_| Lookup::Unknown), Change::None);
+        assert_eq!(display.owner(), 20);
+    }
+
+    #[test]
+    fn lent_display_returns_to_the_lender() {
+        let compositor = process(20, b"compositor", 5);
+        let game = process(30, b"game", 5);
+        let mut display = Display::new();
+        display.acquire(compositor).unwrap();
+
+        assert_eq!(
+            display.transfer(30, Lookup::Found(compositor), true),
+            Err(Refused::NotOwner)
+        );
+        assert_eq!(
+            display.transfer(20, Lookup::Missing, true),
+            Err(Refused::NoTarget)
+        );
+        assert_eq!(display.transfer(20, Lookup::Found(game), true), Ok(2));
+        assert!(display.is_owner(30) && display.is_lender(20));
+
+        // Borrower exits: back to the lender.
+        assert_eq!(display.probe(table(&[compositor])), Change::Returned(3));
+        assert_eq!(display.owner(), 20);
+
+        // Borrower releases.
+        display.transfer(20, Lookup::Found(game), true).unwrap();
+        assert_eq!(display.release(30), Ok(Change::Returned(5)));
+
+        // Lender takes it back from a live borrower.
+        display.transfer(20, Lookup::Found(game), true).unwrap();
+        assert_eq!(display.acquire(compositor), Ok(7));
+        assert!(!display.is_lender(20));
+
+        // Lender gone first: the borrower's exit restores the console.
+        display.transfer(20, Lookup::Found(game), true).unwrap();
+        assert_eq!(display.probe(table(&[game])), Change::None);
+        assert!(!display.is_lender(20));
+        assert_eq!(display.probe(table(&[])), Change::Console);
+    }
+
+    #[test]
+    fn plain_transfer_forgets_the_lender() {
+        let splash = process(10, b"boot_splash", 5);
+        let compositor = process(20, b"compositor", 5);
+        let mut display = Display::new();
+        display.acquire(splash).unwrap();
+
+        assert_eq!(
+            display.transfer(10, Lookup::Found(compositor), false),
+            Ok(2)
+        );
+        assert_eq!(display.release(20), Ok(Change::Console));
+    }
+}
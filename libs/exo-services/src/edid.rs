@@ -0,0 +1,732 @@
+//! EDID parsing and display mode selection.
+//!
+//! The bootloader reads the monitor's EDID from the firmware (EFI EDID
+//! protocol) and hands it to the kernel, which serves it to the display
+//! layer (`SYS_DISPLAY_EDID`). [`Edid::parse`] decodes the base block and
+//! CTA-861 extensions: identity and name, physical size (for
+//! [`crate::scale::detect`]), the modes the monitor lists and its HDR static
+//! metadata. The mode an output runs at is then chosen by [`select`] from
+//! its [`ModeSetting`] (`/etc/exo/outputs.conf`, see [`crate::outputs`]):
+//! the monitor's preferred mode by default, a listed size and refresh rate,
+//! or a custom modeline for monitors whose EDID is missing or wrong.
+
+use crate::math;
+
+pub const BLOCK_LEN: usize = 128;
+/// Bytes kept from the firmware: the base block and three extensions.
+pub const EDID_MAX: usize = 4 * BLOCK_LEN;
+pub const MODES_MAX: usize = 32;
+
+const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+const DESCRIPTOR_LEN: usize = 18;
+const CTA_EXTENSION: u8 = 0x02;
+
+pub const TIMING_HSYNC_POSITIVE: u8 = 1 << 0;
+pub const TIMING_VSYNC_POSITIVE: u8 = 1 << 1;
+pub const TIMING_INTERLACED: u8 = 1 << 2;
+
+const MODELINE_FLAGS: [(&str, u8); 5] = [
+    ("+hsync", TIMING_HSYNC_POSITIVE),
+    ("-hsync", 0),
+    ("+vsync", TIMING_VSYNC_POSITIVE),
+    ("-vsync", 0),
+    ("interlace", TIMING_INTERLACED),
+];
+
+/// Transfer functions of the HDR static metadata block.
+pub const EOTF_SDR: u8 = 1 << 0;
+pub const EOTF_HDR_GAMMA: u8 = 1 << 1;
+pub const EOTF_PQ: u8 = 1 << 2;
+pub const EOTF_HLG: u8 = 1 << 3;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EdidError {
+    TooShort,
+    BadHeader,
+    BadChecksum,
+}
+
+/// Full timing of a mode, as in a detailed timing descriptor or a modeline.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Timing {
+    pub clock_khz: u32,
+    pub hdisplay: u16,
+    pub hsync_start: u16,
+    pub hsync_end: u16,
+    pub htotal: u16,
+    pub vdisplay: u16,
+    pub vsync_start: u16,
+    pub vsync_end: u16,
+    pub vtotal: u16,
+    /// `TIMING_*` bits.
+    pub flags: u8,
+}
+
+impl Timing {
+    /// Detailed timing descriptor; `None` for display descriptors (clock 0).
+    fn from_descriptor(d: &[u8]) -> Option<Self> {
+        let clock = u16::from_le_bytes([d[0], d[1]]) as u32;
+        if clock == 0 {
+            return None;
+        }
+        let hactive = d[2] as u16 | ((d[4] as u16 & 0xf0) << 4);
+        let hblank = d[3] as u16 | ((d[4] as u16 & 0x0f) << 8);
+        let vactive = d[5] as u16 | ((d[7] as u16 & 0xf0) << 4);
+        let vblank = d[6] as u16 | ((d[7] as u16 & 0x0f) << 8);
+        let hsync_offset = d[8] as u16 | ((d[11] as u16 & 0xc0) << 2);
+        let hsync_width = d[9] as u16 | ((d[11] as u16 & 0x30) << 4);
+        let vsync_offset = (d[10] as u16 >> 4) | ((d[11] as u16 & 0x0c) << 2);
+        let vsync_width = (d[10] as u16 & 0x0f) | ((d[11] as u16 & 0x03) << 4);
+        let mut flags = 0;
+        if d[17] & 0x80 != 0 {
+            flags |= TIMING_INTERLACED;
+        }
+        // Digital separate sync: polarities in bits 2 and 1.
+        if d[17] & 0x18 == 0x18 {
+            if d[17] & 0x04 != 0 {
+                flags |= TIMING_VSYNC_POSITIVE;
+            }
+            if d[17] & 0x02 != 0 {
+                flags |= TIMING_HSYNC_POSITIVE;
+            }
+        }
+        let timing = Self {
+            clock_khz: clock * 10,
+            hdisplay: hactive,
+            hsync_start: hactive + hsync_offset,
+            hsync_end: hactive + hsync_offset + hsync_width,
+            htotal: hactive + hblank,
+            vdisplay: vactive,
+            vsync_start: vactive + vsync_offset,
+            vsync_end: vactive + vsync_offset + vsync_width,
+            vtotal: vactive + vblank,
+            flags,
+        };
+        timing.is_valid().then_some(timing)
+    }
+
+    /// Modeline fields after the name, as in `xrandr --newmode`:
+    /// `<clock MHz> hdisp hsyncstart hsyncend htotal vdisp vsyncstart
+    /// vsyncend vtotal [+hsync|-hsync] [+vsync|-vsync] [interlace]`.
+    pub fn parse_modeline(text: &str) -> Option<Self> {
+        let mut fields = text.split_ascii_whitespace();
+        let clock = fields.next()?.parse::<f64>().ok()?;
+        if !clock.is_finite() || clock <= 0.0 {
+            return None;
+        }
+        let mut numbers = [0u16; 8];
+        for n in &mut numbers {
+            *n = fields.next()?.parse().ok()?;
+        }
+        let mut flags = 0;
+        for flag in fields {
+            let (_, bit) = MODELINE_FLAGS
+                .iter()
+                .find(|(name, _)| flag.eq_ignore_ascii_case(name))?;
+            flags |= bit;
+        }
+        let [hdisplay, hsync_start, hsync_end, htotal, vdisplay, vsync_start, vsync_end, vtotal] =
+            numbers;
+        let timing = Self {
+            clock_khz: math::floor(clock * 1000.0 + 0.5) as u32,
+            hdisplay,
+            hsync_start,
+            hsync_end,
+            htotal,
+            vdisplay,
+            vsync_start,
+            vsync_end,
+            vtotal,
+            flags,
+        };
+        timing.is_valid().then_some(timing)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.clock_khz > 0
+            && 0 < self.hdisplay
+            && self.hdisplay <= self.hsync_start
+            && self.hsync_start <= self.hsync_end
+            && self.hsync_end <= self.htotal
+            && 0 < self.vdisplay
+            && self.vdisplay <= self.vsync_start
+            && self.vsync_start <= self.vsync_end
+            && self.vsync_end <= self.vtotal
+    }
+
+    /// Refresh rate in millihertz (fields per second for interlaced modes).
+    pub fn refresh_mhz(&self) -> u32 {
+        let pixels = self.htotal as u64 * self.vtotal as u64;
+        let mut mhz = (self.clock_khz as u64 * 1_000_000 + pixels / 2) / pixels;
+        if self.flags & TIMING_INTERLACED != 0 {
+            mhz *= 2;
+        }
+        mhz as u32
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Mode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_mhz: u32,
+    /// The monitor's native mode.
+    pub preferred: bool,
+    /// `None` for modes listed by size and rate only (standard and
+    /// established timings, CTA VICs).
+    pub timing: Option<Timing>,
+}
+
+impl Mode {
+    pub fn from_timing(timing: Timing) -> Self {
+        Self {
+            width: timing.hdisplay as u32,
+            height: timing.vdisplay as u32,
+            refresh_mhz: timing.refresh_mhz(),
+            preferred: false,
+            timing: Some(timing),
+        }
+    }
+
+    const fn sized(width: u32, height: u32, hz: u32) -> Self {
+        Self {
+            width,
+            height,
+            refresh_mhz: hz * 1000,
+            preferred: false,
+            timing: None,
+        }
+    }
+
+    fn same_as(&self, other: &Mode) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.refresh_mhz.abs_diff(other.refresh_mhz) < 500
+    }
+}
+
+/// HDR static metadata data block (CTA-861.3). Luminances are kept as the
+/// monitor's code values.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Hdr {
+    /// `EOTF_*` bits.
+    pub eotfs: u8,
+    pub max_luminance: Option<u8>,
+    pub max_frame_average: Option<u8>,
+    pub min_luminance: Option<u8>,
+}
+
+impl Hdr {
+    pub fn supports(&self, eotf: u8) -> bool {
+        self.eotfs & eotf != 0
+    }
+
+    fn luminance(code: u8) -> f64 {
+        50.0 * math::powf(2.0, code as f64 / 32.0)
+    }
+
+    /// Peak luminance in cd/m².
+    pub fn max_cd(&self) -> Option<f64> {
+        self.max_luminance.map(Self::luminance)
+    }
+
+    /// Maximum full-frame average luminance in cd/m².
+    pub fn max_frame_average_cd(&self) -> Option<f64> {
+        self.max_frame_average.map(Self::luminance)
+    }
+
+    /// Black level in cd/m², relative to the peak.
+    pub fn min_cd(&self) -> Option<f64> {
+        let max = self.max_cd()?;
+        let code = self.min_luminance? as f64 / 255.0;
+        Some(max * code * code / 100.0)
+    }
+}
+
+/// Established timings, from bit 7 of byte 35 down to bit 7 of byte 37.
+const ESTABLISHED: [(u32, u32, u32); 17] = [
+    (720, 400, 70),
+    (720, 400, 88),
+    (640, 480, 60),
+    (640, 480, 67),
+    (640, 480, 72),
+    (640, 480, 75),
+    (800, 600, 56),
+    (800, 600, 60),
+    (800, 600, 72),
+    (800, 600, 75),
+    (832, 624, 75),
+    (1024, 768, 87),
+    (1024, 768, 60),
+    (1024, 768, 70),
+    (1024, 768, 75),
+    (1280, 1024, 75),
+    (1152, 870, 75),
+];
+
+/// Common CTA-861 video identification codes.
+const VICS: [(u8, u32, u32, u32); 12] = [
+    (1, 640, 480, 60),
+    (4, 1280, 720, 60),
+    (16, 1920, 1080, 60),
+    (19, 1280, 720, 50),
+    (31, 1920, 1080, 50),
+    (32, 1920, 1080, 24),
+    (33, 1920, 1080, 25),
+    (34, 1920, 1080, 30),
+    (63, 1920, 1080, 120),
+    (95, 3840, 2160, 30),
+    (96, 3840, 2160, 50),
+    (97, 3840, 2160, 60),
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Edid {
+    /// PNP vendor ID, e.g. `DEL`.
+    pub manufacturer: [u8; 3],
+    pub product: u16,
+    pub serial: u32,
+    /// Version and revision, e.g. `(1, 4)`.
+    pub version: (u8, u8),
+    /// Image size in millimetres, `(0, 0)` when unknown.
+    pub physical_mm: (u32, u32),
+    pub hdr: Option<Hdr>,
+    name: [u8; 13],
+    name_len: u8,
+    modes: [Mode; MODES_MAX],
+    mode_count: u8,
+}
+
+impl Edid {
+    /// Base block and the extensions that follow it; extensions with a bad
+    /// checksum or past the end of `bytes` are skipped.
+    pub fn parse(bytes: &[u8]) -> Result<Self, EdidError> {
+        if bytes.len() < BLOCK_LEN {
+            return Err(EdidError::TooShort);
+        }
+        let base = &bytes[..BLOCK_LEN];
+        if base[..8] != HEADER {
+            return Err(EdidError::BadHeader);
+        }
+        if !checksum_ok(base) {
+            return Err(EdidError::BadChecksum);
+        }
+        let vendor = u16::from_be_bytes([base[8], base[9]]);
+        let letter = |shift: u16| b'@' + ((vendor >> shift) & 0x1f) as u8;
+        let mut edid = Self {
+            manufacturer: [letter(10), letter(5), letter(0)],
+            product: u16::from_le_bytes([base[10], base[11]]),
+            serial: u32::from_le_bytes([base[12], base[13], base[14], base[15]]),
+            version: (base[18], base[19]),
+            // Bytes 21 and 22 are centimetres, or an aspect ratio when one
+            // of them is zero (projectors).
+            physical_mm: match (base[21], base[22]) {
+                (0, _) | (_, 0) => (0, 0),
+                (w, h) => (w as u32 * 10, h as u32 * 10),
+            },
+            hdr: None,
+            name: [0; 13],
+            name_len: 0,
+            modes: [Mode::default(); MODES_MAX],
+            mode_count: 0,
+        };
+
+        for (i, d) in base[54..126].chunks_exact(DESCRIPTOR_LEN).enumerate() {
+            match Timing::from_descriptor(d) {
+                Some(timing) => {
+                    // The first detailed timing is the preferred mode.
+                    let mut mode = Mode::from_timing(timing);
+                    mode.preferred = i == 0;
+                    if i == 0 {
+                        edid.refine_size(d);
+                    }
+                    edid.push(mode);
+                }
+                None if d[3] == 0xfc => edid.set_name(&d[5..]),
+                None => {}
+            }
+        }
+        let established = u32::from_be_bytes([base[35], base[36], base[37], 0]);
+        for (bit, &(w, h, hz)) in ESTABLISHED.iter().enumerate() {
+            if established & (0x8000_0000 >> bit) != 0 {
+                edid.push(Mode::sized(w, h, hz));
+            }
+        }
+        for pair in base[38..54].chunks_exact(2) {
+            if let Some(mode) = standard_timing(pair[0], pair[1], edid.version) {
+                edid.push(mode);
+            }
+        }
+
+        let extensions = (base[126] as usize).min(bytes.len() / BLOCK_LEN - 1);
+        for block in bytes[BLOCK_LEN..]
+            .chunks_exact(BLOCK_LEN)
+            .take(extensions)
+            .filter(|block| block[0] == CTA_EXTENSION && checksum_ok(block))
+        {
+            edid.parse_cta(block);
+        }
+        Ok(edid)
+    }
+
+    fn parse_cta(&mut self, block: &[u8]) {
+        let dtd_start = (block[2] as usize).clamp(4, 127);
+        let mut pos = 4;
+        while pos < dtd_start {
+            let tag = block[pos] >> 5;
+            let len = (block[pos] & 0x1f) as usize;
+            if pos + 1 + len > dtd_start {
+                break;
+            }
+            let data = &block[pos + 1..pos + 1 + len];
+            match tag {
+                // Video data block: one short video descriptor per byte.
+                2 => {
+                    for &svd in data {
+                        let vic = if svd & 0x7f <= 64 { svd & 0x7f } else { svd };
+                        if let Some(&(_, w, h, hz)) = VICS.iter().find(|v| v.0 == vic) {
+                            self.push(Mode::sized(w, h, hz));
+                        }
+                    }
+                }
+                // Extended tag 6: HDR static metadata.
+                7 if len >= 3 && data[0] == 6 => {
+                    let code = |i: usize| data.get(i).copied().filter(|&c| c != 0);
+                    self.hdr = Some(Hdr {
+                        eotfs: data[1] & 0x3f,
+                        max_luminance: code(3),
+                        max_frame_average: code(4),
+                        min_luminance: code(5),
+                    });
+                }
+                _ => {}
+            }
+            pos += 1 + len;
+        }
+        if block[2] < 4 {
+            return;
+        }
+        for d in block[dtd_start..127].chunks_exact(DESCRIPTOR_LEN) {
+            match Timing::from_descriptor(d) {
+                Some(timing) => self.push(Mode::from_timing(timing)),
+                None => break,
+            }
+        }
+    }
+
+    /// The preferred timing's image size is in millimetres rather than
+    /// centimetres; it wins when it agrees with the base block.
+    fn refine_size(&mut self, d: &[u8]) {
+        let w = d[12] as u32 | ((d[14] as u32 & 0xf0) << 4);
+        let h = d[13] as u32 | ((d[14] as u32 & 0x0f) << 8);
+        let (cm_w, cm_h) = self.physical_mm;
+        let close = |mm: u32, cm: u32| cm == 0 || mm.abs_diff(cm) <= 10;
+        if w > 0 && h > 0 && close(w, cm_w) && close(h, cm_h) {
+            self.physical_mm = (w, h);
+        }
+    }
+
+    fn set_name(&mut self, text: &[u8]) {
+        let len = text
+            .iter()
+            .position(|&b| b == b'\n')
+            .unwrap_or(text.len())
+            .min(self.name.len());
+        let name = &text[..len];
+        let len = name.iter().rposition(|&b| b != b' ').map_or(0, |i| i + 1);
+        if name[..len].is_ascii() {
+            self.name[..len].copy_from_slice(&name[..len]);
+            self.name_len = len as u8;
+        }
+    }
+
+    fn push(&mut self, mode: Mode) {
+        let count = self.mode_count as usize;
+        if let Some(known) = self.modes[..count].iter_mut().find(|m| m.same_as(&mode)) {
+            // Keep the full timing a later descriptor may give.
+            if known.timing.is_none() {
+                known.timing = mode.timing;
+            }
+            return;
+        }
+        if count < MODES_MAX {
+            self.modes[count] = mode;
+            self.mode_count += 1;
+        }
+    }
+
+    /// Monitor name (display product name descriptor), empty when absent.
+    pub fn name(&self) -> &str {
+        core::str::from_utf8(&self.name[..self.name_len as usize]).unwrap_or("")
+    }
+
+    pub fn modes(&self) -> &[Mode] {
+        &self.modes[..self.mode_count as usize]
+    }
+
+    pub fn preferred(&self) -> Option<Mode> {
+        self.modes().iter().copied().find(|mode| mode.preferred)
+    }
+}
+
+fn checksum_ok(block: &[u8]) -> bool {
+    block.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) == 0
+}
+
+fn standard_timing(b0: u8, b1: u8, version: (u8, u8)) -> Option<Mode> {
+    if (b0, b1) == (0x01, 0x01) || b0 == 0x00 {
+        return None;
+    }
+    let width = (b0 as u32 + 31) * 8;
+    let height = match b1 >> 6 {
+        // 1:1 before EDID 1.3.
+        0 if version < (1, 3) => width,
+        0 => width * 10 / 16,
+        1 => width * 3 / 4,
+        2 => width * 4 / 5,
+        _ => width * 9 / 16,
+    };
+    Some(Mode::sized(width, height, (b1 & 0x3f) as u32 + 60))
+}
+
+/// Mode an output is asked to run at.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ModeSetting {
+    Preferred,
+    /// `refresh_mhz` `None`: the highest rate listed for that size.
+    Size {
+        width: u32,
+        height: u32,
+        refresh_mhz: Option<u32>,
+    },
+    /// Modeline, used as is whether or not the monitor lists it.
+    Custom(Timing),
+}
+
+impl ModeSetting {
+    /// `preferred` or `WxH[@Hz]`, e.g. `2560x1440@59.95`.
+    pub fn parse(text: &str) -> Option<Self> {
+        if text == "preferred" {
+            return Some(Self::Preferred);
+        }
+        let (size, rate) = match text.split_once('@') {
+            Some((size, rate)) => (size, Some(rate)),
+            None => (text, None),
+        };
+        let (width, height) = size.split_once('x')?;
+        let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+        let refresh_mhz = match rate {
+            Some(rate) => {
+                let hz = rate.parse::<f64>().ok()?;
+                if !hz.is_finite() || hz <= 0.0 {
+                    return None;
+                }
+                Some(math::floor(hz * 1000.0 + 0.5) as u32)
+            }
+            None => None,
+        };
+        (width > 0 && height > 0).then_some(Self::Size {
+            width,
+            height,
+            refresh_mhz,
+        })
+    }
+}
+
+/// Mode to set among the monitor's `modes` for `setting`. A size the
+/// monitor does not list falls back to the preferred mode, and the
+/// preferred mode, when the EDID has none, to the largest mode at its
+/// highest rate; `None` only for an empty list without a modeline.
+pub fn select(modes: &[Mode], setting: &ModeSetting) -> Option<Mode> {
+    let fallback = || {
+        modes
+            .iter()
+            .copied()
+            .find(|mode| mode.preferred)
+            .or_else(|| {
+                modes
+                    .iter()
+                    .copied()
+                    .max_by_key(|mode| (mode.width * mode.height, mode.refresh_mhz))
+            })
+    };
+    match *setting {
+        ModeSetting::Preferred => fallback(),
+        ModeSetting::Custom(timing) => Some(Mode::from_timing(timing)),
+        ModeSetting::Size {
+            width,
+            height,
+            refresh_mhz,
+        } => modes
+            .iter()
+            .copied()
+            .filter(|mode| (mode.width, mode.height) == (width, height))
+            .min_by_key(|mode| match refresh_mhz {
+                Some(wanted) => mode.refresh_mhz.abs_diff(wanted),
+                None => u32::MAX - mode.refresh_mhz,
+            })
+            .or_else(fallback),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 27" 2560x1440 monitor (DEL "DELL U2719D") with a CTA extension
+    /// listing 1080p and 4K VICs, a 1080p detailed timing and HDR10.
+    fn monitor() -> [u8; 256] {
+        let mut edid = [0u8; 256];
+        edid[..8].copy_from_slice(&HEADER);
+        edid[8..10].copy_from_slice(&[0x10, 0xac]);
+        edid[10..12].copy_from_slice(&0xa0c3u16.to_le_bytes());
+        edid[12..16].copy_from_slice(&0x3453_4c42u32.to_le_bytes());
+        edid[18..20].copy_from_slice(&[1, 4]);
+        edid[21..23].copy_from_slice(&[60, 34]);
+        // 640x480@60, 800x600@60, 1024x768@60.
+        edid[35..38].copy_from_slice(&[0x21, 0x08, 0x00]);
+        // Standard timings: 1920x1080@60, 1280x1024@60, unused.
+        edid[38..44].copy_from_slice(&[0xd1, 0xc0, 0x81, 0x80, 0x01, 0x01]);
+        edid[44..54].copy_from_slice(&[0x01; 10]);
+        // 2560x1440@59.951, 241.5 MHz, 597x336 mm.
+        edid[54..72].copy_from_slice(&[
+            0x56, 0x5e, 0x00, 0xa0, 0xa0, 0xa0, 0x29, 0x50, 0x30, 0x20, 0x35, 0x00, 0x55, 0x50,
+            0x21, 0x00, 0x00, 0x1a,
+        ]);
+        let mut name = [0u8; 18];
+        name[3] = 0xfc;
+        name[5..18].copy_from_slice(b"DELL U2719D\n ");
+        edid[72..90].copy_from_slice(&name);
+        edid[126] = 1;
+        edid[127] = fix(&edid[..128]);
+
+        let cta = &mut edid[128..];
+        cta[..4].copy_from_slice(&[CTA_EXTENSION, 3, 0, 0]);
+        let blocks = [
+            0x43, 0x90, 0x1f, 0x61, // VICs 16 (native), 31, 97
+            0xe3, 0x06, 0x05, 0x01, // HDR: SDR, PQ; no luminance
+        ];
+        cta[4..12].copy_from_slice(&blocks);
+        cta[2] = 12;
+        // 1920x1080@60, 148.5 MHz.
+        cta[12..30].copy_from_slice(&[
+            0x02, 0x3a, 0x80, 0x18, 0x71, 0x38, 0x2d, 0x40, 0x58, 0x2c, 0x45, 0x00, 0x55, 0x50,
+            0x21, 0x00, 0x00, 0x1e,
+        ]);
+        cta[127] = fix(&cta[..128]);
+        edid
+    }
+
+    fn fix(block: &[u8]) -> u8 {
+        0u8.wrapping_sub(block[..127].iter().fold(0u8, |s, &b| s.wrapping_add(b)))
+    }
+
+    #[test]
+    fn parses_identity_size_and_modes() {
+        let edid = Edid::parse(&monitor()).unwrap();
+        assert_eq!(&edid.manufacturer, b"DEL");
+        assert_eq!(edid.product, 0xa0c3);
+        assert_eq!(edid.version, (1, 4));
+        assert_eq!(edid.name(), "DELL U2719D");
+        assert_eq!(edid.physical_mm, (597, 336));
+
+        let preferred = edid.preferred().unwrap();
+        assert_eq!((preferred.width, preferred.height), (2560, 1440));
+        assert_eq!(preferred.refresh_mhz, 59951);
+        let timing = preferred.timing.unwrap();
+        assert_eq!((timing.htotal, timing.vtotal), (2720, 1481));
+        assert_eq!(timing.flags, TIMING_HSYNC_POSITIVE);
+
+        let has = |w, h, hz| {
+            edid.modes()
+                .iter()
+                .any(|m| (m.width, m.height, m.refresh_mhz / 1000) == (w, h, hz))
+        };
+        assert!(has(640, 480, 60) && has(800, 600, 60) && has(1024, 768, 60));
+        assert!(has(1280, 1024, 60) && has(1920, 1080, 50) && has(3840, 2160, 60));
+        // The standard 1080p timing picked up the CTA descriptor.
+        let full_hd = edid
+            .modes()
+            .iter()
+            .find(|m| (m.width, m.height, m.refresh_mhz) == (1920, 1080, 60000))
+            .unwrap();
+        assert_eq!(full_hd.timing.map(|t| t.clock_khz), Some(148_500));
+        assert_eq!(edid.modes().len(), 8);
+
+        let hdr = edid.hdr.unwrap();
+        assert!(hdr.supports(EOTF_PQ) && !hdr.supports(EOTF_HLG));
+        assert_eq!(hdr.max_cd(), None);
+    }
+
+    #[test]
+    fn rejects_broken_blocks() {
+        let mut bytes = monitor();
+        assert_eq!(Edid::parse(&bytes[..100]), Err(EdidError::TooShort));
+        bytes[200] ^= 1;
+        // A corrupt extension is dropped, the base block still parses.
+        let edid = Edid::parse(&bytes).unwrap();
+        assert_eq!(edid.hdr, None);
+        bytes[20] ^= 1;
+        assert_eq!(Edid::parse(&bytes), Err(EdidError::BadChecksum));
+        bytes[0] = 1;
+        assert_eq!(Edid::parse(&bytes), Err(EdidError::BadHeader));
+    }
+
+    #[test]
+    fn hdr_luminance_codes() {
+        let hdr = Hdr {
+            eotfs: EOTF_SDR | EOTF_PQ,
+            max_luminance: Some(96),
+            max_frame_average: Some(64),
+            min_luminance: Some(51),
+        };
+        assert!((hdr.max_cd().unwrap() - 400.0).abs() < 0.01);
+        assert!((hdr.max_frame_average_cd().unwrap() - 200.0).abs() < 0.01);
+        assert!((hdr.min_cd().unwrap() - 0.16).abs() < 0.001);
+    }
+
+    #[test]
+    fn modelines_and_selection() {
+        let modes = Edid::parse(&monitor()).unwrap();
+        let modes = modes.modes();
+        let pick = |setting| select(modes, &setting).map(|m| (m.width, m.height, m.refresh_mhz));
+
+        assert_eq!(pick(ModeSetting::Preferred), Some((2560, 1440, 59951)));
+        let wanted = ModeSetting::parse("1920x1080@50").unwrap();
+        assert_eq!(pick(wanted), Some((1920, 1080, 50000)));
+        assert_eq!(
+            pick(ModeSetting::parse("1920x1080").unwrap()),
+            Some((1920, 1080, 60000))
+        );
+        // Not listed: the preferred mode.
+        assert_eq!(
+            pick(ModeSetting::parse("1366x768@60").unwrap()),
+            Some((2560, 1440, 59951))
+        );
+        assert_eq!(ModeSetting::parse("1920x"), None);
+        assert_eq!(ModeSetting::parse("1920x1080@0"), None);
+
+        // Overclocked modeline for a monitor with a wrong EDID.
+        let timing =
+            Timing::parse_modeline("312.25 2560 2608 2640 2720 1440 1443 1448 1530 +hsync -vsync")
+                .unwrap();
+        assert_eq!(timing.flags, TIMING_HSYNC_POSITIVE);
+        assert_eq!(pick(ModeSetting::Custom(timing)), Some((2560, 1440, 75031)));
+        assert_eq!(
+            Timing::parse_modeline("148.5 1920 1880 2052 2200 1080 1084 1089 1125"),
+            None
+        );
+        assert_eq!(
+            Timing::parse_modeline("148.5 1920 2008 2052 2200 1080 1084 1089 1125 +csync"),
+            None
+        );
+
+        // Without a preferred mode: the largest.
+        let plain = [Mode::sized(1024, 768, 60), Mode::sized(1280, 1024, 75)];
+        assert_eq!(
+            select(&plain, &ModeSetting::Preferred).map(|m| m.width),
+            Some(1280)
+        );
+        assert_eq!(select(&[], &ModeSetting::Preferred), None);
+    }
+}
@@ -0,0 +1,452 @@
+//! System event timeline, kept for debugging and bug reports.
+//!
+//! The `event_journal` daemon appends one line per significant event to
+//! [`JOURNAL_PATH`]: boots (with a random boot id), shutdowns, resumes,
+//! service crashes and restarts, OOM kills and system deployments. Each
+//! kind has a single trusted reporter ([`Kind::reporter`]); the daemon
+//! stamps the boot id and both clocks itself. `exo-events` prints the
+//! timeline, the settings' history page shows it.
+//!
+//! Line format:
+//!
+//! ```text
+//! # <boot id, hex> <UNIX seconds> <uptime ms> <kind> <subject|-> <detail>
+//! 4f1c09a2d35e7b60 1760610000 812 boot - 0
+//! 4f1c09a2d35e7b60 1760610433 433107 crash network_server 139
+//! 4f1c09a2d35e7b60 1760610434 434120 restart network_server 57
+//! ```
+//!
+//! When the journal reaches [`JOURNAL_MAX_BYTES`] it moves to
+//! [`ROTATED_PATH`], replacing the previous one.
+
+pub const JOURNAL_PATH: &str = "/var/log/exo/events";
+pub const ROTATED_PATH: &str = "/var/log/exo/events.old";
+pub const JOURNAL_MAX_BYTES: u64 = 64 << 10;
+
+/// Longest subject kept; longer ones are cut.
+pub const SUBJECT_MAX: usize = 48;
+/// Longest `EVENTS_MSG_RECORD` payload.
+pub const RECORD_MAX: usize = 10 + SUBJECT_MAX;
+/// Longest journal line.
+pub const LINE_MAX: usize = 128;
+
+pub const EVENTS_MSG_HEARTBEAT: u32 = 0;
+/// Payload: see [`encode_record`]. Notification, no reply.
+pub const EVENTS_MSG_RECORD: u32 = 1;
+/// Reply: boot id, events recorded this boot.
+pub const EVENTS_MSG_STATUS: u32 = 2;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Kind {
+    /// Detail: 1 if the previous boot did not shut down cleanly.
+    Boot = 0,
+    Shutdown = 1,
+    /// Detail: time asleep (ms).
+    Resume = 2,
+    /// Subject: service; detail: wait status.
+    Crash = 3,
+    /// Subject: service; detail: new PID.
+    Restart = 4,
+    /// Subject: deployment id; detail: deployment serial.
+    Deploy = 5,
+    /// Detail: free pages at the kill.
+    OomKill = 6,
+}
+
+/// Who may record an event kind.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Reporter {
+    /// The journal itself.
+    Journal,
+    /// Init (PID 1).
+    Init,
+    /// The process owning this endpoint name.
+    Service(&'static str),
+    /// Any process: the updater runs as a plain command.
+    Anyone,
+}
+
+impl Kind {
+    const ALL: [Kind; 7] = [
+        Kind::Boot,
+        Kind::Shutdown,
+        Kind::Resume,
+        Kind::Crash,
+        Kind::Restart,
+        Kind::Deploy,
+        Kind::OomKill,
+    ];
+
+    pub fn from_u8(raw: u8) -> Option<Self> {
+        Self::ALL.get(raw as usize).copied()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Boot => "boot",
+            Kind::Shutdown => "shutdown",
+            Kind::Resume => "resume",
+            Kind::Crash => "crash",
+            Kind::Restart => "restart",
+            Kind::Deploy => "deploy",
+            Kind::OomKill => "oom-kill",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    pub fn reporter(self) -> Reporter {
+        match self {
+            Kind::Boot => Reporter::Journal,
+            Kind::Shutdown | Kind::Crash | Kind::Restart => Reporter::Init,
+            Kind::Resume => Reporter::Service("sleep_monitor"),
+            Kind::OomKill => Reporter::Service("mem_pressure"),
+            Kind::Deploy => Reporter::Anyone,
+        }
+    }
+
+    /// How to show the detail, `None` when it carries nothing.
+    fn detail_label(self) -> Option<&'static str> {
+        match self {
+            Kind::Boot | Kind::Shutdown => None,
+            Kind::Resume => Some("slept_ms"),
+            Kind::Crash => Some("status"),
+            Kind::Restart => Some("pid"),
+            Kind::Deploy => Some("serial"),
+            Kind::OomKill => Some("free_pages"),
+        }
+    }
+}
+
+/// `EVENTS_MSG_RECORD` payload: kind (u8), subject length (u8), detail
+/// (u64 LE), subject.
+pub fn encode_record(kind: Kind, subject: &str, detail: u64, out: &mut [u8]) -> Option<usize> {
+    let subject = &subject.as_bytes()[..subject.len().min(SUBJECT_MAX)];
+    let out = out.get_mut(..10 + subject.len())?;
+    out[0] = kind as u8;
+    out[1] = subject.len() as u8;
+    out[2..10].copy_from_slice(&detail.to_le_bytes());
+    out[10..].copy_from_slice(subject);
+    Some(out.len())
+}
+
+/// `None` for an unknown kind or a truncated payload.
+pub fn decode_record(payload: &[u8]) -> Option<(Kind, &[u8], u64)> {
+    let kind = Kind::from_u8(*payload.first()?)?;
+    let len = (*payload.get(1)? as usize).min(SUBJECT_MAX);
+    let detail = u64::from_le_bytes(payload.get(2..10)?.try_into().unwrap());
+    Some((kind, payload.get(10..10 + len)?, detail))
+}
+
+/// One journal line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Record<'a> {
+    pub boot_id: u64,
+    /// UNIX seconds, 0 while the clock was not set.
+    pub realtime: u64,
+    pub uptime_ms: u64,
+    pub kind: Kind,
+    /// Empty when the event has none.
+    pub subject: &'a str,
+    pub detail: u64,
+}
+
+struct Writer<'a> {
+    out: &'a mut [u8],
+    len: usize,
+}
+
+impl Writer<'_> {
+    fn byte(&mut self, b: u8) -> Option<()> {
+        *self.out.get_mut(self.len)? = b;
+        self.len += 1;
+        Some(())
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> Option<()> {
+        bytes.iter().try_for_each(|&b| self.byte(b))
+    }
+
+    fn decimal(&mut self, value: u64, width: usize) -> Option<()> {
+        let mut digits = [b'0'; 20];
+        let mut n = digits.len();
+        let mut v = value;
+        loop {
+            n -= 1;
+            digits[n] = b'0' + (v % 10) as u8;
+            v /= 10;
+            if v == 0 {
+                break;
+            }
+        }
+        self.bytes(&digits[(digits.len() - width).min(n)..])
+    }
+}
+
+impl<'a> Record<'a> {
+    /// Parses a journal line; `None` for comments and damaged lines (a
+    /// crash mid-write leaves a truncated last line).
+    pub fn parse(line: &'a str) -> Option<Self> {
+        let mut fields = line.split_ascii_whitespace();
+        let boot_id = u64::from_str_radix(fields.next()?, 16).ok()?;
+        let realtime = fields.next()?.parse().ok()?;
+        let uptime_ms = fields.next()?.parse().ok()?;
+        let kind = Kind::from_name(fields.next()?)?;
+        let subject = match fields.next()? {
+            "-" => "",
+            subject => subject,
+        };
+        let detail = fields.next()?.parse().ok()?;
+        fields.next().is_none().then_some(Self {
+            boot_id,
+            realtime,
+            uptime_ms,
+            kind,
+            subject,
+            detail,
+        })
+    }
+
+    /// Journal line, newline included. Whitespace and control characters
+    /// in the subject become `_`.
+    pub fn format(&self, out: &mut [u8]) -> Option<usize> {
+        let mut w = Writer { out, len: 0 };
+        for shift in (0..16).rev() {
+            w.byte(b"0123456789abcdef"[(self.boot_id >> (shift * 4)) as usize & 0xf])?;
+        }
+        w.byte(b' ')?;
+        w.decimal(self.realtime, 1)?;
+        w.byte(b' ')?;
+        w.decimal(self.uptime_ms, 1)?;
+        w.byte(b' ')?;
+        w.bytes(self.kind.name().as_bytes())?;
+        w.byte(b' ')?;
+        if self.subject.is_empty() {
+            w.byte(b'-')?;
+        }
+        for &b in &self.subject.as_bytes()[..self.subject.len().min(SUBJECT_MAX)] {
+            w.byte(if b.is_ascii_graphic() { b } else { b'_' })?;
+        }
+        w.byte(b' ')?;
+        w.decimal(self.detail, 1)?;
+        w.byte(b'\n')?;
+        Some(w.len)
+    }
+
+    /// Line for people: `2026-10-16 08:20:33 +433.107s crash network_server
+    /// status=139`, newline included. Without a clock, the date is dashes.
+    pub fn format_human(&self, out: &mut [u8]) -> Option<usize> {
+        let mut w = Writer { out, len: 0 };
+        if self.realtime == 0 {
+            w.bytes(b"----------.--:--:--")?;
+        } else {
+            let t = crate::schedule::civil_from_unix(self.realtime);
+            w.decimal(t.year.max(0) as u64, 4)?;
+            w.byte(b'-')?;
+            w.decimal(t.month as u64, 2)?;
+            w.byte(b'-')?;
+            w.decimal(t.day as u64, 2)?;
+            w.byte(b' ')?;
+            w.decimal(t.hour as u64, 2)?;
+            w.byte(b':')?;
+            w.decimal(t.minute as u64, 2)?;
+            w.byte(b':')?;
+            w.decimal(t.second as u64, 2)?;
+        }
+        w.bytes(b" +")?;
+        w.decimal(self.uptime_ms / 1000, 1)?;
+        w.byte(b'.')?;
+        w.decimal(self.uptime_ms % 1000, 3)?;
+        w.bytes(b"s ")?;
+        w.bytes(self.kind.name().as_bytes())?;
+        if !self.subject.is_empty() {
+            w.byte(b' ')?;
+            w.bytes(self.subject.as_bytes())?;
+        }
+        match self.kind.detail_label() {
+            Some(label) => {
+                w.byte(b' ')?;
+                w.bytes(label.as_bytes())?;
+                w.byte(b'=')?;
+                w.decimal(self.detail, 1)?;
+            }
+            None if self.kind == Kind::Boot && self.detail != 0 => {
+                w.bytes(b" (previous boot did not shut down)")?;
+            }
+            None => {}
+        }
+        w.byte(b'\n')?;
+        Some(w.len)
+    }
+}
+
+/// Last complete line of a journal read in chunks of any size.
+pub struct Tail {
+    line: [u8; LINE_MAX],
+    len: usize,
+    /// Bytes of the line being read; too long a line is dropped.
+    partial: [u8; LINE_MAX],
+    partial_len: usize,
+}
+
+impl Tail {
+    pub const fn new() -> Self {
+        Self {
+            line: [0; LINE_MAX],
+            len: 0,
+            partial: [0; LINE_MAX],
+            partial_len: 0,
+        }
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) {
+        for &b in chunk {
+            if b == b'\n' {
+                if self.partial_len <= LINE_MAX {
+                    self.line[..self.partial_len]
+                        .copy_from_slice(&self.partial[..self.partial_len]);
+                    self.len = self.partial_len;
+                }
+                self.partial_len = 0;
+                continue;
+            }
+            if self.partial_len < LINE_MAX {
+                self.partial[self.partial_len] = b;
+            }
+            self.partial_len = self.partial_len.saturating_add(1);
+        }
+    }
+
+    pub fn record(&self) -> Option<Record<'_>> {
+        Record::parse(core::str::from_utf8(&self.line[..self.len]).ok()?)
+    }
+}
+
+impl Default for Tail {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The previous boot, as seen from the journal's last record, shut down
+/// cleanly; an empty journal counts as clean.
+pub fn clean_shutdown(last: Option<Record<'_>>) -> bool {
+    last.is_none_or(|record| record.kind == Kind::Shutdown)
+}
+
+/// Which records `exo-events` shows.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Filter {
+    pub kind: Option<Kind>,
+    pub boot_id: Option<u64>,
+}
+
+impl Filter {
+    pub fn matches(&self, record: &Record<'_>) -> bool {
+        self.kind.is_none_or(|kind| kind == record.kind)
+            && self.boot_id.is_none_or(|id| id == record.boot_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_roundtrip_through_journal_lines() {
+        let record = Record {
+            boot_id: 0x4f1c_09a2_d35e_7b60,
+            realtime: 1_760_610_433,
+            uptime_ms: 433_107,
+            kind: Kind::Crash,
+            subject: "network_server",
+            detail: 139,
+        };
+        let mut line = [0u8; LINE_MAX];
+        let n = record.format(&mut line).unwrap();
+        let text = core::str::from_utf8(&line[..n]).unwrap();
+        assert_eq!(
+            text,
+            "4f1c09a2d35e7b60 1760610433 433107 crash network_server 139\n"
+        );
+        assert_eq!(Record::parse(text), Some(record));
+
+        let n = record.format_human(&mut line).unwrap();
+        assert_eq!(
+            core::str::from_utf8(&line[..n]).unwrap(),
+            "2025-10-16 10:27:13 +433.107s crash network_server status=139\n"
+        );
+
+        let boot = Record {
+            realtime: 0,
+            uptime_ms: 45,
+            kind: Kind::Boot,
+            subject: "two words",
+            detail: 1,
+            ..record
+        };
+        let n = boot.format(&mut line).unwrap();
+        let text = core::str::from_utf8(&line[..n]).unwrap();
+        assert_eq!(Record::parse(text).unwrap().subject, "two_words");
+        let n = boot.format_human(&mut line).unwrap();
+        assert!(core::str::from_utf8(&line[..n])
+            .unwrap()
+            .starts_with("----------.--:--:-- +0.045s boot two words (previous"));
+
+        assert_eq!(Record::parse("4f1c 17606 433 crash"), None);
+        assert_eq!(Record::parse("4f1c 17606 433 reboot - 0"), None);
+        assert!(record.format(&mut [0u8; 20]).is_none());
+    }
+
+    #[test]
+    fn record_payloads_carry_kind_subject_and_detail() {
+        let mut payload = [0u8; 64];
+        let n = encode_record(Kind::Resume, "", 90_000, &mut payload).unwrap();
+        assert_eq!(
+            decode_record(&payload[..n]),
+            Some((Kind::Resume, &b""[..], 90_000))
+        );
+        let long = core::str::from_utf8(&[b'x'; SUBJECT_MAX + 10]).unwrap();
+        let n = encode_record(Kind::Deploy, long, 3, &mut payload).unwrap();
+        assert_eq!(n, 10 + SUBJECT_MAX);
+        assert_eq!(decode_record(&payload[..n]).unwrap().1.len(), SUBJECT_MAX);
+        payload[0] = 42;
+        assert_eq!(decode_record(&payload), None);
+        assert_eq!(Kind::OomKill.reporter(), Reporter::Service("mem_pressure"));
+        assert_eq!(Kind::from_name("oom-kill"), Some(Kind::OomKill));
+    }
+
+    #[test]
+    fn tail_finds_how_the_previous_boot_ended() {
+        let journal = "00000000000000aa 0 10 boot - 0\n\
+                       00000000000000aa 100 90000 shutdown - 0\n\
+                       00000000000000bb 0 12 boot - 0\n\
+                       00000000000000bb 200 5000 crash vfs_ser";
+        let mut tail = Tail::new();
+        for chunk in journal.as_bytes().chunks(7) {
+            tail.push(chunk);
+        }
+        // The truncated crash line is not a record: the boot line is last.
+        assert_eq!(tail.record().unwrap().boot_id, 0xbb);
+        assert!(!clean_shutdown(tail.record()));
+
+        let mut tail = Tail::new();
+        tail.push(&journal.as_bytes()[..journal.find("00000000000000bb").unwrap()]);
+        assert!(clean_shutdown(tail.record()));
+        assert!(clean_shutdown(Tail::new().record()));
+
+        let filter = Filter {
+            kind: Some(Kind::Boot),
+            boot_id: Some(0xbb),
+        };
+        let matched = journal
+            .lines()
+            .filter_map(Record::parse)
+            .filter(|r| filter.matches(r))
+            .count();
+        assert_eq!(matched, 1);
+    }
+}
@@ -0,0 +1,359 @@
+//! Font fallback chains of the shared glyph atlas.
+//!
+//! `font_cache` renders each code point with the first font of its
+//! script's chain that has a glyph for it, then tries the `common` chain,
+//! then the built-in console font. Fonts are TrueType files
+//! (`/usr/share/fonts/<name>.ttf`); a font with color layers (COLR) gives
+//! color glyphs, used for emoji.
+//!
+//! Configuration (`/etc/exo/fonts.conf`):
+//!
+//! ```text
+//! # fallback <script> <font>...   fonts tried in order
+//! # preload <first>-<last>        code points (hex) published besides ASCII
+//! fallback common dejavu-sans
+//! fallback emoji noto-color-emoji
+//! preload a0-17f
+//! preload 1f600-1f64f
+//! ```
+
+use crate::config;
+
+pub const CONFIG_PATH: &str = "/etc/exo/fonts.conf";
+pub const FONTS_DIR: &str = "/usr/share/fonts";
+/// Built-in bitmap font, the last resort of every chain.
+pub const CONSOLE_FONT: &str = "console";
+
+pub const FONT_NAME_MAX: usize = 32;
+/// Fonts per chain.
+pub const MAX_CHAIN: usize = 4;
+pub const MAX_RANGES: usize = 8;
+/// Largest preload range, so a typo cannot ask for the whole Unicode space.
+pub const MAX_RANGE_LEN: u32 = 0x400;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Script {
+    /// Punctuation, digits, spaces and anything not listed below.
+    Common = 0,
+    Latin = 1,
+    Greek = 2,
+    Cyrillic = 3,
+    Hebrew = 4,
+    Arabic = 5,
+    Devanagari = 6,
+    Thai = 7,
+    Hangul = 8,
+    /// Hiragana and katakana.
+    Kana = 9,
+    Han = 10,
+    Symbols = 11,
+    Emoji = 12,
+}
+
+pub const SCRIPTS: usize = 13;
+
+impl Script {
+    pub const ALL: [Script; SCRIPTS] = [
+        Script::Common,
+        Script::Latin,
+        Script::Greek,
+        Script::Cyrillic,
+        Script::Hebrew,
+        Script::Arabic,
+        Script::Devanagari,
+        Script::Thai,
+        Script::Hangul,
+        Script::Kana,
+        Script::Han,
+        Script::Symbols,
+        Script::Emoji,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Script::Common => "common",
+            Script::Latin => "latin",
+            Script::Greek => "greek",
+            Script::Cyrillic => "cyrillic",
+            Script::Hebrew => "hebrew",
+            Script::Arabic => "arabic",
+            Script::Devanagari => "devanagari",
+            Script::Thai => "thai",
+            Script::Hangul => "hangul",
+            Script::Kana => "kana",
+            Script::Han => "han",
+            Script::Symbols => "symbols",
+            Script::Emoji => "emoji",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+}
+
+/// Script of a code point, from its Unicode block.
+pub fn script_of(ch: char) -> Script {
+    match ch as u32 {
+        0x41..=0x5a | 0x61..=0x7a | 0xc0..=0x24f | 0x1e00..=0x1eff => Script::Latin,
+        0x370..=0x3ff | 0x1f00..=0x1fff => Script::Greek,
+        0x400..=0x52f => Script::Cyrillic,
+        0x590..=0x5ff => Script::Hebrew,
+        0x600..=0x6ff | 0x750..=0x77f => Script::Arabic,
+        0x900..=0x97f => Script::Devanagari,
+        0xe00..=0xe7f => Script::Thai,
+        0x1100..=0x11ff | 0x3130..=0x318f | 0xac00..=0xd7af => Script::Hangul,
+        0x3040..=0x30ff => Script::Kana,
+        0x3400..=0x4dbf | 0x4e00..=0x9fff => Script::Han,
+        0x2190..=0x23ff | 0x25a0..=0x25ff => Script::Symbols,
+        0x2600..=0x27bf | 0x1f1e6..=0x1f1ff | 0x1f300..=0x1faff => Script::Emoji,
+        _ => Script::Common,
+    }
+}
+
+/// Font files are looked up by name: no path separators, no hidden files.
+pub fn valid_font_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= FONT_NAME_MAX
+        && !name.starts_with('.')
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    Syntax,
+    UnknownScript,
+    BadName,
+    /// Too many fonts for one chain.
+    ChainTooLong,
+    BadRange,
+    UnknownKey,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Directive<'a> {
+    /// Script and the fonts, separated by whitespace.
+    Fallback(Script, &'a str),
+    Preload(u32, u32),
+}
+
+fn parse_range(value: &str) -> Option<(u32, u32)> {
+    let (first, last) = value.split_once('-')?;
+    let first = u32::from_str_radix(first, 16).ok()?;
+    let last = u32::from_str_radix(last, 16).ok()?;
+    let valid = first <= last && last <= 0x10ffff && last - first < MAX_RANGE_LEN;
+    valid.then_some((first, last))
+}
+
+impl<'a> config::Directive<'a> for Directive<'a> {
+    type Error = ConfigError;
+
+    fn parse(line: &'a str) -> Result<Self, ConfigError> {
+        let (key, rest) = line
+            .split_once(|c: char| c.is_ascii_whitespace())
+            .ok_or(ConfigError::Syntax)?;
+        let rest = rest.trim();
+        match key {
+            "fallback" => {
+                let (script, fonts) = rest
+                    .split_once(|c: char| c.is_ascii_whitespace())
+                    .ok_or(ConfigError::Syntax)?;
+                let script = Script::from_name(script).ok_or(ConfigError::UnknownScript)?;
+                let mut count = 0;
+                for font in fonts.split_ascii_whitespace() {
+                    if !valid_font_name(font) {
+                        return Err(ConfigError::BadName);
+                    }
+                    count += 1;
+                }
+                if count > MAX_CHAIN {
+                    return Err(ConfigError::ChainTooLong);
+                }
+                Ok(Directive::Fallback(script, fonts.trim()))
+            }
+            "preload" => {
+                let (first, last) = parse_range(rest).ok_or(ConfigError::BadRange)?;
+                Ok(Directive::Preload(first, last))
+            }
+            _ => Err(ConfigError::UnknownKey),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Name {
+    bytes: [u8; FONT_NAME_MAX],
+    len: u8,
+}
+
+impl Name {
+    const EMPTY: Self = Self {
+        bytes: [0; FONT_NAME_MAX],
+        len: 0,
+    };
+
+    fn new(name: &str) -> Self {
+        let mut bytes = [0; FONT_NAME_MAX];
+        bytes[..name.len()].copy_from_slice(name.as_bytes());
+        Self {
+            bytes,
+            len: name.len() as u8,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or("")
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    chains: [[Name; MAX_CHAIN]; SCRIPTS],
+    ranges: [(u32, u32); MAX_RANGES],
+    n_ranges: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    pub const fn new() -> Self {
+        Self {
+            chains: [[Name::EMPTY; MAX_CHAIN]; SCRIPTS],
+            ranges: [(0, 0); MAX_RANGES],
+            n_ranges: 0,
+        }
+    }
+
+    /// Builds a configuration from a file; invalid lines are skipped (see
+    /// [`config::first_error`]), a later `fallback` line replaces the
+    /// script's chain and ranges beyond [`MAX_RANGES`] are dropped.
+    pub fn parse(config: &str) -> Self {
+        let mut out = Self::new();
+        for directive in config::directives::<Directive>(config) {
+            match directive {
+                Directive::Fallback(script, fonts) => {
+                    let chain = &mut out.chains[script as usize];
+                    *chain = [Name::EMPTY; MAX_CHAIN];
+                    for (slot, font) in chain.iter_mut().zip(fonts.split_ascii_whitespace()) {
+                        *slot = Name::new(font);
+                    }
+                }
+                Directive::Preload(first, last) => {
+                    if let Some(slot) = out.ranges.get_mut(out.n_ranges) {
+                        *slot = (first, last);
+                        out.n_ranges += 1;
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Fonts to try for `ch`, in order: its script's chain, the `common`
+    /// chain, then [`CONSOLE_FONT`]. A font may come up twice.
+    pub fn chain(&self, ch: char) -> impl Iterator<Item = &str> + '_ {
+        let script = script_of(ch) as usize;
+        let common = &self.chains[Script::Common as usize];
+        let own = if script == Script::Common as usize {
+            &[][..]
+        } else {
+            &self.chains[script][..]
+        };
+        own.iter()
+            .chain(common)
+            .filter(|name| name.len > 0)
+            .map(Name::as_str)
+            .chain([CONSOLE_FONT])
+    }
+
+    /// Every font named by a chain, each once.
+    pub fn fonts(&self) -> impl Iterator<Item = &str> + '_ {
+        let all = self.chains.as_flattened();
+        all.iter()
+            .enumerate()
+            .filter(move |&(i, name)| name.len > 0 && !all[..i].contains(name))
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// Code points to publish besides ASCII.
+    pub fn preload(&self) -> impl Iterator<Item = char> + '_ {
+        self.ranges[..self.n_ranges]
+            .iter()
+            .flat_map(|&(first, last)| (first..=last).filter_map(char::from_u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{first_error, parse_line};
+
+    fn parse_directive(line: &str) -> Result<Option<Directive<'_>>, ConfigError> {
+        parse_line(line)
+    }
+
+    #[test]
+    fn classifies_scripts() {
+        assert_eq!(script_of('a'), Script::Latin);
+        assert_eq!(script_of('é'), Script::Latin);
+        assert_eq!(script_of('7'), Script::Common);
+        assert_eq!(script_of('Ж'), Script::Cyrillic);
+        assert_eq!(script_of('ש'), Script::Hebrew);
+        assert_eq!(script_of('한'), Script::Hangul);
+        assert_eq!(script_of('カ'), Script::Kana);
+        assert_eq!(script_of('字'), Script::Han);
+        assert_eq!(script_of('😀'), Script::Emoji);
+        assert_eq!(script_of('🇫'), Script::Emoji);
+        assert_eq!(script_of('→'), Script::Symbols);
+        for script in Script::ALL {
+            assert_eq!(Script::from_name(script.name()), Some(script));
+        }
+    }
+
+    #[test]
+    fn parses_chains_and_ranges() {
+        let text = "fallback common dejavu-sans\n\
+                    fallback emoji  noto-color-emoji twemoji\n\
+                    fallback latin dejavu-sans\n\
+                    preload a0-ff\n";
+        assert_eq!(first_error::<Directive>(text), None);
+        let config = Config::parse(text);
+        assert!(config
+            .chain('😀')
+            .eq(["noto-color-emoji", "twemoji", "dejavu-sans", "console"]));
+        assert!(config.chain('7').eq(["dejavu-sans", "console"]));
+        assert!(config.chain('Ж').eq(["dejavu-sans", "console"]));
+        assert!(config
+            .fonts()
+            .eq(["dejavu-sans", "noto-color-emoji", "twemoji"]));
+        assert_eq!(config.preload().count(), 0x60);
+        assert!(Config::new().chain('a').eq(["console"]));
+
+        assert_eq!(
+            parse_directive("fallback klingon x"),
+            Err(ConfigError::UnknownScript)
+        );
+        assert_eq!(
+            parse_directive("fallback emoji ../x"),
+            Err(ConfigError::BadName)
+        );
+        assert_eq!(
+            parse_directive("fallback han a b c d e"),
+            Err(ConfigError::ChainTooLong)
+        );
+        assert_eq!(parse_directive("fallback han"), Err(ConfigError::Syntax));
+        assert_eq!(
+            parse_directive("preload 0-10ffff"),
+            Err(ConfigError::BadRange)
+        );
+        assert_eq!(parse_directive("preload ff-a0"), Err(ConfigError::BadRange));
+        assert_eq!(parse_directive("size 12"), Err(ConfigError::UnknownKey));
+    }
+}
@@ -0,0 +1,537 @@
+//! Background application freezing policy.
+//!
+//! The session freezer suspends applications the user cannot see, so they
+//! stop waking the CPU: the compositor reports every visibility change of an
+//! application (`FREEZER_MSG_APP_STATE`), the audio server reports streams
+//! starting and stopping (`FREEZER_MSG_AUDIO`), and the daemon freezes an
+//! application once it has stayed minimized or fully occluded for the
+//! configured delay, through the kernel freezer (`exo_freeze`). Unlike
+//! `SIGSTOP`, a frozen application is not told and its parent sees nothing;
+//! it resumes exactly where it stopped as soon as it becomes visible again.
+//!
+//! An application playing audio is never frozen, nor is it for
+//! [`AUDIO_HOLD_MS`] after its last stream stopped (gaps between tracks).
+//! Applications listed as exempt are never frozen either.
+//!
+//! Reports are not authenticated: any process of the session can already
+//! stop its peers with `kill(2)`, and the kernel applies the same rule to
+//! `exo_freeze`.
+//!
+//! Configuration (`/etc/exo/freezer.conf`):
+//!
+//! ```text
+//! # exempt <app>         never frozen
+//! # background <ms>      delay for an occluded application
+//! # minimized <ms>       delay for a minimized application
+//! exempt cosmic-term
+//! background 30000
+//! minimized 5000
+//! ```
+
+use crate::config;
+
+pub const CONFIG_PATH: &str = "/etc/exo/freezer.conf";
+
+pub const MAX_APPS: usize = 64;
+pub const MAX_EXEMPT: usize = 16;
+pub const APP_NAME_MAX: usize = 32;
+
+pub const DEFAULT_BACKGROUND_DELAY_MS: u64 = 30_000;
+pub const DEFAULT_MINIMIZED_DELAY_MS: u64 = 5_000;
+/// An application stays awake this long after its last audio stream.
+pub const AUDIO_HOLD_MS: u64 = 10_000;
+
+pub const FREEZER_MSG_HEARTBEAT: u32 = 0;
+/// Payload: an application report (see [`encode_report`]).
+pub const FREEZER_MSG_APP_STATE: u32 = 1;
+/// Payload: pid (u32 LE), playing (u32 LE, 0 or 1).
+pub const FREEZER_MSG_AUDIO: u32 = 2;
+/// Payload: pid (u32 LE). The application closed; it is thawed if needed.
+pub const FREEZER_MSG_FORGET: u32 = 3;
+/// Reply: tracked applications, frozen applications, freezes so far.
+pub const FREEZER_MSG_STATS: u32 = 4;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Visibility {
+    /// Has the keyboard focus.
+    Focused = 0,
+    /// On screen, at least partly, without focus.
+    Visible = 1,
+    /// Fully occluded or on another workspace.
+    Background = 2,
+    Minimized = 3,
+}
+
+impl Visibility {
+    pub fn from_u8(raw: u8) -> Option<Self> {
+        match raw {
+            0 => Some(Self::Focused),
+            1 => Some(Self::Visible),
+            2 => Some(Self::Background),
+            3 => Some(Self::Minimized),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    Syntax,
+    BadName,
+    BadDelay,
+    UnknownKey,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Directive<'a> {
+    Exempt(&'a str),
+    BackgroundDelay(u64),
+    MinimizedDelay(u64),
+}
+
+pub fn valid_app_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= APP_NAME_MAX
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+impl<'a> config::Directive<'a> for Directive<'a> {
+    type Error = ConfigError;
+
+    fn parse(line: &'a str) -> Result<Self, ConfigError> {
+        let (key, value) = config::key_value(line).ok_or(ConfigError::Syntax)?;
+        let delay = || value.parse::<u64>().map_err(|_| ConfigError::BadDelay);
+        match key {
+            "exempt" if valid_app_name(value) => Ok(Directive::Exempt(value)),
+            "exempt" => Err(ConfigError::BadName),
+            "background" => Ok(Directive::BackgroundDelay(delay()?)),
+            "minimized" => Ok(Directive::MinimizedDelay(delay()?)),
+            _ => Err(ConfigError::UnknownKey),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Name {
+    bytes: [u8; APP_NAME_MAX],
+    len: u8,
+}
+
+impl Name {
+    const EMPTY: Self = Self {
+        bytes: [0; APP_NAME_MAX],
+        len: 0,
+    };
+
+    fn new(name: &str) -> Self {
+        let len = name.len().min(APP_NAME_MAX);
+        let mut bytes = [0; APP_NAME_MAX];
+        bytes[..len].copy_from_slice(&name.as_bytes()[..len]);
+        Self {
+            bytes,
+            len: len as u8,
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    pub background_delay_ms: u64,
+    pub minimized_delay_ms: u64,
+    exempt: [Name; MAX_EXEMPT],
+    n_exempt: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    pub const fn new() -> Self {
+        Self {
+            background_delay_ms: DEFAULT_BACKGROUND_DELAY_MS,
+            minimized_delay_ms: DEFAULT_MINIMIZED_DELAY_MS,
+            exempt: [Name::EMPTY; MAX_EXEMPT],
+            n_exempt: 0,
+        }
+    }
+
+    /// Builds a configuration from a file; invalid lines are skipped (see
+    /// [`config::first_error`]), exemptions beyond [`MAX_EXEMPT`] are
+    /// dropped.
+    pub fn parse(config: &str) -> Self {
+        let mut out = Self::new();
+        for directive in config::directives::<Directive>(config) {
+            match directive {
+                Directive::Exempt(name) => {
+                    if let Some(slot) = out.exempt.get_mut(out.n_exempt) {
+                        *slot = Name::new(name);
+                        out.n_exempt += 1;
+                    }
+                }
+                Directive::BackgroundDelay(ms) => out.background_delay_ms = ms,
+                Directive::MinimizedDelay(ms) => out.minimized_delay_ms = ms,
+            }
+        }
+        out
+    }
+
+    pub fn is_exempt(&self, name: &str) -> bool {
+        self.exempt[..self.n_exempt]
+            .iter()
+            .any(|e| e.as_bytes() == name.as_bytes())
+    }
+}
+
+/// Application report: pid (u32 LE), visibility (u8), name length (u8),
+/// name. Returns the encoded length.
+pub fn encode_report(
+    pid: u32,
+    visibility: Visibility,
+    name: &str,
+    out: &mut [u8],
+) -> Option<usize> {
+    if !valid_app_name(name) {
+        return None;
+    }
+    let len = 6 + name.len();
+    let out = out.get_mut(..len)?;
+    out[..4].copy_from_slice(&pid.to_le_bytes());
+    out[4] = visibility as u8;
+    out[5] = name.len() as u8;
+    out[6..].copy_from_slice(name.as_bytes());
+    Some(len)
+}
+
+pub fn decode_report(payload: &[u8]) -> Option<(u32, Visibility, &str)> {
+    let pid = u32::from_le_bytes(payload.get(..4)?.try_into().ok()?);
+    let visibility = Visibility::from_u8(*payload.get(4)?)?;
+    let len = *payload.get(5)? as usize;
+    let name = core::str::from_utf8(payload.get(6..6 + len)?).ok()?;
+    (pid != 0 && valid_app_name(name)).then_some((pid, visibility, name))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    Freeze(u32),
+    Thaw(u32),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReportError {
+    /// [`MAX_APPS`] applications are already tracked.
+    Full,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct App {
+    /// 0 for a free slot.
+    pid: u32,
+    visibility: Visibility,
+    /// Time of the last visibility change.
+    since_ms: u64,
+    playing: bool,
+    /// Audio hold after the last stream stopped.
+    audio_until_ms: u64,
+    exempt: bool,
+    frozen: bool,
+}
+
+impl App {
+    const FREE: Self = Self {
+        pid: 0,
+        visibility: Visibility::Focused,
+        since_ms: 0,
+        playing: false,
+        audio_until_ms: 0,
+        exempt: false,
+        frozen: false,
+    };
+
+    /// Time from which the application may be frozen; `None` while it must
+    /// stay awake whatever happens next.
+    fn freeze_at(&self, config: &Config) -> Option<u64> {
+        if self.exempt || self.playing {
+            return None;
+        }
+        let delay = match self.visibility {
+            Visibility::Focused | Visibility::Visible => return None,
+            Visibility::Background => config.background_delay_ms,
+            Visibility::Minimized => config.minimized_delay_ms,
+        };
+        Some(self.since_ms.saturating_add(delay).max(self.audio_until_ms))
+    }
+
+    fn wants_frozen(&self, config: &Config, now_ms: u64) -> bool {
+        self.freeze_at(config).is_some_and(|at| now_ms >= at)
+    }
+}
+
+/// Tracked applications and the freeze decisions.
+pub struct Policy {
+    apps: [App; MAX_APPS],
+    config: Config,
+    pub freezes: u64,
+    pub thaws: u64,
+}
+
+impl Policy {
+    pub const fn new(config: Config) -> Self {
+        Self {
+            apps: [App::FREE; MAX_APPS],
+            config,
+            freezes: 0,
+            thaws: 0,
+        }
+    }
+
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    fn find(&mut self, pid: u32) -> Option<&mut App> {
+        self.apps.iter_mut().find(|a| a.pid == pid && pid != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.apps.iter().filter(|a| a.pid != 0).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn frozen(&self) -> usize {
+        self.apps.iter().filter(|a| a.pid != 0 && a.frozen).count()
+    }
+
+    /// Visibility report from the compositor; starts tracking `pid`.
+    pub fn report(
+        &mut self,
+        pid: u32,
+        name: &str,
+        visibility: Visibility,
+        now_ms: u64,
+    ) -> Result<(), ReportError> {
+        if let Some(app) = self.find(pid) {
+            if app.visibility != visibility {
+                app.visibility = visibility;
+                app.since_ms = now_ms;
+            }
+            return Ok(());
+        }
+        let exempt = self.config.is_exempt(name);
+        let slot = self
+            .apps
+            .iter_mut()
+            .find(|a| a.pid == 0)
+            .ok_or(ReportError::Full)?;
+        *slot = App {
+            pid,
+            visibility,
+            since_ms: now_ms,
+            exempt,
+            ..App::FREE
+        };
+        Ok(())
+    }
+
+    /// Audio report; `false` if `pid` is not tracked.
+    pub fn set_audio(&mut self, pid: u32, playing: bool, now_ms: u64) -> bool {
+        let Some(app) = self.find(pid) else {
+            return false;
+        };
+        if app.playing && !playing {
+            app.audio_until_ms = now_ms.saturating_add(AUDIO_HOLD_MS);
+        }
+        app.playing = playing;
+        true
+    }
+
+    /// Stops tracking `pid`; returns whether it was frozen (the caller
+    /// thaws it), `None` if it was not tracked.
+    pub fn forget(&mut self, pid: u32) -> Option<bool> {
+        let app = self.find(pid)?;
+        let frozen = app.frozen;
+        *app = App::FREE;
+        Some(frozen)
+    }
+
+    /// Next freeze or thaw to apply, recorded as done. Thaws come first: a
+    /// visible application must not wait behind background ones.
+    pub fn next_action(&mut self, now_ms: u64) -> Option<Action> {
+        let config = &self.config;
+        if let Some(app) = self
+            .apps
+            .iter_mut()
+            .find(|a| a.pid != 0 && a.frozen && !a.wants_frozen(config, now_ms))
+        {
+            app.frozen = false;
+            self.thaws += 1;
+            return Some(Action::Thaw(app.pid));
+        }
+        let app = self
+            .apps
+            .iter_mut()
+            .find(|a| a.pid != 0 && !a.frozen && a.wants_frozen(config, now_ms))?;
+        app.frozen = true;
+        self.freezes += 1;
+        Some(Action::Freeze(app.pid))
+    }
+
+    /// Milliseconds until the next pending freeze, `None` if nothing is
+    /// scheduled (the daemon then sleeps until the next report).
+    pub fn next_deadline(&self, now_ms: u64) -> Option<u64> {
+        self.apps
+            .iter()
+            .filter(|a| a.pid != 0 && !a.frozen)
+            .filter_map(|a| a.freeze_at(&self.config))
+            .min()
+            .map(|at| at.saturating_sub(now_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{first_error, parse_line};
+
+    fn parse_directive(line: &str) -> Result<Option<Directive<'_>>, ConfigError> {
+        parse_line(line)
+    }
+
+    #[test]
+    fn parses_configuration() {
+        assert_eq!(parse_directive(" # c"), Ok(None));
+        assert_eq!(
+            parse_directive("exempt cosmic-term"),
+            Ok(Some(Directive::Exempt("cosmic-term")))
+        );
+        assert_eq!(
+            parse_directive("minimized 100"),
+            Ok(Some(Directive::MinimizedDelay(100)))
+        );
+        assert_eq!(parse_directive("exempt a/b"), Err(ConfigError::BadName));
+        assert_eq!(parse_directive("background x"), Err(ConfigError::BadDelay));
+        assert_eq!(parse_directive("exempt"), Err(ConfigError::Syntax));
+        assert_eq!(parse_directive("exempt a b"), Err(ConfigError::Syntax));
+        assert_eq!(parse_directive("sleep 1"), Err(ConfigError::UnknownKey));
+
+        let text = "exempt player\nbad line here\nbackground 1000\n";
+        let config = Config::parse(text);
+        assert!(config.is_exempt("player"));
+        assert!(!config.is_exempt("files"));
+        assert_eq!(config.background_delay_ms, 1000);
+        assert_eq!(config.minimized_delay_ms, DEFAULT_MINIMIZED_DELAY_MS);
+        assert_eq!(
+            first_error::<Directive>(text),
+            Some((2, ConfigError::Syntax))
+        );
+    }
+
+    #[test]
+    fn report_roundtrips() {
+        let mut payload = [0u8; 64];
+        let len = encode_report(42, Visibility::Minimized, "files", &mut payload).unwrap();
+        assert_eq!(len, 11);
+        assert_eq!(
+            decode_report(&payload),
+            Some((42, Visibility::Minimized, "files"))
+        );
+        assert_eq!(
+            encode_report(1, Visibility::Focused, "", &mut payload),
+            None
+        );
+        assert_eq!(
+            encode_report(1, Visibility::Focused, "files", &mut [0; 8]),
+            None
+        );
+        payload[4] = 9;
+        assert_eq!(decode_report(&payload), None);
+        assert_eq!(decode_report(&[0; 16]), None);
+    }
+
+    #[test]
+    fn freezes_after_the_delay_and_thaws_on_focus() {
+        let mut policy = Policy::new(Config::new());
+        policy.report(10, "files", Visibility::Focused, 0).unwrap();
+        assert_eq!(policy.next_deadline(0), None);
+
+        policy
+            .report(10, "files", Visibility::Minimized, 1_000)
+            .unwrap();
+        assert_eq!(policy.next_deadline(2_000), Some(4_000));
+        assert_eq!(policy.next_action(5_999), None);
+        assert_eq!(policy.next_action(6_000), Some(Action::Freeze(10)));
+        assert_eq!(policy.next_action(6_001), None);
+        assert_eq!(policy.frozen(), 1);
+
+        // Still minimized: a repeated report does not restart the delay.
+        policy
+            .report(10, "files", Visibility::Minimized, 7_000)
+            .unwrap();
+        assert_eq!(policy.next_action(7_000), None);
+
+        policy
+            .report(10, "files", Visibility::Focused, 8_000)
+            .unwrap();
+        assert_eq!(policy.next_action(8_000), Some(Action::Thaw(10)));
+        assert_eq!((policy.freezes, policy.thaws), (1, 1));
+        assert_eq!(policy.forget(10), Some(false));
+        assert!(policy.is_empty());
+    }
+
+    #[test]
+    fn audio_and_exemptions_keep_apps_awake() {
+        let mut policy = Policy::new(Config::parse("exempt term\n"));
+        policy.report(1, "term", Visibility::Minimized, 0).unwrap();
+        policy
+            .report(2, "player", Visibility::Background, 0)
+            .unwrap();
+        assert!(policy.set_audio(2, true, 0));
+        assert!(!policy.set_audio(3, true, 0));
+        assert_eq!(policy.next_action(60_000), None);
+        assert_eq!(policy.next_deadline(60_000), None);
+
+        // Stream stopped: held awake for AUDIO_HOLD_MS.
+        policy.set_audio(2, false, 60_000);
+        assert_eq!(policy.next_deadline(60_000), Some(AUDIO_HOLD_MS));
+        assert_eq!(
+            policy.next_action(60_000 + AUDIO_HOLD_MS),
+            Some(Action::Freeze(2))
+        );
+
+        // A frozen application that starts playing is thawed first.
+        policy.set_audio(2, true, 80_000);
+        assert_eq!(policy.next_action(80_000), Some(Action::Thaw(2)));
+        assert_eq!(policy.forget(2), Some(false));
+    }
+
+    #[test]
+    fn table_is_bounded() {
+        let mut policy = Policy::new(Config::new());
+        for pid in 1..=MAX_APPS as u32 {
+            policy.report(pid, "app", Visibility::Visible, 0).unwrap();
+        }
+        assert_eq!(
+            policy.report(1000, "app", Visibility::Visible, 0),
+            Err(ReportError::Full)
+        );
+        policy.report(1, "app", Visibility::Minimized, 0).unwrap();
+        assert_eq!(
+            policy.next_action(DEFAULT_MINIMIZED_DELAY_MS),
+            Some(Action::Freeze(1))
+        );
+        assert_eq!(policy.forget(1), Some(true));
+        assert!(policy.report(1000, "app", Visibility::Visible, 0).is_ok());
+    }
+}
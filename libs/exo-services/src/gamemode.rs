@@ -0,0 +1,337 @@
+//! Game mode: a low-latency session for one fullscreen client.
+//!
+//! The display owner asks for it (`GAMEMODE_MSG_ENTER`): either the
+//! compositor on behalf of its fullscreen client, or a client the display
+//! was lent to. The daemon then boosts the client to a real-time or deadline
+//! class through `scheduler_server`, pins the kernel power profile to
+//! performance (`kernel.power.profile`), and tells the compositor to switch
+//! to direct scanout, that is to lend the display to the client
+//! (`FB_TRANSFER_LEND`). Everything is reverted when the client leaves game
+//! mode or exits: the daemon restores the class and the profile it saved,
+//! and `fb_server` hands a lent display back to the compositor by itself.
+//!
+//! Only one client is in game mode at a time.
+//!
+//! Configuration (`/etc/exo/gamemode.conf`):
+//!
+//! ```text
+//! # class realtime|deadline   scheduling class of the client
+//! # runtime <us>              CPU budget per period
+//! # period <us>               one frame at 60 Hz by default
+//! # performance yes|no        pin the performance power profile
+//! # scanout yes|no            ask the compositor for direct scanout
+//! class deadline
+//! runtime 4000
+//! period 16666
+//! ```
+
+use crate::config;
+
+pub const CONFIG_PATH: &str = "/etc/exo/gamemode.conf";
+
+pub const DEFAULT_RUNTIME_US: u32 = 4_000;
+pub const DEFAULT_PERIOD_US: u32 = 16_666;
+
+pub const GAMEMODE_MSG_HEARTBEAT: u32 = 0;
+/// Payload: client pid (u32 LE, 0 = the sender). Display owner only. Reply
+/// `flags`: the steps applied.
+pub const GAMEMODE_MSG_ENTER: u32 = 1;
+/// Payload: client pid (u32 LE, 0 = the sender). From the client or from
+/// the process that entered game mode for it. Reply `flags`: the steps
+/// reverted; with [`STEP_SCANOUT`], the compositor takes the display back.
+pub const GAMEMODE_MSG_EXIT: u32 = 2;
+/// Reply: `handle` = client pid (0 = inactive), `flags` = steps applied.
+pub const GAMEMODE_MSG_STATUS: u32 = 3;
+
+/// The client runs in the configured real-time class.
+pub const STEP_SCHED: u32 = 1 << 0;
+/// The performance power profile is pinned.
+pub const STEP_POWER: u32 = 1 << 1;
+/// The compositor lends the display to the client.
+pub const STEP_SCANOUT: u32 = 1 << 2;
+
+/// Scheduling classes of `scheduler_server` usable for a game.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Class {
+    Realtime,
+    Deadline,
+}
+
+impl Class {
+    /// Value of `SchedulingClass` in `scheduler_server`.
+    pub const fn as_u32(self) -> u32 {
+        match self {
+            Self::Realtime => 3,
+            Self::Deadline => 4,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    Syntax,
+    BadValue,
+    UnknownKey,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Directive {
+    Class(Class),
+    Runtime(u32),
+    Period(u32),
+    Performance(bool),
+    Scanout(bool),
+}
+
+impl<'a> config::Directive<'a> for Directive {
+    type Error = ConfigError;
+
+    fn parse(line: &'a str) -> Result<Self, ConfigError> {
+        let (key, value) = config::key_value(line).ok_or(ConfigError::Syntax)?;
+        let micros = || match value.parse::<u32>() {
+            Ok(us) if us > 0 => Ok(us),
+            _ => Err(ConfigError::BadValue),
+        };
+        let switch = || match value {
+            "yes" => Ok(true),
+            "no" => Ok(false),
+            _ => Err(ConfigError::BadValue),
+        };
+        match key {
+            "class" => match value {
+                "realtime" => Ok(Directive::Class(Class::Realtime)),
+                "deadline" => Ok(Directive::Class(Class::Deadline)),
+                _ => Err(ConfigError::BadValue),
+            },
+            "runtime" => Ok(Directive::Runtime(micros()?)),
+            "period" => Ok(Directive::Period(micros()?)),
+            "performance" => Ok(Directive::Performance(switch()?)),
+            "scanout" => Ok(Directive::Scanout(switch()?)),
+            _ => Err(ConfigError::UnknownKey),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Config {
+    pub class: Class,
+    pub runtime_us: u32,
+    pub period_us: u32,
+    pub performance: bool,
+    pub scanout: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    pub const fn new() -> Self {
+        Self {
+            class: Class::Deadline,
+            runtime_us: DEFAULT_RUNTIME_US,
+            period_us: DEFAULT_PERIOD_US,
+            performance: true,
+            scanout: true,
+        }
+    }
+
+    /// Builds a configuration from a file; invalid lines are skipped (see
+    /// [`config::first_error`]). A runtime longer than its period is clamped
+    /// to it.
+    pub fn parse(config: &str) -> Self {
+        let mut out = Self::new();
+        for directive in config::directives::<Directive>(config) {
+            match directive {
+                Directive::Class(class) => out.class = class,
+                Directive::Runtime(us) => out.runtime_us = us,
+                Directive::Period(us) => out.period_us = us,
+                Directive::Performance(on) => out.performance = on,
+                Directive::Scanout(on) => out.scanout = on,
+            }
+        }
+        out.runtime_us = out.runtime_us.min(out.period_us);
+        out
+    }
+
+    /// Steps a new session goes through.
+    pub fn steps(&self) -> u32 {
+        let mut steps = STEP_SCHED;
+        if self.performance {
+            steps |= STEP_POWER;
+        }
+        if self.scanout {
+            steps |= STEP_SCANOUT;
+        }
+        steps
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Session {
+    pub client: u32,
+    /// Display owner that asked for game mode (the client itself or its
+    /// compositor).
+    pub requester: u32,
+    /// Steps actually applied, the only ones reverted.
+    pub applied: u32,
+    /// Power profile in force before [`STEP_POWER`].
+    pub saved_profile: u64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EnterError {
+    /// The requester does not own the display.
+    NotFullscreen,
+    /// Another client is in game mode.
+    Busy,
+}
+
+pub struct GameMode {
+    config: Config,
+    session: Option<Session>,
+}
+
+impl GameMode {
+    pub const fn new(config: Config) -> Self {
+        Self {
+            config,
+            session: None,
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Takes effect at the next session.
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    pub fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    /// Opens a session for `client` (0 = `sender`) if `sender` owns the
+    /// display; returns the steps left to apply, none when `client` is
+    /// already in game mode.
+    pub fn enter(
+        &mut self,
+        sender: u32,
+        client: u32,
+        display_owner: u32,
+    ) -> Result<u32, EnterError> {
+        let client = if client == 0 { sender } else { client };
+        if sender == 0 || display_owner != sender {
+            return Err(EnterError::NotFullscreen);
+        }
+        match self.session {
+            Some(session) if session.client == client => Ok(0),
+            Some(_) => Err(EnterError::Busy),
+            None => {
+                self.session = Some(Session {
+                    client,
+                    requester: sender,
+                    applied: 0,
+                    saved_profile: 0,
+                });
+                Ok(self.config.steps())
+            }
+        }
+    }
+
+    /// Records a step applied for the current session.
+    pub fn applied(&mut self, step: u32) {
+        if let Some(session) = self.session.as_mut() {
+            session.applied |= step;
+        }
+    }
+
+    pub fn save_profile(&mut self, profile: u64) {
+        if let Some(session) = self.session.as_mut() {
+            session.saved_profile = profile;
+        }
+    }
+
+    /// Closes the session of `client` (0 = `sender`) at the request of the
+    /// client or of its requester; the caller reverts what it returns.
+    pub fn exit(&mut self, sender: u32, client: u32) -> Option<Session> {
+        let client = if client == 0 { sender } else { client };
+        let session = self.session?;
+        if session.client != client || (sender != client && sender != session.requester) {
+            return None;
+        }
+        self.session = None;
+        Some(session)
+    }
+
+    /// Closes the session whatever its owner, when the client is gone.
+    pub fn end(&mut self) -> Option<Session> {
+        self.session.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::first_error;
+
+    #[test]
+    fn parses_config() {
+        let config = Config::parse(
+            "# low latency\nclass realtime\nruntime 20000\nperiod 10000\nscanout no\n",
+        );
+        assert_eq!(config.class, Class::Realtime);
+        assert_eq!((config.runtime_us, config.period_us), (10_000, 10_000));
+        assert!(config.performance);
+        assert_eq!(config.steps(), STEP_SCHED | STEP_POWER);
+
+        assert_eq!(
+            first_error::<Directive>("class deadline\nruntime 0\n"),
+            Some((2, ConfigError::BadValue))
+        );
+        assert_eq!(
+            first_error::<Directive>("turbo yes\n"),
+            Some((1, ConfigError::UnknownKey))
+        );
+        assert_eq!(
+            first_error::<Directive>("scanout\n"),
+            Some((1, ConfigError::Syntax))
+        );
+        assert_eq!(first_error::<Directive>("performance no\n"), None);
+    }
+
+    #[test]
+    fn only_the_display_owner_enters() {
+        let mut mode = GameMode::new(Config::new());
+        assert_eq!(mode.enter(30, 40, 20), Err(EnterError::NotFullscreen));
+        assert_eq!(mode.enter(0, 40, 0), Err(EnterError::NotFullscreen));
+
+        // The compositor (20) asks for its fullscreen client (40).
+        assert_eq!(mode.enter(20, 40, 20), Ok(Config::new().steps()));
+        mode.applied(STEP_SCHED);
+        mode.save_profile(0);
+        assert_eq!(mode.enter(20, 40, 20), Ok(0));
+        assert_eq!(mode.enter(20, 41, 20), Err(EnterError::Busy));
+        assert_eq!(mode.session().unwrap().applied, STEP_SCHED);
+
+        // Neither a third process nor another client may end it.
+        assert_eq!(mode.exit(30, 40), None);
+        assert_eq!(mode.exit(41, 0), None);
+        let session = mode.exit(40, 0).unwrap();
+        assert_eq!((session.client, session.requester), (40, 20));
+        assert!(mode.session().is_none());
+    }
+
+    #[test]
+    fn lent_display_client_enters_for_itself() {
+        let mut mode = GameMode::new(Config::parse("scanout no\nperformance no\n"));
+        assert_eq!(mode.enter(40, 0, 40), Ok(STEP_SCHED));
+        assert_eq!(mode.session().unwrap().requester, 40);
+        assert!(mode.end().is_some());
+        assert!(mode.end().is_none());
+    }
+}
@@ -0,0 +1,327 @@
+//! ICC display profiles: the gamma ramp that calibrates an output.
+//!
+//! The compositor loads the profile of each output
+//! (`/etc/exo/color/<output>.icc`), turns it into a [`Ramp`] and sets it in
+//! `fb_server` (`FB_MSG_SET_GAMMA`) while it owns the display. A profile made
+//! by a calibration tool carries the ramp itself in its `vcgt` tag, which is
+//! used as is. Otherwise the ramp is derived from the tone curves of the
+//! profile (`rTRC`, `gTRC`, `bTRC`): each channel is corrected so that the
+//! output responds as an ideal [`TARGET_GAMMA`] display, the response
+//! content is encoded for.
+//!
+//! Only RGB display profiles are read, and only the parts above: colour
+//! space conversions between profiles (the matrix and white point tags) are
+//! left to clients.
+
+use crate::color::Ramp;
+use crate::math;
+
+pub const PROFILE_DIR: &str = "/etc/exo/color";
+/// Largest profile read; display profiles are a few kilobytes.
+pub const PROFILE_MAX: usize = 64 * 1024;
+pub const TARGET_GAMMA: f64 = 2.2;
+
+const HEADER_LEN: usize = 128;
+const TAG_ENTRY_LEN: usize = 12;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IccError {
+    Truncated,
+    /// No `acsp` signature.
+    NotIcc,
+    /// Not an RGB profile.
+    NotRgb,
+    /// Neither `vcgt` nor the three tone curves, or unreadable ones.
+    NoCurves,
+}
+
+fn be_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// `s15Fixed16Number`.
+fn fixed(data: &[u8], at: usize) -> Option<f64> {
+    Some(be_u32(data, at)? as i32 as f64 / 65_536.0)
+}
+
+pub struct Profile<'a> {
+    data: &'a [u8],
+    tag_count: usize,
+}
+
+impl<'a> Profile<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, IccError> {
+        if data.len() < HEADER_LEN + 4 {
+            return Err(IccError::Truncated);
+        }
+        if &data[36..40] != b"acsp" {
+            return Err(IccError::NotIcc);
+        }
+        if &data[16..20] != b"RGB " {
+            return Err(IccError::NotRgb);
+        }
+        let size = be_u32(data, 0).ok_or(IccError::Truncated)? as usize;
+        let data = data.get(..size).ok_or(IccError::Truncated)?;
+        let tag_count = be_u32(data, HEADER_LEN).ok_or(IccError::Truncated)? as usize;
+        if tag_count > (data.len() - HEADER_LEN - 4) / TAG_ENTRY_LEN {
+            return Err(IccError::Truncated);
+        }
+        Ok(Self { data, tag_count })
+    }
+
+    /// Data of the tag `signature`, if present and inside the profile.
+    pub fn tag(&self, signature: &[u8; 4]) -> Option<&'a [u8]> {
+        (0..self.tag_count).find_map(|i| {
+            let entry = HEADER_LEN + 4 + i * TAG_ENTRY_LEN;
+            if self.data.get(entry..entry + 4)? != signature {
+                return None;
+            }
+            let offset = be_u32(self.data, entry + 4)? as usize;
+            let size = be_u32(self.data, entry + 8)? as usize;
+            self.data.get(offset..offset.checked_add(size)?)
+        })
+    }
+
+    /// Calibration ramp of the output.
+    pub fn ramp(&self) -> Result<Ramp, IccError> {
+        if let Some(ramp) = self.tag(b"vcgt").and_then(vcgt) {
+            return Ok(ramp);
+        }
+        let mut curves = [Curve::Gamma(1.0); 3];
+        for (curve, signature) in curves.iter_mut().zip([b"rTRC", b"gTRC", b"bTRC"]) {
+            *curve = self
+                .tag(signature)
+                .and_then(Curve::parse)
+                .ok_or(IccError::NoCurves)?;
+        }
+        Ok(Ramp::from_fn(|channel, x| {
+            curves[channel].invert(math::powf(x, TARGET_GAMMA))
+        }))
+    }
+}
+
+/// Ramp of a profile file.
+pub fn ramp(profile: &[u8]) -> Result<Ramp, IccError> {
+    Profile::parse(profile)?.ramp()
+}
+
+/// `vcgt` tag: a table (type 0) or a gamma formula (type 1) per channel.
+fn vcgt(tag: &[u8]) -> Option<Ramp> {
+    if tag.get(..4)? != b"vcgt" {
+        return None;
+    }
+    match be_u32(tag, 8)? {
+        0 => {
+            let channels = be_u16(tag, 12)? as usize;
+            let count = be_u16(tag, 14)? as usize;
+            let entry_size = be_u16(tag, 16)? as usize;
+            if channels != 3 || count < 2 || !(1..=2).contains(&entry_size) {
+                return None;
+            }
+            tag.get(18..18 + 3 * count * entry_size)?;
+            let max = if entry_size == 1 { 255.0 } else { 65_535.0 };
+            Some(Ramp::from_fn(|channel, x| {
+                let sample = |i: usize| {
+                    let at = 18 + (channel * count + i) * entry_size;
+                    let value = if entry_size == 1 {
+                        tag[at] as f64
+                    } else {
+                        u16::from_be_bytes([tag[at], tag[at + 1]]) as f64
+                    };
+                    value / max
+                };
+                interpolate(x, count, sample)
+            }))
+        }
+        1 => {
+            let mut formula = [[0.0; 3]; 3];
+            for (channel, params) in formula.iter_mut().enumerate() {
+                for (i, param) in params.iter_mut().enumerate() {
+                    *param = fixed(tag, 12 + (channel * 3 + i) * 4)?;
+                }
+            }
+            Some(Ramp::from_fn(|channel, x| {
+                let [gamma, min, max] = formula[channel];
+                min + (max - min) * math::powf(x, gamma)
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// Linear interpolation at `x` in `[0, 1]` of `count` evenly spaced samples.
+fn interpolate(x: f64, count: usize, sample: impl Fn(usize) -> f64) -> f64 {
+    let position = x.clamp(0.0, 1.0) * (count - 1) as f64;
+    let i = (math::floor(position) as usize).min(count - 2);
+    let t = position - i as f64;
+    sample(i) * (1.0 - t) + sample(i + 1) * t
+}
+
+/// Tone curve: device value to linear light, both in `[0, 1]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Curve<'a> {
+    Gamma(f64),
+    /// Big-endian `u16` samples.
+    Table(&'a [u8]),
+    /// `parametricCurveType` function and its parameters.
+    Parametric(u16, [f64; 7]),
+}
+
+impl<'a> Curve<'a> {
+    fn parse(tag: &'a [u8]) -> Option<Self> {
+        match tag.get(..4)? {
+            b"curv" => match be_u32(tag, 8)? {
+                0 => Some(Self::Gamma(1.0)),
+                1 => Some(Self::Gamma(be_u16(tag, 12)? as f64 / 256.0)),
+                n => tag.get(12..12 + 2 * n as usize).map(Self::Table),
+            },
+            b"para" => {
+                let function = be_u16(tag, 8)?;
+                let count = [1, 3, 4, 5, 7].get(function as usize)?;
+                let mut params = [0.0; 7];
+                for (i, param) in params.iter_mut().take(*count).enumerate() {
+                    *param = fixed(tag, 12 + i * 4)?;
+                }
+                Some(Self::Parametric(function, params))
+            }
+            _ => None,
+        }
+    }
+
+    fn eval(&self, x: f64) -> f64 {
+        let y = match *self {
+            Self::Gamma(g) => math::powf(x, g),
+            Self::Table(table) => interpolate(x, table.len() / 2, |i| {
+                u16::from_be_bytes([table[2 * i], table[2 * i + 1]]) as f64 / 65_535.0
+            }),
+            Self::Parametric(function, [g, a, b, c, d, e, f]) => match function {
+                0 => math::powf(x, g),
+                1 if x >= -b / a => math::powf(a * x + b, g),
+                1 => 0.0,
+                2 if x >= -b / a => math::powf(a * x + b, g) + c,
+                2 => c,
+                3 if x >= d => math::powf(a * x + b, g),
+                3 => c * x,
+                _ if x >= d => math::powf(a * x + b, g) + e,
+                _ => c * x + f,
+            },
+        };
+        y.clamp(0.0, 1.0)
+    }
+
+    /// Device value giving `y`, by bisection on the non-decreasing curve.
+    fn invert(&self, y: f64) -> f64 {
+        let (mut lo, mut hi) = (0.0, 1.0);
+        for _ in 0..24 {
+            let mid = (lo + hi) / 2.0;
+            if self.eval(mid) < y {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        (lo + hi) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    /// RGB display profile holding `tags`.
+    fn profile(tags: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut data = std::vec![0u8; HEADER_LEN];
+        data[16..20].copy_from_slice(b"RGB ");
+        data[36..40].copy_from_slice(b"acsp");
+        data.extend_from_slice(&(tags.len() as u32).to_be_bytes());
+        let mut offset = data.len() + tags.len() * TAG_ENTRY_LEN;
+        for (signature, body) in tags {
+            data.extend_from_slice(*signature);
+            data.extend_from_slice(&(offset as u32).to_be_bytes());
+            data.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            offset += body.len();
+        }
+        for (_, body) in tags {
+            data.extend_from_slice(body);
+        }
+        let size = data.len() as u32;
+        data[..4].copy_from_slice(&size.to_be_bytes());
+        data
+    }
+
+    fn gamma_curve(gamma: f64) -> Vec<u8> {
+        let mut tag = b"curv\0\0\0\0".to_vec();
+        tag.extend_from_slice(&1u32.to_be_bytes());
+        tag.extend_from_slice(&((gamma * 256.0) as u16).to_be_bytes());
+        tag
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert_eq!(ramp(&[0; 16]).err(), Some(IccError::Truncated));
+        let mut data = profile(&[]);
+        assert_eq!(ramp(&data).err(), Some(IccError::NoCurves));
+        data[16..20].copy_from_slice(b"CMYK");
+        assert_eq!(ramp(&data).err(), Some(IccError::NotRgb));
+        data[36..40].copy_from_slice(b"\0\0\0\0");
+        assert_eq!(ramp(&data).err(), Some(IccError::NotIcc));
+        // Tag table running past the profile size.
+        let mut data = profile(&[]);
+        data[HEADER_LEN + 3] = 50;
+        assert_eq!(ramp(&data).err(), Some(IccError::Truncated));
+    }
+
+    #[test]
+    fn tone_curves_are_corrected_to_the_target() {
+        // A display already at 2.2 needs no correction.
+        let ideal = profile(&[
+            (b"rTRC", gamma_curve(2.2)),
+            (b"gTRC", gamma_curve(2.2)),
+            (b"bTRC", gamma_curve(2.2)),
+        ]);
+        let identity = Ramp::identity();
+        let ramp = ramp(&ideal).unwrap();
+        for (got, want) in ramp.channels[1].iter().zip(identity.channels[1].iter()) {
+            assert!(got.abs_diff(*want) <= 1, "{got} {want}");
+        }
+
+        // Linear red (gamma 1.0, as a para tag): the ramp applies 2.2;
+        // a 1.8 blue curve is darkened less.
+        let mut para = b"para\0\0\0\0\0\0\0\0".to_vec();
+        para.extend_from_slice(&65_536u32.to_be_bytes());
+        let data = profile(&[
+            (b"rTRC", para),
+            (b"gTRC", gamma_curve(2.2)),
+            (b"bTRC", gamma_curve(1.8)),
+        ]);
+        let ramp = super::ramp(&data).unwrap();
+        let half = Ramp::gamma([2.2; 3]).channels[0][32];
+        assert!(ramp.channels[0][32].abs_diff(half) <= 1);
+        assert!(ramp.channels[2][32] < identity.channels[2][32]);
+        assert!(ramp.channels[2][32] > half);
+    }
+
+    #[test]
+    fn calibration_tables_win() {
+        // vcgt table, 2 entries of 16 bits per channel: inverted red.
+        let mut vcgt = b"vcgt\0\0\0\0".to_vec();
+        vcgt.extend_from_slice(&0u32.to_be_bytes());
+        for field in [3u16, 2, 2, 0xffff, 0, 0, 0xffff, 0, 0x8000] {
+            vcgt.extend_from_slice(&field.to_be_bytes());
+        }
+        let data = profile(&[(b"rTRC", gamma_curve(1.0)), (b"vcgt", vcgt)]);
+        let ramp = ramp(&data).unwrap();
+        assert_eq!(ramp.channels[0][0], 255);
+        assert_eq!(ramp.channels[0][63], 0);
+        assert_eq!(ramp.channels[1], Ramp::identity().channels[1]);
+        assert_eq!(ramp.channels[2][63], 128);
+    }
+}
@@ -1,5 +1,33 @@
 #![no_std]
 
+pub mod a11y;
+pub mod caps;
+pub mod color;
+pub mod config;
+pub mod display;
+pub mod edid;
+pub mod events;
+pub mod fonts;
+pub mod freezer;
+pub mod gamemode;
+pub mod icc;
+mod math;
+pub mod metered;
+pub mod metrics;
+pub mod nightlight;
+pub mod osk;
+pub mod outputs;
+pub mod preload;
+pub mod pressure;
+pub mod prewarm;
+pub mod resume;
+pub mod scale;
+pub mod scanout;
+pub mod schedule;
+pub mod sounds;
+pub mod splash;
+pub mod subscribers;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ServicePortKind {
     BuildTool,
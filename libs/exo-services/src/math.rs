@@ -0,0 +1,144 @@
+//! Floating-point helpers missing from `core`: accurate to a few ulps over
+//! the ranges the colour and solar computations use, not general-purpose.
+
+pub const PI: f64 = core::f64::consts::PI;
+const LN_2: f64 = core::f64::consts::LN_2;
+
+/// Largest integer not above `x`, for `|x| < 2^53`.
+pub fn floor(x: f64) -> f64 {
+    let t = x as i64 as f64;
+    if t > x {
+        t - 1.0
+    } else {
+        t
+    }
+}
+
+/// `x mod m` in `[0, m)`.
+pub fn rem_euclid(x: f64, m: f64) -> f64 {
+    x - m * floor(x / m)
+}
+
+pub fn ln(x: f64) -> f64 {
+    if x <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    // x = m · 2^e, m in [1, 2).
+    let bits = x.to_bits();
+    let e = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let m = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    // ln m = 2 atanh(s), s in [0, 1/3].
+    let s = (m - 1.0) / (m + 1.0);
+    let s2 = s * s;
+    let mut term = s;
+    let mut sum = 0.0;
+    let mut k = 1.0;
+    while k < 40.0 {
+        sum += term / k;
+        term *= s2;
+        k += 2.0;
+    }
+    e as f64 * LN_2 + 2.0 * sum
+}
+
+pub fn exp(x: f64) -> f64 {
+    if x > 709.0 {
+        return f64::INFINITY;
+    }
+    if x < -708.0 {
+        return 0.0;
+    }
+    // x = k ln 2 + r, |r| <= ln 2 / 2.
+    let k = floor(x / LN_2 + 0.5);
+    let r = x - k * LN_2;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    let mut n = 1.0;
+    while n < 20.0 {
+        term *= r / n;
+        sum += term;
+        n += 1.0;
+    }
+    sum * f64::from_bits(((k as i64 + 1023) as u64) << 52)
+}
+
+/// `x^y` for `x >= 0`.
+pub fn powf(x: f64, y: f64) -> f64 {
+    if x <= 0.0 {
+        return if y == 0.0 { 1.0 } else { 0.0 };
+    }
+    exp(y * ln(x))
+}
+
+pub fn sqrt(x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    powf(x, 0.5)
+}
+
+pub fn sin(x: f64) -> f64 {
+    // Reduce to [-π, π], then to [-π/2, π/2].
+    let mut x = rem_euclid(x + PI, 2.0 * PI) - PI;
+    if x > PI / 2.0 {
+        x = PI - x;
+    } else if x < -PI / 2.0 {
+        x = -PI - x;
+    }
+    let x2 = x * x;
+    let mut term = x;
+    let mut sum = x;
+    let mut n = 1.0;
+    while n < 20.0 {
+        term *= -x2 / ((n + 1.0) * (n + 2.0));
+        sum += term;
+        n += 2.0;
+    }
+    sum
+}
+
+pub fn cos(x: f64) -> f64 {
+    sin(x + PI / 2.0)
+}
+
+/// `acos(c)` by bisection on the decreasing `cos` over `[0, π]`.
+pub fn acos(c: f64) -> f64 {
+    let c = c.clamp(-1.0, 1.0);
+    let (mut lo, mut hi) = (0.0, PI);
+    for _ in 0..60 {
+        let mid = (lo + hi) / 2.0;
+        if cos(mid) > c {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+pub fn to_radians(deg: f64) -> f64 {
+    deg * PI / 180.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn matches_known_values() {
+        assert!(close(ln(core::f64::consts::E), 1.0));
+        assert!(close(ln(1e-3), -6.907755278982137));
+        assert!(close(exp(-2.5), 0.0820849986238988));
+        assert!(close(powf(0.5, 2.2), 0.217637640824031));
+        assert!(close(sqrt(2.0), core::f64::consts::SQRT_2));
+        assert!(close(sin(to_radians(30.0)), 0.5));
+        assert!(close(cos(to_radians(-420.0)), 0.5));
+        assert!(close(acos(0.5), PI / 3.0));
+        assert_eq!((floor(-1.5), floor(2.0)), (-2.0, 2.0));
+        assert!(close(rem_euclid(-30.0, 360.0), 330.0));
+    }
+}
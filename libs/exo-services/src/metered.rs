@@ -0,0 +1,390 @@
+//! Metered connections and data-saver mode.
+//!
+//! The `data_saver` daemon decides whether the current connection is
+//! metered and whether data-saver mode is on, and broadcasts every change:
+//!
+//! - `network_server` reports the DHCP hint (`METERED_MSG_HINT`): a lease
+//!   whose vendor-specific option carries `ANDROID_METERED`, as phone
+//!   hotspots send;
+//! - the configuration file and `METERED_MSG_SET` (the settings page)
+//!   override the hint either way;
+//! - data-saver mode follows the metered state unless forced on or off.
+//!
+//! Subscribers get [`METERED_NOTIFY_STATE`] on each change and init's cron
+//! holds back jobs marked `unmetered`. Updaters, backups and package
+//! downloads ask [`State::should_defer`] before a large transfer.
+//!
+//! Configuration (`/etc/exo/metered.conf`):
+//!
+//! ```text
+//! # metered auto|yes|no       auto: follow the DHCP hint
+//! # data_saver auto|on|off    auto: on while metered
+//! # large_transfer <bytes>    K, M and G suffixes allowed
+//! metered auto
+//! data_saver auto
+//! large_transfer 16M
+//! ```
+
+use crate::config;
+
+pub use crate::subscribers::{decode_subscribe, encode_subscribe};
+
+pub const CONFIG_PATH: &str = "/etc/exo/metered.conf";
+
+/// Transfers at least this large wait for an unmetered connection while
+/// data-saver mode is on.
+pub const DEFAULT_LARGE_TRANSFER_BYTES: u64 = 16 << 20;
+
+/// Marker carried by the DHCP vendor-specific option (43).
+pub const DHCP_METERED_HINT: &[u8] = b"ANDROID_METERED";
+
+pub const METERED_MSG_HEARTBEAT: u32 = 0;
+/// Payload: see [`encode_subscribe`], flags [`SUBSCRIBE_STATE`].
+/// Reply: state flags, large transfer threshold.
+pub const METERED_MSG_SUBSCRIBE: u32 = 1;
+pub const METERED_MSG_UNSUBSCRIBE: u32 = 2;
+/// Reply: state flags, large transfer threshold.
+pub const METERED_MSG_STATE: u32 = 3;
+/// Payload: see [`encode_set`].
+pub const METERED_MSG_SET: u32 = 4;
+/// Payload: hinted (u32 LE, 0 or 1). Sent by `network_server`; no reply.
+pub const METERED_MSG_HINT: u32 = 5;
+/// Reply: state changes so far, subscribers.
+pub const METERED_MSG_STATS: u32 = 6;
+
+/// Notification sent to subscribers; payload: a [`State`].
+pub const METERED_NOTIFY_STATE: u32 = 0x4d54_0001;
+
+/// Subscription flag: receive state changes.
+pub const SUBSCRIBE_STATE: u32 = 1 << 0;
+
+pub const STATE_METERED: u32 = 1 << 0;
+pub const STATE_DATA_SAVER: u32 = 1 << 1;
+/// The network reported the connection as metered.
+pub const STATE_HINTED: u32 = 1 << 2;
+
+/// `METERED_MSG_SET` value leaving a setting unchanged.
+pub const SETTING_KEEP: u8 = 0xff;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Setting {
+    #[default]
+    Auto = 0,
+    On = 1,
+    Off = 2,
+}
+
+impl Setting {
+    pub fn from_u8(raw: u8) -> Option<Self> {
+        match raw {
+            0 => Some(Self::Auto),
+            1 => Some(Self::On),
+            2 => Some(Self::Off),
+            _ => None,
+        }
+    }
+
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "auto" => Some(Self::Auto),
+            "yes" | "on" => Some(Self::On),
+            "no" | "off" => Some(Self::Off),
+            _ => None,
+        }
+    }
+
+    fn resolve(self, auto: bool) -> bool {
+        match self {
+            Self::Auto => auto,
+            Self::On => true,
+            Self::Off => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    Syntax,
+    BadValue,
+    UnknownKey,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Directive {
+    Metered(Setting),
+    DataSaver(Setting),
+    LargeTransfer(u64),
+}
+
+fn parse_size(word: &str) -> Option<u64> {
+    let (digits, shift) = match word.as_bytes().last()? {
+        b'K' => (&word[..word.len() - 1], 10),
+        b'M' => (&word[..word.len() - 1], 20),
+        b'G' => (&word[..word.len() - 1], 30),
+        _ => (word, 0),
+    };
+    digits.parse::<u64>().ok()?.checked_mul(1 << shift)
+}
+
+impl<'a> config::Directive<'a> for Directive {
+    type Error = ConfigError;
+
+    fn parse(line: &'a str) -> Result<Self, ConfigError> {
+        let (key, value) = config::key_value(line).ok_or(ConfigError::Syntax)?;
+        let setting = || Setting::parse(value).ok_or(ConfigError::BadValue);
+        match key {
+            "metered" => Ok(Directive::Metered(setting()?)),
+            "data_saver" => Ok(Directive::DataSaver(setting()?)),
+            "large_transfer" => parse_size(value)
+                .map(Directive::LargeTransfer)
+                .ok_or(ConfigError::BadValue),
+            _ => Err(ConfigError::UnknownKey),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Config {
+    pub metered: Setting,
+    pub data_saver: Setting,
+    pub large_transfer_bytes: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    pub const fn new() -> Self {
+        Self {
+            metered: Setting::Auto,
+            data_saver: Setting::Auto,
+            large_transfer_bytes: DEFAULT_LARGE_TRANSFER_BYTES,
+        }
+    }
+
+    /// Builds a configuration from a file; invalid lines are skipped (see
+    /// [`config::first_error`]).
+    pub fn parse(config: &str) -> Self {
+        let mut out = Self::new();
+        for directive in config::directives::<Directive>(config) {
+            match directive {
+                Directive::Metered(setting) => out.metered = setting,
+                Directive::DataSaver(setting) => out.data_saver = setting,
+                Directive::LargeTransfer(bytes) => out.large_transfer_bytes = bytes,
+            }
+        }
+        out
+    }
+}
+
+/// Current state, as broadcast by the daemon.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct State {
+    pub flags: u32,
+    pub large_transfer_bytes: u64,
+}
+
+impl State {
+    pub const LEN: usize = 12;
+
+    pub fn metered(&self) -> bool {
+        self.flags & STATE_METERED != 0
+    }
+
+    pub fn data_saver(&self) -> bool {
+        self.flags & STATE_DATA_SAVER != 0
+    }
+
+    /// Whether a transfer of `bytes` should wait for an unmetered network.
+    pub fn should_defer(&self, bytes: u64) -> bool {
+        self.data_saver() && bytes >= self.large_transfer_bytes
+    }
+
+    pub fn encode(&self, out: &mut [u8]) -> Option<usize> {
+        let out = out.get_mut(..Self::LEN)?;
+        out[..4].copy_from_slice(&self.flags.to_le_bytes());
+        out[4..].copy_from_slice(&self.large_transfer_bytes.to_le_bytes());
+        Some(Self::LEN)
+    }
+
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        let payload = payload.get(..Self::LEN)?;
+        Some(Self {
+            flags: u32::from_le_bytes(payload[..4].try_into().unwrap()),
+            large_transfer_bytes: u64::from_le_bytes(payload[4..].try_into().unwrap()),
+        })
+    }
+}
+
+/// `METERED_MSG_SET` payload: metered and data-saver settings, each a
+/// [`Setting`] or `None` to keep the current one.
+pub fn encode_set(
+    metered: Option<Setting>,
+    data_saver: Option<Setting>,
+    out: &mut [u8],
+) -> Option<usize> {
+    let out = out.get_mut(..2)?;
+    out[0] = metered.map_or(SETTING_KEEP, |s| s as u8);
+    out[1] = data_saver.map_or(SETTING_KEEP, |s| s as u8);
+    Some(2)
+}
+
+/// `None` if a byte is neither a setting nor [`SETTING_KEEP`].
+#[allow(clippy::type_complexity)]
+pub fn decode_set(payload: &[u8]) -> Option<(Option<Setting>, Option<Setting>)> {
+    let field = |raw: u8| match raw {
+        SETTING_KEEP => Some(None),
+        raw => Setting::from_u8(raw).map(Some),
+    };
+    let payload = payload.get(..2)?;
+    Some((field(payload[0])?, field(payload[1])?))
+}
+
+/// Whether a DHCP vendor-specific option marks the network as metered.
+pub fn dhcp_hint(vendor_option: &[u8]) -> bool {
+    vendor_option
+        .windows(DHCP_METERED_HINT.len())
+        .any(|w| w == DHCP_METERED_HINT)
+}
+
+#[derive(Debug, Default)]
+pub struct Policy {
+    config: Config,
+    hinted: bool,
+    /// State changes so far.
+    pub changes: u64,
+}
+
+impl Policy {
+    pub const fn new(config: Config) -> Self {
+        Self {
+            config,
+            hinted: false,
+            changes: 0,
+        }
+    }
+
+    pub fn state(&self) -> State {
+        let metered = self.config.metered.resolve(self.hinted);
+        let data_saver = self.config.data_saver.resolve(metered);
+        let mut flags = 0;
+        if metered {
+            flags |= STATE_METERED;
+        }
+        if data_saver {
+            flags |= STATE_DATA_SAVER;
+        }
+        if self.hinted {
+            flags |= STATE_HINTED;
+        }
+        State {
+            flags,
+            large_transfer_bytes: self.config.large_transfer_bytes,
+        }
+    }
+
+    /// Applies `update`; returns whether the broadcast state changed.
+    fn update(&mut self, update: impl FnOnce(&mut Self)) -> bool {
+        let before = self.state();
+        update(self);
+        let changed = self.state() != before;
+        if changed {
+            self.changes += 1;
+        }
+        changed
+    }
+
+    pub fn set_config(&mut self, config: Config) -> bool {
+        self.update(|p| p.config = config)
+    }
+
+    pub fn set_hint(&mut self, hinted: bool) -> bool {
+        self.update(|p| p.hinted = hinted)
+    }
+
+    /// Manual override; `None` keeps a setting.
+    pub fn set(&mut self, metered: Option<Setting>, data_saver: Option<Setting>) -> bool {
+        self.update(|p| {
+            if let Some(setting) = metered {
+                p.config.metered = setting;
+            }
+            if let Some(setting) = data_saver {
+                p.config.data_saver = setting;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{first_error, parse_line};
+
+    fn parse_directive(line: &str) -> Result<Option<Directive>, ConfigError> {
+        parse_line(line)
+    }
+
+    #[test]
+    fn parses_configuration() {
+        let text = "# home\nmetered no\ndata_saver auto\nlarge_transfer 4M\n";
+        assert_eq!(first_error::<Directive>(text), None);
+        let config = Config::parse(text);
+        assert_eq!(config.metered, Setting::Off);
+        assert_eq!(config.data_saver, Setting::Auto);
+        assert_eq!(config.large_transfer_bytes, 4 << 20);
+
+        assert_eq!(parse_directive("metered maybe"), Err(ConfigError::BadValue));
+        assert_eq!(
+            parse_directive("large_transfer 12X"),
+            Err(ConfigError::BadValue)
+        );
+        assert_eq!(parse_directive("metered"), Err(ConfigError::Syntax));
+        assert_eq!(
+            first_error::<Directive>("\nroaming on\n"),
+            Some((2, ConfigError::UnknownKey))
+        );
+    }
+
+    #[test]
+    fn hint_drives_auto_settings() {
+        let mut policy = Policy::new(Config::new());
+        assert_eq!(policy.state().flags, 0);
+        assert!(policy.set_hint(true));
+        assert!(!policy.set_hint(true));
+        let state = policy.state();
+        assert!(state.metered() && state.data_saver());
+        assert!(state.should_defer(DEFAULT_LARGE_TRANSFER_BYTES));
+        assert!(!state.should_defer(DEFAULT_LARGE_TRANSFER_BYTES - 1));
+
+        // Forced unmetered: the hint is still reported, nothing is deferred.
+        assert!(policy.set(Some(Setting::Off), None));
+        assert_eq!(policy.state().flags, STATE_HINTED);
+        // Data saver forced on, even unmetered.
+        assert!(policy.set(None, Some(Setting::On)));
+        assert_eq!(policy.state().flags, STATE_HINTED | STATE_DATA_SAVER);
+        assert_eq!(policy.changes, 3);
+    }
+
+    #[test]
+    fn wire_formats_roundtrip() {
+        let mut buf = [0u8; 16];
+        let state = State {
+            flags: STATE_METERED | STATE_DATA_SAVER,
+            large_transfer_bytes: 1 << 30,
+        };
+        assert_eq!(state.encode(&mut buf), Some(State::LEN));
+        assert_eq!(State::decode(&buf), Some(state));
+        assert_eq!(State::decode(&buf[..4]), None);
+
+        let len = encode_set(Some(Setting::On), None, &mut buf).unwrap();
+        assert_eq!(decode_set(&buf[..len]), Some((Some(Setting::On), None)));
+        assert_eq!(decode_set(&[7, SETTING_KEEP]), None);
+
+        assert!(dhcp_hint(b"\x01\x0fANDROID_METERED"));
+        assert!(!dhcp_hint(b"ANDROID"));
+    }
+}
@@ -0,0 +1,861 @@
+//! Local usage metrics and opt-in anonymous reporting.
+//!
+//! The `metrics_daemon` samples counters the system already exposes and
+//! keeps one row per day in [`STORE_PATH`], for [`KEEP_DAYS`] days:
+//!
+//! - boots, unclean boots, boot time, service crashes and restarts and OOM
+//!   kills, counted from the event journal ([`crate::events`]);
+//! - syscalls and IPC messages (`SYS_EXO_PERF_READ`), network bytes
+//!   (`SYS_EXO_NET_USAGE`) and installed memory (`SYS_SYSINFO`).
+//!
+//! Nothing leaves the machine unless the user said yes, at first-run setup
+//! or later with `exo-metrics consent on`. Then, at most once every
+//! [`REPORT_DAYS`] days, the daemon posts a [`Report`] to the collector:
+//! totals over the last complete days, coarsened so they do not single out
+//! a machine, with no boot id, service name, date or address in it.
+//! `exo-metrics report` prints exactly what would be sent.
+//!
+//! Store format (dates in UTC, zero values left out):
+//!
+//! ```text
+//! uploaded 2026-10-09
+//! baseline 4f1c09a2d35e7b60 syscalls=8812034 ipc_messages=120933
+//! 2026-10-15 boots=1 boot_ms=5120 syscalls=9120334 net_rx_bytes=1048576
+//! 2026-10-16 boots=2 unclean_boots=1 crashes=1 restarts=1
+//! ```
+//!
+//! `baseline` holds the raw kernel counters at the last sample and the boot
+//! they belong to, so a restarted daemon does not count them twice.
+//!
+//! Configuration (`/etc/exo/telemetry.conf`):
+//!
+//! ```text
+//! # upload ask|on|off            ask: not answered yet, nothing is sent
+//! # collector <a.b.c.d>:<port>   where reports go
+//! upload off
+//! ```
+
+use crate::events::{Kind, Record};
+use crate::schedule::{civil_from_unix, days_from_civil, SECS_PER_DAY};
+
+pub const STORE_PATH: &str = "/var/lib/exo/metrics";
+pub const CONFIG_PATH: &str = "/etc/exo/telemetry.conf";
+
+/// Daily rows kept in the store.
+pub const KEEP_DAYS: usize = 30;
+/// Days covered by a report, and the least time between two uploads.
+pub const REPORT_DAYS: u64 = 7;
+pub const SAMPLE_INTERVAL_MS: u64 = 10 * 60 * 1000;
+
+/// Largest store file.
+pub const STORE_MAX: usize = 16 << 10;
+/// Largest report body.
+pub const REPORT_MAX: usize = 512;
+/// Largest upload request, headers included.
+pub const REQUEST_MAX: usize = REPORT_MAX + 256;
+/// Path the report is posted to.
+pub const UPLOAD_PATH: &str = "/v1/exo-metrics";
+
+pub const METRICS_MSG_HEARTBEAT: u32 = 0;
+/// Reply: samples taken this run, days stored, day of the last upload;
+/// flags: consent (see [`Consent::from_u8`]).
+pub const METRICS_MSG_STATUS: u32 = 1;
+/// Samples now and saves the store. Reply: as [`METRICS_MSG_STATUS`].
+pub const METRICS_MSG_SAMPLE: u32 = 2;
+
+pub const METRICS: usize = 12;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Metric {
+    Boots = 0,
+    /// Boots after which the journal did not see a shutdown.
+    UncleanBoots = 1,
+    /// Uptime when the journal started, last boot of the day.
+    BootMs = 2,
+    Crashes = 3,
+    Restarts = 4,
+    OomKills = 5,
+    Syscalls = 6,
+    IpcMessages = 7,
+    IpcDropped = 8,
+    NetRxBytes = 9,
+    NetTxBytes = 10,
+    MemTotalMib = 11,
+}
+
+impl Metric {
+    pub const ALL: [Metric; METRICS] = [
+        Metric::Boots,
+        Metric::UncleanBoots,
+        Metric::BootMs,
+        Metric::Crashes,
+        Metric::Restarts,
+        Metric::OomKills,
+        Metric::Syscalls,
+        Metric::IpcMessages,
+        Metric::IpcDropped,
+        Metric::NetRxBytes,
+        Metric::NetTxBytes,
+        Metric::MemTotalMib,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Boots => "boots",
+            Metric::UncleanBoots => "unclean_boots",
+            Metric::BootMs => "boot_ms",
+            Metric::Crashes => "crashes",
+            Metric::Restarts => "restarts",
+            Metric::OomKills => "oom_kills",
+            Metric::Syscalls => "syscalls",
+            Metric::IpcMessages => "ipc_messages",
+            Metric::IpcDropped => "ipc_dropped",
+            Metric::NetRxBytes => "net_rx_bytes",
+            Metric::NetTxBytes => "net_tx_bytes",
+            Metric::MemTotalMib => "mem_total_mib",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|metric| metric.name() == name)
+    }
+
+    /// Counted from the event journal rather than sampled.
+    fn journaled(self) -> bool {
+        (self as u8) <= Metric::OomKills as u8
+    }
+
+    /// Reported value from the daily values of the report days.
+    fn aggregate(self, days: &[Day]) -> u64 {
+        let values = days.iter().map(|day| day.get(self));
+        match self {
+            Metric::BootMs => {
+                let (sum, n) = values
+                    .filter(|&v| v != 0)
+                    .fold((0u64, 0u64), |(s, n), v| (s.saturating_add(v), n + 1));
+                let mean = sum.checked_div(n).unwrap_or(0);
+                (mean + 50) / 100 * 100
+            }
+            Metric::MemTotalMib => match values.max().unwrap_or(0) {
+                0 => 0,
+                mib => mib.checked_next_power_of_two().unwrap_or(mib),
+            },
+            _ => coarse(values.fold(0u64, u64::saturating_add)),
+        }
+    }
+}
+
+/// Exact up to 8, then the power of two below.
+fn coarse(value: u64) -> u64 {
+    if value <= 8 {
+        value
+    } else {
+        1 << (63 - value.leading_zeros())
+    }
+}
+
+struct Writer<'a> {
+    out: &'a mut [u8],
+    len: usize,
+}
+
+impl Writer<'_> {
+    fn byte(&mut self, b: u8) -> Option<()> {
+        *self.out.get_mut(self.len)? = b;
+        self.len += 1;
+        Some(())
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> Option<()> {
+        bytes.iter().try_for_each(|&b| self.byte(b))
+    }
+
+    fn decimal(&mut self, value: u64, width: usize) -> Option<()> {
+        let mut digits = [b'0'; 20];
+        let mut n = digits.len();
+        let mut v = value;
+        loop {
+            n -= 1;
+            digits[n] = b'0' + (v % 10) as u8;
+            v /= 10;
+            if v == 0 {
+                break;
+            }
+        }
+        self.bytes(&digits[(digits.len() - width).min(n)..])
+    }
+
+    /// `YYYY-MM-DD` of a day number.
+    fn date(&mut self, day: u64) -> Option<()> {
+        let t = civil_from_unix(day * SECS_PER_DAY);
+        self.decimal(t.year.max(0) as u64, 4)?;
+        self.byte(b'-')?;
+        self.decimal(t.month as u64, 2)?;
+        self.byte(b'-')?;
+        self.decimal(t.day as u64, 2)
+    }
+
+    /// ` name=value` for each non-zero value.
+    fn values(&mut self, values: &[u64; METRICS]) -> Option<()> {
+        for metric in Metric::ALL {
+            let value = values[metric as usize];
+            if value != 0 {
+                self.byte(b' ')?;
+                self.bytes(metric.name().as_bytes())?;
+                self.byte(b'=')?;
+                self.decimal(value, 1)?;
+            }
+        }
+        Some(())
+    }
+}
+
+/// Day number (days since 1970-01-01) of a `YYYY-MM-DD` date.
+fn parse_date(word: &str) -> Option<u64> {
+    let mut parts = word.splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts
+        .next()?
+        .parse()
+        .ok()
+        .filter(|m| (1..=12).contains(m))?;
+    let day = parts
+        .next()?
+        .parse()
+        .ok()
+        .filter(|d| (1..=31).contains(d))?;
+    u64::try_from(days_from_civil(year, month, day)).ok()
+}
+
+/// `name=value` fields into `values`; `None` on an unknown name.
+fn parse_values<'a>(
+    fields: impl Iterator<Item = &'a str>,
+    values: &mut [u64; METRICS],
+) -> Option<()> {
+    for field in fields {
+        let (name, value) = field.split_once('=')?;
+        values[Metric::from_name(name)? as usize] = value.parse().ok()?;
+    }
+    Some(())
+}
+
+/// Day number of UNIX seconds.
+pub fn day_of(unix_secs: u64) -> u64 {
+    unix_secs / SECS_PER_DAY
+}
+
+/// One day of metrics.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Day {
+    /// Days since 1970-01-01.
+    pub day: u64,
+    values: [u64; METRICS],
+}
+
+impl Day {
+    pub const fn new(day: u64) -> Self {
+        Self {
+            day,
+            values: [0; METRICS],
+        }
+    }
+
+    pub fn get(&self, metric: Metric) -> u64 {
+        self.values[metric as usize]
+    }
+
+    pub fn set(&mut self, metric: Metric, value: u64) {
+        self.values[metric as usize] = value;
+    }
+
+    pub fn add(&mut self, metric: Metric, value: u64) {
+        let slot = &mut self.values[metric as usize];
+        *slot = slot.saturating_add(value);
+    }
+
+    /// Parses a store row; `None` for anything else.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_ascii_whitespace();
+        let mut day = Self::new(parse_date(fields.next()?)?);
+        parse_values(fields, &mut day.values)?;
+        Some(day)
+    }
+
+    /// Store row, newline included.
+    pub fn format(&self, out: &mut [u8]) -> Option<usize> {
+        let mut w = Writer { out, len: 0 };
+        w.date(self.day)?;
+        w.values(&self.values)?;
+        w.byte(b'\n')?;
+        Some(w.len)
+    }
+}
+
+/// Raw kernel counters at the last sample.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Baseline {
+    /// Boot the counters belong to (from the event journal).
+    pub boot_id: u64,
+    raw: [u64; METRICS],
+}
+
+impl Baseline {
+    /// Starts over when `boot_id` is a new boot: the kernel counters
+    /// restarted from zero.
+    pub fn rebase(&mut self, boot_id: u64) {
+        if self.boot_id != boot_id {
+            *self = Self {
+                boot_id,
+                raw: [0; METRICS],
+            };
+        }
+    }
+
+    /// Growth of a counter since the last sample. The network total may
+    /// shrink when a process exits; that counts as no growth.
+    fn advance(&mut self, metric: Metric, raw: u64) -> u64 {
+        let slot = &mut self.raw[metric as usize];
+        let delta = raw.saturating_sub(*slot);
+        *slot = raw;
+        delta
+    }
+}
+
+/// The metrics store.
+#[derive(Clone, Debug)]
+pub struct Store {
+    /// Oldest first.
+    days: [Day; KEEP_DAYS],
+    len: usize,
+    /// Day of the last upload, 0 if none.
+    pub uploaded: u64,
+    pub baseline: Baseline,
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Store {
+    pub const fn new() -> Self {
+        Self {
+            days: [Day::new(0); KEEP_DAYS],
+            len: 0,
+            uploaded: 0,
+            baseline: Baseline {
+                boot_id: 0,
+                raw: [0; METRICS],
+            },
+        }
+    }
+
+    /// Reads a store file; damaged lines are skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut store = Self::new();
+        for line in text.lines() {
+            let mut fields = line.split_ascii_whitespace();
+            match fields.next() {
+                Some("uploaded") => {
+                    if let Some(day) = fields.next().and_then(parse_date) {
+                        store.uploaded = day;
+                    }
+                }
+                Some("baseline") => {
+                    let Some(boot_id) = fields.next().and_then(|w| u64::from_str_radix(w, 16).ok())
+                    else {
+                        continue;
+                    };
+                    let mut raw = [0; METRICS];
+                    if parse_values(fields, &mut raw).is_some() {
+                        store.baseline = Baseline { boot_id, raw };
+                    }
+                }
+                _ => {
+                    if let Some(row) = Day::parse(line) {
+                        if let Some(day) = store.day_mut(row.day) {
+                            *day = row;
+                        }
+                    }
+                }
+            }
+        }
+        store
+    }
+
+    pub fn format(&self, out: &mut [u8]) -> Option<usize> {
+        let mut w = Writer { out, len: 0 };
+        if self.uploaded != 0 {
+            w.bytes(b"uploaded ")?;
+            w.date(self.uploaded)?;
+            w.byte(b'\n')?;
+        }
+        if self.baseline.boot_id != 0 {
+            w.bytes(b"baseline ")?;
+            for shift in (0..16).rev() {
+                w.byte(b"0123456789abcdef"[(self.baseline.boot_id >> (shift * 4)) as usize & 0xf])?;
+            }
+            w.values(&self.baseline.raw)?;
+            w.byte(b'\n')?;
+        }
+        let mut len = w.len;
+        for day in self.days() {
+            len += day.format(&mut out[len..])?;
+        }
+        Some(len)
+    }
+
+    /// Stored days, oldest first.
+    pub fn days(&self) -> &[Day] {
+        &self.days[..self.len]
+    }
+
+    /// Row of `day`, added if missing. When the store is full the oldest
+    /// row goes; `None` if `day` is older than every row of a full store.
+    pub fn day_mut(&mut self, day: u64) -> Option<&mut Day> {
+        let at = match self.days().binary_search_by_key(&day, |d| d.day) {
+            Ok(at) => return Some(&mut self.days[at]),
+            Err(at) => at,
+        };
+        let at = if self.len < KEEP_DAYS {
+            self.days.copy_within(at..self.len, at + 1);
+            self.len += 1;
+            at
+        } else if at == 0 {
+            return None;
+        } else {
+            self.days.copy_within(1..at, 0);
+            at - 1
+        };
+        self.days[at] = Day::new(day);
+        Some(&mut self.days[at])
+    }
+
+    /// Adds the growth of a kernel counter since the last sample to `today`.
+    pub fn sample(&mut self, today: u64, metric: Metric, raw: u64) {
+        let delta = self.baseline.advance(metric, raw);
+        if let Some(day) = self.day_mut(today) {
+            day.add(metric, delta);
+        }
+    }
+
+    /// Clears the journal counts of yesterday and today before they are
+    /// counted again with [`Store::count_event`]: events logged after the
+    /// last sample of yesterday are not lost at midnight.
+    pub fn begin_recount(&mut self, today: u64) {
+        for day in &mut self.days[..self.len] {
+            if day.day + 1 >= today {
+                for metric in Metric::ALL.into_iter().filter(|m| m.journaled()) {
+                    day.set(metric, 0);
+                }
+            }
+        }
+    }
+
+    /// Counts a journal record dated yesterday or today.
+    pub fn count_event(&mut self, today: u64, record: &Record<'_>) {
+        let day = day_of(record.realtime);
+        // Records written before the clock was set carry no date.
+        if record.realtime == 0 || day + 1 < today || day > today {
+            return;
+        }
+        let Some(row) = self.day_mut(day) else {
+            return;
+        };
+        match record.kind {
+            Kind::Boot => {
+                row.add(Metric::Boots, 1);
+                row.add(Metric::UncleanBoots, (record.detail != 0) as u64);
+                row.set(Metric::BootMs, record.uptime_ms);
+            }
+            Kind::Crash => row.add(Metric::Crashes, 1),
+            Kind::Restart => row.add(Metric::Restarts, 1),
+            Kind::OomKill => row.add(Metric::OomKills, 1),
+            Kind::Shutdown | Kind::Resume | Kind::Deploy => {}
+        }
+    }
+
+    /// Report over the [`REPORT_DAYS`] complete days before `today`.
+    pub fn report(&self, today: u64) -> Report {
+        let days: &[Day] = {
+            let all = self.days();
+            let start = all.partition_point(|d| d.day + REPORT_DAYS < today);
+            let end = all.partition_point(|d| d.day < today);
+            &all[start..end]
+        };
+        let mut values = [0; METRICS];
+        for metric in Metric::ALL {
+            values[metric as usize] = metric.aggregate(days);
+        }
+        Report {
+            days: days.len() as u64,
+            values,
+        }
+    }
+
+    /// A report may go out today: the last one is [`REPORT_DAYS`] old and
+    /// there is at least one complete day to send.
+    pub fn upload_due(&self, today: u64) -> bool {
+        self.uploaded + REPORT_DAYS <= today && self.report(today).days != 0
+    }
+}
+
+/// What an upload carries: every metric, whether zero or not, so the
+/// presence of a field tells nothing either.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Report {
+    /// Days with data in the period.
+    pub days: u64,
+    values: [u64; METRICS],
+}
+
+impl Report {
+    pub fn get(&self, metric: Metric) -> u64 {
+        self.values[metric as usize]
+    }
+
+    /// Report body:
+    ///
+    /// ```text
+    /// exo-metrics 1
+    /// days 7
+    /// boots 8
+    /// ...
+    /// ```
+    pub fn format(&self, out: &mut [u8]) -> Option<usize> {
+        let mut w = Writer { out, len: 0 };
+        w.bytes(b"exo-metrics 1\ndays ")?;
+        w.decimal(self.days, 1)?;
+        w.byte(b'\n')?;
+        for metric in Metric::ALL {
+            w.bytes(metric.name().as_bytes())?;
+            w.byte(b' ')?;
+            w.decimal(self.get(metric), 1)?;
+            w.byte(b'\n')?;
+        }
+        Some(w.len)
+    }
+
+    /// HTTP/1.0 request posting the report to `collector`.
+    pub fn format_request(&self, collector: Collector, out: &mut [u8]) -> Option<usize> {
+        let mut body = [0u8; REPORT_MAX];
+        let body_len = self.format(&mut body)?;
+        let mut w = Writer { out, len: 0 };
+        w.bytes(b"POST ")?;
+        w.bytes(UPLOAD_PATH.as_bytes())?;
+        w.bytes(b" HTTP/1.0\r\nHost: ")?;
+        collector.write(&mut w)?;
+        w.bytes(b"\r\nContent-Type: text/plain\r\nContent-Length: ")?;
+        w.decimal(body_len as u64, 1)?;
+        w.bytes(b"\r\n\r\n")?;
+        w.bytes(&body[..body_len])?;
+        Some(w.len)
+    }
+}
+
+/// Whether the user agreed to uploads.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Consent {
+    /// Not asked yet: nothing is sent.
+    #[default]
+    Ask = 0,
+    Off = 1,
+    On = 2,
+}
+
+impl Consent {
+    pub fn from_u8(raw: u8) -> Option<Self> {
+        match raw {
+            0 => Some(Self::Ask),
+            1 => Some(Self::Off),
+            2 => Some(Self::On),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ask => "ask",
+            Self::Off => "off",
+            Self::On => "on",
+        }
+    }
+
+    pub fn from_name(word: &str) -> Option<Self> {
+        match word {
+            "ask" => Some(Self::Ask),
+            "on" | "yes" => Some(Self::On),
+            "off" | "no" => Some(Self::Off),
+            _ => None,
+        }
+    }
+}
+
+/// Report collector, an IPv4 address and TCP port.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Collector {
+    pub addr: [u8; 4],
+    pub port: u16,
+}
+
+impl Collector {
+    /// `a.b.c.d:port`.
+    pub fn parse(word: &str) -> Option<Self> {
+        let (addr, port) = word.rsplit_once(':')?;
+        let mut octets = addr.split('.');
+        let mut out = [0u8; 4];
+        for octet in &mut out {
+            *octet = octets.next()?.parse().ok()?;
+        }
+        let port = port.parse().ok().filter(|&p| p != 0)?;
+        octets.next().is_none().then_some(Self { addr: out, port })
+    }
+
+    fn write(&self, w: &mut Writer<'_>) -> Option<()> {
+        for (i, octet) in self.addr.iter().enumerate() {
+            if i != 0 {
+                w.byte(b'.')?;
+            }
+            w.decimal(*octet as u64, 1)?;
+        }
+        w.byte(b':')?;
+        w.decimal(self.port as u64, 1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    Syntax,
+    BadValue,
+    UnknownKey,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Config {
+    pub consent: Consent,
+    pub collector: Option<Collector>,
+}
+
+impl Config {
+    /// One configuration line applied to `self`; blank lines and comments
+    /// change nothing.
+    pub fn apply(&mut self, line: &str) -> Result<(), ConfigError> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        let mut fields = line.split_ascii_whitespace();
+        let (Some(key), Some(value), None) = (fields.next(), fields.next(), fields.next()) else {
+            return Err(ConfigError::Syntax);
+        };
+        match key {
+            "upload" => self.consent = Consent::from_name(value).ok_or(ConfigError::BadValue)?,
+            "collector" => {
+                self.collector = Some(Collector::parse(value).ok_or(ConfigError::BadValue)?)
+            }
+            _ => return Err(ConfigError::UnknownKey),
+        }
+        Ok(())
+    }
+
+    /// Builds a configuration from a file; invalid lines are skipped.
+    pub fn parse(config: &str) -> Self {
+        let mut out = Self::default();
+        for line in config.lines() {
+            let _ = out.apply(line);
+        }
+        out
+    }
+
+    /// Where reports go, `None` unless the user agreed and a collector is
+    /// set.
+    pub fn upload_to(&self) -> Option<Collector> {
+        self.collector.filter(|_| self.consent == Consent::On)
+    }
+
+    pub fn format(&self, out: &mut [u8]) -> Option<usize> {
+        let mut w = Writer { out, len: 0 };
+        w.bytes(b"upload ")?;
+        w.bytes(self.consent.name().as_bytes())?;
+        w.byte(b'\n')?;
+        if let Some(collector) = self.collector {
+            w.bytes(b"collector ")?;
+            collector.write(&mut w)?;
+            w.byte(b'\n')?;
+        }
+        Some(w.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 20_377; // 2025-10-16
+
+    fn text(out: &[u8], n: usize) -> &str {
+        core::str::from_utf8(&out[..n]).unwrap()
+    }
+
+    fn record(day: u64, kind: Kind, detail: u64) -> Record<'static> {
+        Record {
+            boot_id: 0xbb,
+            realtime: day * SECS_PER_DAY + 3600,
+            uptime_ms: 4_870,
+            kind,
+            subject: "",
+            detail,
+        }
+    }
+
+    #[test]
+    fn store_roundtrips_and_keeps_the_last_days() {
+        let mut store = Store::new();
+        store.baseline.rebase(0x4f1c);
+        store.sample(DAY, Metric::Syscalls, 9_000);
+        store.sample(DAY, Metric::Syscalls, 9_500);
+        store
+            .day_mut(DAY - 1)
+            .unwrap()
+            .set(Metric::MemTotalMib, 7_900);
+        store.uploaded = DAY - 3;
+
+        let mut out = [0u8; STORE_MAX];
+        let n = store.format(&mut out).unwrap();
+        assert_eq!(
+            text(&out, n),
+            "uploaded 2025-10-13\n\
+             baseline 0000000000004f1c syscalls=9500\n\
+             2025-10-15 mem_total_mib=7900\n\
+             2025-10-16 syscalls=9500\n"
+        );
+        let parsed = Store::parse(text(&out, n));
+        assert_eq!(parsed.days(), store.days());
+        assert_eq!(parsed.uploaded, DAY - 3);
+        assert_eq!(parsed.baseline, store.baseline);
+
+        // Same boot: the daemon restarted, growth only.
+        let mut restarted = parsed.clone();
+        restarted.baseline.rebase(0x4f1c);
+        restarted.sample(DAY, Metric::Syscalls, 9_700);
+        assert_eq!(restarted.days()[1].get(Metric::Syscalls), 9_700);
+        // New boot: the kernel counters started over.
+        restarted.baseline.rebase(0x77);
+        restarted.sample(DAY, Metric::Syscalls, 300);
+        assert_eq!(restarted.days()[1].get(Metric::Syscalls), 10_000);
+
+        for day in 0..KEEP_DAYS as u64 + 5 {
+            store.day_mut(DAY + day).unwrap();
+        }
+        assert_eq!(store.days().len(), KEEP_DAYS);
+        assert_eq!(store.days()[0].day, DAY + 5);
+        assert!(store.day_mut(DAY).is_none());
+        assert_eq!(Day::parse("2025-10-16 crashes=x"), None);
+        assert_eq!(Day::parse("2025-13-01 crashes=1"), None);
+        assert_eq!(Day::parse("2025-10-16 uptime=1"), None);
+    }
+
+    #[test]
+    fn journal_counts_cover_yesterday_and_today() {
+        let mut store = Store::new();
+        let events = [
+            record(DAY - 2, Kind::Crash, 139),
+            record(DAY - 1, Kind::Boot, 1),
+            record(DAY - 1, Kind::Crash, 139),
+            record(DAY, Kind::Restart, 57),
+            record(DAY, Kind::OomKill, 12),
+            Record {
+                realtime: 0,
+                ..record(DAY, Kind::Crash, 6)
+            },
+        ];
+        for _ in 0..2 {
+            store.begin_recount(DAY);
+            for event in &events {
+                store.count_event(DAY, event);
+            }
+        }
+        let [yesterday, today] = store.days() else {
+            panic!("{:?}", store.days());
+        };
+        assert_eq!(yesterday.get(Metric::Boots), 1);
+        assert_eq!(yesterday.get(Metric::UncleanBoots), 1);
+        assert_eq!(yesterday.get(Metric::BootMs), 4_870);
+        assert_eq!(yesterday.get(Metric::Crashes), 1);
+        assert_eq!(today.get(Metric::Restarts), 1);
+        assert_eq!(today.get(Metric::OomKills), 1);
+        assert_eq!(today.get(Metric::Crashes), 0);
+    }
+
+    #[test]
+    fn reports_are_coarse_and_cover_complete_days() {
+        let mut store = Store::new();
+        for (i, boot_ms) in [(8, 9_000), (7, 5_120), (6, 0), (1, 4_870), (0, 1)] {
+            let day = store.day_mut(DAY - i).unwrap();
+            day.set(Metric::Boots, 3);
+            day.set(Metric::BootMs, boot_ms);
+            day.set(Metric::NetRxBytes, 1_000_000);
+            day.set(Metric::MemTotalMib, 7_900);
+        }
+        let report = store.report(DAY);
+        assert_eq!(report.days, 3);
+        assert_eq!(report.get(Metric::Boots), 8);
+        assert_eq!(report.get(Metric::BootMs), 5_000);
+        assert_eq!(report.get(Metric::NetRxBytes), 2_097_152);
+        assert_eq!(report.get(Metric::MemTotalMib), 8_192);
+        assert_eq!(coarse(7), 7);
+        assert_eq!(coarse(9), 8);
+
+        let mut out = [0u8; REQUEST_MAX];
+        let n = report.format(&mut out).unwrap();
+        let body = text(&out, n);
+        assert!(body.starts_with("exo-metrics 1\ndays 3\nboots 8\nunclean_boots 0\n"));
+        assert_eq!(body.lines().count(), 2 + METRICS);
+
+        let collector = Collector::parse("192.0.2.10:8080").unwrap();
+        let mut request = [0u8; REQUEST_MAX];
+        let n = report.format_request(collector, &mut request).unwrap();
+        let request = text(&request, n);
+        assert!(request.starts_with("POST /v1/exo-metrics HTTP/1.0\r\nHost: 192.0.2.10:8080\r\n"));
+        let (head, sent) = request.split_once("\r\n\r\n").unwrap();
+        assert_eq!(sent, body);
+        let length = head.rsplit_once("Content-Length: ").unwrap().1;
+        assert_eq!(length.parse::<usize>(), Ok(body.len()));
+
+        assert!(store.upload_due(DAY));
+        store.uploaded = DAY - 3;
+        assert!(!store.upload_due(DAY));
+        assert!(store.upload_due(DAY + 4));
+        assert!(!Store::new().upload_due(DAY));
+    }
+
+    #[test]
+    fn nothing_is_sent_without_consent_and_a_collector() {
+        let config = Config::parse("collector 192.0.2.10:443\n");
+        assert_eq!(config.consent, Consent::Ask);
+        assert_eq!(config.upload_to(), None);
+        let config = Config::parse("upload on\n");
+        assert_eq!(config.upload_to(), None);
+
+        let mut config =
+            Config::parse("# first run\nupload yes\ncollector 192.0.2.10:443\nbogus 1\n");
+        assert_eq!(config.upload_to(), Collector::parse("192.0.2.10:443"));
+        assert_eq!(config.apply("upload maybe"), Err(ConfigError::BadValue));
+        assert_eq!(config.apply("upload"), Err(ConfigError::Syntax));
+        assert_eq!(config.apply("endpoint x"), Err(ConfigError::UnknownKey));
+        config.apply("upload off").unwrap();
+        let mut out = [0u8; 64];
+        let n = config.format(&mut out).unwrap();
+        assert_eq!(text(&out, n), "upload off\ncollector 192.0.2.10:443\n");
+        assert_eq!(Config::parse(text(&out, n)), config);
+
+        assert_eq!(Collector::parse("192.0.2.10"), None);
+        assert_eq!(Collector::parse("192.0.2:80"), None);
+        assert_eq!(Collector::parse("192.0.2.10.1:80"), None);
+        assert_eq!(Collector::parse("192.0.2.10:0"), None);
+        assert_eq!(Consent::from_u8(Consent::On as u8), Some(Consent::On));
+    }
+}
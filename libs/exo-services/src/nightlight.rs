@@ -0,0 +1,408 @@
+//! Night light: warmer screen colours in the evening.
+//!
+//! The session daemon `night_light` computes, once a minute, how far into
+//! the night the configured schedule is and turns that into a colour
+//! temperature, faded in and out over the transition time. It then sets the
+//! matching colour matrix ([`crate::color::temperature_gains`]) in
+//! `fb_server` (`FB_MSG_SET_CTM`). The schedule is either fixed local times
+//! or the sunset and sunrise at the configured location, computed here with
+//! the usual sunrise equation (within a minute or two away from the poles).
+//! Inside the polar circles, polar night keeps the night light on all day
+//! and polar day keeps it off.
+//!
+//! The settings' display page writes the configuration and sends
+//! `NIGHTLIGHT_MSG_RELOAD`.
+//!
+//! Configuration (`/etc/exo/nightlight.conf`):
+//!
+//! ```text
+//! # mode off|manual|sunset
+//! # from <HH:MM>             manual mode: start, local time
+//! # to <HH:MM>               manual mode: end, local time
+//! # utc_offset <+HH:MM>      local time zone of the manual times
+//! # latitude <degrees>       sunset mode: north positive
+//! # longitude <degrees>      sunset mode: east positive
+//! # temperature <kelvin>     night temperature, 1000 to 6500
+//! # transition <minutes>     fade at each end
+//! mode sunset
+//! latitude 48.85
+//! longitude 2.35
+//! temperature 3400
+//! ```
+
+use crate::color::{TEMPERATURE_MIN, TEMPERATURE_NEUTRAL};
+use crate::config;
+use crate::math;
+
+pub const CONFIG_PATH: &str = "/etc/exo/nightlight.conf";
+
+pub const DEFAULT_TEMPERATURE: u32 = 3_400;
+pub const DEFAULT_TRANSITION_MIN: u32 = 30;
+
+pub const NIGHTLIGHT_MSG_HEARTBEAT: u32 = 0;
+/// Reply: `value0` = current temperature (kelvin), `value1` = strength in
+/// thousandths, `flags` = mode.
+pub const NIGHTLIGHT_MSG_STATUS: u32 = 1;
+/// Rereads the configuration and applies it at once.
+pub const NIGHTLIGHT_MSG_RELOAD: u32 = 2;
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    Off,
+    Manual,
+    Sunset,
+}
+
+impl Mode {
+    pub const fn as_u32(self) -> u32 {
+        match self {
+            Self::Off => 0,
+            Self::Manual => 1,
+            Self::Sunset => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    Syntax,
+    BadValue,
+    UnknownKey,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Directive {
+    Mode(Mode),
+    From(u32),
+    To(u32),
+    UtcOffset(i32),
+    Latitude(f64),
+    Longitude(f64),
+    Temperature(u32),
+    Transition(u32),
+}
+
+/// `HH:MM` in minutes since midnight.
+fn parse_clock(value: &str) -> Option<u32> {
+    let (h, m) = value.split_once(':')?;
+    let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+    (h < 24 && m < 60 && !value.starts_with('+')).then_some(h * 60 + m)
+}
+
+impl<'a> config::Directive<'a> for Directive {
+    type Error = ConfigError;
+
+    fn parse(line: &'a str) -> Result<Self, ConfigError> {
+        let (key, value) = config::key_value(line).ok_or(ConfigError::Syntax)?;
+        let clock = || parse_clock(value).ok_or(ConfigError::BadValue);
+        let degrees = |limit: f64| match value.parse::<f64>() {
+            Ok(deg) if (-limit..=limit).contains(&deg) => Ok(deg),
+            _ => Err(ConfigError::BadValue),
+        };
+        match key {
+            "mode" => match value {
+                "off" => Ok(Directive::Mode(Mode::Off)),
+                "manual" => Ok(Directive::Mode(Mode::Manual)),
+                "sunset" => Ok(Directive::Mode(Mode::Sunset)),
+                _ => Err(ConfigError::BadValue),
+            },
+            "from" => Ok(Directive::From(clock()?)),
+            "to" => Ok(Directive::To(clock()?)),
+            "utc_offset" => {
+                let (sign, rest) = match value.as_bytes().first() {
+                    Some(b'+') => (1, &value[1..]),
+                    Some(b'-') => (-1, &value[1..]),
+                    _ => return Err(ConfigError::BadValue),
+                };
+                match parse_clock(rest) {
+                    Some(min) if min <= 14 * 60 => Ok(Directive::UtcOffset(sign * min as i32)),
+                    _ => Err(ConfigError::BadValue),
+                }
+            }
+            "latitude" => Ok(Directive::Latitude(degrees(90.0)?)),
+            "longitude" => Ok(Directive::Longitude(degrees(180.0)?)),
+            "temperature" => match value.parse::<u32>() {
+                Ok(k) if (TEMPERATURE_MIN..=TEMPERATURE_NEUTRAL).contains(&k) => {
+                    Ok(Directive::Temperature(k))
+                }
+                _ => Err(ConfigError::BadValue),
+            },
+            "transition" => match value.parse::<u32>() {
+                Ok(min) if min <= 180 => Ok(Directive::Transition(min)),
+                _ => Err(ConfigError::BadValue),
+            },
+            _ => Err(ConfigError::UnknownKey),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    pub mode: Mode,
+    /// Manual schedule, minutes since local midnight.
+    pub from_min: u32,
+    pub to_min: u32,
+    pub utc_offset_min: i32,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub temperature: u32,
+    pub transition_min: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    pub const fn new() -> Self {
+        Self {
+            mode: Mode::Off,
+            from_min: 21 * 60,
+            to_min: 7 * 60,
+            utc_offset_min: 0,
+            latitude: 0.0,
+            longitude: 0.0,
+            temperature: DEFAULT_TEMPERATURE,
+            transition_min: DEFAULT_TRANSITION_MIN,
+        }
+    }
+
+    /// Builds a configuration from a file; invalid lines are skipped (see
+    /// [`config::first_error`]).
+    pub fn parse(config: &str) -> Self {
+        let mut out = Self::new();
+        for directive in config::directives::<Directive>(config) {
+            match directive {
+                Directive::Mode(mode) => out.mode = mode,
+                Directive::From(min) => out.from_min = min,
+                Directive::To(min) => out.to_min = min,
+                Directive::UtcOffset(min) => out.utc_offset_min = min,
+                Directive::Latitude(deg) => out.latitude = deg,
+                Directive::Longitude(deg) => out.longitude = deg,
+                Directive::Temperature(k) => out.temperature = k,
+                Directive::Transition(min) => out.transition_min = min,
+            }
+        }
+        out
+    }
+
+    /// Night light strength at `now` (Unix seconds), from 0 (off) to 1.
+    pub fn strength(&self, now: i64) -> f64 {
+        let minute = |t: i64| t.div_euclid(60).rem_euclid(MINUTES_PER_DAY);
+        match self.mode {
+            Mode::Off => 0.0,
+            Mode::Manual => {
+                let local = now + self.utc_offset_min as i64 * 60;
+                window_strength(
+                    minute(local),
+                    self.from_min as i64,
+                    self.to_min as i64,
+                    self.transition_min,
+                )
+            }
+            Mode::Sunset => match sun(now.div_euclid(86_400), self.latitude, self.longitude) {
+                Sun::PolarDay => 0.0,
+                Sun::PolarNight => 1.0,
+                Sun::Times { rise, set } => window_strength(
+                    minute(now),
+                    set.rem_euclid(MINUTES_PER_DAY),
+                    rise.rem_euclid(MINUTES_PER_DAY),
+                    self.transition_min,
+                ),
+            },
+        }
+    }
+
+    /// Colour temperature to show at `now`.
+    pub fn temperature(&self, now: i64) -> u32 {
+        temperature_at(self.temperature, self.strength(now))
+    }
+}
+
+/// Temperature `strength` of the way from neutral to `night`.
+pub fn temperature_at(night: u32, strength: f64) -> u32 {
+    let span = TEMPERATURE_NEUTRAL.saturating_sub(night) as f64;
+    TEMPERATURE_NEUTRAL - math::floor(span * strength.clamp(0.0, 1.0) + 0.5) as u32
+}
+
+/// Strength inside the daily window `[from, to)` (minutes, wrapping past
+/// midnight): fades in over `transition` minutes from `from`, out from `to`.
+pub fn window_strength(now: i64, from: i64, to: i64, transition: u32) -> f64 {
+    let since_from = (now - from).rem_euclid(MINUTES_PER_DAY);
+    let length = (to - from).rem_euclid(MINUTES_PER_DAY);
+    let ramp = |minutes: i64| {
+        if transition == 0 {
+            1.0
+        } else {
+            (minutes as f64 / transition as f64).min(1.0)
+        }
+    };
+    if since_from < length {
+        return ramp(since_from);
+    }
+    // Fading out, from the level reached at `to`.
+    let since_to = since_from - length;
+    (ramp(length) * (1.0 - ramp(since_to))).max(0.0)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sun {
+    /// Sunrise and sunset, in minutes from midnight UTC of the day (may
+    /// fall outside `[0, 1440)` far from Greenwich).
+    Times {
+        rise: i64,
+        set: i64,
+    },
+    PolarDay,
+    PolarNight,
+}
+
+/// Sunrise and sunset on `day` (days since the Unix epoch) at `latitude`,
+/// `longitude` (degrees, north and east positive).
+pub fn sun(day: i64, latitude: f64, longitude: f64) -> Sun {
+    const J2000: f64 = 2_451_545.0;
+    const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+    let sin_deg = |deg: f64| math::sin(math::to_radians(deg));
+
+    // Days since J2000 at local solar noon.
+    let n = day as f64 + UNIX_EPOCH_JD + 0.5 - J2000 + 0.0008;
+    let mean = n - longitude / 360.0;
+    let anomaly = math::rem_euclid(357.5291 + 0.985_600_28 * mean, 360.0);
+    let center = 1.9148 * sin_deg(anomaly)
+        + 0.0200 * sin_deg(2.0 * anomaly)
+        + 0.0003 * sin_deg(3.0 * anomaly);
+    let ecliptic = math::rem_euclid(anomaly + center + 180.0 + 102.9372, 360.0);
+    let transit = J2000 + mean + 0.0053 * sin_deg(anomaly) - 0.0069 * sin_deg(2.0 * ecliptic);
+
+    let sin_decl = sin_deg(ecliptic) * sin_deg(23.4397);
+    let cos_decl = math::sqrt(1.0 - sin_decl * sin_decl);
+    let (sin_lat, cos_lat) = (sin_deg(latitude), sin_deg(latitude + 90.0));
+    // Refraction and the solar disc radius: -0.833°.
+    let cos_hour = (sin_deg(-0.833) - sin_lat * sin_decl) / (cos_lat * cos_decl);
+    if cos_hour > 1.0 {
+        return Sun::PolarNight;
+    }
+    if cos_hour < -1.0 {
+        return Sun::PolarDay;
+    }
+    let half_day = math::acos(cos_hour) / (2.0 * math::PI);
+    let minutes = |jd: f64| math::floor((jd - UNIX_EPOCH_JD - day as f64) * 1440.0 + 0.5) as i64;
+    Sun::Times {
+        rise: minutes(transit - half_day),
+        set: minutes(transit + half_day),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{first_error, parse_line};
+
+    fn parse_directive(line: &str) -> Result<Option<Directive>, ConfigError> {
+        parse_line(line)
+    }
+
+    /// 2024-06-21 and 2024-12-21, days since the Unix epoch.
+    const SOLSTICE_JUNE: i64 = 19_895;
+    const SOLSTICE_DECEMBER: i64 = 20_078;
+
+    fn near(minutes: i64, hh: i64, mm: i64) -> bool {
+        let delta = minutes - (hh * 60 + mm);
+        (-5..=5).contains(&delta)
+    }
+
+    #[test]
+    fn parses_schedules() {
+        let config = Config::parse(
+            "mode manual\nfrom 22:30\nto 06:15\nutc_offset -05:00\ntemperature 2700\n\
+             transition 0\nlatitude 91\n",
+        );
+        assert_eq!(config.mode, Mode::Manual);
+        assert_eq!(
+            (config.from_min, config.to_min),
+            (22 * 60 + 30, 6 * 60 + 15)
+        );
+        assert_eq!(config.utc_offset_min, -300);
+        assert_eq!((config.temperature, config.transition_min), (2700, 0));
+        assert_eq!(config.latitude, 0.0);
+        assert_eq!(
+            first_error::<Directive>("mode sunset\nlatitude 91"),
+            Some((2, ConfigError::BadValue))
+        );
+        for bad in ["from 24:00", "to 7", "utc_offset 02:00", "temperature 9000"] {
+            assert_eq!(parse_directive(bad), Err(ConfigError::BadValue), "{bad}");
+        }
+    }
+
+    #[test]
+    fn windows_fade_and_wrap_midnight() {
+        let (from, to) = (21 * 60, 7 * 60);
+        assert_eq!(window_strength(20 * 60, from, to, 30), 0.0);
+        assert_eq!(window_strength(21 * 60 + 15, from, to, 30), 0.5);
+        assert_eq!(window_strength(2 * 60, from, to, 30), 1.0);
+        assert_eq!(window_strength(7 * 60 + 6, from, to, 30), 0.8);
+        assert_eq!(window_strength(7 * 60 + 30, from, to, 30), 0.0);
+        assert_eq!(window_strength(12 * 60, from, to, 0), 0.0);
+
+        // Local time: 21:15 at UTC+2 is 19:15 UTC.
+        let config = Config {
+            mode: Mode::Manual,
+            utc_offset_min: 120,
+            ..Config::new()
+        };
+        let now = SOLSTICE_JUNE * 86_400 + (19 * 60 + 15) * 60;
+        assert_eq!(
+            config.temperature(now),
+            temperature_at(DEFAULT_TEMPERATURE, 0.5)
+        );
+        assert_eq!(
+            temperature_at(DEFAULT_TEMPERATURE, 0.0),
+            TEMPERATURE_NEUTRAL
+        );
+        assert_eq!(
+            temperature_at(DEFAULT_TEMPERATURE, 1.0),
+            DEFAULT_TEMPERATURE
+        );
+        assert_eq!(Config::new().temperature(now), TEMPERATURE_NEUTRAL);
+    }
+
+    #[test]
+    fn follows_the_sun() {
+        let (london_lat, london_lon) = (51.5074, -0.1278);
+        let Sun::Times { rise, set } = sun(SOLSTICE_JUNE, london_lat, london_lon) else {
+            panic!("no sunrise in London");
+        };
+        assert!(near(rise, 3, 43) && near(set, 20, 21), "{rise} {set}");
+        let Sun::Times { rise, set } = sun(SOLSTICE_DECEMBER, london_lat, london_lon) else {
+            panic!("no sunrise in London");
+        };
+        assert!(near(rise, 8, 4) && near(set, 15, 53), "{rise} {set}");
+
+        let (tromso_lat, tromso_lon) = (69.65, 18.96);
+        assert_eq!(sun(SOLSTICE_JUNE, tromso_lat, tromso_lon), Sun::PolarDay);
+        assert_eq!(
+            sun(SOLSTICE_DECEMBER, tromso_lat, tromso_lon),
+            Sun::PolarNight
+        );
+
+        let config = Config {
+            mode: Mode::Sunset,
+            latitude: london_lat,
+            longitude: london_lon,
+            ..Config::new()
+        };
+        let at = |day: i64, hh: i64| day * 86_400 + hh * 3600;
+        assert_eq!(config.strength(at(SOLSTICE_JUNE, 12)), 0.0);
+        assert_eq!(config.strength(at(SOLSTICE_JUNE, 23)), 1.0);
+        assert_eq!(config.strength(at(SOLSTICE_DECEMBER, 17)), 1.0);
+        let polar = Config {
+            latitude: tromso_lat,
+            ..config
+        };
+        assert_eq!(polar.strength(at(SOLSTICE_DECEMBER, 12)), 1.0);
+    }
+}
@@ -0,0 +1,399 @@
+//! On-screen keyboard.
+//!
+//! `osk` is the input method of touch devices: it registers with
+//! `input_server` (`INPUT_MSG_IM_REGISTER`), which activates it when a text
+//! input gets the focus, with a content purpose choosing the layer (digits
+//! for PIN fields, digits and symbols for phone numbers). The compositor
+//! forwards the taps landing on the panel (`OSK_MSG_TAP`) and may show or
+//! hide it; typing on a hardware keyboard hides it too.
+//!
+//! Characters go out as input method commits (`INPUT_IM_COMMIT`), editing
+//! keys (backspace, enter, arrows) as virtual keyboard presses. Shift is
+//! one-shot: it applies to the next letter only.
+//!
+//! The panel fills the bottom [`PANEL_PERCENT`] of the screen, the band
+//! `fb_server` lets `osk` draw in over the compositor. The compositor
+//! repaints that band when the panel is hidden; it learns about it from
+//! `OSK_MSG_STATUS` or, as a shortcut owner, from `INPUT_STATUS_OSK`.
+
+pub use crate::splash::Rect;
+
+pub const OSK_MSG_HEARTBEAT: u32 = 0;
+/// Payload: `x`, `y` (u32 LE, screen coordinates). Display owner only.
+/// Reply status `ENOENT` when the tap misses the shown panel: the
+/// compositor hands it to the client below instead.
+pub const OSK_MSG_TAP: u32 = 1;
+/// Display owner only.
+pub const OSK_MSG_SHOW: u32 = 2;
+pub const OSK_MSG_HIDE: u32 = 3;
+/// Reply `value0`: 1 while the panel is shown, `value1`: panel height in
+/// pixels.
+pub const OSK_MSG_STATUS: u32 = 4;
+
+/// Same value as `FB_OSK_BAND_PERCENT`.
+pub const PANEL_PERCENT: u32 = 40;
+
+/// Content purposes used to pick a layer, numbered as in zwp_text_input_v3
+/// (`INPUT_PURPOSE_*`).
+const PURPOSE_DIGITS: u16 = 2;
+const PURPOSE_NUMBER: u16 = 3;
+const PURPOSE_PHONE: u16 = 4;
+const PURPOSE_PIN: u16 = 9;
+
+/// HID keyboard usages of the editing keys.
+pub const HID_ENTER: u16 = 0x28;
+pub const HID_BACKSPACE: u16 = 0x2a;
+pub const HID_RIGHT: u16 = 0x4f;
+pub const HID_LEFT: u16 = 0x50;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Key {
+    Char(char),
+    Shift,
+    Backspace,
+    Enter,
+    Left,
+    Right,
+    /// Switches to the symbols layer.
+    Symbols,
+    /// Switches back to the letters layer.
+    Letters,
+    Hide,
+}
+
+impl Key {
+    /// Width in units of its row; rows are stretched to the panel width.
+    fn units(self) -> u32 {
+        match self {
+            Key::Char(' ') => 4,
+            _ => 1,
+        }
+    }
+}
+
+/// What a tap asks the input method to send.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Output {
+    Commit(char),
+    /// Virtual key press and release of a HID usage.
+    Key(u16),
+    Hide,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Layer {
+    Letters,
+    Symbols,
+    Digits,
+}
+
+/// Special keys before and after a run of character keys.
+struct Row {
+    before: &'static [Key],
+    chars: &'static str,
+    after: &'static [Key],
+}
+
+impl Row {
+    const fn chars(chars: &'static str) -> Self {
+        Self {
+            before: &[],
+            chars,
+            after: &[],
+        }
+    }
+
+    fn keys(&self) -> impl Iterator<Item = Key> + Clone {
+        self.before
+            .iter()
+            .copied()
+            .chain(self.chars.chars().map(Key::Char))
+            .chain(self.after.iter().copied())
+    }
+}
+
+const LETTERS: [Row; 4] = [
+    Row::chars("qwertyuiop"),
+    Row::chars("asdfghjkl"),
+    Row {
+        before: &[Key::Shift],
+        chars: "zxcvbnm",
+        after: &[Key::Backspace],
+    },
+    Row {
+        before: &[Key::Symbols, Key::Left],
+        chars: " ",
+        after: &[Key::Right, Key::Enter, Key::Hide],
+    },
+];
+
+const SYMBOLS: [Row; 4] = [
+    Row::chars("1234567890"),
+    Row::chars("@#$%&-+()/"),
+    Row {
+        before: &[],
+        chars: "*\"':;!?,.",
+        after: &[Key::Backspace],
+    },
+    Row {
+        before: &[Key::Letters, Key::Left],
+        chars: " ",
+        after: &[Key::Right, Key::Enter, Key::Hide],
+    },
+];
+
+const DIGITS: [Row; 4] = [
+    Row::chars("123"),
+    Row::chars("456"),
+    Row::chars("789"),
+    Row {
+        before: &[Key::Backspace],
+        chars: "0",
+        after: &[Key::Enter, Key::Hide],
+    },
+];
+
+impl Layer {
+    pub fn for_purpose(purpose: u16) -> Self {
+        match purpose {
+            PURPOSE_DIGITS | PURPOSE_PIN => Layer::Digits,
+            PURPOSE_NUMBER | PURPOSE_PHONE => Layer::Symbols,
+            _ => Layer::Letters,
+        }
+    }
+
+    fn rows(self) -> &'static [Row] {
+        match self {
+            Layer::Letters => &LETTERS,
+            Layer::Symbols => &SYMBOLS,
+            Layer::Digits => &DIGITS,
+        }
+    }
+}
+
+pub struct Keyboard {
+    panel: Rect,
+    layer: Layer,
+    shift: bool,
+    shown: bool,
+}
+
+impl Keyboard {
+    pub fn new(screen_w: u32, screen_h: u32) -> Self {
+        let h = screen_h * PANEL_PERCENT / 100;
+        Self {
+            panel: Rect {
+                x: 0,
+                y: screen_h - h,
+                w: screen_w,
+                h,
+            },
+            layer: Layer::Letters,
+            shift: false,
+            shown: false,
+        }
+    }
+
+    pub fn panel(&self) -> Rect {
+        self.panel
+    }
+
+    pub fn layer(&self) -> Layer {
+        self.layer
+    }
+
+    pub fn shifted(&self) -> bool {
+        self.shift
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.shown
+    }
+
+    /// A text input got the focus.
+    pub fn activate(&mut self, purpose: u16) {
+        self.layer = Layer::for_purpose(purpose);
+        self.shift = false;
+    }
+
+    /// Returns whether the panel was hidden.
+    pub fn show(&mut self) -> bool {
+        !core::mem::replace(&mut self.shown, true)
+    }
+
+    /// Returns whether the panel was shown.
+    pub fn hide(&mut self) -> bool {
+        core::mem::replace(&mut self.shown, false)
+    }
+
+    /// Keys of the current layer with their screen rectangles. They tile
+    /// the panel: every point of it belongs to exactly one key.
+    pub fn keys(&self) -> impl Iterator<Item = (Rect, Key)> + '_ {
+        let rows = self.layer.rows();
+        let count = rows.len() as u32;
+        let panel = self.panel;
+        rows.iter().enumerate().flat_map(move |(i, row)| {
+            let i = i as u32;
+            let top = panel.h * i / count;
+            let h = panel.h * (i + 1) / count - top;
+            let total: u32 = row.keys().map(Key::units).sum();
+            row.keys().scan(0u32, move |at, key| {
+                let left = panel.w * *at / total;
+                *at += key.units();
+                let rect = Rect {
+                    x: panel.x + left,
+                    y: panel.y + top,
+                    w: panel.w * *at / total - left,
+                    h,
+                };
+                Some((rect, key))
+            })
+        })
+    }
+
+    pub fn key_at(&self, x: u32, y: u32) -> Option<Key> {
+        if !self.shown {
+            return None;
+        }
+        self.keys()
+            .find(|(r, _)| x >= r.x && x - r.x < r.w && y >= r.y && y - r.y < r.h)
+            .map(|(_, key)| key)
+    }
+
+    /// Label drawn on `key`, ASCII for the console font.
+    pub fn label<'a>(&self, key: Key, buf: &'a mut [u8; 4]) -> &'a str {
+        match key {
+            Key::Char(' ') => "space",
+            Key::Char(c) => self.shifted_char(c).encode_utf8(buf),
+            Key::Shift => "Shift",
+            Key::Backspace => "Bksp",
+            Key::Enter => "Enter",
+            Key::Left => "<",
+            Key::Right => ">",
+            Key::Symbols => "?123",
+            Key::Letters => "ABC",
+            Key::Hide => "Hide",
+        }
+    }
+
+    /// Tap at screen coordinates; `None` when it misses the shown panel or
+    /// only changes the keyboard state (shift, layer).
+    pub fn tap(&mut self, x: u32, y: u32) -> Option<Output> {
+        let key = self.key_at(x, y)?;
+        match key {
+            Key::Char(c) => {
+                let c = self.shifted_char(c);
+                self.shift = false;
+                Some(Output::Commit(c))
+            }
+            Key::Shift => {
+                self.shift = !self.shift;
+                None
+            }
+            Key::Backspace => Some(Output::Key(HID_BACKSPACE)),
+            Key::Enter => Some(Output::Key(HID_ENTER)),
+            Key::Left => Some(Output::Key(HID_LEFT)),
+            Key::Right => Some(Output::Key(HID_RIGHT)),
+            Key::Symbols | Key::Letters => {
+                self.layer = if key == Key::Symbols {
+                    Layer::Symbols
+                } else {
+                    Layer::Letters
+                };
+                self.shift = false;
+                None
+            }
+            Key::Hide => {
+                self.shown = false;
+                Some(Output::Hide)
+            }
+        }
+    }
+
+    fn shifted_char(&self, c: char) -> char {
+        if self.shift {
+            c.to_ascii_uppercase()
+        } else {
+            c
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn center(kb: &Keyboard, key: Key) -> (u32, u32) {
+        let (r, _) = kb.keys().find(|&(_, k)| k == key).unwrap();
+        (r.x + r.w / 2, r.y + r.h / 2)
+    }
+
+    fn tap_key(kb: &mut Keyboard, key: Key) -> Option<Output> {
+        let (x, y) = center(kb, key);
+        kb.tap(x, y)
+    }
+
+    #[test]
+    fn panel_sits_in_the_bottom_band_and_keys_tile_it() {
+        let kb = Keyboard::new(800, 600);
+        assert_eq!(
+            kb.panel(),
+            Rect {
+                x: 0,
+                y: 360,
+                w: 800,
+                h: 240
+            }
+        );
+        let area: u32 = kb.keys().map(|(r, _)| r.w * r.h).sum();
+        assert_eq!(area, 800 * 240);
+        let space = kb.keys().find(|&(_, k)| k == Key::Char(' ')).unwrap().0;
+        let hide = kb.keys().find(|&(_, k)| k == Key::Hide).unwrap().0;
+        assert_eq!(
+            (space.w, hide.x + hide.w, hide.y + hide.h),
+            (4 * hide.w, 800, 600)
+        );
+    }
+
+    #[test]
+    fn taps_commit_characters_with_one_shot_shift() {
+        let mut kb = Keyboard::new(800, 600);
+        assert_eq!(tap_key(&mut kb, Key::Char('q')), None, "hidden panel");
+        assert!(kb.show() && !kb.show());
+        assert_eq!(kb.tap(400, 100), None, "above the panel");
+
+        assert_eq!(tap_key(&mut kb, Key::Shift), None);
+        let mut buf = [0; 4];
+        assert_eq!(kb.label(Key::Char('h'), &mut buf), "H");
+        assert_eq!(tap_key(&mut kb, Key::Char('h')), Some(Output::Commit('H')));
+        assert_eq!(tap_key(&mut kb, Key::Char('i')), Some(Output::Commit('i')));
+        assert_eq!(tap_key(&mut kb, Key::Char(' ')), Some(Output::Commit(' ')));
+        assert_eq!(
+            tap_key(&mut kb, Key::Backspace),
+            Some(Output::Key(HID_BACKSPACE))
+        );
+
+        assert_eq!(tap_key(&mut kb, Key::Symbols), None);
+        assert_eq!(kb.layer(), Layer::Symbols);
+        assert_eq!(tap_key(&mut kb, Key::Char('?')), Some(Output::Commit('?')));
+        assert_eq!(tap_key(&mut kb, Key::Letters), None);
+        assert_eq!(tap_key(&mut kb, Key::Hide), Some(Output::Hide));
+        assert!(!kb.is_shown() && !kb.hide());
+    }
+
+    #[test]
+    fn purpose_picks_the_layer() {
+        let mut kb = Keyboard::new(480, 800);
+        kb.activate(PURPOSE_PIN);
+        assert_eq!(kb.layer(), Layer::Digits);
+        assert_eq!(kb.keys().count(), 13);
+        kb.show();
+        assert_eq!(tap_key(&mut kb, Key::Char('7')), Some(Output::Commit('7')));
+        assert_eq!(tap_key(&mut kb, Key::Enter), Some(Output::Key(HID_ENTER)));
+
+        kb.activate(PURPOSE_PHONE);
+        assert_eq!(kb.layer(), Layer::Symbols);
+        kb.activate(0);
+        assert_eq!(kb.layer(), Layer::Letters);
+    }
+}
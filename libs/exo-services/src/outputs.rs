@@ -0,0 +1,185 @@
+//! Per-output display configuration, written by the settings' display page.
+//!
+//! Configuration (`/etc/exo/outputs.conf`):
+//!
+//! ```text
+//! # scale <output>|* auto|<factor>       factor from 1 to 4, e.g. 1.25
+//! # mode <output>|* preferred|WxH[@Hz]
+//! # modeline <output> <clock MHz> hdisp hsyncstart hsyncend htotal
+//! #          vdisp vsyncstart vsyncend vtotal [+-hsync] [+-vsync] [interlace]
+//! scale * auto
+//! scale eDP-1 1.5
+//! mode HDMI-A-1 1920x1080@60
+//! modeline DP-2 312.25 2560 2608 2640 2720 1440 1443 1448 1530 +hsync -vsync
+//! ```
+//!
+//! For every key, an output's own last line wins over the last `*` line.
+
+use crate::config;
+use crate::edid::{ModeSetting, Timing};
+use crate::scale::{self, Scale, Setting};
+
+pub const CONFIG_PATH: &str = "/etc/exo/outputs.conf";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    Syntax,
+    BadValue,
+    UnknownKey,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Directive<'a> {
+    /// Output name, `*` for every output without a line of its own.
+    Scale(&'a str, Setting),
+    /// `mode` and `modeline` lines, the latter as [`ModeSetting::Custom`].
+    Mode(&'a str, ModeSetting),
+}
+
+fn split_field(text: &str) -> Option<(&str, &str)> {
+    text.split_once(|c: char| c.is_ascii_whitespace())
+        .map(|(head, tail)| (head, tail.trim_start()))
+}
+
+impl<'a> config::Directive<'a> for Directive<'a> {
+    type Error = ConfigError;
+
+    fn parse(line: &'a str) -> Result<Self, ConfigError> {
+        let Some((key, rest)) = split_field(line) else {
+            return Err(ConfigError::Syntax);
+        };
+        let Some((output, value)) = split_field(rest) else {
+            return Err(ConfigError::Syntax);
+        };
+        let single = || {
+            (!value.is_empty() && !value.contains(|c: char| c.is_ascii_whitespace()))
+                .then_some(value)
+                .ok_or(ConfigError::Syntax)
+        };
+        match key {
+            "scale" => {
+                let setting = match single()? {
+                    "auto" => Setting::Auto,
+                    _ => value
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(Scale::from_factor)
+                        .map(Setting::Fixed)
+                        .ok_or(ConfigError::BadValue)?,
+                };
+                Ok(Directive::Scale(output, setting))
+            }
+            "mode" => {
+                let setting = ModeSetting::parse(single()?).ok_or(ConfigError::BadValue)?;
+                Ok(Directive::Mode(output, setting))
+            }
+            "modeline" => {
+                let timing = Timing::parse_modeline(value).ok_or(ConfigError::BadValue)?;
+                Ok(Directive::Mode(output, ModeSetting::Custom(timing)))
+            }
+            _ => Err(ConfigError::UnknownKey),
+        }
+    }
+}
+
+/// Value of `output` among the lines `pick` keeps: its own last line, else
+/// the last `*` line.
+fn lookup<T>(
+    config: &str,
+    output: &str,
+    pick: impl Fn(Directive<'_>) -> Option<(&str, T)>,
+) -> Option<T> {
+    let mut default = None;
+    let mut own = None;
+    for (name, value) in config::directives::<Directive>(config).filter_map(&pick) {
+        if name == output {
+            own = Some(value);
+        } else if name == "*" {
+            default = Some(value);
+        }
+    }
+    own.or(default)
+}
+
+/// Scale setting of `output`, auto without a line.
+pub fn scale_setting(config: &str, output: &str) -> Setting {
+    lookup(config, output, |directive| match directive {
+        Directive::Scale(name, setting) => Some((name, setting)),
+        Directive::Mode(..) => None,
+    })
+    .unwrap_or(Setting::Auto)
+}
+
+/// Scale of `output` per the configuration, detected when auto.
+pub fn scale(config: &str, output: &str, pixels: (u32, u32), physical_mm: (u32, u32)) -> Scale {
+    match scale_setting(config, output) {
+        Setting::Fixed(scale) => scale,
+        Setting::Auto => scale::detect(pixels, physical_mm),
+    }
+}
+
+/// Mode setting of `output`, the monitor's preferred mode without a line.
+pub fn mode_setting(config: &str, output: &str) -> ModeSetting {
+    lookup(config, output, |directive| match directive {
+        Directive::Mode(name, setting) => Some((name, setting)),
+        Directive::Scale(..) => None,
+    })
+    .unwrap_or(ModeSetting::Preferred)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{first_error, parse_line};
+
+    fn parse_directive(line: &str) -> Result<Option<Directive<'_>>, ConfigError> {
+        parse_line(line)
+    }
+
+    #[test]
+    fn configuration_per_output() {
+        let config = "scale * 1.25\nscale eDP-1 auto\nscale HDMI-A-1 2\n\
+                      mode * preferred\nmode HDMI-A-1 1920x1080@60\n\
+                      modeline DP-2 148.5 1920 2008 2052 2200 1080 1084 1089 1125\n";
+        assert_eq!(
+            scale_setting(config, "HDMI-A-1"),
+            Setting::Fixed(Scale(240))
+        );
+        assert_eq!(scale_setting(config, "DP-2"), Setting::Fixed(Scale(150)));
+        assert_eq!(scale(config, "eDP-1", (1920, 1080), (309, 174)), Scale(180));
+        assert_eq!(scale_setting("", "eDP-1"), Setting::Auto);
+
+        assert_eq!(
+            mode_setting(config, "HDMI-A-1"),
+            ModeSetting::Size {
+                width: 1920,
+                height: 1080,
+                refresh_mhz: Some(60_000)
+            }
+        );
+        assert_eq!(mode_setting(config, "eDP-1"), ModeSetting::Preferred);
+        let ModeSetting::Custom(timing) = mode_setting(config, "DP-2") else {
+            panic!("modeline ignored");
+        };
+        assert_eq!((timing.clock_khz, timing.refresh_mhz()), (148_500, 60_000));
+
+        assert_eq!(
+            first_error::<Directive>("scale * auto\nscale eDP-1 5\nscale DP-1"),
+            Some((2, ConfigError::BadValue))
+        );
+        assert_eq!(parse_directive("scale DP-1"), Err(ConfigError::Syntax));
+        assert_eq!(parse_directive("scale DP-1 1 2"), Err(ConfigError::Syntax));
+        assert_eq!(
+            parse_directive("mode DP-1 wide"),
+            Err(ConfigError::BadValue)
+        );
+        assert_eq!(
+            parse_directive("modeline DP-1 148.5 1920"),
+            Err(ConfigError::BadValue)
+        );
+        assert_eq!(
+            parse_directive("rotate DP-1 90"),
+            Err(ConfigError::UnknownKey)
+        );
+    }
+}
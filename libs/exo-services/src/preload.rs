@@ -0,0 +1,195 @@
+//! Boot preload list kept by the init preload service.
+//!
+//! Each boot, the kernel records the files opened during the first seconds
+//! (see `exo_preload(TRACE)`). The service folds that trace into a list
+//! stored on disk and, at the next boot, prefetches the list into the page
+//! cache while the service graph starts.
+//!
+//! One entry per line, `<score> <path>`. A file gains a point for every
+//! traced boot it shows up in (capped at [`MAX_SCORE`]) and loses one for
+//! every boot it does not, so a file touched once by accident drops out
+//! after a single boot while a file used every boot survives a few misses.
+//! Entries from the newest trace come first, in first-access order, which is
+//! the order the next boot will want them in.
+
+/// Points kept by a file seen on every boot.
+pub const MAX_SCORE: u8 = 4;
+/// Entries kept in the list.
+pub const MAX_ENTRIES: usize = 1024;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Entry<'a> {
+    pub score: u8,
+    pub path: &'a str,
+}
+
+/// `<score> <path>`; blank lines and `#` comments yield `None`, as do
+/// relative paths and scores outside `1..=MAX_SCORE`.
+pub fn parse_line(line: &str) -> Option<Entry<'_>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (score, path) = line.split_once(' ')?;
+    let score: u8 = score.parse().ok()?;
+    let path = path.trim();
+    if !(1..=MAX_SCORE).contains(&score) || !path.starts_with('/') {
+        return None;
+    }
+    Some(Entry { score, path })
+}
+
+/// Valid entries of a list, in file order.
+pub fn entries(list: &str) -> impl Iterator<Item = Entry<'_>> {
+    list.lines().filter_map(parse_line)
+}
+
+fn traced_paths(trace: &str) -> impl Iterator<Item = &str> {
+    trace.lines().filter(|p| p.starts_with('/'))
+}
+
+fn score_in(list: &str, path: &str) -> u8 {
+    entries(list)
+        .find(|e| e.path == path)
+        .map_or(0, |e| e.score)
+}
+
+/// Folds the newest `trace` (one path per line) into the `old` list and
+/// writes the new list to `out`. Entries that do not fit are dropped.
+/// Returns the number of bytes written.
+pub fn merge(old: &str, trace: &str, out: &mut [u8]) -> usize {
+    let mut w = Writer { out, len: 0 };
+    let mut kept = 0;
+
+    for (i, path) in traced_paths(trace).enumerate() {
+        if kept == MAX_ENTRIES {
+            break;
+        }
+        // The kernel traces a file once per boot; skip repeats anyway so a
+        // hand-edited trace cannot inflate scores.
+        if traced_paths(trace).take(i).any(|p| p == path) {
+            continue;
+        }
+        let score = (score_in(old, path) + 1).min(MAX_SCORE);
+        if w.line(score, path) {
+            kept += 1;
+        }
+    }
+    for entry in entries(old) {
+        if kept == MAX_ENTRIES {
+            break;
+        }
+        if entry.score <= 1 || traced_paths(trace).any(|p| p == entry.path) {
+            continue;
+        }
+        if w.line(entry.score - 1, entry.path) {
+            kept += 1;
+        }
+    }
+    w.len
+}
+
+struct Writer<'a> {
+    out: &'a mut [u8],
+    len: usize,
+}
+
+impl Writer<'_> {
+    /// Writes one whole line or nothing.
+    fn line(&mut self, score: u8, path: &str) -> bool {
+        let need = 2 + path.len() + 1;
+        let Some(dst) = self.out.get_mut(self.len..self.len + need) else {
+            return false;
+        };
+        dst[0] = b'0' + score;
+        dst[1] = b' ';
+        dst[2..need - 1].copy_from_slice(path.as_bytes());
+        dst[need - 1] = b'\n';
+        self.len += need;
+        true
+    }
+}
+
+/// Share of cold opens during the traced window that the preload served
+/// from cache, in per mille.
+pub fn hit_rate_permille(hits: u64, misses: u64) -> u64 {
+    let total = hits.saturating_add(misses);
+    if total == 0 {
+        return 0;
+    }
+    hits.saturating_mul(1000) / total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged<'a>(old: &str, trace: &str, buf: &'a mut [u8]) -> &'a str {
+        let n = merge(old, trace, buf);
+        core::str::from_utf8(&buf[..n]).unwrap()
+    }
+
+    #[test]
+    fn parses_entries() {
+        assert_eq!(
+            parse_line(" 3 /bin/exosh "),
+            Some(Entry {
+                score: 3,
+                path: "/bin/exosh"
+            })
+        );
+        for bad in [
+            "",
+            "# comment",
+            "0 /bin/a",
+            "9 /bin/a",
+            "2 bin/a",
+            "x /a",
+            "2",
+        ] {
+            assert_eq!(parse_line(bad), None, "{bad}");
+        }
+        let list = "# exo-preload\n2 /a\nbogus\n1 /b\n";
+        assert_eq!(entries(list).count(), 2);
+    }
+
+    #[test]
+    fn first_boot_takes_the_trace_in_order() {
+        let mut buf = [0u8; 256];
+        assert_eq!(
+            merged(
+                "",
+                "/bin/init\n/lib/libc.so\n/bin/init\nrelative\n",
+                &mut buf
+            ),
+            "1 /bin/init\n1 /lib/libc.so\n"
+        );
+    }
+
+    #[test]
+    fn scores_rise_and_decay() {
+        let mut buf = [0u8; 256];
+        let old = "4 /bin/exosh\n1 /tmp/once\n3 /usr/share/font\n";
+        let list = merged(old, "/etc/motd\n/bin/exosh\n", &mut buf);
+        // Newest trace first; /tmp/once falls out, the font keeps 2 points.
+        assert_eq!(list, "1 /etc/motd\n4 /bin/exosh\n2 /usr/share/font\n");
+
+        let mut next = [0u8; 256];
+        let list = merged(list, "/etc/motd\n", &mut next);
+        assert_eq!(list, "2 /etc/motd\n3 /bin/exosh\n1 /usr/share/font\n");
+    }
+
+    #[test]
+    fn output_is_bounded() {
+        let mut small = [0u8; 20];
+        let list = merged("", "/aaaaaaaa\n/bbbbbbbbbbbbbbbb\n/c\n", &mut small);
+        // The long path does not fit; the short one after it still does.
+        assert_eq!(list, "1 /aaaaaaaa\n1 /c\n");
+    }
+
+    #[test]
+    fn hit_rate() {
+        assert_eq!(hit_rate_permille(0, 0), 0);
+        assert_eq!(hit_rate_permille(3, 1), 750);
+    }
+}
@@ -0,0 +1,370 @@
+//! Memory pressure policy for the desktop session.
+//!
+//! The kernel keeps PSI-style stall accounting and turns it, together with
+//! the share of free RAM, into a pressure [`Level`]; `exo_psi(OPEN)` hands
+//! out a descriptor that polls readable whenever the level changes, and
+//! reading it yields a [`PressureReport`].
+//!
+//! The `mem_pressure` daemon watches that descriptor and drives the
+//! escalation implemented by [`Policy`]:
+//!
+//! 1. from [`Level::Medium`] up, subscribed applications are asked to trim
+//!    their caches ([`MEMPRESSURE_NOTIFY_TRIM`]), again every
+//!    [`TRIM_INTERVAL_MS`] while the pressure lasts and at once when it
+//!    gets worse;
+//! 2. if the level stays [`Level::Critical`] for [`KILL_GRACE_MS`] after a
+//!    critical trim request and free RAM is still below
+//!    [`KILL_FREE_PERMILLE`], the daemon asks the kernel OOM killer for a
+//!    victim (`exo_psi(OOM_KILL)`), at most once per [`KILL_COOLDOWN_MS`].
+//!
+//! Watchers (the panel indicator) get [`MEMPRESSURE_NOTIFY_LEVEL`] on every
+//! level change and can query the current level with
+//! [`MEMPRESSURE_MSG_LEVEL`].
+
+pub use crate::subscribers::{
+    decode_subscribe, encode_subscribe, SubscribeError, Subscriber, Subscribers, MAX_SUBSCRIBERS,
+};
+
+/// Trim requests are repeated this often while the pressure lasts.
+pub const TRIM_INTERVAL_MS: u64 = 5_000;
+/// Time applications get to trim before the OOM killer is involved.
+pub const KILL_GRACE_MS: u64 = 3_000;
+/// Minimum time between two kills: the victim needs time to exit.
+pub const KILL_COOLDOWN_MS: u64 = 10_000;
+/// Free RAM (per mille of total) below which a kill is allowed.
+pub const KILL_FREE_PERMILLE: u32 = 30;
+
+pub const MEMPRESSURE_MSG_HEARTBEAT: u32 = 0;
+/// Payload: notification endpoint (u64 LE), subscription flags (u32 LE).
+/// Reply: current level.
+pub const MEMPRESSURE_MSG_SUBSCRIBE: u32 = 1;
+pub const MEMPRESSURE_MSG_UNSUBSCRIBE: u32 = 2;
+/// Reply: level, avg10 (‰), free RAM (‰).
+pub const MEMPRESSURE_MSG_LEVEL: u32 = 3;
+/// Reply: trim requests sent, kills, subscribers.
+pub const MEMPRESSURE_MSG_STATS: u32 = 4;
+
+/// Notification types sent by the daemon to subscribed endpoints, in the
+/// request envelope; far from the small message types servers use for
+/// their own protocol. Payload: a [`Notice`].
+pub const MEMPRESSURE_NOTIFY_TRIM: u32 = 0x4d50_0001;
+pub const MEMPRESSURE_NOTIFY_LEVEL: u32 = 0x4d50_0002;
+
+/// Subscription flag: receive trim requests.
+pub const SUBSCRIBE_TRIM: u32 = 1 << 0;
+/// Subscription flag: receive level changes.
+pub const SUBSCRIBE_LEVEL: u32 = 1 << 1;
+/// Flags accepted by the daemon's [`Subscribers`].
+pub const SUBSCRIBE_ALL: u32 = SUBSCRIBE_TRIM | SUBSCRIBE_LEVEL;
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Level {
+    #[default]
+    None = 0,
+    Low = 1,
+    Medium = 2,
+    Critical = 3,
+}
+
+impl Level {
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        Some(match raw {
+            0 => Self::None,
+            1 => Self::Low,
+            2 => Self::Medium,
+            3 => Self::Critical,
+            _ => return None,
+        })
+    }
+}
+
+/// Snapshot read from the pressure descriptor or `exo_psi(READ)`; same
+/// layout as the kernel's.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PressureReport {
+    /// Share of time stalled on memory, per mille, over 10 s, 60 s, 300 s.
+    pub avg10: u32,
+    pub avg60: u32,
+    pub avg300: u32,
+    pub level: u32,
+    /// Total stall time since boot, in µs.
+    pub total_us: u64,
+    pub free_pages: u64,
+    pub total_pages: u64,
+    /// OOM events: page faults left without a frame after reclaim, and
+    /// kills requested through `exo_psi(OOM_KILL)`.
+    pub oom_events: u64,
+    /// Bumped on every level change and OOM event.
+    pub seq: u64,
+}
+
+const _: () = assert!(core::mem::size_of::<PressureReport>() == 56);
+
+impl PressureReport {
+    pub fn level(&self) -> Level {
+        Level::from_raw(self.level).unwrap_or(Level::Critical)
+    }
+
+    pub fn free_permille(&self) -> u32 {
+        if self.total_pages == 0 {
+            return 1000;
+        }
+        (self.free_pages.min(self.total_pages).saturating_mul(1000) / self.total_pages) as u32
+    }
+}
+
+/// Payload of the daemon's notifications.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Notice {
+    pub level: Level,
+    pub avg10: u32,
+    pub free_permille: u32,
+}
+
+impl Notice {
+    pub const LEN: usize = 12;
+
+    pub fn from_report(report: &PressureReport) -> Self {
+        Self {
+            level: report.level(),
+            avg10: report.avg10,
+            free_permille: report.free_permille(),
+        }
+    }
+
+    pub fn encode(&self, out: &mut [u8]) -> Option<usize> {
+        let out = out.get_mut(..Self::LEN)?;
+        out[0..4].copy_from_slice(&(self.level as u32).to_le_bytes());
+        out[4..8].copy_from_slice(&self.avg10.to_le_bytes());
+        out[8..12].copy_from_slice(&self.free_permille.to_le_bytes());
+        Some(Self::LEN)
+    }
+
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        let payload = payload.get(..Self::LEN)?;
+        let word = |i: usize| u32::from_le_bytes(payload[i..i + 4].try_into().unwrap());
+        Some(Self {
+            level: Level::from_raw(word(0))?,
+            avg10: word(4),
+            free_permille: word(8),
+        })
+    }
+}
+
+/// What to do after a new report.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Decision {
+    /// Tell the level watchers.
+    pub level_changed: bool,
+    /// Ask applications to trim, with this severity.
+    pub trim: Option<Level>,
+    /// Ask the kernel OOM killer for a victim.
+    pub kill: bool,
+}
+
+/// Escalation state: trim requests first, the OOM killer last.
+#[derive(Debug, Default)]
+pub struct Policy {
+    level: Level,
+    /// Severity and time of the last trim request of the current episode.
+    last_trim: Option<(Level, u64)>,
+    /// Time of the first critical trim request still unanswered.
+    critical_trim_at: Option<u64>,
+    last_kill: Option<u64>,
+    pub trims: u64,
+    pub kills: u64,
+}
+
+impl Policy {
+    pub const fn new() -> Self {
+        Self {
+            level: Level::None,
+            last_trim: None,
+            critical_trim_at: None,
+            last_kill: None,
+            trims: 0,
+            kills: 0,
+        }
+    }
+
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    pub fn observe(&mut self, report: &PressureReport, now_ms: u64) -> Decision {
+        let level = report.level();
+        let mut decision = Decision {
+            level_changed: level != self.level,
+            ..Decision::default()
+        };
+        self.level = level;
+
+        if level < Level::Medium {
+            self.last_trim = None;
+            self.critical_trim_at = None;
+            return decision;
+        }
+        if level < Level::Critical {
+            self.critical_trim_at = None;
+        }
+
+        let trim_due = match self.last_trim {
+            None => true,
+            Some((last, at)) => level > last || now_ms.saturating_sub(at) >= TRIM_INTERVAL_MS,
+        };
+        if trim_due {
+            self.last_trim = Some((level, now_ms));
+            self.trims += 1;
+            decision.trim = Some(level);
+            if level == Level::Critical && self.critical_trim_at.is_none() {
+                self.critical_trim_at = Some(now_ms);
+            }
+        }
+
+        let grace_over = self
+            .critical_trim_at
+            .is_some_and(|at| now_ms.saturating_sub(at) >= KILL_GRACE_MS);
+        let cooled_down = self
+            .last_kill
+            .is_none_or(|at| now_ms.saturating_sub(at) >= KILL_COOLDOWN_MS);
+        if grace_over && cooled_down && report.free_permille() < KILL_FREE_PERMILLE {
+            self.last_kill = Some(now_ms);
+            self.kills += 1;
+            // Survivors get a fresh grace period after the next request.
+            self.critical_trim_at = None;
+            decision.kill = true;
+        }
+        decision
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(level: Level, free_permille: u64) -> PressureReport {
+        PressureReport {
+            level: level as u32,
+            free_pages: free_permille,
+            total_pages: 1000,
+            ..PressureReport::default()
+        }
+    }
+
+    #[test]
+    fn notice_and_subscribe_roundtrip() {
+        let mut buf = [0u8; 16];
+        let notice = Notice {
+            level: Level::Medium,
+            avg10: 120,
+            free_permille: 70,
+        };
+        assert_eq!(notice.encode(&mut buf), Some(Notice::LEN));
+        assert_eq!(Notice::decode(&buf), Some(notice));
+        assert_eq!(Notice::decode(&buf[..8]), None);
+        buf[0] = 9;
+        assert_eq!(Notice::decode(&buf), None);
+
+        let len = encode_subscribe(0x2a_0000_0001, SUBSCRIBE_TRIM, &mut buf).unwrap();
+        assert_eq!(
+            decode_subscribe(&buf[..len]),
+            Some((0x2a_0000_0001, SUBSCRIBE_TRIM))
+        );
+    }
+
+    #[test]
+    fn trims_before_killing() {
+        let mut policy = Policy::new();
+        assert_eq!(
+            policy.observe(&report(Level::Low, 120), 0),
+            Decision {
+                level_changed: true,
+                ..Decision::default()
+            }
+        );
+
+        let d = policy.observe(&report(Level::Medium, 60), 100);
+        assert_eq!(d.trim, Some(Level::Medium));
+        // Same level, too soon: nothing new.
+        assert_eq!(
+            policy.observe(&report(Level::Medium, 60), 200),
+            Decision::default()
+        );
+        // Worse: trim again at once.
+        let d = policy.observe(&report(Level::Critical, 10), 300);
+        assert_eq!(
+            (d.level_changed, d.trim, d.kill),
+            (true, Some(Level::Critical), false)
+        );
+        assert!(
+            !policy
+                .observe(&report(Level::Critical, 10), 300 + KILL_GRACE_MS - 1)
+                .kill
+        );
+        assert!(
+            policy
+                .observe(&report(Level::Critical, 10), 300 + KILL_GRACE_MS)
+                .kill
+        );
+        assert_eq!((policy.trims, policy.kills), (2, 1));
+    }
+
+    #[test]
+    fn kill_needs_low_free_memory_and_cooldown() {
+        let mut policy = Policy::new();
+        policy.observe(&report(Level::Critical, 200), 0);
+        // Critical from stalls alone, but RAM came back: no kill.
+        assert!(
+            !policy
+                .observe(&report(Level::Critical, 200), KILL_GRACE_MS)
+                .kill
+        );
+        assert!(
+            policy
+                .observe(&report(Level::Critical, 10), KILL_GRACE_MS + 1)
+                .kill
+        );
+
+        // Still critical right after the kill: a new request, a new grace
+        // period, and the cooldown.
+        let t = KILL_GRACE_MS + 1 + TRIM_INTERVAL_MS;
+        assert_eq!(
+            policy.observe(&report(Level::Critical, 10), t).trim,
+            Some(Level::Critical)
+        );
+        assert!(
+            !policy
+                .observe(&report(Level::Critical, 10), t + KILL_GRACE_MS)
+                .kill
+        );
+        assert!(
+            policy
+                .observe(
+                    &report(Level::Critical, 10),
+                    KILL_GRACE_MS + 1 + KILL_COOLDOWN_MS
+                )
+                .kill
+        );
+    }
+
+    #[test]
+    fn relief_resets_the_episode() {
+        let mut policy = Policy::new();
+        policy.observe(&report(Level::Critical, 10), 0);
+        assert!(
+            policy
+                .observe(&report(Level::None, 500), 1_000)
+                .level_changed
+        );
+        // A new episode starts with a trim and a full grace period.
+        let d = policy.observe(&report(Level::Critical, 10), 2_000);
+        assert_eq!(d.trim, Some(Level::Critical));
+        assert!(
+            !policy
+                .observe(&report(Level::Critical, 10), 2_000 + KILL_GRACE_MS - 1)
+                .kill
+        );
+    }
+}
@@ -0,0 +1,432 @@
+//! Application launch prewarming (zygote-style templates).
+//!
+//! The prewarm service keeps, per application class, a few template
+//! processes that are already forked, have pulled the program and its
+//! libraries into the file cache, and block on a pipe. A launch request from
+//! the launcher hands the arguments to a ready template, which specializes
+//! (working directory, argv) and `execve`s the program: the launch pays
+//! neither the fork nor the cold reads. A class with no ready template falls
+//! back to a cold spawn.
+//!
+//! Templates are the first memory to go: below [`LOW_FREE_PERMILLE`] of free
+//! RAM the pool evicts them, least recently launched class first, and only
+//! refills once free memory is back above [`HIGH_FREE_PERMILLE`].
+//!
+//! Configuration, one class per line (`/etc/exo/prewarm.conf`):
+//!
+//! ```text
+//! # <class> <templates> <program> [file to warm...]
+//! files 1 /usr/bin/cosmic-files /lib/libc.so /usr/lib/libcosmic.so
+//! ```
+
+use crate::config;
+
+pub const CONFIG_PATH: &str = "/etc/exo/prewarm.conf";
+
+pub const MAX_CLASSES: usize = 16;
+pub const MAX_TEMPLATES: u8 = 4;
+pub const CLASS_NAME_MAX: usize = 32;
+/// Arguments after the program name.
+pub const MAX_ARGS: usize = 16;
+/// A launch record fits in one inline IPC payload.
+pub const LAUNCH_MAX: usize = 192;
+
+/// Free RAM (per mille of total) below which templates are evicted.
+pub const LOW_FREE_PERMILLE: u32 = 100;
+/// Free RAM above which evicted templates are refilled.
+pub const HIGH_FREE_PERMILLE: u32 = 200;
+
+pub const PREWARM_MSG_HEARTBEAT: u32 = 0;
+/// Payload: a launch record (see [`encode_launch`]). Reply: pid.
+pub const PREWARM_MSG_LAUNCH: u32 = 1;
+/// Reply: warm launches, cold launches, evictions.
+pub const PREWARM_MSG_STATS: u32 = 2;
+
+/// Reply flag of `PREWARM_MSG_LAUNCH`: served by a template.
+pub const LAUNCH_WARM: u32 = 1 << 0;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    Syntax,
+    BadName,
+    BadCount,
+    RelativePath,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AppClass<'a> {
+    pub name: &'a str,
+    pub templates: u8,
+    pub program: &'a str,
+    warm: &'a str,
+}
+
+impl<'a> AppClass<'a> {
+    /// Files pulled into the cache by each template, program first.
+    pub fn warm_files(&self) -> impl Iterator<Item = &'a str> {
+        core::iter::once(self.program).chain(self.warm.split_ascii_whitespace())
+    }
+}
+
+pub fn valid_class_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= CLASS_NAME_MAX
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+impl<'a> config::Directive<'a> for AppClass<'a> {
+    type Error = ConfigError;
+
+    fn parse(line: &'a str) -> Result<Self, ConfigError> {
+        let mut fields = line.splitn(4, |c: char| c.is_ascii_whitespace());
+        let (Some(name), Some(count), Some(program)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(ConfigError::Syntax);
+        };
+        let warm = fields.next().unwrap_or("").trim();
+        if !valid_class_name(name) {
+            return Err(ConfigError::BadName);
+        }
+        let templates = count.parse::<u8>().map_err(|_| ConfigError::BadCount)?;
+        if templates > MAX_TEMPLATES {
+            return Err(ConfigError::BadCount);
+        }
+        if !program.starts_with('/') || warm.split_ascii_whitespace().any(|p| !p.starts_with('/')) {
+            return Err(ConfigError::RelativePath);
+        }
+        Ok(AppClass {
+            name,
+            templates,
+            program,
+            warm,
+        })
+    }
+}
+
+/// Valid classes of a configuration, in file order; invalid lines are
+/// skipped (see [`config::first_error`]).
+pub fn classes(config: &str) -> impl Iterator<Item = AppClass<'_>> {
+    config::directives(config).take(MAX_CLASSES)
+}
+
+/// Launch request, as sent by the launcher and forwarded to the template:
+/// `class\0cwd\0arg\0arg\0…`. An empty `cwd` keeps the template's; empty
+/// arguments are not allowed (they would be lost in the payload padding).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Launch<'a> {
+    pub class: &'a str,
+    pub cwd: &'a str,
+    record: &'a [u8],
+    args_at: usize,
+}
+
+/// Encodes a launch record into `out`; returns its length.
+pub fn encode_launch(class: &str, cwd: &str, args: &[&str], out: &mut [u8]) -> Option<usize> {
+    if !valid_class_name(class) || !(cwd.is_empty() || cwd.starts_with('/')) {
+        return None;
+    }
+    if args.len() > MAX_ARGS || args.iter().any(|a| a.is_empty()) {
+        return None;
+    }
+    let mut len = 0;
+    for field in [class, cwd].iter().chain(args) {
+        if field.as_bytes().contains(&0) {
+            return None;
+        }
+        let end = len + field.len();
+        if end >= out.len().min(LAUNCH_MAX) {
+            return None;
+        }
+        out[len..end].copy_from_slice(field.as_bytes());
+        out[end] = 0;
+        len = end + 1;
+    }
+    Some(len)
+}
+
+impl<'a> Launch<'a> {
+    /// Decodes a record. Trailing zero padding (a fixed-size IPC payload)
+    /// is ignored.
+    pub fn decode(record: &'a [u8]) -> Option<Self> {
+        let record = &record[..record.len().min(LAUNCH_MAX)];
+        let end = record.iter().rposition(|&b| b != 0).map_or(0, |i| i + 2);
+        let record = record.get(..end)?;
+        let mut fields = record.split(|&b| b == 0);
+        let class = core::str::from_utf8(fields.next()?).ok()?;
+        let cwd = core::str::from_utf8(fields.next()?).ok()?;
+        if !valid_class_name(class) || !(cwd.is_empty() || cwd.starts_with('/')) {
+            return None;
+        }
+        let launch = Self {
+            class,
+            cwd,
+            record,
+            args_at: class.len() + cwd.len() + 2,
+        };
+        (launch.args().count() <= MAX_ARGS).then_some(launch)
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = &'a [u8]> {
+        let rest = self.record.get(self.args_at..).unwrap_or(&[]);
+        // Every field is NUL-terminated: the last split is always empty.
+        rest.split(|&b| b == 0)
+            .take(rest.iter().filter(|&&b| b == 0).count())
+    }
+
+    /// Offsets in the record of each NUL-terminated argument, for `execve`.
+    pub fn arg_offsets(&self, out: &mut [usize]) -> Option<usize> {
+        let mut at = self.args_at;
+        let mut n = 0;
+        for arg in self.args() {
+            *out.get_mut(n)? = at;
+            at += arg.len() + 1;
+            n += 1;
+        }
+        Some(n)
+    }
+}
+
+pub fn free_permille(free: u64, total: u64) -> u32 {
+    if total == 0 {
+        return 1000;
+    }
+    (free.min(total).saturating_mul(1000) / total) as u32
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PoolAction {
+    /// Fork one more template for this class.
+    Spawn(usize),
+    /// Release one template of this class.
+    Evict(usize),
+    Idle,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct ClassSlot {
+    target: u8,
+    ready: u8,
+    /// Time of the last launch; 0 if never launched.
+    last_launch: u64,
+}
+
+/// Template accounting per class and the memory-pressure policy.
+pub struct Pool {
+    slots: [ClassSlot; MAX_CLASSES],
+    len: usize,
+    reclaiming: bool,
+}
+
+impl Default for Pool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pool {
+    pub const fn new() -> Self {
+        Self {
+            slots: [ClassSlot {
+                target: 0,
+                ready: 0,
+                last_launch: 0,
+            }; MAX_CLASSES],
+            len: 0,
+            reclaiming: false,
+        }
+    }
+
+    /// Registers a class; returns its index.
+    pub fn add_class(&mut self, templates: u8) -> Option<usize> {
+        let slot = self.slots.get_mut(self.len)?;
+        *slot = ClassSlot {
+            target: templates.min(MAX_TEMPLATES),
+            ..ClassSlot::default()
+        };
+        self.len += 1;
+        Some(self.len - 1)
+    }
+
+    pub fn ready(&self, class: usize) -> u8 {
+        self.slots.get(class).map_or(0, |s| s.ready)
+    }
+
+    pub fn is_reclaiming(&self) -> bool {
+        self.reclaiming
+    }
+
+    pub fn note_spawned(&mut self, class: usize) {
+        if let Some(slot) = self.slots[..self.len].get_mut(class) {
+            slot.ready = slot.ready.saturating_add(1);
+        }
+    }
+
+    /// A template left the pool: launched, evicted or died.
+    pub fn note_gone(&mut self, class: usize) {
+        if let Some(slot) = self.slots[..self.len].get_mut(class) {
+            slot.ready = slot.ready.saturating_sub(1);
+        }
+    }
+
+    pub fn note_launch(&mut self, class: usize, now: u64) {
+        if let Some(slot) = self.slots[..self.len].get_mut(class) {
+            slot.last_launch = now.max(1);
+        }
+    }
+
+    /// Next step towards the targets given the current free memory. Under
+    /// pressure, evicts from the least recently launched class; otherwise
+    /// refills the most recently launched class first.
+    pub fn next_action(&mut self, free_permille: u32) -> PoolAction {
+        if free_permille < LOW_FREE_PERMILLE {
+            self.reclaiming = true;
+        } else if free_permille >= HIGH_FREE_PERMILLE {
+            self.reclaiming = false;
+        }
+        let slots = self.slots[..self.len].iter().enumerate();
+        if self.reclaiming {
+            return slots
+                .filter(|(_, s)| s.ready > 0)
+                .min_by_key(|(_, s)| s.last_launch)
+                .map_or(PoolAction::Idle, |(i, _)| PoolAction::Evict(i));
+        }
+        slots
+            .filter(|(_, s)| s.ready < s.target)
+            // Ties (never launched) go to the first class in the file.
+            .max_by_key(|&(i, s)| (s.last_launch, usize::MAX - i))
+            .map_or(PoolAction::Idle, |(i, _)| PoolAction::Spawn(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{first_error, parse_line};
+
+    fn parse_class(line: &str) -> Result<Option<AppClass<'_>>, ConfigError> {
+        parse_line(line)
+    }
+
+    #[test]
+    fn parses_classes() {
+        let class = parse_class("files 2 /usr/bin/files  /lib/libc.so /lib/libgui.so")
+            .unwrap()
+            .unwrap();
+        assert_eq!((class.name, class.templates), ("files", 2));
+        let warm: [&str; 3] = ["/usr/bin/files", "/lib/libc.so", "/lib/libgui.so"];
+        assert!(class.warm_files().eq(warm));
+        assert_eq!(
+            parse_class("term 0 /bin/term")
+                .unwrap()
+                .unwrap()
+                .warm_files()
+                .count(),
+            1
+        );
+
+        assert_eq!(parse_class("  # comment"), Ok(None));
+        assert_eq!(parse_class("files 2"), Err(ConfigError::Syntax));
+        assert_eq!(parse_class("a/b 1 /x"), Err(ConfigError::BadName));
+        assert_eq!(parse_class("files 9 /x"), Err(ConfigError::BadCount));
+        assert_eq!(parse_class("files 1 x"), Err(ConfigError::RelativePath));
+        assert_eq!(
+            parse_class("files 1 /x lib.so"),
+            Err(ConfigError::RelativePath)
+        );
+
+        let config = "# apps\nfiles 1 /a\nbad\nterm 2 /b\n";
+        assert_eq!(classes(config).count(), 2);
+        assert_eq!(
+            first_error::<AppClass>(config),
+            Some((3, ConfigError::Syntax))
+        );
+    }
+
+    #[test]
+    fn launch_record_roundtrips() {
+        let mut payload = [0u8; LAUNCH_MAX];
+        let len =
+            encode_launch("files", "/home/ada", &["--new-window", "a b"], &mut payload).unwrap();
+        assert_eq!(&payload[..len], b"files\0/home/ada\0--new-window\0a b\0");
+
+        // Decoded from the zero-padded IPC payload.
+        let launch = Launch::decode(&payload).unwrap();
+        assert_eq!((launch.class, launch.cwd), ("files", "/home/ada"));
+        let args: [&[u8]; 2] = [b"--new-window", b"a b"];
+        assert!(launch.args().eq(args));
+        let mut offsets = [0usize; MAX_ARGS];
+        assert_eq!(launch.arg_offsets(&mut offsets), Some(2));
+        assert_eq!(&offsets[..2], &[16, 29]);
+
+        let len = encode_launch("term", "", &[], &mut payload).unwrap();
+        let launch = Launch::decode(&payload[..len]).unwrap();
+        assert_eq!((launch.cwd, launch.args().count()), ("", 0));
+    }
+
+    #[test]
+    fn rejects_bad_launches() {
+        let mut out = [0u8; LAUNCH_MAX];
+        assert_eq!(encode_launch("../x", "", &[], &mut out), None);
+        assert_eq!(encode_launch("files", "rel", &[], &mut out), None);
+        assert_eq!(encode_launch("files", "", &["a\0b"], &mut out), None);
+        assert_eq!(encode_launch("files", "", &[""], &mut out), None);
+        let long = [b'x'; LAUNCH_MAX];
+        let long = core::str::from_utf8(&long).unwrap();
+        assert_eq!(encode_launch("files", "", &[long], &mut out), None);
+        assert_eq!(
+            encode_launch("files", "", &["a"; MAX_ARGS + 1], &mut out),
+            None
+        );
+
+        assert_eq!(Launch::decode(b""), None);
+        assert_eq!(Launch::decode(b"files\0rel\0"), None);
+        assert_eq!(Launch::decode(b"\xff\0\0"), None);
+    }
+
+    #[test]
+    fn refills_recent_classes_first() {
+        let mut pool = Pool::new();
+        let files = pool.add_class(1).unwrap();
+        let term = pool.add_class(2).unwrap();
+        assert_eq!(pool.next_action(500), PoolAction::Spawn(files));
+        pool.note_spawned(files);
+        assert_eq!(pool.next_action(500), PoolAction::Spawn(term));
+
+        pool.note_launch(term, 10);
+        pool.note_spawned(term);
+        pool.note_gone(files);
+        // term was launched last: its second template comes first.
+        assert_eq!(pool.next_action(500), PoolAction::Spawn(term));
+        pool.note_spawned(term);
+        assert_eq!(pool.next_action(500), PoolAction::Spawn(files));
+        pool.note_spawned(files);
+        assert_eq!(pool.next_action(500), PoolAction::Idle);
+    }
+
+    #[test]
+    fn evicts_under_pressure_with_hysteresis() {
+        let mut pool = Pool::new();
+        let files = pool.add_class(1).unwrap();
+        let term = pool.add_class(1).unwrap();
+        pool.note_spawned(files);
+        pool.note_spawned(term);
+        pool.note_launch(files, 50);
+
+        // Least recently launched class goes first.
+        assert_eq!(pool.next_action(80), PoolAction::Evict(term));
+        pool.note_gone(term);
+        // Still short of the high watermark: keep reclaiming.
+        assert_eq!(pool.next_action(150), PoolAction::Evict(files));
+        pool.note_gone(files);
+        assert_eq!(pool.next_action(150), PoolAction::Idle);
+        assert!(pool.is_reclaiming());
+
+        assert_eq!(pool.next_action(250), PoolAction::Spawn(files));
+        assert!(!pool.is_reclaiming());
+        assert_eq!(free_permille(1, 0), 1000);
+        assert_eq!(free_permille(25, 100), 250);
+    }
+}
@@ -0,0 +1,268 @@
+//! Resume from sleep: the system-wide notice and sleep-aware timers.
+//!
+//! After a suspend, every deadline a service computed before sleeping is
+//! stale: DHCP leases may have expired, peers have dropped TCP connections,
+//! the clock needs a new sync and audio devices were powered off. The
+//! `sleep_monitor` daemon notices each resume and pushes
+//! [`RESUME_NOTIFY_RESUMED`] to its subscribers (the audio server reopens
+//! its devices) and to `network_server`, which renews its lease and resets
+//! connections after a long sleep ([`STALE_CONNECTION_MS`]).
+//!
+//! A resume is noticed two ways:
+//!
+//! - the kernel's resume generation (`SYS_EXO_SLEEP_STATE`) moved, for
+//!   sleeps the platform went through;
+//! - the daemon woke up much later than the timeout it slept on, for sleeps
+//!   the kernel cannot see, such as a virtual machine paused by its host.
+//!
+//! `CLOCK_MONOTONIC` may or may not count the time asleep, depending on how
+//! the platform slept; [`CLOCK_BOOTTIME`] always does. Timers meant to fire
+//! after a wall duration (lease renewal, periodic sync) use it through
+//! [`Periodic`].
+
+pub use crate::subscribers::{decode_subscribe, encode_subscribe};
+
+/// `clock_gettime` clock counting the time asleep.
+pub const CLOCK_BOOTTIME: u64 = 7;
+
+/// The daemon wakes up at least this often.
+pub const TICK_MS: u64 = 2_000;
+/// A wake-up this much later than its timeout counts as a sleep.
+pub const GAP_MIN_MS: u64 = 5_000;
+/// Sleeps at least this long reset TCP connections: peers have usually
+/// timed them out, and the first write would hang until retransmissions
+/// give up.
+pub const STALE_CONNECTION_MS: u64 = 120_000;
+
+pub const RESUME_MSG_HEARTBEAT: u32 = 0;
+/// Payload: see [`encode_subscribe`], flags [`SUBSCRIBE_RESUMED`].
+/// Reply: resumes so far, last sleep (ms).
+pub const RESUME_MSG_SUBSCRIBE: u32 = 1;
+pub const RESUME_MSG_UNSUBSCRIBE: u32 = 2;
+/// Reply: resumes so far, last sleep (ms), subscribers.
+pub const RESUME_MSG_STATUS: u32 = 3;
+
+/// Notification sent on resume; payload: a [`Resumed`].
+pub const RESUME_NOTIFY_RESUMED: u32 = 0x5253_0001;
+
+/// Subscription flag: receive resumes.
+pub const SUBSCRIBE_RESUMED: u32 = 1 << 0;
+
+/// One resume, as notified.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Resumed {
+    /// Resumes since the daemon started, this one included.
+    pub count: u64,
+    /// Time asleep; an estimate when the kernel did not see the sleep.
+    pub slept_ms: u64,
+}
+
+impl Resumed {
+    pub const LEN: usize = 16;
+
+    /// Long enough for TCP connections to be stale.
+    pub fn connections_stale(&self) -> bool {
+        self.slept_ms >= STALE_CONNECTION_MS
+    }
+
+    pub fn encode(&self, out: &mut [u8]) -> Option<usize> {
+        let out = out.get_mut(..Self::LEN)?;
+        out[..8].copy_from_slice(&self.count.to_le_bytes());
+        out[8..].copy_from_slice(&self.slept_ms.to_le_bytes());
+        Some(Self::LEN)
+    }
+
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        let payload = payload.get(..Self::LEN)?;
+        Some(Self {
+            count: u64::from_le_bytes(payload[..8].try_into().unwrap()),
+            slept_ms: u64::from_le_bytes(payload[8..].try_into().unwrap()),
+        })
+    }
+}
+
+/// `SYS_EXO_SLEEP_STATE` output.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct KernelSleep {
+    pub generation: u64,
+    pub last_sleep_ns: u64,
+    pub total_sleep_ns: u64,
+}
+
+impl KernelSleep {
+    pub const LEN: usize = 24;
+
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..Self::LEN)?;
+        let word = |i: usize| u64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().unwrap());
+        Some(Self {
+            generation: word(0),
+            last_sleep_ns: word(1),
+            total_sleep_ns: word(2),
+        })
+    }
+}
+
+/// Notices resumes from the daemon's successive wake-ups.
+#[derive(Debug, Default)]
+pub struct Detector {
+    /// Kernel state at the last wake-up, `None` before the first.
+    kernel: Option<KernelSleep>,
+    last_wake_ns: u64,
+    resumes: u64,
+    last_slept_ms: u64,
+}
+
+impl Detector {
+    pub const fn new() -> Self {
+        Self {
+            kernel: None,
+            last_wake_ns: 0,
+            resumes: 0,
+            last_slept_ms: 0,
+        }
+    }
+
+    pub fn resumes(&self) -> u64 {
+        self.resumes
+    }
+
+    pub fn last_slept_ms(&self) -> u64 {
+        self.last_slept_ms
+    }
+
+    /// One wake-up at `now_ns` (boot time), after waiting at most
+    /// `timeout_ms`; `kernel` is the kernel's sleep state read now. The
+    /// first call only takes the baseline.
+    pub fn observe(
+        &mut self,
+        now_ns: u64,
+        timeout_ms: u64,
+        kernel: KernelSleep,
+    ) -> Option<Resumed> {
+        let previous = self.kernel.replace(kernel);
+        let gap_ms = now_ns.saturating_sub(self.last_wake_ns) / 1_000_000;
+        self.last_wake_ns = now_ns;
+        let previous = previous?;
+        let slept_ms = if kernel.generation != previous.generation {
+            // Several sleeps since the last wake-up: all of them count.
+            kernel
+                .total_sleep_ns
+                .saturating_sub(previous.total_sleep_ns)
+                / 1_000_000
+        } else if gap_ms > timeout_ms.saturating_add(GAP_MIN_MS) {
+            gap_ms - timeout_ms
+        } else {
+            return None;
+        };
+        self.resumes += 1;
+        self.last_slept_ms = slept_ms;
+        Some(Resumed {
+            count: self.resumes,
+            slept_ms,
+        })
+    }
+}
+
+/// Periodic deadline on [`CLOCK_BOOTTIME`]. Periods missed while asleep are
+/// not replayed: the timer fires once on resume and keeps its cadence from
+/// there.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Periodic {
+    period_ns: u64,
+    next_ns: u64,
+}
+
+impl Periodic {
+    /// First due one period after `now_ns`.
+    pub const fn new(period_ms: u64, now_ns: u64) -> Self {
+        let period_ns = period_ms.saturating_mul(1_000_000);
+        Self {
+            period_ns,
+            next_ns: now_ns.saturating_add(period_ns),
+        }
+    }
+
+    /// Whether the timer is due at `now_ns`; if so, rearms it.
+    pub fn poll(&mut self, now_ns: u64) -> bool {
+        if now_ns < self.next_ns {
+            return false;
+        }
+        self.next_ns = self.next_ns.saturating_add(self.period_ns);
+        if self.next_ns <= now_ns {
+            // Slept through several periods.
+            self.next_ns = now_ns.saturating_add(self.period_ns);
+        }
+        true
+    }
+
+    /// Time left before the timer is due, to sleep on.
+    pub fn remaining_ms(&self, now_ns: u64) -> u64 {
+        self.next_ns.saturating_sub(now_ns).div_ceil(1_000_000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: u64 = 1_000_000;
+
+    #[test]
+    fn detects_kernel_and_unseen_sleeps() {
+        let mut detector = Detector::new();
+        let mut kernel = KernelSleep::default();
+        assert_eq!(detector.observe(1_000 * MS, TICK_MS, kernel), None);
+        // Woken early by a request, then on time.
+        assert_eq!(detector.observe(1_500 * MS, TICK_MS, kernel), None);
+        assert_eq!(detector.observe(3_500 * MS, TICK_MS, kernel), None);
+
+        // The platform slept twice between two wake-ups.
+        kernel = KernelSleep {
+            generation: 2,
+            last_sleep_ns: 40_000 * MS,
+            total_sleep_ns: 100_000 * MS,
+        };
+        let resumed = detector.observe(5_500 * MS, TICK_MS, kernel).unwrap();
+        assert_eq!(
+            resumed,
+            Resumed {
+                count: 1,
+                slept_ms: 100_000
+            }
+        );
+        assert!(!resumed.connections_stale());
+
+        // Paused by the host: the kernel saw nothing.
+        let resumed = detector.observe(307_500 * MS, TICK_MS, kernel).unwrap();
+        assert_eq!(
+            resumed,
+            Resumed {
+                count: 2,
+                slept_ms: 300_000
+            }
+        );
+        assert!(resumed.connections_stale());
+        assert_eq!(detector.resumes(), 2);
+
+        let mut payload = [0u8; Resumed::LEN];
+        resumed.encode(&mut payload).unwrap();
+        assert_eq!(Resumed::decode(&payload), Some(resumed));
+        assert_eq!(Resumed::decode(&payload[..8]), None);
+    }
+
+    #[test]
+    fn periodic_timers_do_not_replay_missed_periods() {
+        let mut timer = Periodic::new(60_000, 0);
+        assert!(!timer.poll(59_999 * MS));
+        assert_eq!(timer.remaining_ms(59_999 * MS), 1);
+        assert!(timer.poll(60_500 * MS));
+        // Cadence kept when on time.
+        assert_eq!(timer.remaining_ms(60_500 * MS), 59_500);
+
+        // An hour asleep: one firing, next one a period later.
+        assert!(timer.poll(3_700_000 * MS));
+        assert!(!timer.poll(3_700_001 * MS));
+        assert_eq!(timer.remaining_ms(3_700_000 * MS), 60_000);
+    }
+}
@@ -0,0 +1,301 @@
+//! Fractional output scaling and per-surface scale negotiation.
+//!
+//! Each output has a scale in 120ths, as in the fractional-scale protocol:
+//! 1.25 is sent as 150. The compositor tells every surface the scale it
+//! should render at ([`preferred`]: the output showing most of it). A client
+//! that supports fractional scaling then renders a buffer of
+//! [`Scale::buffer_size`] for its logical size, sets that logical size as
+//! the viewport destination ([`Viewport`]) with a buffer scale of 1, and the
+//! compositor shows the buffer pixel for pixel ([`Scale::is_native`])
+//! instead of upscaling an integer-scale buffer. Older clients get the
+//! rounded-up integer scale ([`Scale::integer`]) and are downscaled.
+//!
+//! The scale of an output is set on the settings' display page
+//! ([`crate::outputs`]), or derived from the physical size reported by the
+//! monitor's EDID ([`detect`]).
+
+use crate::math;
+use crate::scanout::Rect;
+
+/// Density rendered at scale 1.
+pub const REFERENCE_DPI: u32 = 96;
+/// Smallest logical height [`detect`] leaves, so that ordinary windows
+/// still fit.
+pub const MIN_LOGICAL_HEIGHT: u32 = 720;
+
+/// Output or surface scale, in 120ths.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Scale(pub u32);
+
+impl Scale {
+    pub const ONE: Self = Self(120);
+    pub const MAX: Self = Self(480);
+    /// Steps [`detect`] picks from.
+    const STEP: u32 = 30;
+
+    /// Scale `factor`, rounded to the nearest 120th; `None` outside 1 to 4.
+    pub fn from_factor(factor: f64) -> Option<Self> {
+        let scale = Self(math::floor(factor * 120.0 + 0.5) as u32);
+        (factor.is_finite() && Self::ONE <= scale && scale <= Self::MAX).then_some(scale)
+    }
+
+    pub fn factor(self) -> f64 {
+        self.0 as f64 / 120.0
+    }
+
+    /// Integer scale for clients without fractional scaling.
+    pub fn integer(self) -> u32 {
+        self.0.div_ceil(120)
+    }
+
+    fn apply(self, logical: u32) -> u32 {
+        // Round half away from zero, as the protocol asks of clients.
+        ((logical as u64 * self.0 as u64 + 60) / 120) as u32
+    }
+
+    /// Buffer size to render for a surface of `logical` size.
+    pub fn buffer_size(self, logical: (u32, u32)) -> (u32, u32) {
+        (self.apply(logical.0), self.apply(logical.1))
+    }
+
+    /// Logical size an output of `pixels` offers.
+    pub fn logical_size(self, pixels: (u32, u32)) -> (u32, u32) {
+        let divide = |px: u32| ((px as u64 * 120 + self.0 as u64 / 2) / self.0 as u64) as u32;
+        (divide(pixels.0), divide(pixels.1))
+    }
+
+    /// A buffer of `buffer` pixels shown at `logical` size lands on the
+    /// output pixel for pixel.
+    pub fn is_native(self, logical: (u32, u32), buffer: (u32, u32)) -> bool {
+        self.buffer_size(logical) == buffer
+    }
+}
+
+/// Scale of an output from its mode and physical size (EDID, millimetres):
+/// its density over [`REFERENCE_DPI`], in quarter steps, reduced while the
+/// logical height would drop under [`MIN_LOGICAL_HEIGHT`]. Without a
+/// plausible physical size (unknown, or a projector's aspect ratio in
+/// centimetres), 1.
+pub fn detect(pixels: (u32, u32), physical_mm: (u32, u32)) -> Scale {
+    let (width, height) = pixels;
+    let (width_mm, height_mm) = physical_mm;
+    if width == 0 || height == 0 || width_mm < 100 || height_mm < 50 {
+        return Scale::ONE;
+    }
+    // Diagonal density, to average the two axes.
+    let diagonal_px =
+        math::sqrt((width as f64) * (width as f64) + (height as f64) * (height as f64));
+    let diagonal_in =
+        math::sqrt((width_mm as f64) * (width_mm as f64) + (height_mm as f64) * (height_mm as f64))
+            / 25.4;
+    let ratio = diagonal_px / diagonal_in / REFERENCE_DPI as f64;
+    let steps = math::floor(ratio * 120.0 / Scale::STEP as f64 + 0.5) as u32;
+    let mut scale = Scale((steps * Scale::STEP).clamp(Scale::ONE.0, Scale::MAX.0));
+    while scale > Scale::ONE && scale.logical_size(pixels).1 < MIN_LOGICAL_HEIGHT {
+        scale.0 -= Scale::STEP;
+    }
+    scale
+}
+
+fn overlap(a: &Rect, b: &Rect) -> u64 {
+    let left = (a.x as i64).max(b.x as i64);
+    let top = (a.y as i64).max(b.y as i64);
+    let right = (a.x as i64 + a.w as i64).min(b.x as i64 + b.w as i64);
+    let bottom = (a.y as i64 + a.h as i64).min(b.y as i64 + b.h as i64);
+    if right <= left || bottom <= top {
+        return 0;
+    }
+    ((right - left) * (bottom - top)) as u64
+}
+
+/// Scale a surface at `rect` (logical coordinates) should render at: that
+/// of the output showing the largest part of it, the largest scale on a
+/// tie; 1 off every output.
+pub fn preferred(rect: &Rect, outputs: &[(Rect, Scale)]) -> Scale {
+    outputs
+        .iter()
+        .map(|(output, scale)| (overlap(rect, output), *scale))
+        .filter(|(area, _)| *area > 0)
+        .max()
+        .map_or(Scale::ONE, |(_, scale)| scale)
+}
+
+/// Scale an output is configured with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Setting {
+    Auto,
+    Fixed(Scale),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ViewportError {
+    /// Negative or zero size, negative source position.
+    BadValue,
+    /// Without a destination, the source size must be whole.
+    BadSize,
+    /// Source rectangle outside the buffer.
+    OutOfBuffer,
+}
+
+/// Viewport of a surface: crop (`source`, in 24.8 fixed point) and scale
+/// (`destination`) of its buffer.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Viewport {
+    source: Option<[i32; 4]>,
+    destination: Option<(u32, u32)>,
+}
+
+impl Viewport {
+    pub const fn new() -> Self {
+        Self {
+            source: None,
+            destination: None,
+        }
+    }
+
+    /// `x, y, w, h` in 24.8 fixed point; all -1 unsets it.
+    pub fn set_source(&mut self, x: i32, y: i32, w: i32, h: i32) -> Result<(), ViewportError> {
+        const UNSET: i32 = -256;
+        if [x, y, w, h] == [UNSET; 4] {
+            self.source = None;
+            return Ok(());
+        }
+        if x < 0 || y < 0 || w <= 0 || h <= 0 {
+            return Err(ViewportError::BadValue);
+        }
+        self.source = Some([x, y, w, h]);
+        Ok(())
+    }
+
+    /// `-1, -1` unsets it.
+    pub fn set_destination(&mut self, w: i32, h: i32) -> Result<(), ViewportError> {
+        if (w, h) == (-1, -1) {
+            self.destination = None;
+            return Ok(());
+        }
+        if w <= 0 || h <= 0 {
+            return Err(ViewportError::BadValue);
+        }
+        self.destination = Some((w as u32, h as u32));
+        Ok(())
+    }
+
+    /// Logical size of the surface at commit, for a `buffer` of pixels
+    /// with integer `buffer_scale`.
+    pub fn surface_size(
+        &self,
+        buffer: (u32, u32),
+        buffer_scale: u32,
+    ) -> Result<(u32, u32), ViewportError> {
+        let buffer_scale = buffer_scale.max(1);
+        let (width, height) = (buffer.0 / buffer_scale, buffer.1 / buffer_scale);
+        if let Some([x, y, w, h]) = self.source {
+            // Fixed point against buffer coordinates after buffer_scale.
+            let fits =
+                |pos: i32, len: i32, limit: u32| pos as i64 + len as i64 <= (limit as i64) << 8;
+            if !fits(x, w, width) || !fits(y, h, height) {
+                return Err(ViewportError::OutOfBuffer);
+            }
+        }
+        match (self.destination, self.source) {
+            (Some(size), _) => Ok(size),
+            (None, Some([_, _, w, h])) if w & 0xff != 0 || h & 0xff != 0 => {
+                Err(ViewportError::BadSize)
+            }
+            (None, Some([_, _, w, h])) => Ok(((w >> 8) as u32, (h >> 8) as u32)),
+            (None, None) => Ok((width, height)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_round_like_the_protocol() {
+        let scale = Scale::from_factor(1.25).unwrap();
+        assert_eq!(scale, Scale(150));
+        assert_eq!(scale.integer(), 2);
+        assert_eq!(scale.buffer_size((801, 600)), (1001, 750));
+        assert_eq!(Scale(180).logical_size((2560, 1440)), (1707, 960));
+        assert!(scale.is_native((800, 600), (1000, 750)));
+        assert!(!scale.is_native((800, 600), (1600, 1200)));
+        assert_eq!(Scale::from_factor(0.5), None);
+        assert_eq!(Scale::from_factor(f64::NAN), None);
+    }
+
+    #[test]
+    fn detects_scale_from_physical_size() {
+        // 24" 1080p desktop monitor.
+        assert_eq!(detect((1920, 1080), (531, 299)), Scale::ONE);
+        // 14" 1080p laptop: 1.75 would leave 617 logical lines.
+        assert_eq!(detect((1920, 1080), (309, 174)), Scale(180));
+        // 13" 2560x1600 laptop.
+        assert_eq!(detect((2560, 1600), (286, 179)), Scale(240));
+        // 27" 4K monitor.
+        assert_eq!(detect((3840, 2160), (597, 336)), Scale(210));
+        // Unknown size, projector aspect ratio.
+        assert_eq!(detect((3840, 2160), (0, 0)), Scale::ONE);
+        assert_eq!(detect((3840, 2160), (16, 9)), Scale::ONE);
+    }
+
+    #[test]
+    fn surfaces_follow_the_output_showing_most_of_them() {
+        let laptop = (Rect::new(0, 0, 1280, 800), Scale(180));
+        let monitor = (Rect::new(1280, 0, 1920, 1080), Scale::ONE);
+        let outputs = [laptop, monitor];
+        assert_eq!(
+            preferred(&Rect::new(100, 100, 400, 300), &outputs),
+            Scale(180)
+        );
+        assert_eq!(
+            preferred(&Rect::new(1200, 100, 400, 300), &outputs),
+            Scale::ONE
+        );
+        // Split evenly: the sharper output wins.
+        assert_eq!(
+            preferred(&Rect::new(1080, 0, 400, 300), &outputs),
+            Scale(180)
+        );
+        assert_eq!(
+            preferred(&Rect::new(-900, 0, 400, 300), &outputs),
+            Scale::ONE
+        );
+    }
+
+    #[test]
+    fn viewports_give_the_surface_size() {
+        let mut viewport = Viewport::new();
+        assert_eq!(viewport.surface_size((1000, 750), 1), Ok((1000, 750)));
+        assert_eq!(viewport.surface_size((1000, 750), 2), Ok((500, 375)));
+
+        // Fractional client: 1.25 × 800x600 buffer shown at 800x600.
+        viewport.set_destination(800, 600).unwrap();
+        assert_eq!(viewport.surface_size((1000, 750), 1), Ok((800, 600)));
+
+        viewport.set_destination(-1, -1).unwrap();
+        viewport
+            .set_source(0, 0, 100 << 8, (50 << 8) + 128)
+            .unwrap();
+        assert_eq!(
+            viewport.surface_size((1000, 750), 1),
+            Err(ViewportError::BadSize)
+        );
+        viewport.set_source(900 << 8, 0, 200 << 8, 50 << 8).unwrap();
+        assert_eq!(
+            viewport.surface_size((1000, 750), 1),
+            Err(ViewportError::OutOfBuffer)
+        );
+        viewport.set_source(-256, -256, -256, -256).unwrap();
+        assert_eq!(viewport, Viewport::new());
+        assert_eq!(
+            viewport.set_destination(0, 10),
+            Err(ViewportError::BadValue)
+        );
+        assert_eq!(
+            viewport.set_source(-1, 0, 10, 10),
+            Err(ViewportError::BadValue)
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Fullscreen unredirection: direct scanout of an opaque fullscreen surface.
+//!
+//! Each frame, the compositor hands its surface stack (topmost first) to
+//! [`Unredirect::frame`]. When the topmost visible surface covers the whole
+//! output, is fully opaque and drawn untransformed, nothing under it can
+//! show: compositing it would only copy it to the screen. After
+//! [`SETTLE_FRAMES`] such frames in a row the compositor lends the display
+//! to the surface's client (`FB_MSG_TRANSFER_DISPLAY` with
+//! `FB_TRANSFER_LEND`), which then draws straight to the framebuffer, the
+//! only scanout plane `fb_server` has. As soon as the stack changes in a way
+//! that breaks this (a notification above, a resized or translucent window,
+//! another client on top), the compositor takes the display back with
+//! `FB_MSG_ACQUIRE_DISPLAY` and composites again. If the client exits,
+//! `fb_server` returns the display on its own.
+//!
+//! The settle delay keeps a window that flickers through fullscreen (a
+//! maximize animation, a splash) from bouncing the display back and forth.
+//! This is independent of game mode, which only adds scheduling and power
+//! boosts around the same mechanism.
+
+/// Consecutive eligible frames before unredirecting.
+pub const SETTLE_FRAMES: u32 = 3;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: u32,
+    pub h: u32,
+}
+
+impl Rect {
+    pub const fn new(x: i32, y: i32, w: u32, h: u32) -> Self {
+        Self { x, y, w, h }
+    }
+
+    fn right(&self) -> i64 {
+        self.x as i64 + self.w as i64
+    }
+
+    fn bottom(&self) -> i64 {
+        self.y as i64 + self.h as i64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.w == 0 || self.h == 0
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        !self.is_empty()
+            && !other.is_empty()
+            && (self.x as i64) < other.right()
+            && (other.x as i64) < self.right()
+            && (self.y as i64) < other.bottom()
+            && (other.y as i64) < self.bottom()
+    }
+
+    pub fn contains(&self, other: &Rect) -> bool {
+        self.x <= other.x
+            && self.y <= other.y
+            && self.right() >= other.right()
+            && self.bottom() >= other.bottom()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Surface {
+    pub id: u32,
+    /// Client drawing the surface, the one the display is lent to.
+    pub pid: u32,
+    pub rect: Rect,
+    /// The client declared the whole surface opaque.
+    pub opaque: bool,
+    /// Surface-wide alpha applied by the compositor, 255 = none.
+    pub alpha: u8,
+    /// Scaled, rotated or cropped by the compositor.
+    pub transformed: bool,
+}
+
+impl Surface {
+    /// The surface alone can produce the output image.
+    pub fn scans_out(&self, output: &Rect) -> bool {
+        self.opaque && self.alpha == u8::MAX && !self.transformed && self.rect.contains(output)
+    }
+}
+
+/// Surface of the stack that could be scanned out: the topmost one
+/// reaching the output, if it hides everything under it.
+pub fn candidate<'a>(output: &Rect, stack: &'a [Surface]) -> Option<&'a Surface> {
+    let top = stack
+        .iter()
+        .find(|s| s.alpha != 0 && s.rect.intersects(output))?;
+    top.scans_out(output).then_some(top)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    /// Lend the display to this client.
+    Lend { surface: u32, pid: u32 },
+    /// Take the display back and composite again.
+    Reclaim,
+}
+
+/// Unredirection state of one output.
+#[derive(Debug, Default)]
+pub struct Unredirect {
+    /// Candidate seen at the last frame and for how many frames.
+    pending: Option<(u32, u32)>,
+    /// Surface currently scanned out.
+    active: Option<u32>,
+}
+
+impl Unredirect {
+    pub const fn new() -> Self {
+        Self {
+            pending: None,
+            active: None,
+        }
+    }
+
+    /// Surface currently scanned out.
+    pub fn active(&self) -> Option<u32> {
+        self.active
+    }
+
+    /// Feeds one frame; returns what the compositor must do, if anything.
+    pub fn frame(&mut self, output: &Rect, stack: &[Surface]) -> Option<Action> {
+        let Some(top) = candidate(output, stack) else {
+            self.pending = None;
+            return self.active.take().map(|_| Action::Reclaim);
+        };
+        if self.active == Some(top.id) {
+            return None;
+        }
+        let seen = match self.pending {
+            Some((id, frames)) if id == top.id => frames + 1,
+            _ => 1,
+        };
+        self.pending = Some((top.id, seen));
+        if seen < SETTLE_FRAMES {
+            // Another surface took over: composite while it settles.
+            return self.active.take().map(|_| Action::Reclaim);
+        }
+        self.pending = None;
+        self.active = Some(top.id);
+        Some(Action::Lend {
+            surface: top.id,
+            pid: top.pid,
+        })
+    }
+
+    /// The client gave the display back or exited (`fb_server` returned it
+    /// to the compositor).
+    pub fn returned(&mut self) {
+        self.active = None;
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: Rect = Rect::new(0, 0, 1920, 1080);
+
+    fn surface(id: u32, rect: Rect) -> Surface {
+        Surface {
+            id,
+            pid: 100 + id,
+            rect,
+            opaque: true,
+            alpha: u8::MAX,
+            transformed: false,
+        }
+    }
+
+    #[test]
+    fn only_an_opaque_fullscreen_top_surface_is_a_candidate() {
+        let game = surface(1, OUTPUT);
+        let desktop = surface(2, OUTPUT);
+        assert_eq!(candidate(&OUTPUT, &[game, desktop]).map(|s| s.id), Some(1));
+
+        // A notification above it, even small, must be composited.
+        let toast = surface(3, Rect::new(1600, 20, 300, 80));
+        assert!(candidate(&OUTPUT, &[toast, game]).is_none());
+        // Fully transparent or off-screen surfaces do not count.
+        let hidden = Surface { alpha: 0, ..toast };
+        let offscreen = surface(4, Rect::new(1920, 0, 100, 100));
+        assert_eq!(
+            candidate(&OUTPUT, &[hidden, offscreen, game]).map(|s| s.id),
+            Some(1)
+        );
+
+        let translucent = Surface { alpha: 200, ..game };
+        let scaled = Surface {
+            transformed: true,
+            ..game
+        };
+        let windowed = surface(1, Rect::new(10, 10, 800, 600));
+        for top in [translucent, scaled, windowed] {
+            assert!(candidate(&OUTPUT, &[top, desktop]).is_none());
+        }
+        // Larger than the output (overscan) is fine.
+        let larger = surface(1, Rect::new(-8, -8, 1936, 1096));
+        assert!(candidate(&OUTPUT, &[larger]).is_some());
+    }
+
+    #[test]
+    fn lends_after_settling_and_reclaims_at_once() {
+        let game = surface(1, OUTPUT);
+        let toast = surface(3, Rect::new(1600, 20, 300, 80));
+        let mut state = Unredirect::new();
+        for _ in 1..SETTLE_FRAMES {
+            assert_eq!(state.frame(&OUTPUT, &[game]), None);
+        }
+        assert_eq!(
+            state.frame(&OUTPUT, &[game]),
+            Some(Action::Lend {
+                surface: 1,
+                pid: 101
+            })
+        );
+        assert_eq!(state.frame(&OUTPUT, &[game]), None);
+        assert_eq!(state.active(), Some(1));
+
+        assert_eq!(state.frame(&OUTPUT, &[toast, game]), Some(Action::Reclaim));
+        assert_eq!(state.frame(&OUTPUT, &[toast, game]), None);
+        // Gone again: the delay starts over.
+        assert_eq!(state.frame(&OUTPUT, &[game]), None);
+    }
+
+    #[test]
+    fn switching_surfaces_reclaims_first() {
+        let first = surface(1, OUTPUT);
+        let second = surface(2, OUTPUT);
+        let mut state = Unredirect::new();
+        for _ in 0..SETTLE_FRAMES {
+            state.frame(&OUTPUT, &[first]);
+        }
+        assert_eq!(
+            state.frame(&OUTPUT, &[second, first]),
+            Some(Action::Reclaim)
+        );
+        for _ in 2..SETTLE_FRAMES {
+            assert_eq!(state.frame(&OUTPUT, &[second, first]), None);
+        }
+        assert_eq!(
+            state.frame(&OUTPUT, &[second, first]),
+            Some(Action::Lend {
+                surface: 2,
+                pid: 102
+            })
+        );
+
+        state.returned();
+        assert_eq!(state.active(), None);
+    }
+}
@@ -0,0 +1,656 @@
+//! Time-based job scheduling for the init cron service.
+//!
+//! Two spellings describe the same [`Pattern`]:
+//! - crontab fields `m h dom mon dow` plus `@hourly`-style shorthands;
+//! - calendar expressions in the systemd `OnCalendar=` style, written
+//!   `@on(Mon..Fri *-*-* 09:30)` in a crontab line.
+//!
+//! All times are Unix seconds in UTC. A job marked `persistent` that missed
+//! its slot while the system was down runs once at the next start. A job
+//! marked `unmetered` waits while data-saver mode is on (see
+//! [`crate::metered`]).
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScheduleError {
+    Syntax,
+    OutOfRange,
+    /// A field that can never match (e.g. `*/0`).
+    Empty,
+    MissingCommand,
+    BadOwner,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CivilTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// 0 = Sunday.
+    pub weekday: u32,
+}
+
+pub const SECS_PER_DAY: u64 = 86_400;
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (yoe + era * 400 + (month <= 2) as i64, month, day)
+}
+
+pub fn civil_from_unix(secs: u64) -> CivilTime {
+    let days = (secs / SECS_PER_DAY) as i64;
+    let sod = (secs % SECS_PER_DAY) as u32;
+    let (year, month, day) = civil_from_days(days);
+    CivilTime {
+        year,
+        month,
+        day,
+        hour: sod / 3600,
+        minute: sod / 60 % 60,
+        second: sod % 60,
+        weekday: ((days + 4) % 7) as u32,
+    }
+}
+
+/// Matching instants, one bit per allowed value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Pattern {
+    seconds: u64,
+    minutes: u64,
+    hours: u32,
+    /// Bits 1..=31.
+    days: u32,
+    /// Bits 1..=12.
+    months: u16,
+    /// Bit 0 = Sunday.
+    weekdays: u8,
+    year: Option<i64>,
+    /// Cron rule: when both day-of-month and day-of-week are restricted,
+    /// either one matching is enough. Calendar expressions require both.
+    day_or_weekday: bool,
+}
+
+/// Years scanned before giving up (covers Feb 29 and leap-year patterns).
+const SEARCH_DAYS: u64 = 8 * 366;
+
+impl Pattern {
+    fn day_matches(&self, c: &CivilTime) -> bool {
+        if self.year.is_some_and(|y| y != c.year) || self.months & (1 << c.month) == 0 {
+            return false;
+        }
+        let dom = self.days & (1 << c.day) != 0;
+        let dow = self.weekdays & (1 << c.weekday) != 0;
+        if self.day_or_weekday {
+            dom || dow
+        } else {
+            dom && dow
+        }
+    }
+
+    fn first_time_from(&self, sod: u32) -> Option<u32> {
+        let (h0, m0, s0) = (sod / 3600, sod / 60 % 60, sod % 60);
+        let mut h = next_bit(self.hours as u64, h0, 24)?;
+        loop {
+            let m_from = if h == h0 { m0 } else { 0 };
+            let mut m = next_bit(self.minutes, m_from, 60);
+            while let Some(min) = m {
+                let s_from = if h == h0 && min == m0 { s0 } else { 0 };
+                if let Some(s) = next_bit(self.seconds, s_from, 60) {
+                    return Some(h * 3600 + min * 60 + s);
+                }
+                m = next_bit(self.minutes, min + 1, 60);
+            }
+            h = next_bit(self.hours as u64, h + 1, 24)?;
+        }
+    }
+
+    /// First matching instant strictly after `t`.
+    pub fn next_after(&self, t: u64) -> Option<u64> {
+        let start = t + 1;
+        let first_day = start / SECS_PER_DAY;
+        let mut sod = (start % SECS_PER_DAY) as u32;
+        for day in first_day..first_day + SEARCH_DAYS {
+            let c = civil_from_unix(day * SECS_PER_DAY);
+            if self.year.is_some_and(|y| c.year > y) {
+                return None;
+            }
+            if self.day_matches(&c) {
+                if let Some(s) = self.first_time_from(sod) {
+                    return Some(day * SECS_PER_DAY + s as u64);
+                }
+            }
+            sod = 0;
+        }
+        None
+    }
+}
+
+fn next_bit(mask: u64, from: u32, limit: u32) -> Option<u32> {
+    (from..limit).find(|&b| mask & (1 << b) != 0)
+}
+
+const MONTH_NAMES: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+fn parse_value(text: &str, min: u32, names: &[&str]) -> Result<u32, ScheduleError> {
+    if let Ok(v) = text.parse::<u32>() {
+        return Ok(v);
+    }
+    let lower = text.get(..3).ok_or(ScheduleError::Syntax)?;
+    names
+        .iter()
+        .position(|n| n.eq_ignore_ascii_case(lower))
+        .map(|i| i as u32 + min)
+        .ok_or(ScheduleError::Syntax)
+}
+
+/// One field: `*`, `a`, `a<sep>b`, each optionally `/step`, comma-separated.
+fn parse_field(
+    text: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    range_sep: &str,
+) -> Result<u64, ScheduleError> {
+    let mut mask = 0u64;
+    for item in text.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((r, s)) => (r, s.parse::<u32>().map_err(|_| ScheduleError::Syntax)?),
+            None => (item, 1),
+        };
+        if step == 0 {
+            return Err(ScheduleError::Empty);
+        }
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once(range_sep) {
+            (parse_value(a, min, names)?, parse_value(b, min, names)?)
+        } else {
+            let v = parse_value(range, min, names)?;
+            // `5/15` means from 5 to the end of the range.
+            (v, if item.contains('/') { max } else { v })
+        };
+        if lo < min || hi > max || lo > hi {
+            return Err(ScheduleError::OutOfRange);
+        }
+        let mut v = lo;
+        while v <= hi {
+            mask |= 1 << v;
+            v += step;
+        }
+    }
+    if mask == 0 {
+        return Err(ScheduleError::Empty);
+    }
+    Ok(mask)
+}
+
+/// Crontab weekdays accept 7 as a second spelling of Sunday.
+fn cron_weekdays(text: &str) -> Result<u8, ScheduleError> {
+    let mask = parse_field(text, 0, 7, WEEKDAY_NAMES, "-")?;
+    Ok(((mask | mask >> 7) & 0x7f) as u8)
+}
+
+/// Five crontab fields: minute, hour, day of month, month, day of week.
+pub fn parse_cron(fields: [&str; 5]) -> Result<Pattern, ScheduleError> {
+    let [minute, hour, dom, month, dow] = fields;
+    Ok(Pattern {
+        seconds: 1,
+        minutes: parse_field(minute, 0, 59, &[], "-")?,
+        hours: parse_field(hour, 0, 23, &[], "-")? as u32,
+        days: parse_field(dom, 1, 31, &[], "-")? as u32,
+        months: parse_field(month, 1, 12, MONTH_NAMES, "-")? as u16,
+        weekdays: cron_weekdays(dow)?,
+        year: None,
+        day_or_weekday: !dom.starts_with('*') && !dow.starts_with('*'),
+    })
+}
+
+/// `OnCalendar=`-style expression: `[weekdays] [[year-]month-day] [h:m[:s]]`
+/// with `*`, `,` lists, `..` ranges and `/` repetitions, or one of
+/// `minutely`, `hourly`, `daily`, `weekly`, `monthly`, `yearly`.
+pub fn parse_calendar(expr: &str) -> Result<Pattern, ScheduleError> {
+    let expr = match expr.trim() {
+        "minutely" => "*-*-* *:*:00",
+        "hourly" => "*-*-* *:00:00",
+        "daily" => "*-*-* 00:00:00",
+        "weekly" => "Mon *-*-* 00:00:00",
+        "monthly" => "*-*-01 00:00:00",
+        "yearly" | "annually" => "*-01-01 00:00:00",
+        other => other,
+    };
+    let mut p = Pattern {
+        seconds: 1,
+        minutes: 1,
+        hours: 1,
+        days: parse_field("*", 1, 31, &[], "..")? as u32,
+        months: parse_field("*", 1, 12, &[], "..")? as u16,
+        weekdays: 0x7f,
+        year: None,
+        day_or_weekday: false,
+    };
+    let mut tokens = expr.split_ascii_whitespace().peekable();
+    if let Some(first) = tokens.peek() {
+        if first.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            p.weekdays = parse_field(first, 0, 6, WEEKDAY_NAMES, "..")? as u8;
+            tokens.next();
+        }
+    }
+    for token in tokens {
+        if token.contains(':') {
+            let mut parts = token.split(':');
+            p.hours = parse_field(parts.next().unwrap_or(""), 0, 23, &[], "..")? as u32;
+            p.minutes = parse_field(parts.next().ok_or(ScheduleError::Syntax)?, 0, 59, &[], "..")?;
+            p.seconds = match parts.next() {
+                Some(s) => parse_field(s, 0, 59, &[], "..")?,
+                None => 1,
+            };
+            if parts.next().is_some() {
+                return Err(ScheduleError::Syntax);
+            }
+        } else if token.contains('-') {
+            let parts: [&str; 3] = {
+                let mut it = token.splitn(3, '-');
+                let a = it.next().unwrap_or("");
+                match (it.next(), it.next()) {
+                    (Some(b), Some(c)) => [a, b, c],
+                    (Some(b), None) => ["*", a, b],
+                    _ => return Err(ScheduleError::Syntax),
+                }
+            };
+            p.year = match parts[0] {
+                "*" => None,
+                y => Some(y.parse().map_err(|_| ScheduleError::Syntax)?),
+            };
+            p.months = parse_field(parts[1], 1, 12, &[], "..")? as u16;
+            p.days = parse_field(parts[2], 1, 31, &[], "..")? as u32;
+        } else {
+            return Err(ScheduleError::Syntax);
+        }
+    }
+    Ok(p)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Schedule {
+    /// `@reboot`: once per boot.
+    AtBoot,
+    At(Pattern),
+}
+
+impl Schedule {
+    /// When a job should first fire after (re)loading its table. A
+    /// persistent job whose slot passed since `last_run` fires now.
+    pub fn first_fire(&self, persistent: bool, last_run: Option<u64>, now: u64) -> Option<u64> {
+        match self {
+            Schedule::AtBoot => Some(now),
+            Schedule::At(p) => {
+                let missed = persistent
+                    && last_run
+                        .and_then(|last| p.next_after(last))
+                        .is_some_and(|slot| slot <= now);
+                if missed {
+                    Some(now)
+                } else {
+                    p.next_after(now)
+                }
+            }
+        }
+    }
+
+    /// Next slot after a run at `now`. Runs missed while a job was late
+    /// (suspend, clock step) collapse into the one that just happened.
+    pub fn after_run(&self, now: u64) -> Option<u64> {
+        match self {
+            Schedule::AtBoot => None,
+            Schedule::At(p) => p.next_after(now),
+        }
+    }
+}
+
+/// Which crontab a line comes from: the system table carries an owner
+/// column (`root` or a numeric uid), user tables belong to their uid.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Table {
+    System,
+    User(u32),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CronEntry<'a> {
+    pub schedule: Schedule,
+    pub persistent: bool,
+    pub unmetered: bool,
+    pub uid: u32,
+    pub command: &'a str,
+}
+
+fn split_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    match text.find(|c: char| c.is_ascii_whitespace()) {
+        Some(i) => (&text[..i], &text[i..]),
+        None => (text, ""),
+    }
+}
+
+/// `<schedule> [persistent] [unmetered] [owner] <command>`. Comments, blank lines and
+/// `NAME=value` environment lines yield `Ok(None)`.
+pub fn parse_crontab_line(
+    line: &str,
+    table: Table,
+) -> Result<Option<CronEntry<'_>>, ScheduleError> {
+    let line = line.trim();
+    let (first, _) = split_word(line);
+    if line.is_empty() || line.starts_with('#') || first.contains('=') {
+        return Ok(None);
+    }
+    let (schedule, mut rest) = if let Some(body) = line.strip_prefix("@on(") {
+        let close = body.find(')').ok_or(ScheduleError::Syntax)?;
+        (
+            Schedule::At(parse_calendar(&body[..close])?),
+            &body[close + 1..],
+        )
+    } else if let Some(name) = first.strip_prefix('@') {
+        let fields = match name {
+            "reboot" => None,
+            "hourly" => Some(["0", "*", "*", "*", "*"]),
+            "daily" | "midnight" => Some(["0", "0", "*", "*", "*"]),
+            "weekly" => Some(["0", "0", "*", "*", "0"]),
+            "monthly" => Some(["0", "0", "1", "*", "*"]),
+            "yearly" | "annually" => Some(["0", "0", "1", "1", "*"]),
+            _ => return Err(ScheduleError::Syntax),
+        };
+        let schedule = match fields {
+            Some(f) => Schedule::At(parse_cron(f)?),
+            None => Schedule::AtBoot,
+        };
+        (schedule, &line[first.len()..])
+    } else {
+        let mut fields = [""; 5];
+        let mut rest = line;
+        for f in fields.iter_mut() {
+            let (word, tail) = split_word(rest);
+            if word.is_empty() {
+                return Err(ScheduleError::Syntax);
+            }
+            *f = word;
+            rest = tail;
+        }
+        (Schedule::At(parse_cron(fields)?), rest)
+    };
+    let mut persistent = false;
+    let (word, tail) = split_word(rest);
+    if word == "persistent" {
+        persistent = true;
+        rest = tail;
+    }
+    let mut unmetered = false;
+    let (word, tail) = split_word(rest);
+    if word == "unmetered" {
+        unmetered = true;
+        rest = tail;
+    }
+    let uid = match table {
+        Table::User(uid) => uid,
+        Table::System => {
+            let (owner, tail) = split_word(rest);
+            rest = tail;
+            match owner {
+                "root" => 0,
+                _ => owner.parse().map_err(|_| ScheduleError::BadOwner)?,
+            }
+        }
+    };
+    let command = rest.trim();
+    if command.is_empty() {
+        return Err(ScheduleError::MissingCommand);
+    }
+    Ok(Some(CronEntry {
+        schedule,
+        persistent,
+        unmetered,
+        uid,
+        command,
+    }))
+}
+
+/// Stable identity of a crontab line for the last-run state file: editing
+/// the line starts its history afresh.
+pub fn job_key(uid: u32, line: &str) -> u64 {
+    let mut h = 0xcbf2_9ce4_8422_2325u64;
+    for b in uid.to_le_bytes().iter().chain(line.trim().as_bytes()) {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x0100_0000_01b3);
+    }
+    h
+}
+
+/// State file line `<key hex> <last run>`.
+pub fn parse_state_line(line: &str) -> Option<(u64, u64)> {
+    let (key, last) = line.trim().split_once(' ')?;
+    Some((
+        u64::from_str_radix(key, 16).ok()?,
+        last.trim().parse().ok()?,
+    ))
+}
+
+pub fn format_state_line(key: u64, last_run: u64, out: &mut [u8]) -> Option<usize> {
+    let mut len = 0;
+    let mut put = |b: u8| {
+        *out.get_mut(len)? = b;
+        len += 1;
+        Some(())
+    };
+    for shift in (0..16).rev() {
+        put(b"0123456789abcdef"[(key >> (shift * 4)) as usize & 0xf])?;
+    }
+    put(b' ')?;
+    let mut digits = [0u8; 20];
+    let mut n = digits.len();
+    let mut v = last_run;
+    loop {
+        n -= 1;
+        digits[n] = b'0' + (v % 10) as u8;
+        v /= 10;
+        if v == 0 {
+            break;
+        }
+    }
+    for &d in &digits[n..] {
+        put(d)?;
+    }
+    put(b'\n')?;
+    Some(len)
+}
+
+/// Splits a command on whitespace into NUL-terminated words in `buf` for
+/// `execve`; `argv` receives the start offset of each word. No quoting.
+pub fn split_command(command: &str, buf: &mut [u8], argv: &mut [usize]) -> Option<usize> {
+    let mut len = 0;
+    let mut argc = 0;
+    for word in command.split_ascii_whitespace() {
+        *argv.get_mut(argc)? = len;
+        buf.get_mut(len..len + word.len())?
+            .copy_from_slice(word.as_bytes());
+        len += word.len();
+        *buf.get_mut(len)? = 0;
+        len += 1;
+        argc += 1;
+    }
+    (argc > 0).then_some(argc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i64, mo: u32, d: u32, h: u64, mi: u64, s: u64) -> u64 {
+        days_from_civil(y, mo, d) as u64 * SECS_PER_DAY + h * 3600 + mi * 60 + s
+    }
+
+    #[test]
+    fn civil_conversion_roundtrips() {
+        let c = civil_from_unix(at(2024, 2, 29, 13, 5, 9));
+        assert_eq!((c.year, c.month, c.day), (2024, 2, 29));
+        assert_eq!((c.hour, c.minute, c.second, c.weekday), (13, 5, 9, 4));
+        assert_eq!(civil_from_unix(0).weekday, 4);
+    }
+
+    #[test]
+    fn cron_fields_and_dom_dow_rule() {
+        let every_15 = parse_cron(["*/15", "9-17", "*", "*", "mon-fri"]).unwrap();
+        // Saturday 2024-06-01 → Monday 09:00.
+        assert_eq!(
+            every_15.next_after(at(2024, 6, 1, 12, 0, 0)),
+            Some(at(2024, 6, 3, 9, 0, 0))
+        );
+        assert_eq!(
+            every_15.next_after(at(2024, 6, 3, 9, 0, 0)),
+            Some(at(2024, 6, 3, 9, 15, 0))
+        );
+        // The 13th or any Friday.
+        let either = parse_cron(["0", "0", "13", "*", "5"]).unwrap();
+        assert_eq!(
+            either.next_after(at(2024, 6, 1, 0, 0, 0)),
+            Some(at(2024, 6, 7, 0, 0, 0))
+        );
+        let leap = parse_cron(["0", "0", "29", "feb", "*"]).unwrap();
+        assert_eq!(
+            leap.next_after(at(2024, 3, 1, 0, 0, 0)),
+            Some(at(2028, 2, 29, 0, 0, 0))
+        );
+        assert_eq!(
+            parse_cron(["60", "*", "*", "*", "*"]),
+            Err(ScheduleError::OutOfRange)
+        );
+        assert_eq!(
+            parse_cron(["*/0", "*", "*", "*", "*"]),
+            Err(ScheduleError::Empty)
+        );
+        assert_eq!(parse_cron(["0", "0", "*", "*", "7"]).unwrap().weekdays, 1);
+    }
+
+    #[test]
+    fn calendar_expressions() {
+        let p = parse_calendar("Mon..Fri *-*-* 09:30").unwrap();
+        assert_eq!(
+            p.next_after(at(2024, 6, 1, 0, 0, 0)),
+            Some(at(2024, 6, 3, 9, 30, 0))
+        );
+        let p = parse_calendar("*-*-01 06:00:15").unwrap();
+        assert_eq!(
+            p.next_after(at(2024, 1, 31, 0, 0, 0)),
+            Some(at(2024, 2, 1, 6, 0, 15))
+        );
+        let p = parse_calendar("2025-12-25 08:00").unwrap();
+        assert_eq!(
+            p.next_after(at(2024, 1, 1, 0, 0, 0)),
+            Some(at(2025, 12, 25, 8, 0, 0))
+        );
+        assert_eq!(p.next_after(at(2026, 1, 1, 0, 0, 0)), None);
+        // Unlike cron, calendar day and weekday must both match.
+        let p = parse_calendar("Fri *-*-13").unwrap();
+        assert_eq!(
+            p.next_after(at(2024, 6, 1, 0, 0, 0)),
+            Some(at(2024, 9, 13, 0, 0, 0))
+        );
+        assert_eq!(parse_calendar("weekly"), parse_calendar("Mon 00:00"));
+        assert_eq!(parse_calendar("*:*:*:*"), Err(ScheduleError::Syntax));
+    }
+
+    #[test]
+    fn crontab_lines_and_catch_up() {
+        assert_eq!(parse_crontab_line("  # nightly", Table::System), Ok(None));
+        assert_eq!(parse_crontab_line("PATH=/bin", Table::System), Ok(None));
+        let e = parse_crontab_line(
+            "30 2 * * * persistent root /bin/backup --full",
+            Table::System,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            (e.persistent, e.uid, e.command),
+            (true, 0, "/bin/backup --full")
+        );
+        let e = parse_crontab_line("@on(Sat *-*-* 10:00) /bin/sync-photos", Table::User(1000))
+            .unwrap()
+            .unwrap();
+        assert_eq!((e.persistent, e.unmetered, e.uid), (false, false, 1000));
+        let e = parse_crontab_line("@daily persistent unmetered 0 /bin/update", Table::System)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (e.persistent, e.unmetered, e.command),
+            (true, true, "/bin/update")
+        );
+        let e = parse_crontab_line("@reboot 0 /bin/warm-cache", Table::System)
+            .unwrap()
+            .unwrap();
+        assert_eq!(e.schedule, Schedule::AtBoot);
+        assert_eq!(
+            parse_crontab_line("@daily alice /bin/x", Table::System),
+            Err(ScheduleError::BadOwner)
+        );
+        assert_eq!(
+            parse_crontab_line("0 0 * * * 0", Table::System),
+            Err(ScheduleError::MissingCommand)
+        );
+
+        let daily = Schedule::At(parse_cron(["0", "3", "*", "*", "*"]).unwrap());
+        let last = at(2024, 6, 1, 3, 0, 0);
+        let boot = at(2024, 6, 3, 8, 0, 0);
+        assert_eq!(daily.first_fire(true, Some(last), boot), Some(boot));
+        assert_eq!(
+            daily.first_fire(false, Some(last), boot),
+            Some(at(2024, 6, 4, 3, 0, 0))
+        );
+        assert_eq!(
+            daily.first_fire(true, None, boot),
+            Some(at(2024, 6, 4, 3, 0, 0))
+        );
+        assert_eq!(Schedule::AtBoot.after_run(boot), None);
+    }
+
+    #[test]
+    fn state_lines_and_argv() {
+        let key = job_key(0, "@daily root /bin/x");
+        assert_ne!(key, job_key(1000, "@daily root /bin/x"));
+        let mut buf = [0u8; 40];
+        let n = format_state_line(key, 1_717_200_000, &mut buf).unwrap();
+        let line = core::str::from_utf8(&buf[..n]).unwrap();
+        assert_eq!(parse_state_line(line), Some((key, 1_717_200_000)));
+        assert_eq!(format_state_line(key, 1, &mut buf[..10]), None);
+
+        let mut words = [0u8; 32];
+        let mut argv = [0usize; 4];
+        assert_eq!(
+            split_command(" /bin/backup  --full ", &mut words, &mut argv),
+            Some(2)
+        );
+        assert_eq!(&words[..20], b"/bin/backup\0--full\0\0");
+        assert_eq!(argv[..2], [0, 12]);
+        assert_eq!(split_command("a b c d e", &mut words, &mut argv), None);
+    }
+}
@@ -0,0 +1,249 @@
+//! Event sounds settings.
+//!
+//! Event sounds are short, themed one-shots ("message-new", "battery-low")
+//! that clients such as the notification service play themselves: they
+//! resolve and preload them with `exo_media::sound` and write them straight
+//! to their audio stream, so a sound starts without a round trip. This
+//! daemon only owns the shared settings, the sound theme and the global
+//! mute toggled from the settings page (`SOUNDS_MSG_SET`), and broadcasts
+//! them to subscribers ([`SOUNDS_NOTIFY_STATE`]), which reload their cache
+//! when the theme changes and stop playing while muted.
+//!
+//! Configuration (`/etc/exo/sounds.conf`):
+//!
+//! ```text
+//! # theme <name>            directory under /usr/share/sounds
+//! # event_sounds on|off     off: every event sound is muted
+//! theme freedesktop
+//! event_sounds on
+//! ```
+
+use crate::config;
+
+pub use crate::subscribers::{decode_subscribe, encode_subscribe};
+
+pub const CONFIG_PATH: &str = "/etc/exo/sounds.conf";
+
+pub const DEFAULT_THEME: &str = "freedesktop";
+pub const THEME_NAME_MAX: usize = 32;
+
+pub const SOUNDS_MSG_HEARTBEAT: u32 = 0;
+/// Payload: see [`encode_subscribe`], flags [`SUBSCRIBE_STATE`].
+/// Reply: a [`State`].
+pub const SOUNDS_MSG_SUBSCRIBE: u32 = 1;
+pub const SOUNDS_MSG_UNSUBSCRIBE: u32 = 2;
+/// Reply: a [`State`].
+pub const SOUNDS_MSG_STATE: u32 = 3;
+/// Payload: see [`encode_set`].
+pub const SOUNDS_MSG_SET: u32 = 4;
+
+/// Notification sent to subscribers; payload: a [`State`].
+pub const SOUNDS_NOTIFY_STATE: u32 = 0x534e_0001;
+
+/// Subscription flag: receive settings changes.
+pub const SUBSCRIBE_STATE: u32 = 1 << 0;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    Syntax,
+    BadValue,
+    UnknownKey,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Directive<'a> {
+    Theme(&'a str),
+    EventSounds(bool),
+}
+
+/// Theme directory names: no path separators, no hidden directories.
+pub fn valid_theme_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= THEME_NAME_MAX
+        && !name.starts_with('.')
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+impl<'a> config::Directive<'a> for Directive<'a> {
+    type Error = ConfigError;
+
+    fn parse(line: &'a str) -> Result<Self, ConfigError> {
+        let (key, value) = config::key_value(line).ok_or(ConfigError::Syntax)?;
+        match key {
+            "theme" if valid_theme_name(value) => Ok(Directive::Theme(value)),
+            "theme" => Err(ConfigError::BadValue),
+            "event_sounds" => match value {
+                "on" | "yes" => Ok(Directive::EventSounds(true)),
+                "off" | "no" => Ok(Directive::EventSounds(false)),
+                _ => Err(ConfigError::BadValue),
+            },
+            _ => Err(ConfigError::UnknownKey),
+        }
+    }
+}
+
+/// Current settings, as broadcast by the daemon.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct State {
+    pub muted: bool,
+    theme: [u8; THEME_NAME_MAX],
+    theme_len: u8,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl State {
+    /// Muted flag (u8), theme length (u8), theme.
+    pub const MAX_LEN: usize = 2 + THEME_NAME_MAX;
+
+    pub const fn new() -> Self {
+        let mut theme = [0; THEME_NAME_MAX];
+        let default = DEFAULT_THEME.as_bytes();
+        let mut i = 0;
+        while i < default.len() {
+            theme[i] = default[i];
+            i += 1;
+        }
+        Self {
+            muted: false,
+            theme,
+            theme_len: default.len() as u8,
+        }
+    }
+
+    /// Builds the settings from a file; invalid lines are skipped (see
+    /// [`config::first_error`]).
+    pub fn parse(config: &str) -> Self {
+        let mut out = Self::new();
+        for directive in config::directives::<Directive>(config) {
+            match directive {
+                Directive::Theme(name) => out.set_theme(name),
+                Directive::EventSounds(on) => out.muted = !on,
+            }
+        }
+        out
+    }
+
+    pub fn theme(&self) -> &str {
+        core::str::from_utf8(&self.theme[..self.theme_len as usize]).unwrap_or(DEFAULT_THEME)
+    }
+
+    /// Ignores names rejected by [`valid_theme_name`].
+    pub fn set_theme(&mut self, name: &str) {
+        if valid_theme_name(name) {
+            self.theme = [0; THEME_NAME_MAX];
+            self.theme[..name.len()].copy_from_slice(name.as_bytes());
+            self.theme_len = name.len() as u8;
+        }
+    }
+
+    pub fn encode(&self, out: &mut [u8]) -> Option<usize> {
+        let len = 2 + self.theme_len as usize;
+        let out = out.get_mut(..len)?;
+        out[0] = self.muted as u8;
+        out[1] = self.theme_len;
+        out[2..].copy_from_slice(self.theme().as_bytes());
+        Some(len)
+    }
+
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        let (&[muted, len], rest) = payload.split_first_chunk::<2>()?;
+        let theme = core::str::from_utf8(rest.get(..len as usize)?).ok()?;
+        if muted > 1 || !valid_theme_name(theme) {
+            return None;
+        }
+        let mut out = Self::new();
+        out.muted = muted == 1;
+        out.set_theme(theme);
+        Some(out)
+    }
+}
+
+/// `SOUNDS_MSG_SET` payload: mute flag (u8: 0, 1, or 0xff to keep it),
+/// theme length (u8, 0 to keep it), theme.
+pub fn encode_set(muted: Option<bool>, theme: Option<&str>, out: &mut [u8]) -> Option<usize> {
+    let theme = match theme {
+        Some(name) if !valid_theme_name(name) => return None,
+        Some(name) => name,
+        None => "",
+    };
+    let len = 2 + theme.len();
+    let out = out.get_mut(..len)?;
+    out[0] = muted.map_or(0xff, |m| m as u8);
+    out[1] = theme.len() as u8;
+    out[2..].copy_from_slice(theme.as_bytes());
+    Some(len)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn decode_set(payload: &[u8]) -> Option<(Option<bool>, Option<&str>)> {
+    let (&[muted, len], rest) = payload.split_first_chunk::<2>()?;
+    let muted = match muted {
+        0xff => None,
+        0 | 1 => Some(muted == 1),
+        _ => return None,
+    };
+    let theme = match core::str::from_utf8(rest.get(..len as usize)?).ok()? {
+        "" => None,
+        name if valid_theme_name(name) => Some(name),
+        _ => return None,
+    };
+    Some((muted, theme))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{first_error, parse_line};
+
+    fn parse_directive(line: &str) -> Result<Option<Directive<'_>>, ConfigError> {
+        parse_line(line)
+    }
+
+    #[test]
+    fn parses_configuration() {
+        assert_eq!(State::new().theme(), DEFAULT_THEME);
+        let text = "# quiet desk\ntheme exo-light\nevent_sounds off\n";
+        assert_eq!(first_error::<Directive>(text), None);
+        let state = State::parse(text);
+        assert_eq!(state.theme(), "exo-light");
+        assert!(state.muted);
+
+        assert_eq!(parse_directive("theme ../etc"), Err(ConfigError::BadValue));
+        assert_eq!(
+            parse_directive("event_sounds loud"),
+            Err(ConfigError::BadValue)
+        );
+        assert_eq!(parse_directive("theme"), Err(ConfigError::Syntax));
+        assert_eq!(
+            first_error::<Directive>("\nvolume 80\n"),
+            Some((2, ConfigError::UnknownKey))
+        );
+    }
+
+    #[test]
+    fn wire_formats_roundtrip() {
+        let mut buf = [0u8; State::MAX_LEN];
+        let mut state = State::new();
+        state.muted = true;
+        state.set_theme("exo");
+        let len = state.encode(&mut buf).unwrap();
+        assert_eq!(len, 5);
+        assert_eq!(State::decode(&buf[..len]), Some(state));
+        assert_eq!(State::decode(&buf[..len - 1]), None);
+
+        let len = encode_set(Some(false), None, &mut buf).unwrap();
+        assert_eq!(decode_set(&buf[..len]), Some((Some(false), None)));
+        let len = encode_set(None, Some("ocean"), &mut buf).unwrap();
+        assert_eq!(decode_set(&buf[..len]), Some((None, Some("ocean"))));
+        assert_eq!(encode_set(None, Some("a/b"), &mut buf), None);
+        assert_eq!(decode_set(&[2, 0]), None);
+        assert_eq!(decode_set(&[0xff, 2, b'/', b'x']), None);
+    }
+}
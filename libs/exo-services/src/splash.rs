@@ -0,0 +1,521 @@
+//! Boot splash.
+//!
+//! `boot_splash` owns the display while the service graph starts. It draws
+//! through `fb_server`, the only process mapping the framebuffer, with the
+//! owner-only `FB_MSG_FILL_RECT` / `FB_MSG_DRAW_TEXT` requests:
+//!
+//! - init sends a [`Milestone`] (`SPLASH_MSG_PROGRESS`) each time a service
+//!   becomes ready or is given up on; the bar follows those milestones,
+//!   never a timer;
+//! - a compositor or greeter ready to draw sends `SPLASH_MSG_HANDOFF`: the
+//!   splash hands the display over with `FB_MSG_TRANSFER_DISPLAY`, without
+//!   going back to the text console in between, then exits;
+//! - otherwise init sends `SPLASH_MSG_QUIT` before starting the interactive
+//!   shell: the splash releases the display and `fb_server` restores the
+//!   console on the same background colour.
+//!
+//! Configuration (`/etc/exo/splash.conf`), applied on top of a built-in
+//! theme:
+//!
+//! ```text
+//! # theme exo|minimal|light     built-in base theme
+//! # background|foreground|accent|track #rrggbb
+//! # title <text>                rest of the line
+//! # messages on|off             name of the last service under the bar
+//! # bar_width <percent>         of the screen width
+//! # bar_position <percent>      vertical position of the bar
+//! theme exo
+//! title Exo-OS
+//! ```
+
+use crate::config;
+
+pub const CONFIG_PATH: &str = "/etc/exo/splash.conf";
+
+/// Payload: a [`Milestone`]. Sent by init; no reply.
+pub const SPLASH_MSG_PROGRESS: u32 = 1;
+/// Sent by init; the splash gives the display back to the console and
+/// exits. No reply.
+pub const SPLASH_MSG_QUIT: u32 = 2;
+/// Sent by the process taking over the display. Reply: status 0 once the
+/// sender owns the display, then the splash exits.
+pub const SPLASH_MSG_HANDOFF: u32 = 3;
+
+pub const TITLE_MAX: usize = 32;
+pub const MILESTONE_NAME_MAX: usize = 32;
+
+/// Completion is counted in thousandths.
+pub const PROGRESS_FULL: u16 = 1000;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// `0x00RRGGBB`, as carried by `fb_server` drawing requests.
+    pub const fn to_u32(self) -> u32 {
+        ((self.0 as u32) << 16) | ((self.1 as u32) << 8) | self.2 as u32
+    }
+
+    fn parse(word: &str) -> Option<Self> {
+        let hex = word.strip_prefix('#')?;
+        if hex.len() != 6 {
+            return None;
+        }
+        let raw = u32::from_str_radix(hex, 16).ok()?;
+        Some(Self((raw >> 16) as u8, (raw >> 8) as u8, raw as u8))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Theme {
+    pub background: Rgb,
+    pub foreground: Rgb,
+    pub accent: Rgb,
+    /// Unfilled part of the bar.
+    pub track: Rgb,
+    title: [u8; TITLE_MAX],
+    title_len: u8,
+    pub messages: bool,
+    pub bar_width_pct: u8,
+    pub bar_position_pct: u8,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::EXO
+    }
+}
+
+impl Theme {
+    /// Same background as the text console: releasing the display to it
+    /// does not flash.
+    pub const EXO: Self = Self {
+        background: Rgb(0x03, 0x0d, 0x14),
+        foreground: Rgb(0xe6, 0xf1, 0xff),
+        accent: Rgb(0x00, 0xc5, 0xff),
+        track: Rgb(0x0b, 0x2a, 0x3a),
+        title: title(b"Exo-OS"),
+        title_len: 6,
+        messages: true,
+        bar_width_pct: 40,
+        bar_position_pct: 60,
+    };
+    pub const MINIMAL: Self = Self {
+        background: Rgb(0, 0, 0),
+        foreground: Rgb(0xc0, 0xc0, 0xc0),
+        accent: Rgb(0xff, 0xff, 0xff),
+        track: Rgb(0x20, 0x20, 0x20),
+        title: [0; TITLE_MAX],
+        title_len: 0,
+        messages: false,
+        bar_width_pct: 25,
+        bar_position_pct: 75,
+    };
+    pub const LIGHT: Self = Self {
+        background: Rgb(0xf4, 0xf6, 0xf8),
+        foreground: Rgb(0x1c, 0x24, 0x2c),
+        accent: Rgb(0x00, 0x73, 0x9b),
+        track: Rgb(0xd0, 0xd8, 0xe0),
+        title: title(b"Exo-OS"),
+        title_len: 6,
+        messages: true,
+        bar_width_pct: 40,
+        bar_position_pct: 60,
+    };
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "exo" => Some(Self::EXO),
+            "minimal" => Some(Self::MINIMAL),
+            "light" => Some(Self::LIGHT),
+            _ => None,
+        }
+    }
+
+    pub fn title(&self) -> &[u8] {
+        &self.title[..self.title_len as usize]
+    }
+
+    /// Truncated to [`TITLE_MAX`] bytes.
+    pub fn set_title(&mut self, text: &[u8]) {
+        let len = text.len().min(TITLE_MAX);
+        self.title = [0; TITLE_MAX];
+        self.title[..len].copy_from_slice(&text[..len]);
+        self.title_len = len as u8;
+    }
+
+    /// Builds a theme from a configuration file; invalid lines are skipped
+    /// (see [`config::first_error`]). A `theme` line resets the settings
+    /// before it.
+    pub fn parse(config: &str) -> Self {
+        let mut out = Self::EXO;
+        for directive in config::directives::<Directive>(config) {
+            match directive {
+                Directive::Base(theme) => out = theme,
+                Directive::Background(rgb) => out.background = rgb,
+                Directive::Foreground(rgb) => out.foreground = rgb,
+                Directive::Accent(rgb) => out.accent = rgb,
+                Directive::Track(rgb) => out.track = rgb,
+                Directive::Title(text, len) => out.set_title(&text[..len]),
+                Directive::Messages(on) => out.messages = on,
+                Directive::BarWidth(pct) => out.bar_width_pct = pct,
+                Directive::BarPosition(pct) => out.bar_position_pct = pct,
+            }
+        }
+        out
+    }
+}
+
+const fn title(text: &[u8]) -> [u8; TITLE_MAX] {
+    let mut out = [0u8; TITLE_MAX];
+    let mut i = 0;
+    while i < text.len() && i < TITLE_MAX {
+        out[i] = text[i];
+        i += 1;
+    }
+    out
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    Syntax,
+    BadValue,
+    UnknownKey,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Directive {
+    Base(Theme),
+    Background(Rgb),
+    Foreground(Rgb),
+    Accent(Rgb),
+    Track(Rgb),
+    Title([u8; TITLE_MAX], usize),
+    Messages(bool),
+    BarWidth(u8),
+    BarPosition(u8),
+}
+
+fn parse_percent(word: &str) -> Option<u8> {
+    let pct = word.strip_suffix('%').unwrap_or(word).parse::<u8>().ok()?;
+    (1..=100).contains(&pct).then_some(pct)
+}
+
+impl<'a> config::Directive<'a> for Directive {
+    type Error = ConfigError;
+
+    fn parse(line: &'a str) -> Result<Self, ConfigError> {
+        let Some((key, value)) = line.split_once(|c: char| c.is_ascii_whitespace()) else {
+            return Err(ConfigError::Syntax);
+        };
+        let value = value.trim();
+        if key == "title" {
+            let text = value.as_bytes();
+            let len = text.len().min(TITLE_MAX);
+            return Ok(Directive::Title(title(text), len));
+        }
+        if value.split_ascii_whitespace().nth(1).is_some() {
+            return Err(ConfigError::Syntax);
+        }
+        let rgb = || Rgb::parse(value).ok_or(ConfigError::BadValue);
+        let pct = || parse_percent(value).ok_or(ConfigError::BadValue);
+        match key {
+            "theme" => Theme::builtin(value)
+                .map(Directive::Base)
+                .ok_or(ConfigError::BadValue),
+            "background" => Ok(Directive::Background(rgb()?)),
+            "foreground" => Ok(Directive::Foreground(rgb()?)),
+            "accent" => Ok(Directive::Accent(rgb()?)),
+            "track" => Ok(Directive::Track(rgb()?)),
+            "messages" => match value {
+                "on" => Ok(Directive::Messages(true)),
+                "off" => Ok(Directive::Messages(false)),
+                _ => Err(ConfigError::BadValue),
+            },
+            "bar_width" => Ok(Directive::BarWidth(pct()?)),
+            "bar_position" => Ok(Directive::BarPosition(pct()?)),
+            _ => Err(ConfigError::UnknownKey),
+        }
+    }
+}
+
+/// `SPLASH_MSG_PROGRESS` payload: `done` of `total` services settled, the
+/// last one being `name`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Milestone {
+    pub done: u16,
+    pub total: u16,
+    /// The service was given up on (timeout, crash) rather than ready.
+    pub failed: bool,
+    name: [u8; MILESTONE_NAME_MAX],
+    name_len: u8,
+}
+
+impl Milestone {
+    pub const HEADER_LEN: usize = 6;
+    pub const MAX_LEN: usize = Self::HEADER_LEN + MILESTONE_NAME_MAX;
+
+    /// `name` is truncated to [`MILESTONE_NAME_MAX`] bytes.
+    pub fn new(done: u16, total: u16, failed: bool, name: &[u8]) -> Self {
+        let len = name.len().min(MILESTONE_NAME_MAX);
+        let mut out = Self {
+            done,
+            total,
+            failed,
+            name: [0; MILESTONE_NAME_MAX],
+            name_len: len as u8,
+        };
+        out.name[..len].copy_from_slice(&name[..len]);
+        out
+    }
+
+    pub fn name(&self) -> &[u8] {
+        &self.name[..self.name_len as usize]
+    }
+
+    /// Completion in thousandths; an empty graph counts as done.
+    pub fn permille(&self) -> u16 {
+        if self.total == 0 {
+            return PROGRESS_FULL;
+        }
+        let done = self.done.min(self.total) as u32;
+        (done * PROGRESS_FULL as u32 / self.total as u32) as u16
+    }
+
+    pub fn encode(&self, out: &mut [u8]) -> Option<usize> {
+        let len = Self::HEADER_LEN + self.name_len as usize;
+        let out = out.get_mut(..len)?;
+        out[..2].copy_from_slice(&self.done.to_le_bytes());
+        out[2..4].copy_from_slice(&self.total.to_le_bytes());
+        out[4] = self.failed as u8;
+        out[5] = self.name_len;
+        out[Self::HEADER_LEN..].copy_from_slice(self.name());
+        Some(len)
+    }
+
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        let header = payload.get(..Self::HEADER_LEN)?;
+        let failed = match header[4] {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        let len = header[5] as usize;
+        if len > MILESTONE_NAME_MAX {
+            return None;
+        }
+        let name = payload.get(Self::HEADER_LEN..Self::HEADER_LEN + len)?;
+        Some(Self::new(
+            u16::from_le_bytes([header[0], header[1]]),
+            u16::from_le_bytes([header[2], header[3]]),
+            failed,
+            name,
+        ))
+    }
+}
+
+/// Bar position: jumps to each milestone would look jerky, so the drawn
+/// value catches up with the target a step per animation tick. It never
+/// moves backwards, even if milestones arrive out of order.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Progress {
+    shown: u16,
+    target: u16,
+}
+
+impl Progress {
+    pub const fn new() -> Self {
+        Self {
+            shown: 0,
+            target: 0,
+        }
+    }
+
+    pub fn shown(&self) -> u16 {
+        self.shown
+    }
+
+    pub fn set_target(&mut self, permille: u16) {
+        self.target = self.target.max(permille.min(PROGRESS_FULL));
+    }
+
+    /// Moves the drawn value at most `step` towards the target; returns
+    /// whether it changed.
+    pub fn tick(&mut self, step: u16) -> bool {
+        let next = self.target.min(self.shown.saturating_add(step.max(1)));
+        let changed = next != self.shown;
+        self.shown = next;
+        changed
+    }
+
+    /// Jumps to the target (final frame before a handoff).
+    pub fn settle(&mut self) {
+        self.shown = self.target;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+/// Screen positions of the splash elements. Text is drawn with the 8x16
+/// console font, `title_scale` times larger for the title.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Layout {
+    pub title_x: u32,
+    pub title_y: u32,
+    pub title_scale: u32,
+    pub bar: Rect,
+    /// Line under the bar, for milestone names.
+    pub message: Rect,
+}
+
+pub const GLYPH_W: u32 = 8;
+pub const GLYPH_H: u32 = 16;
+const BAR_HEIGHT: u32 = 6;
+
+impl Layout {
+    pub fn new(theme: &Theme, width: u32, height: u32) -> Self {
+        let title_scale = if width >= 1024 { 4 } else { 2 };
+        let bar_w = (width * theme.bar_width_pct as u32 / 100).max(1);
+        let bar_y =
+            (height * theme.bar_position_pct as u32 / 100).min(height - BAR_HEIGHT.min(height));
+        let bar = Rect {
+            x: (width - bar_w) / 2,
+            y: bar_y,
+            w: bar_w,
+            h: BAR_HEIGHT.min(height),
+        };
+        let title_w = theme.title().len() as u32 * GLYPH_W * title_scale;
+        let title_h = GLYPH_H * title_scale;
+        Self {
+            title_x: width.saturating_sub(title_w) / 2,
+            title_y: bar_y.saturating_sub(title_h * 2),
+            title_scale,
+            bar,
+            message: Rect {
+                x: 0,
+                y: (bar_y + BAR_HEIGHT + GLYPH_H).min(height.saturating_sub(GLYPH_H)),
+                w: width,
+                h: GLYPH_H.min(height),
+            },
+        }
+    }
+
+    /// Filled part of the bar for `permille`.
+    pub fn bar_fill(&self, permille: u16) -> Rect {
+        Rect {
+            w: (self.bar.w as u64 * permille.min(PROGRESS_FULL) as u64 / PROGRESS_FULL as u64)
+                as u32,
+            ..self.bar
+        }
+    }
+
+    /// Left edge of `len` characters centred on the message line.
+    pub fn message_x(&self, len: usize) -> u32 {
+        self.message.w.saturating_sub(len as u32 * GLYPH_W) / 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{first_error, parse_line};
+
+    fn parse_directive(line: &str) -> Result<Option<Directive>, ConfigError> {
+        parse_line(line)
+    }
+
+    #[test]
+    fn parses_configuration() {
+        let text = "theme light\nbackground #102030\ntitle  My Exo box \nmessages off\n\
+                    bar_width 50%\nbar_position 80\n";
+        assert_eq!(first_error::<Directive>(text), None);
+        let theme = Theme::parse(text);
+        assert_eq!(theme.background, Rgb(0x10, 0x20, 0x30));
+        assert_eq!(theme.accent, Theme::LIGHT.accent);
+        assert_eq!(theme.title(), b"My Exo box");
+        assert!(!theme.messages);
+        assert_eq!((theme.bar_width_pct, theme.bar_position_pct), (50, 80));
+
+        // A later `theme` line starts over from the built-in theme.
+        assert_eq!(
+            Theme::parse("accent #ffffff\ntheme minimal\n"),
+            Theme::MINIMAL
+        );
+        assert_eq!(parse_directive("accent ffffff"), Err(ConfigError::BadValue));
+        assert_eq!(parse_directive("bar_width 0"), Err(ConfigError::BadValue));
+        assert_eq!(
+            parse_directive("theme plymouth"),
+            Err(ConfigError::BadValue)
+        );
+        assert_eq!(parse_directive("messages"), Err(ConfigError::Syntax));
+        assert_eq!(
+            first_error::<Directive>("\nspinner on\n"),
+            Some((2, ConfigError::UnknownKey))
+        );
+    }
+
+    #[test]
+    fn milestone_roundtrip() {
+        let milestone = Milestone::new(5, 17, true, b"network_server");
+        let mut buf = [0u8; Milestone::MAX_LEN];
+        let len = milestone.encode(&mut buf).unwrap();
+        assert_eq!(len, Milestone::HEADER_LEN + 14);
+        assert_eq!(Milestone::decode(&buf[..len]), Some(milestone));
+        assert_eq!(Milestone::decode(&buf[..len - 1]), None);
+        assert_eq!(milestone.permille(), 294);
+        assert_eq!(Milestone::new(0, 0, false, b"").permille(), PROGRESS_FULL);
+
+        buf[4] = 2;
+        assert_eq!(Milestone::decode(&buf[..len]), None);
+    }
+
+    #[test]
+    fn progress_follows_milestones_forward_only() {
+        let mut progress = Progress::new();
+        assert!(!progress.tick(50));
+        progress.set_target(120);
+        assert!(progress.tick(50));
+        assert!(progress.tick(50));
+        assert!(progress.tick(50));
+        assert_eq!(progress.shown(), 120);
+        assert!(!progress.tick(50));
+
+        // A late, smaller milestone does not pull the bar back.
+        progress.set_target(60);
+        assert!(!progress.tick(50));
+        progress.set_target(2000);
+        progress.settle();
+        assert_eq!(progress.shown(), PROGRESS_FULL);
+    }
+
+    #[test]
+    fn layout_stays_on_screen() {
+        let layout = Layout::new(&Theme::EXO, 1280, 800);
+        assert_eq!(
+            layout.bar,
+            Rect {
+                x: 384,
+                y: 480,
+                w: 512,
+                h: 6
+            }
+        );
+        assert_eq!(layout.title_scale, 4);
+        assert_eq!(layout.title_x, (1280 - 6 * 32) / 2);
+        assert_eq!(layout.bar_fill(500).w, 256);
+        assert_eq!(layout.bar_fill(PROGRESS_FULL).w, 512);
+        assert_eq!(layout.message_x(10), 600);
+
+        let mut theme = Theme::MINIMAL;
+        theme.bar_position_pct = 100;
+        let layout = Layout::new(&theme, 320, 200);
+        assert!(layout.bar.y + layout.bar.h <= 200);
+        assert!(layout.message.y + layout.message.h <= 200);
+    }
+}
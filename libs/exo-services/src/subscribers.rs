@@ -0,0 +1,147 @@
+//! Notification endpoints registered with a session daemon.
+//!
+//! A client subscribes with one of its own endpoints and a set of flags
+//! saying which notifications it wants; daemons push notices to every
+//! subscriber carrying the matching flag. One subscription per process.
+
+pub const MAX_SUBSCRIBERS: usize = 32;
+
+/// Subscribe request payload: notification endpoint (u64 LE), flags (u32 LE).
+pub fn encode_subscribe(endpoint: u64, flags: u32, out: &mut [u8]) -> Option<usize> {
+    let out = out.get_mut(..12)?;
+    out[..8].copy_from_slice(&endpoint.to_le_bytes());
+    out[8..].copy_from_slice(&flags.to_le_bytes());
+    Some(12)
+}
+
+pub fn decode_subscribe(payload: &[u8]) -> Option<(u64, u32)> {
+    let payload = payload.get(..12)?;
+    let endpoint = u64::from_le_bytes(payload[..8].try_into().unwrap());
+    let flags = u32::from_le_bytes(payload[8..].try_into().unwrap());
+    Some((endpoint, flags))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SubscribeError {
+    /// Unknown flags, or no flag at all.
+    BadFlags,
+    /// The endpoint is not the subscriber's: its reply endpoint (the PID)
+    /// or one of its own channels.
+    ForeignEndpoint,
+    Full,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Subscriber {
+    pub pid: u32,
+    pub endpoint: u64,
+    pub flags: u32,
+}
+
+/// Subscribed endpoints, one per process.
+pub struct Subscribers {
+    slots: [Option<Subscriber>; MAX_SUBSCRIBERS],
+    /// Flags the daemon knows about.
+    known: u32,
+}
+
+impl Subscribers {
+    pub const fn new(known: u32) -> Self {
+        Self {
+            slots: [None; MAX_SUBSCRIBERS],
+            known,
+        }
+    }
+
+    /// Adds or replaces `pid`'s subscription.
+    pub fn subscribe(&mut self, pid: u32, endpoint: u64, flags: u32) -> Result<(), SubscribeError> {
+        if flags == 0 || flags & !self.known != 0 {
+            return Err(SubscribeError::BadFlags);
+        }
+        if endpoint != pid as u64 && endpoint >> 32 != pid as u64 {
+            return Err(SubscribeError::ForeignEndpoint);
+        }
+        let entry = Subscriber {
+            pid,
+            endpoint,
+            flags,
+        };
+        if let Some(slot) = self.slots.iter_mut().flatten().find(|s| s.pid == pid) {
+            *slot = entry;
+            return Ok(());
+        }
+        let slot = self
+            .slots
+            .iter_mut()
+            .find(|s| s.is_none())
+            .ok_or(SubscribeError::Full)?;
+        *slot = Some(entry);
+        Ok(())
+    }
+
+    /// Returns whether `pid` was subscribed.
+    pub fn unsubscribe(&mut self, pid: u32) -> bool {
+        match self
+            .slots
+            .iter_mut()
+            .find(|s| s.is_some_and(|s| s.pid == pid))
+        {
+            Some(slot) => {
+                *slot = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Subscribers with any of `flags`.
+    pub fn with(&self, flags: u32) -> impl Iterator<Item = Subscriber> + '_ {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(move |s| s.flags & flags != 0)
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: u32 = 1 << 0;
+    const B: u32 = 1 << 1;
+
+    #[test]
+    fn subscribers_own_their_endpoints() {
+        let mut subs = Subscribers::new(A | B);
+        assert_eq!(subs.subscribe(42, 42, A), Ok(()));
+        assert_eq!(
+            subs.subscribe(7, (42 << 32) | 1, B),
+            Err(SubscribeError::ForeignEndpoint)
+        );
+        assert_eq!(subs.subscribe(7, 7, 0), Err(SubscribeError::BadFlags));
+        assert_eq!(subs.subscribe(7, 7, 1 << 5), Err(SubscribeError::BadFlags));
+        assert_eq!(subs.subscribe(7, (7 << 32) | 3, B), Ok(()));
+        // Re-subscribing replaces the entry.
+        assert_eq!(subs.subscribe(42, 42, A | B), Ok(()));
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs.with(A).count(), 1);
+        assert_eq!(subs.with(B).count(), 2);
+        assert!(subs.unsubscribe(42));
+        assert!(!subs.unsubscribe(42));
+
+        let mut subs = Subscribers::new(A);
+        for pid in 1..=MAX_SUBSCRIBERS as u32 {
+            subs.subscribe(pid, pid as u64, A).unwrap();
+        }
+        assert_eq!(subs.subscribe(99, 99, A), Err(SubscribeError::Full));
+    }
+}
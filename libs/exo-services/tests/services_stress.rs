@@ -3,3 +3,199 @@ fn service_ports_stress() {
     assert_eq!(exo_services::SERVICE_PORTS.len(), 1);
     assert_ne!(exo_services::services_stress_signature(100_000), 0);
 }
+
+#[test]
+fn prewarm_pool_stress() {
+    use exo_services::prewarm::{Pool, PoolAction, MAX_CLASSES};
+
+    let mut pool = Pool::new();
+    while pool.add_class(2).is_some() {}
+    let mut ready = [0u8; MAX_CLASSES];
+    for step in 0..10_000u64 {
+        let free = ((step * 37) % 400) as u32;
+        match pool.next_action(free) {
+            PoolAction::Spawn(i) => {
+                pool.note_spawned(i);
+                ready[i] += 1;
+            }
+            PoolAction::Evict(i) => {
+                pool.note_gone(i);
+                ready[i] -= 1;
+            }
+            PoolAction::Idle => {}
+        }
+        let class = (step % MAX_CLASSES as u64) as usize;
+        if step % 7 == 0 && ready[class] > 0 {
+            pool.note_gone(class);
+            pool.note_launch(class, step);
+            ready[class] -= 1;
+        }
+        assert!((0..MAX_CLASSES).all(|i| pool.ready(i) == ready[i] && ready[i] <= 2));
+    }
+}
+
+#[test]
+fn pressure_policy_stress() {
+    use exo_services::pressure::{Level, Policy, PressureReport, KILL_COOLDOWN_MS};
+
+    let mut policy = Policy::new();
+    let mut last_kill = None;
+    for step in 0..100_000u64 {
+        let now = step * 100;
+        let report = PressureReport {
+            level: ((step / 40) % 4) as u32,
+            free_pages: (step * 13) % 60,
+            total_pages: 1000,
+            ..PressureReport::default()
+        };
+        let decision = policy.observe(&report, now);
+        if decision.trim.is_some() {
+            assert!(report.level() >= Level::Medium);
+        }
+        if decision.kill {
+            assert_eq!(report.level(), Level::Critical);
+            if let Some(at) = last_kill {
+                assert!(now - at >= KILL_COOLDOWN_MS);
+            }
+            last_kill = Some(now);
+        }
+    }
+    assert!(policy.kills > 0 && policy.trims > policy.kills);
+}
+
+#[test]
+fn freezer_policy_stress() {
+    use exo_services::freezer::{Action, Config, Policy, Visibility, MAX_APPS};
+
+    let mut policy = Policy::new(Config::parse(
+        "exempt keep\nbackground 500\nminimized 200\n",
+    ));
+    let mut frozen = [false; MAX_APPS + 1];
+    for step in 0..50_000u64 {
+        let now = step * 10;
+        let pid = (step * 7 % MAX_APPS as u64) as u32 + 1;
+        let name = if pid.is_multiple_of(9) { "keep" } else { "app" };
+        let visibility = Visibility::from_u8((step / 13 % 4) as u8).unwrap();
+        policy.report(pid, name, visibility, now).unwrap();
+        if step % 5 == 0 {
+            policy.set_audio(pid, step % 10 == 0, now);
+        }
+        if step % 97 == 0 {
+            policy.forget(pid);
+            frozen[pid as usize] = false;
+        }
+        while let Some(action) = policy.next_action(now) {
+            match action {
+                Action::Freeze(p) => {
+                    assert!(!frozen[p as usize] && !p.is_multiple_of(9));
+                    frozen[p as usize] = true;
+                }
+                Action::Thaw(p) => {
+                    assert!(frozen[p as usize]);
+                    frozen[p as usize] = false;
+                }
+            }
+        }
+        assert_eq!(policy.frozen(), frozen.iter().filter(|&&f| f).count());
+    }
+    assert!(policy.freezes > 0 && policy.thaws > 0);
+}
+
+#[test]
+fn osk_taps_stress() {
+    use exo_services::osk::{Keyboard, Output};
+
+    for (w, h) in [(320, 240), (800, 600), (1080, 1920), (3840, 2160)] {
+        let mut kb = Keyboard::new(w, h);
+        kb.show();
+        let panel = kb.panel();
+        for step in 0..20_000u32 {
+            if step % 1_000 == 0 {
+                kb.activate((step / 1_000 % 14) as u16);
+            }
+            let x = step.wrapping_mul(7_919) % w;
+            let y = step.wrapping_mul(104_729) % h;
+            let inside = y >= panel.y;
+            assert_eq!(kb.key_at(x, y).is_some(), inside, "{w}x{h} at {x},{y}");
+            match kb.tap(x, y) {
+                Some(Output::Hide) => {
+                    assert!(!kb.is_shown());
+                    kb.show();
+                }
+                Some(Output::Commit(c)) => assert!(c.is_ascii_graphic() || c == ' '),
+                Some(Output::Key(_)) => assert!(inside),
+                None => {}
+            }
+        }
+    }
+}
+
+#[test]
+fn a11y_reader_stress() {
+    use exo_services::a11y::{
+        Event, EventKind, NodeRecord, Reader, Role, Silent, MAX_NODES, NO_PARENT,
+    };
+
+    let mut reader = Reader::new();
+    let names = [
+        "",
+        "OK",
+        "Cancel",
+        "a somewhat longer label for the row",
+        "é",
+    ];
+    // Next index each application sends, with an occasional gap.
+    let mut next = [0u16; 7];
+    let mut seed = 0x9e37_79b9u32;
+    for step in 0..50_000u32 {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        let pid = 1 + (seed >> 24) % 6;
+        if step % 97 == 0 {
+            reader.begin(pid, step);
+            next[pid as usize] = 0;
+            continue;
+        }
+        if seed.is_multiple_of(31) {
+            reader.forget(pid);
+            next[pid as usize] = 0;
+            continue;
+        }
+        let index = (seed >> 8) as u16 % (MAX_NODES as u16 + 8);
+        if seed & 1 == 0 {
+            let index = if seed.is_multiple_of(50) {
+                index
+            } else {
+                next[pid as usize]
+            };
+            next[pid as usize] = index + 1;
+            let parent = match index {
+                0 => NO_PARENT,
+                i => (seed >> 3) as u16 % i,
+            };
+            reader.node(
+                pid,
+                &NodeRecord {
+                    index,
+                    parent,
+                    role: Role::from_u8((seed >> 4) as u8 % 9).unwrap(),
+                    state: (seed >> 16) as u8,
+                    bounds: (0, 0, 1, 1),
+                    name: names[(seed >> 20) as usize % names.len()],
+                    value: None,
+                },
+            );
+        } else {
+            let kind = match (seed >> 2) % 4 {
+                0 => EventKind::Focus,
+                1 => EventKind::NameChanged,
+                2 => EventKind::ValueChanged,
+                _ => EventKind::StateChanged,
+            };
+            reader.event(pid, Event { kind, node: index }, &mut Silent);
+        }
+        assert!(reader.apps() <= exo_services::a11y::MAX_APPS);
+    }
+    assert!(reader.spoken > 0 && reader.dropped > 0);
+}
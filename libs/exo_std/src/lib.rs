@@ -0,0 +1,11 @@
+#![no_std]
+
+//! Userspace standard types for Exo-OS services.
+//!
+//! - [`time`]: [`time::Instant`] (monotonic) and [`time::SystemTime`]
+//!   (realtime) over the kernel clocks, civil dates and RFC 3339;
+//! - [`tz`]: time zones from a subset of the tz database.
+
+mod sys;
+pub mod time;
+pub mod tz;
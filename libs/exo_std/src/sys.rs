@@ -0,0 +1,55 @@
+//! The few system calls this crate makes.
+
+pub const SYS_READ: u64 = 0;
+pub const SYS_OPEN: u64 = 2;
+pub const SYS_CLOSE: u64 = 3;
+pub const SYS_CLOCK_GETTIME: u64 = 228;
+pub const O_RDONLY: u64 = 0;
+
+#[inline(always)]
+pub unsafe fn syscall3(nr: u64, a1: u64, a2: u64, a3: u64) -> i64 {
+    let ret: i64;
+    // SAFETY: the caller is responsible for passing kernel-valid arguments.
+    unsafe {
+        core::arch::asm!(
+            "syscall",
+            in("rax") nr,
+            in("rdi") a1,
+            in("rsi") a2,
+            in("rdx") a3,
+            lateout("rax") ret,
+            out("rcx") _,
+            out("r11") _,
+            options(nostack),
+        );
+    }
+    ret
+}
+
+/// Reads a whole file into `buf`; `None` if it is missing or empty.
+pub fn read_file(path: &[u8], buf: &mut [u8]) -> Option<usize> {
+    // SAFETY: `path` is NUL-terminated by the caller.
+    let fd = unsafe { syscall3(SYS_OPEN, path.as_ptr() as u64, O_RDONLY, 0) };
+    if fd < 0 {
+        return None;
+    }
+    let mut len = 0;
+    while len < buf.len() {
+        // SAFETY: the write is bounded by the end of `buf`.
+        let n = unsafe {
+            syscall3(
+                SYS_READ,
+                fd as u64,
+                buf[len..].as_mut_ptr() as u64,
+                (buf.len() - len) as u64,
+            )
+        };
+        if n <= 0 {
+            break;
+        }
+        len += n as usize;
+    }
+    // SAFETY: closes the descriptor opened above.
+    let _ = unsafe { syscall3(SYS_CLOSE, fd as u64, 0, 0) };
+    (len > 0).then_some(len)
+}
@@ -0,0 +1,798 @@
+//! Clocks and dates.
+//!
+//! [`Instant`] reads `CLOCK_MONOTONIC`: it never goes back and is what
+//! timeouts and elapsed times are measured with. [`SystemTime`] reads
+//! `CLOCK_REALTIME`, the kernel's monotonic clock plus the offset set from
+//! the RTC or NTP: it is the time shown to people and written in logs, and
+//! can jump when the clock is set.
+//!
+//! [`DateTime`] is a civil date and time at a fixed UTC offset, with
+//! RFC 3339 formatting (`2026-10-16T09:30:00.250+02:00`) and parsing. Zone
+//! rules, summer time included, are in [`crate::tz`].
+
+use core::fmt;
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+
+pub use core::time::Duration;
+
+use crate::sys;
+
+/// `clock_gettime` clocks.
+pub const CLOCK_REALTIME: u64 = 0;
+pub const CLOCK_MONOTONIC: u64 = 1;
+/// Like `CLOCK_MONOTONIC`, counting the time asleep.
+pub const CLOCK_BOOTTIME: u64 = 7;
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+const SECS_PER_DAY: i64 = 86_400;
+
+#[repr(C)]
+#[derive(Default)]
+struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+/// Seconds and nanoseconds of `clock`; `None` if it cannot be read.
+fn clock_gettime(clock: u64) -> Option<(i64, u32)> {
+    let mut ts = Timespec::default();
+    // SAFETY: the kernel writes one `Timespec` at the given address.
+    let rc = unsafe {
+        sys::syscall3(
+            sys::SYS_CLOCK_GETTIME,
+            clock,
+            &mut ts as *mut Timespec as u64,
+            0,
+        )
+    };
+    if rc != 0 || !(0..NANOS_PER_SEC as i64).contains(&ts.tv_nsec) {
+        return None;
+    }
+    Some((ts.tv_sec, ts.tv_nsec as u32))
+}
+
+/// A point on the monotonic clock, counted from boot.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Instant(Duration);
+
+impl Instant {
+    /// Now; the boot instant if the clock cannot be read.
+    pub fn now() -> Self {
+        Self::read(CLOCK_MONOTONIC)
+    }
+
+    /// Now on `CLOCK_BOOTTIME`, for deadlines meant to include sleeps.
+    /// Instants of the two clocks must not be compared.
+    pub fn now_boottime() -> Self {
+        Self::read(CLOCK_BOOTTIME)
+    }
+
+    fn read(clock: u64) -> Self {
+        match clock_gettime(clock) {
+            Some((secs, nanos)) if secs >= 0 => Self(Duration::new(secs as u64, nanos)),
+            _ => Self::default(),
+        }
+    }
+
+    /// The instant `nanos` after boot, as exchanged over IPC.
+    pub const fn from_nanos(nanos: u64) -> Self {
+        Self(Duration::from_nanos(nanos))
+    }
+
+    /// Nanoseconds since boot, saturating after 584 years.
+    pub const fn as_nanos(&self) -> u64 {
+        let nanos = self.0.as_nanos();
+        if nanos > u64::MAX as u128 {
+            u64::MAX
+        } else {
+            nanos as u64
+        }
+    }
+
+    /// Time from `earlier` to `self`; zero if `earlier` is later.
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+        self.0.checked_sub(earlier.0)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Self::now().duration_since(*self)
+    }
+
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_add(duration).map(Self)
+    }
+
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_sub(duration).map(Self)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, duration: Duration) -> Instant {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, duration: Duration) -> Instant {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, earlier: Instant) -> Duration {
+        self.duration_since(earlier)
+    }
+}
+
+/// A point in civil time: seconds and nanoseconds since the Unix epoch,
+/// leap seconds not counted.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SystemTime {
+    secs: i64,
+    nanos: u32,
+}
+
+pub const UNIX_EPOCH: SystemTime = SystemTime { secs: 0, nanos: 0 };
+
+/// `later.duration_since(earlier)` with `later` actually earlier: how much.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SystemTimeError(Duration);
+
+impl SystemTimeError {
+    pub fn duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl SystemTime {
+    /// Now; `None` if the clock cannot be read or was never set (it counts
+    /// from the epoch until the RTC or NTP sets it).
+    pub fn now() -> Option<Self> {
+        match clock_gettime(CLOCK_REALTIME)? {
+            (secs, nanos) if secs > 0 => Some(Self { secs, nanos }),
+            _ => None,
+        }
+    }
+
+    /// `secs` after the epoch plus `nanos`, carried into the seconds.
+    pub const fn from_unix(secs: i64, nanos: u32) -> Self {
+        Self {
+            secs: secs + (nanos / NANOS_PER_SEC) as i64,
+            nanos: nanos % NANOS_PER_SEC,
+        }
+    }
+
+    /// Whole seconds since the epoch, rounded down (negative before).
+    pub const fn unix_secs(&self) -> i64 {
+        self.secs
+    }
+
+    pub const fn subsec_nanos(&self) -> u32 {
+        self.nanos
+    }
+
+    fn total_nanos(&self) -> i128 {
+        self.secs as i128 * NANOS_PER_SEC as i128 + self.nanos as i128
+    }
+
+    fn from_total_nanos(total: i128) -> Option<Self> {
+        let secs = total.div_euclid(NANOS_PER_SEC as i128);
+        Some(Self {
+            secs: i64::try_from(secs).ok()?,
+            nanos: total.rem_euclid(NANOS_PER_SEC as i128) as u32,
+        })
+    }
+
+    /// Time from `earlier` to `self`, or by how much `earlier` is later.
+    pub fn duration_since(&self, earlier: SystemTime) -> Result<Duration, SystemTimeError> {
+        let diff = self.total_nanos() - earlier.total_nanos();
+        let duration = |nanos: i128| {
+            let nanos = nanos.unsigned_abs();
+            Duration::new(
+                (nanos / NANOS_PER_SEC as u128) as u64,
+                (nanos % NANOS_PER_SEC as u128) as u32,
+            )
+        };
+        if diff >= 0 {
+            Ok(duration(diff))
+        } else {
+            Err(SystemTimeError(duration(diff)))
+        }
+    }
+
+    pub fn checked_add(&self, duration: Duration) -> Option<SystemTime> {
+        let nanos = i128::try_from(duration.as_nanos()).ok()?;
+        Self::from_total_nanos(self.total_nanos().checked_add(nanos)?)
+    }
+
+    pub fn checked_sub(&self, duration: Duration) -> Option<SystemTime> {
+        let nanos = i128::try_from(duration.as_nanos()).ok()?;
+        Self::from_total_nanos(self.total_nanos().checked_sub(nanos)?)
+    }
+
+    pub fn to_utc(&self) -> DateTime {
+        DateTime::new(*self, 0)
+    }
+}
+
+impl Add<Duration> for SystemTime {
+    type Output = SystemTime;
+
+    fn add(self, duration: Duration) -> SystemTime {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to system time")
+    }
+}
+
+impl AddAssign<Duration> for SystemTime {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl Sub<Duration> for SystemTime {
+    type Output = SystemTime;
+
+    fn sub(self, duration: Duration) -> SystemTime {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from system time")
+    }
+}
+
+impl SubAssign<Duration> for SystemTime {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+pub const fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Days in `month` (1 to 12) of `year`.
+pub const fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days from 1970-01-01 to the given date of the proleptic Gregorian
+/// calendar (Howard Hinnant's `days_from_civil`).
+pub const fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Inverse of [`days_from_civil`]: (year, month, day).
+pub const fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// Day of the week of a day count from the epoch, 0 for Sunday.
+pub const fn weekday_from_days(days: i64) -> u8 {
+    // 1970-01-01 was a Thursday.
+    (days + 4).rem_euclid(7) as u8
+}
+
+/// Longest RFC 3339 text: `YYYY-MM-DDTHH:MM:SS.nnnnnnnnn+HH:MM`.
+pub const RFC3339_MAX: usize = 35;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseError {
+    /// Not `YYYY-MM-DDTHH:MM:SS[.frac](Z|±HH:MM)`.
+    Syntax,
+    /// A field past its range, such as February 30th or 25:00.
+    OutOfRange,
+}
+
+/// A civil date and time at a fixed offset from UTC.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DateTime {
+    pub year: i64,
+    /// 1 to 12.
+    pub month: u8,
+    /// 1 to 31.
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    /// 0 to 60: a leap second read from text is kept.
+    pub second: u8,
+    pub nanosecond: u32,
+    /// Seconds east of UTC.
+    pub offset: i32,
+}
+
+impl DateTime {
+    /// `time` on the wall clocks `offset` seconds east of UTC.
+    pub fn new(time: SystemTime, offset: i32) -> Self {
+        let local = time.secs.saturating_add(offset as i64);
+        let (year, month, day) = civil_from_days(local.div_euclid(SECS_PER_DAY));
+        let secs = local.rem_euclid(SECS_PER_DAY) as u32;
+        Self {
+            year,
+            month,
+            day,
+            hour: (secs / 3600) as u8,
+            minute: (secs / 60 % 60) as u8,
+            second: (secs % 60) as u8,
+            nanosecond: time.nanos,
+            offset,
+        }
+    }
+
+    /// Seconds since the epoch of the wall clock time, ignoring the offset.
+    pub(crate) fn local_secs(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * SECS_PER_DAY
+            + self.hour as i64 * 3600
+            + self.minute as i64 * 60
+            + self.second as i64
+    }
+
+    pub fn to_system_time(&self) -> SystemTime {
+        SystemTime::from_unix(self.local_secs() - self.offset as i64, self.nanosecond)
+    }
+
+    /// Day of the week, 0 for Sunday.
+    pub fn weekday(&self) -> u8 {
+        weekday_from_days(days_from_civil(self.year, self.month, self.day))
+    }
+
+    /// Writes the RFC 3339 form into `out`; `None` if it does not fit, or
+    /// the year or offset cannot be written (years 0 to 9999, whole
+    /// minutes). The fraction has 3, 6 or 9 digits, and none when zero.
+    pub fn format_rfc3339(&self, out: &mut [u8]) -> Option<usize> {
+        if !(0..=9999).contains(&self.year) || self.offset % 60 != 0 {
+            return None;
+        }
+        let mut text = Text::new();
+        text.number(self.year as u32, 4);
+        text.push(b"-");
+        text.number(self.month as u32, 2);
+        text.push(b"-");
+        text.number(self.day as u32, 2);
+        text.push(b"T");
+        text.number(self.hour as u32, 2);
+        text.push(b":");
+        text.number(self.minute as u32, 2);
+        text.push(b":");
+        text.number(self.second as u32, 2);
+        let fraction = match self.nanosecond {
+            0 => 0,
+            n if n % 1_000_000 == 0 => 3,
+            n if n % 1_000 == 0 => 6,
+            _ => 9,
+        };
+        if fraction > 0 {
+            text.push(b".");
+            text.number(self.nanosecond / 10u32.pow(9 - fraction), fraction as usize);
+        }
+        let minutes = self.offset.unsigned_abs() / 60;
+        match self.offset {
+            0 => text.push(b"Z"),
+            offset => {
+                text.push(if offset < 0 { b"-" } else { b"+" });
+                text.number(minutes / 60, 2);
+                text.push(b":");
+                text.number(minutes % 60, 2);
+            }
+        }
+        let text = &text.bytes[..text.len];
+        out.get_mut(..text.len())?.copy_from_slice(text);
+        Some(text.len())
+    }
+
+    /// Parses `YYYY-MM-DDTHH:MM:SS[.frac](Z|±HH:MM)`; `t`, `z` and a space
+    /// for the `T` are accepted, fraction digits past nanoseconds dropped.
+    pub fn parse_rfc3339(text: &str) -> Result<Self, ParseError> {
+        let bytes = text.as_bytes();
+        let year = digits(bytes, 0, 4)?;
+        separator(bytes, 4, b"-")?;
+        let month = digits(bytes, 5, 2)?;
+        separator(bytes, 7, b"-")?;
+        let day = digits(bytes, 8, 2)?;
+        separator(bytes, 10, b"Tt ")?;
+        let hour = digits(bytes, 11, 2)?;
+        separator(bytes, 13, b":")?;
+        let minute = digits(bytes, 14, 2)?;
+        separator(bytes, 16, b":")?;
+        let second = digits(bytes, 17, 2)?;
+
+        let mut at = 19;
+        let mut nanosecond = 0u32;
+        if bytes.get(at) == Some(&b'.') {
+            at += 1;
+            let start = at;
+            while let Some(b @ b'0'..=b'9') = bytes.get(at) {
+                if at - start < 9 {
+                    nanosecond = nanosecond * 10 + (b - b'0') as u32;
+                }
+                at += 1;
+            }
+            match at - start {
+                0 => return Err(ParseError::Syntax),
+                n if n < 9 => nanosecond *= 10u32.pow(9 - n as u32),
+                _ => {}
+            }
+        }
+
+        let offset = match &bytes[at..] {
+            [b'Z' | b'z'] => 0,
+            [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+                let hours = digits(bytes, at + 1, 2)?;
+                let minutes = digits(bytes, at + 4, 2)?;
+                if hours > 23 || minutes > 59 {
+                    return Err(ParseError::OutOfRange);
+                }
+                let offset = (hours * 3600 + minutes * 60) as i32;
+                if *sign == b'-' {
+                    -offset
+                } else {
+                    offset
+                }
+            }
+            _ => return Err(ParseError::Syntax),
+        };
+
+        let year = year as i64;
+        if !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month as u8) as u32
+            || hour > 23
+            || minute > 59
+            || second > 60
+        {
+            return Err(ParseError::OutOfRange);
+        }
+        Ok(Self {
+            year,
+            month: month as u8,
+            day: day as u8,
+            hour: hour as u8,
+            minute: minute as u8,
+            second: second as u8,
+            nanosecond,
+            offset,
+        })
+    }
+}
+
+/// The `width` decimal digits at `at`.
+fn digits(bytes: &[u8], at: usize, width: usize) -> Result<u32, ParseError> {
+    let field = bytes.get(at..at + width).ok_or(ParseError::Syntax)?;
+    field.iter().try_fold(0, |value, &b| match b {
+        b'0'..=b'9' => Ok(value * 10 + (b - b'0') as u32),
+        _ => Err(ParseError::Syntax),
+    })
+}
+
+fn separator(bytes: &[u8], at: usize, allowed: &[u8]) -> Result<(), ParseError> {
+    match bytes.get(at) {
+        Some(b) if allowed.contains(b) => Ok(()),
+        _ => Err(ParseError::Syntax),
+    }
+}
+
+/// RFC 3339 text being written.
+struct Text {
+    bytes: [u8; RFC3339_MAX],
+    len: usize,
+}
+
+impl Text {
+    fn new() -> Self {
+        Self {
+            bytes: [0; RFC3339_MAX],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.bytes[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    /// `value` in `width` digits, zero-padded.
+    fn number(&mut self, mut value: u32, width: usize) {
+        for i in (0..width).rev() {
+            self.bytes[self.len + i] = b'0' + (value % 10) as u8;
+            value /= 10;
+        }
+        self.len += width;
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut text = [0u8; RFC3339_MAX];
+        let len = self.format_rfc3339(&mut text).ok_or(fmt::Error)?;
+        // The text is ASCII.
+        f.write_str(core::str::from_utf8(&text[..len]).map_err(|_| fmt::Error)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(time: &DateTime) -> ([u8; RFC3339_MAX], usize) {
+        let mut out = [0u8; RFC3339_MAX];
+        let len = time.format_rfc3339(&mut out).unwrap();
+        (out, len)
+    }
+
+    fn roundtrip(text: &str) {
+        let (out, len) = format(&DateTime::parse_rfc3339(text).unwrap());
+        assert_eq!(core::str::from_utf8(&out[..len]).unwrap(), text);
+    }
+
+    #[test]
+    fn converts_civil_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        for days in (-800_000..800_000).step_by(997) {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+            assert!(day >= 1 && day <= days_in_month(year, month));
+        }
+        assert!(is_leap_year(2000) && is_leap_year(2028));
+        assert!(!is_leap_year(1900) && !is_leap_year(2026));
+        // 2026-10-16 is a Friday.
+        assert_eq!(weekday_from_days(days_from_civil(2026, 10, 16)), 5);
+    }
+
+    #[test]
+    fn formats_and_parses_rfc3339() {
+        roundtrip("2026-10-16T09:30:00Z");
+        roundtrip("2026-10-16T09:30:00.250+02:00");
+        roundtrip("1969-12-31T23:59:59.000001-05:30");
+        roundtrip("0001-01-01T00:00:00.000000001Z");
+
+        let time = SystemTime::from_unix(1_792_143_000, 250_000_000);
+        let (out, len) = format(&DateTime::new(time, 7200));
+        assert_eq!(&out[..len], b"2026-10-16T11:30:00.250+02:00");
+        let parsed = DateTime::parse_rfc3339("2026-10-16t09:30:00.25z").unwrap();
+        assert_eq!(parsed.to_system_time(), time);
+        assert_eq!(parsed.weekday(), 5);
+        let parsed = DateTime::parse_rfc3339("2026-10-16 11:30:00.2500000009+02:00").unwrap();
+        assert_eq!(parsed.to_system_time(), time);
+        // A leap second reads as the next second.
+        let leap = DateTime::parse_rfc3339("2016-12-31T23:59:60Z").unwrap();
+        assert_eq!(leap.to_system_time().to_utc().year, 2017);
+
+        let err = |text| DateTime::parse_rfc3339(text).unwrap_err();
+        assert_eq!(err("2026-10-16"), ParseError::Syntax);
+        assert_eq!(err("2026-10-16T09:30:00"), ParseError::Syntax);
+        assert_eq!(err("2026-10-16T09:30:00.Z"), ParseError::Syntax);
+        assert_eq!(err("2026-10-16T09:30:00+0200"), ParseError::Syntax);
+        assert_eq!(err("2026/10/16T09:30:00Z"), ParseError::Syntax);
+        assert_eq!(err("2026-1a-16T09:30:00Z"), ParseError::Syntax);
+        assert_eq!(err("2026-02-29T09:30:00Z"), ParseError::OutOfRange);
+        assert_eq!(err("2026-13-01T09:30:00Z"), ParseError::OutOfRange);
+        assert_eq!(err("2026-10-16T24:00:00Z"), ParseError::OutOfRange);
+        assert_eq!(err("2026-10-16T09:30:00+24:00"), ParseError::OutOfRange);
+
+        let mut short = [0u8; 10];
+        assert_eq!(time.to_utc().format_rfc3339(&mut short), None);
+        let far = DateTime::new(SystemTime::from_unix(300_000_000_000, 0), 0);
+        assert_eq!(far.format_rfc3339(&mut [0; RFC3339_MAX]), None);
+    }
+
+    #[test]
+    fn handles_leap_years() {
+        for (year, leap) in [(2024, true), (2000, true), (1900, false), (2100, false)] {
+            assert_eq!(is_leap_year(year), leap, "{year}");
+            assert_eq!(days_in_month(year, 2), if leap { 29 } else { 28 });
+            let days = days_from_civil(year + 1, 1, 1) - days_from_civil(year, 1, 1);
+            assert_eq!(days, if leap { 366 } else { 365 });
+        }
+        roundtrip("2024-02-29T12:00:00Z");
+        roundtrip("2000-02-29T00:00:00+14:00");
+        let err = |text| DateTime::parse_rfc3339(text).unwrap_err();
+        assert_eq!(err("2100-02-29T00:00:00Z"), ParseError::OutOfRange);
+        assert_eq!(err("1900-02-29T00:00:00Z"), ParseError::OutOfRange);
+        assert_eq!(err("2024-02-30T00:00:00Z"), ParseError::OutOfRange);
+
+        let eve = DateTime::parse_rfc3339("2024-02-28T23:59:59Z")
+            .unwrap()
+            .to_system_time();
+        let next = (eve + Duration::from_secs(1)).to_utc();
+        assert_eq!((next.month, next.day, next.hour), (2, 29, 0));
+        let next = (eve + Duration::from_secs(86_401)).to_utc();
+        assert_eq!((next.month, next.day), (3, 1));
+        let century = DateTime::parse_rfc3339("2100-02-28T23:59:59Z")
+            .unwrap()
+            .to_system_time();
+        let next = (century + Duration::from_secs(1)).to_utc();
+        assert_eq!((next.month, next.day), (3, 1));
+        // 2028-02-29 is a Tuesday.
+        assert_eq!(
+            DateTime::parse_rfc3339("2028-02-29T08:00:00Z")
+                .unwrap()
+                .weekday(),
+            2
+        );
+    }
+
+    #[test]
+    fn keeps_negative_offsets() {
+        let west = DateTime::parse_rfc3339("2026-01-01T00:30:00-01:00").unwrap();
+        assert_eq!(west.offset, -3600);
+        let utc = west.to_system_time().to_utc();
+        assert_eq!((utc.year, utc.month, utc.day, utc.hour), (2026, 1, 1, 1));
+
+        // Behind UTC, the wall clock is still on the previous day or year.
+        let (out, len) = format(&DateTime::new(UNIX_EPOCH, -3600));
+        assert_eq!(&out[..len], b"1969-12-31T23:00:00-01:00");
+        let march = DateTime::parse_rfc3339("2024-03-01T00:30:00Z")
+            .unwrap()
+            .to_system_time();
+        let (out, len) = format(&DateTime::new(march, -3600));
+        assert_eq!(&out[..len], b"2024-02-29T23:30:00-01:00");
+        roundtrip("2026-10-16T09:30:00-09:30");
+        roundtrip("2026-10-16T09:30:00-12:00");
+
+        // `-00:00` (offset unknown) is UTC, written back as `Z`.
+        let unknown = DateTime::parse_rfc3339("2026-10-16T09:30:00-00:00").unwrap();
+        assert_eq!(unknown.offset, 0);
+        assert_eq!(
+            unknown,
+            DateTime::parse_rfc3339("2026-10-16T09:30:00Z").unwrap()
+        );
+        // Offsets not in whole minutes cannot be written.
+        let odd = DateTime::new(UNIX_EPOCH, -30);
+        assert_eq!(odd.format_rfc3339(&mut [0; RFC3339_MAX]), None);
+        assert_eq!(odd.to_system_time(), UNIX_EPOCH);
+    }
+
+    #[test]
+    fn roundtrips_through_system_time_and_text() {
+        let offsets = [
+            -12 * 3600,
+            -(9 * 3600 + 1800),
+            -60,
+            0,
+            5 * 3600 + 2700,
+            14 * 3600,
+        ];
+        let nanos = [0, 1, 999_999_999, 250_000_000, 123_456_000];
+        let mut secs = -2_000_000_000i64;
+        let mut i = 0;
+        while secs < 8_000_000_000 {
+            let time = SystemTime::from_unix(secs, nanos[i % nanos.len()]);
+            let offset = offsets[i % offsets.len()];
+            let local = DateTime::new(time, offset);
+            assert_eq!(local.to_system_time(), time);
+            let (out, len) = format(&local);
+            let text = core::str::from_utf8(&out[..len]).unwrap();
+            assert_eq!(DateTime::parse_rfc3339(text), Ok(local), "{text}");
+            secs += 7_777_777;
+            i += 1;
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_rfc3339() {
+        let err = |text| DateTime::parse_rfc3339(text).unwrap_err();
+        for text in [
+            "",
+            "2026",
+            "2026-10-16T09:30",
+            "2026-10-16T09:30:0Z",
+            "2026-10-16X09:30:00Z",
+            "2026-10-16T09.30.00Z",
+            "-2026-10-16T09:30:00Z",
+            "+2026-10-16T09:30:00Z",
+            "2026-10-16T09:30:00ZZ",
+            "2026-10-16T09:30:00Z ",
+            " 2026-10-16T09:30:00Z",
+            "2026-10-16T09:30:00+02:00Z",
+            "2026-10-16T09:30:00+02",
+            "2026-10-16T09:30:00+2:00",
+            "2026-10-16T09:30:00+02:0a",
+            "2026-10-16T09:30:00UTC",
+            "2026-10-16T09:30:00.5",
+            "2026-10-16T09:30:00.+02:00",
+            "2026-10-16T09:30:00,5Z",
+            "2026-10-16T09:30:00.5.5Z",
+            "2026-10-16T09:30:00\u{e9}",
+            "\u{662}026-10-16T09:30:00Z",
+        ] {
+            assert_eq!(err(text), ParseError::Syntax, "{text:?}");
+        }
+        for text in [
+            "0000-00-16T09:30:00Z",
+            "2026-00-16T09:30:00Z",
+            "2026-10-00T09:30:00Z",
+            "2026-04-31T09:30:00Z",
+            "2026-10-32T09:30:00Z",
+            "2026-10-16T09:60:00Z",
+            "2026-10-16T09:30:61Z",
+            "2026-10-16T09:30:00+02:60",
+            "2026-10-16T09:30:00-99:00",
+        ] {
+            assert_eq!(err(text), ParseError::OutOfRange, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn does_duration_arithmetic() {
+        let start = Instant::from_nanos(5_000_000_000);
+        let later = start + Duration::from_millis(1500);
+        assert_eq!(later.as_nanos(), 6_500_000_000);
+        assert_eq!(later - start, Duration::from_millis(1500));
+        assert_eq!(start - later, Duration::ZERO);
+        assert_eq!(start.checked_duration_since(later), None);
+        assert_eq!(start.checked_sub(Duration::from_secs(6)), None);
+        let mut moving = later;
+        moving -= Duration::from_millis(500);
+        assert_eq!(moving.as_nanos(), 6_000_000_000);
+
+        let before = SystemTime::from_unix(-1, 1_500_000_000);
+        assert_eq!(
+            (before.unix_secs(), before.subsec_nanos()),
+            (0, 500_000_000)
+        );
+        let earlier = UNIX_EPOCH - Duration::from_millis(250);
+        assert_eq!(
+            (earlier.unix_secs(), earlier.subsec_nanos()),
+            (-1, 750_000_000)
+        );
+        assert_eq!(
+            before.duration_since(earlier),
+            Ok(Duration::from_millis(750))
+        );
+        let err = earlier.duration_since(before).unwrap_err();
+        assert_eq!(err.duration(), Duration::from_millis(750));
+        assert_eq!(
+            SystemTime::from_unix(i64::MAX, 0).checked_add(Duration::from_secs(1)),
+            None
+        );
+        let mut time = UNIX_EPOCH;
+        time += Duration::from_secs(86_400 * 365);
+        assert_eq!(time.to_utc().year, 1971);
+    }
+}
@@ -0,0 +1,646 @@
+//! Time zones.
+//!
+//! A zone is a POSIX `TZ` rule: the standard time name and offset, then
+//! optionally the summer time name, offset and the two transitions, as in
+//! `CET-1CEST,M3.5.0,M10.5.0/3` (UTC+1, summer time from the last Sunday
+//! of March at 02:00 to the last Sunday of October at 03:00). Note that
+//! POSIX counts offsets west of UTC: `CET-1` is one hour east.
+//!
+//! [`ZONES`] maps the common tz database names to their current rule, as
+//! found at the end of the zone's TZif file. Only the current rule is
+//! known: dates before its last change (e.g. before 2007 for North America)
+//! may be off by the summer time hour.
+//!
+//! The system zone is named in [`TIMEZONE_PATH`], one line holding a tz
+//! database name or a POSIX rule; UTC when missing or unknown.
+
+use crate::sys;
+use crate::time::{
+    civil_from_days, days_from_civil, days_in_month, is_leap_year, weekday_from_days, DateTime,
+    SystemTime,
+};
+
+pub const TIMEZONE_PATH: &[u8] = b"/etc/timezone\0";
+const TIMEZONE_MAX: usize = 128;
+
+/// Longest zone abbreviation kept.
+pub const ABBR_MAX: usize = 8;
+
+/// The tz database names known, with their current POSIX rule.
+pub const ZONES: &[(&str, &str)] = &[
+    ("UTC", "UTC0"),
+    ("Etc/UTC", "UTC0"),
+    ("Africa/Cairo", "EET-2EEST,M4.5.5/0,M10.5.4/24"),
+    ("Africa/Johannesburg", "SAST-2"),
+    ("Africa/Lagos", "WAT-1"),
+    ("Africa/Nairobi", "EAT-3"),
+    ("America/Anchorage", "AKST9AKDT,M3.2.0,M11.1.0"),
+    ("America/Argentina/Buenos_Aires", "<-03>3"),
+    ("America/Chicago", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/Denver", "MST7MDT,M3.2.0,M11.1.0"),
+    ("America/Los_Angeles", "PST8PDT,M3.2.0,M11.1.0"),
+    ("America/Mexico_City", "CST6"),
+    ("America/New_York", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Phoenix", "MST7"),
+    ("America/Santiago", "<-04>4<-03>,M9.1.6/24,M4.1.6/24"),
+    ("America/Sao_Paulo", "<-03>3"),
+    ("America/Toronto", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Vancouver", "PST8PDT,M3.2.0,M11.1.0"),
+    ("Asia/Dubai", "<+04>-4"),
+    ("Asia/Hong_Kong", "HKT-8"),
+    ("Asia/Jakarta", "WIB-7"),
+    ("Asia/Kathmandu", "<+0545>-5:45"),
+    ("Asia/Kolkata", "IST-5:30"),
+    ("Asia/Seoul", "KST-9"),
+    ("Asia/Shanghai", "CST-8"),
+    ("Asia/Singapore", "<+08>-8"),
+    ("Asia/Tokyo", "JST-9"),
+    ("Australia/Adelaide", "ACST-9:30ACDT,M10.1.0,M4.1.0/3"),
+    ("Australia/Brisbane", "AEST-10"),
+    ("Australia/Melbourne", "AEST-10AEDT,M10.1.0,M4.1.0/3"),
+    ("Australia/Perth", "AWST-8"),
+    ("Australia/Sydney", "AEST-10AEDT,M10.1.0,M4.1.0/3"),
+    ("Europe/Amsterdam", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Athens", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Berlin", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Brussels", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Dublin", "GMT0IST,M3.5.0/1,M10.5.0"),
+    ("Europe/Helsinki", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Istanbul", "<+03>-3"),
+    ("Europe/Kyiv", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Lisbon", "WET0WEST,M3.5.0/1,M10.5.0"),
+    ("Europe/London", "GMT0BST,M3.5.0/1,M10.5.0"),
+    ("Europe/Madrid", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Moscow", "MSK-3"),
+    ("Europe/Paris", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Rome", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Stockholm", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Warsaw", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Zurich", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Pacific/Auckland", "NZST-12NZDT,M9.5.0,M4.1.0/3"),
+    ("Pacific/Honolulu", "HST10"),
+];
+
+/// Summer time rules when a rule names summer time without transitions,
+/// as glibc does: those of the United States.
+const DEFAULT_RULES: &str = ",M3.2.0,M11.1.0";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TzError {
+    /// Missing, too short or too long zone abbreviation.
+    Name,
+    /// Offset or transition time out of range or malformed.
+    Offset,
+    /// Transition date malformed.
+    Rule,
+    /// Text after a complete rule.
+    Trailing,
+}
+
+/// A zone abbreviation such as `CEST` or `-03`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Abbr {
+    bytes: [u8; ABBR_MAX],
+    len: u8,
+}
+
+impl Abbr {
+    const UTC: Self = Self {
+        bytes: *b"UTC\0\0\0\0\0",
+        len: 3,
+    };
+
+    pub fn as_str(&self) -> &str {
+        // Only ASCII letters, digits and signs are kept.
+        core::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or("")
+    }
+}
+
+/// The day a transition happens on.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Day {
+    /// `Mm.w.d`: weekday `d` (0 for Sunday) of week `w` of month `m`, 5
+    /// meaning the last.
+    Month { month: u8, week: u8, weekday: u8 },
+    /// `Jn`: day 1 to 365, February 29th never counted.
+    Julian(u16),
+    /// `n`: day 0 to 365, February 29th counted.
+    Ordinal(u16),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Transition {
+    day: Day,
+    /// Wall clock time of the transition, in seconds; may be negative or
+    /// past 24 hours.
+    time: i32,
+}
+
+impl Transition {
+    /// The transition in `year`, in seconds since the epoch of the wall
+    /// clock in effect before it.
+    fn local_secs(&self, year: i64) -> i64 {
+        let jan1 = days_from_civil(year, 1, 1);
+        let days = match self.day {
+            Day::Month {
+                month,
+                week,
+                weekday,
+            } => {
+                let first = days_from_civil(year, month, 1);
+                let mut day = (weekday + 7 - weekday_from_days(first)) % 7 + 7 * (week - 1);
+                while day >= days_in_month(year, month) {
+                    day -= 7;
+                }
+                first + day as i64
+            }
+            Day::Julian(n) => jan1 + n as i64 - 1 + (is_leap_year(year) && n >= 60) as i64,
+            Day::Ordinal(n) => jan1 + n as i64,
+        };
+        days * 86_400 + self.time as i64
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Summer {
+    abbr: Abbr,
+    offset: i32,
+    start: Transition,
+    end: Transition,
+}
+
+/// The offset in effect at some instant.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Offset {
+    /// Seconds east of UTC.
+    pub seconds: i32,
+    pub summer: bool,
+    pub abbr: Abbr,
+}
+
+/// A time zone: a standard offset and maybe summer time.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Zone {
+    abbr: Abbr,
+    /// Seconds east of UTC.
+    offset: i32,
+    summer: Option<Summer>,
+}
+
+impl Zone {
+    pub const UTC: Self = Self {
+        abbr: Abbr::UTC,
+        offset: 0,
+        summer: None,
+    };
+
+    /// A tz database name from [`ZONES`].
+    pub fn named(name: &str) -> Option<Self> {
+        let (_, rule) = ZONES.iter().find(|(known, _)| *known == name)?;
+        Self::posix(rule).ok()
+    }
+
+    /// The zone named in [`TIMEZONE_PATH`]; UTC when missing or unknown.
+    pub fn local() -> Self {
+        let mut buf = [0u8; TIMEZONE_MAX];
+        let Some(len) = sys::read_file(TIMEZONE_PATH, &mut buf) else {
+            return Self::UTC;
+        };
+        let Ok(text) = core::str::from_utf8(&buf[..len]) else {
+            return Self::UTC;
+        };
+        Self::parse(text.lines().next().unwrap_or("").trim()).unwrap_or(Self::UTC)
+    }
+
+    /// A tz database name or else a POSIX rule.
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::named(text).or_else(|| Self::posix(text).ok())
+    }
+
+    /// Parses a POSIX `TZ` rule such as `EST5EDT,M3.2.0,M11.1.0`.
+    pub fn posix(rule: &str) -> Result<Self, TzError> {
+        let mut rule = Rule(rule.as_bytes());
+        let abbr = rule.abbr()?;
+        let offset = -rule.clock(24)?;
+        if rule.0.is_empty() {
+            return Ok(Self {
+                abbr,
+                offset,
+                summer: None,
+            });
+        }
+        let summer_abbr = rule.abbr()?;
+        let summer_offset = match rule.0.first() {
+            None | Some(b',') => offset + 3600,
+            Some(_) => -rule.clock(24)?,
+        };
+        if rule.0.is_empty() {
+            rule = Rule(DEFAULT_RULES.as_bytes());
+        }
+        let start = rule.transition()?;
+        let end = rule.transition()?;
+        if !rule.0.is_empty() {
+            return Err(TzError::Trailing);
+        }
+        Ok(Self {
+            abbr,
+            offset,
+            summer: Some(Summer {
+                abbr: summer_abbr,
+                offset: summer_offset,
+                start,
+                end,
+            }),
+        })
+    }
+
+    /// The offset in effect at `time`.
+    pub fn offset_at(&self, time: SystemTime) -> Offset {
+        let standard = Offset {
+            seconds: self.offset,
+            summer: false,
+            abbr: self.abbr,
+        };
+        let Some(summer) = self.summer else {
+            return standard;
+        };
+        let secs = time.unix_secs();
+        let local = secs.saturating_add(self.offset as i64);
+        let (year, _, _) = civil_from_days(local.div_euclid(86_400));
+        // Each transition happens on the wall clock it ends.
+        let start = summer.start.local_secs(year) - self.offset as i64;
+        let end = summer.end.local_secs(year) - summer.offset as i64;
+        let in_summer = if start < end {
+            (start..end).contains(&secs)
+        } else {
+            // Southern hemisphere: summer time spans the new year.
+            !(end..start).contains(&secs)
+        };
+        if !in_summer {
+            return standard;
+        }
+        Offset {
+            seconds: summer.offset,
+            summer: true,
+            abbr: summer.abbr,
+        }
+    }
+
+    /// `time` on this zone's wall clocks.
+    pub fn to_local(&self, time: SystemTime) -> DateTime {
+        DateTime::new(time, self.offset_at(time).seconds)
+    }
+
+    /// The wall clock time `local` (its offset ignored) in this zone: the
+    /// earlier instant when clocks go back and show it twice, `None` when
+    /// they skip it going forward.
+    pub fn from_local(&self, local: &DateTime) -> Option<DateTime> {
+        let secs = local.local_secs();
+        let summer = self.summer.map(|summer| summer.offset);
+        let mut offsets = [summer, Some(self.offset)];
+        if summer.is_some_and(|summer| summer < self.offset) {
+            offsets.swap(0, 1);
+        }
+        offsets.into_iter().flatten().find_map(|offset| {
+            let time = SystemTime::from_unix(secs - offset as i64, local.nanosecond);
+            (self.offset_at(time).seconds == offset).then_some(DateTime { offset, ..*local })
+        })
+    }
+}
+
+/// The rest of a POSIX rule being parsed.
+struct Rule<'a>(&'a [u8]);
+
+impl<'a> Rule<'a> {
+    fn take_while(&mut self, keep: impl Fn(u8) -> bool) -> &'a [u8] {
+        let len = self
+            .0
+            .iter()
+            .position(|&b| !keep(b))
+            .unwrap_or(self.0.len());
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        taken
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        match self.0.split_first() {
+            Some((&first, rest)) if first == byte => {
+                self.0 = rest;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// `EST`, or `<-03>` when it has digits or signs.
+    fn abbr(&mut self) -> Result<Abbr, TzError> {
+        let name = if self.eat(b'<') {
+            let name = self.take_while(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'-');
+            if !self.eat(b'>') {
+                return Err(TzError::Name);
+            }
+            name
+        } else {
+            self.take_while(|b| b.is_ascii_alphabetic())
+        };
+        if !(3..=ABBR_MAX).contains(&name.len()) {
+            return Err(TzError::Name);
+        }
+        let mut abbr = Abbr {
+            bytes: [0; ABBR_MAX],
+            len: name.len() as u8,
+        };
+        abbr.bytes[..name.len()].copy_from_slice(name);
+        Ok(abbr)
+    }
+
+    /// Up to `max_digits` decimal digits, at least one.
+    fn number(&mut self, max_digits: usize) -> Result<i32, TzError> {
+        let digits = self.take_while(|b| b.is_ascii_digit());
+        if digits.is_empty() || digits.len() > max_digits {
+            return Err(TzError::Offset);
+        }
+        Ok(digits
+            .iter()
+            .fold(0, |value, &b| value * 10 + (b - b'0') as i32))
+    }
+
+    /// `[+-]hh[:mm[:ss]]` in seconds, hours up to `max_hours`.
+    fn clock(&mut self, max_hours: i32) -> Result<i32, TzError> {
+        let negative = self.eat(b'-');
+        if !negative {
+            self.eat(b'+');
+        }
+        let hours = self.number(3)?;
+        let mut secs = hours * 3600;
+        for unit in [60, 1] {
+            if !self.eat(b':') {
+                break;
+            }
+            let value = self.number(2)?;
+            if value > 59 {
+                return Err(TzError::Offset);
+            }
+            secs += value * unit;
+        }
+        if hours > max_hours {
+            return Err(TzError::Offset);
+        }
+        Ok(if negative { -secs } else { secs })
+    }
+
+    /// `,date[/time]`, the time 02:00 by default.
+    fn transition(&mut self) -> Result<Transition, TzError> {
+        if !self.eat(b',') {
+            return Err(TzError::Rule);
+        }
+        let number = |rule: &mut Self, min: i32, max: i32| match rule.number(3) {
+            Ok(n) if (min..=max).contains(&n) => Ok(n as u16),
+            _ => Err(TzError::Rule),
+        };
+        let day = if self.eat(b'M') {
+            let month = number(self, 1, 12)? as u8;
+            let mut next = |max| {
+                if self.eat(b'.') {
+                    number(self, 0, max)
+                } else {
+                    Err(TzError::Rule)
+                }
+            };
+            let week = next(5)? as u8;
+            let weekday = next(6)? as u8;
+            if week == 0 {
+                return Err(TzError::Rule);
+            }
+            Day::Month {
+                month,
+                week,
+                weekday,
+            }
+        } else if self.eat(b'J') {
+            Day::Julian(number(self, 1, 365)?)
+        } else {
+            Day::Ordinal(number(self, 0, 365)?)
+        };
+        let time = if self.eat(b'/') {
+            self.clock(167)?
+        } else {
+            2 * 3600
+        };
+        Ok(Transition { day, time })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::UNIX_EPOCH;
+
+    fn utc(text: &str) -> SystemTime {
+        DateTime::parse_rfc3339(text).unwrap().to_system_time()
+    }
+
+    fn local(zone: &Zone, text: &str) -> ([u8; 40], usize) {
+        let mut out = [0u8; 40];
+        let len = zone.to_local(utc(text)).format_rfc3339(&mut out).unwrap();
+        (out, len)
+    }
+
+    fn assert_local(zone: &Zone, time: &str, expected: &str) {
+        let (out, len) = local(zone, time);
+        assert_eq!(core::str::from_utf8(&out[..len]).unwrap(), expected);
+    }
+
+    #[test]
+    fn every_known_zone_parses() {
+        for (name, rule) in ZONES {
+            assert!(Zone::posix(rule).is_ok(), "{name}");
+        }
+        assert_eq!(Zone::named("UTC"), Some(Zone::UTC));
+        assert_eq!(Zone::named("Mars/Olympus_Mons"), None);
+    }
+
+    #[test]
+    fn follows_summer_time_in_both_hemispheres() {
+        let paris = Zone::named("Europe/Paris").unwrap();
+        // 2026: last Sundays are March 29th and October 25th.
+        assert_local(&paris, "2026-03-29T00:59:59Z", "2026-03-29T01:59:59+01:00");
+        assert_local(&paris, "2026-03-29T01:00:00Z", "2026-03-29T03:00:00+02:00");
+        assert_local(&paris, "2026-10-25T00:59:59Z", "2026-10-25T02:59:59+02:00");
+        assert_local(&paris, "2026-10-25T01:00:00Z", "2026-10-25T02:00:00+01:00");
+        let offset = paris.offset_at(utc("2026-07-14T12:00:00Z"));
+        assert_eq!((offset.seconds, offset.summer), (7200, true));
+        assert_eq!(offset.abbr.as_str(), "CEST");
+
+        let new_york = Zone::named("America/New_York").unwrap();
+        assert_local(
+            &new_york,
+            "2026-03-08T06:59:59Z",
+            "2026-03-08T01:59:59-05:00",
+        );
+        assert_local(
+            &new_york,
+            "2026-03-08T07:00:00Z",
+            "2026-03-08T03:00:00-04:00",
+        );
+        assert_local(
+            &new_york,
+            "2026-11-01T06:00:00Z",
+            "2026-11-01T01:00:00-05:00",
+        );
+
+        let sydney = Zone::named("Australia/Sydney").unwrap();
+        assert_local(&sydney, "2026-01-01T00:00:00Z", "2026-01-01T11:00:00+11:00");
+        assert_local(&sydney, "2026-07-01T00:00:00Z", "2026-07-01T10:00:00+10:00");
+
+        let kathmandu = Zone::named("Asia/Kathmandu").unwrap();
+        assert_local(
+            &kathmandu,
+            "2026-10-16T00:00:00Z",
+            "2026-10-16T05:45:00+05:45",
+        );
+        assert_eq!(kathmandu.offset_at(UNIX_EPOCH).abbr.as_str(), "+0545");
+    }
+
+    #[test]
+    fn switches_at_the_exact_transition() {
+        // Sydney leaves summer time on the first Sunday of April at 03:00
+        // and starts it on the first Sunday of October at 02:00.
+        let sydney = Zone::named("Australia/Sydney").unwrap();
+        assert_local(&sydney, "2026-04-04T15:59:59Z", "2026-04-05T02:59:59+11:00");
+        assert_local(&sydney, "2026-04-04T16:00:00Z", "2026-04-05T02:00:00+10:00");
+        assert_local(&sydney, "2026-10-03T15:59:59Z", "2026-10-04T01:59:59+10:00");
+        assert_local(&sydney, "2026-10-03T16:00:00Z", "2026-10-04T03:00:00+11:00");
+
+        // Santiago: negative offsets, transitions at 24:00 on a Saturday.
+        let santiago = Zone::named("America/Santiago").unwrap();
+        assert_local(
+            &santiago,
+            "2026-09-06T03:59:59Z",
+            "2026-09-05T23:59:59-04:00",
+        );
+        assert_local(
+            &santiago,
+            "2026-09-06T04:00:00Z",
+            "2026-09-06T01:00:00-03:00",
+        );
+        assert_local(
+            &santiago,
+            "2026-04-05T02:59:59Z",
+            "2026-04-04T23:59:59-03:00",
+        );
+        assert_local(
+            &santiago,
+            "2026-04-05T03:00:00Z",
+            "2026-04-04T23:00:00-04:00",
+        );
+
+        // London at 01:00 UTC, in a leap year.
+        let london = Zone::named("Europe/London").unwrap();
+        assert_local(&london, "2028-03-26T00:59:59Z", "2028-03-26T00:59:59Z");
+        assert_local(&london, "2028-03-26T01:00:00Z", "2028-03-26T02:00:00+01:00");
+
+        // The last Tuesday of February is the 29th in 2028, the 23rd in 2027.
+        let zone = Zone::posix("XST3XDT,M2.5.2,M10.5.0").unwrap();
+        assert_local(&zone, "2028-02-29T04:59:59Z", "2028-02-29T01:59:59-03:00");
+        assert_local(&zone, "2028-02-29T05:00:00Z", "2028-02-29T03:00:00-02:00");
+        assert_local(&zone, "2027-02-23T05:00:00Z", "2027-02-23T03:00:00-02:00");
+        assert_local(&zone, "2027-02-28T12:00:00Z", "2027-02-28T10:00:00-02:00");
+    }
+
+    #[test]
+    fn wall_clock_round_trips_outside_the_fold() {
+        let hour = core::time::Duration::from_secs(3600);
+        for name in ["Europe/Paris", "Australia/Sydney", "America/Santiago"] {
+            let zone = Zone::named(name).unwrap();
+            let mut time = utc("2026-01-01T00:00:00Z");
+            while time < utc("2027-01-01T00:00:00Z") {
+                let local = zone.to_local(time);
+                let back = zone.from_local(&local).unwrap();
+                // Shown twice when clocks go back: the earlier reading wins.
+                let earlier = time - hour;
+                if zone.to_local(earlier).local_secs() == local.local_secs() {
+                    assert_eq!(back.to_system_time(), earlier, "{name} {local}");
+                } else {
+                    assert_eq!(back, local, "{name} {local}");
+                }
+                time += hour / 2;
+            }
+        }
+    }
+
+    #[test]
+    fn resolves_wall_clock_times() {
+        let new_york = Zone::named("America/New_York").unwrap();
+        let wall = |text| DateTime::parse_rfc3339(text).unwrap();
+        // Shown twice when clocks go back: the first, in summer time.
+        let fold = new_york.from_local(&wall("2026-11-01T01:30:00Z")).unwrap();
+        assert_eq!(fold.to_system_time(), utc("2026-11-01T05:30:00Z"));
+        // Skipped when they go forward.
+        assert_eq!(new_york.from_local(&wall("2026-03-08T02:30:00Z")), None);
+        let noon = new_york.from_local(&wall("2026-07-04T12:00:00Z")).unwrap();
+        assert_eq!(noon.offset, -4 * 3600);
+        assert_eq!(
+            Zone::UTC
+                .from_local(&wall("2026-07-04T12:00:00+05:00"))
+                .unwrap()
+                .offset,
+            0
+        );
+    }
+
+    #[test]
+    fn parses_posix_rules() {
+        let zone = Zone::posix("<-04>4<-03>,M9.1.6/24,M4.1.6/24").unwrap();
+        assert_eq!(zone.offset, -4 * 3600);
+        assert_eq!(zone.summer.unwrap().offset, -3 * 3600);
+        assert_eq!(zone.summer.unwrap().abbr.as_str(), "-03");
+        // Summer time offset and rules default.
+        let zone = Zone::posix("EST5EDT").unwrap();
+        assert_eq!(zone, Zone::named("America/New_York").unwrap());
+        let zone = Zone::posix("XST-3:30:15YST,J60/1:30,300").unwrap();
+        assert_eq!(zone.offset, 3 * 3600 + 30 * 60 + 15);
+        let summer = zone.summer.unwrap();
+        assert_eq!(summer.start.day, Day::Julian(60));
+        assert_eq!(summer.start.time, 5400);
+        assert_eq!(summer.end.day, Day::Ordinal(300));
+        // J60 is March 1st, leap year or not; 59 is March 1st in 2028 only.
+        assert_eq!(
+            summer.start.local_secs(2028) - 5400,
+            days_from_civil(2028, 3, 1) * 86_400
+        );
+        let ordinal = Transition {
+            day: Day::Ordinal(59),
+            time: 0,
+        };
+        assert_eq!(
+            ordinal.local_secs(2028),
+            days_from_civil(2028, 2, 29) * 86_400
+        );
+
+        assert_eq!(Zone::posix(""), Err(TzError::Name));
+        assert_eq!(Zone::posix("AB1"), Err(TzError::Name));
+        assert_eq!(Zone::posix("<+03-3"), Err(TzError::Name));
+        assert_eq!(Zone::posix("CET"), Err(TzError::Offset));
+        assert_eq!(Zone::posix("CET25"), Err(TzError::Offset));
+        assert_eq!(Zone::posix("CET-1:60"), Err(TzError::Offset));
+        assert_eq!(Zone::posix("CET-1CEST,M13.5.0,M10.5.0"), Err(TzError::Rule));
+        assert_eq!(Zone::posix("CET-1CEST,M3.0.0,M10.5.0"), Err(TzError::Rule));
+        assert_eq!(Zone::posix("CET-1CEST,M3.5.0"), Err(TzError::Rule));
+        assert_eq!(Zone::posix("CET-1CEST,J0,M10.5.0"), Err(TzError::Rule));
+        assert_eq!(
+            Zone::posix("CET-1CEST,M3.5.0,M10.5.0/168"),
+            Err(TzError::Offset)
+        );
+        assert_eq!(
+            Zone::posix("CET-1CEST,M3.5.0,M10.5.0x"),
+            Err(TzError::Trailing)
+        );
+        assert_eq!(
+            Zone::parse("Europe/Paris"),
+            Zone::posix("CET-1CEST,M3.5.0,M10.5.0/3").ok()
+        );
+        assert_eq!(Zone::parse("JST-9"), Zone::named("Asia/Tokyo"));
+    }
+}
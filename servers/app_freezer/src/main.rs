@@ -0,0 +1,227 @@
+#![no_std]
+#![no_main]
+
+//! # app_freezer — gel des applications d'arrière-plan de la session
+//!
+//! Reçoit du compositeur la visibilité de chaque application
+//! (`FREEZER_MSG_APP_STATE`) et du serveur audio l'état de ses flux
+//! (`FREEZER_MSG_AUDIO`), puis gèle par `exo_freeze` les applications
+//! minimisées ou masquées depuis le délai configuré
+//! (`exo_services::freezer::Policy`). Une application qui joue du son, ou
+//! listée `exempt` dans `/etc/exo/freezer.conf`, n'est jamais gelée.
+//!
+//! Le démon ne se réveille que sur message ou à l'échéance du prochain gel :
+//! sans gel prévu, il bloque sur son endpoint.
+
+use core::panic::PanicInfo;
+
+use exo_services::freezer::{
+    self, Action, Config, Policy, ReportError, FREEZER_MSG_APP_STATE, FREEZER_MSG_AUDIO,
+    FREEZER_MSG_FORGET, FREEZER_MSG_HEARTBEAT, FREEZER_MSG_STATS,
+};
+use exo_syscall_abi as syscall;
+use spin::Mutex;
+
+mod protocol;
+
+use protocol::{recv_request, register_endpoint, send_reply, AppFreezerReply, AppFreezerRequest};
+
+const CONFIG_PATH: &[u8] = b"/etc/exo/freezer.conf\0";
+const CONFIG_MAX: usize = 2048;
+
+struct FreezerService {
+    policy: Policy,
+}
+
+static SERVICE: Mutex<FreezerService> = Mutex::new(FreezerService::new());
+
+impl FreezerService {
+    const fn new() -> Self {
+        Self {
+            policy: Policy::new(Config::new()),
+        }
+    }
+
+    fn load(&mut self) {
+        let mut buf = [0u8; CONFIG_MAX];
+        let mut len = 0;
+        // SAFETY: chemin statique terminé par NUL.
+        let fd = unsafe {
+            syscall::syscall2(
+                syscall::SYS_OPEN,
+                CONFIG_PATH.as_ptr() as u64,
+                syscall::O_RDONLY,
+            )
+        };
+        if fd < 0 {
+            return;
+        }
+        while len < CONFIG_MAX {
+            // SAFETY: écriture bornée à la fin du buffer.
+            let n = unsafe {
+                syscall::syscall3(
+                    syscall::SYS_READ,
+                    fd as u64,
+                    buf[len..].as_mut_ptr() as u64,
+                    (CONFIG_MAX - len) as u64,
+                )
+            };
+            if n <= 0 {
+                break;
+            }
+            len += n as usize;
+        }
+        // SAFETY: fermeture du descripteur ouvert ci-dessus.
+        let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+        if let Ok(text) = core::str::from_utf8(&buf[..len]) {
+            self.policy.set_config(Config::parse(text));
+        }
+    }
+
+    /// Applique les gels et dégels dus ; une cible disparue ou hors de
+    /// portée (autre UID) est oubliée.
+    fn apply(&mut self, now: u64) {
+        while let Some(action) = self.policy.next_action(now) {
+            let (op, pid) = match action {
+                Action::Freeze(pid) => (syscall::EXO_FREEZE_FREEZE, pid),
+                Action::Thaw(pid) => (syscall::EXO_FREEZE_THAW, pid),
+            };
+            if freeze_syscall(op, pid) < 0 {
+                self.policy.forget(pid);
+            }
+        }
+    }
+
+    fn handle_app_state(&mut self, payload: &[u8], now: u64) -> AppFreezerReply {
+        let Some((pid, visibility, name)) = freezer::decode_report(payload) else {
+            return AppFreezerReply::error(syscall::EINVAL);
+        };
+        match self.policy.report(pid, name, visibility, now) {
+            Ok(()) => AppFreezerReply::ok(0, 0, 0, 0),
+            Err(ReportError::Full) => AppFreezerReply::error(syscall::ENOSPC),
+        }
+    }
+
+    fn handle_audio(&mut self, payload: &[u8], now: u64) -> AppFreezerReply {
+        let (Some(pid), Some(playing)) = (read_u32(payload, 0), read_u32(payload, 4)) else {
+            return AppFreezerReply::error(syscall::EINVAL);
+        };
+        if self.policy.set_audio(pid, playing != 0, now) {
+            AppFreezerReply::ok(0, 0, 0, 0)
+        } else {
+            AppFreezerReply::error(syscall::ENOENT)
+        }
+    }
+
+    fn handle_forget(&mut self, payload: &[u8]) -> AppFreezerReply {
+        let Some(pid) = read_u32(payload, 0) else {
+            return AppFreezerReply::error(syscall::EINVAL);
+        };
+        match self.policy.forget(pid) {
+            Some(frozen) => {
+                if frozen {
+                    let _ = freeze_syscall(syscall::EXO_FREEZE_THAW, pid);
+                }
+                AppFreezerReply::ok(0, 0, 0, 0)
+            }
+            None => AppFreezerReply::error(syscall::ENOENT),
+        }
+    }
+}
+
+fn read_u32(payload: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        payload.get(at..at + 4)?.try_into().ok()?,
+    ))
+}
+
+fn freeze_syscall(op: u64, pid: u32) -> i64 {
+    // SAFETY: appel sans pointeur ; le noyau vérifie les droits sur `pid`.
+    unsafe { syscall::syscall2(syscall::SYS_EXO_FREEZE, op, pid as u64) }
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+fn monotonic_ms() -> u64 {
+    let mut ts = Timespec::default();
+    // SAFETY: le noyau écrit un `timespec` dans `ts`.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_CLOCK_GETTIME,
+            1,
+            &mut ts as *mut Timespec as u64,
+        )
+    };
+    if rc != 0 || ts.tv_sec < 0 {
+        return 0;
+    }
+    (ts.tv_sec as u64)
+        .saturating_mul(1_000)
+        .saturating_add(ts.tv_nsec as u64 / 1_000_000)
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let endpoint = register_endpoint();
+    SERVICE.lock().load();
+    let mut request = AppFreezerRequest::zeroed();
+
+    loop {
+        let timeout = {
+            let mut service = SERVICE.lock();
+            let now = monotonic_ms();
+            service.apply(now);
+            service.policy.next_deadline(now)
+        };
+        if endpoint == 0 {
+            continue;
+        }
+        match recv_request(endpoint, &mut request, timeout) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => continue,
+        }
+
+        let reply = dispatch(&request);
+        let _ = send_reply(request.sender_pid, &reply);
+    }
+}
+
+fn dispatch(request: &AppFreezerRequest) -> AppFreezerReply {
+    let mut service = SERVICE.lock();
+    let now = monotonic_ms();
+
+    match request.msg_type {
+        FREEZER_MSG_HEARTBEAT => AppFreezerReply::ok(
+            service.policy.len() as u64,
+            service.policy.frozen() as u64,
+            0,
+            0,
+        ),
+        FREEZER_MSG_APP_STATE => service.handle_app_state(&request.payload, now),
+        FREEZER_MSG_AUDIO => service.handle_audio(&request.payload, now),
+        FREEZER_MSG_FORGET => service.handle_forget(&request.payload),
+        FREEZER_MSG_STATS => AppFreezerReply::ok(
+            service.policy.len() as u64,
+            service.policy.frozen() as u64,
+            service.policy.freezes,
+            0,
+        ),
+        _ => AppFreezerReply::error(syscall::EINVAL),
+    }
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        // SAFETY: panic terminale pour un serveur no_std monothread.
+        unsafe {
+            core::arch::asm!("hlt", options(nostack, nomem));
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use exo_syscall_abi as syscall;
+
+/// Canal du serveur, dans l'espace d'endpoints de son PID ; les clients le
+/// retrouvent par son nom (`SYS_IPC_LOOKUP "app_freezer"`).
+pub const APP_FREEZER_CHANNEL: u64 = 1;
+/// Bornes de l'attente d'un tour de boucle lorsqu'un gel est prévu.
+const MIN_TIMEOUT_MS: u64 = 10;
+const MAX_TIMEOUT_MS: u64 = 60_000;
+
+#[repr(C)]
+pub struct AppFreezerRequest {
+    pub sender_pid: u32,
+    pub msg_type: u32,
+    pub payload: [u8; syscall::IPC_INLINE_PAYLOAD_SIZE],
+}
+
+impl AppFreezerRequest {
+    pub const fn zeroed() -> Self {
+        Self {
+            sender_pid: 0,
+            msg_type: 0,
+            payload: [0; syscall::IPC_INLINE_PAYLOAD_SIZE],
+        }
+    }
+}
+
+const _: () = assert!(core::mem::size_of::<AppFreezerRequest>() == syscall::IPC_ENVELOPE_SIZE);
+const _: () =
+    assert!(core::mem::offset_of!(AppFreezerRequest, payload) == syscall::IPC_HEADER_SIZE);
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct AppFreezerReply {
+    pub status: i64,
+    pub handle: u64,
+    pub value0: u64,
+    pub value1: u64,
+    pub flags: u32,
+    pub _pad: [u8; 28],
+}
+
+impl AppFreezerReply {
+    pub const fn ok(handle: u64, value0: u64, value1: u64, flags: u32) -> Self {
+        Self {
+            status: 0,
+            handle,
+            value0,
+            value1,
+            flags,
+            _pad: [0; 28],
+        }
+    }
+
+    pub const fn error(status: i64) -> Self {
+        Self {
+            status,
+            handle: 0,
+            value0: 0,
+            value1: 0,
+            flags: 0,
+            _pad: [0; 28],
+        }
+    }
+}
+
+/// Enregistre `app_freezer` ; retourne l'endpoint, `0` en cas d'échec.
+pub fn register_endpoint() -> u64 {
+    // SAFETY: lecture simple du PID courant.
+    let pid = unsafe { syscall::syscall0(syscall::SYS_GETPID) };
+    if pid <= 0 {
+        return 0;
+    }
+    let endpoint = ((pid as u64) << 32) | APP_FREEZER_CHANNEL;
+    let name = b"app_freezer";
+    // SAFETY: buffer statique valide, endpoint dans l'espace du PID courant.
+    let rc = unsafe {
+        syscall::syscall3(
+            syscall::SYS_IPC_REGISTER,
+            name.as_ptr() as u64,
+            name.len() as u64,
+            endpoint,
+        )
+    };
+    if rc < 0 {
+        0
+    } else {
+        endpoint
+    }
+}
+
+/// Attend une requête ; sans échéance (`None`), bloque jusqu'au prochain
+/// message : rien ne réveille le démon tant qu'aucun gel n'est prévu.
+pub fn recv_request(
+    endpoint: u64,
+    request: &mut AppFreezerRequest,
+    timeout_ms: Option<u64>,
+) -> Result<bool, i64> {
+    let flags = match timeout_ms {
+        Some(ms) => syscall::IPC_FLAG_TIMEOUT | ms.clamp(MIN_TIMEOUT_MS, MAX_TIMEOUT_MS),
+        None => 0,
+    };
+    // SAFETY: le noyau écrit dans `request`, taille bornée à la struct ABI.
+    let rc = unsafe {
+        syscall::syscall4(
+            syscall::SYS_IPC_RECV,
+            endpoint,
+            request as *mut AppFreezerRequest as u64,
+            core::mem::size_of::<AppFreezerRequest>() as u64,
+            flags,
+        )
+    };
+
+    if rc == syscall::ETIMEDOUT {
+        return Ok(false);
+    }
+    if rc < 0 {
+        return Err(rc);
+    }
+    Ok(true)
+}
+
+pub fn send_reply(destination_pid: u32, reply: &AppFreezerReply) -> i64 {
+    // SAFETY: `reply` est une structure POD locale envoyée telle quelle au noyau.
+    unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            destination_pid as u64,
+            reply as *const AppFreezerReply as u64,
+            core::mem::size_of::<AppFreezerReply>() as u64,
+            0,
+            0,
+            0,
+        )
+    }
+}
@@ -0,0 +1,332 @@
+#![no_std]
+#![no_main]
+
+//! # app_prewarm — lancement à chaud des applications
+//!
+//! Service de session lancé par le lanceur du bureau : il garde, par classe
+//! d'application (`/etc/exo/prewarm.conf`), des gabarits forkés d'avance
+//! dont le programme et les bibliothèques sont déjà en cache. Une demande
+//! de lancement est confiée à un gabarit prêt, qui se spécialise et fait
+//! `execve` ; sans gabarit, lancement à froid.
+//!
+//! - seul le processus parent (le lanceur) peut demander un lancement ;
+//! - les gabarits tournent avec les droits du service, donc ceux de la
+//!   session ;
+//! - sous pression mémoire, les gabarits sont libérés en premier
+//!   (politique dans `exo_services::prewarm::Pool`).
+
+use core::panic::PanicInfo;
+
+use exo_services::prewarm::{
+    self, AppClass, Launch, Pool, PoolAction, LAUNCH_WARM, MAX_CLASSES, MAX_TEMPLATES,
+    PREWARM_MSG_HEARTBEAT, PREWARM_MSG_LAUNCH, PREWARM_MSG_STATS,
+};
+use exo_syscall_abi as syscall;
+use spin::Mutex;
+
+mod protocol;
+mod template;
+
+use protocol::{recv_request, register_endpoint, send_reply, PrewarmReply, PrewarmRequest};
+use template::{Exec, Template};
+
+const CONFIG_PATH: &[u8] = b"/etc/exo/prewarm.conf\0";
+const CONFIG_MAX: usize = 4096;
+const MAX_POOL: usize = MAX_CLASSES * MAX_TEMPLATES as usize;
+/// Actions de remplissage / éviction par tour de boucle : une demande de
+/// lancement n'attend jamais une rafale de forks.
+const ACTIONS_PER_TICK: usize = 2;
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct SysInfo {
+    uptime: i64,
+    loads: [u64; 3],
+    totalram: u64,
+    freeram: u64,
+    sharedram: u64,
+    bufferram: u64,
+    totalswap: u64,
+    freeswap: u64,
+    procs: u16,
+    pad: u16,
+    _pad2: u32,
+    totalhigh: u64,
+    freehigh: u64,
+    mem_unit: u32,
+    _pad3: [u8; 8],
+}
+
+struct PrewarmService {
+    config: [u8; CONFIG_MAX],
+    config_len: usize,
+    pool: Pool,
+    templates: [Option<Template>; MAX_POOL],
+    launcher: u32,
+    warm: u64,
+    cold: u64,
+    evicted: u64,
+}
+
+static SERVICE: Mutex<PrewarmService> = Mutex::new(PrewarmService::new());
+
+impl PrewarmService {
+    const fn new() -> Self {
+        Self {
+            config: [0; CONFIG_MAX],
+            config_len: 0,
+            pool: Pool::new(),
+            templates: [None; MAX_POOL],
+            launcher: 0,
+            warm: 0,
+            cold: 0,
+            evicted: 0,
+        }
+    }
+
+    fn config_text(&self) -> &str {
+        core::str::from_utf8(&self.config[..self.config_len]).unwrap_or("")
+    }
+
+    fn class(&self, index: usize) -> Option<AppClass<'_>> {
+        prewarm::classes(self.config_text()).nth(index)
+    }
+
+    /// Lit la configuration. Absente ou invalide : aucune classe, tous les
+    /// lancements sont refusés (ENOENT).
+    fn load(&mut self) {
+        // SAFETY: chemin statique terminé par NUL.
+        let fd = unsafe {
+            syscall::syscall2(
+                syscall::SYS_OPEN,
+                CONFIG_PATH.as_ptr() as u64,
+                syscall::O_RDONLY,
+            )
+        };
+        if fd < 0 {
+            return;
+        }
+        while self.config_len < CONFIG_MAX {
+            // SAFETY: écriture bornée à la fin du buffer.
+            let n = unsafe {
+                syscall::syscall3(
+                    syscall::SYS_READ,
+                    fd as u64,
+                    self.config[self.config_len..].as_mut_ptr() as u64,
+                    (CONFIG_MAX - self.config_len) as u64,
+                )
+            };
+            if n <= 0 {
+                break;
+            }
+            self.config_len += n as usize;
+        }
+        // SAFETY: fermeture du descripteur ouvert ci-dessus.
+        let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+        if core::str::from_utf8(&self.config[..self.config_len]).is_err() {
+            self.config_len = 0;
+        }
+        let mut targets = [0u8; MAX_CLASSES];
+        let mut count = 0;
+        for (target, class) in targets.iter_mut().zip(prewarm::classes(self.config_text())) {
+            *target = class.templates;
+            count += 1;
+        }
+        for &target in &targets[..count] {
+            let _ = self.pool.add_class(target);
+        }
+    }
+
+    /// Rapproche le pool de sa cible selon la mémoire libre.
+    fn balance(&mut self) {
+        let free = free_permille();
+        for _ in 0..ACTIONS_PER_TICK {
+            match self.pool.next_action(free) {
+                PoolAction::Spawn(index) => {
+                    if self.spawn_template(index).is_err() {
+                        return;
+                    }
+                }
+                PoolAction::Evict(index) => {
+                    if let Some(template) = self.take_template(index) {
+                        template.release();
+                        self.pool.note_gone(index);
+                        self.evicted += 1;
+                    }
+                }
+                PoolAction::Idle => return,
+            }
+        }
+    }
+
+    fn spawn_template(&mut self, index: usize) -> Result<(), i64> {
+        let slot = self
+            .templates
+            .iter()
+            .position(Option::is_none)
+            .ok_or(syscall::ENOSPC)?;
+        let class = self.class(index).ok_or(syscall::ENOENT)?;
+        let template = template::spawn(index, &class, self.templates.iter().flatten())?;
+        self.templates[slot] = Some(template);
+        self.pool.note_spawned(index);
+        Ok(())
+    }
+
+    fn take_template(&mut self, index: usize) -> Option<Template> {
+        self.templates
+            .iter_mut()
+            .find(|t| t.is_some_and(|t| t.class == index))?
+            .take()
+    }
+
+    fn handle_launch(&mut self, sender_pid: u32, payload: &[u8]) -> PrewarmReply {
+        if sender_pid != self.launcher {
+            return PrewarmReply::error(syscall::EPERM);
+        }
+        let Some(launch) = Launch::decode(payload) else {
+            return PrewarmReply::error(syscall::EINVAL);
+        };
+        let Some(index) = prewarm::classes(self.config_text()).position(|c| c.name == launch.class)
+        else {
+            return PrewarmReply::error(syscall::ENOENT);
+        };
+        self.pool.note_launch(index, monotonic_ms());
+
+        // Un gabarit mort entre deux récoltes : essayer le suivant.
+        while let Some(template) = self.take_template(index) {
+            self.pool.note_gone(index);
+            let record = &payload[..payload.len().min(prewarm::LAUNCH_MAX)];
+            if let Ok(pid) = template.hand_over(record) {
+                self.warm += 1;
+                return PrewarmReply::ok(pid as u64, self.warm, self.cold, LAUNCH_WARM);
+            }
+        }
+
+        let Some(class) = self.class(index) else {
+            return PrewarmReply::error(syscall::ENOENT);
+        };
+        let Some(mut exec) = Exec::new(class.program, &launch, payload) else {
+            return PrewarmReply::error(syscall::EINVAL);
+        };
+        match template::spawn_cold(&mut exec) {
+            Ok(pid) => {
+                self.cold += 1;
+                PrewarmReply::ok(pid as u64, self.warm, self.cold, 0)
+            }
+            Err(err) => PrewarmReply::error(err),
+        }
+    }
+
+    /// Récolte les fils terminés ; un gabarit mort avant usage sort du pool.
+    fn reap(&mut self) {
+        loop {
+            // SAFETY: wait4 non bloquant sans buffer de statut.
+            let pid =
+                unsafe { syscall::syscall4(syscall::SYS_WAIT4, u64::MAX, 0, syscall::WNOHANG, 0) };
+            if pid <= 0 {
+                return;
+            }
+            let dead = self
+                .templates
+                .iter_mut()
+                .find(|t| t.is_some_and(|t| t.pid == pid as u32))
+                .and_then(Option::take);
+            if let Some(template) = dead {
+                template.release();
+                self.pool.note_gone(template.class);
+            }
+        }
+    }
+}
+
+fn free_permille() -> u32 {
+    let mut info = SysInfo::default();
+    // SAFETY: le noyau écrit une structure `sysinfo` dans `info`.
+    let rc = unsafe { syscall::syscall1(syscall::SYS_SYSINFO, &mut info as *mut SysInfo as u64) };
+    if rc < 0 {
+        return 1000;
+    }
+    prewarm::free_permille(info.freeram, info.totalram)
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+fn monotonic_ms() -> u64 {
+    let mut ts = Timespec::default();
+    // SAFETY: le noyau écrit un `timespec` dans `ts`.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_CLOCK_GETTIME,
+            1,
+            &mut ts as *mut Timespec as u64,
+        )
+    };
+    if rc != 0 || ts.tv_sec < 0 {
+        return 0;
+    }
+    (ts.tv_sec as u64)
+        .saturating_mul(1_000)
+        .saturating_add(ts.tv_nsec as u64 / 1_000_000)
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let endpoint = register_endpoint();
+    {
+        let mut service = SERVICE.lock();
+        // SAFETY: lecture simple du PID parent.
+        service.launcher = unsafe { syscall::syscall0(syscall::SYS_GETPPID) }.max(0) as u32;
+        service.load();
+    }
+    let mut request = PrewarmRequest::zeroed();
+
+    loop {
+        {
+            let mut service = SERVICE.lock();
+            service.reap();
+            service.balance();
+        }
+        if endpoint == 0 {
+            continue;
+        }
+        match recv_request(endpoint, &mut request) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => continue,
+        }
+
+        let reply = dispatch(&request);
+        let _ = send_reply(request.sender_pid, &reply);
+    }
+}
+
+fn dispatch(request: &PrewarmRequest) -> PrewarmReply {
+    let mut service = SERVICE.lock();
+
+    match request.msg_type {
+        PREWARM_MSG_HEARTBEAT => PrewarmReply::ok(
+            service.launcher as u64,
+            service.templates.iter().flatten().count() as u64,
+            service.pool.is_reclaiming() as u64,
+            0,
+        ),
+        PREWARM_MSG_LAUNCH => service.handle_launch(request.sender_pid, &request.payload),
+        PREWARM_MSG_STATS => PrewarmReply::ok(service.warm, service.cold, service.evicted, 0),
+        _ => PrewarmReply::error(syscall::EINVAL),
+    }
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        // SAFETY: panic terminale pour un serveur no_std monothread.
+        unsafe {
+            core::arch::asm!("hlt", options(nostack, nomem));
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Gabarits : processus forkés d'avance qui ont déjà tiré le programme et
+//! ses bibliothèques dans le cache de fichiers, puis attendent sur un pipe.
+//!
+//! Le serveur écrit l'enregistrement de lancement dans le pipe et le ferme ;
+//! le gabarit lit jusqu'à la fin, se spécialise (répertoire, argv) et fait
+//! `execve`. Un pipe fermé sans enregistrement est une éviction : le gabarit
+//! sort.
+
+use exo_services::prewarm::{AppClass, Launch, LAUNCH_MAX, MAX_ARGS};
+use exo_syscall_abi as syscall;
+
+const PATH_MAX: usize = 256;
+
+#[derive(Clone, Copy)]
+pub struct Template {
+    pub pid: u32,
+    pub class: usize,
+    /// Extrémité d'écriture du pipe, côté serveur.
+    pipe: u64,
+}
+
+impl Template {
+    /// Transmet le lancement ; le gabarit devient l'application.
+    pub fn hand_over(self, record: &[u8]) -> Result<u32, i64> {
+        // SAFETY: écriture d'un buffer local puis fermeture du descripteur
+        // possédé par `self`.
+        let rc = unsafe {
+            let rc = syscall::syscall3(
+                syscall::SYS_WRITE,
+                self.pipe,
+                record.as_ptr() as u64,
+                record.len() as u64,
+            );
+            let _ = syscall::syscall1(syscall::SYS_CLOSE, self.pipe);
+            rc
+        };
+        // Enregistrement plus court qu'une écriture atomique de pipe : tout
+        // ou rien.
+        if rc != record.len() as i64 {
+            return Err(if rc < 0 { rc } else { syscall::EIO });
+        }
+        Ok(self.pid)
+    }
+
+    /// Éviction : le gabarit lit la fin du pipe et sort.
+    pub fn release(self) {
+        // SAFETY: fermeture du descripteur possédé par `self`.
+        let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, self.pipe) };
+    }
+}
+
+fn c_path(s: &str) -> Option<[u8; PATH_MAX]> {
+    let mut buf = [0u8; PATH_MAX];
+    buf.get_mut(..s.len())
+        .filter(|_| s.len() < PATH_MAX)?
+        .copy_from_slice(s.as_bytes());
+    Some(buf)
+}
+
+/// Arguments d'`execve` préparés avant le fork.
+pub struct Exec {
+    program: [u8; PATH_MAX],
+    cwd: Option<[u8; PATH_MAX]>,
+    argv: [u64; MAX_ARGS + 2],
+}
+
+impl Exec {
+    /// `argv[1..]` pointe dans `record`, qui doit rester en place jusqu'à
+    /// [`Exec::run`].
+    pub fn new(program: &str, launch: &Launch<'_>, record: &[u8]) -> Option<Self> {
+        let mut offsets = [0usize; MAX_ARGS];
+        let argc = launch.arg_offsets(&mut offsets)?;
+        let mut argv = [0u64; MAX_ARGS + 2];
+        for (slot, &offset) in argv[1..].iter_mut().zip(&offsets[..argc]) {
+            *slot = record.get(offset..)?.as_ptr() as u64;
+        }
+        Some(Self {
+            program: c_path(program)?,
+            cwd: if launch.cwd.is_empty() {
+                None
+            } else {
+                Some(c_path(launch.cwd)?)
+            },
+            argv,
+        })
+    }
+
+    /// chdir + execve ; ne retourne qu'en cas d'échec.
+    pub unsafe fn run(&mut self) -> i64 {
+        if let Some(cwd) = &self.cwd {
+            let rc = syscall::syscall1(syscall::SYS_CHDIR, cwd.as_ptr() as u64);
+            if rc < 0 {
+                return rc;
+            }
+        }
+        self.argv[0] = self.program.as_ptr() as u64;
+        let envp: [u64; 1] = [0];
+        syscall::syscall3(
+            syscall::SYS_EXECVE,
+            self.argv[0],
+            self.argv.as_ptr() as u64,
+            envp.as_ptr() as u64,
+        )
+    }
+}
+
+unsafe fn exit(code: u64) -> ! {
+    let _ = syscall::syscall1(syscall::SYS_EXIT, code);
+    let _ = syscall::syscall1(syscall::SYS_EXIT_GROUP, code);
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
+/// Forke un gabarit de la classe `index`. Les pipes des autres gabarits
+/// (`siblings`) sont fermés dans le fils : sinon une éviction ne leur
+/// enverrait jamais la fin de fichier.
+pub fn spawn<'a>(
+    index: usize,
+    class: &AppClass<'_>,
+    siblings: impl Iterator<Item = &'a Template>,
+) -> Result<Template, i64> {
+    let mut fds = [0i32; 2];
+    // SAFETY: le noyau écrit deux descripteurs dans `fds`.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_PIPE2,
+            fds.as_mut_ptr() as u64,
+            syscall::O_CLOEXEC,
+        )
+    };
+    if rc < 0 {
+        return Err(rc);
+    }
+    let (read_end, write_end) = (fds[0] as u64, fds[1] as u64);
+    // SAFETY: fork complet ; le fils ne revient jamais.
+    let pid = unsafe { syscall::syscall0(syscall::SYS_FORK) };
+    if pid == 0 {
+        // SAFETY: fils : fermeture de descripteurs hérités puis attente.
+        unsafe {
+            let _ = syscall::syscall1(syscall::SYS_CLOSE, write_end);
+            for sibling in siblings {
+                let _ = syscall::syscall1(syscall::SYS_CLOSE, sibling.pipe);
+            }
+            run_template(class, read_end)
+        }
+    }
+    // SAFETY: le parent ne garde que l'extrémité d'écriture.
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, read_end) };
+    if pid < 0 {
+        let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, write_end) };
+        return Err(pid);
+    }
+    Ok(Template {
+        pid: pid as u32,
+        class: index,
+        pipe: write_end,
+    })
+}
+
+unsafe fn run_template(class: &AppClass<'_>, pipe: u64) -> ! {
+    // ExoFS charge le blob entier à l'ouverture : ouvrir suffit à le mettre
+    // en cache pour l'execve et le chargeur dynamique.
+    for file in class.warm_files() {
+        let Some(path) = c_path(file) else {
+            continue;
+        };
+        let fd = syscall::syscall2(
+            syscall::SYS_OPEN,
+            path.as_ptr() as u64,
+            syscall::O_RDONLY | syscall::O_CLOEXEC,
+        );
+        if fd >= 0 {
+            let _ = syscall::syscall1(syscall::SYS_CLOSE, fd as u64);
+        }
+    }
+
+    let mut record = [0u8; LAUNCH_MAX];
+    let mut len = 0usize;
+    while len < LAUNCH_MAX {
+        let n = syscall::syscall3(
+            syscall::SYS_READ,
+            pipe,
+            record[len..].as_mut_ptr() as u64,
+            (LAUNCH_MAX - len) as u64,
+        );
+        if n <= 0 {
+            break;
+        }
+        len += n as usize;
+    }
+    if len == 0 {
+        exit(0);
+    }
+    let Some(launch) = Launch::decode(&record[..len]).filter(|l| l.class == class.name) else {
+        exit(126);
+    };
+    let Some(mut exec) = Exec::new(class.program, &launch, &record) else {
+        exit(126);
+    };
+    let rc = exec.run();
+    exit(if rc == syscall::ENOENT { 127 } else { 126 })
+}
+
+/// Lancement sans gabarit (vfork + execve), même contrat que les autres
+/// serveurs : le fils ne fait que chdir + execve ou _exit.
+pub fn spawn_cold(exec: &mut Exec) -> Result<u32, i64> {
+    // SAFETY: l'enfant vfork n'utilise que `exec`, préparé par le parent.
+    unsafe {
+        let pid = syscall::syscall0(syscall::SYS_VFORK);
+        if pid == 0 {
+            let rc = exec.run();
+            exit(if rc == syscall::ENOENT { 127 } else { 126 });
+        }
+        if pid < 0 {
+            return Err(pid);
+        }
+        Ok(pid as u32)
+    }
+}
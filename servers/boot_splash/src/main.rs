@@ -0,0 +1,274 @@
+#![no_std]
+#![no_main]
+
+//! # boot_splash — écran de démarrage piloté par init
+//!
+//! Lancé par init dès que `fb_server` est prêt, il prend l'affichage
+//! (`FB_MSG_ACQUIRE_DISPLAY`) et dessine titre, barre et nom du dernier
+//! service via les requêtes de dessin de `fb_server` (voir
+//! `exo_services::splash`) :
+//!
+//! - la barre suit les jalons envoyés par init (`SPLASH_MSG_PROGRESS`), en
+//!   rattrapant la cible par petits pas à chaque tour de boucle ;
+//! - `SPLASH_MSG_HANDOFF` (compositeur, greeter) : dernière image à 100 %
+//!   puis transfert direct de l'affichage, sans passer par la console ;
+//! - `SPLASH_MSG_QUIT` (init, avant le shell) : l'affichage revient à la
+//!   console texte, même fond que le thème par défaut.
+//!
+//! Sans framebuffer ou si l'affichage est déjà pris, le splash se retire
+//! aussitôt. S'il meurt, `fb_server` rend la console de lui-même.
+
+use core::panic::PanicInfo;
+
+use exo_services::splash::{
+    Layout, Milestone, Progress, Rect, Rgb, Theme, MILESTONE_NAME_MAX, SPLASH_MSG_HANDOFF,
+    SPLASH_MSG_PROGRESS, SPLASH_MSG_QUIT,
+};
+use exo_syscall_abi as syscall;
+
+mod protocol;
+
+use protocol::{
+    fb_call, fb_send, recv_request, register_endpoint, send_reply, SplashReply, SplashRequest,
+    FB_REPLY_CHANNEL, SPLASH_CHANNEL,
+};
+
+const CONFIG_PATH: &[u8] = b"/etc/exo/splash.conf\0";
+const CONFIG_MAX: usize = 1024;
+const INIT_PID: u32 = 1;
+/// Tour de boucle (~30 images/s pendant l'animation de la barre).
+const FRAME_MS: u64 = 33;
+/// Avance maximale de la barre par image, en millièmes.
+const PROGRESS_STEP: u16 = 15;
+/// Suffixe du message d'un service abandonné.
+const FAILED_SUFFIX: &[u8] = b" (failed)";
+
+struct Splash {
+    theme: Theme,
+    layout: Layout,
+    progress: Progress,
+    fb_reply: u64,
+}
+
+impl Splash {
+    fn fill(&self, rect: Rect, color: Rgb) {
+        if rect.w == 0 || rect.h == 0 {
+            return;
+        }
+        let mut req = syscall::FbRequest::zeroed();
+        req.msg_type = syscall::FB_MSG_FILL_RECT;
+        req.a = rect.x as u64 | (rect.y as u64) << 32;
+        req.b = rect.w as u64 | (rect.h as u64) << 32;
+        req.data[..4].copy_from_slice(&color.to_u32().to_le_bytes());
+        let _ = fb_send(&req);
+    }
+
+    fn text(&self, x: u32, y: u32, text: &[u8], scale: u32) {
+        if text.is_empty() {
+            return;
+        }
+        let mut req = syscall::FbRequest::zeroed();
+        req.msg_type = syscall::FB_MSG_DRAW_TEXT;
+        req.a = x as u64 | (y as u64) << 32;
+        req.b =
+            self.theme.foreground.to_u32() as u64 | (self.theme.background.to_u32() as u64) << 32;
+        let len = text.len().min(syscall::FB_TEXT_MAX - 2);
+        req.data[0] = len as u8;
+        req.data[1] = scale as u8;
+        req.data[2..2 + len].copy_from_slice(&text[..len]);
+        let _ = fb_send(&req);
+    }
+
+    fn draw_all(&self, width: u32, height: u32) {
+        self.fill(
+            Rect {
+                x: 0,
+                y: 0,
+                w: width,
+                h: height,
+            },
+            self.theme.background,
+        );
+        self.text(
+            self.layout.title_x,
+            self.layout.title_y,
+            self.theme.title(),
+            self.layout.title_scale,
+        );
+        self.fill(self.layout.bar, self.theme.track);
+        self.draw_bar();
+    }
+
+    /// La barre ne fait qu'avancer : seule la partie remplie est redessinée.
+    fn draw_bar(&self) {
+        self.fill(
+            self.layout.bar_fill(self.progress.shown()),
+            self.theme.accent,
+        );
+    }
+
+    fn draw_message(&self, milestone: &Milestone) {
+        if !self.theme.messages {
+            return;
+        }
+        let mut line = [0u8; MILESTONE_NAME_MAX + FAILED_SUFFIX.len()];
+        let name = milestone.name();
+        line[..name.len()].copy_from_slice(name);
+        let mut len = name.len();
+        if milestone.failed {
+            line[len..len + FAILED_SUFFIX.len()].copy_from_slice(FAILED_SUFFIX);
+            len += FAILED_SUFFIX.len();
+        }
+        self.fill(self.layout.message, self.theme.background);
+        self.text(
+            self.layout.message_x(len),
+            self.layout.message.y,
+            &line[..len],
+            1,
+        );
+    }
+
+    /// Dernière image : la barre rejoint sa cible.
+    fn settle(&mut self) {
+        self.progress.settle();
+        self.draw_bar();
+    }
+
+    fn fb_request(&self, msg_type: u32, a: u64) -> i64 {
+        let mut req = syscall::FbRequest::zeroed();
+        req.msg_type = msg_type;
+        req.a = a;
+        fb_call(&mut req, self.fb_reply).map_or(syscall::ETIMEDOUT, |reply| reply.status)
+    }
+}
+
+fn load_theme() -> Theme {
+    let mut buf = [0u8; CONFIG_MAX];
+    let mut len = 0;
+    // SAFETY: chemin statique terminé par NUL.
+    let fd = unsafe {
+        syscall::syscall2(
+            syscall::SYS_OPEN,
+            CONFIG_PATH.as_ptr() as u64,
+            syscall::O_RDONLY,
+        )
+    };
+    if fd < 0 {
+        return Theme::default();
+    }
+    while len < CONFIG_MAX {
+        // SAFETY: écriture bornée à la fin du buffer.
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_READ,
+                fd as u64,
+                buf[len..].as_mut_ptr() as u64,
+                (CONFIG_MAX - len) as u64,
+            )
+        };
+        if n <= 0 {
+            break;
+        }
+        len += n as usize;
+    }
+    // SAFETY: fermeture du descripteur ouvert ci-dessus.
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+    core::str::from_utf8(&buf[..len])
+        .map(Theme::parse)
+        .unwrap_or_default()
+}
+
+fn log(bytes: &[u8]) {
+    // SAFETY: buffer statique valide.
+    unsafe {
+        let _ = syscall::syscall3(
+            syscall::SYS_EXO_LOG,
+            bytes.as_ptr() as u64,
+            bytes.len() as u64,
+            1,
+        );
+    }
+}
+
+fn exit(code: u64) -> ! {
+    // SAFETY: fin du processus.
+    unsafe {
+        let _ = syscall::syscall1(syscall::SYS_EXIT, code);
+        let _ = syscall::syscall1(syscall::SYS_EXIT_GROUP, code);
+    }
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let fb_reply = register_endpoint(FB_REPLY_CHANNEL, b"boot_splash_fb");
+    let endpoint = register_endpoint(SPLASH_CHANNEL, b"boot_splash");
+    if fb_reply == 0 || endpoint == 0 {
+        log(b"boot_splash: register failed\n");
+        exit(1);
+    }
+
+    let theme = load_theme();
+    let mut splash = Splash {
+        theme,
+        layout: Layout::new(&theme, 1, 1),
+        progress: Progress::new(),
+        fb_reply,
+    };
+    let size = splash.fb_request(syscall::FB_MSG_DISPLAY_SIZE, 0);
+    if size <= 0 {
+        log(b"boot_splash: no framebuffer, text boot\n");
+        exit(0);
+    }
+    let (width, height) = ((size >> 32) as u32, size as u32);
+    if splash.fb_request(syscall::FB_MSG_ACQUIRE_DISPLAY, 0) < 0 {
+        log(b"boot_splash: display busy\n");
+        exit(0);
+    }
+    splash.layout = Layout::new(&splash.theme, width, height);
+    splash.draw_all(width, height);
+
+    let mut request = SplashRequest::zeroed();
+    loop {
+        if splash.progress.tick(PROGRESS_STEP) {
+            splash.draw_bar();
+        }
+        match recv_request(endpoint, &mut request, FRAME_MS) {
+            Ok(true) => {}
+            Ok(false) | Err(_) => continue,
+        }
+
+        match request.msg_type {
+            SPLASH_MSG_PROGRESS if request.sender_pid == INIT_PID => {
+                if let Some(milestone) = Milestone::decode(&request.payload) {
+                    splash.progress.set_target(milestone.permille());
+                    splash.draw_message(&milestone);
+                }
+            }
+            SPLASH_MSG_QUIT if request.sender_pid == INIT_PID => {
+                splash.settle();
+                let _ = splash.fb_request(syscall::FB_MSG_RELEASE_DISPLAY, 0);
+                exit(0);
+            }
+            // N'importe quel processus pourrait de toute façon prendre
+            // l'affichage une fois le splash parti.
+            SPLASH_MSG_HANDOFF if request.sender_pid != 0 => {
+                splash.settle();
+                let rc =
+                    splash.fb_request(syscall::FB_MSG_TRANSFER_DISPLAY, request.sender_pid as u64);
+                let _ = send_reply(request.sender_pid, &SplashReply::status(rc.min(0)));
+                if rc >= 0 {
+                    exit(0);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    exit(127)
+}
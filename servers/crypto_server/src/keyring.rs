@@ -0,0 +1,812 @@
+//! # keyring — Trousseau de secrets (mots de passe Wi-Fi, identifiants SFTP, jetons)
+//!
+//! Les secrets sont scellés XChaCha20-Poly1305 sous une clé maîtresse dérivée du
+//! mot de passe de session (Argon2id). La clé maîtresse n'existe en mémoire
+//! qu'entre `unlock` (à l'ouverture de session) et `lock` : verrouillé, le
+//! trousseau ne contient plus que des chiffrés.
+//!
+//! Le mot de passe est fixé une fois par `init` (opération privilégiée, faite
+//! par le gestionnaire de session) ; `unlock` refuse un trousseau jamais
+//! initialisé. Sel, vérificateur et chiffrés sont réécrits dans
+//! `/var/lib/exo/keyring` après chaque modification et relus au démarrage :
+//! le fichier ne contient jamais de clair ni de clé.
+//!
+//! Les libellés suivent la convention `<domaine>:<identifiant>` — `wifi:HomeNet`,
+//! `sftp:alice@build.lan:22` (cf. `exo_fs::Location::credential_key`), `app:<nom>`,
+//! `ssh-host:build.lan:22` (clés d'hôte SSH connues, cf. `exo_ssh::known_hosts`).
+//!
+//! ## Règles
+//! - SRV-02 : un secret n'est rendu qu'à son propriétaire (principal du cap_token)
+//! - NS-01 : uniquement spin + tableaux statiques, pas de heap
+//! - CAP-01 : le libellé et le propriétaire sont liés au chiffré via l'AAD
+
+use spin::Mutex;
+
+use crate::keystore::KEY_SIZE;
+use crate::xchacha20::{self, NONCE_SIZE, TAG_SIZE};
+use crate::{secure_random, wipe_bytes};
+use exo_syscall_abi as syscall;
+
+// ── Constantes ───────────────────────────────────────────────────────────────
+
+/// Nombre maximum de secrets dans le trousseau.
+const MAX_ENTRIES: usize = 32;
+/// Quota par principal, même logique que `MAX_KEYS_PER_OWNER` du keystore.
+const MAX_ENTRIES_PER_OWNER: usize = 16;
+/// Longueur maximale d'un libellé.
+pub const LABEL_MAX: usize = 64;
+/// Longueur maximale d'un secret en clair.
+pub const SECRET_MAX: usize = 96;
+/// Longueur maximale du mot de passe de session.
+pub const PASSWORD_MAX: usize = 128;
+
+const SALT_SIZE: usize = 16;
+const SEALED_MAX: usize = SECRET_MAX + TAG_SIZE;
+const AAD_MAX: usize = 8 + LABEL_MAX;
+
+/// Argon2id m=4096 KiB, t=3, p=1. Sans heap, la mémoire de travail est un
+/// tableau statique : 4 MiB au lieu des 64 MiB de fscrypt, compensés par t=3.
+const KDF_MEMORY_BLOCKS: usize = 4096;
+const KDF_PASSES: u32 = 3;
+
+/// Mauvais mots de passe tolérés par principal avant le premier délai.
+const UNLOCK_FREE_FAILURES: u32 = 3;
+/// Premier délai après `UNLOCK_FREE_FAILURES` échecs, doublé à chaque échec
+/// suivant jusqu'à `UNLOCK_DELAY_MAX_NS`.
+const UNLOCK_DELAY_BASE_NS: u64 = 1_000_000_000;
+const UNLOCK_DELAY_MAX_NS: u64 = 15 * 60 * 1_000_000_000;
+/// Principaux suivis ; un nouveau venu prend la place du moins pénalisé.
+const UNLOCK_TRACKED: usize = 16;
+/// Mauvais mots de passe tolérés tous principaux confondus avant le délai
+/// global : changer de principal ne remet pas la recherche à zéro.
+const UNLOCK_GLOBAL_FREE_FAILURES: u32 = 10;
+
+/// Clair scellé par `init` ; sert à détecter un mauvais mot de passe.
+const VERIFIER_PLAINTEXT: &[u8; 16] = b"exo-keyring-v1\0\0";
+const VERIFIER_SIZE: usize = VERIFIER_PLAINTEXT.len() + TAG_SIZE;
+
+/// Image persistée : magic ‖ sel ‖ nonce et chiffré du vérificateur ‖
+/// nombre d'entrées (u8) ‖ entrées de taille fixe.
+const IMAGE_MAGIC: &[u8; 8] = b"EXOKRNG1";
+pub(crate) const IMAGE_HEADER: usize =
+    IMAGE_MAGIC.len() + SALT_SIZE + NONCE_SIZE + VERIFIER_SIZE + 1;
+/// principal (LE) ‖ longueur ‖ libellé ‖ nonce ‖ longueur ‖ chiffré.
+pub(crate) const IMAGE_RECORD: usize = 8 + 1 + LABEL_MAX + NONCE_SIZE + 1 + SEALED_MAX;
+pub(crate) const IMAGE_MAX: usize = IMAGE_HEADER + MAX_ENTRIES * IMAGE_RECORD;
+
+/// Chemins terminés par NUL ; répertoires créés au démarrage.
+const VAULT_DIRS: [&[u8]; 3] = [b"/var\0", b"/var/lib\0", b"/var/lib/exo\0"];
+const VAULT_PATH: &[u8] = b"/var/lib/exo/keyring\0";
+const VAULT_TMP_PATH: &[u8] = b"/var/lib/exo/keyring.tmp\0";
+
+// ── Erreurs ──────────────────────────────────────────────────────────────────
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyringError {
+    /// Paramètres invalides (libellé vide, trop long…).
+    Args,
+    /// Trousseau verrouillé.
+    Locked,
+    /// Aucun mot de passe n'a encore été fixé par `init`.
+    Uninitialized,
+    /// `init` sur un trousseau qui a déjà un mot de passe.
+    Initialized,
+    /// Mot de passe incorrect.
+    BadPassword,
+    /// Chiffré altéré : l'AEAD le refuse sous la bonne clé maîtresse.
+    Corrupt,
+    /// Trop de mauvais mots de passe : le principal doit attendre avant de
+    /// réessayer.
+    Throttled,
+    /// Verrouillage demandé par un autre principal que celui qui a ouvert.
+    Denied,
+    /// Aucun secret sous ce libellé pour ce principal.
+    NotFound,
+    /// Trousseau ou quota plein, échec CSPRNG/KDF ou écriture disque.
+    Busy,
+}
+
+// ── Structures ───────────────────────────────────────────────────────────────
+
+#[derive(Clone, Copy)]
+pub(crate) struct Entry {
+    in_use: bool,
+    pub(crate) owner_principal: u64,
+    label_len: u8,
+    label: [u8; LABEL_MAX],
+    nonce: [u8; NONCE_SIZE],
+    sealed_len: u8,
+    pub(crate) sealed: [u8; SEALED_MAX],
+}
+
+impl Entry {
+    const fn empty() -> Self {
+        Self {
+            in_use: false,
+            owner_principal: 0,
+            label_len: 0,
+            label: [0u8; LABEL_MAX],
+            nonce: [0u8; NONCE_SIZE],
+            sealed_len: 0,
+            sealed: [0u8; SEALED_MAX],
+        }
+    }
+
+    fn label(&self) -> &[u8] {
+        &self.label[..self.label_len as usize]
+    }
+
+    fn matches(&self, owner_principal: u64, label: &[u8]) -> bool {
+        self.in_use && self.owner_principal == owner_principal && self.label() == label
+    }
+}
+
+/// Échecs de déverrouillage d'un principal (ou de tous, pour le compteur
+/// global).
+#[derive(Clone, Copy)]
+struct Failures {
+    principal: u64,
+    count: u32,
+    /// Aucun essai accepté avant cet instant (horloge monotone).
+    retry_at_ns: u64,
+}
+
+impl Failures {
+    const NONE: Self = Self {
+        principal: 0,
+        count: 0,
+        retry_at_ns: 0,
+    };
+
+    /// Compte un échec ; à partir de `free` échecs, impose un délai doublé à
+    /// chaque nouvel échec.
+    fn record(&mut self, free: u32, now_ns: u64) {
+        self.count = self.count.saturating_add(1);
+        if self.count >= free {
+            let doublings = (self.count - free).min(20);
+            let delay = (UNLOCK_DELAY_BASE_NS << doublings).min(UNLOCK_DELAY_MAX_NS);
+            self.retry_at_ns = now_ns.saturating_add(delay);
+        }
+    }
+}
+
+/// Freine la recherche du mot de passe par IPC : chaque principal a droit à
+/// `UNLOCK_FREE_FAILURES` erreurs, puis à un essai par délai, doublé à chaque
+/// nouvel échec. Un compteur global fait de même au-delà de
+/// `UNLOCK_GLOBAL_FREE_FAILURES` erreurs, pour qui multiplie les principaux.
+pub(crate) struct UnlockThrottle {
+    slots: [Failures; UNLOCK_TRACKED],
+    global: Failures,
+}
+
+impl UnlockThrottle {
+    const fn new() -> Self {
+        Self {
+            slots: [Failures::NONE; UNLOCK_TRACKED],
+            global: Failures::NONE,
+        }
+    }
+
+    fn find(&self, principal: u64) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|f| f.count != 0 && f.principal == principal)
+    }
+
+    /// Délai restant avant le prochain essai de `principal`, `0` s'il peut essayer.
+    pub(crate) fn remaining_ns(&self, principal: u64, now_ns: u64) -> u64 {
+        let own = self
+            .find(principal)
+            .map_or(0, |idx| self.slots[idx].retry_at_ns.saturating_sub(now_ns));
+        own.max(self.global.retry_at_ns.saturating_sub(now_ns))
+    }
+
+    fn failed(&mut self, principal: u64, now_ns: u64) {
+        let idx = self.find(principal).unwrap_or_else(|| {
+            let idx = (0..UNLOCK_TRACKED)
+                .min_by_key(|&i| (self.slots[i].count, self.slots[i].retry_at_ns))
+                .unwrap_or(0);
+            self.slots[idx] = Failures {
+                principal,
+                count: 0,
+                retry_at_ns: 0,
+            };
+            idx
+        });
+        self.slots[idx].record(UNLOCK_FREE_FAILURES, now_ns);
+        self.global.record(UNLOCK_GLOBAL_FREE_FAILURES, now_ns);
+    }
+
+    /// Le bon mot de passe a été donné : la recherche, s'il y en avait une,
+    /// n'a plus d'objet.
+    fn succeeded(&mut self, principal: u64) {
+        if let Some(idx) = self.find(principal) {
+            self.slots[idx].count = 0;
+        }
+        self.global = Failures::NONE;
+    }
+}
+
+pub(crate) struct Vault {
+    pub(crate) unlocked: bool,
+    /// Principal qui a ouvert le trousseau : lui seul le verrouille sans
+    /// privilège.
+    unlocked_by: u64,
+    /// `false` tant que `init` n'a pas fixé le mot de passe.
+    initialized: bool,
+    pub(crate) master_key: [u8; KEY_SIZE],
+    salt: [u8; SALT_SIZE],
+    verifier_nonce: [u8; NONCE_SIZE],
+    verifier: [u8; VERIFIER_SIZE],
+    pub(crate) entries: [Entry; MAX_ENTRIES],
+    /// Survit aux `restore` : recharger l'image n'efface pas les pénalités.
+    pub(crate) throttle: UnlockThrottle,
+}
+
+static VAULT: Mutex<Vault> = Mutex::new(Vault::new());
+
+/// Tampon de l'image persistée (NS-01 : pas de heap). Pris après `VAULT`.
+static IMAGE: Mutex<[u8; IMAGE_MAX]> = Mutex::new([0u8; IMAGE_MAX]);
+
+static KDF_MEMORY: Mutex<[argon2::Block; KDF_MEMORY_BLOCKS]> =
+    Mutex::new([argon2::Block::new(); KDF_MEMORY_BLOCKS]);
+
+// ── Helpers internes ─────────────────────────────────────────────────────────
+
+fn derive_master_key(password: &[u8], salt: &[u8; SALT_SIZE], out: &mut [u8; KEY_SIZE]) -> bool {
+    let Ok(params) = argon2::Params::new(KDF_MEMORY_BLOCKS as u32, KDF_PASSES, 1, Some(KEY_SIZE))
+    else {
+        return false;
+    };
+    let kdf = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+    let mut memory = KDF_MEMORY.lock();
+    let ok = kdf
+        .hash_password_into_with_memory(password, salt, out, &mut *memory)
+        .is_ok();
+    for block in memory.iter_mut() {
+        *block = argon2::Block::new();
+    }
+    ok
+}
+
+/// AAD = principal (LE) ‖ libellé : un chiffré déplacé vers un autre slot ou
+/// un autre propriétaire ne s'ouvre plus.
+fn entry_aad(owner_principal: u64, label: &[u8], out: &mut [u8; AAD_MAX]) -> usize {
+    out[..8].copy_from_slice(&owner_principal.to_le_bytes());
+    out[8..8 + label.len()].copy_from_slice(label);
+    8 + label.len()
+}
+
+fn check_label(label: &[u8]) -> Result<(), KeyringError> {
+    if label.is_empty() || label.len() > LABEL_MAX {
+        return Err(KeyringError::Args);
+    }
+    Ok(())
+}
+
+fn check_password(password: &[u8]) -> Result<(), KeyringError> {
+    if password.is_empty() || password.len() > PASSWORD_MAX {
+        return Err(KeyringError::Args);
+    }
+    Ok(())
+}
+
+impl Vault {
+    pub(crate) const fn new() -> Self {
+        Self {
+            unlocked: false,
+            unlocked_by: 0,
+            initialized: false,
+            master_key: [0u8; KEY_SIZE],
+            salt: [0u8; SALT_SIZE],
+            verifier_nonce: [0u8; NONCE_SIZE],
+            verifier: [0u8; VERIFIER_SIZE],
+            entries: [Entry::empty(); MAX_ENTRIES],
+            throttle: UnlockThrottle::new(),
+        }
+    }
+
+    /// Revient au trousseau vierge sans effacer les pénalités de
+    /// déverrouillage.
+    pub(crate) fn reset(&mut self) {
+        let throttle = core::mem::replace(&mut self.throttle, UnlockThrottle::new());
+        *self = Self::new();
+        self.throttle = throttle;
+    }
+
+    /// Fixe le mot de passe : tire le sel et scelle le vérificateur. Le
+    /// trousseau reste verrouillé.
+    pub(crate) fn init(&mut self, password: &[u8]) -> Result<(), KeyringError> {
+        check_password(password)?;
+        if self.initialized {
+            return Err(KeyringError::Initialized);
+        }
+
+        let mut salt = [0u8; SALT_SIZE];
+        if !secure_random(&mut salt) {
+            return Err(KeyringError::Busy);
+        }
+        let mut key = [0u8; KEY_SIZE];
+        if !derive_master_key(password, &salt, &mut key) {
+            wipe_bytes(&mut key);
+            return Err(KeyringError::Busy);
+        }
+        let mut nonce = [0u8; NONCE_SIZE];
+        let mut verifier = [0u8; VERIFIER_SIZE];
+        let sealed =
+            xchacha20::xchacha20_seal(&key, VERIFIER_PLAINTEXT, &[], &mut nonce, &mut verifier);
+        wipe_bytes(&mut key);
+        if sealed != VERIFIER_SIZE {
+            return Err(KeyringError::Busy);
+        }
+
+        self.salt = salt;
+        self.verifier_nonce = nonce;
+        self.verifier = verifier;
+        self.initialized = true;
+        Ok(())
+    }
+
+    /// `now_ns` : horloge monotone, pour les délais après mauvais mot de passe.
+    pub(crate) fn unlock(
+        &mut self,
+        principal: u64,
+        password: &[u8],
+        now_ns: u64,
+    ) -> Result<(), KeyringError> {
+        check_password(password)?;
+        if !self.initialized {
+            return Err(KeyringError::Uninitialized);
+        }
+        if self.throttle.remaining_ns(principal, now_ns) != 0 {
+            return Err(KeyringError::Throttled);
+        }
+
+        let mut key = [0u8; KEY_SIZE];
+        if !derive_master_key(password, &self.salt, &mut key) {
+            wipe_bytes(&mut key);
+            return Err(KeyringError::Busy);
+        }
+        let mut probe = [0u8; VERIFIER_PLAINTEXT.len()];
+        let opened =
+            xchacha20::xchacha20_open(&key, &self.verifier_nonce, &self.verifier, &[], &mut probe);
+        if opened != VERIFIER_PLAINTEXT.len() || &probe != VERIFIER_PLAINTEXT {
+            wipe_bytes(&mut key);
+            self.throttle.failed(principal, now_ns);
+            return Err(KeyringError::BadPassword);
+        }
+        self.throttle.succeeded(principal);
+
+        self.master_key = key;
+        self.unlocked = true;
+        self.unlocked_by = principal;
+        wipe_bytes(&mut key);
+        Ok(())
+    }
+
+    /// `privileged` : l'appelant détient le droit d'administration
+    /// (gestionnaire de session) et peut verrouiller pour un autre.
+    pub(crate) fn lock(&mut self, principal: u64, privileged: bool) -> Result<(), KeyringError> {
+        if !self.unlocked {
+            return Ok(());
+        }
+        if principal != self.unlocked_by && !privileged {
+            return Err(KeyringError::Denied);
+        }
+        wipe_bytes(&mut self.master_key);
+        self.unlocked = false;
+        self.unlocked_by = 0;
+        Ok(())
+    }
+
+    pub(crate) fn set(
+        &mut self,
+        owner_principal: u64,
+        label: &[u8],
+        secret: &[u8],
+    ) -> Result<(), KeyringError> {
+        check_label(label)?;
+        if secret.len() > SECRET_MAX {
+            return Err(KeyringError::Args);
+        }
+        if !self.unlocked {
+            return Err(KeyringError::Locked);
+        }
+
+        let slot = match self
+            .entries
+            .iter()
+            .position(|e| e.matches(owner_principal, label))
+        {
+            Some(idx) => idx,
+            None => {
+                let owned = self
+                    .entries
+                    .iter()
+                    .filter(|e| e.in_use && e.owner_principal == owner_principal)
+                    .count();
+                if owned >= MAX_ENTRIES_PER_OWNER {
+                    return Err(KeyringError::Busy);
+                }
+                self.entries
+                    .iter()
+                    .position(|e| !e.in_use)
+                    .ok_or(KeyringError::Busy)?
+            }
+        };
+
+        let mut aad = [0u8; AAD_MAX];
+        let aad_len = entry_aad(owner_principal, label, &mut aad);
+        let mut nonce = [0u8; NONCE_SIZE];
+        let mut sealed = [0u8; SEALED_MAX];
+        let sealed_len = xchacha20::xchacha20_seal(
+            &self.master_key,
+            secret,
+            &aad[..aad_len],
+            &mut nonce,
+            &mut sealed,
+        );
+        if sealed_len != secret.len() + TAG_SIZE {
+            return Err(KeyringError::Busy);
+        }
+
+        let entry = &mut self.entries[slot];
+        entry.in_use = true;
+        entry.owner_principal = owner_principal;
+        entry.label_len = label.len() as u8;
+        entry.label[..label.len()].copy_from_slice(label);
+        entry.nonce = nonce;
+        entry.sealed_len = sealed_len as u8;
+        entry.sealed = sealed;
+        Ok(())
+    }
+
+    pub(crate) fn get(
+        &self,
+        owner_principal: u64,
+        label: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, KeyringError> {
+        check_label(label)?;
+        if !self.unlocked {
+            return Err(KeyringError::Locked);
+        }
+        let entry = self
+            .entries
+            .iter()
+            .find(|e| e.matches(owner_principal, label))
+            .ok_or(KeyringError::NotFound)?;
+
+        let sealed_len = entry.sealed_len as usize;
+        if out.len() < sealed_len - TAG_SIZE {
+            return Err(KeyringError::Args);
+        }
+        let mut aad = [0u8; AAD_MAX];
+        let aad_len = entry_aad(owner_principal, label, &mut aad);
+        let n = xchacha20::xchacha20_open(
+            &self.master_key,
+            &entry.nonce,
+            &entry.sealed[..sealed_len],
+            &aad[..aad_len],
+            out,
+        );
+        // Un secret vide scelle exactement TAG_SIZE octets : n == 0 est alors légitime.
+        if n == 0 && sealed_len != TAG_SIZE {
+            return Err(KeyringError::Corrupt);
+        }
+        Ok(n)
+    }
+
+    pub(crate) fn delete(
+        &mut self,
+        owner_principal: u64,
+        label: &[u8],
+    ) -> Result<(), KeyringError> {
+        check_label(label)?;
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|e| e.matches(owner_principal, label))
+            .ok_or(KeyringError::NotFound)?;
+        wipe_bytes(&mut entry.sealed);
+        *entry = Entry::empty();
+        Ok(())
+    }
+
+    pub(crate) fn search(
+        &self,
+        owner_principal: u64,
+        prefix: &[u8],
+        skip: usize,
+        out: &mut [u8],
+    ) -> (usize, u32) {
+        let mut written = 0usize;
+        let mut total = 0u32;
+        let mut full = false;
+
+        for entry in self.entries.iter() {
+            if !entry.in_use
+                || entry.owner_principal != owner_principal
+                || !entry.label().starts_with(prefix)
+            {
+                continue;
+            }
+            total += 1;
+            if (total as usize) <= skip || full {
+                continue;
+            }
+            let label = entry.label();
+            if written + 1 + label.len() > out.len() {
+                full = true;
+                continue;
+            }
+            out[written] = label.len() as u8;
+            out[written + 1..written + 1 + label.len()].copy_from_slice(label);
+            written += 1 + label.len();
+        }
+        (written, total)
+    }
+
+    /// Sérialise sel, vérificateur et chiffrés dans `out` ; la clé maîtresse
+    /// n'en fait jamais partie. Retourne la longueur de l'image.
+    pub(crate) fn encode(&self, out: &mut [u8; IMAGE_MAX]) -> usize {
+        let mut at = 0;
+        for field in [
+            &IMAGE_MAGIC[..],
+            &self.salt[..],
+            &self.verifier_nonce[..],
+            &self.verifier[..],
+        ] {
+            out[at..at + field.len()].copy_from_slice(field);
+            at += field.len();
+        }
+        let count_at = at;
+        at += 1;
+
+        let mut count = 0u8;
+        for entry in self.entries.iter().filter(|e| e.in_use) {
+            let record = &mut out[at..at + IMAGE_RECORD];
+            record[..8].copy_from_slice(&entry.owner_principal.to_le_bytes());
+            record[8] = entry.label_len;
+            record[9..9 + LABEL_MAX].copy_from_slice(&entry.label);
+            let nonce_at = 9 + LABEL_MAX;
+            record[nonce_at..nonce_at + NONCE_SIZE].copy_from_slice(&entry.nonce);
+            let sealed_at = nonce_at + NONCE_SIZE;
+            record[sealed_at] = entry.sealed_len;
+            record[sealed_at + 1..].copy_from_slice(&entry.sealed);
+            at += IMAGE_RECORD;
+            count += 1;
+        }
+        out[count_at] = count;
+        at
+    }
+
+    /// Recharge une image produite par `encode`. Le trousseau reste verrouillé ;
+    /// une image tronquée ou incohérente est refusée sans rien modifier.
+    pub(crate) fn restore(&mut self, image: &[u8]) -> bool {
+        if image.len() < IMAGE_HEADER || &image[..IMAGE_MAGIC.len()] != IMAGE_MAGIC {
+            return false;
+        }
+        let count = image[IMAGE_HEADER - 1] as usize;
+        if count > MAX_ENTRIES || image.len() != IMAGE_HEADER + count * IMAGE_RECORD {
+            return false;
+        }
+
+        let mut entries = [Entry::empty(); MAX_ENTRIES];
+        for (entry, record) in entries
+            .iter_mut()
+            .zip(image[IMAGE_HEADER..].chunks_exact(IMAGE_RECORD))
+        {
+            let label_len = record[8] as usize;
+            let nonce_at = 9 + LABEL_MAX;
+            let sealed_at = nonce_at + NONCE_SIZE;
+            let sealed_len = record[sealed_at] as usize;
+            if label_len == 0
+                || label_len > LABEL_MAX
+                || !(TAG_SIZE..=SEALED_MAX).contains(&sealed_len)
+            {
+                return false;
+            }
+            let mut owner = [0u8; 8];
+            owner.copy_from_slice(&record[..8]);
+            entry.in_use = true;
+            entry.owner_principal = u64::from_le_bytes(owner);
+            entry.label_len = label_len as u8;
+            entry.label.copy_from_slice(&record[9..nonce_at]);
+            entry.nonce.copy_from_slice(&record[nonce_at..sealed_at]);
+            entry.sealed_len = sealed_len as u8;
+            entry.sealed.copy_from_slice(&record[sealed_at + 1..]);
+        }
+
+        let mut at = IMAGE_MAGIC.len();
+        for field in [
+            &mut self.salt[..],
+            &mut self.verifier_nonce[..],
+            &mut self.verifier[..],
+        ] {
+            let len = field.len();
+            field.copy_from_slice(&image[at..at + len]);
+            at += len;
+        }
+        wipe_bytes(&mut self.master_key);
+        self.unlocked = false;
+        self.unlocked_by = 0;
+        self.initialized = true;
+        self.entries = entries;
+        true
+    }
+}
+
+// ── Persistance ──────────────────────────────────────────────────────────────
+
+/// Lit au plus `buf.len()` octets de `path` ; `0` si absent.
+fn read_file(path: &[u8], buf: &mut [u8]) -> usize {
+    // SAFETY: chemin statique terminé par NUL.
+    let fd =
+        unsafe { syscall::syscall2(syscall::SYS_OPEN, path.as_ptr() as u64, syscall::O_RDONLY) };
+    if fd < 0 {
+        return 0;
+    }
+    let mut len = 0;
+    while len < buf.len() {
+        // SAFETY: écriture bornée à la fin du buffer.
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_READ,
+                fd as u64,
+                buf[len..].as_mut_ptr() as u64,
+                (buf.len() - len) as u64,
+            )
+        };
+        if n <= 0 {
+            break;
+        }
+        len += n as usize;
+    }
+    // SAFETY: fermeture du descripteur ouvert ci-dessus.
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+    len
+}
+
+/// Écrit `data` dans `path`, lisible du seul propriétaire (0600).
+fn write_file(path: &[u8], data: &[u8]) -> bool {
+    // SAFETY: chemin statique terminé par NUL.
+    let fd = unsafe {
+        syscall::syscall3(
+            syscall::SYS_OPEN,
+            path.as_ptr() as u64,
+            syscall::O_WRONLY | syscall::O_CREAT | syscall::O_TRUNC,
+            0o600,
+        )
+    };
+    if fd < 0 {
+        return false;
+    }
+    let mut done = 0;
+    while done < data.len() {
+        // SAFETY: lecture bornée à `data`.
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_WRITE,
+                fd as u64,
+                data[done..].as_ptr() as u64,
+                (data.len() - done) as u64,
+            )
+        };
+        if n <= 0 {
+            break;
+        }
+        done += n as usize;
+    }
+    // SAFETY: fermeture du descripteur ouvert ci-dessus.
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+    done == data.len()
+}
+
+/// Réécrit l'image (fichier temporaire puis renommage) : un arrêt brutal
+/// laisse l'ancienne image ou la nouvelle, jamais un mélange.
+fn save(vault: &Vault) -> Result<(), KeyringError> {
+    let mut image = IMAGE.lock();
+    let len = vault.encode(&mut image);
+    if !write_file(VAULT_TMP_PATH, &image[..len]) {
+        return Err(KeyringError::Busy);
+    }
+    // SAFETY: chemins statiques terminés par NUL.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_RENAME,
+            VAULT_TMP_PATH.as_ptr() as u64,
+            VAULT_PATH.as_ptr() as u64,
+        )
+    };
+    if rc < 0 {
+        return Err(KeyringError::Busy);
+    }
+    Ok(())
+}
+
+/// Persiste `vault` par `persist` ; en cas d'échec, remet les entrées
+/// `before` d'avant la modification : la mémoire ne s'écarte jamais de
+/// l'image sur disque, qui serait seule relue au redémarrage.
+pub(crate) fn commit(
+    vault: &mut Vault,
+    before: &[Entry; MAX_ENTRIES],
+    persist: impl FnOnce(&Vault) -> Result<(), KeyringError>,
+) -> Result<(), KeyringError> {
+    let result = persist(vault);
+    if result.is_err() {
+        vault.entries = *before;
+    }
+    result
+}
+
+// ── API publique ─────────────────────────────────────────────────────────────
+
+/// Recharge le trousseau persisté, verrouillé. Appelé une fois au démarrage ;
+/// retourne `false` s'il n'existe pas encore (ou est illisible).
+pub fn load() -> bool {
+    for dir in VAULT_DIRS {
+        // SAFETY: chemin statique terminé par NUL.
+        let _ = unsafe { syscall::syscall2(syscall::SYS_MKDIR, dir.as_ptr() as u64, 0o755) };
+    }
+    let mut vault = VAULT.lock();
+    let mut image = IMAGE.lock();
+    let len = read_file(VAULT_PATH, &mut image[..]);
+    len != 0 && vault.restore(&image[..len])
+}
+
+/// Fixe le mot de passe de session d'un trousseau vierge. Opération
+/// privilégiée : l'appelant est vérifié par le dispatcher.
+pub fn init(password: &[u8]) -> Result<(), KeyringError> {
+    let mut vault = VAULT.lock();
+    vault.init(password)?;
+    if let Err(err) = save(&vault) {
+        // Non persisté, le mot de passe serait perdu au redémarrage.
+        vault.reset();
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// Ouvre le trousseau avec le mot de passe de session ; `principal` devient
+/// celui qui peut le verrouiller. Après plusieurs mauvais mots de passe, le
+/// principal reçoit `Throttled` jusqu'à la fin de son délai.
+pub fn unlock(principal: u64, password: &[u8], now_ns: u64) -> Result<(), KeyringError> {
+    VAULT.lock().unlock(principal, password, now_ns)
+}
+
+/// Verrouille le trousseau : la clé maîtresse est effacée, les chiffrés restent.
+/// Réservé au principal qui l'a ouvert, sauf appelant `privileged`.
+pub fn lock(principal: u64, privileged: bool) -> Result<(), KeyringError> {
+    VAULT.lock().lock(principal, privileged)
+}
+
+/// Enregistre (ou remplace) le secret `label` du principal.
+pub fn set(owner_principal: u64, label: &[u8], secret: &[u8]) -> Result<(), KeyringError> {
+    let mut vault = VAULT.lock();
+    let before = vault.entries;
+    vault.set(owner_principal, label, secret)?;
+    commit(&mut vault, &before, save)
+}
+
+/// Déchiffre le secret `label` du principal dans `out`. Retourne sa longueur.
+pub fn get(owner_principal: u64, label: &[u8], out: &mut [u8]) -> Result<usize, KeyringError> {
+    VAULT.lock().get(owner_principal, label, out)
+}
+
+/// Supprime le secret `label` du principal. Autorisé trousseau verrouillé.
+pub fn delete(owner_principal: u64, label: &[u8]) -> Result<(), KeyringError> {
+    let mut vault = VAULT.lock();
+    let before = vault.entries;
+    vault.delete(owner_principal, label)?;
+    commit(&mut vault, &before, save)
+}
+
+/// Écrit dans `out` les libellés du principal commençant par `prefix`, encodés
+/// `[len u8][libellé]`, à partir du `skip`-ième résultat. Retourne
+/// `(octets écrits, total des correspondances)` pour que l'appelant pagine.
+///
+/// Seuls les libellés sortent : la recherche fonctionne trousseau verrouillé.
+pub fn search(owner_principal: u64, prefix: &[u8], skip: usize, out: &mut [u8]) -> (usize, u32) {
+    VAULT.lock().search(owner_principal, prefix, skip, out)
+}
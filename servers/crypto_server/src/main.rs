@@ -17,6 +17,7 @@ use exo_syscall_abi as syscall;
 use keystore::{KeyType, KEY_SIZE};
 use xchacha20::{NONCE_SIZE, TAG_SIZE};
 
+mod keyring;
 mod keystore;
 mod pki;
 mod tls;
@@ -47,6 +48,13 @@ const CRYPTO_KEY_REVOKE_OWNER: u32 = 12;
 const CRYPTO_KEY_STATS: u32 = 13;
 const CRYPTO_TLS_ENCRYPT: u32 = 14;
 const CRYPTO_TLS_DECRYPT: u32 = 15;
+const CRYPTO_KEYRING_UNLOCK: u32 = 16;
+const CRYPTO_KEYRING_LOCK: u32 = 17;
+const CRYPTO_KEYRING_SET: u32 = 18;
+const CRYPTO_KEYRING_GET: u32 = 19;
+const CRYPTO_KEYRING_SEARCH: u32 = 20;
+const CRYPTO_KEYRING_DELETE: u32 = 21;
+const CRYPTO_KEYRING_INIT: u32 = 22;
 const PHOENIX_WAKE_ENTROPY: u32 = 255;
 const KERNEL_EPHEMERAL_REPLY_BIT: u64 = 1u64 << 63;
 
@@ -62,6 +70,11 @@ const CRYPTO_ERR_CAP: u32 = 2;
 const CRYPTO_ERR_KEY_INVALID: u32 = 3;
 const CRYPTO_ERR_AUTH: u32 = 4;
 const CRYPTO_ERR_BUSY: u32 = 5;
+const CRYPTO_ERR_LOCKED: u32 = 6;
+const CRYPTO_ERR_UNINITIALIZED: u32 = 7;
+/// Trop de mauvais mots de passe : réessayer plus tard.
+const CRYPTO_ERR_RETRY: u32 = 8;
+const CLOCK_MONOTONIC: u64 = 1;
 const IPC_RECV_TIMEOUT_MS: u64 = 5_000;
 const IPC_FLAG_TIMEOUT: u64 = syscall::IPC_FLAG_TIMEOUT;
 const ETIMEDOUT: i64 = syscall::ETIMEDOUT;
@@ -104,6 +117,30 @@ pub(crate) fn secure_random(buf: &mut [u8]) -> bool {
     r >= 0 && (r as usize) == buf.len()
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+/// Horloge monotone en ns ; `0` si illisible (aucun délai ne s'écoule alors).
+fn monotonic_ns() -> u64 {
+    let mut ts = Timespec::default();
+    // SAFETY: le noyau écrit dans `ts`, structure locale.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_CLOCK_GETTIME,
+            CLOCK_MONOTONIC,
+            &mut ts as *mut Timespec as u64,
+        )
+    };
+    if rc != 0 || ts.tv_sec < 0 || ts.tv_nsec < 0 {
+        return 0;
+    }
+    (ts.tv_sec as u64).saturating_mul(1_000_000_000) + ts.tv_nsec as u64
+}
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 struct CryptoRequest {
@@ -241,6 +278,28 @@ fn write_u32_le(buf: &mut [u8], offset: usize, value: u32) -> bool {
     true
 }
 
+/// Champ `[len u8][octets]` à `offset`. Retourne le champ et l'offset suivant.
+#[inline(always)]
+fn read_len_prefixed(buf: &[u8], offset: usize) -> Option<(&[u8], usize)> {
+    let len = *buf.get(offset)? as usize;
+    let field = buf.get(offset + 1..offset + 1 + len)?;
+    Some((field, offset + 1 + len))
+}
+
+fn keyring_status(err: keyring::KeyringError) -> u32 {
+    match err {
+        keyring::KeyringError::Args => CRYPTO_ERR_ARGS,
+        keyring::KeyringError::Locked => CRYPTO_ERR_LOCKED,
+        keyring::KeyringError::Uninitialized => CRYPTO_ERR_UNINITIALIZED,
+        keyring::KeyringError::Initialized => CRYPTO_ERR_BUSY,
+        keyring::KeyringError::BadPassword | keyring::KeyringError::Corrupt => CRYPTO_ERR_AUTH,
+        keyring::KeyringError::Denied => CRYPTO_ERR_CAP,
+        keyring::KeyringError::NotFound => CRYPTO_ERR_KEY_INVALID,
+        keyring::KeyringError::Busy => CRYPTO_ERR_BUSY,
+        keyring::KeyringError::Throttled => CRYPTO_ERR_RETRY,
+    }
+}
+
 fn derive_key_hkdf(material: &[u8], output: &mut [u8; KEY_SIZE]) {
     let salt = [0u8; 32];
     let prk = blake3::keyed_hash(&salt, material);
@@ -392,6 +451,21 @@ fn authorize_request(req: &CryptoRequest) -> Result<u64, u32> {
     Ok(principal)
 }
 
+/// Droit d'administration (gestionnaire de session, root) : requis pour fixer
+/// le mot de passe du trousseau ou le verrouiller pour un autre principal.
+fn is_privileged(req: &CryptoRequest) -> bool {
+    // SAFETY: jeton copié dans la requête, lu seulement par le noyau.
+    let rc = unsafe {
+        syscall::exo_cap_check(
+            &req.cap_token,
+            syscall::EXO_CAP_RIGHT_IPC_MANAGE,
+            CRYPTO_SERVER_PID,
+            syscall::EXO_CAP_TYPE_IPC_ENDPOINT,
+        )
+    };
+    rc >= 0
+}
+
 fn phoenix_wake_entropy_from_request(req: &CryptoRequest, payload: &[u8]) -> Option<u64> {
     let compact_entropy = read_u64_le(&req.cap_token.bytes, 0).unwrap_or(0);
     if req.reply_endpoint == 0 && compact_entropy != 0 {
@@ -917,6 +991,80 @@ fn handle_request(req: &CryptoRequest) -> CryptoReply {
             reply.status = CRYPTO_OK;
             reply.write_data(&encoded);
         }
+        CRYPTO_KEYRING_INIT => {
+            reply.status = if !is_privileged(req) {
+                CRYPTO_ERR_CAP
+            } else {
+                match read_len_prefixed(payload, 0) {
+                    Some((password, _)) => match keyring::init(password) {
+                        Ok(()) => CRYPTO_OK,
+                        Err(err) => keyring_status(err),
+                    },
+                    None => CRYPTO_ERR_ARGS,
+                }
+            };
+        }
+        CRYPTO_KEYRING_UNLOCK => {
+            reply.status = match read_len_prefixed(payload, 0) {
+                Some((password, _)) => {
+                    match keyring::unlock(caller_principal, password, monotonic_ns()) {
+                        Ok(()) => CRYPTO_OK,
+                        Err(err) => keyring_status(err),
+                    }
+                }
+                None => CRYPTO_ERR_ARGS,
+            };
+        }
+        CRYPTO_KEYRING_LOCK => {
+            reply.status = match keyring::lock(caller_principal, is_privileged(req)) {
+                Ok(()) => CRYPTO_OK,
+                Err(err) => keyring_status(err),
+            };
+        }
+        CRYPTO_KEYRING_SET => {
+            let fields = read_len_prefixed(payload, 0)
+                .and_then(|(label, next)| Some((label, read_len_prefixed(payload, next)?.0)));
+            reply.status = match fields {
+                Some((label, secret)) => match keyring::set(caller_principal, label, secret) {
+                    Ok(()) => CRYPTO_OK,
+                    Err(err) => keyring_status(err),
+                },
+                None => CRYPTO_ERR_ARGS,
+            };
+        }
+        CRYPTO_KEYRING_GET => {
+            let Some((label, _)) = read_len_prefixed(payload, 0) else {
+                reply.status = CRYPTO_ERR_ARGS;
+                REQUESTS_ERR.fetch_add(1, Ordering::Relaxed);
+                return reply;
+            };
+            let mut secret = [0u8; keyring::SECRET_MAX];
+            match keyring::get(caller_principal, label, &mut secret) {
+                Ok(n) => {
+                    reply.status = CRYPTO_OK;
+                    reply.write_data(&secret[..n]);
+                }
+                Err(err) => reply.status = keyring_status(err),
+            }
+            wipe_bytes(&mut secret);
+        }
+        CRYPTO_KEYRING_SEARCH => {
+            let skip = payload.first().copied().unwrap_or(0) as usize;
+            let prefix = read_len_prefixed(payload, 1).map_or(&[][..], |(prefix, _)| prefix);
+            let (written, total) = keyring::search(caller_principal, prefix, skip, &mut reply.data);
+            reply.status = CRYPTO_OK;
+            reply.key_handle = total;
+            reply.data_len = written as u16;
+        }
+        CRYPTO_KEYRING_DELETE => {
+            reply.status = match read_len_prefixed(payload, 0) {
+                Some((label, _)) => match keyring::delete(caller_principal, label) {
+                    Ok(()) => CRYPTO_OK,
+                    Err(err) => keyring_status(err),
+                },
+                None => CRYPTO_ERR_ARGS,
+            };
+        }
         PHOENIX_WAKE_ENTROPY => {
             let authenticated_kernel_wake =
                 req.sender_pid == 0 || (req.reply_endpoint & KERNEL_EPHEMERAL_REPLY_BIT) != 0;
@@ -958,6 +1106,9 @@ pub extern "C" fn _start() -> ! {
     xchacha20::xchacha20_init();
     keystore::keystore_init();
     tls::tls_init();
+    if keyring::load() {
+        boot_log(b"crypto_server: keyring loaded\n");
+    }
 
     let name = b"crypto_server";
     let register_rc = unsafe {
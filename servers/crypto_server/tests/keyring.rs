@@ -0,0 +1,283 @@
+//! Trousseau hors serveur : `keyring.rs` inclus tel quel, avec l'AEAD réel
+//! et un CSPRNG de test.
+
+#[allow(dead_code)]
+#[path = "../src/xchacha20.rs"]
+mod xchacha20;
+
+#[allow(dead_code)]
+mod keystore {
+    pub const KEY_SIZE: usize = 32;
+}
+
+#[allow(dead_code)]
+#[path = "../src/keyring.rs"]
+mod keyring;
+
+use keyring::{commit, KeyringError, Vault, IMAGE_HEADER, IMAGE_MAX, IMAGE_RECORD, SECRET_MAX};
+
+fn secure_random(buf: &mut [u8]) -> bool {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static STATE: AtomicU64 = AtomicU64::new(0x9e37_79b9_7f4a_7c15);
+    for byte in buf.iter_mut() {
+        let x = STATE.fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed);
+        *byte = (x.wrapping_mul(0xbf58_476d_1ce4_e5b9) >> 56) as u8;
+    }
+    true
+}
+
+fn wipe_bytes(buf: &mut [u8]) {
+    buf.fill(0);
+}
+
+const ALICE: u64 = 0x1001;
+const BOB: u64 = 0x1002;
+const PASSWORD: &[u8] = b"correct horse";
+
+fn unlocked() -> Vault {
+    let mut vault = Vault::new();
+    vault.init(PASSWORD).unwrap();
+    vault.unlock(ALICE, PASSWORD, 0).unwrap();
+    vault
+}
+
+#[test]
+fn unlock_requires_init_and_the_right_password() {
+    let mut vault = Vault::new();
+    assert_eq!(
+        vault.unlock(ALICE, PASSWORD, 0),
+        Err(KeyringError::Uninitialized)
+    );
+    vault.init(PASSWORD).unwrap();
+    assert_eq!(vault.init(b"attacker"), Err(KeyringError::Initialized));
+    assert!(!vault.unlocked);
+    assert_eq!(
+        vault.unlock(ALICE, b"wrong", 0),
+        Err(KeyringError::BadPassword)
+    );
+    assert!(!vault.unlocked);
+    assert_eq!(vault.unlock(ALICE, PASSWORD, 0), Ok(()));
+}
+
+#[test]
+fn secrets_round_trip_per_owner() {
+    let mut vault = unlocked();
+    vault.set(ALICE, b"wifi:HomeNet", b"hunter2").unwrap();
+    vault.set(ALICE, b"app:empty", b"").unwrap();
+
+    let mut out = [0u8; SECRET_MAX];
+    assert_eq!(vault.get(ALICE, b"wifi:HomeNet", &mut out), Ok(7));
+    assert_eq!(&out[..7], b"hunter2");
+    assert_eq!(vault.get(ALICE, b"app:empty", &mut out), Ok(0));
+    assert_eq!(
+        vault.get(BOB, b"wifi:HomeNet", &mut out),
+        Err(KeyringError::NotFound)
+    );
+}
+
+#[test]
+fn locked_vault_refuses_get_and_set() {
+    let mut vault = unlocked();
+    vault.set(ALICE, b"wifi:HomeNet", b"hunter2").unwrap();
+    vault.lock(ALICE, false).unwrap();
+
+    let mut out = [0u8; SECRET_MAX];
+    assert_eq!(
+        vault.get(ALICE, b"wifi:HomeNet", &mut out),
+        Err(KeyringError::Locked)
+    );
+    assert_eq!(
+        vault.set(ALICE, b"wifi:Other", b"x"),
+        Err(KeyringError::Locked)
+    );
+    assert!(vault.master_key.iter().all(|&b| b == 0));
+}
+
+#[test]
+fn only_the_unlocker_or_a_privileged_caller_locks() {
+    let mut vault = unlocked();
+    assert_eq!(vault.lock(BOB, false), Err(KeyringError::Denied));
+    assert!(vault.unlocked);
+    assert_eq!(vault.lock(BOB, true), Ok(()));
+    assert!(!vault.unlocked);
+}
+
+#[test]
+fn tampered_ciphertext_fails_to_open() {
+    let mut vault = unlocked();
+    vault.set(ALICE, b"wifi:HomeNet", b"hunter2").unwrap();
+    vault.entries[0].sealed[0] ^= 1;
+
+    let mut out = [0u8; SECRET_MAX];
+    assert_eq!(
+        vault.get(ALICE, b"wifi:HomeNet", &mut out),
+        Err(KeyringError::Corrupt)
+    );
+
+    // Déplacé vers un autre propriétaire, le chiffré intact ne s'ouvre pas non plus.
+    vault.entries[0].sealed[0] ^= 1;
+    vault.entries[0].owner_principal = BOB;
+    assert_eq!(
+        vault.get(BOB, b"wifi:HomeNet", &mut out),
+        Err(KeyringError::Corrupt)
+    );
+}
+
+#[test]
+fn image_restores_a_locked_vault() {
+    let mut vault = unlocked();
+    vault.set(ALICE, b"wifi:HomeNet", b"hunter2").unwrap();
+    vault.set(BOB, b"sftp:bob@build.lan:22", b"s3cret").unwrap();
+    let mut image = [0u8; IMAGE_MAX];
+    let len = vault.encode(&mut image);
+    assert_eq!(len, IMAGE_HEADER + 2 * IMAGE_RECORD);
+
+    let mut restored = Vault::new();
+    assert!(!restored.restore(&image[..len - 1]));
+    assert!(restored.restore(&image[..len]));
+    assert!(!restored.unlocked);
+    assert_eq!(restored.init(PASSWORD), Err(KeyringError::Initialized));
+    assert_eq!(
+        restored.unlock(BOB, b"wrong", 0),
+        Err(KeyringError::BadPassword)
+    );
+    restored.unlock(BOB, PASSWORD, 0).unwrap();
+
+    let mut out = [0u8; SECRET_MAX];
+    assert_eq!(restored.get(BOB, b"sftp:bob@build.lan:22", &mut out), Ok(6));
+    assert_eq!(&out[..6], b"s3cret");
+    assert_eq!(restored.get(ALICE, b"wifi:HomeNet", &mut out), Ok(7));
+}
+
+#[test]
+fn failed_save_leaves_memory_as_on_disk() {
+    let mut vault = unlocked();
+    vault.set(ALICE, b"wifi:HomeNet", b"hunter2").unwrap();
+
+    let before = vault.entries;
+    vault.set(ALICE, b"wifi:HomeNet", b"changed").unwrap();
+    vault.set(ALICE, b"app:new", b"x").unwrap();
+    assert_eq!(
+        commit(&mut vault, &before, |_| Err(KeyringError::Busy)),
+        Err(KeyringError::Busy)
+    );
+    let mut out = [0u8; SECRET_MAX];
+    assert_eq!(vault.get(ALICE, b"wifi:HomeNet", &mut out), Ok(7));
+    assert_eq!(&out[..7], b"hunter2");
+    assert_eq!(
+        vault.get(ALICE, b"app:new", &mut out),
+        Err(KeyringError::NotFound)
+    );
+
+    let before = vault.entries;
+    vault.delete(ALICE, b"wifi:HomeNet").unwrap();
+    assert_eq!(
+        commit(&mut vault, &before, |_| Err(KeyringError::Busy)),
+        Err(KeyringError::Busy)
+    );
+    assert_eq!(vault.get(ALICE, b"wifi:HomeNet", &mut out), Ok(7));
+
+    // Sauvegarde réussie : la modification reste.
+    let before = vault.entries;
+    vault.delete(ALICE, b"wifi:HomeNet").unwrap();
+    assert_eq!(commit(&mut vault, &before, |_| Ok(())), Ok(()));
+    assert_eq!(
+        vault.get(ALICE, b"wifi:HomeNet", &mut out),
+        Err(KeyringError::NotFound)
+    );
+}
+
+#[test]
+fn repeated_bad_passwords_are_delayed_per_principal() {
+    const SECOND: u64 = 1_000_000_000;
+    let mut vault = Vault::new();
+    vault.init(PASSWORD).unwrap();
+
+    for _ in 0..2 {
+        assert_eq!(
+            vault.unlock(BOB, b"guess", 0),
+            Err(KeyringError::BadPassword)
+        );
+    }
+    assert_eq!(vault.throttle.remaining_ns(BOB, 0), 0);
+    // Troisième échec : une seconde d'attente, puis deux, puis quatre.
+    assert_eq!(
+        vault.unlock(BOB, b"guess", 0),
+        Err(KeyringError::BadPassword)
+    );
+    assert_eq!(vault.throttle.remaining_ns(BOB, 0), SECOND);
+    // Pendant le délai, même le bon mot de passe est refusé sans KDF.
+    assert_eq!(
+        vault.unlock(BOB, PASSWORD, SECOND / 2),
+        Err(KeyringError::Throttled)
+    );
+    assert_eq!(
+        vault.unlock(BOB, b"guess", SECOND),
+        Err(KeyringError::BadPassword)
+    );
+    assert_eq!(vault.throttle.remaining_ns(BOB, SECOND), 2 * SECOND);
+    assert_eq!(
+        vault.unlock(BOB, b"guess", 3 * SECOND),
+        Err(KeyringError::BadPassword)
+    );
+    assert_eq!(vault.throttle.remaining_ns(BOB, 3 * SECOND), 4 * SECOND);
+
+    // Les autres principaux ne sont pas pénalisés.
+    assert_eq!(vault.unlock(ALICE, PASSWORD, 3 * SECOND), Ok(()));
+    vault.lock(ALICE, false).unwrap();
+
+    // Un succès remet le compteur à zéro.
+    assert_eq!(vault.unlock(BOB, PASSWORD, 7 * SECOND), Ok(()));
+    assert_eq!(
+        vault.unlock(BOB, b"guess", 7 * SECOND),
+        Err(KeyringError::BadPassword)
+    );
+    assert_eq!(vault.throttle.remaining_ns(BOB, 7 * SECOND), 0);
+}
+
+#[test]
+fn many_principals_share_a_global_delay() {
+    const SECOND: u64 = 1_000_000_000;
+    let mut vault = Vault::new();
+    vault.init(PASSWORD).unwrap();
+
+    // Deux échecs par principal : jamais de délai individuel, mais le
+    // dixième échec au total déclenche le délai global.
+    for principal in 0x2000..0x2005u64 {
+        for _ in 0..2 {
+            assert_eq!(
+                vault.unlock(principal, b"guess", 0),
+                Err(KeyringError::BadPassword)
+            );
+        }
+    }
+    assert_eq!(vault.throttle.remaining_ns(0x2100, 0), SECOND);
+    assert_eq!(
+        vault.unlock(0x2100, PASSWORD, SECOND / 2),
+        Err(KeyringError::Throttled)
+    );
+
+    // Un déverrouillage réussi lève la pénalité globale.
+    assert_eq!(vault.unlock(ALICE, PASSWORD, SECOND), Ok(()));
+    assert_eq!(vault.throttle.remaining_ns(0x2100, SECOND), 0);
+}
+
+#[test]
+fn reset_keeps_unlock_penalties() {
+    const SECOND: u64 = 1_000_000_000;
+    let mut vault = Vault::new();
+    vault.init(PASSWORD).unwrap();
+    for _ in 0..3 {
+        let _ = vault.unlock(BOB, b"guess", 0);
+    }
+    assert_eq!(vault.throttle.remaining_ns(BOB, 0), SECOND);
+
+    // Échec de sauvegarde dans `init` : le trousseau redevient vierge,
+    // les pénalités restent.
+    vault.reset();
+    assert_eq!(
+        vault.unlock(BOB, PASSWORD, 0),
+        Err(KeyringError::Uninitialized)
+    );
+    assert_eq!(vault.throttle.remaining_ns(BOB, 0), SECOND);
+}
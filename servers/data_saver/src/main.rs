@@ -0,0 +1,236 @@
+#![no_std]
+#![no_main]
+
+//! # data_saver — connexions facturées au volume et mode économie de données
+//!
+//! Tient l'état décrit par `exo_services::metered` :
+//!
+//! - `network_server` signale l'indice DHCP (`METERED_MSG_HINT`) ; les
+//!   réglages de `/etc/exo/metered.conf` et `METERED_MSG_SET` (page de
+//!   réglages) priment sur lui ;
+//! - chaque changement part aux abonnés (`METERED_NOTIFY_STATE`) et à init,
+//!   dont le cron retient les tâches `unmetered` ;
+//! - l'état courant se lit par `METERED_MSG_STATE` : les téléchargements
+//!   volumineux (mises à jour, paquets, sauvegardes) consultent
+//!   `State::should_defer` avant de partir.
+//!
+//! Ni l'indice ni les réglages ne sont authentifiés : au pire, un processus
+//! local retarde des transferts ou en laisse partir sur une connexion
+//! facturée, comme le ferait l'utilisateur depuis les réglages.
+
+use core::panic::PanicInfo;
+
+use exo_services::metered::{
+    self, Config, Policy, State, METERED_MSG_HEARTBEAT, METERED_MSG_HINT, METERED_MSG_SET,
+    METERED_MSG_STATE, METERED_MSG_STATS, METERED_MSG_SUBSCRIBE, METERED_MSG_UNSUBSCRIBE,
+    METERED_NOTIFY_STATE, SUBSCRIBE_STATE,
+};
+use exo_services::subscribers::{SubscribeError, Subscribers, MAX_SUBSCRIBERS};
+use exo_syscall_abi as syscall;
+use spin::Mutex;
+
+mod protocol;
+
+use protocol::{
+    recv_request, register_endpoint, send_notice, send_reply, DataSaverReply, DataSaverRequest,
+    INIT_ENDPOINT, INIT_MSG_DATA_SAVER,
+};
+
+const CONFIG_PATH: &[u8] = b"/etc/exo/metered.conf\0";
+const CONFIG_MAX: usize = 1024;
+
+struct DataSaverService {
+    policy: Policy,
+    subscribers: Subscribers,
+    /// Init n'a pas encore reçu l'état courant.
+    init_pending: bool,
+}
+
+static SERVICE: Mutex<DataSaverService> = Mutex::new(DataSaverService::new());
+
+impl DataSaverService {
+    const fn new() -> Self {
+        Self {
+            policy: Policy::new(Config::new()),
+            subscribers: Subscribers::new(SUBSCRIBE_STATE),
+            init_pending: true,
+        }
+    }
+
+    fn load(&mut self) {
+        let mut buf = [0u8; CONFIG_MAX];
+        let mut len = 0;
+        // SAFETY: chemin statique terminé par NUL.
+        let fd = unsafe {
+            syscall::syscall2(
+                syscall::SYS_OPEN,
+                CONFIG_PATH.as_ptr() as u64,
+                syscall::O_RDONLY,
+            )
+        };
+        if fd < 0 {
+            return;
+        }
+        while len < CONFIG_MAX {
+            // SAFETY: écriture bornée à la fin du buffer.
+            let n = unsafe {
+                syscall::syscall3(
+                    syscall::SYS_READ,
+                    fd as u64,
+                    buf[len..].as_mut_ptr() as u64,
+                    (CONFIG_MAX - len) as u64,
+                )
+            };
+            if n <= 0 {
+                break;
+            }
+            len += n as usize;
+        }
+        // SAFETY: fermeture du descripteur ouvert ci-dessus.
+        let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+        if let Ok(text) = core::str::from_utf8(&buf[..len]) {
+            self.policy.set_config(Config::parse(text));
+        }
+    }
+
+    /// L'état vient de changer : abonnés tout de suite, init au prochain
+    /// tour s'il est injoignable.
+    fn changed(&mut self) {
+        let state = self.policy.state();
+        let mut payload = [0u8; State::LEN];
+        let _ = state.encode(&mut payload);
+        let mut gone = [0u32; MAX_SUBSCRIBERS];
+        let mut n_gone = 0;
+        for subscriber in self.subscribers.with(SUBSCRIBE_STATE) {
+            let rc = send_notice(subscriber.endpoint, METERED_NOTIFY_STATE, &payload);
+            if rc < 0 && rc != syscall::EAGAIN && rc != syscall::ETIMEDOUT {
+                gone[n_gone] = subscriber.pid;
+                n_gone += 1;
+            }
+        }
+        for &pid in &gone[..n_gone] {
+            self.subscribers.unsubscribe(pid);
+        }
+        self.init_pending = true;
+        self.notify_init();
+    }
+
+    fn notify_init(&mut self) {
+        if !self.init_pending {
+            return;
+        }
+        let mut payload = [0u8; State::LEN];
+        let _ = self.policy.state().encode(&mut payload);
+        self.init_pending = send_notice(INIT_ENDPOINT, INIT_MSG_DATA_SAVER, &payload) < 0;
+    }
+
+    fn state_reply(&self) -> DataSaverReply {
+        let state = self.policy.state();
+        DataSaverReply::ok(state.flags as u64, state.large_transfer_bytes, 0, 0)
+    }
+
+    fn handle_subscribe(&mut self, sender_pid: u32, payload: &[u8]) -> DataSaverReply {
+        let Some((endpoint, flags)) = metered::decode_subscribe(payload) else {
+            return DataSaverReply::error(syscall::EINVAL);
+        };
+        match self.subscribers.subscribe(sender_pid, endpoint, flags) {
+            Ok(()) => self.state_reply(),
+            Err(SubscribeError::BadFlags) => DataSaverReply::error(syscall::EINVAL),
+            Err(SubscribeError::ForeignEndpoint) => DataSaverReply::error(syscall::EPERM),
+            Err(SubscribeError::Full) => DataSaverReply::error(syscall::ENOSPC),
+        }
+    }
+
+    fn handle_set(&mut self, payload: &[u8]) -> DataSaverReply {
+        let Some((metered, data_saver)) = metered::decode_set(payload) else {
+            return DataSaverReply::error(syscall::EINVAL);
+        };
+        if self.policy.set(metered, data_saver) {
+            self.changed();
+        }
+        self.state_reply()
+    }
+
+    fn handle_hint(&mut self, payload: &[u8]) {
+        let hinted = match payload
+            .get(..4)
+            .map(|raw| u32::from_le_bytes(raw.try_into().unwrap()))
+        {
+            Some(0) => false,
+            Some(1) => true,
+            _ => return,
+        };
+        if self.policy.set_hint(hinted) {
+            self.changed();
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let endpoint = register_endpoint();
+    SERVICE.lock().load();
+    let mut request = DataSaverRequest::zeroed();
+
+    loop {
+        SERVICE.lock().notify_init();
+        if endpoint == 0 {
+            continue;
+        }
+        match recv_request(endpoint, &mut request) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => continue,
+        }
+
+        if let Some(reply) = dispatch(&request) {
+            let _ = send_reply(request.sender_pid, &reply);
+        }
+    }
+}
+
+/// `None` : notification, sans réponse.
+fn dispatch(request: &DataSaverRequest) -> Option<DataSaverReply> {
+    let mut service = SERVICE.lock();
+
+    let reply = match request.msg_type {
+        METERED_MSG_HEARTBEAT => DataSaverReply::ok(
+            service.policy.state().flags as u64,
+            service.subscribers.len() as u64,
+            0,
+            0,
+        ),
+        METERED_MSG_SUBSCRIBE => service.handle_subscribe(request.sender_pid, &request.payload),
+        METERED_MSG_UNSUBSCRIBE => {
+            if service.subscribers.unsubscribe(request.sender_pid) {
+                DataSaverReply::ok(0, 0, 0, 0)
+            } else {
+                DataSaverReply::error(syscall::ENOENT)
+            }
+        }
+        METERED_MSG_STATE => service.state_reply(),
+        METERED_MSG_SET => service.handle_set(&request.payload),
+        METERED_MSG_HINT => {
+            service.handle_hint(&request.payload);
+            return None;
+        }
+        METERED_MSG_STATS => DataSaverReply::ok(
+            service.policy.changes,
+            service.subscribers.len() as u64,
+            0,
+            0,
+        ),
+        _ => DataSaverReply::error(syscall::EINVAL),
+    };
+    Some(reply)
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        // SAFETY: panic terminale pour un serveur no_std monothread.
+        unsafe {
+            core::arch::asm!("hlt", options(nostack, nomem));
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use exo_syscall_abi as syscall;
+
+/// Canal du serveur, dans l'espace d'endpoints de son PID ; les clients le
+/// retrouvent par son nom (`SYS_IPC_LOOKUP "data_saver"`).
+pub const DATA_SAVER_CHANNEL: u64 = 1;
+/// Le même tour de boucle relance la notification d'init en attente.
+pub const IPC_RECV_TIMEOUT_MS: u64 = 1_000;
+/// Endpoint d'init (PID 1), destinataire de `INIT_MSG_DATA_SAVER`.
+pub const INIT_ENDPOINT: u64 = 1;
+pub const INIT_MSG_DATA_SAVER: u32 = 8;
+
+#[repr(C)]
+pub struct DataSaverRequest {
+    pub sender_pid: u32,
+    pub msg_type: u32,
+    pub payload: [u8; syscall::IPC_INLINE_PAYLOAD_SIZE],
+}
+
+impl DataSaverRequest {
+    pub const fn zeroed() -> Self {
+        Self {
+            sender_pid: 0,
+            msg_type: 0,
+            payload: [0; syscall::IPC_INLINE_PAYLOAD_SIZE],
+        }
+    }
+}
+
+const _: () = assert!(core::mem::size_of::<DataSaverRequest>() == syscall::IPC_ENVELOPE_SIZE);
+const _: () = assert!(core::mem::offset_of!(DataSaverRequest, payload) == syscall::IPC_HEADER_SIZE);
+const _: () = assert!(exo_services::metered::State::LEN <= syscall::IPC_INLINE_PAYLOAD_SIZE);
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct DataSaverReply {
+    pub status: i64,
+    pub handle: u64,
+    pub value0: u64,
+    pub value1: u64,
+    pub flags: u32,
+    pub _pad: [u8; 28],
+}
+
+impl DataSaverReply {
+    pub const fn ok(handle: u64, value0: u64, value1: u64, flags: u32) -> Self {
+        Self {
+            status: 0,
+            handle,
+            value0,
+            value1,
+            flags,
+            _pad: [0; 28],
+        }
+    }
+
+    pub const fn error(status: i64) -> Self {
+        Self {
+            status,
+            handle: 0,
+            value0: 0,
+            value1: 0,
+            flags: 0,
+            _pad: [0; 28],
+        }
+    }
+}
+
+/// Enregistre `data_saver` ; retourne l'endpoint, `0` en cas d'échec.
+pub fn register_endpoint() -> u64 {
+    // SAFETY: lecture simple du PID courant.
+    let pid = unsafe { syscall::syscall0(syscall::SYS_GETPID) };
+    if pid <= 0 {
+        return 0;
+    }
+    let endpoint = ((pid as u64) << 32) | DATA_SAVER_CHANNEL;
+    let name = b"data_saver";
+    // SAFETY: buffer statique valide, endpoint dans l'espace du PID courant.
+    let rc = unsafe {
+        syscall::syscall3(
+            syscall::SYS_IPC_REGISTER,
+            name.as_ptr() as u64,
+            name.len() as u64,
+            endpoint,
+        )
+    };
+    if rc < 0 {
+        0
+    } else {
+        endpoint
+    }
+}
+
+pub fn recv_request(endpoint: u64, request: &mut DataSaverRequest) -> Result<bool, i64> {
+    // SAFETY: le noyau écrit dans `request`, taille bornée à la struct ABI.
+    let rc = unsafe {
+        syscall::syscall4(
+            syscall::SYS_IPC_RECV,
+            endpoint,
+            request as *mut DataSaverRequest as u64,
+            core::mem::size_of::<DataSaverRequest>() as u64,
+            syscall::IPC_FLAG_TIMEOUT | IPC_RECV_TIMEOUT_MS,
+        )
+    };
+
+    if rc == syscall::ETIMEDOUT {
+        return Ok(false);
+    }
+    if rc < 0 {
+        return Err(rc);
+    }
+    Ok(true)
+}
+
+pub fn send_reply(destination_pid: u32, reply: &DataSaverReply) -> i64 {
+    // SAFETY: `reply` est une structure POD locale envoyée telle quelle au noyau.
+    unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            destination_pid as u64,
+            reply as *const DataSaverReply as u64,
+            core::mem::size_of::<DataSaverReply>() as u64,
+            0,
+            0,
+            0,
+        )
+    }
+}
+
+/// Envoie une notification (enveloppe de requête) à un endpoint abonné.
+pub fn send_notice(endpoint: u64, msg_type: u32, payload: &[u8]) -> i64 {
+    let mut message = DataSaverRequest::zeroed();
+    message.msg_type = msg_type;
+    let len = payload.len().min(message.payload.len());
+    message.payload[..len].copy_from_slice(&payload[..len]);
+    // SAFETY: enveloppe POD locale ; le noyau remplace `sender_pid`.
+    unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            endpoint,
+            &message as *const DataSaverRequest as u64,
+            core::mem::size_of::<DataSaverRequest>() as u64,
+            0,
+            0,
+            0,
+        )
+    }
+}
@@ -0,0 +1,306 @@
+//! Gestionnaire de périphériques façon udev : applique `/etc/exo/device.rules`
+//! aux événements hotplug (PCI/USB/bloc) et publie l'événement normalisé aux
+//! abonnés (gestionnaire de fichiers, service audio, automount).
+//!
+//! La syntaxe des règles et le format de l'événement vivent dans
+//! `exo_device::rules` ; ce module ne fait que les effets : droits du nœud
+//! `/dev/<nom>`, liens symboliques, lancement du driver demandé par `LOAD`.
+
+use exo_device::rules::{Action, Device, DeviceEvent, Outcome, RuleError, EVENT_RECORD_BYTES};
+use exo_syscall_abi as syscall;
+
+const RULES_PATH: &[u8] = b"/etc/exo/device.rules\0";
+const RULES_MAX: usize = 4096;
+const PATH_MAX: usize = 128;
+const MAX_SUBSCRIBERS: usize = 8;
+
+/// Étiquette des notifications poussées aux abonnés.
+pub const DEVICE_NOTIFY_EVENT: u32 = 0x4445_5645; // "DEVE"
+
+pub struct RulesFile {
+    buf: [u8; RULES_MAX],
+    len: usize,
+}
+
+impl RulesFile {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; RULES_MAX],
+            len: 0,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+
+    /// (Re)lit le fichier de règles. Absent : aucune règle. Retourne la
+    /// première ligne invalide, qui sera ignorée à l'évaluation.
+    pub fn load(&mut self) -> Option<(usize, RuleError)> {
+        self.len = 0;
+        // SAFETY: chemin statique terminé par NUL.
+        let fd = unsafe {
+            syscall::syscall2(
+                syscall::SYS_OPEN,
+                RULES_PATH.as_ptr() as u64,
+                syscall::O_RDONLY,
+            )
+        };
+        if fd < 0 {
+            return None;
+        }
+        while self.len < RULES_MAX {
+            // SAFETY: écriture bornée à la fin du buffer.
+            let n = unsafe {
+                syscall::syscall3(
+                    syscall::SYS_READ,
+                    fd as u64,
+                    self.buf[self.len..].as_mut_ptr() as u64,
+                    (RULES_MAX - self.len) as u64,
+                )
+            };
+            if n <= 0 {
+                break;
+            }
+            self.len += n as usize;
+        }
+        // SAFETY: fermeture du descripteur ouvert ci-dessus.
+        let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+        // Un fichier coupé au milieu d'une ligne ou non UTF-8 n'est pas appliqué.
+        if core::str::from_utf8(&self.buf[..self.len]).is_err() {
+            self.len = 0;
+            return Some((0, RuleError::Syntax));
+        }
+        exo_device::rules::first_error(self.text())
+    }
+}
+
+/// Chemin NUL-terminé construit sur la pile.
+struct Path {
+    buf: [u8; PATH_MAX],
+    len: usize,
+}
+
+impl Path {
+    fn new() -> Self {
+        Self {
+            buf: [0; PATH_MAX],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) -> Option<&mut Self> {
+        // Garder une place pour le NUL final.
+        self.buf
+            .get_mut(self.len..self.len + bytes.len())
+            .filter(|_| self.len + bytes.len() < PATH_MAX)?
+            .copy_from_slice(bytes);
+        self.len += bytes.len();
+        self.buf[self.len] = 0;
+        Some(self)
+    }
+
+    fn ptr(&self) -> u64 {
+        self.buf.as_ptr() as u64
+    }
+}
+
+fn dev_path(name: &str) -> Option<Path> {
+    let mut p = Path::new();
+    p.push(b"/dev/")?.push(name.as_bytes())?;
+    Some(p)
+}
+
+/// Applique les effets des règles pour `dev`. Les échecs individuels
+/// (nœud absent, lien déjà présent) n'empêchent pas la publication.
+pub fn apply(dev: &Device<'_>, outcome: &Outcome) {
+    match dev.action {
+        Action::Add | Action::Change => {
+            set_node_permissions(dev.kernel, outcome);
+            if dev.action == Action::Add {
+                for link in outcome.symlinks() {
+                    create_link(dev.kernel, link.as_str());
+                }
+                if let Some(driver) = &outcome.load {
+                    spawn_driver(driver.as_str(), dev.kernel);
+                }
+            }
+        }
+        Action::Remove => {
+            for link in outcome.symlinks() {
+                if let Some(path) = dev_path(link.as_str()) {
+                    // SAFETY: chemin NUL-terminé sur la pile.
+                    let _ = unsafe { syscall::syscall1(syscall::SYS_UNLINK, path.ptr()) };
+                }
+            }
+        }
+    }
+}
+
+fn set_node_permissions(kernel: &str, outcome: &Outcome) {
+    let Some(node) = dev_path(kernel) else {
+        return;
+    };
+    if let Some(mode) = outcome.mode {
+        // SAFETY: chemin NUL-terminé sur la pile.
+        let _ = unsafe { syscall::syscall2(syscall::SYS_CHMOD, node.ptr(), mode as u64) };
+    }
+    if outcome.owner.is_some() || outcome.group.is_some() {
+        // -1 : champ inchangé.
+        let uid = outcome.owner.unwrap_or(u32::MAX) as u64;
+        let gid = outcome.group.unwrap_or(u32::MAX) as u64;
+        // SAFETY: chemin NUL-terminé sur la pile.
+        let _ = unsafe { syscall::syscall3(syscall::SYS_CHOWN, node.ptr(), uid, gid) };
+    }
+}
+
+/// `/dev/<link>` → `../…/<kernel>` (cible relative : le lien reste valide
+/// vu depuis un chroot ou un conteneur qui monte `/dev` ailleurs).
+fn create_link(kernel: &str, link: &str) {
+    let Some(path) = dev_path(link) else {
+        return;
+    };
+    // Répertoires intermédiaires (`disk/`, `disk/by-label/`), EEXIST ignoré.
+    let mut end = 5; // après "/dev/"
+    while let Some(slash) = path.buf[end..path.len].iter().position(|&b| b == b'/') {
+        end += slash;
+        let mut dir = Path::new();
+        if dir.push(&path.buf[..end]).is_none() {
+            return;
+        }
+        // SAFETY: chemin NUL-terminé sur la pile.
+        let _ = unsafe { syscall::syscall2(syscall::SYS_MKDIR, dir.ptr(), 0o755) };
+        end += 1;
+    }
+    let mut target = Path::new();
+    for _ in 0..link.bytes().filter(|&b| b == b'/').count() {
+        if target.push(b"../").is_none() {
+            return;
+        }
+    }
+    if target.push(kernel.as_bytes()).is_none() {
+        return;
+    }
+    // Un lien resté d'un branchement précédent pointe peut-être ailleurs.
+    // SAFETY: chemins NUL-terminés sur la pile.
+    unsafe {
+        let _ = syscall::syscall1(syscall::SYS_UNLINK, path.ptr());
+        let _ = syscall::syscall2(syscall::SYS_SYMLINK, target.ptr(), path.ptr());
+    }
+}
+
+/// Lance `driver <kernel>` (vfork + execve). Le fils est récolté par [`reap`].
+fn spawn_driver(driver: &str, kernel: &str) {
+    let mut prog = Path::new();
+    let mut arg = Path::new();
+    if prog.push(driver.as_bytes()).is_none() || arg.push(kernel.as_bytes()).is_none() {
+        return;
+    }
+    let argv: [u64; 3] = [prog.ptr(), arg.ptr(), 0];
+    let envp: [u64; 1] = [0];
+    // SAFETY: l'enfant vfork ne fait qu'execve puis _exit ; les buffers
+    // restent valides jusqu'au retour du parent.
+    unsafe {
+        let pid = syscall::syscall0(syscall::SYS_VFORK);
+        if pid == 0 {
+            let _ = syscall::syscall3(
+                syscall::SYS_EXECVE,
+                argv[0],
+                argv.as_ptr() as u64,
+                envp.as_ptr() as u64,
+            );
+            let _ = syscall::syscall1(syscall::SYS_EXIT, 127);
+            loop {
+                core::hint::spin_loop();
+            }
+        }
+    }
+}
+
+/// Récolte les drivers lancés par `LOAD` qui se sont terminés.
+pub fn reap() {
+    loop {
+        // SAFETY: wait4 non bloquant sans buffer de statut.
+        let pid =
+            unsafe { syscall::syscall4(syscall::SYS_WAIT4, u64::MAX, 0, syscall::WNOHANG, 0) };
+        if pid <= 0 {
+            return;
+        }
+    }
+}
+
+/// Notification envoyée aux abonnés, un événement par message.
+#[repr(C)]
+pub struct DeviceNotify {
+    pub tag: u32,
+    pub seq: u32,
+    pub record: [u8; EVENT_RECORD_BYTES],
+}
+
+pub struct Subscribers {
+    pids: [u32; MAX_SUBSCRIBERS],
+    seq: u32,
+}
+
+impl Subscribers {
+    pub const fn new() -> Self {
+        Self {
+            pids: [0; MAX_SUBSCRIBERS],
+            seq: 0,
+        }
+    }
+
+    pub fn subscribe(&mut self, pid: u32) -> Result<(), i64> {
+        if self.pids.contains(&pid) {
+            return Ok(());
+        }
+        let slot = self
+            .pids
+            .iter_mut()
+            .find(|p| **p == 0)
+            .ok_or(syscall::ENOSPC)?;
+        *slot = pid;
+        Ok(())
+    }
+
+    pub fn unsubscribe(&mut self, pid: u32) {
+        for p in self.pids.iter_mut().filter(|p| **p == pid) {
+            *p = 0;
+        }
+    }
+
+    /// Pousse l'événement à chaque abonné ; un abonné injoignable (process
+    /// terminé) est retiré. Retourne le nombre de livraisons.
+    pub fn publish(&mut self, event: &DeviceEvent<'_>) -> u32 {
+        let mut notify = DeviceNotify {
+            tag: DEVICE_NOTIFY_EVENT,
+            seq: self.seq,
+            record: [0; EVENT_RECORD_BYTES],
+        };
+        self.seq = self.seq.wrapping_add(1);
+        if event.encode(&mut notify.record).is_none() {
+            return 0;
+        }
+        let mut delivered = 0;
+        for pid in self.pids.iter_mut().filter(|p| **p != 0) {
+            // SAFETY: `notify` est une structure POD locale.
+            let rc = unsafe {
+                syscall::syscall6(
+                    syscall::SYS_IPC_SEND,
+                    *pid as u64,
+                    &notify as *const DeviceNotify as u64,
+                    core::mem::size_of::<DeviceNotify>() as u64,
+                    0,
+                    0,
+                    0,
+                )
+            };
+            if rc == syscall::ESRCH {
+                *pid = 0;
+            } else if rc >= 0 {
+                delivered += 1;
+            }
+        }
+        delivered
+    }
+}
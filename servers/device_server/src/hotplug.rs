@@ -8,6 +8,8 @@ pub enum DeviceEventKind {
     Released = 2,
     Faulted = 3,
     PowerChanged = 4,
+    /// Événement hotplug traité par les règles ; `value` = action.
+    Hotplug = 5,
 }
 
 #[derive(Clone, Copy)]
@@ -7,13 +7,16 @@
 //! - registre PCI/topologie ;
 //! - validation et émission des claims vers le noyau ;
 //! - politiques power/reset ;
-//! - journalisation hotplug et IOMMU côté userspace.
+//! - journalisation hotplug et IOMMU côté userspace ;
+//! - règles de périphériques (droits, liens `/dev`, chargement de driver) et
+//!   diffusion des événements normalisés aux abonnés.
 
 use core::panic::PanicInfo;
 
 use spin::Mutex;
 
 mod claim_validator;
+mod devmgr;
 mod hotplug;
 mod iommu_service;
 mod power;
@@ -21,13 +24,16 @@ mod protocol;
 mod registry;
 
 use claim_validator::validate_claim;
+use devmgr::{RulesFile, Subscribers};
+use exo_device::rules::{self, DeviceEvent as BusEvent};
 use hotplug::{DeviceEvent, DeviceEventKind, HotplugQueue};
 use iommu_service::IommuLedger;
 use power::{PowerPolicyTable, PowerState};
 use protocol::{
     read_u32, read_u64, recv_request, register_endpoint, send_heartbeat, send_reply, DeviceReply,
     DeviceRequest, DEVICE_MSG_CLAIM, DEVICE_MSG_EVENT_POLL, DEVICE_MSG_FAULT, DEVICE_MSG_HEARTBEAT,
-    DEVICE_MSG_POWER_SET, DEVICE_MSG_QUERY, DEVICE_MSG_REGISTER_DEVICE, DEVICE_MSG_RELEASE,
+    DEVICE_MSG_HOTPLUG, DEVICE_MSG_POWER_SET, DEVICE_MSG_QUERY, DEVICE_MSG_REGISTER_DEVICE,
+    DEVICE_MSG_RELEASE, DEVICE_MSG_RELOAD_RULES, DEVICE_MSG_SUBSCRIBE,
 };
 use registry::PciRegistry;
 
@@ -36,8 +42,14 @@ struct DeviceService {
     hotplug: HotplugQueue,
     iommu: IommuLedger,
     power: PowerPolicyTable,
+    rules: RulesFile,
+    subscribers: Subscribers,
 }
 
+/// Drapeau de réponse : l'événement a été écarté par `OPTIONS="ignore"`.
+const HOTPLUG_IGNORED: u32 = 1 << 0;
+const HOTPLUG_AUTOMOUNT: u32 = 1 << 1;
+
 impl DeviceService {
     const fn new() -> Self {
         Self {
@@ -45,6 +57,8 @@ impl DeviceService {
             hotplug: HotplugQueue::new(),
             iommu: IommuLedger::new(),
             power: PowerPolicyTable::new(),
+            rules: RulesFile::new(),
+            subscribers: Subscribers::new(),
         }
     }
 
@@ -321,6 +335,72 @@ impl DeviceService {
         )
     }
 
+    fn handle_hotplug(&mut self, sender_pid: u32, payload: &[u8]) -> DeviceReply {
+        // init relaie les événements noyau ; un driver de bus (contrôleur
+        // USB, AHCI…) annonce les périphériques derrière le device qu'il détient.
+        if sender_pid != 1 && self.registry.bdf_of_owner(sender_pid).is_none() {
+            return DeviceReply::error(exo_syscall_abi::EPERM);
+        }
+        let Some(event) = BusEvent::decode(payload) else {
+            return DeviceReply::error(exo_syscall_abi::EINVAL);
+        };
+        let outcome = rules::evaluate(self.rules.text(), &event.device);
+        if outcome.ignore {
+            return DeviceReply::ok(0, 0, 0, HOTPLUG_IGNORED);
+        }
+        devmgr::apply(&event.device, &outcome);
+
+        let published = BusEvent {
+            device: event.device,
+            automount: outcome.automount,
+            link: outcome.symlinks().first().map_or("", |l| l.as_str()),
+        };
+        let delivered = self.subscribers.publish(&published);
+        self.hotplug.push(DeviceEvent::new(
+            DeviceEventKind::Hotplug,
+            self.registry.bdf_of_owner(sender_pid).unwrap_or(0),
+            sender_pid,
+            event.device.action as u64,
+        ));
+        let flags = if outcome.automount {
+            HOTPLUG_AUTOMOUNT
+        } else {
+            0
+        };
+        DeviceReply::ok(
+            delivered as u64,
+            outcome.mode.unwrap_or(0) as u64,
+            outcome.symlinks().len() as u64,
+            flags,
+        )
+    }
+
+    fn handle_subscribe(&mut self, sender_pid: u32, payload: &[u8]) -> DeviceReply {
+        let enable = match read_u32(payload, 0) {
+            Ok(value) => value != 0,
+            Err(err) => return DeviceReply::error(err),
+        };
+        if !enable {
+            self.subscribers.unsubscribe(sender_pid);
+            return DeviceReply::ok(sender_pid as u64, 0, 0, 0);
+        }
+        match self.subscribers.subscribe(sender_pid) {
+            Ok(()) => DeviceReply::ok(sender_pid as u64, 1, 0, 0),
+            Err(err) => DeviceReply::error(err),
+        }
+    }
+
+    fn handle_reload_rules(&mut self, sender_pid: u32) -> DeviceReply {
+        if sender_pid != 1 {
+            return DeviceReply::error(exo_syscall_abi::EPERM);
+        }
+        match self.rules.load() {
+            // Ligne invalide : rapportée, ignorée à l'évaluation.
+            Some((line, _)) => DeviceReply::ok(0, line as u64, 0, 1),
+            None => DeviceReply::ok(0, 0, 0, 0),
+        }
+    }
+
     fn handle_query(&mut self, payload: &[u8]) -> DeviceReply {
         let selector = match read_u32(payload, 0) {
             Ok(value) => value,
@@ -363,9 +443,11 @@ static DEVICE_SERVICE: Mutex<DeviceService> = Mutex::new(DeviceService::new());
 #[no_mangle]
 pub extern "C" fn _start() -> ! {
     register_endpoint();
+    let _ = DEVICE_SERVICE.lock().rules.load();
     let mut request = DeviceRequest::zeroed();
 
     loop {
+        devmgr::reap();
         match recv_request(&mut request) {
             Ok(true) => {}
             Ok(false) => continue,
@@ -395,6 +477,9 @@ fn dispatch(request: &DeviceRequest) -> DeviceReply {
         DEVICE_MSG_EVENT_POLL => service.handle_event_poll(),
         DEVICE_MSG_POWER_SET => service.handle_power_set(request.sender_pid, &request.payload),
         DEVICE_MSG_QUERY => service.handle_query(&request.payload),
+        DEVICE_MSG_HOTPLUG => service.handle_hotplug(request.sender_pid, &request.payload),
+        DEVICE_MSG_SUBSCRIBE => service.handle_subscribe(request.sender_pid, &request.payload),
+        DEVICE_MSG_RELOAD_RULES => service.handle_reload_rules(request.sender_pid),
         _ => DeviceReply::error(exo_syscall_abi::EINVAL),
     }
 }
@@ -11,6 +11,12 @@ pub const DEVICE_MSG_FAULT: u32 = 4;
 pub const DEVICE_MSG_EVENT_POLL: u32 = 5;
 pub const DEVICE_MSG_POWER_SET: u32 = 6;
 pub const DEVICE_MSG_QUERY: u32 = 7;
+/// Événement hotplug normalisé (`exo_device::rules::DeviceEvent`), émis par
+/// init pour le noyau ou par un driver de bus.
+pub const DEVICE_MSG_HOTPLUG: u32 = 8;
+/// Abonnement (payload u32 = 1) ou désabonnement (0) aux événements.
+pub const DEVICE_MSG_SUBSCRIBE: u32 = 9;
+pub const DEVICE_MSG_RELOAD_RULES: u32 = 10;
 
 #[repr(C)]
 pub struct DeviceRequest {
@@ -0,0 +1,287 @@
+#![no_std]
+#![no_main]
+
+//! # event_journal — chronologie des événements système
+//!
+//! Tient le journal décrit par `exo_services::events` : une ligne par
+//! démarrage, arrêt, reprise après veille, plantage ou relance de service,
+//! déploiement et victime de l'OOM killer, horodatée (heure civile et temps
+//! depuis le démarrage) et marquée de l'identifiant de démarrage tiré au
+//! lancement du démon.
+//!
+//! - Au lancement, le démon relit la dernière ligne : si ce n'est pas un
+//!   arrêt, le démarrage précédent s'est mal terminé (détail `1`).
+//! - `EVENTS_MSG_RECORD` n'est accepté que du rapporteur du type
+//!   (`Kind::reporter`) : init pour les arrêts, plantages et relances,
+//!   `sleep_monitor` pour les reprises, `mem_pressure` pour l'OOM.
+//! - Au-delà de `JOURNAL_MAX_BYTES`, le journal passe en `events.old`.
+
+use core::panic::PanicInfo;
+
+use exo_services::events::{
+    self, Kind, Record, Reporter, Tail, EVENTS_MSG_HEARTBEAT, EVENTS_MSG_RECORD, EVENTS_MSG_STATUS,
+    JOURNAL_MAX_BYTES, LINE_MAX,
+};
+use exo_syscall_abi as syscall;
+use spin::Mutex;
+
+mod protocol;
+
+use protocol::{
+    recv_request, register_endpoint, send_reply, EventJournalReply, EventJournalRequest,
+};
+
+/// `events::JOURNAL_PATH` et `events::ROTATED_PATH`, terminés par NUL.
+const JOURNAL_PATH: &[u8] = b"/var/log/exo/events\0";
+const ROTATED_PATH: &[u8] = b"/var/log/exo/events.old\0";
+const JOURNAL_DIRS: [&[u8]; 3] = [b"/var\0", b"/var/log\0", b"/var/log/exo\0"];
+
+const CLOCK_REALTIME: u64 = 0;
+const CLOCK_BOOTTIME: u64 = 7;
+
+#[repr(C)]
+#[derive(Default)]
+struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+struct EventJournal {
+    boot_id: u64,
+    /// Événements enregistrés depuis le démarrage.
+    events: u64,
+    /// Taille du journal courant.
+    size: u64,
+}
+
+static SERVICE: Mutex<EventJournal> = Mutex::new(EventJournal::new());
+
+impl EventJournal {
+    const fn new() -> Self {
+        Self {
+            boot_id: 0,
+            events: 0,
+            size: 0,
+        }
+    }
+
+    /// Tire l'identifiant de démarrage, relit la fin du journal et note le
+    /// démarrage.
+    fn start(&mut self) {
+        let mut id = [0u8; 8];
+        // SAFETY: le noyau écrit au plus `id.len()` octets dans `id`.
+        let _ = unsafe {
+            syscall::syscall3(
+                syscall::SYS_GETRANDOM,
+                id.as_mut_ptr() as u64,
+                id.len() as u64,
+                0,
+            )
+        };
+        // Sans entropie, l'heure distingue encore les démarrages.
+        self.boot_id = match u64::from_le_bytes(id) {
+            0 => clock_ns(CLOCK_REALTIME) ^ clock_ns(CLOCK_BOOTTIME),
+            id => id,
+        };
+        for dir in JOURNAL_DIRS {
+            // SAFETY: chemin statique terminé par NUL.
+            let _ = unsafe { syscall::syscall2(syscall::SYS_MKDIR, dir.as_ptr() as u64, 0o755) };
+        }
+
+        let mut tail = Tail::new();
+        self.size = read_journal(&mut tail);
+        let unclean = !events::clean_shutdown(tail.record());
+        self.append(Kind::Boot, "", unclean as u64);
+    }
+
+    fn append(&mut self, kind: Kind, subject: &str, detail: u64) {
+        let record = Record {
+            boot_id: self.boot_id,
+            realtime: clock_ns(CLOCK_REALTIME) / 1_000_000_000,
+            uptime_ms: clock_ns(CLOCK_BOOTTIME) / 1_000_000,
+            kind,
+            subject,
+            detail,
+        };
+        let mut line = [0u8; LINE_MAX];
+        let Some(len) = record.format(&mut line) else {
+            return;
+        };
+        if self.size + len as u64 > JOURNAL_MAX_BYTES {
+            // SAFETY: chemins statiques terminés par NUL.
+            let rc = unsafe {
+                syscall::syscall2(
+                    syscall::SYS_RENAME,
+                    JOURNAL_PATH.as_ptr() as u64,
+                    ROTATED_PATH.as_ptr() as u64,
+                )
+            };
+            if rc == 0 {
+                self.size = 0;
+            }
+        }
+        // SAFETY: chemin statique terminé par NUL.
+        let fd = unsafe {
+            syscall::syscall3(
+                syscall::SYS_OPEN,
+                JOURNAL_PATH.as_ptr() as u64,
+                syscall::O_WRONLY | syscall::O_CREAT | syscall::O_APPEND,
+                0o644,
+            )
+        };
+        if fd < 0 {
+            return;
+        }
+        // SAFETY: lecture bornée à la ligne formatée.
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_WRITE,
+                fd as u64,
+                line.as_ptr() as u64,
+                len as u64,
+            )
+        };
+        // SAFETY: fermeture du descripteur ouvert ci-dessus.
+        let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+        if n > 0 {
+            self.size += n as u64;
+            self.events += 1;
+        }
+    }
+
+    fn handle_record(&mut self, sender_pid: u32, payload: &[u8]) {
+        let Some((kind, subject, detail)) = events::decode_record(payload) else {
+            return;
+        };
+        let allowed = match kind.reporter() {
+            Reporter::Journal => false,
+            Reporter::Init => sender_pid == 1,
+            Reporter::Service(name) => {
+                lookup_endpoint(name.as_bytes()).is_some_and(|ep| ep >> 32 == sender_pid as u64)
+            }
+            Reporter::Anyone => true,
+        };
+        if !allowed {
+            return;
+        }
+        if let Ok(subject) = core::str::from_utf8(subject) {
+            self.append(kind, subject, detail);
+        }
+    }
+
+    fn status_reply(&self) -> EventJournalReply {
+        EventJournalReply::ok(self.boot_id, self.events, self.size, 0)
+    }
+}
+
+/// Lit le journal courant jusqu'au bout ; retourne sa taille.
+fn read_journal(tail: &mut Tail) -> u64 {
+    // SAFETY: chemin statique terminé par NUL.
+    let fd = unsafe {
+        syscall::syscall2(
+            syscall::SYS_OPEN,
+            JOURNAL_PATH.as_ptr() as u64,
+            syscall::O_RDONLY,
+        )
+    };
+    if fd < 0 {
+        return 0;
+    }
+    let mut size = 0;
+    let mut buf = [0u8; 512];
+    loop {
+        // SAFETY: écriture bornée à la fin du buffer.
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_READ,
+                fd as u64,
+                buf.as_mut_ptr() as u64,
+                buf.len() as u64,
+            )
+        };
+        if n <= 0 {
+            break;
+        }
+        tail.push(&buf[..n as usize]);
+        size += n as u64;
+    }
+    // SAFETY: fermeture du descripteur ouvert ci-dessus.
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+    size
+}
+
+/// `0` si l'horloge n'est pas lisible (ou pas encore réglée).
+fn clock_ns(clock: u64) -> u64 {
+    let mut ts = Timespec::default();
+    // SAFETY: le noyau écrit dans `ts`, structure locale.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_CLOCK_GETTIME,
+            clock,
+            &mut ts as *mut Timespec as u64,
+        )
+    };
+    if rc != 0 || ts.tv_sec < 0 {
+        return 0;
+    }
+    ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64
+}
+
+fn lookup_endpoint(name: &[u8]) -> Option<u64> {
+    // SAFETY: nom valide pendant l'appel.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_IPC_LOOKUP,
+            name.as_ptr() as u64,
+            name.len() as u64,
+        )
+    };
+    (rc > 0).then_some(rc as u64)
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let endpoint = register_endpoint();
+    SERVICE.lock().start();
+    let mut request = EventJournalRequest::zeroed();
+
+    loop {
+        if endpoint == 0 {
+            continue;
+        }
+        match recv_request(endpoint, &mut request) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => continue,
+        }
+
+        if let Some(reply) = dispatch(&request) {
+            let _ = send_reply(request.sender_pid, &reply);
+        }
+    }
+}
+
+/// `None` : notification, sans réponse.
+fn dispatch(request: &EventJournalRequest) -> Option<EventJournalReply> {
+    let mut service = SERVICE.lock();
+
+    let reply = match request.msg_type {
+        EVENTS_MSG_HEARTBEAT | EVENTS_MSG_STATUS => service.status_reply(),
+        EVENTS_MSG_RECORD => {
+            service.handle_record(request.sender_pid, &request.payload);
+            return None;
+        }
+        _ => EventJournalReply::error(syscall::EINVAL),
+    };
+    Some(reply)
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        // SAFETY: panic terminale pour un serveur no_std monothread.
+        unsafe {
+            core::arch::asm!("hlt", options(nostack, nomem));
+        }
+    }
+}
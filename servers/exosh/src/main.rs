@@ -20,6 +20,9 @@ const FS_BENCH_CHUNK: usize = 64 * 1024;
 const DIRENT64_HEADER_SIZE: usize = 24;
 const DT_DIR: u8 = 4;
 const TREE_MAX_DEPTH: usize = 4;
+const DU_MAX_DEPTH: usize = 16;
+const DU_SLOTS: usize = 24;
+const DU_LINKS: usize = 1024;
 const RM_MAX_DEPTH: usize = 8;
 const HISTORY_MAX: usize = 16;
 const EXEC_MAX_ARGS: usize = 32;
@@ -29,6 +32,8 @@ const DD_DEFAULT_BS: u64 = 512;
 const S_IFMT: u32 = 0o170000;
 const S_IFDIR: u32 = 0o040000;
 const S_IFREG: u32 = 0o100000;
+const S_IFLNK: u32 = 0o120000;
+const AT_SYMLINK_NOFOLLOW: u64 = 0x100;
 const ANSI_RESET: &[u8] = b"\x1b[0m";
 const ANSI_DIR: &[u8] = b"\x1b[1;34m";
 const ANSI_EXEC: &[u8] = b"\x1b[1;32m";
@@ -316,6 +321,8 @@ fn run_command(line: &[u8], state: &mut ShellState) {
         cmd_rmdir(rest, state);
     } else if bytes_eq(cmd, b"tree") {
         cmd_tree(rest, state);
+    } else if bytes_eq(cmd, b"du") {
+        cmd_du(rest, state);
     } else if bytes_eq(cmd, b"stat") {
         cmd_stat(rest, state);
     } else if bytes_eq(cmd, b"history") {
@@ -517,7 +524,7 @@ fn cmd_help() {
     write_all(b"Commands:\n");
     write_all(b"  help cd history time shutdown reboot ping tcping bench exit\n");
     write_all(
-        b"  /bin: basename cat clear cp dd dirname echo false ipc-stat kill ls meminfo mkdir mv ps pwd rm rmdir sleep stat sync syscall-stat top touch tree true uname uptime wc whoami\n",
+        b"  /bin: basename cat clear cp dd dirname du echo false ionice ipc-stat ipcmon kill ktrace ls meminfo mkdir mv netusage ps pwd rm rmdir sleep stat sync syscall-stat sysctl top touch tree true uname uptime wc whoami\n",
     );
     write_all(b"Examples:\n");
     write_all(b"  ls -lah /tmp ; rm -rf /tmp/t ; history\n");
@@ -1001,6 +1008,197 @@ fn tree_walk(path: &[u8; PATH_MAX], path_len: usize, depth: usize) {
     let _ = close_fd(fd);
 }
 
+/// Répertoire ouvert pendant un `du` : fd + lot courant de getdents64.
+struct DuDir {
+    fd: i64,
+    buf: [u8; 512],
+    off: usize,
+    len: usize,
+}
+
+struct DuFs;
+
+impl exo_fs::DuTree for DuFs {
+    type Dir = DuDir;
+    type Error = i64;
+
+    fn open(&mut self, parent: &DuDir, name: &[u8]) -> Result<DuDir, i64> {
+        let mut c_name = [0u8; exo_fs::du::NAME_MAX + 1];
+        c_name[..name.len()].copy_from_slice(name);
+        let fd = unsafe {
+            syscall::syscall4(
+                syscall::SYS_OPENAT,
+                parent.fd as u64,
+                c_name.as_ptr() as u64,
+                syscall::O_RDONLY,
+                0,
+            )
+        };
+        if fd < 0 {
+            return Err(fd);
+        }
+        Ok(DuDir {
+            fd,
+            buf: [0; 512],
+            off: 0,
+            len: 0,
+        })
+    }
+
+    fn next(
+        &mut self,
+        dir: &mut DuDir,
+        name: &mut [u8; exo_fs::du::NAME_MAX],
+    ) -> Result<Option<(usize, exo_fs::DuEntry)>, i64> {
+        loop {
+            if dir.off + DIRENT64_HEADER_SIZE > dir.len {
+                let n = unsafe {
+                    syscall::syscall3(
+                        syscall::SYS_GETDENTS64,
+                        dir.fd as u64,
+                        dir.buf.as_mut_ptr() as u64,
+                        dir.buf.len() as u64,
+                    )
+                };
+                if n < 0 {
+                    return Err(n);
+                }
+                if n == 0 {
+                    return Ok(None);
+                }
+                dir.off = 0;
+                dir.len = n as usize;
+            }
+            let off = dir.off;
+            let reclen = u16::from_le_bytes([dir.buf[off + 16], dir.buf[off + 17]]) as usize;
+            if reclen == 0 || off + reclen > dir.len {
+                dir.len = 0;
+                continue;
+            }
+            dir.off += reclen;
+            let record = &dir.buf[off + DIRENT64_HEADER_SIZE..off + reclen];
+            let name_len = record
+                .iter()
+                .position(|&b| b == 0)
+                .unwrap_or(record.len())
+                .min(name.len());
+            if name_len == 0 {
+                continue;
+            }
+            let mut c_name = [0u8; exo_fs::du::NAME_MAX + 1];
+            c_name[..name_len].copy_from_slice(&record[..name_len]);
+            name[..name_len].copy_from_slice(&record[..name_len]);
+            let mut stat = LinuxStat::default();
+            let rc = unsafe {
+                syscall::syscall4(
+                    syscall::SYS_NEWFSTATAT,
+                    dir.fd as u64,
+                    c_name.as_ptr() as u64,
+                    &mut stat as *mut LinuxStat as u64,
+                    AT_SYMLINK_NOFOLLOW,
+                )
+            };
+            if rc < 0 {
+                continue;
+            }
+            let kind = match stat.st_mode & S_IFMT {
+                S_IFDIR => exo_fs::EntryKind::Directory,
+                S_IFLNK => exo_fs::EntryKind::Symlink,
+                _ => exo_fs::EntryKind::File,
+            };
+            let entry = exo_fs::DuEntry {
+                kind,
+                size: stat.st_size.max(0) as u64,
+                allocated: (stat.st_blocks.max(0) as u64).saturating_mul(512),
+                dev: stat.st_dev,
+                ino: stat.st_ino,
+                nlink: stat.st_nlink,
+            };
+            return Ok(Some((name_len, entry)));
+        }
+    }
+
+    fn close(&mut self, dir: DuDir) {
+        let _ = close_fd(dir.fd);
+    }
+}
+
+/// `du [-s] [chemin]` : une ligne par enfant direct, du plus gros au plus
+/// petit, puis le total (tailles allouées, liens durs comptés une fois).
+fn cmd_du(rest: &[u8], state: &ShellState) {
+    let (first, tail) = next_arg(rest);
+    let summary = bytes_eq(first, b"-s");
+    let arg = if summary { next_arg(tail).0 } else { first };
+    let target = if arg.is_empty() { state.cwd() } else { arg };
+    let mut path = [0u8; PATH_MAX];
+    let Some(path_len) = absolute_path(state.cwd(), target, &mut path) else {
+        write_all(b"du: path too long\n");
+        return;
+    };
+    let fd = unsafe {
+        syscall::syscall4(
+            syscall::SYS_OPENAT,
+            AT_FDCWD,
+            path.as_ptr() as u64,
+            syscall::O_RDONLY,
+            0,
+        )
+    };
+    if fd < 0 {
+        print_errno(b"du", fd);
+        return;
+    }
+    let mut root = DuDir {
+        fd,
+        buf: [0; 512],
+        off: 0,
+        len: 0,
+    };
+    let links = exo_fs::HardlinkSet::<DU_LINKS>::new();
+    let mut breakdown = exo_fs::Breakdown::<DU_SLOTS>::new();
+    let mut stack: [Option<DuDir>; DU_MAX_DEPTH] = core::array::from_fn(|_| None);
+    if let Err(rc) = breakdown.plan(&mut DuFs, &mut root, &mut stack, &links) {
+        print_errno(b"du", rc);
+        let _ = close_fd(fd);
+        return;
+    }
+    // exosh est mono-thread : un seul worker draine toute la file.
+    breakdown.work(&mut DuFs, &root, &mut stack, &links);
+    let _ = close_fd(fd);
+
+    if !summary {
+        let mut order = [0usize; DU_SLOTS];
+        let count = breakdown.largest_first(&mut order);
+        for &idx in &order[..count] {
+            let slot = &breakdown.slots()[idx];
+            write_human_u64(slot.usage().allocated);
+            write_all(b"\t");
+            write_bytes(slot.name());
+            if slot.kind() == exo_fs::EntryKind::Directory {
+                write_all(b"/");
+            }
+            write_all(b"\n");
+        }
+        let other = breakdown.other();
+        if other.files + other.dirs != 0 {
+            write_human_u64(other.allocated);
+            write_all(b"\t(other)\n");
+        }
+    }
+    let total = breakdown.total();
+    write_human_u64(total.allocated);
+    write_all(b"\t");
+    write_bytes(&path[..path_len]);
+    write_all(b"\n");
+    if total.errors != 0 || total.truncated != 0 {
+        write_all(b"du: ");
+        write_u64(total.errors);
+        write_all(b" unreadable, ");
+        write_u64(total.truncated);
+        write_all(b" too deep\n");
+    }
+}
+
 fn cmd_stat(rest: &[u8], state: &ShellState) {
     let (arg, _) = next_arg(rest);
     if arg.is_empty() {
@@ -6,6 +6,10 @@ use core::cell::UnsafeCell;
 #[cfg(target_os = "none")]
 use core::panic::PanicInfo;
 #[cfg(target_os = "none")]
+use exo_services::color::{Ctm, Ramp, RAMP_POINTS};
+#[cfg(target_os = "none")]
+use exo_services::display::{Change, Display, Lookup, Process, Refused};
+#[cfg(target_os = "none")]
 use exo_syscall_abi as syscall;
 
 #[cfg(target_os = "none")]
@@ -32,6 +36,9 @@ const RECV_TIMEOUT_MS: u64 = 25;
 const PROGRESSIVE_CLEAR_ROWS: usize = 8;
 #[cfg(target_os = "none")]
 const MAX_TEXT_ROWS: usize = 128;
+/// Owner liveness is probed every 40 loop turns (~1 s when idle).
+#[cfg(target_os = "none")]
+const OWNER_PROBE_INTERVAL: u32 = 40;
 #[cfg(target_os = "none")]
 const ANSI_GROUND: u8 = 0;
 #[cfg(target_os = "none")]
@@ -79,6 +86,7 @@ impl Framebuffer {
     }
 
     fn encode(self, r: u8, g: u8, b: u8) -> u32 {
+        let [r, g, b] = color_mut().correct([r, g, b]);
         match self.format {
             1 => ((b as u32) << 16) | ((g as u32) << 8) | (r as u32),
             _ => ((r as u32) << 16) | ((g as u32) << 8) | (b as u32),
@@ -209,6 +217,41 @@ impl Framebuffer {
         }
     }
 
+    fn encode_packed(self, rgb: u32) -> u32 {
+        self.encode((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+    }
+
+    /// Texte à la police console, chaque point de glyphe agrandi `scale` fois.
+    fn draw_text_scaled(self, x: u32, y: u32, text: &[u8], scale: u32, fg: u32, bg: u32) {
+        let mut gx0 = x;
+        for &byte in text {
+            if gx0 >= self.width {
+                break;
+            }
+            self.fill_rect(gx0, y, CHAR_W * scale, CHAR_H * scale, bg);
+            let glyph = glyph_for(byte);
+            let mut gy = 0usize;
+            while gy < FONT_GLYPH_HEIGHT {
+                let bits = glyph[gy];
+                let mut gx = 0usize;
+                while gx < FONT_GLYPH_WIDTH {
+                    if bits & (0x80 >> gx) != 0 {
+                        self.fill_rect(
+                            gx0 + gx as u32 * scale,
+                            y + gy as u32 * scale,
+                            scale,
+                            scale,
+                            fg,
+                        );
+                    }
+                    gx += 1;
+                }
+                gy += 1;
+            }
+            gx0 = gx0.saturating_add(CHAR_W * scale);
+        }
+    }
+
     fn scroll_up_pixels(self, dy: u32, fill: u32) {
         if dy == 0 || dy >= self.height {
             return;
@@ -325,6 +368,15 @@ impl Console {
         self.begin_clear(false);
     }
 
+    /// Back to a plain text console after a compositor owned the display.
+    fn restore(&mut self) {
+        self.reset_ansi();
+        self.fg = (0xe6, 0xf1, 0xff);
+        self.bg = (0x03, 0x0d, 0x14);
+        self.cursor_visible = true;
+        self.begin_clear(true);
+    }
+
     fn clear_text_row_index(&mut self, row_idx: u32) {
         if !self.fb.is_present() {
             return;
@@ -617,6 +669,243 @@ fn console_mut() -> &'static mut Console {
     unsafe { &mut *CONSOLE.0.get() }
 }
 
+/// Correction appliquée à chaque pixel dessiné : matrice de couleur
+/// (veilleuse), puis table gamma par canal (profil ICC de l'écran). Les
+/// pixels déjà à l'écran ne sont pas repeints.
+#[cfg(target_os = "none")]
+struct ColorState {
+    ctm: Ctm,
+    lut: [[u8; 256]; 3],
+    identity: bool,
+}
+
+#[cfg(target_os = "none")]
+impl ColorState {
+    const fn new() -> Self {
+        let mut curve = [0u8; 256];
+        let mut i = 0;
+        while i < 256 {
+            curve[i] = i as u8;
+            i += 1;
+        }
+        Self {
+            ctm: Ctm::IDENTITY,
+            lut: [curve; 3],
+            identity: true,
+        }
+    }
+
+    fn correct(&self, rgb: [u8; 3]) -> [u8; 3] {
+        if self.identity {
+            return rgb;
+        }
+        let [r, g, b] = self.ctm.apply(rgb);
+        [
+            self.lut[0][r as usize],
+            self.lut[1][g as usize],
+            self.lut[2][b as usize],
+        ]
+    }
+
+    /// Table de 256 entrées interpolée entre les `RAMP_POINTS` points.
+    fn set_ramp(&mut self, ramp: &Ramp) {
+        let last = RAMP_POINTS - 1;
+        for (lut, curve) in self.lut.iter_mut().zip(ramp.channels.iter()) {
+            for (i, entry) in lut.iter_mut().enumerate() {
+                let pos = i * last;
+                let (idx, frac) = (pos / 255, pos % 255);
+                let next = curve[(idx + 1).min(last)] as usize;
+                *entry = ((curve[idx] as usize * (255 - frac) + next * frac + 127) / 255) as u8;
+            }
+        }
+        self.update_identity();
+    }
+
+    fn set_ctm(&mut self, ctm: Ctm) {
+        self.ctm = ctm;
+        self.update_identity();
+    }
+
+    fn update_identity(&mut self) {
+        self.identity = self.ctm == Ctm::IDENTITY
+            && self
+                .lut
+                .iter()
+                .all(|lut| lut.iter().enumerate().all(|(i, v)| *v as usize == i));
+    }
+}
+
+#[cfg(target_os = "none")]
+struct ColorCell(UnsafeCell<ColorState>);
+
+#[cfg(target_os = "none")]
+unsafe impl Sync for ColorCell {}
+
+#[cfg(target_os = "none")]
+// SAFETY: même invariant que CONSOLE, fb_server est mono-thread.
+static COLOR: ColorCell = ColorCell(UnsafeCell::new(ColorState::new()));
+
+#[cfg(target_os = "none")]
+fn color_mut() -> &'static mut ColorState {
+    unsafe { &mut *COLOR.0.get() }
+}
+
+/// Veilleuse : seul autre processus autorisé à poser la matrice de couleur.
+#[cfg(target_os = "none")]
+const NIGHT_LIGHT_NAME: &[u8] = b"night_light";
+/// Clavier virtuel : seul autre processus autorisé à dessiner, dans la bande
+/// basse de l'écran uniquement.
+#[cfg(target_os = "none")]
+const OSK_NAME: &[u8] = b"osk";
+
+/// Vrai si `pid` est le processus enregistré sous `name`.
+#[cfg(target_os = "none")]
+fn is_registered(pid: u32, name: &[u8]) -> bool {
+    let endpoint = unsafe {
+        syscall::syscall3(
+            syscall::SYS_IPC_LOOKUP,
+            name.as_ptr() as u64,
+            name.len() as u64,
+            0,
+        )
+    };
+    pid != 0 && endpoint > 0 && (endpoint as u64) >> 32 == pid as u64
+}
+
+#[cfg(target_os = "none")]
+fn is_night_light(pid: u32) -> bool {
+    is_registered(pid, NIGHT_LIGHT_NAME)
+}
+
+/// Dessin du clavier virtuel par-dessus le compositeur : le rectangle ou le
+/// texte doit commencer dans la bande basse (`FB_OSK_BAND_PERCENT`), il ne
+/// déborde donc jamais vers le haut.
+#[cfg(target_os = "none")]
+fn osk_may_draw(req: &syscall::FbRequest) -> bool {
+    let height = console_mut().fb.height;
+    let band_top = height - height * syscall::FB_OSK_BAND_PERCENT / 100;
+    (req.a >> 32) as u32 >= band_top && is_registered(req.sender_pid, OSK_NAME)
+}
+
+/// Processus autorisés à prendre l'affichage (`FB_MSG_ACQUIRE_DISPLAY`),
+/// reconnus à leur nom IPC comme la veilleuse ; un client de scanout direct
+/// ne le reçoit que prêté par le compositeur.
+#[cfg(target_os = "none")]
+const DISPLAY_SERVER_NAMES: [&[u8]; 2] = [b"boot_splash", b"compositor"];
+/// Capacité du relevé de la table des processus.
+#[cfg(target_os = "none")]
+const PROCESS_LIST_CAP: usize = 512;
+
+#[cfg(target_os = "none")]
+struct DisplayCell(UnsafeCell<Display>);
+
+#[cfg(target_os = "none")]
+unsafe impl Sync for DisplayCell {}
+
+// Propriétaire de l'affichage (aucun = console texte) et prêteur éventuel.
+#[cfg(target_os = "none")]
+// SAFETY: même invariant que CONSOLE, fb_server est mono-thread.
+static DISPLAY: DisplayCell = DisplayCell(UnsafeCell::new(Display::new()));
+
+#[cfg(target_os = "none")]
+fn display_mut() -> &'static mut Display {
+    unsafe { &mut *DISPLAY.0.get() }
+}
+
+#[cfg(target_os = "none")]
+struct ProcessListCell(UnsafeCell<[syscall::ExoProcessInfo; PROCESS_LIST_CAP]>);
+
+#[cfg(target_os = "none")]
+unsafe impl Sync for ProcessListCell {}
+
+#[cfg(target_os = "none")]
+// SAFETY: même invariant que CONSOLE, fb_server est mono-thread.
+static PROCESS_LIST: ProcessListCell = ProcessListCell(UnsafeCell::new(
+    [syscall::ExoProcessInfo::zeroed(); PROCESS_LIST_CAP],
+));
+
+#[cfg(target_os = "none")]
+fn display_owned() -> bool {
+    display_mut().owner() != 0
+}
+
+/// Identité de `pid` dans la table des processus. Un relevé plein peut avoir
+/// omis `pid` : `Unknown` plutôt que `Missing`.
+#[cfg(target_os = "none")]
+fn lookup_process(pid: u32) -> Lookup {
+    if pid == 0 {
+        return Lookup::Missing;
+    }
+    let list = unsafe { &mut *PROCESS_LIST.0.get() };
+    let n = unsafe {
+        syscall::syscall3(
+            syscall::SYS_EXO_PROCESS_LIST,
+            list.as_mut_ptr() as u64,
+            PROCESS_LIST_CAP as u64,
+            core::mem::size_of::<syscall::ExoProcessInfo>() as u64,
+        )
+    };
+    if n < 0 {
+        return Lookup::Unknown;
+    }
+    let n = (n as usize).min(PROCESS_LIST_CAP);
+    match list[..n].iter().find(|entry| entry.pid == pid) {
+        Some(entry) => Lookup::Found(Process {
+            pid,
+            state: entry.state,
+            name: entry.name,
+            cpu_ns: entry.utime_ns.saturating_add(entry.stime_ns),
+        }),
+        None if n == PROCESS_LIST_CAP => Lookup::Unknown,
+        None => Lookup::Missing,
+    }
+}
+
+#[cfg(target_os = "none")]
+fn restore_console(reason: &[u8]) {
+    // La table gamma appartenait au compositeur ; la veilleuse reste.
+    color_mut().set_ramp(&Ramp::identity());
+    let console = console_mut();
+    console.restore();
+    console.write_all(reason);
+}
+
+/// Drops an owner or lender that exited, zombies and reused PIDs included.
+/// A crashed compositor never sends RELEASE: without this probe the screen
+/// would stay frozen on its last frame.
+#[cfg(target_os = "none")]
+fn check_display_owner() {
+    match display_mut().probe(lookup_process) {
+        Change::None => {}
+        Change::Returned(_) => boot_log(b"fb_server: display owner exited\n"),
+        Change::Console => {
+            boot_log(b"fb_server: display owner exited\n");
+            restore_console(b"fb_server: display owner exited, console restored\n");
+        }
+    }
+}
+
+#[cfg(target_os = "none")]
+fn may_acquire_display(pid: u32) -> bool {
+    display_mut().is_lender(pid)
+        || DISPLAY_SERVER_NAMES
+            .iter()
+            .any(|name| is_registered(pid, name))
+}
+
+#[cfg(target_os = "none")]
+fn refused(err: Refused) -> syscall::FbReply {
+    syscall::FbReply {
+        status: match err {
+            Refused::Busy => syscall::EBUSY,
+            Refused::NotOwner => syscall::EPERM,
+            Refused::NoTarget => syscall::ESRCH,
+        },
+        len: display_mut().epoch(),
+        _pad: 0,
+    }
+}
+
 #[cfg(target_os = "none")]
 fn fallback_write(bytes: &[u8]) {
     let _ = bytes;
@@ -642,6 +931,21 @@ fn boot_log(bytes: &[u8]) {
     debug_log(bytes);
 }
 
+/// Tranche `fb_draw` dans la trace noyau (sans effet si elle est coupée).
+#[cfg(target_os = "none")]
+fn trace_draw(phase: u64) {
+    const NAME: &[u8] = b"fb_draw";
+    unsafe {
+        let _ = syscall::syscall4(
+            syscall::SYS_EXO_TRACE,
+            syscall::EXO_TRACE_MARK,
+            NAME.as_ptr() as u64,
+            NAME.len() as u64,
+            phase,
+        );
+    }
+}
+
 #[cfg(target_os = "none")]
 fn exit_failed() -> ! {
     unsafe {
@@ -734,6 +1038,16 @@ fn send_reply(endpoint: u64, status: i64, len: u32) {
 #[cfg(target_os = "none")]
 fn handle(req: &syscall::FbRequest) -> syscall::FbReply {
     match req.msg_type {
+        syscall::FB_MSG_WRITE if display_owned() => syscall::FbReply {
+            status: 0,
+            len: core::cmp::min(req.a as usize, syscall::FB_TEXT_MAX) as u32,
+            _pad: 0,
+        },
+        syscall::FB_MSG_CLEAR | syscall::FB_MSG_SCROLL | syscall::FB_MSG_SET_CURSOR
+            if display_owned() =>
+        {
+            syscall::FbReply::default()
+        }
         syscall::FB_MSG_WRITE => {
             let n = core::cmp::min(req.a as usize, syscall::FB_TEXT_MAX);
             console_mut().write_all(&req.data[..n]);
@@ -762,6 +1076,175 @@ fn handle(req: &syscall::FbRequest) -> syscall::FbReply {
             console.draw_cursor(true);
             syscall::FbReply::default()
         }
+        syscall::FB_MSG_ACQUIRE_DISPLAY => {
+            if req.sender_pid == 0 || !may_acquire_display(req.sender_pid) {
+                return refused(Refused::NotOwner);
+            }
+            check_display_owner();
+            let caller = match lookup_process(req.sender_pid) {
+                Lookup::Found(caller) => caller,
+                Lookup::Missing => return refused(Refused::NoTarget),
+                Lookup::Unknown => {
+                    return syscall::FbReply {
+                        status: syscall::EAGAIN,
+                        len: display_mut().epoch(),
+                        _pad: 0,
+                    }
+                }
+            };
+            match display_mut().acquire(caller) {
+                Ok(epoch) => {
+                    let console = console_mut();
+                    if console.cursor_drawn {
+                        console.draw_cursor(false);
+                    }
+                    syscall::FbReply {
+                        status: 0,
+                        len: epoch,
+                        _pad: 0,
+                    }
+                }
+                Err(err) => refused(err),
+            }
+        }
+        syscall::FB_MSG_RELEASE_DISPLAY => {
+            check_display_owner();
+            match display_mut().release(req.sender_pid) {
+                Ok(Change::Returned(epoch)) => syscall::FbReply {
+                    status: 0,
+                    len: epoch,
+                    _pad: 0,
+                },
+                Ok(_) => {
+                    restore_console(b"");
+                    syscall::FbReply::default()
+                }
+                Err(err) => refused(err),
+            }
+        }
+        syscall::FB_MSG_FILL_RECT | syscall::FB_MSG_DRAW_TEXT
+            if !display_mut().is_owner(req.sender_pid) && !osk_may_draw(req) =>
+        {
+            syscall::FbReply {
+                status: syscall::EPERM,
+                len: 0,
+                _pad: 0,
+            }
+        }
+        syscall::FB_MSG_FILL_RECT => {
+            let fb = console_mut().fb;
+            let rgb = u32::from_le_bytes([req.data[0], req.data[1], req.data[2], req.data[3]]);
+            fb.fill_rect(
+                req.a as u32,
+                (req.a >> 32) as u32,
+                req.b as u32,
+                (req.b >> 32) as u32,
+                fb.encode_packed(rgb),
+            );
+            syscall::FbReply::default()
+        }
+        syscall::FB_MSG_DRAW_TEXT => {
+            let len = core::cmp::min(req.data[0] as usize, syscall::FB_TEXT_MAX - 2);
+            let scale = req.data[1];
+            if scale == 0 || scale > syscall::FB_TEXT_SCALE_MAX {
+                return syscall::FbReply {
+                    status: syscall::EINVAL,
+                    len: 0,
+                    _pad: 0,
+                };
+            }
+            let fb = console_mut().fb;
+            fb.draw_text_scaled(
+                req.a as u32,
+                (req.a >> 32) as u32,
+                &req.data[2..2 + len],
+                scale as u32,
+                fb.encode_packed(req.b as u32),
+                fb.encode_packed((req.b >> 32) as u32),
+            );
+            syscall::FbReply {
+                status: 0,
+                len: len as u32,
+                _pad: 0,
+            }
+        }
+        syscall::FB_MSG_TRANSFER_DISPLAY => {
+            // Écran laissé tel quel : le nouveau propriétaire dessine par-dessus.
+            let target = lookup_process(req.a as u32);
+            match display_mut().transfer(req.sender_pid, target, req.b == syscall::FB_TRANSFER_LEND)
+            {
+                Ok(epoch) => syscall::FbReply {
+                    status: 0,
+                    len: epoch,
+                    _pad: 0,
+                },
+                Err(Refused::NotOwner) => syscall::FbReply {
+                    status: syscall::EPERM,
+                    len: 0,
+                    _pad: 0,
+                },
+                Err(err) => refused(err),
+            }
+        }
+        syscall::FB_MSG_SET_GAMMA => {
+            if !display_mut().is_owner(req.sender_pid) {
+                return syscall::FbReply {
+                    status: syscall::EPERM,
+                    len: 0,
+                    _pad: 0,
+                };
+            }
+            match Ramp::decode(&req.data) {
+                Some(ramp) => {
+                    color_mut().set_ramp(&ramp);
+                    syscall::FbReply::default()
+                }
+                None => syscall::FbReply {
+                    status: syscall::EINVAL,
+                    len: 0,
+                    _pad: 0,
+                },
+            }
+        }
+        syscall::FB_MSG_SET_CTM => {
+            if req.sender_pid == 0
+                || (!display_mut().is_owner(req.sender_pid) && !is_night_light(req.sender_pid))
+            {
+                return syscall::FbReply {
+                    status: syscall::EPERM,
+                    len: 0,
+                    _pad: 0,
+                };
+            }
+            match Ctm::decode(&req.data) {
+                Some(ctm) => {
+                    color_mut().set_ctm(ctm);
+                    syscall::FbReply::default()
+                }
+                None => syscall::FbReply {
+                    status: syscall::EINVAL,
+                    len: 0,
+                    _pad: 0,
+                },
+            }
+        }
+        syscall::FB_MSG_DISPLAY_SIZE => {
+            let fb = console_mut().fb;
+            syscall::FbReply {
+                status: if fb.is_present() {
+                    ((fb.width as i64) << 32) | fb.height as i64
+                } else {
+                    syscall::ENODEV
+                },
+                len: 0,
+                _pad: 0,
+            }
+        }
+        syscall::FB_MSG_DISPLAY_STATE => syscall::FbReply {
+            status: display_mut().owner() as i64,
+            len: display_mut().epoch(),
+            _pad: 0,
+        },
         _ => syscall::FbReply {
             status: syscall::EINVAL,
             len: 0,
@@ -788,8 +1271,11 @@ pub extern "C" fn _start() -> ! {
     }
 
     let mut req = syscall::FbRequest::zeroed();
+    let mut loop_ticks = 0u32;
     loop {
-        console_mut().progress_clear(PROGRESSIVE_CLEAR_ROWS);
+        if !display_owned() {
+            console_mut().progress_clear(PROGRESSIVE_CLEAR_ROWS);
+        }
         let rc = unsafe {
             syscall::syscall4(
                 syscall::SYS_IPC_RECV,
@@ -799,11 +1285,20 @@ pub extern "C" fn _start() -> ! {
                 syscall::IPC_FLAG_TIMEOUT | RECV_TIMEOUT_MS,
             )
         };
+        loop_ticks += 1;
+        if loop_ticks >= OWNER_PROBE_INTERVAL {
+            loop_ticks = 0;
+            check_display_owner();
+        }
         if rc < 0 {
             continue;
         }
+        trace_draw(syscall::EXO_TRACE_BEGIN);
         let reply = handle(&req);
-        console_mut().progress_clear(PROGRESSIVE_CLEAR_ROWS);
+        trace_draw(syscall::EXO_TRACE_END);
+        if !display_owned() {
+            console_mut().progress_clear(PROGRESSIVE_CLEAR_ROWS);
+        }
         send_reply(req.reply_endpoint, reply.status, reply.len);
     }
 }
@@ -0,0 +1,544 @@
+#![no_std]
+#![no_main]
+
+//! # font_cache — atlas partagé de glyphes et d'icônes
+//!
+//! Le panneau, le lanceur, le terminal et le gestionnaire de fichiers
+//! rendaient chacun les mêmes glyphes et icônes. Ce service les rastérise
+//! une fois dans une région `exo_shm` publique (lecture seule pour les
+//! clients) au format `exo_graphics::atlas` :
+//!
+//! - glyphes ASCII et plages préchargées de `/etc/exo/fonts.conf`, à la
+//!   taille de la police console à l'échelle entière courante ;
+//! - icônes du thème (`/usr/share/icons/<thème>/index`, fichiers farbfeld
+//!   `<taille>/<nom>.ff`) en 16 et 32 px.
+//!
+//! Chaque caractère est rendu par la première police de la chaîne de
+//! repli de son écriture (`exo_services::fonts`) qui le connaît : polices
+//! TrueType `/usr/share/fonts/<nom>.ttf`, chargées au démarrage, puis la
+//! police console pour l'ASCII. Les glyphes à calques de couleur (COLR,
+//! emoji) sont publiés en RGBA ; les calques « couleur du texte » y
+//! prennent [`TEXT_COLOR`].
+//!
+//! Un changement de thème ou de police reconstruit l'atlas sur place en
+//! faisant passer la génération par une valeur impaire : les clients
+//! jettent tout ce qu'ils ont lu sous l'ancienne génération.
+
+use core::panic::PanicInfo;
+
+use exo_graphics::atlas::{
+    self, AtlasWriter, ATLAS_BYTES, FONT_CACHE_MSG_ATTACH, FONT_CACHE_MSG_HEARTBEAT,
+    FONT_CACHE_MSG_SET_FONT, FONT_CACHE_MSG_SET_THEME, FONT_CACHE_MSG_STATS, MAX_FONT_SCALE,
+};
+use exo_graphics::sfnt::{self, Font, Rasterizer};
+use exo_services::fonts::{self, Config, CONSOLE_FONT, FONT_NAME_MAX};
+use exo_syscall_abi as syscall;
+use spin::Mutex;
+
+mod protocol;
+// Même police que fb_server (voir la note PATCH-FB-02 sur ce #[path]).
+#[path = "../../../exo-boot/src/display/font.rs"]
+#[allow(clippy::manual_range_contains)]
+mod shared_font;
+
+use protocol::{recv_request, register_endpoint, send_reply, FontCacheReply, FontCacheRequest};
+use shared_font::{glyph_for, FONT_GLYPH_HEIGHT, FONT_GLYPH_WIDTH};
+
+const ICON_ROOT: &[u8] = b"/usr/share/icons/";
+const DEFAULT_THEME: &[u8] = b"exo";
+const THEME_MAX: usize = 64;
+const ICON_SIZES: [u16; 2] = [16, 32];
+const INDEX_MAX: usize = 4096;
+const PATH_MAX: usize = 256;
+/// Plus grande icône acceptée : 32×32 farbfeld (en-tête + 8 octets/pixel).
+const ICON_FILE_MAX: usize = 16 + 32 * 32 * 8;
+/// Plus grand glyphe : cellule RGBA de largeur maximale à l'échelle maximale.
+const GLYPH_MAX: usize = sfnt::MAX_WIDTH as usize * FONT_GLYPH_HEIGHT * MAX_FONT_SCALE as usize * 4;
+const CONFIG_MAX: usize = 2048;
+/// Polices TrueType chargées ; les suivantes de la configuration sont ignorées.
+const MAX_FONTS: usize = 2;
+/// Plus gros fichier de police accepté.
+const FONT_FILE_MAX: usize = 2 * 1024 * 1024;
+/// Couleur des calques « texte » des glyphes en couleur : celle des thèmes
+/// clairs, l'atlas étant commun à tous les clients.
+const TEXT_COLOR: [u8; 4] = [0x20, 0x20, 0x20, 0xff];
+
+/// Fichier de police chargé en mémoire.
+struct LoadedFont {
+    name: [u8; FONT_NAME_MAX],
+    name_len: usize,
+    data: [u8; FONT_FILE_MAX],
+    len: usize,
+}
+
+impl LoadedFont {
+    const fn new() -> Self {
+        Self {
+            name: [0; FONT_NAME_MAX],
+            name_len: 0,
+            data: [0; FONT_FILE_MAX],
+            len: 0,
+        }
+    }
+
+    fn name(&self) -> &str {
+        core::str::from_utf8(&self.name[..self.name_len]).unwrap_or("")
+    }
+}
+
+/// Polices ouvertes pour une reconstruction.
+struct Chains<'a> {
+    config: &'a Config,
+    fonts: [Option<(&'a str, Font<'a>)>; MAX_FONTS],
+}
+
+impl Chains<'_> {
+    /// Rend `ch` avec la première police de sa chaîne qui le connaît ;
+    /// `false` si aucune ne le connaît ou si l'atlas est plein.
+    fn render(
+        &self,
+        ch: char,
+        scale: usize,
+        rasterizer: &mut Rasterizer,
+        pixels: &mut [u8],
+        writer: &mut AtlasWriter<'_>,
+    ) -> bool {
+        let px = (FONT_GLYPH_HEIGHT * scale) as u16;
+        for name in self.config.chain(ch) {
+            if name == CONSOLE_FONT {
+                if !(' '..='~').contains(&ch) {
+                    continue;
+                }
+                let len = atlas::rasterize_bitmap_glyph(
+                    glyph_for(ch as u8),
+                    FONT_GLYPH_WIDTH,
+                    scale,
+                    pixels,
+                );
+                let width = (FONT_GLYPH_WIDTH * scale) as u16;
+                return writer
+                    .add_glyph(ch as u32, px, width, px, &pixels[..len])
+                    .is_ok();
+            }
+            let Some((_, font)) = self.fonts.iter().flatten().find(|(n, _)| *n == name) else {
+                continue;
+            };
+            let Some(gid) = font.glyph_id(ch) else {
+                continue;
+            };
+            if font.is_color(gid) {
+                let Some((w, h)) = rasterizer.color_glyph(font, gid, px, TEXT_COLOR, pixels) else {
+                    return false;
+                };
+                let len = w as usize * h as usize * 4;
+                return writer
+                    .add_color_glyph(ch as u32, px, w, h, &pixels[..len])
+                    .is_ok();
+            }
+            let Some((w, h)) = rasterizer.glyph(font, gid, px, pixels) else {
+                return false;
+            };
+            let len = w as usize * h as usize;
+            return writer
+                .add_glyph(ch as u32, px, w, h, &pixels[..len])
+                .is_ok();
+        }
+        false
+    }
+}
+
+struct FontCache {
+    /// Région `exo_shm` et son mapping en écriture, `0` : pas d'atlas.
+    region: u64,
+    base: u64,
+    generation: u64,
+    scale: u32,
+    theme: [u8; THEME_MAX],
+    theme_len: usize,
+    rebuilds: u64,
+    glyphs: u64,
+    icons: u64,
+    index: [u8; INDEX_MAX],
+    file: [u8; ICON_FILE_MAX],
+    pixels: [u8; GLYPH_MAX],
+    config: Config,
+    fonts: [LoadedFont; MAX_FONTS],
+    n_fonts: usize,
+    rasterizer: Rasterizer,
+}
+
+static CACHE: Mutex<FontCache> = Mutex::new(FontCache::new());
+
+fn exo_shm(op: u64, a2: u64, a3: u64) -> i64 {
+    // SAFETY: exo_shm ne lit ni n'écrit de mémoire userspace.
+    unsafe { syscall::syscall3(syscall::SYS_EXO_SHM, op, a2, a3) }
+}
+
+impl FontCache {
+    const fn new() -> Self {
+        Self {
+            region: 0,
+            base: 0,
+            generation: 0,
+            scale: 1,
+            theme: [0; THEME_MAX],
+            theme_len: 0,
+            rebuilds: 0,
+            glyphs: 0,
+            icons: 0,
+            index: [0; INDEX_MAX],
+            file: [0; ICON_FILE_MAX],
+            pixels: [0; GLYPH_MAX],
+            config: Config::new(),
+            fonts: [const { LoadedFont::new() }; MAX_FONTS],
+            n_fonts: 0,
+            rasterizer: Rasterizer::new(),
+        }
+    }
+
+    /// Lit `/etc/exo/fonts.conf` et charge les polices de ses chaînes ;
+    /// sans fichier, seule la police console sert.
+    fn load_fonts(&mut self) {
+        let mut config = [0u8; CONFIG_MAX];
+        let mut path = PathBuf::new();
+        if !path.push(fonts::CONFIG_PATH.as_bytes()) {
+            return;
+        }
+        let Some(len) = read_file(path.as_c_str(), &mut config) else {
+            return;
+        };
+        let Ok(text) = core::str::from_utf8(&config[..len]) else {
+            return;
+        };
+        self.config = Config::parse(text);
+
+        self.n_fonts = 0;
+        for name in self.config.fonts() {
+            let Some(slot) = self.fonts.get_mut(self.n_fonts) else {
+                break;
+            };
+            let mut path = PathBuf::new();
+            if !path.push(fonts::FONTS_DIR.as_bytes())
+                || !path.push(b"/")
+                || !path.push(name.as_bytes())
+                || !path.push(b".ttf")
+            {
+                continue;
+            }
+            let Some(len) = read_file(path.as_c_str(), &mut slot.data) else {
+                continue;
+            };
+            if Font::parse(&slot.data[..len]).is_err() {
+                continue;
+            }
+            slot.len = len;
+            slot.name[..name.len()].copy_from_slice(name.as_bytes());
+            slot.name_len = name.len();
+            self.n_fonts += 1;
+        }
+    }
+
+    /// Crée la région publique et la mappe en écriture pour ce seul
+    /// processus.
+    fn create(&mut self) -> Result<(), i64> {
+        let region = exo_shm(
+            syscall::EXO_SHM_CREATE,
+            ATLAS_BYTES as u64,
+            syscall::EXO_SHM_PUBLIC_READ,
+        );
+        if region < 0 {
+            return Err(region);
+        }
+        let base = exo_shm(syscall::EXO_SHM_MAP, region as u64, 1);
+        if base < 0 {
+            let _ = exo_shm(syscall::EXO_SHM_DESTROY, region as u64, 0);
+            return Err(base);
+        }
+        self.region = region as u64;
+        self.base = base as u64;
+        Ok(())
+    }
+
+    fn set_theme(&mut self, name: &[u8]) -> bool {
+        // Le nom devient un composant de chemin : ni séparateur ni `..`.
+        if name.is_empty()
+            || name.len() > THEME_MAX
+            || name.starts_with(b".")
+            || name.iter().any(|&b| b == b'/' || b == 0)
+        {
+            return false;
+        }
+        self.theme[..name.len()].copy_from_slice(name);
+        self.theme_len = name.len();
+        true
+    }
+
+    /// Reconstruit l'atlas : glyphes d'abord (toujours présents), puis les
+    /// icônes tant qu'il reste de la place.
+    fn rebuild(&mut self) {
+        if self.base == 0 {
+            return;
+        }
+        // SAFETY: mapping en écriture de ATLAS_BYTES octets posé par
+        // `create`, jamais démappé ; seul ce processus y écrit.
+        let bytes = unsafe { core::slice::from_raw_parts_mut(self.base as *mut u8, ATLAS_BYTES) };
+        let Ok(mut writer) = AtlasWriter::begin(bytes, self.generation) else {
+            return;
+        };
+
+        let scale = self.scale as usize;
+        let mut chains = Chains {
+            config: &self.config,
+            fonts: [None; MAX_FONTS],
+        };
+        for (slot, font) in chains.fonts.iter_mut().zip(&self.fonts[..self.n_fonts]) {
+            *slot = Font::parse(&font.data[..font.len])
+                .ok()
+                .map(|parsed| (font.name(), parsed));
+        }
+        let mut glyphs = 0;
+        for ch in (' '..='~').chain(self.config.preload()) {
+            if chains.render(
+                ch,
+                scale,
+                &mut self.rasterizer,
+                &mut self.pixels,
+                &mut writer,
+            ) {
+                glyphs += 1;
+            }
+        }
+
+        let icons = self.add_icons(&mut writer);
+        self.generation = writer.finish();
+        self.rebuilds += 1;
+        self.glyphs = glyphs;
+        self.icons = icons;
+    }
+
+    fn add_icons(&mut self, writer: &mut AtlasWriter<'_>) -> u64 {
+        let mut path = PathBuf::new();
+        if !path.push(ICON_ROOT)
+            || !path.push(&self.theme[..self.theme_len])
+            || !path.push(b"/index")
+        {
+            return 0;
+        }
+        let Some(index_len) = read_file(path.as_c_str(), &mut self.index) else {
+            return 0;
+        };
+        let index = self.index;
+        let Ok(index) = core::str::from_utf8(&index[..index_len]) else {
+            return 0;
+        };
+
+        let mut icons = 0;
+        for name in index.lines().map(str::trim) {
+            if name.is_empty() || name.starts_with('#') || name.contains('/') {
+                continue;
+            }
+            for size in ICON_SIZES {
+                if self.load_icon(writer, name, size) {
+                    icons += 1;
+                }
+            }
+        }
+        icons
+    }
+
+    fn load_icon(&mut self, writer: &mut AtlasWriter<'_>, name: &str, size: u16) -> bool {
+        let mut digits = [0u8; 5];
+        let mut path = PathBuf::new();
+        if !path.push(ICON_ROOT)
+            || !path.push(&self.theme[..self.theme_len])
+            || !path.push(b"/")
+            || !path.push(format_u16(size, &mut digits))
+            || !path.push(b"/")
+            || !path.push(name.as_bytes())
+            || !path.push(b".ff")
+        {
+            return false;
+        }
+        let Some(len) = read_file(path.as_c_str(), &mut self.file) else {
+            return false;
+        };
+        let Some((width, height, pixels)) = atlas::parse_farbfeld(&self.file[..len]) else {
+            return false;
+        };
+        if width != size || height != size {
+            return false;
+        }
+        let mut rgba = [0u8; 32 * 32 * 4];
+        let n = atlas::farbfeld_to_rgba8(pixels, &mut rgba);
+        n != 0
+            && writer
+                .add_icon(name, size, width, height, &rgba[..n])
+                .is_ok()
+    }
+}
+
+/// Chemin C borné, terminé par NUL.
+struct PathBuf {
+    bytes: [u8; PATH_MAX],
+    len: usize,
+}
+
+impl PathBuf {
+    const fn new() -> Self {
+        Self {
+            bytes: [0; PATH_MAX],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, part: &[u8]) -> bool {
+        if self.len + part.len() >= PATH_MAX {
+            return false;
+        }
+        self.bytes[self.len..self.len + part.len()].copy_from_slice(part);
+        self.len += part.len();
+        self.bytes[self.len] = 0;
+        true
+    }
+
+    fn as_c_str(&self) -> &[u8] {
+        &self.bytes[..=self.len]
+    }
+}
+
+fn format_u16(mut v: u16, buf: &mut [u8; 5]) -> &[u8] {
+    let mut at = buf.len();
+    loop {
+        at -= 1;
+        buf[at] = b'0' + (v % 10) as u8;
+        v /= 10;
+        if v == 0 {
+            return &buf[at..];
+        }
+    }
+}
+
+/// Lit tout le fichier `path` (terminé par NUL) ; `None` s'il est absent,
+/// vide ou plus grand que `buf`.
+fn read_file(path: &[u8], buf: &mut [u8]) -> Option<usize> {
+    // SAFETY: chemin terminé par NUL.
+    let fd =
+        unsafe { syscall::syscall2(syscall::SYS_OPEN, path.as_ptr() as u64, syscall::O_RDONLY) };
+    if fd < 0 {
+        return None;
+    }
+    let mut len = 0usize;
+    let mut overflow = false;
+    loop {
+        if len == buf.len() {
+            let mut probe = [0u8; 1];
+            // SAFETY: lecture d'un octet dans un buffer local.
+            overflow = unsafe {
+                syscall::syscall3(syscall::SYS_READ, fd as u64, probe.as_mut_ptr() as u64, 1)
+            } > 0;
+            break;
+        }
+        // SAFETY: écriture bornée à la fin du buffer.
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_READ,
+                fd as u64,
+                buf[len..].as_mut_ptr() as u64,
+                (buf.len() - len) as u64,
+            )
+        };
+        if n <= 0 {
+            break;
+        }
+        len += n as usize;
+    }
+    // SAFETY: fermeture du descripteur ouvert ci-dessus.
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+    if overflow || len == 0 {
+        None
+    } else {
+        Some(len)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    {
+        let mut cache = CACHE.lock();
+        let _ = cache.set_theme(DEFAULT_THEME);
+        cache.load_fonts();
+        if cache.create().is_ok() {
+            cache.rebuild();
+        }
+    }
+    let endpoint = register_endpoint();
+    let mut request = FontCacheRequest::zeroed();
+
+    loop {
+        if endpoint == 0 {
+            continue;
+        }
+        match recv_request(endpoint, &mut request) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => continue,
+        }
+
+        let reply = dispatch(&request);
+        let _ = send_reply(request.sender_pid, &reply);
+    }
+}
+
+fn dispatch(request: &FontCacheRequest) -> FontCacheReply {
+    let mut cache = CACHE.lock();
+
+    match request.msg_type {
+        FONT_CACHE_MSG_HEARTBEAT => FontCacheReply::ok(
+            cache.region,
+            cache.generation,
+            cache.glyphs + cache.icons,
+            0,
+        ),
+        FONT_CACHE_MSG_ATTACH => {
+            if cache.region == 0 {
+                return FontCacheReply::error(syscall::ENOMEM);
+            }
+            FontCacheReply::ok(cache.region, ATLAS_BYTES as u64, cache.generation, 0)
+        }
+        FONT_CACHE_MSG_SET_THEME => {
+            let payload = &request.payload;
+            let len = payload
+                .iter()
+                .position(|&b| b == 0)
+                .unwrap_or(payload.len());
+            if !cache.set_theme(&payload[..len]) {
+                return FontCacheReply::error(syscall::EINVAL);
+            }
+            cache.rebuild();
+            FontCacheReply::ok(cache.region, 0, cache.generation, 0)
+        }
+        FONT_CACHE_MSG_SET_FONT => {
+            let mut raw = [0u8; 4];
+            raw.copy_from_slice(&request.payload[..4]);
+            let scale = u32::from_le_bytes(raw);
+            if scale == 0 || scale > MAX_FONT_SCALE {
+                return FontCacheReply::error(syscall::EINVAL);
+            }
+            cache.scale = scale;
+            cache.rebuild();
+            FontCacheReply::ok(cache.region, 0, cache.generation, 0)
+        }
+        FONT_CACHE_MSG_STATS => FontCacheReply::ok(cache.rebuilds, cache.glyphs, cache.icons, 0),
+        _ => FontCacheReply::error(syscall::EINVAL),
+    }
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        // SAFETY: panic terminale pour un serveur no_std monothread.
+        unsafe {
+            core::arch::asm!("hlt", options(nostack, nomem));
+        }
+    }
+}
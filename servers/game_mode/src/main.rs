@@ -0,0 +1,281 @@
+#![no_std]
+#![no_main]
+
+//! # game_mode — mode jeu basse latence d'un client plein écran
+//!
+//! Le propriétaire de l'affichage demande le mode jeu (`GAMEMODE_MSG_ENTER`)
+//! pour son client plein écran, ou pour lui-même si l'affichage lui a été
+//! prêté. Le démon vérifie auprès de `fb_server` que le demandeur possède
+//! bien l'affichage, puis (`exo_services::gamemode`) :
+//!
+//! - fait passer le client en classe temps réel ou deadline
+//!   (`SCHED_MSG_GAME_BOOST` à `scheduler_server`) ;
+//! - épingle le profil d'énergie performance (sysctl `kernel.power.profile`),
+//!   après avoir noté le profil courant ;
+//! - répond `STEP_SCANOUT` au compositeur, qui prête alors l'affichage au
+//!   client (`FB_TRANSFER_LEND`) : le jeu dessine directement.
+//!
+//! Tout est défait à `GAMEMODE_MSG_EXIT` ou dès que le client disparaît
+//! (sondé toutes les `POLL_MS`) : classe CFS, profil d'origine, et
+//! `fb_server` rend de lui-même l'affichage prêté au compositeur. Seules
+//! les étapes réellement appliquées sont défaites.
+
+use core::panic::PanicInfo;
+
+use exo_services::gamemode::{
+    Config, EnterError, GameMode, Session, GAMEMODE_MSG_ENTER, GAMEMODE_MSG_EXIT,
+    GAMEMODE_MSG_HEARTBEAT, GAMEMODE_MSG_STATUS, STEP_POWER, STEP_SCANOUT, STEP_SCHED,
+};
+use exo_syscall_abi as syscall;
+use spin::Mutex;
+
+mod protocol;
+
+use protocol::{
+    display_owner, recv_request, register_endpoint, sched_call, send_reply, GameModeReply,
+    GameModeRequest, IDLE_TIMEOUT_MS, POLL_MS, SCHED_MSG_GAME_BOOST, SCHED_MSG_GAME_RESTORE,
+};
+
+const CONFIG_PATH: &[u8] = b"/etc/exo/gamemode.conf\0";
+const CONFIG_MAX: usize = 1024;
+const POWER_PROFILE: &[u8] = b"kernel.power.profile";
+const PROFILE_PERFORMANCE: u64 = 1;
+
+struct GameModeService {
+    mode: GameMode,
+}
+
+static SERVICE: Mutex<GameModeService> = Mutex::new(GameModeService::new());
+
+impl GameModeService {
+    const fn new() -> Self {
+        Self {
+            mode: GameMode::new(Config::new()),
+        }
+    }
+
+    fn load(&mut self) {
+        let mut buf = [0u8; CONFIG_MAX];
+        let mut len = 0;
+        // SAFETY: chemin statique terminé par NUL.
+        let fd = unsafe {
+            syscall::syscall2(
+                syscall::SYS_OPEN,
+                CONFIG_PATH.as_ptr() as u64,
+                syscall::O_RDONLY,
+            )
+        };
+        if fd < 0 {
+            return;
+        }
+        while len < CONFIG_MAX {
+            // SAFETY: écriture bornée à la fin du buffer.
+            let n = unsafe {
+                syscall::syscall3(
+                    syscall::SYS_READ,
+                    fd as u64,
+                    buf[len..].as_mut_ptr() as u64,
+                    (CONFIG_MAX - len) as u64,
+                )
+            };
+            if n <= 0 {
+                break;
+            }
+            len += n as usize;
+        }
+        // SAFETY: fermeture du descripteur ouvert ci-dessus.
+        let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+        if let Ok(text) = core::str::from_utf8(&buf[..len]) {
+            self.mode.set_config(Config::parse(text));
+        }
+    }
+
+    fn handle_enter(&mut self, sender: u32, payload: &[u8]) -> GameModeReply {
+        let Some(pid) = read_u32(payload, 0) else {
+            return GameModeReply::error(syscall::EINVAL);
+        };
+        let client = if pid == 0 { sender } else { pid };
+        if !process_alive(client) {
+            return GameModeReply::error(syscall::ESRCH);
+        }
+        let steps = match self.mode.enter(sender, client, display_owner()) {
+            Ok(steps) => steps,
+            Err(EnterError::NotFullscreen) => return GameModeReply::error(syscall::EPERM),
+            Err(EnterError::Busy) => return GameModeReply::error(syscall::EBUSY),
+        };
+
+        let config = *self.mode.config();
+        if steps & STEP_SCHED != 0 {
+            let mut boost = [0u8; 16];
+            boost[0..4].copy_from_slice(&client.to_le_bytes());
+            boost[4..8].copy_from_slice(&config.class.as_u32().to_le_bytes());
+            boost[8..12].copy_from_slice(&config.runtime_us.to_le_bytes());
+            boost[12..16].copy_from_slice(&config.period_us.to_le_bytes());
+            if sched_call(SCHED_MSG_GAME_BOOST, &boost) >= 0 {
+                self.mode.applied(STEP_SCHED);
+            }
+        }
+        if steps & STEP_POWER != 0 {
+            // Profil déjà performance : rien à défaire.
+            if let Some(previous) = read_profile().filter(|p| *p != PROFILE_PERFORMANCE) {
+                if write_profile(PROFILE_PERFORMANCE) {
+                    self.mode.save_profile(previous);
+                    self.mode.applied(STEP_POWER);
+                }
+            }
+        }
+        // Le prêt de l'affichage revient au compositeur, qui le fait sur
+        // réception de ce drapeau.
+        if steps & STEP_SCANOUT != 0 {
+            self.mode.applied(STEP_SCANOUT);
+        }
+        self.status()
+    }
+
+    fn handle_exit(&mut self, sender: u32, payload: &[u8]) -> GameModeReply {
+        let Some(pid) = read_u32(payload, 0) else {
+            return GameModeReply::error(syscall::EINVAL);
+        };
+        match self.mode.exit(sender, pid) {
+            Some(session) => {
+                revert(&session);
+                GameModeReply::ok(session.client as u64, 0, 0, session.applied)
+            }
+            None => GameModeReply::error(syscall::ENOENT),
+        }
+    }
+
+    fn status(&self) -> GameModeReply {
+        match self.mode.session() {
+            Some(session) => GameModeReply::ok(
+                session.client as u64,
+                session.requester as u64,
+                0,
+                session.applied,
+            ),
+            None => GameModeReply::ok(0, 0, 0, 0),
+        }
+    }
+
+    /// Le client est parti sans `GAMEMODE_MSG_EXIT` : tout est défait.
+    fn check_client(&mut self) {
+        let Some(session) = self.mode.session() else {
+            return;
+        };
+        if !process_alive(session.client) {
+            if let Some(session) = self.mode.end() {
+                revert(&session);
+            }
+        }
+    }
+}
+
+fn revert(session: &Session) {
+    if session.applied & STEP_SCHED != 0 {
+        let _ = sched_call(SCHED_MSG_GAME_RESTORE, &session.client.to_le_bytes());
+    }
+    if session.applied & STEP_POWER != 0 {
+        let _ = write_profile(session.saved_profile);
+    }
+}
+
+fn read_u32(payload: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        payload.get(at..at + 4)?.try_into().ok()?,
+    ))
+}
+
+fn process_alive(pid: u32) -> bool {
+    // SAFETY: signal 0 : simple test d'existence, sans effet.
+    pid != 0 && unsafe { syscall::syscall2(syscall::SYS_KILL, pid as u64, 0) } != syscall::ESRCH
+}
+
+fn read_profile() -> Option<u64> {
+    let mut text = [0u8; 24];
+    // SAFETY: nom statique et buffer local valides pendant l'appel.
+    let rc = unsafe {
+        syscall::syscall5(
+            syscall::SYS_EXO_SYSCTL,
+            syscall::EXO_SYSCTL_READ,
+            POWER_PROFILE.as_ptr() as u64,
+            POWER_PROFILE.len() as u64,
+            text.as_mut_ptr() as u64,
+            text.len() as u64,
+        )
+    };
+    if rc <= 0 {
+        return None;
+    }
+    core::str::from_utf8(&text[..rc as usize])
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn write_profile(profile: u64) -> bool {
+    let value = [b'0' + profile.min(9) as u8];
+    // SAFETY: nom statique et valeur locale valides pendant l'appel.
+    let rc = unsafe {
+        syscall::syscall5(
+            syscall::SYS_EXO_SYSCTL,
+            syscall::EXO_SYSCTL_WRITE,
+            POWER_PROFILE.as_ptr() as u64,
+            POWER_PROFILE.len() as u64,
+            value.as_ptr() as u64,
+            value.len() as u64,
+        )
+    };
+    rc >= 0
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let endpoint = register_endpoint();
+    SERVICE.lock().load();
+    let mut request = GameModeRequest::zeroed();
+
+    loop {
+        let timeout = {
+            let mut service = SERVICE.lock();
+            service.check_client();
+            if service.mode.session().is_some() {
+                POLL_MS
+            } else {
+                IDLE_TIMEOUT_MS
+            }
+        };
+        if endpoint == 0 {
+            continue;
+        }
+        match recv_request(endpoint, &mut request, timeout) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => continue,
+        }
+
+        let reply = dispatch(&request);
+        let _ = send_reply(request.sender_pid, &reply);
+    }
+}
+
+fn dispatch(request: &GameModeRequest) -> GameModeReply {
+    let mut service = SERVICE.lock();
+
+    match request.msg_type {
+        GAMEMODE_MSG_HEARTBEAT | GAMEMODE_MSG_STATUS => service.status(),
+        GAMEMODE_MSG_ENTER => service.handle_enter(request.sender_pid, &request.payload),
+        GAMEMODE_MSG_EXIT => service.handle_exit(request.sender_pid, &request.payload),
+        _ => GameModeReply::error(syscall::EINVAL),
+    }
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        // SAFETY: panic terminale pour un serveur no_std monothread.
+        unsafe {
+            core::arch::asm!("hlt", options(nostack, nomem));
+        }
+    }
+}
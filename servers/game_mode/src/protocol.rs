@@ -0,0 +1,213 @@
+use exo_syscall_abi as syscall;
+
+/// Canal du serveur, dans l'espace d'endpoints de son PID ; les clients le
+/// retrouvent par son nom (`SYS_IPC_LOOKUP "game_mode"`). `scheduler_server`
+/// vérifie ce nom avant d'accepter un boost.
+pub const GAME_MODE_CHANNEL: u64 = 1;
+/// Période de vérification du client en mode jeu.
+pub const POLL_MS: u64 = 250;
+pub const IDLE_TIMEOUT_MS: u64 = 5_000;
+/// Endpoint fixe de `scheduler_server`.
+pub const SCHEDULER_ENDPOINT: u64 = 8;
+pub const SCHED_MSG_GAME_BOOST: u32 = 9;
+pub const SCHED_MSG_GAME_RESTORE: u32 = 10;
+/// Attente maximale d'une réponse de `fb_server` ou `scheduler_server`.
+const CALL_TIMEOUT_MS: u64 = 500;
+
+#[repr(C)]
+pub struct GameModeRequest {
+    pub sender_pid: u32,
+    pub msg_type: u32,
+    pub payload: [u8; syscall::IPC_INLINE_PAYLOAD_SIZE],
+}
+
+impl GameModeRequest {
+    pub const fn zeroed() -> Self {
+        Self {
+            sender_pid: 0,
+            msg_type: 0,
+            payload: [0; syscall::IPC_INLINE_PAYLOAD_SIZE],
+        }
+    }
+}
+
+const _: () = assert!(core::mem::size_of::<GameModeRequest>() == syscall::IPC_ENVELOPE_SIZE);
+const _: () = assert!(core::mem::offset_of!(GameModeRequest, payload) == syscall::IPC_HEADER_SIZE);
+
+/// Même disposition que les réponses de `scheduler_server`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct GameModeReply {
+    pub status: i64,
+    pub handle: u64,
+    pub value0: u64,
+    pub value1: u64,
+    pub flags: u32,
+    pub _pad: [u8; 28],
+}
+
+impl GameModeReply {
+    pub const fn ok(handle: u64, value0: u64, value1: u64, flags: u32) -> Self {
+        Self {
+            status: 0,
+            handle,
+            value0,
+            value1,
+            flags,
+            _pad: [0; 28],
+        }
+    }
+
+    pub const fn error(status: i64) -> Self {
+        Self {
+            status,
+            handle: 0,
+            value0: 0,
+            value1: 0,
+            flags: 0,
+            _pad: [0; 28],
+        }
+    }
+}
+
+/// Enregistre `game_mode` ; retourne l'endpoint, `0` en cas d'échec.
+pub fn register_endpoint() -> u64 {
+    let pid = own_pid();
+    if pid == 0 {
+        return 0;
+    }
+    let endpoint = ((pid as u64) << 32) | GAME_MODE_CHANNEL;
+    let name = b"game_mode";
+    // SAFETY: buffer statique valide, endpoint dans l'espace du PID courant.
+    let rc = unsafe {
+        syscall::syscall3(
+            syscall::SYS_IPC_REGISTER,
+            name.as_ptr() as u64,
+            name.len() as u64,
+            endpoint,
+        )
+    };
+    if rc < 0 {
+        0
+    } else {
+        endpoint
+    }
+}
+
+pub fn own_pid() -> u32 {
+    // SAFETY: lecture simple du PID courant.
+    let pid = unsafe { syscall::syscall0(syscall::SYS_GETPID) };
+    if pid <= 0 {
+        0
+    } else {
+        pid as u32
+    }
+}
+
+pub fn recv_request(
+    endpoint: u64,
+    request: &mut GameModeRequest,
+    timeout_ms: u64,
+) -> Result<bool, i64> {
+    // SAFETY: le noyau écrit dans `request`, taille bornée à la struct ABI.
+    let rc = unsafe {
+        syscall::syscall4(
+            syscall::SYS_IPC_RECV,
+            endpoint,
+            request as *mut GameModeRequest as u64,
+            core::mem::size_of::<GameModeRequest>() as u64,
+            syscall::IPC_FLAG_TIMEOUT | timeout_ms,
+        )
+    };
+
+    if rc == syscall::ETIMEDOUT {
+        return Ok(false);
+    }
+    if rc < 0 {
+        return Err(rc);
+    }
+    Ok(true)
+}
+
+pub fn send_reply(destination_pid: u32, reply: &GameModeReply) -> i64 {
+    // SAFETY: `reply` est une structure POD locale envoyée telle quelle au noyau.
+    unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            destination_pid as u64,
+            reply as *const GameModeReply as u64,
+            core::mem::size_of::<GameModeReply>() as u64,
+            0,
+            0,
+            0,
+        )
+    }
+}
+
+/// Réception d'une réponse dans la boîte par défaut du processus
+/// (endpoint = PID), où `fb_server` et `scheduler_server` répondent.
+fn recv_reply<T>(reply: &mut T) -> bool {
+    // SAFETY: le noyau écrit dans `reply`, taille bornée au type POD.
+    let rc = unsafe {
+        syscall::syscall4(
+            syscall::SYS_IPC_RECV,
+            own_pid() as u64,
+            reply as *mut T as u64,
+            core::mem::size_of::<T>() as u64,
+            syscall::IPC_FLAG_TIMEOUT | CALL_TIMEOUT_MS,
+        )
+    };
+    rc >= 0
+}
+
+/// PID du propriétaire de l'affichage (0 : console ou `fb_server` muet).
+pub fn display_owner() -> u32 {
+    let mut req = syscall::FbRequest::zeroed();
+    req.msg_type = syscall::FB_MSG_DISPLAY_STATE;
+    req.reply_endpoint = own_pid() as u64;
+    // SAFETY: requête POD locale, taille de la struct ABI.
+    let rc = unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            syscall::FB_SERVER_ENDPOINT,
+            &req as *const syscall::FbRequest as u64,
+            core::mem::size_of::<syscall::FbRequest>() as u64,
+            syscall::IPC_FLAG_TIMEOUT,
+            0,
+            0,
+        )
+    };
+    let mut reply = syscall::FbReply::default();
+    if rc < 0 || !recv_reply(&mut reply) || reply.status < 0 {
+        return 0;
+    }
+    reply.status as u32
+}
+
+/// Requête à `scheduler_server` ; retourne le statut de sa réponse.
+pub fn sched_call(msg_type: u32, payload: &[u8]) -> i64 {
+    let mut message = GameModeRequest::zeroed();
+    message.msg_type = msg_type;
+    let len = payload.len().min(message.payload.len());
+    message.payload[..len].copy_from_slice(&payload[..len]);
+    // SAFETY: enveloppe POD locale ; le noyau remplace `sender_pid`.
+    let rc = unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            SCHEDULER_ENDPOINT,
+            &message as *const GameModeRequest as u64,
+            core::mem::size_of::<GameModeRequest>() as u64,
+            syscall::IPC_FLAG_TIMEOUT,
+            0,
+            0,
+        )
+    };
+    if rc < 0 {
+        return rc;
+    }
+    let mut reply = GameModeReply::error(syscall::ETIMEDOUT);
+    if !recv_reply(&mut reply) {
+        return syscall::ETIMEDOUT;
+    }
+    reply.status
+}
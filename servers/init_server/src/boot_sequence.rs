@@ -1,4 +1,4 @@
-use super::{dependency, log, service_manager, service_table, syscall, Service};
+use super::{dependency, log, service_manager, service_table, splash, syscall, Service};
 
 const POLL_INTERVAL_MS: u64 = 5;
 const BOOT_PHASE_TIMEOUT_MS: u64 = 300_000;
@@ -291,6 +291,17 @@ fn note_graph_progress(last_progress_ms: &mut u64) {
     }
 }
 
+/// Jalon du splash : services prêts ou abandonnés sur le total du graphe.
+fn note_splash_milestone(services: &[Service], ready_mask: u64, idx: usize, failed: bool) {
+    if !failed && services[idx].name == "fb_server" {
+        splash::start();
+    }
+    let settled = (0..services.len())
+        .filter(|&i| ready_mask & (1u64 << i) != 0 || services[i].is_dead())
+        .count();
+    splash::milestone(services[idx].name, settled, services.len(), failed);
+}
+
 /// Démarre la chaîne Ring1 canonique V4 par vagues de dépendances.
 ///
 /// Tous les services dont les dépendances déjà prêtes sont satisfaites sont
@@ -323,6 +334,9 @@ pub unsafe fn boot_services(services: &[Service]) -> usize {
                 idx += 1;
                 continue;
             }
+            if owns_interactive_console(service.name) {
+                splash::finish();
+            }
 
             let pid = spawn_service(service.name, service.bin_path);
             if pid == 0 {
@@ -370,6 +384,7 @@ pub unsafe fn boot_services(services: &[Service]) -> usize {
                         log::set_console_quiet(true);
                     }
                     log::service_pid(b"init: ready ", service.name, pid);
+                    note_splash_milestone(services, ready_mask, idx, false);
                     settled = true;
                     readiness_changed = true;
                     note_graph_progress(&mut last_progress_ms);
@@ -395,6 +410,7 @@ pub unsafe fn boot_services(services: &[Service]) -> usize {
                             log::set_console_quiet(true);
                         }
                         log::service_pid(b"init: ready ", service.name, pid);
+                        note_splash_milestone(services, ready_mask, idx, false);
                         settled = true;
                         readiness_changed = true;
                         note_graph_progress(&mut last_progress_ms);
@@ -405,6 +421,7 @@ pub unsafe fn boot_services(services: &[Service]) -> usize {
                     log::service_status(b"init: timeout ", service.name, b"\n");
                     if terminate_timed_out_pid(pid) {
                         service.mark_dead();
+                        note_splash_milestone(services, ready_mask, idx, true);
                         settled = true;
                         readiness_changed = true;
                         note_graph_progress(&mut last_progress_ms);
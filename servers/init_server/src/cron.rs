@@ -0,0 +1,407 @@
+//! Planificateur de tâches (à la cron) porté par init.
+//!
+//! `/etc/crontab` est lu au boot : expressions cron à 5 champs, raccourcis
+//! `@daily`…, `@reboot` et `@on(<expression OnCalendar>)`. Les crontabs
+//! utilisateur (`/var/spool/cron/<utilisateur>`, sans colonne uid) suivent ;
+//! le nom de fichier est résolu par `/etc/passwd` et les tâches tournent
+//! sous cet uid. La syntaxe et le calcul des échéances vivent dans
+//! `exo_services::schedule` ; ce module ne fait que l'horloge, le lancement
+//! (vfork + execve sous l'uid du propriétaire) et l'état persistant.
+//!
+//! Les tâches marquées `persistent` dont une échéance est tombée pendant que
+//! la machine était éteinte sont rattrapées une fois au boot, grâce à la date
+//! de dernière exécution gardée dans `/var/lib/exo-cron/state`.
+//!
+//! Les tâches marquées `unmetered` (sauvegardes, mises à jour) attendent
+//! tant que le mode économie de données est actif (`INIT_MSG_DATA_SAVER`,
+//! envoyé par `data_saver`) : elles restent échues et partent dès qu'il
+//! retombe.
+//!
+//! Les heures sont en UTC ; les lancements et fins de tâche vont au journal
+//! noyau, pas sur la console.
+
+use crate::{log, syscall};
+use exo_services::schedule::{self, Schedule, Table};
+use exo_std::time::SystemTime;
+use spin::Mutex;
+
+const CRONTAB_PATH: &[u8] = b"/etc/crontab\0";
+const SPOOL_DIR: &[u8] = b"/var/spool/cron/";
+const PASSWD_PATH: &[u8] = b"/etc/passwd\0";
+const STATE_PATH: &[u8] = b"/var/lib/exo-cron/state\0";
+
+const MAX_JOBS: usize = 32;
+const MAX_COMMAND: usize = 160;
+const MAX_ARGS: usize = 16;
+const FILE_BUF: usize = 4096;
+const PATH_MAX: usize = 128;
+const DIRENT64_HEADER_SIZE: usize = 24;
+
+#[derive(Clone, Copy)]
+struct Job {
+    key: u64,
+    uid: u32,
+    schedule: Schedule,
+    command: [u8; MAX_COMMAND],
+    command_len: usize,
+    /// Prochaine échéance (secondes UNIX), `None` : plus rien à lancer.
+    next: Option<u64>,
+    last_run: Option<u64>,
+    /// PID de l'exécution en cours : une tâche ne se chevauche pas.
+    pid: u32,
+    /// Différée tant que le mode économie de données est actif.
+    unmetered: bool,
+}
+
+impl Job {
+    fn command(&self) -> &str {
+        core::str::from_utf8(&self.command[..self.command_len]).unwrap_or("")
+    }
+}
+
+struct CronTable {
+    jobs: [Option<Job>; MAX_JOBS],
+    last_tick: u64,
+    data_saver: bool,
+}
+
+static CRON: Mutex<CronTable> = Mutex::new(CronTable {
+    jobs: [None; MAX_JOBS],
+    last_tick: 0,
+    data_saver: false,
+});
+
+fn realtime_secs() -> Option<u64> {
+    SystemTime::now().map(|now| now.unix_secs() as u64)
+}
+
+/// Lit un fichier entier dans `buf` ; `None` s'il est absent ou vide.
+pub(crate) fn read_file(path: &[u8], buf: &mut [u8]) -> Option<usize> {
+    let fd =
+        unsafe { syscall::syscall2(syscall::SYS_OPEN, path.as_ptr() as u64, syscall::O_RDONLY) };
+    if fd < 0 {
+        return None;
+    }
+    let mut len = 0usize;
+    while len < buf.len() {
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_READ,
+                fd as u64,
+                buf[len..].as_mut_ptr() as u64,
+                (buf.len() - len) as u64,
+            )
+        };
+        if n <= 0 {
+            break;
+        }
+        len += n as usize;
+    }
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+    (len > 0).then_some(len)
+}
+
+fn last_run_of(state: &str, key: u64) -> Option<u64> {
+    state
+        .lines()
+        .filter_map(schedule::parse_state_line)
+        .find(|&(k, _)| k == key)
+        .map(|(_, last)| last)
+}
+
+/// Charge `/etc/crontab`, les crontabs utilisateur et l'état persistant.
+/// Appelé une fois, après le démarrage du graphe de services (le VFS doit
+/// être prêt).
+pub fn load() {
+    let Some(now) = realtime_secs() else {
+        log::line(b"init: cron disabled (no realtime clock)");
+        return;
+    };
+    let mut state = [0u8; FILE_BUF];
+    let state_len = read_file(STATE_PATH, &mut state).unwrap_or(0);
+    let state = core::str::from_utf8(&state[..state_len]).unwrap_or("");
+
+    let mut cron = CRON.lock();
+    let mut slot = 0usize;
+    load_table(
+        &mut cron,
+        &mut slot,
+        CRONTAB_PATH,
+        Table::System,
+        state,
+        now,
+    );
+    load_user_tables(&mut cron, &mut slot, state, now);
+    log::journal(b"cron: ", b"crontabs", b" jobs=", slot as i64);
+}
+
+/// Ajoute les tâches d'une crontab à partir de `*slot`.
+fn load_table(
+    cron: &mut CronTable,
+    slot: &mut usize,
+    path: &[u8],
+    table: Table,
+    state: &str,
+    now: u64,
+) {
+    let name = path.strip_suffix(b"\0").unwrap_or(path);
+    let mut text = [0u8; FILE_BUF];
+    let Some(len) = read_file(path, &mut text) else {
+        return;
+    };
+    let Ok(text) = core::str::from_utf8(&text[..len]) else {
+        log::journal(b"cron: ", name, b" not UTF-8 len=", len as i64);
+        return;
+    };
+    for (number, line) in text.lines().enumerate() {
+        let entry = match schedule::parse_crontab_line(line, table) {
+            Ok(Some(entry)) => entry,
+            Ok(None) => continue,
+            Err(_) => {
+                log::journal(b"cron: ", name, b" bad line ", number as i64 + 1);
+                continue;
+            }
+        };
+        if *slot == MAX_JOBS || entry.command.len() > MAX_COMMAND {
+            log::journal(b"cron: ", name, b" skipped line ", number as i64 + 1);
+            continue;
+        }
+        let key = schedule::job_key(entry.uid, line);
+        let last_run = last_run_of(state, key);
+        let mut command = [0u8; MAX_COMMAND];
+        command[..entry.command.len()].copy_from_slice(entry.command.as_bytes());
+        cron.jobs[*slot] = Some(Job {
+            key,
+            uid: entry.uid,
+            schedule: entry.schedule,
+            command,
+            command_len: entry.command.len(),
+            next: entry.schedule.first_fire(entry.persistent, last_run, now),
+            last_run,
+            pid: 0,
+            unmetered: entry.unmetered,
+        });
+        *slot += 1;
+    }
+}
+
+/// Parcourt `/var/spool/cron` : un fichier par utilisateur, nommé d'après
+/// son entrée dans `/etc/passwd`. Les noms inconnus sont ignorés.
+fn load_user_tables(cron: &mut CronTable, slot: &mut usize, state: &str, now: u64) {
+    let mut passwd = [0u8; FILE_BUF];
+    let passwd_len = read_file(PASSWD_PATH, &mut passwd).unwrap_or(0);
+    let passwd = core::str::from_utf8(&passwd[..passwd_len]).unwrap_or("");
+
+    let mut dir = [0u8; PATH_MAX];
+    dir[..SPOOL_DIR.len()].copy_from_slice(SPOOL_DIR);
+    let fd =
+        unsafe { syscall::syscall2(syscall::SYS_OPEN, dir.as_ptr() as u64, syscall::O_RDONLY) };
+    if fd < 0 {
+        return;
+    }
+    let mut buf = [0u8; 1024];
+    loop {
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_GETDENTS64,
+                fd as u64,
+                buf.as_mut_ptr() as u64,
+                buf.len() as u64,
+            )
+        };
+        if n <= 0 {
+            break;
+        }
+        let buf = &buf[..n as usize];
+        let mut off = 0usize;
+        while off + DIRENT64_HEADER_SIZE <= buf.len() {
+            let reclen = u16::from_le_bytes([buf[off + 16], buf[off + 17]]) as usize;
+            if reclen == 0 || off + reclen > buf.len() {
+                break;
+            }
+            let record = &buf[off + DIRENT64_HEADER_SIZE..off + reclen];
+            let name_len = record.iter().position(|&b| b == 0).unwrap_or(record.len());
+            let name = &record[..name_len];
+            off += reclen;
+            if name.is_empty() || name[0] == b'.' {
+                continue;
+            }
+            let Some(uid) = core::str::from_utf8(name)
+                .ok()
+                .and_then(|user| passwd_uid(passwd, user))
+            else {
+                log::journal(b"cron: unknown user ", name, b" len=", name_len as i64);
+                continue;
+            };
+            let end = SPOOL_DIR.len() + name_len;
+            if end >= PATH_MAX {
+                continue;
+            }
+            let mut path = dir;
+            path[SPOOL_DIR.len()..end].copy_from_slice(name);
+            path[end] = 0;
+            load_table(cron, slot, &path[..=end], Table::User(uid), state, now);
+        }
+    }
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+}
+
+/// Uid de `user` dans `/etc/passwd` (`nom:mot de passe:uid:…`).
+fn passwd_uid(passwd: &str, user: &str) -> Option<u32> {
+    passwd.lines().find_map(|line| {
+        let mut fields = line.split(':');
+        if fields.next()? != user {
+            return None;
+        }
+        fields.nth(1)?.parse().ok()
+    })
+}
+
+/// Lance les tâches échues. Appelé à chaque tour de la boucle de
+/// supervision ; ne fait rien tant que la seconde courante n'a pas changé.
+pub fn tick() {
+    let Some(now) = realtime_secs() else {
+        return;
+    };
+    let mut cron = CRON.lock();
+    if now == cron.last_tick {
+        return;
+    }
+    cron.last_tick = now;
+    let data_saver = cron.data_saver;
+    let mut ran = false;
+    for job in cron.jobs.iter_mut().flatten() {
+        if job.pid != 0 || job.next.is_none_or(|next| next > now) {
+            continue;
+        }
+        if job.unmetered && data_saver {
+            continue;
+        }
+        job.pid = unsafe { spawn_job(job) };
+        job.last_run = Some(now);
+        job.next = job.schedule.after_run(now);
+        ran = true;
+    }
+    if ran {
+        save_state(&cron);
+    }
+}
+
+/// Mode économie de données (connexion facturée au volume) : active ou
+/// lève la mise en attente des tâches `unmetered`.
+pub fn set_data_saver(active: bool) {
+    let mut cron = CRON.lock();
+    if cron.data_saver != active {
+        cron.data_saver = active;
+        log::journal(b"cron: ", b"data saver", b" active=", active as i64);
+    }
+}
+
+/// Enregistre la fin d'un fils qui n'est pas un service supervisé.
+pub fn note_exit(pid: u32, wstatus: u32) {
+    let mut cron = CRON.lock();
+    let Some(job) = cron
+        .jobs
+        .iter_mut()
+        .flatten()
+        .find(|job| job.pid == pid && pid != 0)
+    else {
+        return;
+    };
+    job.pid = 0;
+    let code = if wstatus & 0x7f == 0 {
+        ((wstatus >> 8) & 0xff) as i64
+    } else {
+        -((wstatus & 0x7f) as i64)
+    };
+    log::journal(b"cron: done ", job.command().as_bytes(), b" status=", code);
+}
+
+/// vfork + execve sous l'uid du propriétaire, même contrat que
+/// `boot_sequence::spawn_service` : l'enfant ne fait que setgid/setuid +
+/// execve (ou _exit). Retourne le PID, `0` si le lancement échoue.
+unsafe fn spawn_job(job: &Job) -> u32 {
+    let mut words = [0u8; MAX_COMMAND + MAX_ARGS];
+    let mut offsets = [0usize; MAX_ARGS];
+    let Some(argc) = schedule::split_command(job.command(), &mut words, &mut offsets) else {
+        log::journal(
+            b"cron: bad command ",
+            job.command().as_bytes(),
+            b" argc=",
+            0,
+        );
+        return 0;
+    };
+    let mut argv = [0u64; MAX_ARGS + 1];
+    for (arg, &offset) in argv.iter_mut().zip(&offsets[..argc]) {
+        *arg = words.as_ptr().add(offset) as u64;
+    }
+    let envp: [u64; 1] = [0];
+
+    let child_pid = syscall::syscall0(syscall::SYS_VFORK);
+    if child_pid < 0 {
+        log::journal(
+            b"cron: vfork failed ",
+            job.command().as_bytes(),
+            b" rc=",
+            child_pid,
+        );
+        return 0;
+    }
+    if child_pid == 0 {
+        if job.uid != 0 {
+            let gid = syscall::syscall1(syscall::SYS_SETGID, job.uid as u64);
+            let uid = syscall::syscall1(syscall::SYS_SETUID, job.uid as u64);
+            // Jamais lancer la tâche d'un utilisateur avec les droits d'init.
+            if gid < 0 || uid < 0 {
+                let _ = syscall::syscall1(syscall::SYS_EXIT, 126);
+            }
+        }
+        let _ = syscall::syscall3(
+            syscall::SYS_EXECVE,
+            argv[0],
+            argv.as_ptr() as u64,
+            envp.as_ptr() as u64,
+        );
+        let _ = syscall::syscall1(syscall::SYS_EXIT, 127);
+        let _ = syscall::syscall1(syscall::SYS_EXIT_GROUP, 127);
+        loop {
+            core::hint::spin_loop();
+        }
+    }
+    log::journal(b"cron: run ", job.command().as_bytes(), b" pid=", child_pid);
+    child_pid as u32
+}
+
+/// Réécrit l'état persistant (une ligne par tâche déjà lancée).
+fn save_state(cron: &CronTable) {
+    let fd = unsafe {
+        syscall::syscall3(
+            syscall::SYS_OPEN,
+            STATE_PATH.as_ptr() as u64,
+            syscall::O_WRONLY | syscall::O_CREAT | syscall::O_TRUNC,
+            0o600,
+        )
+    };
+    if fd < 0 {
+        return;
+    }
+    let mut line = [0u8; 40];
+    for job in cron.jobs.iter().flatten() {
+        let Some(last) = job.last_run else {
+            continue;
+        };
+        let Some(n) = schedule::format_state_line(job.key, last, &mut line) else {
+            continue;
+        };
+        let _ = unsafe {
+            syscall::syscall3(
+                syscall::SYS_WRITE,
+                fd as u64,
+                line.as_ptr() as u64,
+                n as u64,
+            )
+        };
+    }
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+}
@@ -0,0 +1,42 @@
+//! Rapports au journal d'événements (`event_journal`).
+//!
+//! Init y note les plantages de services (statut de `wait4`), leurs
+//! relances (nouveau PID) et l'arrêt du système ; le journal n'accepte ces
+//! types que de lui (`exo_services::events::Kind::reporter`).
+//!
+//! Envoi sans attente : tant que le journal n'est pas lancé, ou s'il est
+//! mort, les événements sont perdus plutôt que de bloquer la supervision.
+
+use crate::{protocol, syscall};
+use exo_services::events::{self, Kind, EVENTS_MSG_RECORD};
+
+pub fn record(kind: Kind, subject: &str, detail: u64) {
+    let name = b"event_journal";
+    let endpoint = unsafe {
+        syscall::syscall3(
+            syscall::SYS_IPC_LOOKUP,
+            name.as_ptr() as u64,
+            name.len() as u64,
+            0,
+        )
+    };
+    if endpoint <= 0 {
+        return;
+    }
+    let mut message = protocol::InitRequest::zeroed();
+    message.msg_type = EVENTS_MSG_RECORD;
+    if events::encode_record(kind, subject, detail, &mut message.payload).is_none() {
+        return;
+    }
+    let _ = unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            endpoint as u64,
+            &message as *const protocol::InitRequest as u64,
+            core::mem::size_of::<protocol::InitRequest>() as u64,
+            syscall::IPC_FLAG_TIMEOUT,
+            0,
+            0,
+        )
+    };
+}
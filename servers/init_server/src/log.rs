@@ -111,3 +111,16 @@ pub fn service_error(prefix: &[u8], name: &str, code: i64) {
     push_bytes(&mut buf, &mut len, b"\n");
     write_all(&buf[..len]);
 }
+
+/// Écrit toujours dans le journal noyau, même console active : les tâches
+/// planifiées ne doivent pas polluer le terminal interactif.
+pub fn journal(prefix: &[u8], subject: &[u8], label: &[u8], value: i64) {
+    let mut buf = [0u8; 160];
+    let mut len = 0usize;
+    push_bytes(&mut buf, &mut len, prefix);
+    push_bytes(&mut buf, &mut len, subject);
+    push_bytes(&mut buf, &mut len, label);
+    push_i64(&mut buf, &mut len, value);
+    push_bytes(&mut buf, &mut len, b"\n");
+    kernel_log(&buf[..len]);
+}
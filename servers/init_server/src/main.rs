@@ -17,17 +17,24 @@
 
 use core::panic::PanicInfo;
 use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use exo_services::events::Kind;
+use exo_services::metered;
 pub(crate) use exo_syscall_abi as syscall;
 
 mod boot_info;
 mod boot_sequence;
+mod cron;
 mod dependency;
+mod events;
 mod isolation;
 mod log;
+mod netboot;
+mod preload;
 mod protocol;
 mod service_manager;
 mod service_table;
 mod sigchld_handler;
+mod splash;
 mod supervisor;
 mod watchdog;
 
@@ -160,6 +167,7 @@ impl Service {
 type ServiceWatchdog = watchdog::InitWatchdog<{ service_table::SERVICE_COUNT }>;
 
 // Séquence Ring1 canonique issue des docs de création/correction.
+#[cfg(not(feature = "headless"))]
 static SERVICES: [Service; service_table::SERVICE_COUNT] = [
     Service::new("ipc_router", service_table::IPC_ROUTER_BIN),
     Service::new("memory_server", service_table::MEMORY_SERVER_BIN),
@@ -180,6 +188,27 @@ static SERVICES: [Service; service_table::SERVICE_COUNT] = [
     Service::new("exo_shield", service_table::EXO_SHIELD_BIN),
 ];
 
+// Manifeste headless : pas de pile graphique ni clavier PS/2 ; stockage et
+// réseau passent avant la console pour qu'un invité VM soit joignable au plus
+// tôt, chaque vague lançant ses services dans l'ordre de ce tableau.
+#[cfg(feature = "headless")]
+static SERVICES: [Service; service_table::SERVICE_COUNT] = [
+    Service::new("ipc_router", service_table::IPC_ROUTER_BIN),
+    Service::new("memory_server", service_table::MEMORY_SERVER_BIN),
+    Service::new("vfs_server", service_table::VFS_SERVER_BIN),
+    Service::new("device_server", service_table::DEVICE_SERVER_BIN),
+    Service::new("virtio_drivers", service_table::VIRTIO_DRIVERS_BIN),
+    Service::new("e1000_driver", service_table::E1000_DRIVER_BIN),
+    Service::new("virtio_net_driver", service_table::VIRTIO_NET_DRIVER_BIN),
+    Service::new("loopback_driver", service_table::LOOPBACK_DRIVER_BIN),
+    Service::new("network_server", service_table::NETWORK_SERVER_BIN),
+    Service::new("tty_server", service_table::TTY_SERVER_BIN),
+    Service::new("crypto_server", service_table::CRYPTO_SERVER_BIN),
+    Service::new("scheduler_server", service_table::SCHEDULER_SERVER_BIN),
+    Service::new("exo_shield", service_table::EXO_SHIELD_BIN),
+    Service::new("exosh", service_table::EXOSH_BIN),
+];
+
 #[inline(always)]
 fn halt_forever() -> ! {
     loop {
@@ -206,7 +235,13 @@ unsafe fn reap_children(service_watchdog: &mut ServiceWatchdog) {
         // Trouver quel service a crashé et le marquer mort
         let dead_pid = pid as u32;
         if let Some(idx) = supervisor::note_child_exit(&SERVICES, dead_pid) {
+            // Pendant l'arrêt, les sorties sont les réponses au SIGTERM.
+            if !SERVICES[idx].is_disabled() && !sigchld_handler::shutdown_requested() {
+                events::record(Kind::Crash, SERVICES[idx].name, wstatus as u64);
+            }
             service_watchdog.observe_stop(idx);
+        } else {
+            cron::note_exit(dead_pid, wstatus);
         }
     }
 }
@@ -352,6 +387,7 @@ fn handle_control_plane(service_watchdog: &mut ServiceWatchdog) {
                         if rc < 0 {
                             protocol::InitReply::error(rc)
                         } else {
+                            events::record(Kind::Restart, SERVICES[idx].name, rc as u64);
                             protocol::lifecycle_reply(
                                 rc as u32,
                                 supervisor::running_mask(&SERVICES),
@@ -363,11 +399,12 @@ fn handle_control_plane(service_watchdog: &mut ServiceWatchdog) {
             }
             protocol::INIT_MSG_CHILD_DIED => match protocol::read_u32(&request.payload, 0) {
                 Ok(pid) => {
-                    let _ = protocol::read_i32(&request.payload, 4);
+                    let status = protocol::read_i32(&request.payload, 4).unwrap_or(0);
                     if let Some(idx) = supervisor::note_child_exit(&SERVICES, pid) {
                         if SERVICES[idx].is_disabled() {
                             SERVICES[idx].disable();
                         } else {
+                            events::record(Kind::Crash, SERVICES[idx].name, status as u32 as u64);
                             service_watchdog.observe_stop(idx);
                         }
                     }
@@ -376,6 +413,15 @@ fn handle_control_plane(service_watchdog: &mut ServiceWatchdog) {
                 Err(err) => protocol::InitReply::error(err),
             },
             protocol::INIT_MSG_PREPARE_ISOLATION => isolation::prepare_isolation_reply(&SERVICES),
+            protocol::INIT_MSG_DATA_SAVER => {
+                if protocol::sender_owns(request.sender_pid, b"data_saver") {
+                    if let Some(state) = metered::State::decode(&request.payload) {
+                        cron::set_data_saver(state.data_saver());
+                    }
+                }
+                // Notification : pas de réponse.
+                return;
+            }
             _ => protocol::InitReply::error(syscall::EINVAL),
         },
     };
@@ -401,6 +447,8 @@ pub extern "C" fn _start(boot_info_virt: usize) -> ! {
 
     let mut service_watchdog = ServiceWatchdog::new();
 
+    netboot::announce();
+
     log::line(b"init_server: registering control endpoint");
     protocol::register_endpoint();
     unsafe {
@@ -418,9 +466,11 @@ pub extern "C" fn _start(boot_info_virt: usize) -> ! {
         exo_syscall_abi::syscall3(exo_syscall_abi::SYS_CLOCK_GETTIME, 1, 0, 0) as u64 / 1_000_000
     };
     const GLOBAL_BOOT_TIMEOUT_MS: u64 = 45_000; // 45s max pour tous les services
+    preload::start();
     let _ = unsafe { boot_sequence::boot_services(&SERVICES) };
     let boot_elapsed = unsafe {
-        let now = exo_syscall_abi::syscall3(exo_syscall_abi::SYS_CLOCK_GETTIME, 1, 0, 0) as u64 / 1_000_000;
+        let now = exo_syscall_abi::syscall3(exo_syscall_abi::SYS_CLOCK_GETTIME, 1, 0, 0) as u64
+            / 1_000_000;
         now.saturating_sub(boot_global_start_ms)
     };
     if boot_elapsed > GLOBAL_BOOT_TIMEOUT_MS {
@@ -434,12 +484,16 @@ pub extern "C" fn _start(boot_info_virt: usize) -> ! {
         }
         idx += 1;
     }
+    // Shell jamais lancé (dépendances en échec) : la console revient quand même.
+    splash::finish();
     log::line(b"init_server: service graph booted");
+    cron::load();
 
     // ── 3. Boucle de supervision ──────────────────────────────────────────
     loop {
         // Vérifier l'arrêt demandé
         if sigchld_handler::shutdown_requested() {
+            events::record(Kind::Shutdown, "", 0);
             // Envoyer SIGTERM à tous les enfants
             let mut i = 0usize;
             while i < SERVICES.len() {
@@ -486,7 +540,10 @@ pub extern "C" fn _start(boot_info_virt: usize) -> ! {
                 // Service mort : attendre le délai de backoff avant relance
                 let delay = SERVICES[i].restart_delay_ticks.load(Ordering::Relaxed);
                 if delay == 0 || delay == 1 {
-                    let _ = start_service(i, &mut service_watchdog);
+                    let rc = start_service(i, &mut service_watchdog);
+                    if rc > 0 {
+                        events::record(Kind::Restart, SERVICES[i].name, rc as u64);
+                    }
                 } else {
                     // Décrémenter le compteur de délai
                     SERVICES[i]
@@ -497,6 +554,8 @@ pub extern "C" fn _start(boot_info_virt: usize) -> ! {
             i += 1;
         }
 
+        cron::tick();
+        preload::tick();
         handle_control_plane(&mut service_watchdog);
     }
 
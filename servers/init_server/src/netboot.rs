@@ -0,0 +1,39 @@
+//! Boot réseau signalé par exo-boot (`SYS_NETBOOT_INFO`).
+//!
+//! Chargé par PXE ou UEFI HTTP boot, seul le kernel vient du réseau : la
+//! racine reste le volume ExoFS local monté par le noyau (pas de racine
+//! réseau). Init trace le serveur de boot au démarrage.
+
+use crate::{log, syscall};
+
+/// IPv4 du serveur de boot (`[0; 4]` si inconnue), `None` en boot disque.
+pub fn server() -> Option<[u8; 4]> {
+    // SAFETY: syscall sans argument ni pointeur.
+    let ret = unsafe { syscall::syscall0(syscall::SYS_NETBOOT_INFO) };
+    (ret >= 0).then(|| (ret as u32).to_be_bytes())
+}
+
+pub fn announce() {
+    let Some(addr) = server() else {
+        return;
+    };
+    let mut buf = [0u8; 64];
+    let mut len = 0usize;
+    for &part in b"init_server: network boot, server ".iter() {
+        buf[len] = part;
+        len += 1;
+    }
+    for (i, octet) in addr.iter().enumerate() {
+        if i != 0 {
+            buf[len] = b'.';
+            len += 1;
+        }
+        let digits = [octet / 100, octet / 10 % 10, octet % 10];
+        let skip = usize::from(*octet < 100) + usize::from(*octet < 10);
+        for digit in &digits[skip..] {
+            buf[len] = b'0' + digit;
+            len += 1;
+        }
+    }
+    log::line(&buf[..len]);
+}
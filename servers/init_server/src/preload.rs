@@ -0,0 +1,201 @@
+//! Préchargement des fichiers du boot.
+//!
+//! Au démarrage, init ouvre une fenêtre de trace noyau (`exo_preload`) et
+//! lance un fils qui précharge la liste `/var/lib/exo-preload/boot.list`
+//! en priorité d'E/S basse, pendant que le graphe de services démarre. La
+//! fenêtre couvre aussi les premiers lancements d'applications.
+//!
+//! À la fermeture de la fenêtre, la trace est fusionnée dans la liste pour
+//! le boot suivant (`exo_services::preload::merge`) et le bilan (hits,
+//! misses, préchargements inutiles) part au journal noyau.
+
+use crate::{log, syscall};
+use exo_services::preload;
+use spin::Mutex;
+
+const LIST_DIR: &[u8] = b"/var/lib/exo-preload\0";
+const LIST_PATH: &[u8] = b"/var/lib/exo-preload/boot.list\0";
+
+/// Durée de la fenêtre de trace, comptée depuis `start()`.
+const TRACE_WINDOW_MS: u64 = 45_000;
+const LIST_MAX: usize = 64 * 1024;
+/// Même borne que la trace noyau.
+const TRACE_MAX: usize = 64 * 1024;
+const PATH_MAX: usize = 256;
+
+/// Priorité du fils de préchargement : best-effort, niveau le plus bas. Les
+/// lectures des services passent devant.
+const FETCH_IOPRIO: u64 = (syscall::IOPRIO_CLASS_BE << syscall::IOPRIO_CLASS_SHIFT) | 7;
+
+struct Preload {
+    /// Début de la fenêtre (ms monotones), `0` : fenêtre fermée.
+    started_ms: u64,
+    list: [u8; LIST_MAX],
+    list_len: usize,
+    trace: [u8; TRACE_MAX],
+    out: [u8; LIST_MAX],
+}
+
+static PRELOAD: Mutex<Preload> = Mutex::new(Preload {
+    started_ms: 0,
+    list: [0; LIST_MAX],
+    list_len: 0,
+    trace: [0; TRACE_MAX],
+    out: [0; LIST_MAX],
+});
+
+fn exo_preload(op: u64, a2: u64, a3: u64, a4: u64) -> i64 {
+    unsafe { syscall::syscall4(syscall::SYS_EXO_PRELOAD, op, a2, a3, a4) }
+}
+
+/// Ouvre la fenêtre de trace et lance le préchargement. Appelé avant le
+/// démarrage des services : les premiers `open()` sont déjà tracés.
+pub fn start() {
+    if exo_preload(syscall::EXO_PRELOAD_START, 0, 0, 0) < 0 {
+        log::line(b"init: preload disabled (no kernel trace)");
+        return;
+    }
+    let mut state = PRELOAD.lock();
+    state.started_ms = crate::monotonic_ms().max(1);
+
+    let Some(len) = crate::cron::read_file(LIST_PATH, &mut state.list) else {
+        log::line(b"init: preload list empty, tracing this boot");
+        return;
+    };
+    state.list_len = len;
+    let Ok(list) = core::str::from_utf8(&state.list[..len]) else {
+        log::line(b"init: preload list is not UTF-8, rebuilt from this boot");
+        return;
+    };
+    // Le fils a sa propre copie de la liste ; init continue sans attendre.
+    let pid = unsafe { syscall::syscall0(syscall::SYS_FORK) };
+    if pid < 0 {
+        log::journal(b"preload: ", b"fork", b" rc=", pid);
+        return;
+    }
+    if pid == 0 {
+        unsafe { fetch_all(list) }
+    }
+    log::journal(
+        b"preload: ",
+        b"boot",
+        b" entries=",
+        preload::entries(list).count() as i64,
+    );
+}
+
+/// Corps du fils : précharge chaque entrée puis sort. Un fichier disparu
+/// depuis la dernière trace est simplement ignoré.
+unsafe fn fetch_all(list: &str) -> ! {
+    let _ = syscall::syscall3(
+        syscall::SYS_IOPRIO_SET,
+        syscall::IOPRIO_WHO_PROCESS,
+        0,
+        FETCH_IOPRIO,
+    );
+    let mut path = [0u8; PATH_MAX];
+    for entry in preload::entries(list) {
+        let bytes = entry.path.as_bytes();
+        if bytes.len() >= PATH_MAX {
+            continue;
+        }
+        path[..bytes.len()].copy_from_slice(bytes);
+        path[bytes.len()] = 0;
+        let _ = exo_preload(syscall::EXO_PRELOAD_FETCH, path.as_ptr() as u64, 0, 0);
+    }
+    let _ = syscall::syscall1(syscall::SYS_EXIT, 0);
+    let _ = syscall::syscall1(syscall::SYS_EXIT_GROUP, 0);
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
+/// Ferme la fenêtre une fois écoulée et enregistre la liste du boot
+/// suivant. Appelé à chaque tour de la boucle de supervision.
+pub fn tick() {
+    let mut state = PRELOAD.lock();
+    if state.started_ms == 0
+        || crate::monotonic_ms().saturating_sub(state.started_ms) < TRACE_WINDOW_MS
+    {
+        return;
+    }
+    state.started_ms = 0;
+    let _ = exo_preload(syscall::EXO_PRELOAD_STOP, 0, 0, 0);
+    report();
+
+    let state = &mut *state;
+    let mut trace_len = 0usize;
+    while trace_len < TRACE_MAX {
+        let n = exo_preload(
+            syscall::EXO_PRELOAD_TRACE,
+            trace_len as u64,
+            state.trace[trace_len..].as_mut_ptr() as u64,
+            (TRACE_MAX - trace_len) as u64,
+        );
+        if n <= 0 {
+            break;
+        }
+        trace_len += n as usize;
+    }
+    let trace = core::str::from_utf8(&state.trace[..trace_len]).unwrap_or("");
+    let old = core::str::from_utf8(&state.list[..state.list_len]).unwrap_or("");
+    let len = preload::merge(old, trace, &mut state.out);
+    save_list(&state.out[..len]);
+}
+
+fn report() {
+    let mut stats = syscall::ExoPreloadStats::default();
+    let rc = exo_preload(
+        syscall::EXO_PRELOAD_STATS,
+        &mut stats as *mut syscall::ExoPreloadStats as u64,
+        core::mem::size_of::<syscall::ExoPreloadStats>() as u64,
+        0,
+    );
+    if rc < 0 {
+        return;
+    }
+    let rate = preload::hit_rate_permille(stats.hits, stats.misses);
+    log::journal(b"preload: ", b"boot", b" hits=", stats.hits as i64);
+    log::journal(b"preload: ", b"boot", b" misses=", stats.misses as i64);
+    log::journal(b"preload: ", b"boot", b" hit_rate_permille=", rate as i64);
+    log::journal(
+        b"preload: ",
+        b"boot",
+        b" prefetched=",
+        stats.prefetched as i64,
+    );
+    log::journal(b"preload: ", b"boot", b" unused=", stats.unused as i64);
+    log::journal(b"preload: ", b"boot", b" traced=", stats.traced as i64);
+}
+
+fn save_list(list: &[u8]) {
+    let _ = unsafe { syscall::syscall2(syscall::SYS_MKDIR, LIST_DIR.as_ptr() as u64, 0o755) };
+    let fd = unsafe {
+        syscall::syscall3(
+            syscall::SYS_OPEN,
+            LIST_PATH.as_ptr() as u64,
+            syscall::O_WRONLY | syscall::O_CREAT | syscall::O_TRUNC,
+            0o644,
+        )
+    };
+    if fd < 0 {
+        log::journal(b"preload: ", b"save", b" rc=", fd);
+        return;
+    }
+    let mut written = 0usize;
+    while written < list.len() {
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_WRITE,
+                fd as u64,
+                list[written..].as_ptr() as u64,
+                (list.len() - written) as u64,
+            )
+        };
+        if n <= 0 {
+            break;
+        }
+        written += n as usize;
+    }
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+}
@@ -12,6 +12,9 @@ pub const INIT_MSG_RESTART: u32 = 4;
 pub const INIT_MSG_CHILD_DIED: u32 = 5;
 pub const INIT_MSG_PREPARE_ISOLATION: u32 = 6;
 pub const INIT_MSG_PREPARE_ISOLATION_ACK: u32 = 7;
+/// Notification de `data_saver` (payload : `exo_services::metered::State`),
+/// sans réponse. Ignorée si l'émetteur n'est pas le propriétaire du nom.
+pub const INIT_MSG_DATA_SAVER: u32 = 8;
 
 #[repr(C)]
 pub struct InitRequest {
@@ -68,6 +71,19 @@ pub fn register_endpoint() {
     }
 }
 
+/// `true` si `pid` a enregistré l'endpoint `name`.
+pub fn sender_owns(pid: u32, name: &[u8]) -> bool {
+    let endpoint = unsafe {
+        syscall::syscall3(
+            syscall::SYS_IPC_LOOKUP,
+            name.as_ptr() as u64,
+            name.len() as u64,
+            0,
+        )
+    };
+    endpoint > 0 && (endpoint as u64) >> 32 == pid as u64
+}
+
 pub fn recv_request(request: &mut InitRequest) -> Result<bool, i64> {
     let rc = unsafe {
         syscall::syscall4(
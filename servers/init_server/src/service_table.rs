@@ -1,6 +1,8 @@
 use super::Service;
 
-pub const SERVICE_COUNT: usize = 17;
+/// Manifeste headless (feature `headless`, profil serveur) : ni `input_server`,
+/// ni `fb_server`, ni `ps2_driver`. La console passe par COM1 via `tty_server`.
+pub const SERVICE_COUNT: usize = if cfg!(feature = "headless") { 14 } else { 17 };
 
 pub struct ServiceMetadata {
     pub name: &'static str,
@@ -23,15 +25,22 @@ const DEPS_LOOPBACK: &[&str] = &["ipc_router"];
 const DEPS_NETWORK: &[&str] = &["ipc_router", "vfs_server", "device_server"];
 const OPT_DEPS_NETWORK: &[&str] = &["e1000_driver", "virtio_net_driver", "loopback_driver"];
 const DEPS_SCHEDULER: &[&str] = &["ipc_router", "memory_server"];
+#[cfg(not(feature = "headless"))]
 const DEPS_INPUT: &[&str] = &["ipc_router", "device_server"];
+#[cfg(not(feature = "headless"))]
 const DEPS_FB: &[&str] = &["ipc_router", "device_server"];
+#[cfg(not(feature = "headless"))]
 const DEPS_TTY: &[&str] = &["ipc_router", "input_server", "fb_server", "vfs_server"];
+#[cfg(feature = "headless")]
+const DEPS_TTY: &[&str] = &["ipc_router", "vfs_server"];
+#[cfg(not(feature = "headless"))]
 const DEPS_PS2: &[&str] = &["ipc_router", "device_server", "input_server", "tty_server"];
 // STRATA-SEC-01: exosh DOIT attendre exo_shield (vague 5 -> vague 6).
 // exosh ne peut pas etre interactif avant que la surveillance NGAV soit active.
 const DEPS_EXOSH: &[&str] = &[
     "ipc_router",
     "tty_server",
+    #[cfg(not(feature = "headless"))]
     "ps2_driver",
     "vfs_server",
     "exo_shield", // REQUIS : exosh demarre apres SHIELD_READY (vague 6)
@@ -42,9 +51,12 @@ const DEPS_EXO_SHIELD: &[&str] = &[
     "vfs_server",
     "crypto_server",
     "device_server",
+    #[cfg(not(feature = "headless"))]
     "input_server",
+    #[cfg(not(feature = "headless"))]
     "fb_server",
     "tty_server",
+    #[cfg(not(feature = "headless"))]
     "ps2_driver",
     // "exosh" RETIRE: exo_shield precede exosh (invariant Strata vague 5)
 ];
@@ -61,9 +73,12 @@ pub static VIRTIO_NET_DRIVER_BIN: &[u8] = b"/sbin/exo-virtio-net-driver\0";
 pub static LOOPBACK_DRIVER_BIN: &[u8] = b"/sbin/exo-loopback-driver\0";
 pub static NETWORK_SERVER_BIN: &[u8] = b"/sbin/exo-network-server\0";
 pub static SCHEDULER_SERVER_BIN: &[u8] = b"/sbin/exo-scheduler-server\0";
+#[cfg(not(feature = "headless"))]
 pub static INPUT_SERVER_BIN: &[u8] = b"/sbin/exo-input-server\0";
+#[cfg(not(feature = "headless"))]
 pub static FB_SERVER_BIN: &[u8] = b"/sbin/exo-fb-server\0";
 pub static TTY_SERVER_BIN: &[u8] = b"/sbin/exo-tty-server\0";
+#[cfg(not(feature = "headless"))]
 pub static PS2_DRIVER_BIN: &[u8] = b"/sbin/exo-ps2-input\0";
 pub static EXOSH_BIN: &[u8] = b"/bin/exosh\0";
 pub static EXO_SHIELD_BIN: &[u8] = b"/sbin/exo-shield\0";
@@ -101,6 +116,7 @@ pub static CANONICAL_SERVICES: [ServiceMetadata; SERVICE_COUNT] = [
         ready_timeout_ms: 20_000,
         critical: true,
     },
+    #[cfg(not(feature = "headless"))]
     ServiceMetadata {
         name: "input_server",
         bin_path: INPUT_SERVER_BIN,
@@ -109,6 +125,7 @@ pub static CANONICAL_SERVICES: [ServiceMetadata; SERVICE_COUNT] = [
         ready_timeout_ms: 20_000,
         critical: true,
     },
+    #[cfg(not(feature = "headless"))]
     ServiceMetadata {
         name: "fb_server",
         bin_path: FB_SERVER_BIN,
@@ -125,6 +142,7 @@ pub static CANONICAL_SERVICES: [ServiceMetadata; SERVICE_COUNT] = [
         ready_timeout_ms: 30_000,
         critical: true,
     },
+    #[cfg(not(feature = "headless"))]
     ServiceMetadata {
         name: "ps2_driver",
         bin_path: PS2_DRIVER_BIN,
@@ -0,0 +1,120 @@
+//! Écran de démarrage.
+//!
+//! Dès que `fb_server` est prêt, init lance `boot_splash` et lui envoie un
+//! jalon par service prêt ou abandonné (`exo_services::splash`). Avant de
+//! lancer le shell interactif, il lui demande de rendre la console et attend
+//! sa sortie : le prompt ne tombe pas dans un affichage encore pris.
+//!
+//! Le splash n'est pas supervisé : s'il meurt, le boot continue en mode
+//! texte et `fb_server` rend la console de lui-même.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::{boot_sequence, log, protocol, syscall};
+use exo_services::splash::{Milestone, SPLASH_MSG_PROGRESS, SPLASH_MSG_QUIT};
+
+pub static BOOT_SPLASH_BIN: &[u8] = b"/sbin/exo-boot-splash\0";
+
+/// Attente de la sortie du splash après `SPLASH_MSG_QUIT`.
+const QUIT_TIMEOUT_MS: u64 = 1_000;
+const SIGKILL: u64 = 9;
+
+/// PID du splash, `0` : pas lancé ou déjà retiré.
+static SPLASH_PID: AtomicU32 = AtomicU32::new(0);
+
+/// Lance le splash (une seule fois par boot).
+pub fn start() {
+    if SPLASH_PID.load(Ordering::Acquire) != 0 {
+        return;
+    }
+    let pid = unsafe { boot_sequence::spawn_service("boot_splash", BOOT_SPLASH_BIN) };
+    SPLASH_PID.store(pid, Ordering::Release);
+}
+
+/// Endpoint du splash, s'il est bien enregistré par le processus lancé.
+fn endpoint() -> Option<u64> {
+    let pid = SPLASH_PID.load(Ordering::Acquire);
+    if pid == 0 {
+        return None;
+    }
+    let name = b"boot_splash";
+    let endpoint = unsafe {
+        syscall::syscall3(
+            syscall::SYS_IPC_LOOKUP,
+            name.as_ptr() as u64,
+            name.len() as u64,
+            0,
+        )
+    };
+    (endpoint > 0 && (endpoint as u64) >> 32 == pid as u64).then_some(endpoint as u64)
+}
+
+fn notify(msg_type: u32, payload: &[u8]) -> bool {
+    let Some(endpoint) = endpoint() else {
+        return false;
+    };
+    let mut message = protocol::InitRequest::zeroed();
+    message.msg_type = msg_type;
+    let len = payload.len().min(message.payload.len());
+    message.payload[..len].copy_from_slice(&payload[..len]);
+    let rc = unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            endpoint,
+            &message as *const protocol::InitRequest as u64,
+            core::mem::size_of::<protocol::InitRequest>() as u64,
+            syscall::IPC_FLAG_TIMEOUT,
+            0,
+            0,
+        )
+    };
+    rc >= 0
+}
+
+/// `done` services sur `total` ont abouti, le dernier étant `name`.
+pub fn milestone(name: &str, done: usize, total: usize, failed: bool) {
+    let milestone = Milestone::new(done as u16, total as u16, failed, name.as_bytes());
+    let mut payload = [0u8; Milestone::MAX_LEN];
+    if let Some(len) = milestone.encode(&mut payload) {
+        let _ = notify(SPLASH_MSG_PROGRESS, &payload[..len]);
+    }
+}
+
+/// Rend la console : demande au splash de partir et attend sa sortie,
+/// `SIGKILL` au-delà de `QUIT_TIMEOUT_MS`.
+pub fn finish() {
+    let pid = SPLASH_PID.load(Ordering::Acquire);
+    if pid == 0 {
+        return;
+    }
+    let asked = notify(SPLASH_MSG_QUIT, &[]);
+    SPLASH_PID.store(0, Ordering::Release);
+
+    let start_ms = crate::monotonic_ms();
+    loop {
+        let mut wstatus: u32 = 0;
+        let rc = unsafe {
+            syscall::syscall4(
+                syscall::SYS_WAIT4,
+                pid as u64,
+                &mut wstatus as *mut u32 as u64,
+                syscall::WNOHANG,
+                0,
+            )
+        };
+        // Sorti (rc = pid) ou déjà récolté par la supervision (erreur).
+        if rc != 0 {
+            return;
+        }
+        if !asked || crate::monotonic_ms().saturating_sub(start_ms) >= QUIT_TIMEOUT_MS {
+            break;
+        }
+        unsafe {
+            let _ = syscall::syscall0(syscall::SYS_SCHED_YIELD);
+        }
+    }
+    log::line(b"init: boot_splash did not quit, killed");
+    unsafe {
+        let _ = syscall::syscall2(syscall::SYS_KILL, pid as u64, SIGKILL);
+    }
+}
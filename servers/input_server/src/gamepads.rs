@@ -0,0 +1,84 @@
+//! Gamepad slots: each plugged pad is announced by its driver, which also
+//! receives the rumble requests of applications. Pad events themselves go
+//! through `INPUT_MSG_PUSH` like any other input.
+
+use exo_syscall_abi as syscall;
+
+#[derive(Clone, Copy)]
+struct Slot {
+    /// Driver endpoint, 0 = free slot.
+    driver: u64,
+    /// Process of the driver, the only one allowed to disconnect the pad.
+    pid: u32,
+    vendor: u16,
+    product: u16,
+}
+
+impl Slot {
+    const EMPTY: Self = Self {
+        driver: 0,
+        pid: 0,
+        vendor: 0,
+        product: 0,
+    };
+}
+
+pub struct GamepadTable {
+    slots: [Slot; syscall::INPUT_GAMEPAD_MAX],
+}
+
+impl GamepadTable {
+    pub const fn new() -> Self {
+        Self {
+            slots: [Slot::EMPTY; syscall::INPUT_GAMEPAD_MAX],
+        }
+    }
+
+    /// Returns the slot given to the pad, `None` when all are taken.
+    pub fn connect(&mut self, driver: u64, pid: u32, vendor: u16, product: u16) -> Option<u8> {
+        let slot = self.slots.iter().position(|s| s.driver == 0)?;
+        self.slots[slot] = Slot {
+            driver,
+            pid,
+            vendor,
+            product,
+        };
+        Some(slot as u8)
+    }
+
+    /// Frees `slot` if the pad was connected by process `pid`.
+    pub fn disconnect(&mut self, pid: u32, slot: u8) -> bool {
+        match self.slots.get_mut(slot as usize) {
+            Some(s) if s.driver != 0 && pid != 0 && s.pid == pid => {
+                *s = Slot::EMPTY;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn driver(&self, slot: u8) -> Option<u64> {
+        self.slots
+            .get(slot as usize)
+            .map(|s| s.driver)
+            .filter(|d| *d != 0)
+    }
+
+    pub fn info(&self, slot: u8) -> Option<(u16, u16)> {
+        self.driver(slot)?;
+        let s = self.slots[slot as usize];
+        Some((s.vendor, s.product))
+    }
+
+    /// Frees every slot of a driver that went away; returns them as a mask.
+    pub fn drop_driver(&mut self, driver: u64) -> u32 {
+        let mut freed = 0;
+        for (i, s) in self.slots.iter_mut().enumerate() {
+            if s.driver == driver {
+                *s = Slot::EMPTY;
+                freed |= 1 << i;
+            }
+        }
+        freed
+    }
+}
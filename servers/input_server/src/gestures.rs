@@ -0,0 +1,260 @@
+//! Touch gestures, recognized on the touchscreen contacts at each
+//! `INPUT_TOUCH_FRAME`. A three-finger horizontal swipe switches workspaces:
+//! it belongs to the compositor, so the contacts are cancelled for the
+//! clients and the following touch events are swallowed until every finger
+//! is up. A two-finger pinch is reported next to the contacts, which keep
+//! flowing, for the focused client to zoom.
+//!
+//! Positions are in touchscreen units on both axes, so on a wide screen a
+//! pinch measures horizontal spread a bit more finely than vertical; the
+//! scale only has to grow and shrink with the fingers.
+
+use exo_syscall_abi as syscall;
+
+const RANGE: i32 = syscall::INPUT_TOUCH_RANGE as i32;
+pub const SWIPE_FINGERS: usize = 3;
+pub const PINCH_FINGERS: usize = 2;
+/// Horizontal travel before a swipe starts, and before it switches
+/// workspace when the fingers lift.
+const SWIPE_START: i32 = RANGE / 20;
+const SWIPE_COMMIT: i32 = RANGE / 5;
+/// Change in finger spread before a pinch starts.
+const PINCH_START: i32 = RANGE / 25;
+/// Scale of an unchanged pinch.
+pub const PINCH_UNIT: i32 = 256;
+/// Most events one frame adds: a touch cancel and a gesture event.
+pub const MAX_OUT: usize = 2;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Phase {
+    Idle,
+    /// Three fingers down, not moved far enough yet.
+    SwipeCandidate {
+        start: (i32, i32),
+    },
+    /// Two fingers down, spread not changed enough yet.
+    PinchCandidate {
+        spread: i32,
+    },
+    Swipe {
+        start: i32,
+        travel: i32,
+    },
+    Pinch {
+        spread: i32,
+        scale: i32,
+    },
+    /// A swipe took the contacts; wait for every finger to lift.
+    Consumed,
+}
+
+pub struct Gestures {
+    pos: [(i16, i16); syscall::INPUT_TOUCH_MAX],
+    pub(crate) down: [bool; syscall::INPUT_TOUCH_MAX],
+    phase: Phase,
+}
+
+fn isqrt(v: u64) -> u64 {
+    if v < 2 {
+        return v;
+    }
+    let mut x = v;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + v / x) / 2;
+    }
+    x
+}
+
+fn gesture(code: u16, phase: u8, value: i32, fingers: usize) -> syscall::InputEventWire {
+    syscall::InputEventWire {
+        device: syscall::INPUT_DEVICE_TOUCH,
+        state: phase,
+        code,
+        value: value.clamp(i16::MIN as i32, i16::MAX as i32) as i16,
+        ascii: fingers as u8,
+        ..syscall::InputEventWire::default()
+    }
+}
+
+impl Gestures {
+    pub const fn new() -> Self {
+        Self {
+            pos: [(0, 0); syscall::INPUT_TOUCH_MAX],
+            down: [false; syscall::INPUT_TOUCH_MAX],
+            phase: Phase::Idle,
+        }
+    }
+
+    /// Whether the clients currently see the touch contacts.
+    pub fn clients_own_contacts(&self) -> bool {
+        !matches!(self.phase, Phase::Swipe { .. } | Phase::Consumed)
+    }
+
+    /// Feeds one touch event. Returns whether it still goes to the clients,
+    /// and the number of events written to `out`, to publish after it.
+    pub fn feed(
+        &mut self,
+        event: &syscall::InputEventWire,
+        out: &mut [syscall::InputEventWire; MAX_OUT],
+    ) -> (bool, usize) {
+        let forward = self.clients_own_contacts();
+        let slot = event.ascii as usize;
+        match event.code {
+            syscall::INPUT_TOUCH_X if slot < self.pos.len() => self.pos[slot].0 = event.value,
+            syscall::INPUT_TOUCH_Y if slot < self.pos.len() => self.pos[slot].1 = event.value,
+            syscall::INPUT_TOUCH_CONTACT if slot < self.down.len() => {
+                self.down[slot] = event.value != 0
+            }
+            syscall::INPUT_TOUCH_FRAME => return (forward, self.frame(out)),
+            _ => {}
+        }
+        (forward, 0)
+    }
+
+    fn contacts(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.pos
+            .iter()
+            .zip(&self.down)
+            .filter(|(_, down)| **down)
+            .map(|(p, _)| (p.0 as i32, p.1 as i32))
+    }
+
+    fn centroid(&self) -> (i32, i32) {
+        let (mut x, mut y, mut n) = (0, 0, 0);
+        for (cx, cy) in self.contacts() {
+            x += cx;
+            y += cy;
+            n += 1;
+        }
+        let n = n.max(1);
+        (x / n, y / n)
+    }
+
+    /// Distance between the first two contacts.
+    fn spread(&self) -> i32 {
+        let mut contacts = self.contacts();
+        let (Some(a), Some(b)) = (contacts.next(), contacts.next()) else {
+            return 0;
+        };
+        let (dx, dy) = ((a.0 - b.0) as i64, (a.1 - b.1) as i64);
+        isqrt((dx * dx + dy * dy) as u64) as i32
+    }
+
+    /// Tracking for `count` fingers, from their current position.
+    fn candidate(&self, count: usize) -> Phase {
+        match count {
+            SWIPE_FINGERS => Phase::SwipeCandidate {
+                start: self.centroid(),
+            },
+            PINCH_FINGERS => Phase::PinchCandidate {
+                spread: self.spread().max(1),
+            },
+            _ => Phase::Idle,
+        }
+    }
+
+    fn frame(&mut self, out: &mut [syscall::InputEventWire; MAX_OUT]) -> usize {
+        let count = self.down.iter().filter(|d| **d).count();
+        let mut n = 0;
+        let mut emit = |event| {
+            out[n] = event;
+            n += 1;
+        };
+        self.phase = match self.phase {
+            Phase::Idle => self.candidate(count),
+            Phase::SwipeCandidate { start } if count == SWIPE_FINGERS => {
+                let (x, y) = self.centroid();
+                let (dx, dy) = (x - start.0, y - start.1);
+                if dx.abs() >= SWIPE_START && dx.abs() > dy.abs() {
+                    emit(syscall::InputEventWire {
+                        device: syscall::INPUT_DEVICE_TOUCH,
+                        code: syscall::INPUT_TOUCH_CANCEL,
+                        ..syscall::InputEventWire::default()
+                    });
+                    emit(gesture(
+                        syscall::INPUT_GESTURE_SWIPE,
+                        syscall::INPUT_GESTURE_BEGIN,
+                        dx,
+                        SWIPE_FINGERS,
+                    ));
+                    Phase::Swipe {
+                        start: start.0,
+                        travel: dx,
+                    }
+                } else {
+                    self.phase
+                }
+            }
+            Phase::PinchCandidate { spread } if count == PINCH_FINGERS => {
+                let now = self.spread();
+                if (now - spread).abs() >= PINCH_START {
+                    let scale = now * PINCH_UNIT / spread;
+                    emit(gesture(
+                        syscall::INPUT_GESTURE_PINCH,
+                        syscall::INPUT_GESTURE_BEGIN,
+                        scale,
+                        PINCH_FINGERS,
+                    ));
+                    Phase::Pinch { spread, scale }
+                } else {
+                    self.phase
+                }
+            }
+            Phase::SwipeCandidate { .. } | Phase::PinchCandidate { .. } => self.candidate(count),
+            Phase::Swipe { start, .. } if count == SWIPE_FINGERS => {
+                let travel = self.centroid().0 - start;
+                emit(gesture(
+                    syscall::INPUT_GESTURE_SWIPE,
+                    syscall::INPUT_GESTURE_UPDATE,
+                    travel,
+                    SWIPE_FINGERS,
+                ));
+                Phase::Swipe { start, travel }
+            }
+            Phase::Swipe { travel, .. } => {
+                // A lifting finger moves the centroid: the last full frame
+                // decides.
+                let step = if travel.abs() >= SWIPE_COMMIT {
+                    travel.signum()
+                } else {
+                    0
+                };
+                emit(gesture(
+                    syscall::INPUT_GESTURE_SWIPE,
+                    syscall::INPUT_GESTURE_END,
+                    step,
+                    SWIPE_FINGERS,
+                ));
+                if count == 0 {
+                    Phase::Idle
+                } else {
+                    Phase::Consumed
+                }
+            }
+            Phase::Pinch { spread, .. } if count == PINCH_FINGERS => {
+                let scale = self.spread() * PINCH_UNIT / spread;
+                emit(gesture(
+                    syscall::INPUT_GESTURE_PINCH,
+                    syscall::INPUT_GESTURE_UPDATE,
+                    scale,
+                    PINCH_FINGERS,
+                ));
+                Phase::Pinch { spread, scale }
+            }
+            Phase::Pinch { scale, .. } => {
+                emit(gesture(
+                    syscall::INPUT_GESTURE_PINCH,
+                    syscall::INPUT_GESTURE_END,
+                    scale,
+                    PINCH_FINGERS,
+                ));
+                Phase::Idle
+            }
+            Phase::Consumed if count == 0 => Phase::Idle,
+            Phase::Consumed => Phase::Consumed,
+        };
+        n
+    }
+}
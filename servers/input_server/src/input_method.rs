@@ -0,0 +1,100 @@
+//! Input method routing, after zwp_input_method_v2 / zwp_text_input_v3: one
+//! input method (the on-screen keyboard today, CJK composers later) writes
+//! into the text input of the focused client through the broker.
+//!
+//! The input method may grab the keyboard to compose from hardware keys,
+//! and shows or hides its panel; the panel state is what the shortcut table
+//! and the shortcut owners follow.
+
+use exo_syscall_abi as syscall;
+
+pub struct InputMethod {
+    /// Endpoint of the registered input method, 0 = none.
+    im: u64,
+    /// Endpoint of the enabled text input, 0 = none.
+    text_input: u64,
+    purpose: u16,
+    grab: bool,
+    panel: bool,
+}
+
+impl InputMethod {
+    pub const fn new() -> Self {
+        Self {
+            im: 0,
+            text_input: 0,
+            purpose: syscall::INPUT_PURPOSE_NORMAL,
+            grab: false,
+            panel: false,
+        }
+    }
+
+    pub fn register(&mut self, endpoint: u64) -> Result<(), i64> {
+        if endpoint == 0 {
+            return Err(syscall::EINVAL);
+        }
+        if self.im != 0 && self.im != endpoint {
+            return Err(syscall::EBUSY);
+        }
+        self.im = endpoint;
+        Ok(())
+    }
+
+    /// Forgets the input method with its grab and panel. Returns whether the
+    /// panel was shown.
+    pub fn unregister(&mut self) -> bool {
+        let panel = self.panel;
+        self.im = 0;
+        self.grab = false;
+        self.panel = false;
+        panel
+    }
+
+    pub fn im(&self) -> Option<u64> {
+        (self.im != 0).then_some(self.im)
+    }
+
+    /// Endpoints are `pid << 32 | channel`: requests of the input method are
+    /// recognised by their sender, whatever endpoint they reply to.
+    pub fn is_im(&self, pid: u32) -> bool {
+        self.im != 0 && pid != 0 && self.im >> 32 == pid as u64
+    }
+
+    /// Focus moved to `client`, which replaces any previous text input.
+    pub fn enable(&mut self, client: u64, purpose: u16) {
+        self.text_input = client;
+        self.purpose = purpose;
+    }
+
+    pub fn disable(&mut self, client: u64) -> bool {
+        if client == 0 || client != self.text_input {
+            return false;
+        }
+        self.text_input = 0;
+        true
+    }
+
+    /// The enabled text input and its content purpose.
+    pub fn text_input(&self) -> Option<(u64, u16)> {
+        (self.text_input != 0).then_some((self.text_input, self.purpose))
+    }
+
+    pub fn set_grab(&mut self, grab: bool) {
+        self.grab = grab;
+    }
+
+    pub fn grabs_keys(&self) -> bool {
+        self.im != 0 && self.grab
+    }
+
+    /// Returns whether the panel state changed.
+    pub fn set_panel(&mut self, shown: bool) -> bool {
+        let changed = self.panel != shown;
+        self.panel = shown;
+        changed
+    }
+
+    pub fn panel_shown(&self) -> bool {
+        self.panel
+    }
+}
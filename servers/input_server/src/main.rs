@@ -5,6 +5,15 @@ use core::cell::UnsafeCell;
 use core::panic::PanicInfo;
 use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use exo_syscall_abi as syscall;
+use gamepads::GamepadTable;
+use gestures::Gestures;
+use input_method::InputMethod;
+use shortcuts::{BindError, ShortcutTable, MAX_SHORTCUTS};
+
+mod gamepads;
+mod gestures;
+mod input_method;
+mod shortcuts;
 
 const INPUT_QUEUE_LEN: usize = 128;
 
@@ -59,6 +68,30 @@ struct QueueCell(UnsafeCell<InputQueue>);
 unsafe impl Sync for QueueCell {}
 
 static QUEUE: QueueCell = QueueCell(UnsafeCell::new(InputQueue::new()));
+
+struct ShortcutCell(UnsafeCell<ShortcutTable>);
+
+unsafe impl Sync for ShortcutCell {}
+
+static SHORTCUTS: ShortcutCell = ShortcutCell(UnsafeCell::new(ShortcutTable::new()));
+
+struct GamepadCell(UnsafeCell<GamepadTable>);
+
+unsafe impl Sync for GamepadCell {}
+
+static GAMEPADS: GamepadCell = GamepadCell(UnsafeCell::new(GamepadTable::new()));
+
+struct InputMethodCell(UnsafeCell<InputMethod>);
+
+unsafe impl Sync for InputMethodCell {}
+
+static INPUT_METHOD: InputMethodCell = InputMethodCell(UnsafeCell::new(InputMethod::new()));
+
+struct GesturesCell(UnsafeCell<Gestures>);
+
+unsafe impl Sync for GesturesCell {}
+
+static GESTURES: GesturesCell = GesturesCell(UnsafeCell::new(Gestures::new()));
 static DROPPED: AtomicUsize = AtomicUsize::new(0);
 
 // FIX-INPUT-MULTI (ANALYSE_SERVERS_EXOOS §R4) : l'ancienne implémentation
@@ -176,19 +209,289 @@ fn queue_mut() -> &'static mut InputQueue {
     unsafe { &mut *QUEUE.0.get() }
 }
 
+#[inline]
+fn shortcuts_mut() -> &'static mut ShortcutTable {
+    // SAFETY: same single-threaded event loop as `queue_mut`.
+    unsafe { &mut *SHORTCUTS.0.get() }
+}
+
+#[inline]
+fn gamepads_mut() -> &'static mut GamepadTable {
+    // SAFETY: same single-threaded event loop as `queue_mut`.
+    unsafe { &mut *GAMEPADS.0.get() }
+}
+
+#[inline]
+fn input_method_mut() -> &'static mut InputMethod {
+    // SAFETY: same single-threaded event loop as `queue_mut`.
+    unsafe { &mut *INPUT_METHOD.0.get() }
+}
+
+#[inline]
+fn gestures_mut() -> &'static mut Gestures {
+    // SAFETY: same single-threaded event loop as `queue_mut`.
+    unsafe { &mut *GESTURES.0.get() }
+}
+
+/// Hands an event to the subscribers, or queues it for `INPUT_MSG_POLL`.
+fn publish(event: syscall::InputEventWire) -> i64 {
+    if deliver_to_subscriber(event, queue_mut().len as u32) {
+        return 0;
+    }
+    match queue_mut().push(event) {
+        Ok(()) => 0,
+        Err(err) => {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+            err
+        }
+    }
+}
+
+fn publish_gamepad_connection(slot: u8, connected: bool) {
+    let _ = publish(syscall::InputEventWire {
+        device: syscall::INPUT_DEVICE_GAMEPAD,
+        state: 0,
+        code: syscall::INPUT_GAMEPAD_CONNECTED,
+        value: connected as i16,
+        ascii: slot,
+        modifiers: 0,
+        _pad: [0; 4],
+    });
+}
+
+/// Forwards a rumble request to the pad driver. A driver whose endpoint is
+/// gone loses all its pads.
+fn forward_rumble(req: &syscall::InputRequest) -> i64 {
+    let Some(driver) = gamepads_mut().driver(req.event.ascii) else {
+        return syscall::ENODEV;
+    };
+    let rc = unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            driver,
+            req as *const syscall::InputRequest as u64,
+            core::mem::size_of::<syscall::InputRequest>() as u64,
+            0,
+            0,
+            0,
+        )
+    };
+    if rc >= 0 {
+        return 0;
+    }
+    let freed = gamepads_mut().drop_driver(driver);
+    for slot in 0..syscall::INPUT_GAMEPAD_MAX as u8 {
+        if freed & (1 << slot) != 0 {
+            publish_gamepad_connection(slot, false);
+        }
+    }
+    syscall::ENODEV
+}
+
+/// Sends an unsolicited message (`INPUT_STATUS_*`) to `endpoint`.
+fn notify(endpoint: u64, status: i64, event: syscall::InputEventWire) -> bool {
+    let reply = syscall::InputReply {
+        status,
+        event,
+        queue_depth: 0,
+        _pad: [0; 4],
+    };
+    let rc = unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            endpoint,
+            &reply as *const syscall::InputReply as u64,
+            core::mem::size_of::<syscall::InputReply>() as u64,
+            0,
+            0,
+            0,
+        )
+    };
+    rc >= 0
+}
+
+/// Sends a grabbed key press to the shortcut owner. An owner whose endpoint
+/// is gone loses all its bindings.
+fn dispatch_shortcut(owner: u64, action: i16, mut event: syscall::InputEventWire) {
+    event.value = action;
+    if !notify(owner, syscall::INPUT_STATUS_SHORTCUT, event) {
+        shortcuts_mut().drop_owner(owner);
+    }
+}
+
+/// Tells every shortcut owner whether the on-screen keyboard is shown.
+fn announce_panel(shown: bool) {
+    let mut owners = [0u64; MAX_SHORTCUTS];
+    let count = shortcuts_mut().owners(&mut owners);
+    let event = syscall::InputEventWire {
+        value: shown as i16,
+        ..syscall::InputEventWire::default()
+    };
+    for &owner in &owners[..count] {
+        if !notify(owner, syscall::INPUT_STATUS_OSK, event) {
+            shortcuts_mut().drop_owner(owner);
+        }
+    }
+}
+
+/// Sends a message to the input method. An input method whose endpoint is
+/// gone is unregistered.
+fn notify_im(status: i64, event: syscall::InputEventWire) {
+    let Some(im) = input_method_mut().im() else {
+        return;
+    };
+    if !notify(im, status, event) && input_method_mut().unregister() {
+        announce_panel(false);
+    }
+}
+
+fn im_op(op: u8, code: u16) -> syscall::InputEventWire {
+    syscall::InputEventWire {
+        state: op,
+        code,
+        ..syscall::InputEventWire::default()
+    }
+}
+
+/// Touch events go through the gesture recognizer, which may take the
+/// contacts from the clients and adds the gesture events after them.
+fn route_touch(event: syscall::InputEventWire) -> i64 {
+    let mut gestures = [syscall::InputEventWire::default(); gestures::MAX_OUT];
+    let (forward, count) = gestures_mut().feed(&event, &mut gestures);
+    let status = if forward { publish(event) } else { 0 };
+    for gesture in &gestures[..count] {
+        let _ = publish(*gesture);
+    }
+    status
+}
+
+/// Routes an event pushed by a driver or by the virtual keyboard: shortcuts
+/// first, then the input method grab (hardware keys only), then the
+/// subscribers. Touch events go to the gesture recognizer.
+fn route(event: syscall::InputEventWire, virtual_key: bool) -> i64 {
+    if event.device == syscall::INPUT_DEVICE_TOUCH {
+        return route_touch(event);
+    }
+    let keyboard = event.device == syscall::INPUT_DEVICE_KEYBOARD;
+    let panel = input_method_mut().panel_shown();
+    if keyboard && !virtual_key && panel && event.state == syscall::INPUT_KEY_PRESSED {
+        // Typing on a hardware keyboard: the panel is in the way.
+        notify_im(
+            syscall::INPUT_STATUS_IM,
+            im_op(syscall::INPUT_IM_HIDE_PANEL, 0),
+        );
+    }
+    if let Some(binding) = shortcuts_mut().grab(&event, virtual_key && panel) {
+        if binding.owner != 0 {
+            dispatch_shortcut(binding.owner, binding.action, event);
+        }
+        return 0;
+    }
+    if keyboard && !virtual_key && input_method_mut().grabs_keys() {
+        notify_im(syscall::INPUT_STATUS_IM_KEY, event);
+        return 0;
+    }
+    publish(event)
+}
+
+/// `INPUT_MSG_IM_SEND`: text ops are forwarded to the text input, the
+/// others change the input method state.
+fn input_method_send(event: syscall::InputEventWire) -> i64 {
+    match event.state {
+        syscall::INPUT_IM_COMMIT
+        | syscall::INPUT_IM_PREEDIT
+        | syscall::INPUT_IM_DELETE_SURROUNDING => {
+            let Some((client, _)) = input_method_mut().text_input() else {
+                return syscall::ENOENT;
+            };
+            if notify(client, syscall::INPUT_STATUS_TEXT, event) {
+                0
+            } else {
+                input_method_mut().disable(client);
+                syscall::ENOENT
+            }
+        }
+        syscall::INPUT_IM_GRAB | syscall::INPUT_IM_UNGRAB => {
+            input_method_mut().set_grab(event.state == syscall::INPUT_IM_GRAB);
+            0
+        }
+        syscall::INPUT_IM_PANEL => {
+            let shown = event.value != 0;
+            if input_method_mut().set_panel(shown) {
+                announce_panel(shown);
+            }
+            0
+        }
+        _ => syscall::EINVAL,
+    }
+}
+
+/// `reply_endpoint` is client-supplied, `sender_pid` is stamped by the
+/// kernel: an endpoint named as owner must carry the sender's PID in its high
+/// half, so that no client binds or releases things on behalf of another.
+fn sender_owns_endpoint(req: &syscall::InputRequest) -> bool {
+    req.sender_pid != 0 && req.reply_endpoint >> 32 == req.sender_pid as u64
+}
+
+fn input_method_register(req: &syscall::InputRequest) -> i64 {
+    if req.event.value == 0 {
+        if !input_method_mut().is_im(req.sender_pid) {
+            return syscall::ENOENT;
+        }
+        if input_method_mut().unregister() {
+            announce_panel(false);
+        }
+        return 0;
+    }
+    if !sender_owns_endpoint(req) {
+        return syscall::EINVAL;
+    }
+    if let Err(err) = input_method_mut().register(req.reply_endpoint) {
+        return err;
+    }
+    // Text input already focused: the new input method starts right away.
+    if let Some((_, purpose)) = input_method_mut().text_input() {
+        notify_im(
+            syscall::INPUT_STATUS_IM,
+            im_op(syscall::INPUT_IM_ACTIVATE, purpose),
+        );
+    }
+    0
+}
+
+fn text_input(req: &syscall::InputRequest) -> i64 {
+    if req.reply_endpoint == 0 {
+        return syscall::EINVAL;
+    }
+    if req.event.value != 0 {
+        input_method_mut().enable(req.reply_endpoint, req.event.code);
+        notify_im(
+            syscall::INPUT_STATUS_IM,
+            im_op(syscall::INPUT_IM_ACTIVATE, req.event.code),
+        );
+        return 0;
+    }
+    if !input_method_mut().disable(req.reply_endpoint) {
+        return syscall::ENOENT;
+    }
+    notify_im(
+        syscall::INPUT_STATUS_IM,
+        im_op(syscall::INPUT_IM_DEACTIVATE, 0),
+    );
+    0
+}
+
 fn handle(req: &syscall::InputRequest) -> syscall::InputReply {
     match req.msg_type {
         syscall::INPUT_MSG_PUSH => {
-            let status = if deliver_to_subscriber(req.event, queue_mut().len as u32) {
-                0
+            let status = if (req.event.device == syscall::INPUT_DEVICE_GAMEPAD
+                && gamepads_mut().driver(req.event.ascii).is_none())
+                || (req.event.device == syscall::INPUT_DEVICE_TOUCH
+                    && req.event.ascii as usize >= syscall::INPUT_TOUCH_MAX)
+            {
+                syscall::EINVAL
             } else {
-                match queue_mut().push(req.event) {
-                    Ok(()) => 0,
-                    Err(err) => {
-                        DROPPED.fetch_add(1, Ordering::Relaxed);
-                        err
-                    }
-                }
+                route(req.event, false)
             };
             syscall::InputReply {
                 status,
@@ -232,6 +535,150 @@ fn handle(req: &syscall::InputRequest) -> syscall::InputReply {
                 _pad: [0; 4],
             }
         }
+        syscall::INPUT_MSG_BIND_SHORTCUT => {
+            let mut event = syscall::InputEventWire::default();
+            let status = if !sender_owns_endpoint(req) {
+                syscall::EPERM
+            } else {
+                match shortcuts_mut().bind(
+                    req.reply_endpoint,
+                    req.event.value,
+                    req.event.code,
+                    req.event.modifiers,
+                ) {
+                    Ok(()) => 0,
+                    Err(BindError::Invalid) => syscall::EINVAL,
+                    Err(BindError::Full) => syscall::ENOSPC,
+                    Err(BindError::Conflict(holder)) => {
+                        // Reports the combination and action already holding it.
+                        event.device = syscall::INPUT_DEVICE_KEYBOARD;
+                        event.code = holder.code;
+                        event.modifiers = holder.modifiers;
+                        event.value = holder.action;
+                        syscall::EEXIST
+                    }
+                }
+            };
+            syscall::InputReply {
+                status,
+                event,
+                queue_depth: queue_mut().len as u32,
+                _pad: [0; 4],
+            }
+        }
+        syscall::INPUT_MSG_UNBIND_SHORTCUT => syscall::InputReply {
+            status: if !sender_owns_endpoint(req) {
+                syscall::EPERM
+            } else if shortcuts_mut().unbind(req.reply_endpoint, req.event.value) {
+                0
+            } else {
+                syscall::ENOENT
+            },
+            event: syscall::InputEventWire::default(),
+            queue_depth: queue_mut().len as u32,
+            _pad: [0; 4],
+        },
+        syscall::INPUT_MSG_GAMEPAD_CONNECT => {
+            let slot = if sender_owns_endpoint(req) {
+                gamepads_mut().connect(
+                    req.reply_endpoint,
+                    req.sender_pid,
+                    req.event.code,
+                    req.event.value as u16,
+                )
+            } else {
+                None
+            };
+            if let Some(slot) = slot {
+                publish_gamepad_connection(slot, true);
+            }
+            syscall::InputReply {
+                status: match slot {
+                    Some(slot) => slot as i64,
+                    None if req.reply_endpoint == 0 => syscall::EINVAL,
+                    None if !sender_owns_endpoint(req) => syscall::EPERM,
+                    None => syscall::ENOSPC,
+                },
+                event: syscall::InputEventWire::default(),
+                queue_depth: queue_mut().len as u32,
+                _pad: [0; 4],
+            }
+        }
+        syscall::INPUT_MSG_GAMEPAD_DISCONNECT => {
+            let gone = gamepads_mut().disconnect(req.sender_pid, req.event.ascii);
+            if gone {
+                publish_gamepad_connection(req.event.ascii, false);
+            }
+            syscall::InputReply {
+                status: if gone { 0 } else { syscall::ENOENT },
+                event: syscall::InputEventWire::default(),
+                queue_depth: queue_mut().len as u32,
+                _pad: [0; 4],
+            }
+        }
+        syscall::INPUT_MSG_GAMEPAD_RUMBLE => syscall::InputReply {
+            status: forward_rumble(req),
+            event: syscall::InputEventWire::default(),
+            queue_depth: queue_mut().len as u32,
+            _pad: [0; 4],
+        },
+        syscall::INPUT_MSG_GAMEPAD_INFO => {
+            let mut event = syscall::InputEventWire::default();
+            let info = gamepads_mut().info(req.event.ascii);
+            if let Some((vendor, product)) = info {
+                event.device = syscall::INPUT_DEVICE_GAMEPAD;
+                event.code = vendor;
+                event.value = product as i16;
+                event.ascii = req.event.ascii;
+            }
+            syscall::InputReply {
+                status: if info.is_some() { 0 } else { syscall::ENODEV },
+                event,
+                queue_depth: queue_mut().len as u32,
+                _pad: [0; 4],
+            }
+        }
+        syscall::INPUT_MSG_IM_REGISTER => syscall::InputReply {
+            status: input_method_register(req),
+            event: syscall::InputEventWire::default(),
+            queue_depth: queue_mut().len as u32,
+            _pad: [0; 4],
+        },
+        syscall::INPUT_MSG_TEXT_INPUT => syscall::InputReply {
+            status: text_input(req),
+            event: syscall::InputEventWire::default(),
+            queue_depth: queue_mut().len as u32,
+            _pad: [0; 4],
+        },
+        syscall::INPUT_MSG_IM_SEND => syscall::InputReply {
+            status: if input_method_mut().is_im(req.sender_pid) {
+                input_method_send(req.event)
+            } else {
+                syscall::EPERM
+            },
+            event: syscall::InputEventWire::default(),
+            queue_depth: queue_mut().len as u32,
+            _pad: [0; 4],
+        },
+        syscall::INPUT_MSG_VIRTUAL_KEY => {
+            let status = if input_method_mut().is_im(req.sender_pid) {
+                route(
+                    syscall::InputEventWire {
+                        device: syscall::INPUT_DEVICE_KEYBOARD,
+                        ..req.event
+                    },
+                    true,
+                )
+            } else {
+                syscall::EPERM
+            };
+            syscall::InputReply {
+                status,
+                event: syscall::InputEventWire::default(),
+                queue_depth: queue_mut().len as u32,
+                _pad: [0; 4],
+            }
+        }
         _ => syscall::InputReply {
             status: syscall::EINVAL,
             event: syscall::InputEventWire::default(),
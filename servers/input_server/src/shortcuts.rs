@@ -0,0 +1,197 @@
+//! Global shortcuts: components bind a key combination to an action id, the
+//! broker grabs matching key presses and sends them to the owner instead of
+//! broadcasting them to the input subscribers.
+//!
+//! Media keys (volume, playback, brightness) are bound without modifiers and
+//! match whatever modifiers are held, so the daemon owning them gets them
+//! whichever application has the focus, or none.
+//!
+//! Keys typed on the on-screen keyboard while its panel is shown only match
+//! media keys: the modifiers latched on the panel are meant for the focused
+//! application, not for global shortcuts.
+
+use exo_syscall_abi as syscall;
+
+pub const MAX_SHORTCUTS: usize = 32;
+const MAX_HELD: usize = 8;
+
+const MOD_MASK: u8 = syscall::INPUT_MOD_SHIFT
+    | syscall::INPUT_MOD_CTRL
+    | syscall::INPUT_MOD_ALT
+    | syscall::INPUT_MOD_META;
+
+#[derive(Clone, Copy)]
+pub struct Binding {
+    pub owner: u64,
+    pub action: i16,
+    pub code: u16,
+    pub modifiers: u8,
+}
+
+impl Binding {
+    const EMPTY: Self = Self {
+        owner: 0,
+        action: 0,
+        code: 0,
+        modifiers: 0,
+    };
+
+    fn in_use(&self) -> bool {
+        self.owner != 0
+    }
+}
+
+pub enum BindError {
+    Invalid,
+    /// Combination already held by another binding.
+    Conflict(Binding),
+    Full,
+}
+
+/// HID modifier keys (0xe0..=0xe7) report their own bit while pressed; it is
+/// masked out so that `Super` alone is bound as (LeftMeta, no modifiers).
+fn own_modifier(code: u16) -> u8 {
+    match code {
+        0x00e0..=0x00e7 => match code & 3 {
+            0 => syscall::INPUT_MOD_CTRL,
+            1 => syscall::INPUT_MOD_SHIFT,
+            2 => syscall::INPUT_MOD_ALT,
+            _ => syscall::INPUT_MOD_META,
+        },
+        _ => 0,
+    }
+}
+
+fn is_media_key(code: u16) -> bool {
+    matches!(
+        code,
+        syscall::INPUT_KEY_MUTE
+            | syscall::INPUT_KEY_VOLUME_UP
+            | syscall::INPUT_KEY_VOLUME_DOWN
+            | syscall::INPUT_KEY_BRIGHTNESS_UP
+            | syscall::INPUT_KEY_BRIGHTNESS_DOWN
+            | syscall::INPUT_KEY_NEXT_TRACK
+            | syscall::INPUT_KEY_PREV_TRACK
+            | syscall::INPUT_KEY_STOP
+            | syscall::INPUT_KEY_PLAY_PAUSE
+    )
+}
+
+fn effective_modifiers(code: u16, modifiers: u8) -> u8 {
+    if is_media_key(code) {
+        return 0;
+    }
+    modifiers & MOD_MASK & !own_modifier(code)
+}
+
+pub struct ShortcutTable {
+    bindings: [Binding; MAX_SHORTCUTS],
+    /// Codes whose press was grabbed; their release is swallowed too.
+    held: [u16; MAX_HELD],
+}
+
+impl ShortcutTable {
+    pub const fn new() -> Self {
+        Self {
+            bindings: [Binding::EMPTY; MAX_SHORTCUTS],
+            held: [0; MAX_HELD],
+        }
+    }
+
+    /// Binds `(code, modifiers)` to `(owner, action)`. Rebinding an existing
+    /// action of the same owner moves it, which is how user edits are applied.
+    pub fn bind(
+        &mut self,
+        owner: u64,
+        action: i16,
+        code: u16,
+        modifiers: u8,
+    ) -> Result<(), BindError> {
+        if owner == 0 || code == 0 {
+            return Err(BindError::Invalid);
+        }
+        let modifiers = effective_modifiers(code, modifiers);
+        if let Some(other) = self.bindings.iter().find(|b| {
+            b.in_use()
+                && b.code == code
+                && b.modifiers == modifiers
+                && (b.owner != owner || b.action != action)
+        }) {
+            return Err(BindError::Conflict(*other));
+        }
+        let slot = self
+            .bindings
+            .iter()
+            .position(|b| b.in_use() && b.owner == owner && b.action == action)
+            .or_else(|| self.bindings.iter().position(|b| !b.in_use()))
+            .ok_or(BindError::Full)?;
+        self.bindings[slot] = Binding {
+            owner,
+            action,
+            code,
+            modifiers,
+        };
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, owner: u64, action: i16) -> bool {
+        match self
+            .bindings
+            .iter_mut()
+            .find(|b| b.in_use() && b.owner == owner && b.action == action)
+        {
+            Some(binding) => {
+                *binding = Binding::EMPTY;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn drop_owner(&mut self, owner: u64) {
+        for binding in self.bindings.iter_mut().filter(|b| b.owner == owner) {
+            *binding = Binding::EMPTY;
+        }
+    }
+
+    /// Distinct owners holding at least one binding, in `out`; returns how
+    /// many.
+    pub fn owners(&self, out: &mut [u64; MAX_SHORTCUTS]) -> usize {
+        let mut count = 0;
+        for binding in self.bindings.iter().filter(|b| b.in_use()) {
+            if !out[..count].contains(&binding.owner) {
+                out[count] = binding.owner;
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Returns the binding a key event must be routed to. `Some` with owner 0
+    /// means the event is the release of a grabbed key and must be dropped.
+    /// `panel_typing`: the key comes from the shown on-screen keyboard.
+    pub fn grab(&mut self, event: &syscall::InputEventWire, panel_typing: bool) -> Option<Binding> {
+        if event.device != syscall::INPUT_DEVICE_KEYBOARD {
+            return None;
+        }
+        if event.state == syscall::INPUT_KEY_RELEASED {
+            let held = self.held.iter_mut().find(|c| **c == event.code)?;
+            *held = 0;
+            return Some(Binding::EMPTY);
+        }
+        if panel_typing && !is_media_key(event.code) {
+            return None;
+        }
+        let modifiers = effective_modifiers(event.code, event.modifiers);
+        let binding = *self
+            .bindings
+            .iter()
+            .find(|b| b.in_use() && b.code == event.code && b.modifiers == modifiers)?;
+        if !self.held.contains(&event.code) {
+            if let Some(free) = self.held.iter_mut().find(|c| **c == 0) {
+                *free = event.code;
+            }
+        }
+        Some(binding)
+    }
+}
@@ -0,0 +1,71 @@
+#[allow(dead_code)]
+#[path = "../src/gamepads.rs"]
+mod gamepads;
+
+use exo_syscall_abi as syscall;
+use gamepads::GamepadTable;
+
+const USB_PID: u32 = 30;
+const USB: u64 = (USB_PID as u64) << 32 | 2;
+const BT_PID: u32 = 31;
+const BT: u64 = (BT_PID as u64) << 32 | 2;
+
+#[test]
+fn pads_take_the_first_free_slot() {
+    let mut pads = GamepadTable::new();
+    assert_eq!(pads.connect(USB, USB_PID, 0x045e, 0x028e), Some(0));
+    assert_eq!(pads.connect(BT, BT_PID, 0x054c, 0x09cc), Some(1));
+    assert_eq!(pads.driver(1), Some(BT));
+    assert_eq!(pads.info(0), Some((0x045e, 0x028e)));
+    assert!(pads.disconnect(USB_PID, 0));
+    assert_eq!(pads.driver(0), None);
+    assert_eq!(pads.info(0), None);
+    assert_eq!(pads.connect(BT, BT_PID, 0x054c, 0x09cc), Some(0));
+}
+
+#[test]
+fn only_the_connecting_process_disconnects() {
+    let mut pads = GamepadTable::new();
+    assert_eq!(pads.connect(USB, USB_PID, 0x045e, 0x028e), Some(0));
+    assert!(!pads.disconnect(BT_PID, 0));
+    assert!(!pads.disconnect(0, 0));
+    assert_eq!(pads.driver(0), Some(USB));
+    assert!(pads.disconnect(USB_PID, 0));
+    // Already gone.
+    assert!(!pads.disconnect(USB_PID, 0));
+}
+
+#[test]
+fn out_of_range_and_empty_slots_are_refused() {
+    let mut pads = GamepadTable::new();
+    let past_end = syscall::INPUT_GAMEPAD_MAX as u8;
+    assert!(!pads.disconnect(USB_PID, past_end));
+    assert_eq!(pads.driver(past_end), None);
+    assert_eq!(pads.info(past_end), None);
+    assert!(!pads.disconnect(USB_PID, 0));
+}
+
+#[test]
+fn a_full_table_refuses_new_pads() {
+    let mut pads = GamepadTable::new();
+    for slot in 0..syscall::INPUT_GAMEPAD_MAX {
+        assert_eq!(
+            pads.connect(USB, USB_PID, 0x045e, slot as u16),
+            Some(slot as u8)
+        );
+    }
+    assert_eq!(pads.connect(BT, BT_PID, 0x054c, 0x09cc), None);
+}
+
+#[test]
+fn a_dead_driver_loses_all_its_pads() {
+    let mut pads = GamepadTable::new();
+    assert_eq!(pads.connect(USB, USB_PID, 0x045e, 0x028e), Some(0));
+    assert_eq!(pads.connect(BT, BT_PID, 0x054c, 0x09cc), Some(1));
+    assert_eq!(pads.connect(USB, USB_PID, 0x045e, 0x02ea), Some(2));
+    assert_eq!(pads.drop_driver(USB), 0b101);
+    assert_eq!(pads.driver(0), None);
+    assert_eq!(pads.driver(1), Some(BT));
+    assert_eq!(pads.driver(2), None);
+    assert_eq!(pads.drop_driver(USB), 0);
+}
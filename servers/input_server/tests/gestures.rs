@@ -0,0 +1,149 @@
+#[allow(dead_code)]
+#[path = "../src/gestures.rs"]
+mod gestures;
+
+use exo_syscall_abi as syscall;
+use gestures::{Gestures, MAX_OUT};
+
+struct Screen {
+    gestures: Gestures,
+    /// Events the clients saw, in order.
+    clients: Vec<syscall::InputEventWire>,
+}
+
+impl Screen {
+    fn new() -> Self {
+        Self {
+            gestures: Gestures::new(),
+            clients: Vec::new(),
+        }
+    }
+
+    fn send(&mut self, slot: u8, code: u16, value: i16) {
+        let event = syscall::InputEventWire {
+            device: syscall::INPUT_DEVICE_TOUCH,
+            code,
+            value,
+            ascii: slot,
+            ..syscall::InputEventWire::default()
+        };
+        let mut out = [syscall::InputEventWire::default(); MAX_OUT];
+        let (forward, n) = self.gestures.feed(&event, &mut out);
+        if forward {
+            self.clients.push(event);
+        }
+        self.clients.extend_from_slice(&out[..n]);
+    }
+
+    /// One frame placing the contacts of `fingers` (others lifted).
+    fn frame(&mut self, fingers: &[(i16, i16)]) {
+        for slot in 0..syscall::INPUT_TOUCH_MAX {
+            match fingers.get(slot) {
+                Some(&(x, y)) => {
+                    self.send(slot as u8, syscall::INPUT_TOUCH_X, x);
+                    self.send(slot as u8, syscall::INPUT_TOUCH_Y, y);
+                    self.send(slot as u8, syscall::INPUT_TOUCH_CONTACT, 1);
+                }
+                None if self.gestures.down[slot] => {
+                    self.send(slot as u8, syscall::INPUT_TOUCH_CONTACT, 0)
+                }
+                None => {}
+            }
+        }
+        self.send(0, syscall::INPUT_TOUCH_FRAME, 0);
+    }
+
+    fn take(&mut self, code: u16) -> Vec<(u8, i16)> {
+        let seen = self
+            .clients
+            .iter()
+            .filter(|e| e.code == code)
+            .map(|e| (e.state, e.value))
+            .collect();
+        self.clients.clear();
+        seen
+    }
+}
+
+fn three(x: i16) -> [(i16, i16); 3] {
+    [(x, 10_000), (x + 2_000, 10_000), (x + 4_000, 12_000)]
+}
+
+#[test]
+fn three_finger_swipe_takes_the_contacts() {
+    let mut screen = Screen::new();
+    screen.frame(&three(10_000));
+    screen.frame(&three(10_500));
+    assert!(screen.gestures.clients_own_contacts());
+    screen.frame(&three(8_000));
+    // The clients saw the contacts, then lost them to the swipe.
+    assert_eq!(screen.take(syscall::INPUT_TOUCH_CANCEL).len(), 1);
+    screen.frame(&three(2_000));
+    let moved = screen.take(syscall::INPUT_GESTURE_SWIPE);
+    assert_eq!(moved, [(syscall::INPUT_GESTURE_UPDATE, -8_000)]);
+
+    // Lifting one finger ends it; the rest stays hidden from clients.
+    screen.frame(&three(2_000)[..2]);
+    assert!(screen
+        .clients
+        .iter()
+        .all(|e| e.code == syscall::INPUT_GESTURE_SWIPE));
+    assert_eq!(
+        screen.take(syscall::INPUT_GESTURE_SWIPE),
+        [(syscall::INPUT_GESTURE_END, -1)]
+    );
+    screen.frame(&[(2_000, 10_000)]);
+    screen.frame(&[]);
+    assert!(screen.clients.is_empty());
+
+    // Back to normal once every finger is up.
+    screen.frame(&[(5_000, 5_000)]);
+    assert_eq!(screen.take(syscall::INPUT_TOUCH_CONTACT), [(0, 1)]);
+}
+
+#[test]
+fn short_or_vertical_swipes_do_not_switch() {
+    let mut screen = Screen::new();
+    screen.frame(&three(10_000));
+    screen.frame(&three(12_000));
+    screen.frame(&three(13_000));
+    screen.frame(&[]);
+    assert_eq!(
+        screen.take(syscall::INPUT_GESTURE_SWIPE),
+        [
+            (syscall::INPUT_GESTURE_BEGIN, 2_000),
+            (syscall::INPUT_GESTURE_UPDATE, 3_000),
+            (syscall::INPUT_GESTURE_END, 0),
+        ]
+    );
+
+    // Vertical travel leaves the contacts to the clients.
+    let down = |y: i16| [(10_000, y), (12_000, y), (14_000, y)];
+    screen.frame(&down(5_000));
+    screen.frame(&down(15_000));
+    screen.frame(&[]);
+    assert!(screen.take(syscall::INPUT_GESTURE_SWIPE).is_empty());
+}
+
+#[test]
+fn pinch_is_reported_alongside_the_contacts() {
+    let mut screen = Screen::new();
+    let pair = |half: i16| [(16_000 - half, 16_000), (16_000 + half, 16_000)];
+    screen.frame(&pair(2_000));
+    screen.frame(&pair(2_500));
+    screen.frame(&pair(2_700));
+    screen.frame(&pair(4_000));
+    screen.frame(&pair(1_000)[..1]);
+    assert_eq!(
+        screen.take(syscall::INPUT_GESTURE_PINCH),
+        [
+            (syscall::INPUT_GESTURE_BEGIN, 345),
+            (syscall::INPUT_GESTURE_UPDATE, 512),
+            (syscall::INPUT_GESTURE_END, 512),
+        ]
+    );
+    // The client kept receiving the contacts.
+    screen.frame(&pair(2_000));
+    assert!(screen.gestures.clients_own_contacts());
+    assert!(screen.take(syscall::INPUT_TOUCH_CANCEL).is_empty());
+}
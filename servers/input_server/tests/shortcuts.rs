@@ -0,0 +1,186 @@
+#[allow(dead_code)]
+#[path = "../src/shortcuts.rs"]
+mod shortcuts;
+
+use exo_syscall_abi as syscall;
+use shortcuts::{BindError, ShortcutTable, MAX_SHORTCUTS};
+
+const SETTINGS: u64 = 40 << 32 | 1;
+const LAUNCHER: u64 = 41 << 32 | 1;
+const KEY_T: u16 = 0x0017;
+
+fn key(code: u16, modifiers: u8, state: u8) -> syscall::InputEventWire {
+    syscall::InputEventWire {
+        device: syscall::INPUT_DEVICE_KEYBOARD,
+        state,
+        code,
+        modifiers,
+        ..syscall::InputEventWire::default()
+    }
+}
+
+fn press(table: &mut ShortcutTable, code: u16, modifiers: u8) -> Option<(u64, i16)> {
+    table
+        .grab(&key(code, modifiers, syscall::INPUT_KEY_PRESSED), false)
+        .map(|b| (b.owner, b.action))
+}
+
+#[test]
+fn a_held_combination_reports_its_holder() {
+    let mut table = ShortcutTable::new();
+    assert!(table
+        .bind(SETTINGS, 3, KEY_T, syscall::INPUT_MOD_CTRL)
+        .is_ok());
+    match table.bind(LAUNCHER, 7, KEY_T, syscall::INPUT_MOD_CTRL) {
+        Err(BindError::Conflict(holder)) => {
+            assert_eq!(holder.owner, SETTINGS);
+            assert_eq!(holder.action, 3);
+        }
+        _ => panic!("conflicting bind accepted"),
+    }
+    // Same key with another modifier set is a different combination.
+    assert!(table
+        .bind(
+            LAUNCHER,
+            7,
+            KEY_T,
+            syscall::INPUT_MOD_CTRL | syscall::INPUT_MOD_ALT
+        )
+        .is_ok());
+    assert_eq!(
+        press(&mut table, KEY_T, syscall::INPUT_MOD_CTRL),
+        Some((SETTINGS, 3))
+    );
+}
+
+#[test]
+fn rebinding_an_action_moves_it() {
+    let mut table = ShortcutTable::new();
+    assert!(table
+        .bind(SETTINGS, 3, KEY_T, syscall::INPUT_MOD_CTRL)
+        .is_ok());
+    assert!(table
+        .bind(SETTINGS, 3, KEY_T, syscall::INPUT_MOD_ALT)
+        .is_ok());
+    assert_eq!(press(&mut table, KEY_T, syscall::INPUT_MOD_CTRL), None);
+    assert_eq!(
+        press(&mut table, KEY_T, syscall::INPUT_MOD_ALT),
+        Some((SETTINGS, 3))
+    );
+    // The old combination is free again.
+    assert!(table
+        .bind(LAUNCHER, 1, KEY_T, syscall::INPUT_MOD_CTRL)
+        .is_ok());
+}
+
+#[test]
+fn only_the_owner_unbinds() {
+    let mut table = ShortcutTable::new();
+    assert!(table
+        .bind(SETTINGS, 3, KEY_T, syscall::INPUT_MOD_CTRL)
+        .is_ok());
+    assert!(!table.unbind(LAUNCHER, 3));
+    assert_eq!(
+        press(&mut table, KEY_T, syscall::INPUT_MOD_CTRL),
+        Some((SETTINGS, 3))
+    );
+    assert!(table.unbind(SETTINGS, 3));
+    assert!(!table.unbind(SETTINGS, 3));
+    assert_eq!(press(&mut table, KEY_T, syscall::INPUT_MOD_CTRL), None);
+}
+
+#[test]
+fn a_full_table_refuses_new_actions_but_moves_existing_ones() {
+    let mut table = ShortcutTable::new();
+    for action in 0..MAX_SHORTCUTS as i16 {
+        assert!(table
+            .bind(
+                SETTINGS,
+                action,
+                0x0004 + action as u16,
+                syscall::INPUT_MOD_META
+            )
+            .is_ok());
+    }
+    assert!(matches!(
+        table.bind(LAUNCHER, 0, KEY_T, syscall::INPUT_MOD_CTRL),
+        Err(BindError::Full)
+    ));
+    assert!(table
+        .bind(SETTINGS, 0, KEY_T, syscall::INPUT_MOD_CTRL)
+        .is_ok());
+    assert!(table.unbind(SETTINGS, 1));
+    assert!(table
+        .bind(LAUNCHER, 0, KEY_T, syscall::INPUT_MOD_ALT)
+        .is_ok());
+}
+
+#[test]
+fn owner_and_key_code_are_required() {
+    let mut table = ShortcutTable::new();
+    assert!(matches!(
+        table.bind(0, 1, KEY_T, 0),
+        Err(BindError::Invalid)
+    ));
+    assert!(matches!(
+        table.bind(SETTINGS, 1, 0, 0),
+        Err(BindError::Invalid)
+    ));
+}
+
+#[test]
+fn media_keys_match_whatever_modifiers_are_held() {
+    let mut table = ShortcutTable::new();
+    assert!(table
+        .bind(
+            SETTINGS,
+            1,
+            syscall::INPUT_KEY_MUTE,
+            syscall::INPUT_MOD_SHIFT
+        )
+        .is_ok());
+    assert_eq!(
+        press(&mut table, syscall::INPUT_KEY_MUTE, syscall::INPUT_MOD_CTRL),
+        Some((SETTINGS, 1))
+    );
+    // Typing on the shown on-screen keyboard still reaches media keys only.
+    assert!(table
+        .bind(LAUNCHER, 2, KEY_T, syscall::INPUT_MOD_CTRL)
+        .is_ok());
+    let typed = key(KEY_T, syscall::INPUT_MOD_CTRL, syscall::INPUT_KEY_PRESSED);
+    assert!(table.grab(&typed, true).is_none());
+    let mute = key(syscall::INPUT_KEY_MUTE, 0, syscall::INPUT_KEY_PRESSED);
+    assert_eq!(table.grab(&mute, true).map(|b| b.owner), Some(SETTINGS));
+}
+
+#[test]
+fn the_release_of_a_grabbed_key_is_swallowed() {
+    let mut table = ShortcutTable::new();
+    assert!(table
+        .bind(SETTINGS, 3, KEY_T, syscall::INPUT_MOD_CTRL)
+        .is_ok());
+    assert!(press(&mut table, KEY_T, syscall::INPUT_MOD_CTRL).is_some());
+    let release = key(KEY_T, 0, syscall::INPUT_KEY_RELEASED);
+    assert_eq!(table.grab(&release, false).map(|b| b.owner), Some(0));
+    // Released once: the next release is an ordinary event again.
+    assert!(table.grab(&release, false).is_none());
+}
+
+#[test]
+fn dropping_an_owner_frees_its_combinations() {
+    let mut table = ShortcutTable::new();
+    assert!(table
+        .bind(SETTINGS, 3, KEY_T, syscall::INPUT_MOD_CTRL)
+        .is_ok());
+    assert!(table
+        .bind(LAUNCHER, 1, KEY_T, syscall::INPUT_MOD_ALT)
+        .is_ok());
+    let mut owners = [0; MAX_SHORTCUTS];
+    assert_eq!(table.owners(&mut owners), 2);
+    table.drop_owner(SETTINGS);
+    assert_eq!(table.owners(&mut owners), 1);
+    assert_eq!(owners[0], LAUNCHER);
+    assert!(table
+        .bind(LAUNCHER, 2, KEY_T, syscall::INPUT_MOD_CTRL)
+        .is_ok());
+}
@@ -0,0 +1,294 @@
+#![no_std]
+#![no_main]
+
+//! # mem_pressure — politique de pression mémoire de la session
+//!
+//! Surveille le descripteur de pression du noyau (`exo_psi(OPEN)`, lisible
+//! à chaque changement de niveau) et applique l'escalade de
+//! `exo_services::pressure::Policy` :
+//!
+//! - à partir du niveau Medium, demande aux applications abonnées de
+//!   réduire leurs caches (`MEMPRESSURE_NOTIFY_TRIM`) ;
+//! - si la pression reste critique malgré cela, demande une victime à
+//!   l'OOM killer du noyau (`exo_psi(OOM_KILL)`, d'où un lancement root),
+//!   noté dans le journal d'événements ;
+//! - prévient les observateurs (indicateur du panneau) de chaque
+//!   changement de niveau (`MEMPRESSURE_NOTIFY_LEVEL`) ; le niveau courant
+//!   se lit aussi par `MEMPRESSURE_MSG_LEVEL`.
+
+use core::panic::PanicInfo;
+
+use exo_services::events::{self, Kind};
+use exo_services::pressure::{
+    self, Level, Notice, Policy, PressureReport, SubscribeError, Subscribers,
+    MEMPRESSURE_MSG_HEARTBEAT, MEMPRESSURE_MSG_LEVEL, MEMPRESSURE_MSG_STATS,
+    MEMPRESSURE_MSG_SUBSCRIBE, MEMPRESSURE_MSG_UNSUBSCRIBE, MEMPRESSURE_NOTIFY_LEVEL,
+    MEMPRESSURE_NOTIFY_TRIM, SUBSCRIBE_LEVEL, SUBSCRIBE_TRIM,
+};
+use exo_syscall_abi as syscall;
+use spin::Mutex;
+
+mod protocol;
+
+use protocol::{
+    recv_request, register_endpoint, send_notice, send_reply, MemPressureReply, MemPressureRequest,
+};
+
+const REPORT_SIZE: usize = core::mem::size_of::<PressureReport>();
+
+struct PressureService {
+    /// Descripteur de pression ; négatif si `exo_psi(OPEN)` a échoué.
+    fd: i64,
+    report: PressureReport,
+    policy: Policy,
+    subscribers: Subscribers,
+}
+
+static SERVICE: Mutex<PressureService> = Mutex::new(PressureService::new());
+
+impl PressureService {
+    const fn new() -> Self {
+        Self {
+            fd: -1,
+            report: PressureReport {
+                avg10: 0,
+                avg60: 0,
+                avg300: 0,
+                level: 0,
+                total_us: 0,
+                free_pages: 0,
+                total_pages: 0,
+                oom_events: 0,
+                seq: 0,
+            },
+            policy: Policy::new(),
+            subscribers: Subscribers::new(pressure::SUBSCRIBE_ALL),
+        }
+    }
+
+    fn open(&mut self) {
+        // SAFETY: appel sans pointeur ; le noyau installe un descripteur.
+        self.fd = unsafe {
+            syscall::syscall2(
+                syscall::SYS_EXO_PSI,
+                syscall::EXO_PSI_OPEN,
+                syscall::O_NONBLOCK | syscall::O_CLOEXEC,
+            )
+        };
+        self.refresh();
+    }
+
+    /// Lit un événement en attente sur le descripteur ; `false` si rien n'a
+    /// changé depuis la dernière lecture.
+    fn poll_event(&mut self) -> bool {
+        if self.fd < 0 {
+            return false;
+        }
+        let mut report = PressureReport::default();
+        // SAFETY: le noyau écrit au plus `REPORT_SIZE` octets dans `report`.
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_READ,
+                self.fd as u64,
+                &mut report as *mut PressureReport as u64,
+                REPORT_SIZE as u64,
+            )
+        };
+        if n != REPORT_SIZE as i64 {
+            return false;
+        }
+        self.report = report;
+        true
+    }
+
+    /// Instantané courant, sans passer par le descripteur.
+    fn refresh(&mut self) {
+        let mut report = PressureReport::default();
+        // SAFETY: le noyau écrit au plus `REPORT_SIZE` octets dans `report`.
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_EXO_PSI,
+                syscall::EXO_PSI_READ,
+                &mut report as *mut PressureReport as u64,
+                REPORT_SIZE as u64,
+            )
+        };
+        if n == REPORT_SIZE as i64 {
+            self.report = report;
+        }
+    }
+
+    /// Un tour de politique : sur événement, ou à chaque tour tant qu'un
+    /// épisode de pression dure (relances et délai de grâce).
+    fn tick(&mut self) {
+        let event = self.poll_event();
+        let episode = self.policy.level() >= Level::Medium;
+        if !event && !episode {
+            return;
+        }
+        if !event {
+            self.refresh();
+        }
+        let decision = self.policy.observe(&self.report, monotonic_ms());
+        let notice = Notice::from_report(&self.report);
+        if decision.level_changed {
+            self.notify(SUBSCRIBE_LEVEL, MEMPRESSURE_NOTIFY_LEVEL, &notice);
+        }
+        if decision.trim.is_some() {
+            self.notify(SUBSCRIBE_TRIM, MEMPRESSURE_NOTIFY_TRIM, &notice);
+        }
+        if decision.kill {
+            // SAFETY: appel sans pointeur.
+            let rc = unsafe { syscall::syscall1(syscall::SYS_EXO_PSI, syscall::EXO_PSI_OOM_KILL) };
+            if rc == 0 {
+                self.record_oom_kill();
+            }
+        }
+    }
+
+    /// Note la victime dans le journal d'événements (`event_journal`).
+    fn record_oom_kill(&self) {
+        let name = b"event_journal";
+        // SAFETY: nom statique valide pendant l'appel.
+        let journal = unsafe {
+            syscall::syscall2(
+                syscall::SYS_IPC_LOOKUP,
+                name.as_ptr() as u64,
+                name.len() as u64,
+            )
+        };
+        if journal <= 0 {
+            return;
+        }
+        let mut payload = [0u8; events::RECORD_MAX];
+        let free_pages = self.report.free_pages;
+        if let Some(len) = events::encode_record(Kind::OomKill, "", free_pages, &mut payload) {
+            let _ = send_notice(journal as u64, events::EVENTS_MSG_RECORD, &payload[..len]);
+        }
+    }
+
+    /// Notifie les abonnés concernés ; un abonné injoignable est oublié.
+    fn notify(&mut self, flags: u32, msg_type: u32, notice: &Notice) {
+        let mut payload = [0u8; Notice::LEN];
+        let _ = notice.encode(&mut payload);
+        let mut gone = [0u32; pressure::MAX_SUBSCRIBERS];
+        let mut n_gone = 0;
+        for subscriber in self.subscribers.with(flags) {
+            let rc = send_notice(subscriber.endpoint, msg_type, &payload);
+            if rc < 0 && rc != syscall::EAGAIN && rc != syscall::ETIMEDOUT {
+                gone[n_gone] = subscriber.pid;
+                n_gone += 1;
+            }
+        }
+        for &pid in &gone[..n_gone] {
+            self.subscribers.unsubscribe(pid);
+        }
+    }
+
+    fn handle_subscribe(&mut self, sender_pid: u32, payload: &[u8]) -> MemPressureReply {
+        let Some((endpoint, flags)) = pressure::decode_subscribe(payload) else {
+            return MemPressureReply::error(syscall::EINVAL);
+        };
+        match self.subscribers.subscribe(sender_pid, endpoint, flags) {
+            Ok(()) => MemPressureReply::ok(self.policy.level() as u64, 0, 0, 0),
+            Err(SubscribeError::BadFlags) => MemPressureReply::error(syscall::EINVAL),
+            Err(SubscribeError::ForeignEndpoint) => MemPressureReply::error(syscall::EPERM),
+            Err(SubscribeError::Full) => MemPressureReply::error(syscall::ENOSPC),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+fn monotonic_ms() -> u64 {
+    let mut ts = Timespec::default();
+    // SAFETY: le noyau écrit un `timespec` dans `ts`.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_CLOCK_GETTIME,
+            1,
+            &mut ts as *mut Timespec as u64,
+        )
+    };
+    if rc != 0 || ts.tv_sec < 0 {
+        return 0;
+    }
+    (ts.tv_sec as u64)
+        .saturating_mul(1_000)
+        .saturating_add(ts.tv_nsec as u64 / 1_000_000)
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let endpoint = register_endpoint();
+    SERVICE.lock().open();
+    let mut request = MemPressureRequest::zeroed();
+
+    loop {
+        SERVICE.lock().tick();
+        if endpoint == 0 {
+            continue;
+        }
+        match recv_request(endpoint, &mut request) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => continue,
+        }
+
+        let reply = dispatch(&request);
+        let _ = send_reply(request.sender_pid, &reply);
+    }
+}
+
+fn dispatch(request: &MemPressureRequest) -> MemPressureReply {
+    let mut service = SERVICE.lock();
+
+    match request.msg_type {
+        MEMPRESSURE_MSG_HEARTBEAT => MemPressureReply::ok(
+            service.policy.level() as u64,
+            service.subscribers.len() as u64,
+            (service.fd >= 0) as u64,
+            0,
+        ),
+        MEMPRESSURE_MSG_SUBSCRIBE => service.handle_subscribe(request.sender_pid, &request.payload),
+        MEMPRESSURE_MSG_UNSUBSCRIBE => {
+            if service.subscribers.unsubscribe(request.sender_pid) {
+                MemPressureReply::ok(0, 0, 0, 0)
+            } else {
+                MemPressureReply::error(syscall::ENOENT)
+            }
+        }
+        MEMPRESSURE_MSG_LEVEL => {
+            service.refresh();
+            let notice = Notice::from_report(&service.report);
+            MemPressureReply::ok(
+                notice.level as u64,
+                notice.avg10 as u64,
+                notice.free_permille as u64,
+                0,
+            )
+        }
+        MEMPRESSURE_MSG_STATS => MemPressureReply::ok(
+            service.policy.trims,
+            service.policy.kills,
+            service.subscribers.len() as u64,
+            0,
+        ),
+        _ => MemPressureReply::error(syscall::EINVAL),
+    }
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        // SAFETY: panic terminale pour un serveur no_std monothread.
+        unsafe {
+            core::arch::asm!("hlt", options(nostack, nomem));
+        }
+    }
+}
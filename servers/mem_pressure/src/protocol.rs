@@ -0,0 +1,145 @@
+use exo_syscall_abi as syscall;
+
+/// Canal du serveur, dans l'espace d'endpoints de son PID ; les clients le
+/// retrouvent par son nom (`SYS_IPC_LOOKUP "mem_pressure"`).
+pub const MEM_PRESSURE_CHANNEL: u64 = 1;
+/// Court : le même tour de boucle relit le descripteur de pression.
+pub const IPC_RECV_TIMEOUT_MS: u64 = 250;
+
+#[repr(C)]
+pub struct MemPressureRequest {
+    pub sender_pid: u32,
+    pub msg_type: u32,
+    pub payload: [u8; syscall::IPC_INLINE_PAYLOAD_SIZE],
+}
+
+impl MemPressureRequest {
+    pub const fn zeroed() -> Self {
+        Self {
+            sender_pid: 0,
+            msg_type: 0,
+            payload: [0; syscall::IPC_INLINE_PAYLOAD_SIZE],
+        }
+    }
+}
+
+const _: () = assert!(core::mem::size_of::<MemPressureRequest>() == syscall::IPC_ENVELOPE_SIZE);
+const _: () =
+    assert!(core::mem::offset_of!(MemPressureRequest, payload) == syscall::IPC_HEADER_SIZE);
+const _: () = assert!(exo_services::pressure::Notice::LEN <= syscall::IPC_INLINE_PAYLOAD_SIZE);
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct MemPressureReply {
+    pub status: i64,
+    pub handle: u64,
+    pub value0: u64,
+    pub value1: u64,
+    pub flags: u32,
+    pub _pad: [u8; 28],
+}
+
+impl MemPressureReply {
+    pub const fn ok(handle: u64, value0: u64, value1: u64, flags: u32) -> Self {
+        Self {
+            status: 0,
+            handle,
+            value0,
+            value1,
+            flags,
+            _pad: [0; 28],
+        }
+    }
+
+    pub const fn error(status: i64) -> Self {
+        Self {
+            status,
+            handle: 0,
+            value0: 0,
+            value1: 0,
+            flags: 0,
+            _pad: [0; 28],
+        }
+    }
+}
+
+/// Enregistre `mem_pressure` ; retourne l'endpoint, `0` en cas d'échec.
+pub fn register_endpoint() -> u64 {
+    // SAFETY: lecture simple du PID courant.
+    let pid = unsafe { syscall::syscall0(syscall::SYS_GETPID) };
+    if pid <= 0 {
+        return 0;
+    }
+    let endpoint = ((pid as u64) << 32) | MEM_PRESSURE_CHANNEL;
+    let name = b"mem_pressure";
+    // SAFETY: buffer statique valide, endpoint dans l'espace du PID courant.
+    let rc = unsafe {
+        syscall::syscall3(
+            syscall::SYS_IPC_REGISTER,
+            name.as_ptr() as u64,
+            name.len() as u64,
+            endpoint,
+        )
+    };
+    if rc < 0 {
+        0
+    } else {
+        endpoint
+    }
+}
+
+pub fn recv_request(endpoint: u64, request: &mut MemPressureRequest) -> Result<bool, i64> {
+    // SAFETY: le noyau écrit dans `request`, taille bornée à la struct ABI.
+    let rc = unsafe {
+        syscall::syscall4(
+            syscall::SYS_IPC_RECV,
+            endpoint,
+            request as *mut MemPressureRequest as u64,
+            core::mem::size_of::<MemPressureRequest>() as u64,
+            syscall::IPC_FLAG_TIMEOUT | IPC_RECV_TIMEOUT_MS,
+        )
+    };
+
+    if rc == syscall::ETIMEDOUT {
+        return Ok(false);
+    }
+    if rc < 0 {
+        return Err(rc);
+    }
+    Ok(true)
+}
+
+pub fn send_reply(destination_pid: u32, reply: &MemPressureReply) -> i64 {
+    // SAFETY: `reply` est une structure POD locale envoyée telle quelle au noyau.
+    unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            destination_pid as u64,
+            reply as *const MemPressureReply as u64,
+            core::mem::size_of::<MemPressureReply>() as u64,
+            0,
+            0,
+            0,
+        )
+    }
+}
+
+/// Envoie une notification (enveloppe de requête) à un endpoint abonné.
+pub fn send_notice(endpoint: u64, msg_type: u32, payload: &[u8]) -> i64 {
+    let mut message = MemPressureRequest::zeroed();
+    message.msg_type = msg_type;
+    let len = payload.len().min(message.payload.len());
+    message.payload[..len].copy_from_slice(&payload[..len]);
+    // SAFETY: enveloppe POD locale ; le noyau remplace `sender_pid`.
+    unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            endpoint,
+            &message as *const MemPressureRequest as u64,
+            core::mem::size_of::<MemPressureRequest>() as u64,
+            0,
+            0,
+            0,
+        )
+    }
+}
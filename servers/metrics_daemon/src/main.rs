@@ -0,0 +1,533 @@
+#![no_std]
+#![no_main]
+
+//! # metrics_daemon — métriques d'usage locales, envoi anonyme sur accord
+//!
+//! Tient le magasin décrit par `exo_services::metrics` : une ligne par jour
+//! de compteurs déjà exposés par le système, relevés toutes les
+//! `SAMPLE_INTERVAL_MS` :
+//!
+//! - démarrages (dont mal terminés), durée de démarrage, plantages et
+//!   relances de services, victimes de l'OOM killer, recomptés depuis le
+//!   journal d'événements pour la veille et le jour courant ;
+//! - syscalls et messages IPC (`SYS_EXO_PERF_READ`), octets réseau
+//!   (`SYS_EXO_NET_USAGE`) et mémoire installée (`SYS_SYSINFO`), cumulés
+//!   par différence avec le relevé précédent.
+//!
+//! Rien ne part sans `upload on` dans `/etc/exo/telemetry.conf` (premier
+//! démarrage ou `exo-metrics consent on`) et un collecteur configuré : le
+//! rapport hebdomadaire (`Store::report`) part alors en HTTP/1.0 vers le
+//! collecteur, au plus une tentative par jour.
+//!
+//! Sans horloge réglée, aucun relevé n'est daté : les compteurs s'accumulent
+//! jusqu'au premier relevé possible.
+
+use core::panic::PanicInfo;
+
+use exo_services::events::{self, Kind, Record};
+use exo_services::metrics::{
+    self, Config, Metric, Store, METRICS_MSG_HEARTBEAT, METRICS_MSG_SAMPLE, METRICS_MSG_STATUS,
+    REQUEST_MAX, SAMPLE_INTERVAL_MS, STORE_MAX,
+};
+use exo_syscall_abi as syscall;
+use spin::Mutex;
+
+mod protocol;
+
+use protocol::{recv_request, register_endpoint, send_reply, MetricsReply, MetricsRequest};
+
+/// `metrics::STORE_PATH` et `metrics::CONFIG_PATH`, terminés par NUL.
+const STORE_PATH: &[u8] = b"/var/lib/exo/metrics\0";
+const STORE_TMP_PATH: &[u8] = b"/var/lib/exo/metrics.tmp\0";
+const STORE_DIRS: [&[u8]; 3] = [b"/var\0", b"/var/lib\0", b"/var/lib/exo\0"];
+const CONFIG_PATH: &[u8] = b"/etc/exo/telemetry.conf\0";
+const CONFIG_MAX: usize = 512;
+/// Journal tourné puis journal courant (`events::ROTATED_PATH`,
+/// `events::JOURNAL_PATH`).
+const EVENT_JOURNALS: [&[u8]; 2] = [b"/var/log/exo/events.old\0", b"/var/log/exo/events\0"];
+
+/// Premier relevé une minute après le lancement : le journal a alors noté
+/// le démarrage, dont l'identifiant recale les compteurs noyau.
+const FIRST_SAMPLE_DELAY_MS: u64 = 60_000;
+/// Attente de la réponse du collecteur.
+const UPLOAD_REPLY_POLLS: usize = 50;
+const UPLOAD_POLL_MS: u64 = 100;
+
+const CLOCK_REALTIME: u64 = 0;
+const CLOCK_BOOTTIME: u64 = 7;
+const AF_INET: u16 = 2;
+const SOCK_STREAM: u64 = 1;
+
+#[repr(C)]
+#[derive(Default)]
+struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+/// `struct sysinfo` Linux.
+#[repr(C)]
+#[derive(Default)]
+struct LinuxSysInfo {
+    uptime: i64,
+    loads: [u64; 3],
+    totalram: u64,
+    freeram: u64,
+    sharedram: u64,
+    bufferram: u64,
+    totalswap: u64,
+    freeswap: u64,
+    procs: u16,
+    pad: u16,
+    _pad2: u32,
+    totalhigh: u64,
+    freehigh: u64,
+    mem_unit: u32,
+    _pad3: [u8; 8],
+}
+
+struct MetricsDaemon {
+    store: Store,
+    config: Config,
+    /// Relevés depuis le lancement.
+    samples: u64,
+    /// `CLOCK_BOOTTIME` (ms) du prochain relevé.
+    next_sample_ms: u64,
+    /// Jour du dernier envoi échoué : pas de nouvel essai avant demain.
+    upload_failed: u64,
+    buf: [u8; STORE_MAX],
+}
+
+static SERVICE: Mutex<MetricsDaemon> = Mutex::new(MetricsDaemon::new());
+
+impl MetricsDaemon {
+    const fn new() -> Self {
+        Self {
+            store: Store::new(),
+            config: Config {
+                consent: metrics::Consent::Ask,
+                collector: None,
+            },
+            samples: 0,
+            next_sample_ms: 0,
+            upload_failed: 0,
+            buf: [0; STORE_MAX],
+        }
+    }
+
+    fn start(&mut self) {
+        for dir in STORE_DIRS {
+            // SAFETY: chemin statique terminé par NUL.
+            let _ = unsafe { syscall::syscall2(syscall::SYS_MKDIR, dir.as_ptr() as u64, 0o755) };
+        }
+        let len = read_file(STORE_PATH, &mut self.buf);
+        if let Ok(text) = core::str::from_utf8(&self.buf[..len]) {
+            self.store = Store::parse(text);
+        }
+        self.next_sample_ms = clock_ns(CLOCK_BOOTTIME) / 1_000_000 + FIRST_SAMPLE_DELAY_MS;
+    }
+
+    /// Relève si l'échéance est passée.
+    fn tick(&mut self) {
+        if clock_ns(CLOCK_BOOTTIME) / 1_000_000 >= self.next_sample_ms {
+            self.sample();
+        }
+    }
+
+    fn sample(&mut self) {
+        self.next_sample_ms = clock_ns(CLOCK_BOOTTIME) / 1_000_000 + SAMPLE_INTERVAL_MS;
+        let mut config = [0u8; CONFIG_MAX];
+        let len = read_file(CONFIG_PATH, &mut config);
+        self.config = core::str::from_utf8(&config[..len]).map_or(Config::default(), Config::parse);
+
+        let today = metrics::day_of(clock_ns(CLOCK_REALTIME) / 1_000_000_000);
+        if today == 0 {
+            return;
+        }
+
+        let store = &mut self.store;
+        let mut boot_id = store.baseline.boot_id;
+        store.begin_recount(today);
+        for_each_event(&mut |record| {
+            if record.kind == Kind::Boot {
+                boot_id = record.boot_id;
+            }
+            store.count_event(today, record);
+        });
+        store.baseline.rebase(boot_id);
+
+        let counters = [
+            (Metric::Syscalls, syscall::EXO_PERF_DISPATCH_TOTAL),
+            (Metric::IpcMessages, syscall::EXO_PERF_IPC_MESSAGES_SENT),
+            (Metric::IpcDropped, syscall::EXO_PERF_IPC_MESSAGES_DROPPED),
+        ];
+        for (metric, perf_metric) in counters {
+            if let Some(raw) = perf(perf_metric) {
+                store.sample(today, metric, raw);
+            }
+        }
+        if let Some((rx, tx)) = net_usage() {
+            store.sample(today, Metric::NetRxBytes, rx);
+            store.sample(today, Metric::NetTxBytes, tx);
+        }
+        if let Some(mib) = mem_total_mib() {
+            if let Some(day) = store.day_mut(today) {
+                day.set(Metric::MemTotalMib, mib);
+            }
+        }
+        self.samples += 1;
+
+        if self.upload_failed != today && self.store.upload_due(today) {
+            if let Some(collector) = self.config.upload_to() {
+                let mut request = [0u8; REQUEST_MAX];
+                let sent = self
+                    .store
+                    .report(today)
+                    .format_request(collector, &mut request)
+                    .is_some_and(|n| upload(collector, &request[..n]));
+                if sent {
+                    self.store.uploaded = today;
+                } else {
+                    self.upload_failed = today;
+                }
+            }
+        }
+        self.save();
+    }
+
+    /// Réécrit le magasin (fichier temporaire puis renommage).
+    fn save(&mut self) {
+        let Some(len) = self.store.format(&mut self.buf) else {
+            return;
+        };
+        if write_file(STORE_TMP_PATH, &self.buf[..len]) {
+            // SAFETY: chemins statiques terminés par NUL.
+            let _ = unsafe {
+                syscall::syscall2(
+                    syscall::SYS_RENAME,
+                    STORE_TMP_PATH.as_ptr() as u64,
+                    STORE_PATH.as_ptr() as u64,
+                )
+            };
+        }
+    }
+
+    fn status_reply(&self) -> MetricsReply {
+        MetricsReply::ok(
+            self.samples,
+            self.store.days().len() as u64,
+            self.store.uploaded,
+            self.config.consent as u32,
+        )
+    }
+}
+
+/// Lit au plus `buf.len()` octets de `path` ; `0` si absent.
+fn read_file(path: &[u8], buf: &mut [u8]) -> usize {
+    // SAFETY: chemin statique terminé par NUL.
+    let fd =
+        unsafe { syscall::syscall2(syscall::SYS_OPEN, path.as_ptr() as u64, syscall::O_RDONLY) };
+    if fd < 0 {
+        return 0;
+    }
+    let mut len = 0;
+    while len < buf.len() {
+        // SAFETY: écriture bornée à la fin du buffer.
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_READ,
+                fd as u64,
+                buf[len..].as_mut_ptr() as u64,
+                (buf.len() - len) as u64,
+            )
+        };
+        if n <= 0 {
+            break;
+        }
+        len += n as usize;
+    }
+    // SAFETY: fermeture du descripteur ouvert ci-dessus.
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+    len
+}
+
+fn write_file(path: &[u8], data: &[u8]) -> bool {
+    // SAFETY: chemin statique terminé par NUL.
+    let fd = unsafe {
+        syscall::syscall3(
+            syscall::SYS_OPEN,
+            path.as_ptr() as u64,
+            syscall::O_WRONLY | syscall::O_CREAT | syscall::O_TRUNC,
+            0o644,
+        )
+    };
+    if fd < 0 {
+        return false;
+    }
+    let mut done = 0;
+    while done < data.len() {
+        // SAFETY: lecture bornée à `data`.
+        let n = unsafe {
+            syscall::syscall3(
+                syscall::SYS_WRITE,
+                fd as u64,
+                data[done..].as_ptr() as u64,
+                (data.len() - done) as u64,
+            )
+        };
+        if n <= 0 {
+            break;
+        }
+        done += n as usize;
+    }
+    // SAFETY: fermeture du descripteur ouvert ci-dessus.
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+    done == data.len()
+}
+
+/// Appelle `each` sur chaque événement des journaux, du plus ancien au plus
+/// récent.
+fn for_each_event(each: &mut dyn FnMut(&Record)) {
+    for path in EVENT_JOURNALS {
+        // SAFETY: chemin statique terminé par NUL.
+        let fd = unsafe {
+            syscall::syscall2(syscall::SYS_OPEN, path.as_ptr() as u64, syscall::O_RDONLY)
+        };
+        if fd < 0 {
+            continue;
+        }
+        let mut buf = [0u8; 512];
+        let mut line = [0u8; events::LINE_MAX];
+        let mut len = 0usize;
+        loop {
+            // SAFETY: écriture bornée à la fin du buffer.
+            let n = unsafe {
+                syscall::syscall3(
+                    syscall::SYS_READ,
+                    fd as u64,
+                    buf.as_mut_ptr() as u64,
+                    buf.len() as u64,
+                )
+            };
+            if n <= 0 {
+                break;
+            }
+            for &b in &buf[..n as usize] {
+                if b != b'\n' {
+                    if len < line.len() {
+                        line[len] = b;
+                    }
+                    len = len.saturating_add(1);
+                    continue;
+                }
+                // Ligne trop longue : ignorée.
+                if len <= line.len() {
+                    let text = core::str::from_utf8(&line[..len]).ok();
+                    if let Some(record) = text.and_then(Record::parse) {
+                        each(&record);
+                    }
+                }
+                len = 0;
+            }
+        }
+        // SAFETY: fermeture du descripteur ouvert ci-dessus.
+        let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+    }
+}
+
+fn perf(metric: u64) -> Option<u64> {
+    // SAFETY: lecture d'un compteur global, sans pointeur.
+    let rc = unsafe { syscall::syscall2(syscall::SYS_EXO_PERF_READ, metric, 0) };
+    (rc >= 0).then_some(rc as u64)
+}
+
+/// Octets reçus et émis, tous processus confondus.
+fn net_usage() -> Option<(u64, u64)> {
+    let (mut rx, mut tx) = (0u64, 0u64);
+    let mut index = 0u64;
+    loop {
+        let mut entry = syscall::ExoNetUsage::default();
+        // SAFETY: le noyau écrit au plus `size_of::<ExoNetUsage>()` octets
+        // dans `entry`.
+        let rc = unsafe {
+            syscall::syscall3(
+                syscall::SYS_EXO_NET_USAGE,
+                index,
+                &mut entry as *mut syscall::ExoNetUsage as u64,
+                core::mem::size_of::<syscall::ExoNetUsage>() as u64,
+            )
+        };
+        if rc == syscall::ENOENT {
+            return Some((rx, tx));
+        }
+        if rc < 0 {
+            return None;
+        }
+        index = rc as u64;
+        rx = rx.saturating_add(entry.rx_bytes);
+        tx = tx.saturating_add(entry.tx_bytes);
+    }
+}
+
+fn mem_total_mib() -> Option<u64> {
+    let mut info = LinuxSysInfo::default();
+    // SAFETY: le noyau écrit dans `info`, structure locale.
+    let rc = unsafe { syscall::syscall1(syscall::SYS_SYSINFO, &mut info as *mut _ as u64) };
+    if rc < 0 {
+        return None;
+    }
+    let unit = info.mem_unit.max(1) as u64;
+    Some(info.totalram.saturating_mul(unit) >> 20)
+}
+
+/// Envoie `request` au collecteur ; vrai sur une réponse `2xx`.
+fn upload(collector: metrics::Collector, request: &[u8]) -> bool {
+    // SAFETY: création de socket, sans pointeur.
+    let fd = unsafe { syscall::syscall3(syscall::SYS_SOCKET, AF_INET as u64, SOCK_STREAM, 0) };
+    if fd < 0 {
+        return false;
+    }
+    let mut sockaddr = [0u8; 16];
+    sockaddr[0..2].copy_from_slice(&AF_INET.to_ne_bytes());
+    sockaddr[2..4].copy_from_slice(&collector.port.to_be_bytes());
+    sockaddr[4..8].copy_from_slice(&collector.addr);
+    // SAFETY: `sockaddr` est un sockaddr_in local valide pendant l'appel.
+    let rc = unsafe {
+        syscall::syscall3(
+            syscall::SYS_CONNECT,
+            fd as u64,
+            sockaddr.as_ptr() as u64,
+            sockaddr.len() as u64,
+        )
+    };
+    let ok = rc >= 0 && send_all(fd, request) && reply_ok(fd);
+    // SAFETY: fermeture du descripteur ouvert ci-dessus.
+    let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+    ok
+}
+
+fn send_all(fd: i64, data: &[u8]) -> bool {
+    let mut done = 0;
+    while done < data.len() {
+        // SAFETY: lecture bornée à `data`, sans adresse de destination.
+        let n = unsafe {
+            syscall::syscall6(
+                syscall::SYS_SENDTO,
+                fd as u64,
+                data[done..].as_ptr() as u64,
+                (data.len() - done) as u64,
+                0,
+                0,
+                0,
+            )
+        };
+        if n <= 0 {
+            return false;
+        }
+        done += n as usize;
+    }
+    true
+}
+
+/// Attend la ligne d'état `HTTP/1.x 2xx` du collecteur.
+fn reply_ok(fd: i64) -> bool {
+    let mut status = [0u8; 12];
+    let mut len = 0;
+    for _ in 0..UPLOAD_REPLY_POLLS {
+        // SAFETY: écriture bornée à la fin de `status`, sans adresse source.
+        let n = unsafe {
+            syscall::syscall6(
+                syscall::SYS_RECVFROM,
+                fd as u64,
+                status[len..].as_mut_ptr() as u64,
+                (status.len() - len) as u64,
+                0,
+                0,
+                0,
+            )
+        };
+        if n == syscall::EAGAIN {
+            sleep_ms(UPLOAD_POLL_MS);
+            continue;
+        }
+        if n <= 0 {
+            break;
+        }
+        len += n as usize;
+        if len == status.len() {
+            break;
+        }
+    }
+    status[..len].starts_with(b"HTTP/1.") && len >= 10 && status[9] == b'2'
+}
+
+fn sleep_ms(ms: u64) {
+    let ts = Timespec {
+        tv_sec: (ms / 1000) as i64,
+        tv_nsec: ((ms % 1000) * 1_000_000) as i64,
+    };
+    // SAFETY: `ts` est une structure locale lue par le noyau.
+    let _ = unsafe { syscall::syscall2(syscall::SYS_NANOSLEEP, &ts as *const _ as u64, 0) };
+}
+
+/// `0` si l'horloge n'est pas lisible (ou pas encore réglée).
+fn clock_ns(clock: u64) -> u64 {
+    let mut ts = Timespec::default();
+    // SAFETY: le noyau écrit dans `ts`, structure locale.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_CLOCK_GETTIME,
+            clock,
+            &mut ts as *mut Timespec as u64,
+        )
+    };
+    if rc != 0 || ts.tv_sec < 0 {
+        return 0;
+    }
+    ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let endpoint = register_endpoint();
+    SERVICE.lock().start();
+    let mut request = MetricsRequest::zeroed();
+
+    loop {
+        SERVICE.lock().tick();
+        if endpoint == 0 {
+            continue;
+        }
+        match recv_request(endpoint, &mut request) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => continue,
+        }
+
+        let reply = dispatch(&request);
+        let _ = send_reply(request.sender_pid, &reply);
+    }
+}
+
+fn dispatch(request: &MetricsRequest) -> MetricsReply {
+    let mut service = SERVICE.lock();
+
+    match request.msg_type {
+        METRICS_MSG_HEARTBEAT | METRICS_MSG_STATUS => service.status_reply(),
+        METRICS_MSG_SAMPLE => {
+            service.sample();
+            service.status_reply()
+        }
+        _ => MetricsReply::error(syscall::EINVAL),
+    }
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        // SAFETY: panic terminale pour un serveur no_std monothread.
+        unsafe {
+            core::arch::asm!("hlt", options(nostack, nomem));
+        }
+    }
+}
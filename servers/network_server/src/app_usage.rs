@@ -0,0 +1,100 @@
+//! Comptabilité réseau par application.
+//!
+//! Les compteurs sont indexés par nom de processus (lu dans la table des
+//! processus pour le PID propriétaire de la socket) et survivent à la fermeture des sockets comme au
+//! redémarrage de l'application : c'est la consommation cumulée depuis le
+//! démarrage de network_server. Le slot 0 regroupe les sockets sans nom et
+//! celles ouvertes une fois la table pleine.
+
+pub const MAX_APPS: usize = 64;
+pub const APP_NAME_LEN: usize = 16;
+/// Slot « autres » : nom vide.
+pub const OTHER_APP: u16 = 0;
+
+#[derive(Clone, Copy)]
+pub struct AppUsage {
+    pub name: [u8; APP_NAME_LEN],
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    /// Sockets actuellement ouvertes.
+    pub sockets: u32,
+}
+
+impl AppUsage {
+    const fn empty() -> Self {
+        Self {
+            name: [0; APP_NAME_LEN],
+            rx_bytes: 0,
+            tx_bytes: 0,
+            sockets: 0,
+        }
+    }
+}
+
+pub struct AppUsageTable {
+    apps: [AppUsage; MAX_APPS],
+    len: usize,
+}
+
+impl AppUsageTable {
+    pub const fn new() -> Self {
+        Self {
+            apps: [AppUsage::empty(); MAX_APPS],
+            len: 1,
+        }
+    }
+
+    /// Rattache une nouvelle socket à l'application `name` ; retourne son slot.
+    pub fn attach(&mut self, name: &[u8]) -> u16 {
+        let name_len = name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(name.len())
+            .min(APP_NAME_LEN);
+        let name = &name[..name_len];
+        let slot = if name.is_empty() {
+            OTHER_APP as usize
+        } else if let Some(idx) = (1..self.len).find(|&idx| self.name_of(idx) == name) {
+            idx
+        } else if self.len < MAX_APPS {
+            let idx = self.len;
+            self.apps[idx].name[..name.len()].copy_from_slice(name);
+            self.len += 1;
+            idx
+        } else {
+            OTHER_APP as usize
+        };
+        self.apps[slot].sockets = self.apps[slot].sockets.saturating_add(1);
+        slot as u16
+    }
+
+    /// Une socket de `app` vient d'être fermée ; ses octets restent comptés.
+    pub fn detach(&mut self, app: u16) {
+        if let Some(entry) = self.apps[..self.len].get_mut(app as usize) {
+            entry.sockets = entry.sockets.saturating_sub(1);
+        }
+    }
+
+    pub fn note_tx(&mut self, app: u16, bytes: u64) {
+        if let Some(entry) = self.apps[..self.len].get_mut(app as usize) {
+            entry.tx_bytes = entry.tx_bytes.saturating_add(bytes);
+        }
+    }
+
+    pub fn note_rx(&mut self, app: u16, bytes: u64) {
+        if let Some(entry) = self.apps[..self.len].get_mut(app as usize) {
+            entry.rx_bytes = entry.rx_bytes.saturating_add(bytes);
+        }
+    }
+
+    /// Entrée `index` dans l'ordre d'apparition, `None` au-delà de la fin.
+    pub fn get(&self, index: usize) -> Option<AppUsage> {
+        self.apps[..self.len].get(index).copied()
+    }
+
+    fn name_of(&self, idx: usize) -> &[u8] {
+        let name = &self.apps[idx].name;
+        let len = name.iter().position(|&b| b == 0).unwrap_or(APP_NAME_LEN);
+        &name[..len]
+    }
+}
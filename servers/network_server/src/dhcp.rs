@@ -15,6 +15,7 @@ const BROADCAST_FLAG: u16 = 0x8000;
 const OPT_SUBNET_MASK: u8 = 1;
 const OPT_ROUTER: u8 = 3;
 const OPT_DNS: u8 = 6;
+const OPT_VENDOR_SPECIFIC: u8 = 43;
 const OPT_REQUESTED_IP: u8 = 50;
 const OPT_LEASE_TIME: u8 = 51;
 const OPT_MESSAGE_TYPE: u8 = 53;
@@ -22,6 +23,14 @@ const OPT_SERVER_ID: u8 = 54;
 const OPT_PARAMETER_REQUEST: u8 = 55;
 const OPT_END: u8 = 255;
 
+const REQUESTED_OPTIONS: [u8; 5] = [
+    OPT_SUBNET_MASK,
+    OPT_ROUTER,
+    OPT_DNS,
+    OPT_LEASE_TIME,
+    OPT_VENDOR_SPECIFIC,
+];
+
 const MSG_DISCOVER: u8 = 1;
 const MSG_OFFER: u8 = 2;
 const MSG_REQUEST: u8 = 3;
@@ -52,6 +61,8 @@ pub struct DhcpLease {
     pub dns: u32,
     pub server_ip: u32,
     pub lease_seconds: u32,
+    /// Option 43 portant `ANDROID_METERED` (partage de connexion mobile).
+    pub metered: bool,
 }
 
 pub struct DhcpClient {
@@ -61,6 +72,7 @@ pub struct DhcpClient {
     server_ip: u32,
     lease_until_tick: u64,
     mac: [u8; 6],
+    metered: bool,
 }
 
 impl DhcpClient {
@@ -72,6 +84,7 @@ impl DhcpClient {
             server_ip: 0,
             lease_until_tick: 0,
             mac: [0; 6],
+            metered: false,
         }
     }
 
@@ -86,9 +99,17 @@ impl DhcpClient {
         self.offered_ip = 0;
         self.server_ip = 0;
         self.lease_until_tick = 0;
+        self.metered = false;
         self.state = DhcpPhase::Init;
     }
 
+    /// Reprise après veille : le bail a pu expirer et le réseau changer
+    /// (autre point d'accès, autre câble). Nouvelle découverte, sous une
+    /// autre transaction pour ignorer les réponses d'avant la veille.
+    pub fn resume(&mut self) {
+        self.start(self.xid.rotate_left(8) ^ 0x4558_4f44);
+    }
+
     pub fn poll(&mut self, now_ms: u64) -> DhcpAction {
         match self.state {
             DhcpPhase::Init => {
@@ -120,6 +141,7 @@ impl DhcpClient {
                 self.lease_until_tick =
                     now_ms.saturating_add((lease_seconds as u64).saturating_mul(1000));
                 self.state = DhcpPhase::Bound;
+                self.metered = parsed.metered;
                 Some(DhcpLease {
                     ip: parsed.yiaddr,
                     prefix_len: prefix_from_mask(parsed.subnet_mask),
@@ -127,6 +149,7 @@ impl DhcpClient {
                     dns: parsed.dns,
                     server_ip: parsed.server_ip,
                     lease_seconds,
+                    metered: parsed.metered,
                 })
             }
             _ => None,
@@ -137,12 +160,7 @@ impl DhcpClient {
         let opt_start = write_header(out, self.xid, self.mac, 0, 0)?;
         let mut pos = opt_start;
         pos = push_option(out, pos, OPT_MESSAGE_TYPE, &[MSG_DISCOVER])?;
-        pos = push_option(
-            out,
-            pos,
-            OPT_PARAMETER_REQUEST,
-            &[OPT_SUBNET_MASK, OPT_ROUTER, OPT_DNS, OPT_LEASE_TIME],
-        )?;
+        pos = push_option(out, pos, OPT_PARAMETER_REQUEST, &REQUESTED_OPTIONS)?;
         finish_options(out, pos)
     }
 
@@ -155,18 +173,18 @@ impl DhcpClient {
         pos = push_option(out, pos, OPT_MESSAGE_TYPE, &[MSG_REQUEST])?;
         pos = push_option(out, pos, OPT_REQUESTED_IP, &self.offered_ip.to_be_bytes())?;
         pos = push_option(out, pos, OPT_SERVER_ID, &self.server_ip.to_be_bytes())?;
-        pos = push_option(
-            out,
-            pos,
-            OPT_PARAMETER_REQUEST,
-            &[OPT_SUBNET_MASK, OPT_ROUTER, OPT_DNS, OPT_LEASE_TIME],
-        )?;
+        pos = push_option(out, pos, OPT_PARAMETER_REQUEST, &REQUESTED_OPTIONS)?;
         finish_options(out, pos)
     }
 
     pub const fn state(&self) -> DhcpPhase {
         self.state
     }
+
+    /// Le bail courant signale une connexion facturée au volume.
+    pub const fn metered_hint(&self) -> bool {
+        self.metered
+    }
 }
 
 struct ParsedDhcp {
@@ -177,6 +195,7 @@ struct ParsedDhcp {
     router: u32,
     dns: u32,
     lease_seconds: u32,
+    metered: bool,
 }
 
 fn parse_packet(packet: &[u8], xid: u32, mac: [u8; 6]) -> Option<ParsedDhcp> {
@@ -205,6 +224,7 @@ fn parse_packet(packet: &[u8], xid: u32, mac: [u8; 6]) -> Option<ParsedDhcp> {
         router: 0,
         dns: 0,
         lease_seconds: 3600,
+        metered: false,
     };
 
     let mut pos = DHCP_FIXED_LEN + 4;
@@ -233,6 +253,7 @@ fn parse_packet(packet: &[u8], xid: u32, mac: [u8; 6]) -> Option<ParsedDhcp> {
             OPT_ROUTER if len >= 4 => parsed.router = be_u32(data),
             OPT_DNS if len >= 4 => parsed.dns = be_u32(data),
             OPT_LEASE_TIME if len == 4 => parsed.lease_seconds = be_u32(data),
+            OPT_VENDOR_SPECIFIC => parsed.metered |= exo_services::metered::dhcp_hint(data),
             _ => {}
         }
         pos += len;
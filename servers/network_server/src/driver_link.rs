@@ -221,7 +221,7 @@ fn wait_hardware_endpoint(timeout_ms: u64) -> Option<u64> {
     None
 }
 
-fn lookup_endpoint(name: &[u8]) -> Option<u64> {
+pub(crate) fn lookup_endpoint(name: &[u8]) -> Option<u64> {
     let rc = unsafe {
         syscall::syscall2(
             syscall::SYS_IPC_LOOKUP,
@@ -250,7 +250,7 @@ fn sleep_ms(ms: u64) {
     let _ = unsafe { syscall::syscall2(syscall::SYS_NANOSLEEP, &ts as *const _ as u64, 0) };
 }
 
-fn send(endpoint: u64, msg_type: u32, payload: &[u8]) -> i64 {
+pub(crate) fn send(endpoint: u64, msg_type: u32, payload: &[u8]) -> i64 {
     #[repr(C)]
     struct DriverRequest {
         sender_pid: u32,
@@ -12,6 +12,7 @@ use core::panic::PanicInfo;
 
 use spin::Mutex;
 
+mod app_usage;
 mod buf_pool;
 mod dhcp;
 mod driver_link;
@@ -25,14 +26,16 @@ mod stats;
 mod tcp_store;
 mod virtio_device;
 
+use app_usage::AppUsageTable;
 use buf_pool::{NetBufPool, VIRTIO_NET_HDR_SIZE_MODERN};
 use driver_link::DriverLink;
 use isolation::IsolationState;
 use protocol::{
-    parse_driver_ctrl, parse_net_msg, parse_raw_call, recv_raw, register_endpoint, send_rpc_reply,
+    parse_driver_ctrl, parse_net_msg, parse_raw_call, parse_resume_notice, process_name,
+    recv_raw, register_endpoint, send_rpc_reply,
     send_rpc_reply_with_data, DriverCtrlMsg, MacReplyMsg, NetMsg, NetReply, RxReadyMsg,
     TxCompleteMsg, NET_CTRL_MAC_REPLY, NET_CTRL_RX_READY, NET_CTRL_TX_COMPLETE,
-    NET_INLINE_DATA_MAX, NET_OP_ACCEPT, NET_OP_BIND, NET_OP_CLOSE, NET_OP_CONNECT,
+    NET_INLINE_DATA_MAX, NET_OP_ACCEPT, NET_OP_APP_USAGE, NET_OP_BIND, NET_OP_CLOSE, NET_OP_CONNECT,
     NET_OP_GETPEERNAME, NET_OP_GETSOCKNAME, NET_OP_GETSOCKOPT, NET_OP_LISTEN, NET_OP_OPEN,
     NET_OP_RECVFROM, NET_OP_RECVMSG, NET_OP_SENDMSG, NET_OP_SENDTO, NET_OP_SETSOCKOPT,
     NET_OP_SHUTDOWN, NET_OP_SOCKETPAIR, RAW_MSG_SIZE,
@@ -49,6 +52,7 @@ use tcp_store::TcpStateStore;
 use virtio_device::ExoNetDevice;
 
 const DEFAULT_IPV4: u32 = 0x0a00_020f;
+const PROCESS_LIST_CAP: usize = 128;
 const DEFAULT_PREFIX_LEN: u8 = 24;
 
 /// Connexion TCP en attente d'établissement.
@@ -80,15 +84,20 @@ struct NetworkService {
     routes: RouteTable,
     dhcp: dhcp::DhcpClient,
     stats: NetStats,
+    apps: AppUsageTable,
     tcp_store: TcpStateStore,
     isolation: IsolationState,
     bootstrapped: bool,
     ticks: u64,
     unsupported_msg_ops: u64,
     reported_no_hardware_route: bool,
+    /// Dernier indice « connexion facturée » remis à `data_saver`.
+    reported_metered: bool,
     // FIX-SRV-M5 : file d'attente des connexions TCP en cours d'établissement.
     pending_connects: [PendingConnect; MAX_PENDING_CONNECTS],
     pending_connect_count: usize,
+    /// Relevé de la table des processus, pour nommer l'application d'un PID.
+    processes: [exo_syscall_abi::ExoProcessInfo; PROCESS_LIST_CAP],
 }
 
 impl NetworkService {
@@ -102,14 +111,17 @@ impl NetworkService {
             routes: RouteTable::new(),
             dhcp: dhcp::DhcpClient::new(),
             stats: NetStats::new(),
+            apps: AppUsageTable::new(),
             tcp_store: TcpStateStore::new_empty(),
             isolation: IsolationState::new(),
             bootstrapped: false,
             ticks: 0,
             unsupported_msg_ops: 0,
             reported_no_hardware_route: false,
+            reported_metered: false,
             pending_connects: [const { PendingConnect::empty() }; MAX_PENDING_CONNECTS],
             pending_connect_count: 0,
+            processes: [exo_syscall_abi::ExoProcessInfo::zeroed(); PROCESS_LIST_CAP],
         }
     }
 
@@ -165,6 +177,39 @@ impl NetworkService {
         self.driver.flush_tx(&mut self.device, &self.pool);
         self.driver.flush_released(&mut self.device);
         let _ = self.dhcp.poll(self.ticks);
+        self.report_metered_hint();
+    }
+
+    /// Transmet l'indice DHCP à `data_saver` quand il change ; tant que le
+    /// service est absent, nouvel essai tous les 64 ticks.
+    fn report_metered_hint(&mut self) {
+        let hint = self.dhcp.metered_hint();
+        if hint == self.reported_metered || !self.ticks.is_multiple_of(64) {
+            return;
+        }
+        let Some(endpoint) = driver_link::lookup_endpoint(b"data_saver") else {
+            return;
+        };
+        let payload = (hint as u32).to_le_bytes();
+        if driver_link::send(endpoint, exo_services::metered::METERED_MSG_HINT, &payload) >= 0 {
+            self.reported_metered = hint;
+        }
+    }
+
+    /// Reprise signalée par `sleep_monitor` : nouveau bail DHCP et, après
+    /// une longue veille, fermeture des connexions TCP que les pairs ont
+    /// oubliées. Ignorée si elle vient d'un autre processus.
+    fn resumed(&mut self, sender_pid: u32, resumed: exo_services::resume::Resumed) {
+        let monitor = driver_link::lookup_endpoint(b"sleep_monitor");
+        if monitor.map(|endpoint| endpoint >> 32) != Some(sender_pid as u64) {
+            return;
+        }
+        self.dhcp.resume();
+        if resumed.connections_stale() {
+            let aborted = self.iface.abort_tcp_connections();
+            debug_errno(b"network_server: resume, tcp reset ", aborted as i64);
+        }
+        self.tick();
     }
 
     fn flush_released(&mut self) {
@@ -243,6 +288,7 @@ impl NetworkService {
             NET_OP_SETSOCKOPT => self.handle_setsockopt(msg),
             NET_OP_GETSOCKOPT => self.handle_getsockopt(msg),
             NET_OP_CLOSE => self.handle_close(msg),
+            NET_OP_APP_USAGE => self.handle_app_usage(msg),
             _ => NetReply::error(exo_syscall_abi::EINVAL),
         }
     }
@@ -270,6 +316,16 @@ impl NetworkService {
             &[]
         };
         match msg.opcode {
+            NET_OP_OPEN => {
+                let reply = self.handle_open(msg);
+                self.tick();
+                (reply, [0; NET_INLINE_DATA_MAX], 0)
+            }
+            NET_OP_SOCKETPAIR => {
+                let reply = self.handle_socketpair(msg);
+                self.tick();
+                (reply, [0; NET_INLINE_DATA_MAX], 0)
+            }
             NET_OP_SENDTO => {
                 let reply = self.handle_sendto_data(msg, data);
                 self.tick();
@@ -391,15 +447,17 @@ impl NetworkService {
             _ => return NetReply::error(exo_syscall_abi::EINVAL),
         };
         // Créer socket A
-        let snap_a = match self.sockets.open(msg.sender_pid, kind) {
+        let snap_a = match self.open_socket(msg.sender_pid, kind) {
             Ok(s) => s,
             Err(e) => return NetReply::error(e),
         };
         // Créer socket B
-        let snap_b = match self.sockets.open(msg.sender_pid, kind) {
+        let snap_b = match self.open_socket(msg.sender_pid, kind) {
             Ok(s) => s,
             Err(e) => {
-                let _ = self.sockets.close(msg.sender_pid, snap_a.handle);
+                if self.sockets.close(msg.sender_pid, snap_a.handle).is_ok() {
+                    self.apps.detach(snap_a.app);
+                }
                 return NetReply::error(e);
             }
         };
@@ -434,6 +492,23 @@ impl NetworkService {
         Err(exo_syscall_abi::ENETDOWN)
     }
 
+    /// Ouvre une socket et la rattache à l'application de `owner_pid`.
+    ///
+    /// Le nom vient de la table des processus, jamais du message : un client
+    /// ne peut pas imputer son trafic à une autre application.
+    fn open_socket(
+        &mut self,
+        owner_pid: u32,
+        kind: SocketKind,
+    ) -> Result<socket_table::SocketSnapshot, i64> {
+        let app = self.apps.attach(&process_name(owner_pid, &mut self.processes));
+        let result = self.sockets.open(owner_pid, kind, app);
+        if result.is_err() {
+            self.apps.detach(app);
+        }
+        result
+    }
+
     fn handle_open(&mut self, msg: NetMsg) -> NetReply {
         // FIX-SOCK-RAW (Security_Audit_Passe2 §D-01) : chemin client — la
         // vérification de privilège SOCK_RAW doit recevoir le PID réel de
@@ -448,11 +523,12 @@ impl NetworkService {
             Ok(kind) => kind,
             Err(err) => return NetReply::error(err),
         };
-        match self.sockets.open(msg.sender_pid, kind) {
+        match self.open_socket(msg.sender_pid, kind) {
             Ok(snapshot) => match self.iface.register_socket(snapshot.handle, kind) {
                 Ok(()) => socket_reply(0, &snapshot),
                 Err(err) => {
                     let _ = self.sockets.close(msg.sender_pid, snapshot.handle);
+                    self.apps.detach(snapshot.app);
                     NetReply::error(err)
                 }
             },
@@ -579,6 +655,7 @@ impl NetworkService {
             Err(err) => return NetReply::error(err),
         };
         self.iface.apply_socket_state(&snapshot);
+        self.apps.note_tx(snapshot.app, len as u64);
         if !data.is_empty() {
             match self.iface.send_socket_data(&snapshot, data) {
                 Ok(sent) if sent == data.len() => self.stats.note_tx(sent as u64),
@@ -619,6 +696,7 @@ impl NetworkService {
             Ok(snapshot) => snapshot,
             Err(err) => return (NetReply::error(err), data, 0),
         };
+        self.apps.note_rx(snapshot.app, delivered as u64);
         let mut reply = socket_reply(delivered as i64, &snapshot);
         if peer_addr != 0 {
             reply = reply.with_u32(8, peer_addr).with_u16(12, peer_port);
@@ -673,11 +751,25 @@ impl NetworkService {
         match self.sockets.close(msg.sender_pid, msg.fd) {
             Ok(snapshot) => {
                 self.iface.unregister_socket(snapshot.handle);
+                self.apps.detach(snapshot.app);
                 socket_reply(0, &snapshot)
             }
             Err(err) => NetReply::error(err),
         }
     }
+
+    fn handle_app_usage(&mut self, msg: NetMsg) -> NetReply {
+        let index = msg.arg1.min(usize::MAX as u64) as usize;
+        let Some(entry) = self.apps.get(index) else {
+            return NetReply::error(exo_syscall_abi::ENOENT);
+        };
+        let mut reply = NetReply::ok(index as i64 + 1)
+            .with_u64(16, entry.rx_bytes)
+            .with_u64(24, entry.tx_bytes)
+            .with_u32(32, entry.sockets);
+        reply.payload[..app_usage::APP_NAME_LEN].copy_from_slice(&entry.name);
+        reply
+    }
 }
 
 static NETWORK_SERVICE: Mutex<NetworkService> = Mutex::new(NetworkService::new());
@@ -724,6 +816,8 @@ pub extern "C" fn _start() -> ! {
             };
         } else if let Some(ctrl) = parse_driver_ctrl(&raw[..n]) {
             NETWORK_SERVICE.lock().handle_driver_ctrl(ctrl);
+        } else if let Some((sender_pid, resumed)) = parse_resume_notice(&raw[..n]) {
+            NETWORK_SERVICE.lock().resumed(sender_pid, resumed);
         } else {
             NETWORK_SERVICE.lock().tick();
         }
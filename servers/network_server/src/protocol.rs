@@ -1,3 +1,4 @@
+use exo_services::resume::{Resumed, RESUME_NOTIFY_RESUMED};
 use exo_syscall_abi as syscall;
 
 pub const SERVER_ENDPOINT_ID: u64 = 7;
@@ -24,6 +25,10 @@ pub const NET_OP_SETSOCKOPT: u32 = 0x4E0C;
 pub const NET_OP_GETSOCKOPT: u32 = 0x4E0D;
 pub const NET_OP_CLOSE: u32 = 0x4E0E;
 pub const NET_OP_GETPEERNAME: u32 = 0x4E0F;
+/// Consommation cumulée par application : `arg1` = index de l'entrée.
+/// Réponse : status = index suivant (ENOENT en fin), payload = nom [0..16],
+/// rx [16..24], tx [24..32], sockets ouvertes [32..36].
+pub const NET_OP_APP_USAGE: u32 = 0x4E10;
 
 pub const NET_CTRL_DRIVER_INIT: u32 = 0x4F00;
 pub const NET_CTRL_RX_RELEASE: u32 = 0x4F01;
@@ -229,6 +234,44 @@ pub fn parse_driver_ctrl(buf: &[u8]) -> Option<DriverCtrlMsg> {
     }
 }
 
+/// Notification de reprise (`exo_services::resume`) : PID émetteur, posé
+/// par le noyau, et reprise.
+pub fn parse_resume_notice(buf: &[u8]) -> Option<(u32, Resumed)> {
+    if buf.len() < syscall::IPC_HEADER_SIZE {
+        return None;
+    }
+    let sender_pid = u32::from_le_bytes(buf[..4].try_into().ok()?);
+    let msg_type = u32::from_le_bytes(buf[4..8].try_into().ok()?);
+    if msg_type != RESUME_NOTIFY_RESUMED {
+        return None;
+    }
+    Resumed::decode(&buf[syscall::IPC_HEADER_SIZE..]).map(|resumed| (sender_pid, resumed))
+}
+
+/// Nom du processus `pid` d'après la table des processus du noyau, vide s'il
+/// n'y figure pas (ou si le relevé, plein, l'a omis).
+pub fn process_name(
+    pid: u32,
+    list: &mut [syscall::ExoProcessInfo],
+) -> [u8; syscall::EXO_PROCESS_NAME_LEN] {
+    let n = unsafe {
+        syscall::syscall3(
+            syscall::SYS_EXO_PROCESS_LIST,
+            list.as_mut_ptr() as u64,
+            list.len() as u64,
+            core::mem::size_of::<syscall::ExoProcessInfo>() as u64,
+        )
+    };
+    if n <= 0 || pid == 0 {
+        return [0; syscall::EXO_PROCESS_NAME_LEN];
+    }
+    let n = (n as usize).min(list.len());
+    list[..n]
+        .iter()
+        .find(|entry| entry.pid == pid)
+        .map_or([0; syscall::EXO_PROCESS_NAME_LEN], |entry| entry.name)
+}
+
 pub fn register_endpoint() {
     let name = b"network_server";
     unsafe {
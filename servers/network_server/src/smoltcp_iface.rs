@@ -10,7 +10,7 @@ use smoltcp::iface::{
     Config, Interface, PollIngressSingleResult, SocketHandle, SocketSet, SocketStorage,
 };
 use smoltcp::phy::{Device, DeviceCapabilities, Medium, RxToken, TxToken};
-use smoltcp::socket::{icmp, tcp, udp};
+use smoltcp::socket::{icmp, tcp, udp, Socket};
 use smoltcp::time::Instant;
 use smoltcp::wire::{
     EthernetAddress, IpAddress, IpCidr, IpEndpoint, IpListenEndpoint, Ipv4Address,
@@ -418,6 +418,28 @@ impl SmoltcpIface {
         }
     }
 
+    /// Reprise après une longue veille : les pairs ont oublié les
+    /// connexions TCP ; un RST les ferme tout de suite au lieu de laisser la
+    /// prochaine écriture attendre la fin des retransmissions. Retourne le
+    /// nombre de connexions fermées.
+    pub fn abort_tcp_connections(&mut self) -> usize {
+        let mut sockets = socket_set();
+        let mut aborted = 0usize;
+        for (_, socket) in sockets.iter_mut() {
+            let Socket::Tcp(socket) = socket else {
+                continue;
+            };
+            if !matches!(
+                socket.state(),
+                tcp::State::Closed | tcp::State::Listen | tcp::State::TimeWait
+            ) {
+                socket.abort();
+                aborted += 1;
+            }
+        }
+        aborted
+    }
+
     fn ensure_iface(&mut self, device: &mut ExoNetDevice, pool: &NetBufPool, now: Instant) {
         if self.iface.is_some() {
             return;
@@ -88,6 +88,8 @@ pub struct SocketSnapshot {
     pub pending_rx: u32,
     pub tx_bytes: u64,
     pub rx_bytes: u64,
+    /// Slot du propriétaire dans `AppUsageTable`.
+    pub app: u16,
 }
 
 #[derive(Clone, Copy)]
@@ -104,6 +106,7 @@ struct SocketRecord {
     pending_rx: u32,
     tx_bytes: u64,
     rx_bytes: u64,
+    app: u16,
 }
 
 impl SocketRecord {
@@ -121,6 +124,7 @@ impl SocketRecord {
             pending_rx: 0,
             tx_bytes: 0,
             rx_bytes: 0,
+            app: 0,
         }
     }
 }
@@ -138,7 +142,12 @@ impl SocketTable {
         }
     }
 
-    pub fn open(&mut self, owner_pid: u32, kind: SocketKind) -> Result<SocketSnapshot, i64> {
+    pub fn open(
+        &mut self,
+        owner_pid: u32,
+        kind: SocketKind,
+        app: u16,
+    ) -> Result<SocketSnapshot, i64> {
         let Some(idx) = self.sockets.iter().position(|entry| !entry.active) else {
             return Err(syscall::EMFILE);
         };
@@ -156,6 +165,7 @@ impl SocketTable {
             pending_rx: 0,
             tx_bytes: 0,
             rx_bytes: 0,
+            app,
         };
         Ok(self.snapshot(idx))
     }
@@ -343,6 +353,7 @@ impl SocketTable {
             pending_rx: s.pending_rx,
             tx_bytes: s.tx_bytes,
             rx_bytes: s.rx_bytes,
+            app: s.app,
         }
     }
 
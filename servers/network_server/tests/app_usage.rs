@@ -0,0 +1,65 @@
+#[allow(dead_code)]
+#[path = "../src/app_usage.rs"]
+mod app_usage;
+
+use app_usage::{AppUsageTable, MAX_APPS, OTHER_APP};
+
+#[test]
+fn bytes_are_counted_per_application() {
+    let mut table = AppUsageTable::new();
+    let browser = table.attach(b"browser\0\0\0");
+    let mail = table.attach(b"mail");
+    assert_ne!(browser, mail);
+    assert_eq!(table.attach(b"browser"), browser);
+
+    table.note_tx(browser, 100);
+    table.note_rx(browser, 1000);
+    table.note_rx(mail, 7);
+
+    let entry = table.get(browser as usize).unwrap();
+    assert_eq!(&entry.name[..7], b"browser");
+    assert_eq!(
+        (entry.tx_bytes, entry.rx_bytes, entry.sockets),
+        (100, 1000, 2)
+    );
+    let entry = table.get(mail as usize).unwrap();
+    assert_eq!((entry.tx_bytes, entry.rx_bytes, entry.sockets), (0, 7, 1));
+    assert_eq!(table.get(OTHER_APP as usize).unwrap().rx_bytes, 0);
+    assert!(table.get(3).is_none());
+}
+
+#[test]
+fn counters_are_not_reset_by_closing_sockets() {
+    let mut table = AppUsageTable::new();
+    let app = table.attach(b"updater");
+    table.note_tx(app, u64::MAX - 1);
+    table.detach(app);
+    table.detach(app);
+    assert_eq!(table.get(app as usize).unwrap().sockets, 0);
+
+    // L'application redémarre : même slot, cumul conservé et saturé.
+    assert_eq!(table.attach(b"updater"), app);
+    table.note_tx(app, 10);
+    let entry = table.get(app as usize).unwrap();
+    assert_eq!((entry.tx_bytes, entry.sockets), (u64::MAX, 1));
+}
+
+#[test]
+fn full_table_counts_newcomers_as_other() {
+    let mut table = AppUsageTable::new();
+    let mut name = *b"app-00";
+    for idx in 1..MAX_APPS {
+        name[4] = b'0' + (idx / 10) as u8;
+        name[5] = b'0' + (idx % 10) as u8;
+        assert_eq!(table.attach(&name), idx as u16);
+    }
+    assert_eq!(table.attach(b"late"), OTHER_APP);
+    assert_eq!(table.attach(b""), OTHER_APP);
+    assert_eq!(table.attach(b"app-01"), 1);
+
+    table.note_rx(OTHER_APP, 5);
+    table.note_rx(MAX_APPS as u16, 5);
+    let other = table.get(OTHER_APP as usize).unwrap();
+    assert_eq!((other.rx_bytes, other.sockets), (5, 2));
+    assert!(table.get(MAX_APPS).is_none());
+}
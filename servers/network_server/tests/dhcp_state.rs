@@ -29,9 +29,62 @@ fn dhcp_discover_offer_request_ack_reaches_bound() {
     assert_eq!(lease.ip, 0x0a00_020f);
     assert_eq!(lease.gateway, 0x0a00_0202);
     assert_eq!(lease.prefix_len, 24);
+    assert!(!lease.metered && !client.metered_hint());
+}
+
+#[test]
+fn dhcp_vendor_hint_marks_the_lease_metered() {
+    let mac = [0x02, 0x45, 0x58, 0x4f, 0, 1];
+    let mut client = DhcpClient::new();
+    client.configure_mac(mac);
+    client.start(0x1234_5678);
+
+    let mut discover = [0u8; 320];
+    assert_eq!(client.poll(1), DhcpAction::Discover);
+    client.build_discover(&mut discover).unwrap();
+    let xid = u32::from_be_bytes([discover[4], discover[5], discover[6], discover[7]]);
+    client.ingest(&make_reply(xid, mac, 2), 10);
+    assert_eq!(client.poll(11), DhcpAction::Request);
+    let ack = make_reply_with_vendor(xid, mac, 5, b"\x01\x0fANDROID_METERED");
+    let lease = client.ingest(&ack, 12).expect("bound");
+    assert!(lease.metered && client.metered_hint());
+
+    // Le bail suivant repart sans indice.
+    client.resume();
+    assert!(!client.metered_hint());
+}
+
+#[test]
+fn dhcp_resume_restarts_discovery_under_new_xid() {
+    let mac = [0x02, 0x45, 0x58, 0x4f, 0, 1];
+    let mut client = DhcpClient::new();
+    client.configure_mac(mac);
+    client.start(0x1234_5678);
+
+    let mut discover = [0u8; 320];
+    assert_eq!(client.poll(1), DhcpAction::Discover);
+    client.build_discover(&mut discover).unwrap();
+    let xid = u32::from_be_bytes([discover[4], discover[5], discover[6], discover[7]]);
+    client.ingest(&make_reply(xid, mac, 2), 10);
+    assert_eq!(client.poll(11), DhcpAction::Request);
+    client.ingest(&make_reply(xid, mac, 5), 12).expect("bound");
+
+    client.resume();
+    assert_eq!(client.state(), DhcpPhase::Init);
+    assert_eq!(client.poll(13), DhcpAction::Discover);
+    client.build_discover(&mut discover).unwrap();
+    let resumed_xid = u32::from_be_bytes([discover[4], discover[5], discover[6], discover[7]]);
+    assert_ne!(resumed_xid, xid);
+    // Offre d'avant la veille : ignorée.
+    assert_eq!(client.ingest(&make_reply(xid, mac, 2), 14), None);
+    assert_eq!(client.state(), DhcpPhase::Selecting);
 }
 
 fn make_reply(xid: u32, mac: [u8; 6], msg_type: u8) -> [u8; 320] {
+    make_reply_with_vendor(xid, mac, msg_type, &[])
+}
+
+fn make_reply_with_vendor(xid: u32, mac: [u8; 6], msg_type: u8, vendor: &[u8]) -> [u8; 320] {
     let mut packet = [0u8; 320];
     packet[0] = 2;
     packet[1] = 1;
@@ -47,6 +100,9 @@ fn make_reply(xid: u32, mac: [u8; 6], msg_type: u8) -> [u8; 320] {
     push(&mut packet, &mut pos, 3, &0x0a00_0202_u32.to_be_bytes());
     push(&mut packet, &mut pos, 6, &0x0101_0101_u32.to_be_bytes());
     push(&mut packet, &mut pos, 51, &3600_u32.to_be_bytes());
+    if !vendor.is_empty() {
+        push(&mut packet, &mut pos, 43, vendor);
+    }
     packet[pos] = 255;
     packet
 }
@@ -9,7 +9,9 @@ fn tagged_socket_handles_keep_generation_separate_from_connect_lookup() {
     const OWNER: u32 = 13;
 
     let mut sockets = SocketTable::new();
-    let opened = sockets.open(OWNER, SocketKind::Tcp).expect("socket open");
+    let opened = sockets
+        .open(OWNER, SocketKind::Tcp, 0)
+        .expect("socket open");
     let connected = sockets
         .connect(OWNER, opened.handle, 0x0a00_0202, 80)
         .expect("socket connect");
@@ -22,7 +24,9 @@ fn tagged_socket_handles_keep_generation_separate_from_connect_lookup() {
     sockets
         .close(OWNER, opened.handle)
         .expect("socket close preserves first generation");
-    let reopened = sockets.open(OWNER, SocketKind::Tcp).expect("socket reopen");
+    let reopened = sockets
+        .open(OWNER, SocketKind::Tcp, 0)
+        .expect("socket reopen");
 
     assert_ne!(opened.handle, reopened.handle);
     assert_eq!(
@@ -36,7 +40,7 @@ fn raw_icmp_socket_connects_without_a_transport_port() {
     const OWNER: u32 = 13;
 
     let mut sockets = SocketTable::new();
-    let opened = sockets.open(OWNER, SocketKind::Raw).expect("raw open");
+    let opened = sockets.open(OWNER, SocketKind::Raw, 0).expect("raw open");
     let connected = sockets
         .connect(OWNER, opened.handle, 0x0a00_0202, 0)
         .expect("raw icmp connect");
@@ -52,13 +56,13 @@ fn closed_raw_icmp_socket_releases_its_identifier_binding() {
     const ICMP_IDENT: u16 = 0x4558;
 
     let mut sockets = SocketTable::new();
-    let first = sockets.open(OWNER, SocketKind::Raw).expect("raw open");
+    let first = sockets.open(OWNER, SocketKind::Raw, 0).expect("raw open");
     sockets
         .bind(OWNER, first.handle, 0, ICMP_IDENT)
         .expect("raw bind");
     sockets.close(OWNER, first.handle).expect("raw close");
 
-    let second = sockets.open(OWNER, SocketKind::Raw).expect("raw reopen");
+    let second = sockets.open(OWNER, SocketKind::Raw, 0).expect("raw reopen");
     sockets
         .bind(OWNER, second.handle, 0, ICMP_IDENT)
         .expect("identifier can be rebound after close");
@@ -0,0 +1,204 @@
+#![no_std]
+#![no_main]
+
+//! # night_light — veilleuse de session
+//!
+//! Réchauffe les couleurs de l'écran le soir (`exo_services::nightlight`) :
+//! à chaque tour, le démon calcule la température du moment selon l'horaire
+//! configuré (heures fixes ou coucher et lever du soleil au lieu donné) et,
+//! si elle a changé, pose la matrice de couleur correspondante dans
+//! `fb_server` (`FB_MSG_SET_CTM`). Recalcul toutes les `TICK_MS`, toutes
+//! les `FADE_TICK_MS` pendant un fondu.
+//!
+//! La page d'affichage des réglages écrit `/etc/exo/nightlight.conf` puis
+//! envoie `NIGHTLIGHT_MSG_RELOAD`. La table gamma (profil ICC) reste au
+//! compositeur : les deux corrections se composent dans `fb_server`.
+
+use core::panic::PanicInfo;
+
+use exo_services::color::{temperature_gains, Ctm};
+use exo_services::nightlight::{
+    temperature_at, Config, NIGHTLIGHT_MSG_HEARTBEAT, NIGHTLIGHT_MSG_RELOAD, NIGHTLIGHT_MSG_STATUS,
+};
+use exo_syscall_abi as syscall;
+use spin::Mutex;
+
+mod protocol;
+
+use protocol::{
+    fb_call, recv_request, register_endpoint, send_reply, NightLightReply, NightLightRequest,
+    FADE_TICK_MS, FB_REPLY_CHANNEL, NIGHT_LIGHT_CHANNEL, TICK_MS,
+};
+
+const CONFIG_PATH: &[u8] = b"/etc/exo/nightlight.conf\0";
+const CONFIG_MAX: usize = 1024;
+const CLOCK_REALTIME: u64 = 0;
+
+#[repr(C)]
+#[derive(Default)]
+struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+struct NightLightService {
+    config: Config,
+    fb_reply: u64,
+    /// Intensité du dernier calcul, de 0 à 1.
+    strength: f64,
+    /// Température posée dans `fb_server` (`None` : rien de posé).
+    applied: Option<u32>,
+}
+
+static SERVICE: Mutex<NightLightService> = Mutex::new(NightLightService::new());
+
+impl NightLightService {
+    const fn new() -> Self {
+        Self {
+            config: Config::new(),
+            fb_reply: 0,
+            strength: 0.0,
+            applied: None,
+        }
+    }
+
+    fn load(&mut self) {
+        let mut buf = [0u8; CONFIG_MAX];
+        let mut len = 0;
+        // SAFETY: chemin statique terminé par NUL.
+        let fd = unsafe {
+            syscall::syscall2(
+                syscall::SYS_OPEN,
+                CONFIG_PATH.as_ptr() as u64,
+                syscall::O_RDONLY,
+            )
+        };
+        if fd < 0 {
+            self.config = Config::new();
+            return;
+        }
+        while len < CONFIG_MAX {
+            // SAFETY: écriture bornée à la fin du buffer.
+            let n = unsafe {
+                syscall::syscall3(
+                    syscall::SYS_READ,
+                    fd as u64,
+                    buf[len..].as_mut_ptr() as u64,
+                    (CONFIG_MAX - len) as u64,
+                )
+            };
+            if n <= 0 {
+                break;
+            }
+            len += n as usize;
+        }
+        // SAFETY: fermeture du descripteur ouvert ci-dessus.
+        let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+        if let Ok(text) = core::str::from_utf8(&buf[..len]) {
+            self.config = Config::parse(text);
+        }
+    }
+
+    /// Recalcule la température et la pose si elle a changé ; retourne le
+    /// délai avant le prochain tour.
+    fn tick(&mut self) -> u64 {
+        // Horloge pas encore réglée : couleurs neutres.
+        self.strength = realtime_secs().map_or(0.0, |now| self.config.strength(now));
+        let kelvin = self.temperature();
+        if self.applied != Some(kelvin) && self.set_ctm(kelvin) {
+            self.applied = Some(kelvin);
+        }
+        if self.strength > 0.0 && self.strength < 1.0 {
+            FADE_TICK_MS
+        } else {
+            TICK_MS
+        }
+    }
+
+    fn temperature(&self) -> u32 {
+        temperature_at(self.config.temperature, self.strength)
+    }
+
+    fn set_ctm(&self, kelvin: u32) -> bool {
+        let ctm = Ctm::scale(temperature_gains(kelvin)).encode();
+        let mut req = syscall::FbRequest::zeroed();
+        req.msg_type = syscall::FB_MSG_SET_CTM;
+        req.data[..ctm.len()].copy_from_slice(&ctm);
+        fb_call(&mut req, self.fb_reply).is_some_and(|reply| reply.status >= 0)
+    }
+
+    fn status(&self) -> NightLightReply {
+        NightLightReply::ok(
+            self.temperature() as u64,
+            (self.strength * 1000.0) as u64,
+            self.config.mode.as_u32(),
+        )
+    }
+}
+
+fn realtime_secs() -> Option<i64> {
+    let mut ts = Timespec::default();
+    // SAFETY: le noyau écrit dans `ts`, structure locale.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_CLOCK_GETTIME,
+            CLOCK_REALTIME,
+            &mut ts as *mut Timespec as u64,
+        )
+    };
+    if rc != 0 || ts.tv_sec <= 0 {
+        return None;
+    }
+    Some(ts.tv_sec)
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let fb_reply = register_endpoint(FB_REPLY_CHANNEL, b"night_light_fb");
+    let endpoint = register_endpoint(NIGHT_LIGHT_CHANNEL, b"night_light");
+    {
+        let mut service = SERVICE.lock();
+        service.fb_reply = fb_reply;
+        service.load();
+    }
+    let mut request = NightLightRequest::zeroed();
+
+    loop {
+        let timeout = SERVICE.lock().tick();
+        if endpoint == 0 {
+            continue;
+        }
+        match recv_request(endpoint, &mut request, timeout) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => continue,
+        }
+
+        let reply = dispatch(&request);
+        let _ = send_reply(request.sender_pid, &reply);
+    }
+}
+
+fn dispatch(request: &NightLightRequest) -> NightLightReply {
+    let mut service = SERVICE.lock();
+
+    match request.msg_type {
+        NIGHTLIGHT_MSG_HEARTBEAT | NIGHTLIGHT_MSG_STATUS => service.status(),
+        NIGHTLIGHT_MSG_RELOAD => {
+            service.load();
+            service.tick();
+            service.status()
+        }
+        _ => NightLightReply::error(syscall::EINVAL),
+    }
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        // SAFETY: panic terminale pour un serveur no_std monothread.
+        unsafe {
+            core::arch::asm!("hlt", options(nostack, nomem));
+        }
+    }
+}
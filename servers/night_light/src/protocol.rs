@@ -0,0 +1,168 @@
+use exo_syscall_abi as syscall;
+
+/// Canal des requêtes (réglages), retrouvé par son nom
+/// (`SYS_IPC_LOOKUP "night_light"`). `fb_server` vérifie ce nom avant
+/// d'accepter une matrice de couleur.
+pub const NIGHT_LIGHT_CHANNEL: u64 = 1;
+/// Canal des réponses de `fb_server`, séparé pour ne pas les confondre avec
+/// une requête.
+pub const FB_REPLY_CHANNEL: u64 = 2;
+/// Période de recalcul hors transition, et pendant un fondu.
+pub const TICK_MS: u64 = 60_000;
+pub const FADE_TICK_MS: u64 = 5_000;
+/// Attente maximale d'une réponse de `fb_server`.
+const FB_REPLY_TIMEOUT_MS: u64 = 500;
+
+#[repr(C)]
+pub struct NightLightRequest {
+    pub sender_pid: u32,
+    pub msg_type: u32,
+    pub payload: [u8; syscall::IPC_INLINE_PAYLOAD_SIZE],
+}
+
+impl NightLightRequest {
+    pub const fn zeroed() -> Self {
+        Self {
+            sender_pid: 0,
+            msg_type: 0,
+            payload: [0; syscall::IPC_INLINE_PAYLOAD_SIZE],
+        }
+    }
+}
+
+const _: () = assert!(core::mem::size_of::<NightLightRequest>() == syscall::IPC_ENVELOPE_SIZE);
+const _: () =
+    assert!(core::mem::offset_of!(NightLightRequest, payload) == syscall::IPC_HEADER_SIZE);
+const _: () = assert!(exo_services::color::CTM_WIRE_LEN <= syscall::FB_TEXT_MAX);
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct NightLightReply {
+    pub status: i64,
+    pub handle: u64,
+    pub value0: u64,
+    pub value1: u64,
+    pub flags: u32,
+    pub _pad: [u8; 28],
+}
+
+impl NightLightReply {
+    pub const fn ok(value0: u64, value1: u64, flags: u32) -> Self {
+        Self {
+            status: 0,
+            handle: 0,
+            value0,
+            value1,
+            flags,
+            _pad: [0; 28],
+        }
+    }
+
+    pub const fn error(status: i64) -> Self {
+        Self {
+            status,
+            handle: 0,
+            value0: 0,
+            value1: 0,
+            flags: 0,
+            _pad: [0; 28],
+        }
+    }
+}
+
+/// Enregistre `name` sur `channel` ; retourne l'endpoint, `0` en cas d'échec.
+pub fn register_endpoint(channel: u64, name: &[u8]) -> u64 {
+    // SAFETY: lecture simple du PID courant.
+    let pid = unsafe { syscall::syscall0(syscall::SYS_GETPID) };
+    if pid <= 0 {
+        return 0;
+    }
+    let endpoint = ((pid as u64) << 32) | channel;
+    // SAFETY: nom statique valide, endpoint dans l'espace du PID courant.
+    let rc = unsafe {
+        syscall::syscall3(
+            syscall::SYS_IPC_REGISTER,
+            name.as_ptr() as u64,
+            name.len() as u64,
+            endpoint,
+        )
+    };
+    if rc < 0 {
+        0
+    } else {
+        endpoint
+    }
+}
+
+pub fn recv_request(
+    endpoint: u64,
+    request: &mut NightLightRequest,
+    timeout_ms: u64,
+) -> Result<bool, i64> {
+    // SAFETY: le noyau écrit dans `request`, taille bornée à la struct ABI.
+    let rc = unsafe {
+        syscall::syscall4(
+            syscall::SYS_IPC_RECV,
+            endpoint,
+            request as *mut NightLightRequest as u64,
+            core::mem::size_of::<NightLightRequest>() as u64,
+            syscall::IPC_FLAG_TIMEOUT | timeout_ms,
+        )
+    };
+
+    if rc == syscall::ETIMEDOUT {
+        return Ok(false);
+    }
+    if rc < 0 {
+        return Err(rc);
+    }
+    Ok(true)
+}
+
+pub fn send_reply(destination_pid: u32, reply: &NightLightReply) -> i64 {
+    // SAFETY: `reply` est une structure POD locale envoyée telle quelle au noyau.
+    unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            destination_pid as u64,
+            reply as *const NightLightReply as u64,
+            core::mem::size_of::<NightLightReply>() as u64,
+            0,
+            0,
+            0,
+        )
+    }
+}
+
+/// Requête à `fb_server` avec réponse sur `reply_endpoint` ; `None` si
+/// l'envoi échoue ou si la réponse n'arrive pas à temps.
+pub fn fb_call(req: &mut syscall::FbRequest, reply_endpoint: u64) -> Option<syscall::FbReply> {
+    req.reply_endpoint = reply_endpoint;
+    // SAFETY: requête POD locale, taille de la struct ABI.
+    let rc = unsafe {
+        syscall::syscall6(
+            syscall::SYS_IPC_SEND,
+            syscall::FB_SERVER_ENDPOINT,
+            req as *const syscall::FbRequest as u64,
+            core::mem::size_of::<syscall::FbRequest>() as u64,
+            syscall::IPC_FLAG_TIMEOUT,
+            0,
+            0,
+        )
+    };
+    if rc < 0 {
+        return None;
+    }
+    let mut reply = syscall::FbReply::default();
+    // SAFETY: le noyau écrit dans `reply`, taille bornée à la struct ABI.
+    let rc = unsafe {
+        syscall::syscall4(
+            syscall::SYS_IPC_RECV,
+            reply_endpoint,
+            &mut reply as *mut syscall::FbReply as u64,
+            core::mem::size_of::<syscall::FbReply>() as u64,
+            syscall::IPC_FLAG_TIMEOUT | FB_REPLY_TIMEOUT_MS,
+        )
+    };
+    (rc >= 0).then_some(reply)
+}
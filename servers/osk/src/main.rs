@@ -0,0 +1,308 @@
+#![no_std]
+#![no_main]
+
+//! # osk — clavier virtuel, méthode de saisie des écrans tactiles
+//!
+//! S'enregistre comme méthode de saisie auprès de `input_server`
+//! (`INPUT_MSG_IM_REGISTER`) puis suit ses messages (`exo_services::osk`) :
+//!
+//! - `INPUT_IM_ACTIVATE` (un champ texte prend le focus) : couche choisie
+//!   d'après le type de contenu, panneau affiché ;
+//! - `INPUT_IM_DEACTIVATE`, `INPUT_IM_HIDE_PANEL` (frappe sur un clavier
+//!   physique) : panneau masqué.
+//!
+//! Le compositeur transmet les appuis tombant sur le panneau
+//! (`OSK_MSG_TAP`) et peut l'afficher ou le masquer. Les caractères partent
+//! en `INPUT_IM_COMMIT`, les touches d'édition en appui/relâchement de
+//! clavier virtuel (`INPUT_MSG_VIRTUAL_KEY`). Chaque changement de
+//! visibilité est annoncé au courtier (`INPUT_IM_PANEL`), qui prévient les
+//! propriétaires de raccourcis.
+//!
+//! Le panneau est dessiné par `fb_server` dans la bande basse de l'écran,
+//! la seule où il laisse `osk` dessiner par-dessus le compositeur ; c'est
+//! au compositeur de repeindre la bande quand le panneau disparaît.
+
+use core::panic::PanicInfo;
+
+use exo_services::osk::{
+    Key, Keyboard, Output, Rect, OSK_MSG_HEARTBEAT, OSK_MSG_HIDE, OSK_MSG_SHOW, OSK_MSG_STATUS,
+    OSK_MSG_TAP,
+};
+use exo_services::splash::{GLYPH_H, GLYPH_W};
+use exo_syscall_abi as syscall;
+
+mod protocol;
+
+use protocol::{
+    fb_call, fb_send, input_send, recv_input, recv_request, register_endpoint, send_reply,
+    OskReply, OskRequest, FB_REPLY_CHANNEL, IM_CHANNEL, OSK_CHANNEL, REPLY_TIMEOUT_MS,
+};
+
+/// Attente sur chacun des deux canaux par tour de boucle.
+const POLL_MS: u64 = 10;
+const PANEL_COLOR: u32 = 0x0020_2124;
+const KEY_COLOR: u32 = 0x003c_4043;
+/// Touches spéciales (majuscule, effacement, changement de couche…).
+const SPECIAL_COLOR: u32 = 0x002b_2e31;
+const LABEL_COLOR: u32 = 0x00e8_eaed;
+/// Espace entre deux touches, en pixels.
+const KEY_GAP: u32 = 2;
+
+struct Osk {
+    kb: Keyboard,
+    im: u64,
+    fb_reply: u64,
+}
+
+impl Osk {
+    fn fill(&self, rect: Rect, color: u32) {
+        if rect.w == 0 || rect.h == 0 {
+            return;
+        }
+        let mut req = syscall::FbRequest::zeroed();
+        req.msg_type = syscall::FB_MSG_FILL_RECT;
+        req.a = rect.x as u64 | (rect.y as u64) << 32;
+        req.b = rect.w as u64 | (rect.h as u64) << 32;
+        req.data[..4].copy_from_slice(&color.to_le_bytes());
+        let _ = fb_send(&req);
+    }
+
+    fn text(&self, x: u32, y: u32, text: &[u8], bg: u32) {
+        let mut req = syscall::FbRequest::zeroed();
+        req.msg_type = syscall::FB_MSG_DRAW_TEXT;
+        req.a = x as u64 | (y as u64) << 32;
+        req.b = LABEL_COLOR as u64 | (bg as u64) << 32;
+        let len = text.len().min(syscall::FB_TEXT_MAX - 2);
+        req.data[0] = len as u8;
+        req.data[1] = 1;
+        req.data[2..2 + len].copy_from_slice(&text[..len]);
+        let _ = fb_send(&req);
+    }
+
+    /// Panneau complet : fond, touches, étiquettes centrées.
+    fn draw(&self) {
+        self.fill(self.kb.panel(), PANEL_COLOR);
+        let mut buf = [0u8; 4];
+        for (rect, key) in self.kb.keys() {
+            let color = match key {
+                Key::Char(_) => KEY_COLOR,
+                _ => SPECIAL_COLOR,
+            };
+            let inner = Rect {
+                x: rect.x + KEY_GAP / 2,
+                y: rect.y + KEY_GAP / 2,
+                w: rect.w.saturating_sub(KEY_GAP),
+                h: rect.h.saturating_sub(KEY_GAP),
+            };
+            self.fill(inner, color);
+            let label = self.kb.label(key, &mut buf).as_bytes();
+            let label_w = label.len() as u32 * GLYPH_W;
+            if label_w <= inner.w && GLYPH_H <= inner.h {
+                self.text(
+                    inner.x + (inner.w - label_w) / 2,
+                    inner.y + (inner.h - GLYPH_H) / 2,
+                    label,
+                    color,
+                );
+            }
+        }
+    }
+
+    fn im_send(&self, op: u8, code: u16, ascii: u8, value: i16) {
+        let event = syscall::InputEventWire {
+            state: op,
+            code,
+            value,
+            ascii,
+            ..syscall::InputEventWire::default()
+        };
+        let _ = input_send(syscall::INPUT_MSG_IM_SEND, event, self.im);
+    }
+
+    fn show(&mut self) {
+        if self.kb.show() {
+            self.draw();
+            self.im_send(syscall::INPUT_IM_PANEL, 0, 0, 1);
+        }
+    }
+
+    fn hide(&mut self) {
+        if self.kb.hide() {
+            self.im_send(syscall::INPUT_IM_PANEL, 0, 0, 0);
+        }
+    }
+
+    /// Message non sollicité de `input_server` ; ses réponses (statut ≤ 0)
+    /// sont ignorées.
+    fn on_input(&mut self, msg: &syscall::InputReply) {
+        if msg.status != syscall::INPUT_STATUS_IM {
+            return;
+        }
+        match msg.event.state {
+            syscall::INPUT_IM_ACTIVATE => {
+                self.kb.activate(msg.event.code);
+                if self.kb.is_shown() {
+                    self.draw();
+                } else {
+                    self.show();
+                }
+            }
+            syscall::INPUT_IM_DEACTIVATE | syscall::INPUT_IM_HIDE_PANEL => self.hide(),
+            _ => {}
+        }
+    }
+
+    fn tap(&mut self, x: u32, y: u32) -> OskReply {
+        if self.kb.key_at(x, y).is_none() {
+            return OskReply::error(syscall::ENOENT);
+        }
+        let view = (self.kb.layer(), self.kb.shifted());
+        match self.kb.tap(x, y) {
+            Some(Output::Commit(c)) => {
+                let c = c as u32;
+                self.im_send(syscall::INPUT_IM_COMMIT, c as u16, (c >> 16) as u8, 0);
+            }
+            Some(Output::Key(code)) => {
+                for state in [syscall::INPUT_KEY_PRESSED, syscall::INPUT_KEY_RELEASED] {
+                    let event = syscall::InputEventWire {
+                        device: syscall::INPUT_DEVICE_KEYBOARD,
+                        state,
+                        code,
+                        ..syscall::InputEventWire::default()
+                    };
+                    let _ = input_send(syscall::INPUT_MSG_VIRTUAL_KEY, event, self.im);
+                }
+            }
+            // Déjà masqué par `Keyboard::tap`.
+            Some(Output::Hide) => self.im_send(syscall::INPUT_IM_PANEL, 0, 0, 0),
+            None => {}
+        }
+        if self.kb.is_shown() && (self.kb.layer(), self.kb.shifted()) != view {
+            self.draw();
+        }
+        OskReply::ok(0, 0)
+    }
+
+    fn fb_request(&self, msg_type: u32) -> i64 {
+        let mut req = syscall::FbRequest::zeroed();
+        req.msg_type = msg_type;
+        fb_call(&mut req, self.fb_reply).map_or(syscall::ETIMEDOUT, |reply| reply.status)
+    }
+
+    /// Seul le compositeur (propriétaire de l'affichage) pilote le panneau.
+    fn is_display_owner(&self, pid: u32) -> bool {
+        pid != 0 && self.fb_request(syscall::FB_MSG_DISPLAY_STATE) == pid as i64
+    }
+
+    fn dispatch(&mut self, request: &OskRequest) -> OskReply {
+        match request.msg_type {
+            OSK_MSG_HEARTBEAT => OskReply::ok(0, 0),
+            OSK_MSG_STATUS => OskReply::ok(self.kb.is_shown() as u64, self.kb.panel().h as u64),
+            OSK_MSG_TAP | OSK_MSG_SHOW | OSK_MSG_HIDE
+                if !self.is_display_owner(request.sender_pid) =>
+            {
+                OskReply::error(syscall::EPERM)
+            }
+            OSK_MSG_TAP => match (read_u32(&request.payload, 0), read_u32(&request.payload, 4)) {
+                (Some(x), Some(y)) => self.tap(x, y),
+                _ => OskReply::error(syscall::EINVAL),
+            },
+            OSK_MSG_SHOW => {
+                self.show();
+                OskReply::ok(1, self.kb.panel().h as u64)
+            }
+            OSK_MSG_HIDE => {
+                self.hide();
+                OskReply::ok(0, self.kb.panel().h as u64)
+            }
+            _ => OskReply::error(syscall::EINVAL),
+        }
+    }
+}
+
+fn read_u32(payload: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        payload.get(at..at + 4)?.try_into().ok()?,
+    ))
+}
+
+fn log(bytes: &[u8]) {
+    // SAFETY: buffer statique valide.
+    unsafe {
+        let _ = syscall::syscall3(
+            syscall::SYS_EXO_LOG,
+            bytes.as_ptr() as u64,
+            bytes.len() as u64,
+            1,
+        );
+    }
+}
+
+fn exit(code: u64) -> ! {
+    // SAFETY: fin du processus.
+    unsafe {
+        let _ = syscall::syscall1(syscall::SYS_EXIT, code);
+        let _ = syscall::syscall1(syscall::SYS_EXIT_GROUP, code);
+    }
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
+/// Enregistrement auprès de `input_server` ; la réponse arrive sur `im`.
+fn register_input_method(im: u64) -> i64 {
+    let event = syscall::InputEventWire {
+        value: 1,
+        ..syscall::InputEventWire::default()
+    };
+    let rc = input_send(syscall::INPUT_MSG_IM_REGISTER, event, im);
+    if rc < 0 {
+        return rc;
+    }
+    recv_input(im, REPLY_TIMEOUT_MS).map_or(syscall::ETIMEDOUT, |reply| reply.status)
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let fb_reply = register_endpoint(FB_REPLY_CHANNEL, b"osk_fb");
+    let im = register_endpoint(IM_CHANNEL, b"osk_im");
+    let endpoint = register_endpoint(OSK_CHANNEL, b"osk");
+    if fb_reply == 0 || im == 0 || endpoint == 0 {
+        log(b"osk: register failed\n");
+        exit(1);
+    }
+
+    let mut osk = Osk {
+        kb: Keyboard::new(1, 1),
+        im,
+        fb_reply,
+    };
+    let size = osk.fb_request(syscall::FB_MSG_DISPLAY_SIZE);
+    if size <= 0 {
+        log(b"osk: no framebuffer\n");
+        exit(0);
+    }
+    osk.kb = Keyboard::new((size >> 32) as u32, size as u32);
+    if register_input_method(im) < 0 {
+        log(b"osk: another input method is registered\n");
+        exit(0);
+    }
+
+    let mut request = OskRequest::zeroed();
+    loop {
+        while let Some(msg) = recv_input(im, POLL_MS) {
+            osk.on_input(&msg);
+        }
+        match recv_request(endpoint, &mut request, POLL_MS) {
+            Ok(true) => {}
+            Ok(false) | Err(_) => continue,
+        }
+        let reply = osk.dispatch(&request);
+        let _ = send_reply(request.sender_pid, &reply);
+    }
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    exit(127)
+}
@@ -6,7 +6,8 @@
 //! Ce serveur maintient l’état de politique demandé par les processus Ring 3 :
 //! - priorités / nice / classes de scheduling ;
 //! - budgets temps réel bornés ;
-//! - affinité CPU et métriques de yield.
+//! - affinité CPU et métriques de yield ;
+//! - boost temps réel du client en mode jeu, demandé par `game_mode`.
 
 use core::panic::PanicInfo;
 
@@ -21,9 +22,10 @@ mod thread_table;
 use policy_advisor::{PolicyAdvisor, SchedulingClass};
 use protocol::{
     read_i32, read_u32, read_u64, recv_request, register_endpoint, send_heartbeat, send_reply,
-    SchedulerReply, SchedulerRequest, SCHED_MSG_GET_STAT, SCHED_MSG_HEARTBEAT,
-    SCHED_MSG_REALTIME_ADMIT, SCHED_MSG_REALTIME_RELEASE, SCHED_MSG_SET_AFFINITY,
-    SCHED_MSG_SET_POLICY, SCHED_MSG_SET_PRIORITY, SCHED_MSG_THREAD_REGISTER, SCHED_MSG_YIELD,
+    SchedulerReply, SchedulerRequest, GAME_MODE_NAME, SCHED_MSG_GAME_BOOST, SCHED_MSG_GAME_RESTORE,
+    SCHED_MSG_GET_STAT, SCHED_MSG_HEARTBEAT, SCHED_MSG_REALTIME_ADMIT, SCHED_MSG_REALTIME_RELEASE,
+    SCHED_MSG_SET_AFFINITY, SCHED_MSG_SET_POLICY, SCHED_MSG_SET_PRIORITY,
+    SCHED_MSG_THREAD_REGISTER, SCHED_MSG_YIELD,
 };
 use realtime_admit::RealtimeAdmission;
 use stats_collector::StatsCollector;
@@ -393,6 +395,90 @@ impl SchedulerService {
             None => SchedulerReply::error(exo_syscall_abi::ENOENT),
         }
     }
+
+    /// Le boost porte sur le processus entier : son budget temps réel est
+    /// enregistré sous son PID.
+    fn handle_game_boost(&mut self, sender_pid: u32, payload: &[u8]) -> SchedulerReply {
+        if !is_game_mode(sender_pid) {
+            return SchedulerReply::error(exo_syscall_abi::EPERM);
+        }
+        let pid = match read_u32(payload, 0) {
+            Ok(0) => return SchedulerReply::error(exo_syscall_abi::EINVAL),
+            Ok(value) => value,
+            Err(err) => return SchedulerReply::error(err),
+        };
+        let class = match read_u32(payload, 4).map(SchedulingClass::from_u32) {
+            Ok(Some(class @ (SchedulingClass::Realtime | SchedulingClass::Deadline))) => class,
+            Ok(_) => return SchedulerReply::error(exo_syscall_abi::EINVAL),
+            Err(err) => return SchedulerReply::error(err),
+        };
+        let runtime_us = match read_u32(payload, 8) {
+            Ok(value) => value,
+            Err(err) => return SchedulerReply::error(err),
+        };
+        let period_us = match read_u32(payload, 12) {
+            Ok(value) => value,
+            Err(err) => return SchedulerReply::error(err),
+        };
+
+        let snapshot = match self.realtime.admit(pid, runtime_us, period_us) {
+            Ok(snapshot) => snapshot,
+            Err(err) => {
+                self.stats.note_error(pid, pid, err);
+                return SchedulerReply::error(err);
+            }
+        };
+        if let Err(err) = apply_kernel_policy(pid, class) {
+            let _ = self.realtime.release(pid);
+            self.stats.note_error(pid, pid, err);
+            return SchedulerReply::error(err);
+        }
+        SchedulerReply::ok(
+            pid as u64,
+            snapshot.utilization_ppm as u64,
+            snapshot.total_utilization_ppm as u64,
+            class.as_u32(),
+        )
+    }
+
+    fn handle_game_restore(&mut self, sender_pid: u32, payload: &[u8]) -> SchedulerReply {
+        if !is_game_mode(sender_pid) {
+            return SchedulerReply::error(exo_syscall_abi::EPERM);
+        }
+        let pid = match read_u32(payload, 0) {
+            Ok(0) => return SchedulerReply::error(exo_syscall_abi::EINVAL),
+            Ok(value) => value,
+            Err(err) => return SchedulerReply::error(err),
+        };
+        // Budget libéré même si le processus a disparu entre-temps.
+        let _ = self.realtime.release(pid);
+        match apply_kernel_policy(pid, SchedulingClass::Cfs) {
+            Ok(()) | Err(exo_syscall_abi::ESRCH) => SchedulerReply::ok(
+                pid as u64,
+                0,
+                self.realtime.total_utilization_ppm() as u64,
+                SchedulingClass::Cfs.as_u32(),
+            ),
+            Err(err) => {
+                self.stats.note_error(pid, pid, err);
+                SchedulerReply::error(err)
+            }
+        }
+    }
+}
+
+/// Vrai si `pid` est le démon enregistré sous `GAME_MODE_NAME`.
+fn is_game_mode(pid: u32) -> bool {
+    // SAFETY: nom statique valide, aucune écriture côté noyau.
+    let endpoint = unsafe {
+        exo_syscall_abi::syscall3(
+            exo_syscall_abi::SYS_IPC_LOOKUP,
+            GAME_MODE_NAME.as_ptr() as u64,
+            GAME_MODE_NAME.len() as u64,
+            0,
+        )
+    };
+    pid != 0 && endpoint > 0 && (endpoint as u64) >> 32 == pid as u64
 }
 
 static SCHEDULER_SERVICE: Mutex<SchedulerService> = Mutex::new(SchedulerService::new());
@@ -434,6 +520,8 @@ fn dispatch(request: &SchedulerRequest) -> SchedulerReply {
         SCHED_MSG_REALTIME_RELEASE => {
             service.handle_realtime_release(request.sender_pid, &request.payload)
         }
+        SCHED_MSG_GAME_BOOST => service.handle_game_boost(request.sender_pid, &request.payload),
+        SCHED_MSG_GAME_RESTORE => service.handle_game_restore(request.sender_pid, &request.payload),
         _ => SchedulerReply::error(exo_syscall_abi::EINVAL),
     }
 }
@@ -12,6 +12,13 @@ pub const SCHED_MSG_YIELD: u32 = 5;
 pub const SCHED_MSG_GET_STAT: u32 = 6;
 pub const SCHED_MSG_REALTIME_ADMIT: u32 = 7;
 pub const SCHED_MSG_REALTIME_RELEASE: u32 = 8;
+/// Game mode, from the `game_mode` daemon only. Payload: pid (u32), class
+/// (u32, Realtime or Deadline), runtime_us (u32), period_us (u32).
+pub const SCHED_MSG_GAME_BOOST: u32 = 9;
+/// Payload: pid (u32). Back to CFS, real-time budget released.
+pub const SCHED_MSG_GAME_RESTORE: u32 = 10;
+/// Nom sous lequel le démon du mode jeu s'enregistre.
+pub const GAME_MODE_NAME: &[u8] = b"game_mode";
 
 #[repr(C)]
 pub struct SchedulerRequest {
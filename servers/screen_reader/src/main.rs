@@ -0,0 +1,213 @@
+#![no_std]
+#![no_main]
+
+//! # screen_reader — lecteur d'écran
+//!
+//! Reçoit les arbres d'accessibilité publiés par les applications de la
+//! boîte à outils (`exo_services::a11y`) :
+//!
+//! - `A11Y_MSG_BEGIN` puis un `A11Y_MSG_NODE` par nœud remplacent l'arbre
+//!   de l'expéditeur ; les arbres des dernières applications sont gardés ;
+//! - `A11Y_MSG_EVENT` (focus, nom, valeur, état) devient un énoncé confié au
+//!   moteur de synthèse : description complète du nœud qui prend le focus,
+//!   changements du nœud focalisé de l'application active seulement ;
+//! - `A11Y_MSG_FORGET` oublie l'arbre d'une fenêtre fermée.
+//!
+//! Le moteur de synthèse est interchangeable (trait `Speech`) et choisi par
+//! `/etc/exo/screen_reader.conf` : `console` écrit les énoncés sur la sortie
+//! standard en attendant un vrai moteur, `none` se tait.
+//!
+//! Les messages ne sont pas authentifiés : chaque processus ne remplace que
+//! son propre arbre, et une application au premier plan peut déjà afficher
+//! ce qu'elle veut.
+
+use core::panic::PanicInfo;
+
+use exo_services::a11y::{
+    decode_begin, Backend, Config, Event, NodeRecord, Priority, Reader, Silent, Speech,
+    A11Y_MSG_BEGIN, A11Y_MSG_EVENT, A11Y_MSG_FORGET, A11Y_MSG_HEARTBEAT, A11Y_MSG_NODE,
+    A11Y_MSG_STATS, UTTERANCE_MAX,
+};
+use exo_syscall_abi as syscall;
+use spin::Mutex;
+
+mod protocol;
+
+use protocol::{
+    recv_request, register_endpoint, send_reply, ScreenReaderReply, ScreenReaderRequest,
+};
+
+const CONFIG_PATH: &[u8] = b"/etc/exo/screen_reader.conf\0";
+const CONFIG_MAX: usize = 1024;
+const STDOUT: u64 = 1;
+
+/// Énoncés écrits sur la sortie standard, une ligne chacun.
+struct Console;
+
+impl Speech for Console {
+    fn speak(&mut self, text: &str, priority: Priority) {
+        let mut line = [0u8; UTTERANCE_MAX + 3];
+        let mut len = 0;
+        if priority == Priority::Assertive {
+            line[..2].copy_from_slice(b"! ");
+            len = 2;
+        }
+        let text = &text.as_bytes()[..text.len().min(UTTERANCE_MAX)];
+        line[len..len + text.len()].copy_from_slice(text);
+        len += text.len();
+        line[len] = b'\n';
+        // SAFETY: lecture bornée à la ligne formatée.
+        let _ = unsafe {
+            syscall::syscall3(
+                syscall::SYS_WRITE,
+                STDOUT,
+                line.as_ptr() as u64,
+                (len + 1) as u64,
+            )
+        };
+    }
+
+    /// Rien à interrompre : chaque ligne part d'un bloc.
+    fn stop(&mut self) {}
+}
+
+struct ScreenReader {
+    reader: Reader,
+    config: Config,
+}
+
+static SERVICE: Mutex<ScreenReader> = Mutex::new(ScreenReader::new());
+
+impl ScreenReader {
+    const fn new() -> Self {
+        Self {
+            reader: Reader::new(),
+            config: Config::new(),
+        }
+    }
+
+    fn load(&mut self) {
+        let mut buf = [0u8; CONFIG_MAX];
+        let mut len = 0;
+        // SAFETY: chemin statique terminé par NUL.
+        let fd = unsafe {
+            syscall::syscall2(
+                syscall::SYS_OPEN,
+                CONFIG_PATH.as_ptr() as u64,
+                syscall::O_RDONLY,
+            )
+        };
+        if fd < 0 {
+            return;
+        }
+        while len < CONFIG_MAX {
+            // SAFETY: écriture bornée à la fin du buffer.
+            let n = unsafe {
+                syscall::syscall3(
+                    syscall::SYS_READ,
+                    fd as u64,
+                    buf[len..].as_mut_ptr() as u64,
+                    (CONFIG_MAX - len) as u64,
+                )
+            };
+            if n <= 0 {
+                break;
+            }
+            len += n as usize;
+        }
+        // SAFETY: fermeture du descripteur ouvert ci-dessus.
+        let _ = unsafe { syscall::syscall1(syscall::SYS_CLOSE, fd as u64) };
+        if let Ok(text) = core::str::from_utf8(&buf[..len]) {
+            self.config = Config::parse(text);
+        }
+    }
+
+    fn handle_event(&mut self, sender_pid: u32, payload: &[u8]) {
+        let Some(event) = Event::decode(payload) else {
+            return;
+        };
+        if !self.config.enabled {
+            return;
+        }
+        let speech: &mut dyn Speech = match self.config.speech {
+            Backend::Console => &mut Console,
+            Backend::None => &mut Silent,
+        };
+        self.reader.event(sender_pid, event, speech);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let endpoint = register_endpoint();
+    SERVICE.lock().load();
+    let mut request = ScreenReaderRequest::zeroed();
+
+    loop {
+        if endpoint == 0 {
+            continue;
+        }
+        match recv_request(endpoint, &mut request) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => continue,
+        }
+
+        if let Some(reply) = dispatch(&request) {
+            let _ = send_reply(request.sender_pid, &reply);
+        }
+    }
+}
+
+/// `None` : notification, sans réponse.
+fn dispatch(request: &ScreenReaderRequest) -> Option<ScreenReaderReply> {
+    let mut service = SERVICE.lock();
+    let sender = request.sender_pid;
+
+    let reply = match request.msg_type {
+        A11Y_MSG_HEARTBEAT => ScreenReaderReply::ok(
+            service.config.enabled as u64,
+            service.reader.apps() as u64,
+            0,
+            0,
+        ),
+        A11Y_MSG_BEGIN => {
+            if let Some((window, _)) = decode_begin(&request.payload) {
+                service.reader.begin(sender, window);
+            }
+            return None;
+        }
+        A11Y_MSG_NODE => {
+            if let Some(record) = NodeRecord::decode(&request.payload) {
+                service.reader.node(sender, &record);
+            }
+            return None;
+        }
+        A11Y_MSG_EVENT => {
+            service.handle_event(sender, &request.payload);
+            return None;
+        }
+        A11Y_MSG_FORGET => {
+            service.reader.forget(sender);
+            return None;
+        }
+        A11Y_MSG_STATS => ScreenReaderReply::ok(
+            service.reader.spoken,
+            service.reader.dropped,
+            service.reader.apps() as u64,
+            0,
+        ),
+        _ => ScreenReaderReply::error(syscall::EINVAL),
+    };
+    Some(reply)
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        // SAFETY: panic terminale pour un serveur no_std monothread.
+        unsafe {
+            core::arch::asm!("hlt", options(nostack, nomem));
+        }
+    }
+}
@@ -0,0 +1,214 @@
+#![no_std]
+#![no_main]
+
+//! # sleep_monitor — notification de reprise après veille
+//!
+//! Repère chaque reprise (`exo_services::resume::Detector`) : génération de
+//! reprise du noyau (`SYS_EXO_SLEEP_STATE`), ou réveil bien plus tardif que
+//! le délai d'attente quand le noyau n'a rien vu (VM suspendue par l'hôte).
+//! Le démon se réveille au moins toutes les `TICK_MS`.
+//!
+//! À chaque reprise, `RESUME_NOTIFY_RESUMED` part aux abonnés (serveur
+//! audio : réouverture des périphériques) et à `network_server`, dont
+//! l'endpoint bien connu ne peut pas s'abonner : renouvellement du bail DHCP
+//! et, après une longue veille, remise à zéro des connexions TCP. La reprise
+//! est aussi notée dans le journal d'événements (`event_journal`).
+
+use core::panic::PanicInfo;
+
+use exo_services::events::{self, Kind};
+use exo_services::resume::{
+    self, Detector, KernelSleep, Resumed, CLOCK_BOOTTIME, RESUME_MSG_HEARTBEAT, RESUME_MSG_STATUS,
+    RESUME_MSG_SUBSCRIBE, RESUME_MSG_UNSUBSCRIBE, RESUME_NOTIFY_RESUMED, SUBSCRIBE_RESUMED,
+    TICK_MS,
+};
+use exo_services::subscribers::{SubscribeError, Subscribers, MAX_SUBSCRIBERS};
+use exo_syscall_abi as syscall;
+use spin::Mutex;
+
+mod protocol;
+
+use protocol::{
+    recv_request, register_endpoint, send_notice, send_reply, SleepMonitorReply,
+    SleepMonitorRequest,
+};
+
+#[repr(C)]
+#[derive(Default)]
+struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+struct SleepMonitorService {
+    detector: Detector,
+    subscribers: Subscribers,
+}
+
+static SERVICE: Mutex<SleepMonitorService> = Mutex::new(SleepMonitorService::new());
+
+impl SleepMonitorService {
+    const fn new() -> Self {
+        Self {
+            detector: Detector::new(),
+            subscribers: Subscribers::new(SUBSCRIBE_RESUMED),
+        }
+    }
+
+    /// Un réveil, après au plus `timeout_ms` d'attente.
+    fn wake(&mut self, timeout_ms: u64) {
+        let Some(now) = boottime_ns() else {
+            return;
+        };
+        if let Some(resumed) = self.detector.observe(now, timeout_ms, kernel_sleep()) {
+            self.resumed(resumed);
+        }
+    }
+
+    fn resumed(&mut self, resumed: Resumed) {
+        let mut payload = [0u8; Resumed::LEN];
+        let _ = resumed.encode(&mut payload);
+        let mut gone = [0u32; MAX_SUBSCRIBERS];
+        let mut n_gone = 0;
+        for subscriber in self.subscribers.with(SUBSCRIBE_RESUMED) {
+            let rc = send_notice(subscriber.endpoint, RESUME_NOTIFY_RESUMED, &payload);
+            if rc < 0 && rc != syscall::EAGAIN && rc != syscall::ETIMEDOUT {
+                gone[n_gone] = subscriber.pid;
+                n_gone += 1;
+            }
+        }
+        for &pid in &gone[..n_gone] {
+            self.subscribers.unsubscribe(pid);
+        }
+        if let Some(network) = lookup_endpoint(b"network_server") {
+            let _ = send_notice(network, RESUME_NOTIFY_RESUMED, &payload);
+        }
+        if let Some(journal) = lookup_endpoint(b"event_journal") {
+            let mut record = [0u8; events::RECORD_MAX];
+            if let Some(len) =
+                events::encode_record(Kind::Resume, "", resumed.slept_ms, &mut record)
+            {
+                let _ = send_notice(journal, events::EVENTS_MSG_RECORD, &record[..len]);
+            }
+        }
+    }
+
+    fn status_reply(&self) -> SleepMonitorReply {
+        SleepMonitorReply::ok(
+            self.detector.resumes(),
+            self.detector.last_slept_ms(),
+            self.subscribers.len() as u64,
+            0,
+        )
+    }
+
+    fn handle_subscribe(&mut self, sender_pid: u32, payload: &[u8]) -> SleepMonitorReply {
+        let Some((endpoint, flags)) = resume::decode_subscribe(payload) else {
+            return SleepMonitorReply::error(syscall::EINVAL);
+        };
+        match self.subscribers.subscribe(sender_pid, endpoint, flags) {
+            Ok(()) => {
+                SleepMonitorReply::ok(self.detector.resumes(), self.detector.last_slept_ms(), 0, 0)
+            }
+            Err(SubscribeError::BadFlags) => SleepMonitorReply::error(syscall::EINVAL),
+            Err(SubscribeError::ForeignEndpoint) => SleepMonitorReply::error(syscall::EPERM),
+            Err(SubscribeError::Full) => SleepMonitorReply::error(syscall::ENOSPC),
+        }
+    }
+}
+
+fn boottime_ns() -> Option<u64> {
+    let mut ts = Timespec::default();
+    // SAFETY: le noyau écrit dans `ts`, structure locale.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_CLOCK_GETTIME,
+            CLOCK_BOOTTIME,
+            &mut ts as *mut Timespec as u64,
+        )
+    };
+    if rc != 0 || ts.tv_sec < 0 {
+        return None;
+    }
+    Some(ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64)
+}
+
+/// État de veille du noyau ; vide s'il ne le fournit pas (la détection par
+/// écart reste active).
+fn kernel_sleep() -> KernelSleep {
+    let mut buf = [0u8; KernelSleep::LEN];
+    // SAFETY: le noyau écrit au plus `buf.len()` octets dans `buf`.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_EXO_SLEEP_STATE,
+            buf.as_mut_ptr() as u64,
+            buf.len() as u64,
+        )
+    };
+    if rc < 0 {
+        return KernelSleep::default();
+    }
+    KernelSleep::decode(&buf).unwrap_or_default()
+}
+
+fn lookup_endpoint(name: &[u8]) -> Option<u64> {
+    // SAFETY: nom statique valide pendant l'appel.
+    let rc = unsafe {
+        syscall::syscall2(
+            syscall::SYS_IPC_LOOKUP,
+            name.as_ptr() as u64,
+            name.len() as u64,
+        )
+    };
+    (rc > 0).then_some(rc as u64)
+}
+
+#[no_mangle]
+pub extern "C" fn _start() -> ! {
+    let endpoint = register_endpoint();
+    SERVICE.lock().wake(TICK_MS);
+    let mut request = SleepMonitorRequest::zeroed();
+
+    loop {
+        if endpoint == 0 {
+            continue;
+        }
+        let received = recv_request(endpoint, &mut request, TICK_MS);
+        SERVICE.lock().wake(TICK_MS);
+        match received {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => continue,
+        }
+
+        let reply = dispatch(&request);
+        let _ = send_reply(request.sender_pid, &reply);
+    }
+}
+
+fn dispatch(request: &SleepMonitorRequest) -> SleepMonitorReply {
+    let mut service = SERVICE.lock();
+
+    match request.msg_type {
+        RESUME_MSG_HEARTBEAT | RESUME_MSG_STATUS => service.status_reply(),
+        RESUME_MSG_SUBSCRIBE => service.handle_subscribe(request.sender_pid, &request.payload),
+        RESUME_MSG_UNSUBSCRIBE => {
+            if service.subscribers.unsubscribe(request.sender_pid) {
+                SleepMonitorReply::ok(0, 0, 0, 0)
+            } else {
+                SleepMonitorReply::error(syscall::ENOENT)
+            }
+        }
+        _ => SleepMonitorReply::error(syscall::EINVAL),
+    }
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        // SAFETY: panic terminale pour un serveur no_std monothread.
+        unsafe {
+            core::arch::asm!("hlt", options(nostack, nomem));
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Canaux de connexion (RFC 4254) : table des canaux de session et
+//! comptabilité des fenêtres.
+
+pub const MAX_CHANNELS: usize = 4;
+/// Fenêtre annoncée au pair ; on la recharge quand elle passe sous la moitié.
+pub const LOCAL_WINDOW: u32 = 64 * 1024;
+/// Taille max des données par paquet annoncée au pair.
+pub const LOCAL_MAX_PACKET: u32 = 4096;
+
+/// Paramètres d'une requête `pty-req` (RFC 4254 §6.2).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PtyRequest {
+    term: [u8; 32],
+    term_len: u8,
+    pub cols: u32,
+    pub rows: u32,
+    pub width_px: u32,
+    pub height_px: u32,
+}
+
+impl PtyRequest {
+    pub fn new(term: &[u8], cols: u32, rows: u32, width_px: u32, height_px: u32) -> Self {
+        let len = term.len().min(32);
+        let mut buf = [0u8; 32];
+        buf[..len].copy_from_slice(&term[..len]);
+        Self {
+            term: buf,
+            term_len: len as u8,
+            cols,
+            rows,
+            width_px,
+            height_px,
+        }
+    }
+
+    /// Valeur de `TERM` demandée par le client (tronquée à 32 octets).
+    pub fn term(&self) -> &[u8] {
+        &self.term[..self.term_len as usize]
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChannelState {
+    /// Côté client : CHANNEL_OPEN envoyé, confirmation attendue.
+    Opening,
+    /// Ouvert, pas encore de shell/exec.
+    Open,
+    Running,
+    /// CLOSE envoyé, on attend celui du pair.
+    Closing,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Channel {
+    pub remote_id: u32,
+    pub state: ChannelState,
+    /// Octets qu'on a encore le droit d'envoyer.
+    pub remote_window: u32,
+    pub remote_max_packet: u32,
+    /// Octets que le pair a encore le droit de nous envoyer.
+    pub local_window: u32,
+    pub pty: Option<PtyRequest>,
+    pub eof_sent: bool,
+    pub eof_received: bool,
+}
+
+pub struct ChannelTable {
+    slots: [Option<Channel>; MAX_CHANNELS],
+}
+
+impl Default for ChannelTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChannelTable {
+    pub const fn new() -> Self {
+        Self {
+            slots: [None; MAX_CHANNELS],
+        }
+    }
+
+    /// Ouvre un canal ; l'identifiant local est l'indice dans la table.
+    pub fn open(
+        &mut self,
+        remote_id: u32,
+        remote_window: u32,
+        remote_max_packet: u32,
+    ) -> Option<u32> {
+        let id = self.slots.iter().position(Option::is_none)?;
+        self.slots[id] = Some(Channel {
+            remote_id,
+            state: ChannelState::Open,
+            remote_window,
+            remote_max_packet,
+            local_window: LOCAL_WINDOW,
+            pty: None,
+            eof_sent: false,
+            eof_received: false,
+        });
+        Some(id as u32)
+    }
+
+    /// Côté client : réserve un identifiant avant CHANNEL_OPEN ; la fenêtre
+    /// du pair est inconnue jusqu'à la confirmation.
+    pub fn reserve(&mut self) -> Option<u32> {
+        let id = self.open(0, 0, 0)?;
+        if let Some(ch) = self.get_mut(id) {
+            ch.state = ChannelState::Opening;
+        }
+        Some(id)
+    }
+
+    /// CHANNEL_OPEN_CONFIRMATION reçu pour un canal réservé.
+    pub fn confirm(
+        &mut self,
+        id: u32,
+        remote_id: u32,
+        remote_window: u32,
+        remote_max_packet: u32,
+    ) -> bool {
+        match self.get_mut(id) {
+            Some(ch) if ch.state == ChannelState::Opening => {
+                ch.remote_id = remote_id;
+                ch.remote_window = remote_window;
+                ch.remote_max_packet = remote_max_packet;
+                ch.state = ChannelState::Open;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Channel> {
+        self.slots.get(id as usize)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut Channel> {
+        self.slots.get_mut(id as usize)?.as_mut()
+    }
+
+    pub fn remove(&mut self, id: u32) -> Option<Channel> {
+        self.slots.get_mut(id as usize)?.take()
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.is_some())
+            .map(|(i, _)| i as u32)
+    }
+
+    /// Débite la fenêtre locale de `len` octets reçus. Retourne le
+    /// rechargement à annoncer (WINDOW_ADJUST), ou `None` si le pair a
+    /// dépassé sa fenêtre.
+    pub fn consume_local(&mut self, id: u32, len: u32) -> Option<u32> {
+        let ch = self.get_mut(id)?;
+        ch.local_window = ch.local_window.checked_sub(len)?;
+        if ch.local_window < LOCAL_WINDOW / 2 {
+            let refill = LOCAL_WINDOW - ch.local_window;
+            ch.local_window = LOCAL_WINDOW;
+            return Some(refill);
+        }
+        Some(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_refill_at_half() {
+        let mut table = ChannelTable::new();
+        let id = table.open(7, 1000, 512).unwrap();
+        assert_eq!(table.consume_local(id, LOCAL_WINDOW / 4), Some(0));
+        assert_eq!(
+            table.consume_local(id, LOCAL_WINDOW / 4 + 1),
+            Some(LOCAL_WINDOW / 2 + 1)
+        );
+        assert_eq!(table.get(id).unwrap().local_window, LOCAL_WINDOW);
+        assert_eq!(table.consume_local(id, LOCAL_WINDOW + 1), None);
+        for _ in 1..MAX_CHANNELS {
+            table.open(0, 0, 0).unwrap();
+        }
+        assert_eq!(table.open(0, 0, 0), None);
+        assert!(table.remove(id).is_some());
+        assert_eq!(table.ids().count(), MAX_CHANNELS - 1);
+    }
+}
@@ -0,0 +1,1099 @@
+//! Session cliente : le pendant de `server` pour les sessions interactives
+//! (terminal), `exec` (scp) et le sous-système `sftp`.
+//!
+//! Même modèle sans E/S que le serveur : l'application passe les octets de
+//! la socket à `receive()` et écrit `output()`. La clé d'hôte présentée est
+//! soumise à `ClientHost::check_host_key` (cf. `known_hosts`) avant toute
+//! authentification ; un re-échange de clés doit présenter la même.
+
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+
+use crate::channel::{ChannelState, ChannelTable, PtyRequest, LOCAL_MAX_PACKET, LOCAL_WINDOW};
+use crate::kex::{self, ExchangeTranscript, KexMethod, SessionKeys, HOST_KEY_NAME};
+use crate::transport::{self, Deferred, PacketIo, GCM_TAG_LEN, MAX_PACKET_LEN, REKEY_BYTES};
+use crate::wire::{Reader, Writer};
+use crate::{disconnect, msg, SshError};
+
+pub const CLIENT_VERSION: &[u8] = b"SSH-2.0-ExoSSH_0.1";
+pub const IN_BUF: usize = 4 + MAX_PACKET_LEN + GCM_TAG_LEN;
+pub const OUT_BUF: usize = 16 * 1024;
+const MAX_VERSION_LINE: usize = 255;
+const MAX_SERVER_KEXINIT: usize = 4096;
+const MAX_CLIENT_KEXINIT: usize = 512;
+/// KEX_ECDH_INIT hybride et sa mise en paquet.
+const REPLY_ROOM: usize = 1 + 4 + kex::MAX_CLIENT_EPHEMERAL + 64;
+
+/// Ce que la session délègue à l'application (terminal, gestionnaire de
+/// fichiers). Les identifiants de canal sont les identifiants locaux.
+pub trait ClientHost {
+    fn fill_random(&mut self, buf: &mut [u8]);
+    /// Clé d'hôte dont la signature de l'échange vient d'être vérifiée :
+    /// `false` coupe la connexion.
+    fn check_host_key(&mut self, key: &VerifyingKey) -> bool;
+    fn banner(&mut self, _text: &[u8]) {}
+    /// Méthode refusée ; `methods` liste celles qui restent possibles.
+    fn auth_failed(&mut self, _methods: &[u8]) {}
+    fn channel_opened(&mut self, channel: u32, ok: bool);
+    /// Réponse à une requête de canal envoyée avec `want_reply`.
+    fn request_result(&mut self, _channel: u32, _ok: bool) {}
+    fn channel_data(&mut self, channel: u32, data: &[u8]);
+    fn channel_stderr(&mut self, _channel: u32, _data: &[u8]) {}
+    fn exit_status(&mut self, _channel: u32, _status: u32) {}
+    fn channel_eof(&mut self, _channel: u32) {}
+    fn channel_closed(&mut self, channel: u32);
+}
+
+/// Re-échange de clés, même découpage que côté serveur.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Rekey {
+    Idle,
+    /// Notre KEXINIT est parti, le serveur n'a pas encore envoyé le sien.
+    Sent,
+    /// KEX_ECDH_INIT envoyé, réponse attendue.
+    Ecdh,
+    /// Notre NEWKEYS est parti, celui du serveur est attendu.
+    NewKeys,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Phase {
+    Version,
+    KexInit,
+    KexEcdh,
+    NewKeys,
+    Service,
+    /// Prêt pour une tentative d'authentification.
+    Auth,
+    AuthPending,
+    Connected,
+    Closed,
+}
+
+struct Core<H: ClientHost> {
+    host: H,
+    io: PacketIo,
+    phase: Phase,
+    out: [u8; OUT_BUF],
+    out_len: usize,
+    server_version: [u8; MAX_VERSION_LINE],
+    server_version_len: usize,
+    client_kexinit: [u8; MAX_CLIENT_KEXINIT],
+    client_kexinit_len: usize,
+    server_kexinit: [u8; MAX_SERVER_KEXINIT],
+    server_kexinit_len: usize,
+    kex: KexMethod,
+    /// Aléa de l'échange en cours ; effacé dès K calculé.
+    kex_seed: [u8; kex::KEX_SEED_LEN],
+    rekey: Rekey,
+    rekey_limit: u64,
+    deferred: Deferred,
+    /// Clé d'hôte acceptée au premier échange.
+    host_key: Option<VerifyingKey>,
+    session_id: [u8; 32],
+    incoming_keys: Option<SessionKeys>,
+    channels: ChannelTable,
+}
+
+pub struct ClientSession<H: ClientHost> {
+    rx: [u8; IN_BUF],
+    rx_len: usize,
+    core: Core<H>,
+}
+
+impl<H: ClientHost> ClientSession<H> {
+    /// Prépare la ligne de version et le KEXINIT du client dans `output()`.
+    pub fn new(host: H) -> Self {
+        let mut core = Core {
+            host,
+            io: PacketIo::new(),
+            phase: Phase::Version,
+            out: [0; OUT_BUF],
+            out_len: 0,
+            server_version: [0; MAX_VERSION_LINE],
+            server_version_len: 0,
+            client_kexinit: [0; MAX_CLIENT_KEXINIT],
+            client_kexinit_len: 0,
+            server_kexinit: [0; MAX_SERVER_KEXINIT],
+            server_kexinit_len: 0,
+            kex: KexMethod::Curve25519,
+            kex_seed: [0; kex::KEX_SEED_LEN],
+            rekey: Rekey::Idle,
+            rekey_limit: REKEY_BYTES,
+            deferred: Deferred::new(),
+            host_key: None,
+            session_id: [0; 32],
+            incoming_keys: None,
+            channels: ChannelTable::new(),
+        };
+        core.out[..CLIENT_VERSION.len()].copy_from_slice(CLIENT_VERSION);
+        core.out[CLIENT_VERSION.len()..CLIENT_VERSION.len() + 2].copy_from_slice(b"\r\n");
+        core.out_len = CLIENT_VERSION.len() + 2;
+        let _ = core.send_kexinit();
+        Self {
+            rx: [0; IN_BUF],
+            rx_len: 0,
+            core,
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.core.phase
+    }
+
+    pub fn host(&self) -> &H {
+        &self.core.host
+    }
+
+    pub fn host_mut(&mut self) -> &mut H {
+        &mut self.core.host
+    }
+
+    pub fn output(&self) -> &[u8] {
+        &self.core.out[..self.core.out_len]
+    }
+
+    pub fn advance_output(&mut self, n: usize) {
+        let n = n.min(self.core.out_len);
+        self.core.out.copy_within(n..self.core.out_len, 0);
+        self.core.out_len -= n;
+        if let Err(e) = self.core.flush_deferred() {
+            self.core.fail(e);
+        }
+    }
+
+    /// Même contrat que `ServerSession::rekey`.
+    pub fn rekey(&mut self) -> Result<(), SshError> {
+        if !self.core.established() {
+            return Err(SshError::Protocol);
+        }
+        if self.core.rekey == Rekey::Idle {
+            self.core.start_rekey()?;
+        }
+        Ok(())
+    }
+
+    pub fn rekeying(&self) -> bool {
+        self.core.rekey != Rekey::Idle
+    }
+
+    pub fn set_rekey_limit(&mut self, bytes: u64) {
+        self.core.rekey_limit = bytes;
+    }
+
+    /// Même contrat que `ServerSession::receive`.
+    pub fn receive(&mut self, bytes: &[u8]) -> Result<usize, SshError> {
+        if self.core.phase == Phase::Closed {
+            return Err(SshError::Disconnected);
+        }
+        let n = bytes.len().min(IN_BUF - self.rx_len);
+        self.rx[self.rx_len..self.rx_len + n].copy_from_slice(&bytes[..n]);
+        self.rx_len += n;
+        match self.process() {
+            Ok(()) => Ok(n),
+            Err(e) => {
+                self.core.fail(e);
+                Err(e)
+            }
+        }
+    }
+
+    fn process(&mut self) -> Result<(), SshError> {
+        loop {
+            if self.core.phase == Phase::Closed {
+                return Err(SshError::Disconnected);
+            }
+            if self.core.phase == Phase::Version {
+                let Some(eol) = self.rx[..self.rx_len].iter().position(|&b| b == b'\n') else {
+                    if self.rx_len >= MAX_VERSION_LINE {
+                        return Err(SshError::Protocol);
+                    }
+                    return Ok(());
+                };
+                self.core.version_line(&self.rx[..eol])?;
+                self.rx.copy_within(eol + 1..self.rx_len, 0);
+                self.rx_len -= eol + 1;
+                continue;
+            }
+            if OUT_BUF - self.core.out_len < REPLY_ROOM {
+                return Ok(());
+            }
+            let Some((used, at, len)) = self.core.io.open(&mut self.rx[..self.rx_len])? else {
+                return Ok(());
+            };
+            self.core.dispatch(&self.rx[at..at + len])?;
+            self.rx.copy_within(used..self.rx_len, 0);
+            self.rx_len -= used;
+            self.core.maybe_rekey()?;
+        }
+    }
+
+    fn begin_auth(&mut self) -> Result<(), SshError> {
+        if self.core.phase != Phase::Auth {
+            return Err(SshError::Protocol);
+        }
+        self.core.phase = Phase::AuthPending;
+        Ok(())
+    }
+
+    pub fn auth_password(&mut self, user: &[u8], password: &[u8]) -> Result<(), SshError> {
+        self.begin_auth()?;
+        self.core.send_with(|w| {
+            w.u8(msg::USERAUTH_REQUEST)?
+                .string(user)?
+                .string(b"ssh-connection")?
+                .string(b"password")?
+                .bool(false)?
+                .string(password)?;
+            Ok(())
+        })
+    }
+
+    /// Requête signée d'emblée, sans passer par la question PK_OK.
+    pub fn auth_publickey(&mut self, user: &[u8], key: &SigningKey) -> Result<(), SshError> {
+        self.begin_auth()?;
+        let blob = kex::key_blob(&key.verifying_key());
+        let mut buf = [0u8; 512];
+        let mut w = Writer::new(&mut buf);
+        w.string(&self.core.session_id)?;
+        let start = w.len();
+        w.u8(msg::USERAUTH_REQUEST)?
+            .string(user)?
+            .string(b"ssh-connection")?
+            .string(b"publickey")?
+            .bool(true)?
+            .string(HOST_KEY_NAME)?
+            .string(&blob)?;
+        let signed = w.len();
+        let sig = kex::signature_blob(&key.sign(&buf[..signed]));
+        let mut w = Writer::new(&mut buf[signed..]);
+        w.string(&sig)?;
+        let end = signed + w.len();
+        self.core.send(&buf[start..end])
+    }
+
+    /// Ouvre un canal "session" ; `ClientHost::channel_opened` signale
+    /// la réponse du serveur.
+    pub fn open_session(&mut self) -> Result<u32, SshError> {
+        if self.core.phase != Phase::Connected {
+            return Err(SshError::Protocol);
+        }
+        let id = self.core.channels.reserve().ok_or(SshError::BufferFull)?;
+        self.core.send_with(|w| {
+            w.u8(msg::CHANNEL_OPEN)?
+                .string(b"session")?
+                .u32(id)?
+                .u32(LOCAL_WINDOW)?
+                .u32(LOCAL_MAX_PACKET)?;
+            Ok(())
+        })?;
+        Ok(id)
+    }
+
+    fn channel_request<F>(&mut self, channel: u32, name: &[u8], args: F) -> Result<(), SshError>
+    where
+        F: FnOnce(&mut Writer<'_>) -> Result<(), SshError>,
+    {
+        let remote = self.core.open_remote_id(channel)?;
+        self.core.send_with(|w| {
+            w.u8(msg::CHANNEL_REQUEST)?
+                .u32(remote)?
+                .string(name)?
+                .bool(true)?;
+            args(w)
+        })
+    }
+
+    pub fn request_pty(&mut self, channel: u32, pty: &PtyRequest) -> Result<(), SshError> {
+        self.channel_request(channel, b"pty-req", |w| {
+            w.string(pty.term())?
+                .u32(pty.cols)?
+                .u32(pty.rows)?
+                .u32(pty.width_px)?
+                .u32(pty.height_px)?
+                // Aucun mode : TTY_OP_END seul.
+                .string(&[0])?;
+            Ok(())
+        })
+    }
+
+    pub fn request_shell(&mut self, channel: u32) -> Result<(), SshError> {
+        self.channel_request(channel, b"shell", |_| Ok(()))
+    }
+
+    pub fn exec(&mut self, channel: u32, command: &[u8]) -> Result<(), SshError> {
+        self.channel_request(channel, b"exec", |w| {
+            w.string(command)?;
+            Ok(())
+        })
+    }
+
+    /// `sftp` pour le transfert de fichiers (cf. `sftp`).
+    pub fn request_subsystem(&mut self, channel: u32, name: &[u8]) -> Result<(), SshError> {
+        self.channel_request(channel, b"subsystem", |w| {
+            w.string(name)?;
+            Ok(())
+        })
+    }
+
+    /// Redimensionnement du terminal ; pas de réponse attendue.
+    pub fn window_change(&mut self, channel: u32, cols: u32, rows: u32) -> Result<(), SshError> {
+        let remote = self.core.open_remote_id(channel)?;
+        self.core.send_with(|w| {
+            w.u8(msg::CHANNEL_REQUEST)?
+                .u32(remote)?
+                .string(b"window-change")?
+                .bool(false)?
+                .u32(cols)?
+                .u32(rows)?
+                .u32(0)?
+                .u32(0)?;
+            Ok(())
+        })
+    }
+
+    /// Même contrat que `ServerSession::send_data`.
+    pub fn send_data(&mut self, channel: u32, data: &[u8]) -> Result<usize, SshError> {
+        let core = &mut self.core;
+        let ch = core.channels.get(channel).ok_or(SshError::Protocol)?;
+        if ch.eof_sent || matches!(ch.state, ChannelState::Opening | ChannelState::Closing) {
+            return Err(SshError::Protocol);
+        }
+        core.maybe_rekey()?;
+        if core.holds_output() {
+            return Ok(0);
+        }
+        let ch = core.channels.get(channel).ok_or(SshError::Protocol)?;
+        let overhead = 9 + 5 + 32 + GCM_TAG_LEN;
+        let room = (OUT_BUF - core.out_len).saturating_sub(overhead);
+        let n = data
+            .len()
+            .min(ch.remote_window as usize)
+            .min(ch.remote_max_packet as usize)
+            .min(LOCAL_MAX_PACKET as usize)
+            .min(room);
+        if n == 0 {
+            return Ok(0);
+        }
+        let remote = ch.remote_id;
+        let mut buf = [0u8; 9 + LOCAL_MAX_PACKET as usize];
+        let mut w = Writer::new(&mut buf);
+        w.u8(msg::CHANNEL_DATA)?.u32(remote)?.string(&data[..n])?;
+        let len = w.len();
+        core.send(&buf[..len])?;
+        if let Some(ch) = core.channels.get_mut(channel) {
+            ch.remote_window -= n as u32;
+        }
+        Ok(n)
+    }
+
+    pub fn send_eof(&mut self, channel: u32) -> Result<(), SshError> {
+        let remote = self.core.open_remote_id(channel)?;
+        self.core.send_with(|w| {
+            w.u8(msg::CHANNEL_EOF)?.u32(remote)?;
+            Ok(())
+        })?;
+        if let Some(ch) = self.core.channels.get_mut(channel) {
+            ch.eof_sent = true;
+        }
+        Ok(())
+    }
+
+    pub fn close(&mut self, channel: u32) -> Result<(), SshError> {
+        let remote = self.core.open_remote_id(channel)?;
+        self.core.send_with(|w| {
+            w.u8(msg::CHANNEL_CLOSE)?.u32(remote)?;
+            Ok(())
+        })?;
+        if let Some(ch) = self.core.channels.get_mut(channel) {
+            ch.state = ChannelState::Closing;
+        }
+        Ok(())
+    }
+
+    pub fn disconnect(&mut self, reason: u32, description: &str) {
+        self.core.send_disconnect(reason, description);
+    }
+}
+
+impl<H: ClientHost> Core<H> {
+    fn send(&mut self, payload: &[u8]) -> Result<(), SshError> {
+        if self.holds_output() && transport::held_during_kex(payload[0]) {
+            return self.deferred.push(payload);
+        }
+        let host = &mut self.host;
+        let n = self.io.seal(
+            payload,
+            &mut |b| host.fill_random(b),
+            &mut self.out[self.out_len..],
+        )?;
+        self.out_len += n;
+        Ok(())
+    }
+
+    fn send_kexinit(&mut self) -> Result<(), SshError> {
+        let mut cookie = [0u8; 16];
+        self.host.fill_random(&mut cookie);
+        let n = kex::write_kexinit(&cookie, &mut self.client_kexinit)?;
+        self.client_kexinit_len = n;
+        let mut kexinit = [0u8; MAX_CLIENT_KEXINIT];
+        kexinit[..n].copy_from_slice(&self.client_kexinit[..n]);
+        self.send(&kexinit[..n])
+    }
+
+    fn established(&self) -> bool {
+        !matches!(
+            self.phase,
+            Phase::Version | Phase::KexInit | Phase::KexEcdh | Phase::NewKeys | Phase::Closed
+        )
+    }
+
+    fn holds_output(&self) -> bool {
+        matches!(self.rekey, Rekey::Sent | Rekey::Ecdh) || !self.deferred.is_empty()
+    }
+
+    fn start_rekey(&mut self) -> Result<(), SshError> {
+        self.send_kexinit()?;
+        self.rekey = Rekey::Sent;
+        Ok(())
+    }
+
+    fn maybe_rekey(&mut self) -> Result<(), SshError> {
+        if self.rekey == Rekey::Idle
+            && self.established()
+            && self.io.bytes_since_keys() >= self.rekey_limit
+        {
+            self.start_rekey()?;
+        }
+        Ok(())
+    }
+
+    fn flush_deferred(&mut self) -> Result<(), SshError> {
+        if matches!(self.rekey, Rekey::Sent | Rekey::Ecdh) {
+            return Ok(());
+        }
+        while let Some(payload) = self.deferred.front() {
+            let host = &mut self.host;
+            match self.io.seal(
+                payload,
+                &mut |b| host.fill_random(b),
+                &mut self.out[self.out_len..],
+            ) {
+                Ok(n) => self.out_len += n,
+                Err(SshError::BufferFull) => return Ok(()),
+                Err(e) => return Err(e),
+            }
+            self.deferred.pop();
+        }
+        Ok(())
+    }
+
+    fn send_with<F>(&mut self, build: F) -> Result<(), SshError>
+    where
+        F: FnOnce(&mut Writer<'_>) -> Result<(), SshError>,
+    {
+        let mut buf = [0u8; 512];
+        let mut w = Writer::new(&mut buf);
+        build(&mut w)?;
+        let n = w.len();
+        self.send(&buf[..n])
+    }
+
+    fn send_disconnect(&mut self, reason: u32, description: &str) {
+        if self.phase == Phase::Closed {
+            return;
+        }
+        let _ = self.send_with(|w| {
+            w.u8(msg::DISCONNECT)?
+                .u32(reason)?
+                .string(description.as_bytes())?
+                .string(b"")?;
+            Ok(())
+        });
+        self.phase = Phase::Closed;
+    }
+
+    fn fail(&mut self, e: SshError) {
+        let (reason, text) = match e {
+            SshError::Disconnected => {
+                self.phase = Phase::Closed;
+                return;
+            }
+            SshError::NoCommonAlgorithm => (disconnect::KEY_EXCHANGE_FAILED, "no common algorithm"),
+            SshError::Crypto if self.phase == Phase::KexEcdh || self.rekey == Rekey::Ecdh => {
+                (disconnect::KEY_EXCHANGE_FAILED, "key exchange failed")
+            }
+            SshError::Crypto => (disconnect::MAC_ERROR, "bad packet authentication"),
+            SshError::Protocol | SshError::BufferFull => {
+                (disconnect::PROTOCOL_ERROR, "protocol error")
+            }
+        };
+        self.send_disconnect(reason, text);
+    }
+
+    /// Identifiant distant d'un canal confirmé et pas encore fermé.
+    fn open_remote_id(&self, channel: u32) -> Result<u32, SshError> {
+        match self.channels.get(channel) {
+            Some(ch) if matches!(ch.state, ChannelState::Open | ChannelState::Running) => {
+                Ok(ch.remote_id)
+            }
+            _ => Err(SshError::Protocol),
+        }
+    }
+
+    fn version_line(&mut self, line: &[u8]) -> Result<(), SshError> {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        // Le serveur peut envoyer d'autres lignes avant la sienne.
+        if !line.starts_with(b"SSH-") {
+            return Ok(());
+        }
+        if line.len() > MAX_VERSION_LINE - 2
+            || (!line.starts_with(b"SSH-2.0-") && !line.starts_with(b"SSH-1.99-"))
+        {
+            return Err(SshError::Protocol);
+        }
+        self.server_version[..line.len()].copy_from_slice(line);
+        self.server_version_len = line.len();
+        self.phase = Phase::KexInit;
+        Ok(())
+    }
+
+    fn dispatch(&mut self, payload: &[u8]) -> Result<(), SshError> {
+        let id = *payload.first().ok_or(SshError::Protocol)?;
+        match id {
+            msg::DISCONNECT => return Err(SshError::Disconnected),
+            msg::IGNORE | msg::DEBUG | msg::UNIMPLEMENTED => return Ok(()),
+            _ => {}
+        }
+        if self.rekey != Rekey::Idle || (id == msg::KEXINIT && self.established()) {
+            match (self.rekey, id) {
+                (Rekey::Idle | Rekey::Sent, msg::KEXINIT) => {
+                    if self.rekey == Rekey::Idle {
+                        self.send_kexinit()?;
+                    }
+                    self.server_kexinit(payload)?;
+                    self.rekey = Rekey::Ecdh;
+                    return Ok(());
+                }
+                (Rekey::Ecdh, msg::KEX_ECDH_REPLY) => return self.ecdh_reply(payload),
+                (Rekey::NewKeys, msg::NEWKEYS) => {
+                    let keys = self.incoming_keys.take().ok_or(SshError::Protocol)?;
+                    self.io.set_incoming(&keys.server_to_client);
+                    self.rekey = Rekey::Idle;
+                    return Ok(());
+                }
+                (Rekey::Sent, _) => {}
+                _ => return Err(SshError::Protocol),
+            }
+        }
+        match (self.phase, id) {
+            (Phase::KexInit, msg::KEXINIT) => {
+                self.server_kexinit(payload)?;
+                self.phase = Phase::KexEcdh;
+                Ok(())
+            }
+            (Phase::KexEcdh, msg::KEX_ECDH_REPLY) => self.ecdh_reply(payload),
+            (Phase::NewKeys, msg::NEWKEYS) => {
+                let keys = self.incoming_keys.take().ok_or(SshError::Protocol)?;
+                self.io.set_incoming(&keys.server_to_client);
+                self.send_with(|w| {
+                    w.u8(msg::SERVICE_REQUEST)?.string(b"ssh-userauth")?;
+                    Ok(())
+                })?;
+                self.phase = Phase::Service;
+                Ok(())
+            }
+            (Phase::Service, msg::SERVICE_ACCEPT) => {
+                self.phase = Phase::Auth;
+                Ok(())
+            }
+            (Phase::Auth | Phase::AuthPending, msg::USERAUTH_BANNER) => {
+                let mut r = Reader::new(&payload[1..]);
+                self.host.banner(r.string()?);
+                Ok(())
+            }
+            (Phase::AuthPending, msg::USERAUTH_SUCCESS) => {
+                self.phase = Phase::Connected;
+                Ok(())
+            }
+            (Phase::AuthPending, msg::USERAUTH_FAILURE) => {
+                let mut r = Reader::new(&payload[1..]);
+                self.phase = Phase::Auth;
+                self.host.auth_failed(r.string()?);
+                Ok(())
+            }
+            (Phase::Connected, 80..=127) => self.connection(id, &payload[1..]),
+            (Phase::Connected, _) => {
+                let seq = self.io.last_seq_in();
+                self.send_with(|w| {
+                    w.u8(msg::UNIMPLEMENTED)?.u32(seq)?;
+                    Ok(())
+                })
+            }
+            _ => Err(SshError::Protocol),
+        }
+    }
+
+    /// Négocie avec le KEXINIT du serveur et envoie KEX_ECDH_INIT.
+    fn server_kexinit(&mut self, payload: &[u8]) -> Result<(), SshError> {
+        if payload.len() > MAX_SERVER_KEXINIT {
+            return Err(SshError::Protocol);
+        }
+        let chosen = kex::negotiate(&self.client_kexinit[..self.client_kexinit_len], payload)?;
+        self.kex = chosen.kex;
+        self.server_kexinit[..payload.len()].copy_from_slice(payload);
+        self.server_kexinit_len = payload.len();
+        self.host.fill_random(&mut self.kex_seed);
+        let mut q_c = [0u8; kex::MAX_CLIENT_EPHEMERAL];
+        let n = kex::client_ephemeral(self.kex, &self.kex_seed, &mut q_c);
+        let mut init = [0u8; 1 + 4 + kex::MAX_CLIENT_EPHEMERAL];
+        let mut w = Writer::new(&mut init);
+        w.u8(msg::KEX_ECDH_INIT)?.string(&q_c[..n])?;
+        let len = w.len();
+        self.send(&init[..len])
+    }
+
+    fn ecdh_reply(&mut self, payload: &[u8]) -> Result<(), SshError> {
+        let mut r = Reader::new(&payload[1..]);
+        let host_blob = r.string()?;
+        let q_s = r.string()?;
+        let sig = r.string()?;
+        let host_key = kex::parse_key_blob(host_blob)?;
+        let mut q_c = [0u8; kex::MAX_CLIENT_EPHEMERAL];
+        let q_c_len = kex::client_ephemeral(self.kex, &self.kex_seed, &mut q_c);
+        let shared = kex::client_shared_secret(self.kex, &self.kex_seed, q_s);
+        self.kex_seed.fill(0);
+        let shared = shared?;
+        let h = ExchangeTranscript {
+            client_version: CLIENT_VERSION,
+            server_version: &self.server_version[..self.server_version_len],
+            client_kexinit: &self.client_kexinit[..self.client_kexinit_len],
+            server_kexinit: &self.server_kexinit[..self.server_kexinit_len],
+            host_key: host_blob,
+            client_ephemeral: &q_c[..q_c_len],
+            server_ephemeral: q_s,
+        }
+        .hash(&shared);
+        kex::verify_exchange(&host_key, &h, sig)?;
+        match self.host_key {
+            Some(known) if known != host_key => {
+                self.send_disconnect(disconnect::HOST_KEY_NOT_VERIFIABLE, "host key changed");
+                return Err(SshError::Disconnected);
+            }
+            Some(_) => {}
+            None => {
+                if !self.host.check_host_key(&host_key) {
+                    self.send_disconnect(disconnect::HOST_KEY_NOT_VERIFIABLE, "host key rejected");
+                    return Err(SshError::Disconnected);
+                }
+                self.host_key = Some(host_key);
+                self.session_id = h;
+            }
+        }
+        let keys = kex::derive_keys(&shared, &h, &self.session_id);
+        self.send(&[msg::NEWKEYS])?;
+        self.io.set_outgoing(&keys.client_to_server);
+        self.incoming_keys = Some(keys);
+        if self.phase == Phase::KexEcdh {
+            self.phase = Phase::NewKeys;
+            return Ok(());
+        }
+        self.rekey = Rekey::NewKeys;
+        self.flush_deferred()
+    }
+
+    fn connection(&mut self, id: u8, body: &[u8]) -> Result<(), SshError> {
+        let mut r = Reader::new(body);
+        match id {
+            msg::GLOBAL_REQUEST => {
+                let _name = r.string()?;
+                if r.bool()? {
+                    self.send(&[msg::REQUEST_FAILURE])?;
+                }
+                Ok(())
+            }
+            msg::CHANNEL_OPEN => {
+                // Ni X11 ni redirection de ports côté client.
+                let _kind = r.string()?;
+                let sender = r.u32()?;
+                self.send_with(|w| {
+                    w.u8(msg::CHANNEL_OPEN_FAILURE)?
+                        .u32(sender)?
+                        .u32(1)?
+                        .string(b"")?
+                        .string(b"")?;
+                    Ok(())
+                })
+            }
+            msg::CHANNEL_OPEN_CONFIRMATION => {
+                let ch = r.u32()?;
+                let (remote, window, max_packet) = (r.u32()?, r.u32()?, r.u32()?);
+                if !self.channels.confirm(ch, remote, window, max_packet) {
+                    return Err(SshError::Protocol);
+                }
+                self.host.channel_opened(ch, true);
+                Ok(())
+            }
+            msg::CHANNEL_OPEN_FAILURE => {
+                let ch = r.u32()?;
+                match self.channels.get(ch) {
+                    Some(c) if c.state == ChannelState::Opening => {}
+                    _ => return Err(SshError::Protocol),
+                }
+                self.channels.remove(ch);
+                self.host.channel_opened(ch, false);
+                Ok(())
+            }
+            msg::CHANNEL_WINDOW_ADJUST => {
+                let ch = r.u32()?;
+                let add = r.u32()?;
+                let ch = self.channels.get_mut(ch).ok_or(SshError::Protocol)?;
+                ch.remote_window = ch.remote_window.saturating_add(add);
+                Ok(())
+            }
+            msg::CHANNEL_DATA | msg::CHANNEL_EXTENDED_DATA => {
+                let ch = r.u32()?;
+                let stderr = id == msg::CHANNEL_EXTENDED_DATA && r.u32()? == 1;
+                let data = r.string()?;
+                if data.len() > LOCAL_MAX_PACKET as usize {
+                    return Err(SshError::Protocol);
+                }
+                let refill = self
+                    .channels
+                    .consume_local(ch, data.len() as u32)
+                    .ok_or(SshError::Protocol)?;
+                if id == msg::CHANNEL_DATA {
+                    self.host.channel_data(ch, data);
+                } else if stderr {
+                    self.host.channel_stderr(ch, data);
+                }
+                if refill > 0 {
+                    let remote = self.channels.get(ch).ok_or(SshError::Protocol)?.remote_id;
+                    self.send_with(|w| {
+                        w.u8(msg::CHANNEL_WINDOW_ADJUST)?.u32(remote)?.u32(refill)?;
+                        Ok(())
+                    })?;
+                }
+                Ok(())
+            }
+            msg::CHANNEL_EOF => {
+                let ch = r.u32()?;
+                self.channels
+                    .get_mut(ch)
+                    .ok_or(SshError::Protocol)?
+                    .eof_received = true;
+                self.host.channel_eof(ch);
+                Ok(())
+            }
+            msg::CHANNEL_CLOSE => {
+                let ch = r.u32()?;
+                let closed = self.channels.remove(ch).ok_or(SshError::Protocol)?;
+                if closed.state != ChannelState::Closing {
+                    self.send_with(|w| {
+                        w.u8(msg::CHANNEL_CLOSE)?.u32(closed.remote_id)?;
+                        Ok(())
+                    })?;
+                }
+                self.host.channel_closed(ch);
+                Ok(())
+            }
+            msg::CHANNEL_REQUEST => {
+                let ch = r.u32()?;
+                let name = r.string()?;
+                let want_reply = r.bool()?;
+                let remote = self.channels.get(ch).ok_or(SshError::Protocol)?.remote_id;
+                let ok = match name {
+                    b"exit-status" => {
+                        self.host.exit_status(ch, r.u32()?);
+                        true
+                    }
+                    // Processus distant tué : statut 128, le nom du signal
+                    // n'est pas traduit.
+                    b"exit-signal" => {
+                        self.host.exit_status(ch, 128);
+                        true
+                    }
+                    _ => false,
+                };
+                if want_reply {
+                    let reply = if ok {
+                        msg::CHANNEL_SUCCESS
+                    } else {
+                        msg::CHANNEL_FAILURE
+                    };
+                    self.send_with(|w| {
+                        w.u8(reply)?.u32(remote)?;
+                        Ok(())
+                    })?;
+                }
+                Ok(())
+            }
+            msg::CHANNEL_SUCCESS | msg::CHANNEL_FAILURE => {
+                let ch = r.u32()?;
+                self.host.request_result(ch, id == msg::CHANNEL_SUCCESS);
+                Ok(())
+            }
+            msg::REQUEST_SUCCESS | msg::REQUEST_FAILURE => Ok(()),
+            _ => {
+                let seq = self.io.last_seq_in();
+                self.send_with(|w| {
+                    w.u8(msg::UNIMPLEMENTED)?.u32(seq)?;
+                    Ok(())
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::known_hosts::{self, HostKeyStatus};
+    use crate::server::{ServerSession, SessionHost};
+
+    struct Sink {
+        rng: u8,
+        data: Vec<u8>,
+        closed: bool,
+    }
+
+    impl Sink {
+        fn new() -> Self {
+            Self {
+                rng: 1,
+                data: Vec::new(),
+                closed: false,
+            }
+        }
+
+        fn random(&mut self, buf: &mut [u8]) {
+            for b in buf {
+                self.rng = self.rng.wrapping_mul(29).wrapping_add(11);
+                *b = self.rng;
+            }
+        }
+    }
+
+    struct Remote {
+        sink: Sink,
+        user_key: VerifyingKey,
+    }
+
+    impl SessionHost for Remote {
+        fn fill_random(&mut self, buf: &mut [u8]) {
+            self.sink.random(buf);
+        }
+        fn check_password(&mut self, _user: &[u8], _password: &[u8]) -> bool {
+            false
+        }
+        fn authorized_key(&mut self, user: &[u8], key: &VerifyingKey) -> bool {
+            user == b"bob" && *key == self.user_key
+        }
+        fn open_pty(&mut self, _channel: u32, pty: &PtyRequest) -> bool {
+            pty.cols == 120
+        }
+        fn start_shell(&mut self, _channel: u32) -> bool {
+            true
+        }
+        fn exec(&mut self, _channel: u32, _command: &[u8]) -> bool {
+            false
+        }
+        fn channel_data(&mut self, _channel: u32, data: &[u8]) {
+            self.sink.data.extend_from_slice(data);
+        }
+        fn channel_closed(&mut self, _channel: u32) {
+            self.sink.closed = true;
+        }
+    }
+
+    struct Terminal {
+        sink: Sink,
+        stored_host_key: Option<[u8; kex::KEY_BLOB_LEN]>,
+        opened: Option<bool>,
+        results: Vec<bool>,
+        exit: Option<u32>,
+        auth_failures: u32,
+    }
+
+    impl ClientHost for Terminal {
+        fn fill_random(&mut self, buf: &mut [u8]) {
+            self.sink.random(buf);
+        }
+        fn check_host_key(&mut self, key: &VerifyingKey) -> bool {
+            let stored = self.stored_host_key.as_ref().map(|b| &b[..]);
+            match known_hosts::check(stored, key) {
+                HostKeyStatus::Known => true,
+                // Confirmation simulée de l'utilisateur.
+                HostKeyStatus::Unknown => {
+                    self.stored_host_key = Some(kex::key_blob(key));
+                    true
+                }
+                HostKeyStatus::Changed => false,
+            }
+        }
+        fn auth_failed(&mut self, methods: &[u8]) {
+            assert_eq!(methods, b"publickey,password");
+            self.auth_failures += 1;
+        }
+        fn channel_opened(&mut self, _channel: u32, ok: bool) {
+            self.opened = Some(ok);
+        }
+        fn request_result(&mut self, _channel: u32, ok: bool) {
+            self.results.push(ok);
+        }
+        fn channel_data(&mut self, _channel: u32, data: &[u8]) {
+            self.sink.data.extend_from_slice(data);
+        }
+        fn exit_status(&mut self, _channel: u32, status: u32) {
+            self.exit = Some(status);
+        }
+        fn channel_closed(&mut self, _channel: u32) {
+            self.sink.closed = true;
+        }
+    }
+
+    fn terminal(stored_host_key: Option<[u8; kex::KEY_BLOB_LEN]>) -> Terminal {
+        Terminal {
+            sink: Sink::new(),
+            stored_host_key,
+            opened: None,
+            results: Vec::new(),
+            exit: None,
+            auth_failures: 0,
+        }
+    }
+
+    /// Fait circuler les octets dans les deux sens jusqu'au repos.
+    fn pump(
+        c: &mut ClientSession<Terminal>,
+        s: &mut ServerSession<Remote>,
+    ) -> Result<(), SshError> {
+        while !c.output().is_empty() || !s.output().is_empty() {
+            let out = c.output().to_vec();
+            c.advance_output(out.len());
+            if s.phase() != crate::server::Phase::Closed {
+                assert_eq!(s.receive(&out)?, out.len());
+            }
+            let out = s.output().to_vec();
+            s.advance_output(out.len());
+            if c.phase() != Phase::Closed {
+                assert_eq!(c.receive(&out)?, out.len());
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn client_talks_to_exo_sshd() {
+        let host_key = SigningKey::from_bytes(&[6; 32]);
+        let user_key = SigningKey::from_bytes(&[7; 32]);
+        let mut s = ServerSession::new(
+            host_key.clone(),
+            Remote {
+                sink: Sink::new(),
+                user_key: user_key.verifying_key(),
+            },
+        );
+        let mut c = ClientSession::new(terminal(None));
+        pump(&mut c, &mut s).unwrap();
+        assert_eq!(c.phase(), Phase::Auth);
+        assert_eq!(
+            c.host().stored_host_key,
+            Some(kex::key_blob(&host_key.verifying_key()))
+        );
+
+        c.auth_password(b"bob", b"nope").unwrap();
+        pump(&mut c, &mut s).unwrap();
+        assert_eq!((c.phase(), c.host().auth_failures), (Phase::Auth, 1));
+        c.auth_publickey(b"bob", &user_key).unwrap();
+        pump(&mut c, &mut s).unwrap();
+        assert_eq!(c.phase(), Phase::Connected);
+
+        let ch = c.open_session().unwrap();
+        assert_eq!(c.send_data(ch, b"early"), Err(SshError::Protocol));
+        pump(&mut c, &mut s).unwrap();
+        assert_eq!(c.host().opened, Some(true));
+        c.request_pty(ch, &PtyRequest::new(b"xterm", 120, 40, 0, 0))
+            .unwrap();
+        c.request_shell(ch).unwrap();
+        c.request_subsystem(ch, b"sftp").unwrap();
+        pump(&mut c, &mut s).unwrap();
+        assert_eq!(c.host().results, [true, true, false]);
+
+        assert_eq!(c.send_data(ch, b"uname\n"), Ok(6));
+        pump(&mut c, &mut s).unwrap();
+        assert_eq!(s.host().sink.data, b"uname\n");
+        let big = [b'z'; 3 * LOCAL_MAX_PACKET as usize];
+        let mut sent = 0;
+        while sent < big.len() {
+            sent += s.send_data(0, &big[sent..]).unwrap();
+        }
+        s.send_exit_status(0, 3).unwrap();
+        s.close(0).unwrap();
+        pump(&mut c, &mut s).unwrap();
+        assert_eq!(c.host().sink.data.len(), big.len());
+        assert_eq!(c.host().exit, Some(3));
+        assert!(c.host().sink.closed && s.host().sink.closed);
+    }
+
+    #[test]
+    fn rekey_keeps_the_session_running() {
+        let user_key = SigningKey::from_bytes(&[7; 32]);
+        let mut s = ServerSession::new(
+            SigningKey::from_bytes(&[6; 32]),
+            Remote {
+                sink: Sink::new(),
+                user_key: user_key.verifying_key(),
+            },
+        );
+        let mut c = ClientSession::new(terminal(None));
+        assert_eq!(c.rekey(), Err(SshError::Protocol));
+        pump(&mut c, &mut s).unwrap();
+        c.auth_publickey(b"bob", &user_key).unwrap();
+        pump(&mut c, &mut s).unwrap();
+        let ch = c.open_session().unwrap();
+        pump(&mut c, &mut s).unwrap();
+        let session_id = c.core.session_id;
+
+        // Initié par le client : les données attendent la fin de l'échange.
+        c.rekey().unwrap();
+        assert!(c.rekeying());
+        assert_eq!(c.send_data(ch, b"held"), Ok(0));
+        pump(&mut c, &mut s).unwrap();
+        assert!(!c.rekeying() && !s.rekeying());
+        assert_eq!(c.send_data(ch, b"after"), Ok(5));
+        pump(&mut c, &mut s).unwrap();
+        assert_eq!(s.host().sink.data, b"after");
+
+        // Initié par le serveur au passage de la limite de volume.
+        s.set_rekey_limit(1);
+        assert_eq!(s.send_data(0, b"late"), Ok(0));
+        assert!(s.rekeying());
+        s.set_rekey_limit(REKEY_BYTES);
+        pump(&mut c, &mut s).unwrap();
+        assert!(!c.rekeying() && !s.rekeying());
+        assert_eq!(s.send_data(0, b"late"), Ok(4));
+        pump(&mut c, &mut s).unwrap();
+        assert_eq!(c.host().sink.data, b"late");
+        assert_eq!(c.core.session_id, session_id);
+        assert_eq!(c.phase(), Phase::Connected);
+    }
+
+    #[test]
+    fn changed_host_key_is_refused() {
+        let pinned = kex::key_blob(&SigningKey::from_bytes(&[1; 32]).verifying_key());
+        let mut s = ServerSession::new(
+            SigningKey::from_bytes(&[2; 32]),
+            Remote {
+                sink: Sink::new(),
+                user_key: SigningKey::from_bytes(&[3; 32]).verifying_key(),
+            },
+        );
+        let mut c = ClientSession::new(terminal(Some(pinned)));
+        assert_eq!(pump(&mut c, &mut s), Err(SshError::Disconnected));
+        assert_eq!(c.phase(), Phase::Closed);
+        assert_eq!(c.host().stored_host_key, Some(pinned));
+    }
+}
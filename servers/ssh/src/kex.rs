@@ -0,0 +1,547 @@
+//! Échange de clés avec clés d'hôte ssh-ed25519 (RFC 8709) et dérivation
+//! des clés de session (RFC 4253 §7.2).
+//!
+//! Deux méthodes, dans l'ordre de préférence :
+//! - `mlkem768x25519-sha256` : hybride ML-KEM-768 (FIPS 203) + X25519
+//!   (draft-ietf-sshm-mlkem-hybrid-kex), le secret reste sûr tant que l'un
+//!   des deux tient ;
+//! - `curve25519-sha256` (RFC 8731), repli pour les pairs sans ML-KEM.
+//!
+//! Les deux réutilisent KEX_ECDH_INIT / KEX_ECDH_REPLY ; seuls le contenu
+//! des blobs éphémères et l'encodage du secret K changent.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use ml_kem::kem::{Decapsulate, EncapsulationKey};
+use ml_kem::{EncapsulateDeterministic, EncodedSizeUser, KemCore, MlKem768, MlKem768Params, B32};
+use sha2::{Digest, Sha256};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::msg;
+use crate::transport::{DirectionKeys, CIPHER_NAME};
+use crate::wire::{name_list_contains, Reader, Writer};
+use crate::SshError;
+
+pub const KEX_NAMES: &[u8] =
+    b"mlkem768x25519-sha256,curve25519-sha256,curve25519-sha256@libssh.org";
+pub const HOST_KEY_NAME: &[u8] = b"ssh-ed25519";
+/// Ignoré avec un chiffrement AEAD mais la liste ne peut pas être vide.
+pub const MAC_NAMES: &[u8] = b"hmac-sha2-256";
+pub const COMPRESSION_NAMES: &[u8] = b"none";
+
+pub const KEY_BLOB_LEN: usize = 4 + 11 + 4 + 32;
+pub const SIGNATURE_BLOB_LEN: usize = 4 + 11 + 4 + 64;
+
+const MLKEM768_EK_LEN: usize = 1184;
+const MLKEM768_CT_LEN: usize = 1088;
+/// Blob éphémère du client : clé d'encapsulation ML-KEM puis clé X25519.
+pub const MAX_CLIENT_EPHEMERAL: usize = MLKEM768_EK_LEN + 32;
+/// Blob éphémère du serveur : chiffré ML-KEM puis clé X25519.
+pub const MAX_SERVER_EPHEMERAL: usize = MLKEM768_CT_LEN + 32;
+/// Aléa d'un côté pour un échange : secret X25519, puis les graines ML-KEM
+/// (d, z) côté client ou le message m encapsulé côté serveur.
+pub const KEX_SEED_LEN: usize = 32 + 64;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KexMethod {
+    MlKem768X25519,
+    Curve25519,
+}
+
+impl KexMethod {
+    fn from_name(name: &[u8]) -> Option<Self> {
+        match name {
+            b"mlkem768x25519-sha256" => Some(Self::MlKem768X25519),
+            b"curve25519-sha256" | b"curve25519-sha256@libssh.org" => Some(Self::Curve25519),
+            _ => None,
+        }
+    }
+}
+
+/// Résultat de la négociation des deux KEXINIT.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Negotiated {
+    pub kex: KexMethod,
+    /// Le client a envoyé un paquet de kex deviné qui doit être ignoré
+    /// (RFC 4253 §7).
+    pub wrong_guess: bool,
+}
+
+pub fn write_kexinit(cookie: &[u8; 16], out: &mut [u8]) -> Result<usize, SshError> {
+    let mut w = Writer::new(out);
+    w.u8(msg::KEXINIT)?
+        .raw(cookie)?
+        .string(KEX_NAMES)?
+        .string(HOST_KEY_NAME)?
+        .string(CIPHER_NAME)?
+        .string(CIPHER_NAME)?
+        .string(MAC_NAMES)?
+        .string(MAC_NAMES)?
+        .string(COMPRESSION_NAMES)?
+        .string(COMPRESSION_NAMES)?
+        .string(b"")?
+        .string(b"")?
+        .bool(false)?
+        .u32(0)?;
+    Ok(w.len())
+}
+
+struct KexInitLists<'a> {
+    lists: [&'a [u8]; 10],
+    first_follows: bool,
+}
+
+fn parse_kexinit(payload: &[u8]) -> Result<KexInitLists<'_>, SshError> {
+    let mut r = Reader::new(payload);
+    if r.u8()? != msg::KEXINIT {
+        return Err(SshError::Protocol);
+    }
+    r.raw(16)?;
+    let mut lists = [&[][..]; 10];
+    for l in lists.iter_mut() {
+        *l = r.string()?;
+    }
+    Ok(KexInitLists {
+        lists,
+        first_follows: r.bool()?,
+    })
+}
+
+fn first_name(list: &[u8]) -> &[u8] {
+    list.split(|&b| b == b',').next().unwrap_or(&[])
+}
+
+fn choose<'a>(client: &'a [u8], server: &[u8]) -> Option<&'a [u8]> {
+    client
+        .split(|&b| b == b',')
+        .find(|n| name_list_contains(server, n))
+}
+
+/// Vérifie qu'un algorithme commun existe pour chaque catégorie et retient
+/// la méthode de kex (le choix du client prime).
+pub fn negotiate(client_kexinit: &[u8], server_kexinit: &[u8]) -> Result<Negotiated, SshError> {
+    let c = parse_kexinit(client_kexinit)?;
+    let s = parse_kexinit(server_kexinit)?;
+    // kex, clé d'hôte, chiffrement ×2, compression ×2 ; MAC ignoré (AEAD).
+    for i in [1, 2, 3, 6, 7] {
+        choose(c.lists[i], s.lists[i]).ok_or(SshError::NoCommonAlgorithm)?;
+    }
+    let name = choose(c.lists[0], s.lists[0]).ok_or(SshError::NoCommonAlgorithm)?;
+    let kex = KexMethod::from_name(name).ok_or(SshError::NoCommonAlgorithm)?;
+    Ok(Negotiated {
+        kex,
+        wrong_guess: c.first_follows
+            && (first_name(c.lists[0]) != name || first_name(c.lists[1]) != HOST_KEY_NAME),
+    })
+}
+
+pub fn key_blob(key: &VerifyingKey) -> [u8; KEY_BLOB_LEN] {
+    let mut blob = [0u8; KEY_BLOB_LEN];
+    let mut w = Writer::new(&mut blob);
+    // Tailles fixes : ne peut pas déborder.
+    let _ = w
+        .string(HOST_KEY_NAME)
+        .and_then(|w| w.string(key.as_bytes()));
+    blob
+}
+
+pub fn parse_key_blob(blob: &[u8]) -> Result<VerifyingKey, SshError> {
+    let mut r = Reader::new(blob);
+    if r.string()? != HOST_KEY_NAME {
+        return Err(SshError::NoCommonAlgorithm);
+    }
+    let key: [u8; 32] = r.string()?.try_into().map_err(|_| SshError::Protocol)?;
+    VerifyingKey::from_bytes(&key).map_err(|_| SshError::Crypto)
+}
+
+pub fn signature_blob(sig: &Signature) -> [u8; SIGNATURE_BLOB_LEN] {
+    let mut blob = [0u8; SIGNATURE_BLOB_LEN];
+    let mut w = Writer::new(&mut blob);
+    let _ = w
+        .string(HOST_KEY_NAME)
+        .and_then(|w| w.string(&sig.to_bytes()));
+    blob
+}
+
+pub fn parse_signature_blob(blob: &[u8]) -> Result<Signature, SshError> {
+    let mut r = Reader::new(blob);
+    if r.string()? != HOST_KEY_NAME {
+        return Err(SshError::Crypto);
+    }
+    let sig: [u8; 64] = r.string()?.try_into().map_err(|_| SshError::Crypto)?;
+    Ok(Signature::from_bytes(&sig))
+}
+
+/// Ce qui entre dans le hash d'échange H, dans l'ordre de RFC 4253 §8.
+pub struct ExchangeTranscript<'a> {
+    pub client_version: &'a [u8],
+    pub server_version: &'a [u8],
+    pub client_kexinit: &'a [u8],
+    pub server_kexinit: &'a [u8],
+    pub host_key: &'a [u8],
+    pub client_ephemeral: &'a [u8],
+    pub server_ephemeral: &'a [u8],
+}
+
+/// Secret K de l'échange. curve25519-sha256 l'encode en mpint, le kex
+/// hybride en string (c'est déjà un condensat SHA-256).
+#[derive(Clone, Copy)]
+pub struct SharedSecret {
+    k: [u8; 32],
+    kex: KexMethod,
+}
+
+fn hash_string(h: &mut Sha256, s: &[u8]) {
+    h.update((s.len() as u32).to_be_bytes());
+    h.update(s);
+}
+
+/// Le secret X25519 est traité comme un entier non signé big-endian.
+fn hash_shared(h: &mut Sha256, shared: &SharedSecret) {
+    if shared.kex == KexMethod::MlKem768X25519 {
+        return hash_string(h, &shared.k);
+    }
+    let mut buf = [0u8; 4 + 1 + 32];
+    let mut w = Writer::new(&mut buf);
+    let _ = w.mpint(&shared.k);
+    let n = w.len();
+    h.update(&buf[..n]);
+}
+
+impl ExchangeTranscript<'_> {
+    pub fn hash(&self, shared: &SharedSecret) -> [u8; 32] {
+        let mut h = Sha256::new();
+        hash_string(&mut h, self.client_version);
+        hash_string(&mut h, self.server_version);
+        hash_string(&mut h, self.client_kexinit);
+        hash_string(&mut h, self.server_kexinit);
+        hash_string(&mut h, self.host_key);
+        hash_string(&mut h, self.client_ephemeral);
+        hash_string(&mut h, self.server_ephemeral);
+        hash_shared(&mut h, shared);
+        h.finalize().into()
+    }
+}
+
+pub fn ephemeral_public(secret: &[u8; 32]) -> [u8; 32] {
+    PublicKey::from(&StaticSecret::from(*secret)).to_bytes()
+}
+
+/// X25519 ; un résultat non contributif (point d'ordre faible) est refusé.
+pub fn shared_secret(secret: &[u8; 32], peer: &[u8]) -> Result<[u8; 32], SshError> {
+    let peer: [u8; 32] = peer.try_into().map_err(|_| SshError::Protocol)?;
+    let shared = StaticSecret::from(*secret).diffie_hellman(&PublicKey::from(peer));
+    if !shared.was_contributory() {
+        return Err(SshError::Crypto);
+    }
+    Ok(shared.to_bytes())
+}
+
+fn mlkem_seeds(seed: &[u8; KEX_SEED_LEN]) -> (B32, B32) {
+    let mut d = B32::default();
+    let mut z = B32::default();
+    d.copy_from_slice(&seed[32..64]);
+    z.copy_from_slice(&seed[64..]);
+    (d, z)
+}
+
+fn x25519_secret(seed: &[u8; KEX_SEED_LEN]) -> [u8; 32] {
+    let mut x = [0u8; 32];
+    x.copy_from_slice(&seed[..32]);
+    x
+}
+
+/// K = SHA-256(K_PQ || K_CL), K_CL étant le secret X25519 brut.
+fn hybrid_secret(k_pq: &[u8], k_cl: &[u8; 32]) -> SharedSecret {
+    let mut d = Sha256::new();
+    d.update(k_pq);
+    d.update(k_cl);
+    SharedSecret {
+        k: d.finalize().into(),
+        kex: KexMethod::MlKem768X25519,
+    }
+}
+
+/// Blob éphémère du client (Q_C ou C_INIT) tiré de `seed` ; retourne sa
+/// longueur dans `out`.
+pub fn client_ephemeral(
+    kex: KexMethod,
+    seed: &[u8; KEX_SEED_LEN],
+    out: &mut [u8; MAX_CLIENT_EPHEMERAL],
+) -> usize {
+    let x_pub = ephemeral_public(&x25519_secret(seed));
+    match kex {
+        KexMethod::Curve25519 => {
+            out[..32].copy_from_slice(&x_pub);
+            32
+        }
+        KexMethod::MlKem768X25519 => {
+            let (d, z) = mlkem_seeds(seed);
+            let (_, ek) = MlKem768::generate_deterministic(&d, &z);
+            out[..MLKEM768_EK_LEN].copy_from_slice(&ek.as_bytes());
+            out[MLKEM768_EK_LEN..].copy_from_slice(&x_pub);
+            MAX_CLIENT_EPHEMERAL
+        }
+    }
+}
+
+/// Côté serveur : répond au blob du client avec le sien (Q_S ou S_REPLY)
+/// et calcule K.
+pub fn server_ephemeral(
+    kex: KexMethod,
+    seed: &[u8; KEX_SEED_LEN],
+    client: &[u8],
+    out: &mut [u8; MAX_SERVER_EPHEMERAL],
+) -> Result<(usize, SharedSecret), SshError> {
+    let x = x25519_secret(seed);
+    let x_pub = ephemeral_public(&x);
+    match kex {
+        KexMethod::Curve25519 => {
+            let k = shared_secret(&x, client)?;
+            out[..32].copy_from_slice(&x_pub);
+            Ok((32, SharedSecret { k, kex }))
+        }
+        KexMethod::MlKem768X25519 => {
+            if client.len() != MAX_CLIENT_EPHEMERAL {
+                return Err(SshError::Protocol);
+            }
+            let (ek, q_c) = client.split_at(MLKEM768_EK_LEN);
+            let ek = EncapsulationKey::<MlKem768Params>::from_bytes(
+                ek.try_into().map_err(|_| SshError::Protocol)?,
+            );
+            let k_cl = shared_secret(&x, q_c)?;
+            let mut m = B32::default();
+            m.copy_from_slice(&seed[32..64]);
+            let (ct, k_pq) = ek
+                .encapsulate_deterministic(&m)
+                .map_err(|_| SshError::Crypto)?;
+            out[..MLKEM768_CT_LEN].copy_from_slice(&ct);
+            out[MLKEM768_CT_LEN..].copy_from_slice(&x_pub);
+            Ok((MAX_SERVER_EPHEMERAL, hybrid_secret(&k_pq, &k_cl)))
+        }
+    }
+}
+
+/// Côté client : K à partir du blob du serveur et de la graine qui a
+/// produit `client_ephemeral`.
+pub fn client_shared_secret(
+    kex: KexMethod,
+    seed: &[u8; KEX_SEED_LEN],
+    server: &[u8],
+) -> Result<SharedSecret, SshError> {
+    let x = x25519_secret(seed);
+    match kex {
+        KexMethod::Curve25519 => Ok(SharedSecret {
+            k: shared_secret(&x, server)?,
+            kex,
+        }),
+        KexMethod::MlKem768X25519 => {
+            if server.len() != MAX_SERVER_EPHEMERAL {
+                return Err(SshError::Protocol);
+            }
+            let (ct, q_s) = server.split_at(MLKEM768_CT_LEN);
+            let k_cl = shared_secret(&x, q_s)?;
+            let (d, z) = mlkem_seeds(seed);
+            let (dk, _) = MlKem768::generate_deterministic(&d, &z);
+            // Chiffré invalide : ML-KEM rend un secret implicite, l'échec
+            // se voit à la signature de H.
+            let k_pq = dk
+                .decapsulate(ct.try_into().map_err(|_| SshError::Protocol)?)
+                .map_err(|_| SshError::Crypto)?;
+            Ok(hybrid_secret(&k_pq, &k_cl))
+        }
+    }
+}
+
+pub fn sign_exchange(host_key: &SigningKey, h: &[u8; 32]) -> [u8; SIGNATURE_BLOB_LEN] {
+    signature_blob(&host_key.sign(h))
+}
+
+pub fn verify_exchange(
+    host_key: &VerifyingKey,
+    h: &[u8; 32],
+    sig_blob: &[u8],
+) -> Result<(), SshError> {
+    let sig = parse_signature_blob(sig_blob)?;
+    host_key.verify(h, &sig).map_err(|_| SshError::Crypto)
+}
+
+pub struct SessionKeys {
+    pub client_to_server: DirectionKeys,
+    pub server_to_client: DirectionKeys,
+}
+
+fn derive(shared: &SharedSecret, h: &[u8; 32], letter: u8, session_id: &[u8; 32]) -> [u8; 32] {
+    let mut d = Sha256::new();
+    hash_shared(&mut d, shared);
+    d.update(h);
+    d.update([letter]);
+    d.update(session_id);
+    d.finalize().into()
+}
+
+fn iv12(full: [u8; 32]) -> [u8; 12] {
+    let mut iv = [0u8; 12];
+    iv.copy_from_slice(&full[..12]);
+    iv
+}
+
+pub fn derive_keys(shared: &SharedSecret, h: &[u8; 32], session_id: &[u8; 32]) -> SessionKeys {
+    SessionKeys {
+        client_to_server: DirectionKeys {
+            iv: iv12(derive(shared, h, b'A', session_id)),
+            key: derive(shared, h, b'C', session_id),
+        },
+        server_to_client: DirectionKeys {
+            iv: iv12(derive(shared, h, b'B', session_id)),
+            key: derive(shared, h, b'D', session_id),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_kexinit(kex: &[u8], buf: &mut [u8]) -> usize {
+        let mut w = Writer::new(buf);
+        w.u8(msg::KEXINIT).unwrap().raw(&[0; 16]).unwrap();
+        for list in [
+            kex,
+            b"ssh-ed25519",
+            b"chacha20-poly1305@openssh.com,aes256-gcm@openssh.com",
+            b"aes256-gcm@openssh.com",
+            b"umac-64@openssh.com",
+            b"umac-64@openssh.com",
+            b"none,zlib@openssh.com",
+            b"none",
+            b"",
+            b"",
+        ] {
+            w.string(list).unwrap();
+        }
+        w.bool(true).unwrap().u32(0).unwrap();
+        w.len()
+    }
+
+    fn negotiated(kex: KexMethod, wrong_guess: bool) -> Result<Negotiated, SshError> {
+        Ok(Negotiated { kex, wrong_guess })
+    }
+
+    #[test]
+    fn negotiates_and_flags_wrong_guess() {
+        let mut ours = [0u8; 512];
+        let n = write_kexinit(&[0; 16], &mut ours).unwrap();
+        assert_eq!(
+            negotiate(&ours[..n], &ours[..n]),
+            negotiated(KexMethod::MlKem768X25519, false)
+        );
+
+        let mut theirs = [0u8; 512];
+        let m = client_kexinit(b"curve25519-sha256", &mut theirs);
+        assert_eq!(
+            negotiate(&theirs[..m], &ours[..n]),
+            negotiated(KexMethod::Curve25519, false)
+        );
+        let m = client_kexinit(b"mlkem768x25519-sha256,curve25519-sha256", &mut theirs);
+        assert_eq!(
+            negotiate(&theirs[..m], &ours[..n]),
+            negotiated(KexMethod::MlKem768X25519, false)
+        );
+        // OpenSSH 9.x : sntrup761 d'abord, repli sur curve25519.
+        let m = client_kexinit(
+            b"sntrup761x25519-sha512@openssh.com,curve25519-sha256",
+            &mut theirs,
+        );
+        assert_eq!(
+            negotiate(&theirs[..m], &ours[..n]),
+            negotiated(KexMethod::Curve25519, true)
+        );
+        let m = client_kexinit(b"diffie-hellman-group14-sha256", &mut theirs);
+        assert_eq!(
+            negotiate(&theirs[..m], &ours[..n]),
+            Err(SshError::NoCommonAlgorithm)
+        );
+    }
+
+    #[test]
+    fn both_sides_derive_the_same_keys() {
+        let host = SigningKey::from_bytes(&[9; 32]);
+        let blob = key_blob(&host.verifying_key());
+        let (c_seed, s_seed) = ([1u8; KEX_SEED_LEN], [2u8; KEX_SEED_LEN]);
+        for kex in [KexMethod::MlKem768X25519, KexMethod::Curve25519] {
+            let mut q_c = [0u8; MAX_CLIENT_EPHEMERAL];
+            let c_len = client_ephemeral(kex, &c_seed, &mut q_c);
+            let mut q_s = [0u8; MAX_SERVER_EPHEMERAL];
+            let (s_len, k_s) = server_ephemeral(kex, &s_seed, &q_c[..c_len], &mut q_s).unwrap();
+            let k_c = client_shared_secret(kex, &c_seed, &q_s[..s_len]).unwrap();
+            assert_eq!(k_s.k, k_c.k);
+            let t = ExchangeTranscript {
+                client_version: b"SSH-2.0-client",
+                server_version: b"SSH-2.0-server",
+                client_kexinit: b"ic",
+                server_kexinit: b"is",
+                host_key: &blob,
+                client_ephemeral: &q_c[..c_len],
+                server_ephemeral: &q_s[..s_len],
+            };
+            let h = t.hash(&k_s);
+            let sig = sign_exchange(&host, &h);
+            let host_pub = parse_key_blob(&blob).unwrap();
+            assert_eq!(verify_exchange(&host_pub, &h, &sig), Ok(()));
+            assert!(verify_exchange(&host_pub, &[0; 32], &sig).is_err());
+            let keys = derive_keys(&k_c, &h, &h);
+            assert_ne!(keys.client_to_server.key, keys.server_to_client.key);
+        }
+        assert_eq!(
+            shared_secret(&s_seed[..32].try_into().unwrap(), &[0; 32]),
+            Err(SshError::Crypto)
+        );
+    }
+
+    #[test]
+    fn hybrid_secret_mixes_both_halves() {
+        let kex = KexMethod::MlKem768X25519;
+        let c_seed = [3u8; KEX_SEED_LEN];
+        let mut q_c = [0u8; MAX_CLIENT_EPHEMERAL];
+        assert_eq!(
+            client_ephemeral(kex, &c_seed, &mut q_c),
+            MAX_CLIENT_EPHEMERAL
+        );
+        let mut q_s = [0u8; MAX_SERVER_EPHEMERAL];
+        let (_, k) = server_ephemeral(kex, &[4; KEX_SEED_LEN], &q_c, &mut q_s).unwrap();
+
+        // Chiffré ML-KEM altéré : rejet implicite, K diffère sans erreur.
+        let mut bad = q_s;
+        bad[0] ^= 1;
+        let k_bad = client_shared_secret(kex, &c_seed, &bad).unwrap();
+        assert_ne!(k.k, k_bad.k);
+        // Moitié X25519 remplacée par un point d'ordre faible : refus.
+        let mut weak = q_s;
+        weak[MLKEM768_CT_LEN..].fill(0);
+        assert_eq!(
+            client_shared_secret(kex, &c_seed, &weak).map(|s| s.k),
+            Err(SshError::Crypto)
+        );
+        // Blob tronqué (pair qui enverrait une clé X25519 seule).
+        assert_eq!(
+            server_ephemeral(kex, &[4; KEX_SEED_LEN], &q_c[..32], &mut q_s).map(|r| r.0),
+            Err(SshError::Protocol)
+        );
+
+        // K hybride encodé en string, pas en mpint : les hash diffèrent.
+        let as_curve = SharedSecret {
+            k: k.k,
+            kex: KexMethod::Curve25519,
+        };
+        let t = ExchangeTranscript {
+            client_version: b"c",
+            server_version: b"s",
+            client_kexinit: b"",
+            server_kexinit: b"",
+            host_key: b"",
+            client_ephemeral: b"",
+            server_ephemeral: b"",
+        };
+        assert_ne!(t.hash(&k), t.hash(&as_curve));
+    }
+}
@@ -0,0 +1,204 @@
+//! Clés d'hôte connues (TOFU). Elles vivent dans le trousseau de
+//! crypto_server sous le libellé `ssh-host:<hôte>:<port>`, le secret étant
+//! le blob ssh-ed25519 : le trousseau les lie à l'utilisateur et les garde
+//! chiffrées hors session.
+//!
+//! Un `~/.ssh/known_hosts` OpenSSH peut être importé ligne à ligne
+//! (`parse_line`) ; les entrées hachées (`|1|…`) ne sont pas reconnues.
+
+use ed25519_dalek::VerifyingKey;
+use sha2::{Digest, Sha256};
+
+use crate::kex::{self, HOST_KEY_NAME, KEY_BLOB_LEN};
+
+pub const LABEL_PREFIX: &[u8] = b"ssh-host:";
+/// `SHA256:` suivi de 43 caractères base64 sans bourrage.
+pub const FINGERPRINT_LEN: usize = 7 + 43;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HostKeyStatus {
+    Known,
+    /// Première connexion : demander confirmation puis enregistrer.
+    Unknown,
+    /// Clé différente de celle enregistrée : refuser.
+    Changed,
+}
+
+/// Libellé du trousseau pour `host:port`, `None` si `out` est trop petit.
+pub fn keyring_label(host: &[u8], port: u16, out: &mut [u8]) -> Option<usize> {
+    let mut digits = [0u8; 5];
+    let mut pos = digits.len();
+    let mut p = port;
+    loop {
+        pos -= 1;
+        digits[pos] = b'0' + (p % 10) as u8;
+        p /= 10;
+        if p == 0 {
+            break;
+        }
+    }
+    let mut len = 0;
+    for part in [LABEL_PREFIX, host, b":", &digits[pos..]] {
+        out.get_mut(len..len + part.len())?.copy_from_slice(part);
+        len += part.len();
+    }
+    Some(len)
+}
+
+/// Compare la clé présentée au blob lu dans le trousseau.
+pub fn check(stored: Option<&[u8]>, key: &VerifyingKey) -> HostKeyStatus {
+    match stored {
+        None => HostKeyStatus::Unknown,
+        Some(blob) if blob == kex::key_blob(key) => HostKeyStatus::Known,
+        Some(_) => HostKeyStatus::Changed,
+    }
+}
+
+/// Empreinte au format d'OpenSSH, à afficher lors de la confirmation.
+pub fn fingerprint(key: &VerifyingKey) -> [u8; FINGERPRINT_LEN] {
+    let digest: [u8; 32] = Sha256::digest(kex::key_blob(key)).into();
+    let mut out = [0u8; FINGERPRINT_LEN];
+    out[..7].copy_from_slice(b"SHA256:");
+    base64_encode(&digest, &mut out[7..]);
+    out
+}
+
+/// Une ligne `hôtes ssh-ed25519 <base64> [commentaire]`.
+#[derive(Clone, Copy, Debug)]
+pub struct KnownHostLine<'a> {
+    hosts: &'a [u8],
+    pub key: VerifyingKey,
+}
+
+impl KnownHostLine<'_> {
+    /// `host` seul pour le port 22, `[host]:port` sinon (convention OpenSSH).
+    pub fn matches(&self, host: &[u8], port: u16) -> bool {
+        self.hosts.split(|&b| b == b',').any(|pattern| {
+            if port == 22 && pattern == host {
+                return true;
+            }
+            let Some(rest) = pattern.strip_prefix(b"[") else {
+                return false;
+            };
+            let Some(close) = rest.iter().position(|&b| b == b']') else {
+                return false;
+            };
+            rest[..close] == *host && parse_port(&rest[close + 1..]) == Some(port)
+        })
+    }
+}
+
+fn parse_port(text: &[u8]) -> Option<u16> {
+    let digits = text.strip_prefix(b":")?;
+    if digits.is_empty() || digits.len() > 5 {
+        return None;
+    }
+    let mut port: u32 = 0;
+    for &d in digits {
+        if !d.is_ascii_digit() {
+            return None;
+        }
+        port = port * 10 + (d - b'0') as u32;
+    }
+    u16::try_from(port).ok()
+}
+
+/// `None` pour les commentaires, marqueurs (`@revoked`…), entrées hachées
+/// et types de clé autres que ssh-ed25519.
+pub fn parse_line(line: &[u8]) -> Option<KnownHostLine<'_>> {
+    let mut fields = line
+        .split(|b| b.is_ascii_whitespace())
+        .filter(|f| !f.is_empty());
+    let hosts = fields.next()?;
+    if hosts.starts_with(b"#") || hosts.starts_with(b"@") || hosts.starts_with(b"|") {
+        return None;
+    }
+    if fields.next()? != HOST_KEY_NAME {
+        return None;
+    }
+    let mut blob = [0u8; KEY_BLOB_LEN];
+    if base64_decode(fields.next()?, &mut blob)? != KEY_BLOB_LEN {
+        return None;
+    }
+    let key = kex::parse_key_blob(&blob).ok()?;
+    Some(KnownHostLine { hosts, key })
+}
+
+const B64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Base64 sans bourrage ; `out` doit faire ⌈4n/3⌉ octets.
+fn base64_encode(data: &[u8], out: &mut [u8]) {
+    let mut o = 0;
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let v = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..chunk.len() + 1 {
+            out[o] = B64[(v >> (18 - 6 * i) & 0x3f) as usize];
+            o += 1;
+        }
+    }
+}
+
+fn base64_decode(text: &[u8], out: &mut [u8]) -> Option<usize> {
+    let text = text
+        .strip_suffix(b"==")
+        .or_else(|| text.strip_suffix(b"="))
+        .unwrap_or(text);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    let mut len = 0;
+    for &c in text {
+        let v = B64.iter().position(|&b| b == c)? as u32;
+        acc = acc << 6 | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            *out.get_mut(len)? = (acc >> bits) as u8;
+            len += 1;
+        }
+    }
+    Some(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::SigningKey;
+
+    use super::*;
+
+    #[test]
+    fn labels_fingerprints_and_known_hosts_lines() {
+        let key = SigningKey::from_bytes(&[3; 32]).verifying_key();
+        let other = SigningKey::from_bytes(&[4; 32]).verifying_key();
+        let mut label = [0u8; 64];
+        let n = keyring_label(b"build.lan", 2222, &mut label).unwrap();
+        assert_eq!(&label[..n], b"ssh-host:build.lan:2222");
+        assert_eq!(keyring_label(b"build.lan", 22, &mut label[..10]), None);
+
+        let blob = kex::key_blob(&key);
+        assert_eq!(check(None, &key), HostKeyStatus::Unknown);
+        assert_eq!(check(Some(&blob), &key), HostKeyStatus::Known);
+        assert_eq!(check(Some(&blob), &other), HostKeyStatus::Changed);
+        assert!(fingerprint(&key).starts_with(b"SHA256:"));
+        assert_ne!(fingerprint(&key), fingerprint(&other));
+
+        let mut text = [0u8; 68];
+        base64_encode(&blob, &mut text);
+        let mut line = [0u8; 128];
+        let prefix = b"nas,[build.lan]:2222 ssh-ed25519 ";
+        line[..prefix.len()].copy_from_slice(prefix);
+        line[prefix.len()..prefix.len() + 68].copy_from_slice(&text);
+        let entry = parse_line(&line[..prefix.len() + 68]).unwrap();
+        assert_eq!(entry.key, key);
+        assert!(entry.matches(b"nas", 22));
+        assert!(entry.matches(b"build.lan", 2222));
+        assert!(!entry.matches(b"build.lan", 22));
+        assert!(parse_line(b"# comment").is_none());
+        assert!(parse_line(b"|1|salt|hash ssh-ed25519 AAAA").is_none());
+        assert!(parse_line(b"host ssh-rsa AAAAB3NzaC1yc2E").is_none());
+    }
+}
@@ -0,0 +1,85 @@
+#![no_std]
+//! exo-ssh — protocole SSH2 pour Exo-OS.
+//!
+//! - `wire` : types de base RFC 4251 (string, name-list, mpint).
+//! - `transport` : paquets binaires RFC 4253, en clair puis aes256-gcm@openssh.com.
+//! - `kex` : mlkem768x25519-sha256 (hybride ML-KEM-768 + X25519), repli
+//!   curve25519-sha256 (RFC 8731), clés d'hôte ssh-ed25519, dérivation.
+//! - `channel` : canaux de session, fenêtres de flux, requêtes pty/shell/exec.
+//! - `server` : session exo-sshd complète (kex → userauth → canaux).
+//! - `client` : session cliente (terminal, exec, sous-système sftp).
+//! - `known_hosts` : clés d'hôte connues via le trousseau, import OpenSSH.
+//! - `sftp` / `scp` : protocoles de transfert de fichiers sur un canal.
+//! - `sftp_fs` : backend `sftp://` du gestionnaire de fichiers (listing,
+//!   lecture) rendu dans le modèle `exo_fs::DirEntry`.
+//!
+//! Aucune E/S ici : l'hôte fournit les octets reçus, l'entropie et les
+//! décisions d'authentification via les traits `server::SessionHost` et
+//! `client::ClientHost`.
+
+pub mod channel;
+pub mod client;
+pub mod kex;
+pub mod known_hosts;
+pub mod scp;
+pub mod server;
+pub mod sftp;
+pub mod sftp_fs;
+pub mod transport;
+pub mod wire;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SshError {
+    /// Message mal formé ou inattendu à ce stade.
+    Protocol,
+    /// Aucun algorithme commun avec le pair.
+    NoCommonAlgorithm,
+    /// MAC/tag invalide ou signature refusée.
+    Crypto,
+    BufferFull,
+    /// Session terminée (DISCONNECT reçu ou envoyé).
+    Disconnected,
+}
+
+pub mod msg {
+    pub const DISCONNECT: u8 = 1;
+    pub const IGNORE: u8 = 2;
+    pub const UNIMPLEMENTED: u8 = 3;
+    pub const DEBUG: u8 = 4;
+    pub const SERVICE_REQUEST: u8 = 5;
+    pub const SERVICE_ACCEPT: u8 = 6;
+    pub const KEXINIT: u8 = 20;
+    pub const NEWKEYS: u8 = 21;
+    pub const KEX_ECDH_INIT: u8 = 30;
+    pub const KEX_ECDH_REPLY: u8 = 31;
+    pub const USERAUTH_REQUEST: u8 = 50;
+    pub const USERAUTH_FAILURE: u8 = 51;
+    pub const USERAUTH_SUCCESS: u8 = 52;
+    pub const USERAUTH_BANNER: u8 = 53;
+    pub const USERAUTH_PK_OK: u8 = 60;
+    pub const GLOBAL_REQUEST: u8 = 80;
+    pub const REQUEST_SUCCESS: u8 = 81;
+    pub const REQUEST_FAILURE: u8 = 82;
+    pub const CHANNEL_OPEN: u8 = 90;
+    pub const CHANNEL_OPEN_CONFIRMATION: u8 = 91;
+    pub const CHANNEL_OPEN_FAILURE: u8 = 92;
+    pub const CHANNEL_WINDOW_ADJUST: u8 = 93;
+    pub const CHANNEL_DATA: u8 = 94;
+    pub const CHANNEL_EXTENDED_DATA: u8 = 95;
+    pub const CHANNEL_EOF: u8 = 96;
+    pub const CHANNEL_CLOSE: u8 = 97;
+    pub const CHANNEL_REQUEST: u8 = 98;
+    pub const CHANNEL_SUCCESS: u8 = 99;
+    pub const CHANNEL_FAILURE: u8 = 100;
+}
+
+/// Codes de raison DISCONNECT (RFC 4253 §11.1).
+pub mod disconnect {
+    pub const PROTOCOL_ERROR: u32 = 2;
+    pub const KEY_EXCHANGE_FAILED: u32 = 3;
+    pub const MAC_ERROR: u32 = 5;
+    pub const SERVICE_NOT_AVAILABLE: u32 = 7;
+    pub const HOST_KEY_NOT_VERIFIABLE: u32 = 9;
+    pub const BY_APPLICATION: u32 = 11;
+    pub const NO_MORE_AUTH_METHODS: u32 = 14;
+}
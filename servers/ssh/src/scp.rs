@@ -0,0 +1,263 @@
+//! Protocole scp historique (`rcp`) sur un canal `exec` : `scp -t` côté
+//! distant pour envoyer, `scp -f` pour recevoir. Chaque en-tête est une
+//! ligne terminée par `\n`, chaque étape est acquittée par un octet.
+
+use crate::SshError;
+
+/// Acquittement positif, à envoyer aussi après chaque fichier reçu.
+pub const ACK: u8 = 0;
+/// Fin d'un répertoire ouvert par `D`.
+pub const END_DIR: &[u8] = b"E\n";
+
+/// Commande `exec` pour envoyer vers `path` (`recursive` pour un arbre).
+pub fn sink_command(path: &[u8], recursive: bool, out: &mut [u8]) -> Option<usize> {
+    let flags: &[u8] = if recursive {
+        b"scp -r -t -- "
+    } else {
+        b"scp -t -- "
+    };
+    let len = flags.len() + path.len();
+    out.get_mut(..flags.len())?.copy_from_slice(flags);
+    out.get_mut(flags.len()..len)?.copy_from_slice(path);
+    Some(len)
+}
+
+/// Commande `exec` pour recevoir `path`.
+pub fn source_command(path: &[u8], recursive: bool, out: &mut [u8]) -> Option<usize> {
+    let flags: &[u8] = if recursive {
+        b"scp -r -f -- "
+    } else {
+        b"scp -f -- "
+    };
+    let len = flags.len() + path.len();
+    out.get_mut(..flags.len())?.copy_from_slice(flags);
+    out.get_mut(flags.len()..len)?.copy_from_slice(path);
+    Some(len)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Header<'a> {
+    /// `C<mode> <taille> <nom>` : suivi de `size` octets puis d'un ACK.
+    File {
+        mode: u32,
+        size: u64,
+        name: &'a [u8],
+    },
+    /// `D<mode> 0 <nom>` : les entrées suivent jusqu'à `E`.
+    Dir {
+        mode: u32,
+        name: &'a [u8],
+    },
+    EndDir,
+    /// `T<mtime> 0 <atime> 0`, précède l'en-tête concerné avec `-p`.
+    Times {
+        mtime: u64,
+        atime: u64,
+    },
+}
+
+/// Nom sans `/` ni composant `.`/`..` : un serveur hostile ne doit pas
+/// pouvoir écrire hors du répertoire cible.
+fn safe_name(name: &[u8]) -> bool {
+    !name.is_empty() && name != b"." && name != b".." && !name.contains(&b'/')
+}
+
+pub fn encode_header(header: &Header<'_>, out: &mut [u8]) -> Result<usize, SshError> {
+    let mut w = LineWriter { out, len: 0 };
+    match *header {
+        Header::File { mode, size, name } => {
+            w.put(b"C")?;
+            w.octal(mode & 0o7777)?;
+            w.put(b" ")?;
+            w.decimal(size)?;
+            w.put(b" ")?;
+            w.put(name)?;
+        }
+        Header::Dir { mode, name } => {
+            w.put(b"D")?;
+            w.octal(mode & 0o7777)?;
+            w.put(b" 0 ")?;
+            w.put(name)?;
+        }
+        Header::EndDir => w.put(b"E")?,
+        Header::Times { mtime, atime } => {
+            w.put(b"T")?;
+            w.decimal(mtime)?;
+            w.put(b" 0 ")?;
+            w.decimal(atime)?;
+            w.put(b" 0")?;
+        }
+    }
+    w.put(b"\n")?;
+    Ok(w.len)
+}
+
+/// Message du pair : en-tête, ou erreur `\x01`/`\x02` suivie d'un texte.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Incoming<'a> {
+    Header(Header<'a>),
+    /// `fatal` : le pair abandonne le transfert.
+    Error {
+        fatal: bool,
+        message: &'a [u8],
+    },
+}
+
+/// Lit une ligne au début de `buf` ; `Ok(None)` si elle est incomplète.
+pub fn parse_line(buf: &[u8]) -> Result<Option<(usize, Incoming<'_>)>, SshError> {
+    let Some(eol) = buf.iter().position(|&b| b == b'\n') else {
+        return Ok(None);
+    };
+    let line = &buf[..eol];
+    let (&kind, rest) = line.split_first().ok_or(SshError::Protocol)?;
+    let msg = match kind {
+        1 | 2 => Incoming::Error {
+            fatal: kind == 2,
+            message: rest,
+        },
+        b'C' | b'D' => {
+            let mut fields = rest.splitn(3, |&b| b == b' ');
+            let mode = parse_num(fields.next().ok_or(SshError::Protocol)?, 8)? as u32;
+            let size = parse_num(fields.next().ok_or(SshError::Protocol)?, 10)?;
+            let name = fields.next().ok_or(SshError::Protocol)?;
+            if !safe_name(name) {
+                return Err(SshError::Protocol);
+            }
+            Incoming::Header(if kind == b'C' {
+                Header::File { mode, size, name }
+            } else {
+                Header::Dir { mode, name }
+            })
+        }
+        b'E' if rest.is_empty() => Incoming::Header(Header::EndDir),
+        b'T' => {
+            let mut fields = rest.split(|&b| b == b' ');
+            let mtime = parse_num(fields.next().ok_or(SshError::Protocol)?, 10)?;
+            fields.next();
+            let atime = parse_num(fields.next().ok_or(SshError::Protocol)?, 10)?;
+            Incoming::Header(Header::Times { mtime, atime })
+        }
+        _ => return Err(SshError::Protocol),
+    };
+    Ok(Some((eol + 1, msg)))
+}
+
+/// Octet d'acquittement : `Ok(true)` pour ACK ; un avertissement ou une
+/// erreur fatale est suivi d'une ligne à lire avec `parse_line`.
+pub fn check_ack(byte: u8) -> Result<bool, SshError> {
+    match byte {
+        ACK => Ok(true),
+        1 | 2 => Ok(false),
+        _ => Err(SshError::Protocol),
+    }
+}
+
+fn parse_num(text: &[u8], radix: u64) -> Result<u64, SshError> {
+    if text.is_empty() {
+        return Err(SshError::Protocol);
+    }
+    text.iter().try_fold(0u64, |acc, &d| {
+        let v = (d as char)
+            .to_digit(radix as u32)
+            .ok_or(SshError::Protocol)? as u64;
+        acc.checked_mul(radix)
+            .and_then(|a| a.checked_add(v))
+            .ok_or(SshError::Protocol)
+    })
+}
+
+struct LineWriter<'a> {
+    out: &'a mut [u8],
+    len: usize,
+}
+
+impl LineWriter<'_> {
+    fn put(&mut self, bytes: &[u8]) -> Result<(), SshError> {
+        self.out
+            .get_mut(self.len..self.len + bytes.len())
+            .ok_or(SshError::BufferFull)?
+            .copy_from_slice(bytes);
+        self.len += bytes.len();
+        Ok(())
+    }
+
+    fn decimal(&mut self, mut v: u64) -> Result<(), SshError> {
+        let mut digits = [0u8; 20];
+        let mut pos = digits.len();
+        loop {
+            pos -= 1;
+            digits[pos] = b'0' + (v % 10) as u8;
+            v /= 10;
+            if v == 0 {
+                break;
+            }
+        }
+        self.put(&digits[pos..])
+    }
+
+    /// Toujours sur 4 chiffres (`0644`), comme scp.
+    fn octal(&mut self, mode: u32) -> Result<(), SshError> {
+        let digits = [3, 2, 1, 0].map(|i| b'0' + ((mode >> (3 * i)) & 7) as u8);
+        self.put(&digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers_roundtrip_and_reject_traversal() {
+        let mut buf = [0u8; 64];
+        let n = encode_header(
+            &Header::File {
+                mode: 0o100644,
+                size: 1234,
+                name: b"notes.txt",
+            },
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(&buf[..n], b"C0644 1234 notes.txt\n");
+        let (used, msg) = parse_line(&buf[..n]).unwrap().unwrap();
+        assert_eq!(used, n);
+        assert_eq!(
+            msg,
+            Incoming::Header(Header::File {
+                mode: 0o644,
+                size: 1234,
+                name: b"notes.txt"
+            })
+        );
+        assert_eq!(parse_line(b"C0644 12 note"), Ok(None));
+        assert_eq!(
+            parse_line(b"C0644 1 ../../etc/passwd\n"),
+            Err(SshError::Protocol)
+        );
+        assert_eq!(parse_line(b"D0755 0 ..\n"), Err(SshError::Protocol));
+        assert_eq!(
+            parse_line(b"\x02scp: /x: No such file\n")
+                .unwrap()
+                .unwrap()
+                .1,
+            Incoming::Error {
+                fatal: true,
+                message: b"scp: /x: No such file"
+            }
+        );
+        assert_eq!(
+            parse_line(b"T1700000000 0 1700000001 0\n")
+                .unwrap()
+                .unwrap()
+                .1,
+            Incoming::Header(Header::Times {
+                mtime: 1_700_000_000,
+                atime: 1_700_000_001
+            })
+        );
+        let n = sink_command(b"/srv/www", true, &mut buf).unwrap();
+        assert_eq!(&buf[..n], b"scp -r -t -- /srv/www");
+        assert_eq!(check_ack(0), Ok(true));
+        assert_eq!(check_ack(b'x'), Err(SshError::Protocol));
+    }
+}
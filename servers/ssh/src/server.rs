@@ -0,0 +1,1289 @@
+//! Session serveur exo-sshd : échange de versions, kex, `ssh-userauth`
+//! (password / publickey ed25519) puis `ssh-connection` (canaux de session).
+//!
+//! Le service lit la socket, passe les octets à `receive()` puis écrit
+//! `output()` ; tout le reste (entropie, comptes, pty, processus) passe par
+//! `SessionHost`. Un KEXINIT après le premier échange ouvre un re-keying
+//! (RFC 4253 §9) : la session garde sa phase et son identifiant, les
+//! réponses de la couche service attendent le NEWKEYS suivant.
+
+use ed25519_dalek::{Signature, SigningKey, Verifier, VerifyingKey};
+
+use crate::channel::{ChannelState, ChannelTable, PtyRequest, LOCAL_MAX_PACKET, LOCAL_WINDOW};
+use crate::kex::{self, ExchangeTranscript, KexMethod, SessionKeys, HOST_KEY_NAME};
+use crate::transport::{self, Deferred, PacketIo, GCM_TAG_LEN, MAX_PACKET_LEN, REKEY_BYTES};
+use crate::wire::{Reader, Writer};
+use crate::{disconnect, msg, SshError};
+
+pub const SERVER_VERSION: &[u8] = b"SSH-2.0-ExoSSH_0.1";
+/// Un paquet complet doit tenir dans le tampon d'entrée.
+pub const IN_BUF: usize = 4 + MAX_PACKET_LEN + GCM_TAG_LEN;
+pub const OUT_BUF: usize = 16 * 1024;
+pub const MAX_AUTH_ATTEMPTS: u32 = 6;
+/// RFC 4253 §4.2 : 255 octets CR LF compris.
+const MAX_VERSION_LINE: usize = 255;
+/// OpenSSH envoie ~1,5 Kio de listes d'algorithmes.
+const MAX_CLIENT_KEXINIT: usize = 4096;
+const MAX_SERVER_KEXINIT: usize = 512;
+const MAX_USER: usize = 64;
+const AUTH_METHODS: &[u8] = b"publickey,password";
+/// KEX_ECDH_REPLY hybride : clé d'hôte, chiffré ML-KEM + X25519, signature.
+const MAX_ECDH_REPLY: usize =
+    1 + kex::KEY_BLOB_LEN + 4 + kex::MAX_SERVER_EPHEMERAL + 4 + kex::SIGNATURE_BLOB_LEN + 8;
+/// Place gardée en sortie avant de traiter un paquet : la réponse la plus
+/// longue (KEX_ECDH_REPLY) avec son en-tête, son bourrage et son tag.
+const REPLY_ROOM: usize = MAX_ECDH_REPLY + 64;
+
+/// Raisons CHANNEL_OPEN_FAILURE (RFC 4254 §5.1).
+const OPEN_UNKNOWN_CHANNEL_TYPE: u32 = 3;
+const OPEN_RESOURCE_SHORTAGE: u32 = 4;
+
+/// Ce que la session délègue au service. Les identifiants de canal sont
+/// les identifiants locaux.
+pub trait SessionHost {
+    /// Entropie pour le cookie KEXINIT, la clé éphémère et le bourrage.
+    fn fill_random(&mut self, buf: &mut [u8]);
+    fn check_password(&mut self, user: &[u8], password: &[u8]) -> bool;
+    /// `true` si `key` figure dans les clés autorisées de `user`.
+    fn authorized_key(&mut self, user: &[u8], key: &VerifyingKey) -> bool;
+    fn open_pty(&mut self, channel: u32, pty: &PtyRequest) -> bool;
+    fn start_shell(&mut self, channel: u32) -> bool;
+    fn exec(&mut self, channel: u32, command: &[u8]) -> bool;
+    fn set_env(&mut self, _channel: u32, _name: &[u8], _value: &[u8]) -> bool {
+        false
+    }
+    fn channel_data(&mut self, channel: u32, data: &[u8]);
+    fn window_change(&mut self, _channel: u32, _cols: u32, _rows: u32) {}
+    fn channel_eof(&mut self, _channel: u32) {}
+    fn channel_closed(&mut self, channel: u32);
+}
+
+/// Re-échange de clés, une fois le premier échange terminé.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Rekey {
+    Idle,
+    /// Notre KEXINIT est parti, le client n'a pas encore envoyé le sien.
+    Sent,
+    /// KEXINIT échangés, KEX_ECDH_INIT attendu.
+    Ecdh,
+    /// Notre NEWKEYS est parti, celui du client est attendu.
+    NewKeys,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Phase {
+    Version,
+    KexInit,
+    KexEcdh,
+    NewKeys,
+    Service,
+    Auth,
+    Connected,
+    Closed,
+}
+
+struct Core<H: SessionHost> {
+    host: H,
+    host_key: SigningKey,
+    io: PacketIo,
+    phase: Phase,
+    out: [u8; OUT_BUF],
+    out_len: usize,
+    client_version: [u8; MAX_VERSION_LINE],
+    client_version_len: usize,
+    client_kexinit: [u8; MAX_CLIENT_KEXINIT],
+    client_kexinit_len: usize,
+    server_kexinit: [u8; MAX_SERVER_KEXINIT],
+    server_kexinit_len: usize,
+    kex: KexMethod,
+    /// Le client a deviné le mauvais kex : son premier paquet est à ignorer.
+    skip_guessed: bool,
+    rekey: Rekey,
+    rekey_limit: u64,
+    deferred: Deferred,
+    session_id: [u8; 32],
+    incoming_keys: Option<SessionKeys>,
+    auth_attempts: u32,
+    user: [u8; MAX_USER],
+    user_len: usize,
+    channels: ChannelTable,
+}
+
+pub struct ServerSession<H: SessionHost> {
+    rx: [u8; IN_BUF],
+    rx_len: usize,
+    core: Core<H>,
+}
+
+impl<H: SessionHost> ServerSession<H> {
+    /// Prépare la ligne de version et le KEXINIT du serveur dans `output()`.
+    pub fn new(host_key: SigningKey, host: H) -> Self {
+        let mut core = Core {
+            host,
+            host_key,
+            io: PacketIo::new(),
+            phase: Phase::Version,
+            out: [0; OUT_BUF],
+            out_len: 0,
+            client_version: [0; MAX_VERSION_LINE],
+            client_version_len: 0,
+            client_kexinit: [0; MAX_CLIENT_KEXINIT],
+            client_kexinit_len: 0,
+            server_kexinit: [0; MAX_SERVER_KEXINIT],
+            server_kexinit_len: 0,
+            kex: KexMethod::Curve25519,
+            skip_guessed: false,
+            rekey: Rekey::Idle,
+            rekey_limit: REKEY_BYTES,
+            deferred: Deferred::new(),
+            session_id: [0; 32],
+            incoming_keys: None,
+            auth_attempts: 0,
+            user: [0; MAX_USER],
+            user_len: 0,
+            channels: ChannelTable::new(),
+        };
+        core.out[..SERVER_VERSION.len()].copy_from_slice(SERVER_VERSION);
+        core.out[SERVER_VERSION.len()..SERVER_VERSION.len() + 2].copy_from_slice(b"\r\n");
+        core.out_len = SERVER_VERSION.len() + 2;
+        // Tailles fixes : le KEXINIT serveur tient toujours.
+        let _ = core.send_kexinit();
+        Self {
+            rx: [0; IN_BUF],
+            rx_len: 0,
+            core,
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.core.phase
+    }
+
+    pub fn host(&self) -> &H {
+        &self.core.host
+    }
+
+    pub fn host_mut(&mut self) -> &mut H {
+        &mut self.core.host
+    }
+
+    /// Utilisateur authentifié, une fois en phase `Connected`.
+    pub fn user(&self) -> Option<&[u8]> {
+        (self.core.phase == Phase::Connected).then(|| &self.core.user[..self.core.user_len])
+    }
+
+    /// Octets à écrire sur la socket.
+    pub fn output(&self) -> &[u8] {
+        &self.core.out[..self.core.out_len]
+    }
+
+    pub fn advance_output(&mut self, n: usize) {
+        let n = n.min(self.core.out_len);
+        self.core.out.copy_within(n..self.core.out_len, 0);
+        self.core.out_len -= n;
+        if let Err(e) = self.core.flush_deferred() {
+            self.core.fail(e);
+        }
+    }
+
+    /// Lance un re-échange de clés ; sans effet si un échange est déjà en
+    /// cours. `send_data` retourne 0 jusqu'à notre NEWKEYS.
+    pub fn rekey(&mut self) -> Result<(), SshError> {
+        if !self.core.established() {
+            return Err(SshError::Protocol);
+        }
+        if self.core.rekey == Rekey::Idle {
+            self.core.start_rekey()?;
+        }
+        Ok(())
+    }
+
+    /// Un re-échange de clés est en cours.
+    pub fn rekeying(&self) -> bool {
+        self.core.rekey != Rekey::Idle
+    }
+
+    /// Octets (dans les deux sens) après lesquels le serveur relance
+    /// l'échange de clés ; `REKEY_BYTES` par défaut.
+    pub fn set_rekey_limit(&mut self, bytes: u64) {
+        self.core.rekey_limit = bytes;
+    }
+
+    /// Consomme des octets reçus et traite tous les paquets complets.
+    /// Retourne le nombre d'octets acceptés : le reste est à représenter
+    /// une fois `output()` vidé. Sur erreur, un DISCONNECT est laissé dans
+    /// `output()` et la session passe en `Closed`.
+    pub fn receive(&mut self, bytes: &[u8]) -> Result<usize, SshError> {
+        if self.core.phase == Phase::Closed {
+            return Err(SshError::Disconnected);
+        }
+        let n = bytes.len().min(IN_BUF - self.rx_len);
+        self.rx[self.rx_len..self.rx_len + n].copy_from_slice(&bytes[..n]);
+        self.rx_len += n;
+        match self.process() {
+            Ok(()) => Ok(n),
+            Err(e) => {
+                self.core.fail(e);
+                Err(e)
+            }
+        }
+    }
+
+    fn process(&mut self) -> Result<(), SshError> {
+        loop {
+            if self.core.phase == Phase::Closed {
+                return Err(SshError::Disconnected);
+            }
+            if self.core.phase == Phase::Version {
+                let Some(eol) = self.rx[..self.rx_len].iter().position(|&b| b == b'\n') else {
+                    if self.rx_len >= MAX_VERSION_LINE {
+                        return Err(SshError::Protocol);
+                    }
+                    return Ok(());
+                };
+                self.core.version_line(&self.rx[..eol])?;
+                self.consume_rx(eol + 1);
+                continue;
+            }
+            if OUT_BUF - self.core.out_len < REPLY_ROOM {
+                return Ok(());
+            }
+            let Some((used, at, len)) = self.core.io.open(&mut self.rx[..self.rx_len])? else {
+                return Ok(());
+            };
+            self.core.dispatch(&self.rx[at..at + len])?;
+            self.consume_rx(used);
+            self.core.maybe_rekey()?;
+        }
+    }
+
+    fn consume_rx(&mut self, n: usize) {
+        self.rx.copy_within(n..self.rx_len, 0);
+        self.rx_len -= n;
+    }
+
+    /// Envoie des données sur un canal dans la limite de la fenêtre du pair,
+    /// de sa taille de paquet et de la place en sortie. Retourne le nombre
+    /// d'octets pris (0 : réessayer après WINDOW_ADJUST, `advance_output`
+    /// ou la fin d'un échange de clés).
+    pub fn send_data(&mut self, channel: u32, data: &[u8]) -> Result<usize, SshError> {
+        let core = &mut self.core;
+        let ch = core.channels.get(channel).ok_or(SshError::Protocol)?;
+        if ch.eof_sent || ch.state == ChannelState::Closing {
+            return Err(SshError::Protocol);
+        }
+        core.maybe_rekey()?;
+        if core.holds_output() {
+            return Ok(0);
+        }
+        let ch = core.channels.get(channel).ok_or(SshError::Protocol)?;
+        // En-tête CHANNEL_DATA, longueur, bourrage max et tag.
+        let overhead = 9 + 5 + 32 + GCM_TAG_LEN;
+        let room = (OUT_BUF - core.out_len).saturating_sub(overhead);
+        let n = data
+            .len()
+            .min(ch.remote_window as usize)
+            .min(ch.remote_max_packet as usize)
+            .min(LOCAL_MAX_PACKET as usize)
+            .min(room);
+        if n == 0 {
+            return Ok(0);
+        }
+        let remote = ch.remote_id;
+        let mut buf = [0u8; 9 + LOCAL_MAX_PACKET as usize];
+        let mut w = Writer::new(&mut buf);
+        w.u8(msg::CHANNEL_DATA)?.u32(remote)?.string(&data[..n])?;
+        let len = w.len();
+        core.send(&buf[..len])?;
+        if let Some(ch) = core.channels.get_mut(channel) {
+            ch.remote_window -= n as u32;
+        }
+        Ok(n)
+    }
+
+    pub fn send_exit_status(&mut self, channel: u32, status: u32) -> Result<(), SshError> {
+        let remote = self.core.remote_id(channel)?;
+        self.core.send_with(|w| {
+            w.u8(msg::CHANNEL_REQUEST)?
+                .u32(remote)?
+                .string(b"exit-status")?
+                .bool(false)?
+                .u32(status)?;
+            Ok(())
+        })
+    }
+
+    pub fn send_eof(&mut self, channel: u32) -> Result<(), SshError> {
+        let remote = self.core.remote_id(channel)?;
+        self.core.send_with(|w| {
+            w.u8(msg::CHANNEL_EOF)?.u32(remote)?;
+            Ok(())
+        })?;
+        if let Some(ch) = self.core.channels.get_mut(channel) {
+            ch.eof_sent = true;
+        }
+        Ok(())
+    }
+
+    /// Envoie CHANNEL_CLOSE ; le canal est libéré à la réception de celui
+    /// du pair.
+    pub fn close(&mut self, channel: u32) -> Result<(), SshError> {
+        let remote = self.core.remote_id(channel)?;
+        if self.core.channels.get(channel).map(|c| c.state) == Some(ChannelState::Closing) {
+            return Ok(());
+        }
+        self.core.send_with(|w| {
+            w.u8(msg::CHANNEL_CLOSE)?.u32(remote)?;
+            Ok(())
+        })?;
+        if let Some(ch) = self.core.channels.get_mut(channel) {
+            ch.state = ChannelState::Closing;
+        }
+        Ok(())
+    }
+
+    /// Fermeture à l'initiative du serveur (arrêt du service, timeout).
+    pub fn disconnect(&mut self, reason: u32, description: &str) {
+        self.core.send_disconnect(reason, description);
+    }
+}
+
+impl<H: SessionHost> Core<H> {
+    fn send(&mut self, payload: &[u8]) -> Result<(), SshError> {
+        if self.holds_output() && transport::held_during_kex(payload[0]) {
+            return self.deferred.push(payload);
+        }
+        let host = &mut self.host;
+        let n = self.io.seal(
+            payload,
+            &mut |b| host.fill_random(b),
+            &mut self.out[self.out_len..],
+        )?;
+        self.out_len += n;
+        Ok(())
+    }
+
+    /// KEXINIT du serveur, avec un cookie neuf à chaque échange.
+    fn send_kexinit(&mut self) -> Result<(), SshError> {
+        let mut cookie = [0u8; 16];
+        self.host.fill_random(&mut cookie);
+        let n = kex::write_kexinit(&cookie, &mut self.server_kexinit)?;
+        self.server_kexinit_len = n;
+        let mut kexinit = [0u8; MAX_SERVER_KEXINIT];
+        kexinit[..n].copy_from_slice(&self.server_kexinit[..n]);
+        self.send(&kexinit[..n])
+    }
+
+    /// Premier échange terminé (NEWKEYS reçu) et session encore ouverte.
+    fn established(&self) -> bool {
+        !matches!(
+            self.phase,
+            Phase::Version | Phase::KexInit | Phase::KexEcdh | Phase::NewKeys | Phase::Closed
+        )
+    }
+
+    /// Notre KEXINIT est parti sans notre NEWKEYS, ou des paquets attendent
+    /// encore : la couche service ne peut rien émettre.
+    fn holds_output(&self) -> bool {
+        matches!(self.rekey, Rekey::Sent | Rekey::Ecdh) || !self.deferred.is_empty()
+    }
+
+    fn start_rekey(&mut self) -> Result<(), SshError> {
+        self.send_kexinit()?;
+        self.rekey = Rekey::Sent;
+        Ok(())
+    }
+
+    fn maybe_rekey(&mut self) -> Result<(), SshError> {
+        if self.rekey == Rekey::Idle
+            && self.established()
+            && self.io.bytes_since_keys() >= self.rekey_limit
+        {
+            self.start_rekey()?;
+        }
+        Ok(())
+    }
+
+    /// Réémet les paquets retenus tant qu'ils tiennent en sortie.
+    fn flush_deferred(&mut self) -> Result<(), SshError> {
+        if matches!(self.rekey, Rekey::Sent | Rekey::Ecdh) {
+            return Ok(());
+        }
+        while let Some(payload) = self.deferred.front() {
+            let host = &mut self.host;
+            match self.io.seal(
+                payload,
+                &mut |b| host.fill_random(b),
+                &mut self.out[self.out_len..],
+            ) {
+                Ok(n) => self.out_len += n,
+                Err(SshError::BufferFull) => return Ok(()),
+                Err(e) => return Err(e),
+            }
+            self.deferred.pop();
+        }
+        Ok(())
+    }
+
+    fn send_with<F>(&mut self, build: F) -> Result<(), SshError>
+    where
+        F: FnOnce(&mut Writer<'_>) -> Result<(), SshError>,
+    {
+        let mut buf = [0u8; 512];
+        let mut w = Writer::new(&mut buf);
+        build(&mut w)?;
+        let n = w.len();
+        self.send(&buf[..n])
+    }
+
+    fn send_disconnect(&mut self, reason: u32, description: &str) {
+        if self.phase == Phase::Closed {
+            return;
+        }
+        let _ = self.send_with(|w| {
+            w.u8(msg::DISCONNECT)?
+                .u32(reason)?
+                .string(description.as_bytes())?
+                .string(b"")?;
+            Ok(())
+        });
+        self.phase = Phase::Closed;
+    }
+
+    fn fail(&mut self, e: SshError) {
+        let (reason, text) = match e {
+            SshError::Disconnected => {
+                self.phase = Phase::Closed;
+                return;
+            }
+            SshError::NoCommonAlgorithm => (disconnect::KEY_EXCHANGE_FAILED, "no common algorithm"),
+            SshError::Crypto if self.phase == Phase::KexEcdh || self.rekey == Rekey::Ecdh => {
+                (disconnect::KEY_EXCHANGE_FAILED, "key exchange failed")
+            }
+            SshError::Crypto => (disconnect::MAC_ERROR, "bad packet authentication"),
+            SshError::Protocol | SshError::BufferFull => {
+                (disconnect::PROTOCOL_ERROR, "protocol error")
+            }
+        };
+        self.send_disconnect(reason, text);
+    }
+
+    fn remote_id(&self, channel: u32) -> Result<u32, SshError> {
+        Ok(self
+            .channels
+            .get(channel)
+            .ok_or(SshError::Protocol)?
+            .remote_id)
+    }
+
+    fn version_line(&mut self, line: &[u8]) -> Result<(), SshError> {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        // Les lignes avant l'identification sont tolérées (RFC 4253 §4.2).
+        if !line.starts_with(b"SSH-") {
+            return Ok(());
+        }
+        if line.len() > MAX_VERSION_LINE - 2
+            || (!line.starts_with(b"SSH-2.0-") && !line.starts_with(b"SSH-1.99-"))
+        {
+            return Err(SshError::Protocol);
+        }
+        self.client_version[..line.len()].copy_from_slice(line);
+        self.client_version_len = line.len();
+        self.phase = Phase::KexInit;
+        Ok(())
+    }
+
+    fn dispatch(&mut self, payload: &[u8]) -> Result<(), SshError> {
+        let id = *payload.first().ok_or(SshError::Protocol)?;
+        match id {
+            msg::DISCONNECT => return Err(SshError::Disconnected),
+            msg::IGNORE | msg::DEBUG | msg::UNIMPLEMENTED => return Ok(()),
+            _ => {}
+        }
+        if self.skip_guessed && (30..=49).contains(&id) {
+            self.skip_guessed = false;
+            return Ok(());
+        }
+        if self.rekey != Rekey::Idle || (id == msg::KEXINIT && self.established()) {
+            match (self.rekey, id) {
+                (Rekey::Idle | Rekey::Sent, msg::KEXINIT) => {
+                    if self.rekey == Rekey::Idle {
+                        self.send_kexinit()?;
+                    }
+                    self.client_kexinit(payload)?;
+                    self.rekey = Rekey::Ecdh;
+                    return Ok(());
+                }
+                (Rekey::Ecdh, msg::KEX_ECDH_INIT) => return self.ecdh_init(payload),
+                (Rekey::NewKeys, msg::NEWKEYS) => {
+                    let keys = self.incoming_keys.take().ok_or(SshError::Protocol)?;
+                    self.io.set_incoming(&keys.client_to_server);
+                    self.rekey = Rekey::Idle;
+                    return Ok(());
+                }
+                // Le client n'a pas encore vu notre KEXINIT : ses messages
+                // sont traités, nos réponses attendent.
+                (Rekey::Sent, _) => {}
+                // Plus rien d'autre que le kex entre son KEXINIT et son NEWKEYS.
+                _ => return Err(SshError::Protocol),
+            }
+        }
+        match (self.phase, id) {
+            (Phase::KexInit, msg::KEXINIT) => {
+                self.client_kexinit(payload)?;
+                self.phase = Phase::KexEcdh;
+                Ok(())
+            }
+            (Phase::KexEcdh, msg::KEX_ECDH_INIT) => self.ecdh_init(payload),
+            (Phase::NewKeys, msg::NEWKEYS) => {
+                let keys = self.incoming_keys.take().ok_or(SshError::Protocol)?;
+                self.io.set_incoming(&keys.client_to_server);
+                self.phase = Phase::Service;
+                Ok(())
+            }
+            (Phase::Service, msg::SERVICE_REQUEST) => {
+                let mut r = Reader::new(&payload[1..]);
+                let service = r.string()?;
+                if service != b"ssh-userauth" {
+                    self.send_disconnect(disconnect::SERVICE_NOT_AVAILABLE, "unknown service");
+                    return Err(SshError::Disconnected);
+                }
+                self.send_with(|w| {
+                    w.u8(msg::SERVICE_ACCEPT)?.string(service)?;
+                    Ok(())
+                })?;
+                self.phase = Phase::Auth;
+                Ok(())
+            }
+            (Phase::Auth, msg::USERAUTH_REQUEST) => self.userauth(payload),
+            // Un USERAUTH_REQUEST après succès est ignoré (RFC 4252 §5.1).
+            (Phase::Connected, msg::USERAUTH_REQUEST) => Ok(()),
+            (Phase::Connected, 80..=127) => self.connection(id, &payload[1..]),
+            (Phase::Connected, _) => {
+                let seq = self.io.last_seq_in();
+                self.send_with(|w| {
+                    w.u8(msg::UNIMPLEMENTED)?.u32(seq)?;
+                    Ok(())
+                })
+            }
+            _ => Err(SshError::Protocol),
+        }
+    }
+
+    fn client_kexinit(&mut self, payload: &[u8]) -> Result<(), SshError> {
+        if payload.len() > MAX_CLIENT_KEXINIT {
+            return Err(SshError::Protocol);
+        }
+        let chosen = kex::negotiate(payload, &self.server_kexinit[..self.server_kexinit_len])?;
+        self.kex = chosen.kex;
+        self.skip_guessed = chosen.wrong_guess;
+        self.client_kexinit[..payload.len()].copy_from_slice(payload);
+        self.client_kexinit_len = payload.len();
+        Ok(())
+    }
+
+    fn ecdh_init(&mut self, payload: &[u8]) -> Result<(), SshError> {
+        let mut r = Reader::new(&payload[1..]);
+        let q_c = r.string()?;
+        let mut seed = [0u8; kex::KEX_SEED_LEN];
+        self.host.fill_random(&mut seed);
+        let mut q_s = [0u8; kex::MAX_SERVER_EPHEMERAL];
+        let res = kex::server_ephemeral(self.kex, &seed, q_c, &mut q_s);
+        seed.fill(0);
+        let (q_s_len, shared) = res?;
+        let q_s = &q_s[..q_s_len];
+        let host_blob = kex::key_blob(&self.host_key.verifying_key());
+        let h = ExchangeTranscript {
+            client_version: &self.client_version[..self.client_version_len],
+            server_version: SERVER_VERSION,
+            client_kexinit: &self.client_kexinit[..self.client_kexinit_len],
+            server_kexinit: &self.server_kexinit[..self.server_kexinit_len],
+            host_key: &host_blob,
+            client_ephemeral: q_c,
+            server_ephemeral: q_s,
+        }
+        .hash(&shared);
+        // Le H du premier échange reste l'identifiant de session.
+        let first = self.phase == Phase::KexEcdh;
+        if first {
+            self.session_id = h;
+        }
+        let sig = kex::sign_exchange(&self.host_key, &h);
+        let mut reply = [0u8; MAX_ECDH_REPLY];
+        let mut w = Writer::new(&mut reply);
+        w.u8(msg::KEX_ECDH_REPLY)?
+            .string(&host_blob)?
+            .string(q_s)?
+            .string(&sig)?;
+        let n = w.len();
+        self.send(&reply[..n])?;
+        self.send(&[msg::NEWKEYS])?;
+        let keys = kex::derive_keys(&shared, &h, &self.session_id);
+        self.io.set_outgoing(&keys.server_to_client);
+        self.incoming_keys = Some(keys);
+        if first {
+            self.phase = Phase::NewKeys;
+            return Ok(());
+        }
+        self.rekey = Rekey::NewKeys;
+        self.flush_deferred()
+    }
+
+    fn userauth(&mut self, payload: &[u8]) -> Result<(), SshError> {
+        let mut r = Reader::new(&payload[1..]);
+        let user = r.string()?;
+        let service = r.string()?;
+        let method = r.string()?;
+        let ok = if user.len() > MAX_USER || service != b"ssh-connection" {
+            false
+        } else {
+            match method {
+                // Sonde des méthodes disponibles : ne compte pas comme essai.
+                b"none" => return self.auth_failure(false),
+                b"password" => {
+                    let change = r.bool()?;
+                    let password = r.string()?;
+                    !change && self.host.check_password(user, password)
+                }
+                b"publickey" => match self.publickey(user, &mut r)? {
+                    Some(ok) => ok,
+                    None => return Ok(()),
+                },
+                _ => false,
+            }
+        };
+        if !ok {
+            return self.auth_failure(true);
+        }
+        self.user[..user.len()].copy_from_slice(user);
+        self.user_len = user.len();
+        self.send(&[msg::USERAUTH_SUCCESS])?;
+        self.phase = Phase::Connected;
+        Ok(())
+    }
+
+    /// `None` quand la requête était une simple question (PK_OK envoyé).
+    fn publickey(&mut self, user: &[u8], r: &mut Reader<'_>) -> Result<Option<bool>, SshError> {
+        let signed = r.bool()?;
+        let alg = r.string()?;
+        let blob = r.string()?;
+        if alg != HOST_KEY_NAME {
+            return Ok(Some(false));
+        }
+        let Ok(key) = kex::parse_key_blob(blob) else {
+            return Ok(Some(false));
+        };
+        if !self.host.authorized_key(user, &key) {
+            return Ok(Some(false));
+        }
+        if !signed {
+            self.send_with(|w| {
+                w.u8(msg::USERAUTH_PK_OK)?.string(alg)?.string(blob)?;
+                Ok(())
+            })?;
+            return Ok(None);
+        }
+        let Ok(sig) = kex::parse_signature_blob(r.string()?) else {
+            return Ok(Some(false));
+        };
+        // RFC 4252 §7 : la signature couvre l'identifiant de session et la
+        // requête elle-même.
+        let mut data = [0u8; 512];
+        let mut w = Writer::new(&mut data);
+        w.string(&self.session_id)?
+            .u8(msg::USERAUTH_REQUEST)?
+            .string(user)?
+            .string(b"ssh-connection")?
+            .string(b"publickey")?
+            .bool(true)?
+            .string(alg)?
+            .string(blob)?;
+        let n = w.len();
+        Ok(Some(verify(&key, &data[..n], &sig)))
+    }
+
+    fn auth_failure(&mut self, counts: bool) -> Result<(), SshError> {
+        if counts {
+            self.auth_attempts += 1;
+            if self.auth_attempts >= MAX_AUTH_ATTEMPTS {
+                self.send_disconnect(
+                    disconnect::NO_MORE_AUTH_METHODS,
+                    "too many authentication failures",
+                );
+                return Err(SshError::Disconnected);
+            }
+        }
+        self.send_with(|w| {
+            w.u8(msg::USERAUTH_FAILURE)?
+                .string(AUTH_METHODS)?
+                .bool(false)?;
+            Ok(())
+        })
+    }
+
+    fn connection(&mut self, id: u8, body: &[u8]) -> Result<(), SshError> {
+        let mut r = Reader::new(body);
+        match id {
+            msg::GLOBAL_REQUEST => {
+                let _name = r.string()?;
+                if r.bool()? {
+                    self.send(&[msg::REQUEST_FAILURE])?;
+                }
+                Ok(())
+            }
+            msg::CHANNEL_OPEN => {
+                let kind = r.string()?;
+                let sender = r.u32()?;
+                let window = r.u32()?;
+                let max_packet = r.u32()?;
+                let opened = if kind == b"session" {
+                    self.channels
+                        .open(sender, window, max_packet)
+                        .ok_or(OPEN_RESOURCE_SHORTAGE)
+                } else {
+                    Err(OPEN_UNKNOWN_CHANNEL_TYPE)
+                };
+                self.send_with(|w| {
+                    match opened {
+                        Ok(local) => {
+                            w.u8(msg::CHANNEL_OPEN_CONFIRMATION)?
+                                .u32(sender)?
+                                .u32(local)?
+                                .u32(LOCAL_WINDOW)?
+                                .u32(LOCAL_MAX_PACKET)?;
+                        }
+                        Err(reason) => {
+                            w.u8(msg::CHANNEL_OPEN_FAILURE)?
+                                .u32(sender)?
+                                .u32(reason)?
+                                .string(b"")?
+                                .string(b"")?;
+                        }
+                    }
+                    Ok(())
+                })
+            }
+            msg::CHANNEL_WINDOW_ADJUST => {
+                let ch = r.u32()?;
+                let add = r.u32()?;
+                let ch = self.channels.get_mut(ch).ok_or(SshError::Protocol)?;
+                ch.remote_window = ch.remote_window.saturating_add(add);
+                Ok(())
+            }
+            msg::CHANNEL_DATA | msg::CHANNEL_EXTENDED_DATA => {
+                let ch = r.u32()?;
+                if id == msg::CHANNEL_EXTENDED_DATA {
+                    r.u32()?;
+                }
+                let data = r.string()?;
+                if data.len() > LOCAL_MAX_PACKET as usize {
+                    return Err(SshError::Protocol);
+                }
+                let refill = self
+                    .channels
+                    .consume_local(ch, data.len() as u32)
+                    .ok_or(SshError::Protocol)?;
+                // Un client n'a pas de raison d'envoyer sur stderr : ignoré.
+                if id == msg::CHANNEL_DATA {
+                    self.host.channel_data(ch, data);
+                }
+                if refill > 0 {
+                    let remote = self.remote_id(ch)?;
+                    self.send_with(|w| {
+                        w.u8(msg::CHANNEL_WINDOW_ADJUST)?.u32(remote)?.u32(refill)?;
+                        Ok(())
+                    })?;
+                }
+                Ok(())
+            }
+            msg::CHANNEL_EOF => {
+                let ch = r.u32()?;
+                self.channels
+                    .get_mut(ch)
+                    .ok_or(SshError::Protocol)?
+                    .eof_received = true;
+                self.host.channel_eof(ch);
+                Ok(())
+            }
+            msg::CHANNEL_CLOSE => {
+                let ch = r.u32()?;
+                let closed = self.channels.remove(ch).ok_or(SshError::Protocol)?;
+                if closed.state != ChannelState::Closing {
+                    self.send_with(|w| {
+                        w.u8(msg::CHANNEL_CLOSE)?.u32(closed.remote_id)?;
+                        Ok(())
+                    })?;
+                }
+                self.host.channel_closed(ch);
+                Ok(())
+            }
+            msg::CHANNEL_REQUEST => self.channel_request(&mut r),
+            msg::REQUEST_SUCCESS
+            | msg::REQUEST_FAILURE
+            | msg::CHANNEL_SUCCESS
+            | msg::CHANNEL_FAILURE => Ok(()),
+            _ => {
+                let seq = self.io.last_seq_in();
+                self.send_with(|w| {
+                    w.u8(msg::UNIMPLEMENTED)?.u32(seq)?;
+                    Ok(())
+                })
+            }
+        }
+    }
+
+    fn channel_request(&mut self, r: &mut Reader<'_>) -> Result<(), SshError> {
+        let ch = r.u32()?;
+        let name = r.string()?;
+        let want_reply = r.bool()?;
+        let state = self.channels.get(ch).ok_or(SshError::Protocol)?.state;
+        let ok = match name {
+            b"pty-req" => {
+                let term = r.string()?;
+                let pty = PtyRequest::new(term, r.u32()?, r.u32()?, r.u32()?, r.u32()?);
+                // Modes du terminal (RFC 4254 §8) : laissés au pty.
+                r.string()?;
+                let ok = state == ChannelState::Open && self.host.open_pty(ch, &pty);
+                if ok {
+                    if let Some(c) = self.channels.get_mut(ch) {
+                        c.pty = Some(pty);
+                    }
+                }
+                ok
+            }
+            b"env" => {
+                let var = r.string()?;
+                let value = r.string()?;
+                state == ChannelState::Open && self.host.set_env(ch, var, value)
+            }
+            b"shell" | b"exec" => {
+                let ok = state == ChannelState::Open
+                    && if name == b"shell" {
+                        self.host.start_shell(ch)
+                    } else {
+                        let command = r.string()?;
+                        self.host.exec(ch, command)
+                    };
+                if ok {
+                    if let Some(c) = self.channels.get_mut(ch) {
+                        c.state = ChannelState::Running;
+                    }
+                }
+                ok
+            }
+            b"window-change" => {
+                let (cols, rows) = (r.u32()?, r.u32()?);
+                let (width_px, height_px) = (r.u32()?, r.u32()?);
+                if let Some(pty) = self.channels.get_mut(ch).and_then(|c| c.pty.as_mut()) {
+                    pty.cols = cols;
+                    pty.rows = rows;
+                    pty.width_px = width_px;
+                    pty.height_px = height_px;
+                }
+                self.host.window_change(ch, cols, rows);
+                true
+            }
+            _ => false,
+        };
+        if want_reply {
+            let remote = self.remote_id(ch)?;
+            let reply = if ok {
+                msg::CHANNEL_SUCCESS
+            } else {
+                msg::CHANNEL_FAILURE
+            };
+            self.send_with(|w| {
+                w.u8(reply)?.u32(remote)?;
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+}
+
+fn verify(key: &VerifyingKey, data: &[u8], sig: &Signature) -> bool {
+    key.verify(data, sig).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec::Vec;
+
+    use ed25519_dalek::Signer;
+
+    use super::*;
+    use crate::transport::CIPHER_NAME;
+
+    #[derive(Default)]
+    struct TestHost {
+        rng: u8,
+        user_key: Option<VerifyingKey>,
+        pty: Option<PtyRequest>,
+        shell: bool,
+        received: Vec<u8>,
+        closed: bool,
+    }
+
+    impl SessionHost for TestHost {
+        fn fill_random(&mut self, buf: &mut [u8]) {
+            for b in buf {
+                self.rng = self.rng.wrapping_mul(13).wrapping_add(7);
+                *b = self.rng;
+            }
+        }
+        fn check_password(&mut self, user: &[u8], password: &[u8]) -> bool {
+            user == b"alice" && password == b"hunter2"
+        }
+        fn authorized_key(&mut self, user: &[u8], key: &VerifyingKey) -> bool {
+            user == b"alice" && Some(*key) == self.user_key
+        }
+        fn open_pty(&mut self, _channel: u32, pty: &PtyRequest) -> bool {
+            self.pty = Some(*pty);
+            true
+        }
+        fn start_shell(&mut self, _channel: u32) -> bool {
+            self.shell = true;
+            true
+        }
+        fn exec(&mut self, _channel: u32, _command: &[u8]) -> bool {
+            false
+        }
+        fn channel_data(&mut self, _channel: u32, data: &[u8]) {
+            self.received.extend_from_slice(data);
+        }
+        fn channel_closed(&mut self, _channel: u32) {
+            self.closed = true;
+        }
+    }
+
+    struct Client {
+        io: PacketIo,
+        inbox: Vec<u8>,
+    }
+
+    impl Client {
+        fn send(&mut self, srv: &mut ServerSession<TestHost>, payload: &[u8]) {
+            let mut wire = [0u8; 4096];
+            let n = self
+                .io
+                .seal(payload, &mut |b| b.fill(0), &mut wire)
+                .unwrap();
+            assert_eq!(srv.receive(&wire[..n]), Ok(n));
+        }
+
+        fn next(&mut self, srv: &mut ServerSession<TestHost>) -> Vec<u8> {
+            self.inbox.extend_from_slice(srv.output());
+            srv.advance_output(srv.output().len());
+            let (used, at, len) = self.io.open(&mut self.inbox).unwrap().unwrap();
+            let payload = self.inbox[at..at + len].to_vec();
+            self.inbox.drain(..used);
+            payload
+        }
+    }
+
+    fn build(f: impl FnOnce(&mut Writer<'_>) -> Result<(), SshError>) -> Vec<u8> {
+        let mut buf = [0u8; 2048];
+        let mut w = Writer::new(&mut buf);
+        f(&mut w).unwrap();
+        let n = w.len();
+        buf[..n].to_vec()
+    }
+
+    fn userauth(user_key: &SigningKey, session_id: &[u8; 32], sign: bool) -> Vec<u8> {
+        let blob = kex::key_blob(&user_key.verifying_key());
+        let body = |w: &mut Writer<'_>| -> Result<(), SshError> {
+            w.u8(msg::USERAUTH_REQUEST)?
+                .string(b"alice")?
+                .string(b"ssh-connection")?
+                .string(b"publickey")?
+                .bool(sign)?
+                .string(HOST_KEY_NAME)?
+                .string(&blob)?;
+            Ok(())
+        };
+        let mut request = build(body);
+        if sign {
+            let signed = build(|w| {
+                w.string(session_id)?;
+                body(w)
+            });
+            let sig = kex::signature_blob(&user_key.sign(&signed));
+            request.extend_from_slice(&build(|w| {
+                w.string(&sig)?;
+                Ok(())
+            }));
+        }
+        request
+    }
+
+    /// Échange KEX_ECDH_INIT/REPLY puis NEWKEYS côté client de test ;
+    /// retourne H après vérification de la signature du serveur.
+    fn ecdh(
+        c: &mut Client,
+        srv: &mut ServerSession<TestHost>,
+        method: KexMethod,
+        c_seed: &[u8; kex::KEX_SEED_LEN],
+        client_kexinit: &[u8],
+        server_kexinit: &[u8],
+        host_key: &SigningKey,
+    ) -> [u8; 32] {
+        let mut q_c = [0u8; kex::MAX_CLIENT_EPHEMERAL];
+        let q_c_len = kex::client_ephemeral(method, c_seed, &mut q_c);
+        let q_c = &q_c[..q_c_len];
+        c.send(
+            srv,
+            &build(|w| {
+                w.u8(msg::KEX_ECDH_INIT)?.string(q_c)?;
+                Ok(())
+            }),
+        );
+        let reply = c.next(srv);
+        assert_eq!(reply[0], msg::KEX_ECDH_REPLY);
+        let mut r = Reader::new(&reply[1..]);
+        let (k_s, q_s, sig) = (
+            r.string().unwrap(),
+            r.string().unwrap(),
+            r.string().unwrap(),
+        );
+        assert_eq!(kex::parse_key_blob(k_s).unwrap(), host_key.verifying_key());
+        let shared = kex::client_shared_secret(method, c_seed, q_s).unwrap();
+        let h = ExchangeTranscript {
+            client_version: b"SSH-2.0-Test",
+            server_version: SERVER_VERSION,
+            client_kexinit,
+            server_kexinit,
+            host_key: k_s,
+            client_ephemeral: q_c,
+            server_ephemeral: q_s,
+        }
+        .hash(&shared);
+        kex::verify_exchange(&host_key.verifying_key(), &h, sig).unwrap();
+        assert_eq!(c.next(srv), [msg::NEWKEYS]);
+        let keys = kex::derive_keys(&shared, &h, &h);
+        c.io.set_incoming(&keys.server_to_client);
+        c.send(srv, &[msg::NEWKEYS]);
+        c.io.set_outgoing(&keys.client_to_server);
+        h
+    }
+
+    #[test]
+    fn full_session_handshake() {
+        let host_key = SigningKey::from_bytes(&[4; 32]);
+        let user_key = SigningKey::from_bytes(&[8; 32]);
+        let host = TestHost {
+            user_key: Some(user_key.verifying_key()),
+            ..TestHost::default()
+        };
+        let mut srv = ServerSession::new(host_key.clone(), host);
+        let mut c = Client {
+            io: PacketIo::new(),
+            inbox: Vec::new(),
+        };
+
+        // Versions.
+        let banner = srv.output().to_vec();
+        assert!(banner.starts_with(b"SSH-2.0-ExoSSH_0.1\r\n"));
+        srv.advance_output(SERVER_VERSION.len() + 2);
+        assert_eq!(srv.receive(b"SSH-2.0-Test\r\n"), Ok(14));
+        assert_eq!(srv.phase(), Phase::KexInit);
+
+        // Kex.
+        let server_kexinit = c.next(&mut srv);
+        assert_eq!(server_kexinit[0], msg::KEXINIT);
+        let mut client_kexinit = [0u8; 512];
+        let n = kex::write_kexinit(&[3; 16], &mut client_kexinit).unwrap();
+        c.send(&mut srv, &client_kexinit[..n]);
+        let c_seed = [5u8; kex::KEX_SEED_LEN];
+        let h = ecdh(
+            &mut c,
+            &mut srv,
+            KexMethod::MlKem768X25519,
+            &c_seed,
+            &client_kexinit[..n],
+            &server_kexinit,
+            &host_key,
+        );
+
+        // Authentification.
+        c.send(
+            &mut srv,
+            &build(|w| {
+                w.u8(msg::SERVICE_REQUEST)?.string(b"ssh-userauth")?;
+                Ok(())
+            }),
+        );
+        assert_eq!(c.next(&mut srv)[0], msg::SERVICE_ACCEPT);
+        c.send(
+            &mut srv,
+            &build(|w| {
+                w.u8(msg::USERAUTH_REQUEST)?
+                    .string(b"alice")?
+                    .string(b"ssh-connection")?
+                    .string(b"password")?
+                    .bool(false)?
+                    .string(b"wrong")?;
+                Ok(())
+            }),
+        );
+        assert_eq!(c.next(&mut srv)[0], msg::USERAUTH_FAILURE);
+        c.send(&mut srv, &userauth(&user_key, &h, false));
+        assert_eq!(c.next(&mut srv)[0], msg::USERAUTH_PK_OK);
+        c.send(&mut srv, &userauth(&user_key, &h, true));
+        assert_eq!(c.next(&mut srv), [msg::USERAUTH_SUCCESS]);
+        assert_eq!(srv.user(), Some(&b"alice"[..]));
+
+        // Canal de session.
+        let open = |kind: &'static [u8]| {
+            build(move |w| {
+                w.u8(msg::CHANNEL_OPEN)?
+                    .string(kind)?
+                    .u32(42)?
+                    .u32(1000)?
+                    .u32(32768)?;
+                Ok(())
+            })
+        };
+        c.send(&mut srv, &open(b"direct-tcpip"));
+        assert_eq!(c.next(&mut srv)[0], msg::CHANNEL_OPEN_FAILURE);
+        c.send(&mut srv, &open(b"session"));
+        let confirm = c.next(&mut srv);
+        let mut r = Reader::new(&confirm[1..]);
+        assert_eq!((r.u32().unwrap(), r.u32().unwrap()), (42, 0));
+        c.send(
+            &mut srv,
+            &build(|w| {
+                w.u8(msg::CHANNEL_REQUEST)?
+                    .u32(0)?
+                    .string(b"pty-req")?
+                    .bool(true)?
+                    .string(b"xterm-256color")?
+                    .u32(80)?
+                    .u32(24)?
+                    .u32(0)?
+                    .u32(0)?
+                    .string(b"\0")?;
+                Ok(())
+            }),
+        );
+        assert_eq!(c.next(&mut srv), [msg::CHANNEL_SUCCESS, 0, 0, 0, 42]);
+        c.send(
+            &mut srv,
+            &build(|w| {
+                w.u8(msg::CHANNEL_REQUEST)?
+                    .u32(0)?
+                    .string(b"shell")?
+                    .bool(true)?;
+                Ok(())
+            }),
+        );
+        assert_eq!(c.next(&mut srv)[0], msg::CHANNEL_SUCCESS);
+        assert_eq!(srv.host().pty.unwrap().term(), b"xterm-256color");
+        assert!(srv.host().shell);
+
+        c.send(
+            &mut srv,
+            &build(|w| {
+                w.u8(msg::CHANNEL_DATA)?.u32(0)?.string(b"ls\n")?;
+                Ok(())
+            }),
+        );
+        assert_eq!(srv.host().received, b"ls\n");
+
+        // Fenêtre du client : 1000 octets.
+        assert_eq!(srv.send_data(0, &[b'x'; 1500]), Ok(1000));
+        assert_eq!(srv.send_data(0, b"more"), Ok(0));
+        let data = c.next(&mut srv);
+        assert_eq!(&data[..5], &[msg::CHANNEL_DATA, 0, 0, 0, 42]);
+        assert_eq!(data.len(), 9 + 1000);
+        c.send(
+            &mut srv,
+            &build(|w| {
+                w.u8(msg::CHANNEL_WINDOW_ADJUST)?.u32(0)?.u32(500)?;
+                Ok(())
+            }),
+        );
+        assert_eq!(srv.send_data(0, &[b'y'; 800]), Ok(500));
+        assert_eq!(c.next(&mut srv).len(), 9 + 500);
+
+        // Message inconnu, puis fermeture.
+        c.send(&mut srv, &[192]);
+        assert_eq!(c.next(&mut srv)[0], msg::UNIMPLEMENTED);
+        srv.send_exit_status(0, 0).unwrap();
+        srv.close(0).unwrap();
+        assert_eq!(c.next(&mut srv)[0], msg::CHANNEL_REQUEST);
+        assert_eq!(c.next(&mut srv)[0], msg::CHANNEL_CLOSE);
+        c.send(&mut srv, &[msg::CHANNEL_CLOSE, 0, 0, 0, 0]);
+        assert!(srv.host().closed);
+        assert!(srv.output().is_empty());
+    }
+
+    #[test]
+    fn curve25519_fallback_for_peers_without_mlkem() {
+        let host_key = SigningKey::from_bytes(&[4; 32]);
+        let mut srv = ServerSession::new(host_key.clone(), TestHost::default());
+        let mut c = Client {
+            io: PacketIo::new(),
+            inbox: Vec::new(),
+        };
+        srv.advance_output(SERVER_VERSION.len() + 2);
+        srv.receive(b"SSH-2.0-Test\r\n").unwrap();
+        let server_kexinit = c.next(&mut srv);
+        let mut client_kexinit = [0u8; 512];
+        let mut w = Writer::new(&mut client_kexinit);
+        w.u8(msg::KEXINIT).unwrap().raw(&[0; 16]).unwrap();
+        for list in [
+            &b"sntrup761x25519-sha512@openssh.com,curve25519-sha256"[..],
+            HOST_KEY_NAME,
+            CIPHER_NAME,
+            CIPHER_NAME,
+            kex::MAC_NAMES,
+            kex::MAC_NAMES,
+            b"none",
+            b"none",
+            b"",
+            b"",
+        ] {
+            w.string(list).unwrap();
+        }
+        // Paquet deviné pour sntrup761 : le serveur doit l'ignorer.
+        w.bool(true).unwrap().u32(0).unwrap();
+        let n = w.len();
+        c.send(&mut srv, &client_kexinit[..n]);
+        c.send(&mut srv, &[msg::KEX_ECDH_INIT, 0, 0, 0, 1, 0]);
+        assert_eq!(srv.phase(), Phase::KexEcdh);
+        let h = ecdh(
+            &mut c,
+            &mut srv,
+            KexMethod::Curve25519,
+            &[6; kex::KEX_SEED_LEN],
+            &client_kexinit[..n],
+            &server_kexinit,
+            &host_key,
+        );
+        assert_eq!(srv.phase(), Phase::Service);
+        assert_eq!(srv.core.session_id, h);
+    }
+
+    #[test]
+    fn too_many_auth_failures_disconnect() {
+        let mut srv = ServerSession::new(SigningKey::from_bytes(&[1; 32]), TestHost::default());
+        srv.core.phase = Phase::Auth;
+        srv.advance_output(usize::MAX);
+        let request = build(|w| {
+            w.u8(msg::USERAUTH_REQUEST)?
+                .string(b"root")?
+                .string(b"ssh-connection")?
+                .string(b"password")?
+                .bool(false)?
+                .string(b"guess")?;
+            Ok(())
+        });
+        for _ in 1..MAX_AUTH_ATTEMPTS {
+            srv.core.dispatch(&request).unwrap();
+        }
+        assert_eq!(srv.core.dispatch(&request), Err(SshError::Disconnected));
+        assert_eq!(srv.phase(), Phase::Closed);
+        assert_eq!(srv.receive(b"x"), Err(SshError::Disconnected));
+    }
+}
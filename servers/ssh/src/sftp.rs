@@ -0,0 +1,482 @@
+//! Client SFTP version 3 (draft-ietf-secsh-filexfer-02), le dialecte parlé
+//! par OpenSSH. Les paquets transitent sur un canal après la requête
+//! `subsystem "sftp"` ; ce module ne fait qu'encoder les requêtes et
+//! découper les réponses, le backend réseau du gestionnaire de fichiers
+//! (`sftp_fs`) fait correspondre les identifiants.
+
+use crate::wire::{Reader, Writer};
+use crate::SshError;
+
+pub const VERSION: u32 = 3;
+
+pub mod packet {
+    pub const INIT: u8 = 1;
+    pub const VERSION: u8 = 2;
+    pub const OPEN: u8 = 3;
+    pub const CLOSE: u8 = 4;
+    pub const READ: u8 = 5;
+    pub const WRITE: u8 = 6;
+    pub const LSTAT: u8 = 7;
+    pub const OPENDIR: u8 = 11;
+    pub const READDIR: u8 = 12;
+    pub const REMOVE: u8 = 13;
+    pub const MKDIR: u8 = 14;
+    pub const RMDIR: u8 = 15;
+    pub const REALPATH: u8 = 16;
+    pub const STAT: u8 = 17;
+    pub const RENAME: u8 = 18;
+    pub const STATUS: u8 = 101;
+    pub const HANDLE: u8 = 102;
+    pub const DATA: u8 = 103;
+    pub const NAME: u8 = 104;
+    pub const ATTRS: u8 = 105;
+}
+
+/// Drapeaux de `open`.
+pub mod open_flags {
+    pub const READ: u32 = 0x01;
+    pub const WRITE: u32 = 0x02;
+    pub const APPEND: u32 = 0x04;
+    pub const CREAT: u32 = 0x08;
+    pub const TRUNC: u32 = 0x10;
+    pub const EXCL: u32 = 0x20;
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Status {
+    Ok,
+    Eof,
+    NoSuchFile,
+    PermissionDenied,
+    Failure,
+    BadMessage,
+    NoConnection,
+    ConnectionLost,
+    OpUnsupported,
+    Other(u32),
+}
+
+impl Status {
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            0 => Status::Ok,
+            1 => Status::Eof,
+            2 => Status::NoSuchFile,
+            3 => Status::PermissionDenied,
+            4 => Status::Failure,
+            5 => Status::BadMessage,
+            6 => Status::NoConnection,
+            7 => Status::ConnectionLost,
+            8 => Status::OpUnsupported,
+            other => Status::Other(other),
+        }
+    }
+}
+
+const ATTR_SIZE: u32 = 0x01;
+const ATTR_UIDGID: u32 = 0x02;
+const ATTR_PERMISSIONS: u32 = 0x04;
+const ATTR_ACMODTIME: u32 = 0x08;
+const ATTR_EXTENDED: u32 = 0x8000_0000;
+
+const S_IFMT: u32 = 0o170000;
+const S_IFDIR: u32 = 0o040000;
+const S_IFLNK: u32 = 0o120000;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Attrs {
+    pub size: Option<u64>,
+    pub uid_gid: Option<(u32, u32)>,
+    pub permissions: Option<u32>,
+    /// (atime, mtime) en secondes Unix.
+    pub times: Option<(u32, u32)>,
+}
+
+impl Attrs {
+    pub fn is_dir(&self) -> bool {
+        self.permissions.is_some_and(|p| p & S_IFMT == S_IFDIR)
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.permissions.is_some_and(|p| p & S_IFMT == S_IFLNK)
+    }
+
+    fn write(&self, w: &mut Writer<'_>) -> Result<(), SshError> {
+        let flags = self.size.map_or(0, |_| ATTR_SIZE)
+            | self.uid_gid.map_or(0, |_| ATTR_UIDGID)
+            | self.permissions.map_or(0, |_| ATTR_PERMISSIONS)
+            | self.times.map_or(0, |_| ATTR_ACMODTIME);
+        w.u32(flags)?;
+        if let Some(size) = self.size {
+            w.u64(size)?;
+        }
+        if let Some((uid, gid)) = self.uid_gid {
+            w.u32(uid)?.u32(gid)?;
+        }
+        if let Some(p) = self.permissions {
+            w.u32(p)?;
+        }
+        if let Some((atime, mtime)) = self.times {
+            w.u32(atime)?.u32(mtime)?;
+        }
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self, SshError> {
+        let flags = r.u32()?;
+        let mut a = Attrs::default();
+        if flags & ATTR_SIZE != 0 {
+            a.size = Some(r.u64()?);
+        }
+        if flags & ATTR_UIDGID != 0 {
+            a.uid_gid = Some((r.u32()?, r.u32()?));
+        }
+        if flags & ATTR_PERMISSIONS != 0 {
+            a.permissions = Some(r.u32()?);
+        }
+        if flags & ATTR_ACMODTIME != 0 {
+            a.times = Some((r.u32()?, r.u32()?));
+        }
+        if flags & ATTR_EXTENDED != 0 {
+            for _ in 0..r.u32()? {
+                r.string()?;
+                r.string()?;
+            }
+        }
+        Ok(a)
+    }
+}
+
+/// Requêtes client. Chaque paquet est préfixé de sa longueur.
+#[derive(Clone, Copy, Debug)]
+pub enum Request<'a> {
+    Open {
+        path: &'a [u8],
+        flags: u32,
+        attrs: Attrs,
+    },
+    Close {
+        handle: &'a [u8],
+    },
+    Read {
+        handle: &'a [u8],
+        offset: u64,
+        len: u32,
+    },
+    Write {
+        handle: &'a [u8],
+        offset: u64,
+        data: &'a [u8],
+    },
+    Stat {
+        path: &'a [u8],
+    },
+    Lstat {
+        path: &'a [u8],
+    },
+    OpenDir {
+        path: &'a [u8],
+    },
+    ReadDir {
+        handle: &'a [u8],
+    },
+    Remove {
+        path: &'a [u8],
+    },
+    MkDir {
+        path: &'a [u8],
+        attrs: Attrs,
+    },
+    RmDir {
+        path: &'a [u8],
+    },
+    RealPath {
+        path: &'a [u8],
+    },
+    Rename {
+        from: &'a [u8],
+        to: &'a [u8],
+    },
+}
+
+/// Premier paquet de la session : `SSH_FXP_INIT` version 3.
+pub fn encode_init(out: &mut [u8]) -> Result<usize, SshError> {
+    let mut w = Writer::new(out);
+    w.u32(5)?.u8(packet::INIT)?.u32(VERSION)?;
+    Ok(w.len())
+}
+
+pub fn encode_request(id: u32, req: &Request<'_>, out: &mut [u8]) -> Result<usize, SshError> {
+    let (head, body) = out.split_at_mut(4.min(out.len()));
+    if head.len() < 4 {
+        return Err(SshError::BufferFull);
+    }
+    let mut w = Writer::new(body);
+    let kind = match req {
+        Request::Open { .. } => packet::OPEN,
+        Request::Close { .. } => packet::CLOSE,
+        Request::Read { .. } => packet::READ,
+        Request::Write { .. } => packet::WRITE,
+        Request::Stat { .. } => packet::STAT,
+        Request::Lstat { .. } => packet::LSTAT,
+        Request::OpenDir { .. } => packet::OPENDIR,
+        Request::ReadDir { .. } => packet::READDIR,
+        Request::Remove { .. } => packet::REMOVE,
+        Request::MkDir { .. } => packet::MKDIR,
+        Request::RmDir { .. } => packet::RMDIR,
+        Request::RealPath { .. } => packet::REALPATH,
+        Request::Rename { .. } => packet::RENAME,
+    };
+    w.u8(kind)?.u32(id)?;
+    match *req {
+        Request::Open { path, flags, attrs } => {
+            w.string(path)?.u32(flags)?;
+            attrs.write(&mut w)?;
+        }
+        Request::Close { handle } | Request::ReadDir { handle } => {
+            w.string(handle)?;
+        }
+        Request::Read {
+            handle,
+            offset,
+            len,
+        } => {
+            w.string(handle)?.u64(offset)?.u32(len)?;
+        }
+        Request::Write {
+            handle,
+            offset,
+            data,
+        } => {
+            w.string(handle)?.u64(offset)?.string(data)?;
+        }
+        Request::Stat { path }
+        | Request::Lstat { path }
+        | Request::OpenDir { path }
+        | Request::Remove { path }
+        | Request::RmDir { path }
+        | Request::RealPath { path } => {
+            w.string(path)?;
+        }
+        Request::MkDir { path, attrs } => {
+            w.string(path)?;
+            attrs.write(&mut w)?;
+        }
+        Request::Rename { from, to } => {
+            w.string(from)?.string(to)?;
+        }
+    }
+    let len = w.len();
+    head.copy_from_slice(&(len as u32).to_be_bytes());
+    Ok(4 + len)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Response<'a> {
+    Version(u32),
+    Status {
+        id: u32,
+        status: Status,
+        message: &'a [u8],
+    },
+    Handle {
+        id: u32,
+        handle: &'a [u8],
+    },
+    Data {
+        id: u32,
+        data: &'a [u8],
+    },
+    Name {
+        id: u32,
+        entries: NameEntries<'a>,
+    },
+    Attrs {
+        id: u32,
+        attrs: Attrs,
+    },
+}
+
+impl Response<'_> {
+    pub fn id(&self) -> Option<u32> {
+        match *self {
+            Response::Version(_) => None,
+            Response::Status { id, .. }
+            | Response::Handle { id, .. }
+            | Response::Data { id, .. }
+            | Response::Name { id, .. }
+            | Response::Attrs { id, .. } => Some(id),
+        }
+    }
+}
+
+/// Découpe une réponse au début du flux du canal. `Ok(None)` : paquet
+/// incomplet ; sinon `(octets consommés, réponse)`.
+pub fn parse_response(buf: &[u8]) -> Result<Option<(usize, Response<'_>)>, SshError> {
+    let mut r = Reader::new(buf);
+    let Ok(len) = r.u32() else {
+        return Ok(None);
+    };
+    let Ok(body) = r.raw(len as usize) else {
+        return Ok(None);
+    };
+    let mut r = Reader::new(body);
+    let kind = r.u8()?;
+    let resp = match kind {
+        // Les extensions annoncées après la version sont ignorées.
+        packet::VERSION => Response::Version(r.u32()?),
+        packet::STATUS => {
+            let id = r.u32()?;
+            let status = Status::from_code(r.u32()?);
+            // Les serveurs v3 anciens omettent message et langue.
+            let message = r.string().unwrap_or(&[]);
+            Response::Status {
+                id,
+                status,
+                message,
+            }
+        }
+        packet::HANDLE => Response::Handle {
+            id: r.u32()?,
+            handle: r.string()?,
+        },
+        packet::DATA => Response::Data {
+            id: r.u32()?,
+            data: r.string()?,
+        },
+        packet::NAME => {
+            let id = r.u32()?;
+            let count = r.u32()?;
+            Response::Name {
+                id,
+                entries: NameEntries {
+                    reader: Reader::new(r.remaining()),
+                    left: count,
+                },
+            }
+        }
+        packet::ATTRS => Response::Attrs {
+            id: r.u32()?,
+            attrs: Attrs::read(&mut r)?,
+        },
+        _ => return Err(SshError::Protocol),
+    };
+    Ok(Some((4 + len as usize, resp)))
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NameEntry<'a> {
+    pub filename: &'a [u8],
+    /// Ligne façon `ls -l`, seulement indicative.
+    pub longname: &'a [u8],
+    pub attrs: Attrs,
+}
+
+/// Entrées d'une réponse `SSH_FXP_NAME` (READDIR, REALPATH).
+#[derive(Clone, Copy)]
+pub struct NameEntries<'a> {
+    reader: Reader<'a>,
+    left: u32,
+}
+
+impl core::fmt::Debug for NameEntries<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NameEntries")
+            .field("left", &self.left)
+            .finish()
+    }
+}
+
+impl<'a> Iterator for NameEntries<'a> {
+    type Item = Result<NameEntry<'a>, SshError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.left == 0 {
+            return None;
+        }
+        self.left -= 1;
+        let r = &mut self.reader;
+        let entry = (|| {
+            Ok(NameEntry {
+                filename: r.string()?,
+                longname: r.string()?,
+                attrs: Attrs::read(r)?,
+            })
+        })();
+        if entry.is_err() {
+            self.left = 0;
+        }
+        Some(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_requests_and_parses_replies() {
+        let mut buf = [0u8; 128];
+        let n = encode_init(&mut buf).unwrap();
+        assert_eq!(&buf[..n], &[0, 0, 0, 5, packet::INIT, 0, 0, 0, 3]);
+
+        let req = Request::Open {
+            path: b"/srv/a.txt",
+            flags: open_flags::WRITE | open_flags::CREAT | open_flags::TRUNC,
+            attrs: Attrs {
+                permissions: Some(0o644),
+                ..Attrs::default()
+            },
+        };
+        let n = encode_request(7, &req, &mut buf).unwrap();
+        assert_eq!(
+            u32::from_be_bytes(buf[..4].try_into().unwrap()) as usize,
+            n - 4
+        );
+        assert_eq!(buf[4], packet::OPEN);
+        assert_eq!(&buf[5..9], &7u32.to_be_bytes());
+        assert_eq!(
+            encode_request(1, &req, &mut buf[..16]),
+            Err(SshError::BufferFull)
+        );
+
+        // NAME avec deux entrées, la seconde étant un répertoire.
+        let mut reply = [0u8; 128];
+        let mut w = Writer::new(&mut reply[4..]);
+        w.u8(packet::NAME).unwrap().u32(9).unwrap().u32(2).unwrap();
+        w.string(b"a.txt")
+            .unwrap()
+            .string(b"-rw-r--r-- a.txt")
+            .unwrap();
+        w.u32(ATTR_SIZE).unwrap().u64(42).unwrap();
+        w.string(b"src").unwrap().string(b"drwxr-xr-x src").unwrap();
+        w.u32(ATTR_PERMISSIONS)
+            .unwrap()
+            .u32(S_IFDIR | 0o755)
+            .unwrap();
+        let len = w.len();
+        reply[..4].copy_from_slice(&(len as u32).to_be_bytes());
+        assert!(parse_response(&reply[..len + 3]).unwrap().is_none());
+        let (used, resp) = parse_response(&reply[..len + 4]).unwrap().unwrap();
+        assert_eq!((used, resp.id()), (len + 4, Some(9)));
+        let Response::Name { mut entries, .. } = resp else {
+            panic!("expected NAME");
+        };
+        let a = entries.next().unwrap().unwrap();
+        assert_eq!((a.filename, a.attrs.size), (&b"a.txt"[..], Some(42)));
+        let src = entries.next().unwrap().unwrap();
+        assert!(src.attrs.is_dir() && !a.attrs.is_dir());
+        assert!(entries.next().is_none());
+
+        let status = [0, 0, 0, 9, packet::STATUS, 0, 0, 0, 4, 0, 0, 0, 1];
+        let (_, resp) = parse_response(&status).unwrap().unwrap();
+        assert!(matches!(
+            resp,
+            Response::Status {
+                id: 4,
+                status: Status::Eof,
+                ..
+            }
+        ));
+    }
+}
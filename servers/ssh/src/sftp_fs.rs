@@ -0,0 +1,518 @@
+//! Backend `sftp://` du gestionnaire de fichiers : liste un répertoire et
+//! lit un fichier distants sur le sous-système `sftp` d'une session
+//! cliente, et rend les entrées dans le modèle partagé `exo_fs::DirEntry`.
+//!
+//! Une seule opération à la fois. L'hôte envoie ce que chaque appel écrit
+//! dans `out` sur le canal, et repasse les octets reçus à [`SftpFs::feed`]
+//! jusqu'à [`Event::Done`] ou [`Event::Failed`].
+
+use exo_fs::{DirEntry, EntryKind};
+
+use crate::sftp::{self, open_flags, Attrs, NameEntries, Request, Response, Status};
+use crate::SshError;
+
+/// Taille maximale d'un handle (draft-ietf-secsh-filexfer-02 §3).
+pub const HANDLE_MAX: usize = 256;
+/// Octets demandés par `SSH_FXP_READ` ; OpenSSH plafonne à 255 Kio.
+pub const READ_CHUNK: u32 = 32 * 1024;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Phase {
+    /// INIT envoyé, VERSION attendue.
+    Init,
+    Idle,
+    OpenDir,
+    ReadDir,
+    Open,
+    Read,
+    /// CLOSE envoyé ; `Some` si l'opération a échoué entre-temps.
+    Close(Option<Status>),
+}
+
+/// Ce qu'une réponse a produit. Les octets à renvoyer sont comptés à part.
+#[derive(Debug)]
+pub enum Event<'a> {
+    /// Étape intermédiaire (handle reçu, fermeture en cours).
+    None,
+    /// Le serveur a répondu à INIT : `list` et `read` sont possibles.
+    Ready,
+    /// Lot d'entrées du répertoire listé ; d'autres peuvent suivre.
+    Entries(Entries<'a>),
+    /// Morceau du fichier lu, à la position `offset`.
+    Data {
+        offset: u64,
+        data: &'a [u8],
+    },
+    /// Opération terminée, handle fermé.
+    Done,
+    Failed(Status),
+}
+
+/// Entrées d'un `SSH_FXP_NAME`, sans `.` ni `..`.
+#[derive(Debug)]
+pub struct Entries<'a> {
+    names: NameEntries<'a>,
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = Result<DirEntry<'a>, SshError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = match self.names.next()? {
+                Ok(entry) => entry,
+                Err(e) => return Some(Err(e)),
+            };
+            if entry.filename == b"." || entry.filename == b".." {
+                continue;
+            }
+            // Un nom avec `/` sortirait du répertoire listé.
+            if entry.filename.is_empty() || entry.filename.contains(&b'/') {
+                return Some(Err(SshError::Protocol));
+            }
+            return Some(Ok(dir_entry(entry.filename, &entry.attrs)));
+        }
+    }
+}
+
+fn dir_entry<'a>(name: &'a [u8], attrs: &Attrs) -> DirEntry<'a> {
+    let kind = if attrs.is_dir() {
+        EntryKind::Directory
+    } else if attrs.is_symlink() {
+        EntryKind::Symlink
+    } else {
+        EntryKind::File
+    };
+    DirEntry {
+        name,
+        kind,
+        size: if kind == EntryKind::File {
+            attrs.size.unwrap_or(0)
+        } else {
+            0
+        },
+    }
+}
+
+pub struct SftpFs {
+    phase: Phase,
+    id: u32,
+    handle: [u8; HANDLE_MAX],
+    handle_len: usize,
+    offset: u64,
+}
+
+impl Default for SftpFs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SftpFs {
+    pub const fn new() -> Self {
+        Self {
+            phase: Phase::Init,
+            id: 0,
+            handle: [0; HANDLE_MAX],
+            handle_len: 0,
+            offset: 0,
+        }
+    }
+
+    /// Premier paquet, à envoyer une fois le sous-système accepté.
+    pub fn start(&mut self, out: &mut [u8]) -> Result<usize, SshError> {
+        self.phase = Phase::Init;
+        sftp::encode_init(out)
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.phase == Phase::Idle
+    }
+
+    /// Liste `path` (chemin d'une `Location::Sftp`).
+    pub fn list(&mut self, path: &[u8], out: &mut [u8]) -> Result<usize, SshError> {
+        self.begin(Phase::OpenDir, &Request::OpenDir { path }, out)
+    }
+
+    /// Lit `path` du début à la fin, par morceaux de [`READ_CHUNK`].
+    pub fn read(&mut self, path: &[u8], out: &mut [u8]) -> Result<usize, SshError> {
+        let req = Request::Open {
+            path,
+            flags: open_flags::READ,
+            attrs: Attrs::default(),
+        };
+        self.begin(Phase::Open, &req, out)
+    }
+
+    fn begin(
+        &mut self,
+        phase: Phase,
+        req: &Request<'_>,
+        out: &mut [u8],
+    ) -> Result<usize, SshError> {
+        if self.phase != Phase::Idle {
+            return Err(SshError::Protocol);
+        }
+        self.offset = 0;
+        let n = self.send(req, out)?;
+        self.phase = phase;
+        Ok(n)
+    }
+
+    fn send(&mut self, req: &Request<'_>, out: &mut [u8]) -> Result<usize, SshError> {
+        let id = self.id.wrapping_add(1);
+        let n = sftp::encode_request(id, req, out)?;
+        self.id = id;
+        Ok(n)
+    }
+
+    fn send_on_handle(&mut self, phase: Phase, out: &mut [u8]) -> Result<usize, SshError> {
+        let handle = &self.handle[..self.handle_len];
+        let req = match phase {
+            Phase::ReadDir => Request::ReadDir { handle },
+            Phase::Read => Request::Read {
+                handle,
+                offset: self.offset,
+                len: READ_CHUNK,
+            },
+            _ => Request::Close { handle },
+        };
+        let id = self.id.wrapping_add(1);
+        let n = sftp::encode_request(id, &req, out)?;
+        self.id = id;
+        self.phase = phase;
+        Ok(n)
+    }
+
+    /// Consomme une réponse au début de `buf`. `Ok(None)` : paquet
+    /// incomplet ; sinon `(octets consommés, événement, octets écrits
+    /// dans out)`.
+    pub fn feed<'a>(
+        &mut self,
+        buf: &'a [u8],
+        out: &mut [u8],
+    ) -> Result<Option<(usize, Event<'a>, usize)>, SshError> {
+        let Some((used, resp)) = sftp::parse_response(buf)? else {
+            return Ok(None);
+        };
+        let (event, sent) = self.on_response(resp, out)?;
+        Ok(Some((used, event, sent)))
+    }
+
+    fn on_response<'a>(
+        &mut self,
+        resp: Response<'a>,
+        out: &mut [u8],
+    ) -> Result<(Event<'a>, usize), SshError> {
+        if let Response::Version(version) = resp {
+            if self.phase != Phase::Init || version < sftp::VERSION {
+                return Err(SshError::Protocol);
+            }
+            self.phase = Phase::Idle;
+            return Ok((Event::Ready, 0));
+        }
+        if resp.id() != Some(self.id) {
+            return Err(SshError::Protocol);
+        }
+        match (self.phase, resp) {
+            (Phase::OpenDir | Phase::Open, Response::Handle { handle, .. }) => {
+                let dst = self
+                    .handle
+                    .get_mut(..handle.len())
+                    .ok_or(SshError::Protocol)?;
+                dst.copy_from_slice(handle);
+                self.handle_len = handle.len();
+                let next = if self.phase == Phase::OpenDir {
+                    Phase::ReadDir
+                } else {
+                    Phase::Read
+                };
+                Ok((Event::None, self.send_on_handle(next, out)?))
+            }
+            (Phase::OpenDir | Phase::Open, Response::Status { status, .. }) => {
+                self.phase = Phase::Idle;
+                Ok((Event::Failed(status), 0))
+            }
+            (Phase::ReadDir, Response::Name { entries, .. }) => {
+                let sent = self.send_on_handle(Phase::ReadDir, out)?;
+                Ok((Event::Entries(Entries { names: entries }), sent))
+            }
+            (Phase::Read, Response::Data { data, .. }) => {
+                let offset = self.offset;
+                self.offset += data.len() as u64;
+                let sent = self.send_on_handle(Phase::Read, out)?;
+                Ok((Event::Data { offset, data }, sent))
+            }
+            (Phase::ReadDir | Phase::Read, Response::Status { status, .. }) => {
+                let failed = (status != Status::Eof).then_some(status);
+                let sent = self.send_on_handle(Phase::Close(failed), out)?;
+                Ok((Event::None, sent))
+            }
+            (Phase::Close(failed), Response::Status { .. }) => {
+                // Un CLOSE refusé ne change rien pour l'appelant.
+                self.phase = Phase::Idle;
+                self.handle_len = 0;
+                Ok((failed.map_or(Event::Done, Event::Failed), 0))
+            }
+            _ => Err(SshError::Protocol),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sftp::packet;
+    use crate::wire::{Reader, Writer};
+
+    const S_IFDIR: u32 = 0o040000;
+    const S_IFLNK: u32 = 0o120000;
+
+    /// Requête envoyée : (type, id, reste).
+    fn request(out: &[u8]) -> (u8, u32, &[u8]) {
+        let mut r = Reader::new(out);
+        let len = r.u32().unwrap() as usize;
+        assert_eq!(len + 4, out.len());
+        (r.u8().unwrap(), r.u32().unwrap(), r.remaining())
+    }
+
+    fn reply(buf: &mut [u8], kind: u8, id: u32, body: impl FnOnce(&mut Writer<'_>)) -> usize {
+        let mut w = Writer::new(&mut buf[4..]);
+        w.u8(kind).unwrap().u32(id).unwrap();
+        body(&mut w);
+        let len = w.len();
+        buf[..4].copy_from_slice(&(len as u32).to_be_bytes());
+        len + 4
+    }
+
+    fn status(buf: &mut [u8], id: u32, code: u32) -> usize {
+        reply(buf, packet::STATUS, id, |w| {
+            w.u32(code)
+                .unwrap()
+                .string(b"")
+                .unwrap()
+                .string(b"")
+                .unwrap();
+        })
+    }
+
+    fn ready() -> SftpFs {
+        let mut fs = SftpFs::new();
+        let mut out = [0u8; 64];
+        fs.start(&mut out).unwrap();
+        let version = [0, 0, 0, 5, packet::VERSION, 0, 0, 0, 3];
+        let (used, event, sent) = fs.feed(&version, &mut out).unwrap().unwrap();
+        assert_eq!((used, sent), (version.len(), 0));
+        assert!(matches!(event, Event::Ready));
+        fs
+    }
+
+    #[test]
+    fn lists_a_directory_until_eof_then_closes() {
+        let mut fs = ready();
+        let mut out = [0u8; 512];
+        let mut buf = [0u8; 512];
+        let n = fs.list(b"/srv/www", &mut out).unwrap();
+        let (kind, id, _) = request(&out[..n]);
+        assert_eq!(kind, packet::OPENDIR);
+        assert_eq!(fs.list(b"/", &mut out), Err(SshError::Protocol));
+
+        let len = reply(&mut buf, packet::HANDLE, id, |w| {
+            w.string(b"h1").unwrap();
+        });
+        // Réponse coupée : rien n'est consommé.
+        assert!(fs.feed(&buf[..len - 1], &mut out).unwrap().is_none());
+        let (_, event, sent) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        assert!(matches!(event, Event::None));
+        let (kind, id, rest) = request(&out[..sent]);
+        assert_eq!(
+            (kind, rest),
+            (packet::READDIR, &[0, 0, 0, 2, b'h', b'1'][..])
+        );
+
+        let len = reply(&mut buf, packet::NAME, id, |w| {
+            w.u32(4).unwrap();
+            for (name, perms, size) in [
+                (&b"."[..], S_IFDIR | 0o755, 0),
+                (b"index.html", 0o100644, 512),
+                (b"assets", S_IFDIR | 0o755, 4096),
+                (b"current", S_IFLNK | 0o777, 7),
+            ] {
+                w.string(name).unwrap().string(b"").unwrap();
+                w.u32(0x05).unwrap().u64(size).unwrap().u32(perms).unwrap();
+            }
+        });
+        let (_, event, sent) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        let Event::Entries(entries) = event else {
+            panic!("expected entries");
+        };
+        let mut names = [DirEntry {
+            name: &[],
+            kind: EntryKind::File,
+            size: 0,
+        }; 3];
+        for (slot, entry) in names.iter_mut().zip(entries) {
+            *slot = entry.unwrap();
+        }
+        assert_eq!(
+            names,
+            [
+                DirEntry {
+                    name: b"index.html",
+                    kind: EntryKind::File,
+                    size: 512
+                },
+                DirEntry {
+                    name: b"assets",
+                    kind: EntryKind::Directory,
+                    size: 0
+                },
+                DirEntry {
+                    name: b"current",
+                    kind: EntryKind::Symlink,
+                    size: 0
+                },
+            ]
+        );
+        let (kind, id, _) = request(&out[..sent]);
+        assert_eq!(kind, packet::READDIR);
+
+        let len = status(&mut buf, id, 1);
+        let (_, event, sent) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        assert!(matches!(event, Event::None));
+        let (kind, id, _) = request(&out[..sent]);
+        assert_eq!(kind, packet::CLOSE);
+        let len = status(&mut buf, id, 0);
+        let (_, event, sent) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        assert!(matches!(event, Event::Done));
+        assert_eq!(sent, 0);
+        assert!(fs.is_ready());
+    }
+
+    #[test]
+    fn reads_a_file_in_chunks() {
+        let mut fs = ready();
+        let mut out = [0u8; 512];
+        let mut buf = [0u8; 512];
+        let n = fs.read(b"/etc/motd", &mut out).unwrap();
+        let (kind, id, _) = request(&out[..n]);
+        assert_eq!(kind, packet::OPEN);
+        let len = reply(&mut buf, packet::HANDLE, id, |w| {
+            w.string(b"f").unwrap();
+        });
+        let (_, _, sent) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        let (kind, mut id, _) = request(&out[..sent]);
+        assert_eq!(kind, packet::READ);
+
+        for (chunk, at) in [(&b"hello "[..], 0), (b"world", 6)] {
+            let len = reply(&mut buf, packet::DATA, id, |w| {
+                w.string(chunk).unwrap();
+            });
+            let (_, event, sent) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+            assert!(matches!(event, Event::Data { offset, data } if offset == at && data == chunk));
+            let (kind, next, rest) = request(&out[..sent]);
+            assert_eq!(kind, packet::READ);
+            // handle "f", puis la position suivante.
+            assert_eq!(&rest[5..13], &(at + chunk.len() as u64).to_be_bytes());
+            id = next;
+        }
+        let len = status(&mut buf, id, 1);
+        let (_, _, sent) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        let (kind, id, _) = request(&out[..sent]);
+        assert_eq!(kind, packet::CLOSE);
+        let len = status(&mut buf, id, 0);
+        let (_, event, _) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        assert!(matches!(event, Event::Done));
+    }
+
+    #[test]
+    fn errors_are_reported_after_closing_the_handle() {
+        let mut fs = ready();
+        let mut out = [0u8; 512];
+        let mut buf = [0u8; 512];
+        // Refus à l'ouverture : pas de handle à fermer.
+        let n = fs.list(b"/root", &mut out).unwrap();
+        let (_, id, _) = request(&out[..n]);
+        let len = status(&mut buf, id, 3);
+        let (_, event, sent) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        assert!(matches!(event, Event::Failed(Status::PermissionDenied)));
+        assert_eq!(sent, 0);
+
+        // Échec en cours de lecture : CLOSE d'abord, erreur ensuite.
+        let n = fs.read(b"/var/log/big", &mut out).unwrap();
+        let (_, id, _) = request(&out[..n]);
+        let len = reply(&mut buf, packet::HANDLE, id, |w| {
+            w.string(b"f").unwrap();
+        });
+        let (_, _, sent) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        let (_, id, _) = request(&out[..sent]);
+        let len = status(&mut buf, id, 4);
+        let (_, event, sent) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        assert!(matches!(event, Event::None));
+        let (kind, id, _) = request(&out[..sent]);
+        assert_eq!(kind, packet::CLOSE);
+        let len = status(&mut buf, id, 0);
+        let (_, event, _) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        assert!(matches!(event, Event::Failed(Status::Failure)));
+        assert!(fs.is_ready());
+    }
+
+    #[test]
+    fn rejects_unexpected_replies() {
+        let mut out = [0u8; 512];
+        let mut buf = [0u8; 512];
+        let mut fs = SftpFs::new();
+        fs.start(&mut out).unwrap();
+        assert_eq!(fs.list(b"/", &mut out), Err(SshError::Protocol));
+        let old = [0, 0, 0, 5, packet::VERSION, 0, 0, 0, 2];
+        assert!(fs.feed(&old, &mut out).is_err());
+
+        let mut fs = ready();
+        let n = fs.list(b"/", &mut out).unwrap();
+        let (_, id, _) = request(&out[..n]);
+        // Mauvais identifiant, puis mauvais type de réponse.
+        let len = reply(&mut buf, packet::HANDLE, id + 1, |w| {
+            w.string(b"h").unwrap();
+        });
+        assert_eq!(
+            fs.feed(&buf[..len], &mut out).err(),
+            Some(SshError::Protocol)
+        );
+        let len = reply(&mut buf, packet::DATA, id, |w| {
+            w.string(b"x").unwrap();
+        });
+        assert_eq!(
+            fs.feed(&buf[..len], &mut out).err(),
+            Some(SshError::Protocol)
+        );
+        let handle = [b'h'; HANDLE_MAX + 1];
+        let len = reply(&mut buf, packet::HANDLE, id, |w| {
+            w.string(&handle).unwrap();
+        });
+        assert_eq!(
+            fs.feed(&buf[..len], &mut out).err(),
+            Some(SshError::Protocol)
+        );
+
+        // Nom d'entrée qui sortirait du répertoire.
+        let len = reply(&mut buf, packet::HANDLE, id, |w| {
+            w.string(b"h").unwrap();
+        });
+        let (_, _, sent) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        let (_, id, _) = request(&out[..sent]);
+        let len = reply(&mut buf, packet::NAME, id, |w| {
+            w.u32(1).unwrap();
+            w.string(b"../etc")
+                .unwrap()
+                .string(b"")
+                .unwrap()
+                .u32(0)
+                .unwrap();
+        });
+        let (_, event, _) = fs.feed(&buf[..len], &mut out).unwrap().unwrap();
+        let Event::Entries(mut entries) = event else {
+            panic!("expected entries");
+        };
+        assert_eq!(entries.next(), Some(Err(SshError::Protocol)));
+    }
+}
@@ -0,0 +1,286 @@
+//! Protocole de paquets binaires (RFC 4253 §6) : en clair jusqu'à NEWKEYS,
+//! puis aes256-gcm@openssh.com (RFC 5647) — la longueur reste en clair et
+//! sert de données associées, le compteur du nonce avance à chaque paquet.
+
+use aes_gcm::aead::{AeadInPlace, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce, Tag};
+
+use crate::{msg, SshError};
+
+/// Plus grand `packet_length` accepté (RFC 4253 impose au moins 35000 ;
+/// nos canaux annoncent des paquets de 4 Kio, KEXINIT tient largement).
+pub const MAX_PACKET_LEN: usize = 16 * 1024;
+pub const GCM_TAG_LEN: usize = 16;
+pub const CIPHER_NAME: &[u8] = b"aes256-gcm@openssh.com";
+/// Volume après lequel un re-échange de clés est lancé (RFC 4253 §9 :
+/// « after each gigabyte of transmitted data »).
+pub const REKEY_BYTES: u64 = 1 << 30;
+/// Paquets retenus pendant un échange de clés : réponses de canal et
+/// d'authentification, jamais de données (`send_data` attend).
+const DEFERRED_BUF: usize = 4096;
+
+struct Cipher {
+    aead: Aes256Gcm,
+    fixed: [u8; 4],
+    counter: u64,
+}
+
+impl Cipher {
+    fn new(key: &[u8; 32], iv: &[u8; 12]) -> Self {
+        Self {
+            aead: Aes256Gcm::new(key.into()),
+            fixed: [iv[0], iv[1], iv[2], iv[3]],
+            counter: u64::from_be_bytes([iv[4], iv[5], iv[6], iv[7], iv[8], iv[9], iv[10], iv[11]]),
+        }
+    }
+
+    fn next_nonce(&mut self) -> [u8; 12] {
+        let mut n = [0u8; 12];
+        n[..4].copy_from_slice(&self.fixed);
+        n[4..].copy_from_slice(&self.counter.to_be_bytes());
+        self.counter = self.counter.wrapping_add(1);
+        n
+    }
+}
+
+/// Chiffrement d'un sens de la connexion, issu de la dérivation de clés.
+#[derive(Clone, Copy)]
+pub struct DirectionKeys {
+    pub key: [u8; 32],
+    pub iv: [u8; 12],
+}
+
+#[derive(Default)]
+pub struct PacketIo {
+    seq_in: u32,
+    seq_out: u32,
+    enc: Option<Cipher>,
+    dec: Option<Cipher>,
+    bytes_since_keys: u64,
+}
+
+impl PacketIo {
+    pub const fn new() -> Self {
+        Self {
+            seq_in: 0,
+            seq_out: 0,
+            enc: None,
+            dec: None,
+            bytes_since_keys: 0,
+        }
+    }
+
+    /// Après l'envoi de NEWKEYS ; remet à zéro le compteur de re-keying.
+    pub fn set_outgoing(&mut self, keys: &DirectionKeys) {
+        self.enc = Some(Cipher::new(&keys.key, &keys.iv));
+        self.bytes_since_keys = 0;
+    }
+
+    /// Après la réception de NEWKEYS.
+    pub fn set_incoming(&mut self, keys: &DirectionKeys) {
+        self.dec = Some(Cipher::new(&keys.key, &keys.iv));
+    }
+
+    /// Octets émis et reçus depuis les dernières clés sortantes.
+    pub fn bytes_since_keys(&self) -> u64 {
+        self.bytes_since_keys
+    }
+
+    /// Numéro de séquence du dernier paquet reçu (pour UNIMPLEMENTED).
+    pub fn last_seq_in(&self) -> u32 {
+        self.seq_in.wrapping_sub(1)
+    }
+
+    /// Encapsule `payload` dans `out`. `padding` fournit les octets
+    /// aléatoires de bourrage.
+    pub fn seal(
+        &mut self,
+        payload: &[u8],
+        padding: &mut dyn FnMut(&mut [u8]),
+        out: &mut [u8],
+    ) -> Result<usize, SshError> {
+        let (block, counted) = if self.enc.is_some() { (16, 1) } else { (8, 5) };
+        let mut pad = block - (counted + payload.len()) % block;
+        if pad < 4 {
+            pad += block;
+        }
+        let packet_len = 1 + payload.len() + pad;
+        let tag_len = if self.enc.is_some() { GCM_TAG_LEN } else { 0 };
+        let total = 4 + packet_len + tag_len;
+        if packet_len > MAX_PACKET_LEN || out.len() < total {
+            return Err(SshError::BufferFull);
+        }
+        out[..4].copy_from_slice(&(packet_len as u32).to_be_bytes());
+        out[4] = pad as u8;
+        out[5..5 + payload.len()].copy_from_slice(payload);
+        padding(&mut out[5 + payload.len()..4 + packet_len]);
+        if let Some(c) = self.enc.as_mut() {
+            let nonce = c.next_nonce();
+            let (head, body) = out.split_at_mut(4);
+            let tag = c
+                .aead
+                .encrypt_in_place_detached(Nonce::from_slice(&nonce), head, &mut body[..packet_len])
+                .map_err(|_| SshError::Crypto)?;
+            body[packet_len..packet_len + GCM_TAG_LEN].copy_from_slice(&tag);
+        }
+        self.seq_out = self.seq_out.wrapping_add(1);
+        self.bytes_since_keys += total as u64;
+        Ok(total)
+    }
+
+    /// Tente d'extraire un paquet complet du début de `input`, déchiffré
+    /// sur place. Retourne `(octets consommés, début du payload, longueur)`.
+    pub fn open(&mut self, input: &mut [u8]) -> Result<Option<(usize, usize, usize)>, SshError> {
+        if input.len() < 4 {
+            return Ok(None);
+        }
+        let packet_len = u32::from_be_bytes([input[0], input[1], input[2], input[3]]) as usize;
+        let block = if self.dec.is_some() { 16 } else { 8 };
+        let counted = if self.dec.is_some() {
+            packet_len
+        } else {
+            packet_len + 4
+        };
+        if !(5..=MAX_PACKET_LEN).contains(&packet_len) || counted % block != 0 {
+            return Err(SshError::Protocol);
+        }
+        let tag_len = if self.dec.is_some() { GCM_TAG_LEN } else { 0 };
+        let total = 4 + packet_len + tag_len;
+        if input.len() < total {
+            return Ok(None);
+        }
+        if let Some(c) = self.dec.as_mut() {
+            let nonce = c.next_nonce();
+            let (head, body) = input.split_at_mut(4);
+            let (data, rest) = body.split_at_mut(packet_len);
+            c.aead
+                .decrypt_in_place_detached(
+                    Nonce::from_slice(&nonce),
+                    head,
+                    data,
+                    Tag::from_slice(&rest[..GCM_TAG_LEN]),
+                )
+                .map_err(|_| SshError::Crypto)?;
+        }
+        let pad = input[4] as usize;
+        if pad < 4 || pad + 1 > packet_len {
+            return Err(SshError::Protocol);
+        }
+        self.seq_in = self.seq_in.wrapping_add(1);
+        self.bytes_since_keys += total as u64;
+        Ok(Some((total, 5, packet_len - 1 - pad)))
+    }
+}
+
+/// Entre l'envoi de KEXINIT et celui de NEWKEYS, seuls les messages de
+/// transport (hors SERVICE_*) et de kex peuvent partir (RFC 4253 §7.1).
+pub fn held_during_kex(id: u8) -> bool {
+    matches!(id, msg::SERVICE_REQUEST | msg::SERVICE_ACCEPT) || id >= msg::USERAUTH_REQUEST
+}
+
+/// File des payloads retenus pendant un échange de clés, réémis dans
+/// l'ordre une fois NEWKEYS envoyé. Chaque entrée : longueur u16 + payload.
+pub struct Deferred {
+    buf: [u8; DEFERRED_BUF],
+    len: usize,
+}
+
+impl Deferred {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; DEFERRED_BUF],
+            len: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, payload: &[u8]) -> Result<(), SshError> {
+        if DEFERRED_BUF - self.len < 2 + payload.len() {
+            return Err(SshError::BufferFull);
+        }
+        self.buf[self.len..self.len + 2].copy_from_slice(&(payload.len() as u16).to_be_bytes());
+        self.buf[self.len + 2..self.len + 2 + payload.len()].copy_from_slice(payload);
+        self.len += 2 + payload.len();
+        Ok(())
+    }
+
+    /// Plus ancien payload retenu.
+    pub fn front(&self) -> Option<&[u8]> {
+        if self.len == 0 {
+            return None;
+        }
+        let n = u16::from_be_bytes([self.buf[0], self.buf[1]]) as usize;
+        Some(&self.buf[2..2 + n])
+    }
+
+    pub fn pop(&mut self) {
+        if let Some(n) = self.front().map(|p| 2 + p.len()) {
+            self.buf.copy_within(n..self.len, 0);
+            self.len -= n;
+        }
+    }
+}
+
+impl Default for Deferred {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packets_roundtrip_in_clear_and_encrypted() {
+        let mut tx = PacketIo::new();
+        let mut rx = PacketIo::new();
+        let mut wire = [0u8; 128];
+        let mut zero_pad = |b: &mut [u8]| b.fill(0);
+
+        let n = tx.seal(b"hello", &mut zero_pad, &mut wire).unwrap();
+        assert_eq!(n % 8, 0);
+        assert_eq!(rx.open(&mut wire[..n - 1]).unwrap(), None);
+        let (used, at, len) = rx.open(&mut wire[..n]).unwrap().unwrap();
+        assert_eq!((used, &wire[at..at + len]), (n, &b"hello"[..]));
+
+        let keys = DirectionKeys {
+            key: [7; 32],
+            iv: [1; 12],
+        };
+        tx.set_outgoing(&keys);
+        rx.set_incoming(&keys);
+        for _ in 0..3 {
+            let n = tx
+                .seal(b"secret payload", &mut zero_pad, &mut wire)
+                .unwrap();
+            assert_eq!((n - 4 - GCM_TAG_LEN) % 16, 0);
+            let (_, at, len) = rx.open(&mut wire[..n]).unwrap().unwrap();
+            assert_eq!(&wire[at..at + len], b"secret payload");
+        }
+
+        let n = tx.seal(b"tampered", &mut zero_pad, &mut wire).unwrap();
+        wire[7] ^= 1;
+        assert_eq!(rx.open(&mut wire[..n]), Err(SshError::Crypto));
+    }
+
+    #[test]
+    fn deferred_payloads_keep_their_order() {
+        let mut q = Deferred::new();
+        assert_eq!(q.front(), None);
+        q.push(&[msg::CHANNEL_SUCCESS, 0, 0, 0, 1]).unwrap();
+        q.push(&[msg::CHANNEL_CLOSE, 0, 0, 0, 1]).unwrap();
+        assert_eq!(q.front(), Some(&[msg::CHANNEL_SUCCESS, 0, 0, 0, 1][..]));
+        q.pop();
+        assert_eq!(q.front().map(|p| p[0]), Some(msg::CHANNEL_CLOSE));
+        q.pop();
+        assert!(q.is_empty());
+        assert_eq!(q.push(&[0; DEFERRED_BUF]), Err(SshError::BufferFull));
+
+        assert!(held_during_kex(msg::CHANNEL_DATA) && held_during_kex(msg::SERVICE_ACCEPT));
+        assert!(!held_during_kex(msg::KEX_ECDH_REPLY) && !held_during_kex(msg::DISCONNECT));
+    }
+}
@@ -0,0 +1,152 @@
+//! Encodage des types SSH (RFC 4251 §5).
+
+use crate::SshError;
+
+#[derive(Clone, Copy)]
+pub struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    pub fn u8(&mut self) -> Result<u8, SshError> {
+        let b = *self.buf.get(self.pos).ok_or(SshError::Protocol)?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    pub fn bool(&mut self) -> Result<bool, SshError> {
+        Ok(self.u8()? != 0)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, SshError> {
+        let b = self.raw(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, SshError> {
+        let b = self.raw(8)?;
+        let mut v = [0u8; 8];
+        v.copy_from_slice(b);
+        Ok(u64::from_be_bytes(v))
+    }
+
+    pub fn raw(&mut self, len: usize) -> Result<&'a [u8], SshError> {
+        let b = self
+            .buf
+            .get(self.pos..self.pos + len)
+            .ok_or(SshError::Protocol)?;
+        self.pos += len;
+        Ok(b)
+    }
+
+    pub fn string(&mut self) -> Result<&'a [u8], SshError> {
+        let len = self.u32()? as usize;
+        self.raw(len)
+    }
+
+    pub fn utf8(&mut self) -> Result<&'a str, SshError> {
+        core::str::from_utf8(self.string()?).map_err(|_| SshError::Protocol)
+    }
+
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.buf[self.pos..]
+    }
+}
+
+pub struct Writer<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn raw(&mut self, bytes: &[u8]) -> Result<&mut Self, SshError> {
+        let dst = self
+            .buf
+            .get_mut(self.len..self.len + bytes.len())
+            .ok_or(SshError::BufferFull)?;
+        dst.copy_from_slice(bytes);
+        self.len += bytes.len();
+        Ok(self)
+    }
+
+    pub fn u8(&mut self, v: u8) -> Result<&mut Self, SshError> {
+        self.raw(&[v])
+    }
+
+    pub fn bool(&mut self, v: bool) -> Result<&mut Self, SshError> {
+        self.raw(&[v as u8])
+    }
+
+    pub fn u32(&mut self, v: u32) -> Result<&mut Self, SshError> {
+        self.raw(&v.to_be_bytes())
+    }
+
+    pub fn u64(&mut self, v: u64) -> Result<&mut Self, SshError> {
+        self.raw(&v.to_be_bytes())
+    }
+
+    pub fn string(&mut self, s: &[u8]) -> Result<&mut Self, SshError> {
+        self.u32(s.len() as u32)?.raw(s)
+    }
+
+    /// Entier positif en big-endian, encodé en mpint (zéros de tête
+    /// retirés, 0x00 ajouté si le bit de poids fort est levé).
+    pub fn mpint(&mut self, be: &[u8]) -> Result<&mut Self, SshError> {
+        let start = be.iter().position(|&b| b != 0).unwrap_or(be.len());
+        let digits = &be[start..];
+        let pad = digits.first().is_some_and(|&b| b & 0x80 != 0);
+        self.u32((digits.len() + pad as usize) as u32)?;
+        if pad {
+            self.u8(0)?;
+        }
+        self.raw(digits)
+    }
+}
+
+/// `true` si `name` figure dans la name-list `list`.
+pub fn name_list_contains(list: &[u8], name: &[u8]) -> bool {
+    list.split(|&b| b == b',').any(|n| n == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_mpint_per_rfc4251() {
+        let mut buf = [0u8; 32];
+        let mut w = Writer::new(&mut buf);
+        w.mpint(&[0, 0, 0x80]).unwrap().mpint(&[0, 0]).unwrap();
+        let n = w.len();
+        assert_eq!(&buf[..n], &[0, 0, 0, 2, 0, 0x80, 0, 0, 0, 0]);
+        let mut r = Reader::new(&buf[..n]);
+        assert_eq!(r.string().unwrap(), &[0, 0x80]);
+        assert_eq!(r.string().unwrap(), &[] as &[u8]);
+        assert!(r.u8().is_err());
+        assert!(name_list_contains(
+            b"a,curve25519-sha256,b",
+            b"curve25519-sha256"
+        ));
+        assert!(!name_list_contains(
+            b"curve25519-sha256@libssh.org",
+            b"curve25519-sha256"
+        ));
+    }
+}
@@ -238,6 +238,7 @@ pub const SYS_DELETE_MODULE: u64 = 176;
 pub const SYS_QUERY_MODULE: u64 = 177;
 pub const SYS_QUOTACTL: u64 = 179;
 pub const SYS_GETTID: u64 = 186;
+pub const SYS_READAHEAD: u64 = 187;
 pub const SYS_TKILL: u64 = 200;
 pub const SYS_TIME: u64 = 201;
 pub const SYS_FUTEX: u64 = 202;
@@ -263,6 +264,14 @@ pub const SYS_EPOLL_CTL: u64 = 233;
 pub const SYS_TGKILL: u64 = 234;
 pub const SYS_UTIMES: u64 = 235;
 pub const SYS_WAITID: u64 = 247;
+pub const SYS_IOPRIO_SET: u64 = 251;
+pub const SYS_IOPRIO_GET: u64 = 252;
+pub const IOPRIO_WHO_PROCESS: u64 = 1;
+pub const IOPRIO_CLASS_SHIFT: u64 = 13;
+pub const IOPRIO_CLASS_NONE: u64 = 0;
+pub const IOPRIO_CLASS_RT: u64 = 1;
+pub const IOPRIO_CLASS_BE: u64 = 2;
+pub const IOPRIO_CLASS_IDLE: u64 = 3;
 pub const SYS_OPENAT: u64 = 257;
 pub const SYS_MKDIRAT: u64 = 258;
 pub const SYS_MKNODAT: u64 = 259;
@@ -347,7 +356,71 @@ pub const SYS_EXO_LOG: u64 = 350;
 pub const SYS_EXO_PROCESS_LIST: u64 = 351;
 pub const SYS_EXO_PHOENIX_STATE_SET: u64 = 352;
 pub const SYS_EXO_PHOENIX_STATE_GET: u64 = 353;
+pub const SYS_EXO_SYSCTL: u64 = 354;
+pub const EXO_SYSCTL_READ: u64 = 0;
+pub const EXO_SYSCTL_WRITE: u64 = 1;
+pub const EXO_SYSCTL_LIST: u64 = 2;
+pub const SYS_EXO_PRELOAD: u64 = 355;
+pub const EXO_PRELOAD_START: u64 = 0;
+pub const EXO_PRELOAD_STOP: u64 = 1;
+pub const EXO_PRELOAD_TRACE: u64 = 2;
+pub const EXO_PRELOAD_FETCH: u64 = 3;
+pub const EXO_PRELOAD_STATS: u64 = 4;
+pub const SYS_EXO_SHM: u64 = 356;
+pub const EXO_SHM_CREATE: u64 = 0;
+pub const EXO_SHM_MAP: u64 = 1;
+pub const EXO_SHM_UNMAP: u64 = 2;
+pub const EXO_SHM_DESTROY: u64 = 3;
+pub const EXO_SHM_PUBLIC_READ: u64 = 1;
+pub const SYS_EXO_PSI: u64 = 357;
+pub const EXO_PSI_OPEN: u64 = 0;
+pub const EXO_PSI_READ: u64 = 1;
+pub const EXO_PSI_OOM_KILL: u64 = 2;
+pub const SYS_EXO_FREEZE: u64 = 358;
+pub const EXO_FREEZE_FREEZE: u64 = 0;
+pub const EXO_FREEZE_THAW: u64 = 1;
+pub const EXO_FREEZE_STATE: u64 = 2;
+pub const SYS_EXO_NET_USAGE: u64 = 359;
+/// `exo_bpf(op, ...)`: verified bpf programs and maps shared with userspace.
+/// Root only. Attach points are trace kinds 1..=6 and `EXO_BPF_NET_*`.
 pub const SYS_EXO_BPF: u64 = 360;
+pub const EXO_BPF_MAP_CREATE: u64 = 0;
+pub const EXO_BPF_MAP_LOOKUP: u64 = 1;
+pub const EXO_BPF_MAP_UPDATE: u64 = 2;
+pub const EXO_BPF_MAP_DELETE: u64 = 3;
+pub const EXO_BPF_MAP_NEXT_KEY: u64 = 4;
+pub const EXO_BPF_MAP_FREE: u64 = 5;
+pub const EXO_BPF_PROG_LOAD: u64 = 6;
+pub const EXO_BPF_PROG_UNLOAD: u64 = 7;
+pub const EXO_BPF_PROG_ATTACH: u64 = 8;
+pub const EXO_BPF_PROG_DETACH: u64 = 9;
+pub const EXO_BPF_MAP_ARRAY: u64 = 0;
+pub const EXO_BPF_MAP_HASH: u64 = 1;
+pub const EXO_BPF_PROG_TRACE: u64 = 0;
+pub const EXO_BPF_PROG_NET_FILTER: u64 = 1;
+pub const EXO_BPF_NET_INGRESS: u64 = 0x100;
+pub const EXO_BPF_NET_EGRESS: u64 = 0x101;
+pub const SYS_EXO_IPC_STAT: u64 = 361;
+pub const SYS_EXO_TRACE: u64 = 362;
+pub const EXO_TRACE_MARK: u64 = 0;
+pub const EXO_TRACE_EXPORT: u64 = 1;
+pub const EXO_TRACE_INSTANT: u64 = 0;
+pub const EXO_TRACE_BEGIN: u64 = 1;
+pub const EXO_TRACE_END: u64 = 2;
+pub const SYS_EXO_SLEEP_STATE: u64 = 363;
+pub const SYS_PCI_CLAIM_DEVICE: u64 = 364;
+pub const SYS_PCI_MAP_BAR: u64 = 365;
+pub const SYS_IRQ_EVENTFD: u64 = 366;
+pub const SYS_DMA_BUFFER: u64 = 367;
+/// `exo_livepatch(APPLY, module, len)` → patched site index, or
+/// `exo_livepatch(REVERT, name, len)` → 0. Root only; the module is a signed
+/// EXOMOD image, `EBUSY` while a thread sleeps in the target function.
+pub const SYS_EXO_LIVEPATCH: u64 = 368;
+pub const EXO_LIVEPATCH_APPLY: u64 = 0;
+pub const EXO_LIVEPATCH_REVERT: u64 = 1;
+/// `exo_cap_list(pid, index, buf, len)` → next slot, `ENOENT` past the last
+/// capability. `pid = 0` is the caller; another process requires root.
+pub const SYS_EXO_CAP_LIST: u64 = 369;
 
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -392,6 +465,12 @@ pub const SYS_EXOFS_EPOCH_COMMIT: u64 = 518;
 pub const SYS_EXOFS_OPEN_BY_PATH: u64 = 519;
 pub const SYS_EXOFS_READDIR: u64 = 520;
 pub const SYS_FRAMEBUFFER_INFO: u64 = 521;
+/// `display_edid(buf, buf_len)` → size of the monitor's EDID (base block and
+/// extensions, see `exo_services::edid`), `ENOENT` without one.
+pub const SYS_DISPLAY_EDID: u64 = 522;
+/// `netboot_info()` → boot server IPv4 (big-endian `u32`, 0 when unknown) if
+/// exo-boot was loaded over PXE or UEFI HTTP boot, `ENOENT` on a disk boot.
+pub const SYS_NETBOOT_INFO: u64 = 523;
 pub const SYS_EXOFS_FIRST: u64 = SYS_EXOFS_PATH_RESOLVE;
 pub const SYS_EXOFS_LAST: u64 = SYS_EXOFS_READDIR;
 pub const SYS_EXOFS_COUNT: u64 = SYS_EXOFS_LAST - SYS_EXOFS_FIRST + 1;
@@ -471,6 +550,72 @@ impl Default for ExoProcessInfo {
     }
 }
 
+/// Consommation réseau cumulée d'une application (`exo_net_usage`).
+///
+/// `name` est vide pour le groupe des sockets sans propriétaire nommé.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct ExoNetUsage {
+    pub name: [u8; EXO_PROCESS_NAME_LEN],
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub sockets: u32,
+    pub _pad: u32,
+}
+
+pub const EXO_IPC_LABEL_LEN: usize = 32;
+
+/// Statistiques d'une mailbox IPC raw (`exo_ipc_stat`).
+///
+/// Latence : séjour dans l'anneau des messages livrés, moyenne =
+/// `total_latency_ns / received`.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct ExoIpcStat {
+    pub endpoint: u64,
+    /// 0 si le propriétaire n'est pas connu (mailbox interne au noyau).
+    pub owner_pid: u32,
+    pub depth: u32,
+    pub capacity: u32,
+    pub peak_depth: u32,
+    pub sent: u64,
+    pub received: u64,
+    pub dropped: u64,
+    pub expired: u64,
+    pub peak_latency_ns: u64,
+    pub total_latency_ns: u64,
+    /// Nom passé à `exo_ipc_create`, complété de NUL.
+    pub name: [u8; EXO_IPC_LABEL_LEN],
+}
+
+/// Une capability de la table d'un processus (`exo_cap_list`).
+///
+/// `type_tag` suit `CapObjectType` du noyau ; `rights` est le masque brut,
+/// bits `EXOFS_RIGHT_*` pour un inode ExoFS (type 3).
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct ExoCapEntry {
+    pub object_id: u64,
+    pub rights: u32,
+    pub generation: u32,
+    pub type_tag: u32,
+    pub _pad: u32,
+}
+
+/// Bilan de la fenêtre de préchargement (`exo_preload(STATS)`).
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct ExoPreloadStats {
+    pub recording: u64,
+    pub traced: u64,
+    pub dropped: u64,
+    pub prefetched: u64,
+    pub already_cached: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub unused: u64,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub struct IpcMessage {
@@ -606,6 +751,26 @@ pub const EXOFS_RIGHT_READ_WRITE: u32 = EXOFS_RIGHT_READ
     | EXOFS_RIGHT_SETMETA
     | EXOFS_RIGHT_LIST;
 
+pub const EXOFS_QUOTA_GET: u32 = 0x0001;
+pub const EXOFS_QUOTA_SET: u32 = 0x0002;
+pub const EXOFS_QUOTA_SOFT_LIMIT: u32 = 0x0008;
+pub const EXOFS_QUOTA_HARD_LIMIT: u32 = 0x0010;
+pub const EXOFS_QUOTA_BY_PROJECT: u32 = 0x0020;
+pub const EXOFS_QUOTA_ASSIGN_PROJECT: u32 = 0x0040;
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct ExofsQuotaInfo {
+    pub used_bytes: u64,
+    pub soft_bytes: u64,
+    pub hard_bytes: u64,
+    pub used_objects: u64,
+    pub soft_objects: u64,
+    pub hard_objects: u64,
+    pub flags: u32,
+    pub _pad: u32,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
 pub struct ExofsPathResolveResult {
@@ -745,6 +910,88 @@ pub const INPUT_MSG_POLL: u32 = 0x121;
 pub const INPUT_MSG_HEARTBEAT: u32 = 0x122;
 pub const INPUT_MSG_ATTACH: u32 = 0x123;
 pub const INPUT_MSG_DETACH: u32 = 0x124;
+/// Global shortcut: `event.code`/`event.modifiers` is the combination,
+/// `event.value` the owner's action id, `reply_endpoint` the owner. The
+/// owner endpoint must carry the sender's PID in its high 32 bits (`EPERM`
+/// otherwise), for binding and unbinding alike.
+pub const INPUT_MSG_BIND_SHORTCUT: u32 = 0x125;
+pub const INPUT_MSG_UNBIND_SHORTCUT: u32 = 0x126;
+/// `InputReply::status` of a grabbed key press sent to the shortcut owner;
+/// `event.value` carries the action id.
+pub const INPUT_STATUS_SHORTCUT: i64 = 1;
+/// To the input method; `event.state` = `INPUT_IM_ACTIVATE` (`event.code` =
+/// content purpose), `INPUT_IM_DEACTIVATE` or `INPUT_IM_HIDE_PANEL` (a
+/// hardware key was pressed while the panel is shown).
+pub const INPUT_STATUS_IM: i64 = 2;
+/// Keyboard event grabbed by the input method (`INPUT_IM_GRAB`).
+pub const INPUT_STATUS_IM_KEY: i64 = 3;
+/// To the text input; `event.state` = `INPUT_IM_COMMIT`,
+/// `INPUT_IM_PREEDIT` or `INPUT_IM_DELETE_SURROUNDING`, as sent by the
+/// input method.
+pub const INPUT_STATUS_TEXT: i64 = 4;
+/// To every shortcut owner when the on-screen keyboard panel is shown
+/// (`event.value` 1) or hidden (0).
+pub const INPUT_STATUS_OSK: i64 = 5;
+
+/// Input method ops. Text ops carry one character as
+/// `event.code | event.ascii << 16` (a PREEDIT of 0 clears the preedit);
+/// `INPUT_IM_DELETE_SURROUNDING` deletes `event.code` characters before the
+/// cursor and `event.value` after it. `INPUT_IM_PANEL`: `event.value` 1
+/// shown, 0 hidden.
+pub const INPUT_IM_COMMIT: u8 = 1;
+pub const INPUT_IM_PREEDIT: u8 = 2;
+pub const INPUT_IM_DELETE_SURROUNDING: u8 = 3;
+pub const INPUT_IM_GRAB: u8 = 4;
+pub const INPUT_IM_UNGRAB: u8 = 5;
+pub const INPUT_IM_PANEL: u8 = 6;
+/// Broker to input method only.
+pub const INPUT_IM_ACTIVATE: u8 = 7;
+pub const INPUT_IM_DEACTIVATE: u8 = 8;
+pub const INPUT_IM_HIDE_PANEL: u8 = 9;
+
+/// Content purposes, numbered as in zwp_text_input_v3.
+pub const INPUT_PURPOSE_NORMAL: u16 = 0;
+pub const INPUT_PURPOSE_DIGITS: u16 = 2;
+pub const INPUT_PURPOSE_NUMBER: u16 = 3;
+pub const INPUT_PURPOSE_PHONE: u16 = 4;
+pub const INPUT_PURPOSE_URL: u16 = 5;
+pub const INPUT_PURPOSE_EMAIL: u16 = 6;
+pub const INPUT_PURPOSE_PASSWORD: u16 = 8;
+pub const INPUT_PURPOSE_PIN: u16 = 9;
+pub const INPUT_PURPOSE_TERMINAL: u16 = 13;
+/// Gamepad plugged in, from its driver: `event.code` = USB vendor,
+/// `event.value` = product, `reply_endpoint` = driver endpoint receiving the
+/// rumble requests, which must carry the sender's PID in its high 32 bits
+/// (`EPERM` otherwise). Reply `status` = slot; subscribers get an
+/// `INPUT_GAMEPAD_CONNECTED` event.
+pub const INPUT_MSG_GAMEPAD_CONNECT: u32 = 0x127;
+/// `event.ascii` = slot; only from the process that connected it (`ENOENT`
+/// otherwise).
+pub const INPUT_MSG_GAMEPAD_DISCONNECT: u32 = 0x128;
+/// `event.ascii` = slot, `event.code` = `strong << 8 | weak` motor levels,
+/// `event.value` = duration in ms (0 stops). Forwarded as is to the driver.
+pub const INPUT_MSG_GAMEPAD_RUMBLE: u32 = 0x129;
+/// `event.ascii` = slot. Reply `event.code` = vendor, `event.value` =
+/// product, `ENODEV` for an empty slot.
+pub const INPUT_MSG_GAMEPAD_INFO: u32 = 0x12a;
+pub const INPUT_GAMEPAD_MAX: usize = 4;
+/// Input method (zwp_input_method_v2): `event.value` 1 registers
+/// `reply_endpoint` as the input method, 0 unregisters it. `EBUSY` while
+/// another one is registered. The input method then receives
+/// `INPUT_STATUS_IM` and `INPUT_STATUS_IM_KEY` messages.
+pub const INPUT_MSG_IM_REGISTER: u32 = 0x12b;
+/// Text input (zwp_text_input_v3) of the focused client: `event.value` 1
+/// enables it with `event.code` = content purpose (`INPUT_PURPOSE_*`), 0
+/// disables it. `reply_endpoint` receives the `INPUT_STATUS_TEXT` messages.
+pub const INPUT_MSG_TEXT_INPUT: u32 = 0x12c;
+/// From the registered input method only; `event.state` = `INPUT_IM_*` op.
+/// `ENOENT` for a text op without an enabled text input.
+pub const INPUT_MSG_IM_SEND: u32 = 0x12d;
+/// Virtual keyboard (zwp_virtual_keyboard_v1), from the registered input
+/// method only: `event` is published as a keyboard event. While the panel
+/// is shown, only media keys are grabbed by shortcuts, so that modifiers
+/// latched on the panel reach the application.
+pub const INPUT_MSG_VIRTUAL_KEY: u32 = 0x12e;
 
 pub const TTY_MSG_INPUT_BYTE: u32 = 0x130;
 pub const TTY_MSG_READ_LINE: u32 = 0x131;
@@ -755,15 +1002,155 @@ pub const FB_MSG_WRITE: u32 = 0x140;
 pub const FB_MSG_CLEAR: u32 = 0x141;
 pub const FB_MSG_SCROLL: u32 = 0x142;
 pub const FB_MSG_SET_CURSOR: u32 = 0x143;
+/// Display ownership for a compositor. While owned, console text requests are
+/// swallowed; the console is restored when the owner releases or exits.
+/// Only the processes registered as `boot_splash` or `compositor` (and the
+/// lender of a lent display) may acquire; others get `EPERM`. The owner is
+/// tracked by its process-list identity, so a zombie or a reused PID loses it.
+pub const FB_MSG_ACQUIRE_DISPLAY: u32 = 0x144;
+pub const FB_MSG_RELEASE_DISPLAY: u32 = 0x145;
+/// Reply: `status` = owner PID (0 = console), `len` = display epoch, bumped on
+/// every acquisition so clients notice a restarted compositor.
+pub const FB_MSG_DISPLAY_STATE: u32 = 0x146;
+/// Owner, or the process registered as `osk` when `y` lies in the bottom
+/// `FB_OSK_BAND_PERCENT` of the screen. `a` = `x | y << 32`,
+/// `b` = `w | h << 32`, `data[..4]` = colour `0x00RRGGBB` (LE).
+pub const FB_MSG_FILL_RECT: u32 = 0x147;
+/// Same senders as `FB_MSG_FILL_RECT`. `a` = `x | y << 32`,
+/// `b` = `fg | bg << 32` (`0x00RRGGBB`),
+/// `data[0]` = text length, `data[1]` = scale (1..=`FB_TEXT_SCALE_MAX`),
+/// text from `data[2]`; glyphs are those of the console font.
+pub const FB_MSG_DRAW_TEXT: u32 = 0x148;
+/// Owner only: `a` = PID of the new owner. The screen is left as drawn, so
+/// a boot splash hands over to a compositor without a console frame. With
+/// `b` = `FB_TRANSFER_LEND`, the display returns to the lender instead of the
+/// console when the new owner releases it or exits, and the lender may take
+/// it back at any time with `FB_MSG_ACQUIRE_DISPLAY` (direct scanout of a
+/// fullscreen client).
+pub const FB_MSG_TRANSFER_DISPLAY: u32 = 0x149;
+pub const FB_TRANSFER_LEND: u64 = 1;
+/// Reply: `status` = `width << 32 | height`, `ENODEV` without framebuffer.
+pub const FB_MSG_DISPLAY_SIZE: u32 = 0x14a;
+/// Owner only: gamma ramp applied to everything drawn from now on, in the
+/// wire format of `exo_services::color::Ramp` (64 points per channel, in
+/// `data[..192]`). Reset to identity when the console comes back.
+pub const FB_MSG_SET_GAMMA: u32 = 0x14b;
+/// Owner or the process registered as `night_light`: colour matrix applied
+/// before the gamma ramp, in the wire format of `exo_services::color::Ctm`
+/// (9 × i16 LE, s2.13, in `data[..18]`).
+pub const FB_MSG_SET_CTM: u32 = 0x14c;
+pub const FB_TEXT_SCALE_MAX: u8 = 8;
+/// Height of the band where the on-screen keyboard may draw over the
+/// compositor, in percent of the screen height.
+pub const FB_OSK_BAND_PERCENT: u32 = 40;
 
 pub const INPUT_DEVICE_KEYBOARD: u8 = 1;
 pub const INPUT_DEVICE_MOUSE: u8 = 2;
+/// Gamepad events carry the slot in `ascii`.
+pub const INPUT_DEVICE_GAMEPAD: u8 = 3;
+/// Touchscreen events carry the contact slot in `ascii`, gestures the
+/// finger count.
+pub const INPUT_DEVICE_TOUCH: u8 = 4;
+/// Tablet (stylus) events carry the tool (`INPUT_TABLET_TOOL_*`) in `ascii`.
+pub const INPUT_DEVICE_TABLET: u8 = 5;
 pub const INPUT_KEY_RELEASED: u8 = 0;
 pub const INPUT_KEY_PRESSED: u8 = 1;
 pub const INPUT_MOD_SHIFT: u8 = 1 << 0;
 pub const INPUT_MOD_CTRL: u8 = 1 << 1;
 pub const INPUT_MOD_ALT: u8 = 1 << 2;
 pub const INPUT_MOD_META: u8 = 1 << 3;
+/// Lock states (keyboard LEDs), reported with the modifiers; shortcut
+/// matching ignores them.
+pub const INPUT_MOD_CAPS_LOCK: u8 = 1 << 4;
+pub const INPUT_MOD_NUM_LOCK: u8 = 1 << 5;
+pub const INPUT_MOD_SCROLL_LOCK: u8 = 1 << 6;
+
+/// Media key codes, whatever the source device. Mute and volume are HID
+/// keyboard-page usages; the others are consumer-page usages as
+/// `0x0c00 | usage`. Shortcuts bound to them match under any modifiers.
+pub const INPUT_KEY_MUTE: u16 = 0x007f;
+pub const INPUT_KEY_VOLUME_UP: u16 = 0x0080;
+pub const INPUT_KEY_VOLUME_DOWN: u16 = 0x0081;
+pub const INPUT_KEY_BRIGHTNESS_UP: u16 = 0x0c6f;
+pub const INPUT_KEY_BRIGHTNESS_DOWN: u16 = 0x0c70;
+pub const INPUT_KEY_NEXT_TRACK: u16 = 0x0cb5;
+pub const INPUT_KEY_PREV_TRACK: u16 = 0x0cb6;
+pub const INPUT_KEY_STOP: u16 = 0x0cb7;
+pub const INPUT_KEY_PLAY_PAUSE: u16 = 0x0ccd;
+
+/// Gamepad codes. `INPUT_GAMEPAD_CONNECTED`: `value` 1 plugged, 0 gone.
+/// Buttons (`INPUT_BTN_*`, positional, Xbox layout): `value` 1/0. Sticks:
+/// -32768..=32767, positive right/down; triggers: 0..=32767.
+pub const INPUT_GAMEPAD_CONNECTED: u16 = 0x0300;
+pub const INPUT_BTN_SOUTH: u16 = 0x0310;
+pub const INPUT_BTN_EAST: u16 = 0x0311;
+pub const INPUT_BTN_WEST: u16 = 0x0312;
+pub const INPUT_BTN_NORTH: u16 = 0x0313;
+pub const INPUT_BTN_TL: u16 = 0x0314;
+pub const INPUT_BTN_TR: u16 = 0x0315;
+pub const INPUT_BTN_SELECT: u16 = 0x0316;
+pub const INPUT_BTN_START: u16 = 0x0317;
+pub const INPUT_BTN_MODE: u16 = 0x0318;
+pub const INPUT_BTN_THUMBL: u16 = 0x0319;
+pub const INPUT_BTN_THUMBR: u16 = 0x031a;
+pub const INPUT_BTN_DPAD_UP: u16 = 0x031b;
+pub const INPUT_BTN_DPAD_DOWN: u16 = 0x031c;
+pub const INPUT_BTN_DPAD_LEFT: u16 = 0x031d;
+pub const INPUT_BTN_DPAD_RIGHT: u16 = 0x031e;
+pub const INPUT_ABS_X: u16 = 0x0330;
+pub const INPUT_ABS_Y: u16 = 0x0331;
+pub const INPUT_ABS_RX: u16 = 0x0332;
+pub const INPUT_ABS_RY: u16 = 0x0333;
+pub const INPUT_ABS_LT: u16 = 0x0334;
+pub const INPUT_ABS_RT: u16 = 0x0335;
+
+/// Touch codes (wl_touch). Contacts live in slots `0..INPUT_TOUCH_MAX`; a
+/// contact goes down as `INPUT_TOUCH_X`, `INPUT_TOUCH_Y` then
+/// `INPUT_TOUCH_CONTACT` with `value` 1, moves as `INPUT_TOUCH_X`/`_Y` and
+/// lifts as `INPUT_TOUCH_CONTACT` with `value` 0. Positions are
+/// 0..=`INPUT_TOUCH_RANGE` across the screen. `INPUT_TOUCH_FRAME` closes
+/// the changes of one report; `INPUT_TOUCH_CANCEL` means the current
+/// contacts were taken by a system gesture and must be forgotten.
+pub const INPUT_TOUCH_X: u16 = 0x0340;
+pub const INPUT_TOUCH_Y: u16 = 0x0341;
+pub const INPUT_TOUCH_CONTACT: u16 = 0x0342;
+pub const INPUT_TOUCH_FRAME: u16 = 0x0343;
+pub const INPUT_TOUCH_CANCEL: u16 = 0x0344;
+pub const INPUT_TOUCH_MAX: usize = 10;
+pub const INPUT_TOUCH_RANGE: i16 = i16::MAX;
+/// Touch gestures, recognized by input_server; `state` = phase.
+/// `INPUT_GESTURE_SWIPE` (three fingers, for workspace switching): `value` =
+/// horizontal travel since the start, then at the end -1 (leftwards), 1
+/// (rightwards) or 0 (too short to switch); the contacts are cancelled when
+/// it starts.
+/// `INPUT_GESTURE_PINCH` (two fingers, forwarded to clients as
+/// zwp_pointer_gesture_pinch_v1): `value` = scale since the start, 256 =
+/// unchanged; the contacts keep flowing.
+pub const INPUT_GESTURE_SWIPE: u16 = 0x0350;
+pub const INPUT_GESTURE_PINCH: u16 = 0x0351;
+pub const INPUT_GESTURE_BEGIN: u8 = 1;
+pub const INPUT_GESTURE_UPDATE: u8 = 2;
+pub const INPUT_GESTURE_END: u8 = 3;
+
+/// Tablet tool codes (zwp_tablet_tool_v2). `INPUT_TABLET_PROXIMITY`: `value`
+/// 1 when the tool comes in range, 0 when it leaves. Position 0..=
+/// `INPUT_TOUCH_RANGE` across the tablet (mapped to the screen), pressure
+/// 0..=`INPUT_TABLET_PRESSURE_MAX`, tilt in hundredths of a degree
+/// (positive towards right/bottom). `INPUT_TABLET_TIP` and
+/// `INPUT_TABLET_BUTTON` (barrel button): `value` 1/0. `INPUT_TABLET_FRAME`
+/// closes the changes of one report.
+pub const INPUT_TABLET_PROXIMITY: u16 = 0x0360;
+pub const INPUT_TABLET_X: u16 = 0x0361;
+pub const INPUT_TABLET_Y: u16 = 0x0362;
+pub const INPUT_TABLET_PRESSURE: u16 = 0x0363;
+pub const INPUT_TABLET_TILT_X: u16 = 0x0364;
+pub const INPUT_TABLET_TILT_Y: u16 = 0x0365;
+pub const INPUT_TABLET_TIP: u16 = 0x0366;
+pub const INPUT_TABLET_BUTTON: u16 = 0x0367;
+pub const INPUT_TABLET_FRAME: u16 = 0x0368;
+pub const INPUT_TABLET_PRESSURE_MAX: i16 = i16::MAX;
+pub const INPUT_TABLET_TOOL_PEN: u8 = 1;
+pub const INPUT_TABLET_TOOL_ERASER: u8 = 2;
 
 pub const TTY_LINE_MAX: usize = 184;
 pub const FB_TEXT_MAX: usize = 208;
@@ -984,6 +1371,12 @@ pub const MAP_ANONYMOUS: u64 = 0x20;
 
 pub const IPC_FLAG_TIMEOUT: u64 = 0x0001;
 pub const IPC_FLAG_INJECT_SRC_PID: u64 = 0x0002;
+/// Classe de priorité d'un message (5ᵉ argument de `SYS_IPC_SEND`) : la
+/// mailbox livre les messages `HIGH` avant les autres, `BULK` en dernier,
+/// avec une protection anti-famine. 0 = normal.
+pub const IPC_PRIO_NORMAL: u64 = 0;
+pub const IPC_PRIO_HIGH: u64 = 1;
+pub const IPC_PRIO_BULK: u64 = 2;
 pub const WNOHANG: u64 = 1;
 pub const SA_RESTART: u64 = 0x10000000;
 /// SA_RESTORER : le champ `sa_restorer` de la sigaction est fourni (obligatoire
@@ -1014,6 +1407,7 @@ pub fn sigreturn_trampoline() -> u64 {
 }
 pub const EPERM: i64 = -1;
 pub const ENOENT: i64 = -2;
+pub const ESRCH: i64 = -3;
 pub const EINTR: i64 = -4;
 pub const EIO: i64 = -5;
 pub const E2BIG: i64 = -7;
@@ -1042,3 +1436,4 @@ pub const ENETDOWN: i64 = -100;
 pub const ENETUNREACH: i64 = -101;
 pub const ENOSYS: i64 = -38;
 pub const ETIMEDOUT: i64 = -110;
+pub const EDQUOT: i64 = -122;
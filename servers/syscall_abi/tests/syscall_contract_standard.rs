@@ -6,6 +6,9 @@ fn syscall_contract_standard_exofs_and_core_numbers() {
     assert_eq!(abi::SYS_WRITE, 1);
     assert_eq!(abi::SYS_OPEN, 2);
     assert_eq!(abi::SYS_GETPID, 39);
+    assert_eq!(abi::SYS_READAHEAD, 187);
+    assert_eq!(abi::SYS_IOPRIO_SET, 251);
+    assert_eq!(abi::SYS_IOPRIO_GET, 252);
     assert_eq!(abi::SYS_SYNC_FILE_RANGE, 277);
     assert_eq!(abi::SYS_EPOLL_CREATE1, 291);
     assert_eq!(abi::SYS_DUP3, 292);
@@ -18,6 +21,10 @@ fn syscall_contract_standard_exofs_and_core_numbers() {
     assert_eq!(abi::SYS_OPENAT2, 437);
 
     assert_eq!(abi::SYS_EXO_IPC_SEND, 300);
+    assert_eq!(
+        (abi::IPC_PRIO_NORMAL, abi::IPC_PRIO_HIGH, abi::IPC_PRIO_BULK),
+        (0, 1, 2)
+    );
     assert_eq!(abi::SYS_EXO_MEM_SHARE, 310);
     assert_eq!(abi::SYS_EXO_MEM_MAP_PID, 312);
     assert_eq!(abi::SYS_EXO_MEM_MPROTECT_PID, 314);
@@ -25,6 +32,23 @@ fn syscall_contract_standard_exofs_and_core_numbers() {
     assert_eq!(abi::SYS_EXO_LOG, 350);
     assert_eq!(abi::SYS_EXO_PROCESS_LIST, 351);
     assert_eq!(abi::SYS_EXO_PHOENIX_STATE_SET, 352);
+    assert_eq!(abi::SYS_EXO_SYSCTL, 354);
+    assert_eq!(abi::SYS_EXO_PRELOAD, 355);
+    assert_eq!(abi::SYS_EXO_SHM, 356);
+    assert_eq!(abi::SYS_EXO_PSI, 357);
+    assert_eq!(abi::SYS_EXO_FREEZE, 358);
+    assert_eq!(abi::SYS_EXO_NET_USAGE, 359);
+    assert_eq!(abi::SYS_EXO_BPF, 360);
+    assert_eq!(abi::SYS_EXO_IPC_STAT, 361);
+    assert_eq!(abi::SYS_EXO_TRACE, 362);
+    assert_eq!(abi::SYS_EXO_SLEEP_STATE, 363);
+    assert_eq!(abi::SYS_PCI_CLAIM_DEVICE, 364);
+    assert_eq!(abi::SYS_PCI_MAP_BAR, 365);
+    assert_eq!(abi::SYS_IRQ_EVENTFD, 366);
+    assert_eq!(abi::SYS_DMA_BUFFER, 367);
+    assert_eq!(abi::SYS_EXO_LIVEPATCH, 368);
+    assert_eq!(abi::SYS_EXO_CAP_LIST, 369);
+    assert_eq!(core::mem::size_of::<abi::ExoCapEntry>(), 24);
 
     assert_eq!(abi::SYS_EXOFS_FIRST, 500);
     assert_eq!(abi::SYS_EXOFS_PATH_RESOLVE, 500);
@@ -37,6 +61,8 @@ fn syscall_contract_standard_exofs_and_core_numbers() {
     assert_eq!(abi::SYS_EXOFS_LAST, 520);
     assert_eq!(abi::SYS_EXOFS_COUNT, 21);
     assert_eq!(abi::SYS_FRAMEBUFFER_INFO, 521);
+    assert_eq!(abi::SYS_DISPLAY_EDID, 522);
+    assert_eq!(abi::SYS_NETBOOT_INFO, 523);
 
     assert_eq!(abi::SYS_IRQ_REGISTER, 530);
     assert_eq!(abi::SYS_PCI_SET_TOPOLOGY, 546);
@@ -54,6 +80,8 @@ fn syscall_contract_standard_exofs_layouts_and_rights() {
     assert_eq!(core::mem::size_of::<abi::ExofsPathResolveResult>(), 104);
     assert_eq!(core::mem::size_of::<abi::ExofsOpenArgs>(), 48);
     assert_eq!(core::mem::size_of::<abi::ExoProcessInfo>(), 48);
+    assert_eq!(core::mem::size_of::<abi::ExoNetUsage>(), 40);
+    assert_eq!(core::mem::size_of::<abi::ExoIpcStat>(), 104);
     assert!(core::mem::size_of::<abi::InputRequest>() <= abi::IPC_KERNEL_MAX_MSG_SIZE);
     assert!(core::mem::size_of::<abi::InputReply>() <= abi::IPC_KERNEL_MAX_MSG_SIZE);
     assert!(core::mem::size_of::<abi::TtyRequest>() <= abi::IPC_KERNEL_MAX_MSG_SIZE);
@@ -13,6 +13,11 @@ const INPUT_DRAIN_LIMIT: usize = 32;
 const INPUT_DRAIN_TIMEOUT_MS: u64 = 1;
 const FB_SEND_RETRY_LIMIT: usize = 8;
 const RAW_CALL_MAGIC: u32 = 0x4558_4F43;
+/// COM1 (console série, profil serveur) : données et état de ligne.
+const COM1_DATA_PORT: u64 = 0x3F8;
+const COM1_LSR_PORT: u64 = 0x3FD;
+const LSR_DATA_READY: u8 = 0x01;
+const SERIAL_DRAIN_LIMIT: usize = 64;
 
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -294,6 +299,32 @@ fn drain_input_events(input_endpoints: Option<InputEndpoints>) {
     drain_stream_input_events(endpoints.stream);
 }
 
+#[inline]
+fn serial_read_port(port: u64) -> Option<u8> {
+    let rc = unsafe { syscall::syscall2(syscall::SYS_IOPORT_READ, port, 1) };
+    (rc >= 0).then_some(rc as u8)
+}
+
+/// Consomme les octets reçus sur COM1. `false` si le noyau refuse l'accès
+/// (console série inactive) : l'appelant cesse alors de sonder le port.
+fn drain_serial_input() -> bool {
+    for _ in 0..SERIAL_DRAIN_LIMIT {
+        let Some(lsr) = serial_read_port(COM1_LSR_PORT) else {
+            return false;
+        };
+        if lsr & LSR_DATA_READY == 0 {
+            break;
+        }
+        let Some(byte) = serial_read_port(COM1_DATA_PORT) else {
+            return false;
+        };
+        if byte != 0 {
+            let _ = handle_input(byte);
+        }
+    }
+    true
+}
+
 fn handle_read_line() -> syscall::TtyReply {
     take_ready_line().unwrap_or_else(|| reply(syscall::EAGAIN, 0, &[]))
 }
@@ -508,12 +539,19 @@ pub extern "C" fn _start() -> ! {
     } else {
         boot_log(b"tty_server: input attach pending\n");
     }
+    let mut serial_input = drain_serial_input();
+    if serial_input {
+        boot_log(b"tty_server: serial console attached\n");
+    }
     let mut recv_buf = [0u8; syscall::IPC_KERNEL_MAX_MSG_SIZE];
     loop {
         if input_endpoints.is_none() {
             input_endpoints = register_input_endpoints();
         }
         drain_input_events(input_endpoints);
+        if serial_input {
+            serial_input = drain_serial_input();
+        }
         let rc = unsafe {
             syscall::syscall4(
                 syscall::SYS_IPC_RECV,
@@ -0,0 +1,234 @@
+//! Catalogue des options de build.
+//!
+//! Chaque option regroupe ce qu'elle apporte à l'image : features cargo du
+//! kernel, paquets serveurs Ring1 et binaires installés dans `/sbin` ou `/bin`.
+//! Les dépendances pointent TOUJOURS vers une option déclarée plus haut, ce
+//! qui permet de résoudre la configuration en un seul passage.
+
+/// Option activable dans un fichier `.exoconfig` (`CONFIG_<NAME>=y|n`).
+pub struct ConfigOption {
+    /// Nom sans le préfixe `CONFIG_`.
+    pub name: &'static str,
+    pub help: &'static str,
+    pub default: bool,
+    /// Options requises (toutes doivent être actives).
+    pub depends: &'static [&'static str],
+    /// Features du crate `exo-os-kernel`.
+    pub kernel_features: &'static [&'static str],
+    /// Paquets cargo Ring1 (`-p <paquet>`).
+    pub packages: &'static [&'static str],
+    /// Features de paquets (`-F <paquet>/<feature>`).
+    pub package_features: &'static [&'static str],
+    /// Features de paquets appliquées quand l'option est DÉSACTIVÉE.
+    pub package_features_off: &'static [&'static str],
+    /// Binaires copiés dans `/sbin`.
+    pub sbin: &'static [&'static str],
+    /// Binaires coreutils copiés dans `/bin`.
+    pub bin: &'static [&'static str],
+}
+
+impl ConfigOption {
+    const fn new(name: &'static str, help: &'static str, default: bool) -> Self {
+        Self {
+            name,
+            help,
+            default,
+            depends: &[],
+            kernel_features: &[],
+            packages: &[],
+            package_features: &[],
+            package_features_off: &[],
+            sbin: &[],
+            bin: &[],
+        }
+    }
+}
+
+/// Socle toujours présent : chaîne de boot Ring1 critique et shell.
+pub const CORE_PACKAGES: &[&str] = &[
+    "exo-init-server",
+    "exo-ipc-router",
+    "exo-memory-server",
+    "exo-vfs-server",
+    "exo-crypto-server",
+    "exo-device-server",
+    "exo-scheduler-server",
+    "exo-tty-server",
+    "exo-exosh",
+    "exo-shield",
+    "exo-mem-pressure",
+    "exo-event-journal",
+];
+
+/// Binaires `/sbin` du socle (`exosh` est copié à part dans `/bin`).
+pub const CORE_SBIN: &[&str] = &[
+    "exo-init-server",
+    "exo-ipc-router",
+    "exo-memory-server",
+    "exo-vfs-server",
+    "exo-crypto-server",
+    "exo-device-server",
+    "exo-scheduler-server",
+    "exo-tty-server",
+    "exo-shield",
+    "exo-mem-pressure",
+    "exo-event-journal",
+];
+
+/// Coreutils du socle.
+pub const CORE_BIN: &[&str] = &[
+    "basename", "cat", "clear", "cp", "dd", "dirname", "echo", "false", "ionice", "kill", "ls",
+    "meminfo", "mkdir", "mv", "ps", "pwd", "rm", "rmdir", "sleep", "stat", "sync", "sysctl", "top",
+    "touch", "tree", "true", "uname", "uptime", "wc", "whoami",
+];
+
+pub static OPTIONS: &[ConfigOption] = &[
+    ConfigOption {
+        kernel_features: &["multiboot2_compat"],
+        ..ConfigOption::new(
+            "MULTIBOOT2",
+            "Boot GRUB 2 / Multiboot2 (workflow ISO + QEMU actuel)",
+            true,
+        )
+    },
+    ConfigOption {
+        kernel_features: &["kvm"],
+        ..ConfigOption::new("KVM", "Hyperviseur VMX/SVM et /dev/exo-kvm", true)
+    },
+    ConfigOption {
+        kernel_features: &["ia32_compat"],
+        ..ConfigOption::new(
+            "IA32_COMPAT",
+            "Binaires ELF32 i386 (int 0x80, sysenter)",
+            false,
+        )
+    },
+    ConfigOption {
+        kernel_features: &["strict_exec_signatures"],
+        ..ConfigOption::new(
+            "STRICT_EXEC_SIGNATURES",
+            "Refuser les binaires dont la signature Ed25519 est invalide",
+            false,
+        )
+    },
+    ConfigOption {
+        kernel_features: &["serial_console"],
+        ..ConfigOption::new(
+            "SERIAL_CONSOLE",
+            "Console série COM1 toujours active (sinon `console=ttyS0`)",
+            false,
+        )
+    },
+    // Désactivée : manifeste init headless, la console passe par tty_server
+    // sur COM1 (profil serveur).
+    ConfigOption {
+        packages: &["exo-input-server", "exo-fb-server", "exo-ps2-input"],
+        package_features_off: &["exo-init-server/headless"],
+        sbin: &["exo-input-server", "exo-fb-server", "exo-ps2-input"],
+        ..ConfigOption::new(
+            "FRAMEBUFFER",
+            "Console locale (fb_server, input_server, clavier PS/2)",
+            true,
+        )
+    },
+    ConfigOption {
+        packages: &["exo-virtio-drivers"],
+        sbin: &["exo-virtio-drivers"],
+        ..ConfigOption::new("VIRTIO", "Pilotes VirtIO Ring1 (bloc, console)", true)
+    },
+    ConfigOption {
+        packages: &["exo-network-server", "exo-loopback-driver"],
+        package_features: &["exo-network-server/baremetal-bin"],
+        sbin: &["exo-network-server", "exo-loopback-driver"],
+        bin: &["netusage"],
+        ..ConfigOption::new("NET", "Pile réseau (network_server, loopback)", true)
+    },
+    ConfigOption {
+        depends: &["NET"],
+        packages: &["exo-e1000-driver"],
+        sbin: &["exo-e1000-driver"],
+        ..ConfigOption::new("NET_E1000", "Pilote réseau Intel e1000", true)
+    },
+    ConfigOption {
+        depends: &["NET"],
+        packages: &["exo-virtio-net-driver"],
+        sbin: &["exo-virtio-net-driver"],
+        ..ConfigOption::new("NET_VIRTIO", "Pilote réseau VirtIO", true)
+    },
+    ConfigOption {
+        packages: &["exo-sleep-monitor"],
+        sbin: &["exo-sleep-monitor"],
+        ..ConfigOption::new("POWER", "Suivi de veille et de consommation", true)
+    },
+    ConfigOption {
+        depends: &["NET"],
+        packages: &["exo-data-saver"],
+        sbin: &["exo-data-saver"],
+        ..ConfigOption::new("DATA_SAVER", "Économie de données sur réseau mesuré", true)
+    },
+    ConfigOption {
+        depends: &["FRAMEBUFFER"],
+        packages: &[
+            "exo-boot-splash",
+            "exo-font-cache",
+            "exo-night-light",
+            "exo-game-mode",
+            "exo-app-prewarm",
+            "exo-app-freezer",
+        ],
+        sbin: &[
+            "exo-boot-splash",
+            "exo-font-cache",
+            "exo-night-light",
+            "exo-game-mode",
+            "exo-app-prewarm",
+            "exo-app-freezer",
+        ],
+        ..ConfigOption::new(
+            "DESKTOP",
+            "Pile bureau (splash, polices, veilleuse, mode jeu, préchargement)",
+            true,
+        )
+    },
+    ConfigOption {
+        depends: &["DESKTOP"],
+        packages: &["exo-osk"],
+        sbin: &["exo-osk"],
+        ..ConfigOption::new("OSK", "Clavier virtuel des écrans tactiles", true)
+    },
+    ConfigOption {
+        depends: &["DESKTOP"],
+        packages: &["exo-screen-reader"],
+        sbin: &["exo-screen-reader"],
+        ..ConfigOption::new("SCREEN_READER", "Lecteur d'écran (arbre d'accessibilité)", true)
+    },
+    // Réservée : aucun serveur audio n'existe encore, les profils peuvent
+    // déjà l'exclure.
+    ConfigOption {
+        depends: &["DESKTOP"],
+        ..ConfigOption::new("AUDIO", "Pile audio", true)
+    },
+    // Relevés locaux ; l'envoi reste soumis à l'accord de l'utilisateur.
+    ConfigOption {
+        packages: &["exo-metrics-daemon"],
+        sbin: &["exo-metrics-daemon"],
+        bin: &["exo-metrics"],
+        ..ConfigOption::new(
+            "METRICS",
+            "Métriques d'usage locales, envoi anonyme sur accord",
+            true,
+        )
+    },
+    ConfigOption {
+        bin: &["exo-caps", "exo-events", "ipc-stat", "ipcmon", "ktrace", "syscall-stat"],
+        ..ConfigOption::new("DIAG_TOOLS", "Outils de diagnostic (ktrace, ipcmon…)", true)
+    },
+];
+
+/// Option du catalogue par nom (sans `CONFIG_`).
+pub fn find(name: &str) -> Option<(usize, &'static ConfigOption)> {
+    OPTIONS
+        .iter()
+        .enumerate()
+        .find(|(_, option)| option.name == name)
+}
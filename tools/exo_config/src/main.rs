@@ -0,0 +1,385 @@
+//! exo-config — configuration statique du build Exo-OS (façon Kconfig).
+//!
+//! Sous-commandes :
+//!   list
+//!       Affiche le catalogue des options (`catalog.rs`) et leurs défauts.
+//!   gen --config <profil.exoconfig> [--out DIR]
+//!       Résout le profil et écrit dans DIR (défaut `target/exo-config`) :
+//!       - `config.mk` : features cargo du kernel et listes de paquets /
+//!         binaires de l'image racine, inclus par le Makefile ;
+//!       - `resolved.exoconfig` : toutes les options, explicites.
+//!
+//! Format d'un profil : une option par ligne, `CONFIG_<NOM>=y|n` ou
+//! `# CONFIG_<NOM> is not set`. Les autres commentaires et lignes vides sont
+//! ignorés ; une option absente prend sa valeur par défaut.
+//!
+//! Résolution : une option activée par défaut dont une dépendance est
+//! désactivée est désactivée à son tour ; une option explicitement à `y`
+//! dont une dépendance manque est une erreur.
+
+mod catalog;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+use catalog::{ConfigOption, CORE_BIN, CORE_PACKAGES, CORE_SBIN, OPTIONS};
+
+const DEFAULT_OUT_DIR: &str = "target/exo-config";
+const CONFIG_PREFIX: &str = "CONFIG_";
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let cmd = args.get(1).map(String::as_str).unwrap_or("");
+    let rest = &args[args.len().min(2)..];
+    let code = match cmd {
+        "list" => cmd_list(),
+        "gen" => cmd_gen(rest),
+        _ => {
+            usage();
+            2
+        }
+    };
+    exit(code);
+}
+
+fn usage() {
+    eprintln!(
+        "exo-config — configuration statique du build Exo-OS\n\
+         \n\
+         Usage:\n\
+         \x20 exo-config list\n\
+         \x20 exo-config gen --config <profil.exoconfig> [--out DIR]\n"
+    );
+}
+
+/// Récupère la valeur d'une option `--name value` dans `args`.
+fn opt<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+fn cmd_list() -> i32 {
+    for option in OPTIONS {
+        let default = if option.default { 'y' } else { 'n' };
+        print!(
+            "{CONFIG_PREFIX}{:<24} [{default}] {}",
+            option.name, option.help
+        );
+        if !option.depends.is_empty() {
+            print!(" (requiert {})", option.depends.join(", "));
+        }
+        println!();
+    }
+    0
+}
+
+fn cmd_gen(args: &[String]) -> i32 {
+    let Some(config_path) = opt(args, "--config") else {
+        eprintln!("exo-config: --config is required");
+        return 2;
+    };
+    let out_dir = PathBuf::from(opt(args, "--out").unwrap_or(DEFAULT_OUT_DIR));
+    match generate(Path::new(config_path), &out_dir) {
+        Ok(()) => {
+            println!("exo-config: {} -> {}", config_path, out_dir.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("exo-config: {config_path}: {err}");
+            1
+        }
+    }
+}
+
+fn generate(config_path: &Path, out_dir: &Path) -> Result<(), String> {
+    let text = fs::read_to_string(config_path).map_err(|e| e.to_string())?;
+    let config = resolve(&parse(&text)?)?;
+    let profile = config_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    fs::create_dir_all(out_dir).map_err(|e| e.to_string())?;
+    fs::write(
+        out_dir.join("config.mk"),
+        render_makefile(&config, &profile),
+    )
+    .map_err(|e| e.to_string())?;
+    fs::write(
+        out_dir.join("resolved.exoconfig"),
+        render_resolved(&config, &profile),
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Analyse et résolution
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Valeur explicitement donnée par le profil, indexée comme `OPTIONS`.
+type Explicit = Vec<Option<bool>>;
+
+fn parse(text: &str) -> Result<Explicit, String> {
+    let mut explicit = vec![None; OPTIONS.len()];
+    for (lineno, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        let (name, value) = if let Some(rest) = line.strip_prefix("# ") {
+            match rest.strip_suffix(" is not set") {
+                Some(name) => (name, false),
+                None => continue,
+            }
+        } else if line.is_empty() || line.starts_with('#') {
+            continue;
+        } else {
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected CONFIG_<NAME>=y|n", lineno + 1))?;
+            let value = match value.trim() {
+                "y" => true,
+                "n" => false,
+                other => return Err(format!("line {}: invalid value '{other}'", lineno + 1)),
+            };
+            (name.trim(), value)
+        };
+        let short = name
+            .strip_prefix(CONFIG_PREFIX)
+            .ok_or_else(|| format!("line {}: '{name}' lacks the CONFIG_ prefix", lineno + 1))?;
+        let (idx, _) = catalog::find(short)
+            .ok_or_else(|| format!("line {}: unknown option {name}", lineno + 1))?;
+        if explicit[idx].replace(value).is_some() {
+            return Err(format!("line {}: {name} set twice", lineno + 1));
+        }
+    }
+    Ok(explicit)
+}
+
+/// Configuration résolue : état final de chaque option de `OPTIONS`.
+struct Resolved {
+    enabled: Vec<bool>,
+}
+
+impl Resolved {
+    fn active(&self) -> impl Iterator<Item = &'static ConfigOption> + '_ {
+        OPTIONS
+            .iter()
+            .zip(&self.enabled)
+            .filter(|(_, &on)| on)
+            .map(|(option, _)| option)
+    }
+
+    fn is_enabled(&self, name: &str) -> bool {
+        catalog::find(name).is_some_and(|(idx, _)| self.enabled[idx])
+    }
+}
+
+fn resolve(explicit: &Explicit) -> Result<Resolved, String> {
+    let mut enabled = vec![false; OPTIONS.len()];
+    for (idx, option) in OPTIONS.iter().enumerate() {
+        let wanted = explicit[idx].unwrap_or(option.default);
+        let missing = option.depends.iter().find(|dep| {
+            // Dépendances déclarées plus haut : déjà résolues.
+            !catalog::find(dep).is_some_and(|(dep_idx, _)| dep_idx < idx && enabled[dep_idx])
+        });
+        enabled[idx] = match (wanted, missing) {
+            (false, _) => false,
+            (true, None) => true,
+            (true, Some(dep)) if explicit[idx] == Some(true) => {
+                return Err(format!(
+                    "{CONFIG_PREFIX}{}=y requires {CONFIG_PREFIX}{dep}",
+                    option.name
+                ));
+            }
+            (true, Some(_)) => false,
+        };
+    }
+    Ok(Resolved { enabled })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Rendu
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Liste Makefile multi-ligne (`\` de continuation), vide si `items` l'est.
+fn make_list(var: &str, items: &[String]) -> String {
+    let mut out = format!("{var} :=");
+    for item in items {
+        out.push_str(" \\\n\t");
+        out.push_str(item);
+    }
+    out.push('\n');
+    out
+}
+
+fn render_makefile(config: &Resolved, profile: &str) -> String {
+    let features: Vec<&str> = config
+        .active()
+        .flat_map(|o| o.kernel_features.iter().copied())
+        .collect();
+    let packages: Vec<String> = CORE_PACKAGES
+        .iter()
+        .copied()
+        .chain(config.active().flat_map(|o| o.packages.iter().copied()))
+        .map(|p| format!("-p {p}"))
+        .collect();
+    let package_features: Vec<String> = OPTIONS
+        .iter()
+        .zip(&config.enabled)
+        .flat_map(|(o, &on)| {
+            if on {
+                o.package_features
+            } else {
+                o.package_features_off
+            }
+        })
+        .map(|f| format!("-F {f}"))
+        .collect();
+    let sbin: Vec<String> = CORE_SBIN
+        .iter()
+        .copied()
+        .chain(config.active().flat_map(|o| o.sbin.iter().copied()))
+        .map(str::to_owned)
+        .collect();
+    let mut bin: Vec<String> = CORE_BIN
+        .iter()
+        .copied()
+        .chain(config.active().flat_map(|o| o.bin.iter().copied()))
+        .map(str::to_owned)
+        .collect();
+    bin.sort();
+
+    let mut out = format!("# Généré par exo-config (profil {profile}) — ne pas éditer.\n");
+    out.push_str(&format!("EXO_CONFIG_PROFILE := {profile}\n"));
+    // Les défauts du crate kernel ne s'appliquent jamais : le profil fait foi.
+    out.push_str("KERNEL_FEATURE_FLAGS := --no-default-features");
+    if !features.is_empty() {
+        out.push_str(&format!(" --features {}", features.join(",")));
+    }
+    out.push('\n');
+    out.push_str(&make_list("ROOTFS_SERVER_PACKAGES", &packages));
+    out.push_str(&make_list("ROOTFS_SERVER_FEATURES", &package_features));
+    out.push_str(&make_list("ROOTFS_SBIN_BINS", &sbin));
+    out.push_str(&make_list("ROOTFS_BIN_BINS", &bin));
+    out
+}
+
+fn render_resolved(config: &Resolved, profile: &str) -> String {
+    let mut out = format!("# exo-config : profil {profile} résolu\n");
+    for option in OPTIONS {
+        if config.is_enabled(option.name) {
+            out.push_str(&format!("{CONFIG_PREFIX}{}=y\n", option.name));
+        } else {
+            out.push_str(&format!("# {CONFIG_PREFIX}{} is not set\n", option.name));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve_text(text: &str) -> Result<Resolved, String> {
+        resolve(&parse(text)?)
+    }
+
+    #[test]
+    fn defaults_enable_the_full_desktop_image() {
+        let config = resolve_text("").unwrap();
+        assert!(config.is_enabled("DESKTOP"));
+        assert!(config.is_enabled("NET_E1000"));
+        assert!(!config.is_enabled("IA32_COMPAT"));
+        let mk = render_makefile(&config, "desktop");
+        assert!(mk.contains("--no-default-features --features multiboot2_compat,kvm\n"));
+        assert!(mk.contains("\t-p exo-boot-splash"));
+        assert!(mk.contains("\t-F exo-network-server/baremetal-bin"));
+        assert!(mk.contains("\t-p exo-fb-server"));
+        assert!(!mk.contains("exo-init-server/headless"));
+    }
+
+    #[test]
+    fn server_profile_is_headless_with_serial_console() {
+        let config = resolve_text(include_str!("../../../configs/server.exoconfig")).unwrap();
+        assert!(!config.is_enabled("FRAMEBUFFER"));
+        assert!(!config.is_enabled("DESKTOP"));
+        let mk = render_makefile(&config, "server");
+        assert!(mk.contains("--features multiboot2_compat,kvm,serial_console\n"));
+        assert!(mk.contains("\t-F exo-init-server/headless"));
+        assert!(!mk.contains("exo-fb-server"));
+        assert!(!mk.contains("exo-ps2-input"));
+    }
+
+    #[test]
+    fn disabled_dependency_drops_default_children() {
+        let config = resolve_text("# CONFIG_NET is not set\nCONFIG_DESKTOP=n\n").unwrap();
+        assert!(!config.is_enabled("NET_E1000"));
+        assert!(!config.is_enabled("DATA_SAVER"));
+        assert!(!config.is_enabled("AUDIO"));
+        let mk = render_makefile(&config, "server");
+        assert!(!mk.contains("exo-network-server"));
+        assert!(!mk.contains("exo-font-cache"));
+        assert!(mk.contains("ROOTFS_SERVER_FEATURES :=\n"));
+    }
+
+    #[test]
+    fn explicit_option_with_missing_dependency_is_rejected() {
+        let err = resolve_text("CONFIG_NET=n\nCONFIG_NET_VIRTIO=y\n")
+            .err()
+            .unwrap();
+        assert!(err.contains("CONFIG_NET_VIRTIO=y requires CONFIG_NET"));
+    }
+
+    #[test]
+    fn parse_rejects_unknown_and_malformed_lines() {
+        assert!(parse("CONFIG_BOGUS=y").is_err());
+        assert!(parse("CONFIG_KVM=m").is_err());
+        assert!(parse("KVM=y").is_err());
+        assert!(parse("CONFIG_KVM=y\nCONFIG_KVM=n").is_err());
+        assert!(parse("# plain comment\n\nCONFIG_KVM=n").is_ok());
+    }
+
+    #[test]
+    fn catalogue_dependencies_point_backwards() {
+        for (idx, option) in OPTIONS.iter().enumerate() {
+            for dep in option.depends {
+                let (dep_idx, _) = catalog::find(dep).unwrap();
+                assert!(dep_idx < idx, "{} depends on later {dep}", option.name);
+            }
+        }
+    }
+
+    #[test]
+    fn kernel_features_exist_in_kernel_manifest() {
+        let manifest = include_str!("../../../kernel/Cargo.toml");
+        for option in OPTIONS {
+            for feature in option.kernel_features {
+                let decl = format!("\n{feature} = [");
+                assert!(
+                    manifest.contains(&decl),
+                    "kernel feature {feature} not declared"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn shipped_profiles_resolve() {
+        let profiles = [
+            (
+                "desktop",
+                include_str!("../../../configs/desktop.exoconfig"),
+            ),
+            ("server", include_str!("../../../configs/server.exoconfig")),
+            (
+                "embedded",
+                include_str!("../../../configs/embedded.exoconfig"),
+            ),
+        ];
+        for (profile, text) in profiles {
+            assert!(resolve_text(text).is_ok(), "profile {profile}");
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
 use std::fs::{self, File};
-use std::io::{Seek, SeekFrom, Write};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -70,6 +70,9 @@ struct Args {
     /// dans le superblock). Requiert `--passphrase`.
     encrypt: bool,
     passphrase: Option<String>,
+    /// Manifeste du commit rootfs (BLAKE3 par blob + empreinte de l'image),
+    /// comparé par `make repro-check`.
+    manifest: Option<PathBuf>,
 }
 
 /// Flag incompat ENCRYPTION (doit matcher `incompat_flags::ENCRYPTION` du kernel).
@@ -122,10 +125,29 @@ fn run() -> Result<(), Box<dyn Error>> {
         volume_key = Some(vk);
     }
 
+    if args.encrypt && std::env::var_os("SOURCE_DATE_EPOCH").is_some() {
+        eprintln!(
+            "exofs-mkroot: warning: --encrypt tire une clé de volume aléatoire, \
+             l'image ne sera pas reproductible"
+        );
+    }
+    let timestamp = build_timestamp()?;
+
     let mappings = write_payload_blobs(&mut image, args.size, &payloads, volume_key.as_ref())?;
     write_object_catalog(&mut image, &mappings, args.size)?;
-    write_superblocks(&mut image, args.size, mappings.len() as u64, wrapped_vk.as_ref())?;
+    write_superblocks(
+        &mut image,
+        args.size,
+        mappings.len() as u64,
+        wrapped_vk.as_ref(),
+        timestamp,
+    )?;
     image.sync_all()?;
+    drop(image);
+
+    if let Some(path) = &args.manifest {
+        write_manifest(path, &args.image, &payloads)?;
+    }
 
     println!(
         "ExoFS root image {}: {} blobs, {} bytes{}",
@@ -140,9 +162,64 @@ fn run() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Horodatage du superblock : `SOURCE_DATE_EPOCH` si défini (builds
+/// reproductibles), sinon l'heure courante.
+fn build_timestamp() -> Result<u64, Box<dyn Error>> {
+    match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(raw) => Ok(raw
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid SOURCE_DATE_EPOCH: {raw:?}"))?),
+        Err(_) => Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs()),
+    }
+}
+
+/// Écrit le manifeste du commit rootfs : une ligne `<blake3> <chemin>` par
+/// blob (ordre des chemins, identique à l'ordre d'écriture) puis
+/// `commit <blake3 de l'image>`. Deux builds reproductibles produisent un
+/// manifeste identique octet pour octet.
+fn write_manifest(
+    path: &Path,
+    image_path: &Path,
+    payloads: &[PayloadBlob],
+) -> Result<(), Box<dyn Error>> {
+    let mut out = String::new();
+    for payload in payloads {
+        out.push_str(&hex(&blake3_hash(&payload.data)));
+        out.push(' ');
+        out.push_str(&payload.image_path);
+        out.push('\n');
+    }
+    // blake3 est compilé sans `std` (workspace) : pas d'impl `io::Write`.
+    let mut hasher = blake3::Hasher::new();
+    let mut image = File::open(image_path)?;
+    let mut chunk = vec![0u8; 1 << 20];
+    loop {
+        let read = image.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&chunk[..read]);
+    }
+    let commit = hex(hasher.finalize().as_bytes());
+    out.push_str("commit ");
+    out.push_str(&commit);
+    out.push('\n');
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, out)?;
+    println!("ExoFS root commit {commit}");
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
 /// Lit `n` octets aléatoires depuis /dev/urandom (environnement de build Linux/WSL).
 fn random_bytes(n: usize) -> Result<Vec<u8>, Box<dyn Error>> {
-    use std::io::Read;
     let mut f = File::open("/dev/urandom")?;
     let mut buf = vec![0u8; n];
     f.read_exact(&mut buf)?;
@@ -154,13 +231,16 @@ fn parse_args() -> Result<Args, Box<dyn Error>> {
     if args.contains(["-h", "--help"]) {
         println!(
             "usage: exofs-mkroot --image PATH --size 512M --root DIR \
-             [--encrypt --passphrase PW]"
+             [--manifest PATH] [--encrypt --passphrase PW]"
         );
         std::process::exit(0);
     }
 
     let encrypt = args.contains("--encrypt");
     let passphrase = args.opt_value_from_str::<_, String>("--passphrase")?;
+    let manifest = args.opt_value_from_os_str("--manifest", |value| {
+        Ok::<_, &'static str>(PathBuf::from(value))
+    })?;
 
     let image = args
         .opt_value_from_os_str("--image", |value| {
@@ -191,6 +271,7 @@ fn parse_args() -> Result<Args, Box<dyn Error>> {
         size: parse_size(&raw_size)?,
         encrypt,
         passphrase,
+        manifest,
     })
 }
 
@@ -585,8 +666,9 @@ fn write_superblocks(
     image_size: u64,
     object_count: u64,
     wrapped_vk: Option<&[u8; exo_fscrypt::WRAPPED_VK_LEN]>,
+    timestamp: u64,
 ) -> Result<(), Box<dyn Error>> {
-    let sb = build_superblock(image_size, object_count, wrapped_vk)?;
+    let sb = build_superblock(image_size, object_count, wrapped_vk, timestamp)?;
     for offset in [0, 3 * EXOFS_BLOCK_SIZE, image_size - EXOFS_BLOCK_SIZE] {
         image.seek(SeekFrom::Start(offset))?;
         image.write_all(&sb)?;
@@ -598,8 +680,8 @@ fn build_superblock(
     image_size: u64,
     object_count: u64,
     wrapped_vk: Option<&[u8; exo_fscrypt::WRAPPED_VK_LEN]>,
+    now: u64,
 ) -> Result<[u8; SUPERBLOCK_SIZE], Box<dyn Error>> {
-    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
     let heap_end = image_size - 2 * EXOFS_BLOCK_SIZE;
     let uuid_hash = blake3::hash(
         &[
@@ -0,0 +1,96 @@
+#!/usr/bin/env bash
+# tools/repro_check.sh — vérification de reproductibilité du build Exo-OS.
+#
+# Copie l'arbre courant dans deux répertoires de profondeurs différentes,
+# lance `make release rootfs-image` (+ `_make_iso` si grub-mkrescue est
+# présent) dans chacun avec le même SOURCE_DATE_EPOCH, puis compare les
+# BLAKE3/SHA-256 du kernel, des binaires rootfs, du manifeste ExoFS (commit
+# rootfs) et de l'ISO. Code de sortie ≠ 0 au premier artefact divergent.
+#
+# Usage : bash tools/repro_check.sh [DIR]   (défaut : target/repro)
+#
+# Les copies n'emportent pas .secrets/ : le kernel n'y est pas signé (la
+# signature Ed25519 est déterministe, mais dépend de la clé locale).
+
+set -euo pipefail
+
+ROOT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+OUT_DIR="${1:-target/repro}"
+cd "$ROOT_DIR"
+mkdir -p "$OUT_DIR"
+OUT_DIR="$(cd "$OUT_DIR" && pwd)"
+
+if [ ! -f Cargo.lock ]; then
+    echo "repro-check: Cargo.lock absent — lancer un build avant (versions figées)" >&2
+    exit 1
+fi
+
+SOURCE_DATE_EPOCH="${SOURCE_DATE_EPOCH:-$(git log -1 --format=%ct 2>/dev/null || echo 0)}"
+export SOURCE_DATE_EPOCH
+
+# Deux chemins de longueurs différentes : une fuite de chemin absolu dans un
+# artefact change alors ses octets.
+BUILD_A="$OUT_DIR/a/exo-os"
+BUILD_B="$OUT_DIR/b/rebuild/exo-os"
+
+ARTIFACTS=(
+    target/x86_64-unknown-none/release/exo-os-kernel
+    target/exophoenix/kernel-a-release.elf
+    target/exofs-rootfs.manifest
+)
+
+build_copy() {
+    local dest="$1"
+    rm -rf "$dest"
+    mkdir -p "$dest"
+    tar -C "$ROOT_DIR" \
+        --exclude=./target --exclude=./.git --exclude=./.secrets \
+        --exclude=./iso_build --exclude='./*.iso' \
+        -cf - . | tar -C "$dest" -xf -
+    if [ -f target/exo-config/config.mk ]; then
+        mkdir -p "$dest/target/exo-config"
+        cp target/exo-config/config.mk "$dest/target/exo-config/config.mk"
+    fi
+    echo "[repro] build dans $dest (SOURCE_DATE_EPOCH=$SOURCE_DATE_EPOCH)"
+    make -C "$dest" --no-print-directory release rootfs-image >"$dest.log" 2>&1 || {
+        echo "repro-check: échec du build, voir $dest.log" >&2
+        exit 1
+    }
+    if command -v grub-mkrescue >/dev/null 2>&1; then
+        make -C "$dest" --no-print-directory _make_iso \
+            KERNEL_BIN=target/x86_64-unknown-none/release/exo-os-kernel \
+            ISO_OUTPUT=target/exo-os-repro.iso >>"$dest.log" 2>&1
+    fi
+}
+
+build_copy "$BUILD_A"
+build_copy "$BUILD_B"
+
+if [ -f "$BUILD_A/target/exo-os-repro.iso" ]; then
+    ARTIFACTS+=(target/exo-os-repro.iso)
+fi
+while IFS= read -r bin; do
+    ARTIFACTS+=("target/exofs-rootfs/$bin")
+done < <(cd "$BUILD_A/target/exofs-rootfs" && find . -type f | sed 's|^\./||' | LC_ALL=C sort)
+
+FAIL=0
+: >"$OUT_DIR/SHA256SUMS"
+for artifact in "${ARTIFACTS[@]}"; do
+    sum_a="$(sha256sum "$BUILD_A/$artifact" | cut -d' ' -f1)"
+    sum_b="$(sha256sum "$BUILD_B/$artifact" 2>/dev/null | cut -d' ' -f1)"
+    if [ "$sum_a" = "$sum_b" ]; then
+        echo "$sum_a  $artifact" >>"$OUT_DIR/SHA256SUMS"
+    else
+        echo "[DIFF] $artifact" >&2
+        echo "       a=$sum_a" >&2
+        echo "       b=${sum_b:-absent}" >&2
+        FAIL=1
+    fi
+done
+
+if [ "$FAIL" -ne 0 ]; then
+    echo "repro-check: build NON reproductible" >&2
+    exit 1
+fi
+echo "[repro] OK : ${#ARTIFACTS[@]} artefacts identiques ($(grep '^commit ' "$BUILD_A/target/exofs-rootfs.manifest"))"
+echo "[repro] empreintes : $OUT_DIR/SHA256SUMS"
@@ -0,0 +1,72 @@
+#!/usr/bin/env python3
+"""
+trace_extract.py — Extraction des traces noyau depuis un log série
+ExoOS
+
+`ktrace export` (ou `exo_trace(EXPORT)`) écrit la trace au format Chrome
+trace-event sur la console de debug (port 0xE9), entre les balises
+`=== EXO-TRACE BEGIN ===` et `=== EXO-TRACE END ===`. Ce script récupère
+la dernière trace complète du log (ou toutes avec --all) et l'écrit dans un
+fichier JSON à ouvrir dans chrome://tracing ou https://ui.perfetto.dev.
+
+Usage :
+    python3 tools/trace_extract.py e9.txt [-o trace.json] [--all]
+"""
+
+import argparse
+import json
+import pathlib
+import sys
+
+BEGIN = "=== EXO-TRACE BEGIN ==="
+END = "=== EXO-TRACE END ==="
+
+
+def extract(log: str):
+    """Retourne la liste des documents trouvés, dans l'ordre du log."""
+    traces = []
+    pos = 0
+    while True:
+        start = log.find(BEGIN, pos)
+        if start < 0:
+            return traces
+        start += len(BEGIN)
+        end = log.find(END, start)
+        if end < 0:
+            print("trace_extract: dernière trace tronquée, ignorée", file=sys.stderr)
+            return traces
+        traces.append(log[start:end].strip())
+        pos = end + len(END)
+
+
+def main() -> int:
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[1])
+    parser.add_argument("log", type=pathlib.Path, help="log de la console de debug")
+    parser.add_argument("-o", "--output", type=pathlib.Path, default=pathlib.Path("trace.json"))
+    parser.add_argument("--all", action="store_true", help="écrit trace.N.json pour chaque export")
+    args = parser.parse_args()
+
+    log = args.log.read_text(encoding="utf-8", errors="replace")
+    traces = extract(log)
+    if not traces:
+        print(f"trace_extract: aucune trace dans {args.log}", file=sys.stderr)
+        return 1
+
+    selected = list(enumerate(traces)) if args.all else [(len(traces) - 1, traces[-1])]
+    for index, text in selected:
+        try:
+            doc = json.loads(text)
+        except json.JSONDecodeError as err:
+            # D'autres messages de la console peuvent s'intercaler (SMP).
+            print(f"trace_extract: trace {index} illisible : {err}", file=sys.stderr)
+            return 1
+        out = args.output
+        if args.all:
+            out = out.with_name(f"{out.stem}.{index}{out.suffix}")
+        out.write_text(json.dumps(doc), encoding="utf-8")
+        print(f"{out}: {len(doc['traceEvents'])} événements")
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -2,6 +2,7 @@
 
 #[cfg(not(target_os = "none"))]
 pub mod host {
+    use exo_services::{caps, config};
     use std::fs::{self, File, OpenOptions};
     use std::io::{self, Read, Write};
     use std::path::Path;
@@ -57,6 +58,85 @@ pub mod host {
         writeln!(out)
     }
 
+    fn rust_sources(dir: &Path, files: &mut Vec<std::path::PathBuf>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                rust_sources(&path, files)?;
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Constantes de droits citées par `servers/<service>/src` au-delà du
+    /// manifeste, une ligne par usage ; retourne le nombre d'avertissements.
+    /// `syscall_abi` définit les constantes et n'est pas inspecté.
+    pub fn lint_caps(manifest: &str, root: &Path, out: &mut dyn Write) -> io::Result<usize> {
+        let mut services = fs::read_dir(root.join("servers"))?.collect::<io::Result<Vec<_>>>()?;
+        services.sort_by_key(|entry| entry.file_name());
+        let mut warnings = 0;
+        for entry in services {
+            let service = entry.file_name().to_string_lossy().into_owned();
+            let src = entry.path().join("src");
+            if service == "syscall_abi" || !src.is_dir() {
+                continue;
+            }
+            let mut files = Vec::new();
+            rust_sources(&src, &mut files)?;
+            files.sort();
+            for file in files {
+                let source = fs::read_to_string(&file)?;
+                for warning in caps::lint(manifest, &service, &source) {
+                    let rights: Vec<&str> = warning.kind.names(warning.excess).collect();
+                    writeln!(
+                        out,
+                        "{}:{}: warning: {service} asks for {} rights its manifest does not declare: {} ({})",
+                        file.strip_prefix(root).unwrap_or(&file).display(),
+                        warning.line,
+                        warning.kind.name(),
+                        rights.join(","),
+                        warning.constant,
+                    )?;
+                    warnings += 1;
+                }
+            }
+        }
+        Ok(warnings)
+    }
+
+    /// `exo-caps lint [manifest] [root]` : seul le linter tourne sur l'hôte,
+    /// par défaut sur `configs/caps.manifest` depuis la racine du dépôt.
+    pub fn caps_main() -> i32 {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if args.first().map(String::as_str) != Some("lint") || args.len() > 3 {
+            eprintln!("usage: exo-caps lint [manifest] [root]");
+            return 2;
+        }
+        let manifest_path = args.get(1).map_or("configs/caps.manifest", String::as_str);
+        let root = Path::new(args.get(2).map_or(".", String::as_str));
+        let manifest = match fs::read_to_string(manifest_path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("exo-caps: {manifest_path}: {err}");
+                return 2;
+            }
+        };
+        if let Some((line, err)) = config::first_error::<caps::Grant>(&manifest) {
+            eprintln!("exo-caps: {manifest_path}:{line}: {err:?}");
+            return 2;
+        }
+        match lint_caps(&manifest, root, &mut std::io::stdout()) {
+            Ok(0) => 0,
+            Ok(_) => 1,
+            Err(err) => {
+                eprintln!("exo-caps: {err}");
+                2
+            }
+        }
+    }
+
     pub fn host_main(command: &str) -> i32 {
         let args: Vec<String> = std::env::args().skip(1).collect();
         let result = match command {
@@ -99,6 +179,7 @@ pub use host::{cat, echo, ls, mkdir, rm, rmdir, touch};
 #[cfg(target_os = "none")]
 pub mod bare {
     use core::panic::PanicInfo;
+    use exo_services::{caps, events, metrics};
     use exo_syscall_abi as syscall;
 
     const STDOUT: u64 = 1;
@@ -113,6 +194,7 @@ pub mod bare {
     const S_IFMT: u32 = 0o170000;
     const S_IFDIR: u32 = 0o040000;
     const SIGTERM: u64 = 15;
+    const CLOCK_REALTIME: u64 = 0;
     const CLOCK_MONOTONIC: u64 = 1;
     const RM_MAX_DEPTH: usize = 8;
     const EXDEV: i64 = -18;
@@ -1602,6 +1684,418 @@ pub mod bare {
         0
     }
 
+    pub fn cmd_netusage(_args: &Args) -> i32 {
+        write_all(STDOUT, b"NAME RX TX SOCKETS\n");
+        let mut index = 0u64;
+        loop {
+            let mut entry = syscall::ExoNetUsage::default();
+            let rc = unsafe {
+                syscall::syscall3(
+                    syscall::SYS_EXO_NET_USAGE,
+                    index,
+                    &mut entry as *mut syscall::ExoNetUsage as u64,
+                    core::mem::size_of::<syscall::ExoNetUsage>() as u64,
+                )
+            };
+            if rc == syscall::ENOENT {
+                return 0;
+            }
+            if rc < 0 {
+                return print_errno(b"netusage", rc);
+            }
+            index = rc as u64;
+            if entry.rx_bytes == 0 && entry.tx_bytes == 0 && entry.sockets == 0 {
+                continue;
+            }
+            let mut end = 0usize;
+            while end < entry.name.len() && entry.name[end] != 0 {
+                end += 1;
+            }
+            let name = if end == 0 { b"(other)".as_slice() } else { &entry.name[..end] };
+            write_all(STDOUT, name);
+            write_byte(STDOUT, b' ');
+            write_human(entry.rx_bytes);
+            write_byte(STDOUT, b' ');
+            write_human(entry.tx_bytes);
+            write_byte(STDOUT, b' ');
+            write_u64(STDOUT, entry.sockets as u64);
+            write_byte(STDOUT, b'\n');
+        }
+    }
+
+    const IPCMON_MAX: usize = 64;
+
+    fn ipc_stat(index: u64, entry: &mut syscall::ExoIpcStat) -> i64 {
+        unsafe {
+            syscall::syscall3(
+                syscall::SYS_EXO_IPC_STAT,
+                index,
+                entry as *mut syscall::ExoIpcStat as u64,
+                core::mem::size_of::<syscall::ExoIpcStat>() as u64,
+            )
+        }
+    }
+
+    fn write_hex(fd: u64, mut value: u64) {
+        let mut buf = [0u8; 18];
+        let mut pos = buf.len();
+        loop {
+            pos -= 1;
+            buf[pos] = b"0123456789abcdef"[(value & 0xf) as usize];
+            value >>= 4;
+            if value == 0 {
+                break;
+            }
+        }
+        pos -= 2;
+        buf[pos] = b'0';
+        buf[pos + 1] = b'x';
+        write_all(fd, &buf[pos..]);
+    }
+
+    /// `ipcmon [interval_ms]` : une ligne par mailbox IPC ; avec un
+    /// intervalle, ajoute le débit d'envoi mesuré sur cet intervalle.
+    pub fn cmd_ipcmon(args: &Args) -> i32 {
+        let interval_ms = if args.len() > 1 {
+            match parse_u64(args.get(1)) {
+                Some(ms) if ms > 0 => Some(ms),
+                _ => return print_errno(b"ipcmon", -22),
+            }
+        } else {
+            None
+        };
+
+        let mut before = [(0u64, 0u64); IPCMON_MAX];
+        let mut n_before = 0usize;
+        if let Some(ms) = interval_ms {
+            let mut index = 0u64;
+            let mut entry = syscall::ExoIpcStat::default();
+            while n_before < IPCMON_MAX {
+                let rc = ipc_stat(index, &mut entry);
+                if rc < 0 {
+                    break;
+                }
+                before[n_before] = (entry.endpoint, entry.sent);
+                n_before += 1;
+                index = rc as u64;
+            }
+            let ts = LinuxTimespec {
+                tv_sec: (ms / 1000) as i64,
+                tv_nsec: ((ms % 1000) * 1_000_000) as i64,
+            };
+            let _ = unsafe { syscall::syscall2(syscall::SYS_NANOSLEEP, &ts as *const _ as u64, 0) };
+        }
+
+        write_all(
+            STDOUT,
+            b"ENDPOINT NAME PID DEPTH PEAK SENT RECV DROP EXPIRED LAT_AVG_US LAT_MAX_US",
+        );
+        if interval_ms.is_some() {
+            write_all(STDOUT, b" MSG/S");
+        }
+        write_byte(STDOUT, b'\n');
+        let mut index = 0u64;
+        loop {
+            let mut entry = syscall::ExoIpcStat::default();
+            let rc = ipc_stat(index, &mut entry);
+            if rc == syscall::ENOENT {
+                return 0;
+            }
+            if rc < 0 {
+                return print_errno(b"ipcmon", rc);
+            }
+            index = rc as u64;
+            write_hex(STDOUT, entry.endpoint);
+            write_byte(STDOUT, b' ');
+            let mut end = 0usize;
+            while end < entry.name.len() && entry.name[end] != 0 {
+                end += 1;
+            }
+            write_all(
+                STDOUT,
+                if end == 0 {
+                    b"-".as_slice()
+                } else {
+                    &entry.name[..end]
+                },
+            );
+            write_byte(STDOUT, b' ');
+            write_u64(STDOUT, entry.owner_pid as u64);
+            write_byte(STDOUT, b' ');
+            write_u64(STDOUT, entry.depth as u64);
+            write_byte(STDOUT, b'/');
+            write_u64(STDOUT, entry.capacity as u64);
+            write_byte(STDOUT, b' ');
+            write_u64(STDOUT, entry.peak_depth as u64);
+            for value in [entry.sent, entry.received, entry.dropped, entry.expired] {
+                write_byte(STDOUT, b' ');
+                write_u64(STDOUT, value);
+            }
+            write_byte(STDOUT, b' ');
+            write_u64(
+                STDOUT,
+                entry.total_latency_ns / entry.received.max(1) / 1000,
+            );
+            write_byte(STDOUT, b' ');
+            write_u64(STDOUT, entry.peak_latency_ns / 1000);
+            if let Some(ms) = interval_ms {
+                write_byte(STDOUT, b' ');
+                match before[..n_before]
+                    .iter()
+                    .find(|(ep, _)| *ep == entry.endpoint)
+                {
+                    Some(&(_, sent)) => write_u64(
+                        STDOUT,
+                        entry.sent.saturating_sub(sent).saturating_mul(1000) / ms,
+                    ),
+                    None => write_byte(STDOUT, b'-'),
+                }
+            }
+            write_byte(STDOUT, b'\n');
+        }
+    }
+
+    /// `ktrace on|off|export` : pilote la trace noyau ; `export` l'écrit au
+    /// format Chrome trace-event sur la console de debug.
+    pub fn cmd_ktrace(args: &Args) -> i32 {
+        const ENABLED: &[u8] = b"kernel.trace.enabled";
+        let op = if args.len() > 1 { args.get(1) } else { b"" };
+        let value: &[u8] = match op {
+            b"on" => b"1",
+            b"off" => b"0",
+            b"export" => {
+                let rc =
+                    unsafe { syscall::syscall1(syscall::SYS_EXO_TRACE, syscall::EXO_TRACE_EXPORT) };
+                if rc < 0 {
+                    return print_errno(b"ktrace", rc);
+                }
+                write_u64(STDOUT, rc as u64);
+                write_all(STDOUT, b" events written to the debug console\n");
+                return 0;
+            }
+            _ => return print_errno(b"ktrace", -22),
+        };
+        let rc = unsafe {
+            syscall::syscall5(
+                syscall::SYS_EXO_SYSCTL,
+                syscall::EXO_SYSCTL_WRITE,
+                ENABLED.as_ptr() as u64,
+                ENABLED.len() as u64,
+                value.as_ptr() as u64,
+                value.len() as u64,
+            )
+        };
+        if rc < 0 {
+            return print_errno(b"ktrace", rc);
+        }
+        0
+    }
+
+    fn sysctl_print(name: &[u8]) -> i64 {
+        let mut value = [0u8; 32];
+        let rc = unsafe {
+            syscall::syscall5(
+                syscall::SYS_EXO_SYSCTL,
+                syscall::EXO_SYSCTL_READ,
+                name.as_ptr() as u64,
+                name.len() as u64,
+                value.as_mut_ptr() as u64,
+                value.len() as u64,
+            )
+        };
+        if rc >= 0 {
+            write_all(STDOUT, name);
+            write_all(STDOUT, b" = ");
+            write_all(STDOUT, &value[..rc as usize]);
+        }
+        rc
+    }
+
+    pub fn cmd_sysctl(args: &Args) -> i32 {
+        if args.len() < 2 {
+            let mut name = [0u8; 64];
+            let mut index = 0u64;
+            loop {
+                let rc = unsafe {
+                    syscall::syscall5(
+                        syscall::SYS_EXO_SYSCTL,
+                        syscall::EXO_SYSCTL_LIST,
+                        index,
+                        0,
+                        name.as_mut_ptr() as u64,
+                        name.len() as u64,
+                    )
+                };
+                if rc < 0 {
+                    break;
+                }
+                let rc = sysctl_print(&name[..rc as usize]);
+                if rc < 0 {
+                    return print_errno(b"sysctl", rc);
+                }
+                index += 1;
+            }
+            return 0;
+        }
+        let mut status = 0;
+        let mut i = 1usize;
+        while i < args.len() {
+            let arg = args.get(i);
+            let rc = match arg.iter().position(|&b| b == b'=') {
+                Some(eq) => {
+                    let (name, value) = (&arg[..eq], &arg[eq + 1..]);
+                    let rc = unsafe {
+                        syscall::syscall5(
+                            syscall::SYS_EXO_SYSCTL,
+                            syscall::EXO_SYSCTL_WRITE,
+                            name.as_ptr() as u64,
+                            name.len() as u64,
+                            value.as_ptr() as u64,
+                            value.len() as u64,
+                        )
+                    };
+                    if rc < 0 {
+                        rc
+                    } else {
+                        sysctl_print(name)
+                    }
+                }
+                None => sysctl_print(arg),
+            };
+            if rc < 0 {
+                status = print_errno(arg, rc);
+            }
+            i += 1;
+        }
+        status
+    }
+
+    const IOPRIO_CLASS_NAMES: [&[u8]; 4] = [b"none", b"realtime", b"best-effort", b"idle"];
+
+    fn ionice_class(arg: &[u8]) -> Option<u64> {
+        if let Some(class) = parse_u64(arg) {
+            return (class <= syscall::IOPRIO_CLASS_IDLE).then_some(class);
+        }
+        IOPRIO_CLASS_NAMES
+            .iter()
+            .position(|name| eq(name, arg))
+            .map(|class| class as u64)
+    }
+
+    /// `ionice [-c classe] [-n niveau] [-p pid | commande [args...]]`.
+    /// Sans `-c`/`-n` : affiche la priorité d'I/O de `pid` (ou du shell).
+    pub fn cmd_ionice(args: &Args) -> i32 {
+        let mut class = None;
+        let mut level = None;
+        let mut pid = 0u64;
+        let mut i = 1usize;
+        while i < args.len() {
+            let arg = args.get(i);
+            let value = args.get(i + 1);
+            let parsed = if eq(arg, b"-c") {
+                class = ionice_class(value);
+                class.is_some()
+            } else if eq(arg, b"-n") {
+                level = parse_u64(value).filter(|&n| n < 8);
+                level.is_some()
+            } else if eq(arg, b"-p") {
+                pid = parse_i32(value).map_or(u64::MAX, |p| p as u64);
+                pid != u64::MAX
+            } else {
+                break;
+            };
+            if !parsed {
+                return print_errno(b"ionice", -22);
+            }
+            i += 2;
+        }
+
+        if class.is_none() && level.is_none() {
+            let rc = unsafe {
+                syscall::syscall2(syscall::SYS_IOPRIO_GET, syscall::IOPRIO_WHO_PROCESS, pid)
+            };
+            if rc < 0 {
+                return print_errno(b"ionice", rc);
+            }
+            let class = (rc as u64 >> syscall::IOPRIO_CLASS_SHIFT) as usize;
+            write_all(
+                STDOUT,
+                IOPRIO_CLASS_NAMES.get(class).copied().unwrap_or(&b"?"[..]),
+            );
+            if class != syscall::IOPRIO_CLASS_IDLE as usize {
+                write_all(STDOUT, b": prio ");
+                // Classe « none » : niveau par défaut de best-effort.
+                let level = if rc == 0 { 4 } else { rc as u64 & 7 };
+                write_u64(STDOUT, level);
+            }
+            write_byte(STDOUT, b'\n');
+            return 0;
+        }
+
+        // `-n` seul : best-effort, comme ionice(1).
+        let class = class.unwrap_or(syscall::IOPRIO_CLASS_BE);
+        let level = if class == syscall::IOPRIO_CLASS_IDLE {
+            0
+        } else {
+            level.unwrap_or(4)
+        };
+        let ioprio = if class == syscall::IOPRIO_CLASS_NONE {
+            0
+        } else {
+            (class << syscall::IOPRIO_CLASS_SHIFT) | level
+        };
+        let rc = unsafe {
+            syscall::syscall3(
+                syscall::SYS_IOPRIO_SET,
+                syscall::IOPRIO_WHO_PROCESS,
+                pid,
+                ioprio,
+            )
+        };
+        if rc < 0 {
+            return print_errno(b"ionice", rc);
+        }
+        if i >= args.len() {
+            return 0;
+        }
+        if pid != 0 {
+            return print_errno(b"ionice", -22);
+        }
+
+        // La priorité est héritée par la commande, lancée à la place d'ionice.
+        let mut path = [0u8; 256];
+        let cmd = args.get(i);
+        let prefix: &[u8] = if cmd.contains(&b'/') { b"" } else { b"/bin/" };
+        if prefix.len() + cmd.len() >= path.len() {
+            return print_errno(b"ionice", -36);
+        }
+        path[..prefix.len()].copy_from_slice(prefix);
+        path[prefix.len()..prefix.len() + cmd.len()].copy_from_slice(cmd);
+        // Les arguments pointent dans la pile initiale : déjà NUL-terminés.
+        let mut argv = [0u64; ARG_MAX + 1];
+        let mut n = 0usize;
+        while i + n < args.len() {
+            argv[n] = args.get(i + n).as_ptr() as u64;
+            n += 1;
+        }
+        let mut envp = [0u64; ENV_MAX + 1];
+        let mut e = 0usize;
+        while e < args.envc {
+            envp[e] = args.envp[e].as_ptr() as u64;
+            e += 1;
+        }
+        let rc = unsafe {
+            syscall::syscall3(
+                syscall::SYS_EXECVE,
+                path.as_ptr() as u64,
+                argv.as_ptr() as u64,
+                envp.as_ptr() as u64,
+            )
+        };
+        print_errno(cmd, rc);
+        127
+    }
+
     pub fn cmd_top(args: &Args) -> i32 {
         cmd_ps(args)
     }
@@ -1936,6 +2430,352 @@ pub mod bare {
         write_byte(STDOUT, b'\n');
         0
     }
+
+    /// Journal tourné puis journal courant, du plus ancien au plus récent.
+    const EVENT_JOURNALS: [&[u8]; 2] = [b"/var/log/exo/events.old\0", b"/var/log/exo/events\0"];
+
+    /// Appelle `each` sur chaque événement des journaux ; `false` si aucun
+    /// journal n'existe.
+    fn for_each_event(each: &mut dyn FnMut(&events::Record)) -> bool {
+        let mut found = false;
+        for path in EVENT_JOURNALS {
+            let fd =
+                unsafe { syscall::syscall2(syscall::SYS_OPEN, path.as_ptr() as u64, syscall::O_RDONLY) };
+            if fd < 0 {
+                continue;
+            }
+            found = true;
+            let mut buf = [0u8; IO_BUF];
+            let mut line = [0u8; events::LINE_MAX];
+            let mut len = 0usize;
+            loop {
+                let n = unsafe {
+                    syscall::syscall3(
+                        syscall::SYS_READ,
+                        fd as u64,
+                        buf.as_mut_ptr() as u64,
+                        buf.len() as u64,
+                    )
+                };
+                if n <= 0 {
+                    break;
+                }
+                for &b in &buf[..n as usize] {
+                    if b != b'\n' {
+                        if len < line.len() {
+                            line[len] = b;
+                        }
+                        len = len.saturating_add(1);
+                        continue;
+                    }
+                    // Ligne trop longue : ignorée.
+                    if len <= line.len() {
+                        let text = core::str::from_utf8(&line[..len]).ok();
+                        if let Some(record) = text.and_then(events::Record::parse) {
+                            each(&record);
+                        }
+                    }
+                    len = 0;
+                }
+            }
+            close(fd);
+        }
+        found
+    }
+
+    /// `exo-events [-b] [type]` : chronologie des événements système
+    /// (démarrages, arrêts, reprises, plantages...) ; `-b` se limite au
+    /// démarrage courant, `type` à un type (`crash`, `oom-kill`...).
+    pub fn cmd_events(args: &Args) -> i32 {
+        let mut filter = events::Filter::default();
+        let mut current_boot = false;
+        let mut i = 1usize;
+        while i < args.len() {
+            let arg = args.get(i);
+            if eq(arg, b"-b") {
+                current_boot = true;
+            } else {
+                match core::str::from_utf8(arg).ok().and_then(events::Kind::from_name) {
+                    Some(kind) => filter.kind = Some(kind),
+                    None => return print_errno(b"exo-events", -22),
+                }
+            }
+            i += 1;
+        }
+
+        let mut last_boot = None;
+        if !for_each_event(&mut |record| {
+            if record.kind == events::Kind::Boot {
+                last_boot = Some(record.boot_id);
+            }
+        }) {
+            return print_errno(b"exo-events", -2);
+        }
+        if current_boot {
+            match last_boot {
+                Some(id) => filter.boot_id = Some(id),
+                None => return 0,
+            }
+        }
+        for_each_event(&mut |record| {
+            if !filter.matches(record) {
+                return;
+            }
+            let mut line = [0u8; 2 * events::LINE_MAX];
+            if let Some(n) = record.format_human(&mut line) {
+                write_all(STDOUT, &line[..n]);
+            }
+        });
+        0
+    }
+
+    /// `metrics::CONFIG_PATH` et `metrics::STORE_PATH`, terminés par NUL.
+    const METRICS_CONFIG: &[u8] = b"/etc/exo/telemetry.conf\0";
+    const METRICS_CONFIG_DIRS: [&[u8]; 2] = [b"/etc\0", b"/etc/exo\0"];
+    const METRICS_STORE: &[u8] = b"/var/lib/exo/metrics\0";
+
+    /// Lit `path` (terminé par NUL) dans `buf` ; `0` s'il est absent.
+    fn read_small_file(path: &[u8], buf: &mut [u8]) -> usize {
+        let fd =
+            unsafe { syscall::syscall2(syscall::SYS_OPEN, path.as_ptr() as u64, syscall::O_RDONLY) };
+        if fd < 0 {
+            return 0;
+        }
+        let mut len = 0usize;
+        while len < buf.len() {
+            let n = unsafe {
+                syscall::syscall3(
+                    syscall::SYS_READ,
+                    fd as u64,
+                    buf[len..].as_mut_ptr() as u64,
+                    (buf.len() - len) as u64,
+                )
+            };
+            if n <= 0 {
+                break;
+            }
+            len += n as usize;
+        }
+        close(fd);
+        len
+    }
+
+    fn realtime_secs() -> u64 {
+        let mut ts = LinuxTimespec::default();
+        let rc = unsafe {
+            syscall::syscall2(
+                syscall::SYS_CLOCK_GETTIME,
+                CLOCK_REALTIME,
+                &mut ts as *mut LinuxTimespec as u64,
+            )
+        };
+        if rc < 0 || ts.tv_sec < 0 {
+            0
+        } else {
+            ts.tv_sec as u64
+        }
+    }
+
+    /// `exo-metrics [report | consent on|off|ask]` : réglage d'envoi et
+    /// métriques quotidiennes relevées par `metrics_daemon` ; `report`
+    /// affiche exactement ce qui partirait au collecteur, `consent` accorde
+    /// ou retire l'envoi.
+    pub fn cmd_metrics(args: &Args) -> i32 {
+        let mut text = [0u8; 512];
+        let len = read_small_file(METRICS_CONFIG, &mut text);
+        let mut config = core::str::from_utf8(&text[..len])
+            .map_or(metrics::Config::default(), metrics::Config::parse);
+
+        if args.len() == 3 && eq(args.get(1), b"consent") {
+            let Some(consent) = core::str::from_utf8(args.get(2))
+                .ok()
+                .and_then(metrics::Consent::from_name)
+            else {
+                return print_errno(b"exo-metrics", -22);
+            };
+            config.consent = consent;
+            let Some(n) = config.format(&mut text) else {
+                return print_errno(b"exo-metrics", -22);
+            };
+            for dir in METRICS_CONFIG_DIRS {
+                let _ = unsafe { syscall::syscall2(syscall::SYS_MKDIR, dir.as_ptr() as u64, 0o755) };
+            }
+            let fd = unsafe {
+                syscall::syscall3(
+                    syscall::SYS_OPEN,
+                    METRICS_CONFIG.as_ptr() as u64,
+                    syscall::O_WRONLY | syscall::O_CREAT | syscall::O_TRUNC,
+                    0o644,
+                )
+            };
+            if fd < 0 {
+                return print_errno(b"exo-metrics", fd);
+            }
+            let rc = write_fd_all(fd as u64, &text[..n]);
+            close(fd);
+            return if rc < 0 { print_errno(b"exo-metrics", rc) } else { 0 };
+        }
+
+        let report = args.len() == 2 && eq(args.get(1), b"report");
+        if args.len() > 1 && !report {
+            return print_errno(b"exo-metrics", -22);
+        }
+        let mut buf = [0u8; metrics::STORE_MAX];
+        let len = read_small_file(METRICS_STORE, &mut buf);
+        let store = core::str::from_utf8(&buf[..len])
+            .map_or(metrics::Store::new(), metrics::Store::parse);
+        if report {
+            let today = metrics::day_of(realtime_secs());
+            if let Some(n) = store.report(today).format(&mut buf) {
+                write_all(STDOUT, &buf[..n]);
+            }
+            return 0;
+        }
+        if let Some(n) = config.format(&mut text) {
+            write_all(STDOUT, &text[..n]);
+        }
+        for day in store.days() {
+            if let Some(n) = day.format(&mut text) {
+                write_all(STDOUT, &text[..n]);
+            }
+        }
+        0
+    }
+
+    /// `caps::MANIFEST_PATH`, terminé par NUL.
+    const CAPS_MANIFEST: &[u8] = b"/etc/exo/caps.manifest\0";
+
+    fn cap_list(pid: u64, index: u64, entry: &mut syscall::ExoCapEntry) -> i64 {
+        unsafe {
+            syscall::syscall4(
+                syscall::SYS_EXO_CAP_LIST,
+                pid,
+                index,
+                entry as *mut syscall::ExoCapEntry as u64,
+                core::mem::size_of::<syscall::ExoCapEntry>() as u64,
+            )
+        }
+    }
+
+    fn write_cap_kind(type_tag: u32) {
+        match caps::Kind::from_tag(type_tag) {
+            Some(kind) => write_all(STDOUT, kind.name().as_bytes()),
+            None => {
+                write_all(STDOUT, b"type");
+                write_u64(STDOUT, type_tag as u64);
+            }
+        }
+    }
+
+    /// Droits par nom, séparés par des virgules ; les bits sans nom (ou d'un
+    /// type inconnu) en hexadécimal.
+    fn write_cap_rights(type_tag: u32, rights: u32) {
+        let Some(kind) = caps::Kind::from_tag(type_tag) else {
+            write_hex(STDOUT, rights as u64);
+            return;
+        };
+        if rights == caps::ALL_RIGHTS {
+            write_all(STDOUT, b"all");
+            return;
+        }
+        let mut names = kind.names(rights);
+        let mut first = true;
+        for name in names.by_ref() {
+            if !first {
+                write_byte(STDOUT, b',');
+            }
+            write_all(STDOUT, name.as_bytes());
+            first = false;
+        }
+        let unnamed = names.unnamed();
+        if unnamed != 0 {
+            if !first {
+                write_byte(STDOUT, b',');
+            }
+            write_hex(STDOUT, unnamed as u64);
+        } else if first {
+            write_byte(STDOUT, b'-');
+        }
+    }
+
+    /// `exo-caps [pid]` : table de capabilities d'un processus (l'appelant
+    /// par défaut, root pour un autre). `exo-caps diff <pid> <service>`
+    /// confronte la table au manifeste : `+` droits détenus non déclarés,
+    /// `-` droits déclarés jamais pris ; code 1 si un droit dépasse.
+    pub fn cmd_caps(args: &Args) -> i32 {
+        let diff = args.len() == 4 && eq(args.get(1), b"diff");
+        let pid = match args.len() {
+            1 => Some(0),
+            2 => parse_u64(args.get(1)),
+            _ if diff => parse_u64(args.get(2)),
+            _ => None,
+        };
+        let Some(pid) = pid else {
+            return print_errno(b"exo-caps", -22);
+        };
+        let mut text = [0u8; 4096];
+        let (manifest, service) = if diff {
+            let len = read_small_file(CAPS_MANIFEST, &mut text);
+            match (
+                core::str::from_utf8(&text[..len]),
+                core::str::from_utf8(args.get(3)),
+            ) {
+                (Ok(manifest), Ok(service)) => (manifest, service),
+                _ => return print_errno(b"exo-caps", -22),
+            }
+        } else {
+            write_all(STDOUT, b"SLOT OBJECT TYPE GEN RIGHTS\n");
+            ("", "")
+        };
+
+        let mut usage = caps::Usage::default();
+        let mut over = false;
+        let mut index = 0u64;
+        loop {
+            let mut entry = syscall::ExoCapEntry::default();
+            let rc = cap_list(pid, index, &mut entry);
+            if rc == syscall::ENOENT {
+                break;
+            }
+            if rc < 0 {
+                return print_errno(b"exo-caps", rc);
+            }
+            index = rc as u64;
+            if !diff {
+                write_u64(STDOUT, index - 1);
+                write_byte(STDOUT, b' ');
+                write_hex(STDOUT, entry.object_id);
+                write_byte(STDOUT, b' ');
+                write_cap_kind(entry.type_tag);
+                write_byte(STDOUT, b' ');
+                write_u64(STDOUT, entry.generation as u64);
+                write_byte(STDOUT, b' ');
+                write_cap_rights(entry.type_tag, entry.rights);
+                write_byte(STDOUT, b'\n');
+                continue;
+            }
+            usage.add(entry.type_tag, entry.rights);
+            let excess = caps::excess(manifest, service, entry.type_tag, entry.rights);
+            if excess != 0 {
+                over = true;
+                write_all(STDOUT, b"+ ");
+                write_hex(STDOUT, entry.object_id);
+                write_byte(STDOUT, b' ');
+                write_cap_kind(entry.type_tag);
+                write_byte(STDOUT, b' ');
+                write_cap_rights(entry.type_tag, excess);
+                write_byte(STDOUT, b'\n');
+            }
+        }
+        for (kind, unused) in usage.unused(manifest, service) {
+            write_all(STDOUT, b"- ");
+            write_cap_kind(kind as u32);
+            write_byte(STDOUT, b' ');
+            write_cap_rights(kind as u32, unused);
+            write_byte(STDOUT, b'\n');
+        }
+        i32::from(over)
+    }
 }
 
 #[cfg(target_os = "none")]
@@ -1992,4 +2832,69 @@ mod tests {
         rm(&file).unwrap();
         fs::remove_dir(&dir).unwrap();
     }
+
+    /// La table du linter reste alignée sur les constantes de l'ABI.
+    #[test]
+    fn caps_right_constants_match_the_abi() {
+        use exo_services::caps::RIGHT_CONSTANTS;
+        use exo_syscall_abi as abi;
+        let abi_values = [
+            abi::EXO_CAP_RIGHT_IPC_CONNECT,
+            abi::EXO_CAP_RIGHT_IPC_SEND,
+            abi::EXO_CAP_RIGHT_IPC_RECV,
+            abi::EXO_CAP_RIGHT_IPC_MANAGE,
+            abi::EXOFS_RIGHT_READ,
+            abi::EXOFS_RIGHT_WRITE,
+            abi::EXOFS_RIGHT_CREATE,
+            abi::EXOFS_RIGHT_DELETE,
+            abi::EXOFS_RIGHT_STAT,
+            abi::EXOFS_RIGHT_SETMETA,
+            abi::EXOFS_RIGHT_LIST,
+            abi::EXOFS_RIGHT_EXEC,
+            abi::EXOFS_RIGHT_CHOWN,
+            abi::EXOFS_RIGHT_CHMOD,
+            abi::EXOFS_RIGHT_INSPECT_CONTENT,
+            abi::EXOFS_RIGHT_SNAPSHOT_CREATE,
+            abi::EXOFS_RIGHT_RELATION_CREATE,
+            abi::EXOFS_RIGHT_GC_TRIGGER,
+            abi::EXOFS_RIGHT_EXPORT,
+            abi::EXOFS_RIGHT_IMPORT,
+            abi::EXOFS_RIGHT_ADMIN,
+            abi::EXOFS_RIGHT_ALL,
+            abi::EXOFS_RIGHT_READ_ONLY,
+            abi::EXOFS_RIGHT_READ_WRITE,
+        ];
+        assert_eq!(RIGHT_CONSTANTS.len(), abi_values.len());
+        for (&(name, _, rights), value) in RIGHT_CONSTANTS.iter().zip(abi_values) {
+            assert_eq!(rights, value, "{name}");
+        }
+    }
+
+    /// Les services du dépôt restent dans `configs/caps.manifest`.
+    #[test]
+    fn repo_services_stay_within_their_caps_manifest() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../..");
+        let manifest = fs::read_to_string(root.join("configs/caps.manifest")).unwrap();
+        assert_eq!(
+            exo_services::config::first_error::<exo_services::caps::Grant>(&manifest),
+            None
+        );
+        let mut out = Vec::new();
+        let warnings = lint_caps(&manifest, &root, &mut out).unwrap();
+        assert_eq!(warnings, 0, "{}", String::from_utf8_lossy(&out));
+
+        let dir = tmpdir();
+        fs::create_dir_all(dir.join("servers/greedy/src")).unwrap();
+        fs::write(
+            dir.join("servers/greedy/src/main.rs"),
+            "let r = syscall::EXOFS_RIGHT_READ;\nlet all = syscall::EXOFS_RIGHT_ALL;\n",
+        )
+        .unwrap();
+        let mut out = Vec::new();
+        assert_eq!(lint_caps("greedy file read\n", &dir, &mut out).unwrap(), 1);
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("servers/greedy/src/main.rs:2: warning: greedy asks for file rights"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }